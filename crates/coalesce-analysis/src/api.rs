@@ -0,0 +1,653 @@
+// Public API surface
+//
+// `ApiManifest` lists what a project offers its callers, the surface a translation has to
+// keep: the exported functions, the public classes, interfaces and enums with their public
+// methods, fields and properties, and exported constants and variables, each with a signature
+// written the same way whatever the language. What counts as exported follows the source
+// language: a stated access level where there is one, `pub` in Rust, a capital letter in Go,
+// no leading underscore in Python (or a place in `__all__`), `export` in JavaScript modules
+// that use it, anything not `static` in C, and every member of an interface. Comparing the
+// manifest of the source with that of the translated output names what went missing, what
+// changed arity and what was added, matching names case- and underscore-insensitively so that
+// `get_total` and `GetTotal` are the same function.
+use crate::cfg::{has_tag, original_text};
+use crate::globals::declared_names;
+use coalesce_core::{Language, Modifier, NodeType, UIRNode, Visibility};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+const PARAMETER_LIST_TAGS: &[&str] = &["parameter_list", "parameters", "formal_parameters"];
+const NAME_TAGS: &[&str] = &["identifier", "field_identifier", "property_identifier", "name"];
+const VARIADIC_TAGS: &[&str] = &["list_splat_pattern", "dictionary_splat_pattern", "rest_pattern", "variadic_parameter"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKind {
+    Function,
+    Class,
+    Interface,
+    Enum,
+    Method,
+    Field,
+    Property,
+    Constant,
+    Variable,
+}
+
+impl ApiKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiKind::Function => "function",
+            ApiKind::Class => "class",
+            ApiKind::Interface => "interface",
+            ApiKind::Enum => "enum",
+            ApiKind::Method => "method",
+            ApiKind::Field => "field",
+            ApiKind::Property => "property",
+            ApiKind::Constant => "constant",
+            ApiKind::Variable => "variable",
+        }
+    }
+    
+    /// Kinds a translation may trade for one another: a function becoming a method, a class an
+    /// enum, a field a property
+    fn category(self) -> u8 {
+        match self {
+            ApiKind::Function | ApiKind::Method => 0,
+            ApiKind::Class | ApiKind::Interface | ApiKind::Enum => 1,
+            ApiKind::Field | ApiKind::Property | ApiKind::Constant | ApiKind::Variable => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiParameter {
+    pub name: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    /// Has a default value
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    /// Collects the remaining arguments: `*args`, `...rest`, `params`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub variadic: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiItem {
+    pub kind: ApiKind,
+    /// The name qualified by its class, as in `Shop.buy`
+    pub name: String,
+    pub module: String,
+    pub signature: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<ApiParameter>,
+    /// The return type of a function, or the type of a field or constant
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_static: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_async: bool,
+    #[serde(default)]
+    pub node_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
+impl ApiItem {
+    fn new(kind: ApiKind, name: String, module: &str, node: &UIRNode) -> Self {
+        ApiItem {
+            kind,
+            signature: String::new(),
+            name,
+            module: module.to_string(),
+            parameters: Vec::new(),
+            type_name: None,
+            is_static: node.modifiers.contains(&Modifier::Static),
+            is_async: node.is_async,
+            node_id: node.id.clone(),
+            line: node.source_location.as_ref().map(|location| location.start_line),
+        }
+    }
+    
+    /// `Shop.buy(item: Item, count?: int, ...rest) -> bool`, `Shop.total: int`, `class Shop`
+    fn sign(mut self) -> Self {
+        self.signature = match self.kind {
+            ApiKind::Function | ApiKind::Method => {
+                let parameters: Vec<String> = self.parameters.iter()
+                    .map(|parameter| {
+                        let mut text = format!("{}{}{}", if parameter.variadic { "..." } else { "" }, parameter.name, if parameter.optional { "?" } else { "" });
+                        if let Some(type_name) = &parameter.type_name {
+                            text = format!("{}: {}", text, type_name);
+                        }
+                        text
+                    })
+                    .collect();
+                let returns = self.type_name.as_ref().map(|type_name| format!(" -> {}", type_name)).unwrap_or_default();
+                format!("{}({}){}", self.name, parameters.join(", "), returns)
+            }
+            ApiKind::Class | ApiKind::Interface | ApiKind::Enum => format!("{} {}", self.kind.as_str(), self.name),
+            _ => match &self.type_name {
+                Some(type_name) => format!("{}: {}", self.name, type_name),
+                None => self.name.clone(),
+            },
+        };
+        self
+    }
+    
+    /// The parameters a caller can pass one by one
+    fn arity(&self) -> usize {
+        self.parameters.iter().filter(|parameter| !parameter.variadic).count()
+    }
+}
+
+impl fmt::Display for ApiItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind.category() {
+            // The signature of a type starts with its kind
+            1 => write!(f, "`{}`", self.signature),
+            _ => write!(f, "{} `{}`", self.kind.as_str(), self.signature),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiChangeKind {
+    /// In the source but not the translation
+    Missing,
+    /// A function whose arity the translation changed
+    Changed,
+    /// In the translation but not the source
+    Added,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiChange {
+    pub kind: ApiChangeKind,
+    pub name: String,
+    /// The signature in the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// The signature in the translation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub found: Option<String>,
+}
+
+impl fmt::Display for ApiChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.expected, &self.found) {
+            (Some(expected), Some(found)) => write!(f, "`{}` became `{}`", expected, found),
+            (Some(expected), None) => write!(f, "`{}` is missing", expected),
+            (None, Some(found)) => write!(f, "`{}` was added", found),
+            (None, None) => write!(f, "`{}`", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiManifest {
+    items: Vec<ApiItem>,
+}
+
+impl ApiManifest {
+    /// The public surface of modules named by their file stem
+    pub fn extract<'a>(modules: impl IntoIterator<Item = (&'a str, &'a UIRNode)>) -> Self {
+        let mut items = Vec::new();
+        for (name, root) in modules {
+            let mut scan = Scan {
+                module: name,
+                language: root.metadata.source_language.clone(),
+                exports: python_exports(root),
+                uses_export: root.children.iter().any(|child| has_tag(child, &["export_statement"])),
+                in_const: false,
+                items: Vec::new(),
+            };
+            scan.visit(root, None, false);
+            items.extend(scan.items);
+        }
+        ApiManifest { items }
+    }
+    
+    pub fn items(&self) -> &[ApiItem] {
+        &self.items
+    }
+    
+    pub fn of_kind(&self, kind: ApiKind) -> impl Iterator<Item = &ApiItem> {
+        self.items.iter().filter(move |item| item.kind == kind)
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    
+    /// How the surface of `translated` differs from this one: what it lacks, the functions
+    /// whose arity it changed, and what it adds
+    pub fn compare(&self, translated: &ApiManifest) -> Vec<ApiChange> {
+        let key = |item: &ApiItem| (item.kind.category(), normalize(&item.name));
+        let found: HashMap<_, &ApiItem> = translated.items.iter().map(|item| (key(item), item)).collect();
+        let expected: HashSet<_> = self.items.iter().map(key).collect();
+        
+        let mut changes = Vec::new();
+        for item in &self.items {
+            match found.get(&key(item)) {
+                None => changes.push(ApiChange { kind: ApiChangeKind::Missing, name: item.name.clone(), expected: Some(item.signature.clone()), found: None }),
+                Some(other) if item.kind.category() == 0 && other.arity() != item.arity() => changes.push(ApiChange {
+                    kind: ApiChangeKind::Changed,
+                    name: item.name.clone(),
+                    expected: Some(item.signature.clone()),
+                    found: Some(other.signature.clone()),
+                }),
+                Some(_) => {}
+            }
+        }
+        for item in translated.items.iter().filter(|item| !expected.contains(&key(item))) {
+            changes.push(ApiChange { kind: ApiChangeKind::Added, name: item.name.clone(), expected: None, found: Some(item.signature.clone()) });
+        }
+        changes
+    }
+}
+
+/// The class whose members are being listed
+#[derive(Clone)]
+struct Owner {
+    name: String,
+    interface: bool,
+}
+
+struct Scan<'a> {
+    module: &'a str,
+    language: Language,
+    /// Python's `__all__`, when the module sets it
+    exports: Option<HashSet<String>>,
+    /// Whether a JavaScript module exports anything, leaving the rest private
+    uses_export: bool,
+    /// Inside a JavaScript `const` declaration
+    in_const: bool,
+    items: Vec<ApiItem>,
+}
+
+impl Scan<'_> {
+    fn visit(&mut self, node: &UIRNode, owner: Option<&Owner>, exporting: bool) {
+        match &node.node_type {
+            NodeType::Function if has_tag(node, &["function_declarator"]) => {}
+            NodeType::Function | NodeType::Closure { .. } => {
+                let Some(name) = function_name(node) else {
+                    return;
+                };
+                let receiver = receiver_type(node).map(|name| Owner { name, interface: false });
+                let owner = receiver.as_ref().or(owner);
+                if self.exported(node, &name, owner, exporting) {
+                    self.push_function(node, &name, owner);
+                }
+                return;
+            }
+            NodeType::Class | NodeType::Interface | NodeType::Enum { .. } => {
+                if has_tag(node, &["impl_item"]) {
+                    let owner = impl_target(node).map(|name| Owner { name, interface: false });
+                    for child in &node.children {
+                        self.visit(child, owner.as_ref(), false);
+                    }
+                    return;
+                }
+                let Some(name) = node.name.clone().filter(|name| !name.starts_with("anonymous_")) else {
+                    // The struct or interface a Go type declaration names
+                    for child in &node.children {
+                        self.visit(child, owner, exporting);
+                    }
+                    return;
+                };
+                if !self.exported(node, &name, owner, exporting) {
+                    return;
+                }
+                let kind = match node.node_type {
+                    NodeType::Enum { .. } => ApiKind::Enum,
+                    _ if node.node_type == NodeType::Interface || declares_interface(node) => ApiKind::Interface,
+                    _ => ApiKind::Class,
+                };
+                let qualified = qualify(owner, &name);
+                self.push(ApiItem::new(kind, qualified.clone(), self.module, node));
+                let inner = Owner { name: qualified, interface: kind == ApiKind::Interface };
+                for child in &node.children {
+                    self.visit(child, Some(&inner), false);
+                }
+                return;
+            }
+            NodeType::Field { field_type, .. } | NodeType::Property { property_type: field_type, .. } => {
+                let (Some(owner), Some(name)) = (owner, &node.name) else {
+                    return;
+                };
+                if self.exported(node, name, Some(owner), exporting) {
+                    let kind = if matches!(node.node_type, NodeType::Field { .. }) { ApiKind::Field } else { ApiKind::Property };
+                    let mut item = ApiItem::new(kind, qualify(Some(owner), name), self.module, node);
+                    item.type_name = field_type.clone();
+                    self.push(item);
+                }
+                return;
+            }
+            NodeType::Variable | NodeType::Constant => {
+                if let Some(name) = &node.name {
+                    let value = node.children.iter().find(|child| matches!(child.node_type, NodeType::Closure { .. }));
+                    if let (Some(closure), None) = (value, owner) {
+                        if self.exported(node, name, None, exporting) {
+                            self.push_function(closure, name, None);
+                        }
+                    } else if self.exported(node, name, owner, exporting) {
+                        let constant = node.node_type == NodeType::Constant || self.in_const || (self.language == Language::Python && capitals(name));
+                        let kind = match (owner, constant) {
+                            (Some(_), _) => ApiKind::Field,
+                            (None, true) => ApiKind::Constant,
+                            (None, false) => ApiKind::Variable,
+                        };
+                        let mut item = ApiItem::new(kind, qualify(owner, name), self.module, node);
+                        item.type_name = annotated(node, "type");
+                        self.push(item);
+                    }
+                }
+                return;
+            }
+            NodeType::Comment { .. } | NodeType::Import { .. } => return,
+            _ => {}
+        }
+        
+        if has_tag(node, &["declaration"]) && owner.is_none() {
+            self.visit_declaration(node);
+            return;
+        }
+        if has_tag(node, &["const_item", "static_item", "const_spec"]) {
+            let constant = !node.children.iter().any(|child| has_tag(child, &["mutable_specifier"]));
+            for name in node.children.iter().filter(|child| has_tag(child, &["identifier"])).filter_map(|child| child.name.clone()) {
+                if self.exported(node, &name, owner, exporting) {
+                    let kind = if constant { ApiKind::Constant } else { ApiKind::Variable };
+                    self.push(ApiItem::new(kind, name, self.module, node));
+                }
+            }
+            return;
+        }
+        let exporting = exporting || has_tag(node, &["export_statement"]);
+        let declares_const = annotated(node, "declaration_kind").as_deref() == Some("const");
+        self.in_const |= declares_const;
+        for child in &node.children {
+            self.visit(child, owner, exporting);
+        }
+        self.in_const &= !declares_const;
+    }
+    
+    /// A C declaration at file scope: a prototype, or variables
+    fn visit_declaration(&mut self, declaration: &UIRNode) {
+        let is_static = declaration.children.iter().any(|child| has_tag(child, &["storage_class_specifier"]) && original_text(child) == "static");
+        if is_static {
+            return;
+        }
+        if let Some(declarator) = declaration.children.iter().find(|child| has_tag(child, &["function_declarator"])) {
+            if let Some(name) = &declarator.name {
+                let mut item = ApiItem::new(ApiKind::Function, name.clone(), self.module, declaration);
+                item.parameters = parameters(declarator, false);
+                item.type_name = type_before(declaration, &declarator.id);
+                self.push(item.sign());
+            }
+            return;
+        }
+        let constant = declaration.children.iter().any(|child| has_tag(child, &["type_qualifier"]) && original_text(child) == "const");
+        for name in declared_names(declaration) {
+            let kind = if constant { ApiKind::Constant } else { ApiKind::Variable };
+            let mut item = ApiItem::new(kind, name, self.module, declaration);
+            item.type_name = type_before(declaration, "");
+            self.push(item);
+        }
+    }
+    
+    fn push_function(&mut self, function: &UIRNode, name: &str, owner: Option<&Owner>) {
+        let kind = if owner.is_some() { ApiKind::Method } else { ApiKind::Function };
+        let mut item = ApiItem::new(kind, qualify(owner, name), self.module, function);
+        item.parameters = parameters(function, owner.is_some());
+        item.type_name = return_type(function, name);
+        self.push(item);
+    }
+    
+    /// Add an item unless the module already lists its name, as a C prototype does its
+    /// definition's or Python's first assignment its later ones
+    fn push(&mut self, item: ApiItem) {
+        if !self.items.iter().any(|listed| listed.name == item.name) {
+            self.items.push(item.sign());
+        }
+    }
+    
+    fn exported(&self, node: &UIRNode, name: &str, owner: Option<&Owner>, exporting: bool) -> bool {
+        if let Some(visibility) = node.visibility {
+            return matches!(visibility, Visibility::Public | Visibility::Protected | Visibility::ProtectedInternal);
+        }
+        if owner.is_some_and(|owner| owner.interface) {
+            return true;
+        }
+        let underscored = name.starts_with('_') && !(name.starts_with("__") && name.ends_with("__"));
+        match self.language {
+            Language::Rust => node.children.iter().any(|child| has_tag(child, &["visibility_modifier"]) && original_text(child) == "pub"),
+            Language::Go => name.starts_with(|c: char| c.is_uppercase()),
+            Language::Python => match (&self.exports, owner) {
+                (Some(exports), None) => exports.contains(name),
+                (None, None) => !name.starts_with('_'),
+                (_, Some(_)) => !underscored,
+            },
+            Language::JavaScript | Language::TypeScript => match owner {
+                None => exporting || (!self.uses_export && !name.starts_with('_')),
+                Some(_) => !name.starts_with(['_', '#']),
+            },
+            Language::C | Language::Cpp => !node.children.iter().any(|child| has_tag(child, &["storage_class_specifier"]) && original_text(child) == "static"),
+            // Members default to private and types to internal
+            Language::CSharp => false,
+            _ => !name.starts_with('_'),
+        }
+    }
+}
+
+fn qualify(owner: Option<&Owner>, name: &str) -> String {
+    match owner {
+        Some(owner) => format!("{}.{}", owner.name, name),
+        None => name.to_string(),
+    }
+}
+
+/// `getTotal`, `get_total` and `GetTotal` alike
+fn normalize(name: &str) -> String {
+    name.chars().filter(|c| *c != '_').flat_map(char::to_lowercase).collect()
+}
+
+fn annotated(node: &UIRNode, key: &str) -> Option<String> {
+    node.metadata.annotations.get(key).and_then(|value| value.as_str()).map(str::to_string)
+}
+
+/// A function's name, which Go keeps in a method's `field_identifier`
+fn function_name(function: &UIRNode) -> Option<String> {
+    match &function.name {
+        Some(name) if !matches!(function.node_type, NodeType::Closure { .. }) => Some(name.clone()),
+        _ => function.children.iter()
+            .find(|child| has_tag(child, &["field_identifier"]))
+            .map(|child| original_text(child).to_string()),
+    }
+}
+
+/// The index of the child naming a function
+fn name_index(function: &UIRNode, name: &str) -> Option<usize> {
+    function.children.iter().position(|child| {
+        has_tag(child, NAME_TAGS) && (child.name.as_deref() == Some(name) || original_text(child) == name)
+    })
+}
+
+/// The type a Go method's receiver names
+fn receiver_type(function: &UIRNode) -> Option<String> {
+    if !has_tag(function, &["method_declaration"]) || function.metadata.source_language != Language::Go {
+        return None;
+    }
+    let receiver = function.children.iter().find(|child| has_tag(child, PARAMETER_LIST_TAGS))?;
+    let parameter = receiver.children.iter().find(|child| child.node_type == NodeType::Variable)?;
+    annotated(parameter, "type").map(|type_name| type_name.trim_start_matches('*').to_string())
+}
+
+/// The type a Rust `impl` block implements, the last it names: `S` in `impl T for S`
+fn impl_target(block: &UIRNode) -> Option<String> {
+    block.children.iter()
+        .rfind(|child| is_type(child))
+        .map(|child| original_text(child).to_string())
+}
+
+/// Whether a class node wraps an interface, as a Go type declaration does
+fn declares_interface(class: &UIRNode) -> bool {
+    class.children.iter().any(|child| {
+        child.node_type == NodeType::Interface || (!matches!(child.node_type, NodeType::Class) && declares_interface(child))
+    })
+}
+
+/// Parameters in order, leaving out the receiver a method names first
+fn parameters(function: &UIRNode, method: bool) -> Vec<ApiParameter> {
+    if let Some(declarator) = function.children.iter().find(|child| has_tag(child, &["function_declarator"])) {
+        return parameters(declarator, method);
+    }
+    let start = function_name(function).and_then(|name| name_index(function, &name)).map_or(0, |index| index + 1);
+    let nodes: Vec<&UIRNode> = match function.children[start..].iter().find(|child| has_tag(child, PARAMETER_LIST_TAGS)) {
+        Some(list) => list.children.iter().filter(|child| child.node_type == NodeType::Variable).collect(),
+        None => function.children.iter().filter(|child| child.node_type == NodeType::Variable).collect(),
+    };
+    
+    let mut parameters = Vec::new();
+    for (index, node) in nodes.into_iter().enumerate() {
+        let receiver = has_tag(node, &["self_parameter"]) || (method && index == 0 && matches!(node.name.as_deref(), Some("self" | "cls")));
+        if receiver {
+            continue;
+        }
+        let type_name = annotated(node, "type");
+        let optional = node.metadata.annotations.contains_key("default_value")
+            || node.metadata.annotations.get("has_default").is_some_and(|value| value == true)
+            || node.children.iter().any(|child| has_tag(child, &["equals_value_clause"]));
+        let text = original_text(node);
+        let variadic = has_tag(node, VARIADIC_TAGS) || text.starts_with("...") || text.starts_with("params ");
+        // Go declares `a, b int` together
+        let names: Vec<String> = match node.children.iter().filter(|child| has_tag(child, &["identifier"])).filter_map(|child| child.name.clone()).collect::<Vec<_>>() {
+            shared if shared.len() > 1 => shared,
+            _ => node.name.iter().cloned().collect(),
+        };
+        for name in names {
+            parameters.push(ApiParameter { name, type_name: type_name.clone(), optional, variadic });
+        }
+    }
+    parameters
+}
+
+/// What a function returns: its `return_type` annotation, or the type written after `->`,
+/// after the parameters as in Go, or before the name as in C and C#
+fn return_type(function: &UIRNode, name: &str) -> Option<String> {
+    if let Some(annotation) = annotated(function, "return_type") {
+        return Some(annotation);
+    }
+    let children = &function.children;
+    if let Some(arrow) = children.iter().position(|child| original_text(child) == "->") {
+        return children.get(arrow + 1).filter(|child| is_type(child)).map(|child| original_text(child).to_string());
+    }
+    let name = name_index(function, name)?;
+    let list = children[name..].iter().position(|child| has_tag(child, PARAMETER_LIST_TAGS)).map(|index| name + index);
+    let after = list.and_then(|list| {
+        children[list + 1..].iter()
+            .take_while(|child| !has_tag(child, &["block", "statement_block", "compound_statement"]))
+            .find(|child| is_type(child) || has_tag(child, PARAMETER_LIST_TAGS))
+    });
+    match after {
+        Some(written) => Some(original_text(written).trim_start_matches(':').trim().to_string()),
+        None => type_before(function, &children[name].id),
+    }
+}
+
+/// The type written before the child with id `before`, or before any declarator when empty
+fn type_before(node: &UIRNode, before: &str) -> Option<String> {
+    node.children.iter()
+        .take_while(|child| child.id != before && !(before.is_empty() && has_tag(child, &["init_declarator", "pointer_declarator", "array_declarator"])))
+        .filter(|child| is_type(child))
+        .last()
+        .map(|child| original_text(child).to_string())
+}
+
+fn is_type(node: &UIRNode) -> bool {
+    node.metadata.semantic_tags.iter().any(|tag| {
+        tag.ends_with("_type") || matches!(tag.as_str(), "type_identifier" | "predefined_type" | "void_keyword" | "sized_type_specifier" | "type_annotation")
+    })
+}
+
+/// A name in capitals, by convention a Python constant
+fn capitals(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_uppercase()) && !name.chars().any(|c| c.is_ascii_lowercase())
+}
+
+/// The names a Python module lists in `__all__`
+fn python_exports(module: &UIRNode) -> Option<HashSet<String>> {
+    if module.metadata.source_language != Language::Python {
+        return None;
+    }
+    let mut exports = None;
+    let mut pending = vec![module];
+    while let Some(node) = pending.pop() {
+        let target = node.children.first().filter(|child| child.node_type == NodeType::Variable && child.name.as_deref() == Some("__all__"));
+        if let (Some(_), Some(list)) = (target, node.children.get(1)) {
+            let names = list.children.iter().filter_map(|child| match &child.literal {
+                Some(coalesce_core::LiteralValue::String(name)) => Some(name.clone()),
+                _ => None,
+            });
+            exports.get_or_insert_with(HashSet::new).extend(names);
+        }
+        if !matches!(node.node_type, NodeType::Function | NodeType::Class) {
+            pending.extend(node.children.iter());
+        }
+    }
+    exports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::{CSharpParser, GoParser, PythonParser, RustParser};
+    
+    fn signatures(manifest: &ApiManifest) -> Vec<&str> {
+        manifest.items().iter().map(|item| item.signature.as_str()).collect()
+    }
+    
+    #[test]
+    fn test_python_surface_and_comparison_with_rust() {
+        let source = "LIMIT = 10\n_cache = {}\n\ndef get_total(items: list, rate: float = 0.2, *extra) -> float:\n    return sum(items) * rate\n\ndef _helper():\n    pass\n\nclass Cart:\n    currency = 'EUR'\n    def add(self, item):\n        pass\n    def _recount(self):\n        pass\n";
+        let module = PythonParser::new().unwrap().parse(source).unwrap();
+        let manifest = ApiManifest::extract([("cart.py", &module)]);
+        
+        assert_eq!(signatures(&manifest), [
+            "LIMIT",
+            "get_total(items: list, rate?: float, ...extra) -> float",
+            "class Cart",
+            "Cart.currency",
+            "Cart.add(item)",
+        ]);
+        assert_eq!(manifest.of_kind(ApiKind::Constant).count(), 1);
+        
+        let translated = "pub const LIMIT: i32 = 10;\npub fn get_total(items: Vec<f64>) -> f64 { 0.0 }\npub struct Cart { pub currency: String }\nimpl Cart {\n    pub fn add(&mut self, item: String) {}\n    fn recount(&self) {}\n    pub fn clear(&mut self) {}\n}\n";
+        let translated = RustParser::new().unwrap().parse(translated).unwrap();
+        let translated = ApiManifest::extract([("cart.rs", &translated)]);
+        let changes = manifest.compare(&translated);
+        let changes: Vec<(ApiChangeKind, &str)> = changes.iter().map(|change| (change.kind, change.name.as_str())).collect();
+        assert_eq!(changes, [(ApiChangeKind::Changed, "get_total"), (ApiChangeKind::Added, "Cart.clear")]);
+    }
+    
+    #[test]
+    fn test_csharp_and_go_visibility() {
+        let source = "public class Shop { public int Count { get; set; } private int n; public string Name(int id, string s = \"x\") { return s; } internal void Hide() {} }\nclass Hidden { public void M() {} }\npublic interface IShop { void Buy(int x); }";
+        let module = CSharpParser::new().unwrap().parse(source).unwrap();
+        let manifest = ApiManifest::extract([("shop.cs", &module)]);
+        let names: Vec<(ApiKind, &str)> = manifest.items().iter().map(|item| (item.kind, item.name.as_str())).collect();
+        assert_eq!(&names[..3], [(ApiKind::Class, "Shop"), (ApiKind::Property, "Shop.Count"), (ApiKind::Method, "Shop.Name")]);
+        assert_eq!(manifest.items()[2].signature, "Shop.Name(id: int, s?: string) -> string");
+        assert!(names.contains(&(ApiKind::Interface, "IShop")));
+        assert!(!names.iter().any(|(_, name)| name.starts_with("Hidden") || name.ends_with("Hide")));
+        
+        let source = "package shop\n\nfunc Add(a, b int) (int, error) { return a + b, nil }\nfunc helper() {}\ntype Point struct { X int; y int }\nfunc (p *Point) Move(dx int) {}\n";
+        let module = GoParser::new().unwrap().parse(source).unwrap();
+        let manifest = ApiManifest::extract([("shop.go", &module)]);
+        assert_eq!(signatures(&manifest), ["Add(a: int, b: int) -> (int, error)", "class Point", "Point.X: int", "Point.Move(dx: int)"]);
+    }
+}
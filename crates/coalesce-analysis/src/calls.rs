@@ -0,0 +1,411 @@
+// Call graphs
+//
+// A `CallGraph` records which functions call which, across one module or the modules of a
+// project. Its callables are each module's top-level code, its functions and methods, the
+// classes it constructs, and the external functions it calls but does not declare; each call
+// site is an edge from the innermost function around it to the callable it names. Bare names
+// are looked up in the caller's scope, `self.m()` and `this.m()` in the caller's class, and
+// names a module does not declare, or imports, in the other modules: the one the import
+// names, or else the only one declaring the name. Calls through variables and other
+// expressions name no callable and are not recorded. Overloads share one callable.
+use crate::symbols::{ScopeKind, SymbolKind, SymbolTable};
+use coalesce_core::{ExpressionType, Graph, NodeType, UIRNode};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallableKind {
+    /// The top-level code of a module
+    Module,
+    Function,
+    Method,
+    /// A class, called to construct it
+    Class,
+    /// Declared outside the modules of the graph
+    External,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Callable {
+    /// `module::Class.method`, or the name as called for external callables
+    pub id: String,
+    /// The name qualified by the classes and functions it is declared in
+    pub name: String,
+    pub module: Option<String>,
+    pub kind: CallableKind,
+    /// The declaring node, absent for external callables
+    pub node_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Call {
+    pub caller: String,
+    pub callee: String,
+    /// The call expression
+    pub node_id: String,
+    pub line: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CallGraph {
+    callables: Vec<Callable>,
+    calls: Vec<Call>,
+    #[serde(skip)]
+    index: HashMap<String, usize>,
+}
+
+impl CallGraph {
+    /// The call graph of a single module
+    pub fn build(module: &UIRNode) -> Self {
+        Self::from_modules([("module", module)])
+    }
+    
+    /// The call graph of modules named by their file stem, linking calls between them
+    pub fn from_modules<'a>(modules: impl IntoIterator<Item = (&'a str, &'a UIRNode)>) -> Self {
+        let modules: Vec<Module> = modules.into_iter()
+            .map(|(name, root)| {
+                let mut imports = HashMap::new();
+                collect_imports(root, &mut imports);
+                Module { name: name.to_string(), root, symbols: SymbolTable::build(root), imports }
+            })
+            .collect();
+        let mut builder = Builder { graph: CallGraph::default(), modules: &modules, declared: HashMap::new(), exports: HashMap::new() };
+        for (index, module) in modules.iter().enumerate() {
+            let callable = builder.add(Callable {
+                id: module.name.clone(),
+                name: module.name.clone(),
+                module: Some(module.name.clone()),
+                kind: CallableKind::Module,
+                node_id: Some(module.root.id.clone()),
+            });
+            builder.declared.insert((index, module.root.id.clone()), callable);
+            builder.declare(index, module.root, &[]);
+        }
+        for (index, module) in modules.iter().enumerate() {
+            let caller = builder.declared[&(index, module.root.id.clone())];
+            builder.link(index, module.root, caller);
+        }
+        builder.graph
+    }
+    
+    pub fn callables(&self) -> &[Callable] {
+        &self.callables
+    }
+    
+    /// Every call site, in module and document order
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+    
+    pub fn callable(&self, id: &str) -> Option<&Callable> {
+        self.index.get(id).map(|&index| &self.callables[index])
+    }
+    
+    /// Callables whose id or qualified name is `name`
+    pub fn find(&self, name: &str) -> Vec<&Callable> {
+        self.callables.iter().filter(|callable| callable.id == name || callable.name == name).collect()
+    }
+    
+    /// The callables calling any callable named `name` directly, in the order first seen
+    pub fn callers(&self, name: &str) -> Vec<&Callable> {
+        let targets = self.ids(name);
+        self.unique(self.calls.iter().filter(|call| targets.contains(call.callee.as_str())).map(|call| call.caller.as_str()))
+    }
+    
+    /// The callables that any callable named `name` calls directly, in the order first seen
+    pub fn callees(&self, name: &str) -> Vec<&Callable> {
+        let sources = self.ids(name);
+        self.unique(self.calls.iter().filter(|call| sources.contains(call.caller.as_str())).map(|call| call.callee.as_str()))
+    }
+    
+    /// Everything that reaches a callable named `name` through a chain of calls: what a change
+    /// to it may affect, nearest callers first
+    pub fn transitive_callers(&self, name: &str) -> Vec<&Callable> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<&str> = self.ids(name).into_iter().collect();
+        let mut found = Vec::new();
+        while let Some(target) = queue.pop_front() {
+            for call in self.calls.iter().filter(|call| call.callee == target) {
+                if seen.insert(call.caller.as_str()) {
+                    found.push(call.caller.as_str());
+                    queue.push_back(call.caller.as_str());
+                }
+            }
+        }
+        self.unique(found.into_iter())
+    }
+    
+    /// One node per callable and one edge per caller and callee, labelled with the number of
+    /// call sites when there are several
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new("calls");
+        let modules: HashSet<_> = self.callables.iter().filter_map(|callable| callable.module.as_deref()).collect();
+        for callable in &self.callables {
+            let label = match (&callable.module, callable.kind) {
+                (_, CallableKind::External) => format!("{}\n(external)", callable.name),
+                (Some(module), kind) if modules.len() > 1 && kind != CallableKind::Module => format!("{}\n{}", callable.name, module),
+                _ => callable.name.clone(),
+            };
+            graph.add_node(callable.id.clone(), label);
+        }
+        let mut edges: Vec<(&str, &str, usize)> = Vec::new();
+        for call in &self.calls {
+            match edges.iter_mut().find(|(caller, callee, _)| *caller == call.caller && *callee == call.callee) {
+                Some((_, _, count)) => *count += 1,
+                None => edges.push((&call.caller, &call.callee, 1)),
+            }
+        }
+        for (caller, callee, count) in edges {
+            graph.add_edge(caller, callee, (count > 1).then(|| format!("×{}", count)), false);
+        }
+        graph
+    }
+    
+    fn ids(&self, name: &str) -> HashSet<&str> {
+        self.find(name).into_iter().map(|callable| callable.id.as_str()).collect()
+    }
+    
+    fn unique<'a>(&self, ids: impl Iterator<Item = &'a str>) -> Vec<&Callable> {
+        let mut seen = HashSet::new();
+        ids.filter(|id| seen.insert(*id)).filter_map(|id| self.callable(id)).collect()
+    }
+}
+
+struct Module<'a> {
+    name: String,
+    root: &'a UIRNode,
+    symbols: SymbolTable,
+    imports: HashMap<String, &'a UIRNode>,
+}
+
+struct Builder<'a, 'm> {
+    graph: CallGraph,
+    modules: &'m [Module<'a>],
+    /// Callables by module and declaring node id
+    declared: HashMap<(usize, String), usize>,
+    /// Top-level functions and classes by module and name
+    exports: HashMap<(usize, String), usize>,
+}
+
+impl Builder<'_, '_> {
+    fn add(&mut self, callable: Callable) -> usize {
+        if let Some(&index) = self.graph.index.get(&callable.id) {
+            return index;
+        }
+        let index = self.graph.callables.len();
+        self.graph.index.insert(callable.id.clone(), index);
+        self.graph.callables.push(callable);
+        index
+    }
+    
+    fn declare(&mut self, module: usize, node: &UIRNode, qualifiers: &[String]) {
+        let mut inner = qualifiers.to_vec();
+        let kind = match node.node_type {
+            NodeType::Function if self.in_class(module, &node.id) => Some(CallableKind::Method),
+            NodeType::Function => Some(CallableKind::Function),
+            NodeType::Class => Some(CallableKind::Class),
+            _ => None,
+        };
+        if let (Some(kind), Some(name)) = (kind, &node.name) {
+            inner.push(name.clone());
+            let qualified = inner.join(".");
+            let module_name = self.modules[module].name.clone();
+            let callable = self.add(Callable {
+                id: format!("{}::{}", module_name, qualified),
+                name: qualified,
+                module: Some(module_name),
+                kind,
+                node_id: Some(node.id.clone()),
+            });
+            self.declared.insert((module, node.id.clone()), callable);
+            if qualifiers.is_empty() {
+                self.exports.insert((module, name.clone()), callable);
+            }
+        }
+        for child in &node.children {
+            self.declare(module, child, &inner);
+        }
+    }
+    
+    fn link(&mut self, module: usize, node: &UIRNode, caller: usize) {
+        let caller = match node.node_type {
+            NodeType::Function => self.declared.get(&(module, node.id.clone())).copied().unwrap_or(caller),
+            _ => caller,
+        };
+        if node.node_type == NodeType::Expression(ExpressionType::FunctionCall) {
+            if let Some(callee) = self.callee(module, node) {
+                let call = Call {
+                    caller: self.graph.callables[caller].id.clone(),
+                    callee: self.graph.callables[callee].id.clone(),
+                    node_id: node.id.clone(),
+                    line: node.source_location.as_ref().map(|location| location.start_line),
+                };
+                self.graph.calls.push(call);
+            }
+        }
+        for child in &node.children {
+            self.link(module, child, caller);
+        }
+    }
+    
+    /// The callable a call names, adding an external callable for names declared nowhere
+    fn callee(&mut self, module: usize, call: &UIRNode) -> Option<usize> {
+        let (node, text) = callee_text(call)?;
+        let symbols = &self.modules[module].symbols;
+        let scope = symbols.enclosing_scope(&node.id)?;
+        let resolved = match text.rsplit_once('.') {
+            None => match symbols.lookup(scope, text) {
+                Some(symbol) => {
+                    let symbol = symbols.symbol(symbol);
+                    match symbol.kind {
+                        SymbolKind::Function | SymbolKind::Method | SymbolKind::Class => {
+                            self.declared.get(&(module, symbol.node_id.clone())).copied()
+                        }
+                        SymbolKind::Import => self.imported(module, &symbol.node_id, text, text),
+                        // A variable or parameter holding a function
+                        _ => return None,
+                    }
+                }
+                None => self.exported_elsewhere(module, text, None),
+            },
+            Some(("self" | "this" | "Me", member)) => {
+                let mut class = Some(scope);
+                while let Some(current) = class.filter(|&current| symbols.scope(current).kind != ScopeKind::Class) {
+                    class = symbols.scope(current).parent;
+                }
+                let method = symbols.lookup_in(class?, member)?;
+                self.declared.get(&(module, symbols.symbol(method).node_id.clone())).copied()
+            }
+            Some((receiver, member)) => {
+                let root = receiver.split('.').next().unwrap_or(receiver);
+                match symbols.lookup(scope, root).map(|symbol| symbols.symbol(symbol)) {
+                    Some(symbol) if symbol.kind == SymbolKind::Import => self.imported(module, &symbol.node_id, root, member),
+                    // A method of a local value
+                    Some(_) => return None,
+                    None => None,
+                }
+            }
+        };
+        Some(resolved.unwrap_or_else(|| self.add(Callable {
+            id: text.to_string(),
+            name: text.to_string(),
+            module: None,
+            kind: CallableKind::External,
+            node_id: None,
+        })))
+    }
+    
+    /// A function another module declares, reached through the import binding `bound`
+    fn imported(&self, module: usize, import_id: &str, bound: &str, member: &str) -> Option<usize> {
+        let import = self.modules[module].imports.get(import_id);
+        let Some(NodeType::Import { path, symbols, .. }) = import.map(|import| &import.node_type) else {
+            return self.exported_elsewhere(module, member, None);
+        };
+        // `from m import f as g` binds `g` to `m.f`
+        let member = match symbols.iter().find(|symbol| symbol.alias.as_deref().unwrap_or(&symbol.name) == bound) {
+            Some(symbol) if bound == member => symbol.name.as_str(),
+            _ => member,
+        };
+        let stem = path.split(['/', '.', ':']).rfind(|segment| !segment.is_empty());
+        self.exported_elsewhere(module, member, stem)
+    }
+    
+    /// The top-level function `name` of the module `stem`, or of the only other module with one
+    fn exported_elsewhere(&self, module: usize, name: &str, stem: Option<&str>) -> Option<usize> {
+        let candidates: Vec<(usize, usize)> = self.modules.iter().enumerate()
+            .filter(|(index, _)| *index != module)
+            .filter_map(|(index, _)| Some((index, *self.exports.get(&(index, name.to_string()))?)))
+            .collect();
+        let named = candidates.iter().find(|(index, _)| Some(module_stem(&self.modules[*index].name)) == stem);
+        match (named, candidates.as_slice()) {
+            (Some((_, callable)), _) => Some(*callable),
+            (None, [(_, callable)]) => Some(*callable),
+            _ => None,
+        }
+    }
+    
+    fn in_class(&self, module: usize, node_id: &str) -> bool {
+        let symbols = &self.modules[module].symbols;
+        symbols.enclosing_scope(node_id).is_some_and(|scope| symbols.scope(scope).kind == ScopeKind::Class)
+    }
+}
+
+/// Import nodes by id
+fn collect_imports<'a>(node: &'a UIRNode, imports: &mut HashMap<String, &'a UIRNode>) {
+    if matches!(node.node_type, NodeType::Import { .. }) {
+        imports.insert(node.id.clone(), node);
+    }
+    for child in &node.children {
+        collect_imports(child, imports);
+    }
+}
+
+/// The node naming a call's callee and the name as written: `f`, `self.step`, `os.path.join`
+pub(crate) fn callee_text(call: &UIRNode) -> Option<(&UIRNode, &str)> {
+    if let Some(name) = call.name.as_deref() {
+        return Some((call, name));
+    }
+    let callee = call.children.first()?;
+    // Member accesses are spelled out only in their source text
+    let text = match callee.node_type {
+        NodeType::Expression(ExpressionType::Variable) if callee.children.is_empty() => callee.name.as_deref()?,
+        _ => callee.metadata.annotations.get("original_text")?.as_str()?,
+    };
+    let is_name = !text.is_empty() && text.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '$'));
+    is_name.then_some((callee, text))
+}
+
+/// `utils` of `src/utils.py`
+pub(crate) fn module_stem(name: &str) -> &str {
+    let file = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    file.split('.').next().unwrap_or(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::{JavaScriptParser, PythonParser};
+    
+    fn ids(callables: Vec<&Callable>) -> Vec<&str> {
+        callables.into_iter().map(|callable| callable.id.as_str()).collect()
+    }
+    
+    #[test]
+    fn test_python_call_graph() {
+        let parser = PythonParser::new().unwrap();
+        let source = "class Report:\n    def render(self):\n        return self.format(load())\n    def format(self, rows):\n        return ', '.join(rows)\n\ndef load():\n    return read()\n\ndef read():\n    print('reading')\n    return []\n\nreport = Report()\nreport.render()\nrows = load()\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let graph = CallGraph::build(&uir);
+        assert_eq!(graph.callable("module::Report.render").unwrap().kind, CallableKind::Method);
+        assert_eq!(ids(graph.callees("Report.render")), vec!["module::Report.format", "module::load"]);
+        assert_eq!(ids(graph.callers("read")), vec!["module::load"]);
+        assert_eq!(ids(graph.transitive_callers("read")), vec!["module::load", "module::Report.render", "module"]);
+        assert!(graph.callable("report.render").is_none());
+        assert_eq!(graph.callable("print").unwrap().kind, CallableKind::External);
+        assert_eq!(ids(graph.callees("module")), vec!["module::Report", "module::load"]);
+        
+        let dot = graph.to_graph().to_dot();
+        assert!(dot.contains("\"module::load\" -> \"module::read\";"));
+    }
+    
+    #[test]
+    fn test_calls_between_modules_follow_imports() {
+        let parser = JavaScriptParser::new().unwrap();
+        let utils = parser.parse("export function slugify(s) { console.log(s); return s; }\nexport function unused() {}").unwrap();
+        let strings = parser.parse("export function slugify(s) { return s; }").unwrap();
+        let app = parser.parse("import { slugify as slug } from './utils';\nfunction title(t) { return slug(t); }\ntitle('a');").unwrap();
+        
+        let graph = CallGraph::from_modules([("src/utils.js", &utils), ("src/strings.js", &strings), ("src/app.js", &app)]);
+        assert_eq!(ids(graph.callers("src/utils.js::slugify")), vec!["src/app.js::title"]);
+        assert!(graph.callers("src/strings.js::slugify").is_empty());
+        assert!(graph.callers("unused").is_empty());
+        assert_eq!(ids(graph.callees("slugify")), vec!["console.log"]);
+        
+        let json = serde_json::to_value(&graph).unwrap();
+        let call = json["calls"].as_array().unwrap().iter().find(|call| call["caller"] == "src/app.js::title").unwrap();
+        assert_eq!(call["callee"], "src/utils.js::slugify");
+    }
+}
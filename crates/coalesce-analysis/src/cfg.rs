@@ -0,0 +1,912 @@
+// Control-flow graphs
+//
+// A `ControlFlowGraph` splits a function body into basic blocks, runs of statements that
+// execute in order, joined by edges for the ways control moves between them: the two arms
+// of a conditional, loop entries, back edges and exits, `break` and `continue`, switch cases
+// and C fall-through, exceptions raised in a try body, `goto` and returns. Blocks list the
+// ids of their statements, so data-flow analyses can read each statement back from the tree.
+// A conditional's or loop's condition ends the block before it; C-style `for` headers are split
+// into the initializer, the condition and the update. Statements after a return or jump, and
+// branches a literal condition rules out, start a block nothing leads to, which is how
+// unreachable code shows. Parsers differ in how they
+// mark control flow, so statements are recognized by node type or by tree-sitter kind.
+// Nested functions, closures and classes are single statements with graphs of their own.
+use coalesce_core::{ControlFlowType, Graph, Language, LiteralValue, LoopType, NodeType, StatementType, UIRNode, Walk, walk};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct BlockId(usize);
+
+impl BlockId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// Falling through to the next statement
+    Next,
+    /// A condition held
+    True,
+    /// A condition failed
+    False,
+    /// From the end of a loop body back to its condition
+    Loop,
+    Break,
+    Continue,
+    /// From a switch to one of its cases
+    Case,
+    /// From a statement that may raise to a handler, or out of the function
+    Exception,
+    Goto,
+    Return,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BasicBlock {
+    /// Ids of the statements, conditions and loop headers executed in order
+    pub statements: Vec<String>,
+    #[serde(skip)]
+    text: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Edge {
+    pub from: BlockId,
+    pub to: BlockId,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlFlowGraph {
+    /// Id of the function node
+    pub function: String,
+    pub name: Option<String>,
+    blocks: Vec<BasicBlock>,
+    edges: Vec<Edge>,
+    entry: BlockId,
+    exit: BlockId,
+    #[serde(skip)]
+    block_of: HashMap<String, BlockId>,
+}
+
+impl ControlFlowGraph {
+    /// The graph of one function
+    pub fn build(function: &UIRNode) -> Self {
+        let mut builder = Builder {
+            language: function.metadata.source_language.clone(),
+            blocks: Vec::new(),
+            edges: Vec::new(),
+            breaks: Vec::new(),
+            continues: Vec::new(),
+            handlers: Vec::new(),
+            cleanups: Vec::new(),
+            labels: HashMap::new(),
+            gotos: Vec::new(),
+            exit: BlockId(1),
+        };
+        let entry = builder.block();
+        let exit = builder.block();
+        let end = builder.sequence(body(function), entry);
+        builder.edge(end, exit, EdgeKind::Next);
+        for (from, label) in std::mem::take(&mut builder.gotos) {
+            let to = builder.labels.get(&label).copied().unwrap_or(exit);
+            builder.edge(from, to, EdgeKind::Goto);
+        }
+        
+        let mut graph = ControlFlowGraph {
+            function: function.id.clone(),
+            name: function.name.clone(),
+            blocks: builder.blocks,
+            edges: builder.edges,
+            entry,
+            exit,
+            block_of: HashMap::new(),
+        };
+        graph.simplify();
+        graph
+    }
+    
+    /// The graphs of every function and method in `module`, in document order
+    pub fn build_all(module: &UIRNode) -> Vec<Self> {
+        let mut graphs = Vec::new();
+        walk(module, &mut |node: &UIRNode| {
+            // C keeps the name and parameters in a nested declarator node
+            if node.node_type == NodeType::Function && !has_tag(node, &["function_declarator"]) {
+                graphs.push(Self::build(node));
+            }
+            Walk::Continue
+        });
+        graphs
+    }
+    
+    /// The block control enters the function through
+    pub fn entry(&self) -> BlockId {
+        self.entry
+    }
+    
+    /// The empty block every return, uncaught exception and the end of the body lead to
+    pub fn exit(&self) -> BlockId {
+        self.exit
+    }
+    
+    pub fn block(&self, id: BlockId) -> &BasicBlock {
+        &self.blocks[id.0]
+    }
+    
+    pub fn blocks(&self) -> impl Iterator<Item = (BlockId, &BasicBlock)> {
+        self.blocks.iter().enumerate().map(|(index, block)| (BlockId(index), block))
+    }
+    
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+    
+    pub fn successors(&self, id: BlockId) -> Vec<BlockId> {
+        self.edges.iter().filter(|edge| edge.from == id).map(|edge| edge.to).collect()
+    }
+    
+    pub fn predecessors(&self, id: BlockId) -> Vec<BlockId> {
+        self.edges.iter().filter(|edge| edge.to == id).map(|edge| edge.from).collect()
+    }
+    
+    /// The block holding the statement with id `node_id`
+    pub fn block_of(&self, node_id: &str) -> Option<BlockId> {
+        self.block_of.get(node_id).copied()
+    }
+    
+    /// Blocks some path from the entry reaches
+    pub fn reachable(&self) -> HashSet<BlockId> {
+        let mut seen = HashSet::from([self.entry]);
+        let mut queue = VecDeque::from([self.entry]);
+        while let Some(block) = queue.pop_front() {
+            for successor in self.successors(block) {
+                if seen.insert(successor) {
+                    queue.push_back(successor);
+                }
+            }
+        }
+        seen
+    }
+    
+    /// Ids of the statements no path from the entry reaches, in block order
+    pub fn unreachable_statements(&self) -> Vec<&str> {
+        let reachable = self.reachable();
+        self.blocks()
+            .filter(|(id, _)| !reachable.contains(id))
+            .flat_map(|(_, block)| block.statements.iter().map(String::as_str))
+            .collect()
+    }
+    
+    /// Blocks as nodes listing the first line of each statement, edges labelled by kind
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new(self.name.clone().unwrap_or_else(|| self.function.clone()));
+        for (id, block) in self.blocks() {
+            let heading = match id {
+                _ if id == self.entry => format!("B{} (entry)", id.0),
+                _ if id == self.exit => format!("B{} (exit)", id.0),
+                _ => format!("B{}", id.0),
+            };
+            let lines: Vec<String> = block.text.iter().map(|text| text.lines().next().unwrap_or_default().trim().to_string()).collect();
+            let label = if lines.is_empty() { heading } else { format!("{}\n{}", heading, lines.join("\n")) };
+            graph.add_node(format!("B{}", id.0), label);
+        }
+        for edge in &self.edges {
+            let label = match edge.kind {
+                EdgeKind::Next => None,
+                kind => serde_json::to_value(kind).ok().and_then(|value| value.as_str().map(str::to_string)),
+            };
+            let dashed = matches!(edge.kind, EdgeKind::Exception | EdgeKind::Goto);
+            graph.add_edge(format!("B{}", edge.from.0), format!("B{}", edge.to.0), label, dashed);
+        }
+        graph
+    }
+    
+    /// Drop the empty blocks that only pass control on or that nothing leads to, then renumber
+    /// the rest
+    fn simplify(&mut self) {
+        let mut removed = vec![false; self.blocks.len()];
+        loop {
+            let candidate = (0..self.blocks.len()).find(|&index| {
+                let id = BlockId(index);
+                if removed[index] || id == self.entry || id == self.exit || !self.blocks[index].statements.is_empty() {
+                    return false;
+                }
+                // Nothing leads to the block left after a jump when no statements follow it
+                if !self.edges.iter().any(|edge| edge.to == id) {
+                    return true;
+                }
+                let outgoing: Vec<&Edge> = self.edges.iter().filter(|edge| edge.from == id).collect();
+                match outgoing.as_slice() {
+                    [] => true,
+                    [edge] => edge.kind == EdgeKind::Next && edge.to != id,
+                    _ => false,
+                }
+            });
+            let Some(index) = candidate else {
+                break;
+            };
+            let id = BlockId(index);
+            let target = self.edges.iter().find(|edge| edge.from == id).map(|edge| edge.to);
+            self.edges.retain(|edge| edge.from != id && (target.is_some() || edge.to != id));
+            if let Some(target) = target {
+                for edge in self.edges.iter_mut().filter(|edge| edge.to == id) {
+                    edge.to = target;
+                }
+            }
+            removed[index] = true;
+        }
+        
+        // Number blocks in reverse postorder, so they read top to bottom like the source, with
+        // unreachable ones after in the order they were made
+        let mut postorder = Vec::new();
+        let mut visited = vec![false; self.blocks.len()];
+        let mut stack = vec![(self.entry, false)];
+        while let Some((id, finished)) = stack.pop() {
+            if finished {
+                postorder.push(id);
+                continue;
+            }
+            if std::mem::replace(&mut visited[id.0], true) {
+                continue;
+            }
+            stack.push((id, true));
+            stack.extend(self.successors(id).into_iter().filter(|successor| !visited[successor.0]).map(|successor| (successor, false)));
+        }
+        let order = postorder.into_iter().rev()
+            .chain((0..self.blocks.len()).map(BlockId).filter(|id| !visited[id.0] && !removed[id.0]));
+        let mut renumbered = HashMap::new();
+        let mut old_blocks: Vec<Option<BasicBlock>> = std::mem::take(&mut self.blocks).into_iter().map(Some).collect();
+        let mut blocks = Vec::new();
+        for id in order {
+            if let Some(block) = old_blocks[id.0].take() {
+                renumbered.insert(id, BlockId(blocks.len()));
+                blocks.push(block);
+            }
+        }
+        let mut edges: Vec<Edge> = Vec::new();
+        for edge in &self.edges {
+            let edge = Edge { from: renumbered[&edge.from], to: renumbered[&edge.to], kind: edge.kind };
+            if !edges.contains(&edge) {
+                edges.push(edge);
+            }
+        }
+        self.blocks = blocks;
+        self.edges = edges;
+        self.entry = renumbered[&self.entry];
+        self.exit = renumbered[&self.exit];
+        self.block_of = self.blocks().flat_map(|(id, block)| block.statements.iter().map(move |statement| (statement.clone(), id))).collect();
+    }
+}
+
+/// How a statement moves control
+pub(crate) enum Flow {
+    Return,
+    Throw,
+    Break,
+    Continue,
+    Goto,
+    Label,
+    Conditional,
+    Loop(LoopType),
+    Switch,
+    Try,
+    /// Statements run in order
+    Block,
+    Simple,
+    /// Keywords and punctuation
+    Noise,
+}
+
+pub(crate) const BLOCK_TAGS: &[&str] = &["block", "compound_statement", "statement_block", "statement_list", "else_clause"];
+const ELSE_TAGS: &[&str] = &["else_clause", "elif_clause", "else"];
+/// The header parts of a `for` loop whose parser lists them by role, in order
+const LOOP_HEADER_TAGS: &[&str] = &["loop_initializer", "loop_condition", "loop_update"];
+const SIGNATURE_TAGS: &[&str] = &["identifier", "parameter_list", "parameters", "formal_parameters", "decorator", "type"];
+const SWITCH_TAGS: &[&str] = &["switch_statement", "expression_switch_statement", "type_switch_statement", "match_statement", "match_expression"];
+pub(crate) const CASE_TAGS: &[&str] = &["case_statement", "switch_case", "switch_default", "case_clause", "default_clause", "switch_section", "expression_case", "default_case", "when_clause", "match_arm"];
+pub(crate) const HANDLER_TAGS: &[&str] = &["except_clause", "catch_clause", "catch", "rescue", "handler"];
+const CLEANUP_TAGS: &[&str] = &["finally_clause", "finally", "ensure"];
+
+pub(crate) fn has_tag(node: &UIRNode, tags: &[&str]) -> bool {
+    node.metadata.semantic_tags.iter().any(|tag| tags.contains(&tag.as_str()))
+}
+
+pub(crate) fn original_text(node: &UIRNode) -> &str {
+    node.metadata.annotations.get("original_text").and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn classify(node: &UIRNode) -> Flow {
+    match kind(node) {
+        Flow::Simple if node.children.iter().any(moves_control) => Flow::Block,
+        flow => flow,
+    }
+}
+
+/// How a statement moves control, judged by itself and not by what it contains
+pub(crate) fn kind(node: &UIRNode) -> Flow {
+    match &node.node_type {
+        NodeType::Comment { .. } => return Flow::Noise,
+        NodeType::Expression(_) if node.literal.is_none() => {
+            // Keywords, and punctuation such as braces and empty statements
+            let text = original_text(node);
+            let keyword = node.children.is_empty() && node.metadata.semantic_tags.first().is_some_and(|tag| tag == text);
+            if keyword || (!text.is_empty() && !text.chars().any(char::is_alphanumeric)) {
+                return Flow::Noise;
+            }
+        }
+        _ => {}
+    }
+    match &node.node_type {
+        NodeType::Function | NodeType::Closure { .. } | NodeType::Class => Flow::Simple,
+        NodeType::Statement(StatementType::Return) => Flow::Return,
+        NodeType::Statement(StatementType::Throw) => Flow::Throw,
+        NodeType::Statement(StatementType::Break) => Flow::Break,
+        NodeType::Statement(StatementType::Continue) => Flow::Continue,
+        NodeType::ControlFlow(ControlFlowType::Loop(loop_type)) => Flow::Loop(loop_type.clone()),
+        NodeType::ControlFlow(ControlFlowType::Switch) => Flow::Switch,
+        NodeType::ControlFlow(ControlFlowType::Try) if !has_tag(node, HANDLER_TAGS) => Flow::Try,
+        NodeType::ControlFlow(ControlFlowType::Goto) => Flow::Goto,
+        _ => match node.metadata.semantic_tags.first().map(String::as_str) {
+            Some("return_statement" | "return_expression") => Flow::Return,
+            Some("throw_statement" | "raise_statement") => Flow::Throw,
+            Some("break_statement" | "break_expression") => Flow::Break,
+            Some("continue_statement" | "continue_expression") => Flow::Continue,
+            Some("goto_statement") => Flow::Goto,
+            Some("labeled_statement") => Flow::Label,
+            Some(tag) if SWITCH_TAGS.contains(&tag) => Flow::Switch,
+            Some("try_statement") => Flow::Try,
+            _ if matches!(node.node_type, NodeType::ControlFlow(ControlFlowType::Conditional)) => Flow::Conditional,
+            Some("if_statement" | "if_expression") => Flow::Conditional,
+            _ if has_tag(node, BLOCK_TAGS) => Flow::Block,
+            _ => Flow::Simple,
+        },
+    }
+}
+
+/// Whether control can leave a statement other than by running to its end
+fn moves_control(node: &UIRNode) -> bool {
+    match kind(node) {
+        Flow::Noise => false,
+        Flow::Simple if matches!(node.node_type, NodeType::Function | NodeType::Closure { .. } | NodeType::Class) => false,
+        Flow::Simple | Flow::Block => node.children.iter().any(moves_control),
+        _ => true,
+    }
+}
+
+/// The statements of a function's body, without its name, parameters or return type
+fn body(function: &UIRNode) -> Vec<&UIRNode> {
+    if let Some(block) = function.children.iter().rfind(|child| has_tag(child, BLOCK_TAGS)) {
+        return vec![block];
+    }
+    // Python lists the body's statements after the parameters
+    function.children.iter()
+        .filter(|child| match child.node_type {
+            NodeType::Variable | NodeType::Function | NodeType::Class => false,
+            _ => !matches!(classify(child), Flow::Noise) && child.name.is_none() || child.name != function.name,
+        })
+        .filter(|child| !child.metadata.semantic_tags.first().is_some_and(|tag| tag.ends_with("type") || SIGNATURE_TAGS.contains(&tag.as_str())))
+        .collect()
+}
+
+/// The parts of a C-style `for` header between semicolons; a declaration ends with its own
+fn header_parts(nodes: &[UIRNode]) -> Vec<Vec<&UIRNode>> {
+    let mut parts = vec![Vec::new()];
+    for node in nodes {
+        let text = original_text(node);
+        if text != ";" {
+            if matches!(classify(node), Flow::Noise) {
+                continue;
+            }
+            parts.last_mut().unwrap().push(node);
+            if !text.ends_with(';') {
+                continue;
+            }
+        }
+        parts.push(Vec::new());
+    }
+    parts
+}
+
+struct Builder {
+    language: Language,
+    blocks: Vec<BasicBlock>,
+    edges: Vec<Edge>,
+    /// Where `break` and `continue` go in the innermost loop or switch
+    breaks: Vec<BlockId>,
+    continues: Vec<BlockId>,
+    /// Handler entries of the enclosing try bodies, innermost last
+    handlers: Vec<Vec<BlockId>>,
+    /// Entries of the enclosing `finally` blocks, and whether control leaves the function through them
+    cleanups: Vec<(BlockId, bool)>,
+    labels: HashMap<String, BlockId>,
+    gotos: Vec<(BlockId, String)>,
+    exit: BlockId,
+}
+
+impl Builder {
+    fn block(&mut self) -> BlockId {
+        self.blocks.push(BasicBlock::default());
+        BlockId(self.blocks.len() - 1)
+    }
+    
+    fn edge(&mut self, from: BlockId, to: BlockId, kind: EdgeKind) {
+        self.edges.push(Edge { from, to, kind });
+    }
+    
+    /// Where a return or uncaught exception goes: the innermost `finally`, or the exit
+    fn leave(&mut self) -> BlockId {
+        match self.cleanups.last_mut() {
+            Some((entry, leaves)) => {
+                *leaves = true;
+                *entry
+            }
+            None => self.exit,
+        }
+    }
+    
+    fn append(&mut self, block: BlockId, node: &UIRNode) {
+        self.blocks[block.0].statements.push(node.id.clone());
+        self.blocks[block.0].text.push(original_text(node).to_string());
+    }
+    
+    /// Run `nodes` in order from `current`, returning the block control leaves them from
+    fn sequence<'n>(&mut self, nodes: impl IntoIterator<Item = &'n UIRNode>, mut current: BlockId) -> BlockId {
+        for node in nodes {
+            current = self.statement(node, current);
+        }
+        current
+    }
+    
+    fn statement(&mut self, node: &UIRNode, current: BlockId) -> BlockId {
+        match classify(node) {
+            Flow::Noise => current,
+            Flow::Simple => {
+                self.append(current, node);
+                current
+            }
+            Flow::Block => self.sequence(&node.children, current),
+            Flow::Return => {
+                self.append(current, node);
+                let target = self.leave();
+                self.edge(current, target, EdgeKind::Return);
+                self.block()
+            }
+            Flow::Throw => {
+                self.append(current, node);
+                // Inside a try body, the edges to its handlers are added with the body's
+                if self.handlers.is_empty() {
+                    let target = self.leave();
+                    self.edge(current, target, EdgeKind::Exception);
+                }
+                self.block()
+            }
+            Flow::Break | Flow::Continue => {
+                self.append(current, node);
+                let (targets, kind) = match classify(node) {
+                    Flow::Break => (&self.breaks, EdgeKind::Break),
+                    _ => (&self.continues, EdgeKind::Continue),
+                };
+                if let Some(&target) = targets.last() {
+                    self.edge(current, target, kind);
+                }
+                self.block()
+            }
+            Flow::Goto => {
+                self.append(current, node);
+                let label = node.children.iter()
+                    .rfind(|child| !matches!(classify(child), Flow::Noise))
+                    .map(|child| original_text(child).to_string())
+                    .or_else(|| node.name.clone())
+                    .unwrap_or_default();
+                self.gotos.push((current, label));
+                self.block()
+            }
+            Flow::Label => {
+                let target = self.block();
+                self.edge(current, target, EdgeKind::Next);
+                let mut children = node.children.iter().filter(|child| !matches!(classify(child), Flow::Noise));
+                if let Some(label) = children.next() {
+                    self.labels.insert(original_text(label).to_string(), target);
+                }
+                self.sequence(children, target)
+            }
+            Flow::Conditional => self.conditional(node, current),
+            Flow::Loop(loop_type) => self.looping(node, loop_type, current),
+            Flow::Switch => self.switch(node, current),
+            Flow::Try => self.try_block(node, current),
+        }
+    }
+    
+    fn conditional(&mut self, node: &UIRNode, current: BlockId) -> BlockId {
+        let parts = significant(node);
+        let Some((condition, rest)) = parts.split_first() else {
+            return current;
+        };
+        self.append(current, condition);
+        let join = self.block();
+        let then_entry = self.block();
+        if constant(condition) != Some(false) {
+            self.edge(current, then_entry, EdgeKind::True);
+        }
+        let then_end = self.sequence(rest.iter().copied().filter(|part| !has_tag(part, ELSE_TAGS)), then_entry);
+        self.edge(then_end, join, EdgeKind::Next);
+        
+        // `elif` clauses are conditionals chained on the previous condition failing
+        let mut failed = (constant(condition) != Some(true)).then_some(current);
+        for branch in rest.iter().filter(|part| has_tag(part, ELSE_TAGS)) {
+            let Some(from) = failed else {
+                break;
+            };
+            let entry = self.block();
+            self.edge(from, entry, EdgeKind::False);
+            if has_tag(branch, &["elif_clause"]) {
+                let parts = significant(branch);
+                let Some((condition, body)) = parts.split_first() else {
+                    continue;
+                };
+                self.append(entry, condition);
+                let body_entry = self.block();
+                if constant(condition) != Some(false) {
+                    self.edge(entry, body_entry, EdgeKind::True);
+                }
+                let end = self.sequence(body.iter().copied(), body_entry);
+                self.edge(end, join, EdgeKind::Next);
+                failed = (constant(condition) != Some(true)).then_some(entry);
+            } else {
+                let end = self.sequence(&branch.children, entry);
+                self.edge(end, join, EdgeKind::Next);
+                failed = None;
+            }
+        }
+        if let Some(from) = failed {
+            self.edge(from, join, EdgeKind::False);
+        }
+        join
+    }
+    
+    fn looping(&mut self, node: &UIRNode, loop_type: LoopType, current: BlockId) -> BlockId {
+        let parts = significant(node);
+        let after = self.block();
+        
+        if loop_type == LoopType::DoWhile {
+            let Some((condition, body)) = parts.split_last() else {
+                return current;
+            };
+            let body_entry = self.block();
+            let test = self.block();
+            self.edge(current, body_entry, EdgeKind::Next);
+            self.breaks.push(after);
+            self.continues.push(test);
+            let end = self.sequence(body.iter().copied(), body_entry);
+            self.breaks.pop();
+            self.continues.pop();
+            self.edge(end, test, EdgeKind::Next);
+            self.append(test, condition);
+            if constant(condition) != Some(false) {
+                self.edge(test, body_entry, EdgeKind::Loop);
+            }
+            if constant(condition) != Some(true) {
+                self.edge(test, after, EdgeKind::False);
+            }
+            return after;
+        }
+        
+        // `for (init; condition; update)` lists its header before the closing parenthesis, Go in
+        // a clause of its own, and JavaScript as the parts it has, each tagged with its role
+        let role = |part: &UIRNode, tag: &str| has_tag(part, &[tag]);
+        let tagged = parts.iter().any(|part| LOOP_HEADER_TAGS.iter().any(|tag| role(part, tag)));
+        let close = node.children.iter().position(|child| original_text(child) == ")" && child.children.is_empty());
+        let clause = parts.iter().position(|part| has_tag(part, &["for_clause"]));
+        let (header, body) = match (&loop_type, clause, close) {
+            (LoopType::For, ..) if tagged => {
+                let header = LOOP_HEADER_TAGS.iter()
+                    .map(|tag| parts.iter().copied().filter(|part| role(part, tag)).collect())
+                    .collect();
+                let length = parts.iter().take_while(|part| LOOP_HEADER_TAGS.iter().any(|tag| role(part, tag))).count();
+                (header, &parts[length..])
+            }
+            (LoopType::While, ..) => {
+                // Rust's `loop` has no condition
+                let length = parts.iter().take(1).filter(|part| !has_tag(part, BLOCK_TAGS)).count();
+                (vec![parts[..length].to_vec()], &parts[length..])
+            }
+            (LoopType::ForEach, ..) => (vec![parts.iter().copied().take(2).collect()], parts.get(2..).unwrap_or_default()),
+            (_, Some(clause), _) => (header_parts(&parts[clause].children), &parts[clause + 1..]),
+            (_, None, Some(close)) => {
+                let open = node.children.iter().position(|child| original_text(child) == "(").unwrap_or_default();
+                (header_parts(&node.children[open..close]), &parts[parts.len() - significant_after(node, close)..])
+            }
+            _ => {
+                let length = parts.iter().take_while(|part| !has_tag(part, BLOCK_TAGS)).count();
+                (vec![parts[..length].to_vec()], &parts[length..])
+            }
+        };
+        let (initializer, condition, update) = match header.as_slice() {
+            [initializer, condition, update] => (initializer.clone(), condition.clone(), update.clone()),
+            _ => (Vec::new(), header.concat(), Vec::new()),
+        };
+        for part in initializer {
+            self.append(current, part);
+        }
+        let test = self.block();
+        self.edge(current, test, EdgeKind::Next);
+        // A literal condition such as `while True` or `while (0)` only ever goes one way
+        let fixed = match condition.as_slice() {
+            [condition] => constant(condition),
+            _ => None,
+        };
+        let infinite = condition.is_empty() || fixed == Some(true);
+        for part in condition {
+            self.append(test, part);
+        }
+        let next = if update.is_empty() {
+            test
+        } else {
+            let block = self.block();
+            for part in update {
+                self.append(block, part);
+            }
+            self.edge(block, test, EdgeKind::Loop);
+            block
+        };
+        let body_entry = self.block();
+        if fixed != Some(false) {
+            self.edge(test, body_entry, if infinite { EdgeKind::Next } else { EdgeKind::True });
+        }
+        // `for (;;)` and Go's bare `for` only leave by `break`
+        if !infinite {
+            self.edge(test, after, EdgeKind::False);
+        }
+        self.breaks.push(after);
+        self.continues.push(next);
+        let end = self.sequence(body.iter().copied(), body_entry);
+        self.breaks.pop();
+        self.continues.pop();
+        let kind = if next == test { EdgeKind::Loop } else { EdgeKind::Next };
+        self.edge(end, next, kind);
+        after
+    }
+    
+    fn switch(&mut self, node: &UIRNode, current: BlockId) -> BlockId {
+        let parts = significant(node);
+        let Some((subject, rest)) = parts.split_first() else {
+            return current;
+        };
+        let mut cases = Vec::new();
+        for part in rest {
+            collect_cases(part, &mut cases);
+        }
+        if cases.is_empty() {
+            return self.sequence(parts.iter().copied(), current);
+        }
+        
+        self.append(current, subject);
+        let after = self.block();
+        let falls_through = matches!(self.language, Language::C | Language::Cpp | Language::JavaScript | Language::TypeScript | Language::Java);
+        let mut previous: Option<BlockId> = None;
+        let mut has_default = false;
+        self.breaks.push(after);
+        for case in cases {
+            has_default |= is_default(case);
+            let entry = self.block();
+            self.edge(current, entry, EdgeKind::Case);
+            if let Some(previous) = previous.take() {
+                self.edge(previous, entry, EdgeKind::Next);
+            }
+            let end = self.sequence(&case.children, entry);
+            if falls_through {
+                previous = Some(end);
+            } else {
+                self.edge(end, after, EdgeKind::Next);
+            }
+        }
+        self.breaks.pop();
+        if let Some(previous) = previous {
+            self.edge(previous, after, EdgeKind::Next);
+        }
+        if !has_default {
+            self.edge(current, after, EdgeKind::False);
+        }
+        after
+    }
+    
+    fn try_block(&mut self, node: &UIRNode, current: BlockId) -> BlockId {
+        let parts = significant(node);
+        let is_handler = |part: &UIRNode| matches!(part.node_type, NodeType::ControlFlow(ControlFlowType::Catch { .. })) || has_tag(part, HANDLER_TAGS);
+        let is_cleanup = |part: &UIRNode| part.node_type == NodeType::ControlFlow(ControlFlowType::Finally) || has_tag(part, CLEANUP_TAGS);
+        let body: Vec<&UIRNode> = parts.iter().copied().filter(|part| !is_handler(part) && !is_cleanup(part)).collect();
+        let handlers: Vec<&UIRNode> = parts.iter().copied().filter(|part| is_handler(part)).collect();
+        let cleanup = parts.iter().copied().find(|part| is_cleanup(part));
+        
+        let after = self.block();
+        let cleanup_entry = cleanup.map(|_| self.block());
+        let handler_entries: Vec<BlockId> = handlers.iter().map(|_| self.block()).collect();
+        let targets = if handler_entries.is_empty() { cleanup_entry.into_iter().collect() } else { handler_entries.clone() };
+        
+        // Anything in the body may raise, so every block of it leads to the handlers
+        let first = self.blocks.len();
+        let body_entry = self.block();
+        self.edge(current, body_entry, EdgeKind::Next);
+        if let Some(entry) = cleanup_entry {
+            self.cleanups.push((entry, false));
+        }
+        self.handlers.push(targets.clone());
+        let body_end = self.sequence(body, body_entry);
+        self.handlers.pop();
+        for block in first..self.blocks.len() {
+            for &target in &targets {
+                self.edge(BlockId(block), target, EdgeKind::Exception);
+            }
+        }
+        
+        let finish = cleanup_entry.unwrap_or(after);
+        self.edge(body_end, finish, EdgeKind::Next);
+        for (handler, entry) in handlers.into_iter().zip(handler_entries) {
+            let end = self.sequence(&handler.children, entry);
+            self.edge(end, finish, EdgeKind::Next);
+        }
+        if let (Some(cleanup), Some(entry)) = (cleanup, cleanup_entry) {
+            let leaves = self.cleanups.pop().is_some_and(|(_, leaves)| leaves);
+            let end = self.sequence(&cleanup.children, entry);
+            self.edge(end, after, EdgeKind::Next);
+            // A return that ran the cleanup on its way out carries on leaving the function
+            if leaves {
+                let target = self.leave();
+                self.edge(end, target, EdgeKind::Return);
+            }
+        }
+        after
+    }
+}
+
+/// Children other than keywords and punctuation
+pub(crate) fn significant(node: &UIRNode) -> Vec<&UIRNode> {
+    node.children.iter().filter(|child| !matches!(classify(child), Flow::Noise)).collect()
+}
+
+/// How many of a node's children after `index` are not keywords or punctuation
+fn significant_after(node: &UIRNode, index: usize) -> usize {
+    node.children[index + 1..].iter().filter(|child| !matches!(classify(child), Flow::Noise)).count()
+}
+
+/// The value of a condition that is a boolean or integer literal
+fn constant(condition: &UIRNode) -> Option<bool> {
+    match condition.literal {
+        Some(LiteralValue::Bool(value)) => Some(value),
+        Some(LiteralValue::Int(value)) => Some(value != 0),
+        _ => None,
+    }
+}
+
+/// Whether a case clause is the one taken when no other matches
+pub(crate) fn is_default(case: &UIRNode) -> bool {
+    has_tag(case, &["switch_default", "default_clause", "default_case"])
+        || case.children.iter().any(|child| has_tag(child, &["default"]))
+        || original_text(case).starts_with("default")
+        || original_text(case).starts_with("_ ")
+}
+
+/// The case clauses of a switch, inside whatever block wraps them
+fn collect_cases<'n>(node: &'n UIRNode, cases: &mut Vec<&'n UIRNode>) {
+    if has_tag(node, CASE_TAGS) {
+        cases.push(node);
+    } else {
+        for child in &node.children {
+            collect_cases(child, cases);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::{CParser, JavaScriptParser, PythonParser};
+    
+    /// The block holding the statement whose source is `text`
+    fn block(graph: &ControlFlowGraph, text: &str) -> BlockId {
+        graph.blocks().find(|(_, block)| block.text.iter().any(|statement| statement == text)).map(|(id, _)| id)
+            .unwrap_or_else(|| panic!("no block runs {}", text))
+    }
+    
+    fn edge(graph: &ControlFlowGraph, from: BlockId, to: BlockId) -> Option<EdgeKind> {
+        graph.edges().iter().find(|edge| edge.from == from && edge.to == to).map(|edge| edge.kind)
+    }
+    
+    #[test]
+    fn test_python_branches_loops_and_unreachable_code() {
+        let parser = PythonParser::new().unwrap();
+        let source = "def f(x):\n    if x > 1:\n        y = 1\n    elif x < 0:\n        y = 2\n    else:\n        y = 3\n    while y:\n        y -= 1\n        if y == 5:\n            break\n    return y\n    print(y)\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let graphs = ControlFlowGraph::build_all(&uir);
+        assert_eq!(graphs.len(), 1);
+        let graph = &graphs[0];
+        let block = |text: &str| block(graph, text);
+        
+        assert_eq!(block("x > 1"), graph.entry());
+        assert_eq!(edge(graph, block("x > 1"), block("y = 1")), Some(EdgeKind::True));
+        assert_eq!(edge(graph, block("x > 1"), block("x < 0")), Some(EdgeKind::False));
+        assert_eq!(edge(graph, block("x < 0"), block("y = 2")), Some(EdgeKind::True));
+        assert_eq!(edge(graph, block("x < 0"), block("y = 3")), Some(EdgeKind::False));
+        for branch in ["y = 1", "y = 2", "y = 3"] {
+            assert_eq!(graph.successors(block(branch)), vec![block("y")]);
+        }
+        
+        assert_eq!(edge(graph, block("y"), block("y -= 1")), Some(EdgeKind::True));
+        assert_eq!(edge(graph, block("y"), block("return y")), Some(EdgeKind::False));
+        assert_eq!(edge(graph, block("break"), block("return y")), Some(EdgeKind::Break));
+        assert!(graph.edges().iter().any(|edge| edge.to == block("y") && edge.kind == EdgeKind::Loop));
+        assert_eq!(edge(graph, block("return y"), graph.exit()), Some(EdgeKind::Return));
+        let unreachable = graph.unreachable_statements();
+        assert_eq!(unreachable.len(), 1);
+        assert_eq!(graph.block_of(unreachable[0]), Some(block("print(y)")));
+    }
+    
+    #[test]
+    fn test_c_for_switch_fall_through_and_goto() {
+        let parser = CParser::new().unwrap();
+        let source = "int f(int n) {\n  int s = 0;\n  for (int i = 0; i < n; i++) { if (i == 3) continue; s += i; }\n  switch (n) { case 1: s = 1; case 2: s = 2; break; default: s = 0; }\n  if (s) goto done;\n  s = 5;\ndone:\n  return s;\n}\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let graphs = ControlFlowGraph::build_all(&uir);
+        assert_eq!(graphs.len(), 1);
+        let graph = &graphs[0];
+        let block = |text: &str| block(graph, text);
+        
+        // The initializer runs once, the update after each pass and after `continue`
+        assert_eq!(block("int i = 0;"), graph.entry());
+        assert_eq!(edge(graph, block("i++"), block("i < n")), Some(EdgeKind::Loop));
+        assert_eq!(edge(graph, block("continue;"), block("i++")), Some(EdgeKind::Continue));
+        assert_eq!(edge(graph, block("i < n"), block("n")), Some(EdgeKind::False));
+        
+        assert_eq!(edge(graph, block("n"), block("s = 1;")), Some(EdgeKind::Case));
+        assert_eq!(edge(graph, block("s = 1;"), block("s = 2;")), Some(EdgeKind::Next));
+        assert_eq!(edge(graph, block("s = 2;"), block("s")), Some(EdgeKind::Break));
+        assert_eq!(edge(graph, block("s = 0;"), block("s")), Some(EdgeKind::Next));
+        
+        assert_eq!(edge(graph, block("goto done;"), block("return s;")), Some(EdgeKind::Goto));
+        assert_eq!(edge(graph, block("s = 5;"), block("return s;")), Some(EdgeKind::Next));
+        assert!(graph.unreachable_statements().is_empty());
+    }
+    
+    #[test]
+    fn test_javascript_loops_are_read_from_their_uir_kind() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "function f(n) {\n  let s = 0;\n  for (let i = 0; i < n; i++) { if (i == 3) continue; s += i; }\n  do { s--; } while (s > 10);\n  return s;\n}\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let graphs = ControlFlowGraph::build_all(&uir);
+        assert_eq!(graphs.len(), 1);
+        let graph = &graphs[0];
+        let block = |text: &str| block(graph, text);
+        
+        assert_eq!(edge(graph, block("i++"), block("i < n")), Some(EdgeKind::Loop));
+        assert_eq!(edge(graph, block("continue;"), block("i++")), Some(EdgeKind::Continue));
+        assert_eq!(edge(graph, block("i < n"), block("i == 3")), Some(EdgeKind::True));
+        assert_eq!(edge(graph, block("s > 10"), block("s--;")), Some(EdgeKind::Loop));
+        assert_eq!(edge(graph, block("s > 10"), block("return s;")), Some(EdgeKind::False));
+        assert!(graph.unreachable_statements().is_empty());
+    }
+    
+    #[test]
+    fn test_python_returns_run_finally_blocks() {
+        let parser = PythonParser::new().unwrap();
+        let source = "def f(x):\n    try:\n        g(x)\n    except ValueError:\n        return 0\n    finally:\n        done()\n    return 1\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let graph = ControlFlowGraph::build(&uir.children[0]);
+        let block = |text: &str| block(&graph, text);
+        
+        assert_eq!(edge(&graph, block("g(x)"), block("return 0")), Some(EdgeKind::Exception));
+        assert_eq!(edge(&graph, block("g(x)"), block("done()")), Some(EdgeKind::Next));
+        assert_eq!(edge(&graph, block("return 0"), block("done()")), Some(EdgeKind::Return));
+        assert_eq!(edge(&graph, block("done()"), block("return 1")), Some(EdgeKind::Next));
+        assert_eq!(edge(&graph, block("done()"), graph.exit()), Some(EdgeKind::Return));
+        assert_eq!(graph.to_graph().nodes.len(), graph.blocks().count());
+    }
+}
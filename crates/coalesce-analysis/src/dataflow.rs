@@ -0,0 +1,794 @@
+// Data flow
+//
+// `DataFlow` runs the classic analyses over a function's control-flow graph. Every parameter,
+// declaration and assignment is a definition of the variable it names, and every read a use.
+// Reaching definitions link each use to the definitions whose value it may see (use-def chains)
+// and each definition to the uses that may see it (def-use chains); liveness gives the variables
+// still to be read on entry to and exit from each block. From these follow what translation
+// needs: whether a declaration is assigned again after it has a value (`let` or `let mut` in
+// Rust), reads that some path reaches before any assignment, values nothing reads, and
+// variables that Python-style sources first assign inside a block and read after it, which a
+// target declaring them in place would leave out of scope. Variables are identified by name
+// within the function; uses of names it never defines, such as globals and builtins, are left
+// out. A statement's reads happen before its writes, and nested functions and closures only
+// read the variables they capture.
+use crate::cfg::{BlockId, ControlFlowGraph, Flow, has_tag, kind, original_text, significant};
+use crate::resolve::resolve;
+use coalesce_core::{ControlFlowType, ExpressionType, Language, LoopType, NodeType, UIRNode, Walk, walk, walk_mut};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefinitionKind {
+    Parameter,
+    /// A declaration, leaving the variable unset when it has no initializer
+    Declaration { initialized: bool },
+    /// A later assignment, including `+=` and `++`
+    Assignment,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Definition {
+    /// Id of the node naming the variable: the parameter, declarator or assignment target
+    pub node_id: String,
+    pub variable: String,
+    pub kind: DefinitionKind,
+    pub block: BlockId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// A read some path reaches before the variable is assigned
+    UninitializedRead,
+    /// An assigned value no path reads
+    UnusedValue,
+    /// A variable first assigned inside a block and read after it
+    EscapesBlock,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataFlowWarning {
+    pub kind: WarningKind,
+    pub variable: String,
+    /// Id of the read, or of the definition for the other kinds
+    pub node_id: String,
+    pub function: Option<String>,
+    pub line: Option<u32>,
+}
+
+impl fmt::Display for DataFlowWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        match self.kind {
+            WarningKind::UninitializedRead => write!(f, "`{}` may be read before it is assigned", self.variable)?,
+            WarningKind::UnusedValue => write!(f, "value assigned to `{}` is never read", self.variable)?,
+            WarningKind::EscapesBlock => write!(f, "`{}` is first assigned inside a block but read after it; it must be declared before the block", self.variable)?,
+        }
+        if let Some(function) = &self.function {
+            write!(f, " in {}", function)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct DataFlow {
+    cfg: ControlFlowGraph,
+    definitions: Vec<Definition>,
+    /// Definitions reaching each use, by the use's node id
+    reaching: HashMap<String, Vec<usize>>,
+    /// Uses each definition reaches
+    uses: Vec<Vec<String>>,
+    live_in: Vec<BTreeSet<String>>,
+    live_out: Vec<BTreeSet<String>>,
+    reassigned: HashSet<usize>,
+    /// Names declared `global` or `nonlocal`, whose values outlive the function
+    shared: HashSet<String>,
+    warnings: Vec<DataFlowWarning>,
+}
+
+impl DataFlow {
+    /// Analyze one function, whose identifiers `resolve` has bound
+    pub fn analyze(function: &UIRNode) -> Self {
+        let cfg = ControlFlowGraph::build(function);
+        let mut nodes = HashMap::new();
+        let mut parents = HashMap::new();
+        index(function, &mut nodes, &mut parents);
+        let bindings = loop_bindings(function);
+        let block_count = cfg.blocks().count();
+        
+        // What each block reads and writes, in order
+        let mut definitions: Vec<Definition> = Vec::new();
+        let mut effects: Vec<Vec<Effect>> = (0..block_count).map(|_| Vec::new()).collect();
+        for parameter in parameters(function) {
+            if let Some(name) = &parameter.name {
+                effects[cfg.entry().index()].push(Effect::Define(definitions.len()));
+                definitions.push(Definition { node_id: parameter.id.clone(), variable: name.clone(), kind: DefinitionKind::Parameter, block: cfg.entry() });
+            }
+        }
+        for (block, statements) in cfg.blocks() {
+            for statement in &statements.statements {
+                let Some(node) = nodes.get(statement.as_str()) else {
+                    continue;
+                };
+                let mut collector = Collector::default();
+                if bindings.contains(statement.as_str()) {
+                    collector.bind(node, DefinitionKind::Declaration { initialized: true });
+                } else {
+                    collector.visit(node);
+                }
+                effects[block.index()].extend(collector.uses.into_iter().map(|(node_id, variable)| Effect::Use { node_id, variable }));
+                for (node_id, variable, kind) in collector.definitions {
+                    effects[block.index()].push(Effect::Define(definitions.len()));
+                    definitions.push(Definition { node_id, variable, kind, block });
+                }
+            }
+        }
+        let variables: HashSet<String> = definitions.iter().map(|definition| definition.variable.clone()).collect();
+        for block in &mut effects {
+            block.retain(|effect| !matches!(effect, Effect::Use { variable, .. } if !variables.contains(variable)));
+        }
+        
+        // Reaching definitions at block boundaries, then through each block's statements
+        let predecessors: Vec<Vec<BlockId>> = cfg.blocks().map(|(id, _)| cfg.predecessors(id)).collect();
+        let transfer = |reaching: &mut BTreeSet<usize>, effect: &Effect| {
+            if let Effect::Define(index) = effect {
+                reaching.retain(|other| definitions[*other].variable != definitions[*index].variable);
+                reaching.insert(*index);
+            }
+        };
+        let mut reach_out: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); block_count];
+        loop {
+            let mut changed = false;
+            for block in 0..block_count {
+                let mut reaching: BTreeSet<usize> = predecessors[block].iter().flat_map(|from| reach_out[from.index()].iter().copied()).collect();
+                for effect in &effects[block] {
+                    transfer(&mut reaching, effect);
+                }
+                if reaching != reach_out[block] {
+                    reach_out[block] = reaching;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        
+        let mut reaching_uses: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut uses = vec![Vec::new(); definitions.len()];
+        // Earlier definitions of the same variable each definition overwrites
+        let mut overwrites: Vec<Vec<usize>> = vec![Vec::new(); definitions.len()];
+        for block in 0..block_count {
+            let mut reaching: BTreeSet<usize> = predecessors[block].iter().flat_map(|from| reach_out[from.index()].iter().copied()).collect();
+            for effect in &effects[block] {
+                let same = |variable: &str| reaching.iter().copied().filter(|&index| definitions[index].variable == variable).collect::<Vec<_>>();
+                match effect {
+                    Effect::Use { node_id, variable } => {
+                        let reached = same(variable);
+                        for &index in &reached {
+                            uses[index].push(node_id.clone());
+                        }
+                        reaching_uses.entry(node_id.clone()).or_default().extend(reached);
+                    }
+                    Effect::Define(index) => overwrites[*index] = same(&definitions[*index].variable),
+                }
+                transfer(&mut reaching, effect);
+            }
+        }
+        
+        // The declarations each definition descends from, through the assignments between them
+        let mut roots: Vec<BTreeSet<usize>> = definitions.iter().enumerate()
+            .map(|(index, definition)| match definition.kind {
+                DefinitionKind::Assignment => BTreeSet::new(),
+                _ => BTreeSet::from([index]),
+            })
+            .collect();
+        loop {
+            let mut changed = false;
+            for index in 0..definitions.len() {
+                if definitions[index].kind != DefinitionKind::Assignment {
+                    continue;
+                }
+                let inherited: BTreeSet<usize> = overwrites[index].iter().flat_map(|&earlier| roots[earlier].iter().copied()).collect();
+                if !inherited.is_subset(&roots[index]) {
+                    roots[index].extend(inherited);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        // Assigning a declaration without an initializer gives it its first value, not a new one
+        let mut reassigned = HashSet::new();
+        for (index, definition) in definitions.iter().enumerate() {
+            if definition.kind == DefinitionKind::Assignment {
+                for &earlier in &overwrites[index] {
+                    if definitions[earlier].kind != (DefinitionKind::Declaration { initialized: false }) {
+                        reassigned.extend(roots[earlier].iter().copied());
+                    }
+                }
+            }
+        }
+        
+        // Liveness, backwards from the uses not preceded by a definition in their block
+        let mut read_first: Vec<BTreeSet<String>> = vec![BTreeSet::new(); block_count];
+        let mut written: Vec<BTreeSet<String>> = vec![BTreeSet::new(); block_count];
+        for block in 0..block_count {
+            for effect in &effects[block] {
+                match effect {
+                    Effect::Use { variable, .. } if !written[block].contains(variable) => {
+                        read_first[block].insert(variable.clone());
+                    }
+                    Effect::Use { .. } => {}
+                    Effect::Define(index) => {
+                        written[block].insert(definitions[*index].variable.clone());
+                    }
+                }
+            }
+        }
+        let successors: Vec<Vec<BlockId>> = cfg.blocks().map(|(id, _)| cfg.successors(id)).collect();
+        let mut live_in: Vec<BTreeSet<String>> = vec![BTreeSet::new(); block_count];
+        let mut live_out: Vec<BTreeSet<String>> = vec![BTreeSet::new(); block_count];
+        loop {
+            let mut changed = false;
+            for block in (0..block_count).rev() {
+                let out: BTreeSet<String> = successors[block].iter().flat_map(|to| live_in[to.index()].iter().cloned()).collect();
+                let mut live: BTreeSet<String> = out.difference(&written[block]).cloned().collect();
+                live.extend(read_first[block].iter().cloned());
+                if live != live_in[block] || out != live_out[block] {
+                    live_in[block] = live;
+                    live_out[block] = out;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        
+        let mut flow = DataFlow { cfg, definitions, reaching: reaching_uses, uses, live_in, live_out, reassigned, shared: shared_names(function), warnings: Vec::new() };
+        flow.warnings = flow.find_warnings(function, &nodes, &parents, &roots, &bindings);
+        flow
+    }
+    
+    pub fn cfg(&self) -> &ControlFlowGraph {
+        &self.cfg
+    }
+    
+    pub fn definitions(&self) -> &[Definition] {
+        &self.definitions
+    }
+    
+    /// The definitions whose value the use with id `node_id` may read
+    pub fn reaching(&self, node_id: &str) -> Vec<&Definition> {
+        self.reaching.get(node_id).into_iter().flatten().map(|&index| &self.definitions[index]).collect()
+    }
+    
+    /// Ids of the uses that may read the value of the definition at `node_id`
+    pub fn uses(&self, node_id: &str) -> Vec<&str> {
+        self.definitions.iter().enumerate()
+            .filter(|(_, definition)| definition.node_id == node_id)
+            .flat_map(|(index, _)| self.uses[index].iter().map(String::as_str))
+            .collect()
+    }
+    
+    /// Variables that may still be read on entry to `block`
+    pub fn live_in(&self, block: BlockId) -> &BTreeSet<String> {
+        &self.live_in[block.index()]
+    }
+    
+    /// Variables that may still be read after `block`
+    pub fn live_out(&self, block: BlockId) -> &BTreeSet<String> {
+        &self.live_out[block.index()]
+    }
+    
+    /// Whether the parameter or declaration at `node_id` is assigned again once it has a value
+    pub fn is_reassigned(&self, node_id: &str) -> bool {
+        self.definitions.iter().enumerate()
+            .any(|(index, definition)| definition.node_id == node_id && self.reassigned.contains(&index))
+    }
+    
+    pub fn warnings(&self) -> &[DataFlowWarning] {
+        &self.warnings
+    }
+    
+    /// The first definition of each local variable that nothing ever reads, leaving out
+    /// parameters, `_`-prefixed names and variables only defined in unreachable code
+    pub fn unread_variables(&self) -> Vec<&Definition> {
+        let reachable = self.cfg.reachable();
+        let mut seen = HashSet::new();
+        let mut unread = Vec::new();
+        for definition in &self.definitions {
+            if !seen.insert(definition.variable.as_str()) || definition.variable.starts_with('_') || self.shared.contains(&definition.variable) {
+                continue;
+            }
+            let mut all = self.definitions.iter().enumerate().filter(|(_, other)| other.variable == definition.variable);
+            let read = all.clone().any(|(index, other)| other.kind == DefinitionKind::Parameter || !self.uses[index].is_empty());
+            if !read && all.any(|(_, other)| reachable.contains(&other.block)) {
+                unread.push(definition);
+            }
+        }
+        unread
+    }
+    
+    fn find_warnings(&self, function: &UIRNode, nodes: &HashMap<&str, &UIRNode>, parents: &HashMap<&str, &str>, roots: &[BTreeSet<usize>], bindings: &HashSet<&str>) -> Vec<DataFlowWarning> {
+        let warning = |kind: WarningKind, variable: &str, node_id: &str| DataFlowWarning {
+            kind,
+            variable: variable.to_string(),
+            node_id: node_id.to_string(),
+            function: function.name.clone(),
+            line: nodes.get(node_id).and_then(|node| node.source_location.as_ref()).map(|location| location.start_line),
+        };
+        let mut warnings = Vec::new();
+        let mut read: Vec<(&String, &Vec<usize>)> = self.reaching.iter().collect();
+        read.sort_by_key(|(node_id, _)| self.cfg.block_of(node_id).map(BlockId::index));
+        // Each declaration is reported at its first read only
+        let mut reported = HashSet::new();
+        for (node_id, reaching) in read {
+            if let Some(&index) = reaching.iter().find(|&&index| self.definitions[index].kind == DefinitionKind::Declaration { initialized: false }) {
+                if reported.insert(index) {
+                    warnings.push(warning(WarningKind::UninitializedRead, &self.definitions[index].variable, node_id));
+                }
+            }
+        }
+        
+        let shared = &self.shared;
+        let reachable = self.cfg.reachable();
+        for (index, definition) in self.definitions.iter().enumerate() {
+            let assigns = matches!(definition.kind, DefinitionKind::Assignment | DefinitionKind::Declaration { initialized: true });
+            if assigns && self.uses[index].is_empty() && !definition.variable.starts_with('_') && !shared.contains(&definition.variable)
+                && !bindings.contains(definition.node_id.as_str()) && reachable.contains(&definition.block) {
+                warnings.push(warning(WarningKind::UnusedValue, &definition.variable, &definition.node_id));
+            }
+        }
+        
+        // Only sources that declare by assigning leave a variable's scope to the function
+        if matches!(function.metadata.source_language, Language::Python | Language::R | Language::Bash) {
+            let ancestor = |node_id: &str| {
+                let mut current = parents.get(node_id).copied();
+                while let Some(id) = current {
+                    if id == function.id {
+                        return None;
+                    }
+                    if nodes.get(id).is_some_and(|node| matches!(node.node_type, NodeType::ControlFlow(_))) {
+                        return Some(id);
+                    }
+                    current = parents.get(id).copied();
+                }
+                None
+            };
+            let within = |node_id: &str, block: &str| {
+                let mut current = Some(node_id);
+                while let Some(id) = current {
+                    if id == block {
+                        return true;
+                    }
+                    current = parents.get(id).copied();
+                }
+                false
+            };
+            for (index, definition) in self.definitions.iter().enumerate() {
+                if !matches!(definition.kind, DefinitionKind::Declaration { .. }) {
+                    continue;
+                }
+                let Some(block) = ancestor(&definition.node_id) else {
+                    continue;
+                };
+                let escapes = roots.iter().enumerate()
+                    .filter(|(_, roots)| roots.contains(&index))
+                    .flat_map(|(descendant, _)| self.uses[descendant].iter())
+                    .any(|node_id| !within(node_id, block));
+                if escapes {
+                    warnings.push(warning(WarningKind::EscapesBlock, &definition.variable, &definition.node_id));
+                }
+            }
+        }
+        warnings.sort_by_key(|warning| warning.line);
+        warnings
+    }
+}
+
+/// Analyze every function in `module`, annotating each parameter and declaration with whether
+/// it is `reassigned`, and return the warnings found
+pub fn analyze_data_flow(module: &mut UIRNode) -> Vec<DataFlowWarning> {
+    resolve(module);
+    let mut flows = Vec::new();
+    walk(&*module, &mut |node: &UIRNode| {
+        if node.node_type == NodeType::Function && !has_tag(node, &["function_declarator"]) {
+            flows.push(DataFlow::analyze(node));
+        }
+        Walk::Continue
+    });
+    
+    let mut reassigned = HashMap::new();
+    let mut warnings = Vec::new();
+    for flow in &flows {
+        for definition in &flow.definitions {
+            if definition.kind != DefinitionKind::Assignment {
+                reassigned.insert(definition.node_id.clone(), flow.is_reassigned(&definition.node_id));
+            }
+        }
+        warnings.extend(flow.warnings.iter().cloned());
+    }
+    walk_mut(module, &mut |node: &mut UIRNode| {
+        if let Some(&value) = reassigned.get(&node.id) {
+            node.metadata.annotations.insert("reassigned".to_string(), Value::Bool(value));
+        }
+        Walk::Continue
+    });
+    warnings
+}
+
+enum Effect {
+    Use { node_id: String, variable: String },
+    Define(usize),
+}
+
+pub(crate) const ASSIGNMENT_TAGS: &[&str] = &["assignment", "assignment_expression", "assignment_statement", "augmented_assignment", "augmented_assignment_expression", "compound_assignment_expr"];
+const AUGMENTED_TAGS: &[&str] = &["augmented_assignment", "augmented_assignment_expression", "compound_assignment_expr"];
+pub(crate) const UPDATE_TAGS: &[&str] = &["update_expression", "inc_statement", "dec_statement"];
+/// Declarations naming their variables before an `=` and the initial value after it
+const DECLARATOR_TAGS: &[&str] = &["init_declarator", "let_declaration", "variable_declarator", "range_clause", "var_spec", "short_var_declaration"];
+/// Accesses whose later parts name members rather than variables
+pub(crate) const MEMBER_TAGS: &[&str] = &["attribute", "member_expression", "field_expression", "selector_expression", "field_access"];
+/// Properties named by the variable they read, as in `{ data }`
+const SHORTHAND_TAGS: &[&str] = &["shorthand_property_identifier"];
+
+/// The reads and writes of one statement
+#[derive(Default)]
+struct Collector {
+    uses: Vec<(String, String)>,
+    definitions: Vec<(String, String, DefinitionKind)>,
+    /// Inside a nested function or closure, which only reads
+    nested: usize,
+}
+
+impl Collector {
+    fn visit(&mut self, node: &UIRNode) {
+        match &node.node_type {
+            NodeType::Comment { .. } => return,
+            NodeType::Function | NodeType::Class | NodeType::Closure { .. } => {
+                self.nested += 1;
+                for child in &node.children {
+                    self.visit(child);
+                }
+                self.nested -= 1;
+                return;
+            }
+            _ => {}
+        }
+        if self.nested == 0 && self.definition(node) {
+            return;
+        }
+        if let Some(name) = read_name(node) {
+            self.uses.push((node.id.clone(), name.to_string()));
+            return;
+        }
+        if has_tag(node, SHORTHAND_TAGS) && node.children.is_empty() {
+            self.uses.push((node.id.clone(), original_text(node).to_string()));
+            return;
+        }
+        for (position, child) in node.children.iter().enumerate() {
+            // `a.b` reads `a` only, and `f(key=value)` reads only the value
+            let member = position > 0 && has_tag(node, MEMBER_TAGS) && child.children.is_empty();
+            let keyword = position == 0 && has_tag(node, &["keyword_argument"]);
+            if !member && !keyword {
+                self.visit(child);
+            }
+        }
+    }
+    
+    /// Record the writes `node` makes along with its reads, if it writes
+    fn definition(&mut self, node: &UIRNode) -> bool {
+        if node.node_type == NodeType::Variable {
+            return self.variable(node, false, false);
+        }
+        let assignment = node.node_type == NodeType::Expression(ExpressionType::Assignment) || has_tag(node, ASSIGNMENT_TAGS);
+        if assignment {
+            let parts = significant(node);
+            let Some((target, values)) = parts.split_first() else {
+                return false;
+            };
+            let augmented = node.operator.is_some() || has_tag(node, AUGMENTED_TAGS) || node.children.iter()
+                .any(|child| child.children.is_empty() && original_text(child).len() > 1 && original_text(child).ends_with('=')
+                    && !matches!(original_text(child), "==" | "!=" | "<=" | ">=" | ":="));
+            for value in values {
+                self.visit(value);
+            }
+            self.target(target, augmented);
+            return true;
+        }
+        if has_tag(node, UPDATE_TAGS) {
+            match significant(node).first() {
+                Some(target) => self.target(target, true),
+                None => return false,
+            }
+            return true;
+        }
+        if has_tag(node, &["declaration"]) && node.node_type != NodeType::Function {
+            // C declares several variables at once, each with or without a value
+            for child in &node.children {
+                match read_name(child) {
+                    Some(name) => self.define(child, name, DefinitionKind::Declaration { initialized: false }),
+                    None if has_tag(child, DECLARATOR_TAGS) => {
+                        self.definition(child);
+                    }
+                    // An array has storage whether or not its elements are set
+                    None if has_tag(child, &["array_declarator"]) => self.bind(child, DefinitionKind::Declaration { initialized: true }),
+                    None if has_tag(child, &["pointer_declarator", "reference_declarator"]) => self.bind(child, DefinitionKind::Declaration { initialized: false }),
+                    None => {}
+                }
+            }
+            return true;
+        }
+        if has_tag(node, DECLARATOR_TAGS) {
+            let equals = node.children.iter().position(|child| child.children.is_empty() && matches!(original_text(child), "=" | ":="));
+            let (names, values) = node.children.split_at(equals.unwrap_or(node.children.len()));
+            for value in values {
+                self.visit(value);
+            }
+            for name in names {
+                self.bind(name, DefinitionKind::Declaration { initialized: equals.is_some() });
+            }
+            return true;
+        }
+        false
+    }
+    
+    /// A declared or assigned variable, with the value it is given
+    fn variable(&mut self, node: &UIRNode, assigned: bool, augmented: bool) -> bool {
+        let Some(name) = node.name.as_deref().filter(|name| !name.contains('.')) else {
+            return false;
+        };
+        // The variable's own identifier and type come before its value
+        let equals = node.children.iter().position(|child| child.children.is_empty() && matches!(original_text(child), "=" | ":="));
+        let values: Vec<&UIRNode> = match equals {
+            Some(equals) => node.children[equals + 1..].iter().collect(),
+            // Punctuation is no value, but an empty `[]` or `{}` made of it is
+            None => node.children.iter()
+                .filter(|child| !child.children.is_empty() || !matches!(kind(child), Flow::Noise))
+                .filter(|child| read_name(child) != Some(name) && !child.metadata.semantic_tags.first().is_some_and(|tag| tag.contains("type")))
+                .collect(),
+        };
+        for value in &values {
+            self.visit(value);
+        }
+        // A binding of a destructuring pattern, as `status` of `const {status} = filters`, is
+        // given its value by the pattern and reads the value it takes apart
+        let destructured = has_tag(node, &["destructured"]);
+        if let Some(from) = node.metadata.annotations.get("destructured_from").and_then(Value::as_str) {
+            if !from.is_empty() && from.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$') {
+                self.uses.push((node.id.clone(), from.to_string()));
+            }
+        }
+        let rebinds = node.metadata.annotations.contains_key("resolved_id");
+        if augmented {
+            self.uses.push((node.id.clone(), name.to_string()));
+        }
+        let kind = match (rebinds || augmented, assigned || destructured || !values.is_empty()) {
+            (true, _) => DefinitionKind::Assignment,
+            (false, initialized) => DefinitionKind::Declaration { initialized },
+        };
+        self.define(node, name, kind);
+        true
+    }
+    
+    /// The target of an assignment, which `+=` and `++` also read
+    fn target(&mut self, target: &UIRNode, augmented: bool) {
+        if target.node_type == NodeType::Variable {
+            self.variable(target, true, augmented);
+        } else if let Some(name) = read_name(target) {
+            if augmented {
+                self.uses.push((target.id.clone(), name.to_string()));
+            }
+            self.define(target, name, DefinitionKind::Assignment);
+        } else if target.name.is_none() && matches!(target.node_type, NodeType::Expression(ExpressionType::Variable)) {
+            // Lists of targets, as in `a, b = b, a`
+            for part in significant(target) {
+                self.target(part, augmented);
+            }
+        } else {
+            // Assigning a member or element reads the object
+            self.visit(target);
+        }
+    }
+    
+    /// Every variable named in a declarator or loop pattern
+    fn bind(&mut self, node: &UIRNode, kind: DefinitionKind) {
+        if let Some(name) = read_name(node) {
+            self.define(node, name, kind);
+        } else if node.node_type == NodeType::Variable {
+            self.variable(node, true, false);
+        } else if !node.metadata.semantic_tags.first().is_some_and(|tag| tag.contains("type")) {
+            for child in &node.children {
+                self.bind(child, kind);
+            }
+        }
+    }
+    
+    fn define(&mut self, node: &UIRNode, name: &str, kind: DefinitionKind) {
+        self.definitions.push((node.id.clone(), name.to_string(), kind));
+    }
+}
+
+/// The variable a plain identifier reads
+fn read_name(node: &UIRNode) -> Option<&str> {
+    match node.node_type {
+        NodeType::Expression(ExpressionType::Variable) if node.children.is_empty() => {
+            node.name.as_deref().filter(|name| !name.contains('.') && *name != "_")
+        }
+        _ => None,
+    }
+}
+
+fn index<'n>(node: &'n UIRNode, nodes: &mut HashMap<&'n str, &'n UIRNode>, parents: &mut HashMap<&'n str, &'n str>) {
+    nodes.insert(&node.id, node);
+    for child in &node.children {
+        parents.insert(&child.id, &node.id);
+        index(child, nodes, parents);
+    }
+}
+
+/// Parameters, listed directly under the function or in a parameter list or C declarator
+fn parameters(function: &UIRNode) -> Vec<&UIRNode> {
+    let mut parameters = Vec::new();
+    let mut pending: Vec<&UIRNode> = function.children.iter().rev().collect();
+    while let Some(node) = pending.pop() {
+        match node.node_type {
+            NodeType::Variable => parameters.push(node),
+            NodeType::Function if has_tag(node, &["function_declarator"]) => pending.extend(node.children.iter().rev()),
+            NodeType::Expression(_) if has_tag(node, &["parameter_list", "parameters", "formal_parameters"]) => pending.extend(node.children.iter().rev()),
+            _ => {}
+        }
+    }
+    parameters
+}
+
+/// Ids of the variables or patterns `for` loops bind each element to
+fn loop_bindings(function: &UIRNode) -> HashSet<&str> {
+    let mut bindings = HashSet::new();
+    let mut pending = vec![function];
+    while let Some(node) = pending.pop() {
+        let foreach = matches!(node.node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)))
+            || has_tag(node, &["for_expression", "for_in_statement"]);
+        if foreach {
+            if let Some(binding) = significant(node).first() {
+                bindings.insert(binding.id.as_str());
+            }
+        }
+        pending.extend(node.children.iter());
+    }
+    bindings
+}
+
+/// Names a Python function declares `global` or `nonlocal`, whose values outlive the call
+pub(crate) fn shared_names(function: &UIRNode) -> HashSet<String> {
+    let mut names = HashSet::new();
+    walk(function, &mut |node: &UIRNode| {
+        if has_tag(node, &["global_statement", "nonlocal_statement"]) {
+            names.extend(node.children.iter().filter_map(|child| child.name.clone()));
+        }
+        Walk::Continue
+    });
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::{CParser, JavaScriptParser, PythonParser, RustParser};
+    
+    fn definition<'a>(flow: &'a DataFlow, variable: &str, kind: DefinitionKind) -> &'a Definition {
+        flow.definitions().iter().find(|definition| definition.variable == variable && definition.kind == kind).unwrap()
+    }
+    
+    #[test]
+    fn test_python_use_def_chains_and_liveness() {
+        let parser = PythonParser::new().unwrap();
+        let source = "def f(n, items):\n    total = 0\n    for x in items:\n        total += x\n    if n > 1:\n        label = \"big\"\n    else:\n        label = \"small\"\n    unused = n\n    return total, label\n";
+        
+        let mut uir = parser.parse(source).unwrap();
+        let warnings = analyze_data_flow(&mut uir);
+        let flow = DataFlow::analyze(&uir.children[0]);
+        
+        // Both branches' labels reach the return, and the loop's sum reaches itself
+        let returned = uir.select("Statement.Return Expression.Variable[name=label]").unwrap()[0];
+        assert_eq!(flow.reaching(&returned.id).len(), 2);
+        let sum = definition(&flow, "total", DefinitionKind::Assignment);
+        assert_eq!(flow.uses(&sum.node_id).len(), 2);
+        assert!(flow.live_in(sum.block).contains("total"));
+        assert!(!flow.live_out(sum.block).contains("x"));
+        
+        let total = definition(&flow, "total", DefinitionKind::Declaration { initialized: true });
+        let label = definition(&flow, "label", DefinitionKind::Declaration { initialized: true });
+        assert!(flow.is_reassigned(&total.node_id));
+        assert!(!flow.is_reassigned(&label.node_id));
+        let annotation = |node_id: &str| {
+            uir.select("Variable").unwrap().into_iter().find(|node| node.id == node_id).and_then(|node| node.metadata.annotations.get("reassigned").cloned())
+        };
+        assert_eq!(annotation(&total.node_id), Some(Value::Bool(true)));
+        assert_eq!(annotation(&label.node_id), Some(Value::Bool(false)));
+        
+        let kinds: Vec<_> = warnings.iter().map(|warning| (warning.kind, warning.variable.as_str())).collect();
+        assert_eq!(kinds, vec![(WarningKind::EscapesBlock, "label"), (WarningKind::UnusedValue, "unused")]);
+        assert_eq!(warnings[1].to_string(), "line 9: value assigned to `unused` is never read in f");
+    }
+    
+    #[test]
+    fn test_c_reads_before_assignment() {
+        let parser = CParser::new().unwrap();
+        let source = "int f(int n) {\n  int x;\n  int y = n, z;\n  if (n > 0) x = y + 1;\n  x += 2;\n  return x + z;\n}\n";
+        
+        let mut uir = parser.parse(source).unwrap();
+        let warnings = analyze_data_flow(&mut uir);
+        let uninitialized: Vec<_> = warnings.iter()
+            .filter(|warning| warning.kind == WarningKind::UninitializedRead)
+            .map(|warning| (warning.variable.as_str(), warning.line))
+            .collect();
+        assert_eq!(uninitialized, vec![("x", Some(5)), ("z", Some(6))]);
+        
+        let flow = DataFlow::analyze(&uir.children[0]);
+        let x = definition(&flow, "x", DefinitionKind::Declaration { initialized: false });
+        let y = definition(&flow, "y", DefinitionKind::Declaration { initialized: true });
+        assert!(flow.is_reassigned(&x.node_id));
+        assert!(!flow.is_reassigned(&y.node_id));
+        assert!(!flow.is_reassigned(&definition(&flow, "n", DefinitionKind::Parameter).node_id));
+    }
+    
+    #[test]
+    fn test_rust_deferred_initialization_is_not_reassignment() {
+        let parser = RustParser::new().unwrap();
+        let source = "fn f(n: i32) -> i32 {\n    let mut s = 0;\n    let t: i32;\n    s += n;\n    t = s;\n    for i in 0..n { s = i; }\n    t + s\n}\n";
+        
+        let mut uir = parser.parse(source).unwrap();
+        let warnings = analyze_data_flow(&mut uir);
+        let flow = DataFlow::analyze(&uir.children[0]);
+        
+        assert!(flow.is_reassigned(&definition(&flow, "s", DefinitionKind::Declaration { initialized: true }).node_id));
+        assert!(!flow.is_reassigned(&definition(&flow, "t", DefinitionKind::Declaration { initialized: false }).node_id));
+        assert!(!flow.is_reassigned(&definition(&flow, "i", DefinitionKind::Declaration { initialized: true }).node_id));
+        assert!(warnings.is_empty());
+    }
+    
+    #[test]
+    fn test_javascript_initializers_and_shorthand_properties_read() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "async function load(u) {\n  const response = await g(u);\n  let p = response;\n  return p;\n}\nasync function fetchIt(u) {\n  const data = await g(u);\n  return { data, error: null };\n}\n";
+        
+        let mut uir = parser.parse(source).unwrap();
+        let warnings = analyze_data_flow(&mut uir);
+        assert!(warnings.is_empty(), "{:?}", warnings.iter().map(|warning| warning.to_string()).collect::<Vec<_>>());
+        
+        let flow = DataFlow::analyze(&uir.children[0]);
+        let response = definition(&flow, "response", DefinitionKind::Declaration { initialized: true });
+        assert_eq!(flow.uses(&response.node_id).len(), 1);
+        let flow = DataFlow::analyze(&uir.children[1]);
+        let data = definition(&flow, "data", DefinitionKind::Declaration { initialized: true });
+        assert_eq!(flow.uses(&data.node_id).len(), 1);
+    }
+    
+    #[test]
+    fn test_javascript_destructured_bindings_are_assigned() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "function f(pair) {\n  const filters = load();\n  const {status, limit = 3} = filters;\n  const [a, b] = pair;\n  const items = [];\n  items.push(status, limit, a, b);\n  return items;\n}\nfunction g(n) {\n  let x;\n  if (n) { x = 1; }\n  return x + x;\n}\n";
+        
+        let mut uir = parser.parse(source).unwrap();
+        let warnings: Vec<_> = analyze_data_flow(&mut uir).iter().map(|warning| warning.to_string()).collect();
+        // `x` is reported once, at its first read
+        assert_eq!(warnings, vec!["line 12: `x` may be read before it is assigned in g"]);
+        
+        let flow = DataFlow::analyze(&uir.children[0]);
+        let status = definition(&flow, "status", DefinitionKind::Declaration { initialized: true });
+        assert_eq!(flow.uses(&status.node_id).len(), 1);
+        let filters = definition(&flow, "filters", DefinitionKind::Declaration { initialized: true });
+        assert_eq!(flow.uses(&filters.node_id).len(), 2);
+    }
+}
@@ -0,0 +1,341 @@
+// Dead code
+//
+// `DeadCode` finds what a translation can leave behind: functions no live code calls or names,
+// statements no path through their function reaches, and local variables whose values are
+// never read. A module's top-level code is live, and so are `main`, public, exported and
+// decorated functions, as well as methods and classes, which objects reach in ways the call
+// graph does not follow. Every function that live code calls, or names to pass it along as a
+// callback, is live in turn. Names are matched loosely across modules, so a function is only
+// reported when no live code anywhere mentions it. Nothing inside an unused function is
+// reported apart from the function itself.
+use crate::calls::{CallGraph, Callable, CallableKind};
+use crate::cfg::has_tag;
+use crate::dataflow::DataFlow;
+use coalesce_core::{ExpressionType, NodeType, UIRNode, Visibility, Walk, walk, walk_mut};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadCodeKind {
+    /// A function no live code calls or names
+    UnusedFunction,
+    /// A statement no path from its function's entry reaches
+    Unreachable,
+    /// A local variable that is assigned but never read
+    UnreadVariable,
+}
+
+impl DeadCodeKind {
+    /// The value of the `dead_code` annotation
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeadCodeKind::UnusedFunction => "unused_function",
+            DeadCodeKind::Unreachable => "unreachable",
+            DeadCodeKind::UnreadVariable => "unread_variable",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadCodeItem {
+    pub kind: DeadCodeKind,
+    /// The function's qualified name, the variable, or the statement's first line
+    pub name: String,
+    pub module: String,
+    /// The function the item is in, absent for unused functions
+    pub function: Option<String>,
+    pub node_id: String,
+    pub line: Option<u32>,
+}
+
+impl fmt::Display for DeadCodeItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: ", self.module, line)?,
+            None => write!(f, "{}: ", self.module)?,
+        }
+        match self.kind {
+            DeadCodeKind::UnusedFunction => write!(f, "function `{}` is never used", self.name)?,
+            DeadCodeKind::Unreachable => write!(f, "`{}` is unreachable", self.name)?,
+            DeadCodeKind::UnreadVariable => write!(f, "`{}` is assigned but never read", self.name)?,
+        }
+        if let Some(function) = &self.function {
+            write!(f, " in {}", function)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeadCode {
+    items: Vec<DeadCodeItem>,
+}
+
+impl DeadCode {
+    /// Dead code in modules named by their file stem, whose identifiers `resolve` has bound
+    pub fn find<'a>(modules: impl IntoIterator<Item = (&'a str, &'a UIRNode)>) -> Self {
+        let modules: Vec<(&str, &UIRNode)> = modules.into_iter().collect();
+        let graph = CallGraph::from_modules(modules.iter().copied());
+        let declared: HashMap<(&str, &str), usize> = graph.callables().iter().enumerate()
+            .filter_map(|(index, callable)| Some(((callable.module.as_deref()?, callable.node_id.as_deref()?), index)))
+            .collect();
+        
+        let ids: HashMap<&str, usize> = graph.callables().iter().enumerate().map(|(index, callable)| (callable.id.as_str(), index)).collect();
+        let mut scan = Scan {
+            callables: graph.callables(),
+            declared: &declared,
+            ids: &ids,
+            module: "",
+            functions: HashMap::new(),
+            enclosing: HashMap::new(),
+            roots: HashSet::new(),
+            names: Vec::new(),
+        };
+        for &(module, root) in &modules {
+            scan.module = module;
+            scan.visit(root, None, None, false);
+        }
+        let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for &function in scan.functions.keys() {
+            let name = graph.callables()[function].name.rsplit('.').next().unwrap_or_default();
+            by_name.entry(name).or_default().push(function);
+        }
+        let mut references: HashMap<usize, HashSet<usize>> = HashMap::new();
+        for (owner, name) in &scan.names {
+            references.entry(*owner).or_default().extend(by_name.get(name.as_str()).into_iter().flatten().copied());
+        }
+        for call in graph.calls() {
+            if let (Some(&caller), Some(&callee)) = (ids.get(call.caller.as_str()), ids.get(call.callee.as_str())) {
+                references.entry(caller).or_default().insert(callee);
+            }
+        }
+        
+        // Everything the roots reach through calls and references is live
+        let mut live: HashSet<usize> = scan.roots.clone();
+        let mut queue: VecDeque<usize> = live.iter().copied().collect();
+        while let Some(current) = queue.pop_front() {
+            for &next in references.get(&current).into_iter().flatten() {
+                if live.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        
+        let mut items = Vec::new();
+        let mut dead = HashSet::new();
+        let mut unused: Vec<usize> = scan.functions.keys().copied().filter(|function| !live.contains(function)).collect();
+        unused.sort_unstable();
+        for function in unused {
+            let (module, node) = scan.functions[&function];
+            dead.insert((module, node.id.as_str()));
+            // A function nested in an unused one goes with it
+            if scan.enclosing.get(&function).is_some_and(|outer| scan.functions.contains_key(outer) && !live.contains(outer)) {
+                continue;
+            }
+            items.push(DeadCodeItem {
+                kind: DeadCodeKind::UnusedFunction,
+                name: graph.callables()[function].name.clone(),
+                module: module.to_string(),
+                function: None,
+                node_id: node.id.clone(),
+                line: line(node),
+            });
+        }
+        
+        for &(module, root) in &modules {
+            let mut nodes = HashMap::new();
+            index(root, &mut nodes);
+            walk(root, &mut |node: &UIRNode| {
+                if dead.contains(&(module, node.id.as_str())) {
+                    return Walk::SkipChildren;
+                }
+                if node.node_type != NodeType::Function || has_tag(node, &["function_declarator"]) {
+                    return Walk::Continue;
+                }
+                let flow = DataFlow::analyze(node);
+                let item = |kind: DeadCodeKind, name: String, node_id: &str| DeadCodeItem {
+                    kind,
+                    name,
+                    module: module.to_string(),
+                    function: node.name.clone(),
+                    node_id: node_id.to_string(),
+                    line: nodes.get(node_id).and_then(|node| line(node)),
+                };
+                for statement in flow.cfg().unreachable_statements() {
+                    let text = nodes.get(statement).map(|node| first_line(node)).unwrap_or_default();
+                    items.push(item(DeadCodeKind::Unreachable, text, statement));
+                }
+                for definition in flow.unread_variables() {
+                    items.push(item(DeadCodeKind::UnreadVariable, definition.variable.clone(), &definition.node_id));
+                }
+                Walk::Continue
+            });
+        }
+        let order: HashMap<&str, usize> = modules.iter().enumerate().map(|(index, (module, _))| (*module, index)).collect();
+        items.sort_by_key(|item| (item.kind, order.get(item.module.as_str()).copied(), item.line));
+        DeadCode { items }
+    }
+    
+    pub fn items(&self) -> &[DeadCodeItem] {
+        &self.items
+    }
+    
+    pub fn of_kind(&self, kind: DeadCodeKind) -> impl Iterator<Item = &DeadCodeItem> {
+        self.items.iter().filter(move |item| item.kind == kind)
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    
+    /// Mark each dead node in the module named `name` with a `dead_code` annotation naming its kind
+    pub fn annotate(&self, name: &str, module: &mut UIRNode) {
+        let kinds: HashMap<&str, DeadCodeKind> = self.items.iter()
+            .filter(|item| item.module == name)
+            .map(|item| (item.node_id.as_str(), item.kind))
+            .collect();
+        walk_mut(module, &mut |node: &mut UIRNode| {
+            if let Some(kind) = kinds.get(node.id.as_str()) {
+                node.metadata.annotations.insert("dead_code".to_string(), Value::String(kind.as_str().to_string()));
+            }
+            Walk::Continue
+        });
+    }
+}
+
+/// Functions, the roots of liveness and the names each callable mentions, from one walk
+struct Scan<'g, 'a> {
+    callables: &'g [Callable],
+    declared: &'g HashMap<(&'g str, &'g str), usize>,
+    ids: &'g HashMap<&'g str, usize>,
+    module: &'a str,
+    functions: HashMap<usize, (&'a str, &'a UIRNode)>,
+    /// The callable each function is declared in
+    enclosing: HashMap<usize, usize>,
+    roots: HashSet<usize>,
+    names: Vec<(usize, String)>,
+}
+
+impl<'a> Scan<'_, 'a> {
+    fn visit(&mut self, node: &'a UIRNode, owner: Option<usize>, parent: Option<&UIRNode>, exported: bool) {
+        let mut owner = owner;
+        // A C declarator only names the function around it
+        let callable = match has_tag(node, &["function_declarator"]) {
+            true => None,
+            false => self.callable(node, owner),
+        };
+        if let Some(callable) = callable {
+            match self.callables[callable].kind {
+                CallableKind::Function => {
+                    self.functions.insert(callable, (self.module, node));
+                    if let Some(owner) = owner {
+                        self.enclosing.insert(callable, owner);
+                    }
+                    let entry = node.name.as_deref().is_some_and(|name| name.eq_ignore_ascii_case("main"));
+                    if entry || exported || node.visibility == Some(Visibility::Public) || !node.attributes.is_empty() {
+                        self.roots.insert(callable);
+                    }
+                }
+                _ => {
+                    self.roots.insert(callable);
+                }
+            }
+            owner = Some(callable);
+        }
+        
+        // A function's own name is not a use of it
+        let names_parent = parent.is_some_and(|parent| parent.node_type == NodeType::Function && parent.name == node.name);
+        if let (Some(owner), Some(name), false) = (owner, &node.name, names_parent) {
+            if node.node_type == NodeType::Expression(ExpressionType::Variable) {
+                self.names.push((owner, name.clone()));
+            }
+        }
+        let exports = has_tag(node, &["export_statement"]);
+        for child in &node.children {
+            self.visit(child, owner, Some(node), exports);
+        }
+    }
+    
+    /// The callable `node` declares; a function declared by a prototype first is the callable
+    /// of that name
+    fn callable(&self, node: &UIRNode, owner: Option<usize>) -> Option<usize> {
+        if let Some(&callable) = self.declared.get(&(self.module, node.id.as_str())) {
+            return Some(callable);
+        }
+        let name = node.name.as_ref().filter(|_| matches!(node.node_type, NodeType::Function | NodeType::Class))?;
+        let qualified = match owner.map(|owner| &self.callables[owner]) {
+            Some(outer) if outer.kind != CallableKind::Module => format!("{}::{}.{}", self.module, outer.name, name),
+            _ => format!("{}::{}", self.module, name),
+        };
+        self.ids.get(qualified.as_str()).copied()
+    }
+}
+
+fn index<'a>(node: &'a UIRNode, nodes: &mut HashMap<&'a str, &'a UIRNode>) {
+    nodes.insert(&node.id, node);
+    for child in &node.children {
+        index(child, nodes);
+    }
+}
+
+fn line(node: &UIRNode) -> Option<u32> {
+    node.source_location.as_ref().map(|location| location.start_line)
+}
+
+fn first_line(node: &UIRNode) -> String {
+    crate::cfg::original_text(node).lines().next().unwrap_or_default().trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolve::resolve;
+    use coalesce_core::Parser;
+    use coalesce_parser::{CParser, JavaScriptParser, PythonParser};
+    
+    fn names(dead: &DeadCode, kind: DeadCodeKind) -> Vec<&str> {
+        dead.of_kind(kind).map(|item| item.name.as_str()).collect()
+    }
+    
+    #[test]
+    fn test_python_unused_functions_branches_and_variables() {
+        let source = "import os\n\ndef helper():\n    return 1\n\ndef legacy():\n    def inner():\n        pass\n    return inner\n\ndef callback(x):\n    return x\n\ndef run(items):\n    total = helper()\n    scratch = len(items)\n    if False:\n        print(\"never\")\n    return list(map(callback, items)) + [total]\n    print(\"done\")\n\n@app.route(\"/\")\ndef index():\n    pass\n\nrun([1])\n";
+        let mut module = PythonParser::new().unwrap().parse(source).unwrap();
+        resolve(&mut module);
+        let dead = DeadCode::find([("app", &module)]);
+        
+        assert_eq!(names(&dead, DeadCodeKind::UnusedFunction), ["legacy"]);
+        assert_eq!(names(&dead, DeadCodeKind::Unreachable), ["print(\"never\")", "print(\"done\")"]);
+        assert_eq!(names(&dead, DeadCodeKind::UnreadVariable), ["scratch"]);
+        assert_eq!(dead.items()[0].to_string(), "app:6: function `legacy` is never used");
+        
+        dead.annotate("app", &mut module);
+        let legacy = module.children.iter().find(|child| child.name.as_deref() == Some("legacy")).unwrap();
+        assert_eq!(legacy.metadata.annotations.get("dead_code"), Some(&Value::String("unused_function".to_string())));
+    }
+    
+    #[test]
+    fn test_c_constant_conditions_and_prototypes() {
+        let source = "static int unused(void);\n\nstatic int twice(int x) { return x * 2; }\n\nstatic int unused(void) { return 0; }\n\nint main(void) {\n    int spare = 3;\n    while (1) {\n        if (twice(1)) break;\n    }\n    if (0) {\n        spare = 4;\n    }\n    return 0;\n}\n";
+        let mut module = CParser::new().unwrap().parse(source).unwrap();
+        resolve(&mut module);
+        let dead = DeadCode::find([("main", &module)]);
+        
+        assert_eq!(names(&dead, DeadCodeKind::UnusedFunction), ["unused"]);
+        assert_eq!(names(&dead, DeadCodeKind::Unreachable), ["spare = 4;"]);
+        assert_eq!(names(&dead, DeadCodeKind::UnreadVariable), ["spare"]);
+    }
+    
+    #[test]
+    fn test_calls_and_exports_across_modules() {
+        let util = JavaScriptParser::new().unwrap().parse("function format(x) { return `${x}`; }\nfunction stale() { return format(1); }\nexport function shout(x) { return format(x).toUpperCase(); }\n").unwrap();
+        let app = JavaScriptParser::new().unwrap().parse("function show(x) { console.log(format(x)); }\nshow(1);\n").unwrap();
+        let dead = DeadCode::find([("util", &util), ("app", &app)]);
+        
+        assert_eq!(names(&dead, DeadCodeKind::UnusedFunction), ["stale"]);
+        assert_eq!(dead.items()[0].module, "util");
+    }
+}
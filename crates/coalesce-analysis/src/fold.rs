@@ -0,0 +1,532 @@
+// Constant folding
+//
+// `fold_constants` is an optional pass that simplifies the UIR before generation. Operations
+// whose operands are all literals become the literal they compute, following the source
+// language: `7 / 2` is `3` in C but `3.5` in Python, and a C comparison yields `1` or `0`.
+// Object-like C macros that expand to a number count as that number, so `DEBUG && x` folds
+// when `DEBUG` is `0`. Conditions that are then constant are settled: an `if` gives way to
+// the branch it always takes, a `while` that never runs and an `if` that never does without an
+// `else` disappear, the `do { ... } while (0)` of macro bodies becomes its body, and a ternary
+// becomes the value it picks. A condition keeps what still decides it, `1 && ready` becoming
+// `ready`. Results that would overflow, divisions by zero and operands with side effects are
+// left alone. The pass runs when the `optimize` section of `.coalesce/config.json`, or the
+// command line, enables it; each kind of simplification can be turned off on its own.
+use crate::cfg::{BLOCK_TAGS, Flow, has_tag, kind, original_text, significant};
+use coalesce_core::{ExpressionType, Language, LiteralValue, NodeType, Operator, Result, UIRNode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+const ELSE_TAGS: &[&str] = &["else_clause", "elif_clause", "else"];
+const TERNARY_TAGS: &[&str] = &["conditional_expression", "ternary_expression"];
+/// Declarations that would leave their scope if a block were spliced into the one around it
+const DECLARATION_TAGS: &[&str] = &["declaration", "lexical_declaration", "variable_declaration", "short_var_declaration", "let_declaration", "local_declaration_statement", "local_variable_declaration"];
+
+/// The `optimize` section of the project config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FoldConfig {
+    /// Whether the pass runs at all
+    pub enabled: bool,
+    /// Fold operations on literals
+    pub expressions: bool,
+    /// Settle conditionals and loops whose condition is constant
+    pub conditions: bool,
+    /// Read C macros that expand to a number as that number
+    pub macros: bool,
+}
+
+impl Default for FoldConfig {
+    fn default() -> Self {
+        Self { enabled: false, expressions: true, conditions: true, macros: true }
+    }
+}
+
+impl FoldConfig {
+    /// Read the `optimize` section of a project config file, the defaults when it has none
+    pub fn from_config_file(config_path: impl AsRef<Path>) -> Result<Self> {
+        let config: Value = serde_json::from_str(&std::fs::read_to_string(config_path)?)?;
+        match config.get("optimize") {
+            Some(section) => Ok(serde_json::from_value(section.clone())?),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// Fold constant expressions and settle constant conditions in `module` when `config`
+/// enables it, returning how many were simplified. Folded nodes keep their id and note the
+/// source they replace in a `folded_from` annotation
+pub fn fold_constants(module: &mut UIRNode, config: &FoldConfig) -> usize {
+    if !config.enabled {
+        return 0;
+    }
+    let mut folder = Folder { config, language: module.metadata.source_language.clone(), simplified: 0 };
+    folder.visit(module);
+    folder.simplified
+}
+
+struct Folder<'a> {
+    config: &'a FoldConfig,
+    language: Language,
+    simplified: usize,
+}
+
+/// What a constant conditional leaves in place of itself
+enum Outcome {
+    Keep(Box<UIRNode>),
+    Splice(Vec<UIRNode>),
+    Remove,
+}
+
+impl Folder<'_> {
+    fn visit(&mut self, node: &mut UIRNode) {
+        for child in &mut node.children {
+            self.visit(child);
+        }
+        if self.config.expressions {
+            self.fold(node);
+        }
+        if self.config.conditions {
+            self.settle_ternary(node);
+            self.settle_statements(node);
+        }
+    }
+    
+    /// The value of a literal, or of a macro that expands to one
+    fn value(&self, node: &UIRNode) -> Option<LiteralValue> {
+        match node.node_type {
+            NodeType::Expression(ExpressionType::Literal) => node.literal.clone(),
+            NodeType::Expression(ExpressionType::Variable) if self.config.macros && has_tag(node, &["macro_reference"]) => {
+                node.metadata.annotations.get("macro_expansion").and_then(Value::as_str).and_then(parse_number)
+            }
+            _ => None,
+        }
+    }
+    
+    /// Replace an operation on constants by its result
+    fn fold(&mut self, node: &mut UIRNode) {
+        let (NodeType::Expression(ExpressionType::Arithmetic | ExpressionType::Comparison | ExpressionType::Logical), Some(operator)) = (&node.node_type, node.operator) else {
+            return;
+        };
+        let operands: Option<Vec<LiteralValue>> = significant(node).into_iter().map(|operand| self.value(operand)).collect();
+        let result = match operands.as_deref() {
+            Some([operand]) => self.unary(operator, operand),
+            Some([left, right]) => self.binary(operator, left, right),
+            _ => None,
+        };
+        if let Some(result) = result {
+            self.become_literal(node, result);
+        }
+    }
+    
+    fn unary(&self, operator: Operator, operand: &LiteralValue) -> Option<LiteralValue> {
+        match (operator, operand) {
+            (Operator::Negate, LiteralValue::Int(value)) => value.checked_neg().map(LiteralValue::Int),
+            (Operator::Negate, LiteralValue::Float(value)) => Some(LiteralValue::Float(-value)),
+            (Operator::Plus, LiteralValue::Int(_) | LiteralValue::Float(_)) => Some(operand.clone()),
+            (Operator::BitNot, LiteralValue::Int(value)) => Some(LiteralValue::Int(!value)),
+            (Operator::Not, _) => truth(operand).map(|value| self.boolean(!value)),
+            _ => None,
+        }
+    }
+    
+    fn binary(&self, operator: Operator, left: &LiteralValue, right: &LiteralValue) -> Option<LiteralValue> {
+        use LiteralValue::{Float, Int};
+        let result = match (operator, left, right) {
+            (Operator::And, _, _) => {
+                let decided = if truth(left)? { right } else { left };
+                if self.c_like() { self.boolean(truth(decided)?) } else { decided.clone() }
+            }
+            (Operator::Or, _, _) => {
+                let decided = if truth(left)? { left } else { right };
+                if self.c_like() { self.boolean(truth(decided)?) } else { decided.clone() }
+            }
+            (Operator::Equal | Operator::StrictEqual, _, _) => self.boolean(equal(left, right)?),
+            (Operator::NotEqual | Operator::StrictNotEqual, _, _) => self.boolean(!equal(left, right)?),
+            (Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual, _, _) => {
+                let ordering = number(left)?.partial_cmp(&number(right)?)?;
+                self.boolean(match operator {
+                    Operator::Less => ordering.is_lt(),
+                    Operator::LessEqual => ordering.is_le(),
+                    Operator::Greater => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                })
+            }
+            (Operator::Concat, LiteralValue::String(left), LiteralValue::String(right)) => LiteralValue::String(format!("{}{}", left, right)),
+            (Operator::Add, LiteralValue::String(left), LiteralValue::String(right)) if !matches!(self.language, Language::C | Language::Cpp | Language::Rust) => {
+                LiteralValue::String(format!("{}{}", left, right))
+            }
+            (_, Int(left), Int(right)) => self.integer(operator, *left, *right)?,
+            (_, Int(_) | Float(_), Int(_) | Float(_)) => {
+                let (left, right) = (number(left)?, number(right)?);
+                let value = match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide if right != 0.0 => left / right,
+                    _ => return None,
+                };
+                Float(value)
+            }
+            _ => return None,
+        };
+        match result {
+            // C's `int` and the like are narrower than the folder's
+            LiteralValue::Int(value) if self.c_like() || matches!(self.language, Language::CSharp | Language::Java) => i32::try_from(value).ok().map(|_| result),
+            LiteralValue::Float(value) if !value.is_finite() => None,
+            _ => Some(result),
+        }
+    }
+    
+    fn integer(&self, operator: Operator, left: i64, right: i64) -> Option<LiteralValue> {
+        let floored = matches!(self.language, Language::Python | Language::R);
+        let value = match operator {
+            Operator::Add => left.checked_add(right)?,
+            Operator::Subtract => left.checked_sub(right)?,
+            Operator::Multiply => left.checked_mul(right)?,
+            Operator::Divide if self.divides_to_float() => return (right != 0).then(|| LiteralValue::Float(left as f64 / right as f64)),
+            Operator::Divide => left.checked_div(right)?,
+            Operator::IntegerDivide if floored => {
+                let quotient = left.checked_div(right)?;
+                if left % right != 0 && (left < 0) != (right < 0) { quotient - 1 } else { quotient }
+            }
+            Operator::IntegerDivide => left.checked_div(right)?,
+            Operator::Modulo if floored => left.checked_rem(right)?.checked_add(right)?.checked_rem(right)?,
+            Operator::Modulo => left.checked_rem(right)?,
+            Operator::Power => left.checked_pow(u32::try_from(right).ok()?)?,
+            Operator::BitAnd => left & right,
+            Operator::BitOr => left | right,
+            Operator::BitXor => left ^ right,
+            Operator::ShiftLeft => left.checked_shl(u32::try_from(right).ok().filter(|&shift| shift < 63)?)?,
+            Operator::ShiftRight => left.checked_shr(u32::try_from(right).ok().filter(|&shift| shift < 63)?)?,
+            _ => return None,
+        };
+        Some(LiteralValue::Int(value))
+    }
+    
+    /// Whether `/` between integers gives a float
+    fn divides_to_float(&self) -> bool {
+        matches!(self.language, Language::Python | Language::JavaScript | Language::TypeScript | Language::R | Language::VisualBasic | Language::Erlang | Language::Elixir)
+    }
+    
+    /// Whether comparisons and logical operators yield an integer
+    fn c_like(&self) -> bool {
+        matches!(self.language, Language::C | Language::Cpp)
+    }
+    
+    fn boolean(&self, value: bool) -> LiteralValue {
+        if self.c_like() { LiteralValue::Int(i64::from(value)) } else { LiteralValue::Bool(value) }
+    }
+    
+    fn become_literal(&mut self, node: &mut UIRNode, value: LiteralValue) {
+        let folded_from = original_text(node).to_string();
+        node.metadata.annotations.insert("folded_from".to_string(), Value::String(folded_from));
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(self.spell(&value)));
+        node.metadata.semantic_tags = vec!["folded_constant".to_string()];
+        node.node_type = NodeType::Expression(ExpressionType::Literal);
+        node.operator = None;
+        node.children.clear();
+        node.literal = Some(value);
+        self.simplified += 1;
+    }
+    
+    /// A literal as the source language writes it
+    fn spell(&self, value: &LiteralValue) -> String {
+        match value {
+            LiteralValue::Int(value) => value.to_string(),
+            LiteralValue::Float(value) => format!("{:?}", value),
+            LiteralValue::Bool(value) if matches!(self.language, Language::Python | Language::VisualBasic) => if *value { "True" } else { "False" }.to_string(),
+            LiteralValue::Bool(value) => value.to_string(),
+            LiteralValue::String(text) => Value::String(text.clone()).to_string(),
+            LiteralValue::Char(c) => format!("'{}'", c),
+            LiteralValue::Null => "null".to_string(),
+        }
+    }
+    
+    /// Drop what no longer decides a condition: `1 && ready` is `ready`, `0 && ready` is `0`
+    fn reduce_condition(&mut self, condition: &mut UIRNode) {
+        if !matches!(condition.operator, Some(Operator::And | Operator::Or)) {
+            return;
+        }
+        for operand in &mut condition.children {
+            self.reduce_condition(operand);
+        }
+        let operands = significant(condition);
+        let [left, right] = operands.as_slice() else {
+            return;
+        };
+        let and = condition.operator == Some(Operator::And);
+        let (left_truth, right_truth) = (self.value(left).as_ref().and_then(truth), self.value(right).as_ref().and_then(truth));
+        // A constant on the left decides or drops out; one on the right only drops out, as
+        // what it would leave behind might have side effects
+        let kept = match (left_truth, right_truth) {
+            (Some(value), _) if value == and => Some((*right).clone()),
+            (Some(_), _) => Some((*left).clone()),
+            (_, Some(value)) if value == and => Some((*left).clone()),
+            _ => None,
+        };
+        if let Some(kept) = kept {
+            let folded_from = original_text(condition).to_string();
+            *condition = kept;
+            condition.metadata.annotations.insert("folded_from".to_string(), Value::String(folded_from));
+            self.simplified += 1;
+        }
+    }
+    
+    /// Replace a ternary whose condition is constant by the value it picks
+    fn settle_ternary(&mut self, node: &mut UIRNode) {
+        if !has_tag(node, TERNARY_TAGS) {
+            return;
+        }
+        // Python writes the value first: `a if condition else b`
+        let python = self.language == Language::Python;
+        let operands: Vec<usize> = (0..node.children.len()).filter(|&index| !matches!(kind(&node.children[index]), Flow::Noise)).collect();
+        let [first, second, third] = operands[..] else {
+            return;
+        };
+        let (condition, then, otherwise) = if python { (second, first, third) } else { (first, second, third) };
+        self.reduce_condition(&mut node.children[condition]);
+        if let Some(value) = self.value(&node.children[condition]).as_ref().and_then(truth) {
+            let chosen = node.children[if value { then } else { otherwise }].clone();
+            let folded_from = original_text(node).to_string();
+            *node = chosen;
+            node.metadata.annotations.insert("folded_from".to_string(), Value::String(folded_from));
+            self.simplified += 1;
+        }
+    }
+    
+    /// Settle the constant conditionals and loops among a node's children
+    fn settle_statements(&mut self, parent: &mut UIRNode) {
+        let holds_statements = matches!(parent.node_type, NodeType::Module | NodeType::Function)
+            || (has_tag(parent, BLOCK_TAGS) && !has_tag(parent, &["else_clause"]))
+            || has_tag(parent, &["block", "module"]);
+        let mut index = 0;
+        while index < parent.children.len() {
+            let outcome = self.settle(&mut parent.children[index], holds_statements);
+            match outcome {
+                None => index += 1,
+                Some(Outcome::Keep(kept)) => {
+                    parent.children[index] = *kept;
+                    index += 1;
+                }
+                Some(Outcome::Splice(statements)) => {
+                    let count = statements.len();
+                    parent.children.splice(index..=index, statements);
+                    index += count;
+                }
+                Some(Outcome::Remove) => {
+                    parent.children.remove(index);
+                }
+            }
+        }
+    }
+    
+    /// What a statement comes to when its condition is constant; `in_block` when it can be
+    /// removed or spliced into the statements around it
+    fn settle(&mut self, statement: &mut UIRNode, in_block: bool) -> Option<Outcome> {
+        if has_tag(statement, TERNARY_TAGS) {
+            return None;
+        }
+        let flow = kind(statement);
+        if !matches!(flow, Flow::Conditional | Flow::Loop(_)) {
+            return None;
+        }
+        let parts: Vec<usize> = (0..statement.children.len()).filter(|&index| !matches!(kind(&statement.children[index]), Flow::Noise)).collect();
+        let outcome = match flow {
+            Flow::Conditional => {
+                let (&condition, rest) = parts.split_first()?;
+                if rest.is_empty() {
+                    return None;
+                }
+                self.reduce_condition(&mut statement.children[condition]);
+                let taken = truth(&self.value(&statement.children[condition])?)?;
+                let branches: Vec<&UIRNode> = rest.iter().map(|&index| &statement.children[index]).collect();
+                // JavaScript lists the statements of a branch; other grammars wrap it in a block or
+                // a single statement, and may follow it by a bare alternative
+                let (then, alternative) = match branches.iter().position(|part| has_tag(part, ELSE_TAGS)) {
+                    Some(index) => (&branches[..index], Some(branches[index])),
+                    None if statement.metadata.source_language == Language::JavaScript => (&branches[..], None),
+                    None => (&branches[..branches.len().min(1)], branches.get(1).copied()),
+                };
+                if then.is_empty() {
+                    return None;
+                }
+                match (taken, alternative) {
+                    (true, _) => self.replace_with_all(then, in_block)?,
+                    (false, None) if in_block => Outcome::Remove,
+                    (false, None) => return None,
+                    // Python's `elif` would have to become the `if`
+                    (false, Some(alternative)) if has_tag(alternative, &["elif_clause"]) => return None,
+                    (false, Some(alternative)) if has_tag(alternative, &["else_clause"]) => {
+                        let body = significant(alternative);
+                        // A JavaScript `else` lists its statements; other grammars wrap them in a block
+                        match body.as_slice() {
+                            [.., last] if statement.metadata.source_language != Language::JavaScript => self.replace_with(last, in_block),
+                            _ => self.replace_with_all(&body, in_block)?,
+                        }
+                    }
+                    (false, Some(alternative)) => self.replace_with(alternative, in_block),
+                }
+            }
+            Flow::Loop(_) if has_tag(statement, &["while_statement"]) && in_block
+                && !parts.is_empty() && !parts.iter().any(|&index| has_tag(&statement.children[index], ELSE_TAGS)) =>
+            {
+                self.reduce_condition(&mut statement.children[parts[0]]);
+                match truth(&self.value(&statement.children[parts[0]])?)? {
+                    false => Outcome::Remove,
+                    true => return None,
+                }
+            }
+            // The `do { ... } while (0)` that makes a macro body one statement
+            Flow::Loop(_) if has_tag(statement, &["do_statement"]) && in_block => {
+                let (&condition, body) = parts.split_last()?;
+                let body: Vec<&UIRNode> = body.iter().map(|&index| &statement.children[index]).collect();
+                if truth(&self.value(&statement.children[condition])?)? || body.iter().any(|part| breaks_out(part) || matches!(kind(part), Flow::Break | Flow::Continue)) {
+                    return None;
+                }
+                self.replace_with_all(&body, in_block)?
+            }
+            _ => return None,
+        };
+        self.simplified += 1;
+        Some(outcome)
+    }
+    
+    /// The statements of a branch standing in for the statement that held them: a lone
+    /// statement or block as `replace_with` would have it, several when they can be spliced
+    /// into the block around them without leaving their scope
+    fn replace_with_all(&self, statements: &[&UIRNode], in_block: bool) -> Option<Outcome> {
+        match statements {
+            [] if in_block => Some(Outcome::Remove),
+            [statement] => Some(self.replace_with(statement, in_block)),
+            _ => {
+                let scoped = self.language != Language::Python && statements.iter().any(|statement| has_tag(statement, DECLARATION_TAGS));
+                (in_block && !scoped).then(|| Outcome::Splice(statements.iter().map(|&statement| statement.clone()).collect()))
+            }
+        }
+    }
+    
+    /// A branch standing in for the statement that held it: its statements when they can join
+    /// the block around it without leaving their scope, else the branch whole
+    fn replace_with(&self, branch: &UIRNode, in_block: bool) -> Outcome {
+        let is_block = has_tag(branch, BLOCK_TAGS) || has_tag(branch, &["block"]);
+        let scoped = self.language != Language::Python && branch.children.iter().any(|child| has_tag(child, DECLARATION_TAGS));
+        if in_block && is_block && !scoped {
+            Outcome::Splice(significant(branch).into_iter().cloned().collect())
+        } else {
+            Outcome::Keep(Box::new(branch.clone()))
+        }
+    }
+}
+
+/// Whether a loop body has a `break` or `continue` of its own, outside nested loops and switches
+fn breaks_out(body: &UIRNode) -> bool {
+    body.children.iter().any(|child| match kind(child) {
+        Flow::Break | Flow::Continue => true,
+        Flow::Loop(_) | Flow::Switch => false,
+        _ if matches!(child.node_type, NodeType::Function | NodeType::Closure { .. } | NodeType::Class) => false,
+        _ => breaks_out(child),
+    })
+}
+
+/// The truth of a constant as a condition
+fn truth(value: &LiteralValue) -> Option<bool> {
+    match value {
+        LiteralValue::Bool(value) => Some(*value),
+        LiteralValue::Int(value) => Some(*value != 0),
+        LiteralValue::Float(value) => Some(*value != 0.0),
+        LiteralValue::String(text) => Some(!text.is_empty()),
+        LiteralValue::Null => Some(false),
+        LiteralValue::Char(_) => None,
+    }
+}
+
+fn number(value: &LiteralValue) -> Option<f64> {
+    match value {
+        LiteralValue::Int(value) => Some(*value as f64),
+        LiteralValue::Float(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Equality of constants of the same kind, numbers comparing by value
+fn equal(left: &LiteralValue, right: &LiteralValue) -> Option<bool> {
+    match (left, right) {
+        (LiteralValue::Int(left), LiteralValue::Int(right)) => Some(left == right),
+        (LiteralValue::Int(_) | LiteralValue::Float(_), LiteralValue::Int(_) | LiteralValue::Float(_)) => Some(number(left)? == number(right)?),
+        (LiteralValue::String(left), LiteralValue::String(right)) => Some(left == right),
+        (LiteralValue::Bool(left), LiteralValue::Bool(right)) => Some(left == right),
+        _ => None,
+    }
+}
+
+/// A macro body that is a number: `0`, `(1)`, `0x10`, `100UL`
+fn parse_number(expansion: &str) -> Option<LiteralValue> {
+    let mut text = expansion.trim();
+    while let Some(inner) = text.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+        text = inner.trim();
+    }
+    let digits = text.trim_end_matches(['u', 'U', 'l', 'L']);
+    let value = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+    Some(LiteralValue::Int(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::{CParser, JavaScriptParser, PythonParser};
+    
+    const ENABLED: FoldConfig = FoldConfig { enabled: true, expressions: true, conditions: true, macros: true };
+    
+    fn texts(nodes: &[UIRNode]) -> Vec<&str> {
+        nodes.iter().map(original_text).filter(|text| !matches!(*text, "{" | "}")).collect()
+    }
+    
+    #[test]
+    fn test_c_macros_arithmetic_and_dead_branches() {
+        let source = "#define DEBUG 0\n#define SWAP(a, b) do { int t = a; a = b; b = t; } while (0)\nint f(int y, int ready) {\n    int x = (2 * 3) + 7 / 2;\n    if (DEBUG && y) { y = 1; } else { y = 2; }\n    if (1 && ready) { x = 0; }\n    do { x++; } while (0);\n    while (DEBUG) { y--; }\n    return 1 < 2 ? x : y;\n}\n";
+        let mut module = CParser::new().unwrap().parse(source).unwrap();
+        assert_eq!(fold_constants(&mut module, &FoldConfig::default()), 0);
+        
+        let simplified = fold_constants(&mut module, &ENABLED);
+        let function = module.children.iter().find(|child| child.name.as_deref() == Some("f")).unwrap();
+        let body = function.children.iter().find(|child| has_tag(child, &["compound_statement"])).unwrap();
+        assert_eq!(texts(&body.children), ["int x = (2 * 3) + 7 / 2;", "y = 2;", "if (1 && ready) { x = 0; }", "x++;", "return 1 < 2 ? x : y;"]);
+        assert_eq!(simplified, 10);
+        
+        let declaration = &body.children[1].children[1];
+        assert_eq!(declaration.children[2].literal, Some(LiteralValue::Int(9)));
+        assert_eq!(original_text(&body.children[3].children[1]), "ready");
+        assert_eq!(body.children[5].children[1].name.as_deref(), Some("x"));
+    }
+    
+    #[test]
+    fn test_python_division_logic_and_constant_ifs() {
+        let source = "x = 7 / 2 + 2 ** 3\nlabel = 'a' + 'b'\ny = 1 if not True else 2\nz = 0 or 5\nif True:\n    print(x)\nelse:\n    print(y)\nif False:\n    pass\nwhile 0:\n    pass\n";
+        let mut module = PythonParser::new().unwrap().parse(source).unwrap();
+        fold_constants(&mut module, &ENABLED);
+        
+        let value = |index: usize| module.children[index].children[0].children.last().unwrap().literal.clone();
+        assert_eq!(value(0), Some(LiteralValue::Float(11.5)));
+        assert_eq!(value(1), Some(LiteralValue::String("ab".to_string())));
+        assert_eq!(value(2), Some(LiteralValue::Int(2)));
+        assert_eq!(value(3), Some(LiteralValue::Int(5)));
+        assert_eq!(module.children.len(), 5);
+        assert_eq!(original_text(&module.children[4]), "print(x)");
+        assert_eq!(module.children[0].children[0].children[1].metadata.annotations["folded_from"], "7 / 2 + 2 ** 3");
+    }
+    
+    #[test]
+    fn test_javascript_branches_listing_several_statements() {
+        let source = "function f(x) { if (false) { a(); } else { b(); c(); } do { d(); e(); } while (0); while (false) { g(); } if (true) { h(); i(); } return x; }";
+        let mut module = JavaScriptParser::new().unwrap().parse(source).unwrap();
+        fold_constants(&mut module, &ENABLED);
+        
+        let function = &module.children[0];
+        assert_eq!(texts(&function.children[1..]), ["b();", "c();", "d();", "e();", "h();", "i();", "return x;"]);
+    }
+}
@@ -0,0 +1,482 @@
+// Global and shared state
+//
+// `GlobalState` takes inventory of the state that outlives a single call, the hardest thing to
+// carry into Rust's ownership or Go's goroutines: variables declared at module level, C
+// `static` locals, static fields and Python class attributes, and singletons, classes keeping
+// an instance of themselves in a static field. Constants are left out: C `const`, non-`mut`
+// Rust statics, `const` and `readonly` fields, and JavaScript `const` or Python upper-case
+// names bound to a literal. Each item lists the functions of its module that use it and those
+// that assign it. A function uses a global when it names it without declaring a local of the
+// same name, and a static field when it names it through the class, `this`, `self` or `cls`,
+// or bare from a method of the class in languages that allow that.
+use crate::cfg::{has_tag, original_text, significant};
+use crate::dataflow::{ASSIGNMENT_TAGS, DataFlow, DefinitionKind, MEMBER_TAGS, UPDATE_TAGS, shared_names};
+use coalesce_core::{ExpressionType, Language, Modifier, NodeType, UIRNode, Walk, walk_mut};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateKind {
+    /// A variable declared at module level
+    Global,
+    /// A function's C `static` local, kept between calls
+    StaticLocal,
+    /// A static field or class attribute, shared by every instance
+    StaticField,
+    /// A class keeping its one instance in a static field
+    Singleton,
+}
+
+impl StateKind {
+    /// The value of the `shared_state` annotation
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StateKind::Global => "global",
+            StateKind::StaticLocal => "static_local",
+            StateKind::StaticField => "static_field",
+            StateKind::Singleton => "singleton",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedState {
+    pub kind: StateKind,
+    /// The variable, `Class.field`, or the singleton class
+    pub name: String,
+    pub module: String,
+    /// The function declaring a static local
+    pub function: Option<String>,
+    /// The declaring node
+    pub node_id: String,
+    pub line: Option<u32>,
+    /// Functions that read or assign it, or for a singleton that refer to its class
+    pub used_by: Vec<String>,
+    pub assigned_by: Vec<String>,
+}
+
+impl fmt::Display for SharedState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: ", self.module, line)?,
+            None => write!(f, "{}: ", self.module)?,
+        }
+        write!(f, "{} `{}`", self.kind.as_str().replace('_', " "), self.name)?;
+        if let Some(function) = &self.function {
+            write!(f, " in {}", function)?;
+        }
+        if !self.assigned_by.is_empty() {
+            write!(f, ", assigned by {}", self.assigned_by.join(", "))?;
+        }
+        if !self.used_by.is_empty() {
+            write!(f, ", used by {}", self.used_by.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GlobalState {
+    items: Vec<SharedState>,
+}
+
+impl GlobalState {
+    /// The shared state of modules named by their file stem
+    pub fn find<'a>(modules: impl IntoIterator<Item = (&'a str, &'a UIRNode)>) -> Self {
+        let mut items = Vec::new();
+        for (name, root) in modules {
+            let mut scan = Scan { language: root.metadata.source_language.clone(), found: Vec::new(), functions: Vec::new() };
+            scan.visit(root, None, None, None);
+            let accesses: Vec<Accesses> = scan.functions.iter().map(|(function, _, _)| Accesses::of(function)).collect();
+            let python = scan.language == Language::Python;
+            
+            for found in scan.found {
+                let mut used_by = BTreeSet::new();
+                let mut assigned_by = BTreeSet::new();
+                for ((function, qualified, class), accesses) in scan.functions.iter().zip(&accesses) {
+                    let (uses, assigns) = match found.kind {
+                        StateKind::Global => {
+                            let global = !accesses.locals.contains(&found.variable);
+                            (global && accesses.names(&found.variable, None), global && accesses.assigns(&found.variable, None))
+                        }
+                        StateKind::StaticLocal => {
+                            let declaring = found.function.as_deref() == Some(function.id.as_str());
+                            (declaring, declaring && accesses.assigns(&found.variable, None))
+                        }
+                        StateKind::StaticField => {
+                            let owner = found.class.as_deref();
+                            let receivers = ["this", "self", "cls", "Self", "Me", "static"].into_iter().chain(owner);
+                            let receivers: Vec<&str> = receivers.collect();
+                            let bare = !python && class.as_deref() == owner && !accesses.locals.contains(&found.variable);
+                            let uses = accesses.names(&found.variable, Some(&receivers)) || (bare && accesses.names(&found.variable, None));
+                            let assigns = accesses.assigns(&found.variable, Some(&receivers)) || (bare && accesses.assigns(&found.variable, None));
+                            (uses, assigns)
+                        }
+                        StateKind::Singleton => {
+                            let outside = class.as_deref() != Some(found.variable.as_str());
+                            (outside && (accesses.names(&found.variable, None) || accesses.receivers.contains(&found.variable)), false)
+                        }
+                    };
+                    if uses || assigns {
+                        used_by.insert(qualified.clone());
+                    }
+                    if assigns {
+                        assigned_by.insert(qualified.clone());
+                    }
+                }
+                let function = found.function.as_deref()
+                    .and_then(|id| scan.functions.iter().find(|(function, _, _)| function.id == id))
+                    .map(|(_, qualified, _)| qualified.clone());
+                items.push(SharedState {
+                    kind: found.kind,
+                    name: found.name,
+                    module: name.to_string(),
+                    function,
+                    node_id: found.node.id.clone(),
+                    line: found.node.source_location.as_ref().map(|location| location.start_line),
+                    used_by: used_by.into_iter().collect(),
+                    assigned_by: assigned_by.into_iter().collect(),
+                });
+            }
+        }
+        GlobalState { items }
+    }
+    
+    pub fn items(&self) -> &[SharedState] {
+        &self.items
+    }
+    
+    pub fn of_kind(&self, kind: StateKind) -> impl Iterator<Item = &SharedState> {
+        self.items.iter().filter(move |item| item.kind == kind)
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    
+    /// Mark the declarations of the module named `name` with a `shared_state` annotation naming
+    /// their kind, and the functions assigning them in `assigned_by`
+    pub fn annotate(&self, name: &str, module: &mut UIRNode) {
+        let items: HashMap<&str, &SharedState> = self.items.iter()
+            .filter(|item| item.module == name)
+            .map(|item| (item.node_id.as_str(), item))
+            .collect();
+        walk_mut(module, &mut |node: &mut UIRNode| {
+            if let Some(item) = items.get(node.id.as_str()) {
+                node.metadata.annotations.insert("shared_state".to_string(), Value::String(item.kind.as_str().to_string()));
+                node.metadata.annotations.insert("assigned_by".to_string(), json!(item.assigned_by));
+            }
+            Walk::Continue
+        });
+    }
+}
+
+/// A declaration of shared state
+struct Found<'a> {
+    kind: StateKind,
+    name: String,
+    /// The name functions use for it: the variable, field or class
+    variable: String,
+    node: &'a UIRNode,
+    /// Ids of the function declaring a static local
+    function: Option<String>,
+    class: Option<String>,
+}
+
+struct Scan<'a> {
+    language: Language,
+    found: Vec<Found<'a>>,
+    /// Every function, with its name qualified by its class and the class's name
+    functions: Vec<(&'a UIRNode, String, Option<String>)>,
+}
+
+impl<'a> Scan<'a> {
+    fn visit(&mut self, node: &'a UIRNode, parent: Option<&'a UIRNode>, function: Option<&'a UIRNode>, class: Option<&'a UIRNode>) {
+        let (mut function, mut class) = (function, class);
+        match node.node_type {
+            NodeType::Function if !has_tag(node, &["function_declarator"]) => {
+                let name = node.name.clone().unwrap_or_default();
+                let qualified = match class.and_then(|class| class.name.as_deref()) {
+                    Some(owner) if function.is_none() => format!("{}.{}", owner, name),
+                    _ => name,
+                };
+                self.functions.push((node, qualified, class.and_then(|class| class.name.clone())));
+                function = Some(node);
+            }
+            NodeType::Class => {
+                class = Some(node);
+                function = None;
+                self.visit_class(node);
+            }
+            _ => {}
+        }
+        let class_name = class.and_then(|class| class.name.clone());
+        match (function, class) {
+            (None, None) => {
+                for (variable, constant) in self.globals(node, parent) {
+                    if !constant {
+                        self.found.push(Found { kind: StateKind::Global, name: variable.clone(), variable, node, function: None, class: None });
+                    }
+                }
+            }
+            (Some(function), _) if has_tag(node, &["declaration"]) && node.node_type != NodeType::Function => {
+                let is_static = node.children.iter().any(|child| has_tag(child, &["storage_class_specifier"]) && original_text(child) == "static");
+                if is_static && !is_const(node) {
+                    for variable in declared_names(node) {
+                        self.found.push(Found { kind: StateKind::StaticLocal, name: variable.clone(), variable, node, function: Some(function.id.clone()), class: class_name.clone() });
+                    }
+                }
+            }
+            _ => {}
+        }
+        for child in &node.children {
+            self.visit(child, Some(node), function, class);
+        }
+    }
+    
+    /// Static fields and class attributes, and whether they make the class a singleton
+    fn visit_class(&mut self, class: &'a UIRNode) {
+        let Some(class_name) = class.name.clone() else {
+            return;
+        };
+        let mut singleton = false;
+        let mut fields = Vec::new();
+        collect_class_fields(class, &mut fields, self.language == Language::Python);
+        for (field, value) in fields {
+            let Some(name) = field.name.clone() else {
+                continue;
+            };
+            let constant = match &field.node_type {
+                NodeType::Field { .. } => field.modifiers.iter().any(|modifier| matches!(modifier, Modifier::Const | Modifier::ReadOnly)),
+                _ => upper_case(&name) && value.is_some_and(|value| value.literal.is_some()),
+            };
+            let is_static = field.modifiers.contains(&Modifier::Static) || field.node_type == NodeType::Variable;
+            if constant || !is_static {
+                continue;
+            }
+            let own_type = matches!(&field.node_type, NodeType::Field { field_type: Some(field_type), .. } if *field_type == class_name);
+            singleton |= own_type || name.to_ascii_lowercase().contains("instance");
+            self.found.push(Found {
+                kind: StateKind::StaticField,
+                name: format!("{}.{}", class_name, name),
+                variable: name,
+                node: field,
+                function: None,
+                class: Some(class_name.clone()),
+            });
+        }
+        if singleton {
+            self.found.push(Found { kind: StateKind::Singleton, name: class_name.clone(), variable: class_name, node: class, function: None, class: None });
+        }
+    }
+    
+    /// The variables a module-level node declares, and whether each is a constant
+    fn globals(&self, node: &UIRNode, parent: Option<&UIRNode>) -> Vec<(String, bool)> {
+        match &node.node_type {
+            NodeType::Variable => {
+                let Some(name) = node.name.clone().filter(|name| !(name.starts_with("__") && name.ends_with("__"))) else {
+                    return Vec::new();
+                };
+                let value = match parent {
+                    Some(parent) if parent.node_type == NodeType::Expression(ExpressionType::Assignment) => significant(parent).last().copied(),
+                    _ => significant(node).last().copied(),
+                };
+                let literal = value.is_some_and(|value| value.literal.is_some());
+                let declared_const = parent.and_then(|parent| parent.metadata.annotations.get("declaration_kind")).and_then(Value::as_str) == Some("const");
+                let constant = literal && (declared_const || (self.language == Language::Python && upper_case(&name)));
+                // Python's later assignments rebind the first
+                let first = !self.found.iter().any(|found| found.kind == StateKind::Global && found.variable == name);
+                if first { vec![(name, constant)] } else { Vec::new() }
+            }
+            _ if has_tag(node, &["declaration"]) => {
+                let constant = is_const(node);
+                declared_names(node).into_iter().map(|name| (name, constant)).collect()
+            }
+            _ if has_tag(node, &["static_item"]) => {
+                let mutable = node.children.iter().any(|child| has_tag(child, &["mutable_specifier"]));
+                node.children.iter()
+                    .filter(|child| child.node_type == NodeType::Expression(ExpressionType::Variable))
+                    .filter_map(|child| child.name.clone())
+                    .map(|name| (name, !mutable))
+                    .take(1)
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// What one function names and assigns, outside functions and classes nested in it
+#[derive(Default)]
+struct Accesses {
+    /// Parameters and locals
+    locals: HashSet<String>,
+    /// Bare names read or assigned, and whether assigned
+    bare: HashMap<String, bool>,
+    /// Members named through a receiver: member, receiver and whether assigned
+    members: Vec<(String, String, bool)>,
+    receivers: HashSet<String>,
+}
+
+impl Accesses {
+    fn of(function: &UIRNode) -> Self {
+        let flow = DataFlow::analyze(function);
+        let shared = shared_names(function);
+        let mut accesses = Accesses {
+            locals: flow.definitions().iter()
+                .filter(|definition| definition.kind != DefinitionKind::Assignment && !shared.contains(&definition.variable))
+                .map(|definition| definition.variable.clone())
+                .collect(),
+            ..Accesses::default()
+        };
+        for child in &function.children {
+            accesses.visit(child, false);
+        }
+        accesses
+    }
+    
+    fn visit(&mut self, node: &UIRNode, assigned: bool) {
+        if matches!(node.node_type, NodeType::Function | NodeType::Class) {
+            return;
+        }
+        if has_tag(node, MEMBER_TAGS) {
+            let text = original_text(node);
+            if let Some((receiver, member)) = text.rsplit_once('.') {
+                self.members.push((member.to_string(), receiver.to_string(), assigned));
+                self.receivers.insert(receiver.to_string());
+            }
+            if let Some(receiver) = node.children.first() {
+                self.visit(receiver, false);
+            }
+            return;
+        }
+        let bare = matches!(node.node_type, NodeType::Variable | NodeType::Expression(ExpressionType::Variable)) && node.children.is_empty();
+        if let (true, Some(name)) = (bare, &node.name) {
+            *self.bare.entry(name.clone()).or_default() |= assigned;
+            return;
+        }
+        
+        let assignment = node.node_type == NodeType::Expression(ExpressionType::Assignment) || has_tag(node, ASSIGNMENT_TAGS);
+        let update = has_tag(node, UPDATE_TAGS)
+            || (has_tag(node, &["postfix_unary_expression", "prefix_unary_expression"]) && node.children.iter().any(|child| matches!(original_text(child), "++" | "--")));
+        let target = (assignment || update).then(|| significant(node).first().map(|target| target.id.clone())).flatten();
+        for child in &node.children {
+            self.visit(child, target.as_deref() == Some(child.id.as_str()));
+        }
+    }
+    
+    /// Whether the function names `name`, bare or through one of `receivers`
+    fn names(&self, name: &str, receivers: Option<&[&str]>) -> bool {
+        match receivers {
+            None => self.bare.contains_key(name),
+            Some(receivers) => self.members.iter().any(|(member, receiver, _)| member == name && receivers.contains(&receiver.as_str())),
+        }
+    }
+    
+    fn assigns(&self, name: &str, receivers: Option<&[&str]>) -> bool {
+        match receivers {
+            None => self.bare.get(name).copied().unwrap_or_default(),
+            Some(receivers) => self.members.iter().any(|(member, receiver, assigned)| *assigned && member == name && receivers.contains(&receiver.as_str())),
+        }
+    }
+}
+
+/// Fields and class-level variables of a class, with the value a Python attribute is bound to
+fn collect_class_fields<'a>(node: &'a UIRNode, fields: &mut Vec<(&'a UIRNode, Option<&'a UIRNode>)>, python: bool) {
+    for child in &node.children {
+        match &child.node_type {
+            NodeType::Function | NodeType::Class => {}
+            NodeType::Field { .. } => fields.push((child, None)),
+            NodeType::Variable if python => {
+                let value = (node.node_type == NodeType::Expression(ExpressionType::Assignment)).then(|| significant(node).last().copied()).flatten();
+                if !fields.iter().any(|(field, _)| field.name == child.name) {
+                    fields.push((child, value));
+                }
+            }
+            _ => collect_class_fields(child, fields, python),
+        }
+    }
+}
+
+/// The variables a C declaration declares, leaving out the functions a prototype declares
+pub(crate) fn declared_names(declaration: &UIRNode) -> Vec<String> {
+    fn name(node: &UIRNode) -> Option<String> {
+        match node.node_type {
+            NodeType::Expression(ExpressionType::Variable) if node.children.is_empty() => node.name.clone(),
+            NodeType::Function => None,
+            _ if has_tag(node, &["init_declarator", "pointer_declarator", "array_declarator"]) => node.children.iter().find_map(name),
+            _ => None,
+        }
+    }
+    declaration.children.iter().filter_map(name).collect()
+}
+
+fn is_const(declaration: &UIRNode) -> bool {
+    declaration.children.iter().any(|child| has_tag(child, &["type_qualifier"]) && original_text(child) == "const")
+}
+
+/// `MAX_SIZE`, the convention for a constant in languages without them
+fn upper_case(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_uppercase()) && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::{CParser, CSharpParser, PythonParser};
+    
+    fn summary(state: &GlobalState) -> Vec<(StateKind, &str, Vec<&str>, Vec<&str>)> {
+        state.items().iter()
+            .map(|item| (item.kind, item.name.as_str(), item.used_by.iter().map(String::as_str).collect(), item.assigned_by.iter().map(String::as_str).collect()))
+            .collect()
+    }
+    
+    #[test]
+    fn test_python_globals_class_attributes_and_singletons() {
+        let source = "MAX_RETRIES = 3\nregistry = {}\ncounter = 0\n\nclass Config:\n    _instance = None\n    TIMEOUT = 30\n    def __new__(cls):\n        if cls._instance is None:\n            cls._instance = super().__new__(cls)\n        return cls._instance\n\ndef bump():\n    global counter\n    counter += 1\n    return counter\n\ndef peek():\n    counter = 5\n    return registry.get(counter)\n\ndef settings():\n    return Config()\n";
+        let mut module = PythonParser::new().unwrap().parse(source).unwrap();
+        let state = GlobalState::find([("app", &module)]);
+        
+        assert_eq!(summary(&state), [
+            (StateKind::Global, "registry", vec!["peek"], vec![]),
+            (StateKind::Global, "counter", vec!["bump"], vec!["bump"]),
+            (StateKind::StaticField, "Config._instance", vec!["Config.__new__"], vec!["Config.__new__"]),
+            (StateKind::Singleton, "Config", vec!["settings"], vec![]),
+        ]);
+        assert_eq!(state.items()[1].to_string(), "app:3: global `counter`, assigned by bump, used by bump");
+        
+        state.annotate("app", &mut module);
+        let config = module.children.iter().find(|child| child.name.as_deref() == Some("Config")).unwrap();
+        assert_eq!(config.metadata.annotations["shared_state"], "singleton");
+    }
+    
+    #[test]
+    fn test_c_globals_and_static_locals() {
+        let source = "static int counter = 0;\nconst int LIMIT = 5;\nint total, *cursor;\nint next(void);\n\nint next(void) {\n    static int calls;\n    static const int step = 1;\n    calls++;\n    return counter += step;\n}\n\nint peek(int total) {\n    return total + LIMIT;\n}\n";
+        let module = CParser::new().unwrap().parse(source).unwrap();
+        let state = GlobalState::find([("main", &module)]);
+        
+        assert_eq!(summary(&state), [
+            (StateKind::Global, "counter", vec!["next"], vec!["next"]),
+            (StateKind::Global, "total", vec![], vec![]),
+            (StateKind::Global, "cursor", vec![], vec![]),
+            (StateKind::StaticLocal, "calls", vec!["next"], vec!["next"]),
+        ]);
+        assert_eq!(state.items()[3].function.as_deref(), Some("next"));
+    }
+    
+    #[test]
+    fn test_csharp_static_fields() {
+        let source = "class Registry { private static Registry instance; public static int Count; public const int Max = 3; private int size; public static Registry Get() { Count++; return instance; } }";
+        let module = CSharpParser::new().unwrap().parse(source).unwrap();
+        let state = GlobalState::find([("registry", &module)]);
+        
+        let names: Vec<(StateKind, &str)> = state.items().iter().map(|item| (item.kind, item.name.as_str())).collect();
+        assert_eq!(names, [(StateKind::StaticField, "Registry.instance"), (StateKind::StaticField, "Registry.Count"), (StateKind::Singleton, "Registry")]);
+        assert_eq!(state.items()[1].assigned_by.len(), 1);
+    }
+}
@@ -0,0 +1,512 @@
+// Type inference for dynamically typed sources
+//
+// Python and JavaScript leave most variables, parameters and return values untyped, and the
+// generators for typed targets fall back to a default such as `i32` for each of them.
+// `infer_types` works the missing types out from how values flow through the module: a
+// variable takes the types of everything assigned to it, a parameter the types of the
+// arguments at each call of its function, and a function the types of the values it returns.
+// Types of different assignments are unified, widening `int` to `double` and a type and `None`
+// to `Optional`; values that disagree in any other way leave the variable untyped. The pass
+// repeats until nothing changes, so types flow through calls and variables declared later in
+// the module. Inferred types are written in the generators' common vocabulary (`int`,
+// `double`, `string`, `bool`, `List[T]`, `Dict[K, V]`, `Optional[T]` and class names) as the
+// same `type` and `return_type` annotations declared types use. Declared types are kept.
+use crate::resolve::resolve;
+use crate::symbols::{SymbolId, SymbolKind, SymbolTable};
+use coalesce_core::{ControlFlowType, ExpressionType, Language, LiteralValue, LoopType, NodeType, Operator, StatementType, UIRNode, Walk, walk_mut};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Passes after which inference gives up on reaching a fixed point
+const MAX_PASSES: usize = 16;
+
+/// An inferred type
+#[derive(Debug, Clone, PartialEq)]
+enum Type {
+    Int,
+    Double,
+    String,
+    Bool,
+    Null,
+    List(Box<Type>),
+    Dict(Box<Type>, Box<Type>),
+    Optional(Box<Type>),
+    /// A declared type or class, kept as spelled
+    Named(String),
+    /// Values of types that do not unify
+    Conflict,
+}
+
+impl Type {
+    fn declared(name: &str) -> Self {
+        match name.trim() {
+            "int" | "Int" | "i32" | "i64" | "long" => Type::Int,
+            "float" | "double" | "number" | "f32" | "f64" => Type::Double,
+            "str" | "string" | "String" => Type::String,
+            "bool" | "boolean" => Type::Bool,
+            name => Type::Named(name.to_string()),
+        }
+    }
+    
+    fn is_numeric(&self) -> bool {
+        matches!(self, Type::Int | Type::Double)
+    }
+    
+    /// The type of a value that may be either
+    fn unify(self, other: Type) -> Type {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Type::Conflict, _) | (_, Type::Conflict) => Type::Conflict,
+            (Type::Int, Type::Double) | (Type::Double, Type::Int) => Type::Double,
+            (Type::Null, Type::Optional(t)) | (Type::Optional(t), Type::Null) => Type::Optional(t),
+            (Type::Null, t) | (t, Type::Null) => Type::Optional(Box::new(t)),
+            (Type::Optional(a), Type::Optional(b)) => Type::Optional(Box::new(a.unify(*b))),
+            (Type::Optional(a), b) | (b, Type::Optional(a)) => Type::Optional(Box::new(a.unify(b))),
+            (Type::List(a), Type::List(b)) => Type::List(Box::new(a.unify(*b))),
+            (Type::Dict(k1, v1), Type::Dict(k2, v2)) => Type::Dict(Box::new(k1.unify(*k2)), Box::new(v1.unify(*v2))),
+            _ => Type::Conflict,
+        }
+    }
+    
+    /// The type as annotated, if it is fully known
+    fn name(&self) -> Option<String> {
+        match self {
+            Type::Int => Some("int".to_string()),
+            Type::Double => Some("double".to_string()),
+            Type::String => Some("string".to_string()),
+            Type::Bool => Some("bool".to_string()),
+            Type::List(element) => Some(format!("List[{}]", element.name()?)),
+            Type::Dict(key, value) => Some(format!("Dict[{}, {}]", key.name()?, value.name()?)),
+            Type::Optional(inner) => Some(format!("Optional[{}]", inner.name()?)),
+            Type::Named(name) => Some(name.clone()),
+            Type::Null | Type::Conflict => None,
+        }
+    }
+}
+
+/// Annotate the untyped variables, parameters and functions of `module` with inferred types,
+/// returning how many annotations were added
+pub fn infer_types(module: &mut UIRNode) -> usize {
+    let resolution = resolve(module);
+    let mut inference = Inference {
+        symbols: &resolution.symbols,
+        parameters: HashMap::new(),
+        variables: HashMap::new(),
+        returns: HashMap::new(),
+        changed: true,
+    };
+    inference.collect_parameters(module);
+    for _ in 0..MAX_PASSES {
+        if !inference.changed {
+            break;
+        }
+        inference.changed = false;
+        inference.visit(module, None);
+    }
+    
+    let mut annotations: HashMap<String, (&str, String)> = HashMap::new();
+    for (symbol, inferred) in &inference.variables {
+        let symbol = resolution.symbols.symbol(*symbol);
+        if let (None, Some(name)) = (&symbol.declared_type, inferred.name()) {
+            annotations.insert(symbol.node_id.clone(), ("type", name));
+        }
+    }
+    for (function, inferred) in &inference.returns {
+        if let Some(name) = inferred.name() {
+            annotations.insert(function.clone(), ("return_type", name));
+        }
+    }
+    let mut added = 0;
+    walk_mut(module, &mut |node: &mut UIRNode| {
+        if let Some((key, name)) = annotations.remove(&node.id) {
+            if !node.metadata.annotations.contains_key(key) {
+                node.metadata.annotations.insert(key.to_string(), Value::String(name));
+                added += 1;
+            }
+        }
+        Walk::Continue
+    });
+    module.debug_validate();
+    added
+}
+
+struct Inference<'a> {
+    symbols: &'a SymbolTable,
+    /// The parameter symbols of each function and closure, by node id
+    parameters: HashMap<String, Vec<Option<SymbolId>>>,
+    variables: HashMap<SymbolId, Type>,
+    /// Return types by function node id
+    returns: HashMap<String, Type>,
+    changed: bool,
+}
+
+impl Inference<'_> {
+    fn collect_parameters(&mut self, node: &UIRNode) {
+        if matches!(node.node_type, NodeType::Function | NodeType::Closure { .. }) {
+            let parameters = node.children.iter()
+                .filter(|child| child.node_type == NodeType::Variable)
+                .map(|parameter| self.symbols.binding(&parameter.id))
+                .collect();
+            self.parameters.insert(node.id.clone(), parameters);
+        }
+        for child in &node.children {
+            self.collect_parameters(child);
+        }
+    }
+    
+    fn visit(&mut self, node: &UIRNode, function: Option<&UIRNode>) {
+        match &node.node_type {
+            NodeType::Variable => {
+                // A declarator or parameter with its initial value
+                let value = match node.children.last() {
+                    Some(value) => self.type_of(value),
+                    None => node.metadata.annotations.get("default_value").and_then(|v| v.as_str()).and_then(literal_type),
+                };
+                self.assign(self.symbols.binding(&node.id), value);
+            }
+            NodeType::Expression(ExpressionType::Assignment) | NodeType::Expression(ExpressionType::Literal) if is_augmented(node) => {
+                if let (Some(target), Some(value)) = (node.children.first(), node.children.last()) {
+                    let symbol = self.symbol_of(target);
+                    let current = symbol.and_then(|symbol| self.variables.get(&symbol).cloned());
+                    let operator = augmented_operator(node, target, &self.symbols.language);
+                    let value = match (operator, current, self.type_of(value)) {
+                        (Some(operator), Some(current), Some(value)) => arithmetic(operator, &current, Some(&value)),
+                        _ => None,
+                    };
+                    self.assign(symbol, value);
+                }
+            }
+            NodeType::Expression(ExpressionType::Assignment) if node.children.len() >= 2 => {
+                let target = &node.children[0];
+                if matches!(target.node_type, NodeType::Variable | NodeType::Expression(ExpressionType::Variable)) {
+                    let value = self.type_of(&node.children[node.children.len() - 1]);
+                    self.assign(self.symbol_of(target), value);
+                }
+            }
+            NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)) if node.children.len() >= 2 => {
+                let element = match self.type_of(&node.children[1]) {
+                    Some(Type::List(element)) => Some(*element),
+                    Some(Type::Dict(key, _)) => Some(*key),
+                    Some(Type::String) => Some(Type::String),
+                    _ => None,
+                };
+                self.assign(self.symbol_of(&node.children[0]), element);
+            }
+            NodeType::Statement(StatementType::Return) => {
+                if let (Some(function), Some(value)) = (function, node.children.first()) {
+                    if let Some(value) = self.type_of(value) {
+                        let unified = match self.returns.get(&function.id) {
+                            Some(current) => current.clone().unify(value),
+                            None => value,
+                        };
+                        if self.returns.get(&function.id) != Some(&unified) {
+                            self.returns.insert(function.id.clone(), unified);
+                            self.changed = true;
+                        }
+                    }
+                }
+            }
+            NodeType::Expression(ExpressionType::FunctionCall) => {
+                // Each argument flows into the parameter in its position
+                let callee = self.callee(node);
+                let parameters = callee.and_then(|symbol| self.parameters.get(&self.symbols.symbol(symbol).node_id)).cloned();
+                if let Some(parameters) = parameters {
+                    for (parameter, argument) in parameters.into_iter().zip(arguments(node)) {
+                        let value = self.type_of(argument);
+                        self.assign(parameter, value);
+                    }
+                }
+            }
+            _ => {}
+        }
+        
+        let function = match node.node_type {
+            NodeType::Function | NodeType::Closure { .. } => Some(node),
+            _ => function,
+        };
+        for child in &node.children {
+            self.visit(child, function);
+        }
+    }
+    
+    /// Unify a value's type into a variable's, unless the variable's type was declared
+    fn assign(&mut self, symbol: Option<SymbolId>, value: Option<Type>) {
+        let (Some(symbol), Some(value)) = (symbol, value) else {
+            return;
+        };
+        if self.symbols.symbol(symbol).declared_type.is_some() {
+            return;
+        }
+        let unified = match self.variables.get(&symbol) {
+            Some(current) => current.clone().unify(value),
+            None => value,
+        };
+        if self.variables.get(&symbol) != Some(&unified) {
+            self.variables.insert(symbol, unified);
+            self.changed = true;
+        }
+    }
+    
+    /// The symbol a declaration, assignment target or reference names
+    fn symbol_of(&self, node: &UIRNode) -> Option<SymbolId> {
+        self.symbols.binding(&node.id).or_else(|| {
+            let declaration = node.metadata.annotations.get("resolved_id")?.as_str()?;
+            self.symbols.binding(declaration)
+        })
+    }
+    
+    /// The symbol a call's callee resolved to
+    fn callee(&self, call: &UIRNode) -> Option<SymbolId> {
+        let callee = call.children.first()
+            .filter(|child| child.node_type == NodeType::Expression(ExpressionType::Variable))
+            .unwrap_or(call);
+        self.symbol_of(callee)
+    }
+    
+    fn type_of(&self, node: &UIRNode) -> Option<Type> {
+        match &node.node_type {
+            NodeType::Expression(ExpressionType::Literal) => match &node.literal {
+                Some(value) => Some(literal_value_type(value)),
+                None => self.collection_type(node),
+            },
+            NodeType::Expression(ExpressionType::Variable) => {
+                let symbol = self.symbol_of(node)?;
+                match &self.symbols.symbol(symbol).declared_type {
+                    Some(declared) => Some(Type::declared(declared)),
+                    None => self.variables.get(&symbol).cloned(),
+                }
+            }
+            NodeType::Expression(ExpressionType::Arithmetic) => {
+                let operator = node.operator?;
+                let left = self.type_of(node.children.first()?)?;
+                let right = match node.children.len() {
+                    1 => None,
+                    _ => Some(self.type_of(node.children.last()?)?),
+                };
+                arithmetic(operator, &left, right.as_ref())
+            }
+            NodeType::Expression(ExpressionType::Comparison) => Some(Type::Bool),
+            NodeType::Expression(ExpressionType::Logical) => match node.operator {
+                Some(Operator::Not) => Some(Type::Bool),
+                // `a or b` is one of its operands
+                _ => {
+                    let left = self.type_of(node.children.first()?)?;
+                    let right = self.type_of(node.children.last()?)?;
+                    Some(left.unify(right)).filter(|unified| *unified != Type::Conflict)
+                }
+            },
+            NodeType::Expression(ExpressionType::FunctionCall) => match self.callee(node) {
+                Some(symbol) => {
+                    let symbol = self.symbols.symbol(symbol);
+                    match symbol.kind {
+                        SymbolKind::Class => Some(Type::Named(symbol.name.clone())),
+                        SymbolKind::Function | SymbolKind::Method => match &symbol.declared_type {
+                            Some(declared) => Some(Type::declared(declared)),
+                            None => self.returns.get(&symbol.node_id).cloned(),
+                        },
+                        _ => None,
+                    }
+                }
+                None => {
+                    let callee = node.children.first().and_then(|callee| callee.name.as_deref()).or(node.name.as_deref())?;
+                    builtin_return_type(callee)
+                }
+            },
+            _ => None,
+        }
+    }
+    
+    /// List, dictionary and string literals, which parsers keep as tagged literals without a value
+    fn collection_type(&self, node: &UIRNode) -> Option<Type> {
+        let elements = || node.children.iter().filter(|child| !is_punctuation(child));
+        match node.metadata.semantic_tags.first().map(String::as_str) {
+            Some("string" | "concatenated_string" | "template_string") => Some(Type::String),
+            Some("list" | "array" | "tuple") => {
+                let element = elements().map(|element| self.type_of(element)).reduce(|a, b| Some(a?.unify(b?)))??;
+                Some(Type::List(Box::new(element)))
+            }
+            Some("dictionary" | "object") => {
+                let (key, value) = elements()
+                    .map(|pair| {
+                        let key = pair.children.first()?;
+                        let key = match key.metadata.semantic_tags.first().map(String::as_str) {
+                            // `{x: 1}` names its keys
+                            Some("property_identifier" | "identifier") => Type::String,
+                            _ => self.type_of(key)?,
+                        };
+                        Some((key, self.type_of(pair.children.last()?)?))
+                    })
+                    .reduce(|a, b| {
+                        let ((k1, v1), (k2, v2)) = (a?, b?);
+                        Some((k1.unify(k2), v1.unify(v2)))
+                    })??;
+                Some(Type::Dict(Box::new(key), Box::new(value)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The type of an arithmetic expression's value; `right` is `None` for unary operators
+fn arithmetic(operator: Operator, left: &Type, right: Option<&Type>) -> Option<Type> {
+    let Some(right) = right else {
+        return match operator {
+            Operator::Negate | Operator::Plus if left.is_numeric() => Some(left.clone()),
+            Operator::BitNot if *left == Type::Int => Some(Type::Int),
+            _ => None,
+        };
+    };
+    let numeric = || match (left, right) {
+        (Type::Int, Type::Int) => Some(Type::Int),
+        _ if left.is_numeric() && right.is_numeric() => Some(Type::Double),
+        _ => None,
+    };
+    match operator {
+        Operator::Concat => Some(Type::String),
+        // JavaScript converts the other operand of a string `+`
+        Operator::Add if *left == Type::String || *right == Type::String => Some(Type::String),
+        Operator::Add => match (left, right) {
+            (Type::List(_), Type::List(_)) => Some(left.clone().unify(right.clone())),
+            _ => numeric(),
+        },
+        // Python repeats strings and lists
+        Operator::Multiply if matches!(left, Type::String | Type::List(_)) && *right == Type::Int => Some(left.clone()),
+        Operator::Divide if left.is_numeric() && right.is_numeric() => Some(Type::Double),
+        Operator::Modulo if *left == Type::String => Some(Type::String),
+        Operator::BitAnd | Operator::BitOr | Operator::BitXor | Operator::ShiftLeft | Operator::ShiftRight
+            | Operator::UnsignedShiftRight => (*left == Type::Int && *right == Type::Int).then_some(Type::Int),
+        _ => numeric(),
+    }
+}
+
+fn literal_value_type(value: &LiteralValue) -> Type {
+    match value {
+        LiteralValue::Int(_) => Type::Int,
+        LiteralValue::Float(_) => Type::Double,
+        LiteralValue::String(_) | LiteralValue::Char(_) => Type::String,
+        LiteralValue::Bool(_) => Type::Bool,
+        LiteralValue::Null => Type::Null,
+    }
+}
+
+/// The type of a default value kept only as source text
+fn literal_type(text: &str) -> Option<Type> {
+    let text = text.trim();
+    if text.parse::<i64>().is_ok() {
+        Some(Type::Int)
+    } else if text.parse::<f64>().is_ok() {
+        Some(Type::Double)
+    } else if text.starts_with(['"', '\'']) {
+        Some(Type::String)
+    } else {
+        match text {
+            "True" | "False" | "true" | "false" => Some(Type::Bool),
+            _ => None,
+        }
+    }
+}
+
+/// Return types of the Python and JavaScript builtins that convert and measure values
+fn builtin_return_type(name: &str) -> Option<Type> {
+    match name {
+        "str" | "repr" | "chr" | "input" | "String" => Some(Type::String),
+        "int" | "len" | "ord" | "parseInt" => Some(Type::Int),
+        "float" | "Number" | "parseFloat" => Some(Type::Double),
+        "bool" | "isinstance" | "Boolean" | "isNaN" => Some(Type::Bool),
+        _ => None,
+    }
+}
+
+/// `x += 1`: an assignment the parser recorded an operator on, or one tagged augmented
+fn is_augmented(node: &UIRNode) -> bool {
+    node.node_type == NodeType::Expression(ExpressionType::Assignment) && node.operator.is_some()
+        || node.metadata.semantic_tags.iter().any(|tag| tag == "augmented_assignment" || tag == "augmented_assignment_expression")
+}
+
+/// The operator of an augmented assignment: `+` of `x += 1`
+fn augmented_operator(node: &UIRNode, target: &UIRNode, language: &Language) -> Option<Operator> {
+    if node.operator.is_some() {
+        return node.operator;
+    }
+    let text = original_text(node).strip_prefix(original_text(target))?;
+    let symbol = text.trim_start().split('=').next()?;
+    Operator::binary(symbol, language)
+}
+
+fn original_text(node: &UIRNode) -> &str {
+    node.metadata.annotations.get("original_text").and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn is_punctuation(node: &UIRNode) -> bool {
+    let text = original_text(node);
+    !text.is_empty() && text.chars().all(|c| matches!(c, ';' | ',' | '{' | '}' | '(' | ')' | '[' | ']' | ':'))
+}
+
+/// The arguments of a call, whether listed directly or in an argument list node
+fn arguments(call: &UIRNode) -> Vec<&UIRNode> {
+    let mut arguments = Vec::new();
+    for child in call.children.iter().skip(1).filter(|child| !is_punctuation(child)) {
+        if matches!(child.name.as_deref(), Some("argument_list" | "arguments")) {
+            arguments.extend(child.children.iter().filter(|argument| !is_punctuation(argument)));
+        } else {
+            arguments.push(child);
+        }
+    }
+    arguments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::{JavaScriptParser, PythonParser};
+    
+    fn annotation<'a>(uir: &'a UIRNode, selector: &str, key: &str) -> Option<&'a str> {
+        let node = uir.select(selector).unwrap()[0];
+        node.metadata.annotations.get(key).and_then(|v| v.as_str())
+    }
+    
+    #[test]
+    fn test_python_types_flow_through_calls_and_returns() {
+        let parser = PythonParser::new().unwrap();
+        let source = "def area(w, h):\n    scale = 1.5\n    return w * h * scale\n\ndef label(n, unit='cm'):\n    return str(n) + unit\n\nsize = area(2, 3)\ntotal = 0\nfor x in [1, 2]:\n    total += x\nname = label(size)\nnothing = None\nnothing = name\n";
+        
+        let mut uir = parser.parse(source).unwrap();
+        assert!(infer_types(&mut uir) > 0);
+        assert_eq!(annotation(&uir, "Function[name=area] > Variable[name=w]", "type"), Some("int"));
+        assert_eq!(annotation(&uir, "Function[name=area] Variable[name=scale]", "type"), Some("double"));
+        assert_eq!(annotation(&uir, "Function[name=area]", "return_type"), Some("double"));
+        assert_eq!(annotation(&uir, "Function[name=label] > Variable[name=n]", "type"), Some("double"));
+        assert_eq!(annotation(&uir, "Function[name=label] > Variable[name=unit]", "type"), Some("string"));
+        assert_eq!(annotation(&uir, "Function[name=label]", "return_type"), Some("string"));
+        assert_eq!(annotation(&uir, "Variable[name=total]", "type"), Some("int"));
+        assert_eq!(annotation(&uir, "Variable[name=nothing]", "type"), Some("Optional[string]"));
+    }
+    
+    #[test]
+    fn test_conflicting_and_declared_types_are_left_alone() {
+        let parser = PythonParser::new().unwrap();
+        let source = "def f(x: float, y):\n    return y\n\nf(1, 'a')\nf(2, 3)\nz: int = 1\nz = 2.5\n";
+        
+        let mut uir = parser.parse(source).unwrap();
+        infer_types(&mut uir);
+        assert_eq!(annotation(&uir, "Function[name=f] > Variable[name=x]", "type"), Some("float"));
+        assert_eq!(annotation(&uir, "Function[name=f] > Variable[name=y]", "type"), None);
+        assert_eq!(annotation(&uir, "Function[name=f]", "return_type"), None);
+        assert_eq!(annotation(&uir, "Variable[name=z]", "type"), None);
+    }
+    
+    #[test]
+    fn test_javascript_collections_and_updates() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "let items = [1, 2];\nconst ages = {ann: 31};\nlet t = 0;\nt += 1.5;\nfunction first(list) { return list[0] > 1; }\nfirst(items);";
+        
+        let mut uir = parser.parse(source).unwrap();
+        infer_types(&mut uir);
+        assert_eq!(annotation(&uir, "Variable[name=items]", "type"), Some("List[int]"));
+        assert_eq!(annotation(&uir, "Variable[name=ages]", "type"), Some("Dict[string, int]"));
+        assert_eq!(annotation(&uir, "Variable[name=t]", "type"), Some("double"));
+        assert_eq!(annotation(&uir, "Function[name=first] > Variable[name=list]", "type"), Some("List[int]"));
+        assert_eq!(annotation(&uir, "Function[name=first]", "return_type"), Some("bool"));
+    }
+}
@@ -0,0 +1,28 @@
+// Program analyses over UIR for Coalesce
+pub mod symbols;
+pub mod resolve;
+pub mod infer;
+pub mod calls;
+pub mod cfg;
+pub mod dataflow;
+pub mod deadcode;
+pub mod metrics;
+pub mod recursion;
+pub mod globals;
+pub mod api;
+pub mod fold;
+pub mod purity;
+
+pub use symbols::{Scope, ScopeId, ScopeKind, Symbol, SymbolId, SymbolKind, SymbolTable};
+pub use resolve::{Resolution, resolve};
+pub use infer::infer_types;
+pub use calls::{Call, CallGraph, Callable, CallableKind};
+pub use cfg::{BasicBlock, BlockId, ControlFlowGraph, Edge, EdgeKind};
+pub use dataflow::{DataFlow, DataFlowWarning, Definition, DefinitionKind, WarningKind, analyze_data_flow};
+pub use deadcode::{DeadCode, DeadCodeItem, DeadCodeKind};
+pub use metrics::{FunctionMetrics, measure_complexity};
+pub use recursion::{Recursion, RecursionKind, RecursiveGroup};
+pub use globals::{GlobalState, SharedState, StateKind};
+pub use api::{ApiChange, ApiChangeKind, ApiItem, ApiKind, ApiManifest, ApiParameter};
+pub use fold::{FoldConfig, fold_constants};
+pub use purity::{Effect, EffectKind, FunctionPurity, Purity, PurityKind};
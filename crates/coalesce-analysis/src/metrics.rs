@@ -0,0 +1,170 @@
+// Complexity metrics
+//
+// `FunctionMetrics` measures how much work a function is to migrate: its cyclomatic
+// complexity, one more than the decisions it makes (conditions, loops, cases other than the
+// default, exception handlers, ternaries and the short-circuit `&&`, `||` and `??`); how
+// deeply its control structures nest, an `else if` counting as a sibling of its `if`; its
+// statements as its control-flow graph records them, a condition standing for its `if` or
+// loop; and the source lines it spans. Nested functions and classes are measured on their
+// own, while closures count toward the function they are written in.
+use crate::cfg::{CASE_TAGS, ControlFlowGraph, Flow, HANDLER_TAGS, has_tag, is_default, kind};
+use coalesce_core::{ControlFlowType, NodeType, Operator, UIRNode, Walk, walk_mut};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+const TERNARY_TAGS: &[&str] = &["ternary_expression", "conditional_expression"];
+/// Comprehensions, whose `for` and `if` clauses are the loops and conditions
+const COMPREHENSION_TAGS: &[&str] = &["list_comprehension", "set_comprehension", "dictionary_comprehension", "generator_expression"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionMetrics {
+    /// The name qualified by the classes and functions it is declared in
+    pub name: String,
+    pub node_id: String,
+    pub line: Option<u32>,
+    pub cyclomatic: u32,
+    pub nesting_depth: u32,
+    pub statements: usize,
+    pub lines: u32,
+}
+
+impl FunctionMetrics {
+    pub fn measure(function: &UIRNode) -> Self {
+        let location = function.source_location.as_ref();
+        FunctionMetrics {
+            name: function.name.clone().unwrap_or_default(),
+            node_id: function.id.clone(),
+            line: location.map(|location| location.start_line),
+            cyclomatic: 1 + function.children.iter().map(decisions).sum::<u32>(),
+            nesting_depth: function.children.iter().map(|child| depth(child, false)).max().unwrap_or_default(),
+            statements: ControlFlowGraph::build(function).blocks().map(|(_, block)| block.statements.len()).sum(),
+            lines: location.map(|location| location.end_line.saturating_sub(location.start_line) + 1).unwrap_or_default(),
+        }
+    }
+}
+
+/// Measure every function in `module`, setting its `complexity_score` to its cyclomatic
+/// complexity and its other metrics in a `metrics` annotation; the module's score is the sum
+pub fn measure_complexity(module: &mut UIRNode) -> Vec<FunctionMetrics> {
+    let mut measured = Vec::new();
+    collect(module, &mut Vec::new(), &mut measured);
+    
+    let by_id: HashMap<&str, &FunctionMetrics> = measured.iter().map(|metrics| (metrics.node_id.as_str(), metrics)).collect();
+    walk_mut(module, &mut |node: &mut UIRNode| {
+        if let Some(metrics) = by_id.get(node.id.as_str()) {
+            node.metadata.complexity_score = Some(metrics.cyclomatic as f32);
+            node.metadata.annotations.insert("metrics".to_string(), json!({
+                "cyclomatic": metrics.cyclomatic,
+                "nesting_depth": metrics.nesting_depth,
+                "statements": metrics.statements,
+                "lines": metrics.lines,
+            }));
+        }
+        Walk::Continue
+    });
+    if !measured.is_empty() {
+        module.metadata.complexity_score = Some(measured.iter().map(|metrics| metrics.cyclomatic as f32).sum());
+    }
+    measured
+}
+
+fn collect(node: &UIRNode, scope: &mut Vec<String>, measured: &mut Vec<FunctionMetrics>) {
+    let declares = declares_scope(node) && !has_tag(node, &["function_declarator"]);
+    if declares {
+        if let Some(name) = &node.name {
+            scope.push(name.clone());
+        }
+    }
+    if declares && node.node_type == NodeType::Function {
+        let mut metrics = FunctionMetrics::measure(node);
+        metrics.name = scope.join(".");
+        measured.push(metrics);
+    }
+    for child in &node.children {
+        collect(child, scope, measured);
+    }
+    if declares && node.name.is_some() {
+        scope.pop();
+    }
+}
+
+fn declares_scope(node: &UIRNode) -> bool {
+    matches!(node.node_type, NodeType::Function | NodeType::Class)
+}
+
+/// The decisions a node and its children make, outside nested functions and classes
+fn decisions(node: &UIRNode) -> u32 {
+    if declares_scope(node) {
+        return 0;
+    }
+    let decides = if has_tag(node, COMPREHENSION_TAGS) {
+        false
+    } else if matches!(node.node_type, NodeType::ControlFlow(ControlFlowType::Catch { .. })) || has_tag(node, HANDLER_TAGS) {
+        true
+    } else if has_tag(node, CASE_TAGS) {
+        !is_default(node)
+    } else if matches!(node.operator, Some(Operator::And | Operator::Or | Operator::Coalesce)) || has_tag(node, TERNARY_TAGS) {
+        true
+    } else {
+        match kind(node) {
+            Flow::Conditional => true,
+            // Rust's `loop` only leaves by `break`
+            Flow::Loop(_) => !has_tag(node, &["loop_expression"]),
+            _ => false,
+        }
+    };
+    u32::from(decides) + node.children.iter().map(decisions).sum::<u32>()
+}
+
+/// How deeply control structures nest within a node; `in_else` when it is an `else` clause's
+fn depth(node: &UIRNode, in_else: bool) -> u32 {
+    if declares_scope(node) {
+        return 0;
+    }
+    let nests = match kind(node) {
+        Flow::Conditional => !in_else && !has_tag(node, &["elif_clause"]) && !has_tag(node, TERNARY_TAGS),
+        Flow::Loop(_) => !has_tag(node, COMPREHENSION_TAGS),
+        Flow::Switch | Flow::Try => true,
+        _ => false,
+    };
+    let is_else = has_tag(node, &["else_clause", "else"]);
+    let inner = node.children.iter().map(|child| depth(child, is_else)).max().unwrap_or_default();
+    u32::from(nests) + inner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::{CParser, PythonParser};
+    
+    #[test]
+    fn test_python_decisions_nesting_and_nested_functions() {
+        let source = "class Ledger:\n    def settle(self, items, limit):\n        total = 0\n        for item in items:\n            if item > limit and item % 2:\n                total += item\n            elif item < 0:\n                total -= 1\n            else:\n                try:\n                    total += int(item)\n                except ValueError:\n                    pass\n        def check():\n            if total:\n                return 1\n            return [item for item in items if item]\n        return total if total else None\n";
+        let mut module = PythonParser::new().unwrap().parse(source).unwrap();
+        let measured = measure_complexity(&mut module);
+        
+        let names: Vec<&str> = measured.iter().map(|metrics| metrics.name.as_str()).collect();
+        assert_eq!(names, ["Ledger.settle", "Ledger.settle.check"]);
+        let settle = &measured[0];
+        assert_eq!((settle.cyclomatic, settle.nesting_depth, settle.lines), (7, 3, 17));
+        assert_eq!((measured[1].cyclomatic, measured[1].nesting_depth), (4, 1));
+        assert_eq!(module.metadata.complexity_score, Some(11.0));
+    }
+    
+    #[test]
+    fn test_c_switch_cases_and_else_if_chains() {
+        let source = "int grade(int score, int bonus) {\n    int points = score;\n    if (score > 90) {\n        points += 2;\n    } else if (score > 80 || bonus) {\n        points += 1;\n    } else {\n        switch (bonus) {\n        case 1:\n        case 2:\n            points++;\n            break;\n        default:\n            break;\n        }\n    }\n    return points;\n}\n";
+        let mut module = CParser::new().unwrap().parse(source).unwrap();
+        let measured = measure_complexity(&mut module);
+        
+        assert_eq!(measured.len(), 1);
+        let grade = &measured[0];
+        assert_eq!((grade.cyclomatic, grade.nesting_depth), (6, 2));
+        assert!(grade.statements >= 8);
+        let function = &module.children[0];
+        assert_eq!(function.metadata.complexity_score, Some(6.0));
+        assert_eq!(function.metadata.annotations["metrics"]["nesting_depth"], 2);
+    }
+}
@@ -0,0 +1,403 @@
+// Purity and side effects
+//
+// `Purity` tells which functions are pure, computing their result from their arguments alone
+// and changing nothing, so that a generator can make them a Rust `const fn`, memoize them, or
+// reorder their calls. A function is impure when it writes a variable declared outside it,
+// writes through `self` or an argument or calls a mutating method on them, does I/O, reads the
+// clock or a random source, calls a library function whose semantics list side effects, or
+// calls an impure function. Calls to external functions none of that covers, and that are not
+// known to be pure, leave a function's purity unknown; so do calls to functions whose purity
+// is unknown. Library semantics come from the caller, by function or pattern name, so the
+// effects the library abstraction layer records for its patterns can be passed in.
+use crate::calls::{CallGraph, Callable, CallableKind, callee_text};
+use crate::cfg::{has_tag, original_text, significant};
+use crate::dataflow::{ASSIGNMENT_TAGS, DataFlow, DefinitionKind, UPDATE_TAGS, shared_names};
+use crate::globals::declared_names;
+use coalesce_core::{ExpressionType, NodeType, UIRNode, Walk, walk, walk_mut};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+const RECEIVERS: &[&str] = &["self", "this", "Me", "cls"];
+const IO_CALLS: &[&str] = &["print", "input", "open", "printf", "puts", "putchar", "scanf", "getchar", "gets", "fgets", "fputs", "fprintf", "fopen", "fclose", "fread", "fwrite", "fflush", "perror", "exit", "abort", "system", "alert", "prompt", "fetch", "readline", "cat", "sleep", "println!", "print!", "eprintln!", "eprint!", "panic!"];
+const IO_PREFIXES: &[&str] = &["console.", "System.out.", "System.err.", "System.in.", "Console.", "fmt.Print", "fmt.Fprint", "fmt.Scan", "os.", "sys.", "io.", "fs.", "document.", "window.", "localStorage.", "sessionStorage.", "process.", "requests.", "httpx.", "http.", "socket.", "shutil.", "subprocess.", "logging.", "logger.", "log.", "File.", "Files.", "Directory.", "std::io::", "std::fs::"];
+const NONDETERMINISTIC_CALLS: &[&str] = &["rand", "srand", "random", "time", "clock", "getpid", "uuid4", "Math.random", "Date.now", "DateTime.Now", "System.currentTimeMillis", "System.nanoTime"];
+const NONDETERMINISTIC_PREFIXES: &[&str] = &["random.", "time.", "datetime.", "uuid.", "secrets."];
+const PURE_CALLS: &[&str] = &[
+    "len", "abs", "min", "max", "round", "sum", "sorted", "reversed", "str", "int", "float", "bool", "tuple", "list", "dict", "set", "frozenset",
+    "range", "enumerate", "zip", "map", "filter", "isinstance", "repr", "ord", "chr", "divmod", "pow", "any", "all",
+    "strlen", "strcmp", "strncmp", "memcmp", "sqrt", "sin", "cos", "tan", "exp", "floor", "ceil", "fabs", "toupper", "tolower", "isdigit", "isalpha", "isspace", "atoi", "atof",
+    "parseInt", "parseFloat", "isNaN", "String", "Number", "Boolean", "Array.isArray", "JSON.stringify", "JSON.parse", "Object.keys", "Object.values", "Object.entries",
+];
+const PURE_PREFIXES: &[&str] = &["Math.", "math.", "String.", "Number.", "Integer.", "Character.", "operator.", "itertools."];
+/// Methods that change the collection or object they are called on
+const MUTATING_METHODS: &[&str] = &[
+    "append", "extend", "insert", "remove", "pop", "clear", "update", "add", "discard", "sort", "reverse", "setdefault", "popitem",
+    "push", "shift", "unshift", "splice", "fill", "set", "delete", "put", "push_back", "pop_back", "emplace_back", "erase",
+    "Add", "AddRange", "Remove", "Clear", "Insert",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurityKind {
+    Pure,
+    /// Calls something that may have side effects
+    Unknown,
+    Impure,
+}
+
+impl PurityKind {
+    /// The value of the `purity` annotation
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PurityKind::Pure => "pure",
+            PurityKind::Unknown => "unknown",
+            PurityKind::Impure => "impure",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectKind {
+    Io,
+    /// Writing state that outlives the call, or an argument
+    Mutation,
+    /// Reading the clock or a random source
+    Nondeterminism,
+    /// A library call whose semantics list side effects
+    Library,
+    /// A call to a function of the program that is impure or of unknown purity
+    Call,
+    /// A call to an external function nothing is known about
+    UnknownCall,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Effect {
+    pub kind: EffectKind,
+    pub description: String,
+    pub line: Option<u32>,
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{} (line {})", self.description, line),
+            None => write!(f, "{}", self.description),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionPurity {
+    pub function: Callable,
+    pub purity: PurityKind,
+    /// What makes the function impure or its purity unknown, in source order
+    pub effects: Vec<Effect>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Purity {
+    functions: Vec<FunctionPurity>,
+}
+
+impl Purity {
+    /// The purity of the functions and methods of modules named by their file stem, given the
+    /// side effects of library functions by name; a name mapped to none is a pure function
+    pub fn analyze<'a>(modules: impl IntoIterator<Item = (&'a str, &'a UIRNode)>, library_effects: &HashMap<String, Vec<String>>) -> Self {
+        let modules: Vec<(&str, &UIRNode)> = modules.into_iter().collect();
+        let graph = CallGraph::from_modules(modules.iter().copied());
+        let recorded: HashSet<&str> = graph.calls().iter().map(|call| call.node_id.as_str()).collect();
+        let mut nodes: HashMap<&str, &UIRNode> = HashMap::new();
+        for (_, root) in &modules {
+            collect_functions(root, &mut nodes);
+        }
+        
+        let mut functions = Vec::new();
+        for callable in graph.callables().iter().filter(|callable| matches!(callable.kind, CallableKind::Function | CallableKind::Method)) {
+            let Some(node) = callable.node_id.as_deref().and_then(|id| nodes.get(id)) else {
+                continue;
+            };
+            let mut body = Body::of(node, &recorded);
+            for child in &node.children {
+                body.visit(child);
+            }
+            let mut effects = body.effects;
+            for call in graph.calls().iter().filter(|call| call.caller == callable.id) {
+                let Some(callee) = graph.callable(&call.callee).filter(|callee| callee.kind == CallableKind::External) else {
+                    continue;
+                };
+                if let Some((kind, description)) = external_effect(&callee.name, library_effects) {
+                    effects.push(Effect { kind, description, line: call.line });
+                }
+            }
+            effects.sort_by_key(|effect| effect.line);
+            let purity = effects.iter().map(|effect| match effect.kind {
+                EffectKind::UnknownCall => PurityKind::Unknown,
+                _ => PurityKind::Impure,
+            }).max().unwrap_or(PurityKind::Pure);
+            functions.push(FunctionPurity { function: callable.clone(), purity, effects });
+        }
+        
+        // Callers take on the impurity of what they call, until nothing changes
+        let index: HashMap<&str, usize> = functions.iter().enumerate().map(|(index, purity)| (purity.function.id.as_str(), index)).collect();
+        let calls: Vec<(usize, usize, Option<u32>)> = graph.calls().iter()
+            .filter_map(|call| Some((*index.get(call.caller.as_str())?, *index.get(call.callee.as_str())?, call.line)))
+            .filter(|(caller, callee, _)| caller != callee)
+            .collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &(caller, callee, _) in &calls {
+                if functions[callee].purity > functions[caller].purity {
+                    functions[caller].purity = functions[callee].purity;
+                    changed = true;
+                }
+            }
+        }
+        for &(caller, callee, line) in &calls {
+            let description = match functions[callee].purity {
+                PurityKind::Pure => continue,
+                PurityKind::Unknown => format!("calls `{}`, whose effects are unknown", functions[callee].function.id),
+                PurityKind::Impure => format!("calls impure `{}`", functions[callee].function.id),
+            };
+            if !functions[caller].effects.iter().any(|effect| effect.description == description) {
+                functions[caller].effects.push(Effect { kind: EffectKind::Call, description, line });
+            }
+        }
+        for function in &mut functions {
+            function.effects.sort_by_key(|effect| effect.line);
+        }
+        Purity { functions }
+    }
+    
+    pub fn functions(&self) -> &[FunctionPurity] {
+        &self.functions
+    }
+    
+    /// The purity of the callable with id `id`
+    pub fn of(&self, id: &str) -> Option<&FunctionPurity> {
+        self.functions.iter().find(|function| function.function.id == id)
+    }
+    
+    pub fn of_kind(&self, purity: PurityKind) -> impl Iterator<Item = &FunctionPurity> {
+        self.functions.iter().filter(move |function| function.purity == purity)
+    }
+    
+    /// Mark the functions of the module named `name` with a `purity` annotation, `pure`,
+    /// `impure` or `unknown`, and what makes them so in `side_effects`
+    pub fn annotate(&self, name: &str, module: &mut UIRNode) {
+        let marks: HashMap<&str, &FunctionPurity> = self.functions.iter()
+            .filter(|function| function.function.module.as_deref() == Some(name))
+            .filter_map(|function| Some((function.function.node_id.as_deref()?, function)))
+            .collect();
+        walk_mut(module, &mut |node: &mut UIRNode| {
+            if let Some(function) = marks.get(node.id.as_str()) {
+                node.metadata.annotations.insert("purity".to_string(), Value::String(function.purity.as_str().to_string()));
+                if !function.effects.is_empty() {
+                    let effects: Vec<String> = function.effects.iter().map(|effect| effect.description.clone()).collect();
+                    node.metadata.annotations.insert("side_effects".to_string(), json!(effects));
+                }
+            }
+            Walk::Continue
+        });
+    }
+}
+
+/// The writes and mutating method calls of one function body, outside functions and classes
+/// nested in it
+struct Body<'a> {
+    parameters: HashSet<String>,
+    locals: HashSet<String>,
+    /// C `static` locals, which keep their value between calls
+    statics: HashSet<String>,
+    /// Calls the call graph records, classified from their callee
+    recorded: &'a HashSet<&'a str>,
+    effects: Vec<Effect>,
+}
+
+impl<'a> Body<'a> {
+    fn of(function: &UIRNode, recorded: &'a HashSet<&'a str>) -> Self {
+        let flow = DataFlow::analyze(function);
+        let mut statics = HashSet::new();
+        walk(function, &mut |node: &UIRNode| {
+            if has_tag(node, &["declaration"]) && node.children.iter().any(|child| has_tag(child, &["storage_class_specifier"]) && original_text(child) == "static") {
+                statics.extend(declared_names(node));
+            }
+            Walk::Continue
+        });
+        let outliving: HashSet<String> = shared_names(function).into_iter().chain(statics.iter().cloned()).collect();
+        let defined = |kind: fn(&DefinitionKind) -> bool| -> HashSet<String> {
+            flow.definitions().iter()
+                .filter(|definition| kind(&definition.kind) && !outliving.contains(&definition.variable))
+                .map(|definition| definition.variable.clone())
+                .collect()
+        };
+        Body {
+            parameters: defined(|kind| *kind == DefinitionKind::Parameter),
+            locals: defined(|kind| matches!(kind, DefinitionKind::Declaration { .. })),
+            statics,
+            recorded,
+            effects: Vec::new(),
+        }
+    }
+    
+    fn visit(&mut self, node: &UIRNode) {
+        if matches!(node.node_type, NodeType::Function | NodeType::Class) {
+            return;
+        }
+        let line = node.source_location.as_ref().map(|location| location.start_line);
+        let assignment = node.node_type == NodeType::Expression(ExpressionType::Assignment) || has_tag(node, ASSIGNMENT_TAGS);
+        let update = has_tag(node, UPDATE_TAGS)
+            || (has_tag(node, &["postfix_unary_expression", "prefix_unary_expression"]) && node.children.iter().any(|child| matches!(original_text(child), "++" | "--")));
+        if assignment || update {
+            if let Some(target) = significant(node).first() {
+                for target in original_text(target).split(',') {
+                    self.write(target.trim(), line);
+                }
+            }
+        }
+        if node.node_type == NodeType::Expression(ExpressionType::FunctionCall) && !self.recorded.contains(node.id.as_str()) {
+            if let Some((receiver, method)) = callee_text(node).and_then(|(_, text)| text.rsplit_once('.')) {
+                if MUTATING_METHODS.contains(&method) {
+                    self.mutate(receiver, method, line);
+                }
+            }
+        }
+        for child in &node.children {
+            self.visit(child);
+        }
+    }
+    
+    fn write(&mut self, target: &str, line: Option<u32>) {
+        let root = root(target);
+        if root.is_empty() || (root == target && (self.locals.contains(root) || self.parameters.contains(root))) {
+            return;
+        }
+        let description = if self.statics.contains(target) {
+            format!("writes static local `{}`", target)
+        } else if root == target {
+            format!("writes `{}`, declared outside the function", target)
+        } else if RECEIVERS.contains(&root) {
+            format!("writes `{}`", target)
+        } else if self.parameters.contains(root) {
+            format!("writes `{}` of an argument", target)
+        } else if self.locals.contains(root) {
+            return;
+        } else {
+            format!("writes `{}`, declared outside the function", target)
+        };
+        self.effects.push(Effect { kind: EffectKind::Mutation, description, line });
+    }
+    
+    fn mutate(&mut self, receiver: &str, method: &str, line: Option<u32>) {
+        let root = root(receiver);
+        let description = if RECEIVERS.contains(&root) {
+            format!("mutates `{}` with `{}`", receiver, method)
+        } else if self.parameters.contains(root) {
+            format!("mutates argument `{}` with `{}`", receiver, method)
+        } else if self.locals.contains(root) || root.is_empty() {
+            return;
+        } else {
+            format!("mutates `{}` with `{}`, declared outside the function", receiver, method)
+        };
+        self.effects.push(Effect { kind: EffectKind::Mutation, description, line });
+    }
+}
+
+/// Function nodes by id, leaving out C's declarators
+fn collect_functions<'a>(node: &'a UIRNode, functions: &mut HashMap<&'a str, &'a UIRNode>) {
+    if node.node_type == NodeType::Function && !has_tag(node, &["function_declarator"]) {
+        functions.insert(node.id.as_str(), node);
+    }
+    for child in &node.children {
+        collect_functions(child, functions);
+    }
+}
+
+/// The variable an assignment target or receiver starts from: `p` of `*p`, `p->x` and `p[0].x`
+fn root(target: &str) -> &str {
+    let target = target.trim_start_matches(['*', '&', '(', ' ']);
+    let end = target.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$')).unwrap_or(target.len());
+    &target[..end]
+}
+
+/// The effect of calling the external function `name`, none for a pure one
+fn external_effect(name: &str, library_effects: &HashMap<String, Vec<String>>) -> Option<(EffectKind, String)> {
+    let last = name.rsplit(['.', ':']).next().unwrap_or(name);
+    if let Some(effects) = library_effects.get(name).or_else(|| library_effects.get(last)) {
+        return (!effects.is_empty()).then(|| (EffectKind::Library, format!("calls `{}`, with side effects {}", name, effects.join(", "))));
+    }
+    let known = |names: &[&str], prefixes: &[&str]| names.contains(&name) || prefixes.iter().any(|prefix| name.starts_with(prefix));
+    if known(NONDETERMINISTIC_CALLS, NONDETERMINISTIC_PREFIXES) {
+        Some((EffectKind::Nondeterminism, format!("calls nondeterministic `{}`", name)))
+    } else if known(IO_CALLS, IO_PREFIXES) {
+        Some((EffectKind::Io, format!("does I/O with `{}`", name)))
+    } else if known(PURE_CALLS, PURE_PREFIXES) {
+        None
+    } else {
+        Some((EffectKind::UnknownCall, format!("calls `{}`, whose effects are unknown", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::{CParser, PythonParser};
+    
+    fn purities(purity: &Purity) -> Vec<(&str, PurityKind)> {
+        purity.functions().iter().map(|function| (function.function.name.as_str(), function.purity)).collect()
+    }
+    
+    #[test]
+    fn test_python_purity_and_propagation() {
+        let source = "import random\n\ncount = 0\n\ndef square(x):\n    total = x * x\n    return abs(total)\n\ndef bump():\n    global count\n    count += 1\n\ndef report(x):\n    print(square(x))\n\ndef roll():\n    return random.randint(1, 6)\n\ndef collect(items, x):\n    items.append(x)\n\ndef build(x):\n    result = []\n    result.append(square(x))\n    return result\n\ndef outer(x):\n    return report(x)\n\ndef vague(x):\n    return frobnicate(x)\n\ndef hooked():\n    useEffect(None)\n\nclass Counter:\n    def inc(self):\n        self.n += 1\n";
+        let mut module = PythonParser::new().unwrap().parse(source).unwrap();
+        let library = HashMap::from([("useEffect".to_string(), vec!["dom_mutation".to_string()])]);
+        let purity = Purity::analyze([("module", &module)], &library);
+        
+        assert_eq!(purities(&purity), [
+            ("square", PurityKind::Pure),
+            ("bump", PurityKind::Impure),
+            ("report", PurityKind::Impure),
+            ("roll", PurityKind::Impure),
+            ("collect", PurityKind::Impure),
+            ("build", PurityKind::Pure),
+            ("outer", PurityKind::Impure),
+            ("vague", PurityKind::Unknown),
+            ("hooked", PurityKind::Impure),
+            ("Counter.inc", PurityKind::Impure),
+        ]);
+        let descriptions = |id: &str| -> Vec<String> { purity.of(id).unwrap().effects.iter().map(ToString::to_string).collect() };
+        assert_eq!(descriptions("module::bump"), ["writes `count`, declared outside the function (line 11)"]);
+        assert_eq!(descriptions("module::report"), ["does I/O with `print` (line 14)"]);
+        assert_eq!(descriptions("module::collect"), ["mutates argument `items` with `append` (line 20)"]);
+        assert_eq!(descriptions("module::outer"), ["calls impure `module::report` (line 28)"]);
+        assert_eq!(purity.of("module::hooked").unwrap().effects[0].kind, EffectKind::Library);
+        
+        purity.annotate("module", &mut module);
+        let square = module.children.iter().find(|child| child.name.as_deref() == Some("square")).unwrap();
+        assert_eq!(square.metadata.annotations["purity"], "pure");
+        let roll = module.children.iter().find(|child| child.name.as_deref() == Some("roll")).unwrap();
+        assert_eq!(roll.metadata.annotations["side_effects"], json!(["calls nondeterministic `random.randint`"]));
+    }
+    
+    #[test]
+    fn test_c_statics_pointers_and_io() {
+        let source = "int total;\nint add(int a, int b) { int sum = a + b; return sum; }\nint next(void) { static int id = 0; return ++id; }\nvoid store(int *out, int value) { *out = value; }\nvoid accumulate(int value) { total += value; }\nvoid show(int value) { printf(\"%d\\n\", add(value, 1)); }\n";
+        let module = CParser::new().unwrap().parse(source).unwrap();
+        let purity = Purity::analyze([("module", &module)], &HashMap::new());
+        assert_eq!(purities(&purity), [
+            ("add", PurityKind::Pure),
+            ("next", PurityKind::Impure),
+            ("store", PurityKind::Impure),
+            ("accumulate", PurityKind::Impure),
+            ("show", PurityKind::Impure),
+        ]);
+        assert_eq!(purity.of("module::next").unwrap().effects[0].description, "writes static local `id`");
+        assert_eq!(purity.of("module::store").unwrap().effects[0].description, "writes `*out` of an argument");
+    }
+}
@@ -0,0 +1,242 @@
+// Recursion and cycles
+//
+// `Recursion` finds the functions that call themselves, directly or through one another, and
+// the modules that depend on each other in a cycle: what a target needs to know before turning
+// recursion into iteration, or mapping COBOL's `PERFORM` onto structured calls. Both come from
+// the strongly connected components of a graph. In the call graph, a function alone in its
+// component is recursive when it calls itself, and the functions of a larger component are
+// mutually recursive. A module depends on another when it imports it, the last segment of the
+// import path naming the other's file stem, or calls a function the other declares.
+use crate::calls::{CallGraph, Callable, CallableKind, module_stem};
+use coalesce_core::{NodeType, UIRNode, Walk, walk, walk_mut};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecursionKind {
+    /// A function calling itself
+    Direct,
+    /// Functions calling each other in a cycle
+    Mutual,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecursiveGroup {
+    pub kind: RecursionKind,
+    /// In the order the call graph declares them
+    pub functions: Vec<Callable>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Recursion {
+    groups: Vec<RecursiveGroup>,
+    /// Modules that depend on each other, by name
+    module_cycles: Vec<Vec<String>>,
+}
+
+impl Recursion {
+    /// Recursion within and cycles between modules named by their file stem
+    pub fn find<'a>(modules: impl IntoIterator<Item = (&'a str, &'a UIRNode)>) -> Self {
+        let modules: Vec<(&str, &UIRNode)> = modules.into_iter().collect();
+        let graph = CallGraph::from_modules(modules.iter().copied());
+        let callables = graph.callables();
+        let index: HashMap<&str, usize> = callables.iter().enumerate().map(|(index, callable)| (callable.id.as_str(), index)).collect();
+        let module_index: HashMap<&str, usize> = modules.iter().enumerate().map(|(index, (name, _))| (*name, index)).collect();
+        
+        let mut calls = vec![BTreeSet::new(); callables.len()];
+        let mut depends = vec![BTreeSet::new(); modules.len()];
+        for call in graph.calls() {
+            let (Some(&caller), Some(&callee)) = (index.get(call.caller.as_str()), index.get(call.callee.as_str())) else {
+                continue;
+            };
+            calls[caller].insert(callee);
+            let owner = |callable: usize| callables[callable].module.as_deref().and_then(|module| module_index.get(module).copied());
+            if let (Some(from), Some(to)) = (owner(caller), owner(callee)) {
+                if from != to {
+                    depends[from].insert(to);
+                }
+            }
+        }
+        for (from, (_, root)) in modules.iter().enumerate() {
+            walk(root, &mut |node: &UIRNode| {
+                if let NodeType::Import { path, .. } = &node.node_type {
+                    let stem = path.split(['/', '.', ':']).rfind(|segment| !segment.is_empty());
+                    let named = modules.iter().position(|(name, _)| Some(module_stem(name)) == stem);
+                    if let Some(to) = named.filter(|&to| to != from) {
+                        depends[from].insert(to);
+                    }
+                }
+                Walk::Continue
+            });
+        }
+        
+        let mut groups = Vec::new();
+        for component in components(&calls) {
+            let kind = match component.as_slice() {
+                [single] if calls[*single].contains(single) => RecursionKind::Direct,
+                [_] => continue,
+                _ => RecursionKind::Mutual,
+            };
+            let functions: Vec<Callable> = component.iter().map(|&member| callables[member].clone()).collect();
+            if functions.iter().all(|callable| callable.kind != CallableKind::Module) {
+                groups.push(RecursiveGroup { kind, functions });
+            }
+        }
+        groups.sort_by_key(|group| group.functions.first().and_then(|callable| index.get(callable.id.as_str()).copied()));
+        let mut module_cycles: Vec<Vec<String>> = components(&depends).into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| component.into_iter().map(|module| modules[module].0.to_string()).collect())
+            .collect();
+        module_cycles.sort();
+        Recursion { groups, module_cycles }
+    }
+    
+    pub fn groups(&self) -> &[RecursiveGroup] {
+        &self.groups
+    }
+    
+    pub fn module_cycles(&self) -> &[Vec<String>] {
+        &self.module_cycles
+    }
+    
+    /// The group of the callable with id `id`, if it is recursive
+    pub fn group_of(&self, id: &str) -> Option<&RecursiveGroup> {
+        self.groups.iter().find(|group| group.functions.iter().any(|callable| callable.id == id))
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty() && self.module_cycles.is_empty()
+    }
+    
+    /// Mark the recursive functions of the module named `name` with a `recursion` annotation,
+    /// `direct` or `mutual`, and the ids of the functions each calls back through in
+    /// `recursive_with`; a module in a dependency cycle lists the cycle in `module_cycle`
+    pub fn annotate(&self, name: &str, module: &mut UIRNode) {
+        let mut marks: HashMap<&str, &RecursiveGroup> = HashMap::new();
+        for group in &self.groups {
+            for callable in group.functions.iter().filter(|callable| callable.module.as_deref() == Some(name)) {
+                if let Some(node_id) = &callable.node_id {
+                    marks.insert(node_id, group);
+                }
+            }
+        }
+        walk_mut(module, &mut |node: &mut UIRNode| {
+            if let Some(group) = marks.get(node.id.as_str()) {
+                let kind = serde_json::to_value(group.kind).unwrap_or(Value::Null);
+                node.metadata.annotations.insert("recursion".to_string(), kind);
+                if group.kind == RecursionKind::Mutual {
+                    let others: Vec<&str> = group.functions.iter()
+                        .filter(|callable| callable.node_id.as_deref() != Some(node.id.as_str()))
+                        .map(|callable| callable.id.as_str())
+                        .collect();
+                    node.metadata.annotations.insert("recursive_with".to_string(), json!(others));
+                }
+            }
+            Walk::Continue
+        });
+        if let Some(cycle) = self.module_cycles.iter().find(|cycle| cycle.iter().any(|member| member == name)) {
+            module.metadata.annotations.insert("module_cycle".to_string(), json!(cycle));
+        }
+    }
+}
+
+/// The strongly connected components of a graph given by each node's successors, by Tarjan's
+/// algorithm, with each component's nodes in ascending order
+fn components(successors: &[BTreeSet<usize>]) -> Vec<Vec<usize>> {
+    struct State {
+        next: usize,
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        found: Vec<Vec<usize>>,
+    }
+    fn connect(node: usize, successors: &[BTreeSet<usize>], state: &mut State) {
+        state.index[node] = Some(state.next);
+        state.low[node] = state.next;
+        state.next += 1;
+        state.stack.push(node);
+        state.on_stack[node] = true;
+        for &next in &successors[node] {
+            match state.index[next] {
+                None => {
+                    connect(next, successors, state);
+                    state.low[node] = state.low[node].min(state.low[next]);
+                }
+                Some(index) if state.on_stack[next] => state.low[node] = state.low[node].min(index),
+                Some(_) => {}
+            }
+        }
+        if Some(state.low[node]) == state.index[node] {
+            let mut component = Vec::new();
+            while let Some(member) = state.stack.pop() {
+                state.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            component.sort_unstable();
+            state.found.push(component);
+        }
+    }
+    
+    let count = successors.len();
+    let mut state = State { next: 0, index: vec![None; count], low: vec![0; count], stack: Vec::new(), on_stack: vec![false; count], found: Vec::new() };
+    for node in 0..count {
+        if state.index[node].is_none() {
+            connect(node, successors, &mut state);
+        }
+    }
+    state.found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::PythonParser;
+    
+    #[test]
+    fn test_python_direct_and_mutual_recursion() {
+        let source = "def factorial(n):\n    return 1 if n < 2 else n * factorial(n - 1)\n\ndef is_even(n):\n    return n == 0 or is_odd(n - 1)\n\ndef is_odd(n):\n    return n != 0 and is_even(n - 1)\n\nclass Tree:\n    def size(self):\n        return 1 + sum(child.size() for child in self.children)\n    def depth(self):\n        return self.depth_below(0)\n    def depth_below(self, level):\n        return self.depth() + level\n\nprint(factorial(5), is_even(4))\n";
+        let parser = PythonParser::new().unwrap();
+        let mut module = parser.parse(source).unwrap();
+        let recursion = Recursion::find([("module", &module)]);
+        
+        let groups: Vec<(RecursionKind, Vec<&str>)> = recursion.groups().iter()
+            .map(|group| (group.kind, group.functions.iter().map(|callable| callable.name.as_str()).collect()))
+            .collect();
+        assert_eq!(groups, [
+            (RecursionKind::Direct, vec!["factorial"]),
+            (RecursionKind::Mutual, vec!["is_even", "is_odd"]),
+            (RecursionKind::Mutual, vec!["Tree.depth", "Tree.depth_below"]),
+        ]);
+        assert!(recursion.module_cycles().is_empty());
+        
+        recursion.annotate("module", &mut module);
+        let is_odd = module.children.iter().find(|child| child.name.as_deref() == Some("is_odd")).unwrap();
+        assert_eq!(is_odd.metadata.annotations["recursion"], "mutual");
+        assert_eq!(is_odd.metadata.annotations["recursive_with"], json!(["module::is_even"]));
+    }
+    
+    #[test]
+    fn test_module_cycles_through_imports_and_calls() {
+        let parser = PythonParser::new().unwrap();
+        let orders = parser.parse("from billing import charge\n\ndef place(order):\n    return charge(order)\n").unwrap();
+        let billing = parser.parse("import orders\n\ndef charge(order):\n    return order.total\n\ndef refund(order):\n    return orders.place(order)\n").unwrap();
+        let report = parser.parse("from orders import place\n\nplace(None)\n").unwrap();
+        let mut modules = [("src/orders.py", orders), ("src/billing.py", billing), ("src/report.py", report)];
+        let recursion = Recursion::find(modules.iter().map(|(name, uir)| (*name, uir)));
+        
+        assert_eq!(recursion.module_cycles(), [vec!["src/orders.py".to_string(), "src/billing.py".to_string()]]);
+        let (name, report) = &mut modules[2];
+        recursion.annotate(name, report);
+        assert!(!report.metadata.annotations.contains_key("module_cycle"));
+        let (name, orders) = &mut modules[0];
+        recursion.annotate(name, orders);
+        assert_eq!(orders.metadata.annotations["module_cycle"], json!(["src/orders.py", "src/billing.py"]));
+    }
+}
@@ -0,0 +1,170 @@
+// Identifier resolution
+//
+// Parsers leave identifiers as bare names. `resolve` looks each one up in the module's symbol
+// table from the scope it appears in and records the id of the declaring node (a parameter,
+// local or global variable, field, function or import) as the reference's `resolved_id`
+// annotation, so a reference keeps pointing at its declaration through renaming and
+// serialization, and id reassignment keeps it up to date. Assignments that rebind an existing
+// variable are resolved the same way. Names that resolve to nothing are builtins or globals
+// of other modules and libraries, and are left unannotated.
+//
+// A block scope covers its whole control flow node, header included, so a loop condition
+// sits in the same scope as the body's declarations. Block-scoped names are only usable
+// after their declaration, so a block's symbols declared later than a reference are passed
+// over in favour of the enclosing scopes.
+use crate::symbols::{ScopeKind, SymbolId, SymbolTable};
+use coalesce_core::{ExpressionType, NodeType, UIRNode, Walk, walk, walk_mut};
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub struct Resolution {
+    pub symbols: SymbolTable,
+    references: HashMap<SymbolId, Vec<String>>,
+    unresolved: Vec<String>,
+}
+
+impl Resolution {
+    /// Ids of the nodes referring to `symbol`, other than its declaration, in document order
+    pub fn references(&self, symbol: SymbolId) -> &[String] {
+        self.references.get(&symbol).map(Vec::as_slice).unwrap_or_default()
+    }
+    
+    /// Ids of identifiers that name nothing the module declares
+    pub fn unresolved(&self) -> &[String] {
+        &self.unresolved
+    }
+}
+
+/// Bind every identifier in `module` to its declaration, annotating it with `resolved_id`
+pub fn resolve(module: &mut UIRNode) -> Resolution {
+    let symbols = SymbolTable::build(module);
+    let mut references: HashMap<SymbolId, Vec<String>> = HashMap::new();
+    let mut unresolved = Vec::new();
+    let mut positions = HashMap::new();
+    walk(&*module, &mut |node: &UIRNode| {
+        let position = positions.len();
+        positions.insert(node.id.clone(), position);
+        Walk::Continue
+    });
+    let position = |id: &str| positions.get(id).copied().unwrap_or_default();
+    walk_mut(module, &mut |node: &mut UIRNode| {
+        let Some(name) = reference_name(node) else {
+            return Walk::Continue;
+        };
+        let symbol = symbols.binding(&node.id).or_else(|| {
+            let mut scope = symbols.enclosing_scope(&node.id)?;
+            loop {
+                let symbol = symbols.lookup(scope, name)?;
+                let declared_in = symbols.scope(symbols.symbol(symbol).scope);
+                if declared_in.kind != ScopeKind::Block || position(&symbols.symbol(symbol).node_id) < position(&node.id) {
+                    return Some(symbol);
+                }
+                scope = declared_in.parent?;
+            }
+        });
+        match symbol {
+            Some(symbol) if symbols.symbol(symbol).node_id != node.id => {
+                let declaration = symbols.symbol(symbol).node_id.clone();
+                node.metadata.annotations.insert("resolved_id".to_string(), Value::String(declaration));
+                references.entry(symbol).or_default().push(node.id.clone());
+            }
+            Some(_) => {}
+            None => unresolved.push(node.id.clone()),
+        }
+        Walk::Continue
+    });
+    if !module.metadata.semantic_tags.iter().any(|t| t == "identifiers_resolved") {
+        module.metadata.semantic_tags.push("identifiers_resolved".to_string());
+    }
+    module.debug_validate();
+    Resolution { symbols, references, unresolved }
+}
+
+/// The name a node refers to or binds: plain identifiers, callees named on the call, and
+/// assignment targets, but not member accesses such as `a.b`
+fn reference_name(node: &UIRNode) -> Option<&str> {
+    let name = node.name.as_deref().filter(|name| !name.contains('.'))?;
+    match node.node_type {
+        NodeType::Expression(ExpressionType::Variable) if node.children.is_empty() => Some(name),
+        NodeType::Expression(ExpressionType::FunctionCall) | NodeType::Variable => Some(name),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::{GeneratorConfig, NamingConvention, Parser};
+    use coalesce_parser::{JavaScriptParser, PythonParser};
+    
+    fn resolved_id(node: &UIRNode) -> Option<&str> {
+        node.metadata.annotations.get("resolved_id").and_then(|v| v.as_str())
+    }
+    
+    #[test]
+    fn test_python_references_resolve_to_declarations() {
+        let parser = PythonParser::new().unwrap();
+        let source = "import os\nLIMIT = 3\ndef f(n):\n    total = 0\n    total = total + n + LIMIT\n    print(os.sep)\n    return total\n";
+        
+        let mut uir = parser.parse(source).unwrap();
+        let resolution = resolve(&mut uir);
+        let function = &uir.children[2];
+        let parameter = &function.children[0];
+        let declaration = uir.select("Function Variable[name=total]").unwrap()[0].id.clone();
+        
+        let totals = uir.select("Expression.Variable[name=total]").unwrap();
+        assert_eq!(totals.len(), 2);
+        assert!(totals.iter().all(|node| resolved_id(node) == Some(declaration.as_str())));
+        let rebinding = uir.select("Function Variable[name=total]").unwrap()[1];
+        assert_eq!(resolved_id(rebinding), Some(declaration.as_str()));
+        
+        let n = uir.select("Expression.Variable[name=n]").unwrap()[0];
+        assert_eq!(resolved_id(n), Some(parameter.id.as_str()));
+        let limit = uir.select("Expression.Variable[name=LIMIT]").unwrap()[0];
+        assert_eq!(resolved_id(limit), Some(uir.children[1].children[0].children[0].id.as_str()));
+        
+        // Builtins resolve to nothing
+        let print = uir.select("Expression.Variable[name=print]").unwrap()[0];
+        assert!(resolved_id(print).is_none());
+        assert!(resolution.unresolved().contains(&print.id));
+        
+        let symbol = resolution.symbols.binding(&declaration).unwrap();
+        assert_eq!(resolution.references(symbol).len(), 3);
+        assert!(uir.metadata.semantic_tags.contains(&"identifiers_resolved".to_string()));
+    }
+    
+    #[test]
+    fn test_javascript_shadowing_resolves_innermost() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "let x = 1;\nfunction f(x) { for (let i = 0; i < x; i++) { const x = i; g(x); } return x; }\nfunction g(v) { return x + v; }";
+        
+        let mut uir = parser.parse(source).unwrap();
+        resolve(&mut uir);
+        let global = uir.select("Module > Statement Variable[name=x]").unwrap()[0].id.clone();
+        let parameter = uir.select("Function[name=f] > Variable[name=x]").unwrap()[0].id.clone();
+        let local = uir.select("ControlFlow Variable[name=x]").unwrap()[0].id.clone();
+        
+        let references: Vec<_> = uir.select("Expression.Variable[name=x]").unwrap().into_iter()
+            .map(|node| resolved_id(node).unwrap_or_default().to_string())
+            .collect();
+        assert_eq!(references, vec![parameter.clone(), local, parameter, global]);
+        
+        let call = uir.select("Expression.Variable[name=g]").unwrap()[0];
+        assert_eq!(resolved_id(call), uir.select("Function[name=g]").unwrap().first().map(|node| node.id.as_str()));
+    }
+    
+    #[test]
+    fn test_naming_renames_only_resolved_references() {
+        let parser = PythonParser::new().unwrap();
+        let mut uir = parser.parse("def f(userName):\n    return userName\nprint(userName)\n").unwrap();
+        resolve(&mut uir);
+        let config = GeneratorConfig { naming: Some(NamingConvention::SnakeCase), ..GeneratorConfig::default() };
+        
+        let renamed = config.apply_naming(&uir);
+        let names: Vec<_> = renamed.select("Expression.Variable").unwrap().into_iter()
+            .filter_map(|node| node.name.as_deref())
+            .collect();
+        assert!(names.contains(&"user_name"));
+        assert!(names.contains(&"userName"));
+    }
+}
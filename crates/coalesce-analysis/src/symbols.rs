@@ -0,0 +1,423 @@
+// Symbol tables over UIR
+//
+// A `SymbolTable` records what a module declares and where: a tree of scopes (the module, each
+// class, function and closure, and each control-flow block in languages that scope by block)
+// and the symbols declared in each, with their kind, declared type and declaring node. Python,
+// R and Bash declare a variable by assigning to it, so only the first assignment in a scope
+// declares; later ones are further bindings of the same symbol. Bare names are looked up from
+// the innermost scope outwards, seeing class members only in languages where methods can name
+// them without `self` or `this`.
+use coalesce_core::{ControlFlowType, ExpressionType, ImportedSymbol, Language, LoopType, NodeType, UIRNode, Visibility};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct ScopeId(usize);
+
+impl ScopeId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct SymbolId(usize);
+
+impl SymbolId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ScopeKind {
+    Module,
+    Class, // Classes, interfaces and enums
+    Function, // Functions, methods and closures
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Class,
+    Interface,
+    Enum,
+    Variant,
+    Field,
+    Property,
+    Constant,
+    Parameter,
+    Variable,
+    Import,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    /// The node that opens the scope
+    pub node_id: String,
+    pub name: Option<String>,
+    pub parent: Option<ScopeId>,
+    pub children: Vec<ScopeId>,
+    /// Symbols declared directly in this scope, in declaration order
+    pub symbols: Vec<SymbolId>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The node that first declares the symbol
+    pub node_id: String,
+    pub scope: ScopeId,
+    /// The type the source declares: a variable's or field's type, a function's return type
+    pub declared_type: Option<String>,
+    pub visibility: Option<Visibility>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolTable {
+    pub language: Language,
+    scopes: Vec<Scope>,
+    symbols: Vec<Symbol>,
+    /// Every node that declares or rebinds a symbol
+    #[serde(skip)]
+    bindings: HashMap<String, SymbolId>,
+    /// Scope-opening nodes
+    #[serde(skip)]
+    opened: HashMap<String, ScopeId>,
+    /// The scope every node sits in
+    #[serde(skip)]
+    enclosing: HashMap<String, ScopeId>,
+}
+
+impl SymbolTable {
+    /// The scopes and declarations of `module` and everything in it
+    pub fn build(module: &UIRNode) -> Self {
+        let mut table = Self {
+            language: module.metadata.source_language.clone(),
+            scopes: Vec::new(),
+            symbols: Vec::new(),
+            bindings: HashMap::new(),
+            opened: HashMap::new(),
+            enclosing: HashMap::new(),
+        };
+        let root = table.open(ScopeKind::Module, module, None);
+        for child in &module.children {
+            table.visit(child, module, root);
+        }
+        table
+    }
+    
+    pub fn root(&self) -> ScopeId {
+        ScopeId(0)
+    }
+    
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0]
+    }
+    
+    pub fn symbol(&self, id: SymbolId) -> &Symbol {
+        &self.symbols[id.0]
+    }
+    
+    pub fn scopes(&self) -> impl Iterator<Item = (ScopeId, &Scope)> {
+        self.scopes.iter().enumerate().map(|(index, scope)| (ScopeId(index), scope))
+    }
+    
+    pub fn symbols(&self) -> impl Iterator<Item = (SymbolId, &Symbol)> {
+        self.symbols.iter().enumerate().map(|(index, symbol)| (SymbolId(index), symbol))
+    }
+    
+    /// The symbol `name` refers to in `scope`, searching enclosing scopes outwards
+    pub fn lookup(&self, scope: ScopeId, name: &str) -> Option<SymbolId> {
+        let members_visible = matches!(
+            self.language,
+            Language::Cpp | Language::Java | Language::CSharp | Language::Kotlin | Language::VisualBasic
+        );
+        let mut current = Some(scope);
+        while let Some(id) = current {
+            let searched = &self.scopes[id.0];
+            if id == scope || searched.kind != ScopeKind::Class || members_visible {
+                if let Some(symbol) = self.lookup_in(id, name) {
+                    return Some(symbol);
+                }
+            }
+            current = searched.parent;
+        }
+        None
+    }
+    
+    /// The symbol `name` declared directly in `scope`
+    pub fn lookup_in(&self, scope: ScopeId, name: &str) -> Option<SymbolId> {
+        self.scopes[scope.0].symbols.iter().copied().find(|&symbol| self.symbols[symbol.0].name == name)
+    }
+    
+    /// The symbol a declaration, parameter or assignment target node binds
+    pub fn binding(&self, node_id: &str) -> Option<SymbolId> {
+        self.bindings.get(node_id).copied()
+    }
+    
+    /// The scope a class, function, closure or block node opens
+    pub fn scope_opened_by(&self, node_id: &str) -> Option<ScopeId> {
+        self.opened.get(node_id).copied()
+    }
+    
+    /// The scope a node sits in; a scope-opening node sits in its parent scope
+    pub fn enclosing_scope(&self, node_id: &str) -> Option<ScopeId> {
+        self.enclosing.get(node_id).copied()
+    }
+    
+    fn visit(&mut self, node: &UIRNode, parent: &UIRNode, scope: ScopeId) {
+        self.enclosing.insert(node.id.clone(), scope);
+        let in_class = self.scopes[scope.0].kind == ScopeKind::Class;
+        let mut inner = scope;
+        match &node.node_type {
+            NodeType::Function => {
+                let kind = if in_class { SymbolKind::Method } else { SymbolKind::Function };
+                self.declare_named(node, kind, scope, declared_type(node, "return_type"));
+                inner = self.open(ScopeKind::Function, node, Some(scope));
+            }
+            NodeType::Closure { .. } => inner = self.open(ScopeKind::Function, node, Some(scope)),
+            NodeType::Class | NodeType::Interface => {
+                let kind = if node.node_type == NodeType::Class { SymbolKind::Class } else { SymbolKind::Interface };
+                self.declare_named(node, kind, scope, None);
+                inner = self.open(ScopeKind::Class, node, Some(scope));
+            }
+            NodeType::Enum { variants } => {
+                self.declare_named(node, SymbolKind::Enum, scope, None);
+                inner = self.open(ScopeKind::Class, node, Some(scope));
+                for variant in variants {
+                    self.declare(&variant.name, SymbolKind::Variant, node, inner, None);
+                }
+            }
+            NodeType::Field { field_type, .. } => self.declare_named(node, SymbolKind::Field, scope, field_type.clone()),
+            NodeType::Property { property_type, .. } => self.declare_named(node, SymbolKind::Property, scope, property_type.clone()),
+            NodeType::Constant => self.declare_named(node, SymbolKind::Constant, scope, declared_type(node, "type")),
+            NodeType::Variable => self.declare_variable(node, parent, scope),
+            NodeType::Import { path, alias, symbols } => {
+                for name in self.imported_names(node, path, alias.as_deref(), symbols) {
+                    self.declare(&name, SymbolKind::Import, node, scope, None);
+                }
+            }
+            NodeType::ControlFlow(control_flow) => {
+                if self.scopes_by_block() {
+                    inner = self.open(ScopeKind::Block, node, Some(scope));
+                }
+                if let ControlFlowType::Catch { binding: Some(binding), exception_types } = control_flow {
+                    self.declare(binding, SymbolKind::Variable, node, inner, exception_types.first().cloned());
+                }
+                // The target of `for x in items`
+                if let (ControlFlowType::Loop(LoopType::ForEach), Some(target)) = (control_flow, node.children.first()) {
+                    if target.node_type == NodeType::Expression(ExpressionType::Variable) {
+                        if let Some(name) = &target.name {
+                            let declaring = self.declaring_scope(inner, false);
+                            self.bind(name, SymbolKind::Variable, target, declaring, None);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        for child in &node.children {
+            self.visit(child, node, inner);
+        }
+    }
+    
+    /// Parameters, local and global variables, and Python-style class attributes
+    fn declare_variable(&mut self, node: &UIRNode, parent: &UIRNode, scope: ScopeId) {
+        let Some(name) = &node.name else {
+            return;
+        };
+        let parameter = matches!(parent.node_type, NodeType::Function | NodeType::Closure { .. });
+        let kind = match self.scopes[scope.0].kind {
+            _ if parameter => SymbolKind::Parameter,
+            ScopeKind::Class => SymbolKind::Field,
+            _ => SymbolKind::Variable,
+        };
+        // Python's `x: int = 1` keeps the type on the assignment
+        let declared_type = match declared_type(node, "type") {
+            None if parent.node_type == NodeType::Expression(ExpressionType::Assignment) => declared_type(parent, "type"),
+            declared => declared,
+        };
+        let hoisted = parent.metadata.annotations.get("declaration_kind").and_then(|v| v.as_str()) == Some("var");
+        let declaring = self.declaring_scope(scope, hoisted);
+        self.bind(name, kind, node, declaring, declared_type);
+    }
+    
+    /// `scope`, or for `var` declarations the function or module around it
+    fn declaring_scope(&self, scope: ScopeId, hoisted: bool) -> ScopeId {
+        let mut current = scope;
+        while hoisted && self.scopes[current.0].kind == ScopeKind::Block {
+            current = self.scopes[current.0].parent.unwrap_or(current);
+        }
+        current
+    }
+    
+    /// Declare `name` unless assigning to an existing variable of the scope rebinds it
+    fn bind(&mut self, name: &str, kind: SymbolKind, node: &UIRNode, scope: ScopeId, declared_type: Option<String>) {
+        let existing = self.lookup_in(scope, name)
+            .filter(|&symbol| matches!(self.symbols[symbol.0].kind, SymbolKind::Variable | SymbolKind::Parameter | SymbolKind::Field));
+        match existing {
+            Some(symbol) => {
+                self.bindings.insert(node.id.clone(), symbol);
+            }
+            None => self.declare(name, kind, node, scope, declared_type),
+        }
+    }
+    
+    fn declare_named(&mut self, node: &UIRNode, kind: SymbolKind, scope: ScopeId, declared_type: Option<String>) {
+        if let Some(name) = &node.name {
+            self.declare(name, kind, node, scope, declared_type);
+        }
+    }
+    
+    fn declare(&mut self, name: &str, kind: SymbolKind, node: &UIRNode, scope: ScopeId, declared_type: Option<String>) {
+        let id = SymbolId(self.symbols.len());
+        self.symbols.push(Symbol {
+            name: name.to_string(),
+            kind,
+            node_id: node.id.clone(),
+            scope,
+            declared_type,
+            visibility: node.visibility,
+        });
+        self.scopes[scope.0].symbols.push(id);
+        self.bindings.entry(node.id.clone()).or_insert(id);
+    }
+    
+    fn open(&mut self, kind: ScopeKind, node: &UIRNode, parent: Option<ScopeId>) -> ScopeId {
+        let id = ScopeId(self.scopes.len());
+        self.scopes.push(Scope {
+            kind,
+            node_id: node.id.clone(),
+            name: node.name.clone(),
+            parent,
+            children: Vec::new(),
+            symbols: Vec::new(),
+        });
+        if let Some(parent) = parent {
+            self.scopes[parent.0].children.push(id);
+        }
+        self.opened.insert(node.id.clone(), id);
+        id
+    }
+    
+    /// Whether a variable declared inside a loop or conditional is local to it
+    fn scopes_by_block(&self) -> bool {
+        !matches!(
+            self.language,
+            Language::Python | Language::R | Language::Bash | Language::Cobol | Language::Abap | Language::Rpg
+                | Language::Erlang | Language::Elixir | Language::Assembly
+        )
+    }
+    
+    /// The names an import binds: its alias, the symbols it brings in, or the module name
+    fn imported_names(&self, node: &UIRNode, path: &str, alias: Option<&str>, symbols: &[ImportedSymbol]) -> Vec<String> {
+        if let Some(alias) = alias {
+            return vec![alias.to_string()];
+        }
+        if !symbols.is_empty() {
+            return symbols.iter()
+                .map(|symbol| symbol.alias.clone().unwrap_or_else(|| symbol.name.clone()))
+                .filter(|name| name != "*")
+                .collect();
+        }
+        let segments = || path.split(['/', '.', ':']).filter(|segment| !segment.is_empty());
+        let name = match self.language {
+            // `import os.path` binds `os`
+            Language::Python => segments().next(),
+            // Includes and side-effect imports bind nothing
+            Language::C | Language::Cpp | Language::JavaScript | Language::TypeScript => None,
+            _ => segments().next_back().or(node.name.as_deref()),
+        };
+        name.map(str::to_string).into_iter().collect()
+    }
+}
+
+fn declared_type(node: &UIRNode, key: &str) -> Option<String> {
+    node.metadata.annotations.get(key).and_then(|v| v.as_str()).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Parser;
+    use coalesce_parser::{JavaScriptParser, PythonParser};
+    
+    fn names(table: &SymbolTable, scope: ScopeId) -> Vec<(&str, SymbolKind)> {
+        table.scope(scope).symbols.iter().map(|&id| (table.symbol(id).name.as_str(), table.symbol(id).kind)).collect()
+    }
+    
+    #[test]
+    fn test_python_scopes_and_assignments() {
+        let parser = PythonParser::new().unwrap();
+        let source = "import os.path\nfrom m import a as b\nX = 1\nclass C:\n    y: int = 2\n    def m(self, z):\n        w = z\n        if z:\n            w = 3\n        return w\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let table = SymbolTable::build(&uir);
+        assert_eq!(names(&table, table.root()), vec![
+            ("os", SymbolKind::Import), ("b", SymbolKind::Import), ("X", SymbolKind::Variable), ("C", SymbolKind::Class),
+        ]);
+        
+        let class = table.scope(table.root()).children[0];
+        assert_eq!(names(&table, class), vec![("y", SymbolKind::Field), ("m", SymbolKind::Method)]);
+        let y = table.lookup_in(class, "y").unwrap();
+        assert_eq!(table.symbol(y).declared_type.as_deref(), Some("int"));
+        
+        // The assignment inside `if` rebinds the function's `w`, and class attributes are not bare names
+        let method = table.scope(class).children[0];
+        assert_eq!(names(&table, method), vec![
+            ("self", SymbolKind::Parameter), ("z", SymbolKind::Parameter), ("w", SymbolKind::Variable),
+        ]);
+        assert!(table.lookup(method, "y").is_none());
+        assert_eq!(table.lookup(method, "X"), table.lookup_in(table.root(), "X"));
+    }
+    
+    #[test]
+    fn test_javascript_block_scopes_and_hoisting() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "function f(a) { let y = a; for (let i = 0; i < y; i++) { var h = i; const z = i; } return y; }";
+        
+        let uir = parser.parse(source).unwrap();
+        let table = SymbolTable::build(&uir);
+        let function = table.scope(table.root()).children[0];
+        assert_eq!(table.scope(function).kind, ScopeKind::Function);
+        assert_eq!(names(&table, function), vec![
+            ("a", SymbolKind::Parameter), ("y", SymbolKind::Variable), ("h", SymbolKind::Variable),
+        ]);
+        
+        let block = table.scope(function).children[0];
+        assert_eq!(table.scope(block).kind, ScopeKind::Block);
+        assert_eq!(names(&table, block), vec![("i", SymbolKind::Variable), ("z", SymbolKind::Variable)]);
+        assert_eq!(table.lookup(block, "a"), table.lookup_in(function, "a"));
+        assert!(table.lookup(function, "z").is_none());
+    }
+    
+    #[test]
+    fn test_bindings_and_enclosing_scopes() {
+        let parser = PythonParser::new().unwrap();
+        let source = "def f(n):\n    total = 0\n    for i in range(n):\n        total = total + i\n    return lambda k: k + total\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let table = SymbolTable::build(&uir);
+        let function = &uir.children[0];
+        let scope = table.scope_opened_by(&function.id).unwrap();
+        let total = table.lookup_in(scope, "total").unwrap();
+        let loop_variable = table.lookup_in(scope, "i").unwrap();
+        assert_eq!(table.symbol(loop_variable).kind, SymbolKind::Variable);
+        
+        let rebound: Vec<_> = uir.select("Variable[name=total]").unwrap().into_iter()
+            .map(|node| table.binding(&node.id))
+            .collect();
+        assert_eq!(rebound, vec![Some(total), Some(total)]);
+        
+        let closure = uir.select("Closure").unwrap()[0];
+        assert_eq!(table.enclosing_scope(&closure.id), Some(scope));
+        let closure_scope = table.scope_opened_by(&closure.id).unwrap();
+        assert_eq!(names(&table, closure_scope), vec![("k", SymbolKind::Parameter)]);
+        assert_eq!(table.lookup(closure_scope, "total"), Some(total));
+    }
+}
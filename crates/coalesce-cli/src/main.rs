@@ -1,11 +1,20 @@
 use clap::{Arg, Command};
-use coalesce_core::{UIRNode, NodeType, Language, Metadata, Parser, Generator};
-use coalesce_parser::{JavaScriptParser, CParser, CppParser, CSharpParser, FSharpParser, VisualBasicParser, RustParser, GoParser, detect_language, create_parser};
-use coalesce_gen::{PythonGenerator, RustGenerator, CGenerator, GoGenerator};
-use coalesce_lal::LibraryAbstractionLayer;
+use coalesce_core::{Language, Generator, GeneratorConfig, BraceStyle, GraphFormat, IdScheme, NamingConvention, Parser, Selector, SourceEncoding, SourceLocation, UIRNode, CONFIG_FILE, project_root, read_source};
+use coalesce_parser::{create_parser, GrammarRegistry};
+use coalesce_gen::{PythonGenerator, RustGenerator, CGenerator, GoGenerator, TypeScriptGenerator, TemplateGenerator};
+use coalesce_lal::{Detection, LibraryAbstractionLayer};
+use coalesce_lal::coverage::CoverageReport;
+use coalesce_lal::lint::Severity;
+use coalesce_lal::manifest::TargetManifest;
+use coalesce_lal::packs::PatternPacks;
+use coalesce_analysis::{ApiChangeKind, ApiManifest, CallGraph, DeadCode, DeadCodeKind, FoldConfig, GlobalState, Purity, PurityKind, Recursion, RecursionKind, StateKind, analyze_data_flow, fold_constants, infer_types, measure_complexity, resolve};
 use anyhow::Result;
+use std::collections::BTreeMap;
 use std::fs;
 
+mod migrate;
+mod report;
+
 fn main() -> Result<()> {
     let matches = Command::new("coalesce")
         .version("0.1.0")
@@ -22,23 +31,231 @@ fn main() -> Result<()> {
                 .arg(
                     Arg::new("from")
                         .long("from")
-                        .help("Source language (javascript, c, cpp, csharp, fsharp, vb, rust, go)")
+                        .help("Source language (javascript, python, c, cpp, csharp, fsharp, vb, rust, go, cobol, kotlin, erlang, elixir, bash, r, abap, rpg, asm)")
                         .default_value("javascript")
                 )
                 .arg(
                     Arg::new("to")
                         .long("to")
-                        .help("Target language (python, rust, c, go)")
+                        .help("Target language (python, rust, c, go, typescript, lua)")
                         .default_value("python")
                 )
+                .arg(
+                    Arg::new("templates")
+                        .long("templates")
+                        .help("Generate with a JSON template set instead of a built-in generator")
+                )
+                .arg(
+                    Arg::new("stable-ids")
+                        .long("stable-ids")
+                        .value_name("SEED")
+                        .help("Number nodes by their path in the tree instead of source position, prefixed with SEED (default: n); inserting a node renumbers its later siblings, which --content-ids avoids")
+                        .num_args(0..=1)
+                        .default_missing_value("n")
+                )
+                .arg(
+                    Arg::new("content-ids")
+                        .long("content-ids")
+                        .value_name("SCHEME")
+                        .help("Hash node ids from their content, unchanged by whitespace edits; positional also hashes sibling order (content, positional)")
+                        .num_args(0..=1)
+                        .default_missing_value("content")
+                        .conflicts_with("stable-ids")
+                )
+                .arg(
+                    Arg::new("optimize")
+                        .long("optimize")
+                        .help("Fold constant expressions and settle constant conditions before generating (see optimize in .coalesce/config.json)")
+                        .action(clap::ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("dump-uir")
+                        .long("dump-uir")
+                        .value_name("PATH")
+                        .help("Write the transformed UIR to PATH instead of printing it")
+                )
+                .arg(
+                    Arg::new("uir-format")
+                        .long("uir-format")
+                        .help("Format for --dump-uir (json, binary)")
+                        .value_parser(["json", "binary"])
+                        .default_value("json")
+                )
+                .arg(
+                    Arg::new("uir-diff")
+                        .long("uir-diff")
+                        .help("Show how library transformation changed the parsed UIR")
+                        .action(clap::ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("ecosystem")
+                        .long("ecosystem")
+                        .help("Target library to translate to, e.g. tokio; one the target project depends on, else the language's standard library, when omitted")
+                )
+                .arg(
+                    Arg::new("target-project")
+                        .long("target-project")
+                        .value_name("DIR")
+                        .help("Project whose manifest (Cargo.toml, package.json, requirements.txt, go.mod, *.csproj) lists the libraries to translate to")
+                        .default_value(".")
+                )
+                .arg(
+                    Arg::new("manifest")
+                        .long("manifest")
+                        .value_name("DIR")
+                        .help("Write the target's manifest (Cargo.toml, requirements.txt, go.mod, package.json, *.csproj) declaring the packages the translated library calls need into DIR")
+                )
+                .arg(
+                    Arg::new("library-uir")
+                        .long("library-uir")
+                        .help("Replace library calls with the parsed UIR of their translations, for the generator to lay out, instead of annotating them")
+                        .action(clap::ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("indent-width")
+                        .long("indent-width")
+                        .help("Spaces per indentation level; target idiom when omitted")
+                        .value_parser(clap::value_parser!(usize))
+                )
+                .arg(
+                    Arg::new("tabs")
+                        .long("tabs")
+                        .help("Indent with tabs instead of spaces")
+                        .action(clap::ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("brace-style")
+                        .long("brace-style")
+                        .help("Opening brace placement (same-line, next-line)")
+                        .default_value("same-line")
+                )
+                .arg(
+                    Arg::new("naming")
+                        .long("naming")
+                        .help("Naming convention for functions and variables (snake, camel, pascal); target idiom when omitted")
+                )
+                .arg(
+                    Arg::new("max-line-length")
+                        .long("max-line-length")
+                        .help("Split calls and signatures longer than this")
+                        .value_parser(clap::value_parser!(usize))
+                )
         )
         .subcommand(
             Command::new("analyze-libs")
                 .about("Analyze library dependencies in code")
+                .arg(
+                    Arg::new("input")
+                        .help("Input code snippet or file paths")
+                        .required(true)
+                        .num_args(1..)
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("language")
+                        .long("language")
+                        .help("Source language")
+                        .default_value("javascript")
+                )
+                .arg(
+                    Arg::new("target")
+                        .long("target")
+                        .help("Report how much of the library usage translates to this language automatically")
+                )
+                .arg(
+                    Arg::new("ecosystem")
+                        .long("ecosystem")
+                        .help("Target library to translate to, e.g. requests; one the target project depends on, else the language's standard library, when omitted")
+                        .requires("target")
+                )
+                .arg(
+                    Arg::new("target-project")
+                        .long("target-project")
+                        .value_name("DIR")
+                        .help("Project whose manifest (Cargo.toml, package.json, requirements.txt, go.mod, *.csproj) lists the libraries to translate to")
+                        .default_value(".")
+                )
+                .arg(
+                    Arg::new("project")
+                        .long("project")
+                        .value_name("DIR")
+                        .help("Inventory every dependency the manifests in DIR declare (package.json, requirements.txt, go.mod, Cargo.toml, *.csproj), used or not")
+                )
+                .arg(
+                    Arg::new("encoding")
+                        .long("encoding")
+                        .help("Source file encoding (utf-8, utf-16le, utf-16be, latin1, shift-jis, ebcdic); detected when omitted")
+                )
+        )
+        .subcommand(
+            Command::new("query")
+                .about("List the UIR nodes matching a selector, e.g. \"Function[name=main] > ControlFlow\"")
+                .arg(
+                    Arg::new("selector")
+                        .help("Node selector")
+                        .required(true)
+                        .index(1)
+                )
                 .arg(
                     Arg::new("input")
                         .help("Input code snippet or file path")
                         .required(true)
+                        .index(2)
+                )
+                .arg(
+                    Arg::new("language")
+                        .long("language")
+                        .help("Source language")
+                        .default_value("javascript")
+                )
+                .arg(
+                    Arg::new("encoding")
+                        .long("encoding")
+                        .help("Source file encoding (utf-8, utf-16le, utf-16be, latin1, shift-jis, ebcdic); detected when omitted")
+                )
+        )
+        .subcommand(
+            Command::new("graph")
+                .about("Render the parsed UIR as a Graphviz or Mermaid graph")
+                .arg(
+                    Arg::new("input")
+                        .help("Input code snippet or file path")
+                        .required(true)
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("language")
+                        .long("language")
+                        .help("Source language")
+                        .default_value("javascript")
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("Graph format (dot, mermaid)")
+                        .default_value("dot")
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_name("PATH")
+                        .help("Write the graph to PATH instead of standard output")
+                )
+                .arg(
+                    Arg::new("encoding")
+                        .long("encoding")
+                        .help("Source file encoding (utf-8, utf-16le, utf-16be, latin1, shift-jis, ebcdic); detected when omitted")
+                )
+        )
+        .subcommand(
+            Command::new("calls")
+                .about("Extract the call graph of one or more modules")
+                .arg(
+                    Arg::new("input")
+                        .help("Input code snippet or file paths; calls between the files are linked")
+                        .required(true)
+                        .num_args(1..)
                         .index(1)
                 )
                 .arg(
@@ -47,6 +264,246 @@ fn main() -> Result<()> {
                         .help("Source language")
                         .default_value("javascript")
                 )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("Output format (json, dot, mermaid)")
+                        .default_value("json")
+                )
+                .arg(
+                    Arg::new("callers")
+                        .long("callers")
+                        .value_name("NAME")
+                        .help("List everything that calls NAME, directly or through other functions, instead of the graph")
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_name("PATH")
+                        .help("Write the graph to PATH instead of standard output")
+                )
+                .arg(
+                    Arg::new("encoding")
+                        .long("encoding")
+                        .help("Source file encoding (utf-8, utf-16le, utf-16be, latin1, shift-jis, ebcdic); detected when omitted")
+                )
+        )
+        .subcommand(
+            Command::new("analyze")
+                .about("Report on one or more modules")
+                .arg(
+                    Arg::new("input")
+                        .help("Input code snippet or file paths, analyzed as one program")
+                        .required(true)
+                        .num_args(1..)
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("language")
+                        .long("language")
+                        .help("Source language")
+                        .default_value("javascript")
+                )
+                .arg(
+                    Arg::new("dead-code")
+                        .long("dead-code")
+                        .help("Report unused functions, unreachable code and variables that are never read")
+                        .action(clap::ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("recursion")
+                        .long("recursion")
+                        .help("Report recursive functions and modules that depend on each other in a cycle")
+                        .action(clap::ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("globals")
+                        .long("globals")
+                        .help("Report global variables, static mutable state and singletons, and the functions using them")
+                        .action(clap::ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("purity")
+                        .long("purity")
+                        .help("Report which functions are pure and the side effects of the others")
+                        .action(clap::ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("Output format (text, json)")
+                        .default_value("text")
+                )
+                .arg(
+                    Arg::new("encoding")
+                        .long("encoding")
+                        .help("Source file encoding (utf-8, utf-16le, utf-16be, latin1, shift-jis, ebcdic); detected when omitted")
+                )
+        )
+        .subcommand(
+            Command::new("metrics")
+                .about("Tabulate the complexity of every function, most complex first")
+                .arg(
+                    Arg::new("input")
+                        .help("Input code snippet or file paths")
+                        .required(true)
+                        .num_args(1..)
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("language")
+                        .long("language")
+                        .help("Source language")
+                        .default_value("javascript")
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("Output format (text, json)")
+                        .default_value("text")
+                )
+                .arg(
+                    Arg::new("encoding")
+                        .long("encoding")
+                        .help("Source file encoding (utf-8, utf-16le, utf-16be, latin1, shift-jis, ebcdic); detected when omitted")
+                )
+        )
+        .subcommand(
+            Command::new("api")
+                .about("Extract the public API of one or more modules into a manifest, or check it against one")
+                .arg(
+                    Arg::new("input")
+                        .help("Input code snippet or file paths")
+                        .required(true)
+                        .num_args(1..)
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("language")
+                        .long("language")
+                        .help("Source language")
+                        .default_value("javascript")
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("Output format (json, text)")
+                        .default_value("json")
+                )
+                .arg(
+                    Arg::new("against")
+                        .long("against")
+                        .value_name("MANIFEST")
+                        .help("Compare the inputs, as translated output, with the manifest at MANIFEST and report what differs")
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_name("PATH")
+                        .help("Write the manifest to PATH instead of standard output")
+                )
+                .arg(
+                    Arg::new("encoding")
+                        .long("encoding")
+                        .help("Source file encoding (utf-8, utf-16le, utf-16be, latin1, shift-jis, ebcdic); detected when omitted")
+                )
+        )
+        .subcommand(
+            Command::new("patterns")
+                .about("Manage the project's library pattern packs")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("sync")
+                        .about("Fetch the pattern packs declared in .coalesce/config.json and pin them in .coalesce/patterns.lock")
+                        .arg(
+                            Arg::new("update")
+                                .long("update")
+                                .help("Re-resolve packs to their configured revisions instead of the locked ones")
+                                .action(clap::ArgAction::SetTrue)
+                        )
+                )
+                .subcommand(
+                    Command::new("lint")
+                        .about("Check pattern files for placeholders, ecosystems, regexes and imports that won't translate")
+                        .arg(
+                            Arg::new("paths")
+                                .help("Pattern files or directories of them (default: .coalesce/patterns)")
+                                .num_args(0..)
+                                .index(1)
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("migrate")
+                .about("Translate a project's source files into its target languages (see .coalesce/config.json)")
+                .visible_alias("translate")
+                .arg(
+                    Arg::new("directory")
+                        .help("Project directory")
+                        .default_value(".")
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .short('o')
+                        .value_name("DIR")
+                        .help("Write each target's tree and migration-report.json into DIR (default: coalesce-out in the project)")
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .help("Target languages, instead of target_languages in the config (python, rust, c, go, typescript, lua)")
+                        .num_args(1..)
+                )
+                .arg(
+                    Arg::new("ecosystem")
+                        .long("ecosystem")
+                        .help("Target library to translate library calls to, e.g. tokio; the language's standard library when omitted")
+                )
+                .arg(
+                    Arg::new("library-uir")
+                        .long("library-uir")
+                        .help("Replace library calls with the parsed UIR of their translations instead of annotating them")
+                        .action(clap::ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("watch")
+                        .long("watch")
+                        .short('w')
+                        .help("Keep translating source files as they change, reparsing only the edited declarations")
+                        .action(clap::ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("Write nothing; list the files the migration would create or change")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("watch")
+                )
+                .arg(
+                    Arg::new("diff")
+                        .long("diff")
+                        .help("Write nothing; show how each file would change as a unified diff against what's already in the output")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("watch")
+                )
+                .arg(
+                    Arg::new("report")
+                        .long("report")
+                        .value_name("FORMAT")
+                        .help("Format of the migration report: files translated, constructs skipped, generator TODOs, libraries and confidence (json, sarif)")
+                        .value_parser(["json", "sarif"])
+                        .default_value("json")
+                )
+                .arg(
+                    Arg::new("report-file")
+                        .long("report-file")
+                        .value_name("PATH")
+                        .help("Write the report to PATH instead of the output directory, even with --dry-run or --diff")
+                )
         )
         .subcommand(
             Command::new("init")
@@ -59,42 +516,129 @@ fn main() -> Result<()> {
                 )
         )
         .get_matches();
-
+    
     match matches.subcommand() {
         Some(("demo", sub_matches)) => {
             let input = sub_matches.get_one::<String>("input").unwrap();
             let from = sub_matches.get_one::<String>("from").unwrap();
             let to = sub_matches.get_one::<String>("to").unwrap();
             
+            let brace_label = sub_matches.get_one::<String>("brace-style").unwrap();
+            let Some(brace_style) = BraceStyle::from_label(brace_label) else {
+                println!("❌ Unsupported brace style: {}", brace_label);
+                return Ok(());
+            };
+            let id_scheme = match sub_matches.get_one::<String>("content-ids") {
+                Some(label) => match IdScheme::from_label(label) {
+                    Some(scheme) => Some(scheme),
+                    None => {
+                        println!("❌ Unsupported id scheme: {}", label);
+                        return Ok(());
+                    }
+                },
+                None => None,
+            };
+            let naming = match sub_matches.get_one::<String>("naming") {
+                Some(label) => match NamingConvention::from_label(label) {
+                    Some(naming) => Some(naming),
+                    None => {
+                        println!("❌ Unsupported naming convention: {}", label);
+                        return Ok(());
+                    }
+                },
+                None => None,
+            };
+            let style = GeneratorConfig {
+                indent_width: sub_matches.get_one::<usize>("indent-width").copied(),
+                use_tabs: sub_matches.get_flag("tabs"),
+                brace_style,
+                naming,
+                max_line_length: sub_matches.get_one::<usize>("max-line-length").copied(),
+            };
+            
+            // Grammars registered in the project config extend the built-in languages
+            let config_path = project_dir()?.join(CONFIG_FILE);
+            let grammars = if config_path.exists() {
+                GrammarRegistry::from_config_file(&config_path)?
+            } else {
+                GrammarRegistry::new()
+            };
+            let mut optimize = if config_path.exists() {
+                FoldConfig::from_config_file(&config_path)?
+            } else {
+                FoldConfig::default()
+            };
+            optimize.enabled |= sub_matches.get_flag("optimize");
+            
             println!("🚀 Coalesce Demo");
             println!("📝 Input: {}", input);
             println!("🔄 Translating from {} to {}", from, to);
             
             // Parse the source language
-            let source_language = match from.as_str() {
-                "javascript" | "js" => Language::JavaScript,
-                "c" => Language::C,
-                "cpp" | "c++" => Language::Cpp,
-                "csharp" | "cs" | "c#" => Language::CSharp,
-                "fsharp" | "fs" | "f#" => Language::FSharp,
-                "vb" | "visualbasic" | "visual-basic" => Language::VisualBasic,
-                "rust" | "rs" => Language::Rust,
-                "go" => Language::Go,
-                _ => {
+            let source_language = match source_language(from) {
+                Some(language) => language,
+                None if grammars.contains(from) => Language::Custom(from.clone()),
+                None => {
                     println!("❌ Unsupported source language: {}", from);
                     return Ok(());
                 }
             };
             
             // Create parser and parse the input
-            let parser = create_parser(source_language.clone())?;
+            let parser: Box<dyn Parser> = match &source_language {
+                Language::Custom(name) => Box::new(grammars.create_parser(name)?),
+                _ => create_parser(source_language.clone())?,
+            };
             let mut uir = parser.parse(input)?;
+            if let Some(seed) = sub_matches.get_one::<String>("stable-ids") {
+                uir.assign_stable_ids(seed);
+            }
+            if let Some(scheme) = id_scheme {
+                uir.assign_content_ids(scheme);
+            }
+            let simplified = fold_constants(&mut uir, &optimize);
+            if simplified > 0 {
+                println!("✂️  Simplified {} constant expression(s) and condition(s)", simplified);
+            }
+            // Typed targets declare what dynamically typed sources leave implicit
+            if matches!(source_language, Language::Python | Language::JavaScript) {
+                infer_types(&mut uir);
+            }
+            // Declarations learn whether they are reassigned, for `let` versus `let mut`
+            let warnings = analyze_data_flow(&mut uir);
+            measure_complexity(&mut uir);
+            if !warnings.is_empty() {
+                println!("⚠️  Data-flow warnings:");
+                for warning in &warnings {
+                    println!("  • {}", warning);
+                }
+                println!();
+            }
+            
+            // Transform library patterns for target language
+            let target_lang_enum = match to.as_str() {
+                "python" | "py" => Language::Python,
+                "rust" | "rs" => Language::Rust,
+                "c" => Language::C,
+                "go" => Language::Go,
+                "typescript" | "ts" => Language::TypeScript,
+                "javascript" | "js" => Language::JavaScript,
+                "csharp" | "cs" => Language::CSharp,
+                _ => source_language, // Fallback
+            };
+            let ecosystem = sub_matches.get_one::<String>("ecosystem").map(String::as_str);
             
             // Initialize Library Abstraction Layer
-            let lal = LibraryAbstractionLayer::new()?;
+            let (mut lal, project_patterns) = library_layer()?;
+            if project_patterns > 0 {
+                println!("📚 Loaded {} project library pattern(s)", project_patterns);
+            }
+            if ecosystem.is_none() {
+                target_project(&mut lal, sub_matches, &target_lang_enum)?;
+            }
             
             // Analyze library dependencies
-            let dependencies = lal.analyze_dependencies(input, source_language.clone())?;
+            let dependencies = lal.analyze_dependencies(&uir, input)?;
             
             if !dependencies.is_empty() {
                 println!("🔍 Detected library dependencies:");
@@ -109,106 +653,666 @@ fn main() -> Result<()> {
             
             // Enhance UIR with library metadata
             lal.enhance_uir(&mut uir, &dependencies)?;
+            // Functions learn whether they are pure, for `const fn` and memoization
+            Purity::analyze([("input", &uir)], &lal.side_effects()).annotate("input", &mut uir);
             
-            // Transform library patterns for target language
-            let target_lang_enum = match to.as_str() {
-                "python" | "py" => Language::Python,
-                "rust" | "rs" => Language::Rust,
-                "c" => Language::C,
-                "go" => Language::Go,
-                _ => source_language, // Fallback
+            let (enhanced_uir, translated) = match sub_matches.get_flag("library-uir").then(|| create_parser(target_lang_enum.clone())) {
+                Some(Ok(target_parser)) => {
+                    let (rewritten, coverage) = lal.rewrite_library_calls_with_coverage(&uir, input, &dependencies, target_parser.as_ref(), ecosystem)?;
+                    (rewritten, coverage.translated_dependencies(&dependencies))
+                }
+                Some(Err(_)) => {
+                    println!("⚠️  No {} parser to read library translations with: annotating them instead\n", to);
+                    (lal.transform_library_calls(&uir, target_lang_enum.clone(), ecosystem)?, Vec::new())
+                }
+                None => (lal.transform_library_calls(&uir, target_lang_enum.clone(), ecosystem)?, Vec::new()),
             };
+            // Only calls the output translated need the target's packages
+            let target_manifest = sub_matches.get_one::<String>("manifest")
+                .map(|dir| (dir, lal.target_manifest(&translated, target_lang_enum.clone(), ecosystem)));
             
-            let enhanced_uir = lal.transform_library_calls(&uir, target_lang_enum, None)?;
-            
-            println!("🔧 Generated UIR:");
-            println!("{}", serde_json::to_string_pretty(&enhanced_uir)?);
-            
-            // Generate target code
-            let generated_code = match to.as_str() {
-                "python" | "py" => {
-                    let generator = PythonGenerator;
-                    generator.generate(&enhanced_uir)?
+            if sub_matches.get_flag("uir-diff") {
+                let changes = uir.diff(&enhanced_uir);
+                if changes.is_empty() {
+                    println!("🧬 Transformation left the UIR unchanged\n");
+                } else {
+                    println!("🧬 UIR changes:\n{}", changes);
                 }
-                "rust" | "rs" => {
-                    let generator = RustGenerator;
-                    generator.generate(&enhanced_uir)?
-                }
-                "c" => {
-                    let generator = CGenerator;
-                    generator.generate(&enhanced_uir)?
+            }
+            
+            match sub_matches.get_one::<String>("dump-uir") {
+                Some(path) => {
+                    let bytes = match sub_matches.get_one::<String>("uir-format").map(String::as_str) {
+                        Some("binary") => enhanced_uir.to_bytes()?,
+                        _ => serde_json::to_vec_pretty(&enhanced_uir)?,
+                    };
+                    fs::write(path, &bytes)?;
+                    println!("💾 Wrote UIR to {} ({} bytes)", path, bytes.len());
                 }
-                "go" => {
-                    let generator = GoGenerator;
-                    generator.generate(&enhanced_uir)?
+                None => {
+                    println!("🔧 Generated UIR:");
+                    println!("{}", serde_json::to_string_pretty(&enhanced_uir)?);
                 }
-                _ => format!("# Target language '{}' not yet supported\n", to)
+            }
+            
+            // Generate target code
+            let generator: Option<Box<dyn Generator>> = match (sub_matches.get_one::<String>("templates"), to.as_str()) {
+                (Some(path), _) => Some(Box::new(TemplateGenerator::from_file(path)?)),
+                (None, "lua") => Some(Box::new(TemplateGenerator::lua()?)),
+                (None, "python" | "py") => Some(Box::new(PythonGenerator)),
+                (None, "rust" | "rs") => Some(Box::new(RustGenerator)),
+                (None, "c") => Some(Box::new(CGenerator)),
+                (None, "go") => Some(Box::new(GoGenerator)),
+                (None, "typescript" | "ts") => Some(Box::new(TypeScriptGenerator)),
+                (None, _) => None,
+            };
+            let generated_code = match generator {
+                Some(generator) => generator.generate_with_config(&enhanced_uir, &style)?,
+                None => format!("# Target language '{}' not yet supported\n", to),
             };
             
             println!("\n🎯 Generated {} code:", to);
             println!("{}", generated_code);
             
+            if let Some((dir, manifest)) = target_manifest {
+                let path = write_target_manifest(dir, &manifest)?;
+                report_target_manifest(path.as_deref(), &manifest);
+            }
+            
             println!("✅ Demo complete! This is just the beginning...");
         }
         Some(("analyze-libs", sub_matches)) => {
+            let language_str = sub_matches.get_one::<String>("language").unwrap();
+            
+            let target = match sub_matches.get_one::<String>("target") {
+                Some(label) => match source_language(label) {
+                    Some(language) => Some((label, language)),
+                    None => {
+                        println!("❌ Unsupported language: {}", label);
+                        return Ok(());
+                    }
+                },
+                None => None,
+            };
+            let Some(source_language) = source_language(language_str) else {
+                println!("❌ Unsupported language: {}", language_str);
+                return Ok(());
+            };
+            let ecosystem = sub_matches.get_one::<String>("ecosystem").map(String::as_str);
+            let encoding = match sub_matches.get_one::<String>("encoding") {
+                Some(label) => match SourceEncoding::from_label(label) {
+                    Some(encoding) => Some(encoding),
+                    None => {
+                        println!("❌ Unsupported encoding: {}", label);
+                        return Ok(());
+                    }
+                },
+                None => None,
+            };
+            
+            println!("🔍 Analyzing library dependencies in {} code...", language_str);
+            let (mut lal, project_patterns) = library_layer()?;
+            if project_patterns > 0 {
+                println!("📚 Loaded {} project library pattern(s)", project_patterns);
+            }
+            if let (Some((_, language)), None) = (&target, ecosystem) {
+                target_project(&mut lal, sub_matches, language)?;
+            }
+            
+            let mut coverage = CoverageReport::default();
+            let mut manual = Vec::new();
+            let mut detected = Vec::new();
+            for input in sub_matches.get_many::<String>("input").unwrap() {
+                // Read input (could be file or direct code)
+                let code = if std::path::Path::new(input).exists() {
+                    let source = read_source(input, encoding)?;
+                    println!("📂 Reading from file: {} ({})", input, source.encoding.name());
+                    source.text
+                } else {
+                    input.clone()
+                };
+                
+                // Analyze the parsed code
+                let uir = create_parser(source_language.clone())?.parse(&code)?;
+                let Detection { dependencies, conflicts } = lal.analyze(&uir, &code)?.in_file(input_name(input));
+                
+                if dependencies.is_empty() {
+                    println!("✅ No library dependencies detected.");
+                } else {
+                    println!("📦 Detected {} library dependencies:", dependencies.len());
+                    for dep in &dependencies {
+                        println!("\n🔧 Library: {} ({})", dep.name, dep.ecosystem);
+                        if !dep.usage_patterns.is_empty() {
+                            println!("   Patterns found:");
+                            for usage in &dep.usage_patterns {
+                                println!("     • {}: {} ({})", 
+                                    usage.pattern_name, 
+                                    usage.semantic_intent,
+                                    usage.method_name
+                                );
+                                println!("       at {}: {}", call_site(&usage.source_location), usage.snippet);
+                                if !usage.parameters.is_empty() {
+                                    let parameters: BTreeMap<_, _> = usage.parameters.iter().collect();
+                                    println!("       Parameters: {:?}", parameters);
+                                }
+                            }
+                        }
+                        
+                        // Show available target ecosystems
+                        let targets = lal.get_target_ecosystems(&dep.name);
+                        if !targets.is_empty() {
+                            println!("   🎯 Can translate to: {}", targets.join(", "));
+                        }
+                    }
+                }
+                for conflict in &conflicts {
+                    println!(
+                        "⚖️  Patterns overlap at {} ({}): kept {} over {} ({})",
+                        call_site(&conflict.source_location),
+                        conflict.semantic_intent,
+                        conflict.kept,
+                        conflict.dropped.join(", "),
+                        conflict.reason,
+                    );
+                }
+                
+                if let Some((_, language)) = &target {
+                    // Only calls the rewrite replaces count: a rule whose code doesn't parse
+                    // in the target leaves the call as it was
+                    let module = match create_parser(language.clone()) {
+                        Ok(parser) => lal.rewrite_library_calls_with_coverage(&uir, &code, &dependencies, parser.as_ref(), ecosystem)?.1,
+                        Err(_) => lal.coverage(&dependencies, language.clone(), ecosystem).only_applied(|_| false),
+                    };
+                    for usage in module.usages.iter().filter(|usage| usage.target.is_none()) {
+                        manual.push(format!("{}:{} ({}) at {}: {}", usage.library, usage.pattern, usage.method_name, call_site(&usage.source_location), usage.snippet));
+                    }
+                    coverage.merge(module);
+                }
+                detected.extend(dependencies);
+            }
+            
+            if let Some((label, _)) = target {
+                match (coverage.percent(), coverage.confidence()) {
+                    (Some(percent), Some(confidence)) => println!(
+                        "\n📊 Coverage for {}: {:.0}% of library calls translated automatically, {} need manual work (confidence {:.2})",
+                        label,
+                        percent,
+                        coverage.manual,
+                        confidence,
+                    ),
+                    _ => println!("\n📊 Coverage for {}: no library calls", label),
+                }
+                for usage in &manual {
+                    println!("   ✋ {}", usage);
+                }
+            }
+            
+            if let Some(root) = sub_matches.get_one::<String>("project") {
+                println!("\n📋 Dependency inventory of {}:", root);
+                for dependency in lal.inventory(root, &detected)? {
+                    let version = dependency.version.as_deref().map(|version| format!(" {}", version)).unwrap_or_default();
+                    let usages = match dependency.usage_patterns.len() {
+                        0 => "unused".to_string(),
+                        count => format!("{} usage(s)", count),
+                    };
+                    println!("   📦 {}{} ({}): {}", dependency.name, version, dependency.ecosystem, usages);
+                }
+            }
+        }
+        Some(("query", sub_matches)) => {
+            let selector = sub_matches.get_one::<String>("selector").unwrap();
             let input = sub_matches.get_one::<String>("input").unwrap();
             let language_str = sub_matches.get_one::<String>("language").unwrap();
             
-            // Parse the source language
-            let source_language = match language_str.as_str() {
-                "javascript" | "js" => Language::JavaScript,
-                "c" => Language::C,
-                "cpp" | "c++" => Language::Cpp,
-                "csharp" | "cs" | "c#" => Language::CSharp,
-                "python" | "py" => Language::Python,
-                _ => {
-                    println!("❌ Unsupported language: {}", language_str);
+            let Some(language) = source_language(language_str) else {
+                println!("❌ Unsupported language: {}", language_str);
+                return Ok(());
+            };
+            let selector = match Selector::parse(selector) {
+                Ok(selector) => selector,
+                Err(error) => {
+                    println!("❌ {}", error);
                     return Ok(());
                 }
             };
             
-            println!("🔍 Analyzing library dependencies in {} code...", language_str);
-            
             // Read input (could be file or direct code)
             let code = if std::path::Path::new(input).exists() {
-                println!("📂 Reading from file: {}", input);
-                fs::read_to_string(input)?
+                let encoding = match sub_matches.get_one::<String>("encoding") {
+                    Some(label) => match SourceEncoding::from_label(label) {
+                        Some(encoding) => Some(encoding),
+                        None => {
+                            println!("❌ Unsupported encoding: {}", label);
+                            return Ok(());
+                        }
+                    },
+                    None => None,
+                };
+                read_source(input, encoding)?.text
             } else {
                 input.clone()
             };
             
-            // Initialize LAL and analyze
-            let lal = LibraryAbstractionLayer::new()?;
-            let dependencies = lal.analyze_dependencies(&code, source_language)?;
+            let uir = create_parser(language)?.parse(&code)?;
+            let matches = selector.select(&uir);
+            if matches.is_empty() {
+                println!("🔍 No nodes match.");
+            } else {
+                println!("🔍 {} matching nodes:", matches.len());
+                for node in matches {
+                    let name = node.name.as_ref().map(|name| format!(" `{}`", name)).unwrap_or_default();
+                    let line = node.source_location.as_ref().map(|l| format!(" at line {}", l.start_line)).unwrap_or_default();
+                    println!("  • {}{}{} ({})", node.kind_path(), name, line, node.id);
+                }
+            }
+        }
+        Some(("graph", sub_matches)) => {
+            let input = sub_matches.get_one::<String>("input").unwrap();
+            let language_str = sub_matches.get_one::<String>("language").unwrap();
+            let format_str = sub_matches.get_one::<String>("format").unwrap();
             
-            if dependencies.is_empty() {
-                println!("✅ No library dependencies detected.");
+            let Some(language) = source_language(language_str) else {
+                println!("❌ Unsupported language: {}", language_str);
+                return Ok(());
+            };
+            let Some(format) = GraphFormat::from_label(format_str) else {
+                println!("❌ Unsupported graph format: {}", format_str);
+                return Ok(());
+            };
+            
+            // Read input (could be file or direct code)
+            let code = if std::path::Path::new(input).exists() {
+                let encoding = match sub_matches.get_one::<String>("encoding") {
+                    Some(label) => match SourceEncoding::from_label(label) {
+                        Some(encoding) => Some(encoding),
+                        None => {
+                            println!("❌ Unsupported encoding: {}", label);
+                            return Ok(());
+                        }
+                    },
+                    None => None,
+                };
+                read_source(input, encoding)?.text
             } else {
-                println!("📦 Detected {} library dependencies:", dependencies.len());
-                for dep in &dependencies {
-                    println!("\n🔧 Library: {} ({})", dep.name, dep.ecosystem);
-                    if !dep.usage_patterns.is_empty() {
-                        println!("   Patterns found:");
-                        for usage in &dep.usage_patterns {
-                            println!("     • {}: {} ({})", 
-                                usage.pattern_name, 
-                                usage.semantic_intent,
-                                usage.method_name
-                            );
-                            if !usage.parameters.is_empty() {
-                                println!("       Parameters: {:?}", usage.parameters);
+                input.clone()
+            };
+            
+            let uir = create_parser(language)?.parse(&code)?;
+            let graph = uir.to_graph().render(format);
+            // Plain output, so the graph can be piped straight into `dot` or a Markdown file
+            match sub_matches.get_one::<String>("output") {
+                Some(path) => {
+                    fs::write(path, graph)?;
+                    println!("✅ Wrote graph to {}", path);
+                }
+                None => print!("{}", graph),
+            }
+        }
+        Some(("calls", sub_matches)) => {
+            let language_str = sub_matches.get_one::<String>("language").unwrap();
+            let format_str = sub_matches.get_one::<String>("format").unwrap();
+            
+            let Some(language) = source_language(language_str) else {
+                println!("❌ Unsupported language: {}", language_str);
+                return Ok(());
+            };
+            
+            let Some(modules) = parse_modules(sub_matches, language)? else {
+                return Ok(());
+            };
+            let graph = CallGraph::from_modules(modules.iter().map(|(name, uir)| (name.as_str(), uir)));
+            
+            if let Some(name) = sub_matches.get_one::<String>("callers") {
+                let direct: Vec<_> = graph.callers(name).into_iter().map(|callable| callable.id.clone()).collect();
+                let callers = graph.transitive_callers(name);
+                if graph.find(name).is_empty() {
+                    println!("❌ Nothing named {} is declared or called", name);
+                } else if callers.is_empty() {
+                    println!("🔍 Nothing calls {}.", name);
+                } else {
+                    println!("🔍 {} callers reach {}:", callers.len(), name);
+                    for callable in callers {
+                        let reach = if direct.contains(&callable.id) { "direct" } else { "indirect" };
+                        println!("  • {} ({})", callable.id, reach);
+                    }
+                }
+                return Ok(());
+            }
+            
+            let rendered = match format_str.as_str() {
+                "json" => format!("{}\n", serde_json::to_string_pretty(&graph)?),
+                label => match GraphFormat::from_label(label) {
+                    Some(format) => graph.to_graph().render(format),
+                    None => {
+                        println!("❌ Unsupported call graph format: {}", format_str);
+                        return Ok(());
+                    }
+                },
+            };
+            match sub_matches.get_one::<String>("output") {
+                Some(path) => {
+                    fs::write(path, rendered)?;
+                    println!("✅ Wrote call graph to {}", path);
+                }
+                None => print!("{}", rendered),
+            }
+        }
+        Some(("analyze", sub_matches)) => {
+            let language_str = sub_matches.get_one::<String>("language").unwrap();
+            let format_str = sub_matches.get_one::<String>("format").unwrap();
+            
+            let Some(language) = source_language(language_str) else {
+                println!("❌ Unsupported language: {}", language_str);
+                return Ok(());
+            };
+            let dead_code = sub_matches.get_flag("dead-code");
+            let recursion = sub_matches.get_flag("recursion");
+            let globals = sub_matches.get_flag("globals");
+            let purity = sub_matches.get_flag("purity");
+            if !dead_code && !recursion && !globals && !purity {
+                println!("❌ Choose an analysis to run: --dead-code, --recursion, --globals, --purity");
+                return Ok(());
+            }
+            if !matches!(format_str.as_str(), "text" | "json") {
+                println!("❌ Unsupported report format: {}", format_str);
+                return Ok(());
+            }
+            let json = format_str == "json";
+            let Some(mut modules) = parse_modules(sub_matches, language)? else {
+                return Ok(());
+            };
+            for (_, uir) in &mut modules {
+                resolve(uir);
+            }
+            
+            let mut report = serde_json::Map::new();
+            if dead_code {
+                let dead = DeadCode::find(modules.iter().map(|(name, uir)| (name.as_str(), uir)));
+                if json {
+                    report.insert("dead_code".to_string(), serde_json::to_value(&dead)?);
+                } else if dead.is_empty() {
+                    println!("✅ No dead code found.");
+                } else {
+                    let sections = [
+                        (DeadCodeKind::UnusedFunction, "Unused functions"),
+                        (DeadCodeKind::Unreachable, "Unreachable code"),
+                        (DeadCodeKind::UnreadVariable, "Variables never read"),
+                    ];
+                    println!("🧹 Dead code in {} module(s): {} item(s) translation can leave out", modules.len(), dead.items().len());
+                    for (kind, heading) in sections {
+                        let items: Vec<_> = dead.of_kind(kind).collect();
+                        if !items.is_empty() {
+                            println!("\n{} ({}):", heading, items.len());
+                            for item in items {
+                                println!("  • {}", item);
+                            }
+                        }
+                    }
+                }
+            }
+            if recursion {
+                let found = Recursion::find(modules.iter().map(|(name, uir)| (name.as_str(), uir)));
+                if json {
+                    report.insert("recursion".to_string(), serde_json::to_value(&found)?);
+                } else if found.is_empty() {
+                    println!("✅ No recursion or module cycles found.");
+                } else {
+                    if dead_code {
+                        println!();
+                    }
+                    println!("🔁 Recursion in {} module(s):", modules.len());
+                    if !found.groups().is_empty() {
+                        println!("\nRecursive functions ({}):", found.groups().len());
+                        for group in found.groups() {
+                            let ids: Vec<&str> = group.functions.iter().map(|callable| callable.id.as_str()).collect();
+                            match group.kind {
+                                RecursionKind::Direct => println!("  • {} calls itself", ids.join(", ")),
+                                RecursionKind::Mutual => println!("  • {} call each other", ids.join(", ")),
+                            }
+                        }
+                    }
+                    if !found.module_cycles().is_empty() {
+                        println!("\nModule cycles ({}):", found.module_cycles().len());
+                        for cycle in found.module_cycles() {
+                            println!("  • {} depend on each other", cycle.join(", "));
+                        }
+                    }
+                }
+            }
+            if globals {
+                let state = GlobalState::find(modules.iter().map(|(name, uir)| (name.as_str(), uir)));
+                if json {
+                    report.insert("globals".to_string(), serde_json::to_value(&state)?);
+                } else if state.is_empty() {
+                    println!("✅ No global or shared mutable state found.");
+                } else {
+                    if dead_code || recursion {
+                        println!();
+                    }
+                    let sections = [
+                        (StateKind::Global, "Global variables"),
+                        (StateKind::StaticLocal, "Static locals"),
+                        (StateKind::StaticField, "Static fields"),
+                        (StateKind::Singleton, "Singletons"),
+                    ];
+                    println!("🌐 Shared mutable state in {} module(s): {} item(s) to give an owner", modules.len(), state.items().len());
+                    for (kind, heading) in sections {
+                        let items: Vec<_> = state.of_kind(kind).collect();
+                        if !items.is_empty() {
+                            println!("\n{} ({}):", heading, items.len());
+                            for item in items {
+                                println!("  • {}", item);
                             }
                         }
                     }
-                    
-                    // Show available target ecosystems
-                    let targets = lal.get_target_ecosystems(&dep.name);
-                    if !targets.is_empty() {
-                        println!("   🎯 Can translate to: {}", targets.join(", "));
+                }
+            }
+            if purity {
+                let library_effects = library_layer()?.0.side_effects();
+                let found = Purity::analyze(modules.iter().map(|(name, uir)| (name.as_str(), uir)), &library_effects);
+                if json {
+                    report.insert("purity".to_string(), serde_json::to_value(&found)?);
+                } else if found.functions().is_empty() {
+                    println!("✅ No functions to analyze.");
+                } else {
+                    if dead_code || recursion || globals {
+                        println!();
+                    }
+                    let sections = [
+                        (PurityKind::Pure, "Pure functions"),
+                        (PurityKind::Impure, "Impure functions"),
+                        (PurityKind::Unknown, "Purity unknown"),
+                    ];
+                    println!("🧪 Purity of {} function(s) in {} module(s):", found.functions().len(), modules.len());
+                    for (kind, heading) in sections {
+                        let functions: Vec<_> = found.of_kind(kind).collect();
+                        if !functions.is_empty() {
+                            println!("\n{} ({}):", heading, functions.len());
+                            for function in functions {
+                                let effects: Vec<String> = function.effects.iter().map(ToString::to_string).collect();
+                                match effects.is_empty() {
+                                    true => println!("  • {}", function.function.id),
+                                    false => println!("  • {}: {}", function.function.id, effects.join("; ")),
+                                }
+                            }
+                        }
                     }
                 }
             }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
         }
+        Some(("metrics", sub_matches)) => {
+            let language_str = sub_matches.get_one::<String>("language").unwrap();
+            let format_str = sub_matches.get_one::<String>("format").unwrap();
+            
+            let Some(language) = source_language(language_str) else {
+                println!("❌ Unsupported language: {}", language_str);
+                return Ok(());
+            };
+            let Some(mut modules) = parse_modules(sub_matches, language)? else {
+                return Ok(());
+            };
+            let mut rows = Vec::new();
+            for (name, uir) in &mut modules {
+                rows.extend(measure_complexity(uir).into_iter().map(|metrics| (name.clone(), metrics)));
+            }
+            rows.sort_by_key(|(_, metrics)| std::cmp::Reverse((metrics.cyclomatic, metrics.nesting_depth, metrics.statements)));
+            
+            match format_str.as_str() {
+                "json" => {
+                    let report: Vec<_> = rows.iter().map(|(module, metrics)| serde_json::json!({ "module": module, "function": metrics })).collect();
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                }
+                "text" if rows.is_empty() => println!("🔍 No functions found."),
+                "text" => {
+                    println!("📈 {} function(s) in {} module(s), most complex first:\n", rows.len(), modules.len());
+                    println!("  {:>10}  {:>7}  {:>10}  {:>5}  Function", "Complexity", "Nesting", "Statements", "Lines");
+                    for (module, metrics) in &rows {
+                        let location = match metrics.line {
+                            Some(line) => format!("{}:{}", module, line),
+                            None => module.clone(),
+                        };
+                        println!("  {:>10}  {:>7}  {:>10}  {:>5}  {} ({})", metrics.cyclomatic, metrics.nesting_depth, metrics.statements, metrics.lines, metrics.name, location);
+                    }
+                }
+                _ => println!("❌ Unsupported report format: {}", format_str),
+            }
+        }
+        Some(("api", sub_matches)) => {
+            let language_str = sub_matches.get_one::<String>("language").unwrap();
+            let format_str = sub_matches.get_one::<String>("format").unwrap();
+            
+            let Some(language) = source_language(language_str) else {
+                println!("❌ Unsupported language: {}", language_str);
+                return Ok(());
+            };
+            if !matches!(format_str.as_str(), "json" | "text") {
+                println!("❌ Unsupported report format: {}", format_str);
+                return Ok(());
+            }
+            let Some(mut modules) = parse_modules(sub_matches, language)? else {
+                return Ok(());
+            };
+            for (_, uir) in &mut modules {
+                resolve(uir);
+                infer_types(uir);
+            }
+            let manifest = ApiManifest::extract(modules.iter().map(|(name, uir)| (name.as_str(), uir)));
+            
+            if let Some(path) = sub_matches.get_one::<String>("against") {
+                let expected: ApiManifest = match serde_json::from_str(&fs::read_to_string(path)?) {
+                    Ok(expected) => expected,
+                    Err(e) => {
+                        println!("❌ {} is not an API manifest: {}", path, e);
+                        return Ok(());
+                    }
+                };
+                let changes = expected.compare(&manifest);
+                if format_str == "json" {
+                    println!("{}", serde_json::to_string_pretty(&changes)?);
+                } else if changes.is_empty() {
+                    println!("✅ The public API matches {} ({} item(s)).", path, expected.items().len());
+                } else {
+                    let sections = [
+                        (ApiChangeKind::Missing, "Missing"),
+                        (ApiChangeKind::Changed, "Changed"),
+                        (ApiChangeKind::Added, "Added"),
+                    ];
+                    println!("⚠️  The public API differs from {} in {} place(s):", path, changes.len());
+                    for (kind, heading) in sections {
+                        let listed: Vec<_> = changes.iter().filter(|change| change.kind == kind).collect();
+                        if !listed.is_empty() {
+                            println!("\n{} ({}):", heading, listed.len());
+                            for change in listed {
+                                println!("  • {}", change);
+                            }
+                        }
+                    }
+                }
+                return Ok(());
+            }
+            
+            let rendered = match format_str.as_str() {
+                "json" => format!("{}\n", serde_json::to_string_pretty(&manifest)?),
+                _ => {
+                    let mut text = format!("🔌 Public API of {} module(s): {} item(s)\n", modules.len(), manifest.items().len());
+                    for item in manifest.items() {
+                        let location = item.line.map(|line| format!("{}:{}", item.module, line)).unwrap_or_else(|| item.module.clone());
+                        text.push_str(&format!("  • {} ({})\n", item, location));
+                    }
+                    text
+                }
+            };
+            match sub_matches.get_one::<String>("output") {
+                Some(path) => {
+                    fs::write(path, rendered)?;
+                    println!("✅ Wrote API manifest to {}", path);
+                }
+                None => print!("{}", rendered),
+            }
+        }
+        Some(("patterns", sub_matches)) => {
+            if let Some(("sync", sync_matches)) = sub_matches.subcommand() {
+                let config_path = project_dir()?.join(CONFIG_FILE);
+                if !config_path.exists() {
+                    println!("❌ No project config at {} (run `coalesce init` first)", config_path.display());
+                    return Ok(());
+                }
+                let packs = PatternPacks::from_config_file(&config_path)?;
+                if packs.sources().is_empty() {
+                    println!("✅ No pattern packs declared under \"pattern_packs\" in {}", config_path.display());
+                    return Ok(());
+                }
+                
+                println!("🔄 Syncing {} pattern pack(s)...", packs.sources().len());
+                for synced in packs.sync(sync_matches.get_flag("update"))? {
+                    let pin = match &synced.pack.revision {
+                        Some(revision) => format!(" @ {}", &revision[..revision.len().min(12)]),
+                        None => String::new(),
+                    };
+                    println!(
+                        "  📦 {}{}: {} pattern(s), sha256 {}{}",
+                        synced.pack.name, pin, synced.patterns, &synced.pack.sha256[..12],
+                        if synced.changed { " (updated)" } else { "" },
+                    );
+                }
+                println!("🔒 Pinned in {}", packs.lock_path().display());
+            }
+            if let Some(("lint", lint_matches)) = sub_matches.subcommand() {
+                let paths: Vec<String> = match lint_matches.get_many::<String>("paths") {
+                    Some(paths) => paths.cloned().collect(),
+                    None => vec![project_dir()?.join(".coalesce/patterns").display().to_string()],
+                };
+                
+                println!("🔍 Linting pattern files ({})...", paths.join(", "));
+                let report = LibraryAbstractionLayer::new()?.lint_patterns(&paths)?;
+                for issue in &report.issues {
+                    let icon = match issue.severity {
+                        Severity::Error => "❌",
+                        Severity::Warning => "⚠️ ",
+                    };
+                    match &issue.pattern {
+                        Some(pattern) => println!("  {} {} ({}): {}", icon, issue.file.display(), pattern, issue.message),
+                        None => println!("  {} {}: {}", icon, issue.file.display(), issue.message),
+                    }
+                }
+                if report.issues.is_empty() {
+                    println!("✅ {} pattern(s) in {} file(s), no issues", report.patterns, report.files);
+                } else {
+                    println!(
+                        "{} {} error(s), {} warning(s) in {} pattern(s) across {} file(s)",
+                        if report.errors() > 0 { "❌" } else { "⚠️ " },
+                        report.errors(), report.warnings(), report.patterns, report.files,
+                    );
+                }
+                if report.errors() > 0 {
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(("migrate", sub_matches)) => migrate::migrate(sub_matches)?,
         Some(("init", sub_matches)) => {
             let directory = sub_matches.get_one::<String>("directory").unwrap();
             
@@ -216,7 +1320,7 @@ fn main() -> Result<()> {
             
             // Create project structure
             fs::create_dir_all(format!("{}/src", directory))?;
-            fs::create_dir_all(format!("{}/.coalesce", directory))?;
+            fs::create_dir_all(format!("{}/.coalesce/patterns", directory))?;
             
             let config = r#"{
   "version": "0.1.0",
@@ -224,17 +1328,28 @@ fn main() -> Result<()> {
   "source_languages": ["javascript"],
   "target_languages": ["python", "rust"],
   "preserve_legacy_patterns": true,
-  "ml_enhancement": true
+  "ml_enhancement": true,
+  "optimize": {
+    "enabled": false,
+    "expressions": true,
+    "conditions": true,
+    "macros": true
+  },
+  "grammars": [],
+  "pattern_packs": [],
+  "patterns": []
 }"#;
-            
+
             fs::write(format!("{}/.coalesce/config.json", directory), config)?;
             
             println!("✅ Project initialized!");
             println!("📁 Created: {}/src", directory);
             println!("⚙️  Created: {}/.coalesce/config.json", directory);
+            println!("📚 Created: {}/.coalesce/patterns (add *.yaml or *.toml library patterns here)", directory);
             println!("\n🚀 Next steps:");
             println!("   cd {}", directory);
-            println!("   coalesce analyze ./src");
+            println!("   coalesce analyze src/*.js --dead-code");
+            println!("   coalesce migrate");
         }
         _ => {
             println!("🌟 Welcome to Coalesce!");
@@ -245,12 +1360,139 @@ fn main() -> Result<()> {
             println!("🔧 Or:  coalesce demo \"Function Add(a As Integer, b As Integer) As Integer\" --from vb --to go");
             println!("🚀 Or:  coalesce demo \"func add(a, b int) int {{ return a + b }}\" --from go --to python");
             println!("� Or:  coalesce analyze-libs \"import React, {{ useState }} from 'react'\" --language javascript");
+            println!("🔎 Or:  coalesce query \"Function > ControlFlow\" \"function f(x) {{ if (x) {{ return 1; }} }}\"");
+            println!("📊 Or:  coalesce graph \"function add(a, b) {{ return a + b; }}\" --format mermaid");
+            println!("📞 Or:  coalesce calls app.py utils.py --language python --callers load");
+            println!("🧹 Or:  coalesce analyze app.py utils.py --language python --dead-code --recursion --globals --purity");
+            println!("📈 Or:  coalesce metrics app.py utils.py --language python");
+            println!("🔌 Or:  coalesce api app.py utils.py --language python -o api.json");
+            println!("📚 Or:  coalesce patterns sync");
             println!("�📦 Or:  coalesce init ./my-project");
+            println!("🚚 Or:  coalesce migrate ./my-project --to rust");
             println!("\n🔧 Supported languages:");
-            println!("   📥 Source: javascript, c, cpp, csharp, fsharp, vb, rust, go");
+            println!("   📥 Source: javascript, python, c, cpp, csharp, fsharp, vb, rust, go, cobol, kotlin, erlang, elixir, bash, r, abap, rpg, asm");
             println!("   📤 Target: python, rust, c, go");
         }
     }
+    
+    Ok(())
+}
 
+/// The root of the project the working directory is in; outside of one, the working directory
+fn project_dir() -> Result<std::path::PathBuf> {
+    let cwd = std::env::current_dir()?;
+    Ok(project_root(&cwd).unwrap_or(cwd))
+}
+
+/// The library abstraction layer of the project the working directory is in, and how many
+/// patterns it loaded over the built-in ones
+fn library_layer() -> Result<(LibraryAbstractionLayer, usize)> {
+    let lal = LibraryAbstractionLayer::new_for_project(project_dir()?)?;
+    let loaded = lal.project_patterns();
+    Ok((lal, loaded))
+}
+
+/// Write the manifest of a translation into `dir`, naming the project after it; its path, if
+/// the target has a manifest format
+fn write_target_manifest(dir: &str, manifest: &TargetManifest) -> Result<Option<std::path::PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let dir_name = std::path::Path::new(dir).canonicalize()?;
+    let name = package_name(dir_name.file_name().and_then(|name| name.to_str()).unwrap_or("app"));
+    match (manifest.file_name(&name), manifest.render(&name)) {
+        (Some(file), Some(text)) => {
+            let path = std::path::Path::new(dir).join(file);
+            fs::write(&path, text)?;
+            Ok(Some(path))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// A project name as a package name, its characters other than letters, digits, `-` and `_`
+/// replaced with `-`
+fn package_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect()
+}
+
+/// Say what `write_target_manifest` wrote, and which libraries it has no package for
+fn report_target_manifest(path: Option<&std::path::Path>, manifest: &TargetManifest) {
+    match path {
+        Some(path) => println!("📦 Wrote {} with {} package(s)", path.display(), manifest.packages.len()),
+        None => println!("⚠️  No manifest to write for {} code", manifest.ecosystem),
+    }
+    for library in &manifest.unresolved {
+        println!("⚠️  No package known for {}: list it under `packages` in its rules if it needs one", library);
+    }
+}
+
+/// Read the libraries the `--target-project` depends on, for translations to prefer
+fn target_project(lal: &mut LibraryAbstractionLayer, sub_matches: &clap::ArgMatches, target_lang: &Language) -> Result<()> {
+    let root = sub_matches.get_one::<String>("target-project").unwrap();
+    if let Some(manifest) = lal.use_target_project(root, target_lang)? {
+        println!("🎯 Preferring the {} libraries {} depends on", lal.target_dependencies().len(), manifest.display());
+    }
     Ok(())
 }
+
+/// A usage's location as `file:line:column`, counting columns from 1
+fn call_site(location: &SourceLocation) -> String {
+    format!("{}:{}:{}", location.file, location.start_line, location.start_column + 1)
+}
+
+/// A file input by its path, a snippet as `module`
+fn input_name(input: &str) -> &str {
+    if std::path::Path::new(input).exists() { input } else { "module" }
+}
+
+/// Parse each input file as a module named by its path, or a snippet as a single module;
+/// `None` after reporting an unsupported `--encoding`
+fn parse_modules(sub_matches: &clap::ArgMatches, language: Language) -> Result<Option<Vec<(String, UIRNode)>>> {
+    let mut modules = Vec::new();
+    for input in sub_matches.get_many::<String>("input").unwrap() {
+        let (name, code) = if std::path::Path::new(input).exists() {
+            let encoding = match sub_matches.get_one::<String>("encoding") {
+                Some(label) => match SourceEncoding::from_label(label) {
+                    Some(encoding) => Some(encoding),
+                    None => {
+                        println!("❌ Unsupported encoding: {}", label);
+                        return Ok(None);
+                    }
+                },
+                None => None,
+            };
+            (input.clone(), read_source(input, encoding)?.text)
+        } else {
+            ("module".to_string(), input.clone())
+        };
+        modules.push((name, create_parser(language.clone())?.parse(&code)?));
+    }
+    Ok(Some(modules))
+}
+
+/// The language a `--from` or `--language` label names
+fn source_language(label: &str) -> Option<Language> {
+    let language = match label {
+        "javascript" | "js" => Language::JavaScript,
+        "c" => Language::C,
+        "cpp" | "c++" => Language::Cpp,
+        "csharp" | "cs" | "c#" => Language::CSharp,
+        "fsharp" | "fs" | "f#" => Language::FSharp,
+        "vb" | "visualbasic" | "visual-basic" => Language::VisualBasic,
+        "rust" | "rs" => Language::Rust,
+        "go" => Language::Go,
+        "python" | "py" => Language::Python,
+        "cobol" | "cob" | "cbl" => Language::Cobol,
+        "kotlin" | "kt" => Language::Kotlin,
+        "erlang" | "erl" => Language::Erlang,
+        "elixir" | "ex" => Language::Elixir,
+        "bash" | "sh" | "shell" => Language::Bash,
+        "r" => Language::R,
+        "abap" => Language::Abap,
+        "rpg" | "rpgle" => Language::Rpg,
+        "asm" | "assembly" => Language::Assembly,
+        _ => return None,
+    };
+    Some(language)
+}
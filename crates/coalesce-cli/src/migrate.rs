@@ -0,0 +1,781 @@
+// Project migration: translate every source file of a project set up by `coalesce init` into
+// each of its target languages, as a tree mirroring the project's, with a report of what each
+// translation left for review
+
+use crate::{package_name, source_language};
+use crate::report::{DetectedLibrary, FileReport, LegacyReport, MigrationReport, ReportFormat, SkippedConstruct, TargetReport};
+use coalesce_core::{CommentStyle, Generator, GeneratorConfig, IncrementalParser, Language, NamingConvention, NodeType, ProjectConfig, TextEdit, UIRNode, CONFIG_FILE, read_source};
+use coalesce_parser::{create_incremental_parser, create_parser, GrammarRegistry, SyntaxTree};
+use coalesce_gen::{PythonGenerator, RustGenerator, CGenerator, GoGenerator, TypeScriptGenerator, TemplateGenerator};
+use coalesce_lal::{LibraryAbstractionLayer, LibraryDependency};
+use coalesce_lal::coverage::CoverageReport;
+use coalesce_analysis::{FoldConfig, Purity, analyze_data_flow, fold_constants, infer_types, measure_complexity};
+use anyhow::Result;
+use notify::{RecursiveMode, Watcher};
+use similar::TextDiff;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Directories no project keeps its own sources in: dependencies, build output and caches
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "__pycache__", "bin", "obj", "dist", "build"];
+
+/// How long after a change `--watch` waits for the rest of a save's events
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// A target language: how to name its files and generate them
+struct Target {
+    label: String,
+    language: Language,
+    extension: &'static str,
+    generator: Box<dyn Generator>,
+}
+
+/// A source file's analyzed UIR, ready to translate to each target
+struct Analyzed {
+    uir: UIRNode,
+    code: String,
+    dependencies: Vec<LibraryDependency>,
+    skipped: Vec<SkippedConstruct>,
+    legacy: Vec<LegacyReport>,
+    warnings: Vec<String>,
+}
+
+/// A source file as last read: its syntax tree, for a later edit to reparse only what it
+/// changed, its analyzed UIR, and what each target made of it
+struct SourceFile {
+    tree: Option<SyntaxTree>,
+    module: std::result::Result<Analyzed, String>,
+    /// One per target, in their order
+    reports: Vec<FileReport>,
+    /// The library usages each target's translation replaced, in the same order
+    coverage: Vec<CoverageReport>,
+}
+
+/// How `--dry-run` and `--diff` show a migration instead of writing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Preview {
+    /// List the files it would create or change
+    List,
+    /// Show how each would change as a unified diff
+    Diff,
+}
+
+impl Preview {
+    /// What the preview shows of the file at `path`, holding `existing` if it exists, becoming
+    /// `text`; nothing for a file it wouldn't change
+    fn show(self, path: &Path, existing: Option<&str>, text: &str) -> String {
+        if existing == Some(text) {
+            return String::new();
+        }
+        match self {
+            Preview::List => format!("   {} {}\n", if existing.is_some() { "~" } else { "+" }, path.display()),
+            Preview::Diff => {
+                let old = if existing.is_some() { path.display().to_string() } else { "/dev/null".to_string() };
+                let diff = TextDiff::from_lines(existing.unwrap_or_default(), text);
+                diff.unified_diff().header(&old, &path.display().to_string()).to_string()
+            }
+        }
+    }
+}
+
+/// A project's migration: its settings and, by path from its root, its source files as last
+/// translated
+struct Migration {
+    root: PathBuf,
+    /// The root and output as the file system names them, to match found and watched paths against
+    canonical_root: PathBuf,
+    canonical_out: PathBuf,
+    out: PathBuf,
+    config: ProjectConfig,
+    sources: Vec<Language>,
+    targets: Vec<Target>,
+    grammars: GrammarRegistry,
+    optimize: FoldConfig,
+    lal: LibraryAbstractionLayer,
+    ecosystem: Option<String>,
+    library_uir: bool,
+    style: GeneratorConfig,
+    preview: Option<Preview>,
+    report_format: ReportFormat,
+    /// Where to write the report instead of the output directory; even in a preview
+    report_file: Option<PathBuf>,
+    files: BTreeMap<PathBuf, SourceFile>,
+}
+
+/// Run `coalesce migrate`, or `coalesce translate`
+pub(crate) fn migrate(sub_matches: &clap::ArgMatches) -> Result<()> {
+    let Some(mut migration) = Migration::new(sub_matches)? else {
+        return Ok(());
+    };
+    let labels: Vec<&str> = migration.targets.iter().map(|target| target.label.as_str()).collect();
+    println!("🚚 Migrating {} from {} to {}", migration.root.display(), migration.config.source_languages.join(", "), labels.join(", "));
+    if migration.lal.project_patterns() > 0 {
+        println!("📚 Loaded {} project library pattern(s)", migration.lal.project_patterns());
+    }
+    
+    let mut files = Vec::new();
+    collect_files(&migration.canonical_root, &migration.canonical_out, &mut files)?;
+    let files: Vec<PathBuf> = files.iter().filter_map(|path| migration.source_path(path)).collect();
+    println!("📂 Found {} source file(s)", files.len());
+    for relative in &files {
+        migration.update(relative)?;
+    }
+    
+    let report = migration.write_report()?;
+    for target in &report.targets {
+        let translated = target.files.iter().filter(|file| file.output.is_some()).count();
+        println!("\n🎯 {}: translated {} of {} file(s) into {}", target.language, translated, target.files.len(), target.output.display());
+        for file in target.files.iter().filter(|file| file.error.is_some()) {
+            println!("   ❌ {}: {}", file.source.display(), file.error.as_deref().unwrap_or_default());
+        }
+        if let (Some(manifest), None) = (&target.manifest, migration.preview) {
+            println!("   📦 Wrote {}", manifest.display());
+        }
+        for library in &target.unresolved {
+            println!("   ⚠️  No package known for {}: list it under `packages` in its rules if it needs one", library);
+        }
+        let fallbacks: usize = target.files.iter().map(|file| file.fallbacks.len()).sum();
+        if fallbacks > 0 {
+            println!("   🚧 {} line(s) hold a TODO or source copied as written, to translate by hand", fallbacks);
+        }
+        match target.coverage.percent() {
+            Some(percent) => println!(
+                "   📊 {:.0}% of library calls translated automatically, {} need manual work",
+                percent,
+                target.coverage.manual,
+            ),
+            None => println!("   📊 No library calls"),
+        }
+    }
+    let legacy: usize = migration.files.values().filter_map(|file| file.module.as_ref().ok()).map(|module| module.legacy.len()).sum();
+    if legacy > 0 {
+        let kept = if migration.config.preserve_legacy_patterns { "kept as comments for review" } else { "not kept: preserve_legacy_patterns is off" };
+        println!("\n🏛️  {} legacy pattern(s), {}", legacy, kept);
+    }
+    if let Some(path) = migration.report_path() {
+        println!("\n📝 Wrote {}", path.display());
+    }
+    if migration.preview.is_some() {
+        println!("\n🔍 No translation written: this was a preview");
+    }
+    
+    if sub_matches.get_flag("watch") {
+        migration.watch()?;
+    }
+    Ok(())
+}
+
+impl Migration {
+    /// The migration `coalesce migrate` asks for; `None` after reporting why there's none
+    fn new(sub_matches: &clap::ArgMatches) -> Result<Option<Self>> {
+        let root = PathBuf::from(sub_matches.get_one::<String>("directory").unwrap());
+        let Some(config) = ProjectConfig::load(&root)? else {
+            println!("❌ No {} in {}: run `coalesce init` to create one", CONFIG_FILE, root.display());
+            return Ok(None);
+        };
+        let out = match sub_matches.get_one::<String>("out") {
+            Some(dir) => PathBuf::from(dir),
+            None => root.join("coalesce-out"),
+        };
+        let labels: Vec<String> = match sub_matches.get_many::<String>("to") {
+            Some(labels) => labels.cloned().collect(),
+            None => config.target_languages.clone(),
+        };
+        
+        let config_path = root.join(CONFIG_FILE);
+        let grammars = GrammarRegistry::from_config(&config, config_path.parent().unwrap_or(&root))?;
+        let optimize = FoldConfig::from_config_file(&config_path)?;
+        
+        let mut sources = Vec::new();
+        for label in &config.source_languages {
+            match source_language(label) {
+                Some(language) => sources.push(language),
+                None if grammars.contains(label) => sources.push(Language::Custom(label.clone())),
+                None => {
+                    println!("❌ Unsupported source language in {}: {}", CONFIG_FILE, label);
+                    return Ok(None);
+                }
+            }
+        }
+        let mut targets = Vec::new();
+        for label in &labels {
+            match target(label)? {
+                Some(target) => targets.push(target),
+                None => {
+                    println!("❌ Unsupported target language: {}", label);
+                    return Ok(None);
+                }
+            }
+        }
+        if sources.is_empty() || targets.is_empty() {
+            println!("❌ {} lists no source or target languages", CONFIG_FILE);
+            return Ok(None);
+        }
+        
+        let style = config.style.clone().unwrap_or_default();
+        let preview = match (sub_matches.get_flag("diff"), sub_matches.get_flag("dry-run")) {
+            (true, _) => Some(Preview::Diff),
+            (false, true) => Some(Preview::List),
+            (false, false) => None,
+        };
+        // The output may live inside the project; it isn't a source
+        if preview.is_none() {
+            fs::create_dir_all(&out)?;
+        }
+        Ok(Some(Self {
+            canonical_root: root.canonicalize()?,
+            canonical_out: if out.exists() { out.canonicalize()? } else { std::path::absolute(&out)? },
+            lal: LibraryAbstractionLayer::new_for_project(&root)?,
+            root,
+            out,
+            config,
+            sources,
+            targets,
+            grammars,
+            optimize,
+            ecosystem: sub_matches.get_one::<String>("ecosystem").cloned(),
+            library_uir: sub_matches.get_flag("library-uir"),
+            style,
+            preview,
+            report_format: sub_matches.get_one::<String>("report").and_then(|label| ReportFormat::from_label(label)).unwrap_or(ReportFormat::Json),
+            report_file: sub_matches.get_one::<String>("report-file").map(PathBuf::from),
+            files: BTreeMap::new(),
+        }))
+    }
+    
+    /// A found or watched path's path from the root, if it's a file to translate: one in a
+    /// source language, outside skipped directories and the output
+    fn source_path(&self, path: &Path) -> Option<PathBuf> {
+        if path.starts_with(&self.canonical_out) {
+            return None;
+        }
+        let relative = path.strip_prefix(&self.canonical_root).ok()?;
+        let skipped = relative.parent()?.components().any(|component| is_skipped(&component.as_os_str().to_string_lossy()));
+        (!skipped && self.language_of(relative).is_some()).then(|| relative.to_path_buf())
+    }
+    
+    fn language_of(&self, relative: &Path) -> Option<Language> {
+        let name = relative.file_name()?.to_str()?;
+        let extension = relative.extension()?.to_str()?;
+        self.sources.iter().find(|language| match language {
+            Language::Custom(_) => self.grammars.language_for_file(name).as_ref() == Some(*language),
+            language => extensions(language).iter().any(|known| known.eq_ignore_ascii_case(extension)),
+        }).cloned()
+    }
+    
+    /// Read, analyze and translate a source file again, unless its text is what was last
+    /// translated; the names of the declarations an edit changed, when it reparsed only those
+    fn update(&mut self, relative: &Path) -> Result<Option<Vec<String>>> {
+        let Some(language) = self.language_of(relative) else {
+            return Ok(None);
+        };
+        let previous = self.files.remove(relative);
+        let code = match read_source(&self.canonical_root.join(relative).to_string_lossy(), None) {
+            Ok(source) => source.text,
+            Err(e) => {
+                self.files.insert(relative.to_path_buf(), self.failed(relative, e.to_string()));
+                return Ok(Some(Vec::new()));
+            }
+        };
+        if let Some(previous) = previous {
+            if previous.module.as_ref().is_ok_and(|module| module.code == code) {
+                self.files.insert(relative.to_path_buf(), previous);
+                return Ok(None);
+            }
+            if let Some(tree) = previous.tree {
+                return self.translate_file(relative, &language, code, Some(tree)).map(Some);
+            }
+        }
+        self.translate_file(relative, &language, code, None).map(Some)
+    }
+    
+    fn translate_file(&mut self, relative: &Path, language: &Language, code: String, previous: Option<SyntaxTree>) -> Result<Vec<String>> {
+        let (tree, changed, module) = match self.parse(language, &code, previous) {
+            Ok((tree, uir, changed)) => (tree, changed, self.analyze(relative, language, code, uir).map_err(|e| e.to_string())),
+            Err(e) => (None, Vec::new(), Err(e.to_string())),
+        };
+        let module = match module {
+            Ok(module) => module,
+            Err(e) => {
+                let mut file = self.failed(relative, e);
+                file.tree = tree;
+                self.files.insert(relative.to_path_buf(), file);
+                return Ok(changed);
+            }
+        };
+        let mut reports = Vec::new();
+        let mut coverage = Vec::new();
+        for target in &self.targets {
+            let mut file = FileReport {
+                source: relative.to_path_buf(),
+                output: None,
+                error: None,
+                library_usages: module.dependencies.iter().map(|dependency| dependency.usage_patterns.len()).sum(),
+                skipped: module.skipped.clone(),
+                fallbacks: Vec::new(),
+                legacy: module.legacy.clone(),
+                warnings: module.warnings.clone(),
+            };
+            match self.translate(&module, target) {
+                Ok((code, fallbacks, translated)) => {
+                    coverage.push(translated);
+                    let path = self.out.join(&target.label).join(output_path(relative, target));
+                    self.write(&path, &code)?;
+                    file.fallbacks = fallbacks;
+                    file.output = Some(path);
+                }
+                Err(e) => {
+                    file.error = Some(e.to_string());
+                    coverage.push(CoverageReport::default());
+                }
+            }
+            reports.push(file);
+        }
+        self.files.insert(relative.to_path_buf(), SourceFile { tree, module: Ok(module), reports, coverage });
+        Ok(changed)
+    }
+    
+    /// Write a file of the migration, or in a preview show how it would change; one it
+    /// wouldn't change isn't shown
+    fn write(&self, path: &Path, text: &str) -> Result<()> {
+        let Some(preview) = self.preview else {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, text)?;
+            return Ok(());
+        };
+        print!("{}", preview.show(path, fs::read_to_string(path).ok().as_deref(), text));
+        Ok(())
+    }
+    
+    /// A file that couldn't be translated, and why
+    fn failed(&self, relative: &Path, error: String) -> SourceFile {
+        let report = FileReport {
+            source: relative.to_path_buf(),
+            output: None,
+            error: Some(error.clone()),
+            library_usages: 0,
+            skipped: Vec::new(),
+            fallbacks: Vec::new(),
+            legacy: Vec::new(),
+            warnings: Vec::new(),
+        };
+        SourceFile { tree: None, module: Err(error), reports: vec![report; self.targets.len()], coverage: vec![CoverageReport::default(); self.targets.len()] }
+    }
+    
+    /// Parse a source file, reparsing only what changed since `previous` where the language's
+    /// parser keeps syntax trees; the names of the declarations that reparse converted again
+    fn parse(&self, language: &Language, code: &str, previous: Option<SyntaxTree>) -> Result<(Option<SyntaxTree>, UIRNode, Vec<String>)> {
+        let incremental: Option<Box<dyn IncrementalParser<Tree = SyntaxTree>>> = match language {
+            Language::Custom(name) => Some(Box::new(self.grammars.create_parser(name)?)),
+            _ => create_incremental_parser(language.clone()).ok(),
+        };
+        let Some(parser) = incremental else {
+            return Ok((None, create_parser(language.clone())?.parse(code)?, Vec::new()));
+        };
+        let edit = previous.as_ref().and_then(|tree| TextEdit::between(tree.source(), code).map(|edit| (tree, edit)));
+        match edit {
+            Some((tree, edit)) => {
+                let update = parser.reparse(tree, &[edit])?;
+                let uir = parser.to_uir(&update.tree)?;
+                let changed = update.changed.iter().filter_map(|node| node.name.clone()).collect();
+                Ok((Some(update.tree), uir, changed))
+            }
+            None => {
+                let (tree, uir) = parser.parse_tree(code)?;
+                Ok((Some(tree), uir, Vec::new()))
+            }
+        }
+    }
+    
+    /// Analyze a parsed source file the way `demo` does, keeping its legacy patterns as
+    /// comments when the project asks to
+    fn analyze(&self, relative: &Path, language: &Language, code: String, mut uir: UIRNode) -> Result<Analyzed> {
+        fold_constants(&mut uir, &self.optimize);
+        if matches!(language, Language::Python | Language::JavaScript) {
+            infer_types(&mut uir);
+        }
+        let warnings = analyze_data_flow(&mut uir).iter().map(|warning| warning.to_string()).collect();
+        measure_complexity(&mut uir);
+        
+        let skipped = uir.metadata.annotations.get("diagnostics")
+            .and_then(|diagnostics| serde_json::from_value(diagnostics.clone()).ok())
+            .unwrap_or_default();
+        let name = relative.to_string_lossy();
+        let dependencies = self.lal.analyze(&uir, &code)?.in_file(&name).dependencies;
+        self.lal.enhance_uir(&mut uir, &dependencies)?;
+        Purity::analyze([(name.as_ref(), &uir)], &self.lal.side_effects()).annotate(&name, &mut uir);
+        
+        let preserve = self.config.preserve_legacy_patterns;
+        let mut legacy = Vec::new();
+        collect_legacy(&uir, preserve, &mut legacy);
+        if preserve {
+            let own = legacy_comments(&uir);
+            preserve_legacy(&mut uir);
+            uir.children.splice(0..0, own);
+        }
+        Ok(Analyzed { uir, code, dependencies, skipped, legacy, warnings })
+    }
+    
+    /// Translate an analyzed module's library calls for a target and generate its code, with
+    /// the lines of it that are generator fallbacks and the coverage of the calls it translated;
+    /// with `--library-uir`, calls become the UIR of their translations where the target has a
+    /// parser, else they are only annotated and none counts as translated
+    fn translate(&self, module: &Analyzed, target: &Target) -> Result<(String, Vec<usize>, CoverageReport)> {
+        let ecosystem = self.ecosystem.as_deref();
+        let (uir, coverage) = match self.library_uir.then(|| create_parser(target.language.clone())) {
+            Some(Ok(parser)) => self.lal.rewrite_library_calls_with_coverage(&module.uir, &module.code, &module.dependencies, parser.as_ref(), ecosystem)?,
+            _ => (
+                self.lal.transform_library_calls(&module.uir, target.language.clone(), ecosystem)?,
+                self.lal.coverage(&module.dependencies, target.language.clone(), ecosystem).only_applied(|_| false),
+            ),
+        };
+        let (code, fallbacks) = target.generator.generate_with_fallbacks(&uir, &self.style)?;
+        Ok((code, fallbacks, coverage))
+    }
+    
+    /// Forget a deleted source file, deleting its translations
+    fn remove(&mut self, relative: &Path) -> Result<bool> {
+        let Some(file) = self.files.remove(relative) else {
+            return Ok(false);
+        };
+        for output in file.reports.iter().filter_map(|report| report.output.as_ref()) {
+            if output.exists() {
+                fs::remove_file(output)?;
+            }
+        }
+        Ok(true)
+    }
+    
+    /// Write each target's manifest and the migration report, of the files as last translated;
+    /// a preview only shows the manifests
+    fn write_report(&self) -> Result<MigrationReport> {
+        let ecosystem = self.ecosystem.as_deref();
+        let project = match self.canonical_root.file_name().and_then(|name| name.to_str()) {
+            _ if !self.config.project_name.is_empty() => self.config.project_name.clone(),
+            Some(name) => name.to_string(),
+            None => "app".to_string(),
+        };
+        let mut libraries: BTreeMap<(String, String), DetectedLibrary> = BTreeMap::new();
+        for (relative, module) in self.files.iter().filter_map(|(relative, file)| Some((relative, file.module.as_ref().ok()?))) {
+            for dependency in &module.dependencies {
+                let library = libraries.entry((dependency.name.clone(), dependency.ecosystem.clone())).or_insert_with(|| DetectedLibrary {
+                    name: dependency.name.clone(),
+                    ecosystem: dependency.ecosystem.clone(),
+                    usages: 0,
+                    files: Vec::new(),
+                });
+                library.usages += dependency.usage_patterns.len();
+                library.files.push(relative.clone());
+            }
+        }
+        let mut report = MigrationReport {
+            project: project.clone(),
+            source_languages: self.config.source_languages.clone(),
+            preserve_legacy_patterns: self.config.preserve_legacy_patterns,
+            libraries: libraries.into_values().collect(),
+            targets: Vec::new(),
+        };
+        for (i, target) in self.targets.iter().enumerate() {
+            let dir = self.out.join(&target.label);
+            let mut coverage = CoverageReport::default();
+            let mut dependencies = Vec::new();
+            for file in self.files.values() {
+                // The manifest lists the packages of the calls the output translated
+                if let Ok(module) = &file.module {
+                    dependencies.extend(file.coverage[i].translated_dependencies(&module.dependencies));
+                }
+                coverage.merge(file.coverage[i].clone());
+            }
+            if target.language == Language::Rust {
+                self.write_crate_root(&dir, i)?;
+            }
+            let (manifest, unresolved) = if dependencies.is_empty() {
+                (None, Vec::new())
+            } else {
+                let manifest = self.lal.target_manifest(&dependencies, target.language.clone(), ecosystem);
+                let name = package_name(&project);
+                let path = match (manifest.file_name(&name), manifest.render(&name)) {
+                    (Some(file), Some(text)) => {
+                        let path = dir.join(file);
+                        self.write(&path, &text)?;
+                        Some(path)
+                    }
+                    _ => None,
+                };
+                (path, manifest.unresolved.into_iter().collect())
+            };
+            report.targets.push(TargetReport {
+                language: target.label.clone(),
+                output: dir,
+                files: self.files.values().map(|file| file.reports[i].clone()).collect(),
+                manifest,
+                automatic_percent: coverage.percent(),
+                confidence: coverage.confidence(),
+                coverage,
+                unresolved,
+            });
+        }
+        if let Some(path) = self.report_path() {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, self.report_format.render(&report)?)?;
+        }
+        Ok(report)
+    }
+    
+    /// Declare the Rust target's modules, the `i`th target's translations under `src`: each in
+    /// the crate root `src/lib.rs` or, in a directory, that directory's `mod.rs`
+    fn write_crate_root(&self, dir: &Path, i: usize) -> Result<()> {
+        let src = dir.join("src");
+        let mut modules: BTreeMap<PathBuf, BTreeSet<String>> = BTreeMap::new();
+        for output in self.files.values().filter_map(|file| file.reports[i].output.as_ref()) {
+            let Ok(relative) = output.strip_prefix(&src) else {
+                continue;
+            };
+            let mut parent = PathBuf::new();
+            for component in relative.with_extension("").components() {
+                let name = component.as_os_str().to_string_lossy().to_string();
+                modules.entry(parent.clone()).or_default().insert(name.clone());
+                parent.push(name);
+            }
+        }
+        for (parent, children) in &modules {
+            let declarations: String = children.iter().map(|child| format!("pub mod {};\n", child)).collect();
+            let file = if parent.as_os_str().is_empty() { src.join("lib.rs") } else { src.join(parent).join("mod.rs") };
+            self.write(&file, &declarations)?;
+        }
+        Ok(())
+    }
+    
+    /// Where the report goes: `--report-file`, else the output directory unless previewing
+    fn report_path(&self) -> Option<PathBuf> {
+        match (&self.report_file, self.preview) {
+            (Some(path), _) => Some(path.clone()),
+            (None, None) => Some(self.out.join(self.report_format.file_name())),
+            (None, Some(_)) => None,
+        }
+    }
+    
+    /// Translate source files again as they change, until interrupted
+    fn watch(&mut self) -> Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        watcher.watch(&self.canonical_root, RecursiveMode::Recursive)?;
+        println!("\n👀 Watching {} for changes (Ctrl+C to stop)", self.root.display());
+        
+        while let Ok(event) = receiver.recv() {
+            // An editor's save is several events; take them together
+            let mut paths = BTreeSet::new();
+            let mut next = Some(event);
+            while let Some(event) = next {
+                paths.extend(event?.paths);
+                next = receiver.recv_timeout(WATCH_DEBOUNCE).ok();
+            }
+            
+            let mut changed = false;
+            for path in paths {
+                let Some(relative) = self.source_path(&path) else {
+                    continue;
+                };
+                if !path.is_file() {
+                    if self.remove(&relative)? {
+                        println!("🗑️  {}: removed its translations", relative.display());
+                        changed = true;
+                    }
+                    continue;
+                }
+                let Some(declarations) = self.update(&relative)? else {
+                    continue;
+                };
+                changed = true;
+                match declarations.is_empty() {
+                    true => println!("↻ {}", relative.display()),
+                    false => println!("↻ {} ({})", relative.display(), declarations.join(", ")),
+                }
+                for (target, report) in self.targets.iter().zip(&self.files[&relative].reports) {
+                    match (&report.output, &report.error) {
+                        (Some(output), _) => println!("   🎯 {}: {}", target.label, output.display()),
+                        (None, error) => println!("   ❌ {}: {}", target.label, error.as_deref().unwrap_or_default()),
+                    }
+                }
+            }
+            if changed {
+                self.write_report()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn collect_legacy(node: &UIRNode, preserved: bool, legacy: &mut Vec<LegacyReport>) {
+    for pattern in &node.metadata.legacy_patterns {
+        legacy.push(LegacyReport {
+            pattern_type: pattern.pattern_type.clone(),
+            original: pattern.original_construct.clone(),
+            hint: pattern.modernization_hint.clone(),
+            preserved,
+            line: node.source_location.as_ref().map(|location| location.start_line),
+        });
+    }
+    for child in &node.children {
+        collect_legacy(child, preserved, legacy);
+    }
+}
+
+/// Put a comment quoting each legacy construct before the node it was found in
+fn preserve_legacy(node: &mut UIRNode) {
+    for child in &mut node.children {
+        preserve_legacy(child);
+    }
+    let mut children = Vec::new();
+    for child in std::mem::take(&mut node.children) {
+        children.extend(legacy_comments(&child));
+        children.push(child);
+    }
+    node.children = children;
+}
+
+fn legacy_comments(node: &UIRNode) -> Vec<UIRNode> {
+    node.metadata.legacy_patterns.iter().enumerate().map(|(i, pattern)| {
+        let text = match &pattern.modernization_hint {
+            Some(hint) => format!("legacy {} preserved from the source: {} ({})", pattern.pattern_type, pattern.original_construct, hint),
+            None => format!("legacy {} preserved from the source: {}", pattern.pattern_type, pattern.original_construct),
+        };
+        let mut comment = UIRNode::new(
+            format!("{}~legacy{}", node.id, i),
+            NodeType::Comment { style: CommentStyle::Line, text, attached_to: Some(node.id.clone()) },
+        );
+        comment.metadata.source_language = node.metadata.source_language.clone();
+        comment
+    }).collect()
+}
+
+/// The files under `dir`, sorted, skipping hidden directories, those of dependencies and
+/// build output, and `out`
+fn collect_files(dir: &Path, out: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?.map(|entry| entry.map(|entry| entry.path())).collect::<std::io::Result<_>>()?;
+    entries.sort();
+    for path in entries {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if path.is_dir() {
+            let skipped = is_skipped(name) || path.canonicalize()? == out;
+            if !skipped {
+                collect_files(&path, out, files)?;
+            }
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_skipped(dir: &str) -> bool {
+    dir.starts_with('.') || SKIPPED_DIRS.contains(&dir)
+}
+
+/// Extensions of a built-in source language's files, in lower case; mainframe sources are
+/// often named in upper case, as `ACCOUNT.COB`
+fn extensions(language: &Language) -> &'static [&'static str] {
+    match language {
+        Language::JavaScript => &["js", "mjs", "cjs", "jsx"],
+        Language::Python => &["py"],
+        Language::C => &["c", "h"],
+        Language::Cpp => &["cpp", "cc", "cxx", "hpp", "hh", "hxx"],
+        Language::CSharp => &["cs"],
+        Language::FSharp => &["fs", "fsx"],
+        Language::VisualBasic => &["vb", "bas"],
+        Language::Rust => &["rs"],
+        Language::Go => &["go"],
+        Language::Cobol => &["cob", "cbl", "cpy"],
+        Language::Kotlin => &["kt", "kts"],
+        Language::Erlang => &["erl", "hrl"],
+        Language::Elixir => &["ex", "exs"],
+        Language::Bash => &["sh", "bash"],
+        Language::R => &["r"],
+        Language::Abap => &["abap"],
+        Language::Rpg => &["rpg", "rpgle", "sqlrpgle"],
+        Language::Assembly => &["asm", "s"],
+        _ => &[],
+    }
+}
+
+/// Rust keywords, which can't name a module
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for", "if",
+    "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static", "struct", "super",
+    "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+/// Where a source file's translation goes in its target's directory: Rust's under `src`, and
+/// Rust and Python modules, and the directories holding them, named so the language can import
+/// them, as `api_client.rs` of `api-client.js`
+fn output_path(relative: &Path, target: &Target) -> PathBuf {
+    let path = relative.with_extension(target.extension);
+    if !matches!(target.language, Language::Rust | Language::Python) {
+        return path;
+    }
+    let mut output = PathBuf::new();
+    if target.language == Language::Rust {
+        output.push("src");
+    }
+    let components: Vec<String> = relative.with_extension("").components().map(|component| component.as_os_str().to_string_lossy().to_string()).collect();
+    for (i, component) in components.iter().enumerate() {
+        let mut name: String = NamingConvention::SnakeCase.apply(component).chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+            .collect();
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            name.insert(0, '_');
+        }
+        // The crate root and directory modules are declarations `write_crate_root` writes
+        let reserved = RUST_KEYWORDS.contains(&name.as_str()) || (i == 0 && matches!(name.as_str(), "lib" | "main"));
+        if target.language == Language::Rust && reserved {
+            name.push_str("_module");
+        }
+        output.push(name);
+    }
+    output.set_extension(target.extension);
+    output
+}
+
+/// The target a `--to` label or `target_languages` entry names
+fn target(label: &str) -> Result<Option<Target>> {
+    let (language, extension, generator): (Language, &'static str, Box<dyn Generator>) = match label {
+        "python" | "py" => (Language::Python, "py", Box::new(PythonGenerator)),
+        "rust" | "rs" => (Language::Rust, "rs", Box::new(RustGenerator)),
+        "c" => (Language::C, "c", Box::new(CGenerator)),
+        "go" => (Language::Go, "go", Box::new(GoGenerator)),
+        "typescript" | "ts" => (Language::TypeScript, "ts", Box::new(TypeScriptGenerator)),
+        "lua" => (Language::Custom("lua".to_string()), "lua", Box::new(TemplateGenerator::lua()?)),
+        _ => return Ok(None),
+    };
+    Ok(Some(Target { label: label.to_string(), language, extension, generator }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_previews_list_and_diff_only_changed_files() {
+        let path = Path::new("out/python/app.py");
+        assert_eq!(Preview::List.show(path, None, "x = 1\n"), "   + out/python/app.py\n");
+        assert_eq!(Preview::List.show(path, Some("x = 0\n"), "x = 1\n"), "   ~ out/python/app.py\n");
+        assert_eq!(Preview::Diff.show(path, Some("x = 1\n"), "x = 1\n"), "");
+        
+        assert_eq!(Preview::Diff.show(path, Some("x = 0\ny = 2\n"), "x = 1\ny = 2\n"), concat!(
+            "--- out/python/app.py\n",
+            "+++ out/python/app.py\n",
+            "@@ -1,2 +1,2 @@\n",
+            "-x = 0\n",
+            "+x = 1\n",
+            " y = 2\n",
+        ));
+        assert!(Preview::Diff.show(path, None, "x = 1\n").starts_with("--- /dev/null\n+++ out/python/app.py\n@@ -0,0 +1 @@\n+x = 1\n"));
+    }
+}
@@ -0,0 +1,229 @@
+// Migration reports: what `coalesce migrate` translated and what it left for review, as JSON or
+// as SARIF 2.1.0 for code-scanning tools and CI annotations
+
+use coalesce_lal::coverage::CoverageReport;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReportFormat {
+    Json,
+    Sarif,
+}
+
+impl ReportFormat {
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "json" => Some(Self::Json),
+            "sarif" => Some(Self::Sarif),
+            _ => None,
+        }
+    }
+    
+    /// The report's name in the output directory
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Json => "migration-report.json",
+            Self::Sarif => "migration-report.sarif",
+        }
+    }
+    
+    pub fn render(self, report: &MigrationReport) -> serde_json::Result<String> {
+        match self {
+            Self::Json => serde_json::to_string_pretty(report),
+            Self::Sarif => serde_json::to_string_pretty(&sarif(report)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct MigrationReport {
+    pub project: String,
+    pub source_languages: Vec<String>,
+    pub preserve_legacy_patterns: bool,
+    /// The libraries the sources use, whatever the target
+    pub libraries: Vec<DetectedLibrary>,
+    pub targets: Vec<TargetReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct DetectedLibrary {
+    pub name: String,
+    pub ecosystem: String,
+    pub usages: usize,
+    pub files: Vec<PathBuf>,
+}
+
+/// What translating the project to one language produced
+#[derive(Debug, Serialize)]
+pub(crate) struct TargetReport {
+    pub language: String,
+    pub output: PathBuf,
+    pub files: Vec<FileReport>,
+    /// The manifest declaring the packages the translated library calls need
+    pub manifest: Option<PathBuf>,
+    /// The share of library calls translated automatically, in percent; none without any
+    pub automatic_percent: Option<f32>,
+    /// How sure the translations of library calls are, from 0 to 1; none without any
+    pub confidence: Option<f32>,
+    pub coverage: CoverageReport,
+    /// Libraries whose translations need packages no rule lists
+    pub unresolved: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct FileReport {
+    pub source: PathBuf,
+    pub output: Option<PathBuf>,
+    /// Why the file wasn't translated
+    pub error: Option<String>,
+    pub library_usages: usize,
+    /// Source the parser couldn't make sense of, left out of the translation
+    pub skipped: Vec<SkippedConstruct>,
+    /// Lines of the output where the generator wrote a TODO in place of a node it can't
+    /// translate, or copied its source as written
+    pub fallbacks: Vec<usize>,
+    pub legacy: Vec<LegacyReport>,
+    pub warnings: Vec<String>,
+}
+
+/// A syntax error, as the parser's `diagnostics` record it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SkippedConstruct {
+    pub kind: String,
+    pub message: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A construct the source's parser flagged as legacy
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct LegacyReport {
+    pub pattern_type: String,
+    pub original: String,
+    pub hint: Option<String>,
+    /// Whether the translation keeps it as a comment
+    pub preserved: bool,
+    pub line: Option<u32>,
+}
+
+/// Rules of the SARIF report: its id, level and description
+const RULES: &[(&str, &str, &str)] = &[
+    ("untranslated-file", "error", "A source file couldn't be translated"),
+    ("syntax-error", "warning", "Source the parser couldn't read, left out of the translation"),
+    ("generator-fallback", "warning", "A node the target's generator can't translate yet, left as a TODO or as its source"),
+    ("manual-library-call", "warning", "A library call no rule translates to the target, to write by hand"),
+    ("legacy-pattern", "note", "A legacy construct to restructure in the translation"),
+    ("data-flow", "note", "A data-flow warning about the source"),
+];
+
+/// The report as a SARIF log: a result per file left untranslated, syntax error, generator
+/// fallback, untranslated library call, legacy construct and data-flow warning; what depends
+/// only on the source is reported once, whatever the targets
+fn sarif(report: &MigrationReport) -> Value {
+    let mut results = Vec::new();
+    if let Some(first) = report.targets.first() {
+        for file in &first.files {
+            for skipped in &file.skipped {
+                results.push(result("syntax-error", skipped.message.clone(), location(&file.source, Some(skipped.line), Some(skipped.column))));
+            }
+            for legacy in &file.legacy {
+                let message = match &legacy.hint {
+                    Some(hint) => format!("legacy {} `{}`: {}", legacy.pattern_type, legacy.original, hint),
+                    None => format!("legacy {} `{}`", legacy.pattern_type, legacy.original),
+                };
+                results.push(result("legacy-pattern", message, location(&file.source, legacy.line, None)));
+            }
+            for warning in &file.warnings {
+                results.push(result("data-flow", warning.clone(), location(&file.source, None, None)));
+            }
+        }
+    }
+    for target in &report.targets {
+        for file in &target.files {
+            if let Some(error) = &file.error {
+                let message = format!("not translated to {}: {}", target.language, error);
+                results.push(result("untranslated-file", message, location(&file.source, None, None)));
+            }
+            if let Some(output) = &file.output {
+                for line in &file.fallbacks {
+                    let message = format!("the {} generator can't translate this node of {} yet", target.language, file.source.display());
+                    results.push(result("generator-fallback", message, location(output, Some(*line as u32), None)));
+                }
+            }
+        }
+        for usage in target.coverage.usages.iter().filter(|usage| usage.target.is_none()) {
+            let message = format!("{} ({}:{}) has no {} translation: write it by hand", usage.method_name, usage.library, usage.pattern, target.language);
+            let location = location(Path::new(&usage.source_location.file), Some(usage.source_location.start_line), Some(usage.source_location.start_column + 1));
+            let mut result = result("manual-library-call", message, location);
+            result["properties"] = json!({ "confidence": score(usage.confidence), "snippet": usage.snippet });
+            results.push(result);
+        }
+    }
+    
+    let rules: Vec<Value> = RULES.iter().map(|(id, level, description)| json!({
+        "id": id,
+        "shortDescription": { "text": description },
+        "defaultConfiguration": { "level": level },
+    })).collect();
+    let targets: Vec<Value> = report.targets.iter().map(|target| json!({
+        "language": target.language,
+        "files": target.files.len(),
+        "translated": target.files.iter().filter(|file| file.output.is_some()).count(),
+        "automaticPercent": target.automatic_percent.map(score),
+        "confidence": target.confidence.map(score),
+    })).collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "coalesce",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+            "properties": {
+                "project": report.project,
+                "libraries": report.libraries,
+                "targets": targets,
+            },
+        }],
+    })
+}
+
+/// A score to three decimals, without the digits widening an `f32` adds
+fn score(value: f32) -> f64 {
+    (f64::from(value) * 1000.0).round() / 1000.0
+}
+
+fn result(rule: &str, message: String, location: Value) -> Value {
+    let level = RULES.iter().find(|(id, ..)| *id == rule).map_or("warning", |(_, level, _)| level);
+    json!({
+        "ruleId": rule,
+        "level": level,
+        "message": { "text": message },
+        "locations": [location],
+    })
+}
+
+/// A location in a file, by its path from the project root unless absolute; at a line and
+/// column, from 1, when known
+fn location(path: &Path, line: Option<u32>, column: Option<u32>) -> Value {
+    let uri = path.to_string_lossy().replace('\\', "/");
+    let artifact = match path.is_absolute() {
+        true => json!({ "uri": format!("file://{}", uri) }),
+        false => json!({ "uri": uri.trim_start_matches("./"), "uriBaseId": "SRCROOT" }),
+    };
+    let mut physical = json!({ "artifactLocation": artifact });
+    if let Some(line) = line.filter(|line| *line > 0) {
+        physical["region"] = json!({ "startLine": line });
+        if let Some(column) = column {
+            physical["region"]["startColumn"] = json!(column);
+        }
+    }
+    json!({ "physicalLocation": physical })
+}
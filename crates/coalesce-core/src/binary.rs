@@ -0,0 +1,333 @@
+// Compact binary encoding of UIR trees
+//
+// JSON repeats every field name and node kind once per node, which dominates the size of
+// large trees. This format writes the tree's serde data model with each distinct string
+// stored once in a table up front and referred to by index, and integers as LEB128 varints.
+// It is self-describing like JSON, so skipped optional fields and free-form annotations
+// round-trip the same way:
+//
+//     "UIRB" version:u8 string_count:varint (length:varint bytes)* value
+//
+// A value is a tag byte followed by its payload: null, false and true have none, unsigned
+// and negative integers a varint (`-1 - n` for negatives), floats 8 little-endian bytes,
+// strings a table index, arrays a length and the items, objects a length and key index,
+// value pairs. Decoding refuses arrays and objects nested more than `MAX_DEPTH` deep, so a
+// hostile file fails with an error instead of overflowing the stack.
+use crate::errors::{CoalesceError, Result};
+use crate::types::UIRNode;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 4] = b"UIRB";
+const VERSION: u8 = 1;
+
+/// How many arrays and objects a decoded value may nest, the limit serde_json puts on JSON
+/// input, so a tree loads from either format or neither; each node takes two levels (the node
+/// object and its `children` array)
+const MAX_DEPTH: usize = 128;
+
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const UNSIGNED: u8 = 3;
+const NEGATIVE: u8 = 4;
+const FLOAT: u8 = 5;
+const STRING: u8 = 6;
+const ARRAY: u8 = 7;
+const OBJECT: u8 = 8;
+
+impl UIRNode {
+    /// Encode the tree in the binary format
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let value = serde_json::to_value(self)?;
+        let mut strings = StringTable::default();
+        let mut body = Vec::new();
+        encode(&value, &mut strings, &mut body);
+        
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        write_varint(&mut bytes, strings.strings.len() as u64);
+        for string in &strings.strings {
+            write_varint(&mut bytes, string.len() as u64);
+            bytes.extend_from_slice(string.as_bytes());
+        }
+        bytes.extend(body);
+        Ok(bytes)
+    }
+    
+    /// Decode a tree written by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(format_error("not a binary UIR file"));
+        }
+        let version = reader.byte()?;
+        if version != VERSION {
+            return Err(format_error(&format!("unsupported version {}", version)));
+        }
+        let count = reader.varint()?;
+        let mut strings = Vec::new();
+        for _ in 0..count {
+            let length = reader.varint()? as usize;
+            let text = std::str::from_utf8(reader.take(length)?)
+                .map_err(|_| format_error("string table entry is not UTF-8"))?;
+            strings.push(text.to_string());
+        }
+        let value = reader.value(&strings, 0)?;
+        if reader.pos != bytes.len() {
+            return Err(format_error("trailing bytes after the tree"));
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+#[derive(Default)]
+struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, u64>,
+}
+
+impl StringTable {
+    fn index(&mut self, string: &str) -> u64 {
+        if let Some(&index) = self.indices.get(string) {
+            return index;
+        }
+        let index = self.strings.len() as u64;
+        self.strings.push(string.to_string());
+        self.indices.insert(string.to_string(), index);
+        index
+    }
+}
+
+fn encode(value: &Value, strings: &mut StringTable, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.push(NULL),
+        Value::Bool(false) => out.push(FALSE),
+        Value::Bool(true) => out.push(TRUE),
+        Value::Number(number) => {
+            if let Some(unsigned) = number.as_u64() {
+                out.push(UNSIGNED);
+                write_varint(out, unsigned);
+            } else if let Some(signed) = number.as_i64() {
+                out.push(NEGATIVE);
+                write_varint(out, !(signed as u64));
+            } else {
+                out.push(FLOAT);
+                out.extend_from_slice(&number.as_f64().unwrap_or_default().to_le_bytes());
+            }
+        }
+        Value::String(string) => {
+            out.push(STRING);
+            write_varint(out, strings.index(string));
+        }
+        Value::Array(items) => {
+            out.push(ARRAY);
+            write_varint(out, items.len() as u64);
+            for item in items {
+                encode(item, strings, out);
+            }
+        }
+        Value::Object(fields) => {
+            out.push(OBJECT);
+            write_varint(out, fields.len() as u64);
+            for (key, field) in fields {
+                write_varint(out, strings.index(key));
+                encode(field, strings, out);
+            }
+        }
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(length).filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| format_error("unexpected end of data"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+    
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(format_error("varint longer than 64 bits"))
+    }
+    
+    fn string(&mut self, strings: &[String]) -> Result<String> {
+        let index = self.varint()? as usize;
+        strings.get(index).cloned().ok_or_else(|| format_error(&format!("string index {} out of range", index)))
+    }
+    
+    fn value(&mut self, strings: &[String], depth: usize) -> Result<Value> {
+        let tag = self.byte()?;
+        if matches!(tag, ARRAY | OBJECT) && depth >= MAX_DEPTH {
+            return Err(format_error(&format!("nested deeper than {} levels", MAX_DEPTH)));
+        }
+        Ok(match tag {
+            NULL => Value::Null,
+            FALSE => Value::Bool(false),
+            TRUE => Value::Bool(true),
+            UNSIGNED => Value::Number(self.varint()?.into()),
+            NEGATIVE => Value::Number((!self.varint()? as i64).into()),
+            FLOAT => {
+                let bytes: [u8; 8] = self.take(8)?.try_into().unwrap_or_default();
+                Number::from_f64(f64::from_le_bytes(bytes)).map(Value::Number).unwrap_or(Value::Null)
+            }
+            STRING => Value::String(self.string(strings)?),
+            ARRAY => {
+                let length = self.varint()?;
+                let mut items = Vec::new();
+                for _ in 0..length {
+                    items.push(self.value(strings, depth + 1)?);
+                }
+                Value::Array(items)
+            }
+            OBJECT => {
+                let length = self.varint()?;
+                let mut fields = Map::new();
+                for _ in 0..length {
+                    let key = self.string(strings)?;
+                    fields.insert(key, self.value(strings, depth + 1)?);
+                }
+                Value::Object(fields)
+            }
+            tag => return Err(format_error(&format!("unknown tag {}", tag))),
+        })
+    }
+}
+
+fn format_error(message: &str) -> CoalesceError {
+    CoalesceError::BinaryFormatError(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::UIRBuilder;
+    use crate::types::{Language, Operator};
+    
+    fn sample() -> UIRNode {
+        let b = UIRBuilder::new();
+        b.module("shapes").body([
+            b.function("scale")
+                .typed_param("factor", "float")
+                .returns("float")
+                .documented("Scale by a factor")
+                .body([b.return_value(b.binary(
+                    Operator::Multiply,
+                    b.identifier("factor"),
+                    b.literal(-2.5),
+                ))]),
+            b.assign(b.identifier("count"), b.literal(-7)),
+            b.call("print", [b.literal("héllo"), b.literal(true), b.literal(u32::MAX as i64 * 4)]),
+        ]).in_language(Language::Python)
+    }
+    
+    /// Binary data with `depth` arrays nested inside one another around a null
+    fn nested_arrays(depth: usize) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([VERSION, 0]);
+        for _ in 0..depth {
+            bytes.extend([ARRAY, 1]);
+        }
+        bytes.push(NULL);
+        bytes
+    }
+    
+    fn error_message(bytes: &[u8]) -> String {
+        match UIRNode::from_bytes(bytes) {
+            Err(CoalesceError::BinaryFormatError(message)) => message,
+            other => panic!("expected a format error, got {:?}", other.map(|node| node.id)),
+        }
+    }
+    
+    #[test]
+    fn test_round_trip_keeps_every_field() {
+        let tree = sample();
+        let bytes = tree.to_bytes().unwrap();
+        let decoded = UIRNode::from_bytes(&bytes).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&tree).unwrap());
+        assert!(bytes.len() < serde_json::to_vec(&tree).unwrap().len());
+    }
+    
+    #[test]
+    fn test_round_trip_of_deep_tree() {
+        let b = UIRBuilder::new();
+        let mut tree = b.identifier("x");
+        for _ in 0..60 {
+            tree = b.binary(Operator::Add, tree, b.literal(1));
+        }
+        let decoded = UIRNode::from_bytes(&tree.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.id, tree.id);
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&tree).unwrap());
+    }
+    
+    #[test]
+    fn test_nesting_past_the_limit_is_an_error() {
+        // The depth check runs before serde sees the value, so plain arrays reach it
+        assert!(matches!(UIRNode::from_bytes(&nested_arrays(MAX_DEPTH)), Err(CoalesceError::SerializationError(_))));
+        assert_eq!(error_message(&nested_arrays(MAX_DEPTH + 1)), "nested deeper than 128 levels");
+        assert_eq!(error_message(&nested_arrays(1_000_000)), "nested deeper than 128 levels");
+    }
+    
+    #[test]
+    fn test_malformed_input_is_an_error() {
+        let bytes = sample().to_bytes().unwrap();
+        
+        assert_eq!(error_message(b"JSON{}"), "not a binary UIR file");
+        assert_eq!(error_message(b"UI"), "unexpected end of data");
+        
+        let mut version = bytes.clone();
+        version[4] = 9;
+        assert_eq!(error_message(&version), "unsupported version 9");
+        
+        for length in [5, bytes.len() / 2, bytes.len() - 1] {
+            assert_eq!(error_message(&bytes[..length]), "unexpected end of data");
+        }
+        
+        let mut trailing = bytes.clone();
+        trailing.push(NULL);
+        assert_eq!(error_message(&trailing), "trailing bytes after the tree");
+        
+        let mut header = MAGIC.to_vec();
+        header.extend([VERSION, 1, 2, 0xff, 0xfe]);
+        assert_eq!(error_message(&header), "string table entry is not UTF-8");
+        
+        let mut header = MAGIC.to_vec();
+        header.extend([VERSION, 0]);
+        assert_eq!(error_message(&[header.as_slice(), &[42]].concat()), "unknown tag 42");
+        assert_eq!(error_message(&[header.as_slice(), &[STRING, 3]].concat()), "string index 3 out of range");
+        assert_eq!(error_message(&[header.as_slice(), &[UNSIGNED], &[0xff; 10]].concat()), "varint longer than 64 bits");
+        assert_eq!(error_message(&[header.as_slice(), &[ARRAY, 0xff, 0xff, 0xff, 0xff, 0x0f]].concat()), "unexpected end of data");
+    }
+    
+    #[test]
+    fn test_well_formed_value_of_the_wrong_shape_is_an_error() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([VERSION, 0, TRUE]);
+        assert!(matches!(UIRNode::from_bytes(&bytes), Err(CoalesceError::SerializationError(_))));
+    }
+}
@@ -0,0 +1,303 @@
+// Fluent construction of UIR trees
+//
+// Parsers fill in every field of a node from the syntax tree; code that makes nodes of its own
+// (tests, generators lowering one construct into others, pattern templates) only knows the
+// shape it wants. A `UIRBuilder`'s constructors and the `with_`-style methods of `UIRNode`
+// build that shape with the annotations generators read, e.g.
+//
+//     let b = UIRBuilder::new();
+//     b.function("add")
+//         .typed_param("a", "int")
+//         .typed_param("b", "int")
+//         .returns("int")
+//         .body([b.return_value(b.binary(Operator::Add, b.identifier("a"), b.identifier("b")))])
+//
+// A builder numbers the nodes it makes, and parameters take their function's id, so the trees
+// one builder forms pass `validate`.
+use crate::types::{ControlFlowType, ExpressionType, LiteralValue, Modifier, NodeType, Operator, StatementType, UIRNode, Visibility, Language};
+use crate::visit::{Walk, walk_mut};
+use serde_json::Value;
+use std::cell::Cell;
+
+/// Makes UIR nodes with ids unique among the ones it made
+#[derive(Debug, Default)]
+pub struct UIRBuilder {
+    next_id: Cell<usize>,
+}
+
+impl UIRBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// A node of `node_type` with a fresh id starting with `kind`
+    fn built(&self, kind: &str, node_type: NodeType) -> UIRNode {
+        let id = self.next_id.get() + 1;
+        self.next_id.set(id);
+        UIRNode::new(format!("{}_{}", kind, id), node_type)
+    }
+    
+    /// A module holding the declarations and statements added with `body`
+    pub fn module(&self, name: impl Into<String>) -> UIRNode {
+        self.built("module", NodeType::Module).named(name)
+    }
+    
+    /// A function without parameters or body
+    pub fn function(&self, name: impl Into<String>) -> UIRNode {
+        self.built("function", NodeType::Function).named(name)
+    }
+    
+    pub fn class(&self, name: impl Into<String>) -> UIRNode {
+        self.built("class", NodeType::Class).named(name)
+    }
+    
+    /// A field declared with `field_type`
+    pub fn field(&self, name: impl Into<String>, field_type: impl Into<String>) -> UIRNode {
+        let field_type = field_type.into();
+        self.built("field", NodeType::Field { field_type: Some(field_type.clone()), default_value: None })
+            .named(name)
+            .annotated("type", field_type)
+    }
+    
+    /// A variable declaration, or a parameter when added to a function with `param`
+    pub fn variable(&self, name: impl Into<String>) -> UIRNode {
+        self.built("variable", NodeType::Variable).named(name)
+    }
+    
+    /// A reference to the variable or function `name`
+    pub fn identifier(&self, name: impl Into<String>) -> UIRNode {
+        let name = name.into();
+        self.built("identifier", NodeType::Expression(ExpressionType::Variable))
+            .annotated("original_text", name.clone())
+            .named(name)
+    }
+    
+    /// A literal with its value and its spelling in C-family syntax
+    pub fn literal(&self, value: impl Into<LiteralValue>) -> UIRNode {
+        let value = value.into();
+        let spelling = match &value {
+            LiteralValue::Int(n) => n.to_string(),
+            LiteralValue::Float(f) => format!("{:?}", f),
+            LiteralValue::String(s) => format!("{:?}", s),
+            LiteralValue::Bool(b) => b.to_string(),
+            LiteralValue::Char(c) => format!("{:?}", c),
+            LiteralValue::Null => "null".to_string(),
+        };
+        let mut literal = self.built("literal", NodeType::Expression(ExpressionType::Literal)).annotated("original_text", spelling);
+        literal.literal = Some(value);
+        literal
+    }
+    
+    /// `left operator right`, an arithmetic, comparison or logical expression by the operator
+    pub fn binary(&self, operator: Operator, left: UIRNode, right: UIRNode) -> UIRNode {
+        let text = format!("{} {} {}", source_text(&left), operator.symbol(), source_text(&right));
+        let mut expression = self.built("binary", NodeType::Expression(operator.expression_type()))
+            .annotated("original_text", text)
+            .add_child(left)
+            .add_child(right);
+        expression.operator = Some(operator);
+        expression
+    }
+    
+    /// A call of the function `callee` with `arguments`
+    pub fn call(&self, callee: impl Into<String>, arguments: impl IntoIterator<Item = UIRNode>) -> UIRNode {
+        let callee = callee.into();
+        let arguments: Vec<UIRNode> = arguments.into_iter().collect();
+        let text = format!("{}({})", callee, arguments.iter().map(source_text).collect::<Vec<_>>().join(", "));
+        self.built("call", NodeType::Expression(ExpressionType::FunctionCall))
+            .annotated("original_text", text)
+            .named(callee)
+            .body(arguments)
+    }
+    
+    /// `target = value`
+    pub fn assign(&self, target: UIRNode, value: UIRNode) -> UIRNode {
+        let text = format!("{} = {}", source_text(&target), source_text(&value));
+        self.built("assignment", NodeType::Expression(ExpressionType::Assignment))
+            .annotated("original_text", text)
+            .add_child(target)
+            .add_child(value)
+    }
+    
+    /// `return value`
+    pub fn return_value(&self, value: UIRNode) -> UIRNode {
+        self.built("return", NodeType::Statement(StatementType::Return)).add_child(value)
+    }
+    
+    /// A conditional running `body` when `condition` holds
+    pub fn conditional(&self, condition: UIRNode, body: impl IntoIterator<Item = UIRNode>) -> UIRNode {
+        self.built("conditional", NodeType::ControlFlow(ControlFlowType::Conditional))
+            .add_child(condition)
+            .body(body)
+    }
+}
+
+impl UIRNode {
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+    
+    /// Add an untyped parameter after the existing ones, its id the function's and its name
+    pub fn param(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        let parameter = UIRNode::new(format!("{}.{}", self.id, name), NodeType::Variable).named(name);
+        self.with_param(parameter)
+    }
+    
+    /// Add a parameter declared with `param_type` after the existing ones
+    pub fn typed_param(self, name: impl Into<String>, param_type: impl Into<String>) -> Self {
+        let name = name.into();
+        let parameter = UIRNode::new(format!("{}.{}", self.id, name), NodeType::Variable).named(name).typed(param_type);
+        self.with_param(parameter)
+    }
+    
+    /// Add `parameter` after the existing parameters, ahead of any body statements
+    pub fn with_param(mut self, parameter: UIRNode) -> Self {
+        let position = self.children.iter().take_while(|c| c.node_type == NodeType::Variable).count();
+        self.children.insert(position, parameter.tagged("parameter"));
+        self
+    }
+    
+    /// Declare the function's return type
+    pub fn returns(self, return_type: impl Into<String>) -> Self {
+        self.annotated("return_type", return_type.into())
+    }
+    
+    /// Declare the variable's or parameter's type
+    pub fn typed(self, declared_type: impl Into<String>) -> Self {
+        self.annotated("type", declared_type.into())
+    }
+    
+    /// Append statements, members or arguments
+    pub fn body(mut self, children: impl IntoIterator<Item = UIRNode>) -> Self {
+        self.children.extend(children);
+        self
+    }
+    
+    pub fn annotated(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.metadata.annotations.insert(key.into(), value.into());
+        self
+    }
+    
+    pub fn tagged(mut self, tag: impl Into<String>) -> Self {
+        self.metadata.semantic_tags.push(tag.into());
+        self
+    }
+    
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = Some(visibility);
+        self
+    }
+    
+    pub fn with_modifier(mut self, modifier: Modifier) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+    
+    pub fn asynchronous(mut self) -> Self {
+        self.is_async = true;
+        self
+    }
+    
+    pub fn documented(mut self, documentation: impl Into<String>) -> Self {
+        self.documentation = Some(documentation.into());
+        self
+    }
+    
+    /// Mark this node and everything under it as written in `language`
+    pub fn in_language(mut self, language: Language) -> Self {
+        walk_mut(&mut self, &mut |node: &mut UIRNode| {
+            node.metadata.source_language = language.clone();
+            Walk::Continue
+        });
+        self
+    }
+}
+
+fn source_text(uir: &UIRNode) -> &str {
+    uir.metadata.annotations.get("original_text").and_then(|v| v.as_str()).unwrap_or("")
+}
+
+impl From<i64> for LiteralValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<i32> for LiteralValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<f64> for LiteralValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<bool> for LiteralValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<char> for LiteralValue {
+    fn from(value: char) -> Self {
+        Self::Char(value)
+    }
+}
+
+impl From<&str> for LiteralValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for LiteralValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_function_with_parameters_and_body_validates() {
+        let b = UIRBuilder::new();
+        let add = b.function("add")
+            .typed_param("a", "int")
+            .body([b.return_value(b.binary(Operator::Add, b.identifier("a"), b.identifier("b")))])
+            .typed_param("b", "int")
+            .returns("int");
+        
+        let parameters: Vec<&str> = add.children.iter().take(2).map(|p| p.id.as_str()).collect();
+        assert_eq!(parameters, [format!("{}.a", add.id), format!("{}.b", add.id)]);
+        assert!(add.children[1].metadata.semantic_tags.contains(&"parameter".to_string()));
+        assert_eq!(add.children[1].metadata.annotations["type"], "int");
+        assert_eq!(add.metadata.annotations["return_type"], "int");
+        assert_eq!(source_text(&add.children[2].children[0]), "a + b");
+        assert_eq!(b.module("math").body([add, b.function("zero")]).validate(), Ok(()));
+    }
+    
+    #[test]
+    fn test_literals_and_calls_are_spelled_in_c_family_syntax() {
+        let b = UIRBuilder::new();
+        let call = b.call("print", [b.literal("hi"), b.literal(2.0), b.literal('c'), b.literal(LiteralValue::Null)]);
+        assert_eq!(source_text(&call), r#"print("hi", 2.0, 'c', null)"#);
+        assert_eq!(call.children[0].literal, Some(LiteralValue::String("hi".to_string())));
+        assert_eq!(source_text(&b.assign(b.identifier("done"), b.literal(true))), "done = true");
+    }
+    
+    #[test]
+    fn test_in_language_marks_every_node() {
+        let b = UIRBuilder::new();
+        let tree = b.conditional(b.identifier("ready"), [b.call("go", [])]).in_language(Language::Python);
+        assert_eq!(tree.metadata.source_language, Language::Python);
+        assert_eq!(tree.children[1].metadata.source_language, Language::Python);
+        assert_ne!(tree.children[0].id, tree.children[1].id);
+    }
+}
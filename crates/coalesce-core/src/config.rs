@@ -0,0 +1,79 @@
+// Project configuration: the `.coalesce/config.json` that `coalesce init` writes. The sections
+// of optional features, `optimize`, `pattern_packs` and `patterns`, are read by the crates that
+// implement them.
+
+use crate::errors::Result;
+use crate::style::GeneratorConfig;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where a project keeps its configuration, relative to its root
+pub const CONFIG_FILE: &str = ".coalesce/config.json";
+
+/// The root of the project `dir` is in: the nearest of it and its ancestors with a `.coalesce`
+/// directory
+pub fn project_root(dir: impl AsRef<Path>) -> Option<PathBuf> {
+    dir.as_ref().ancestors()
+        .find(|ancestor| ancestor.join(".coalesce").is_dir())
+        .map(Path::to_path_buf)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub project_name: String,
+    /// Languages of the files to translate, by their command-line names, e.g. `javascript`
+    #[serde(default)]
+    pub source_languages: Vec<String>,
+    /// Languages to translate them to
+    #[serde(default)]
+    pub target_languages: Vec<String>,
+    /// Whether constructs the parsers flag as legacy, such as COBOL's `GO TO`, come out in the
+    /// translation as comments of their original code, for review, besides being reported
+    #[serde(default = "default_true")]
+    pub preserve_legacy_patterns: bool,
+    #[serde(default)]
+    pub ml_enhancement: bool,
+    /// Layout and naming `migrate` generates code with; each target language's own when omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<GeneratorConfig>,
+    /// Tree-sitter grammars to load for languages without a built-in parser
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grammars: Vec<GrammarConfig>,
+}
+
+/// One entry of the `grammars` list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrammarConfig {
+    pub name: String,
+    /// Compiled grammar: a shared library (`.so`, `.dylib` or `.dll`), relative to the directory
+    /// holding the config
+    pub path: PathBuf,
+    /// Exported language function, `tree_sitter_<name>` when omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// File extensions, without the dot, that select this grammar
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ProjectConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+    
+    /// The configuration of the project at `root`; `None` when it has none
+    pub fn load(root: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = root.as_ref().join(CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Self::from_file(path).map(Some)
+    }
+}
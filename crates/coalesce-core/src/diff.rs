@@ -0,0 +1,352 @@
+// Structural differences between two UIR trees
+//
+// Children are matched to the other tree's children of the same parent by id, then by kind and
+// name, so a diff between a parse and its transformed tree (same ids) and between two parses
+// of edited source (shifted ids) both line nodes up. Matched nodes whose relative order
+// changed are moved; a removed and an added node with the same id, under different parents,
+// are one node moved between them. Nodes are addressed by their path of child indices.
+use crate::types::{NodeType, UIRNode};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::mem::discriminant;
+
+/// Child indices leading from the root to a node; the root's path is empty
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NodePath(pub Vec<usize>);
+
+impl NodePath {
+    pub fn child(&self, index: usize) -> NodePath {
+        let mut path = self.0.clone();
+        path.push(index);
+        NodePath(path)
+    }
+}
+
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "/");
+        }
+        for index in &self.0 {
+            write!(f, "/{}", index)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Added,
+    /// Addressed by its path in the old tree
+    Removed,
+    /// Now at the change's path, formerly at `from`
+    Moved { from: NodePath },
+    /// Fields other than children that differ, annotations by key (`annotations.<key>`)
+    Modified { fields: Vec<String> },
+}
+
+/// One difference; `path` is in the new tree except for removals
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeChange {
+    pub kind: ChangeKind,
+    pub path: NodePath,
+    pub node_id: String,
+    /// The node's kind and name, e.g. "function `add`"
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeDiff {
+    /// Changes in document order, a node's own change before those of its descendants
+    pub changes: Vec<NodeChange>,
+}
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for TreeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            match &change.kind {
+                ChangeKind::Added => writeln!(f, "+ {} {}", change.path, change.label)?,
+                ChangeKind::Removed => writeln!(f, "- {} {}", change.path, change.label)?,
+                ChangeKind::Moved { from } => writeln!(f, "> {} {} (from {})", change.path, change.label, from)?,
+                ChangeKind::Modified { fields } => writeln!(f, "~ {} {} ({})", change.path, change.label, fields.join(", "))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl UIRNode {
+    /// The changes that turn this tree into `other`
+    pub fn diff(&self, other: &UIRNode) -> TreeDiff {
+        let mut differ = Differ::default();
+        differ.compare(self, other, &NodePath::default(), &NodePath::default());
+        TreeDiff { changes: differ.pair_moves() }
+    }
+    
+    /// The node at `path`, if the tree has one
+    pub fn node_at(&self, path: &NodePath) -> Option<&UIRNode> {
+        path.0.iter().try_fold(self, |node, &index| node.children.get(index))
+    }
+}
+
+#[derive(Default)]
+struct Differ<'a> {
+    changes: Vec<NodeChange>,
+    /// Removed and added nodes by the index of their change, paired up into moves at the end
+    removed: Vec<(usize, &'a UIRNode)>,
+    added: Vec<(usize, &'a UIRNode)>,
+}
+
+impl<'a> Differ<'a> {
+    fn compare(&mut self, old: &'a UIRNode, new: &'a UIRNode, old_path: &NodePath, new_path: &NodePath) {
+        let fields = changed_fields(old, new);
+        if !fields.is_empty() {
+            self.push(ChangeKind::Modified { fields }, new_path.clone(), new);
+        }
+        
+        let matches = match_children(&old.children, &new.children);
+        let mut matched = vec![false; old.children.len()];
+        for index in matches.iter().flatten() {
+            matched[*index] = true;
+        }
+        for (index, child) in old.children.iter().enumerate().filter(|(index, _)| !matched[*index]) {
+            self.removed.push((self.changes.len(), child));
+            self.push(ChangeKind::Removed, old_path.child(index), child);
+        }
+        
+        // Nodes outside the longest run kept in order are the ones that moved
+        let in_order = longest_increasing(&matches);
+        for (index, child) in new.children.iter().enumerate() {
+            let path = new_path.child(index);
+            match matches[index] {
+                Some(old_index) => {
+                    if !in_order.contains(&index) {
+                        self.push(ChangeKind::Moved { from: old_path.child(old_index) }, path.clone(), child);
+                    }
+                    self.compare(&old.children[old_index], child, &old_path.child(old_index), &path);
+                }
+                None => {
+                    self.added.push((self.changes.len(), child));
+                    self.push(ChangeKind::Added, path, child);
+                }
+            }
+        }
+    }
+    
+    fn push(&mut self, kind: ChangeKind, path: NodePath, node: &UIRNode) {
+        self.changes.push(NodeChange { kind, path, node_id: node.id.clone(), label: describe(node) });
+    }
+    
+    /// Turn each addition with the id of a removal into a move, followed by its own changes
+    fn pair_moves(mut self) -> Vec<NodeChange> {
+        let mut dropped = vec![false; self.changes.len()];
+        let mut nested: Vec<Vec<NodeChange>> = vec![Vec::new(); self.changes.len()];
+        for &(added, new) in &self.added {
+            let Some(position) = self.removed.iter().position(|(_, old)| old.id == new.id) else {
+                continue;
+            };
+            let (removed, old) = self.removed.remove(position);
+            let from = self.changes[removed].path.clone();
+            let to = self.changes[added].path.clone();
+            let mut inner = Differ::default();
+            inner.compare(old, new, &from, &to);
+            self.changes[added].kind = ChangeKind::Moved { from };
+            dropped[removed] = true;
+            nested[added] = inner.pair_moves();
+        }
+        
+        let mut changes = Vec::new();
+        for ((change, dropped), nested) in self.changes.into_iter().zip(dropped).zip(nested) {
+            if !dropped {
+                changes.push(change);
+                changes.extend(nested);
+            }
+        }
+        changes
+    }
+}
+
+/// For each new child, the index of the old child it corresponds to
+fn match_children(old: &[UIRNode], new: &[UIRNode]) -> Vec<Option<usize>> {
+    let mut taken = vec![false; old.len()];
+    let mut matches = vec![None; new.len()];
+    let by_id = |a: &UIRNode, b: &UIRNode| a.id == b.id;
+    let by_kind = |a: &UIRNode, b: &UIRNode| discriminant(&a.node_type) == discriminant(&b.node_type) && a.name == b.name;
+    for same in [&by_id as &dyn Fn(&UIRNode, &UIRNode) -> bool, &by_kind] {
+        for (index, child) in new.iter().enumerate() {
+            if matches[index].is_some() {
+                continue;
+            }
+            if let Some(old_index) = (0..old.len()).find(|&i| !taken[i] && same(&old[i], child)) {
+                taken[old_index] = true;
+                matches[index] = Some(old_index);
+            }
+        }
+    }
+    matches
+}
+
+/// Indices into `matches` of a longest subsequence of matched children in their old order
+fn longest_increasing(matches: &[Option<usize>]) -> Vec<usize> {
+    let matched: Vec<(usize, usize)> = matches.iter().enumerate()
+        .filter_map(|(index, old)| old.map(|old| (index, old)))
+        .collect();
+    let mut length = vec![1; matched.len()];
+    let mut previous = vec![None; matched.len()];
+    for i in 0..matched.len() {
+        for j in 0..i {
+            if matched[j].1 < matched[i].1 && length[j] + 1 > length[i] {
+                length[i] = length[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+    let mut kept = Vec::new();
+    let mut cursor = (0..matched.len()).max_by_key(|&i| (length[i], std::cmp::Reverse(i)));
+    while let Some(i) = cursor {
+        kept.push(matched[i].0);
+        cursor = previous[i];
+    }
+    kept
+}
+
+fn changed_fields(old: &UIRNode, new: &UIRNode) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut check = |name: &str, differs: bool| {
+        if differs {
+            fields.push(name.to_string());
+        }
+    };
+    check("node_type", old.node_type != new.node_type);
+    check("name", old.name != new.name);
+    check("operator", old.operator != new.operator);
+    check("literal", old.literal != new.literal);
+    check("visibility", old.visibility != new.visibility);
+    check("modifiers", old.modifiers != new.modifiers);
+    check("is_async", old.is_async != new.is_async);
+    check("attributes", old.attributes != new.attributes);
+    check("ownership", old.ownership != new.ownership);
+    check("documentation", old.documentation != new.documentation);
+    check("semantic_tags", old.metadata.semantic_tags != new.metadata.semantic_tags);
+    
+    let old_annotations = &old.metadata.annotations;
+    let new_annotations = &new.metadata.annotations;
+    let mut keys: Vec<&String> = old_annotations.keys().chain(new_annotations.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        if old_annotations.get(key) != new_annotations.get(key) {
+            fields.push(format!("annotations.{}", key));
+        }
+    }
+    fields
+}
+
+fn describe(uir: &UIRNode) -> String {
+    let kind = match &uir.node_type {
+        NodeType::Module => "module".to_string(),
+        NodeType::Function => "function".to_string(),
+        NodeType::Class => "class".to_string(),
+        NodeType::Interface => "interface".to_string(),
+        NodeType::Variable => "variable".to_string(),
+        NodeType::Constant => "constant".to_string(),
+        NodeType::Field { .. } => "field".to_string(),
+        NodeType::Property { .. } => "property".to_string(),
+        NodeType::Enum { .. } => "enum".to_string(),
+        NodeType::Closure { .. } => "closure".to_string(),
+        NodeType::ControlFlow(control_flow) => format!("{:?}", control_flow),
+        NodeType::Expression(expression) => format!("{:?} expression", expression),
+        NodeType::Statement(statement) => format!("{:?} statement", statement),
+        NodeType::Comment { .. } => "comment".to_string(),
+        NodeType::Import { .. } => "import".to_string(),
+    };
+    match &uir.name {
+        Some(name) => format!("{} `{}`", kind, name),
+        None => kind,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::UIRBuilder;
+    use crate::types::{Operator, Visibility};
+    
+    fn sample(b: &UIRBuilder) -> UIRNode {
+        b.module("app").body([
+            b.function("first").body([b.return_value(b.literal(1))]),
+            b.function("second").param("x"),
+            b.class("Point").body([b.field("x", "int")]),
+        ])
+    }
+    
+    fn summary(diff: &TreeDiff) -> Vec<String> {
+        diff.to_string().lines().map(str::to_string).collect()
+    }
+    
+    #[test]
+    fn test_identical_trees_have_no_changes() {
+        let tree = sample(&UIRBuilder::new());
+        assert!(tree.diff(&tree.clone()).is_empty());
+        assert_eq!(tree.diff(&tree).to_string(), "");
+    }
+    
+    #[test]
+    fn test_added_removed_and_modified() {
+        let b = UIRBuilder::new();
+        let old = sample(&b);
+        let mut new = old.clone();
+        new.children[0].visibility = Some(Visibility::Public);
+        new.children[0].metadata.annotations.insert("return_type".to_string(), "int".into());
+        new.children.remove(1);
+        new.children.push(b.function("third"));
+        new.children[1].children[0].children.push(b.binary(Operator::Add, b.literal(1), b.literal(2)));
+        
+        assert_eq!(summary(&old.diff(&new)), [
+            "- /1 function `second`",
+            "~ /0 function `first` (visibility, annotations.return_type)",
+            "+ /1/0/0 Arithmetic expression",
+            "+ /2 function `third`",
+        ]);
+        let diff = old.diff(&new);
+        assert_eq!(diff.changes[0].node_id, old.children[1].id);
+        assert_eq!(old.node_at(&diff.changes[0].path).unwrap().name.as_deref(), Some("second"));
+        assert_eq!(new.node_at(&diff.changes[2].path).unwrap().operator, Some(Operator::Add));
+        assert!(new.node_at(&NodePath(vec![7])).is_none());
+    }
+    
+    #[test]
+    fn test_reordered_and_reparented_nodes_are_moves() {
+        let old = sample(&UIRBuilder::new());
+        let mut new = old.clone();
+        let first = new.children.remove(0);
+        new.children.push(first);
+        let field = new.children[1].children.remove(0);
+        new.children[0].children.push(field);
+        
+        assert_eq!(summary(&old.diff(&new)), [
+            "> /0/1 field `x` (from /2/0)",
+            "> /2 function `first` (from /0)",
+        ]);
+    }
+    
+    #[test]
+    fn test_children_match_by_kind_and_name_when_ids_differ() {
+        // One builder numbers each tree's nodes apart
+        let b = UIRBuilder::new();
+        let old = sample(&b);
+        let new = sample(&b);
+        assert_ne!(old.id, new.id);
+        assert!(old.diff(&new).is_empty());
+        
+        let mut renamed = sample(&b);
+        renamed.children[1].name = Some("other".to_string());
+        assert_eq!(summary(&old.diff(&renamed)), ["- /1 function `second`", "+ /1 function `other`"]);
+    }
+}
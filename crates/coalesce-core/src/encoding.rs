@@ -0,0 +1,280 @@
+use crate::errors::{CoalesceError, Result};
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+/// Character encodings seen in legacy source trees
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1, // Decoded as Windows-1252, the superset most "Latin-1" files actually use
+    ShiftJis,
+    Ebcdic, // IBM code page 037, the common US/Canada mainframe code page
+}
+
+impl SourceEncoding {
+    /// Resolve a user-supplied label such as `latin1`, `sjis` or `cp037`
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "utf8" => Some(Self::Utf8),
+            "utf16" | "utf16le" => Some(Self::Utf16Le),
+            "utf16be" => Some(Self::Utf16Be),
+            "latin1" | "iso88591" | "windows1252" | "cp1252" => Some(Self::Latin1),
+            "shiftjis" | "sjis" | "cp932" | "windows31j" => Some(Self::ShiftJis),
+            "ebcdic" | "cp037" | "ibm037" => Some(Self::Ebcdic),
+            _ => None,
+        }
+    }
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf16Be => "UTF-16BE",
+            Self::Latin1 => "Latin-1",
+            Self::ShiftJis => "Shift_JIS",
+            Self::Ebcdic => "EBCDIC (CP037)",
+        }
+    }
+    
+    fn codec(&self) -> Option<&'static Encoding> {
+        match self {
+            Self::Utf8 => Some(UTF_8),
+            Self::Utf16Le => Some(UTF_16LE),
+            Self::Utf16Be => Some(UTF_16BE),
+            Self::Latin1 => Some(WINDOWS_1252),
+            Self::ShiftJis => Some(SHIFT_JIS),
+            Self::Ebcdic => None,
+        }
+    }
+}
+
+/// Source text together with the encoding it was decoded from
+#[derive(Debug, Clone)]
+pub struct DecodedSource {
+    pub text: String,
+    pub encoding: SourceEncoding,
+    pub had_bom: bool,
+}
+
+/// Guess the encoding of raw source bytes
+///
+/// A byte order mark wins outright. Otherwise valid UTF-8 is taken as UTF-8, and the
+/// remaining candidates are scored on byte-distribution heuristics in the spirit of chardet.
+pub fn detect_encoding(bytes: &[u8]) -> SourceEncoding {
+    if let Some((encoding, _)) = sniff_bom(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return SourceEncoding::Utf8;
+    }
+    if looks_like_ebcdic(bytes) {
+        return SourceEncoding::Ebcdic;
+    }
+    if looks_like_shift_jis(bytes) {
+        return SourceEncoding::ShiftJis;
+    }
+    SourceEncoding::Latin1
+}
+
+/// Decode raw source bytes, detecting the encoding unless one is given explicitly
+///
+/// An explicit encoding is strict: bytes that are malformed in it are an error rather than
+/// being silently replaced, since the caller asserted what the file contains.
+pub fn decode_source(bytes: &[u8], encoding: Option<SourceEncoding>) -> Result<DecodedSource> {
+    let bom = sniff_bom(bytes);
+    let detected = encoding.is_none();
+    let encoding = encoding.unwrap_or_else(|| detect_encoding(bytes));
+    let had_bom = bom.is_some_and(|(bom_encoding, _)| bom_encoding == encoding);
+    let body = match bom {
+        Some((_, len)) if had_bom => &bytes[len..],
+        _ => bytes,
+    };
+    
+    let text = match encoding.codec() {
+        Some(codec) => {
+            let (text, malformed) = codec.decode_without_bom_handling(body);
+            if malformed && !detected {
+                return Err(CoalesceError::EncodingError(format!(
+                    "source is not valid {}", encoding.name()
+                )));
+            }
+            text.into_owned()
+        }
+        None => decode_ebcdic(body),
+    };
+    
+    Ok(DecodedSource { text, encoding, had_bom })
+}
+
+/// Read a source file from disk and decode it
+pub fn read_source(file_path: &str, encoding: Option<SourceEncoding>) -> Result<DecodedSource> {
+    let bytes = std::fs::read(file_path)?;
+    decode_source(&bytes, encoding)
+}
+
+fn sniff_bom(bytes: &[u8]) -> Option<(SourceEncoding, usize)> {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => Some((SourceEncoding::Utf8, 3)),
+        [0xFF, 0xFE, ..] => Some((SourceEncoding::Utf16Le, 2)),
+        [0xFE, 0xFF, ..] => Some((SourceEncoding::Utf16Be, 2)),
+        _ => None,
+    }
+}
+
+/// EBCDIC text is dominated by 0x40 (space) and letters above 0x80, and has no ASCII newlines
+fn looks_like_ebcdic(bytes: &[u8]) -> bool {
+    let spaces = bytes.iter().filter(|&&b| b == 0x40).count();
+    let ascii_spaces = bytes.iter().filter(|&&b| b == b' ').count();
+    let ascii_newlines = bytes.iter().filter(|&&b| b == b'\n').count();
+    let letters = bytes.iter().filter(|&&b| matches!(b, 0x81..=0x89 | 0x91..=0x99 | 0xA2..=0xA9 | 0xC1..=0xC9 | 0xD1..=0xD9 | 0xE2..=0xE9 | 0xF0..=0xF9)).count();
+    
+    spaces > ascii_spaces && ascii_newlines <= bytes.len() / 200 && (letters + spaces) * 10 > bytes.len() * 7
+}
+
+/// Shift-JIS pairs a lead byte in 0x81-0x9F/0xE0-0xFC with a trail byte in 0x40-0xFC. A pair
+/// of a Latin-1 letter and an ASCII letter right after another ASCII letter, as `\xe9d` of
+/// `Cr\xe9dit`, reads more likely as an accented word, so most pairs must be otherwise
+fn looks_like_shift_jis(bytes: &[u8]) -> bool {
+    let (_, malformed) = SHIFT_JIS.decode_without_bom_handling(bytes);
+    if malformed {
+        return false;
+    }
+    
+    let mut pairs = 0;
+    let mut accented = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let lead = bytes[i];
+        if matches!(lead, 0x81..=0x9F | 0xE0..=0xFC) && i + 1 < bytes.len() {
+            pairs += 1;
+            let after_letter = i > 0 && bytes[i - 1].is_ascii_alphabetic();
+            if lead >= 0xC0 && after_letter && bytes[i + 1].is_ascii_alphabetic() {
+                accented += 1;
+            }
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    pairs > 0 && accented * 2 < pairs
+}
+
+fn decode_ebcdic(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|&b| match CP037[b as usize] {
+            // Mainframe records end in NEL (0x15) rather than LF
+            '\u{0085}' => '\n',
+            c => c,
+        })
+        .collect()
+}
+
+const CP037: [char; 256] = [
+    '\u{0000}', '\u{0001}', '\u{0002}', '\u{0003}', '\u{009C}', '\u{0009}', '\u{0086}', '\u{007F}',
+    '\u{0097}', '\u{008D}', '\u{008E}', '\u{000B}', '\u{000C}', '\u{000D}', '\u{000E}', '\u{000F}',
+    '\u{0010}', '\u{0011}', '\u{0012}', '\u{0013}', '\u{009D}', '\u{0085}', '\u{0008}', '\u{0087}',
+    '\u{0018}', '\u{0019}', '\u{0092}', '\u{008F}', '\u{001C}', '\u{001D}', '\u{001E}', '\u{001F}',
+    '\u{0080}', '\u{0081}', '\u{0082}', '\u{0083}', '\u{0084}', '\u{000A}', '\u{0017}', '\u{001B}',
+    '\u{0088}', '\u{0089}', '\u{008A}', '\u{008B}', '\u{008C}', '\u{0005}', '\u{0006}', '\u{0007}',
+    '\u{0090}', '\u{0091}', '\u{0016}', '\u{0093}', '\u{0094}', '\u{0095}', '\u{0096}', '\u{0004}',
+    '\u{0098}', '\u{0099}', '\u{009A}', '\u{009B}', '\u{0014}', '\u{0015}', '\u{009E}', '\u{001A}',
+    '\u{0020}', '\u{00A0}', '\u{00E2}', '\u{00E4}', '\u{00E0}', '\u{00E1}', '\u{00E3}', '\u{00E5}',
+    '\u{00E7}', '\u{00F1}', '\u{00A2}', '\u{002E}', '\u{003C}', '\u{0028}', '\u{002B}', '\u{007C}',
+    '\u{0026}', '\u{00E9}', '\u{00EA}', '\u{00EB}', '\u{00E8}', '\u{00ED}', '\u{00EE}', '\u{00EF}',
+    '\u{00EC}', '\u{00DF}', '\u{0021}', '\u{0024}', '\u{002A}', '\u{0029}', '\u{003B}', '\u{00AC}',
+    '\u{002D}', '\u{002F}', '\u{00C2}', '\u{00C4}', '\u{00C0}', '\u{00C1}', '\u{00C3}', '\u{00C5}',
+    '\u{00C7}', '\u{00D1}', '\u{00A6}', '\u{002C}', '\u{0025}', '\u{005F}', '\u{003E}', '\u{003F}',
+    '\u{00F8}', '\u{00C9}', '\u{00CA}', '\u{00CB}', '\u{00C8}', '\u{00CD}', '\u{00CE}', '\u{00CF}',
+    '\u{00CC}', '\u{0060}', '\u{003A}', '\u{0023}', '\u{0040}', '\u{0027}', '\u{003D}', '\u{0022}',
+    '\u{00D8}', '\u{0061}', '\u{0062}', '\u{0063}', '\u{0064}', '\u{0065}', '\u{0066}', '\u{0067}',
+    '\u{0068}', '\u{0069}', '\u{00AB}', '\u{00BB}', '\u{00F0}', '\u{00FD}', '\u{00FE}', '\u{00B1}',
+    '\u{00B0}', '\u{006A}', '\u{006B}', '\u{006C}', '\u{006D}', '\u{006E}', '\u{006F}', '\u{0070}',
+    '\u{0071}', '\u{0072}', '\u{00AA}', '\u{00BA}', '\u{00E6}', '\u{00B8}', '\u{00C6}', '\u{00A4}',
+    '\u{00B5}', '\u{007E}', '\u{0073}', '\u{0074}', '\u{0075}', '\u{0076}', '\u{0077}', '\u{0078}',
+    '\u{0079}', '\u{007A}', '\u{00A1}', '\u{00BF}', '\u{00D0}', '\u{00DD}', '\u{00DE}', '\u{00AE}',
+    '\u{005E}', '\u{00A3}', '\u{00A5}', '\u{00B7}', '\u{00A9}', '\u{00A7}', '\u{00B6}', '\u{00BC}',
+    '\u{00BD}', '\u{00BE}', '\u{005B}', '\u{005D}', '\u{00AF}', '\u{00A8}', '\u{00B4}', '\u{00D7}',
+    '\u{007B}', '\u{0041}', '\u{0042}', '\u{0043}', '\u{0044}', '\u{0045}', '\u{0046}', '\u{0047}',
+    '\u{0048}', '\u{0049}', '\u{00AD}', '\u{00F4}', '\u{00F6}', '\u{00F2}', '\u{00F3}', '\u{00F5}',
+    '\u{007D}', '\u{004A}', '\u{004B}', '\u{004C}', '\u{004D}', '\u{004E}', '\u{004F}', '\u{0050}',
+    '\u{0051}', '\u{0052}', '\u{00B9}', '\u{00FB}', '\u{00FC}', '\u{00F9}', '\u{00FA}', '\u{00FF}',
+    '\u{005C}', '\u{00F7}', '\u{0053}', '\u{0054}', '\u{0055}', '\u{0056}', '\u{0057}', '\u{0058}',
+    '\u{0059}', '\u{005A}', '\u{00B2}', '\u{00D4}', '\u{00D6}', '\u{00D2}', '\u{00D3}', '\u{00D5}',
+    '\u{0030}', '\u{0031}', '\u{0032}', '\u{0033}', '\u{0034}', '\u{0035}', '\u{0036}', '\u{0037}',
+    '\u{0038}', '\u{0039}', '\u{00B3}', '\u{00DB}', '\u{00DC}', '\u{00D9}', '\u{00DA}', '\u{009F}',
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// `text` as UTF-16 after its byte order mark, in the byte order `encoding` names
+    fn utf16_with_bom(text: &str, encoding: SourceEncoding) -> Vec<u8> {
+        let mut bytes = match encoding {
+            SourceEncoding::Utf16Be => vec![0xFE, 0xFF],
+            _ => vec![0xFF, 0xFE],
+        };
+        for unit in text.encode_utf16() {
+            match encoding {
+                SourceEncoding::Utf16Be => bytes.extend(unit.to_be_bytes()),
+                _ => bytes.extend(unit.to_le_bytes()),
+            }
+        }
+        bytes
+    }
+    
+    #[test]
+    fn test_latin1_source_is_detected_and_decoded() {
+        let bytes = b"# Caf\xe9 r\xe9sum\xe9\nname = 'Jos\xe9'\n";
+        assert_eq!(detect_encoding(bytes), SourceEncoding::Latin1);
+        let decoded = decode_source(bytes, None).unwrap();
+        assert_eq!(decoded.text, "# Café résumé\nname = 'José'\n");
+        assert!(!decoded.had_bom);
+        
+        // An explicit encoding the bytes aren't valid in is an error, not replacement characters
+        assert!(decode_source(bytes, Some(SourceEncoding::Utf8)).is_err());
+        assert_eq!(SourceEncoding::from_label("ISO-8859-1"), Some(SourceEncoding::Latin1));
+        
+        let path = std::env::temp_dir().join(format!("coalesce_latin1_{}.py", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        let read = read_source(path.to_str().unwrap(), None);
+        std::fs::remove_file(&path).ok();
+        assert_eq!(read.unwrap().text, decoded.text);
+    }
+    
+    #[test]
+    fn test_accented_letters_between_ascii_letters_are_latin1() {
+        for (bytes, text) in [
+            (&b"# Cr\xe9dit client\n"[..], "# Crédit client\n"),
+            (&b"title = 'Se\xf1or'\n"[..], "title = 'Señor'\n"),
+        ] {
+            assert_eq!(detect_encoding(bytes), SourceEncoding::Latin1);
+            assert_eq!(decode_source(bytes, None).unwrap().text, text);
+        }
+    }
+    
+    #[test]
+    fn test_japanese_source_is_detected_as_shift_jis() {
+        let text = "# テスト\nname = '日本語'\n";
+        let (bytes, _, _) = SHIFT_JIS.encode(text);
+        assert_eq!(detect_encoding(&bytes), SourceEncoding::ShiftJis);
+        assert_eq!(decode_source(&bytes, None).unwrap().text, text);
+    }
+    
+    #[test]
+    fn test_utf16_byte_order_marks_decide_the_encoding_and_are_dropped() {
+        let text = "x = 'über'\nprint(x)\n";
+        for encoding in [SourceEncoding::Utf16Le, SourceEncoding::Utf16Be] {
+            let bytes = utf16_with_bom(text, encoding);
+            assert_eq!(detect_encoding(&bytes), encoding);
+            let decoded = decode_source(&bytes, None).unwrap();
+            assert_eq!(decoded.text, text);
+            assert_eq!(decoded.encoding, encoding);
+            assert!(decoded.had_bom);
+        }
+        
+        let decoded = decode_source(b"\xEF\xBB\xBFx = 1\n", None).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.encoding, decoded.had_bom), ("x = 1\n", SourceEncoding::Utf8, true));
+    }
+}
@@ -20,15 +20,27 @@ pub enum CoalesceError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     
+    #[error("Encoding error: {0}")]
+    EncodingError(String),
+    
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
     
+    #[error("Invalid binary UIR: {0}")]
+    BinaryFormatError(String),
+    
     #[error("Unsupported language: {0:?}")]
     UnsupportedLanguage(crate::types::Language),
     
+    #[error("Invalid selector: {0}")]
+    SelectorError(String),
+    
     #[error("Transformation error: {0}")]
     TransformationError(String),
     
+    #[error("Pattern pack error: {0}")]
+    PatternPackError(String),
+    
     #[error("Legacy pattern preservation failed: {pattern}")]
     LegacyPatternError { pattern: String },
 }
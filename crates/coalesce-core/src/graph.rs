@@ -0,0 +1,229 @@
+// Graph rendering for Graphviz and Mermaid
+//
+// `Graph` is a plain list of labelled nodes and edges that renders as DOT or as a Mermaid
+// flowchart. `UIRNode::to_graph` draws the tree with parent-to-child edges in child order and
+// dashed edges from comments to the declarations they are attached to; other analyses build
+// their own graphs the same way.
+use crate::types::{NodeType, UIRNode};
+use crate::visit::{Walk, walk};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl GraphFormat {
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.to_lowercase().as_str() {
+            "dot" | "graphviz" => Some(Self::Dot),
+            "mermaid" | "mmd" => Some(Self::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub label: Option<String>,
+    /// Drawn dashed, for relations other than the graph's main one
+    pub dashed: bool,
+}
+
+/// A directed graph, rendered with nodes and edges in insertion order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Graph {
+    pub name: String,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl Graph {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), nodes: Vec::new(), edges: Vec::new() }
+    }
+    
+    pub fn add_node(&mut self, id: impl Into<String>, label: impl Into<String>) {
+        self.nodes.push(GraphNode { id: id.into(), label: label.into() });
+    }
+    
+    pub fn add_edge(&mut self, from: impl Into<String>, to: impl Into<String>, label: Option<String>, dashed: bool) {
+        self.edges.push(GraphEdge { from: from.into(), to: to.into(), label, dashed });
+    }
+    
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+    
+    pub fn to_dot(&self) -> String {
+        let mut out = format!("digraph {} {{\n    node [shape=box, fontname=\"monospace\"];\n", dot_string(&self.name));
+        for node in &self.nodes {
+            out.push_str(&format!("    {} [label={}];\n", dot_string(&node.id), dot_string(&node.label)));
+        }
+        for edge in &self.edges {
+            let mut attributes = Vec::new();
+            if let Some(label) = &edge.label {
+                attributes.push(format!("label={}", dot_string(label)));
+            }
+            if edge.dashed {
+                attributes.push("style=dashed".to_string());
+            }
+            let attributes = if attributes.is_empty() { String::new() } else { format!(" [{}]", attributes.join(", ")) };
+            out.push_str(&format!("    {} -> {}{};\n", dot_string(&edge.from), dot_string(&edge.to), attributes));
+        }
+        out.push_str("}\n");
+        out
+    }
+    
+    /// A `flowchart TD`; Mermaid ids allow only word characters, so nodes are numbered
+    pub fn to_mermaid(&self) -> String {
+        let keys: HashMap<&str, String> = self.nodes.iter().enumerate()
+            .map(|(index, node)| (node.id.as_str(), format!("n{}", index)))
+            .collect();
+        let key = |id: &str| keys.get(id).cloned().unwrap_or_else(|| mermaid_id(id));
+        
+        let mut out = String::from("flowchart TD\n");
+        for node in &self.nodes {
+            out.push_str(&format!("    {}[\"{}\"]\n", key(&node.id), mermaid_text(&node.label)));
+        }
+        for edge in &self.edges {
+            let arrow = if edge.dashed { "-.->" } else { "-->" };
+            let label = edge.label.as_ref().map(|label| format!("|\"{}\"|", mermaid_text(label))).unwrap_or_default();
+            out.push_str(&format!("    {} {}{} {}\n", key(&edge.from), arrow, label, key(&edge.to)));
+        }
+        out
+    }
+}
+
+impl UIRNode {
+    /// The tree as a graph of its nodes, labelled by kind, name and literal or operator
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new(self.name.clone().unwrap_or_else(|| "uir".to_string()));
+        walk(self, &mut |node: &UIRNode| {
+            graph.add_node(node.id.clone(), graph_label(node));
+            for child in &node.children {
+                graph.add_edge(node.id.clone(), child.id.clone(), None, false);
+            }
+            if let NodeType::Comment { attached_to: Some(target), .. } = &node.node_type {
+                graph.add_edge(node.id.clone(), target.clone(), Some("attached to".to_string()), true);
+            }
+            Walk::Continue
+        });
+        graph
+    }
+}
+
+fn graph_label(node: &UIRNode) -> String {
+    let mut label = node.kind_path();
+    if let Some(name) = &node.name {
+        label.push_str(&format!("\n{}", name));
+    }
+    if let Some(operator) = node.operator {
+        label.push_str(&format!("\n{}", operator.symbol()));
+    }
+    if let Some(text) = node.literal.as_ref().and(node.metadata.annotations.get("original_text")).and_then(|v| v.as_str()) {
+        label.push_str(&format!("\n{}", text));
+    }
+    if let NodeType::Comment { text, .. } = &node.node_type {
+        let first_line = text.lines().next().unwrap_or_default();
+        label.push_str(&format!("\n{}", first_line));
+    }
+    label
+}
+
+/// A quoted DOT string, with newlines as DOT's centred line breaks
+fn dot_string(text: &str) -> String {
+    let escaped = text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// Text for a quoted Mermaid label, which takes HTML entities and `<br/>` line breaks
+fn mermaid_text(text: &str) -> String {
+    text.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;").replace('>', "&gt;").replace('\n', "<br/>")
+}
+
+fn mermaid_id(id: &str) -> String {
+    id.chars().map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::UIRBuilder;
+    use crate::types::{CommentStyle, Operator};
+    
+    fn sample() -> Graph {
+        let mut graph = Graph::new("calls");
+        graph.add_node("main.rs", "main");
+        graph.add_node("say \"hi\"", "greet\n<name>");
+        graph.add_edge("main.rs", "say \"hi\"", Some("calls".to_string()), false);
+        graph.add_edge("say \"hi\"", "main.rs", None, true);
+        graph
+    }
+    
+    #[test]
+    fn test_dot_quotes_ids_and_labels() {
+        assert_eq!(sample().render(GraphFormat::Dot), concat!(
+            "digraph \"calls\" {\n",
+            "    node [shape=box, fontname=\"monospace\"];\n",
+            "    \"main.rs\" [label=\"main\"];\n",
+            "    \"say \\\"hi\\\"\" [label=\"greet\\n<name>\"];\n",
+            "    \"main.rs\" -> \"say \\\"hi\\\"\" [label=\"calls\"];\n",
+            "    \"say \\\"hi\\\"\" -> \"main.rs\" [style=dashed];\n",
+            "}\n",
+        ));
+    }
+    
+    #[test]
+    fn test_mermaid_numbers_nodes_and_escapes_labels() {
+        assert_eq!(sample().render(GraphFormat::Mermaid), concat!(
+            "flowchart TD\n",
+            "    n0[\"main\"]\n",
+            "    n1[\"greet<br/>&lt;name&gt;\"]\n",
+            "    n0 -->|\"calls\"| n1\n",
+            "    n1 -.-> n0\n",
+        ));
+        assert_eq!(GraphFormat::from_label("Graphviz"), Some(GraphFormat::Dot));
+        assert_eq!(GraphFormat::from_label("mmd"), Some(GraphFormat::Mermaid));
+        assert_eq!(GraphFormat::from_label("svg"), None);
+    }
+    
+    #[test]
+    fn test_tree_graph_labels_nodes_and_draws_attachments() {
+        let b = UIRBuilder::new();
+        let function = b.function("add").body([b.return_value(b.binary(Operator::Add, b.literal(1), b.literal(2)))]);
+        let comment = UIRNode::new("comment_1".to_string(), NodeType::Comment {
+            style: CommentStyle::Line,
+            text: "Adds\ntwo numbers".to_string(),
+            attached_to: Some(function.id.clone()),
+        });
+        let graph = b.module("math").body([comment, function]).to_graph();
+        
+        let labels: Vec<&str> = graph.nodes.iter().map(|node| node.label.as_str()).collect();
+        assert_eq!(labels, [
+            "Module\nmath",
+            "Comment\nAdds",
+            "Function\nadd",
+            "Statement.Return",
+            "Expression.Arithmetic\n+",
+            "Expression.Literal\n1",
+            "Expression.Literal\n2",
+        ]);
+        assert_eq!(graph.name, "math");
+        let attachment = graph.edges.iter().find(|edge| edge.dashed).unwrap();
+        assert_eq!((attachment.from.as_str(), attachment.to.as_str()), ("comment_1", graph.nodes[2].id.as_str()));
+        assert_eq!(graph.edges.iter().filter(|edge| !edge.dashed).count(), 6);
+    }
+}
@@ -0,0 +1,231 @@
+// Content-derived node ids
+//
+// Parser ids embed line, column and a prefix of the source text, so reformatting a file
+// renames every node after the edit. These ids hash what a node is instead. A declaration
+// (a named function, class, field and the like) is its enclosing node's id plus its kind and
+// name, so editing a function's body keeps the function's id; any other node is its enclosing
+// node's id plus a digest of its content and its children's, with whitespace ignored. Siblings
+// with equal content take `#2`, `#3`, ... suffixes in document order, which sets their
+// descendants apart too.
+use crate::style::NamingConvention;
+use crate::types::{NodeType, UIRNode};
+use crate::visit::{Walk, walk_mut};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// How `assign_content_ids` identifies a node among its siblings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdScheme {
+    /// By content alone: nodes keep their ids when siblings are inserted, removed or reordered
+    Content,
+    /// By content and index among its siblings: identical siblings get distinct ids without
+    /// suffixes, and a node that moves gets a new id
+    Positional,
+}
+
+impl IdScheme {
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "content" => Some(Self::Content),
+            "positional" => Some(Self::Positional),
+            _ => None,
+        }
+    }
+}
+
+impl UIRNode {
+    /// Replace parser ids with ids hashed from each node's content, such as
+    /// `function_5f0e4c1a9b3d2e87`, that survive whitespace and formatting edits
+    pub fn assign_content_ids(&mut self, scheme: IdScheme) {
+        let mut digests = Vec::new();
+        collect_digests(self, &mut digests);
+        let mut ids = Vec::new();
+        collect_ids(self, "", None, scheme, &mut digests.into_iter(), &mut HashMap::new(), &mut ids);
+        
+        let mut renamed = HashMap::new();
+        let mut ids = ids.into_iter();
+        walk_mut(self, &mut |node: &mut UIRNode| {
+            let id = ids.next().unwrap_or_default();
+            renamed.insert(std::mem::replace(&mut node.id, id.clone()), id);
+            Walk::Continue
+        });
+        self.retarget_references(&renamed);
+    }
+}
+
+/// Push the ids of `node` and its descendants in document order, reading their digests from
+/// `digests` in the same order and counting the ids `seen` so far
+fn collect_ids(
+    node: &UIRNode,
+    parent: &str,
+    index: Option<usize>,
+    scheme: IdScheme,
+    digests: &mut impl Iterator<Item = String>,
+    seen: &mut HashMap<String, usize>,
+    ids: &mut Vec<String>,
+) {
+    let mut hash = Fnv::new();
+    hash.write(parent);
+    hash.write(&digests.next().unwrap_or_default());
+    if let Some(index) = index.filter(|_| scheme == IdScheme::Positional) {
+        hash.write(&index.to_string());
+    }
+    let kind = node.kind_path();
+    let prefix = NamingConvention::SnakeCase.apply(kind.rsplit('.').next().unwrap_or_default());
+    let mut id = format!("{}_{:016x}", prefix, hash.finish());
+    let count = seen.entry(id.clone()).or_insert(0);
+    *count += 1;
+    if *count > 1 {
+        id = format!("{}#{}", id, count);
+    }
+    ids.push(id.clone());
+    for (index, child) in node.children.iter().enumerate() {
+        collect_ids(child, &id, Some(index), scheme, digests, seen, ids);
+    }
+}
+
+/// Push the digests of `node` and its descendants in document order, returning the node's:
+/// what identifies it regardless of where it sits, which is the kind and name of a
+/// declaration and the content of any other node and its children
+fn collect_digests(node: &UIRNode, digests: &mut Vec<String>) -> String {
+    let slot = digests.len();
+    digests.push(String::new());
+    let mut hash = Fnv::new();
+    for child in &node.children {
+        hash.write(&collect_digests(child, digests));
+    }
+    
+    let kind = node.kind_path();
+    let digest = match &node.name {
+        Some(name) if is_declaration(&node.node_type) => format!("{}:{}", kind, name),
+        _ => {
+            let mut content = serde_json::json!({
+                "node_type": node.node_type,
+                "name": node.name,
+                "operator": node.operator,
+                "literal": node.literal,
+                "visibility": node.visibility,
+                "modifiers": node.modifiers,
+                "is_async": node.is_async,
+                "attributes": node.attributes,
+                "ownership": node.ownership,
+                "documentation": node.documentation,
+                "tags": node.metadata.semantic_tags,
+                "annotations": node.metadata.annotations,
+            });
+            strip_whitespace(&mut content);
+            hash.write(&content.to_string());
+            format!("{:016x}", hash.finish())
+        }
+    };
+    digests[slot] = digest.clone();
+    digest
+}
+
+fn is_declaration(node_type: &NodeType) -> bool {
+    matches!(
+        node_type,
+        NodeType::Module | NodeType::Function | NodeType::Class | NodeType::Interface | NodeType::Constant
+            | NodeType::Field { .. } | NodeType::Property { .. } | NodeType::Enum { .. }
+    )
+}
+
+/// Drop whitespace from every string, so reformatted source text hashes the same
+fn strip_whitespace(value: &mut Value) {
+    match value {
+        Value::String(text) => text.retain(|c| !c.is_whitespace()),
+        Value::Array(items) => items.iter_mut().for_each(strip_whitespace),
+        Value::Object(fields) => fields.values_mut().for_each(strip_whitespace),
+        _ => {}
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `std`'s hashers is fixed across Rust releases and platforms
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+    
+    fn write(&mut self, text: &str) {
+        // A separator keeps ("ab", "c") and ("a", "bc") apart
+        for byte in text.bytes().chain([0xff]) {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::UIRBuilder;
+    
+    fn program(b: &UIRBuilder, body: UIRNode) -> UIRNode {
+        b.module("app").body([b.function("run").body([body]), b.function("stop")])
+    }
+    
+    fn content_ids(mut tree: UIRNode, scheme: IdScheme) -> UIRNode {
+        tree.assign_content_ids(scheme);
+        tree
+    }
+    
+    #[test]
+    fn test_declarations_keep_their_ids_when_bodies_change() {
+        let b = UIRBuilder::new();
+        let old = content_ids(program(&b, b.call("log", [b.literal(1)])), IdScheme::Content);
+        let new = content_ids(program(&b, b.call("log", [b.literal(2)])), IdScheme::Content);
+        
+        assert!(old.children[0].id.starts_with("function_"), "{}", old.children[0].id);
+        assert_eq!(old.id, new.id);
+        assert_eq!(old.children[0].id, new.children[0].id);
+        assert_eq!(old.children[1].id, new.children[1].id);
+        assert_ne!(old.children[0].children[0].id, new.children[0].children[0].id);
+        assert_eq!(new.validate(), Ok(()));
+    }
+    
+    #[test]
+    fn test_whitespace_in_source_text_does_not_change_ids() {
+        let b = UIRBuilder::new();
+        let spaced = b.identifier("x").annotated("original_text", " x ");
+        let old = content_ids(program(&b, b.identifier("x")), IdScheme::Content);
+        let new = content_ids(program(&b, spaced), IdScheme::Content);
+        assert_eq!(old.children[0].children[0].id, new.children[0].children[0].id);
+    }
+    
+    #[test]
+    fn test_identical_siblings_by_scheme() {
+        let b = UIRBuilder::new();
+        let calls = || b.function("run").body([b.call("tick", []), b.call("tick", [])]);
+        
+        let content = content_ids(calls(), IdScheme::Content);
+        assert_eq!(content.children[1].id, format!("{}#2", content.children[0].id));
+        
+        let positional = content_ids(calls(), IdScheme::Positional);
+        assert!(!positional.children[1].id.contains('#'), "{}", positional.children[1].id);
+        assert_ne!(positional.children[0].id, positional.children[1].id);
+        assert_eq!(IdScheme::from_label("positional"), Some(IdScheme::Positional));
+        assert_eq!(IdScheme::from_label("random"), None);
+    }
+    
+    #[test]
+    fn test_content_ids_survive_reordering_and_positional_ones_do_not() {
+        let b = UIRBuilder::new();
+        let tree = b.function("run").body([b.call("start", []), b.call("finish", [])]);
+        let mut swapped = tree.clone();
+        swapped.children.reverse();
+        
+        let old = content_ids(tree.clone(), IdScheme::Content);
+        let new = content_ids(swapped.clone(), IdScheme::Content);
+        assert_eq!(old.children[0].id, new.children[1].id);
+        
+        let old = content_ids(tree, IdScheme::Positional);
+        let new = content_ids(swapped, IdScheme::Positional);
+        assert_ne!(old.children[0].id, new.children[1].id);
+    }
+}
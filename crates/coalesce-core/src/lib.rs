@@ -2,7 +2,30 @@
 pub mod types;
 pub mod traits;
 pub mod errors;
+pub mod encoding;
+pub mod style;
+pub mod validation;
+pub mod builder;
+pub mod visit;
+pub mod diff;
+pub mod query;
+pub mod ids;
+pub mod tree;
+pub mod binary;
+pub mod graph;
+pub mod config;
 
 pub use types::*;
+pub use builder::UIRBuilder;
 pub use traits::*;
 pub use errors::*;
+pub use encoding::{SourceEncoding, DecodedSource, detect_encoding, decode_source, read_source};
+pub use style::{GeneratorConfig, BraceStyle, NamingConvention};
+pub use validation::{ValidationRule, Violation};
+pub use visit::{Visitor, VisitorMut, Walk, walk, walk_mut};
+pub use diff::{ChangeKind, NodeChange, NodePath, TreeDiff};
+pub use query::Selector;
+pub use ids::IdScheme;
+pub use tree::{NodeIndex, UIRTree};
+pub use graph::{Graph, GraphEdge, GraphFormat, GraphNode};
+pub use config::{project_root, GrammarConfig, ProjectConfig, CONFIG_FILE};
@@ -0,0 +1,415 @@
+// Selectors over UIR trees
+//
+// A CSS-like syntax for finding nodes: `Function[name=main] > ControlFlow` selects the
+// control flow directly inside `main`. A selector is a chain of compounds joined by `>`
+// (child) or whitespace (descendant), and a list of chains separated by commas matches nodes
+// any of them matches. A compound is a node kind and any number of `[key]` or `[key op value]`
+// filters, with `op` one of `=`, `!=`, `^=` (prefix), `$=` (suffix) and `*=` (substring).
+//
+// Kinds are dotted paths through the node type, matched by prefix: `ControlFlow` matches every
+// control flow node, `ControlFlow.Loop.While` only while loops, `Statement.Return` returns;
+// `*` matches any node. Filter keys are `name`, `id`, `tag` (a semantic tag), `visibility`,
+// `modifier` (any of the node's modifiers), `async` and, otherwise, annotation keys.
+use crate::errors::{CoalesceError, Result};
+use crate::types::{ControlFlowType, NodeType, UIRNode};
+use crate::visit::{Visitor, Walk, walk};
+use std::str::FromStr;
+
+const KINDS: &[&str] = &[
+    "Module", "Function", "Class", "Interface", "Variable", "Constant", "Field", "Property", "Enum",
+    "Closure", "ControlFlow", "Expression", "Statement", "Comment", "Import",
+];
+
+/// A parsed selector, reusable across trees
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    chains: Vec<Vec<(Combinator, Compound)>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Compound {
+    kind: Option<Vec<String>>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    key: String,
+    test: Option<(Test, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Test {
+    Equals,
+    NotEquals,
+    Prefix,
+    Suffix,
+    Contains,
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self> {
+        let mut chains = Vec::new();
+        for alternative in split_top_level(selector) {
+            chains.push(parse_chain(alternative)?);
+        }
+        Ok(Self { chains })
+    }
+    
+    /// The nodes under and including `root` that match, in document order
+    pub fn select<'a>(&self, root: &'a UIRNode) -> Vec<&'a UIRNode> {
+        let mut selection = Selection { selector: self, ancestors: Vec::new(), selected: Vec::new() };
+        walk(root, &mut selection);
+        selection.selected
+    }
+    
+    /// Whether `node`, below `ancestors` (outermost first), matches
+    pub fn matches(&self, node: &UIRNode, ancestors: &[&UIRNode]) -> bool {
+        self.chains.iter().any(|chain| matches_chain(chain, node, ancestors))
+    }
+}
+
+impl FromStr for Selector {
+    type Err = CoalesceError;
+    
+    fn from_str(selector: &str) -> Result<Self> {
+        Self::parse(selector)
+    }
+}
+
+impl UIRNode {
+    /// The nodes in this tree matching `selector`, in document order
+    pub fn select(&self, selector: &str) -> Result<Vec<&UIRNode>> {
+        Ok(Selector::parse(selector)?.select(self))
+    }
+    
+    /// The node type as the dotted path selectors match, e.g. `ControlFlow.Loop.While`
+    pub fn kind_path(&self) -> String {
+        match &self.node_type {
+            NodeType::Field { .. } => "Field".to_string(),
+            NodeType::Property { .. } => "Property".to_string(),
+            NodeType::Enum { .. } => "Enum".to_string(),
+            NodeType::Closure { .. } => "Closure".to_string(),
+            NodeType::Comment { .. } => "Comment".to_string(),
+            NodeType::Import { .. } => "Import".to_string(),
+            NodeType::ControlFlow(ControlFlowType::Loop(loop_type)) => format!("ControlFlow.Loop.{:?}", loop_type),
+            NodeType::ControlFlow(ControlFlowType::Catch { .. }) => "ControlFlow.Catch".to_string(),
+            NodeType::ControlFlow(control_flow) => format!("ControlFlow.{:?}", control_flow),
+            NodeType::Expression(expression) => format!("Expression.{:?}", expression),
+            NodeType::Statement(statement) => format!("Statement.{:?}", statement),
+            node_type => format!("{:?}", node_type),
+        }
+    }
+}
+
+struct Selection<'s, 'a> {
+    selector: &'s Selector,
+    ancestors: Vec<&'a UIRNode>,
+    selected: Vec<&'a UIRNode>,
+}
+
+impl<'a> Visitor<'a> for Selection<'_, 'a> {
+    fn enter(&mut self, node: &'a UIRNode) -> Walk {
+        if self.selector.matches(node, &self.ancestors) {
+            self.selected.push(node);
+        }
+        self.ancestors.push(node);
+        Walk::Continue
+    }
+    
+    fn leave(&mut self, _node: &'a UIRNode) -> Walk {
+        self.ancestors.pop();
+        Walk::Continue
+    }
+}
+
+/// Match the chain's last compound against `node` and the rest against its ancestors
+fn matches_chain(chain: &[(Combinator, Compound)], node: &UIRNode, ancestors: &[&UIRNode]) -> bool {
+    let Some(((combinator, compound), rest)) = chain.split_last() else {
+        return true;
+    };
+    if !compound.matches(node) {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+    match combinator {
+        Combinator::Child => ancestors.split_last()
+            .is_some_and(|(parent, above)| matches_chain(rest, parent, above)),
+        Combinator::Descendant => (0..ancestors.len()).rev()
+            .any(|index| matches_chain(rest, ancestors[index], &ancestors[..index])),
+    }
+}
+
+impl Compound {
+    fn matches(&self, node: &UIRNode) -> bool {
+        if let Some(kind) = &self.kind {
+            let path = node.kind_path();
+            let segments: Vec<&str> = path.split('.').collect();
+            if kind.len() > segments.len() || kind.iter().zip(&segments).any(|(want, have)| want != have) {
+                return false;
+            }
+        }
+        self.filters.iter().all(|filter| filter.matches(node))
+    }
+}
+
+impl Filter {
+    fn matches(&self, node: &UIRNode) -> bool {
+        let values: Vec<String> = match self.key.as_str() {
+            "name" => node.name.iter().cloned().collect(),
+            "id" => vec![node.id.clone()],
+            "tag" => node.metadata.semantic_tags.clone(),
+            "visibility" => node.visibility.iter().map(|v| format!("{:?}", v).to_lowercase()).collect(),
+            "modifier" => node.modifiers.iter().map(|m| format!("{:?}", m).to_lowercase()).collect(),
+            "async" => if node.is_async { vec!["true".to_string()] } else { Vec::new() },
+            key => node.metadata.annotations.get(key)
+                .map(|value| value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()))
+                .into_iter()
+                .collect(),
+        };
+        let Some((test, operand)) = &self.test else {
+            return !values.is_empty();
+        };
+        let equals = |value: &str| value == operand || self.folds_case() && value.eq_ignore_ascii_case(operand);
+        let any = |predicate: &dyn Fn(&str) -> bool| values.iter().any(|value| predicate(value));
+        match test {
+            Test::Equals => any(&equals),
+            // A node without the key has no value equal to the operand
+            Test::NotEquals => !any(&equals),
+            Test::Prefix => any(&|value| value.starts_with(operand.as_str())),
+            Test::Suffix => any(&|value| value.ends_with(operand.as_str())),
+            Test::Contains => any(&|value| value.contains(operand.as_str())),
+        }
+    }
+    
+    /// Visibilities and modifiers are keywords, written in whatever case the source language uses
+    fn folds_case(&self) -> bool {
+        matches!(self.key.as_str(), "visibility" | "modifier")
+    }
+}
+
+/// Split on commas outside brackets and quotes
+fn split_top_level(selector: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (index, c) in selector.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(&selector[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&selector[start..]);
+    parts
+}
+
+fn parse_chain(text: &str) -> Result<Vec<(Combinator, Compound)>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut pos = 0;
+    let mut chain = Vec::new();
+    let mut combinator = Combinator::Descendant;
+    loop {
+        while pos < chars.len() && chars[pos].is_whitespace() {
+            pos += 1;
+        }
+        if pos == chars.len() {
+            break;
+        }
+        if chars[pos] == '>' {
+            if chain.is_empty() || combinator == Combinator::Child {
+                return Err(selector_error(text, "`>` needs a compound on each side"));
+            }
+            combinator = Combinator::Child;
+            pos += 1;
+            continue;
+        }
+        let compound = parse_compound(text, &chars, &mut pos)?;
+        chain.push((combinator, compound));
+        combinator = Combinator::Descendant;
+    }
+    if chain.is_empty() {
+        return Err(selector_error(text, "empty selector"));
+    }
+    if combinator == Combinator::Child {
+        return Err(selector_error(text, "`>` needs a compound on each side"));
+    }
+    Ok(chain)
+}
+
+fn parse_compound(text: &str, chars: &[char], pos: &mut usize) -> Result<Compound> {
+    let kind: String = take_while(chars, pos, |c| c.is_alphanumeric() || c == '_' || c == '.' || c == '*');
+    if kind.is_empty() && chars[*pos] != '[' {
+        return Err(selector_error(text, &format!("unexpected `{}`", chars[*pos])));
+    }
+    let kind = match kind.as_str() {
+        "" | "*" => None,
+        kind => {
+            let segments: Vec<String> = kind.split('.').map(str::to_string).collect();
+            if !KINDS.contains(&segments[0].as_str()) {
+                return Err(selector_error(text, &format!("unknown node kind `{}`", segments[0])));
+            }
+            Some(segments)
+        }
+    };
+    
+    let mut filters = Vec::new();
+    while *pos < chars.len() && chars[*pos] == '[' {
+        *pos += 1;
+        let key = take_while(chars, pos, |c| c.is_alphanumeric() || c == '_' || c == '-');
+        if key.is_empty() {
+            return Err(selector_error(text, "expected a key after `[`"));
+        }
+        let operator = take_while(chars, pos, |c| matches!(c, '=' | '!' | '^' | '$' | '*'));
+        let test = match operator.as_str() {
+            "" => None,
+            "=" => Some(Test::Equals),
+            "!=" => Some(Test::NotEquals),
+            "^=" => Some(Test::Prefix),
+            "$=" => Some(Test::Suffix),
+            "*=" => Some(Test::Contains),
+            operator => return Err(selector_error(text, &format!("unknown operator `{}`", operator))),
+        };
+        let test = match test {
+            Some(test) => Some((test, parse_value(text, chars, pos)?)),
+            None => None,
+        };
+        if *pos == chars.len() || chars[*pos] != ']' {
+            return Err(selector_error(text, "expected `]`"));
+        }
+        *pos += 1;
+        filters.push(Filter { key, test });
+    }
+    
+    Ok(Compound { kind, filters })
+}
+
+/// A quoted value, or a bare one running to the closing bracket
+fn parse_value(text: &str, chars: &[char], pos: &mut usize) -> Result<String> {
+    match chars.get(*pos) {
+        Some(&quote) if quote == '"' || quote == '\'' => {
+            *pos += 1;
+            let value = take_while(chars, pos, |c| c != quote);
+            if *pos == chars.len() {
+                return Err(selector_error(text, "unterminated quoted value"));
+            }
+            *pos += 1;
+            Ok(value)
+        }
+        _ => Ok(take_while(chars, pos, |c| c != ']').trim().to_string()),
+    }
+}
+
+fn take_while(chars: &[char], pos: &mut usize, predicate: impl Fn(char) -> bool) -> String {
+    let start = *pos;
+    while *pos < chars.len() && predicate(chars[*pos]) {
+        *pos += 1;
+    }
+    chars[start..*pos].iter().collect()
+}
+
+fn selector_error(selector: &str, message: &str) -> CoalesceError {
+    CoalesceError::SelectorError(format!("{} in `{}`", message, selector.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::UIRBuilder;
+    use crate::types::{LoopType, Modifier, Operator, Visibility};
+    
+    fn sample() -> UIRNode {
+        let b = UIRBuilder::new();
+        let condition = b.binary(Operator::Greater, b.identifier("n"), b.literal(0));
+        let mut count = UIRNode::new("loop_1".to_string(), NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)));
+        count.children.push(b.binary(Operator::Greater, b.identifier("n"), b.literal(1)));
+        b.module("app").body([
+            b.function("main")
+                .with_visibility(Visibility::Public)
+                .body([b.conditional(condition, [b.call("print", [b.identifier("n")])]), count]),
+            b.function("helper")
+                .with_modifier(Modifier::Static)
+                .asynchronous()
+                .tagged("internal")
+                .body([b.call("print", [b.literal("hi")])]),
+        ])
+    }
+    
+    fn names<'a>(nodes: &[&'a UIRNode]) -> Vec<&'a str> {
+        nodes.iter().map(|node| node.name.as_deref().unwrap_or(node.id.as_str())).collect()
+    }
+    
+    #[test]
+    fn test_kinds_match_by_prefix() {
+        let tree = sample();
+        assert_eq!(tree.select("*").unwrap().len(), 15);
+        assert_eq!(names(&tree.select("Function").unwrap()), ["main", "helper"]);
+        assert_eq!(tree.select("ControlFlow").unwrap().len(), 2);
+        assert_eq!(names(&tree.select("ControlFlow.Loop.While").unwrap()), ["loop_1"]);
+        assert!(tree.select("ControlFlow.Loop.For").unwrap().is_empty());
+        assert_eq!(tree.select("Expression.FunctionCall").unwrap().len(), 2);
+        assert_eq!(tree.kind_path(), "Module");
+        assert_eq!(tree.children[0].children[1].kind_path(), "ControlFlow.Loop.While");
+    }
+    
+    #[test]
+    fn test_combinators_and_alternatives() {
+        let tree = sample();
+        assert_eq!(names(&tree.select("Function[name=main] Expression.FunctionCall").unwrap()), ["print"]);
+        assert!(tree.select("Function[name=main] > Expression.FunctionCall").unwrap().is_empty());
+        assert_eq!(tree.select("Function > ControlFlow > Expression").unwrap().len(), 3);
+        assert_eq!(names(&tree.select("ControlFlow.Loop, Function[name=helper]").unwrap()), ["loop_1", "helper"]);
+        assert_eq!(tree.select("Module ControlFlow Expression.Variable").unwrap().len(), 3);
+    }
+    
+    #[test]
+    fn test_filters() {
+        let tree = sample();
+        assert_eq!(names(&tree.select("Function[visibility=PUBLIC]").unwrap()), ["main"]);
+        assert_eq!(names(&tree.select("Function[modifier=static][async]").unwrap()), ["helper"]);
+        assert_eq!(names(&tree.select("[tag=internal]").unwrap()), ["helper"]);
+        assert_eq!(names(&tree.select("Function[name!=main]").unwrap()), ["helper"]);
+        assert_eq!(names(&tree.select("Function[name^=he][name$=er][name*=elp]").unwrap()), ["helper"]);
+        assert_eq!(names(&tree.select("Expression.Literal[original_text='\"hi\"']").unwrap()), [tree.children[1].children[0].children[0].id.as_str()]);
+        assert_eq!(tree.select("Expression[original_text*='n >']").unwrap().len(), 2);
+        assert_eq!(names(&tree.select("[id=loop_1]").unwrap()), ["loop_1"]);
+        assert!(tree.select("Function[name=\"a, b\"]").unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_invalid_selectors_are_errors() {
+        let message = |selector: &str| match Selector::parse(selector) {
+            Err(CoalesceError::SelectorError(message)) => message,
+            other => panic!("expected a selector error for `{}`, got {:?}", selector, other),
+        };
+        assert_eq!(message(""), "empty selector in ``");
+        assert_eq!(message("Function,"), "empty selector in ``");
+        assert_eq!(message("> Function"), "`>` needs a compound on each side in `> Function`");
+        assert_eq!(message("Function >"), "`>` needs a compound on each side in `Function >`");
+        assert_eq!(message("Function > > Class"), "`>` needs a compound on each side in `Function > > Class`");
+        assert_eq!(message("Method"), "unknown node kind `Method` in `Method`");
+        assert_eq!(message("Function ~ Class"), "unexpected `~` in `Function ~ Class`");
+        assert_eq!(message("Function[name~=x]"), "expected `]` in `Function[name~=x]`");
+        assert_eq!(message("Function[name=x"), "expected `]` in `Function[name=x`");
+        assert_eq!(message("Function[]"), "expected a key after `[` in `Function[]`");
+        assert_eq!(message("Function[name='x]"), "unterminated quoted value in `Function[name='x]`");
+        assert_eq!(message("Function[name=!x]"), "unknown operator `=!` in `Function[name=!x]`");
+        assert_eq!("Function > Class".parse::<Selector>().unwrap(), Selector::parse("Function>Class").unwrap());
+    }
+}
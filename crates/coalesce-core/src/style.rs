@@ -0,0 +1,183 @@
+use crate::types::{UIRNode, NodeType, ExpressionType};
+use crate::visit::{Walk, walk, walk_mut};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Where opening braces go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BraceStyle {
+    SameLine, // K&R: `if x {`
+    NextLine, // Allman: the brace on its own line
+}
+
+impl BraceStyle {
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "sameline" | "kr" | "knr" | "attach" => Some(Self::SameLine),
+            "nextline" | "allman" | "break" => Some(Self::NextLine),
+            _ => None,
+        }
+    }
+}
+
+/// Casing for function and variable names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NamingConvention {
+    SnakeCase,
+    CamelCase,
+    PascalCase,
+}
+
+impl NamingConvention {
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "snake" | "snakecase" => Some(Self::SnakeCase),
+            "camel" | "camelcase" => Some(Self::CamelCase),
+            "pascal" | "pascalcase" => Some(Self::PascalCase),
+            _ => None,
+        }
+    }
+    
+    /// Recase `name`, keeping leading and trailing underscores (`__init__`, `_private`)
+    pub fn apply(&self, name: &str) -> String {
+        let core = name.trim_matches('_');
+        if core.is_empty() {
+            return name.to_string();
+        }
+        let prefix = &name[..name.len() - name.trim_start_matches('_').len()];
+        let suffix = &name[name.trim_end_matches('_').len()..];
+        
+        let words = split_words(core);
+        let capitalize = |word: &str| {
+            let mut chars = word.chars();
+            chars.next()
+                .map(|first| first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect::<String>())
+                .unwrap_or_default()
+        };
+        let recased = match self {
+            Self::SnakeCase => words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>().join("_"),
+            Self::CamelCase => words.iter().enumerate()
+                .map(|(i, w)| if i == 0 { w.to_lowercase() } else { capitalize(w) })
+                .collect(),
+            Self::PascalCase => words.iter().map(|w| capitalize(w)).collect(),
+        };
+        format!("{}{}{}", prefix, recased, suffix)
+    }
+}
+
+/// Split an identifier into words at separators and case changes: `parseHTTPResponse` gives
+/// `parse`, `HTTP`, `Response`
+fn split_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if matches!(c, '_' | '-' | ' ') {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next_is_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Formatting preferences generators follow as they lay out code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneratorConfig {
+    /// Spaces per indentation level; `None` keeps the target language's own width
+    pub indent_width: Option<usize>,
+    pub use_tabs: bool,
+    /// Placement of the braces opening blocks and declarations; ignored for Python, and for
+    /// Go whose grammar requires same-line braces
+    pub brace_style: BraceStyle,
+    /// Applied to functions and variables declared in the translated code; `None` keeps
+    /// the target language's own convention. Rust output stays snake_case, which rustc lints for
+    pub naming: Option<NamingConvention>,
+    /// Calls and signatures longer than this are split one argument per line
+    pub max_line_length: Option<usize>,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            indent_width: None,
+            use_tabs: false,
+            brace_style: BraceStyle::SameLine,
+            naming: None,
+            max_line_length: None,
+        }
+    }
+}
+
+impl GeneratorConfig {
+    /// Copy of `uir` with declared functions and variables renamed to the configured convention
+    ///
+    /// Only names the tree declares are changed, so calls into libraries keep their spelling.
+    /// In a tree whose identifiers have been resolved, a reference is renamed only when it
+    /// resolves to a declaration in the tree, not merely when its name matches one.
+    pub fn apply_naming(&self, uir: &UIRNode) -> UIRNode {
+        let mut renamed = uir.clone();
+        if let Some(naming) = self.naming {
+            let mut declared = HashSet::new();
+            walk(uir, &mut |node: &UIRNode| {
+                collect_declared(node, &mut declared);
+                Walk::Continue
+            });
+            let resolved = uir.metadata.semantic_tags.iter().any(|t| t == "identifiers_resolved");
+            walk_mut(&mut renamed, &mut |node: &mut UIRNode| {
+                rename(node, naming, &declared, resolved);
+                Walk::Continue
+            });
+        }
+        renamed.debug_validate();
+        renamed
+    }
+}
+
+fn collect_declared(uir: &UIRNode, declared: &mut HashSet<String>) {
+    match &uir.node_type {
+        NodeType::Function | NodeType::Variable => declared.extend(uir.name.clone()),
+        NodeType::Expression(ExpressionType::Assignment) => {
+            if let Some(target) = uir.children.first() {
+                if target.children.is_empty() {
+                    declared.extend(target.name.clone());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn rename(uir: &mut UIRNode, naming: NamingConvention, declared: &HashSet<String>, resolved: bool) {
+    let reference = matches!(uir.node_type, NodeType::Expression(ExpressionType::Variable | ExpressionType::FunctionCall));
+    let renamable = match &uir.node_type {
+        NodeType::Function | NodeType::Variable | NodeType::Expression(ExpressionType::FunctionCall) => true,
+        // Identifiers only; member accesses and similar carry their node kind as a name
+        NodeType::Expression(ExpressionType::Variable) => uir.children.is_empty(),
+        _ => false,
+    };
+    // In a resolved tree, a reference that resolves to nothing names a builtin or library
+    // global, even when the tree declares the same name in another scope
+    let refers_to_declaration = uir.name.as_deref().is_some_and(|name| declared.contains(name))
+        && !(resolved && reference && !uir.metadata.annotations.contains_key("resolved_id"));
+    if renamable && refers_to_declaration {
+        if let Some(name) = uir.name.as_mut() {
+            if !matches!(name.as_str(), "self" | "this" | "cls") {
+                *name = naming.apply(name);
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
-use crate::{UIRNode, Language};
+use crate::{UIRNode, Language, TextEdit};
 use crate::errors::Result;
+use crate::encoding::{read_source, SourceEncoding};
+use crate::style::GeneratorConfig;
 
 /// Trait for language parsers
 pub trait Parser {
@@ -9,11 +11,41 @@ pub trait Parser {
     /// Parse source code into UIR
     fn parse(&self, source: &str) -> Result<UIRNode>;
     
-    /// Parse a specific file
+    /// Parse a specific file, detecting its encoding
     fn parse_file(&self, file_path: &str) -> Result<UIRNode> {
-        let source = std::fs::read_to_string(file_path)?;
-        self.parse(&source)
+        let source = read_source(file_path, None)?;
+        self.parse(&source.text)
     }
+    
+    /// Parse a file whose encoding is known up front
+    fn parse_file_with_encoding(&self, file_path: &str, encoding: SourceEncoding) -> Result<UIRNode> {
+        let source = read_source(file_path, Some(encoding))?;
+        self.parse(&source.text)
+    }
+}
+
+/// Result of reparsing after a set of edits
+#[derive(Debug)]
+pub struct IncrementalUpdate<T> {
+    /// The updated tree, to pass to the next reparse
+    pub tree: T,
+    /// UIR for the smallest enclosing declarations or statements affected by the edits
+    pub changed: Vec<UIRNode>,
+}
+
+/// Trait for parsers that can reuse a previous syntax tree after edits
+pub trait IncrementalParser: Parser {
+    /// Parser-specific syntax tree kept between parses
+    type Tree;
+    
+    /// Parse source from scratch, keeping the tree for later edits
+    fn parse_tree(&self, source: &str) -> Result<(Self::Tree, UIRNode)>;
+    
+    /// Apply edits in order to a previous tree and convert only what changed
+    fn reparse(&self, previous: &Self::Tree, edits: &[TextEdit]) -> Result<IncrementalUpdate<Self::Tree>>;
+    
+    /// Convert a whole tree, such as one `reparse` returned, as `parse` would its source
+    fn to_uir(&self, tree: &Self::Tree) -> Result<UIRNode>;
 }
 
 /// Trait for code generators
@@ -22,8 +54,27 @@ pub trait Generator {
     fn target_language(&self) -> Language;
     
     /// Generate code from UIR
+    ///
+    /// Output must depend only on `uir`: the same tree yields byte-identical code, so
+    /// implementations must not iterate hash maps or sets when emitting.
     fn generate(&self, uir: &UIRNode) -> Result<String>;
     
+    /// Generate code following a team's style preferences
+    ///
+    /// Generators that lay out code themselves override this to follow the config's layout;
+    /// by default only its naming is applied.
+    fn generate_with_config(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<String> {
+        self.generate(&config.apply_naming(uir))
+    }
+    
+    /// `generate_with_config`, with the lines of the code that are fallbacks: a TODO in place of
+    /// a node the generator can't translate, or source of another language copied as written
+    ///
+    /// Generators that fall back on code override this to report it; by default none is.
+    fn generate_with_fallbacks(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<(String, Vec<usize>)> {
+        Ok((self.generate_with_config(uir, config)?, Vec::new()))
+    }
+    
     /// Generate code and write to file
     fn generate_file(&self, uir: &UIRNode, output_path: &str) -> Result<()> {
         let code = self.generate(uir)?;
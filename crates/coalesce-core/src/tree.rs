@@ -0,0 +1,270 @@
+// Arena-backed UIR trees
+//
+// `UIRNode` owns its children, which suits serialization and generators walking top-down but
+// gives no way back up and makes sharing a subtree a deep clone. `UIRTree` keeps every node
+// in one vector, addressed by `NodeIndex`, with parent links and ordered child lists, so
+// analyses can move freely between a node, its parent and its siblings. Nodes in the arena
+// hold their own fields; their `children` vectors are empty and the tree's links replace them.
+use crate::diff::NodePath;
+use crate::types::{NodeType, UIRNode};
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut};
+
+/// A node's position in a `UIRTree`, valid for as long as the tree is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeIndex(usize);
+
+impl NodeIndex {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    node: UIRNode,
+    parent: Option<NodeIndex>,
+    children: Vec<NodeIndex>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UIRTree {
+    slots: Vec<Slot>,
+    root: NodeIndex,
+    by_id: HashMap<String, NodeIndex>,
+}
+
+impl UIRTree {
+    /// Move `root` and its descendants into an arena, in document order
+    pub fn new(root: UIRNode) -> Self {
+        let mut tree = Self { slots: Vec::new(), root: NodeIndex(0), by_id: HashMap::new() };
+        tree.root = tree.push(root, None);
+        tree
+    }
+    
+    /// Rebuild the owned tree, leaving out detached nodes
+    pub fn to_node(&self) -> UIRNode {
+        self.subtree(self.root)
+    }
+    
+    /// `to_node` without cloning each node
+    pub fn into_node(mut self) -> UIRNode {
+        self.take(self.root)
+    }
+    
+    /// The owned tree rooted at `index`
+    pub fn subtree(&self, index: NodeIndex) -> UIRNode {
+        let mut node = self[index].clone();
+        node.children = self.children(index).iter().map(|&child| self.subtree(child)).collect();
+        node
+    }
+    
+    pub fn root(&self) -> NodeIndex {
+        self.root
+    }
+    
+    /// Nodes in the arena, including detached ones
+    pub fn node_count(&self) -> usize {
+        self.slots.len()
+    }
+    
+    pub fn parent(&self, index: NodeIndex) -> Option<NodeIndex> {
+        self.slots[index.0].parent
+    }
+    
+    pub fn children(&self, index: NodeIndex) -> &[NodeIndex] {
+        &self.slots[index.0].children
+    }
+    
+    /// The node's position among its parent's children; `None` for the root and detached nodes
+    pub fn position(&self, index: NodeIndex) -> Option<usize> {
+        let parent = self.parent(index)?;
+        self.children(parent).iter().position(|&child| child == index)
+    }
+    
+    pub fn previous_sibling(&self, index: NodeIndex) -> Option<NodeIndex> {
+        let position = self.position(index)?.checked_sub(1)?;
+        Some(self.children(self.parent(index)?)[position])
+    }
+    
+    pub fn next_sibling(&self, index: NodeIndex) -> Option<NodeIndex> {
+        let position = self.position(index)?;
+        self.children(self.parent(index)?).get(position + 1).copied()
+    }
+    
+    /// The node's parent, its parent's parent and so on up to the root
+    pub fn ancestors(&self, index: NodeIndex) -> impl Iterator<Item = NodeIndex> + '_ {
+        std::iter::successors(self.parent(index), |&ancestor| self.parent(ancestor))
+    }
+    
+    /// The node and everything below it, in document order
+    pub fn descendants(&self, index: NodeIndex) -> Vec<NodeIndex> {
+        let mut order = Vec::new();
+        let mut stack = vec![index];
+        while let Some(next) = stack.pop() {
+            order.push(next);
+            stack.extend(self.children(next).iter().rev());
+        }
+        order
+    }
+    
+    pub fn depth(&self, index: NodeIndex) -> usize {
+        self.ancestors(index).count()
+    }
+    
+    /// The child indices leading from the root to the node, as `UIRNode::node_at` takes them
+    pub fn path(&self, index: NodeIndex) -> NodePath {
+        let mut path = Vec::new();
+        let mut current = index;
+        while let Some(position) = self.position(current) {
+            path.push(position);
+            current = self.parent(current).unwrap_or(current);
+        }
+        path.reverse();
+        NodePath(path)
+    }
+    
+    /// The attached node with id `id`
+    pub fn find(&self, id: &str) -> Option<NodeIndex> {
+        self.by_id.get(id).copied()
+    }
+    
+    /// Move `node` and its descendants into the arena as the child of `parent` at `position`
+    pub fn insert_child(&mut self, parent: NodeIndex, position: usize, node: UIRNode) -> NodeIndex {
+        let index = self.push(node, Some(parent));
+        self.slots[parent.0].children.insert(position, index);
+        index
+    }
+    
+    pub fn append_child(&mut self, parent: NodeIndex, node: UIRNode) -> NodeIndex {
+        let position = self.children(parent).len();
+        self.insert_child(parent, position, node)
+    }
+    
+    /// Unlink the node and its descendants from the tree; their slots stay in the arena, so
+    /// other indices remain valid, but `find` and `to_node` no longer see them
+    pub fn detach(&mut self, index: NodeIndex) {
+        if let Some(parent) = self.slots[index.0].parent.take() {
+            self.slots[parent.0].children.retain(|&child| child != index);
+        }
+        for detached in self.descendants(index) {
+            let id = &self.slots[detached.0].node.id;
+            if self.by_id.get(id) == Some(&detached) {
+                self.by_id.remove(id);
+            }
+        }
+    }
+    
+    fn take(&mut self, index: NodeIndex) -> UIRNode {
+        let mut node = std::mem::replace(&mut self.slots[index.0].node, UIRNode::new(String::new(), NodeType::Module));
+        let children = std::mem::take(&mut self.slots[index.0].children);
+        node.children = children.into_iter().map(|child| self.take(child)).collect();
+        node
+    }
+    
+    fn push(&mut self, mut node: UIRNode, parent: Option<NodeIndex>) -> NodeIndex {
+        let index = NodeIndex(self.slots.len());
+        let children = std::mem::take(&mut node.children);
+        self.by_id.entry(node.id.clone()).or_insert(index);
+        self.slots.push(Slot { node, parent, children: Vec::new() });
+        for child in children {
+            let child = self.push(child, Some(index));
+            self.slots[index.0].children.push(child);
+        }
+        index
+    }
+}
+
+impl Index<NodeIndex> for UIRTree {
+    type Output = UIRNode;
+    
+    fn index(&self, index: NodeIndex) -> &UIRNode {
+        &self.slots[index.0].node
+    }
+}
+
+impl IndexMut<NodeIndex> for UIRTree {
+    /// Changing a node's id through this does not update `find`
+    fn index_mut(&mut self, index: NodeIndex) -> &mut UIRNode {
+        &mut self.slots[index.0].node
+    }
+}
+
+impl From<UIRNode> for UIRTree {
+    fn from(root: UIRNode) -> Self {
+        Self::new(root)
+    }
+}
+
+impl From<UIRTree> for UIRNode {
+    fn from(tree: UIRTree) -> Self {
+        tree.into_node()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::UIRBuilder;
+    
+    fn sample(b: &UIRBuilder) -> UIRNode {
+        b.module("app").body([
+            b.function("first").body([b.call("log", [b.identifier("x")])]),
+            b.function("second"),
+            b.function("third"),
+        ])
+    }
+    
+    fn names(tree: &UIRTree, indices: impl IntoIterator<Item = NodeIndex>) -> Vec<String> {
+        indices.into_iter().map(|index| tree[index].name.clone().unwrap_or_default()).collect()
+    }
+    
+    #[test]
+    fn test_links_between_parents_siblings_and_ancestors() {
+        let node = sample(&UIRBuilder::new());
+        let tree = UIRTree::new(node.clone());
+        let x = tree.find(&node.children[0].children[0].children[0].id).unwrap();
+        let second = tree.children(tree.root())[1];
+        
+        assert_eq!(tree.node_count(), 6);
+        assert_eq!(names(&tree, tree.ancestors(x)), ["log", "first", "app"]);
+        assert_eq!(tree.depth(x), 3);
+        assert_eq!(tree.path(x), NodePath(vec![0, 0, 0]));
+        assert_eq!(node.node_at(&tree.path(x)).unwrap().id, tree[x].id);
+        assert_eq!(names(&tree, tree.previous_sibling(second)), ["first"]);
+        assert_eq!(names(&tree, tree.next_sibling(second)), ["third"]);
+        assert_eq!(tree.next_sibling(tree.children(tree.root())[2]), None);
+        assert_eq!(tree.position(tree.root()), None);
+        assert_eq!(names(&tree, tree.descendants(tree.root())), ["app", "first", "log", "x", "second", "third"]);
+        assert!(tree.to_node().diff(&node).is_empty());
+        assert!(UIRNode::from(tree).diff(&node).is_empty());
+    }
+    
+    #[test]
+    fn test_inserted_and_detached_nodes() {
+        let b = UIRBuilder::new();
+        let mut tree = UIRTree::new(sample(&b));
+        let root = tree.root();
+        let first = tree.children(root)[0];
+        let extra = tree.insert_child(root, 1, b.function("extra").body([b.call("tick", [])]));
+        let tick_id = tree[tree.children(extra)[0]].id.clone();
+        
+        assert_eq!(names(&tree, tree.children(root).to_vec()), ["first", "extra", "second", "third"]);
+        assert_eq!(tree.parent(extra), Some(root));
+        assert!(tree.find(&tick_id).is_some());
+        
+        tree.detach(first);
+        let first_id = tree[first].id.clone();
+        assert_eq!(tree.parent(first), None);
+        assert_eq!(tree.find(&first_id), None);
+        assert_eq!(tree.node_count(), 8);
+        assert_eq!(tree[first].name.as_deref(), Some("first"));
+        assert_eq!(names(&tree, tree.children(root).to_vec()), ["extra", "second", "third"]);
+        
+        tree.append_child(first, b.function("later"));
+        let rebuilt = tree.into_node();
+        let names: Vec<_> = rebuilt.children.iter().filter_map(|c| c.name.as_deref()).collect();
+        assert_eq!(names, ["extra", "second", "third"]);
+        assert_eq!(rebuilt.validate(), Ok(()));
+    }
+}
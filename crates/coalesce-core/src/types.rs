@@ -1,5 +1,6 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use crate::visit::{Walk, walk_mut};
 
 /// Universal Intermediate Representation Node
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,9 +11,34 @@ pub struct UIRNode {
     pub children: Vec<UIRNode>,
     pub metadata: Metadata,
     pub source_location: Option<SourceLocation>,
+    /// Set on arithmetic, comparison and logical expressions, and on compound assignments such
+    /// as `n -= 1`, where it is the operation applied to the target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator: Option<Operator>,
+    /// Set on literal expressions whose value the parser could read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub literal: Option<LiteralValue>,
+    /// Set on declarations whose access level the source states or implies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<Modifier>,
+    /// Set on functions, methods and lambdas declared `async` (or `suspend`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_async: bool,
+    /// Decorators, attributes and annotations on a declaration, in source order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<Attribute>,
+    /// Set on parameters and fields whose declaration says how they hold their value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ownership: Option<Ownership>,
+    /// The doc comment or docstring documenting a declaration, as plain text without its
+    /// delimiters or markup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum NodeType {
     Module,
     Function,
@@ -20,21 +46,116 @@ pub enum NodeType {
     Interface,
     Variable,
     Constant,
+    /// A field of a struct or class, with its declared type and the value it defaults to
+    Field { field_type: Option<String>, default_value: Option<String> },
+    /// A property read and written through accessors, as in C# `{ get; set; }` and VB
+    /// `Property`. Accessor bodies are `Function` children tagged `getter` and `setter`; an
+    /// auto-implemented property has none, and `has_getter`/`has_setter` say which it offers
+    Property { property_type: Option<String>, default_value: Option<String>, has_getter: bool, has_setter: bool },
+    /// An enumeration and its variants, which in Rust and similar languages carry data
+    Enum { variants: Vec<EnumVariant> },
+    /// An anonymous function: a lambda, arrow function or closure, with the names it
+    /// captures from enclosing functions in order of first use
+    Closure { captures: Vec<String>, capture_mode: CaptureMode },
     ControlFlow(ControlFlowType),
     Expression(ExpressionType),
     Statement(StatementType),
+    /// A source comment without its delimiters, attached by id to the declaration it precedes
+    /// or trails on the same line
+    Comment { style: CommentStyle, text: String, attached_to: Option<String> },
+    /// An import, `using`, `use` or `#include` of the module at `path`, bound to `alias` when
+    /// renamed; `symbols` lists the names brought in from it, empty when only the module is
+    Import { path: String, alias: Option<String>, symbols: Vec<ImportedSymbol> },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommentStyle {
+    Line,
+    Block,
+    Doc, // `///`, `/** */`, `//!`: documentation the target should keep as its own doc comments
+}
+
+/// A name brought in by an import, such as `d` and `e` in Python's `from m import d as e`;
+/// `*` for a wildcard import
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportedSymbol {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+impl ImportedSymbol {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), alias: None }
+    }
+    
+    /// Read `name` or `name as alias`, the form Python, Rust and JavaScript share
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        match text.split_once(" as ") {
+            Some((name, alias)) => Self { name: name.trim().to_string(), alias: Some(alias.trim().to_string()) },
+            None => Self::new(text),
+        }
+    }
+}
+
+/// A variant of an enum: a plain name, one with an explicit `value` (C `Red = 1`), or one
+/// carrying `fields` (Rust `Move { x: i32 }` or `Write(String)`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnumVariant {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<VariantField>,
+}
+
+/// Part of a variant's payload; unnamed for tuple variants
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariantField {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub field_type: String,
+}
+
+impl EnumVariant {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), value: None, fields: Vec::new() }
+    }
+}
+
+/// How a parameter or field holds its value, which targets with manual or checked memory
+/// management need in order to pass it on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Ownership {
+    ByValue, // A copy: C `int n`, Rust `i32`, Go `Point`
+    Owned, // Moved in, so the holder releases it: Rust `String`, C++ `T&&` and `std::unique_ptr<T>`
+    Pointer { mutable: bool }, // C `char *` may change what it points at, `const char *` may not
+    Reference { mutable: bool }, // Rust `&mut T` and `&T`, C++ `T&` and `const T&`, C# `ref` and `in`
+}
+
+/// How a closure holds the variables it captures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaptureMode {
+    ByValue, // Copies or moves: Rust `move`, C++ `[=]`, immutable languages
+    ByReference, // Shares the enclosing variables: C++ `[&]`, Rust without `move`, garbage-collected languages
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ControlFlowType {
     Conditional,
     Loop(LoopType),
     Switch,
-    Try,
+    Try, // The protected statements, then its Catch and Finally clauses
+    /// A handler for the listed exception types (any exception when empty), optionally
+    /// binding the caught exception to a variable
+    Catch { exception_types: Vec<String>, binding: Option<String> },
+    Finally,
     Goto, // For legacy pattern preservation
+    Concurrent, // Spawned task: goroutines, threads, async tasks
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LoopType {
     For,
     While,
@@ -42,7 +163,7 @@ pub enum LoopType {
     ForEach,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ExpressionType {
     Literal,
     Variable,
@@ -51,15 +172,722 @@ pub enum ExpressionType {
     Comparison,
     Logical,
     Assignment,
+    Await, // The awaited expression is the only child
+    Yield, // The yielded value, if any, is the only child; tagged `yield_from` for delegation
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Operator of an arithmetic, comparison or logical expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Operator {
+    // Arithmetic
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    IntegerDivide,
+    Modulo,
+    Power,
+    Concat,
+    Negate,
+    Plus,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    ShiftLeft,
+    ShiftRight,
+    UnsignedShiftRight,
+    // Comparison
+    Equal,
+    NotEqual,
+    StrictEqual, // No coercion: JavaScript `===`, Erlang `=:=`
+    StrictNotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Is,
+    IsNot,
+    In,
+    NotIn,
+    InstanceOf,
+    // Logical
+    And,
+    Or,
+    Not,
+    Xor,
+    Coalesce, // `??`, `?:`
+    // JavaScript's keyword operators
+    TypeOf,
+    Delete,
+    Void,
+}
+
+impl Operator {
+    /// Read a binary operator as spelled in `language`
+    pub fn binary(symbol: &str, language: &Language) -> Option<Self> {
+        let symbol = symbol.trim();
+        let keyword = symbol.to_ascii_lowercase();
+        let keyword = keyword.split_whitespace().collect::<Vec<_>>().join(" ");
+        
+        // Spellings whose meaning depends on the language
+        let specific = match (language, symbol) {
+            (Language::R | Language::VisualBasic, "^") => Some(Self::Power),
+            (Language::FSharp, "^") => Some(Self::Concat),
+            (Language::FSharp, "&") => Some(Self::And),
+            (Language::R, "&") => Some(Self::And),
+            (Language::R, "|") => Some(Self::Or),
+            (Language::R, "%%") => Some(Self::Modulo),
+            (Language::R, "%/%") => Some(Self::IntegerDivide),
+            (Language::R, "%in%") => Some(Self::In),
+            (Language::VisualBasic, "&") => Some(Self::Concat),
+            (Language::Abap, "&&" | "&") => Some(Self::Concat),
+            (Language::VisualBasic, "\\") => Some(Self::IntegerDivide),
+            (Language::Elixir, "<>") => Some(Self::Concat),
+            (Language::Erlang | Language::Elixir, "++") => Some(Self::Concat),
+            (Language::Erlang | Language::Fortran, "/=") => Some(Self::NotEqual),
+            (Language::Erlang, "=<") => Some(Self::LessEqual),
+            (Language::Erlang, "=:=") => Some(Self::StrictEqual),
+            (Language::Erlang, "=/=") => Some(Self::StrictNotEqual),
+            (Language::FSharp, "&&&") => Some(Self::BitAnd),
+            (Language::FSharp, "|||") => Some(Self::BitOr),
+            (Language::FSharp, "^^^") => Some(Self::BitXor),
+            (Language::FSharp, "<<<") => Some(Self::ShiftLeft),
+            (Language::FSharp, ">>>") => Some(Self::ShiftRight),
+            (Language::VisualBasic | Language::Cobol | Language::Abap | Language::Rpg | Language::FSharp | Language::Bash, "=") => Some(Self::Equal),
+            (Language::VisualBasic | Language::Cobol | Language::Abap | Language::Rpg | Language::FSharp, "<>") => Some(Self::NotEqual),
+            (Language::Bash, "-eq") => Some(Self::Equal),
+            (Language::Bash, "-ne") => Some(Self::NotEqual),
+            (Language::Bash, "-lt") => Some(Self::Less),
+            (Language::Bash, "-le") => Some(Self::LessEqual),
+            (Language::Bash, "-gt") => Some(Self::Greater),
+            (Language::Bash, "-ge") => Some(Self::GreaterEqual),
+            (Language::Bash, "-a") => Some(Self::And),
+            (Language::Bash, "-o") => Some(Self::Or),
+            _ => None,
+        };
+        if specific.is_some() {
+            return specific;
+        }
+        
+        Some(match symbol {
+            "+" => Self::Add,
+            "-" => Self::Subtract,
+            "*" => Self::Multiply,
+            "/" => Self::Divide,
+            "//" => Self::IntegerDivide,
+            "%" => Self::Modulo,
+            "**" => Self::Power,
+            "&" => Self::BitAnd,
+            "|" => Self::BitOr,
+            "^" => Self::BitXor,
+            "<<" => Self::ShiftLeft,
+            ">>" => Self::ShiftRight,
+            ">>>" => Self::UnsignedShiftRight,
+            "==" => Self::Equal,
+            "!=" => Self::NotEqual,
+            "===" => Self::StrictEqual,
+            "!==" => Self::StrictNotEqual,
+            "<" => Self::Less,
+            "<=" => Self::LessEqual,
+            ">" => Self::Greater,
+            ">=" => Self::GreaterEqual,
+            "!in" => Self::NotIn,
+            "!is" => Self::IsNot,
+            "&&" => Self::And,
+            "||" => Self::Or,
+            "??" | "?:" => Self::Coalesce,
+            _ => match keyword.as_str() {
+                "and" | "andalso" => Self::And,
+                "or" | "orelse" => Self::Or,
+                "xor" => Self::Xor,
+                "is" => Self::Is,
+                "is not" | "isnot" => Self::IsNot,
+                "in" => Self::In,
+                "not in" => Self::NotIn,
+                "instanceof" => Self::InstanceOf,
+                "mod" | "rem" => Self::Modulo,
+                "div" => Self::IntegerDivide,
+                "eq" | "equal" | "equal to" => Self::Equal,
+                "ne" | "not equal" | "not equal to" => Self::NotEqual,
+                "lt" | "less" | "less than" => Self::Less,
+                "le" | "less or equal" | "less than or equal to" => Self::LessEqual,
+                "gt" | "greater" | "greater than" => Self::Greater,
+                "ge" | "greater or equal" | "greater than or equal to" => Self::GreaterEqual,
+                "band" => Self::BitAnd,
+                "bor" => Self::BitOr,
+                "bxor" => Self::BitXor,
+                "bsl" => Self::ShiftLeft,
+                "bsr" => Self::ShiftRight,
+                _ => return None,
+            },
+        })
+    }
+    
+    /// Read a prefix operator as spelled in `language`
+    pub fn unary(symbol: &str, language: &Language) -> Option<Self> {
+        match (language, symbol.trim()) {
+            (Language::Erlang, "bnot") => Some(Self::BitNot),
+            (Language::FSharp, "~~~") => Some(Self::BitNot),
+            (Language::FSharp, "-.") => Some(Self::Negate),
+            (_, "-") => Some(Self::Negate),
+            (_, "+") => Some(Self::Plus),
+            (_, "~") => Some(Self::BitNot),
+            (_, "!") => Some(Self::Not),
+            (_, keyword) if keyword.eq_ignore_ascii_case("not") => Some(Self::Not),
+            (Language::JavaScript | Language::TypeScript, "typeof") => Some(Self::TypeOf),
+            (Language::JavaScript | Language::TypeScript, "delete") => Some(Self::Delete),
+            (Language::JavaScript | Language::TypeScript, "void") => Some(Self::Void),
+            _ => None,
+        }
+    }
+    
+    /// C-family spelling, or the keyword where C has none
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Add | Self::Plus => "+",
+            Self::Subtract | Self::Negate => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+            Self::IntegerDivide => "//",
+            Self::Modulo => "%",
+            Self::Power => "**",
+            Self::Concat => "++",
+            Self::BitAnd => "&",
+            Self::BitOr => "|",
+            Self::BitXor => "^",
+            Self::BitNot => "~",
+            Self::ShiftLeft => "<<",
+            Self::ShiftRight => ">>",
+            Self::UnsignedShiftRight => ">>>",
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::StrictEqual => "===",
+            Self::StrictNotEqual => "!==",
+            Self::Less => "<",
+            Self::LessEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterEqual => ">=",
+            Self::Is => "is",
+            Self::IsNot => "is not",
+            Self::In => "in",
+            Self::NotIn => "not in",
+            Self::InstanceOf => "instanceof",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::Not => "!",
+            Self::Xor => "xor",
+            Self::Coalesce => "??",
+            Self::TypeOf => "typeof",
+            Self::Delete => "delete",
+            Self::Void => "void",
+        }
+    }
+    
+    pub fn is_unary(&self) -> bool {
+        matches!(self, Self::Negate | Self::Plus | Self::BitNot | Self::Not | Self::TypeOf | Self::Delete | Self::Void)
+    }
+    
+    /// The expression kind this operator forms
+    pub fn expression_type(&self) -> ExpressionType {
+        match self {
+            Self::Equal | Self::NotEqual | Self::StrictEqual | Self::StrictNotEqual | Self::Less | Self::LessEqual
+                | Self::Greater | Self::GreaterEqual | Self::Is | Self::IsNot | Self::In | Self::NotIn | Self::InstanceOf => ExpressionType::Comparison,
+            Self::And | Self::Or | Self::Not | Self::Xor | Self::Coalesce => ExpressionType::Logical,
+            _ => ExpressionType::Arithmetic,
+        }
+    }
+    
+    /// Binding strength, loosest first. Languages disagree on where bitwise operators sit
+    /// relative to comparisons and arithmetic, so `is_bitwise` operands are grouped whatever
+    /// this says; unary operators bind tighter than every binary operator but `**`
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Coalesce => 1,
+            Self::Or | Self::Xor => 2,
+            Self::And => 3,
+            Self::Equal | Self::NotEqual | Self::StrictEqual | Self::StrictNotEqual | Self::Less | Self::LessEqual
+                | Self::Greater | Self::GreaterEqual | Self::Is | Self::IsNot | Self::In | Self::NotIn | Self::InstanceOf => 4,
+            Self::BitOr => 5,
+            Self::BitXor => 6,
+            Self::BitAnd => 7,
+            Self::ShiftLeft | Self::ShiftRight | Self::UnsignedShiftRight => 8,
+            Self::Add | Self::Subtract | Self::Concat => 9,
+            Self::Multiply | Self::Divide | Self::IntegerDivide | Self::Modulo => 10,
+            Self::Negate | Self::Plus | Self::BitNot | Self::Not | Self::TypeOf | Self::Delete | Self::Void => 11,
+            Self::Power => 12,
+        }
+    }
+    
+    pub fn is_bitwise(&self) -> bool {
+        matches!(
+            self,
+            Self::BitAnd | Self::BitOr | Self::BitXor | Self::BitNot | Self::ShiftLeft | Self::ShiftRight | Self::UnsignedShiftRight
+        )
+    }
+    
+    /// Whether `a op (b op c)` equals `(a op b) op c`, so the grouping can be left out
+    pub fn is_associative(&self) -> bool {
+        matches!(self, Self::Add | Self::Multiply | Self::Concat | Self::And | Self::Or | Self::BitAnd | Self::BitOr | Self::BitXor)
+    }
+    
+    /// `2 ** 3 ** 2` is `2 ** (3 ** 2)`
+    pub fn is_right_associative(&self) -> bool {
+        matches!(self, Self::Power | Self::Coalesce)
+    }
+}
+
+/// Value of a literal expression, with quotes, escapes, digit separators and suffixes resolved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LiteralValue {
+    Int(i64),
+    Float(f64),
+    String(String),
+    Bool(bool),
+    Char(char),
+    Null,
+}
+
+/// How a quoted literal escapes characters
+#[derive(Clone, Copy, PartialEq)]
+enum Escapes {
+    Backslash,
+    Doubled, // `""` inside a string: VB, COBOL, ABAP, RPG and verbatim strings
+    Raw,
+    Shell, // Bash double quotes, where only `\$`, `` \` ``, `\"` and `\\` are escapes
+}
+
+impl LiteralValue {
+    /// Read a literal as spelled in `language`
+    ///
+    /// Returns `None` for text that is not a plain literal: identifiers, interpolated or byte
+    /// strings, multi-character constants and integers that do not fit in an `i64`.
+    pub fn parse(text: &str, language: &Language) -> Option<Self> {
+        let text = text.trim();
+        let folded = text.to_ascii_lowercase();
+        let keyword = match language {
+            Language::VisualBasic | Language::Abap | Language::Cobol | Language::Rpg | Language::Fortran => folded.as_str(),
+            _ => text,
+        };
+        
+        let value = match (language, keyword) {
+            (Language::Python, "True") | (Language::R, "TRUE") => Some(Self::Bool(true)),
+            (Language::Python, "False") | (Language::R, "FALSE") => Some(Self::Bool(false)),
+            (Language::Python, "None") => Some(Self::Null),
+            (Language::Go | Language::Elixir, "nil") => Some(Self::Null),
+            (Language::C | Language::Cpp | Language::R, "NULL") | (Language::Cpp, "nullptr") => Some(Self::Null),
+            (Language::VisualBasic, "nothing") => Some(Self::Null),
+            (Language::Abap, "abap_true") | (Language::Rpg, "*on") | (Language::Fortran, ".true.") => Some(Self::Bool(true)),
+            (Language::Abap, "abap_false") | (Language::Rpg, "*off") | (Language::Fortran, ".false.") => Some(Self::Bool(false)),
+            (Language::Cobol, "zero" | "zeros" | "zeroes") | (Language::Rpg, "*zero" | "*zeros") => Some(Self::Int(0)),
+            (Language::Cobol, "space" | "spaces") | (Language::Abap, "space") | (Language::Rpg, "*blank" | "*blanks") => {
+                Some(Self::String(" ".to_string()))
+            }
+            (Language::Cobol, "null" | "nulls") | (Language::Rpg, "*null") => Some(Self::Null),
+            // Shell `true` and `false` are commands
+            (Language::Bash, _) => None,
+            (_, "true") => Some(Self::Bool(true)),
+            (_, "false") => Some(Self::Bool(false)),
+            (Language::JavaScript | Language::TypeScript | Language::Java | Language::Kotlin | Language::CSharp | Language::FSharp, "null") => Some(Self::Null),
+            _ => None,
+        };
+        value
+            .or_else(|| Self::parse_number(text, language))
+            .or_else(|| Self::parse_quoted(text, language))
+    }
+    
+    fn parse_number(text: &str, language: &Language) -> Option<Self> {
+        // COBOL and RPG literals may carry their sign
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) if matches!(language, Language::Cobol | Language::Rpg) => (true, rest),
+            _ => (false, text.strip_prefix('+').filter(|_| matches!(language, Language::Cobol | Language::Rpg)).unwrap_or(text)),
+        };
+        let vb_radix = *language == Language::VisualBasic && text.starts_with('&');
+        if !(text.starts_with(|c: char| c.is_ascii_digit()) || vb_radix || (text.starts_with('.') && text.len() > 1)) {
+            return None;
+        }
+        let digits: String = text.chars()
+            .filter(|&c| c != '_' && !(c == '\'' && *language == Language::Cpp))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        
+        let prefixed = |prefixes: [&str; 3]| {
+            prefixes.iter().zip([16, 8, 2]).find_map(|(prefix, radix)| digits.strip_prefix(prefix).map(|rest| (radix, rest)))
+        };
+        let radix = match language {
+            Language::Erlang => digits.split_once('#').and_then(|(base, rest)| Some((base.parse().ok()?, rest))),
+            Language::VisualBasic => prefixed(["&h", "&o", "&b"]),
+            _ => prefixed(["0x", "0o", "0b"]),
+        };
+        let radix = radix.or_else(|| {
+            // C-style octal such as `0755`
+            let octal = matches!(language, Language::C | Language::Cpp | Language::Go)
+                && digits.len() > 1 && digits.starts_with('0') && digits[1..].starts_with(|c: char| c.is_ascii_digit());
+            octal.then(|| (8, &digits[1..]))
+        });
+        
+        let value = match radix {
+            Some((radix, body)) => {
+                let body = Self::strip_rust_suffix(body, language).0;
+                let body = body.trim_end_matches(|c: char| !c.is_digit(radix));
+                Self::Int(i64::from_str_radix(body, radix).ok()?)
+            }
+            None => {
+                let (body, rust_float) = Self::strip_rust_suffix(&digits, language);
+                if *language == Language::Python && body.ends_with('j') {
+                    return None; // Complex
+                }
+                let number = body.trim_end_matches(|c: char| c.is_ascii_alphabetic() || "%&@!#".contains(c));
+                let suffix = &body[number.len()..];
+                // `1f`, `1d` and `1m` in C-family languages, `1R`, `1!`, `1#` and `1@` in VB
+                let float_suffix = suffix.contains(|c: char| "fdmr!#@".contains(c));
+                if number.contains('.') || number.contains('e') || float_suffix || rust_float {
+                    Self::Float(number.parse().ok()?)
+                } else {
+                    Self::Int(number.parse().ok()?)
+                }
+            }
+        };
+        Some(match (negative, value) {
+            (true, Self::Int(value)) => Self::Int(-value),
+            (true, Self::Float(value)) => Self::Float(-value),
+            (_, value) => value,
+        })
+    }
+    
+    /// Remove a Rust type suffix such as `u8` or `f64`; true when it names a float type
+    fn strip_rust_suffix<'a>(digits: &'a str, language: &Language) -> (&'a str, bool) {
+        if *language != Language::Rust {
+            return (digits, false);
+        }
+        for suffix in ["i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32", "f64"] {
+            if let Some(body) = digits.strip_suffix(suffix) {
+                return (body, suffix.starts_with('f'));
+            }
+        }
+        (digits, false)
+    }
+    
+    fn parse_quoted(text: &str, language: &Language) -> Option<Self> {
+        // Erlang `$a` and Elixir `?a`
+        let sigil = match language {
+            Language::Erlang => text.strip_prefix('$'),
+            Language::Elixir => text.strip_prefix('?'),
+            _ => None,
+        };
+        if let Some(character) = sigil {
+            return Self::single_char(Self::unescape(character, '\0', Escapes::Backslash)?);
+        }
+        // VB `"a"c`
+        if *language == Language::VisualBasic {
+            if let Some(quoted) = text.strip_suffix("\"c").or_else(|| text.strip_suffix("\"C")) {
+                return Self::single_char(Self::unescape(quoted.strip_prefix('"')?, '"', Escapes::Doubled)?);
+            }
+        }
+        
+        let start = text.find(['"', '\'', '`'])?;
+        let (prefix, quoted) = text.split_at(start);
+        let quote = quoted.chars().next()?;
+        let lower = prefix.to_ascii_lowercase();
+        let mut escapes = match language {
+            Language::VisualBasic | Language::Cobol | Language::Abap | Language::Rpg | Language::Fortran => Escapes::Doubled,
+            Language::Bash if quote == '\'' => Escapes::Raw,
+            Language::Bash => Escapes::Shell,
+            Language::Go if quote == '`' => Escapes::Raw,
+            _ => Escapes::Backslash,
+        };
+        let mut quoted = quoted;
+        // The character that may not appear unescaped inside the body
+        let mut closing = quote;
+        match (language, lower.as_str()) {
+            (_, "") => {}
+            (Language::Python, prefix) if prefix.chars().all(|c| "ru".contains(c)) => {
+                if prefix.contains('r') {
+                    escapes = Escapes::Raw;
+                }
+            }
+            (Language::Rust, prefix) if prefix.starts_with('r') && prefix[1..].chars().all(|c| c == '#') => {
+                let hashes = &prefix[1..];
+                quoted = quoted.strip_suffix(hashes)?;
+                escapes = Escapes::Raw;
+                if !hashes.is_empty() {
+                    closing = '\0';
+                }
+            }
+            (Language::CSharp | Language::FSharp, "@") => escapes = Escapes::Doubled,
+            (Language::C | Language::Cpp, "l" | "u" | "u8") => {}
+            (Language::C | Language::Cpp, prefix) if prefix.ends_with('r') => {
+                // `R"delim(...)delim"`
+                let body = quoted.strip_prefix('"')?.strip_suffix('"')?;
+                let (delimiter, rest) = body.split_once('(')?;
+                return Some(Self::String(rest.strip_suffix(delimiter)?.strip_suffix(')')?.to_string()));
+            }
+            (Language::Bash, "$") if quote == '\'' => escapes = Escapes::Backslash,
+            // Interpolated, byte and other prefixed strings
+            _ => return None,
+        }
+        
+        // Triple-quoted strings
+        if matches!(language, Language::Python | Language::Kotlin | Language::FSharp) && quoted.len() >= 6 {
+            let triple = quoted.get(..3).filter(|triple| matches!(*triple, "\"\"\"" | "'''"));
+            if triple.is_some_and(|triple| quoted.ends_with(triple)) {
+                if *language != Language::Python {
+                    escapes = Escapes::Raw;
+                }
+                let body = &quoted[3..quoted.len() - 3];
+                if Self::interpolates(body, language, quote, prefix) {
+                    return None;
+                }
+                let value = if escapes == Escapes::Raw { body.to_string() } else { Self::unescape(body, '\0', escapes)? };
+                return Some(Self::String(value));
+            }
+        }
+        
+        let body = quoted.strip_prefix(quote)?.strip_suffix(quote)?;
+        if Self::interpolates(body, language, quote, prefix) {
+            return None;
+        }
+        let value = Self::unescape(body, closing, escapes)?;
+        match (language, quote) {
+            (Language::C | Language::Cpp | Language::CSharp | Language::Java | Language::Rust | Language::Go
+                | Language::Kotlin | Language::FSharp, '\'') => Self::single_char(value),
+            // Single-quoted Erlang names are atoms
+            (Language::Erlang, '\'') => None,
+            _ => Some(Self::String(value)),
+        }
+    }
+    
+    fn single_char(value: String) -> Option<Self> {
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(Self::Char(c)),
+            _ => None,
+        }
+    }
+    
+    /// Whether a string body substitutes expressions into its text
+    fn interpolates(body: &str, language: &Language, quote: char, prefix: &str) -> bool {
+        let dollar = |body: &str| body.match_indices('$').any(|(i, _)| {
+            let escaped = body[..i].ends_with('\\');
+            !escaped && body[i + 1..].starts_with(|c: char| c == '{' || c == '_' || c.is_alphabetic())
+        });
+        match language {
+            Language::JavaScript | Language::TypeScript => quote == '`' && body.contains("${"),
+            Language::Kotlin => dollar(body),
+            Language::Elixir => quote == '"' && body.contains("#{"),
+            Language::Bash => quote == '"' && prefix.is_empty() && (dollar(body) || body.contains('`') || body.contains("$(")),
+            _ => false,
+        }
+    }
+    
+    /// Resolve the escapes of a string body, or `None` if an unescaped `quote` ends it early
+    fn unescape(body: &str, quote: char, escapes: Escapes) -> Option<String> {
+        let mut value = String::with_capacity(body.len());
+        let mut chars = body.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, escapes) {
+                (c, Escapes::Doubled) if c == quote => {
+                    chars.next_if_eq(&quote)?;
+                    value.push(quote);
+                }
+                (c, _) if c == quote => return None,
+                ('\\', Escapes::Shell) => match chars.peek() {
+                    Some(&next) if "$`\"\\".contains(next) => {
+                        value.push(next);
+                        chars.next();
+                    }
+                    _ => value.push('\\'),
+                },
+                ('\\', Escapes::Backslash) => {
+                    let escape = chars.next()?;
+                    let code = |chars: &mut std::iter::Peekable<std::str::Chars>, radix: u32, max: usize| {
+                        let mut digits = String::new();
+                        while digits.len() < max {
+                            match chars.next_if(|d| d.is_digit(radix)) {
+                                Some(d) => digits.push(d),
+                                None => break,
+                            }
+                        }
+                        u32::from_str_radix(&digits, radix).ok().and_then(char::from_u32)
+                    };
+                    let resolved = match escape {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        'a' => '\u{07}',
+                        'b' => '\u{08}',
+                        'f' => '\u{0c}',
+                        'v' => '\u{0b}',
+                        'e' => '\u{1b}',
+                        '0'..='7' => {
+                            let mut digits = escape.to_string();
+                            while digits.len() < 3 {
+                                match chars.next_if(|d| d.is_digit(8)) {
+                                    Some(d) => digits.push(d),
+                                    None => break,
+                                }
+                            }
+                            char::from_u32(u32::from_str_radix(&digits, 8).ok()?)?
+                        }
+                        'x' => code(&mut chars, 16, 2)?,
+                        'u' if chars.next_if_eq(&'{').is_some() => {
+                            let resolved = code(&mut chars, 16, 6)?;
+                            chars.next_if_eq(&'}')?;
+                            resolved
+                        }
+                        'u' => code(&mut chars, 16, 4)?,
+                        'U' => code(&mut chars, 16, 8)?,
+                        // A line continuation
+                        '\n' => continue,
+                        '\\' | '\'' | '"' | '`' | '$' | '?' => escape,
+                        other => {
+                            value.push('\\');
+                            other
+                        }
+                    };
+                    value.push(resolved);
+                }
+                (c, _) => value.push(c),
+            }
+        }
+        Some(value)
+    }
+}
+
+/// Access level of a declaration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Visibility {
+    Public,
+    Protected,
+    Internal, // The assembly or module: C# `internal`, VB `Friend`
+    ProtectedInternal,
+    PrivateProtected,
+    Private,
+}
+
+impl Visibility {
+    /// Read an access keyword as spelled in `language`
+    pub fn from_keyword(keyword: &str, language: &Language) -> Option<Self> {
+        match (language, keyword.to_ascii_lowercase().as_str()) {
+            (_, "public") => Some(Self::Public),
+            (_, "protected") => Some(Self::Protected),
+            (_, "private") => Some(Self::Private),
+            (Language::VisualBasic, "friend") | (_, "internal") => Some(Self::Internal),
+            _ => None,
+        }
+    }
+    
+    /// The access level the keywords of one declaration state together, e.g. C# `protected internal`
+    pub fn from_keywords<'a>(keywords: impl IntoIterator<Item = &'a str>, language: &Language) -> Option<Self> {
+        let levels: Vec<Self> = keywords.into_iter().filter_map(|k| Self::from_keyword(k, language)).collect();
+        let has = |level| levels.contains(&level);
+        match levels.as_slice() {
+            [] => None,
+            _ if has(Self::Protected) && has(Self::Internal) => Some(Self::ProtectedInternal),
+            _ if has(Self::Private) && has(Self::Protected) => Some(Self::PrivateProtected),
+            [level, ..] => Some(*level),
+        }
+    }
+}
+
+/// Declaration modifier other than the access level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Modifier {
+    Static,
+    Abstract,
+    Const,
+    ReadOnly,
+    Virtual,
+    Override,
+    Sealed, // Cannot be inherited from or overridden: C# `sealed`, C++ `final`
+}
+
+impl Modifier {
+    /// Read a modifier keyword as spelled in `language`
+    pub fn from_keyword(keyword: &str, language: &Language) -> Option<Self> {
+        match (language, keyword.to_ascii_lowercase().as_str()) {
+            (Language::VisualBasic, "shared") | (_, "static") => Some(Self::Static),
+            (Language::VisualBasic, "mustinherit" | "mustoverride") | (_, "abstract") => Some(Self::Abstract),
+            (Language::Cpp, "constexpr") | (_, "const") => Some(Self::Const),
+            (_, "readonly") => Some(Self::ReadOnly),
+            (Language::VisualBasic, "overridable") | (_, "virtual") => Some(Self::Virtual),
+            (Language::VisualBasic, "overrides") | (_, "override") => Some(Self::Override),
+            (Language::VisualBasic, "notinheritable" | "notoverridable") | (Language::Cpp, "final") | (_, "sealed") => Some(Self::Sealed),
+            _ => None,
+        }
+    }
+}
+
+/// A decorator, attribute or annotation: Python's `@app.route("/")`, C#'s `[HttpGet]`, Java's
+/// `@Override`, Rust's `#[derive(Debug)]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attribute {
+    /// The path as written, such as `app.route` or `System.Obsolete`
+    pub name: String,
+    /// The source text of each argument, `key=value` pairs included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<String>,
+}
+
+impl Attribute {
+    /// Read an attribute without its delimiters: `name`, `name(arg, ...)` or `name = value`
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        let Some(split) = text.find(['(', '=']) else {
+            return Self { name: text.to_string(), arguments: Vec::new() };
+        };
+        let name = text[..split].trim().to_string();
+        let rest = text[split + 1..].trim();
+        let arguments = if text[split..].starts_with('(') {
+            split_arguments(rest.strip_suffix(')').unwrap_or(rest))
+        } else {
+            vec![rest.to_string()]
+        };
+        Self { name, arguments }
+    }
+}
+
+/// Split an argument list at the commas outside brackets and string literals
+fn split_arguments(list: &str) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in list.chars() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                ',' if depth == 0 => {
+                    arguments.push(current.trim().to_string());
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            },
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        arguments.push(current.trim().to_string());
+    }
+    arguments
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StatementType {
     Expression,
     Return,
     Break,
     Continue,
     Throw,
+    Defer, // Runs when the enclosing function exits
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +896,7 @@ pub struct Metadata {
     pub semantic_tags: Vec<String>,
     pub complexity_score: Option<f32>,
     pub dependencies: Vec<String>,
+    #[serde(serialize_with = "serialize_sorted")]
     pub annotations: HashMap<String, serde_json::Value>,
     pub legacy_patterns: Vec<LegacyPattern>,
 }
@@ -80,7 +909,7 @@ pub struct LegacyPattern {
     pub preserve_exactly: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SourceLocation {
     pub file: String,
     pub start_line: u32,
@@ -89,6 +918,37 @@ pub struct SourceLocation {
     pub end_column: u32,
 }
 
+/// A replacement of `start_byte..old_end_byte` with `new_text`, in offsets of the text it applies to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub start_byte: usize,
+    pub old_end_byte: usize,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    /// The one edit turning `old` into `new`, replacing what lies between their common prefix
+    /// and suffix; `None` when they are equal
+    pub fn between(old: &str, new: &str) -> Option<Self> {
+        if old == new {
+            return None;
+        }
+        let prefix: usize = old.chars().zip(new.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        let suffix: usize = old[prefix..].chars().rev().zip(new[prefix..].chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        Some(Self {
+            start_byte: prefix,
+            old_end_byte: old.len() - suffix,
+            new_text: new[prefix..new.len() - suffix].to_string(),
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Language {
     JavaScript,
@@ -97,6 +957,14 @@ pub enum Language {
     Rust,
     Go,
     Java,
+    Kotlin,
+    Erlang,
+    Elixir,
+    Bash,
+    R,
+    Abap,
+    Rpg,
+    Assembly,
     CSharp,
     FSharp,
     VisualBasic,
@@ -105,6 +973,7 @@ pub enum Language {
     C,
     Cpp,
     // SoftEtherVPN is primarily C, so this is crucial
+    Custom(String), // Parsed by an external grammar loaded at runtime
 }
 
 impl UIRNode {
@@ -116,6 +985,14 @@ impl UIRNode {
             children: Vec::new(),
             metadata: Metadata::default(),
             source_location: None,
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         }
     }
     
@@ -128,6 +1005,73 @@ impl UIRNode {
         self.children.push(child);
         self
     }
+    
+    /// Replace parser ids, which encode source positions and text, with ids derived from each
+    /// node's path in the tree (`seed`, `seed.0`, `seed.0.2`, ...)
+    ///
+    /// Ids depend only on the tree's shape, not on line and column, so reformatting or editing
+    /// text within a node leaves them alone. Inserting, removing or reordering a sibling shifts
+    /// the ids of the siblings after it and of everything under them; `assign_content_ids`
+    /// keeps those.
+    pub fn assign_stable_ids(&mut self, seed: &str) {
+        let mut renamed = HashMap::new();
+        self.rename_ids(seed, &mut renamed);
+        self.retarget_references(&renamed);
+    }
+    
+    /// Give nodes whose parser id repeats an earlier one in document order a `#2`, `#3`, ...
+    /// suffix, as when nested nodes share a kind, a start and a text prefix
+    ///
+    /// The first node keeps its id, so comments attached to it stay attached.
+    pub fn make_ids_unique(&mut self) {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        walk_mut(self, &mut |node: &mut UIRNode| {
+            let count = seen.entry(node.id.clone()).or_insert(0);
+            *count += 1;
+            if *count > 1 {
+                let mut suffix = *count;
+                while seen.contains_key(&format!("{}#{}", node.id, suffix)) {
+                    suffix += 1;
+                }
+                node.id = format!("{}#{}", node.id, suffix);
+                seen.insert(node.id.clone(), 1);
+            }
+            Walk::Continue
+        });
+    }
+    
+    fn rename_ids(&mut self, seed: &str, renamed: &mut HashMap<String, String>) {
+        renamed.insert(std::mem::replace(&mut self.id, seed.to_string()), seed.to_string());
+        for (index, child) in self.children.iter_mut().enumerate() {
+            child.rename_ids(&format!("{}.{}", seed, index), renamed);
+        }
+    }
+    
+    /// Point comments and resolved identifiers at the new ids of the nodes they refer to
+    pub(crate) fn retarget_references(&mut self, renamed: &HashMap<String, String>) {
+        walk_mut(self, &mut |node: &mut UIRNode| {
+            if let NodeType::Comment { attached_to: Some(target), .. } = &mut node.node_type {
+                if let Some(id) = renamed.get(target) {
+                    *target = id.clone();
+                }
+            }
+            if let Some(serde_json::Value::String(target)) = node.metadata.annotations.get_mut("resolved_id") {
+                if let Some(id) = renamed.get(target) {
+                    *target = id.clone();
+                }
+            }
+            Walk::Continue
+        });
+    }
+}
+
+/// Serialize a map with its keys in sorted order, so output does not depend on hash seeds
+pub fn serialize_sorted<S, V>(map: &HashMap<String, V>, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    V: Serialize,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
 }
 
 impl Default for Metadata {
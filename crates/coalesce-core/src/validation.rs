@@ -0,0 +1,235 @@
+// Structural invariants of a UIR tree
+//
+// Generators and passes rely on a few shapes that the type system cannot express: ids name
+// one node each, a function's `Variable` children are its parameters and come before its body,
+// conditionals and `while` loops lead with their condition, `do-while` loops have one, and
+// comments are attached to nodes that exist. The arms of a switch, the handlers of a try and
+// the clauses of a multi-clause function are conditionals too, but a default arm, a `finally`
+// or a clause matching only on its parameters has no condition, nor does a loop tagged
+// `infinite_loop`. `UIRNode::validate` reports every node that breaks one.
+use crate::types::{ControlFlowType, LoopType, NodeType, UIRNode};
+use crate::visit::{Walk, walk};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// The invariant a node breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ValidationRule {
+    /// Another node earlier in the tree has the same id
+    DuplicateId,
+    /// A function or closure parameter follows a statement of its body
+    ParameterAfterBody,
+    /// A conditional or `while` loop does not lead with its condition, or a `do-while` loop
+    /// has none
+    MissingCondition,
+    /// A comment is attached to an id that no node in the tree has
+    DanglingAttachment,
+}
+
+/// A node that breaks `rule`, with a description naming what is wrong
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Violation {
+    pub node_id: String,
+    pub rule: ValidationRule,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} at {}: {}", self.rule, self.node_id, self.message)
+    }
+}
+
+impl UIRNode {
+    /// Check the tree's structural invariants, returning every violation found
+    pub fn validate(&self) -> Result<(), Vec<Violation>> {
+        let mut ids = HashSet::new();
+        let mut violations = Vec::new();
+        self.check(false, &mut ids, &mut violations);
+        self.check_attachments(&ids, &mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+    
+    /// Panic with the violations in debug builds; parsers and passes call this on the trees
+    /// they return, so a broken invariant fails tests where it starts. Release builds skip it.
+    pub fn debug_validate(&self) {
+        #[cfg(debug_assertions)]
+        if let Err(violations) = self.validate() {
+            let report: Vec<String> = violations.iter().map(|v| format!("  {}", v)).collect();
+            panic!("invalid UIR tree:\n{}", report.join("\n"));
+        }
+    }
+    
+    /// `arm` is set for the children of a switch, try or multi-clause function, whose
+    /// conditionals are its branches
+    fn check<'a>(&'a self, arm: bool, ids: &mut HashSet<&'a str>, violations: &mut Vec<Violation>) {
+        if !ids.insert(&self.id) {
+            violations.push(self.violation(ValidationRule::DuplicateId, format!("id `{}` is already taken", self.id)));
+        }
+        match &self.node_type {
+            NodeType::Function | NodeType::Closure { .. } => {
+                let first_statement = self.children.iter()
+                    .position(|c| !matches!(c.node_type, NodeType::Variable | NodeType::Comment { .. }));
+                if let Some(first_statement) = first_statement {
+                    for parameter in self.children[first_statement..].iter().filter(|c| c.node_type == NodeType::Variable) {
+                        violations.push(parameter.violation(
+                            ValidationRule::ParameterAfterBody,
+                            format!("parameter {} of {} follows the body", display_name(parameter), display_name(self)),
+                        ));
+                    }
+                }
+            }
+            NodeType::ControlFlow(ControlFlowType::Conditional) if !arm && !self.leads_with_condition() => {
+                violations.push(self.violation(ValidationRule::MissingCondition, "no condition expression".to_string()));
+            }
+            NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)) if !self.has_tag("infinite_loop") && !self.leads_with_condition() => {
+                violations.push(self.violation(ValidationRule::MissingCondition, "no condition expression".to_string()));
+            }
+            // The condition follows the body
+            NodeType::ControlFlow(ControlFlowType::Loop(LoopType::DoWhile))
+                if !self.children.iter().any(|c| matches!(c.node_type, NodeType::Expression(_))) =>
+            {
+                violations.push(self.violation(ValidationRule::MissingCondition, "no condition expression".to_string()));
+            }
+            _ => {}
+        }
+        let arms = matches!(self.node_type, NodeType::ControlFlow(ControlFlowType::Switch | ControlFlowType::Try))
+            || self.has_tag("multi_clause");
+        for child in &self.children {
+            child.check(arms, ids, violations);
+        }
+    }
+    
+    fn has_tag(&self, tag: &str) -> bool {
+        self.metadata.semantic_tags.iter().any(|t| t == tag)
+    }
+    
+    /// Whether the first child, comments aside, is an expression: the condition
+    fn leads_with_condition(&self) -> bool {
+        self.children.iter()
+            .find(|c| !matches!(c.node_type, NodeType::Comment { .. }))
+            .is_some_and(|c| matches!(c.node_type, NodeType::Expression(_)))
+    }
+    
+    fn check_attachments(&self, ids: &HashSet<&str>, violations: &mut Vec<Violation>) {
+        walk(self, &mut |node: &UIRNode| {
+            if let NodeType::Comment { attached_to: Some(target), .. } = &node.node_type {
+                if !ids.contains(target.as_str()) {
+                    violations.push(node.violation(ValidationRule::DanglingAttachment, format!("attached to missing node `{}`", target)));
+                }
+            }
+            Walk::Continue
+        });
+    }
+    
+    fn violation(&self, rule: ValidationRule, message: String) -> Violation {
+        Violation { node_id: self.id.clone(), rule, message }
+    }
+}
+
+fn display_name(uir: &UIRNode) -> String {
+    match &uir.name {
+        Some(name) => format!("`{}`", name),
+        None => "(unnamed)".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::UIRBuilder;
+    use crate::types::{CommentStyle, Operator, StatementType};
+    
+    fn comment(attached_to: &str) -> UIRNode {
+        let style = CommentStyle::Line;
+        UIRNode::new("comment_1".to_string(), NodeType::Comment { style, text: "note".to_string(), attached_to: Some(attached_to.to_string()) })
+    }
+    
+    fn rules(tree: &UIRNode) -> Vec<(ValidationRule, String)> {
+        tree.validate().unwrap_err().into_iter().map(|v| (v.rule, v.node_id)).collect()
+    }
+    
+    #[test]
+    fn test_built_trees_are_valid() {
+        let b = UIRBuilder::new();
+        let function = b.function("add").typed_param("a", "int").typed_param("b", "int").returns("int")
+            .body([b.return_value(b.binary(Operator::Add, b.identifier("a"), b.identifier("b")))]);
+        let target = function.id.clone();
+        let tree = b.module("math").body([comment(&target), function]);
+        assert_eq!(tree.validate(), Ok(()));
+        tree.debug_validate();
+        // Each builder counts on its own
+        assert_eq!(UIRBuilder::new().function("add").id, "function_1");
+    }
+    
+    #[test]
+    fn test_each_rule_is_reported() {
+        let b = UIRBuilder::new();
+        let mut late = b.function("f").param("a").body([b.return_value(b.identifier("a"))]);
+        late.children.push(b.variable("b"));
+        let late_id = late.children[2].id.clone();
+        let bare = UIRNode::new("if_1".to_string(), NodeType::ControlFlow(ControlFlowType::Conditional));
+        let twin = UIRNode::new("if_1".to_string(), NodeType::Variable);
+        let tree = b.module("m").body([late, bare, twin, comment("gone")]);
+        
+        assert_eq!(rules(&tree), [
+            (ValidationRule::ParameterAfterBody, late_id),
+            (ValidationRule::MissingCondition, "if_1".to_string()),
+            (ValidationRule::DuplicateId, "if_1".to_string()),
+            (ValidationRule::DanglingAttachment, "comment_1".to_string()),
+        ]);
+        let messages: Vec<String> = tree.validate().unwrap_err().iter().map(|v| v.message.clone()).collect();
+        assert_eq!(messages, [
+            "parameter `b` of `f` follows the body",
+            "no condition expression",
+            "id `if_1` is already taken",
+            "attached to missing node `gone`",
+        ]);
+    }
+    
+    #[test]
+    fn test_comments_before_the_body_do_not_end_the_parameters() {
+        let b = UIRBuilder::new();
+        let mut function = b.function("f").param("a");
+        function.children.push(comment("function_x"));
+        function.children.push(b.variable("b"));
+        let function_id = function.id.clone();
+        if let NodeType::Comment { attached_to, .. } = &mut function.children[1].node_type {
+            *attached_to = Some(function_id);
+        }
+        assert_eq!(function.validate(), Ok(()));
+    }
+    
+    #[test]
+    fn test_conditions_lead_except_in_arms_and_infinite_loops() {
+        let b = UIRBuilder::new();
+        let call = || b.call("g", [b.identifier("x")]);
+        let statement = || UIRNode::new("break_1".to_string(), NodeType::Statement(StatementType::Break));
+        let mut body_only = UIRNode::new("if_1".to_string(), NodeType::ControlFlow(ControlFlowType::Conditional));
+        body_only.children = vec![statement(), call()];
+        assert_eq!(rules(&body_only), [(ValidationRule::MissingCondition, "if_1".to_string())]);
+    
+        let mut switch = UIRNode::new("switch_1".to_string(), NodeType::ControlFlow(ControlFlowType::Switch));
+        switch.children.push(b.identifier("x"));
+        switch.children.push(UIRNode::new("default_1".to_string(), NodeType::ControlFlow(ControlFlowType::Conditional)));
+        let mut finally = UIRNode::new("finally_1".to_string(), NodeType::ControlFlow(ControlFlowType::Conditional));
+        finally.children.push(statement());
+        let try_block = UIRNode::new("try_1".to_string(), NodeType::ControlFlow(ControlFlowType::Try)).add_child(finally);
+        let repeat = UIRNode::new("repeat_1".to_string(), NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)))
+            .tagged("infinite_loop")
+            .add_child(UIRNode::new("break_2".to_string(), NodeType::Statement(StatementType::Break)));
+        assert_eq!(b.module("m").body([switch, try_block, repeat]).validate(), Ok(()));
+    }
+    
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "invalid UIR tree:\n  DuplicateId at x: id `x` is already taken")]
+    fn test_debug_validate_panics_with_the_violations() {
+        UIRNode::new("x".to_string(), NodeType::Module).add_child(UIRNode::new("x".to_string(), NodeType::Variable)).debug_validate();
+    }
+}
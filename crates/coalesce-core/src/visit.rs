@@ -0,0 +1,168 @@
+// Depth-first traversal of UIR trees
+//
+// A `Visitor` sees each node twice: `enter` before its children and `leave` after them. Both
+// return a `Walk` that lets the visitor skip a subtree or end the traversal, so searches stop
+// at their first match. `VisitorMut` is the same over `&mut UIRNode`, for passes that rewrite
+// the tree in place. Closures taking a node and returning a `Walk` are visitors that only
+// implement `enter`.
+use crate::types::UIRNode;
+
+/// How the traversal continues after a hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Walk {
+    /// Visit the node's children, then its next sibling
+    Continue,
+    /// Do not visit the node's children; returned from `leave`, the same as `Continue`
+    SkipChildren,
+    /// End the traversal without calling any further hooks
+    Stop,
+}
+
+pub trait Visitor<'a> {
+    /// Called before the node's children are visited
+    fn enter(&mut self, _node: &'a UIRNode) -> Walk {
+        Walk::Continue
+    }
+    
+    /// Called after the node's children are visited, or skipped
+    fn leave(&mut self, _node: &'a UIRNode) -> Walk {
+        Walk::Continue
+    }
+}
+
+pub trait VisitorMut {
+    /// Called before the node's children are visited, so changes to them are walked
+    fn enter(&mut self, _node: &mut UIRNode) -> Walk {
+        Walk::Continue
+    }
+    
+    /// Called after the node's children are visited, or skipped
+    fn leave(&mut self, _node: &mut UIRNode) -> Walk {
+        Walk::Continue
+    }
+}
+
+impl<'a, F: FnMut(&'a UIRNode) -> Walk> Visitor<'a> for F {
+    fn enter(&mut self, node: &'a UIRNode) -> Walk {
+        self(node)
+    }
+}
+
+impl<F: FnMut(&mut UIRNode) -> Walk> VisitorMut for F {
+    fn enter(&mut self, node: &mut UIRNode) -> Walk {
+        self(node)
+    }
+}
+
+/// Visit `node` and its descendants in document order; `Walk::Stop` if a hook ended the walk
+pub fn walk<'a, V: Visitor<'a> + ?Sized>(node: &'a UIRNode, visitor: &mut V) -> Walk {
+    match visitor.enter(node) {
+        Walk::Stop => return Walk::Stop,
+        Walk::SkipChildren => {}
+        Walk::Continue => {
+            for child in &node.children {
+                if walk(child, visitor) == Walk::Stop {
+                    return Walk::Stop;
+                }
+            }
+        }
+    }
+    match visitor.leave(node) {
+        Walk::Stop => Walk::Stop,
+        _ => Walk::Continue,
+    }
+}
+
+/// `walk` over a mutable tree
+pub fn walk_mut<V: VisitorMut + ?Sized>(node: &mut UIRNode, visitor: &mut V) -> Walk {
+    match visitor.enter(node) {
+        Walk::Stop => return Walk::Stop,
+        Walk::SkipChildren => {}
+        Walk::Continue => {
+            for child in &mut node.children {
+                if walk_mut(child, visitor) == Walk::Stop {
+                    return Walk::Stop;
+                }
+            }
+        }
+    }
+    match visitor.leave(node) {
+        Walk::Stop => Walk::Stop,
+        _ => Walk::Continue,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::UIRBuilder;
+    
+    fn sample() -> UIRNode {
+        let b = UIRBuilder::new();
+        b.module("app").body([
+            b.function("first").body([b.call("log", [b.identifier("x")])]),
+            b.function("second"),
+        ])
+    }
+    
+    /// Records each hook as `+name` on entering and `-name` on leaving
+    struct Trace {
+        events: Vec<String>,
+        skip: &'static str,
+        stop: &'static str,
+    }
+    
+    impl<'a> Visitor<'a> for Trace {
+        fn enter(&mut self, node: &'a UIRNode) -> Walk {
+            let name = node.name.as_deref().unwrap_or("?");
+            self.events.push(format!("+{}", name));
+            match name {
+                n if n == self.stop => Walk::Stop,
+                n if n == self.skip => Walk::SkipChildren,
+                _ => Walk::Continue,
+            }
+        }
+        
+        fn leave(&mut self, node: &'a UIRNode) -> Walk {
+            self.events.push(format!("-{}", node.name.as_deref().unwrap_or("?")));
+            Walk::Continue
+        }
+    }
+    
+    #[test]
+    fn test_hooks_run_in_document_order() {
+        let tree = sample();
+        let mut trace = Trace { events: Vec::new(), skip: "", stop: "" };
+        assert_eq!(walk(&tree, &mut trace), Walk::Continue);
+        assert_eq!(trace.events.join(" "), "+app +first +log +x -x -log -first +second -second -app");
+    }
+    
+    #[test]
+    fn test_skipped_children_are_left_and_stop_ends_the_walk() {
+        let tree = sample();
+        let mut trace = Trace { events: Vec::new(), skip: "first", stop: "" };
+        walk(&tree, &mut trace);
+        assert_eq!(trace.events.join(" "), "+app +first -first +second -second -app");
+        
+        let mut trace = Trace { events: Vec::new(), skip: "", stop: "log" };
+        assert_eq!(walk(&tree, &mut trace), Walk::Stop);
+        assert_eq!(trace.events.join(" "), "+app +first +log");
+    }
+    
+    #[test]
+    fn test_closures_rewrite_the_tree() {
+        let mut tree = sample();
+        walk_mut(&mut tree, &mut |node: &mut UIRNode| {
+            if let Some(name) = &mut node.name {
+                name.make_ascii_uppercase();
+            }
+            Walk::Continue
+        });
+        let mut names = Vec::new();
+        walk(&tree, &mut |node: &UIRNode| {
+            names.extend(node.name.clone());
+            Walk::Continue
+        });
+        assert_eq!(names, ["APP", "FIRST", "LOG", "X", "SECOND"]);
+    }
+}
@@ -0,0 +1,236 @@
+use coalesce_core::{Language, UIRNode};
+use crate::layout::indent;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Import statements a translated module needs
+///
+/// Library transformations record theirs in `required_imports` annotations; generators add
+/// the ones their own output relies on. Entries keep the target's syntax and are merged and
+/// deduplicated when rendered.
+#[derive(Debug, Clone, Default)]
+pub struct ImportSet {
+    entries: Vec<String>,
+}
+
+impl ImportSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Gather the `required_imports` annotations of every node in `uir`
+    pub fn collect(uir: &UIRNode) -> Self {
+        let mut imports = Self::new();
+        imports.extend_from(uir);
+        imports
+    }
+    
+    /// Add the `required_imports` of every node in `uir`
+    pub fn extend_from(&mut self, uir: &UIRNode) {
+        match uir.metadata.annotations.get("required_imports") {
+            // The transformer stores the list JSON-encoded
+            Some(serde_json::Value::String(encoded)) => {
+                match serde_json::from_str::<Vec<String>>(encoded) {
+                    Ok(list) => list.into_iter().for_each(|import| self.insert(import)),
+                    Err(_) => self.insert(encoded.clone()),
+                }
+            }
+            Some(serde_json::Value::Array(list)) => {
+                list.iter().filter_map(|import| import.as_str()).for_each(|import| self.insert(import));
+            }
+            _ => {}
+        }
+        for child in &uir.children {
+            self.extend_from(child);
+        }
+    }
+    
+    pub fn insert(&mut self, import: impl Into<String>) {
+        let import = import.into();
+        let import = import.trim();
+        if !import.is_empty() && !self.entries.iter().any(|existing| existing == import) {
+            self.entries.push(import.to_string());
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    /// The import block for `language`, followed by a blank line unless empty
+    pub fn render(&self, language: &Language) -> String {
+        let lines = self.lines(language);
+        if lines.is_empty() {
+            return String::new();
+        }
+        format!("{}\n\n", lines.join("\n"))
+    }
+    
+    /// One line per statement of the import block for `language`
+    pub fn lines(&self, language: &Language) -> Vec<String> {
+        match language {
+            Language::Python => self.python_lines(),
+            Language::Rust => self.rust_lines(),
+            Language::Go => self.go_lines(),
+            Language::C | Language::Cpp => self.c_lines(),
+            Language::JavaScript | Language::TypeScript => self.javascript_lines(),
+            _ => self.entries.clone(),
+        }
+    }
+    
+    /// `import` lines, then `from` imports merged per module, then any setup statements
+    fn python_lines(&self) -> Vec<String> {
+        let mut modules = BTreeSet::new();
+        let mut from_imports: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        let mut setup = Vec::new();
+        for entry in &self.entries {
+            if let Some(names) = entry.strip_prefix("import ") {
+                let names: Vec<&str> = names.split(',').map(str::trim).collect();
+                if names.iter().all(|name| is_python_alias(name)) {
+                    modules.extend(names);
+                }
+            } else if let Some((module, names)) = entry.strip_prefix("from ").and_then(|rest| rest.split_once(" import ")) {
+                let names: Vec<&str> = names.trim().trim_start_matches('(').trim_end_matches(')')
+                    .split(',').map(str::trim).filter(|name| !name.is_empty()).collect();
+                if is_python_path(module.trim().trim_start_matches('.')) && names.iter().all(|name| *name == "*" || is_python_alias(name)) {
+                    from_imports.entry(module.trim()).or_default().extend(names);
+                }
+            } else if entry.split_once(" = ").is_some_and(|(target, _)| is_identifier(target.trim())) {
+                // e.g. `Base = declarative_base()`, which must follow the imports it uses
+                setup.push(entry.clone());
+            }
+        }
+        
+        let mut lines: Vec<String> = modules.into_iter().map(|module| format!("import {}", module)).collect();
+        for (module, names) in from_imports {
+            let names: Vec<&str> = if names.contains("*") { vec!["*"] } else { names.into_iter().collect() };
+            lines.push(format!("from {} import {}", module, names.join(", ")));
+        }
+        lines.extend(setup);
+        lines
+    }
+    
+    fn rust_lines(&self) -> Vec<String> {
+        let lines: BTreeSet<String> = self.entries.iter()
+            .filter_map(|entry| {
+                let entry = entry.trim_end_matches(';').trim();
+                if let Some(name) = entry.strip_prefix("extern crate ") {
+                    return is_identifier(name).then(|| format!("{};", entry));
+                }
+                let path = entry.strip_prefix("use ").unwrap_or(entry);
+                let valid = path.contains("::")
+                    && path.chars().all(|c| c.is_alphanumeric() || "_:{}, *".contains(c));
+                valid.then(|| format!("use {};", path))
+            })
+            .collect();
+        lines.into_iter().collect()
+    }
+    
+    /// A single `import` or a parenthesized block, sorted by path as gofmt does
+    fn go_lines(&self) -> Vec<String> {
+        let mut specs: BTreeMap<String, String> = BTreeMap::new();
+        for entry in &self.entries {
+            // Statements quote their paths; bare paths come from generators
+            let (body, statement) = match entry.strip_prefix("import") {
+                Some(rest) if rest.starts_with([' ', '(']) => (rest.trim(), true),
+                Some(_) => continue,
+                None => (entry.as_str(), false),
+            };
+            let body = body.strip_prefix('(').and_then(|b| b.strip_suffix(')')).unwrap_or(body);
+            for spec in body.lines().map(str::trim).filter(|spec| !spec.is_empty()) {
+                let (alias, path) = match spec.rsplit_once(' ') {
+                    Some((alias, path)) => (Some(alias.trim()), path),
+                    None => (None, spec),
+                };
+                if statement && !(path.len() > 2 && path.starts_with('"') && path.ends_with('"')) {
+                    continue;
+                }
+                let path = path.trim_matches('"');
+                let valid_path = !path.is_empty() && path.chars().all(|c| c.is_alphanumeric() || "_./-".contains(c));
+                let valid_alias = alias.is_none_or(|alias| alias == "_" || alias == "." || is_identifier(alias));
+                if valid_path && valid_alias {
+                    let quoted = format!("\"{}\"", path);
+                    let spec = alias.map_or(quoted.clone(), |alias| format!("{} {}", alias, quoted));
+                    specs.entry(quoted).or_insert(spec);
+                }
+            }
+        }
+        match specs.len() {
+            0 => Vec::new(),
+            1 => specs.into_values().map(|spec| format!("import {}", spec)).collect(),
+            _ => std::iter::once("import (".to_string())
+                .chain(specs.into_values().map(|spec| indent(&spec, 1)))
+                .chain(std::iter::once(")".to_string()))
+                .collect(),
+        }
+    }
+    
+    /// `#include` lines in the order they were added, since headers may depend on each other
+    fn c_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        for entry in &self.entries {
+            let header = entry.strip_prefix("#include").map(str::trim).unwrap_or(entry);
+            let (open, name, close) = match (header.chars().next(), header.chars().last()) {
+                (Some('<'), Some('>')) | (Some('"'), Some('"')) if header.len() > 2 => {
+                    (&header[..1], &header[1..header.len() - 1], &header[header.len() - 1..])
+                }
+                _ => ("<", header, ">"),
+            };
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || "_./-+".contains(c)) {
+                continue;
+            }
+            let line = format!("#include {}{}{}", open, name, close);
+            if !lines.contains(&line) {
+                lines.push(line);
+            }
+        }
+        lines
+    }
+    
+    /// Named imports merged per module; default, namespace and side-effect imports kept as written
+    fn javascript_lines(&self) -> Vec<String> {
+        let mut named: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        let mut other = BTreeSet::new();
+        for entry in &self.entries {
+            let Some(rest) = entry.trim_end_matches(';').strip_prefix("import ") else { continue };
+            let (clause, module) = match rest.rsplit_once(" from ") {
+                Some((clause, module)) => (Some(clause.trim()), module.trim()),
+                None => (None, rest.trim()),
+            };
+            let quoted = module.len() > 2 && (module.starts_with('\'') && module.ends_with('\'') || module.starts_with('"') && module.ends_with('"'));
+            if !quoted {
+                continue;
+            }
+            match clause.and_then(|c| c.strip_prefix('{')).and_then(|c| c.strip_suffix('}')) {
+                Some(names) => named.entry(module).or_default()
+                    .extend(names.split(',').map(str::trim).filter(|name| !name.is_empty())),
+                None => {
+                    other.insert(format!("import {};", rest.trim()));
+                }
+            }
+        }
+        
+        let mut lines: Vec<String> = other.into_iter().collect();
+        for (module, names) in named {
+            lines.push(format!("import {{ {} }} from {};", names.into_iter().collect::<Vec<_>>().join(", "), module));
+        }
+        lines
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// A dotted module path such as `os.path`
+fn is_python_path(path: &str) -> bool {
+    path.split('.').all(is_identifier)
+}
+
+/// `name` or `name as alias`
+fn is_python_alias(name: &str) -> bool {
+    match name.split_once(" as ") {
+        Some((path, alias)) => is_python_path(path.trim()) && is_identifier(alias.trim()),
+        None => is_python_path(name),
+    }
+}
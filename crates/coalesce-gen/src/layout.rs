@@ -0,0 +1,140 @@
+// Layout of generated code
+//
+// Generators emit indentation, braces and argument lists through these helpers, which follow
+// the `GeneratorConfig` of the `generate_with_config` call in progress. Layout is decided where
+// the code is built, so only block braces move and only argument lists wrap; strings, comments
+// and object literals come out as the generator wrote them.
+//
+// Code a generator falls back on is delimited as it is emitted, while a
+// `generate_with_fallbacks` call is in progress, and the delimiters become line numbers there.
+use crate::FALLBACK_TODO;
+use coalesce_core::{BraceStyle, Generator, GeneratorConfig, Result, UIRNode};
+use std::cell::{Cell, RefCell};
+
+thread_local! {
+    static STYLE: RefCell<GeneratorConfig> = RefCell::new(GeneratorConfig::default());
+    static MARKING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Private-use characters around code a generator fell back on, which no source holds
+const FALLBACK_START: char = '\u{E000}';
+const FALLBACK_END: char = '\u{E001}';
+
+/// Restores the style that was in effect, even when generation panics
+struct StyleGuard(Option<GeneratorConfig>);
+
+impl Drop for StyleGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            STYLE.with(|style| style.replace(previous));
+        }
+    }
+}
+
+/// `generator`'s code for `uir` with `config`'s naming applied to the tree and its layout to
+/// the code; generators' `generate_with_config` calls this
+pub(crate) fn generate_styled(generator: &dyn Generator, uir: &UIRNode, config: &GeneratorConfig) -> Result<String> {
+    let renamed = config.apply_naming(uir);
+    let _guard = StyleGuard(Some(STYLE.with(|style| style.replace(config.clone()))));
+    generator.generate(&renamed)
+}
+
+/// Restores whether fallbacks were marked, even when generation panics
+struct MarkingGuard(bool);
+
+impl Drop for MarkingGuard {
+    fn drop(&mut self) {
+        MARKING.with(|marking| marking.set(self.0));
+    }
+}
+
+/// The code `generate` returns, with the lines of it that are fallbacks: those `fallback`
+/// marked, and those a TODO comment is on; generators' `generate_with_fallbacks` calls this
+pub(crate) fn generate_marked(generate: impl FnOnce() -> Result<String>) -> Result<(String, Vec<usize>)> {
+    let marked = {
+        let _guard = MarkingGuard(MARKING.with(|marking| marking.replace(true)));
+        generate()?
+    };
+    let mut lines = Vec::new();
+    let mut open = 0usize;
+    let code: Vec<String> = marked.split('\n').enumerate()
+        .map(|(i, line)| {
+            let unmarked: String = line.chars().filter(|&c| c != FALLBACK_START && c != FALLBACK_END).collect();
+            if open > 0 || line.contains(FALLBACK_START) || unmarked.contains(FALLBACK_TODO) {
+                lines.push(i + 1);
+            }
+            open += line.matches(FALLBACK_START).count();
+            open = open.saturating_sub(line.matches(FALLBACK_END).count());
+            unmarked
+        })
+        .collect();
+    Ok((code.join("\n"), lines))
+}
+
+/// `code` a generator falls back on, marked for the `generate_with_fallbacks` call in progress
+pub(crate) fn fallback(code: &str) -> String {
+    match MARKING.with(Cell::get) {
+        true => format!("{}{}{}", FALLBACK_START, code, FALLBACK_END),
+        false => code.to_string(),
+    }
+}
+
+/// One level of indentation: the configured one, or `native_width` spaces, the generator's own
+pub(crate) fn indent_unit(native_width: usize) -> String {
+    STYLE.with(|style| {
+        let style = style.borrow();
+        if style.use_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(style.indent_width.unwrap_or(native_width))
+        }
+    })
+}
+
+/// `code` indented `level` levels of the generators' usual four spaces, leaving blank lines empty
+pub(crate) fn indent(code: &str, level: usize) -> String {
+    let padding = indent_unit(4).repeat(level);
+    code.lines()
+        .map(|line| if line.trim().is_empty() { String::new() } else { format!("{}{}", padding, line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A block statement or declaration: `header`, the opening brace where the brace style puts
+/// it, the already indented `body` and the closing brace
+pub(crate) fn block(header: &str, body: &str) -> String {
+    match brace_style() {
+        // A block left empty, as one only Python's `pass` filled, closes on its own line
+        BraceStyle::SameLine if body.trim().is_empty() => format!("{} {{\n}}", header),
+        BraceStyle::NextLine if body.trim().is_empty() => format!("{}\n{{\n}}", header),
+        BraceStyle::SameLine => format!("{} {{\n{}\n}}", header, body),
+        BraceStyle::NextLine => format!("{}\n{{\n{}\n}}", header, body),
+    }
+}
+
+/// `keyword`, as `else`, continuing a statement after the closing brace of its previous block
+pub(crate) fn continued(keyword: &str) -> String {
+    match brace_style() {
+        BraceStyle::SameLine => format!(" {}", keyword),
+        BraceStyle::NextLine => format!("\n{}", keyword),
+    }
+}
+
+fn brace_style() -> BraceStyle {
+    STYLE.with(|style| style.borrow().brace_style)
+}
+
+/// `callee(items)`, split one item per line when longer than the configured maximum; the
+/// closing parenthesis stays on the last item's line so no language sees a dangling separator
+pub(crate) fn argument_list(callee: &str, items: &[String]) -> String {
+    let single = format!("{}({})", callee, items.join(", "));
+    let max = STYLE.with(|style| style.borrow().max_line_length);
+    match max {
+        Some(max) if items.len() > 1 && single.chars().count() > max => {
+            let unit = indent_unit(4);
+            let lines: Vec<String> = items.iter().map(|item| format!("{}{}", unit, item)).collect();
+            format!("{}(\n{})", callee, lines.join(",\n"))
+        }
+        _ => single,
+    }
+}
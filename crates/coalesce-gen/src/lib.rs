@@ -1,69 +1,130 @@
-use coalesce_core::{Generator, Language, UIRNode, NodeType, ExpressionType, StatementType, Result};
+use coalesce_core::{CaptureMode, CommentStyle, EnumVariant, Generator, GeneratorConfig, Language, LiteralValue, Metadata, Modifier, NamingConvention, Operator, Ownership, UIRNode, Visibility, NodeType, ControlFlowType, LoopType, ExpressionType, StatementType, Result, Walk, walk, walk_mut};
+use std::collections::{HashMap, HashSet};
 
+mod imports;
+mod layout;
 mod system_generators;
+mod template_generator;
+mod web_generators;
 
+pub use imports::ImportSet;
 pub use system_generators::{CGenerator, GoGenerator};
+pub use template_generator::{TemplateGenerator, TemplateSet};
+pub use web_generators::TypeScriptGenerator;
+use layout::{argument_list, block, continued, fallback, generate_marked, generate_styled, indent};
+
+/// What generators write, as a comment, in place of a node they can't translate yet
+pub const FALLBACK_TODO: &str = "TODO: Implement UIR node generation";
+
+/// The source of a node `target` has no translation for, copied as written; a construct
+/// rather than a single token, from another language, is recorded as a fallback
+pub(crate) fn verbatim(uir: &UIRNode, text: &str, target: Language) -> String {
+    let same = uir.metadata.source_language == target
+        || (target == Language::TypeScript && uir.metadata.source_language == Language::JavaScript);
+    if !same && uir.children.iter().any(|c| !is_punctuation(c)) {
+        return fallback(text);
+    }
+    text.to_string()
+}
 
 pub struct PythonGenerator;
 
+/// A class attribute with its type hint and initializer, if the source gave them
+struct PythonField {
+    name: String,
+    hint: Option<String>,
+    value: Option<String>,
+}
+
 impl Generator for PythonGenerator {
     fn target_language(&self) -> Language {
         Language::Python
     }
     
+    fn generate_with_config(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<String> {
+        generate_styled(self, uir, config)
+    }
+    
+    fn generate_with_fallbacks(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<(String, Vec<usize>)> {
+        generate_marked(|| self.generate_with_config(uir, config))
+    }
+    
     fn generate(&self, uir: &UIRNode) -> Result<String> {
         match &uir.node_type {
             NodeType::Module => {
-                let mut code = String::from("# Generated by Coalesce\n\n");
-                
-                for child in &uir.children {
-                    code.push_str(&self.generate(child)?);
-                    code.push('\n');
+                let items = module_items(uir);
+                // Variables declared or assigned at module level, directly or in a declaration statement
+                let globals: HashSet<String> = items.iter()
+                    .flat_map(|c| if matches!(c.node_type, NodeType::Statement(_)) { c.children.iter().collect() } else { vec![*c] })
+                    .filter_map(|c| match c.node_type {
+                        NodeType::Variable => c.name.as_deref(),
+                        NodeType::Expression(ExpressionType::Assignment) => c.children.first().and_then(|t| t.name.as_deref()),
+                        _ => None,
+                    })
+                    .map(identifier)
+                    .collect();
+                let mut body = String::new();
+                for child in items.into_iter().filter(|c| !is_import(c) && !is_documentation(c)) {
+                    // Functions and classes carry theirs as docstrings
+                    if !matches!(child.node_type, NodeType::Function | NodeType::Class | NodeType::Enum { .. } | NodeType::Property { .. }) {
+                        body.push_str(&documentation_lines(child, "#"));
+                    }
+                    match written_globals(child, &globals) {
+                        written if child.node_type == NodeType::Function && !written.is_empty() => {
+                            // Assigning a module-level name inside a function needs `global`
+                            let mut function = child.clone();
+                            function.metadata.annotations.insert("globals".to_string(), written.into());
+                            body.push_str(&self.generate(&function)?);
+                        }
+                        _ => body.push_str(&self.generate(child)?),
+                    }
+                    body.push('\n');
                 }
                 
+                let mut imports = ImportSet::collect(uir);
+                self.implicit_imports(&body, &mut imports);
+                self.decorator_imports(uir, &mut imports);
+                let mut code = String::from("# Generated by Coalesce\n\n");
+                if let Some(docstring) = self.docstring(uir) {
+                    code.push_str(&format!("{}\n\n", docstring));
+                }
+                code.push_str(&imports.render(&Language::Python));
+                code.push_str(&body);
                 Ok(code)
             }
             NodeType::Function => {
-                self.generate_function(uir)
+                self.generate_function(uir, false)
+            }
+            // Module-level accessors, as in VB6 modules, are plain functions
+            NodeType::Property { .. } => {
+                let functions = accessor_functions(uir).iter()
+                    .map(|function| self.generate_function(function, false))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(functions.join("\n\n"))
             }
             NodeType::Class => {
                 self.generate_class(uir)
             }
             NodeType::Variable => {
-                // For function parameters and variable references
-                Ok(uir.name.as_deref().unwrap_or("unknown_var").to_string())
-            }
-            NodeType::Statement(StatementType::Return) => {
-                self.generate_return_statement(uir)
-            }
-            NodeType::Expression(ExpressionType::Arithmetic) => {
-                self.generate_arithmetic_expression(uir)
+                self.generate_module_variable(uir)
             }
-            NodeType::Expression(ExpressionType::Variable) => {
-                Ok(uir.name.as_deref().unwrap_or("unknown").to_string())
+            NodeType::Enum { variants } => {
+                self.generate_enum(uir, variants)
             }
-            NodeType::Expression(ExpressionType::Literal) => {
-                // Extract literal value from original text
-                if let Some(original) = uir.metadata.annotations.get("original_text") {
-                    if let Some(text) = original.as_str() {
-                        Ok(text.to_string())
-                    } else {
-                        Ok("0".to_string()) // default literal
-                    }
-                } else {
-                    Ok("0".to_string()) // default literal
-                }
+            NodeType::Statement(_) | NodeType::ControlFlow(_) | NodeType::Comment { .. } => {
+                self.generate_statement(uir)
             }
             _ => {
-                Ok("# TODO: Implement UIR node generation\n".to_string())
+                self.generate_expression(uir)
             }
         }
     }
 }
 
 impl PythonGenerator {
-    fn generate_function(&self, uir: &UIRNode) -> Result<String> {
-        let func_name = uir.name.as_deref().unwrap_or("generated_function");
+    /// Emit a function; methods gain the `self` receiver Python requires
+    fn generate_function(&self, uir: &UIRNode, is_method: bool) -> Result<String> {
+        let func_name = identifier(uir.name.as_deref().unwrap_or("generated_function"));
         
         // Extract parameters from children (Variable nodes that are direct children)
         let mut parameters = Vec::new();
@@ -72,147 +133,838 @@ impl PythonGenerator {
         for child in &uir.children {
             match &child.node_type {
                 NodeType::Variable => {
-                    // This is a parameter
                     if let Some(param_name) = &child.name {
-                        parameters.push(param_name.clone());
+                        parameters.push(self.generate_parameter(child, param_name)?);
                     }
                 }
-                NodeType::Statement(_) => {
-                    // This is a statement in the function body
-                    statements.push(child);
-                }
+                NodeType::Import { .. } => {}
                 _ => {
-                    // Other types might be statements too
                     statements.push(child);
                 }
             }
         }
         
-        let params_str = parameters.join(", ");
+        let written = python_decorators(uir);
+        let is_static = is_static(uir) || written.contains(&"staticmethod");
+        let mut decorators: String = written.iter().map(|decorator| format!("@{}\n", decorator)).collect();
+        if is_method && is_static && !written.contains(&"staticmethod") {
+            decorators.push_str("@staticmethod\n");
+        } else if is_method && !is_static && !parameters.first().is_some_and(|p| p == "self" || p == "cls") {
+            parameters.insert(0, "self".to_string());
+        }
         
-        // Generate function body
-        let body = if statements.is_empty() {
-            "    pass".to_string()
-        } else {
-            let mut body_code = String::new();
-            for stmt in statements {
-                let stmt_code = self.generate(stmt)?;
-                // Add proper indentation
-                for line in stmt_code.lines() {
-                    if !line.trim().is_empty() {
-                        body_code.push_str(&format!("    {}\n", line));
-                    }
-                }
-            }
-            body_code.trim_end().to_string()
+        let return_hint = match self.annotated_type(uir, "return_type") {
+            Some(return_type) => format!(" -> {}", return_type),
+            None => String::new(),
+        };
+        let is_async = is_async(uir);
+        let keyword = if is_async { "async def" } else { "def" };
+        
+        let func_name = match func_name.as_str() {
+            "constructor" | "init" | "New" if is_method => "__init__",
+            name => name,
         };
         
-        Ok(format!("def {}({}):\n{}", func_name, params_str, body))
+        let mut body = self.generate_block(&statements)?;
+        if let Some(globals) = uir.metadata.annotations.get("globals").and_then(|v| v.as_array()) {
+            let names: Vec<&str> = globals.iter().filter_map(|v| v.as_str()).collect();
+            body = format!("{}\n{}", indent(&format!("global {}", names.join(", ")), 1), body);
+        }
+        if let Some(docstring) = self.docstring(uir) {
+            body = format!("{}\n{}", indent(&docstring, 1), body);
+        }
+        Ok(format!(
+            "{}{}{}:\n{}",
+            decorators, argument_list(&format!("{} {}", keyword, func_name), &parameters), return_hint, body
+        ))
+    }
+    
+    /// `name: hint = default`, with the `*` or `**` of a parameter collecting the extra arguments
+    fn generate_parameter(&self, uir: &UIRNode, name: &str) -> Result<String> {
+        let splat = match uir.metadata.annotations.get("splat").and_then(|v| v.as_str()) {
+            Some("list") => "*",
+            Some("dictionary") => "**",
+            _ if uir.metadata.annotations.get("rest").and_then(|v| v.as_bool()).unwrap_or(false) => "*",
+            _ => "",
+        };
+        let has_default = uir.metadata.annotations.get("has_default").and_then(|v| v.as_bool()).unwrap_or(false);
+        let default = match (declared_default(uir), initializer(uir)) {
+            (Some(value), _) if uir.metadata.source_language == Language::Python => Some(value.to_string()),
+            (Some(value), _) => Some(match value {
+                "true" => "True".to_string(),
+                "false" => "False".to_string(),
+                "null" | "nil" | "undefined" => "None".to_string(),
+                value => value.to_string(),
+            }),
+            (None, Some(value)) if has_default => Some(self.generate_expression(value)?),
+            _ => None,
+        };
+        let hint = self.annotated_type(uir, "type").filter(|_| !matches!(name, "self" | "cls"));
+        Ok(match (hint, default) {
+            (Some(hint), Some(default)) => format!("{}{}: {} = {}", splat, name, hint, default),
+            (Some(hint), None) => format!("{}{}: {}", splat, name, hint),
+            (None, Some(default)) => format!("{}{}={}", splat, name, default),
+            (None, None) => format!("{}{}", splat, name),
+        })
+    }
+    
+    /// `documentation` as a docstring, with the closing quotes on their own line when it spans
+    /// several
+    fn docstring(&self, uir: &UIRNode) -> Option<String> {
+        let documentation = uir.documentation.as_deref()?
+            .replace('\\', "\\\\")
+            .replace("\"\"\"", "\\\"\\\"\\\"");
+        if documentation.contains('\n') {
+            Some(format!("\"\"\"{}\n\"\"\"", documentation))
+        } else {
+            Some(format!("\"\"\"{}\"\"\"", documentation))
+        }
+    }
+    
+    /// A module-level variable, such as a COBOL data item: `NAME: hint = value`, starting from
+    /// the type's zero value when the source gives none so functions can read it
+    fn generate_module_variable(&self, uir: &UIRNode) -> Result<String> {
+        let name = identifier(uir.name.as_deref().unwrap_or("value"));
+        let hint = self.annotated_type(uir, "type");
+        let value = match (initializer(uir), declared_default(uir)) {
+            (Some(value), _) => self.generate_expression(value)?,
+            (None, Some(value)) => value.to_string(),
+            (None, None) => match hint.as_deref() {
+                Some("int") => "0".to_string(),
+                Some("float") => "0.0".to_string(),
+                Some("str") => "\"\"".to_string(),
+                Some("bool") => "False".to_string(),
+                _ => "None".to_string(),
+            },
+        };
+        Ok(match hint {
+            Some(hint) => format!("{}: {} = {}", name, hint, value),
+            None => format!("{} = {}", name, value),
+        })
     }
     
+    /// Field-only classes become dataclasses; others keep class attributes and methods
     fn generate_class(&self, uir: &UIRNode) -> Result<String> {
-        let class_name = uir.name.as_deref().unwrap_or("GeneratedClass");
+        let class_name = identifier(uir.name.as_deref().unwrap_or("GeneratedClass"));
+        
+        let methods: Vec<&UIRNode> = uir.children.iter()
+            .filter(|c| c.node_type == NodeType::Function || !accessors(c).is_empty())
+            .collect();
+        let fields = self.class_fields(uir)?;
+        
+        // Field-only classes become dataclasses; the source's decorators stay as written
+        let mut decorators = python_decorators(uir);
+        let decorated = decorators.iter().any(|decorator| is_dataclass(decorator));
+        let is_dataclass = decorated || methods.is_empty() && !fields.is_empty();
+        if is_dataclass && !decorated {
+            decorators.push("dataclass");
+        }
+        
+        let mut sections: Vec<String> = self.docstring(uir).into_iter().collect();
+        if is_dataclass && !fields.is_empty() {
+            // Dataclass fields without defaults must come before those with one
+            let (required, defaulted): (Vec<_>, Vec<_>) = fields.into_iter().partition(|field| field.value.is_none());
+            let lines: Vec<String> = required.into_iter().chain(defaulted)
+                .map(|field| {
+                    let hint = field.hint.unwrap_or_else(|| "Any".to_string());
+                    match field.value {
+                        Some(value) => format!("{}: {} = {}", field.name, hint, value),
+                        None => format!("{}: {}", field.name, hint),
+                    }
+                })
+                .collect();
+            sections.push(lines.join("\n"));
+        } else if !fields.is_empty() {
+            let class_vars: Vec<String> = fields.into_iter()
+                .map(|field| match (field.hint, field.value) {
+                    (Some(hint), Some(value)) => format!("{}: {} = {}", field.name, hint, value),
+                    (Some(hint), None) => format!("{}: {}", field.name, hint),
+                    (None, value) => format!("{} = {}", field.name, value.unwrap_or_else(|| "None".to_string())),
+                })
+                .collect();
+            sections.push(class_vars.join("\n"));
+        }
+        for method in methods {
+            let mut section: Vec<String> = attached_comments(&uir.children, method).into_iter()
+                .map(|comment| comment_code(comment, "#", "#", None))
+                .collect();
+            section.push(match method.node_type {
+                NodeType::Property { .. } => self.generate_property(method)?,
+                _ => self.generate_function(method, true)?,
+            });
+            sections.push(section.join("\n"));
+        }
         
+        let class_body = if sections.is_empty() {
+            "pass".to_string()
+        } else {
+            sections.join("\n\n")
+        };
+        let decorators: String = decorators.iter().map(|decorator| format!("@{}\n", decorator)).collect();
+        let bases = class_bases(uir);
+        let bases = if bases.is_empty() { String::new() } else { format!("({})", bases.join(", ")) };
+        Ok(format!("{}class {}{}:\n{}", decorators, class_name, bases, indent(&class_body, 1)))
+    }
+    
+    /// A property's accessors as an `@property` getter and its `@name.setter`; a setter without
+    /// a getter to attach to stays a `set_name` method
+    fn generate_property(&self, uir: &UIRNode) -> Result<String> {
+        let name = uir.name.as_deref().unwrap_or("generated_property");
+        let accessors = accessors(uir);
+        let has_getter = accessors.iter().any(|a| !is_setter(a));
         let mut methods = Vec::new();
-        let mut class_vars = Vec::new();
+        for accessor in accessors {
+            let mut method = accessor_method(uir, accessor);
+            let decorator = match (is_setter(accessor), has_getter) {
+                (false, _) => "@property\n".to_string(),
+                (true, true) => format!("@{}.setter\n", name),
+                (true, false) => {
+                    method.name = Some(format!("set_{}", name));
+                    String::new()
+                }
+            };
+            methods.push(format!("{}{}", decorator, self.generate_function(&method, true)?));
+        }
+        Ok(methods.join("\n\n"))
+    }
+    
+    /// An `Enum` class; variants carrying data become dataclasses joined in a `Union`
+    fn generate_enum(&self, uir: &UIRNode, variants: &[EnumVariant]) -> Result<String> {
+        let enum_name = uir.name.as_deref().unwrap_or("GeneratedEnum");
+        if !has_payload(variants) {
+            let values = enum_values(variants);
+            let members: Vec<String> = variants.iter().zip(values)
+                .map(|(variant, value)| match variant.value {
+                    Some(_) => format!("{} = {}", variant.name, value),
+                    None => format!("{} = auto()", variant.name),
+                })
+                .collect();
+            let mut body = if members.is_empty() { "pass".to_string() } else { members.join("\n") };
+            if let Some(docstring) = self.docstring(uir) {
+                body = format!("{}\n\n{}", docstring, body);
+            }
+            return Ok(format!("class {}(Enum):\n{}", enum_name, indent(&body, 1)));
+        }
         
+        let mut classes = Vec::new();
+        for variant in variants {
+            let fields: Vec<String> = variant.fields.iter().enumerate()
+                .map(|(index, field)| {
+                    let name = field.name.clone().unwrap_or_else(|| format!("_{}", index));
+                    format!("{}: {}", name, self.map_type(&field.field_type))
+                })
+                .collect();
+            let body = if fields.is_empty() { "pass".to_string() } else { fields.join("\n") };
+            classes.push(format!("@dataclass\nclass {}:\n{}", variant.name, indent(&body, 1)));
+        }
+        let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+        classes.push(format!("{} = Union[{}]", enum_name, names.join(", ")));
+        Ok(classes.join("\n\n\n"))
+    }
+    
+    /// Name, type hint and initializer of each field: declared members, constructor properties
+    /// and class-level assignments such as `x: int = 0`
+    fn class_fields(&self, uir: &UIRNode) -> Result<Vec<PythonField>> {
+        let mut fields = Vec::new();
         for child in &uir.children {
             match &child.node_type {
-                NodeType::Function => {
-                    let method_code = self.generate_function(child)?;
-                    // Indent the method
-                    let indented_method = method_code.lines()
-                        .map(|line| if line.trim().is_empty() { line.to_string() } else { format!("    {}", line) })
-                        .collect::<Vec<_>>()
-                        .join("\n");
-                    methods.push(indented_method);
+                _ if is_field(child) => {
+                    let Some(name) = &child.name else { continue };
+                    let value = match child.children.iter().find(|c| matches!(c.node_type, NodeType::Expression(_))) {
+                        Some(value) => Some(self.generate_expression(value)?),
+                        None => declared_default(child).map(str::to_string),
+                    };
+                    fields.push(PythonField { name: identifier(name), hint: self.annotated_type(child, "type"), value });
                 }
-                NodeType::Variable => {
-                    if let Some(var_name) = &child.name {
-                        class_vars.push(format!("    {} = None", var_name));
+                NodeType::Statement(StatementType::Expression) => {
+                    for assignment in child.children.iter().filter(|c| c.node_type == NodeType::Expression(ExpressionType::Assignment)) {
+                        let Some(name) = assignment.children.first().and_then(|t| t.name.clone()) else { continue };
+                        let value = match assignment.children.get(1) {
+                            Some(value) => Some(self.generate_expression(value)?),
+                            None => None,
+                        };
+                        fields.push(PythonField { name: identifier(&name), hint: self.annotated_type(assignment, "type"), value });
                     }
                 }
                 _ => {}
             }
         }
-        
-        let mut class_body = String::new();
-        
-        if class_vars.is_empty() && methods.is_empty() {
-            class_body.push_str("    pass");
-        } else {
-            if !class_vars.is_empty() {
-                class_body.push_str(&class_vars.join("\n"));
-                if !methods.is_empty() {
-                    class_body.push_str("\n\n");
+        Ok(fields)
+    }
+    
+    /// Statements indented one level, or `pass` when there are none
+    fn generate_block(&self, statements: &[&UIRNode]) -> Result<String> {
+        let mut lines = Vec::new();
+        let mut has_code = false;
+        for stmt in statements {
+            let code = self.generate_statement(stmt)?;
+            if !code.trim().is_empty() {
+                // Nodes left as a `TODO` comment are comments too
+                has_code |= !is_comment(stmt) && code.lines().any(|line| !line.trim_start().starts_with('#'));
+                lines.push(code);
+            }
+        }
+        // A block of only comments still needs a statement
+        if !has_code {
+            lines.push("pass".to_string());
+        }
+        Ok(indent(&lines.join("\n"), 1))
+    }
+    
+    fn generate_statement(&self, uir: &UIRNode) -> Result<String> {
+        match &uir.node_type {
+            NodeType::Comment { .. } => Ok(comment_code(uir, "#", "#", None)),
+            NodeType::Statement(StatementType::Return) => {
+                match uir.children.first() {
+                    None => Ok("return".to_string()),
+                    // A `yield` only stands unparenthesized as a statement or an assigned value
+                    Some(value) if value.node_type == NodeType::Expression(ExpressionType::Yield) => {
+                        Ok(format!("return ({})", self.generate_expression(value)?))
+                    }
+                    Some(value) => Ok(format!("return {}", self.generate_expression(value)?)),
                 }
             }
-            
-            if !methods.is_empty() {
-                class_body.push_str(&methods.join("\n\n"));
+            NodeType::Statement(StatementType::Throw) => {
+                // Grammars that keep their tokens lead with the `throw` keyword
+                let mut expressions = uir.children.iter()
+                    .filter(|c| !is_punctuation(c) && !matches!(original_text(c), "throw" | "raise"))
+                    .filter(|c| matches!(c.node_type, NodeType::Expression(_)));
+                let exception = expressions.next();
+                // Python's `raise X from e` chains the exception being handled
+                let cause = match expressions.next() {
+                    Some(cause) if uir.metadata.source_language == Language::Python => format!(" from {}", self.generate_expression(cause)?),
+                    _ => String::new(),
+                };
+                match exception {
+                    // A constructed exception, or one already bound such as a caught `err`
+                    Some(exception) if matches!(exception.node_type, NodeType::Expression(ExpressionType::FunctionCall | ExpressionType::Variable)) => {
+                        Ok(format!("raise {}{}", self.generate_expression(exception)?, cause))
+                    }
+                    // Re-raise of the exception being handled
+                    None if matches!(original_text(uir).trim(), "raise" | "throw" | "throw;") => Ok("raise".to_string()),
+                    _ => Ok(format!("raise Exception({})", raised_message(uir))),
+                }
+            }
+            NodeType::Statement(StatementType::Break) => Ok("break".to_string()),
+            NodeType::Statement(StatementType::Continue) => Ok("continue".to_string()),
+            NodeType::Statement(_) if has_tag(uir, &["with_statement"]) => {
+                let clause = uir.children.iter()
+                    .find(|c| has_tag(c, &["with_clause"]))
+                    .map(original_text)
+                    .unwrap_or("");
+                let body: Vec<&UIRNode> = uir.children.iter()
+                    .filter(|c| matches!(c.node_type, NodeType::Statement(_) | NodeType::ControlFlow(_)))
+                    .collect();
+                let keyword = if is_async(uir) { "async with" } else { "with" };
+                Ok(format!("{} {}:\n{}", keyword, clause, self.generate_block(&body)?))
+            }
+            NodeType::Statement(_) => {
+                // Blocks and expression statements wrap the real statements
+                let mut lines = Vec::new();
+                for child in uir.children.iter().filter(|c| !is_punctuation(c)) {
+                    lines.push(self.generate_statement(child)?);
+                }
+                Ok(lines.join("\n"))
+            }
+            NodeType::Variable => {
+                let name = identifier(uir.name.as_deref().unwrap_or("value"));
+                let value = match (destructured_value(uir, |key| format!("[\"{}\"]", key)), destructured_default(uir)) {
+                    // The default stands in for a missing key or element
+                    (Some(value), Some(default)) => {
+                        let default = self.generate_expression(default)?;
+                        let (source, step) = value.strip_suffix(']').and_then(|value| value.rsplit_once('[')).unwrap_or((value.as_str(), ""));
+                        match step.parse::<usize>() {
+                            Ok(index) => format!("{}[{}] if len({}) > {} else {}", source, index, source, index, default),
+                            Err(_) => format!("{}.get({}, {})", source, step, default),
+                        }
+                    }
+                    (Some(value), None) => value,
+                    (None, _) => match initializer(uir) {
+                        Some(value) => self.generate_expression(value)?,
+                        None => "None".to_string(),
+                    },
+                };
+                Ok(format!("{} = {}", name, value))
+            }
+            NodeType::ControlFlow(ControlFlowType::Conditional) => {
+                self.generate_conditional(uir, "if")
+            }
+            NodeType::ControlFlow(ControlFlowType::Loop(loop_type)) => {
+                let parts = loop_parts(uir);
+                let mut code = match (loop_type, parts.target, parts.iterable, counted_loop(&parts)) {
+                    (LoopType::ForEach, Some(target), Some(iterable), _) => {
+                        let keyword = if is_async(uir) { "async for" } else { "for" };
+                        format!(
+                            "{} {} in {}:\n{}",
+                            keyword,
+                            self.generate_expression(target)?,
+                            self.generate_expression(iterable)?,
+                            self.generate_block(&parts.body)?
+                        )
+                    }
+                    (LoopType::For, _, _, Some(counted)) => {
+                        format!("for {} in {}:\n{}", identifier(counted.variable), self.generate_range(&counted)?, self.generate_block(&parts.body)?)
+                    }
+                    // Any other `for (init; condition; update)` runs as a `while`, and `do ... while`
+                    // as an endless loop that tests its condition last
+                    _ => {
+                        let initializer = match parts.initializer {
+                            Some(initializer) => format!("{}\n", self.generate_statement(initializer)?),
+                            None => String::new(),
+                        };
+                        let condition = match parts.condition {
+                            Some(condition) if *loop_type != LoopType::DoWhile => self.generate_expression(condition)?,
+                            _ => "True".to_string(),
+                        };
+                        let body = lowered_body(uir, &parts);
+                        format!("{}while {}:\n{}", initializer, condition, self.generate_block(&body.iter().collect::<Vec<_>>())?)
+                    }
+                };
+                if !parts.otherwise.is_empty() {
+                    code.push_str(&format!("\nelse:\n{}", self.generate_block(&parts.otherwise)?));
+                }
+                Ok(code)
+            }
+            NodeType::ControlFlow(ControlFlowType::Try) => {
+                self.generate_try(uir)
+            }
+            NodeType::ControlFlow(ControlFlowType::Goto) => match paragraph_jump(uir) {
+                Some(jump) => self.generate_statement(&jump),
+                None => Ok(format!("# {}", FALLBACK_TODO)),
+            },
+            NodeType::Expression(_) => {
+                self.generate_expression(uir)
+            }
+            _ => {
+                Ok(format!("# {}", FALLBACK_TODO))
+            }
+        }
+    }
+    
+    fn generate_conditional(&self, uir: &UIRNode, keyword: &str) -> Result<String> {
+        let Some(condition) = uir.children.first() else {
+            return Ok(format!("# {}", FALLBACK_TODO));
+        };
+        
+        let mut then_branch = Vec::new();
+        let mut else_branches = Vec::new();
+        for child in &uir.children[1..] {
+            if has_tag(child, &["else_clause", "elif_clause", "else"]) {
+                else_branches.push(child);
+            } else {
+                then_branch.push(child);
             }
         }
         
-        Ok(format!("class {}:\n{}", class_name, class_body))
+        let mut code = format!("{} {}:\n{}", keyword, self.generate_expression(condition)?, self.generate_block(&then_branch)?);
+        for branch in else_branches {
+            if has_tag(branch, &["elif_clause"]) {
+                code.push('\n');
+                code.push_str(&self.generate_conditional(branch, "elif")?);
+            } else {
+                let body: Vec<&UIRNode> = branch.children.iter().collect();
+                code.push_str(&format!("\nelse:\n{}", self.generate_block(&body)?));
+            }
+        }
+        Ok(code)
     }
     
-    fn generate_return_statement(&self, uir: &UIRNode) -> Result<String> {
-        if uir.children.is_empty() {
-            Ok("return".to_string())
-        } else {
-            let expr_code = self.generate(&uir.children[0])?;
-            Ok(format!("return {}", expr_code.trim()))
+    fn generate_try(&self, uir: &UIRNode) -> Result<String> {
+        let mut body = Vec::new();
+        let mut handlers = Vec::new();
+        let mut otherwise = Vec::new();
+        let mut cleanup = Vec::new();
+        for child in &uir.children {
+            if is_cleanup(child) {
+                cleanup.push(child);
+            } else if is_handler(child) {
+                handlers.push(child);
+            } else if has_tag(child, &["else_clause"]) {
+                otherwise.extend(child.children.iter());
+            } else {
+                body.push(child);
+            }
+        }
+        
+        let mut code = format!("try:\n{}", self.generate_block(&body)?);
+        for handler in &handlers {
+            // The exception pattern (`ValueError as e`) is kept as written when it is Python's
+            let pattern = handler.children.iter()
+                .find(|c| !matches!(c.node_type, NodeType::Statement(_) | NodeType::ControlFlow(_)))
+                .map(original_text);
+            let handler_body: Vec<&UIRNode> = handler.children.iter()
+                .filter(|c| matches!(c.node_type, NodeType::Statement(_) | NodeType::ControlFlow(_)))
+                .collect();
+            let header = match pattern {
+                _ if handler.metadata.source_language != Language::Python => {
+                    format!("except Exception as {}:", handler_binding(handler).unwrap_or_else(|| "e".to_string()))
+                }
+                Some(pattern) if !pattern.is_empty() => format!("except {}:", pattern),
+                _ => "except:".to_string(),
+            };
+            code.push_str(&format!("\n{}\n{}", header, self.generate_block(&handler_body)?));
         }
+        // `else` runs when the body raised nothing
+        if !otherwise.is_empty() {
+            code.push_str(&format!("\nelse:\n{}", self.generate_block(&otherwise)?));
+        }
+        if !cleanup.is_empty() {
+            let statements: Vec<&UIRNode> = cleanup.iter().flat_map(|c| c.children.iter()).collect();
+            code.push_str(&format!("\nfinally:\n{}", self.generate_block(&statements)?));
+        } else if handlers.is_empty() {
+            code.push_str(&format!("\nfinally:\n{}", indent("pass", 1)));
+        }
+        Ok(code)
     }
     
-    fn generate_arithmetic_expression(&self, uir: &UIRNode) -> Result<String> {
-        // For binary expressions, we expect two operands
-        if uir.children.len() == 2 {
-            let left = self.generate(&uir.children[0])?.trim().to_string();
-            let right = self.generate(&uir.children[1])?.trim().to_string();
-            
-            // Extract the operator from the original text if available
-            let operator = if let Some(original) = uir.metadata.annotations.get("original_text") {
-                if let Some(text) = original.as_str() {
-                    // Simple heuristic: if it contains "+", use "+"
-                    if text.contains(" + ") {
-                        " + "
-                    } else if text.contains(" - ") {
-                        " - "
-                    } else if text.contains(" * ") {
-                        " * "
-                    } else if text.contains(" / ") {
-                        " / "
-                    } else {
-                        " + " // default
+    fn generate_expression(&self, uir: &UIRNode) -> Result<String> {
+        let original = original_text(uir);
+        if let Some((collection, index)) = subscript_parts(uir) {
+            return Ok(format!("{}[{}]", self.generate_expression(collection)?, self.generate_expression(index)?));
+        }
+        if let Some(comprehension) = comprehension_parts(uir) {
+            let mut code = format!(
+                "[{} for {} in {}",
+                self.generate_expression(comprehension.element)?,
+                self.generate_expression(comprehension.target)?,
+                self.generate_expression(comprehension.iterable)?,
+            );
+            for condition in &comprehension.conditions {
+                code.push_str(&format!(" if {}", self.generate_expression(condition)?));
+            }
+            code.push(']');
+            return Ok(code);
+        }
+        if let Some((condition, value, otherwise)) = ternary_parts(uir) {
+            return Ok(format!(
+                "{} if {} else {}",
+                ternary_operand(self.generate_expression(value)?, value),
+                ternary_operand(self.generate_expression(condition)?, condition),
+                self.generate_expression(otherwise)?,
+            ));
+        }
+        match &uir.node_type {
+            NodeType::Variable | NodeType::Expression(ExpressionType::Variable) if original.contains('.') && uir.children.len() >= 2 => {
+                let mut parts = Vec::new();
+                for child in uir.children.iter().filter(|c| original_text(c) != ".") {
+                    parts.push(self.generate_expression(child)?);
+                }
+                Ok(parts.join("."))
+            }
+            NodeType::Variable | NodeType::Expression(ExpressionType::Variable) => {
+                // For function parameters and variable references
+                match uir.name.as_deref().unwrap_or("unknown") {
+                    "this" => Ok("self".to_string()),
+                    name => Ok(identifier(name)),
+                }
+            }
+            NodeType::Expression(ExpressionType::Literal) => {
+                if let Some(value) = &uir.literal {
+                    return Ok(literal_code(value, &Language::Python));
+                }
+                // Extract literal value from original text
+                Ok(match original {
+                    "" => "0".to_string(),
+                    "true" => "True".to_string(),
+                    "false" => "False".to_string(),
+                    "null" | "nil" | "undefined" => "None".to_string(),
+                    "this" => "self".to_string(),
+                    text if text.starts_with("this.") => format!("self.{}", &text[5..]),
+                    text => verbatim(uir, text, Language::Python),
+                })
+            }
+            NodeType::Expression(ExpressionType::Arithmetic | ExpressionType::Comparison | ExpressionType::Logical) => {
+                match uir.children.as_slice() {
+                    [operand] => {
+                        let operand_code = grouped(self.generate_expression(operand)?, uir, operand, true);
+                        match lowered_operation(uir, std::slice::from_ref(&operand_code), &Language::Python) {
+                            Lowering::Code(code) => return Ok(code),
+                            Lowering::Untranslated => return Ok(verbatim(uir, original, Language::Python)),
+                            Lowering::Operator => {}
+                        }
+                        if expression_operator(uir) == Some(Operator::Not) {
+                            Ok(format!("not {}", operand_code))
+                        } else if expression_operator(uir) == Some(Operator::Negate) {
+                            Ok(format!("-{}", operand_code))
+                        } else {
+                            Ok(operand_code)
+                        }
                     }
-                } else {
-                    " + " // default
+                    [left, right] => {
+                        let left_code = grouped(self.generate_expression(left)?, uir, left, false);
+                        let right_code = grouped(self.generate_expression(right)?, uir, right, true);
+                        match lowered_operation(uir, &[left_code.clone(), right_code.clone()], &Language::Python) {
+                            Lowering::Code(code) => return Ok(code),
+                            Lowering::Untranslated => return Ok(verbatim(uir, original, Language::Python)),
+                            Lowering::Operator => {}
+                        }
+                        let operator = match (uir.operator, binary_operator(uir, left)) {
+                            (Some(Operator::Is), _) => "is",
+                            (Some(Operator::IsNot), _) => "is not",
+                            (Some(Operator::In), _) => "in",
+                            (Some(Operator::NotIn), _) => "not in",
+                            (Some(Operator::IntegerDivide), _) => "//",
+                            (_, Some("&&")) => "and",
+                            (_, Some("||")) => "or",
+                            // `//` stays floor division when the source was Python
+                            (_, Some("/")) if original.contains("//") && uir.metadata.source_language == Language::Python => "//",
+                            (_, Some(op)) => op,
+                            (_, None) => return Ok(verbatim(uir, original, Language::Python)),
+                        };
+                        Ok(format!("{} {} {}", left_code, operator, right_code))
+                    }
+                    _ => Ok("unknown_expression".to_string()),
                 }
-            } else {
-                " + " // default
-            };
-            
-            Ok(format!("{}{}{}", left, operator, right))
-        } else {
-            Ok("unknown_expression".to_string())
+            }
+            NodeType::Expression(ExpressionType::FunctionCall) => {
+                let (function, arguments) = call_parts(uir);
+                let callee = match function {
+                    Some(function) => self.generate_expression(function)?,
+                    None => identifier(uir.name.as_deref().unwrap_or("unknown")),
+                };
+                let arguments = arguments.into_iter().map(|a| self.generate_expression(a)).collect::<Result<Vec<_>>>()?;
+                Ok(argument_list(&callee, &arguments))
+            }
+            NodeType::Expression(ExpressionType::Assignment) if uir.children.len() >= 2 => {
+                let target = self.generate_expression(&uir.children[0])?;
+                let (symbol, value) = match assignment_form(uir, &Language::Python) {
+                    AssignmentForm::Plain => ("=", self.generate_expression(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Compound(symbol) => (symbol, self.generate_expression(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Expanded(operation) => ("=", self.generate_expression(&operation)?),
+                };
+                Ok(format!("{} {} {}", target, symbol, value))
+            }
+            NodeType::Expression(ExpressionType::Await) => match operand(uir) {
+                Some(awaited) => Ok(format!("await {}", self.generate_expression(awaited)?)),
+                None => Ok("unknown_expression".to_string()),
+            },
+            NodeType::Expression(ExpressionType::Yield) => match operand(uir) {
+                Some(value) if has_tag(uir, &["yield_from"]) => Ok(format!("yield from {}", self.generate_expression(value)?)),
+                Some(value) => Ok(format!("yield {}", self.generate_expression(value)?)),
+                None => Ok("yield".to_string()),
+            },
+            NodeType::Closure { .. } => {
+                let (parameters, body) = closure_parts(uir);
+                match closure_value(&body) {
+                    Some(value) => {
+                        let names: Vec<&str> = parameters.iter().filter_map(|p| p.name.as_deref()).collect();
+                        let separator = if names.is_empty() { "" } else { " " };
+                        Ok(format!("lambda{}{}: {}", separator, names.join(", "), self.generate_expression(value)?))
+                    }
+                    // Python lambdas hold a single expression
+                    None if uir.metadata.source_language == Language::Python => Ok(original.to_string()),
+                    None => Ok("unknown_expression".to_string()),
+                }
+            }
+            _ if !original.is_empty() && uir.metadata.source_language == Language::Python => Ok(original.to_string()),
+            _ => Ok("unknown_expression".to_string()),
+        }
+    }
+    
+    /// `range(start, end, step)`, leaving out a start of 0 and a step of 1
+    fn generate_range(&self, counted: &CountedLoop) -> Result<String> {
+        let end = counted.exclusive_end(self.generate_expression(counted.end)?);
+        let start = self.generate_expression(counted.start)?;
+        Ok(match (start.as_str(), counted.step) {
+            ("0", 1) => format!("range({})", end),
+            (_, 1) => format!("range({}, {})", start, end),
+            (_, step) => format!("range({}, {}, {})", start, end, step),
+        })
+    }
+    
+    /// Imports for the typing names and decorators used in `body`
+    fn implicit_imports(&self, body: &str, imports: &mut ImportSet) {
+        if body.contains("@dataclass\n") || body.contains("@dataclass(") {
+            imports.insert("from dataclasses import dataclass");
+        }
+        if body.contains("(Enum):") {
+            imports.insert(if body.contains("auto()") { "from enum import Enum, auto" } else { "from enum import Enum" });
+        }
+        let typing: Vec<&str> = ["Any", "Dict", "List", "Optional", "Set", "Tuple", "Union"].into_iter()
+            .filter(|name| {
+                body.contains(&format!("{}[", name))
+                    || [": ", "-> ", "[", ", "].iter().any(|before| body.contains(&format!("{}{}", before, name)))
+            })
+            .collect();
+        if !typing.is_empty() {
+            imports.insert(format!("from typing import {}", typing.join(", ")));
+        }
+    }
+    
+    /// The source's imports of the names its decorators use, such as `functools` for
+    /// `@functools.cache`
+    fn decorator_imports<'a>(&self, uir: &'a UIRNode, imports: &mut ImportSet) {
+        let mut used: HashSet<&'a str> = HashSet::new();
+        walk(uir, &mut |node: &'a UIRNode| {
+            for decorator in python_decorators(node) {
+                let end = decorator.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(decorator.len());
+                used.insert(&decorator[..end]);
+            }
+            Walk::Continue
+        });
+        for import in module_items(uir) {
+            let NodeType::Import { path, alias, symbols } = &import.node_type else { continue };
+            if symbols.is_empty() {
+                let bound = alias.as_deref().unwrap_or_else(|| path.split('.').next().unwrap_or_default());
+                match alias {
+                    Some(alias) if used.contains(bound) => imports.insert(format!("import {} as {}", path, alias)),
+                    None if used.contains(bound) => imports.insert(format!("import {}", path)),
+                    _ => {}
+                }
+                continue;
+            }
+            let names: Vec<String> = symbols.iter()
+                .filter(|symbol| used.contains(symbol.alias.as_deref().unwrap_or(&symbol.name)))
+                .map(|symbol| match &symbol.alias {
+                    Some(alias) => format!("{} as {}", symbol.name, alias),
+                    None => symbol.name.clone(),
+                })
+                .collect();
+            if !names.is_empty() {
+                imports.insert(format!("from {} import {}", path, names.join(", ")));
+            }
+        }
+    }
+    
+    /// PEP 484 hint for a type annotation, if the source declared one
+    fn annotated_type(&self, uir: &UIRNode, key: &str) -> Option<String> {
+        declared_type(uir, key).map(|source_type| self.map_type(source_type))
+    }
+    
+    /// Map a source-language type to a `typing` hint, recursing into generic arguments
+    fn map_type(&self, source_type: &str) -> String {
+        let source_type = source_type.trim()
+            .trim_start_matches('&')
+            .trim_start_matches("mut ")
+            .trim_start_matches("const ")
+            .trim();
+        
+        if matches!(source_type, "char*" | "char *" | "const char*" | "wchar_t*") {
+            return "str".to_string();
+        }
+        // ML-style postfix generics: `int list`, `string option`
+        if let Some((inner, wrapper)) = source_type.rsplit_once(' ') {
+            match wrapper {
+                "list" | "array" | "seq" => return format!("List[{}]", self.map_type(inner)),
+                "option" | "voption" => return format!("Optional[{}]", self.map_type(inner)),
+                "set" => return format!("Set[{}]", self.map_type(inner)),
+                _ => {}
+            }
+        }
+        if let Some(inner) = source_type.strip_suffix('?') {
+            return format!("Optional[{}]", self.map_type(inner));
+        }
+        if let Some(inner) = source_type.strip_suffix("[]").or_else(|| source_type.strip_prefix("[]")) {
+            return format!("List[{}]", self.map_type(inner));
+        }
+        if let Some(rest) = source_type.strip_prefix("map[") {
+            // Go map[K]V
+            if let Some((key, value)) = rest.split_once(']') {
+                return format!("Dict[{}, {}]", self.map_type(key), self.map_type(value));
+            }
+        }
+        if source_type.starts_with('(') && source_type.ends_with(')') && source_type.len() > 2 {
+            let elements: Vec<String> = split_type_arguments(&source_type[1..source_type.len() - 1])
+                .iter().map(|e| self.map_type(e)).collect();
+            return format!("Tuple[{}]", elements.join(", "));
+        }
+        
+        let (base, arguments) = match source_type.find(['<', '[']) {
+            Some(open) if source_type.ends_with(['>', ']']) => {
+                (&source_type[..open], split_type_arguments(&source_type[open + 1..source_type.len() - 1]))
+            }
+            _ => (source_type, Vec::new()),
+        };
+        let base = base.rsplit(['.', ':']).next().unwrap_or(base);
+        let mapped: Vec<String> = arguments.iter().map(|a| self.map_type(a)).collect();
+        let argument = |i: usize| mapped.get(i).cloned().unwrap_or_else(|| "Any".to_string());
+        
+        match base {
+            "int" | "long" | "short" | "byte" | "sbyte" | "uint" | "ulong" | "ushort" | "i8" | "i16" | "i32" | "i64"
+            | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "int8" | "int16" | "int32"
+            | "int64" | "uint8" | "uint16" | "uint32" | "uint64" | "size_t" | "Int" | "Long" | "Short" | "Integer"
+            | "Byte" | "unsigned" | "signed" | "bigint" | "BigInteger" | "BigInt" => "int".to_string(),
+            "float" | "double" | "decimal" | "f32" | "f64" | "float32" | "float64" | "Float" | "Double" | "Decimal"
+            | "number" | "Number" => "float".to_string(),
+            "string" | "String" | "str" | "char" | "Char" | "rune" | "StringBuilder" => "str".to_string(),
+            "bool" | "boolean" | "Boolean" => "bool".to_string(),
+            "void" | "None" | "()" | "Unit" | "Void" => "None".to_string(),
+            "object" | "Object" | "Any" | "any" | "dynamic" | "interface{}" => "Any".to_string(),
+            "Vec" | "List" | "IList" | "IEnumerable" | "ICollection" | "ArrayList" | "LinkedList" | "VecDeque"
+            | "list" | "Sequence" | "Iterable" | "Array" | "MutableList" | "Seq" => format!("List[{}]", argument(0)),
+            "Set" | "HashSet" | "BTreeSet" | "ISet" | "set" | "frozenset" | "MutableSet" => format!("Set[{}]", argument(0)),
+            "Map" | "HashMap" | "BTreeMap" | "Dictionary" | "IDictionary" | "dict" | "Dict" | "MutableMap" | "Mapping" => {
+                format!("Dict[{}, {}]", argument(0), argument(1))
+            }
+            "Option" | "Optional" | "Nullable" => format!("Optional[{}]", argument(0)),
+            "Box" | "Rc" | "Arc" | "RefCell" | "Cell" | "Mutex" | "Cow" => argument(0),
+            "Result" => argument(0),
+            "tuple" | "Tuple" => format!("Tuple[{}]", mapped.join(", ")),
+            "error" | "Error" => "Exception".to_string(),
+            _ if mapped.is_empty() => base.to_string(),
+            _ => format!("{}[{}]", base, mapped.join(", ")),
         }
     }
 }
 
 pub struct RustGenerator;
 
+/// Per-function state for the Rust generator
+#[derive(Default)]
+struct RustFunctionContext {
+    /// The function raises errors, so it returns `Result` and returns are wrapped in `Ok`
+    fallible: bool,
+    /// Parameters passed as `&mut` to non-Copy values, which need `*` to reassign
+    borrowed_mut: HashSet<String>,
+    /// Names assigned more than once, which need `let mut` where no declaration says `reassigned`
+    reassigned: HashSet<String>,
+    /// Locals and parameters already in scope
+    declared: HashSet<String>,
+}
+
 impl Generator for RustGenerator {
     fn target_language(&self) -> Language {
         Language::Rust
     }
     
+    fn generate_with_config(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<String> {
+        generate_styled(self, uir, config)
+    }
+    
+    fn generate_with_fallbacks(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<(String, Vec<usize>)> {
+        generate_marked(|| self.generate_with_config(uir, config))
+    }
+    
     fn generate(&self, uir: &UIRNode) -> Result<String> {
         match &uir.node_type {
             NodeType::Module => {
+                let (scoped, state) = scope_module_variables(&module_assignments_declared(mark_fallible_calls(uir)));
+                let uir = &scoped;
                 let mut code = String::from("// Generated by Coalesce\n\n");
+                code.push_str(&documentation_lines(uir, "//!"));
+                if uir.documentation.is_some() {
+                    code.push('\n');
+                }
+                code.push_str(&ImportSet::collect(uir).render(&Language::Rust));
+                if !state.is_empty() {
+                    code.push_str(&self.generate_state(&state)?);
+                    code.push('\n');
+                }
                 
-                for child in &uir.children {
+                for child in module_items(uir).into_iter().filter(|c| !is_import(c) && !is_documentation(c)) {
+                    if child.node_type == NodeType::Variable && state.iter().any(|v| v.id == child.id) {
+                        continue;
+                    }
+                    // A later assignment to a module-level name, or a destructuring declaration,
+                    // has no place outside a function
+                    if module_assignment(child).is_some() || declared_variables(child).is_some() {
+                        code.push_str(&format!("// {}: {}\n", FALLBACK_TODO, original_text(child)));
+                        continue;
+                    }
+                    // Functions, structs and enums document themselves
+                    if !matches!(child.node_type, NodeType::Function | NodeType::Class | NodeType::Enum { .. } | NodeType::Property { .. }) {
+                        code.push_str(&documentation_lines(child, "///"));
+                    }
                     code.push_str(&self.generate(child)?);
                     code.push('\n');
                 }
@@ -220,144 +972,2934 @@ impl Generator for RustGenerator {
                 Ok(code)
             }
             NodeType::Function => {
-                self.generate_function(uir)
+                self.generate_function(uir, None)
             }
-            NodeType::Variable => {
-                Ok(uir.name.as_deref().unwrap_or("unknown_var").to_string())
+            NodeType::Property { .. } => {
+                let functions = accessor_functions(uir).iter()
+                    .map(|function| self.generate_function(function, None))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(functions.join("\n\n"))
             }
-            NodeType::Statement(StatementType::Return) => {
-                self.generate_return_statement(uir)
+            NodeType::Class => {
+                self.generate_struct(uir)
             }
-            NodeType::Expression(ExpressionType::Arithmetic) => {
-                self.generate_arithmetic_expression(uir)
+            NodeType::Variable => {
+                self.generate_const(uir)
             }
-            NodeType::Expression(ExpressionType::Variable) => {
-                Ok(uir.name.as_deref().unwrap_or("unknown").to_string())
+            NodeType::Enum { variants } => {
+                self.generate_enum(uir, variants)
             }
-            NodeType::Expression(ExpressionType::Literal) => {
-                // Extract literal value from original text
-                if let Some(original) = uir.metadata.annotations.get("original_text") {
-                    if let Some(text) = original.as_str() {
-                        Ok(text.to_string())
-                    } else {
-                        Ok("0".to_string()) // default literal
-                    }
-                } else {
-                    Ok("0".to_string()) // default literal
-                }
+            NodeType::Statement(_) | NodeType::ControlFlow(_) | NodeType::Comment { .. } => {
+                self.generate_statement(uir, &mut RustFunctionContext::default())
             }
             _ => {
-                Ok("// TODO: Implement UIR node generation\n".to_string())
+                self.generate_expression(uir)
             }
         }
     }
 }
 
 impl RustGenerator {
-    fn generate_function(&self, uir: &UIRNode) -> Result<String> {
-        let func_name = uir.name.as_deref().unwrap_or("generated_function");
+    /// A module-level variable no function writes, such as a COBOL data item, as a `const`
+    fn generate_const(&self, uir: &UIRNode) -> Result<String> {
+        let name = rust_variable_name(uir.name.as_deref().unwrap_or("value")).to_uppercase();
+        let const_type = match self.value_type(uir) {
+            value_type if value_type == "String" => "&str".to_string(),
+            value_type => value_type,
+        };
+        let value = match initializer(uir) {
+            Some(value) => self.converted(self.generate_expression(value)?, value, &const_type),
+            None => zero_value(&const_type),
+        };
+        Ok(format!("const {}: {} = {};", name, const_type, value))
+    }
+    
+    /// The module-level variables functions write, such as COBOL's working storage, as the
+    /// fields of one `State` those functions take by `&mut`, as Rust has no safe mutable globals
+    fn generate_state(&self, variables: &[UIRNode]) -> Result<String> {
+        let mut fields = Vec::new();
+        let mut values = Vec::new();
+        for variable in variables {
+            let name = rust_variable_name(variable.name.as_deref().unwrap_or("value"));
+            let field_type = self.value_type(variable);
+            let value = match initializer(variable) {
+                Some(value) if field_type == "String" && value.literal.is_some() => format!("{}.to_string()", self.generate_expression(value)?),
+                Some(value) => self.converted(self.generate_expression(value)?, value, &field_type),
+                None => zero_value(&field_type),
+            };
+            fields.push(format!("pub {}: {},", name, field_type));
+            values.push(format!("{}: {},", name, value));
+        }
+        let state = block("pub struct State", &indent(&fields.join("\n"), 1));
+        let default = block("fn default() -> Self", &indent(&block("Self", &indent(&values.join("\n"), 1)), 1));
+        Ok(format!(
+            "/// Module-level variables the functions change\n#[derive(Debug, Clone)]\n{}\n\n{}\n",
+            state,
+            block("impl Default for State", &indent(&default, 1))
+        ))
+    }
+    
+    /// The Rust type of a module-level variable: its declared one, else its value's
+    fn value_type(&self, uir: &UIRNode) -> String {
+        match (self.annotated_type(uir, "type"), initializer(uir).and_then(|value| value.literal.as_ref())) {
+            (Some(rust_type), _) => rust_type,
+            (None, Some(LiteralValue::String(_))) => "String".to_string(),
+            (None, Some(LiteralValue::Float(_))) => "f64".to_string(),
+            (None, Some(LiteralValue::Bool(_))) => "bool".to_string(),
+            (None, Some(LiteralValue::Char(_))) => "char".to_string(),
+            (None, _) => "i32".to_string(),
+        }
+    }
+    
+    /// The numeric Rust type `uir` evaluates to, where literals and declared types tell it
+    fn numeric_type(&self, uir: &UIRNode) -> Option<String> {
+        let numeric = |rust_type: String| rust_type.starts_with(['i', 'u', 'f']).then_some(rust_type).filter(|t| t.len() <= 5);
+        match &uir.node_type {
+            NodeType::Expression(ExpressionType::Literal) => match uir.literal {
+                Some(LiteralValue::Float(_)) => Some("f64".to_string()),
+                Some(LiteralValue::Int(_)) => Some("i32".to_string()),
+                _ => None,
+            },
+            NodeType::Variable | NodeType::Expression(ExpressionType::Variable) => self.annotated_type(uir, "type").and_then(numeric),
+            NodeType::Expression(ExpressionType::Arithmetic) if uir.children.len() == 2 => {
+                let (left, right) = (self.numeric_type(&uir.children[0]), self.numeric_type(&uir.children[1]));
+                match (left, right) {
+                    (Some(left), _) if left.starts_with('f') => Some(left),
+                    (_, Some(right)) if right.starts_with('f') => Some(right),
+                    (Some(left), Some(_)) => Some(left),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+    
+    /// `code`, the value of `uir`, converted to the numeric `target` type when it is another one:
+    /// Rust does no implicit conversion between integers and floats
+    fn converted(&self, code: String, uir: &UIRNode, target: &str) -> String {
+        let Some(source) = self.numeric_type(uir) else { return code };
+        if source == target || !target.starts_with(['i', 'u', 'f']) || target.len() > 5 {
+            return code;
+        }
+        if uir.node_type == NodeType::Expression(ExpressionType::Literal) && target.starts_with('f') && !code.contains('.') {
+            return format!("{}.0", code);
+        }
+        let operand = if uir.children.len() >= 2 && expression_operator(uir).is_some() { format!("({})", code) } else { code };
+        format!("({} as {})", operand, target)
+    }
+    
+    /// Emit a free function, or a method when `receiver` is `&self`/`&mut self`
+    fn generate_function(&self, uir: &UIRNode, receiver: Option<&str>) -> Result<String> {
+        let renamed = parameters_renamed(uir);
+        let uir = renamed.as_ref().unwrap_or(uir);
+        let func_name = to_snake_case(uir.name.as_deref().unwrap_or("generated_function"));
         
-        // Extract parameters from children
-        let mut parameters = Vec::new();
+        let mut parameters: Vec<&UIRNode> = Vec::new();
         let mut statements = Vec::new();
-        
         for child in &uir.children {
             match &child.node_type {
-                NodeType::Variable => {
-                    if let Some(param_name) = &child.name {
-                        // In Rust, we need to add basic type annotations
-                        parameters.push(format!("{}: i32", param_name)); // Simple default type
-                    }
-                }
-                NodeType::Statement(_) => {
-                    statements.push(child);
-                }
-                _ => {
-                    statements.push(child);
-                }
+                NodeType::Variable => parameters.push(child),
+                NodeType::Import { .. } => {}
+                _ => statements.push(child),
             }
         }
         
-        let params_str = parameters.join(", ");
+        let mut context = RustFunctionContext {
+            fallible: statements.iter().any(|s| raises(s)),
+            ..RustFunctionContext::default()
+        };
+        let mut assignments = HashMap::new();
+        for statement in &statements {
+            self.count_assignments(statement, &mut assignments);
+        }
+        context.reassigned = assignments.iter()
+            .filter(|(_, &count)| count > 1)
+            .map(|(name, _)| name.clone())
+            .collect();
         
-        // Generate function body
-        let body = if statements.is_empty() {
-            "    // Empty function".to_string()
-        } else {
-            let mut body_code = String::new();
-            for (i, stmt) in statements.iter().enumerate() {
-                let stmt_code = self.generate(stmt)?;
-                let is_last = i == statements.len() - 1;
-                
-                // In Rust, the last statement can be an expression (no semicolon)
-                // if it's a return statement
-                if is_last && matches!(stmt.node_type, NodeType::Statement(StatementType::Return)) {
-                    // Convert "return expr" to just "expr" for Rust
-                    let expr_code = if stmt_code.trim().starts_with("return ") {
-                        stmt_code.trim().strip_prefix("return ").unwrap_or(stmt_code.trim())
-                    } else {
-                        stmt_code.trim()
-                    };
-                    body_code.push_str(&format!("    {}\n", expr_code));
-                } else {
-                    for line in stmt_code.lines() {
-                        if !line.trim().is_empty() {
-                            body_code.push_str(&format!("    {};\n", line.trim()));
-                        }
+        let mut params = Vec::new();
+        if let Some(receiver) = receiver {
+            params.push(receiver.to_string());
+        }
+        // Module-level state, reached through the struct holding it
+        if uir.metadata.annotations.get("uses_state").and_then(|v| v.as_bool()).unwrap_or(false) {
+            params.push("state: &mut State".to_string());
+        }
+        for param in parameters {
+            let Some(original_name) = param.name.as_deref() else { continue };
+            if matches!(original_name, "self" | "this" | "cls") {
+                continue;
+            }
+            let name = to_snake_case(original_name);
+            let param_type = self.annotated_type(param, "type").unwrap_or_else(|| "i32".to_string());
+            let assigned = reassigned(param).unwrap_or_else(|| assignments.contains_key(&name));
+            let mutated = assigned
+                || param.metadata.annotations.get("mutable").and_then(|v| v.as_bool()).unwrap_or(false)
+                || param.metadata.semantic_tags.iter().any(|t| t == "mutable")
+                || statements.iter().any(|s| self.mutates(s, original_name));
+            
+            let binding = if assigned { "mut " } else { "" };
+            // A declared borrow or pointer decides how the parameter is passed; otherwise the body does
+            let declaration = match (param.ownership, declared_type(param, "type")) {
+                (Some(Ownership::Reference { mutable } | Ownership::Pointer { mutable }), Some(source_type)) => {
+                    // C strings keep their `String` mapping rather than becoming `char` references
+                    let target = if param_type == "String" { param_type } else { self.map_type(pointee(source_type)) };
+                    if mutable {
+                        context.borrowed_mut.insert(name.clone());
+                        format!("{}: &mut {}", name, target)
+                    } else {
+                        format!("{}: {}", name, self.borrowed(&target))
                     }
                 }
-            }
-            body_code.trim_end().to_string()
+                // Moved or copied in, so changing it needs only a `mut` binding
+                (Some(Ownership::Owned | Ownership::ByValue), _) => {
+                    format!("{}{}: {}", if mutated { "mut " } else { "" }, name, param_type)
+                }
+                _ if self.is_copy(&param_type) => format!("{}{}: {}", binding, name, param_type),
+                _ if mutated => {
+                    context.borrowed_mut.insert(name.clone());
+                    format!("{}: &mut {}", name, param_type)
+                }
+                _ => format!("{}: {}", name, self.borrowed(&param_type)),
+            };
+            params.push(declaration);
+            context.declared.insert(name);
+        }
+        
+        let value_type = match self.annotated_type(uir, "return_type") {
+            Some(return_type) if return_type != "()" => Some(return_type),
+            Some(_) => None,
+            // No declared type: keep the historical i32 default when something is returned
+            None if statements.iter().any(|s| returns_value(s)) => Some("i32".to_string()),
+            None => None,
+        };
+        let return_type = match (&value_type, context.fallible) {
+            (Some(value_type), true) => format!(" -> Result<{}, Box<dyn std::error::Error>>", value_type),
+            (None, true) => " -> Result<(), Box<dyn std::error::Error>>".to_string(),
+            (Some(value_type), false) => format!(" -> {}", value_type),
+            (None, false) => String::new(),
         };
         
-        // Determine return type based on content (simple heuristic)
-        let return_type = if statements.iter().any(|s| matches!(s.node_type, NodeType::Statement(StatementType::Return))) {
-            " -> i32" // Simple default return type
+        // Trailing comments do not stop the last statement being the tail
+        let last = statements.iter().rposition(|s| !is_comment(s));
+        let mut body_code = String::new();
+        for (i, stmt) in statements.iter().enumerate() {
+            let is_last = Some(i) == last;
+            
+            // The last return becomes the tail expression
+            if is_last && matches!(stmt.node_type, NodeType::Statement(StatementType::Return)) && !stmt.children.is_empty() {
+                let expr_code = self.generate_expression(&stmt.children[0])?;
+                let tail = if context.fallible { format!("Ok({})", expr_code) } else { expr_code };
+                body_code.push_str(&format!("{}\n", tail));
+            } else {
+                let code = self.generate_statement(stmt, &mut context)?;
+                if !code.is_empty() {
+                    body_code.push_str(&code);
+                    body_code.push('\n');
+                }
+            }
+        }
+        let ends_with_value = last
+            .is_some_and(|i| matches!(statements[i].node_type, NodeType::Statement(StatementType::Return | StatementType::Throw)));
+        if context.fallible && value_type.is_none() && !ends_with_value {
+            body_code.push_str("Ok(())\n");
+        }
+        
+        let body = if body_code.trim().is_empty() {
+            indent("// Empty function", 1)
         } else {
-            ""
+            indent(body_code.trim_end(), 1)
         };
         
-        Ok(format!("fn {}({}){} {{\n{}\n}}", func_name, params_str, return_type, body))
+        let visibility = if uir.visibility == Some(Visibility::Public) { "pub " } else { "" };
+        let asyncness = if is_async(uir) { "async " } else { "" };
+        let signature = argument_list(&format!("{}{}fn {}", visibility, asyncness, func_name), &params);
+        Ok(format!("{}{}", documentation_lines(uir, "///"), block(&format!("{}{}", signature, return_type), &body)))
     }
     
-    fn generate_return_statement(&self, uir: &UIRNode) -> Result<String> {
-        if uir.children.is_empty() {
-            Ok("return".to_string())
+    /// Classes become a struct for their fields plus an impl block for their methods
+    fn generate_struct(&self, uir: &UIRNode) -> Result<String> {
+        let struct_name = to_pascal_case(uir.name.as_deref().unwrap_or("GeneratedStruct"));
+        
+        let mut fields = Vec::new();
+        let mut field_names = Vec::new();
+        let mut methods = Vec::new();
+        for child in &uir.children {
+            match &child.node_type {
+                _ if is_field(child) => {
+                    if let Some(field_name) = &child.name {
+                        let field_type = self.annotated_type(child, "type").unwrap_or_else(|| "i32".to_string());
+                        let field_name = to_snake_case(field_name);
+                        fields.push(format!(
+                            "{}{}{}: {},",
+                            documentation_lines(child, "///"), rust_visibility(child.visibility), field_name, field_type
+                        ));
+                        field_names.push(field_name);
+                    }
+                }
+                // Fields declared in a class body, as `x: int = 0` in Python
+                NodeType::Statement(StatementType::Expression) => {
+                    for assignment in child.children.iter().filter(|c| c.node_type == NodeType::Expression(ExpressionType::Assignment)) {
+                        if let Some(field_name) = assignment.children.first().and_then(|t| t.name.as_deref()) {
+                            let field_type = self.annotated_type(assignment, "type").unwrap_or_else(|| "i32".to_string());
+                            let field_name = to_snake_case(field_name);
+                            fields.push(format!("pub {}: {},", field_name, field_type));
+                            field_names.push(field_name);
+                        }
+                    }
+                }
+                // A getter named after the property and a `set_` method taking the new value
+                NodeType::Property { .. } => {
+                    for (accessor, method) in accessors(child).into_iter().zip(accessor_functions(child)) {
+                        let receiver = if is_setter(accessor) { "&mut self" } else { "&self" };
+                        methods.push(indent(&self.generate_function(&method, Some(receiver))?, 1));
+                    }
+                }
+                NodeType::Function => {
+                    let receiver = if is_static(child) {
+                        None
+                    } else if child.children.iter().any(|c| self.assigns_to_self(c)) {
+                        Some("&mut self")
+                    } else {
+                        Some("&self")
+                    };
+                    let mut method: Vec<String> = attached_comments(&uir.children, child).into_iter()
+                        .map(|comment| comment_code(comment, "//", "///", Some(("/*", "*/"))))
+                        .collect();
+                    method.push(self.generate_function(child, receiver)?);
+                    methods.push(indent(&method.join("\n"), 1));
+                }
+                _ => {}
+            }
+        }
+        
+        // Dynamic languages declare fields by assigning them in methods
+        let mut assigned_fields = Vec::new();
+        for child in &uir.children {
+            self.collect_self_fields(child, &mut assigned_fields);
+        }
+        for field_name in assigned_fields {
+            if !field_names.contains(&field_name) {
+                fields.push(format!("pub {}: i32,", field_name));
+                field_names.push(field_name);
+            }
+        }
+        
+        let mut code = documentation_lines(uir, "///");
+        if fields.is_empty() {
+            code.push_str(&format!("pub struct {};", struct_name));
         } else {
-            let expr_code = self.generate(&uir.children[0])?;
-            Ok(format!("return {}", expr_code.trim()))
+            code.push_str(&block(&format!("pub struct {}", struct_name), &indent(&fields.join("\n"), 1)));
+        }
+        if !methods.is_empty() {
+            code.push_str(&format!("\n\n{}", block(&format!("impl {}", struct_name), &methods.join("\n\n"))));
         }
+        Ok(code)
     }
     
-    fn generate_arithmetic_expression(&self, uir: &UIRNode) -> Result<String> {
-        // Same logic as Python generator
-        if uir.children.len() == 2 {
-            let left = self.generate(&uir.children[0])?.trim().to_string();
-            let right = self.generate(&uir.children[1])?.trim().to_string();
-            
-            let operator = if let Some(original) = uir.metadata.annotations.get("original_text") {
-                if let Some(text) = original.as_str() {
-                    if text.contains(" + ") {
-                        " + "
-                    } else if text.contains(" - ") {
-                        " - "
-                    } else if text.contains(" * ") {
-                        " * "
-                    } else if text.contains(" / ") {
-                        " / "
+    /// An enum with each variant's fields, or its discriminant when the source gave one
+    fn generate_enum(&self, uir: &UIRNode, variants: &[EnumVariant]) -> Result<String> {
+        let enum_name = to_pascal_case(uir.name.as_deref().unwrap_or("GeneratedEnum"));
+        let values = enum_values(variants);
+        let lines: Vec<String> = variants.iter().zip(values)
+            .map(|(variant, value)| {
+                let name = to_pascal_case(&variant.name);
+                let fields: Vec<(Option<&str>, String)> = variant.fields.iter()
+                    .map(|field| (field.name.as_deref(), self.map_type(&field.field_type)))
+                    .collect();
+                if fields.is_empty() {
+                    return match variant.value {
+                        Some(_) => format!("{} = {},", name, value),
+                        None => format!("{},", name),
+                    };
+                }
+                if fields.iter().all(|(field_name, _)| field_name.is_some()) {
+                    let fields: Vec<String> = fields.into_iter()
+                        .map(|(field_name, field_type)| format!("{}: {}", to_snake_case(field_name.unwrap_or_default()), field_type))
+                        .collect();
+                    format!("{} {{ {} }},", name, fields.join(", "))
+                } else {
+                    let types: Vec<String> = fields.into_iter().map(|(_, field_type)| field_type).collect();
+                    format!("{}({}),", name, types.join(", "))
+                }
+            })
+            .collect();
+        Ok(format!("{}{}", documentation_lines(uir, "///"), block(&format!("pub enum {}", enum_name), &indent(&lines.join("\n"), 1))))
+    }
+    
+    fn generate_block(&self, statements: &[&UIRNode], context: &mut RustFunctionContext) -> Result<String> {
+        let mut code = String::new();
+        for stmt in statements {
+            let statement = self.generate_statement(stmt, context)?;
+            if !statement.is_empty() {
+                code.push_str(&statement);
+                code.push('\n');
+            }
+        }
+        Ok(indent(code.trim_end(), 1))
+    }
+    
+    fn generate_statement(&self, uir: &UIRNode, context: &mut RustFunctionContext) -> Result<String> {
+        match &uir.node_type {
+            NodeType::Comment { .. } => Ok(comment_code(uir, "//", "///", Some(("/*", "*/")))),
+            NodeType::Statement(StatementType::Return) => {
+                if uir.children.is_empty() {
+                    Ok(if context.fallible { "return Ok(());" } else { "return;" }.to_string())
+                } else {
+                    let expr_code = self.generate_expression(&uir.children[0])?;
+                    if context.fallible {
+                        Ok(format!("return Ok({});", expr_code))
                     } else {
-                        " + "
+                        Ok(format!("return {};", expr_code))
+                    }
+                }
+            }
+            NodeType::Statement(StatementType::Throw) => {
+                Ok(format!("return Err({}.into());", raised_message(uir)))
+            }
+            NodeType::Statement(StatementType::Break) => Ok("break;".to_string()),
+            NodeType::Statement(StatementType::Continue) => Ok("continue;".to_string()),
+            NodeType::Statement(_) => {
+                // Blocks and expression statements wrap the real statements
+                let mut lines = Vec::new();
+                for child in uir.children.iter().filter(|c| !is_punctuation(c)) {
+                    lines.push(self.generate_statement(child, context)?);
+                    lines.retain(|line| !line.is_empty());
+                }
+                Ok(lines.join("\n"))
+            }
+            NodeType::Variable => {
+                let name = rust_variable_name(uir.name.as_deref().unwrap_or("value"));
+                let value = match (destructured_value(uir, |key| format!(".{}", key)), initializer(uir)) {
+                    (Some(value), _) => Some(value),
+                    (None, Some(value)) => Some(self.generate_expression(value)?),
+                    (None, None) => None,
+                };
+                let mutable = reassigned(uir).unwrap_or_else(|| context.reassigned.contains(&name));
+                let binding = if mutable { "let mut" } else { "let" };
+                context.declared.insert(name.clone());
+                Ok(match (value, self.annotated_type(uir, "type")) {
+                    (Some(value), _) => format!("{} {} = {};", binding, name, value),
+                    (None, Some(rust_type)) => format!("{} {}: {};", binding, name, rust_type),
+                    (None, None) => format!("{} {};", binding, name),
+                })
+            }
+            NodeType::Expression(ExpressionType::Assignment) if uir.children.len() >= 2 => {
+                let target = self.generate_expression(&uir.children[0])?;
+                // Stored as the target's type where that is known, as COBOL truncates into an integer item
+                let target_type = self.numeric_type(&uir.children[0]).unwrap_or_default();
+                let stored = |value: &UIRNode| -> Result<String> { Ok(self.converted(self.generate_expression(value)?, value, &target_type)) };
+                let (symbol, value) = match assignment_form(uir, &Language::Rust) {
+                    AssignmentForm::Plain => ("=", stored(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Compound(symbol) => (symbol, stored(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Expanded(operation) => ("=", stored(&operation)?),
+                };
+                // A compound assignment updates a variable that already exists
+                if context.borrowed_mut.contains(&target) {
+                    Ok(format!("*{} {} {};", target, symbol, value))
+                } else if context.declared.contains(&target) || target.contains('.') || uir.operator.is_some() {
+                    Ok(format!("{} {} {};", target, symbol, value))
+                } else {
+                    context.declared.insert(target.clone());
+                    let mutable = reassigned(&uir.children[0]).unwrap_or_else(|| context.reassigned.contains(&target));
+                    let binding = if mutable { "let mut" } else { "let" };
+                    Ok(format!("{} {} = {};", binding, target, value))
+                }
+            }
+            NodeType::ControlFlow(ControlFlowType::Conditional) => {
+                self.generate_conditional(uir, context)
+            }
+            NodeType::ControlFlow(ControlFlowType::Loop(loop_type)) => {
+                let parts = loop_parts(uir);
+                let mut code = String::new();
+                let lowered: Vec<UIRNode>;
+                let (header, body): (String, Vec<&UIRNode>) = match (loop_type, parts.target, parts.iterable, counted_loop(&parts)) {
+                    (LoopType::ForEach, Some(target), Some(iterable), _) => {
+                        let iterable = self.generate_expression(iterable)?;
+                        let iterable = if iterates_keys(uir) { format!("{}.keys()", iterable) } else { iterable };
+                        (format!("for {} in {}", self.generate_expression(target)?, iterable), parts.body.clone())
                     }
+                    (LoopType::For, _, _, Some(counted)) => {
+                        (format!("for {} in {}", rust_variable_name(counted.variable), self.generate_range(&counted)?), parts.body.clone())
+                    }
+                    (LoopType::While, ..) if parts.condition.is_some() => {
+                        (format!("while {}", self.generate_expression(parts.condition.unwrap_or(uir))?), parts.body.clone())
+                    }
+                    // Any other `for (init; condition; update)` runs as a `while`, and `do ... while`
+                    // as a `loop` that tests its condition last
+                    _ => {
+                        if let Some(initializer) = parts.initializer {
+                            code.push_str(&format!("{}\n", self.generate_statement(initializer, context)?));
+                        }
+                        lowered = lowered_body(uir, &parts);
+                        let header = match parts.condition {
+                            Some(condition) if *loop_type != LoopType::DoWhile => format!("while {}", self.generate_expression(condition)?),
+                            _ => "loop".to_string(),
+                        };
+                        (header, lowered.iter().collect())
+                    }
+                };
+                code.push_str(&block(&header, &self.generate_block(&body, context)?));
+                // Python's loop `else` runs unless the loop broke out
+                if parts.body.iter().any(|statement| breaks(statement)) && !parts.otherwise.is_empty() {
+                    code.push_str(&format!("\n// {}", FALLBACK_TODO));
                 } else {
-                    " + "
+                    for statement in &parts.otherwise {
+                        code.push('\n');
+                        code.push_str(&self.generate_statement(statement, context)?);
+                    }
+                }
+                Ok(code)
+            }
+            NodeType::ControlFlow(ControlFlowType::Try) => {
+                self.generate_try(uir, context)
+            }
+            NodeType::ControlFlow(ControlFlowType::Goto) => match paragraph_jump(uir) {
+                Some(jump) => self.generate_statement(&jump, context),
+                None => Ok(format!("// {}", FALLBACK_TODO)),
+            },
+            // Python's `pass` only fills an otherwise empty block, and the names `global` declares
+            // are reached through `state`
+            NodeType::Expression(_) if has_tag(uir, &["pass_statement", "global_statement"]) => Ok(String::new()),
+            NodeType::Expression(_) => {
+                Ok(format!("{};", self.generate_expression(uir)?))
+            }
+            _ => {
+                Ok(format!("// {}", FALLBACK_TODO))
+            }
+        }
+    }
+    
+    fn generate_conditional(&self, uir: &UIRNode, context: &mut RustFunctionContext) -> Result<String> {
+        let Some(condition) = uir.children.first() else {
+            return Ok(format!("// {}", FALLBACK_TODO));
+        };
+        
+        let mut then_branch = Vec::new();
+        let mut else_branches = Vec::new();
+        for child in &uir.children[1..] {
+            if has_tag(child, &["else_clause", "elif_clause", "else"]) {
+                else_branches.push(child);
+            } else {
+                then_branch.push(child);
+            }
+        }
+        
+        let mut code = block(&format!("if {}", self.generate_expression(condition)?), &self.generate_block(&then_branch, context)?);
+        for branch in else_branches {
+            if has_tag(branch, &["elif_clause"]) {
+                code.push_str(&continued("else "));
+                code.push_str(&self.generate_conditional(branch, context)?);
+            } else {
+                let body: Vec<&UIRNode> = branch.children.iter().collect();
+                code.push_str(&block(&continued("else"), &self.generate_block(&body, context)?));
+            }
+        }
+        Ok(code)
+    }
+    
+    /// try/catch runs the body in a closure returning `Result` and matches on its error. Names
+    /// the body binds are returned from the closure, so they stay in scope after the try, and
+    /// the handler gives its own values for them
+    fn generate_try(&self, uir: &UIRNode, context: &mut RustFunctionContext) -> Result<String> {
+        let mut body = Vec::new();
+        let mut handlers = Vec::new();
+        let mut cleanup = Vec::new();
+        for child in &uir.children {
+            if is_cleanup(child) {
+                cleanup.push(child);
+            } else if is_handler(child) {
+                handlers.push(child);
+            } else {
+                body.push(child);
+            }
+        }
+        
+        let cleanup_statements: Vec<&UIRNode> = cleanup.iter().flat_map(|block| block.children.iter()).collect();
+        // Nothing to catch: the body runs where it stands, then the cleanup
+        if handlers.is_empty() && !body.iter().any(|statement| raises(statement)) {
+            let mut code = Vec::new();
+            for statement in body.iter().copied().chain(cleanup_statements) {
+                let statement = self.generate_statement(statement, context)?;
+                if !statement.is_empty() {
+                    code.push(statement);
                 }
+            }
+            return Ok(code.join("\n"));
+        }
+        
+        let bound: Vec<String> = bound_names(&body, &context.declared, rust_variable_name).into_iter().map(|(name, _)| name).collect();
+        let values = match bound.as_slice() {
+            [name] => name.clone(),
+            names => format!("({})", names.join(", ")),
+        };
+        let mut body_context = RustFunctionContext { fallible: false, ..RustFunctionContext::default() };
+        body_context.declared = context.declared.clone();
+        body_context.reassigned = context.reassigned.clone();
+        let closure = format!(
+            "(|| -> Result<_, Box<dyn std::error::Error>> {{\n{}\n{}\n}})()",
+            self.generate_block(&body, &mut body_context)?,
+            indent(&format!("Ok({})", values), 1)
+        );
+        
+        let patterns: Vec<String> = bound.iter()
+            .map(|name| if context.reassigned.contains(name) { format!("mut {}", name) } else { name.clone() })
+            .collect();
+        let binding = match patterns.as_slice() {
+            [] => "let result".to_string(),
+            [pattern] => format!("let {}", pattern),
+            patterns => format!("let ({})", patterns.join(", ")),
+        };
+        // Uncaught errors propagate once the cleanup has run
+        let mut propagated = None;
+        let mut code = if handlers.is_empty() && cleanup_statements.is_empty() && bound.is_empty() {
+            format!("{}?;", closure)
+        } else if handlers.is_empty() && cleanup_statements.is_empty() {
+            format!("{} = {}?;", binding, closure)
+        } else if handlers.is_empty() {
+            propagated = Some(if bound.is_empty() { "result?;".to_string() } else { format!("{} = result?;", binding) });
+            format!("let result = {};", closure)
+        } else {
+            // Handler bodies are their block children; exception patterns are dropped
+            let handler_body: Vec<&UIRNode> = handlers.iter()
+                .flat_map(|h| h.children.iter())
+                .filter(|c| matches!(c.node_type, NodeType::Statement(_) | NodeType::ControlFlow(_)))
+                .collect();
+            let error = handlers.iter().find_map(|h| handler_binding(h)).map_or("e".to_string(), |b| to_snake_case(&b));
+            let mut handler_context = RustFunctionContext { declared: context.declared.clone(), ..RustFunctionContext::default() };
+            handler_context.fallible = context.fallible;
+            handler_context.borrowed_mut = context.borrowed_mut.clone();
+            handler_context.reassigned = context.reassigned.clone();
+            let mut handler_code = self.generate_block(&handler_body, &mut handler_context)?;
+            if bound.is_empty() {
+                block(&format!("{} = {};\nif let Err({}) = result", binding, closure, error), &handler_code)
             } else {
-                " + "
+                // A name the handler leaves unset takes its type's default
+                let fallback: Vec<String> = bound.iter()
+                    .map(|name| if handler_context.declared.contains(name) { name.clone() } else { "Default::default()".to_string() })
+                    .collect();
+                let fallback = match fallback.as_slice() {
+                    [value] => value.clone(),
+                    values => format!("({})", values.join(", ")),
+                };
+                handler_code = [handler_code, indent(&fallback, 1)].iter().filter(|c| !c.is_empty()).cloned().collect::<Vec<_>>().join("\n");
+                let arms = format!("Ok(values) => values,\n{}", block(&format!("Err({}) =>", error), &handler_code));
+                format!("{};", block(&format!("{} = match {}", binding, closure), &indent(&arms, 1)))
+            }
+        };
+        context.declared.extend(bound);
+        
+        // finally runs after the handler, at the same level
+        for statement in cleanup_statements {
+            let statement = self.generate_statement(statement, context)?;
+            if !statement.is_empty() {
+                code.push('\n');
+                code.push_str(&statement);
+            }
+        }
+        if let Some(propagated) = propagated {
+            code.push('\n');
+            code.push_str(&propagated);
+        }
+        Ok(code)
+    }
+    
+    fn generate_expression(&self, uir: &UIRNode) -> Result<String> {
+        let original = uir.metadata.annotations.get("original_text").and_then(|v| v.as_str()).unwrap_or("");
+        // Rust the parser read, such as a library rule's code, stands as written
+        if !original.is_empty() && uir.metadata.source_language == Language::Rust {
+            return Ok(original.to_string());
+        }
+        if let Some((collection, index)) = subscript_parts(uir) {
+            return Ok(format!("{}[{}]", self.generate_expression(collection)?, self.generate_expression(index)?));
+        }
+        // An iterator chain: filters borrow each item, which a `&` pattern copies out
+        if let Some(comprehension) = comprehension_parts(uir) {
+            let target = self.generate_expression(comprehension.target)?;
+            let mut code = format!("{}.iter().cloned()", self.generate_expression(comprehension.iterable)?);
+            for condition in &comprehension.conditions {
+                code.push_str(&format!(".filter(|&{}| {})", target, self.generate_expression(condition)?));
+            }
+            let element = self.generate_expression(comprehension.element)?;
+            if element != target {
+                code.push_str(&format!(".map(|{}| {})", target, element));
+            }
+            code.push_str(".collect::<Vec<_>>()");
+            return Ok(code);
+        }
+        // Rust's `if` is an expression
+        if let Some((condition, value, otherwise)) = ternary_parts(uir) {
+            let otherwise_code = self.generate_expression(otherwise)?;
+            let alternative = match ternary_parts(otherwise) {
+                Some(_) => otherwise_code,
+                None => format!("{{ {} }}", otherwise_code),
             };
-            
-            Ok(format!("{}{}{}", left, operator, right))
+            return Ok(format!(
+                "if {} {{ {} }} else {}",
+                ternary_operand(self.generate_expression(condition)?, condition),
+                self.generate_expression(value)?,
+                alternative,
+            ));
+        }
+        match &uir.node_type {
+            NodeType::Variable | NodeType::Expression(ExpressionType::Variable) if original.contains('.') && uir.children.len() >= 2 => {
+                // Member access such as `items.append`; Python's list append is Vec::push
+                let mut parts = Vec::new();
+                for child in uir.children.iter().filter(|c| original_text(c) != ".") {
+                    parts.push(self.generate_expression(child)?);
+                }
+                if parts.len() > 1 && parts.last().is_some_and(|p| p == "append") {
+                    parts.pop();
+                    parts.push("push".to_string());
+                }
+                Ok(parts.join("."))
+            }
+            NodeType::Variable | NodeType::Expression(ExpressionType::Variable) => {
+                let name = match uir.metadata.source_language {
+                    // Erlang's variables all start upper case
+                    Language::Erlang => to_snake_case(uir.name.as_deref().unwrap_or("unknown")),
+                    _ => rust_variable_name(uir.name.as_deref().unwrap_or("unknown")),
+                };
+                if uir.metadata.semantic_tags.iter().any(|t| t == "module_constant") {
+                    Ok(name.to_uppercase())
+                } else if has_tag(uir, &["module_state"]) {
+                    Ok(format!("state.{}", name))
+                } else {
+                    Ok(name)
+                }
+            }
+            NodeType::Expression(ExpressionType::Literal) => {
+                if let Some(value) = &uir.literal {
+                    return Ok(literal_code(value, &Language::Rust));
+                }
+                // Python's `return a, b` returns a tuple
+                if has_tag(uir, &["expression_list"]) && !uir.children.is_empty() {
+                    let values = uir.children.iter().filter(|c| !is_punctuation(c)).map(|c| self.generate_expression(c)).collect::<Result<Vec<_>>>()?;
+                    return Ok(format!("({})", values.join(", ")));
+                }
+                Ok(match original {
+                    "" => "0".to_string(),
+                    "True" | "true" => "true".to_string(),
+                    "False" | "false" => "false".to_string(),
+                    "None" | "null" | "nil" => "None".to_string(),
+                    "this" => "self".to_string(),
+                    text if text.starts_with("this.") => format!("self.{}", &text[5..]),
+                    text if text.starts_with('\'') && text.ends_with('\'') && text.len() > 3 => {
+                        format!("\"{}\"", &text[1..text.len() - 1])
+                    }
+                    text => verbatim(uir, text, Language::Rust),
+                })
+            }
+            NodeType::Expression(ExpressionType::Arithmetic | ExpressionType::Comparison | ExpressionType::Logical) => {
+                self.generate_operator_expression(uir)
+            }
+            NodeType::Expression(ExpressionType::FunctionCall) => {
+                // Macro arguments are tokens rather than expressions: keep them as written
+                if let Some((name, tokens)) = macro_invocation(uir) {
+                    return Ok(format!("{}!{}", name, original_text(tokens)));
+                }
+                let (function, arguments) = call_parts(uir);
+                let callee = match function {
+                    Some(function) => self.generate_expression(function)?,
+                    None => uir.name.as_deref().map_or_else(|| "unknown".to_string(), to_snake_case),
+                };
+                let mut arguments = arguments.into_iter().map(|a| self.generate_expression(a)).collect::<Result<Vec<_>>>()?;
+                if has_tag(uir, &["passes_state"]) {
+                    arguments.insert(0, "state".to_string());
+                }
+                // The caller returns `Result` too, or is the closure a try block lowers to
+                let propagate = if has_tag(uir, &["fallible_call"]) { "?" } else { "" };
+                Ok(format!("{}{}", argument_list(&callee, &arguments), propagate))
+            }
+            NodeType::Expression(ExpressionType::Assignment) if uir.children.len() >= 2 => {
+                let target = self.generate_expression(&uir.children[0])?;
+                let (symbol, value) = match assignment_form(uir, &Language::Rust) {
+                    AssignmentForm::Plain => ("=", self.generate_expression(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Compound(symbol) => (symbol, self.generate_expression(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Expanded(operation) => ("=", self.generate_expression(&operation)?),
+                };
+                Ok(format!("{} {} {}", target, symbol, value))
+            }
+            NodeType::Expression(ExpressionType::Await) => match operand(uir) {
+                Some(future) => Ok(format!("{}.await", self.generate_expression(future)?)),
+                None => Ok("unknown_expression".to_string()),
+            },
+            NodeType::Closure { captures, capture_mode } => {
+                let renamed = parameters_renamed(uir);
+                let (parameters, body) = closure_parts(renamed.as_ref().unwrap_or(uir));
+                let params: Vec<String> = parameters.iter().filter_map(|p| p.name.as_deref()).map(to_snake_case).collect();
+                let body_code = match closure_value(&body) {
+                    Some(value) => self.generate_expression(value)?,
+                    None => {
+                        let mut context = RustFunctionContext {
+                            declared: params.iter().cloned().collect(),
+                            ..RustFunctionContext::default()
+                        };
+                        format!("{{\n{}\n}}", self.generate_block(&body, &mut context)?)
+                    }
+                };
+                // Captures the source copied must be owned by the closure; shared ones are borrowed
+                let capture = if *capture_mode == CaptureMode::ByValue && !captures.is_empty() { "move " } else { "" };
+                Ok(format!("{}|{}| {}", capture, params.join(", "), body_code))
+            }
+            _ if !original.is_empty() => Ok(format!("/* {} */", verbatim(uir, original, Language::Rust).replace("*/", "* /"))),
+            _ => Ok("unknown_expression".to_string()),
+        }
+    }
+    
+    fn generate_operator_expression(&self, uir: &UIRNode) -> Result<String> {
+        let untranslated = || format!("/* {} */", verbatim(uir, original_text(uir), Language::Rust).replace("*/", "* /"));
+        match uir.children.as_slice() {
+            [operand] => {
+                let operand_code = grouped(self.generate_expression(operand)?, uir, operand, true);
+                match lowered_operation(uir, std::slice::from_ref(&operand_code), &Language::Rust) {
+                    Lowering::Code(code) => return Ok(code),
+                    Lowering::Untranslated => return Ok(untranslated()),
+                    Lowering::Operator => {}
+                }
+                if expression_operator(uir) == Some(Operator::Not) {
+                    Ok(format!("!{}", operand_code))
+                } else if expression_operator(uir) == Some(Operator::Negate) {
+                    Ok(format!("-{}", operand_code))
+                } else {
+                    Ok(operand_code)
+                }
+            }
+            [left, right] if expression_operator(uir) == Some(Operator::Power) => {
+                self.generate_power(left, right)
+            }
+            [left, right] if expression_operator(uir) == Some(Operator::IntegerDivide) => {
+                self.generate_floor_division(left, right)
+            }
+            [left, right] => {
+                // An integer operand mixed with a float one converts first
+                let float = [left, right].into_iter().filter_map(|operand| self.numeric_type(operand)).find(|t| t.starts_with('f'));
+                let operand = |operand: &UIRNode, right: bool| -> Result<String> {
+                    let code = self.generate_expression(operand)?;
+                    Ok(match (&float, self.numeric_type(operand)) {
+                        (Some(float), Some(operand_type)) if !operand_type.starts_with('f') => self.converted(code, operand, float),
+                        _ => grouped(code, uir, operand, right),
+                    })
+                };
+                let (left_code, right_code) = (operand(left, false)?, operand(right, true)?);
+                match (lowered_operation(uir, &[left_code.clone(), right_code.clone()], &Language::Rust), binary_operator(uir, left)) {
+                    (Lowering::Code(code), _) => Ok(code),
+                    (Lowering::Operator, Some(operator)) => Ok(format!("{} {} {}", left_code, operator, right_code)),
+                    (Lowering::Untranslated, _) | (_, None) => Ok(untranslated()),
+                }
+            }
+            _ => Ok("unknown_expression".to_string()),
+        }
+    }
+    
+    /// `**` as a method of the base: `powf` for a float exponent, on the base cast to `f64`,
+    /// `powi` for a float base and `pow` otherwise, which takes a `u32`
+    fn generate_power(&self, base: &UIRNode, exponent: &UIRNode) -> Result<String> {
+        let mut base_code = self.generate_expression(base)?;
+        let float = is_float_literal(base) || is_float_literal(exponent);
+        if base.node_type == NodeType::Expression(ExpressionType::Literal) {
+            // A bare numeric literal has no type to call a method on
+            base_code = format!("{}_{}", base_code, if float { "f64" } else { "i32" });
+        } else {
+            if expression_operator(base).is_some() {
+                base_code = format!("({})", base_code);
+            }
+            if is_float_literal(exponent) {
+                base_code = format!("({} as f64)", base_code);
+            }
+        }
+        let mut exponent_code = self.generate_expression(exponent)?;
+        if expression_operator(exponent).is_some() {
+            exponent_code = format!("({})", exponent_code);
+        }
+        let is_literal = exponent.node_type == NodeType::Expression(ExpressionType::Literal);
+        Ok(match (is_float_literal(exponent), is_float_literal(base)) {
+            (true, _) => format!("{}.powf({})", base_code, exponent_code),
+            (false, true) if is_literal => format!("{}.powi({})", base_code, exponent_code),
+            (false, true) => format!("{}.powi({} as i32)", base_code, exponent_code),
+            (false, false) if is_literal => format!("{}.pow({})", base_code, exponent_code),
+            (false, false) => format!("{}.pow({} as u32)", base_code, exponent_code),
+        })
+    }
+    
+    /// Python's `//`, which rounds down where integer `/` truncates toward zero: integers are
+    /// divided as `f64`s and floored back, floats just floored
+    fn generate_floor_division(&self, left: &UIRNode, right: &UIRNode) -> Result<String> {
+        let operand = |operand: &UIRNode| -> Result<String> {
+            let code = self.generate_expression(operand)?;
+            Ok(if operand.children.len() >= 2 && expression_operator(operand).is_some() { format!("({})", code) } else { code })
+        };
+        let (left_code, right_code) = (operand(left)?, operand(right)?);
+        if is_float_literal(left) || is_float_literal(right) {
+            Ok(format!("({} / {}).floor()", left_code, right_code))
+        } else {
+            Ok(format!("({} as f64 / {} as f64).floor() as i32", left_code, right_code))
+        }
+    }
+    
+    /// `start..end`, stepped, or reversed when the loop counts down
+    fn generate_range(&self, counted: &CountedLoop) -> Result<String> {
+        let start = self.generate_expression(counted.start)?;
+        let end = self.generate_expression(counted.end)?;
+        let operator = if counted.inclusive { "..=" } else { ".." };
+        let stepped = |range: String| match counted.step.abs() {
+            1 => range,
+            step => format!("{}.step_by({})", range, step),
+        };
+        Ok(match counted.step {
+            1 => format!("{}{}{}", start, operator, end),
+            step if step > 0 => stepped(format!("({}{}{})", start, operator, end)),
+            _ => stepped(format!("({}..={}).rev()", counted.inclusive_end(end), start)),
+        })
+    }
+    
+    fn count_assignments(&self, uir: &UIRNode, counts: &mut HashMap<String, usize>) {
+        if matches!(uir.node_type, NodeType::Function | NodeType::Closure { .. }) {
+            return;
+        }
+        if uir.node_type == NodeType::Expression(ExpressionType::Assignment) {
+            if let Some(name) = uir.children.first().and_then(|t| t.name.as_deref()) {
+                // A compound assignment such as `i *= 2` changes a value the variable already had
+                *counts.entry(rust_variable_name(name)).or_insert(0) += if uir.operator.is_some() { 2 } else { 1 };
+            }
+        }
+        for child in &uir.children {
+            self.count_assignments(child, counts);
+        }
+    }
+    
+    /// Whether the body calls a mutating method on `name`, e.g. `items.append(x)`
+    fn mutates(&self, uir: &UIRNode, name: &str) -> bool {
+        const MUTATING_METHODS: &[&str] = &[
+            "append", "push", "push_back", "insert", "remove", "pop", "clear", "extend", "add", "put",
+            "set", "sort", "update", "delete", "splice", "shift", "unshift", "Add", "Remove", "Clear",
+        ];
+        
+        if uir.node_type == NodeType::Expression(ExpressionType::FunctionCall) {
+            let text = uir.metadata.annotations.get("original_text").and_then(|v| v.as_str()).unwrap_or("");
+            if let Some(call) = text.strip_prefix(name).and_then(|rest| rest.strip_prefix('.')) {
+                let method: String = call.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+                if MUTATING_METHODS.contains(&method.as_str()) {
+                    return true;
+                }
+            }
+        }
+        uir.children.iter().any(|c| self.mutates(c, name))
+    }
+    
+    fn assigns_to_self(&self, uir: &UIRNode) -> bool {
+        if uir.node_type == NodeType::Expression(ExpressionType::Assignment) {
+            let target = uir.children.first()
+                .and_then(|t| t.metadata.annotations.get("original_text"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            if target.starts_with("self.") || target.starts_with("this.") {
+                return true;
+            }
+        }
+        uir.children.iter().any(|c| self.assigns_to_self(c))
+    }
+    
+    fn collect_self_fields(&self, uir: &UIRNode, fields: &mut Vec<String>) {
+        walk(uir, &mut |node: &UIRNode| {
+            if node.node_type == NodeType::Expression(ExpressionType::Assignment) {
+                let target = node.children.first()
+                    .and_then(|t| t.metadata.annotations.get("original_text"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                if let Some(field) = target.strip_prefix("self.").or_else(|| target.strip_prefix("this.")) {
+                    let field = to_snake_case(field);
+                    if !fields.contains(&field) {
+                        fields.push(field);
+                    }
+                }
+            }
+            Walk::Continue
+        });
+    }
+    
+    fn is_copy(&self, rust_type: &str) -> bool {
+        matches!(rust_type, "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+            | "u128" | "usize" | "f32" | "f64" | "bool" | "char")
+    }
+    
+    /// Read-only parameter type: `&str` for strings, slices for vectors, `&T` otherwise
+    fn borrowed(&self, rust_type: &str) -> String {
+        if rust_type == "String" {
+            "&str".to_string()
+        } else if let Some(element) = rust_type.strip_prefix("Vec<").and_then(|t| t.strip_suffix('>')) {
+            format!("&[{}]", element)
         } else {
-            Ok("unknown_expression".to_string())
+            format!("&{}", rust_type)
+        }
+    }
+    
+    fn annotated_type(&self, uir: &UIRNode, key: &str) -> Option<String> {
+        declared_type(uir, key).map(|source_type| self.map_type(source_type))
+    }
+    
+    /// Map a source-language type to Rust, recursing into generic arguments
+    fn map_type(&self, source_type: &str) -> String {
+        let source_type = source_type.trim();
+        if let Some(inner) = source_type.strip_suffix('?') {
+            return format!("Option<{}>", self.map_type(inner));
+        }
+        if let Some(inner) = source_type.strip_suffix("[]") {
+            return format!("Vec<{}>", self.map_type(inner));
+        }
+        if let Some(inner) = source_type.strip_prefix("[]") {
+            return format!("Vec<{}>", self.map_type(inner));
+        }
+        if matches!(source_type, "char*" | "char *" | "const char*" | "const char *") {
+            return "String".to_string();
+        }
+        
+        let (base, arguments) = match source_type.find(['<', '[']) {
+            Some(open) if source_type.ends_with(['>', ']']) => {
+                (&source_type[..open], split_type_arguments(&source_type[open + 1..source_type.len() - 1]))
+            }
+            _ => (source_type, Vec::new()),
+        };
+        let base = base.rsplit(['.', ':']).next().unwrap_or(base);
+        let mapped: Vec<String> = arguments.iter().map(|a| self.map_type(a)).collect();
+        let argument = |i: usize| mapped.get(i).cloned().unwrap_or_else(|| "i32".to_string());
+        
+        match base {
+            "int" | "Int" | "Integer" | "int32" | "Int32" => "i32".to_string(),
+            "long" | "Long" | "int64" | "Int64" => "i64".to_string(),
+            "short" | "Short" | "int16" => "i16".to_string(),
+            "byte" | "Byte" | "uint8" => "u8".to_string(),
+            "uint" | "uint32" | "UInt32" | "unsigned" => "u32".to_string(),
+            "ulong" | "uint64" | "size_t" => "u64".to_string(),
+            "float" | "Float" | "float32" => "f32".to_string(),
+            "double" | "Double" | "float64" | "decimal" | "Decimal" | "number" => "f64".to_string(),
+            "bool" | "boolean" | "Boolean" => "bool".to_string(),
+            "char" | "Char" | "rune" => "char".to_string(),
+            "str" | "string" | "String" => "String".to_string(),
+            "void" | "None" | "Unit" | "Void" => "()".to_string(),
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "f32" | "f64" => {
+                base.to_string()
+            }
+            "list" | "List" | "IList" | "ArrayList" | "MutableList" | "Sequence" | "IEnumerable" | "Array" | "Seq" => {
+                format!("Vec<{}>", argument(0))
+            }
+            "dict" | "Dict" | "Map" | "HashMap" | "Dictionary" | "IDictionary" | "MutableMap" | "Mapping" => {
+                format!("HashMap<{}, {}>", argument(0), argument(1))
+            }
+            "set" | "Set" | "HashSet" | "MutableSet" => format!("HashSet<{}>", argument(0)),
+            "Optional" | "Option" | "Nullable" => format!("Option<{}>", argument(0)),
+            _ if mapped.is_empty() => to_pascal_case(base),
+            _ => format!("{}<{}>", to_pascal_case(base), mapped.join(", ")),
+        }
+    }
+}
+
+/// Tags of the clauses that handle errors raised in a try body
+const HANDLER_TAGS: &[&str] = &["except_clause", "catch_clause", "catch", "rescue", "handler"];
+
+/// Tags of the clauses that run after a try body whether or not it raised
+const CLEANUP_TAGS: &[&str] = &["finally_clause", "finally", "ensure"];
+
+/// A clause handling errors raised in a try body: a `Catch` node, or a clause parsers only tag
+pub(crate) fn is_handler(uir: &UIRNode) -> bool {
+    matches!(uir.node_type, NodeType::ControlFlow(ControlFlowType::Catch { .. })) || has_tag(uir, HANDLER_TAGS)
+}
+
+/// A clause that runs after a try body whether or not it raised
+pub(crate) fn is_cleanup(uir: &UIRNode) -> bool {
+    uir.node_type == NodeType::ControlFlow(ControlFlowType::Finally) || has_tag(uir, CLEANUP_TAGS)
+}
+
+/// Whether a statement raises outside of any try block that would catch it, itself or by a
+/// call `mark_fallible_calls` tagged
+pub(crate) fn raises(uir: &UIRNode) -> bool {
+    raises_through(uir, &|call| has_tag(call, &["fallible_call"]))
+}
+
+/// `raises`, taking calls for which `fallible` holds to raise
+fn raises_through(uir: &UIRNode, fallible: &dyn Fn(&UIRNode) -> bool) -> bool {
+    match &uir.node_type {
+        NodeType::Statement(StatementType::Throw) => true,
+        NodeType::Expression(ExpressionType::FunctionCall) if fallible(uir) => true,
+        NodeType::Function | NodeType::Closure { .. } => false,
+        // A handler catches what its body raises, but not what it raises itself
+        NodeType::ControlFlow(ControlFlowType::Try) => {
+            let caught = uir.children.iter().any(is_handler);
+            uir.children.iter()
+                .filter(|c| !caught || is_handler(c) || is_cleanup(c))
+                .any(|c| raises_through(c, fallible))
+        }
+        _ => uir.children.iter().any(|c| raises_through(c, fallible)),
+    }
+}
+
+/// The function or method a call names when it is one the module could define: `parse` of
+/// `parse(s)`, `check` of `self.check()`
+pub(crate) fn local_callee(call: &UIRNode) -> Option<&str> {
+    let callee = match (&call.name, call_parts(call).0) {
+        (Some(name), _) => name.as_str(),
+        (None, Some(function)) => original_text(function),
+        (None, None) => return None,
+    };
+    let callee = callee.strip_prefix("self.").or_else(|| callee.strip_prefix("this.")).unwrap_or(callee);
+    (!callee.is_empty() && callee.chars().all(|c| c.is_alphanumeric() || c == '_')).then_some(callee)
+}
+
+/// The plain assignment `statement` makes to a name, as Python's module-level `LIMIT = 10`
+fn module_assignment(statement: &UIRNode) -> Option<&UIRNode> {
+    if statement.node_type != NodeType::Statement(StatementType::Expression) {
+        return None;
+    }
+    match statement.children.iter().filter(|c| !is_punctuation(c) && !is_comment(c)).collect::<Vec<_>>().as_slice() {
+        [assignment] if assignment.node_type == NodeType::Expression(ExpressionType::Assignment) && assignment.operator.is_none() => {
+            let target = assignment.children.first()?;
+            let simple = matches!(target.node_type, NodeType::Variable | NodeType::Expression(ExpressionType::Variable)) && target.children.is_empty();
+            (simple && target.name.is_some() && assignment.children.len() >= 2).then_some(*assignment)
+        }
+        _ => None,
+    }
+}
+
+/// `module` with the first module-level assignment to each name made a variable declaration,
+/// and the variables of JavaScript's `const a = 1, b = 2` declared on their own, so generators
+/// without module-level statements declare them as they would a data item
+pub(crate) fn module_assignments_declared(mut module: UIRNode) -> UIRNode {
+    let mut declared = HashSet::new();
+    let mut declare = |children: &mut Vec<UIRNode>| {
+        let mut spliced = Vec::with_capacity(children.len());
+        for child in children.drain(..) {
+            let variables = declared_variables(&child)
+                .filter(|variables| !is_import(&child) && variables.iter().all(|v| !v.metadata.annotations.contains_key("destructured_from")))
+                .map(|variables| variables.into_iter().cloned().collect::<Vec<_>>());
+            match variables {
+                Some(mut variables) => {
+                    variables[0].documentation = variables[0].documentation.take().or_else(|| child.documentation.clone());
+                    declared.extend(variables.iter().filter_map(|v| v.name.clone()));
+                    spliced.extend(variables);
+                }
+                None => spliced.push(child),
+            }
+        }
+        *children = spliced;
+        for child in children.iter_mut() {
+            let Some(assignment) = module_assignment(child) else {
+                continue;
+            };
+            let target = &assignment.children[0];
+            let name = target.name.clone().unwrap_or_default();
+            if !declared.insert(name.clone()) {
+                continue;
+            }
+            let mut variable = UIRNode::new(child.id.clone(), NodeType::Variable);
+            variable.name = Some(name);
+            variable.metadata = target.metadata.clone();
+            variable.metadata.annotations.insert("original_text".to_string(), original_text(child).into());
+            variable.source_location = child.source_location.clone();
+            variable.documentation = child.documentation.clone();
+            variable.children.push(assignment.children[assignment.children.len() - 1].clone());
+            *child = variable;
+        }
+    };
+    declare(&mut module.children);
+    for child in module.children.iter_mut().filter(|c| c.node_type == NodeType::Module) {
+        declare(&mut child.children);
+    }
+    module
+}
+
+/// The keys a call to `function`, defined in `owner`'s body if a method, is resolved to by
+/// `callee_key`: its name for a free function, `Owner.name` and `*.name` for a method
+pub(crate) fn function_keys(function: &UIRNode, owner: Option<&str>) -> Vec<String> {
+    let Some(name) = function.name.as_deref() else {
+        return Vec::new();
+    };
+    match owner {
+        Some(owner) => vec![format!("{}.{}", owner, name), format!("*.{}", name)],
+        None => vec![name.to_string()],
+    }
+}
+
+/// The function of the module a call made in `owner`'s methods may run, as `function_keys`
+/// keys it: `parse` of `parse(s)`, `Account.check` of `self.check()`, and `*.deposit` of
+/// `a.deposit(5)`, a method of whichever class `a` is
+pub(crate) fn callee_key(call: &UIRNode, owner: Option<&str>) -> Option<String> {
+    let callee = match (&call.name, call_parts(call).0) {
+        (Some(name), _) => name.as_str(),
+        (None, Some(function)) => original_text(function),
+        (None, None) => return None,
+    };
+    let is_name = |name: &str| !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    match callee.rsplit_once('.') {
+        None => is_name(callee).then(|| callee.to_string()),
+        Some(("self" | "this", method)) => owner.filter(|_| is_name(method)).map(|owner| format!("{}.{}", owner, method)),
+        Some((_, method)) => is_name(method).then(|| format!("*.{}", method)),
+    }
+}
+
+/// Every function of `uir` with the class whose method it is, if any
+pub(crate) fn functions_with_owners<'a>(uir: &'a UIRNode, owner: Option<&'a str>, functions: &mut Vec<(&'a UIRNode, Option<&'a str>)>) {
+    for child in &uir.children {
+        match child.node_type {
+            NodeType::Function => {
+                functions.push((child, owner));
+                functions_with_owners(child, None, functions);
+            }
+            NodeType::Class => functions_with_owners(child, child.name.as_deref(), functions),
+            _ => functions_with_owners(child, owner, functions),
         }
     }
 }
+
+/// A copy of `module` in which calls to its functions that raise, themselves or through
+/// calls of their own, are tagged `fallible_call` and annotated with the `callee` they resolve
+/// to, so generators whose errors are return values make the callers fallible too and pass
+/// the error on at the call
+pub(crate) fn mark_fallible_calls(module: &UIRNode) -> UIRNode {
+    let mut functions = Vec::new();
+    functions_with_owners(module, None, &mut functions);
+    let mut fallible: HashSet<String> = HashSet::new();
+    loop {
+        let found: Vec<String> = functions.iter()
+            .filter(|(function, owner)| function_keys(function, *owner).iter().any(|key| !fallible.contains(key)))
+            .filter(|(function, owner)| {
+                function.children.iter().any(|c| raises_through(c, &|call| callee_key(call, *owner).is_some_and(|key| fallible.contains(&key))))
+            })
+            .flat_map(|(function, owner)| function_keys(function, *owner))
+            .collect();
+        if found.iter().all(|key| fallible.contains(key)) {
+            break;
+        }
+        fallible.extend(found);
+    }
+    
+    fn mark(node: &mut UIRNode, owner: Option<&str>, fallible: &HashSet<String>) {
+        match node.node_type {
+            // Errors raised in a closure are not the enclosing function's
+            NodeType::Closure { .. } => return,
+            NodeType::Class => {
+                let owner = node.name.clone();
+                for child in &mut node.children {
+                    mark(child, owner.as_deref(), fallible);
+                }
+                return;
+            }
+            NodeType::Expression(ExpressionType::FunctionCall) => {
+                if let Some(key) = callee_key(node, owner).filter(|key| fallible.contains(key)) {
+                    node.metadata.semantic_tags.push("fallible_call".to_string());
+                    node.metadata.annotations.insert("callee".to_string(), key.into());
+                }
+            }
+            _ => {}
+        }
+        for child in &mut node.children {
+            mark(child, owner, fallible);
+        }
+    }
+    let mut marked = module.clone();
+    if !fallible.is_empty() {
+        mark(&mut marked, None, &fallible);
+    }
+    marked
+}
+
+/// The value a variable of `rust_type` starts from when its source gives none, as COBOL's data
+/// items without a `VALUE`
+fn zero_value(rust_type: &str) -> String {
+    match rust_type {
+        "&str" => "\"\"".to_string(),
+        "String" => "String::new()".to_string(),
+        "f32" | "f64" => "0.0".to_string(),
+        "bool" => "false".to_string(),
+        numeric if numeric.starts_with(['i', 'u']) && numeric.len() <= 5 => "0".to_string(),
+        _ => "Default::default()".to_string(),
+    }
+}
+
+/// A variable's name in Rust: snake case, except names such as `MAX_SIZE` or `Some` that
+/// start upper case; COBOL's hyphenated data names, `WS-TOTAL`, are snake case too
+fn rust_variable_name(name: &str) -> String {
+    if name.starts_with(char::is_uppercase) && !name.contains('-') {
+        identifier(name)
+    } else {
+        to_snake_case(name)
+    }
+}
+
+/// `uir`, a function or closure, with its parameters that `rust_variable_name` would leave
+/// upper case renamed snake case, as Rust binds them, and the references to them in its body
+/// with them: `Other` of `function f(Other)` is declared and read as `other`
+fn parameters_renamed(uir: &UIRNode) -> Option<UIRNode> {
+    let renamed: HashMap<&str, String> = uir.children.iter()
+        .filter(|c| c.node_type == NodeType::Variable)
+        .filter_map(|parameter| parameter.name.as_deref())
+        .filter(|name| rust_variable_name(name) != to_snake_case(name))
+        .map(|name| (name, to_snake_case(name)))
+        .collect();
+    if renamed.is_empty() {
+        return None;
+    }
+    let mut renamed_uir = uir.clone();
+    for child in &mut renamed_uir.children {
+        // Nested functions and classes bind names of their own
+        walk_mut(child, &mut |node: &mut UIRNode| match node.node_type {
+            NodeType::Function | NodeType::Class => Walk::SkipChildren,
+            NodeType::Variable | NodeType::Expression(ExpressionType::Variable) => {
+                if let Some(name) = node.name.as_deref().and_then(|name| renamed.get(name)) {
+                    node.name = Some(name.clone());
+                }
+                Walk::Continue
+            }
+            _ => Walk::Continue,
+        });
+    }
+    Some(renamed_uir)
+}
+
+/// `module` with its bare module-level variables, such as COBOL data items, scoped for Rust:
+/// references to the ones no function writes are tagged `module_constant`, those to the written
+/// ones `module_state`, as fields of one state struct. Functions reaching that state, directly
+/// or through a call, are annotated `uses_state` and calls to them tagged `passes_state`.
+/// Returns the written variables too
+fn scope_module_variables(module: &UIRNode) -> (UIRNode, Vec<UIRNode>) {
+    let variables: Vec<&UIRNode> = module_items(module).into_iter().filter(|c| c.node_type == NodeType::Variable).collect();
+    let mut scoped = module.clone();
+    if variables.is_empty() {
+        return (scoped, Vec::new());
+    }
+    let mut written = HashSet::new();
+    walk(module, &mut |function: &UIRNode| {
+        if function.node_type != NodeType::Function {
+            return Walk::Continue;
+        }
+        walk(function, &mut |node: &UIRNode| {
+            if node.node_type == NodeType::Expression(ExpressionType::Assignment) {
+                written.extend(node.children.first().and_then(|t| t.name.as_deref()).map(rust_variable_name));
+            }
+            Walk::Continue
+        });
+        Walk::SkipChildren
+    });
+    let declared: HashMap<String, &UIRNode> = variables.iter()
+        .filter_map(|&v| v.name.as_deref().map(|name| (rust_variable_name(name), v)))
+        .collect();
+    
+    // COBOL's `PERFORM CALC-PARA` and `GO TO END-PARA` name their paragraph as written
+    let callee = |call: &UIRNode| match call.node_type {
+        NodeType::Expression(ExpressionType::FunctionCall) => local_callee(call).or(call.name.as_deref()).map(to_snake_case),
+        NodeType::ControlFlow(ControlFlowType::Goto) => call.name.as_deref().map(to_snake_case),
+        _ => None,
+    };
+    // Tag the references, noting which functions read or write the state and whom they call
+    let mut users = HashSet::new();
+    let mut callers: Vec<(String, HashSet<String>)> = Vec::new();
+    walk_mut(&mut scoped, &mut |node: &mut UIRNode| {
+        if node.node_type != NodeType::Function {
+            return Walk::Continue;
+        }
+        let function = to_snake_case(node.name.as_deref().unwrap_or_default());
+        let mut callees = HashSet::new();
+        let mut uses_state = false;
+        let mut scope = |reference: &mut UIRNode| {
+            let name = rust_variable_name(reference.name.as_deref().unwrap_or_default());
+            let Some(variable) = declared.get(&name) else { return };
+            let tag = if written.contains(&name) { "module_state" } else { "module_constant" };
+            uses_state |= tag == "module_state";
+            reference.metadata.semantic_tags.push(tag.to_string());
+            // The type lets arithmetic mixing it with floats convert
+            let value_type = match initializer(variable).and_then(|value| value.literal.as_ref()) {
+                Some(LiteralValue::Float(_)) => Some(serde_json::Value::from("double")),
+                Some(LiteralValue::Int(_)) => Some(serde_json::Value::from("int")),
+                _ => None,
+            };
+            if let Some(value_type) = variable.metadata.annotations.get("type").cloned().or(value_type) {
+                reference.metadata.annotations.entry("type".to_string()).or_insert(value_type);
+            }
+        };
+        walk_mut(node, &mut |reference: &mut UIRNode| {
+            callees.extend(callee(reference));
+            match reference.node_type {
+                NodeType::Expression(ExpressionType::Variable) => scope(reference),
+                // Python's assignment targets are variables, not references
+                NodeType::Expression(ExpressionType::Assignment) => {
+                    if let Some(target) = reference.children.first_mut().filter(|t| t.node_type == NodeType::Variable) {
+                        scope(target);
+                    }
+                }
+                _ => {}
+            }
+            Walk::Continue
+        });
+        if uses_state {
+            users.insert(function.clone());
+        }
+        callers.push((function, callees));
+        Walk::SkipChildren
+    });
+    if users.is_empty() {
+        return (scoped, Vec::new());
+    }
+    loop {
+        let reached: Vec<String> = callers.iter()
+            .filter(|(caller, callees)| !users.contains(caller) && callees.iter().any(|c| users.contains(c)))
+            .map(|(caller, _)| caller.clone())
+            .collect();
+        if reached.is_empty() {
+            break;
+        }
+        users.extend(reached);
+    }
+    walk_mut(&mut scoped, &mut |node: &mut UIRNode| {
+        if node.node_type == NodeType::Function && node.name.as_deref().is_some_and(|name| users.contains(&to_snake_case(name))) {
+            node.metadata.annotations.insert("uses_state".to_string(), serde_json::Value::Bool(true));
+        }
+        if callee(node).is_some_and(|c| users.contains(&c)) {
+            node.metadata.semantic_tags.push("passes_state".to_string());
+        }
+        Walk::Continue
+    });
+    
+    let state = variables.into_iter()
+        .filter(|v| written.contains(&rust_variable_name(v.name.as_deref().unwrap_or_default())))
+        .cloned()
+        .collect();
+    (scoped, state)
+}
+
+/// Names `statements` bind that are not in `declared`, spelled by `spell`, in order with the
+/// value first given them: assignment targets and declared variables, outside nested functions
+pub(crate) fn bound_names<'a>(statements: &[&'a UIRNode], declared: &HashSet<String>, spell: impl Fn(&str) -> String) -> Vec<(String, Option<&'a UIRNode>)> {
+    let mut names: Vec<(String, Option<&UIRNode>)> = Vec::new();
+    for statement in statements {
+        walk(statement, &mut |node: &'a UIRNode| {
+            let binding = match node.node_type {
+                NodeType::Function | NodeType::Closure { .. } | NodeType::Class => return Walk::SkipChildren,
+                NodeType::Expression(ExpressionType::Assignment) if node.operator.is_none() => node.children.first()
+                    .filter(|target| matches!(target.node_type, NodeType::Variable | NodeType::Expression(ExpressionType::Variable)) && target.children.is_empty())
+                    .and_then(|target| target.name.as_deref())
+                    .map(|name| (name, node.children.last())),
+                NodeType::Variable if declared_variables(node).is_none() && !has_tag(node, &["parameter"]) => {
+                    node.name.as_deref().map(|name| (name, initializer(node)))
+                }
+                _ => None,
+            };
+            match binding.filter(|(name, _)| !name.contains('.')) {
+                Some((name, value)) => {
+                    let name = spell(name);
+                    if !declared.contains(&name) && !names.iter().any(|(bound, _)| *bound == name) {
+                        names.push((name, value));
+                    }
+                    Walk::SkipChildren
+                }
+                None => Walk::Continue,
+            }
+        });
+    }
+    names
+}
+
+pub(crate) fn returns_value(uir: &UIRNode) -> bool {
+    match uir.node_type {
+        NodeType::Statement(StatementType::Return) => !uir.children.is_empty(),
+        NodeType::Function | NodeType::Closure { .. } => false,
+        _ => uir.children.iter().any(returns_value),
+    }
+}
+
+/// COBOL's `GO TO paragraph` as the call and return it modernizes to: the paragraph runs,
+/// and control does not come back to the statements after the jump
+pub(crate) fn paragraph_jump(uir: &UIRNode) -> Option<UIRNode> {
+    let target = uir.name.as_deref()?;
+    if uir.node_type != NodeType::ControlFlow(ControlFlowType::Goto) || !has_tag(uir, &["go_to"]) || uir.metadata.annotations.contains_key("depending_on") {
+        return None;
+    }
+    let node = |suffix: &str, node_type: NodeType| {
+        let mut node = UIRNode::new(format!("{}_{}", uir.id, suffix), node_type);
+        node.metadata.source_language = uir.metadata.source_language.clone();
+        node
+    };
+    let mut call = node("call", NodeType::Expression(ExpressionType::FunctionCall));
+    call.name = Some(target.to_string());
+    if has_tag(uir, &["passes_state"]) {
+        call.metadata.semantic_tags.push("passes_state".to_string());
+    }
+    Some(node("jump", NodeType::Statement(StatementType::Expression))
+        .add_child(call)
+        .add_child(node("return", NodeType::Statement(StatementType::Return))))
+}
+
+/// Double-quoted error message for a throw: the string literal the source raised with, if any
+pub(crate) fn raised_message(uir: &UIRNode) -> String {
+    fn find_string(node: &UIRNode) -> Option<String> {
+        if let Some(LiteralValue::String(message)) = &node.literal {
+            return Some(format!("\"{}\"", message.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+        let text = original_text(node);
+        let quoted = text.len() >= 2 && (text.starts_with('"') && text.ends_with('"') || text.starts_with('\'') && text.ends_with('\''));
+        if node.node_type == NodeType::Expression(ExpressionType::Literal) && quoted {
+            return Some(format!("\"{}\"", &text[1..text.len() - 1]));
+        }
+        node.children.iter().find_map(find_string)
+    }
+    
+    find_string(uir).unwrap_or_else(|| {
+        let text = match original_text(uir) {
+            "" => "error",
+            text => text,
+        };
+        format!("{:?}", text.trim_start_matches("throw ").trim_start_matches("raise ").trim_end_matches(';'))
+    })
+}
+
+/// C-family spelling of the operator between `left` and the right operand of a binary expression
+pub(crate) fn binary_operator<'a>(uir: &'a UIRNode, left: &UIRNode) -> Option<&'a str> {
+    if let Some(operator) = uir.operator {
+        return Some(match operator {
+            Operator::StrictEqual | Operator::Is => "==",
+            Operator::StrictNotEqual | Operator::IsNot => "!=",
+            Operator::IntegerDivide => "/",
+            operator => operator.symbol(),
+        });
+    }
+    // Parsers that do not record it leave the operator between the operands in the original text
+    let between = original_text(uir).get(original_text(left).len()..).unwrap_or("");
+    match between.split_whitespace().next().unwrap_or("+") {
+        "and" => Some("&&"),
+        "or" => Some("||"),
+        "is" => Some("=="),
+        "===" => Some("=="),
+        "!==" => Some("!="),
+        "//" => Some("/"),
+        op if op.chars().all(|c| "+-*/%<>=!&|^".contains(c)) && !op.is_empty() => Some(op),
+        _ => None,
+    }
+}
+
+/// How a generator writes an operation the target has no operator of the same spelling for
+pub(crate) enum Lowering {
+    /// With the operator, as `binary_operator` spells it
+    Operator,
+    /// With this code in place of the operation
+    Code(String),
+    /// Not at all: the target has nothing that does the same
+    Untranslated,
+}
+
+/// How `target` writes `uir`, an operation of JavaScript's `instanceof`, `in`, `>>>`, `typeof`,
+/// `delete` or `void`, over the code of its operands
+pub(crate) fn lowered_operation(uir: &UIRNode, operands: &[String], target: &Language) -> Lowering {
+    // `as` and casts bind tighter than any binary operator
+    let atom = |code: &String| if code.contains(' ') && !code.starts_with('(') { format!("({})", code) } else { code.clone() };
+    let Some(operator) = expression_operator(uir) else {
+        return Lowering::Operator;
+    };
+    let code = match (operator, operands, target) {
+        (_, _, Language::JavaScript | Language::TypeScript) => return Lowering::Operator,
+        (Operator::InstanceOf, [value, class], Language::Python) => format!("isinstance({}, {})", value, class),
+        (Operator::InstanceOf, [value, class], Language::Rust) => format!("(&{} as &dyn std::any::Any).is::<{}>()", atom(value), class),
+        (Operator::InstanceOf, [value, class], Language::Go) => format!("func() bool {{ _, ok := any({}).(*{}); return ok }}()", value, class),
+        (Operator::In, _, Language::Python) => return Lowering::Operator,
+        (Operator::In, [key, object], Language::Rust) => format!("{}.contains_key(&{})", atom(object), atom(key)),
+        (Operator::In, [key, object], Language::Go) => format!("func() bool {{ _, ok := {}[{}]; return ok }}()", object, key),
+        (Operator::UnsignedShiftRight, [value, shift], Language::Python) => format!("({} & 0xFFFFFFFF) >> {}", value, shift),
+        (Operator::UnsignedShiftRight, [value, shift], Language::Rust) => format!("({} as u32) >> {}", atom(value), shift),
+        (Operator::UnsignedShiftRight, [value, shift], Language::Go) => format!("int(uint32({}) >> {})", value, shift),
+        (Operator::UnsignedShiftRight, [value, shift], Language::C) => format!("(unsigned int){} >> {}", atom(value), shift),
+        (Operator::TypeOf, [value], Language::Python) => format!("type({}).__name__", value),
+        (Operator::TypeOf, [value], Language::Rust) => format!("std::any::type_name_of_val(&{})", value),
+        (Operator::TypeOf, [value], Language::Go) => format!("fmt.Sprintf(\"%T\", {})", value),
+        (Operator::Delete, [target], Language::Python) => format!("del {}", target),
+        (Operator::Void, [_], Language::Python) => "None".to_string(),
+        (Operator::Void, [_], Language::Rust) => "()".to_string(),
+        (Operator::Void, [_], Language::Go) => "nil".to_string(),
+        (Operator::Void, [_], Language::C) => "NULL".to_string(),
+        (Operator::InstanceOf | Operator::In | Operator::UnsignedShiftRight | Operator::TypeOf | Operator::Delete | Operator::Void, ..) => {
+            return Lowering::Untranslated;
+        }
+        _ => return Lowering::Operator,
+    };
+    Lowering::Code(code)
+}
+
+/// How `language` writes an assignment: plainly, with a compound operator such as `-=`, or, for
+/// a compound assignment `language` has no compound form of, as a plain assignment of the
+/// operation it performs, `n = n ** 2`
+pub(crate) enum AssignmentForm {
+    Plain,
+    Compound(&'static str),
+    Expanded(Box<UIRNode>),
+}
+
+pub(crate) fn assignment_form(uir: &UIRNode, language: &Language) -> AssignmentForm {
+    let Some(operation) = assignment_operation(uir) else {
+        return AssignmentForm::Plain;
+    };
+    let compound = match (operation.operator, language) {
+        (Some(Operator::Add), _) => "+=",
+        (Some(Operator::Subtract), _) => "-=",
+        (Some(Operator::Multiply), _) => "*=",
+        (Some(Operator::Divide), _) => "/=",
+        (Some(Operator::Modulo), _) => "%=",
+        (Some(Operator::BitAnd), _) => "&=",
+        (Some(Operator::BitOr), _) => "|=",
+        (Some(Operator::BitXor), _) => "^=",
+        (Some(Operator::ShiftLeft), _) => "<<=",
+        (Some(Operator::ShiftRight), _) => ">>=",
+        (Some(Operator::IntegerDivide), Language::Python) => "//=",
+        (Some(Operator::Power), Language::Python | Language::JavaScript | Language::TypeScript) => "**=",
+        (Some(Operator::UnsignedShiftRight), Language::JavaScript | Language::TypeScript) => ">>>=",
+        (Some(Operator::Coalesce), Language::JavaScript | Language::TypeScript | Language::CSharp) => "??=",
+        _ => return AssignmentForm::Expanded(Box::new(operation)),
+    };
+    AssignmentForm::Compound(compound)
+}
+
+/// The operation a compound assignment performs, `n - 1` of `n -= 1`; `None` for a plain one
+pub(crate) fn assignment_operation(uir: &UIRNode) -> Option<UIRNode> {
+    let (Some(operator), [target, .., value]) = (uir.operator, uir.children.as_slice()) else {
+        return None;
+    };
+    Some(UIRNode {
+        id: format!("{}_operation", uir.id),
+        node_type: NodeType::Expression(operator.expression_type()),
+        name: None,
+        children: vec![target.clone(), value.clone()],
+        metadata: Metadata { source_language: uir.metadata.source_language.clone(), ..Default::default() },
+        source_location: uir.source_location.clone(),
+        operator: Some(operator),
+        literal: None,
+        visibility: None,
+        modifiers: Vec::new(),
+        is_async: false,
+        attributes: Vec::new(),
+        ownership: None,
+        documentation: None,
+    })
+}
+
+/// Whether a node is a floating-point literal, such as `0.5` or `1e3`
+pub(crate) fn is_float_literal(uir: &UIRNode) -> bool {
+    if uir.node_type != NodeType::Expression(ExpressionType::Literal) {
+        return false;
+    }
+    match &uir.literal {
+        Some(literal) => matches!(literal, LiteralValue::Float(_)),
+        None => {
+            let text = original_text(uir);
+            text.parse::<f64>().is_ok() && text.contains(['.', 'e', 'E'])
+        }
+    }
+}
+
+/// The operator of an expression node, read back from its text when the parser did not record it
+pub(crate) fn expression_operator(uir: &UIRNode) -> Option<Operator> {
+    if !matches!(uir.node_type, NodeType::Expression(ExpressionType::Arithmetic | ExpressionType::Comparison | ExpressionType::Logical)) {
+        return None;
+    }
+    if uir.operator.is_some() {
+        return uir.operator;
+    }
+    match uir.children.as_slice() {
+        [_] => {
+            let text = original_text(uir);
+            let symbol = if text.starts_with("not ") { "not" } else { text.get(..1).unwrap_or("") };
+            Operator::unary(symbol, &Language::C)
+        }
+        [left, _] => Operator::binary(binary_operator(uir, left)?, &Language::C),
+        _ => None,
+    }
+}
+
+/// `code` for `operand`, the left or right operand of `parent`, in parentheses when the source
+/// grouped it or when leaving them out would regroup the expression
+pub(crate) fn grouped(code: String, parent: &UIRNode, operand: &UIRNode, right: bool) -> String {
+    if needs_parentheses(parent, operand, right) {
+        format!("({})", code)
+    } else {
+        code
+    }
+}
+
+/// `code` for a branch or the condition of a conditional expression, in parentheses when it is
+/// a conditional expression itself
+pub(crate) fn ternary_operand(code: String, operand: &UIRNode) -> String {
+    if ternary_parts(operand).is_some() {
+        format!("({})", code)
+    } else {
+        code
+    }
+}
+
+fn needs_parentheses(parent: &UIRNode, operand: &UIRNode, right: bool) -> bool {
+    // Conditional expressions bind more loosely than any operator
+    if ternary_parts(operand).is_some() {
+        return true;
+    }
+    let inner = expression_operator(operand);
+    if has_tag(operand, &["parenthesized"]) {
+        // A comma sequence is the one literal-like node that ends at its first comma
+        return inner.is_some() || has_tag(operand, &["sequence_expression"]) || !matches!(
+            operand.node_type,
+            NodeType::Expression(ExpressionType::Variable | ExpressionType::Literal | ExpressionType::FunctionCall)
+        );
+    }
+    let (Some(outer), Some(inner)) = (expression_operator(parent), inner) else {
+        return false;
+    };
+    if outer.is_unary() {
+        // `-(-a)` rather than C's decrement `--a`
+        return true;
+    }
+    let logical = |operator: Operator| matches!(operator, Operator::And | Operator::Or | Operator::Xor);
+    let comparison = |operator: Operator| operator.expression_type() == ExpressionType::Comparison;
+    // Groupings whose meaning differs between target languages, or that some reject unparenthesized
+    let ambiguous = (outer.is_bitwise() || inner.is_bitwise()) && outer != inner && !logical(outer) && !logical(inner)
+        || comparison(outer) && comparison(inner)
+        || outer == Operator::Coalesce && logical(inner) || inner == Operator::Coalesce && logical(outer)
+        // Python's `not` binds more loosely than comparisons and arithmetic
+        || inner == Operator::Not && !logical(outer);
+    ambiguous
+        || inner.precedence() < outer.precedence()
+        || inner.precedence() == outer.precedence() && if right {
+            !(outer.is_right_associative() || outer == inner && outer.is_associative())
+        } else {
+            outer.is_right_associative()
+        }
+}
+
+pub(crate) fn original_text(uir: &UIRNode) -> &str {
+    uir.metadata.annotations.get("original_text").and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// `value` spelled for `language`, using C-family spelling for targets without their own
+pub(crate) fn literal_code(value: &LiteralValue, language: &Language) -> String {
+    match value {
+        LiteralValue::Int(value) => value.to_string(),
+        // Debug keeps the fraction of whole numbers, e.g. `2.0`
+        LiteralValue::Float(value) => format!("{:?}", value),
+        LiteralValue::Bool(value) => match (language, value) {
+            (Language::Python, true) => "True".to_string(),
+            (Language::Python, false) => "False".to_string(),
+            _ => value.to_string(),
+        },
+        LiteralValue::Null => match language {
+            Language::Python | Language::Rust => "None",
+            Language::Go => "nil",
+            Language::C | Language::Cpp => "NULL",
+            _ => "null",
+        }.to_string(),
+        LiteralValue::Char(c) => match language {
+            Language::C | Language::Cpp | Language::Rust | Language::Go | Language::Java | Language::CSharp | Language::Kotlin => {
+                quote_literal(&c.to_string(), '\'', language)
+            }
+            _ => quote_literal(&c.to_string(), '"', language),
+        },
+        LiteralValue::String(text) => quote_literal(text, '"', language),
+    }
+}
+
+fn quote_literal(text: &str, quote: char, language: &Language) -> String {
+    let mut quoted = String::from(quote);
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            _ if c == quote => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            // Octal, since a C `\x` escape runs on through any hex digits after it
+            _ if c.is_control() && matches!(language, Language::C | Language::Cpp) => {
+                let mut bytes = [0; 4];
+                for byte in c.encode_utf8(&mut bytes).bytes() {
+                    quoted.push_str(&format!("\\{:03o}", byte));
+                }
+            }
+            _ if c.is_control() && (c as u32) < 0x80 => quoted.push_str(&format!("\\x{:02x}", c as u32)),
+            _ if c.is_control() && *language == Language::Rust => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            _ if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push(quote);
+    quoted
+}
+
+/// Name a handler binds the caught error to, e.g. `e` in `except ValueError as e`
+pub(crate) fn handler_binding(handler: &UIRNode) -> Option<String> {
+    if let NodeType::ControlFlow(ControlFlowType::Catch { binding, .. }) = &handler.node_type {
+        return binding.clone();
+    }
+    if has_tag(handler, &["as_pattern_target"]) {
+        return handler.children.first().and_then(|c| c.name.clone());
+    }
+    handler.children.iter()
+        .filter(|c| !matches!(c.node_type, NodeType::Statement(_) | NodeType::ControlFlow(_)))
+        .find_map(handler_binding)
+}
+
+/// Separator tokens some parsers keep as literal children, such as `;` or `{`
+pub(crate) fn is_punctuation(uir: &UIRNode) -> bool {
+    let text = original_text(uir);
+    !text.is_empty() && text.chars().all(|c| matches!(c, ';' | ',' | '{' | '}' | '(' | ')' | '[' | ']' | ':'))
+}
+
+/// The condition and the two values of a conditional expression, JavaScript's `a ? b : c` or
+/// Python's `b if a else c`
+pub(crate) fn ternary_parts(uir: &UIRNode) -> Option<(&UIRNode, &UIRNode, &UIRNode)> {
+    if !has_tag(uir, &["ternary_expression", "conditional_expression"]) {
+        return None;
+    }
+    let operands: Vec<&UIRNode> = uir.children.iter()
+        .filter(|c| !is_punctuation(c) && !is_comment(c) && !matches!(original_text(c), "?" | "if" | "else"))
+        .collect();
+    match operands[..] {
+        [value, condition, otherwise] if uir.metadata.source_language == Language::Python => Some((condition, value, otherwise)),
+        [condition, value, otherwise] => Some((condition, value, otherwise)),
+        _ => None,
+    }
+}
+
+/// The parts of a list comprehension over one iterable, Python's `[x * 2 for x in items if x > 1]`
+/// or Erlang's `[X * 2 || X <- Items, X > 1]`
+pub(crate) struct Comprehension<'a> {
+    pub element: &'a UIRNode,
+    pub target: &'a UIRNode,
+    pub iterable: &'a UIRNode,
+    pub conditions: Vec<&'a UIRNode>,
+}
+
+/// `uir` as a list comprehension binding one variable from one iterable; `None` for anything
+/// else, including comprehensions over several
+pub(crate) fn comprehension_parts(uir: &UIRNode) -> Option<Comprehension<'_>> {
+    if uir.node_type != NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)) || !has_tag(uir, &["list_comprehension"]) {
+        return None;
+    }
+    let content = |node: &UIRNode| !is_punctuation(node) && !is_comment(node) && !matches!(original_text(node), "for" | "in" | "if");
+    let parts: Vec<&UIRNode> = uir.children.iter().filter(|c| content(c)).collect();
+    let (element, clauses) = parts.split_first()?;
+    let mut generators = clauses.iter().filter(|c| has_tag(c, &["for_in_clause"]));
+    let (Some(generator), None) = (generators.next(), generators.next()) else { return None };
+    let conditions = clauses.iter()
+        .filter(|c| has_tag(c, &["if_clause"]))
+        .map(|c| c.children.iter().find(|condition| content(condition)))
+        .collect::<Option<Vec<_>>>()?;
+    if clauses.len() != conditions.len() + 1 {
+        return None;
+    }
+    match generator.children.iter().filter(|c| content(c)).collect::<Vec<_>>()[..] {
+        [target, iterable] if matches!(target.node_type, NodeType::Variable | NodeType::Expression(ExpressionType::Variable)) => {
+            Some(Comprehension { element, target, iterable, conditions })
+        }
+        _ => None,
+    }
+}
+
+/// The collection and index of an element access such as `message[0]`; `None` for anything else
+pub(crate) fn subscript_parts(uir: &UIRNode) -> Option<(&UIRNode, &UIRNode)> {
+    if !has_tag(uir, &["subscript"]) {
+        return None;
+    }
+    match uir.children.iter().filter(|c| !is_punctuation(c)).collect::<Vec<_>>()[..] {
+        [collection, index] => Some((collection, index)),
+        _ => None,
+    }
+}
+
+/// Functions declared `async`, whether the parser set the flag or only tagged the node
+pub(crate) fn is_async(uir: &UIRNode) -> bool {
+    uir.is_async
+        || uir.metadata.semantic_tags.iter().any(|t| t == "async")
+        || uir.metadata.annotations.get("async").and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// The awaited or yielded expression of an `Await` or `Yield` node
+pub(crate) fn operand(uir: &UIRNode) -> Option<&UIRNode> {
+    uir.children.iter().find(|c| !is_punctuation(c))
+}
+
+/// The function a call calls and its arguments. Calls the line-based parsers build are named
+/// and hold only arguments, so the function is `None`; otherwise it is the first child, whatever
+/// its kind (`console.log` is no `Variable`), and the arguments are the rest, or the items of
+/// the argument list among them.
+pub(crate) fn call_parts(uir: &UIRNode) -> (Option<&UIRNode>, Vec<&UIRNode>) {
+    // A macro's path and `!` aren't arguments; the tokens of its token tree stand in for them
+    if let Some((_, tokens)) = macro_invocation(uir) {
+        return (None, tokens.children.iter().filter(|c| !is_punctuation(c) && !is_comment(c)).collect());
+    }
+    let mut children = uir.children.iter().filter(|c| !is_punctuation(c) && !is_comment(c));
+    let function = match uir.name {
+        Some(_) => None,
+        None => children.next(),
+    };
+    let mut arguments = Vec::new();
+    for child in children {
+        // Parsers that keep every node name the argument list or only tag it
+        let kind = child.name.as_deref().or(child.metadata.semantic_tags.first().map(String::as_str));
+        if matches!(kind, Some("argument_list" | "arguments" | "value_arguments")) {
+            arguments.extend(child.children.iter().filter(|a| !is_punctuation(a) && !is_comment(a)));
+        } else {
+            arguments.push(child);
+        }
+    }
+    (function, arguments)
+}
+
+/// A Rust macro invocation's path and token tree, as in `tracing::info!("x {}", e)`
+pub(crate) fn macro_invocation(uir: &UIRNode) -> Option<(&str, &UIRNode)> {
+    if !has_tag(uir, &["macro_invocation"]) {
+        return None;
+    }
+    let tokens = uir.children.iter().find(|c| c.metadata.semantic_tags.first().is_some_and(|t| t == "token_tree"))?;
+    Some((uir.name.as_deref()?, tokens))
+}
+
+pub(crate) fn is_comment(uir: &UIRNode) -> bool {
+    matches!(uir.node_type, NodeType::Comment { .. })
+}
+
+/// The decorators a Python definition was written with, without their `@`
+fn python_decorators(uir: &UIRNode) -> Vec<&str> {
+    uir.metadata.semantic_tags.iter().filter_map(|tag| tag.strip_prefix("decorator:")).collect()
+}
+
+/// Whether a decorator is `@dataclass`, with or without arguments
+fn is_dataclass(decorator: &str) -> bool {
+    let name = decorator.split('(').next().unwrap_or_default().trim();
+    name == "dataclass" || name.ends_with(".dataclass")
+}
+
+/// A module's items, those of the modules nested in it in their place: COBOL's divisions and
+/// sections are modules within the program, which becomes a single file
+pub(crate) fn module_items(uir: &UIRNode) -> Vec<&UIRNode> {
+    let mut items = Vec::new();
+    for child in &uir.children {
+        match child.node_type {
+            NodeType::Module => items.extend(module_items(child)),
+            _ => items.push(child),
+        }
+    }
+    items
+}
+
+/// A name as an identifier: COBOL's hyphenated names, such as `MAIN-PARA`, take underscores
+pub(crate) fn identifier(name: &str) -> String {
+    let hyphenated = !name.starts_with('-') && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if hyphenated {
+        name.replace('-', "_")
+    } else {
+        name.to_string()
+    }
+}
+
+/// The module-level names in `globals` that `function` assigns, sorted, other than its
+/// parameters and those its own `global` statement declares; nested functions bind their own
+fn written_globals(function: &UIRNode, globals: &HashSet<String>) -> Vec<String> {
+    let mut bound: HashSet<String> = function.children.iter()
+        .filter(|c| c.node_type == NodeType::Variable)
+        .filter_map(|c| c.name.as_deref().map(identifier))
+        .collect();
+    let mut written = Vec::new();
+    for child in &function.children {
+        walk(child, &mut |node: &UIRNode| {
+            if matches!(node.node_type, NodeType::Function | NodeType::Closure { .. }) {
+                return Walk::SkipChildren;
+            }
+            if has_tag(node, &["global_statement"]) {
+                bound.extend(node.children.iter().filter_map(|c| c.name.as_deref().map(identifier)));
+            } else if node.node_type == NodeType::Expression(ExpressionType::Assignment) {
+                written.extend(node.children.first().and_then(|t| t.name.as_deref()).map(identifier));
+            }
+            Walk::Continue
+        });
+    }
+    written.retain(|name| globals.contains(name) && !bound.contains(name));
+    written.sort();
+    written.dedup();
+    written
+}
+
+/// Imports of the source language's modules, which have no counterpart in the target, as are
+/// CommonJS's `const _ = require('lodash')`; the target's own imports come from
+/// `required_imports` and the generated code
+pub(crate) fn is_import(uir: &UIRNode) -> bool {
+    let required = |variable: &&UIRNode| {
+        let from = variable.metadata.annotations.get("destructured_from").and_then(|v| v.as_str());
+        let callee = initializer(variable).filter(|value| value.node_type == NodeType::Expression(ExpressionType::FunctionCall))
+            .and_then(|call| call_parts(call).0);
+        from.is_some_and(|from| from.starts_with("require(")) || callee.is_some_and(|callee| callee.name.as_deref() == Some("require"))
+    };
+    matches!(uir.node_type, NodeType::Import { .. })
+        || declared_variables(uir).is_some_and(|variables| variables.iter().all(required))
+}
+
+/// The source type recorded under `key`; a field's `type` is the one it was declared with
+pub(crate) fn declared_type<'a>(uir: &'a UIRNode, key: &str) -> Option<&'a str> {
+    match (&uir.node_type, key) {
+        (NodeType::Field { field_type: Some(field_type), .. }, "type") => Some(field_type),
+        (NodeType::Property { property_type: Some(property_type), .. }, "type") => Some(property_type),
+        _ => uir.metadata.annotations.get(key).and_then(|v| v.as_str()),
+    }
+}
+
+/// Whether data-flow analysis found a parameter or declaration assigned again once it has a
+/// value; without the analysis, generators count assignments by name
+pub(crate) fn reassigned(uir: &UIRNode) -> Option<bool> {
+    uir.metadata.annotations.get("reassigned").and_then(|v| v.as_bool())
+}
+
+/// The expression a declared variable starts out as: the last expression among its children
+/// other than its own name, as `g(a)` of JavaScript's `x = g(a)` or Go's `x := g(a)`
+pub(crate) fn initializer(uir: &UIRNode) -> Option<&UIRNode> {
+    let name = uir.name.as_deref().unwrap_or_default();
+    uir.children.iter()
+        .rev()
+        .filter(|c| matches!(c.node_type, NodeType::Expression(_) | NodeType::Closure { .. }) && !is_punctuation(c))
+        .find(|c| !matches!(original_text(c), "=" | ":=") && original_text(c) != name)
+}
+
+/// What a variable bound by destructuring, as `p` of `let [p, q] = pair`, takes from its
+/// source: the source's text followed by an index (`[0]`) or a key, spelled by `key`, per
+/// level of the pattern
+pub(crate) fn destructured_value(uir: &UIRNode, key: impl Fn(&str) -> String) -> Option<String> {
+    let source = uir.metadata.annotations.get("destructured_from")?.as_str()?;
+    let mut value = if source.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '.')) {
+        source.to_string()
+    } else {
+        format!("({})", source)
+    };
+    let path = uir.metadata.annotations.get("destructuring_path").and_then(|v| v.as_array());
+    for step in path.into_iter().flatten() {
+        match step {
+            serde_json::Value::Number(index) => value.push_str(&format!("[{}]", index)),
+            serde_json::Value::String(name) => value.push_str(&key(name)),
+            _ => {}
+        }
+    }
+    Some(value)
+}
+
+/// The default of a variable bound by destructuring, as `3` of `const {limit = 3} = filters`
+pub(crate) fn destructured_default(uir: &UIRNode) -> Option<&UIRNode> {
+    let defaulted = uir.metadata.annotations.get("has_default").and_then(|v| v.as_bool()).unwrap_or(false);
+    initializer(uir).filter(|_| defaulted)
+}
+
+/// The variables a declaration statement introduces, as the `Variable` children JavaScript's
+/// `const x = 1, [p, q] = pair` wraps; `None` for any other statement
+pub(crate) fn declared_variables(uir: &UIRNode) -> Option<Vec<&UIRNode>> {
+    if uir.node_type != NodeType::Statement(StatementType::Expression) {
+        return None;
+    }
+    let variables: Vec<&UIRNode> = uir.children.iter().filter(|c| !is_punctuation(c) && !is_comment(c)).collect();
+    (!variables.is_empty() && variables.iter().all(|c| c.node_type == NodeType::Variable)).then_some(variables)
+}
+
+/// `const`, `let` or `var` of a JavaScript declaration statement
+pub(crate) fn declaration_kind(uir: &UIRNode) -> Option<&str> {
+    uir.metadata.annotations.get("declaration_kind").and_then(|v| v.as_str())
+}
+
+/// The type a reference or pointer refers to: `T` of `&'a mut T`, `*const T`, `const T*` or `T&`
+pub(crate) fn pointee(source_type: &str) -> &str {
+    let mut pointee = source_type.trim();
+    loop {
+        let stripped = pointee.trim_start_matches('&').trim_start();
+        let stripped = match stripped.strip_prefix('\'') {
+            // A lifetime such as `'a` ends at the first space
+            Some(lifetime) => lifetime.split_once(' ').map_or(stripped, |(_, rest)| rest),
+            None => stripped,
+        };
+        let stripped = ["*const ", "*mut ", "mut ", "const ", "struct "].iter()
+            .find_map(|prefix| stripped.strip_prefix(prefix))
+            .unwrap_or(stripped)
+            .trim_end_matches(['*', '&'])
+            .trim_end()
+            .trim_end_matches(" const")
+            .trim();
+        if stripped == pointee {
+            return pointee;
+        }
+        pointee = stripped;
+    }
+}
+
+/// The initializer of a field or parameter, as written in the source
+pub(crate) fn declared_default(uir: &UIRNode) -> Option<&str> {
+    match &uir.node_type {
+        NodeType::Field { default_value: Some(value), .. } | NodeType::Property { default_value: Some(value), .. } => Some(value),
+        _ => uir.metadata.annotations.get("default_value").and_then(|v| v.as_str()),
+    }
+}
+
+/// Whether a class member holds data: a field, or a variable or constant declared in the body
+pub(crate) fn is_field(uir: &UIRNode) -> bool {
+    match uir.node_type {
+        NodeType::Field { .. } | NodeType::Variable | NodeType::Constant => true,
+        // Auto-implemented properties hold their value like a field
+        NodeType::Property { .. } => accessors(uir).is_empty(),
+        _ => false,
+    }
+}
+
+/// Whether a method is its class's constructor, by the name the source gave it
+pub(crate) fn is_constructor(method: &UIRNode) -> bool {
+    matches!(method.name.as_deref(), Some("constructor" | "__init__" | "init" | "New"))
+}
+
+/// The fields the methods of a class assign through `self` or `this`, which is how dynamic
+/// languages declare them, in order of first assignment
+pub(crate) fn assigned_fields(class: &UIRNode) -> Vec<String> {
+    let mut fields = Vec::new();
+    walk(class, &mut |node: &UIRNode| {
+        if node.node_type == NodeType::Expression(ExpressionType::Assignment) {
+            let target = node.children.first().map_or("", original_text);
+            if let Some(field) = target.strip_prefix("self.").or_else(|| target.strip_prefix("this.")) {
+                if !fields.iter().any(|known| known == field) {
+                    fields.push(field.to_string());
+                }
+            }
+        }
+        Walk::Continue
+    });
+    fields
+}
+
+/// The getter and setter bodies of a property; none for an auto-implemented one
+pub(crate) fn accessors(uir: &UIRNode) -> Vec<&UIRNode> {
+    uir.children.iter()
+        .filter(|c| c.node_type == NodeType::Function && c.metadata.semantic_tags.iter().any(|t| t == "getter" || t == "setter"))
+        .collect()
+}
+
+/// A property's accessors as methods or functions of their own: the getter named after the
+/// property and the setter `set_<name>`, for targets without properties
+pub(crate) fn accessor_functions(property: &UIRNode) -> Vec<UIRNode> {
+    accessors(property).into_iter()
+        .map(|accessor| {
+            let mut function = accessor_method(property, accessor);
+            if is_setter(accessor) {
+                function.name = function.name.map(|name| format!("set_{}", name));
+            }
+            function
+        })
+        .collect()
+}
+
+/// Whether an accessor sets its property rather than reading it
+pub(crate) fn is_setter(accessor: &UIRNode) -> bool {
+    accessor.metadata.semantic_tags.iter().any(|t| t == "setter")
+}
+
+/// An accessor as a method named after its property: a getter returning the property's type,
+/// or a setter taking it as `value`, which C# setters leave implicit
+pub(crate) fn accessor_method(property: &UIRNode, accessor: &UIRNode) -> UIRNode {
+    let mut method = accessor.clone();
+    method.name = property.name.clone();
+    method.visibility = accessor.visibility.or(property.visibility);
+    method.modifiers.extend(property.modifiers.iter().cloned());
+    let property_type = declared_type(property, "type").map(|t| serde_json::Value::String(t.to_string()));
+    if !is_setter(accessor) {
+        method.documentation = accessor.documentation.clone().or_else(|| property.documentation.clone());
+        if let Some(property_type) = property_type {
+            method.metadata.annotations.entry("return_type".to_string()).or_insert(property_type);
+        }
+    } else if !method.children.iter().any(|c| c.node_type == NodeType::Variable) {
+        method = match declared_type(property, "type") {
+            Some(property_type) => method.typed_param("value", property_type),
+            None => method.param("value"),
+        };
+    }
+    method
+}
+
+/// The value of each variant for targets that number enums like C: the value the source gave
+/// it, or one more than the previous variant's (0 for the first). Go's `iota` is its position.
+pub(crate) fn enum_values(variants: &[EnumVariant]) -> Vec<String> {
+    let mut values: Vec<String> = Vec::new();
+    for (index, variant) in variants.iter().enumerate() {
+        let value = match (variant.value.as_deref(), values.last()) {
+            (Some("iota"), _) => index.to_string(),
+            (Some(value), _) => value.to_string(),
+            (None, None) => "0".to_string(),
+            (None, Some(previous)) => match previous.parse::<i64>() {
+                Ok(number) => (number + 1).to_string(),
+                Err(_) => format!("{} + 1", previous),
+            },
+        };
+        values.push(value);
+    }
+    values
+}
+
+/// Whether any variant carries data, making the enum a tagged union
+pub(crate) fn has_payload(variants: &[EnumVariant]) -> bool {
+    variants.iter().any(|v| !v.fields.is_empty())
+}
+
+/// A comment in the target's syntax: each line after `line`, or `doc` for doc comments, and
+/// block comments between `block` delimiters when the target has them
+///
+/// Doc comments attached to a declaration come out empty: they are emitted from its
+/// `documentation` in the target's own documentation format instead
+pub(crate) fn comment_code(uir: &UIRNode, line: &str, doc: &str, block: Option<(&str, &str)>) -> String {
+    let NodeType::Comment { style, text, .. } = &uir.node_type else {
+        return String::new();
+    };
+    if is_documentation(uir) {
+        return String::new();
+    }
+    if let (CommentStyle::Block, Some((open, close))) = (style, block) {
+        return format!("{} {} {}", open, text, close);
+    }
+    let prefix = if *style == CommentStyle::Doc { doc } else { line };
+    if text.is_empty() {
+        return prefix.to_string();
+    }
+    text.lines()
+        .map(|l| if l.trim().is_empty() { prefix.to_string() } else { format!("{} {}", prefix, l) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The comments among `siblings` attached to `declaration`, for generators that regroup
+/// declarations and would otherwise leave their comments behind
+pub(crate) fn attached_comments<'a>(siblings: &'a [UIRNode], declaration: &UIRNode) -> Vec<&'a UIRNode> {
+    siblings.iter()
+        .filter(|c| !is_documentation(c))
+        .filter(|c| matches!(&c.node_type, NodeType::Comment { attached_to: Some(id), .. } if *id == declaration.id))
+        .collect()
+}
+
+/// A doc comment attached to a declaration, whose text the parser also recorded as the
+/// declaration's `documentation`
+pub(crate) fn is_documentation(uir: &UIRNode) -> bool {
+    matches!(&uir.node_type, NodeType::Comment { style: CommentStyle::Doc, attached_to: Some(_), .. })
+}
+
+/// `documentation` as comment lines after `prefix`, such as Rust's `///` or Go's `//`, each
+/// ending in a newline; empty for undocumented nodes
+pub(crate) fn documentation_lines(uir: &UIRNode, prefix: &str) -> String {
+    uir.documentation.iter()
+        .flat_map(|documentation| documentation.lines())
+        .map(|line| if line.trim().is_empty() { format!("{}\n", prefix) } else { format!("{} {}\n", prefix, line) })
+        .collect()
+}
+
+/// `documentation` as a `/** */` block for JSDoc and Javadoc-style targets, ending in a newline;
+/// empty for undocumented nodes
+pub(crate) fn documentation_block(uir: &UIRNode) -> String {
+    match uir.documentation.as_deref() {
+        None => String::new(),
+        Some(documentation) if !documentation.contains('\n') => format!("/** {} */\n", documentation),
+        Some(_) => format!("/**\n{} */\n", documentation_lines(uir, " *")),
+    }
+}
+
+/// A closure's parameters and the statements or expression of its body
+pub(crate) fn closure_parts(uir: &UIRNode) -> (Vec<&UIRNode>, Vec<&UIRNode>) {
+    uir.children.iter()
+        .filter(|c| !is_punctuation(c))
+        .partition(|c| c.node_type == NodeType::Variable)
+}
+
+/// The single value a closure body evaluates to, as in `x => x + 1` or `{ return x + 1; }`
+pub(crate) fn closure_value<'a>(body: &[&'a UIRNode]) -> Option<&'a UIRNode> {
+    match body {
+        [value] => match &value.node_type {
+            NodeType::Expression(_) => Some(value),
+            NodeType::Statement(StatementType::Return) => {
+                let parts: Vec<&UIRNode> = value.children.iter().filter(|c| !is_punctuation(c)).collect();
+                closure_value(&parts)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The parts of a loop: the `for` header's initializer, condition and update, the target and
+/// iterable of a for-each, the statements of its body, and those of the `else` clause Python
+/// runs when the loop ends without `break`
+pub(crate) struct LoopParts<'a> {
+    pub(crate) initializer: Option<&'a UIRNode>,
+    pub(crate) condition: Option<&'a UIRNode>,
+    pub(crate) update: Option<&'a UIRNode>,
+    pub(crate) target: Option<&'a UIRNode>,
+    pub(crate) iterable: Option<&'a UIRNode>,
+    pub(crate) body: Vec<&'a UIRNode>,
+    pub(crate) otherwise: Vec<&'a UIRNode>,
+}
+
+pub(crate) fn loop_parts(uir: &UIRNode) -> LoopParts<'_> {
+    let mut rest: Vec<&UIRNode> = uir.children.iter().filter(|c| !is_punctuation(c)).collect();
+    let mut parts = LoopParts { initializer: None, condition: None, update: None, target: None, iterable: None, body: Vec::new(), otherwise: Vec::new() };
+    let is_expression = |c: &&UIRNode| matches!(c.node_type, NodeType::Expression(_));
+    match uir.node_type {
+        // JavaScript tags each part of the header it has
+        NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For)) => {
+            let role = |tag: &str| rest.iter().copied().find(|c| has_tag(c, &[tag]));
+            (parts.initializer, parts.condition, parts.update) = (role("loop_initializer"), role("loop_condition"), role("loop_update"));
+            rest.retain(|c| !has_tag(c, &["loop_initializer", "loop_condition", "loop_update"]));
+        }
+        NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)) if rest.len() >= 2 => {
+            (parts.target, parts.iterable) = (Some(rest[0]), Some(rest[1]));
+            rest.drain(..2);
+        }
+        // Rust's `loop` has no condition
+        NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)) if rest.first().is_some_and(is_expression) => {
+            parts.condition = Some(rest.remove(0));
+        }
+        NodeType::ControlFlow(ControlFlowType::Loop(LoopType::DoWhile)) if rest.last().is_some_and(is_expression) => {
+            parts.condition = rest.pop();
+        }
+        _ => {}
+    }
+    let (otherwise, body): (Vec<&UIRNode>, Vec<&UIRNode>) = rest.into_iter().partition(|c| has_tag(c, &["else_clause"]));
+    parts.body = body;
+    parts.otherwise = otherwise.into_iter().flat_map(|c| c.children.iter()).collect();
+    parts
+}
+
+/// A `for` loop counting a variable it declares from `start` to `end` by a constant `step`,
+/// which targets with ranges write as `range(start, end)` or `start..end`
+pub(crate) struct CountedLoop<'a> {
+    pub(crate) variable: &'a str,
+    pub(crate) start: &'a UIRNode,
+    pub(crate) end: &'a UIRNode,
+    pub(crate) inclusive: bool,
+    pub(crate) step: i64,
+}
+
+pub(crate) fn counted_loop<'a>(parts: &LoopParts<'a>) -> Option<CountedLoop<'a>> {
+    // A `var` outlives the loop, holding the value that ended it
+    let declaration = parts.initializer?;
+    if !matches!(declaration_kind(declaration), Some("let" | "const")) {
+        return None;
+    }
+    let [variable] = declared_variables(declaration)?[..] else {
+        return None;
+    };
+    let name = variable.name.as_deref()?;
+    let (condition, update) = (parts.condition?, parts.update?);
+    let [counter, end] = condition.children.iter().filter(|c| !is_punctuation(c)).collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let amount = update.children.last().and_then(integer_value)?;
+    let step = match update.operator? {
+        Operator::Add => amount,
+        Operator::Subtract => -amount,
+        _ => return None,
+    };
+    let inclusive = match (condition.operator?, step > 0) {
+        (Operator::Less, true) | (Operator::Greater, false) => false,
+        (Operator::LessEqual, true) | (Operator::GreaterEqual, false) => true,
+        _ => return None,
+    };
+    let counts = counter.name.as_deref() == Some(name) && update.children.first().and_then(|t| t.name.as_deref()) == Some(name);
+    if !counts || step == 0 || parts.body.iter().any(|statement| assigns(statement, name)) {
+        return None;
+    }
+    Some(CountedLoop { variable: name, start: initializer(variable)?, end, inclusive, step })
+}
+
+impl CountedLoop<'_> {
+    /// The bound a half-open range stops at, given `end` rendered for the target
+    pub(crate) fn exclusive_end(&self, end: String) -> String {
+        self.offset_end(end, self.inclusive)
+    }
+    
+    /// The last value counted, given `end` rendered for the target
+    pub(crate) fn inclusive_end(&self, end: String) -> String {
+        self.offset_end(end, !self.inclusive)
+    }
+    
+    /// `end`, moved one further in the direction of counting when `shift` holds, or back
+    fn offset_end(&self, end: String, shift: bool) -> String {
+        let offset = match (shift, self.inclusive) {
+            (false, _) => return end,
+            (true, true) => self.step.signum(),
+            (true, false) => -self.step.signum(),
+        };
+        match integer_value(self.end) {
+            Some(value) => (value + offset).to_string(),
+            None if offset > 0 => format!("{} + 1", end),
+            None => format!("{} - 1", end),
+        }
+    }
+}
+
+/// Whether a for-each loop walks the keys of what it iterates, as JavaScript's `for (k in o)`
+pub(crate) fn iterates_keys(uir: &UIRNode) -> bool {
+    uir.metadata.annotations.get("iteration").and_then(|v| v.as_str()) == Some("in")
+}
+
+/// The value of an integer literal
+pub(crate) fn integer_value(uir: &UIRNode) -> Option<i64> {
+    match (&uir.node_type, &uir.literal) {
+        (NodeType::Expression(ExpressionType::Literal), Some(LiteralValue::Int(value))) => Some(*value),
+        (NodeType::Expression(ExpressionType::Literal), None) => original_text(uir).parse().ok(),
+        _ => None,
+    }
+}
+
+/// Whether `uir` assigns to the variable `name`
+pub(crate) fn assigns(uir: &UIRNode, name: &str) -> bool {
+    uir.node_type == NodeType::Expression(ExpressionType::Assignment) && uir.children.first().and_then(|t| t.name.as_deref()) == Some(name)
+        || uir.children.iter().any(|c| assigns(c, name))
+}
+
+/// The body of a `for` or `do ... while` loop lowered to a `while` or endless loop: its
+/// statements followed by the `for` update, or by the `do` loop's exit test, which each
+/// `continue` of the loop runs first
+pub(crate) fn lowered_body(uir: &UIRNode, parts: &LoopParts) -> Vec<UIRNode> {
+    let tail = match uir.node_type {
+        NodeType::ControlFlow(ControlFlowType::Loop(LoopType::DoWhile)) => parts.condition.map(exit_test),
+        _ => parts.update.cloned(),
+    };
+    let mut body: Vec<UIRNode> = parts.body.iter().map(|statement| (*statement).clone()).collect();
+    if let Some(tail) = tail {
+        for statement in &mut body {
+            run_before_continue(statement, &tail);
+        }
+        body.push(tail);
+    }
+    body
+}
+
+/// `if !(condition) break`
+fn exit_test(condition: &UIRNode) -> UIRNode {
+    let language = condition.metadata.source_language.clone();
+    let mut negation = UIRNode::new(format!("{}_negation", condition.id), NodeType::Expression(ExpressionType::Logical))
+        .add_child(condition.clone())
+        .annotated("original_text", format!("!({})", original_text(condition)));
+    negation.operator = Some(Operator::Not);
+    let exit = UIRNode::new(format!("{}_break", condition.id), NodeType::Statement(StatementType::Break));
+    UIRNode::new(format!("{}_exit", condition.id), NodeType::ControlFlow(ControlFlowType::Conditional))
+        .body([negation, exit])
+        .in_language(language)
+}
+
+fn run_before_continue(statement: &mut UIRNode, tail: &UIRNode) {
+    match statement.node_type {
+        NodeType::Statement(StatementType::Continue) => {
+            let id = format!("{}_continued", statement.id);
+            let continued = std::mem::replace(statement, UIRNode::new(id, NodeType::Statement(StatementType::Expression)));
+            statement.metadata.source_language = continued.metadata.source_language.clone();
+            statement.children = vec![tail.clone(), continued];
+        }
+        // A nested loop's `continue` is its own
+        NodeType::ControlFlow(ControlFlowType::Loop(_)) | NodeType::Function | NodeType::Closure { .. } => {}
+        _ => {
+            for child in &mut statement.children {
+                run_before_continue(child, tail);
+            }
+        }
+    }
+}
+
+/// Whether `uir` leaves the loop around it with `break`, ignoring loops nested inside
+pub(crate) fn breaks(uir: &UIRNode) -> bool {
+    match uir.node_type {
+        NodeType::Statement(StatementType::Break) => true,
+        NodeType::ControlFlow(ControlFlowType::Loop(_) | ControlFlowType::Switch) | NodeType::Function | NodeType::Closure { .. } => false,
+        _ => uir.children.iter().any(breaks),
+    }
+}
+
+/// The classes a class extends, as written: Python's bases and keywords such as `metaclass=M`,
+/// or what other parsers record as `inherits`
+pub(crate) fn class_bases(uir: &UIRNode) -> Vec<String> {
+    if uir.metadata.source_language == Language::Python {
+        return uir.metadata.dependencies.clone();
+    }
+    match uir.metadata.annotations.get("inherits") {
+        Some(serde_json::Value::String(base)) => vec![base.clone()],
+        Some(serde_json::Value::Array(bases)) => bases.iter().filter_map(|b| b.as_str()).map(str::to_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Static members, whether the parser recorded a modifier or only a `static` tag
+pub(crate) fn is_static(uir: &UIRNode) -> bool {
+    uir.modifiers.contains(&Modifier::Static) || uir.metadata.semantic_tags.iter().any(|t| t == "static")
+}
+
+/// The Rust field prefix for a source access level; unannotated fields stay public
+fn rust_visibility(visibility: Option<Visibility>) -> &'static str {
+    match visibility {
+        None | Some(Visibility::Public) => "pub ",
+        Some(Visibility::Internal | Visibility::ProtectedInternal | Visibility::Protected) => "pub(crate) ",
+        Some(Visibility::Private | Visibility::PrivateProtected) => "",
+    }
+}
+
+pub(crate) fn has_tag(uir: &UIRNode, tags: &[&str]) -> bool {
+    uir.metadata.semantic_tags.iter().any(|t| tags.contains(&t.as_str()))
+        || uir.name.as_deref().is_some_and(|n| tags.contains(&n))
+}
+
+/// `parseHTTPResponse` / `ParseResponse` / `parse-response` -> `parse_http_response` etc.
+pub(crate) fn to_snake_case(name: &str) -> String {
+    NamingConvention::SnakeCase.apply(name)
+}
+
+/// `user_account` / `userAccount` -> `UserAccount`
+pub(crate) fn to_pascal_case(name: &str) -> String {
+    NamingConvention::PascalCase.apply(name)
+}
+
+/// Split generic arguments on top-level commas: `K, List<V>` -> [`K`, `List<V>`]
+pub(crate) fn split_type_arguments(arguments: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in arguments.char_indices() {
+        match c {
+            '<' | '[' | '(' => depth += 1,
+            '>' | ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(arguments[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = arguments[start..].trim();
+    if !last.is_empty() {
+        parts.push(last);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::{BraceStyle, Parser};
+    use coalesce_parser::{CobolParser, ErlangParser, JavaScriptParser, PythonParser};
+    
+    pub(crate) fn generate(generator: &dyn Generator, source: &str) -> String {
+        generate_with(generator, source, &GeneratorConfig::default())
+    }
+    
+    fn generate_with(generator: &dyn Generator, source: &str, config: &GeneratorConfig) -> String {
+        let uir = JavaScriptParser::new().unwrap().parse(source).unwrap();
+        generator.generate_with_config(&uir, config).unwrap()
+    }
+    
+    #[test]
+    fn test_javascript_declarations_keep_their_initializers() {
+        let source = "function f(a){ const x = g(a); let [p, q] = a; return x + p; }";
+        
+        let python = generate(&PythonGenerator, source);
+        assert!(python.contains("    x = g(a)\n    p = a[0]\n    q = a[1]\n"), "{}", python);
+        assert!(!python.contains(FALLBACK_TODO));
+        
+        let rust = generate(&RustGenerator, source);
+        assert!(rust.contains("    let x = g(a);\n    let p = a[0];\n    let q = a[1];\n"), "{}", rust);
+        assert!(!rust.contains(FALLBACK_TODO));
+    }
+    
+    #[test]
+    fn test_rust_declares_javascript_module_variables_as_items() {
+        let source = "const _ = require('lodash');\nconst LIMIT = 5;\nlet count = 0;\nconst { a } = LIMIT;\nfunction f() { count += 1; return LIMIT + count; }";
+        let rust = generate(&RustGenerator, source);
+        assert!(!rust.contains("let "), "{}", rust);
+        assert!(!rust.contains("require("), "{}", rust);
+        assert!(rust.contains("const LIMIT: i32 = 5;\n"), "{}", rust);
+        assert!(rust.contains("pub struct State {\n    pub count: i32,\n}"), "{}", rust);
+        assert!(rust.contains(&format!("// {}: const {{ a }} = LIMIT;\n", FALLBACK_TODO)), "{}", rust);
+    }
+    
+    #[test]
+    fn test_rust_reads_parameters_by_the_names_it_declares_them_with() {
+        let source = "const LIMIT = 5;\nfunction addOne(myVal, Other) {\n  const f = (Inner) => Inner + Other;\n  return myVal + f(Other) + LIMIT;\n}\n";
+        let rust = generate(&RustGenerator, source);
+        assert!(rust.contains("fn add_one(my_val: i32, other: i32) -> i32 {\n"), "{}", rust);
+        assert!(rust.contains("    let f = |inner| inner + other;\n    my_val + f(other) + LIMIT\n"), "{}", rust);
+    }
+    
+    #[test]
+    fn test_javascript_destructuring_defaults_are_kept() {
+        let source = "function f(filters, pair){ const {status, limit = 3} = filters; const [a, b = 2] = pair; return [status, limit, a, b]; }";
+        
+        let python = generate(&PythonGenerator, source);
+        assert!(python.contains("    status = filters[\"status\"]\n    limit = filters.get(\"limit\", 3)\n"), "{}", python);
+        assert!(python.contains("    b = pair[1] if len(pair) > 1 else 2\n"), "{}", python);
+        
+        let typescript = generate(&TypeScriptGenerator, source);
+        assert!(typescript.contains("  const limit = filters.limit ?? 3;\n"), "{}", typescript);
+        assert!(typescript.contains("  const b = pair[1] ?? 2;\n"), "{}", typescript);
+    }
+    
+    #[test]
+    fn test_rust_callers_of_fallible_functions_propagate_their_errors() {
+        let source = "function check(v) { if (v < 0) { throw new Error(\"negative\"); } return v; }\n\
+            function total(a, b) { return check(a) + check(b); }\n\
+            function safe(a) { try { const t = total(a, 1); log(t); } catch (e) { log(e); } }\n\
+            function lazy(a) { const f = () => check(a); return f; }";
+        let rust = generate(&RustGenerator, source);
+        assert!(rust.contains("fn total(a: i32, b: i32) -> Result<i32, Box<dyn std::error::Error>> {\n    Ok(check(a)? + check(b)?)\n}"), "{}", rust);
+        // Caught by the try, which lowers to a closure returning `Result`
+        assert!(rust.contains("fn safe(a: i32) {\n"), "{}", rust);
+        assert!(rust.contains("        let t = total(a, 1)?;\n"), "{}", rust);
+        // A closure's errors are its own
+        assert!(rust.contains("fn lazy(a: i32) -> i32 {\n    let f = || check(a);\n"), "{}", rust);
+    }
+    
+    /// A throwing method and a free function of the same name that doesn't throw
+    pub(crate) const THROWING_METHOD: &str = "class Account {\n\
+        \x20 deposit(amount) { if (amount < 0) { throw new Error(\"negative\"); } this.balance += amount; return this.balance; }\n\
+        \x20 refill() { return this.deposit(10); }\n}\n\
+        function deposit(total) { return total + 1; }\n\
+        function run(a) { try { a.deposit(5); deposit(1); } catch (e) { log(e); } }\n";
+    
+    #[test]
+    fn test_rust_propagates_errors_of_methods_by_their_owner() {
+        let rust = generate(&RustGenerator, THROWING_METHOD);
+        assert!(rust.contains("        Ok(self.deposit(10)?)\n"), "{}", rust);
+        assert!(rust.contains("fn deposit(total: i32) -> i32 {\n"), "{}", rust);
+        assert!(rust.contains("        a.deposit(5)?;\n        deposit(1);\n        Ok(())\n"), "{}", rust);
+    }
+    
+    #[test]
+    fn test_styles_move_block_braces_and_wrap_argument_lists_only() {
+        let comment = "// TODO: a comment longer than the maximum line length, which stays on one line";
+        let source = format!("{}\nfunction make(a, b) {{ if (a > b) {{ return {{ a: a, b: b }}; }} else {{ return compute(a, b, \"the label text\"); }} }}", comment);
+        let style = GeneratorConfig {
+            use_tabs: true,
+            brace_style: BraceStyle::NextLine,
+            max_line_length: Some(30),
+            ..GeneratorConfig::default()
+        };
+        let rust = generate_with(&RustGenerator, &source, &style);
+        assert!(rust.contains(comment), "{}", rust);
+        let expected = "fn make(a: i32, b: i32) -> i32\n{\n\
+            \tif a > b\n\t{\n\
+            \t\treturn { a: a, b: b };\n\
+            \t}\n\telse\n\t{\n\
+            \t\treturn compute(\n\t\t\ta,\n\t\t\tb,\n\t\t\t\"the label text\");\n\
+            \t}\n}";
+        assert!(rust.contains(expected), "{}", rust);
+        // Go's grammar keeps braces on the line they open
+        let go = generate_with(&GoGenerator, &source, &style);
+        assert!(go.contains("func make(a int, b int) int {\n\tif a > b {\n"), "{}", go);
+        // Without a configured width each target keeps its own
+        let typescript = generate_with(&TypeScriptGenerator, "class P { m() { return 1; } }", &GeneratorConfig::default());
+        assert!(typescript.contains("\n  m(): any {\n    return 1;\n  }\n"), "{}", typescript);
+        let wide = GeneratorConfig { indent_width: Some(4), ..GeneratorConfig::default() };
+        let typescript = generate_with(&TypeScriptGenerator, "class P { m() { return 1; } }", &wide);
+        assert!(typescript.contains("\n    m(): any {\n        return 1;\n    }\n"), "{}", typescript);
+    }
+    
+    #[test]
+    fn test_python_round_trips_parameters_clauses_and_bases() {
+        let source = "class C(B, metaclass=M):\n    def todo(self):\n        ...\n\
+            async def f(a, b=2, *args, c=3, **kw):\n    async with lock() as l:\n        pass\n    async for x in items():\n        print(x)\n\
+            \x20   try:\n        g()\n    except ValueError as e:\n        raise KeyError(\"k\") from e\n    else:\n        h()\n    finally:\n        done()\n\
+            \x20   while a:\n        a -= 1\n    else:\n        print(\"no break\")\n    return b\n\
+            def gen():\n    return (yield)\n";
+        let uir = PythonParser::new().unwrap().parse(source).unwrap();
+        let python = PythonGenerator.generate(&uir).unwrap();
+        assert!(python.ends_with(source), "{}", python);
+        
+        // A body left with only a TODO still needs a statement
+        let mut todo = PythonParser::new().unwrap().parse("def f():\n    return 1\n").unwrap();
+        todo.children[0].children[0].node_type = NodeType::Interface;
+        let python = PythonGenerator.generate(&todo).unwrap();
+        assert!(python.contains(&format!("def f():\n    # {}\n    pass", FALLBACK_TODO)), "{}", python);
+    }
+    
+    #[test]
+    fn test_loops_keep_their_headers_in_every_target() {
+        let source = "function f(n, o) {\n\
+            \x20 let s = 0;\n\
+            \x20 for (let i = 0; i < n; i++) { s += i; }\n\
+            \x20 for (let i = 1; i < n; i *= 2) { if (i == 3) continue; s += i; }\n\
+            \x20 do { s++; } while (s < 5);\n\
+            \x20 for (const k in o) { log(k); }\n\
+            \x20 return s;\n}";
+        
+        let python = generate(&PythonGenerator, source);
+        assert!(python.contains("    for i in range(n):\n        s += i\n"), "{}", python);
+        assert!(python.contains("    i = 1\n    while i < n:\n        if i == 3:\n            i *= 2\n            continue\n        s += i\n        i *= 2\n"), "{}", python);
+        assert!(python.contains("    while True:\n        s += 1\n        if not (s < 5):\n            break\n"), "{}", python);
+        
+        let rust = generate(&RustGenerator, source);
+        assert!(rust.contains("    for i in 0..n {\n"), "{}", rust);
+        assert!(rust.contains("    let mut i = 1;\n    while i < n {\n"), "{}", rust);
+        assert!(rust.contains("    loop {\n        s += 1;\n        if !(s < 5) {\n            break;\n        }\n    }\n"), "{}", rust);
+        assert!(rust.contains("    for k in o.keys() {\n"), "{}", rust);
+        
+        let go = generate(&GoGenerator, source);
+        assert!(go.contains("    for i := 0; i < n; i += 1 {\n"), "{}", go);
+        assert!(go.contains("    for i := 1; i < n; i *= 2 {\n        if i == 3 {\n            continue\n        }\n"), "{}", go);
+        assert!(go.contains("    for k := range o {\n"), "{}", go);
+        
+        let lua = generate(&TemplateGenerator::lua().unwrap(), source);
+        assert!(lua.contains("    for i = 0, n - 1 do\n"), "{}", lua);
+        assert!(lua.contains("            i = i * 2\n            goto continue\n"), "{}", lua);
+        assert!(lua.contains("        i = i * 2\n        ::continue::\n    end\n"), "{}", lua);
+        assert!(lua.contains("    for k in pairs(o) do\n"), "{}", lua);
+        for code in [&python, &rust, &go, &lua] {
+            assert!(!code.contains("if while") && !code.contains("if for"), "{}", code);
+        }
+    }
+    
+    #[test]
+    fn test_ternaries_group_and_lower_per_target() {
+        let source = "function f(a, b, c) { const x = a ? b : c ? 2 : 3; return (a ? b : c) + 1 + x; }";
+        
+        let python = generate(&PythonGenerator, source);
+        assert!(python.contains("x = b if a else 2 if c else 3\n"), "{}", python);
+        assert!(python.contains("return (b if a else c) + 1 + x\n"), "{}", python);
+        
+        let rust = generate(&RustGenerator, source);
+        assert!(rust.contains("let x = if a { b } else if c { 2 } else { 3 };"), "{}", rust);
+        assert!(rust.contains("(if a { b } else { c }) + 1 + x"), "{}", rust);
+        
+        let go = generate(&GoGenerator, source);
+        assert!(go.contains("return (func() int { if a { return b }; return c }()) + 1 + x"), "{}", go);
+        
+        let typescript = generate(&TypeScriptGenerator, source);
+        assert!(typescript.contains("return (a ? b : c) + 1 + x;"), "{}", typescript);
+        
+        let lua = generate(&TemplateGenerator::lua().unwrap(), source);
+        assert!(lua.contains("local x = a and b or c and 2 or 3\n"), "{}", lua);
+        assert!(lua.contains("return (a and b or c) + 1 + x\n"), "{}", lua);
+        
+        let python_source = PythonParser::new().unwrap()
+            .parse("def f(a, b, c):\n    return (b if a else c) + 1\n").unwrap();
+        let rust = RustGenerator.generate(&python_source).unwrap();
+        assert!(rust.contains("(if a { b } else { c }) + 1"), "{}", rust);
+    }
+    
+    #[test]
+    fn test_rust_try_returns_its_bindings_and_module_assignments_are_constants() {
+        let source = "LIMIT = 10\nLIMIT = 20\n\n\
+            def load(n):\n\
+            \x20   try:\n\
+            \x20       value = n * LIMIT\n\
+            \x20       other = value + 1\n\
+            \x20   except ValueError as e:\n\
+            \x20       value = 0\n\
+            \x20   finally:\n\
+            \x20       pass\n\
+            \x20   return value, other\n\n\
+            def root(n):\n\
+            \x20   return (n + 1) ** 0.5\n";
+        let module = PythonParser::new().unwrap().parse(source).unwrap();
+        let rust = RustGenerator.generate(&module).unwrap();
+        assert!(rust.contains("const LIMIT: i32 = 10;"), "{}", rust);
+        assert!(rust.contains(&format!("// {}: LIMIT = 20", FALLBACK_TODO)), "{}", rust);
+        assert!(!rust.contains("\nlet "), "{}", rust);
+        assert!(rust.contains("= match (|| -> Result<_, Box<dyn std::error::Error>> {"), "{}", rust);
+        assert!(rust.contains("Ok((value, other))\n"), "{}", rust);
+        assert!(rust.contains("Ok(values) => values,"), "{}", rust);
+        // The handler leaves `other` unset
+        assert!(rust.contains("(value, Default::default())\n"), "{}", rust);
+        assert!(!rust.contains("pass"), "{}", rust);
+        assert!(rust.contains("    (value, other)\n}"), "{}", rust);
+        assert!(rust.contains("((n + 1) as f64).powf(0.5)"), "{}", rust);
+    }
+    
+    #[test]
+    fn test_rust_working_storage_is_one_state_struct_passed_by_mut() {
+        let source = "       IDENTIFICATION DIVISION.\n       PROGRAM-ID. SAMPLE.\n       DATA DIVISION.\n\
+            \x20      WORKING-STORAGE SECTION.\n\
+            \x20      01 WS-COUNT PIC 9(3) VALUE 0.\n\
+            \x20      01 WS-TOTAL PIC 9(5)V99 VALUE 0.\n\
+            \x20      01 WS-LIMIT PIC 9(3) VALUE 50.\n\
+            \x20      PROCEDURE DIVISION.\n\
+            \x20      MAIN-PARA.\n\
+            \x20          IF WS-LIMIT > 10\n\
+            \x20              COMPUTE WS-TOTAL = WS-COUNT * 1.5\n\
+            \x20          END-IF\n\
+            \x20          PERFORM CALC-PARA\n\
+            \x20          GO TO END-PARA.\n\
+            \x20      CALC-PARA.\n\
+            \x20          ADD 1 TO WS-COUNT.\n\
+            \x20      END-PARA.\n\
+            \x20          DISPLAY WS-TOTAL\n\
+            \x20          STOP RUN.\n";
+        let module = CobolParser::new().unwrap().parse(source).unwrap();
+        let rust = RustGenerator.generate(&module).unwrap();
+        assert!(rust.contains("pub struct State {\n    pub ws_count: i32,\n    pub ws_total: f64,\n}"), "{}", rust);
+        assert!(rust.contains("ws_count: 0,\n            ws_total: 0.0,"), "{}", rust);
+        assert!(rust.contains("const WS_LIMIT: i32 = 50;"), "{}", rust);
+        assert!(!rust.contains("let mut ws_"), "{}", rust);
+        assert!(rust.contains("fn main_para(state: &mut State) {"), "{}", rust);
+        // The `END-IF` closes the `IF` rather than ending the computed expression
+        assert!(rust.contains("    state.ws_total = (state.ws_count as f64) * 1.5;\n    }\n    calc_para(state);"), "{}", rust);
+        assert!(rust.contains("end_para(state);\n    return;"), "{}", rust);
+        assert!(rust.contains("state.ws_count = 1 + state.ws_count;"), "{}", rust);
+        assert!(rust.contains("fn end_para(state: &mut State) {\n    display(state.ws_total);"), "{}", rust);
+    }
+    
+    #[test]
+    fn test_erlang_clauses_and_comprehensions_lower_to_python() {
+        let source = "-module(m).\n\nfact(0) -> 1;\nfact(N) when N > 0 -> N * fact(N - 1).\n\n\
+            double(L) -> [X * 2 || X <- L].\n\n\
+            loop() ->\n    receive\n        {ping, From} -> From ! pong, loop();\n        stop -> ok\n    end.\n";
+        let module = ErlangParser::new().unwrap().parse(source).unwrap();
+        let python = PythonGenerator.generate(&module).unwrap();
+        assert!(python.contains("    if N == 0:\n        return 1\n    elif N > 0:\n        return N * fact(N - 1)\n    else:\n        raise"), "{}", python);
+        assert!(python.contains("return [X * 2 for X in L]"), "{}", python);
+        assert!(python.contains("    if Message[0] == \"ping\":\n        From = Message[1]\n"), "{}", python);
+        assert!(!python.contains("while True"), "{}", python);
+    }
+    
+    #[test]
+    fn test_floor_division_and_power_map_per_target() {
+        let module = PythonParser::new().unwrap()
+            .parse("def f(a, b):\n    c = a // b\n    a //= 2\n    return c + a ** b\n").unwrap();
+        
+        let python = PythonGenerator.generate(&module).unwrap();
+        assert!(python.contains("c = a // b\n"), "{}", python);
+        assert!(python.contains("a //= 2\n"), "{}", python);
+        
+        let rust = RustGenerator.generate(&module).unwrap();
+        assert!(rust.contains("let c = (a as f64 / b as f64).floor() as i32;"), "{}", rust);
+        assert!(rust.contains("a = (a as f64 / 2 as f64).floor() as i32;"), "{}", rust);
+        
+        let go = GoGenerator.generate(&module).unwrap();
+        assert!(go.contains("c := int(math.Floor(float64(a) / float64(b)))"), "{}", go);
+        assert!(go.contains("import \"math\""), "{}", go);
+        
+        let typescript = TypeScriptGenerator.generate(&module).unwrap();
+        assert!(typescript.contains("Math.floor(a / b)"), "{}", typescript);
+        
+        let lua = TemplateGenerator::lua().unwrap().generate(&module).unwrap();
+        assert!(lua.contains("local c = a // b\n"), "{}", lua);
+        assert!(lua.contains("a = a // 2\n"), "{}", lua);
+        assert!(lua.contains("return c + a ^ b\n"), "{}", lua);
+    }
+    
+    #[test]
+    fn test_source_copied_as_written_is_a_fallback() {
+        let source = "function f(ids) {\n  const cache = new Map();\n  const n = 1;\n  return { ids: ids, n: n };\n}\n";
+        let uir = JavaScriptParser::new().unwrap().parse(source).unwrap();
+        let config = GeneratorConfig::default();
+        let (rust, lines) = RustGenerator.generate_with_fallbacks(&uir, &config).unwrap();
+        let copied: Vec<&str> = lines.iter().map(|line| rust.lines().nth(line - 1).unwrap().trim()).collect();
+        assert_eq!(copied, vec!["let cache = new Map();", "{ ids: ids, n: n }"], "{}", rust);
+        // TypeScript reads JavaScript as it is
+        let (_, lines) = TypeScriptGenerator.generate_with_fallbacks(&uir, &config).unwrap();
+        assert!(lines.is_empty());
+    }
+    
+    #[test]
+    fn test_constructs_copied_as_written_are_fallbacks_in_every_target() {
+        let source = "function f(a, b, items) {\n  const isDate = a instanceof Date;\n  const shifted = b >>> 2;\n  const doubled = items.map(x => x * 2);\n  const point = { x: 1, y: b };\n  return point;\n}\n";
+        let uir = JavaScriptParser::new().unwrap().parse(source).unwrap();
+        let config = GeneratorConfig::default();
+        let fallbacks = |generator: &dyn Generator| {
+            let (code, lines) = generator.generate_with_fallbacks(&uir, &config).unwrap();
+            // Marking the fallbacks leaves the code as generating it does
+            assert_eq!(code, generator.generate_with_config(&uir, &config).unwrap());
+            lines.iter().map(|line| code.lines().nth(line - 1).unwrap().trim().to_string()).collect::<Vec<_>>()
+        };
+        
+        assert!(fallbacks(&PythonGenerator).contains(&"point = { x: 1, y: b }".to_string()));
+        assert!(fallbacks(&RustGenerator).contains(&"let point = { x: 1, y: b };".to_string()));
+        assert!(fallbacks(&GoGenerator).contains(&"point := { x: 1, y: b }".to_string()));
+        let c = fallbacks(&CGenerator);
+        for line in ["int isDate = /* a instanceof Date */;", "int doubled = items.map(/* TODO: Implement UIR node generation */", "int point = { x: 1, y: b };"] {
+            assert!(c.contains(&line.to_string()), "{:?}", c);
+        }
+        let lua = fallbacks(&TemplateGenerator::lua().unwrap());
+        for line in ["local isDate = a instanceof Date", "local shifted = b >>> 2", "local point = { x: 1, y: b }"] {
+            assert!(lua.contains(&line.to_string()), "{:?}", lua);
+        }
+        assert!(fallbacks(&TypeScriptGenerator).is_empty());
+    }
+    
+    const KEYWORD_OPERATORS: &str = "function f(a, o, x) {\n  const p = a instanceof Error;\n  const q = \"k\" in o;\n  const r = x >>> 2;\n  const s = typeof x;\n  delete o.k;\n  const v = void 0;\n}\n";
+    
+    #[test]
+    fn test_javascript_keyword_operators_lower_per_target() {
+        let python = generate(&PythonGenerator, KEYWORD_OPERATORS);
+        assert!(python.contains("    p = isinstance(a, Error)\n    q = \"k\" in o\n    r = (x & 0xFFFFFFFF) >> 2\n    s = type(x).__name__\n    del o.k\n    v = None\n"), "{}", python);
+        let rust = generate(&RustGenerator, KEYWORD_OPERATORS);
+        assert!(rust.contains("    let p = (&a as &dyn std::any::Any).is::<Error>();\n    let q = o.contains_key(&\"k\");\n    let r = (x as u32) >> 2;\n    let s = std::any::type_name_of_val(&x);\n"), "{}", rust);
+        let go = generate(&GoGenerator, KEYWORD_OPERATORS);
+        assert!(go.contains("import \"fmt\""), "{}", go);
+        assert!(go.contains("    p := func() bool { _, ok := any(a).(*Error); return ok }()\n    q := func() bool { _, ok := o[\"k\"]; return ok }()\n    r := int(uint32(x) >> 2)\n    s := fmt.Sprintf(\"%T\", x)\n"), "{}", go);
+        let c = generate(&CGenerator, KEYWORD_OPERATORS);
+        assert!(c.contains("    int r = (unsigned int)x >> 2;\n"), "{}", c);
+        let typescript = generate(&TypeScriptGenerator, KEYWORD_OPERATORS);
+        assert!(typescript.contains("  const p = a instanceof Error;\n  const q = \"k\" in o;\n  const r = x >>> 2;\n  const s = typeof x;\n  delete o.k;\n  const v = void 0;\n"), "{}", typescript);
+        for code in [&python, &rust, &go, &c, &typescript] {
+            assert!(!code.contains(" + Error"), "{}", code);
+        }
+    }
+    
+    #[test]
+    fn test_keyword_operators_without_a_lowering_are_fallbacks() {
+        let uir = JavaScriptParser::new().unwrap().parse(KEYWORD_OPERATORS).unwrap();
+        let config = GeneratorConfig::default();
+        let (c, lines) = CGenerator.generate_with_fallbacks(&uir, &config).unwrap();
+        let copied: Vec<&str> = lines.iter().map(|line| c.lines().nth(line - 1).unwrap().trim()).collect();
+        assert_eq!(copied, vec!["int p = /* a instanceof Error */;", "int q = /* \"k\" in o */;", "int s = /* typeof x */;", "/* delete o.k */;"], "{}", c);
+        let (lua, lines) = TemplateGenerator::lua().unwrap().generate_with_fallbacks(&uir, &config).unwrap();
+        let copied: Vec<&str> = lines.iter().map(|line| lua.lines().nth(line - 1).unwrap().trim()).collect();
+        assert_eq!(copied.len(), 6, "{}", lua);
+        assert!(copied.contains(&"local p = a instanceof Error"), "{}", lua);
+    }
+    
+    /// A class with a constructor and a defaulted parameter, then a function with a lambda,
+    /// counted, for-each and while loops and a try/finally
+    pub(crate) const CONSTRUCTS: &str = "class Counter {\n  constructor(start) { this.count = start; }\n  add(step = 1) { this.count += step; return this.count; }\n}\n\n\
+        function run(items, limit) {\n\
+        \x20 const double = (x) => x * 2;\n\
+        \x20 let total = 0;\n\
+        \x20 for (let i = 0; i < limit; i++) { total += double(i); }\n\
+        \x20 for (const item of items) { total += item; }\n\
+        \x20 while (total > 100) { total -= 10; }\n\
+        \x20 try { total = check(total); } finally { log(total); }\n\
+        \x20 return total;\n}\n";
+    
+    #[test]
+    fn test_rust_generates_classes_loops_lambdas_and_try_finally() {
+        let rust = generate(&RustGenerator, CONSTRUCTS);
+        assert!(rust.contains("pub struct Counter {\n    pub count: i32,\n}"), "{}", rust);
+        assert!(rust.contains("    fn constructor(&mut self, start: i32) {\n        self.count = start;\n    }\n"), "{}", rust);
+        assert!(rust.contains("    fn add(&mut self, step: i32) -> i32 {\n        self.count += step;\n        self.count\n    }\n"), "{}", rust);
+        assert!(rust.contains("fn run(items: i32, limit: i32) -> i32 {\n    let double = |x| x * 2;\n    let mut total = 0;\n"), "{}", rust);
+        assert!(rust.contains("    for i in 0..limit {\n        total += double(i);\n    }\n    for item in items {\n"), "{}", rust);
+        assert!(rust.contains("    while total > 100 {\n        total -= 10;\n    }\n"), "{}", rust);
+        // Nothing in the try raises, so it runs where it stands
+        assert!(rust.contains("    }\n    total = check(total);\n    log(total);\n    total\n}"), "{}", rust);
+        
+        // What the try raises propagates once the finally has run
+        let rust = generate(&RustGenerator, "function strict(v) { try { if (v < 0) { throw new Error(\"negative\"); } v = v + 1; } finally { log(v); } return v; }");
+        assert!(rust.contains("fn strict(mut v: i32) -> Result<i32, Box<dyn std::error::Error>> {\n    let result = (|| -> Result<_, Box<dyn std::error::Error>> {\n"), "{}", rust);
+        assert!(rust.contains("        Ok(())\n    })();\n    log(v);\n    result?;\n    Ok(v)\n}"), "{}", rust);
+    }
+    
+    #[test]
+    fn test_python_hints_types_and_makes_field_only_classes_dataclasses() {
+        let source = "class Point:\n    x: int\n    y: int = 0\n\n\
+            def norm(p: Point, scale: float) -> float:\n    if p.x > 0:\n        for i in range(3):\n            while scale > 1:\n                scale -= 1\n    return scale\n";
+        let uir = PythonParser::new().unwrap().parse(source).unwrap();
+        let python = PythonGenerator.generate(&uir).unwrap();
+        assert!(python.contains("from dataclasses import dataclass\n\n@dataclass\nclass Point:\n    x: int\n    y: int = 0\n"), "{}", python);
+        assert!(python.contains("def norm(p: Point, scale: float) -> float:\n    if p.x > 0:\n        for i in range(3):\n            while scale > 1:\n                scale -= 1\n    return scale\n"), "{}", python);
+        
+        // A class with behaviour stays a plain class
+        let python = generate(&PythonGenerator, "class Counter { constructor(start) { this.count = start; } }");
+        assert!(python.contains("class Counter:\n    def __init__(self, start):\n") && !python.contains("@dataclass"), "{}", python);
+    }
+    
+    #[test]
+    fn test_python_generates_classes_loops_lambdas_and_try_finally() {
+        let python = generate(&PythonGenerator, CONSTRUCTS);
+        assert!(python.contains("class Counter:\n    def __init__(self, start):\n        self.count = start\n\n    def add(self, step=1):\n        self.count += step\n        return self.count\n"), "{}", python);
+        assert!(python.contains("def run(items, limit):\n    double = lambda x: x * 2\n    total = 0\n"), "{}", python);
+        assert!(python.contains("    for i in range(limit):\n        total += double(i)\n    for item in items:\n        total += item\n"), "{}", python);
+        assert!(python.contains("    while total > 100:\n        total -= 10\n"), "{}", python);
+        assert!(python.contains("    try:\n        total = check(total)\n    finally:\n        log(total)\n    return total\n"), "{}", python);
+    }
+    
+}
@@ -1,45 +1,126 @@
 // Additional system language generators for C and Go
 
-use coalesce_core::{Generator, Language, UIRNode, NodeType, ExpressionType, StatementType, Result};
+use coalesce_core::{EnumVariant, Generator, GeneratorConfig, Language, LiteralValue, Operator, Ownership, UIRNode, NodeType, ControlFlowType, LoopType, ExpressionType, StatementType, Result, Walk, walk, walk_mut};
+use crate::{accessor_functions, assigned_fields, is_constructor, assignment_form, to_pascal_case, comprehension_parts, subscript_parts, AssignmentForm, attached_comments, binary_operator, bound_names, call_parts, closure_parts, closure_value, comment_code, declared_variables, destructured_value, function_keys, functions_with_owners, grouped, FALLBACK_TODO, declared_type, documentation_lines, enum_values, expression_operator, handler_binding, identifier, initializer, ImportSet, local_callee, mark_fallible_calls, module_assignments_declared, has_tag, indent, is_comment, is_documentation, is_field, is_import, is_punctuation, is_static, module_items, operand, literal_code, lowered_operation, Lowering, paragraph_jump, pointee, original_text, raised_message, raises, returns_value, split_type_arguments, is_cleanup, is_handler, breaks, iterates_keys, loop_parts, lowered_body, ternary_parts, verbatim};
+use crate::layout::{argument_list, block, continued, generate_marked, generate_styled};
+use std::borrow::Cow;
+use std::collections::HashSet;
 
 pub struct CGenerator;
 
+/// A module and the modules nested in it, outermost first
+fn nested_modules(uir: &UIRNode) -> Vec<&UIRNode> {
+    std::iter::once(uir)
+        .chain(uir.children.iter().filter(|c| c.node_type == NodeType::Module).flat_map(nested_modules))
+        .collect()
+}
+
+/// Structs and functions of a module in dependency order, ready to print as C
+struct CUnit<'a> {
+    enums: Vec<&'a UIRNode>,
+    structs: Vec<&'a UIRNode>,
+    /// Functions and methods, with property accessors made into functions of their own
+    functions: Vec<(Cow<'a, UIRNode>, Option<&'a str>)>,
+    /// Comments attached to the structs and functions, which move with them when reordered
+    comments: Vec<&'a UIRNode>,
+}
+
 impl Generator for CGenerator {
     fn target_language(&self) -> Language {
         Language::C
     }
     
+    fn generate_with_config(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<String> {
+        generate_styled(self, uir, config)
+    }
+    
+    fn generate_with_fallbacks(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<(String, Vec<usize>)> {
+        generate_marked(|| self.generate_with_config(uir, config))
+    }
+    
     fn generate(&self, uir: &UIRNode) -> Result<String> {
         match &uir.node_type {
             NodeType::Module => {
-                let mut code = String::from("// Generated by Coalesce\n#include <stdio.h>\n\n");
-                
-                for child in &uir.children {
-                    code.push_str(&self.generate(child)?);
-                    code.push('\n');
-                }
+                let unit = self.collect_unit(uir);
+                let declarations = self.generate_declarations(&unit)?;
+                let definitions = self.generate_definitions(&unit)?;
                 
+                let mut includes = ImportSet::new();
+                includes.insert("stdio.h");
+                self.includes_for(&format!("{}{}", declarations, definitions), &mut includes);
+                includes.extend_from(uir);
+                let mut code = String::from("// Generated by Coalesce\n");
+                code.push_str(&includes.render(&Language::C));
+                code.push_str(&declarations);
+                code.push_str(&definitions);
                 Ok(code)
             }
             NodeType::Function => {
-                self.generate_function(uir)
+                self.generate_function(uir, None)
+            }
+            NodeType::Class => {
+                self.generate_struct(uir)
             }
             NodeType::Variable => {
-                Ok(uir.name.as_deref().unwrap_or("unknown_var").to_string())
+                Ok(c_identifier(uir.name.as_deref().unwrap_or("unknown_var")))
             }
             NodeType::Statement(StatementType::Return) => {
                 self.generate_return_statement(uir)
             }
-            NodeType::Expression(ExpressionType::Arithmetic) => {
-                self.generate_arithmetic_expression(uir)
+            NodeType::Expression(ExpressionType::Arithmetic | ExpressionType::Comparison | ExpressionType::Logical) => {
+                self.generate_operator_expression(uir)
+            }
+            NodeType::Expression(ExpressionType::Variable) if original_text(uir).contains('.') && uir.children.len() >= 2 => {
+                let mut parts = Vec::new();
+                for child in uir.children.iter().filter(|c| original_text(c) != ".") {
+                    parts.push(self.generate(child)?);
+                }
+                // Methods take their struct by pointer
+                match parts.split_first() {
+                    Some((receiver, fields)) if receiver == "self" => Ok(format!("self->{}", fields.join("."))),
+                    _ => Ok(parts.join(".")),
+                }
             }
             NodeType::Expression(ExpressionType::Variable) => {
-                Ok(uir.name.as_deref().unwrap_or("unknown").to_string())
+                Ok(c_identifier(uir.name.as_deref().unwrap_or("unknown")))
+            }
+            NodeType::Expression(ExpressionType::Assignment) if uir.children.len() >= 2 => {
+                let target = self.generate(&uir.children[0])?;
+                Ok(match assignment_form(uir, &Language::C) {
+                    AssignmentForm::Plain => format!("{} = {}", target, self.generate(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Compound(symbol) => format!("{} {} {}", target, symbol, self.generate(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Expanded(operation) => format!("{} = {}", target, self.generate(&operation)?),
+                })
+            }
+            NodeType::ControlFlow(ControlFlowType::Conditional) => {
+                self.generate_conditional(uir)
+            }
+            NodeType::ControlFlow(ControlFlowType::Loop(loop_type)) => {
+                self.generate_loop(uir, loop_type)
+            }
+            NodeType::ControlFlow(ControlFlowType::Try) => {
+                self.generate_try(uir)
+            }
+            NodeType::Expression(ExpressionType::FunctionCall) => {
+                let (function, arguments) = call_parts(uir);
+                let callee = match function {
+                    Some(function) => self.generate(function)?,
+                    None => c_identifier(uir.name.as_deref().unwrap_or("unknown")),
+                };
+                let arguments = arguments.into_iter().map(|a| self.generate(a)).collect::<Result<Vec<_>>>()?;
+                Ok(argument_list(&callee, &arguments))
             }
             NodeType::Expression(ExpressionType::Literal) => {
+                if let Some(value) = &uir.literal {
+                    return Ok(literal_code(value, &Language::C));
+                }
                 if let Some(original) = uir.metadata.annotations.get("original_text") {
                     if let Some(text) = original.as_str() {
-                        Ok(text.to_string())
+                        Ok(match text.strip_prefix("this.") {
+                            Some(field) => format!("self->{}", field),
+                            None if text == "this" => "self".to_string(),
+                            None => verbatim(uir, text, Language::C),
+                        })
                     } else {
                         Ok("0".to_string())
                     }
@@ -47,60 +128,466 @@ impl Generator for CGenerator {
                     Ok("0".to_string())
                 }
             }
+            NodeType::Statement(_) if declared_variables(uir).is_some() => {
+                let mut lines = Vec::new();
+                for variable in declared_variables(uir).unwrap_or_default() {
+                    let name = c_identifier(variable.name.as_deref().unwrap_or("value"));
+                    let c_type = self.annotated_type(variable, "type").unwrap_or_else(|| "int".to_string());
+                    let value = match (destructured_value(variable, |key| format!(".{}", key)), initializer(variable)) {
+                        (Some(value), _) => Some(value),
+                        (None, Some(value)) => Some(self.generate(value)?),
+                        (None, None) => None,
+                    };
+                    lines.push(match value {
+                        Some(value) => format!("{} = {}", declare(&c_type, &name), value),
+                        None => declare(&c_type, &name),
+                    });
+                }
+                // The function body terminates the last one
+                Ok(lines.join(";\n"))
+            }
+            // Expression statements wrap the expression itself
+            NodeType::Statement(StatementType::Expression) => match uir.children.iter().filter(|c| !is_punctuation(c)).collect::<Vec<_>>().as_slice() {
+                [expression] => self.generate(expression),
+                _ => Ok(format!("/* {} */\n", FALLBACK_TODO)),
+            },
+            // Without an event loop the awaited call simply blocks
+            NodeType::Expression(ExpressionType::Await) => match operand(uir) {
+                Some(call) => self.generate(call),
+                None => Ok(format!("/* {} */\n", FALLBACK_TODO)),
+            },
             _ => {
-                Ok("/* TODO: Implement UIR node generation */\n".to_string())
+                Ok(format!("/* {} */\n", FALLBACK_TODO))
             }
         }
     }
+    
+    /// Writes the definitions to `output_path` and a matching header beside it
+    fn generate_file(&self, uir: &UIRNode, output_path: &str) -> Result<()> {
+        let path = std::path::Path::new(output_path);
+        let header_path = path.with_extension("h");
+        let header_name = header_path.file_name().and_then(|n| n.to_str()).unwrap_or("generated.h");
+        
+        std::fs::write(&header_path, self.generate_header(uir, header_name)?)?;
+        std::fs::write(path, self.generate_source(uir, header_name)?)?;
+        Ok(())
+    }
 }
 
 impl CGenerator {
-    fn generate_function(&self, uir: &UIRNode) -> Result<String> {
-        let func_name = uir.name.as_deref().unwrap_or("generated_function");
+    /// Header with struct declarations and function prototypes, guarded against double inclusion
+    pub fn generate_header(&self, uir: &UIRNode, header_name: &str) -> Result<String> {
+        let unit = self.collect_unit(uir);
+        let declarations = self.generate_declarations(&unit)?;
+        let guard: String = header_name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect();
         
-        let mut parameters = Vec::new();
-        let mut statements = Vec::new();
-        
-        for child in &uir.children {
-            match &child.node_type {
-                NodeType::Variable => {
-                    if let Some(param_name) = &child.name {
-                        parameters.push(format!("int {}", param_name)); // Default to int type
+        let mut code = format!("// Generated by Coalesce\n#ifndef {guard}\n#define {guard}\n\n");
+        let mut includes = ImportSet::new();
+        self.includes_for(&declarations, &mut includes);
+        code.push_str(&includes.render(&Language::C));
+        code.push_str(&declarations);
+        code.push_str(&format!("#endif /* {} */\n", guard));
+        Ok(code)
+    }
+    
+    /// Definitions for a module whose declarations live in `header_name`
+    pub fn generate_source(&self, uir: &UIRNode, header_name: &str) -> Result<String> {
+        let unit = self.collect_unit(uir);
+        let definitions = self.generate_definitions(&unit)?;
+        let mut includes = ImportSet::new();
+        includes.insert("stdio.h");
+        includes.insert(format!("\"{}\"", header_name));
+        includes.extend_from(uir);
+        Ok(format!("// Generated by Coalesce\n{}{}", includes.render(&Language::C), definitions))
+    }
+    
+    /// Gather enums, structs and functions (methods included) and order structs and functions
+    /// by their dependencies
+    fn collect_unit<'a>(&self, uir: &'a UIRNode) -> CUnit<'a> {
+        let mut enums = Vec::new();
+        let mut structs = Vec::new();
+        let mut functions = Vec::new();
+        let mut comments = Vec::new();
+        // Nested modules, such as COBOL's divisions, add their items to the one file
+        for module in nested_modules(uir) {
+            for child in &module.children {
+                match &child.node_type {
+                    NodeType::Class | NodeType::Interface => {
+                        structs.push(child);
+                        comments.extend(attached_comments(&module.children, child));
+                        for method in child.children.iter().filter(|c| c.node_type == NodeType::Function) {
+                            functions.push((Cow::Borrowed(method), child.name.as_deref()));
+                            comments.extend(attached_comments(&child.children, method));
+                        }
+                        for property in &child.children {
+                            functions.extend(accessor_functions(property).into_iter().map(|f| (Cow::Owned(f), child.name.as_deref())));
+                        }
                     }
+                    NodeType::Function => {
+                        functions.push((Cow::Borrowed(child), None));
+                        comments.extend(attached_comments(&module.children, child));
+                    }
+                    NodeType::Property { .. } => {
+                        functions.extend(accessor_functions(child).into_iter().map(|f| (Cow::Owned(f), None)));
+                    }
+                    NodeType::Enum { .. } => {
+                        enums.push(child);
+                        comments.extend(attached_comments(&module.children, child));
+                    }
+                    _ => {}
                 }
-                NodeType::Statement(_) => {
-                    statements.push(child);
-                }
-                _ => {
-                    statements.push(child);
+            }
+        }
+        
+        let structs = topological_order(structs, |s| s.name.clone().unwrap_or_default(), |s| self.struct_dependencies(s));
+        let functions = topological_order(
+            functions,
+            |(f, owner)| self.function_name(f, *owner),
+            |(f, _)| {
+                let mut calls = f.metadata.dependencies.clone();
+                collect_calls(f, &mut calls);
+                calls
+            },
+        );
+        CUnit { enums, structs, functions, comments }
+    }
+    
+    /// Enums, forward typedefs, struct definitions and function prototypes
+    fn generate_declarations(&self, unit: &CUnit) -> Result<String> {
+        let mut code = String::new();
+        for e in &unit.enums {
+            if let NodeType::Enum { variants } = &e.node_type {
+                code.push_str(&self.comments_for(unit, e));
+                code.push_str(&self.generate_enum(e, variants));
+                code.push_str("\n\n");
+            }
+        }
+        if !unit.structs.is_empty() {
+            // Forward typedefs let structs refer to each other through pointers
+            for s in &unit.structs {
+                let name = s.name.as_deref().unwrap_or("GeneratedStruct");
+                code.push_str(&format!("typedef struct {} {};\n", name, name));
+            }
+            code.push('\n');
+            for s in &unit.structs {
+                code.push_str(&self.comments_for(unit, s));
+                code.push_str(&self.generate_struct(s)?);
+                code.push_str("\n\n");
+            }
+        }
+        if !unit.functions.is_empty() {
+            for (function, owner) in &unit.functions {
+                code.push_str(&self.prototype(function, *owner));
+                code.push_str(";\n");
+            }
+            code.push('\n');
+        }
+        Ok(code)
+    }
+    
+    fn generate_definitions(&self, unit: &CUnit) -> Result<String> {
+        let mut code = String::new();
+        for (function, owner) in &unit.functions {
+            code.push_str(&self.comments_for(unit, function));
+            code.push_str(&self.generate_function(function, *owner)?);
+            code.push('\n');
+        }
+        Ok(code)
+    }
+    
+    /// The comments attached to `declaration`, one per line, then its documentation
+    fn comments_for(&self, unit: &CUnit, declaration: &UIRNode) -> String {
+        let mut code: String = unit.comments.iter()
+            .filter(|c| !is_documentation(c))
+            .filter(|c| matches!(&c.node_type, NodeType::Comment { attached_to: Some(id), .. } if *id == declaration.id))
+            .map(|c| format!("{}\n", comment_code(c, "//", "///", Some(("/*", "*/")))))
+            .collect();
+        code.push_str(&documentation_lines(declaration, "///"));
+        code
+    }
+    
+    /// Headers for the standard types used in `code`
+    fn includes_for(&self, code: &str, includes: &mut ImportSet) {
+        if code.contains("bool ") {
+            includes.insert("stdbool.h");
+        }
+        if code.contains("size_t ") {
+            includes.insert("stddef.h");
+        }
+        if code.contains("pow(") {
+            includes.insert("math.h");
+        }
+    }
+    
+    fn generate_struct(&self, uir: &UIRNode) -> Result<String> {
+        let struct_name = uir.name.as_deref().unwrap_or("GeneratedStruct");
+        
+        let mut fields = Vec::new();
+        for (field_name, source_type) in self.struct_fields(uir) {
+            let mut field_type = source_type.map(|t| self.map_type(t)).unwrap_or_else(|| "int".to_string());
+            // A struct can only contain itself through a pointer
+            if field_type == struct_name {
+                field_type = format!("struct {} *", struct_name);
+            }
+            fields.push(format!("{};", declare(&field_type, field_name)));
+        }
+        let declared: Vec<&str> = self.struct_fields(uir).into_iter().map(|(name, _)| name).collect();
+        for field_name in assigned_fields(uir).iter().filter(|name| !declared.contains(&name.as_str())) {
+            fields.push(format!("{};", declare("int", field_name)));
+        }
+        if fields.is_empty() {
+            // Empty structs are not standard C
+            fields.push("char _unused;".to_string());
+        }
+        
+        Ok(format!("{};", block(&format!("struct {}", struct_name), &indent(&fields.join("\n"), 1))))
+    }
+    
+    /// A typedef'd enum; C has no tagged unions, so variants keep their tags but not their data
+    fn generate_enum(&self, uir: &UIRNode, variants: &[EnumVariant]) -> String {
+        let enum_name = uir.name.as_deref().unwrap_or("GeneratedEnum");
+        let values = enum_values(variants);
+        let enumerators: Vec<String> = variants.iter().zip(values)
+            .map(|(variant, value)| match variant.value {
+                Some(_) => format!("{} = {}", variant.name, value),
+                None => variant.name.clone(),
+            })
+            .collect();
+        format!("{} {};", block("typedef enum", &indent(&enumerators.join(",\n"), 1)), enum_name)
+    }
+    
+    /// Methods become free functions prefixed with their struct, taking it as `self`
+    fn function_name(&self, uir: &UIRNode, owner: Option<&str>) -> String {
+        let name = c_identifier(uir.name.as_deref().unwrap_or("generated_function"));
+        match owner {
+            Some(owner) => format!("{}_{}", owner, name),
+            None => name,
+        }
+    }
+    
+    fn prototype(&self, uir: &UIRNode, owner: Option<&str>) -> String {
+        let mut parameters = Vec::new();
+        if let Some(owner) = owner {
+            if !is_static(uir) {
+                parameters.push(format!("{} *self", owner));
+            }
+        }
+        for child in &uir.children {
+            if let (NodeType::Variable, Some(param_name)) = (&child.node_type, &child.name) {
+                if matches!(param_name.as_str(), "self" | "this" | "cls") {
+                    continue;
                 }
+                let param_type = match (child.ownership, declared_type(child, "type")) {
+                    // References become pointers, `const` unless the callee may write through them
+                    (Some(Ownership::Reference { mutable } | Ownership::Pointer { mutable }), Some(source_type)) => {
+                        format!("{}{} *", if mutable { "" } else { "const " }, self.map_type(pointee(source_type)))
+                    }
+                    _ => self.annotated_type(child, "type").unwrap_or_else(|| "int".to_string()), // Default to int type
+                };
+                parameters.push(declare(&param_type, &c_identifier(param_name)));
             }
         }
+        if parameters.is_empty() {
+            parameters.push("void".to_string());
+        }
         
-        let params_str = parameters.join(", ");
+        let return_type = match self.annotated_type(uir, "return_type") {
+            Some(return_type) => return_type,
+            None if uir.children.iter().any(returns_value) => "int".to_string(), // Simple default return type
+            None => "void".to_string(),
+        };
+        argument_list(&declare(&return_type, &self.function_name(uir, owner)), &parameters)
+    }
+    
+    fn generate_function(&self, uir: &UIRNode, owner: Option<&str>) -> Result<String> {
+        let statements: Vec<&UIRNode> = uir.children.iter()
+            .filter(|c| c.node_type != NodeType::Variable && !is_import(c))
+            .collect();
         
         let body = if statements.is_empty() {
-            "    /* Empty function */".to_string()
+            indent("/* Empty function */", 1)
         } else {
-            let mut body_code = String::new();
-            for stmt in &statements {
-                let stmt_code = self.generate(stmt)?;
-                for line in stmt_code.lines() {
-                    if !line.trim().is_empty() {
-                        body_code.push_str(&format!("    {};\n", line.trim()));
+            self.generate_block(&statements)?
+        };
+        
+        Ok(block(&self.prototype(uir, owner), &body))
+    }
+    
+    /// Statements indented a level, as the body of a block
+    fn generate_block(&self, statements: &[&UIRNode]) -> Result<String> {
+        Ok(indent(&self.generate_statements(statements)?, 1))
+    }
+    
+    /// Statements one per line, each ended by `;` unless it ends in a block
+    fn generate_statements(&self, statements: &[&UIRNode]) -> Result<String> {
+        let mut code = String::new();
+        for stmt in statements {
+            if is_comment(stmt) {
+                code.push_str(&format!("{}\n", comment_code(stmt, "//", "///", Some(("/*", "*/")))));
+                continue;
+            }
+            let stmt_code = self.generate(stmt)?;
+            if stmt_code.trim().is_empty() {
+                continue;
+            }
+            code.push_str(stmt_code.trim());
+            if !matches!(stmt.node_type, NodeType::ControlFlow(ControlFlowType::Conditional | ControlFlowType::Loop(_) | ControlFlowType::Try)) {
+                code.push(';');
+            }
+            code.push('\n');
+        }
+        Ok(code.trim_end().to_string())
+    }
+    
+    fn generate_conditional(&self, uir: &UIRNode) -> Result<String> {
+        let Some(condition) = uir.children.first() else {
+            return Ok(format!("/* {} */", FALLBACK_TODO));
+        };
+        let (otherwise, then_branch): (Vec<&UIRNode>, Vec<&UIRNode>) = uir.children[1..].iter()
+            .partition(|c| has_tag(c, &["else_clause", "elif_clause", "else"]));
+        let mut code = block(&format!("if ({})", self.generate(condition)?.trim()), &self.generate_block(&then_branch)?);
+        for branch in otherwise {
+            let body: Vec<&UIRNode> = branch.children.iter().filter(|c| !is_punctuation(c)).collect();
+            let nested = match body.as_slice() {
+                _ if has_tag(branch, &["elif_clause"]) => Some(branch),
+                [conditional] if conditional.node_type == NodeType::ControlFlow(ControlFlowType::Conditional) => Some(*conditional),
+                _ => None,
+            };
+            match nested {
+                Some(conditional) => code.push_str(&format!("{} {}", continued("else"), self.generate_conditional(conditional)?)),
+                None => code.push_str(&block(&continued("else"), &self.generate_block(&body)?)),
+            }
+        }
+        Ok(code)
+    }
+    
+    /// `for` and `while` loops keep their headers; C has no for-each, which would need the
+    /// length of what it walks
+    fn generate_loop(&self, uir: &UIRNode, loop_type: &LoopType) -> Result<String> {
+        let parts = loop_parts(uir);
+        let clause = |part: Option<&UIRNode>| -> Result<String> {
+            Ok(match part {
+                Some(part) => self.generate(part)?.trim().to_string(),
+                None => String::new(),
+            })
+        };
+        let body = self.generate_block(&parts.body)?;
+        let code = match loop_type {
+            LoopType::For if parts.initializer.is_some() || parts.update.is_some() => block(
+                &format!("for ({}; {}; {})", clause(parts.initializer)?, clause(parts.condition)?, clause(parts.update)?),
+                &body,
+            ),
+            LoopType::While | LoopType::For if parts.condition.is_some() => block(&format!("while ({})", clause(parts.condition)?), &body),
+            LoopType::DoWhile if parts.condition.is_some() => format!("{}{} ({});", block("do", &body), continued("while"), clause(parts.condition)?),
+            LoopType::While | LoopType::DoWhile => block("while (1)", &body),
+            _ => return Ok(format!("/* {} */", FALLBACK_TODO)),
+        };
+        // Python's loop `else` runs unless the loop broke out
+        if parts.body.iter().any(|statement| breaks(statement)) && !parts.otherwise.is_empty() {
+            return Ok(format!("{}\n/* {} */", code, FALLBACK_TODO));
+        }
+        let otherwise = self.generate_statements(&parts.otherwise)?;
+        Ok(if otherwise.is_empty() { code } else { format!("{}\n{}", code, otherwise) })
+    }
+    
+    /// C has no exceptions: the body runs as it is, then the `finally` statements; handlers
+    /// have nothing to catch and are left to do by hand
+    fn generate_try(&self, uir: &UIRNode) -> Result<String> {
+        let body: Vec<&UIRNode> = uir.children.iter().filter(|c| !is_handler(c) && !is_cleanup(c)).collect();
+        let cleanup: Vec<&UIRNode> = uir.children.iter().filter(|c| is_cleanup(c)).flat_map(|c| c.children.iter()).collect();
+        let mut code = vec![self.generate_statements(&body)?];
+        if uir.children.iter().any(is_handler) {
+            code.push(format!("/* {} */", FALLBACK_TODO));
+        }
+        code.push(self.generate_statements(&cleanup)?);
+        code.retain(|c| !c.is_empty());
+        Ok(code.join("\n"))
+    }
+    
+    /// Field names with their source types: declared members and class-level assignments
+    fn struct_fields<'a>(&self, uir: &'a UIRNode) -> Vec<(&'a str, Option<&'a str>)> {
+        let field_type = |node: &'a UIRNode| declared_type(node, "type");
+        let mut fields = Vec::new();
+        for child in &uir.children {
+            match &child.node_type {
+                _ if is_field(child) => {
+                    if let Some(name) = &child.name {
+                        fields.push((name.as_str(), field_type(child)));
+                    }
+                }
+                NodeType::Statement(StatementType::Expression) => {
+                    for assignment in child.children.iter().filter(|c| c.node_type == NodeType::Expression(ExpressionType::Assignment)) {
+                        if let Some(name) = assignment.children.first().and_then(|t| t.name.as_deref()) {
+                            fields.push((name, field_type(assignment)));
+                        }
                     }
                 }
+                _ => {}
             }
-            body_code.trim_end().to_string()
-        };
+        }
+        fields
+    }
+    
+    /// Structs a struct embeds by value or extends must be defined before it
+    fn struct_dependencies(&self, uir: &UIRNode) -> Vec<String> {
+        let mut dependencies = uir.metadata.dependencies.clone();
+        for (_, field_type) in self.struct_fields(uir) {
+            if let Some(field_type) = field_type {
+                dependencies.extend(field_type.split(|c: char| !c.is_alphanumeric() && c != '_').map(str::to_string));
+            }
+        }
+        dependencies
+    }
+    
+    fn annotated_type(&self, uir: &UIRNode, key: &str) -> Option<String> {
+        declared_type(uir, key).map(|source_type| self.map_type(source_type))
+    }
+    
+    /// Map a source-language type to C; collections become pointers to their elements
+    fn map_type(&self, source_type: &str) -> String {
+        let source_type = source_type.trim();
+        if source_type.contains('*') {
+            return source_type.to_string();
+        }
+        if let Some(inner) = source_type.strip_suffix('?') {
+            return format!("{} *", self.map_type(inner));
+        }
+        if let Some(inner) = source_type.strip_suffix("[]").or_else(|| source_type.strip_prefix("[]")) {
+            return format!("{} *", self.map_type(inner));
+        }
         
-        let return_type = if statements.iter().any(|s| matches!(s.node_type, NodeType::Statement(StatementType::Return))) {
-            "int" // Simple default return type
-        } else {
-            "void"
+        let (base, arguments) = match source_type.find(['<', '[']) {
+            Some(open) if source_type.ends_with(['>', ']']) => {
+                (&source_type[..open], split_type_arguments(&source_type[open + 1..source_type.len() - 1]))
+            }
+            _ => (source_type, Vec::new()),
         };
+        let base = base.rsplit(['.', ':']).next().unwrap_or(base);
+        let element = || arguments.first().map(|a| self.map_type(a)).unwrap_or_else(|| "void".to_string());
         
-        Ok(format!("{} {}({}) {{\n{}\n}}", return_type, func_name, params_str, body))
+        match base {
+            "int" | "Int" | "Integer" | "int32" | "Int32" | "i32" => "int".to_string(),
+            "long" | "Long" | "int64" | "Int64" | "i64" => "long long".to_string(),
+            "short" | "Short" | "int16" | "i16" => "short".to_string(),
+            "byte" | "Byte" | "uint8" | "u8" => "unsigned char".to_string(),
+            "uint" | "uint32" | "UInt32" | "unsigned" | "u32" => "unsigned int".to_string(),
+            "ulong" | "uint64" | "u64" => "unsigned long long".to_string(),
+            "size_t" | "usize" => "size_t".to_string(),
+            "float" | "Float" | "float32" | "f32" => "float".to_string(),
+            "double" | "Double" | "float64" | "f64" | "decimal" | "Decimal" | "number" => "double".to_string(),
+            "bool" | "boolean" | "Boolean" => "bool".to_string(),
+            "char" | "Char" | "rune" => "char".to_string(),
+            "str" | "string" | "String" => "const char *".to_string(),
+            "void" | "None" | "Unit" | "Void" | "()" => "void".to_string(),
+            "list" | "List" | "IList" | "ArrayList" | "MutableList" | "Sequence" | "IEnumerable" | "Array" | "Seq" | "Vec" => {
+                format!("{} *", element())
+            }
+            "Optional" | "Option" | "Nullable" => format!("{} *", element()),
+            "dict" | "Dict" | "Map" | "HashMap" | "Dictionary" | "object" | "Object" | "Any" | "any" => "void *".to_string(),
+            _ => base.to_string(),
+        }
     }
     
     fn generate_return_statement(&self, uir: &UIRNode) -> Result<String> {
@@ -112,176 +599,1216 @@ impl CGenerator {
         }
     }
     
-    fn generate_arithmetic_expression(&self, uir: &UIRNode) -> Result<String> {
-        if uir.children.len() == 2 {
-            let left = self.generate(&uir.children[0])?.trim().to_string();
-            let right = self.generate(&uir.children[1])?.trim().to_string();
-            
-            let operator = if let Some(original) = uir.metadata.annotations.get("original_text") {
-                if let Some(text) = original.as_str() {
-                    if text.contains(" + ") {
-                        " + "
-                    } else if text.contains(" - ") {
-                        " - "
-                    } else if text.contains(" * ") {
-                        " * "
-                    } else if text.contains(" / ") {
-                        " / "
-                    } else {
-                        " + "
-                    }
+    fn generate_operator_expression(&self, uir: &UIRNode) -> Result<String> {
+        let untranslated = || format!("/* {} */", verbatim(uir, original_text(uir), Language::C).replace("*/", "* /"));
+        match uir.children.as_slice() {
+            [operand] => {
+                let operand_code = grouped(self.generate(operand)?.trim().to_string(), uir, operand, true);
+                match lowered_operation(uir, std::slice::from_ref(&operand_code), &Language::C) {
+                    Lowering::Code(code) => return Ok(code),
+                    Lowering::Untranslated => return Ok(untranslated()),
+                    Lowering::Operator => {}
+                }
+                if expression_operator(uir) == Some(Operator::Not) {
+                    Ok(format!("!{}", operand_code))
+                } else if expression_operator(uir) == Some(Operator::Negate) {
+                    Ok(format!("-{}", operand_code))
                 } else {
-                    " + "
+                    Ok(operand_code)
                 }
-            } else {
-                " + "
-            };
-            
-            Ok(format!("{}{}{}", left, operator, right))
-        } else {
-            Ok("unknown_expression".to_string())
+            }
+            // C has no power operator
+            [left, right] if expression_operator(uir) == Some(Operator::Power) => {
+                Ok(format!("pow({}, {})", self.generate(left)?.trim(), self.generate(right)?.trim()))
+            }
+            [left, right] => {
+                let left_code = grouped(self.generate(left)?.trim().to_string(), uir, left, false);
+                let right_code = grouped(self.generate(right)?.trim().to_string(), uir, right, true);
+                match (lowered_operation(uir, &[left_code.clone(), right_code.clone()], &Language::C), binary_operator(uir, left)) {
+                    (Lowering::Code(code), _) => Ok(code),
+                    (Lowering::Operator, Some(operator)) => Ok(format!("{} {} {}", left_code, operator, right_code)),
+                    (Lowering::Untranslated, _) | (_, None) => Ok(untranslated()),
+                }
+            }
+            _ => Ok("unknown_expression".to_string()),
         }
     }
 }
 
 pub struct GoGenerator;
 
+/// Per-function state for the Go generator
+#[derive(Default)]
+struct GoFunctionContext {
+    /// Go type of the value the function returns, if any
+    value_type: Option<String>,
+    /// The function raises errors, so it returns `error` last
+    fallible: bool,
+    /// Locals and parameters already in scope, assigned with `=` rather than `:=`
+    declared: HashSet<String>,
+}
+
+/// Whether `uir` calls a fallible function of the module outside a closure
+fn calls_fallible(uir: &UIRNode) -> bool {
+    let mut found = false;
+    walk(uir, &mut |node: &UIRNode| match node.node_type {
+        NodeType::Closure { .. } => Walk::SkipChildren,
+        _ if has_tag(node, &["fallible_call"]) => {
+            found = true;
+            Walk::Stop
+        }
+        _ => Walk::Continue,
+    });
+    found
+}
+
+/// `statement` with `cleanup` run before each return or raise in it, outside nested functions
+fn cleaned_up_before_leaving(statement: &UIRNode, cleanup: &[&UIRNode]) -> UIRNode {
+    let mut statement = statement.clone();
+    if cleanup.is_empty() {
+        return statement;
+    }
+    walk_mut(&mut statement, &mut |node: &mut UIRNode| match node.node_type {
+        NodeType::Function | NodeType::Closure { .. } => Walk::SkipChildren,
+        NodeType::Statement(StatementType::Return | StatementType::Throw) => {
+            let mut block = UIRNode::new(format!("{}_cleanup", node.id), NodeType::Statement(StatementType::Expression));
+            block.children = cleanup.iter().map(|&statement| statement.clone()).collect();
+            block.children.push(node.clone());
+            *node = block;
+            Walk::SkipChildren
+        }
+        _ => Walk::Continue,
+    });
+    statement
+}
+
+impl GoFunctionContext {
+    /// `return` with the zero value for the result followed by `error_value`
+    fn error_return(&self, error_value: &str) -> String {
+        match &self.value_type {
+            Some(value_type) => format!("return {}, {}", zero_value(value_type), error_value),
+            None => format!("return {}", error_value),
+        }
+    }
+}
+
 impl Generator for GoGenerator {
     fn target_language(&self) -> Language {
         Language::Go
     }
     
+    fn generate_with_config(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<String> {
+        generate_styled(self, uir, config)
+    }
+    
+    fn generate_with_fallbacks(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<(String, Vec<usize>)> {
+        generate_marked(|| self.generate_with_config(uir, config))
+    }
+    
     fn generate(&self, uir: &UIRNode) -> Result<String> {
         match &uir.node_type {
             NodeType::Module => {
-                let mut code = String::from("// Generated by Coalesce\npackage main\n\n");
+                let marked = module_assignments_declared(self.mark_fallible_calls(uir));
+                let uir = &marked;
+                let mut body = String::new();
+                for child in module_items(uir).into_iter().filter(|c| !is_import(c) && !is_documentation(c)) {
+                    // A property's accessor functions are documented one by one
+                    if !matches!(child.node_type, NodeType::Property { .. }) {
+                        body.push_str(&documentation_lines(child, "//"));
+                    }
+                    // `:=` only declares inside a function
+                    let variables = match &child.node_type {
+                        NodeType::Variable => Some(vec![child]),
+                        _ => declared_variables(child),
+                    };
+                    match variables {
+                        Some(variables) => {
+                            let declarations = variables.into_iter().map(|v| self.generate_package_variable(v)).collect::<Result<Vec<_>>>()?;
+                            body.push_str(&declarations.join("\n"));
+                        }
+                        None => body.push_str(&self.generate(child)?),
+                    }
+                    body.push('\n');
+                }
                 
-                for child in &uir.children {
-                    code.push_str(&self.generate(child)?);
+                let mut imports = ImportSet::collect(uir);
+                if body.contains("errors.New(") {
+                    imports.insert("errors");
+                }
+                if body.contains("math.Pow(") || body.contains("math.Floor(") {
+                    imports.insert("math");
+                }
+                if body.contains("fmt.Sprintf(") {
+                    imports.insert("fmt");
+                }
+                // Package documentation sits right above the package clause
+                let mut code = String::from("// Generated by Coalesce\n");
+                if uir.documentation.is_some() {
                     code.push('\n');
+                    code.push_str(&documentation_lines(uir, "//"));
                 }
-                
+                code.push_str("package main\n\n");
+                code.push_str(&imports.render(&Language::Go));
+                code.push_str(&body);
                 Ok(code)
             }
             NodeType::Function => {
-                self.generate_function(uir)
-            }
-            NodeType::Variable => {
-                Ok(uir.name.as_deref().unwrap_or("unknownVar").to_string())
+                self.generate_function(uir, None)
             }
-            NodeType::Statement(StatementType::Return) => {
-                self.generate_return_statement(uir)
+            NodeType::Property { .. } => {
+                let functions = accessor_functions(uir).iter()
+                    .map(|function| Ok(format!("{}{}", documentation_lines(function, "//"), self.generate_function(function, None)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(functions.join("\n\n"))
             }
-            NodeType::Expression(ExpressionType::Arithmetic) => {
-                self.generate_arithmetic_expression(uir)
+            NodeType::Class => {
+                self.generate_struct(uir)
             }
-            NodeType::Expression(ExpressionType::Variable) => {
-                Ok(uir.name.as_deref().unwrap_or("unknown").to_string())
+            NodeType::Enum { variants } => {
+                Ok(self.generate_enum(uir, variants))
             }
-            NodeType::Expression(ExpressionType::Literal) => {
-                if let Some(original) = uir.metadata.annotations.get("original_text") {
-                    if let Some(text) = original.as_str() {
-                        Ok(text.to_string())
-                    } else {
-                        Ok("0".to_string())
-                    }
-                } else {
-                    Ok("0".to_string())
-                }
+            NodeType::Statement(_) | NodeType::ControlFlow(_) | NodeType::Comment { .. } => {
+                self.generate_statement(uir, &mut GoFunctionContext::default())
             }
             _ => {
-                Ok("// TODO: Implement UIR node generation\n".to_string())
+                self.generate_expression(uir)
             }
         }
     }
 }
 
 impl GoGenerator {
-    fn generate_function(&self, uir: &UIRNode) -> Result<String> {
-        let func_name = uir.name.as_deref().unwrap_or("generatedFunction");
+    /// `mark_fallible_calls`, also tagging `valueless_call` the calls to functions returning
+    /// nothing but the error
+    fn mark_fallible_calls(&self, module: &UIRNode) -> UIRNode {
+        let mut valueless = HashSet::new();
+        let mut functions = Vec::new();
+        functions_with_owners(module, None, &mut functions);
+        for (function, owner) in functions {
+            if self.value_type(function).is_none() {
+                valueless.extend(function_keys(function, owner));
+            }
+        }
+        let mut marked = mark_fallible_calls(module);
+        walk_mut(&mut marked, &mut |node: &mut UIRNode| {
+            let callee = node.metadata.annotations.get("callee").and_then(|callee| callee.as_str());
+            if has_tag(node, &["fallible_call"]) && callee.is_some_and(|callee| valueless.contains(callee)) {
+                node.metadata.semantic_tags.push("valueless_call".to_string());
+            }
+            Walk::Continue
+        });
+        marked
+    }
+    
+    /// A package-level `var`, typed where it has no value
+    fn generate_package_variable(&self, uir: &UIRNode) -> Result<String> {
+        let name = identifier(uir.name.as_deref().unwrap_or("value"));
+        match (destructured_value(uir, |key| format!(".{}", key)), initializer(uir)) {
+            (Some(value), _) => Ok(format!("var {} = {}", name, value)),
+            (None, Some(value)) => Ok(format!("var {} = {}", name, self.generate_expression(value)?)),
+            (None, None) => Ok(format!("var {} {}", name, self.annotated_type(uir, "type").unwrap_or_else(|| "interface{}".to_string()))),
+        }
+    }
+    
+    /// Go type of the value `function` returns besides any error
+    fn value_type(&self, function: &UIRNode) -> Option<String> {
+        match self.annotated_type(function, "return_type") {
+            Some(return_type) if !return_type.is_empty() => Some(return_type),
+            Some(_) => None,
+            // No declared type: keep the historical int default when something is returned
+            None if function.children.iter().any(returns_value) => Some("int".to_string()),
+            None => None,
+        }
+    }
+    
+    /// A named integer type and a `const` block of its values, counted with `iota` unless the
+    /// source numbered them itself; variants keep their tags but not their data
+    fn generate_enum(&self, uir: &UIRNode, variants: &[EnumVariant]) -> String {
+        let enum_name = uir.name.as_deref().unwrap_or("GeneratedEnum");
+        let counted = variants.iter().enumerate()
+            .all(|(index, variant)| variant.value.is_none() || (index == 0 && variant.value.as_deref() == Some("iota")));
+        let constants: Vec<String> = if counted {
+            variants.iter().enumerate()
+                .map(|(index, variant)| if index == 0 {
+                    format!("{} {} = iota", variant.name, enum_name)
+                } else {
+                    variant.name.clone()
+                })
+                .collect()
+        } else {
+            variants.iter().zip(enum_values(variants))
+                .map(|(variant, value)| format!("{} {} = {}", variant.name, enum_name, value))
+                .collect()
+        };
+        format!("type {} int\n\nconst (\n{}\n)", enum_name, indent(&constants.join("\n"), 1))
+    }
+    
+    /// A struct of the fields a class declares or assigns through `self`, then its methods
+    fn generate_struct(&self, uir: &UIRNode) -> Result<String> {
+        let struct_name = identifier(uir.name.as_deref().unwrap_or("GeneratedStruct"));
+        let mut fields: Vec<(String, String)> = Vec::new();
+        for child in uir.children.iter().filter(|c| is_field(c)) {
+            if let Some(name) = &child.name {
+                fields.push((identifier(name), self.annotated_type(child, "type").unwrap_or_else(|| "int".to_string())));
+            }
+        }
+        for name in assigned_fields(uir) {
+            let name = identifier(&name);
+            if !fields.iter().any(|(known, _)| *known == name) {
+                fields.push((name, "int".to_string()));
+            }
+        }
+        let mut code = if fields.is_empty() {
+            format!("type {} struct{{}}", struct_name)
+        } else {
+            let fields: Vec<String> = fields.iter().map(|(name, field_type)| format!("{} {}", name, field_type)).collect();
+            format!("type {} struct {{\n{}\n}}", struct_name, indent(&fields.join("\n"), 1))
+        };
+        let methods = uir.children.iter()
+            .filter(|c| c.node_type == NodeType::Function)
+            .cloned()
+            .chain(uir.children.iter().flat_map(accessor_functions));
+        for method in methods {
+            code.push_str(&format!("\n\n{}{}", documentation_lines(&method, "//"), self.generate_function(&method, Some(&struct_name))?));
+        }
+        Ok(code)
+    }
+    
+    /// Emit a function, or a method of the struct `owner`: a constructor becomes `New<owner>`
+    /// returning the struct it fills in, a static method a function prefixed with `owner`, and
+    /// any other method takes the struct as its `self` receiver
+    fn generate_function(&self, uir: &UIRNode, owner: Option<&str>) -> Result<String> {
+        let name = identifier(uir.name.as_deref().unwrap_or("generatedFunction"));
+        let constructor = owner.filter(|_| is_constructor(uir));
+        let func_name = match owner {
+            Some(owner) if constructor.is_some() => format!("New{}", owner),
+            Some(owner) if is_static(uir) => format!("{}{}", owner, to_pascal_case(&name)),
+            Some(owner) => format!("(self *{}) {}", owner, name),
+            None => name,
+        };
         
         let mut parameters = Vec::new();
         let mut statements = Vec::new();
+        let mut declared = HashSet::new();
         
         for child in &uir.children {
             match &child.node_type {
                 NodeType::Variable => {
                     if let Some(param_name) = &child.name {
-                        parameters.push(format!("{} int", param_name)); // Default to int type
+                        if matches!(param_name.as_str(), "self" | "this" | "cls") {
+                            continue;
+                        }
+                        let param_type = match (child.ownership, declared_type(child, "type")) {
+                            // Only a pointer lets the callee change what the caller passed
+                            (Some(Ownership::Reference { mutable: true } | Ownership::Pointer { mutable: true }), Some(source_type)) => {
+                                format!("*{}", self.map_type(pointee(source_type)))
+                            }
+                            (Some(Ownership::Reference { .. } | Ownership::Pointer { .. }), Some(source_type)) => self.map_type(pointee(source_type)),
+                            _ => self.annotated_type(child, "type").unwrap_or_else(|| "int".to_string()),
+                        };
+                        parameters.push(format!("{} {}", param_name, param_type));
+                        declared.insert(param_name.clone());
                     }
                 }
-                NodeType::Statement(_) => {
-                    statements.push(child);
-                }
+                NodeType::Import { .. } => {}
                 _ => {
                     statements.push(child);
                 }
             }
         }
         
-        let params_str = parameters.join(", ");
+        let mut context = GoFunctionContext {
+            fallible: statements.iter().any(|s| raises(s)),
+            value_type: match constructor {
+                Some(owner) => Some(format!("*{}", owner)),
+                None => self.value_type(uir),
+            },
+            declared,
+        };
+        
+        let return_type = match (&context.value_type, context.fallible) {
+            (Some(value_type), true) => format!(" ({}, error)", value_type),
+            (None, true) => " error".to_string(),
+            (Some(value_type), false) => format!(" {}", value_type),
+            (None, false) => String::new(),
+        };
+        
+        let mut body_code = String::new();
+        if let Some(owner) = constructor {
+            body_code.push_str(&format!("self := &{}{{}}\n", owner));
+        }
+        for (i, stmt) in statements.iter().enumerate() {
+            let is_last = i == statements.len() - 1 && constructor.is_none();
+            let code = match &stmt.node_type {
+                NodeType::ControlFlow(ControlFlowType::Try) => self.generate_try(stmt, &mut context, is_last)?,
+                _ => self.generate_statement(stmt, &mut context)?,
+            };
+            body_code.push_str(&code);
+            body_code.push('\n');
+        }
+        
+        // Go requires a terminating statement once there are results
+        let terminated = statements.last()
+            .is_some_and(|s| matches!(s.node_type, NodeType::Statement(StatementType::Return | StatementType::Throw)));
+        if constructor.is_some() {
+            body_code.push_str(if context.fallible { "return self, nil\n" } else { "return self\n" });
+        } else if context.fallible && !terminated {
+            body_code.push_str(&context.error_return("nil"));
+            body_code.push('\n');
+        }
         
-        let body = if statements.is_empty() {
-            "    // Empty function".to_string()
+        let body = if body_code.trim().is_empty() {
+            indent("// Empty function", 1)
         } else {
-            let mut body_code = String::new();
-            for stmt in &statements {
-                let stmt_code = self.generate(stmt)?;
-                for line in stmt_code.lines() {
-                    if !line.trim().is_empty() {
-                        body_code.push_str(&format!("    {}\n", line.trim()));
+            indent(body_code.trim_end(), 1)
+        };
+        
+        // Go's grammar puts the brace on the same line whatever the brace style
+        Ok(format!("{}{} {{\n{}\n}}", argument_list(&format!("func {}", func_name), &parameters), return_type, body))
+    }
+    
+    fn generate_block(&self, statements: &[&UIRNode], context: &mut GoFunctionContext) -> Result<String> {
+        let mut code = String::new();
+        for stmt in statements {
+            code.push_str(&self.generate_statement(stmt, context)?);
+            code.push('\n');
+        }
+        Ok(indent(code.trim_end(), 1))
+    }
+    
+    fn generate_statement(&self, uir: &UIRNode, context: &mut GoFunctionContext) -> Result<String> {
+        // Go the parser read, such as a library rule's code, stands as written
+        if uir.metadata.source_language == Language::Go && !original_text(uir).is_empty() {
+            walk(uir, &mut |node: &UIRNode| {
+                if node.node_type == NodeType::Variable {
+                    context.declared.extend(node.name.iter().flat_map(|name| name.split(", ")).map(identifier));
+                }
+                Walk::Continue
+            });
+            return Ok(original_text(uir).replace('\t', "    "));
+        }
+        let simple = matches!(uir.node_type, NodeType::Variable | NodeType::Expression(_) | NodeType::Statement(StatementType::Return));
+        if simple && calls_fallible(uir) {
+            return self.generate_checked(uir, context);
+        }
+        match &uir.node_type {
+            NodeType::Comment { .. } => Ok(comment_code(uir, "//", "//", Some(("/*", "*/")))),
+            NodeType::Statement(StatementType::Return) => {
+                let value = match uir.children.first() {
+                    Some(expr) => Some(self.generate_expression(expr)?),
+                    None => None,
+                };
+                Ok(match (value, context.fallible) {
+                    (Some(value), true) => format!("return {}, nil", value),
+                    (None, true) => context.error_return("nil"),
+                    (Some(value), false) => format!("return {}", value),
+                    (None, false) => "return".to_string(),
+                })
+            }
+            NodeType::Statement(StatementType::Throw) => {
+                Ok(context.error_return(&format!("errors.New({})", raised_message(uir))))
+            }
+            NodeType::Statement(StatementType::Defer) => {
+                let deferred = match uir.children.iter().find(|c| c.node_type == NodeType::Expression(ExpressionType::FunctionCall)) {
+                    Some(call) => self.generate_expression(call)?,
+                    None => original_text(uir).trim_start_matches("defer ").to_string(),
+                };
+                Ok(format!("defer {}", deferred))
+            }
+            NodeType::Statement(StatementType::Break) => Ok("break".to_string()),
+            NodeType::Statement(StatementType::Continue) => Ok("continue".to_string()),
+            NodeType::Statement(_) if has_tag(uir, &["with_statement", "using_statement"]) => {
+                self.generate_with(uir, context)
+            }
+            NodeType::Statement(_) => {
+                // Blocks and expression statements wrap the real statements
+                let mut lines = Vec::new();
+                for child in uir.children.iter().filter(|c| !is_punctuation(c)) {
+                    lines.push(self.generate_statement(child, context)?);
+                }
+                Ok(lines.join("\n"))
+            }
+            NodeType::Variable => {
+                let name = identifier(uir.name.as_deref().unwrap_or("value"));
+                let value = match (destructured_value(uir, |key| format!(".{}", key)), initializer(uir)) {
+                    (Some(value), _) => Some(value),
+                    (None, Some(value)) => Some(self.generate_expression(value)?),
+                    (None, None) => None,
+                };
+                context.declared.insert(name.clone());
+                Ok(match value {
+                    Some(value) => format!("{} := {}", name, value),
+                    None => format!("var {} {}", name, self.annotated_type(uir, "type").unwrap_or_else(|| "interface{}".to_string())),
+                })
+            }
+            NodeType::Expression(ExpressionType::Assignment) if uir.children.len() >= 2 => {
+                let target = self.generate_expression(&uir.children[0])?;
+                let (symbol, value) = match assignment_form(uir, &Language::Go) {
+                    AssignmentForm::Plain => ("=", self.generate_expression(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Compound(symbol) => (symbol, self.generate_expression(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Expanded(operation) => ("=", self.generate_expression(&operation)?),
+                };
+                // A compound assignment updates a variable that already exists
+                if context.declared.contains(&target) || target.contains('.') || uir.operator.is_some() {
+                    Ok(format!("{} {} {}", target, symbol, value))
+                } else {
+                    context.declared.insert(target.clone());
+                    Ok(format!("{} := {}", target, value))
+                }
+            }
+            NodeType::ControlFlow(ControlFlowType::Conditional) => {
+                self.generate_conditional(uir, context)
+            }
+            NodeType::ControlFlow(ControlFlowType::Loop(loop_type)) => {
+                let parts = loop_parts(uir);
+                let lowered: Vec<UIRNode>;
+                let (header, body): (String, Vec<&UIRNode>) = match (loop_type, parts.target, parts.iterable) {
+                    // JavaScript's `for (k in o)` walks the keys
+                    (LoopType::ForEach, Some(target), Some(iterable)) if iterates_keys(uir) => (
+                        format!("for {} := range {}", self.generate_expression(target)?, self.generate_expression(iterable)?),
+                        parts.body.clone(),
+                    ),
+                    (LoopType::ForEach, Some(target), Some(iterable)) => (
+                        format!("for _, {} := range {}", self.generate_expression(target)?, self.generate_expression(iterable)?),
+                        parts.body.clone(),
+                    ),
+                    (LoopType::For, ..) if parts.initializer.is_some() || parts.update.is_some() => {
+                        let clause = |part: Option<&UIRNode>, context: &mut GoFunctionContext| match part {
+                            Some(part) => self.generate_statement(part, context),
+                            None => Ok(String::new()),
+                        };
+                        let initializer = clause(parts.initializer, context)?;
+                        let condition = match parts.condition {
+                            Some(condition) => self.generate_expression(condition)?,
+                            None => String::new(),
+                        };
+                        let update = clause(parts.update, context)?;
+                        (format!("for {}; {}; {}", initializer, condition, update), parts.body.clone())
+                    }
+                    (LoopType::While | LoopType::For, ..) if parts.condition.is_some() => (
+                        format!("for {}", self.generate_expression(parts.condition.unwrap_or(uir))?),
+                        parts.body.clone(),
+                    ),
+                    // `do ... while` tests its condition last
+                    _ => {
+                        lowered = lowered_body(uir, &parts);
+                        ("for".to_string(), lowered.iter().collect())
+                    }
+                };
+                let mut code = format!("{} {{\n{}\n}}", header, self.generate_block(&body, context)?);
+                // Python's loop `else` runs unless the loop broke out
+                if parts.body.iter().any(|statement| breaks(statement)) && !parts.otherwise.is_empty() {
+                    code.push_str(&format!("\n// {}", FALLBACK_TODO));
+                } else {
+                    for statement in &parts.otherwise {
+                        code.push('\n');
+                        code.push_str(&self.generate_statement(statement, context)?);
                     }
                 }
+                Ok(code)
+            }
+            NodeType::ControlFlow(ControlFlowType::Try) => {
+                self.generate_try(uir, context, false)
             }
-            body_code.trim_end().to_string()
+            NodeType::ControlFlow(ControlFlowType::Goto) => match paragraph_jump(uir) {
+                Some(jump) => self.generate_statement(&jump, context),
+                None => Ok(format!("// {}", FALLBACK_TODO)),
+            },
+            NodeType::Expression(_) => {
+                self.generate_expression(uir)
+            }
+            _ => {
+                Ok(format!("// {}", FALLBACK_TODO))
+            }
+        }
+    }
+    
+    /// A statement calling fallible functions: each result is bound first and its error
+    /// returned, and the statement uses the bound values; a declaration or a call statement
+    /// binds the call it makes directly
+    fn generate_checked(&self, uir: &UIRNode, context: &mut GoFunctionContext) -> Result<String> {
+        let mut lines = Vec::new();
+        let direct = match uir.node_type {
+            NodeType::Variable if !uir.metadata.annotations.contains_key("destructured_from") => initializer(uir),
+            _ => Some(uir),
         };
+        if let Some(call) = direct.filter(|c| has_tag(c, &["fallible_call"])) {
+            let mut call = call.clone();
+            for argument in &mut call.children {
+                self.bind_fallible_calls(argument, &mut lines, context)?;
+            }
+            let call = self.generate_expression(&call)?;
+            if uir.node_type == NodeType::Variable {
+                let name = identifier(uir.name.as_deref().unwrap_or("value"));
+                lines.push(format!("{}, err := {}", name, call));
+                lines.push(format!("if err != nil {{\n{}\n}}", indent(&context.error_return("err"), 1)));
+                context.declared.insert(name);
+            } else {
+                let results = if has_tag(uir, &["valueless_call"]) { "err" } else { "_, err" };
+                lines.push(format!("if {} := {}; err != nil {{\n{}\n}}", results, call, indent(&context.error_return("err"), 1)));
+            }
+            return Ok(lines.join("\n"));
+        }
         
-        let return_type = if statements.iter().any(|s| matches!(s.node_type, NodeType::Statement(StatementType::Return))) {
-            " int" // Simple default return type
+        let mut statement = uir.clone();
+        self.bind_fallible_calls(&mut statement, &mut lines, context)?;
+        lines.push(self.generate_statement(&statement, context)?);
+        Ok(lines.join("\n"))
+    }
+    
+    /// Replace the fallible calls in `uir`, arguments first, with locals their results are
+    /// bound to by `lines`
+    fn bind_fallible_calls(&self, uir: &mut UIRNode, lines: &mut Vec<String>, context: &mut GoFunctionContext) -> Result<()> {
+        if matches!(uir.node_type, NodeType::Closure { .. }) {
+            return Ok(());
+        }
+        for child in &mut uir.children {
+            self.bind_fallible_calls(child, lines, context)?;
+        }
+        if has_tag(uir, &["fallible_call"]) {
+            let base = format!("{}Result", local_callee(uir).unwrap_or("call"));
+            let name = (1..)
+                .map(|n| if n == 1 { base.clone() } else { format!("{}{}", base, n) })
+                .find(|name| !context.declared.contains(name))
+                .unwrap_or(base);
+            lines.push(format!("{}, err := {}", name, self.generate_expression(uir)?));
+            lines.push(format!("if err != nil {{\n{}\n}}", indent(&context.error_return("err"), 1)));
+            context.declared.insert(name.clone());
+            // The bound result stands where the call was, under the call's id
+            let mut result = UIRNode::new(uir.id.clone(), NodeType::Expression(ExpressionType::Variable)).named(name.clone());
+            result.metadata.annotations.insert("original_text".to_string(), name.into());
+            *uir = result;
+        }
+        Ok(())
+    }
+    
+    fn generate_conditional(&self, uir: &UIRNode, context: &mut GoFunctionContext) -> Result<String> {
+        let Some(condition) = uir.children.first() else {
+            return Ok(format!("// {}", FALLBACK_TODO));
+        };
+        let mut lines = Vec::new();
+        let condition = if calls_fallible(condition) {
+            let mut condition = condition.clone();
+            self.bind_fallible_calls(&mut condition, &mut lines, context)?;
+            Cow::Owned(condition)
         } else {
-            ""
+            Cow::Borrowed(condition)
         };
         
-        Ok(format!("func {}({}){} {{\n{}\n}}", func_name, params_str, return_type, body))
+        let mut then_branch = Vec::new();
+        let mut else_branches = Vec::new();
+        for child in &uir.children[1..] {
+            if has_tag(child, &["else_clause", "elif_clause", "else"]) {
+                else_branches.push(child);
+            } else {
+                then_branch.push(child);
+            }
+        }
+        
+        let mut code = format!("if {} {{\n{}\n}}", self.generate_expression(&condition)?, self.generate_block(&then_branch, context)?);
+        for branch in else_branches {
+            if has_tag(branch, &["elif_clause"]) {
+                // Calls bound for its condition need a block to go in
+                let nested = self.generate_conditional(branch, context)?;
+                if nested.starts_with("if ") {
+                    code.push_str(" else ");
+                    code.push_str(&nested);
+                } else {
+                    code.push_str(&format!(" else {{\n{}\n}}", indent(&nested, 1)));
+                }
+            } else {
+                let body: Vec<&UIRNode> = branch.children.iter().collect();
+                code.push_str(&format!(" else {{\n{}\n}}", self.generate_block(&body, context)?));
+            }
+        }
+        lines.push(code);
+        Ok(lines.join("\n"))
     }
     
-    fn generate_return_statement(&self, uir: &UIRNode) -> Result<String> {
-        if uir.children.is_empty() {
-            Ok("return".to_string())
+    /// try/catch runs the body in a closure returning `error` and checks it. Names the body
+    /// binds are declared ahead of the closure so they stay in scope after it. finally becomes
+    /// a `defer` when nothing follows the try; otherwise it runs after the handler, and before
+    /// any return that leaves the handler early
+    fn generate_try(&self, uir: &UIRNode, context: &mut GoFunctionContext, at_tail: bool) -> Result<String> {
+        let mut body = Vec::new();
+        let mut handlers = Vec::new();
+        let mut cleanup = Vec::new();
+        for child in &uir.children {
+            if is_cleanup(child) {
+                cleanup.push(child);
+            } else if is_handler(child) {
+                handlers.push(child);
+            } else {
+                body.push(child);
+            }
+        }
+        
+        let mut lines = Vec::new();
+        let cleanup: Vec<&UIRNode> = cleanup.iter().flat_map(|c| c.children.iter()).collect();
+        let deferred = at_tail && !cleanup.is_empty();
+        if deferred {
+            lines.push(format!("defer func() {{\n{}\n}}()", self.generate_block(&cleanup, context)?));
+        }
+        
+        if handlers.is_empty() && !body.iter().any(|s| raises(s)) {
+            // try/finally around code that cannot fail needs no error check
+            for stmt in body {
+                lines.push(self.generate_statement(stmt, context)?);
+            }
         } else {
-            let expr_code = self.generate(&uir.children[0])?;
-            Ok(format!("return {}", expr_code.trim()))
+            for (name, value) in bound_names(&body, &context.declared, identifier) {
+                let go_type = value.and_then(|value| self.expression_type(value)).unwrap_or_else(|| "int".to_string());
+                lines.push(format!("var {} {}", name, go_type));
+                context.declared.insert(name);
+            }
+            let mut body_context = GoFunctionContext {
+                value_type: None,
+                fallible: true,
+                declared: context.declared.clone(),
+            };
+            let closure = format!("func() error {{\n{}\n{}\n}}()", self.generate_block(&body, &mut body_context)?, indent("return nil", 1));
+            let early: Vec<&UIRNode> = if deferred { Vec::new() } else { cleanup.clone() };
+            if handlers.is_empty() {
+                // Nothing catches the error, so it propagates
+                let mut propagate = Vec::new();
+                for statement in &early {
+                    propagate.push(self.generate_statement(statement, context)?);
+                }
+                propagate.push(context.error_return("err"));
+                lines.push(format!("if err := {}; err != nil {{\n{}\n}}", closure, indent(&propagate.join("\n"), 1)));
+            } else {
+                let binding = handlers.iter().find_map(|h| handler_binding(h)).unwrap_or_else(|| "err".to_string());
+                let handler_body: Vec<UIRNode> = handlers.iter()
+                    .flat_map(|h| h.children.iter())
+                    .filter(|c| matches!(c.node_type, NodeType::Statement(_) | NodeType::ControlFlow(_)))
+                    .map(|statement| cleaned_up_before_leaving(statement, &early))
+                    .collect();
+                let handler_body: Vec<&UIRNode> = handler_body.iter().collect();
+                lines.push(format!("if {binding} := {}; {binding} != nil {{\n{}\n}}", closure, self.generate_block(&handler_body, context)?));
+            }
+        }
+        
+        if !deferred {
+            for statement in cleanup {
+                lines.push(self.generate_statement(statement, context)?);
+            }
         }
+        Ok(lines.join("\n"))
     }
     
-    fn generate_arithmetic_expression(&self, uir: &UIRNode) -> Result<String> {
-        if uir.children.len() == 2 {
-            let left = self.generate(&uir.children[0])?.trim().to_string();
-            let right = self.generate(&uir.children[1])?.trim().to_string();
-            
-            let operator = if let Some(original) = uir.metadata.annotations.get("original_text") {
-                if let Some(text) = original.as_str() {
-                    if text.contains(" + ") {
-                        " + "
-                    } else if text.contains(" - ") {
-                        " - "
-                    } else if text.contains(" * ") {
-                        " * "
-                    } else if text.contains(" / ") {
-                        " / "
-                    } else {
-                        " + "
+    /// `with open(p) as f:` / `using (var f = ...)` binds the resource and defers its Close
+    fn generate_with(&self, uir: &UIRNode, context: &mut GoFunctionContext) -> Result<String> {
+        let mut lines = Vec::new();
+        let mut body = Vec::new();
+        for child in &uir.children {
+            match &child.node_type {
+                NodeType::Statement(_) | NodeType::ControlFlow(_) => body.push(child),
+                _ => {
+                    for (resource, target) in self.with_items(child) {
+                        let value = self.generate_expression(resource)?;
+                        match target {
+                            Some(name) => {
+                                lines.push(format!("{} := {}", name, value));
+                                lines.push(format!("defer {}.Close()", name));
+                                context.declared.insert(name);
+                            }
+                            None => lines.push(format!("defer {}.Close()", value)),
+                        }
                     }
-                } else {
-                    " + "
                 }
-            } else {
-                " + "
-            };
-            
-            Ok(format!("{}{}{}", left, operator, right))
-        } else {
-            Ok("unknown_expression".to_string())
+            }
+        }
+        for stmt in body {
+            lines.push(self.generate_statement(stmt, context)?);
+        }
+        Ok(lines.join("\n"))
+    }
+    
+    /// Resource expressions of a with clause and the names they are bound to
+    fn with_items<'a>(&self, uir: &'a UIRNode) -> Vec<(&'a UIRNode, Option<String>)> {
+        if has_tag(uir, &["as_pattern"]) && !uir.children.is_empty() {
+            let target = uir.children.iter()
+                .find(|c| has_tag(c, &["as_pattern_target"]))
+                .map(|t| original_text(t).to_string());
+            return vec![(&uir.children[0], target)];
+        }
+        if uir.node_type == NodeType::Expression(ExpressionType::FunctionCall) {
+            return vec![(uir, None)];
+        }
+        uir.children.iter().flat_map(|c| self.with_items(c)).collect()
+    }
+    
+    fn generate_expression(&self, uir: &UIRNode) -> Result<String> {
+        let original = original_text(uir);
+        // Nor a comprehension: a function literal appends the chosen elements to a slice
+        if let Some((collection, index)) = subscript_parts(uir) {
+            return Ok(format!("{}[{}]", self.generate(collection)?, self.generate(index)?));
+        }
+        if let Some(comprehension) = comprehension_parts(uir) {
+            let element_type = self.expression_type(comprehension.element).unwrap_or_else(|| "int".to_string());
+            let conditions = comprehension.conditions.iter()
+                .map(|condition| Ok(grouped(self.generate_expression(condition)?, uir, condition, false)))
+                .collect::<Result<Vec<_>>>()?;
+            let mut append = format!("result = append(result, {})", self.generate_expression(comprehension.element)?);
+            if !conditions.is_empty() {
+                append = block(&format!("if {}", conditions.join(" && ")), &indent(&append, 1));
+            }
+            let body = format!(
+                "result := []{}{{}}\n{}\nreturn result",
+                element_type,
+                block(
+                    &format!("for _, {} := range {}", self.generate_expression(comprehension.target)?, self.generate_expression(comprehension.iterable)?),
+                    &indent(&append, 1),
+                ),
+            );
+            return Ok(block(&format!("func() []{}", element_type), &indent(&body, 1)) + "()");
+        }
+        // Go has no conditional expression: a function literal returns the chosen value
+        if let Some((condition, value, otherwise)) = ternary_parts(uir) {
+            let value_type = self.expression_type(value)
+                .or_else(|| self.expression_type(otherwise))
+                .unwrap_or_else(|| "int".to_string());
+            return Ok(format!(
+                "func() {} {{ if {} {{ return {} }}; return {} }}()",
+                value_type,
+                self.generate_expression(condition)?,
+                self.generate_expression(value)?,
+                self.generate_expression(otherwise)?,
+            ));
+        }
+        match &uir.node_type {
+            NodeType::Variable | NodeType::Expression(ExpressionType::Variable) if original.contains('.') && uir.children.len() >= 2 => {
+                let mut parts = Vec::new();
+                for child in uir.children.iter().filter(|c| original_text(c) != ".") {
+                    parts.push(self.generate_expression(child)?);
+                }
+                Ok(parts.join("."))
+            }
+            NodeType::Variable | NodeType::Expression(ExpressionType::Variable) => {
+                Ok(identifier(uir.name.as_deref().unwrap_or("unknown")))
+            }
+            NodeType::Expression(ExpressionType::Literal) => {
+                if let Some(value) = &uir.literal {
+                    return Ok(literal_code(value, &Language::Go));
+                }
+                Ok(match original {
+                    "" => "0".to_string(),
+                    "True" | "true" => "true".to_string(),
+                    "False" | "false" => "false".to_string(),
+                    "None" | "null" | "nil" => "nil".to_string(),
+                    "this" => "self".to_string(),
+                    text if text.starts_with("this.") => format!("self.{}", &text[5..]),
+                    text if text.starts_with('\'') && text.ends_with('\'') && text.len() > 3 => {
+                        format!("\"{}\"", &text[1..text.len() - 1])
+                    }
+                    text => verbatim(uir, text, Language::Go),
+                })
+            }
+            NodeType::Expression(ExpressionType::Arithmetic | ExpressionType::Comparison | ExpressionType::Logical) => {
+                let untranslated = || format!("/* {} */", verbatim(uir, original, Language::Go).replace("*/", "* /"));
+                match uir.children.as_slice() {
+                    [operand] => {
+                        let operand_code = grouped(self.generate_expression(operand)?, uir, operand, true);
+                        match lowered_operation(uir, std::slice::from_ref(&operand_code), &Language::Go) {
+                            Lowering::Code(code) => return Ok(code),
+                            Lowering::Untranslated => return Ok(untranslated()),
+                            Lowering::Operator => {}
+                        }
+                        if expression_operator(uir) == Some(Operator::Not) {
+                            Ok(format!("!{}", operand_code))
+                        } else if expression_operator(uir) == Some(Operator::Negate) {
+                            Ok(format!("-{}", operand_code))
+                        } else {
+                            Ok(operand_code)
+                        }
+                    }
+                    // Go has no power operator, and `math.Pow` takes float64s
+                    [left, right] if expression_operator(uir) == Some(Operator::Power) => {
+                        let float = |operand: &UIRNode| -> Result<String> {
+                            let code = self.generate_expression(operand)?;
+                            Ok(match operand.node_type {
+                                NodeType::Expression(ExpressionType::Literal) => code,
+                                _ => format!("float64({})", code),
+                            })
+                        };
+                        Ok(format!("math.Pow({}, {})", float(left)?, float(right)?))
+                    }
+                    // Python's `//` rounds down, where integer `/` truncates toward zero
+                    [left, right] if expression_operator(uir) == Some(Operator::IntegerDivide) => {
+                        let float = |operand: &UIRNode| -> Result<String> {
+                            Ok(format!("float64({})", self.generate_expression(operand)?))
+                        };
+                        Ok(format!("int(math.Floor({} / {}))", float(left)?, float(right)?))
+                    }
+                    [left, right] => {
+                        let left_code = grouped(self.generate_expression(left)?, uir, left, false);
+                        let right_code = grouped(self.generate_expression(right)?, uir, right, true);
+                        match (lowered_operation(uir, &[left_code.clone(), right_code.clone()], &Language::Go), binary_operator(uir, left)) {
+                            (Lowering::Code(code), _) => Ok(code),
+                            (Lowering::Operator, Some(operator)) => Ok(format!("{} {} {}", left_code, operator, right_code)),
+                            (Lowering::Untranslated, _) | (_, None) => Ok(untranslated()),
+                        }
+                    }
+                    _ => Ok("unknown_expression".to_string()),
+                }
+            }
+            NodeType::Expression(ExpressionType::FunctionCall) => {
+                let (function, arguments) = call_parts(uir);
+                let callee = match function {
+                    Some(function) => self.generate_expression(function)?,
+                    None => identifier(uir.name.as_deref().unwrap_or("unknown")),
+                };
+                let arguments = arguments.into_iter().map(|a| self.generate_expression(a)).collect::<Result<Vec<_>>>()?;
+                Ok(argument_list(&callee, &arguments))
+            }
+            NodeType::Expression(ExpressionType::Assignment) if uir.children.len() >= 2 => {
+                let target = self.generate_expression(&uir.children[0])?;
+                let (symbol, value) = match assignment_form(uir, &Language::Go) {
+                    AssignmentForm::Plain => ("=", self.generate_expression(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Compound(symbol) => (symbol, self.generate_expression(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Expanded(operation) => ("=", self.generate_expression(&operation)?),
+                };
+                Ok(format!("{} {} {}", target, symbol, value))
+            }
+            // Go calls block, so the awaited call is made directly
+            NodeType::Expression(ExpressionType::Await) => match operand(uir) {
+                Some(call) => self.generate_expression(call),
+                None => Ok("unknown_expression".to_string()),
+            },
+            // Parameters are typed as the source declares, else `int` like a function's
+            NodeType::Closure { .. } => {
+                let (parameters, body) = closure_parts(uir);
+                let mut context = GoFunctionContext {
+                    declared: parameters.iter().filter_map(|p| p.name.as_deref()).map(identifier).collect(),
+                    ..GoFunctionContext::default()
+                };
+                let parameters: Vec<String> = parameters.iter()
+                    .map(|p| format!("{} {}", identifier(p.name.as_deref().unwrap_or("arg")), self.annotated_type(p, "type").unwrap_or_else(|| "int".to_string())))
+                    .collect();
+                match closure_value(&body) {
+                    Some(value) => {
+                        let value_type = self.annotated_type(uir, "return_type")
+                            .or_else(|| self.expression_type(value))
+                            .unwrap_or_else(|| "int".to_string());
+                        Ok(format!("func({}) {} {{ return {} }}", parameters.join(", "), value_type, self.generate_expression(value)?))
+                    }
+                    None => {
+                        let value_type = match body.iter().any(|s| returns_value(s)) {
+                            true => {
+                                let value_type = self.annotated_type(uir, "return_type").unwrap_or_else(|| "int".to_string());
+                                context.value_type = Some(value_type.clone());
+                                format!(" {}", value_type)
+                            }
+                            false => String::new(),
+                        };
+                        Ok(format!("func({}){} {{\n{}\n}}", parameters.join(", "), value_type, self.generate_block(&body, &mut context)?))
+                    }
+                }
+            }
+            _ if !original.is_empty() => Ok(format!("/* {} */", original.replace("*/", "* /"))),
+            _ => Ok("unknown_expression".to_string()),
+        }
+    }
+    
+    fn annotated_type(&self, uir: &UIRNode, key: &str) -> Option<String> {
+        declared_type(uir, key).map(|source_type| self.map_type(source_type))
+    }
+    
+    /// The Go type of an expression, where a literal or a declared type gives it away
+    fn expression_type(&self, uir: &UIRNode) -> Option<String> {
+        if uir.node_type != NodeType::Expression(ExpressionType::Literal) {
+            return self.annotated_type(uir, "type");
+        }
+        let go_type = match &uir.literal {
+            Some(LiteralValue::Int(_)) => "int",
+            Some(LiteralValue::Float(_)) => "float64",
+            Some(LiteralValue::String(_)) => "string",
+            Some(LiteralValue::Bool(_)) => "bool",
+            Some(LiteralValue::Char(_)) => "rune",
+            Some(LiteralValue::Null) => return None,
+            None => match original_text(uir) {
+                "true" | "false" | "True" | "False" => "bool",
+                text if text.starts_with(['"', '\'', '`']) => "string",
+                text if text.parse::<i64>().is_ok() => "int",
+                text if text.parse::<f64>().is_ok() => "float64",
+                _ => return None,
+            },
+        };
+        Some(go_type.to_string())
+    }
+    
+    /// Map a source-language type to Go, recursing into generic arguments; `void` maps to ""
+    fn map_type(&self, source_type: &str) -> String {
+        let source_type = source_type.trim();
+        if let Some(inner) = source_type.strip_suffix('?') {
+            return format!("*{}", self.map_type(inner));
+        }
+        if let Some(inner) = source_type.strip_suffix("[]") {
+            return format!("[]{}", self.map_type(inner));
+        }
+        if source_type.starts_with("[]") || source_type.starts_with("map[") || source_type.starts_with('*') {
+            return source_type.to_string();
         }
+        
+        let (base, arguments) = match source_type.find(['<', '[']) {
+            Some(open) if source_type.ends_with(['>', ']']) => {
+                (&source_type[..open], split_type_arguments(&source_type[open + 1..source_type.len() - 1]))
+            }
+            _ => (source_type, Vec::new()),
+        };
+        let base = base.rsplit(['.', ':']).next().unwrap_or(base);
+        let mapped: Vec<String> = arguments.iter().map(|a| self.map_type(a)).collect();
+        let argument = |i: usize| mapped.get(i).cloned().unwrap_or_else(|| "interface{}".to_string());
+        
+        match base {
+            "int" | "Int" | "Integer" | "int32" | "Int32" => "int".to_string(),
+            "long" | "Long" | "int64" | "Int64" => "int64".to_string(),
+            "short" | "Short" | "int16" => "int16".to_string(),
+            "byte" | "Byte" | "uint8" => "byte".to_string(),
+            "uint" | "uint32" | "UInt32" | "unsigned" => "uint32".to_string(),
+            "ulong" | "uint64" | "size_t" => "uint64".to_string(),
+            "float" | "Float" | "float32" => "float32".to_string(),
+            "double" | "Double" | "float64" | "decimal" | "Decimal" | "number" => "float64".to_string(),
+            "bool" | "boolean" | "Boolean" => "bool".to_string(),
+            "char" | "Char" | "rune" => "rune".to_string(),
+            "str" | "string" | "String" => "string".to_string(),
+            "void" | "None" | "Unit" | "Void" | "()" => String::new(),
+            "list" | "List" | "IList" | "ArrayList" | "MutableList" | "Sequence" | "IEnumerable" | "Array" | "Seq" | "Vec" => {
+                format!("[]{}", argument(0))
+            }
+            "dict" | "Dict" | "Map" | "HashMap" | "Dictionary" | "IDictionary" | "MutableMap" | "Mapping" => {
+                format!("map[{}]{}", argument(0), argument(1))
+            }
+            "set" | "Set" | "HashSet" | "MutableSet" => format!("map[{}]bool", argument(0)),
+            "Optional" | "Option" | "Nullable" => format!("*{}", argument(0)),
+            "error" | "Exception" | "Error" => "error".to_string(),
+            _ => base.to_string(),
+        }
+    }
+}
+
+/// C's reserved words, which other languages name variables and functions with
+const C_KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else", "enum", "extern",
+    "float", "for", "goto", "if", "inline", "int", "long", "register", "restrict", "return", "short", "signed",
+    "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned", "void", "volatile", "while",
+];
+
+/// A name as a C identifier, with an underscore after one C reserves: `double_`
+fn c_identifier(name: &str) -> String {
+    let name = identifier(name);
+    match C_KEYWORDS.contains(&name.as_str()) {
+        true => format!("{}_", name),
+        false => name,
+    }
+}
+
+/// `type name`, attaching pointer stars to the name as C style does
+fn declare(c_type: &str, name: &str) -> String {
+    match c_type.strip_suffix('*') {
+        Some(base) => format!("{} *{}", base.trim_end(), name),
+        None => format!("{} {}", c_type, name),
+    }
+}
+
+/// Order `items` so each comes after the items it depends on, keeping source order otherwise;
+/// cycles are broken where they are found, which prototypes and forward typedefs make legal
+fn topological_order<T: Clone>(items: Vec<T>, name: impl Fn(&T) -> String, dependencies: impl Fn(&T) -> Vec<String>) -> Vec<T> {
+    fn visit<T: Clone>(
+        index: usize,
+        items: &[T],
+        names: &[String],
+        dependencies: &impl Fn(&T) -> Vec<String>,
+        state: &mut [u8],
+        ordered: &mut Vec<T>,
+    ) {
+        // 0 = unvisited, 1 = in progress, 2 = done
+        if state[index] != 0 {
+            return;
+        }
+        state[index] = 1;
+        for dependency in dependencies(&items[index]) {
+            if let Some(dep_index) = names.iter().position(|n| *n == dependency) {
+                visit(dep_index, items, names, dependencies, state, ordered);
+            }
+        }
+        state[index] = 2;
+        ordered.push(items[index].clone());
+    }
+    
+    let names: Vec<String> = items.iter().map(&name).collect();
+    let mut state = vec![0; items.len()];
+    let mut ordered = Vec::with_capacity(items.len());
+    for index in 0..items.len() {
+        visit(index, &items, &names, &dependencies, &mut state, &mut ordered);
+    }
+    ordered
+}
+
+/// Names of the functions called anywhere below `uir`
+fn collect_calls(uir: &UIRNode, calls: &mut Vec<String>) {
+    walk(uir, &mut |node: &UIRNode| {
+        if node.node_type == NodeType::Expression(ExpressionType::FunctionCall) {
+            let callee = node.name.clone().or_else(|| call_parts(node).0.and_then(|f| f.name.clone()));
+            calls.extend(callee);
+        }
+        Walk::Continue
+    });
+}
+
+/// The zero value Go requires alongside a non-nil error
+fn zero_value(go_type: &str) -> String {
+    match go_type {
+        "int" | "int8" | "int16" | "int32" | "int64" | "uint" | "uint8" | "uint16" | "uint32" | "uint64"
+        | "byte" | "rune" | "float32" | "float64" => "0".to_string(),
+        "string" => "\"\"".to_string(),
+        "bool" => "false".to_string(),
+        "error" | "interface{}" | "any" => "nil".to_string(),
+        t if t.starts_with("[]") || t.starts_with("map[") || t.starts_with('*') || t.starts_with("chan ") => "nil".to_string(),
+        t => format!("{}{{}}", t),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{generate, CONSTRUCTS, THROWING_METHOD};
+    use coalesce_core::Parser;
+    use coalesce_parser::PythonParser;
+    
+    #[test]
+    fn test_go_defers_cleanup_that_ends_a_function() {
+        let go = generate(&GoGenerator, "function save(path, data) {\n  try {\n    write(path, data);\n  } finally {\n    close(path);\n  }\n}\n");
+        assert!(go.contains("func save(path int, data int) {\n    defer func() {\n        close(path)\n    }()\n    write(path, data)\n}"), "{}", go);
+        
+        // A resource a `with` binds is closed as the function leaves
+        let module = PythonParser::new().unwrap().parse("def first(path):\n    with open(path) as f:\n        return f.readline()\n").unwrap();
+        let go = GoGenerator.generate(&module).unwrap();
+        assert!(go.contains("    f := open(path)\n    defer f.Close()\n    return f.readline()\n"), "{}", go);
+    }
+    
+    #[test]
+    fn test_go_binds_and_checks_the_errors_of_fallible_calls() {
+        let source = "function check(v) { if (v < 0) { throw new Error(\"negative\"); } return v; }\n\
+            function note(v) { if (v > 9) { throw new Error(\"big\"); } log(v); }\n\
+            function safe(a) { try { const t = check(a) + 1; note(t); } catch (e) { log(e); } }";
+        let go = generate(&GoGenerator, source);
+        assert!(go.contains("func note(v int) error {"), "{}", go);
+        let expected = "    if e := func() error {\n\
+            \x20       checkResult, err := check(a)\n\
+            \x20       if err != nil {\n\
+            \x20           return err\n\
+            \x20       }\n\
+            \x20       t := checkResult + 1\n\
+            \x20       if err := note(t); err != nil {\n\
+            \x20           return err\n\
+            \x20       }\n\
+            \x20       return nil\n\
+            \x20   }(); e != nil {\n";
+        assert!(go.contains(expected), "{}", go);
+    }
+    
+    #[test]
+    fn test_go_checks_the_errors_of_methods_called_through_a_receiver() {
+        let go = generate(&GoGenerator, THROWING_METHOD);
+        assert!(go.contains("func (self *Account) deposit(amount int) (int, error) {\n"), "{}", go);
+        assert!(go.contains("    depositResult, err := self.deposit(10)\n    if err != nil {\n        return 0, err\n    }\n"), "{}", go);
+        assert!(go.contains("func deposit(total int) int {\n"), "{}", go);
+        let expected = "    if e := func() error {\n\
+            \x20       if _, err := a.deposit(5); err != nil {\n\
+            \x20           return err\n\
+            \x20       }\n\
+            \x20       deposit(1)\n\
+            \x20       return nil\n\
+            \x20   }(); e != nil {\n";
+        assert!(go.contains(expected), "{}", go);
+    }
+    
+    #[test]
+    fn test_go_try_declares_its_bindings_and_finally_runs_before_returns() {
+        let source = "LIMIT = 10\n\n\
+            def load(path):\n\
+            \x20   try:\n\
+            \x20       value = int(path)\n\
+            \x20   except ValueError as e:\n\
+            \x20       return LIMIT\n\
+            \x20   finally:\n\
+            \x20       print(\"done\")\n\
+            \x20   double = lambda x: x * 2\n\
+            \x20   return double(value)\n";
+        let module = PythonParser::new().unwrap().parse(source).unwrap();
+        let go = GoGenerator.generate(&module).unwrap();
+        assert!(go.contains("var LIMIT = 10\n"), "{}", go);
+        assert!(go.contains("    var value int\n    if e := func() error {\n        value = int(path)\n"), "{}", go);
+        assert!(go.contains("}(); e != nil {\n        print(\"done\")\n        return LIMIT\n    }\n    print(\"done\")\n"), "{}", go);
+        assert!(go.contains("double := func(x int) int { return x * 2 }"), "{}", go);
+        
+        let javascript = generate(&GoGenerator, "const _ = require('lodash');\nconst limit = 2;\nfunction f(a) { const g = (x) => { const y = x + a; return y; }; return g(1); }");
+        // CommonJS imports have no counterpart in Go
+        assert!(!javascript.contains("require("), "{}", javascript);
+        assert!(javascript.contains("var limit = 2\n"), "{}", javascript);
+        assert!(javascript.contains("g := func(x int) int {\n"), "{}", javascript);
+    }
+    
+    #[test]
+    fn test_go_generates_classes_loops_lambdas_and_try_finally() {
+        let go = generate(&GoGenerator, CONSTRUCTS);
+        assert!(go.contains("type Counter struct {\n    count int\n}\n\n"), "{}", go);
+        assert!(go.contains("func NewCounter(start int) *Counter {\n    self := &Counter{}\n    self.count = start\n    return self\n}\n\n"), "{}", go);
+        assert!(go.contains("func (self *Counter) add(step int) int {\n    self.count += step\n    return self.count\n}\n"), "{}", go);
+        assert!(go.contains("func run(items int, limit int) int {\n    double := func(x int) int { return x * 2 }\n    total := 0\n"), "{}", go);
+        assert!(go.contains("    for i := 0; i < limit; i += 1 {\n        total += double(i)\n    }\n    for _, item := range items {\n"), "{}", go);
+        assert!(go.contains("    for total > 100 {\n        total -= 10\n    }\n    total = check(total)\n    log(total)\n    return total\n"), "{}", go);
+        
+        // A finally runs on the error path too
+        let go = generate(&GoGenerator, "function strict(v) { try { if (v < 0) { throw new Error(\"negative\"); } v = v + 1; } finally { log(v); } return v; }");
+        assert!(go.contains("    }(); err != nil {\n        log(v)\n        return 0, err\n    }\n    log(v)\n    return v, nil\n"), "{}", go);
+        
+        // Static methods have no receiver
+        let go = generate(&GoGenerator, "class Counter { static zero() { return 0; } }");
+        assert!(go.contains("func CounterZero() int {\n    return 0\n}"), "{}", go);
+        // Python's classes take the same shape
+        let module = PythonParser::new().unwrap().parse("class Counter:\n    def __init__(self, start):\n        self.count = start\n").unwrap();
+        let go = GoGenerator.generate(&module).unwrap();
+        assert!(go.contains("func NewCounter(start int) *Counter {\n    self := &Counter{}\n    self.count = start\n"), "{}", go);
+    }
+    
+    #[test]
+    fn test_c_header_declares_structs_and_prototypes_in_dependency_order() {
+        let source = "class Line:\n    start: Point\n    end: Point\n\nclass Point:\n    x: int\n    y: int\n\n\
+            def length(l: Line) -> int:\n    return dx(l) + 1\n\ndef dx(l: Line) -> int:\n    return l.end.x - l.start.x\n";
+        let uir = PythonParser::new().unwrap().parse(source).unwrap();
+        let header = CGenerator.generate_header(&uir, "geometry.h").unwrap();
+        assert!(header.starts_with("// Generated by Coalesce\n#ifndef GEOMETRY_H\n#define GEOMETRY_H\n\n"), "{}", header);
+        assert!(header.contains("typedef struct Point Point;\ntypedef struct Line Line;\n\nstruct Point {\n    int x;\n    int y;\n};\n\nstruct Line {\n    Point start;\n    Point end;\n};\n"), "{}", header);
+        assert!(header.contains("int dx(Line l);\nint length(Line l);\n"), "{}", header);
+        assert!(header.ends_with("#endif /* GEOMETRY_H */\n"), "{}", header);
+        
+        // Definitions include the header and follow the order of their prototypes
+        let source = CGenerator.generate_source(&uir, "geometry.h").unwrap();
+        assert!(source.contains("#include \"geometry.h\"\n"), "{}", source);
+        assert!(source.contains("int dx(Line l) {\n    return l.end.x - l.start.x;\n}\nint length(Line l) {\n    return dx(l) + 1;\n}\n"), "{}", source);
+        assert!(!source.contains("struct Point {"), "{}", source);
+    }
+    
+    #[test]
+    fn test_c_generates_classes_loops_and_try_finally() {
+        let c = generate(&CGenerator, CONSTRUCTS);
+        assert!(c.contains("struct Counter {\n    int count;\n};"), "{}", c);
+        assert!(c.contains("void Counter_constructor(Counter *self, int start) {\n    self->count = start;\n}"), "{}", c);
+        assert!(c.contains("int Counter_add(Counter *self, int step) {\n    self->count += step;\n    return self->count;\n}"), "{}", c);
+        assert!(c.contains("    for (int i = 0; i < limit; i += 1) {\n        total += double_(i);\n    }\n"), "{}", c);
+        assert!(c.contains("    while (total > 100) {\n        total -= 10;\n    }\n"), "{}", c);
+        // Without exceptions the finally simply follows the body
+        assert!(c.contains("    total = check(total);\n    log(total);\n    return total;\n"), "{}", c);
+        // C has neither closures nor for-each, and reserves `double`
+        assert!(c.contains(&format!("    int double_ = /* {} */;\n", FALLBACK_TODO)), "{}", c);
+        assert!(c.contains(&format!("    }}\n    /* {} */\n    while", FALLBACK_TODO)), "{}", c);
+        let c = generate(&CGenerator, "function scale(long) { return long * 2; }");
+        assert!(c.contains("int scale(int long_) {\n    return long_ * 2;\n}"), "{}", c);
+        
+        let c = generate(&CGenerator, "function f(a) { if (a > 1) { a = 2; } else if (a < 0) { a = 0; } else { a -= 1; } do { a++; } while (a < 5); return a; }");
+        assert!(c.contains("    if (a > 1) {\n        a = 2;\n    } else if (a < 0) {\n        a = 0;\n    } else {\n        a -= 1;\n    }\n"), "{}", c);
+        assert!(c.contains("    do {\n        a += 1;\n    } while (a < 5);\n"), "{}", c);
     }
 }
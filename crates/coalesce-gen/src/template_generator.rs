@@ -0,0 +1,684 @@
+// Template-driven generation
+//
+// A template set maps node keys such as `Function`, `ControlFlow.Loop.While` or
+// `Expression.Literal` to Handlebars templates. Keys fall back from the most specific to the
+// general (`ControlFlow.Loop.While` -> `ControlFlow.Loop` -> `ControlFlow`), and nodes with no
+// template render their children one per line, which flattens wrapper blocks. A node's parser
+// kinds (its semantic tags, e.g. `with_statement`) are tried before its node keys.
+//
+// Every template sees `kind`, `name`, `text`, `tags`, `annotations`, `owner` (the enclosing
+// class), `documentation` (its lines, for declarations that have it) and `children`
+// (rendered). Doc comments attached to a declaration render nothing, since their text is the
+// declaration's `documentation`. Node kinds add their own keys:
+//
+// - Module: `imports` (lines collected from `required_imports`)
+// - Function: `parameters` ({name, type, default}), `parameter_names`, `return_type`, `is_async`, `body`,
+//   and `constructor` for a method constructing its class
+// - Closure: as Function, plus `captures` and `value` when the body is a single expression
+// - Comment: `style` (Line, Block or Doc) and `lines`, the text without delimiters
+// - Import: `path`, `alias` and `symbols` ({name, alias}); source imports render nothing
+//   unless the template set maps them
+// - Class/Interface: `fields` ({name, type, default}), `body`, where a property's getter is
+//   a method named after it and its setter `set_<name>`
+// - Property: `body`, its accessors rendered as those functions
+// - Enum: `variants` ({name, value, fields}), numbered from 0 where the source gave no value
+// - Arithmetic/Comparison/Logical: `operator`, `left`, `right`, or `operand` when unary
+// - Literal: `value`; FunctionCall: `callee`, `arguments`
+// - Assignment: `target`, `value`, `declares` (first assignment in the function)
+// - Await/Yield: `value` (the awaited or yielded expression, absent for a bare `yield`)
+// - Variable: `name`, plus `value` and `declares` for declarators with an initializer
+// - Conditional: `condition`, `body`, `alternatives` ({condition, body}; no condition for else)
+// - Loops: `body`, `continues`, and `otherwise` (Python's loop `else`, unless the body breaks);
+//   `condition` (while); `target`, `iterable` and `keys` (for-each, over keys as JavaScript's
+//   `for...in`); `range` ({variable, start, stop, step}) for a `for` counting by a constant
+//   step to the inclusive `stop`. Any other `for` has its `initializer`, its `condition` and its
+//   update at the end of `body`, and `do ... while` has neither, its test ending the body
+// - with_statement: `resources` ({target, value}), `body`
+// - Try: `body`, `handlers`, `binding`, `cleanup`; Return: `value`; Throw: `message`
+// - Expression.Ternary, tried first for conditional expressions: `condition`, `then`,
+//   `otherwise`, each in parentheses when a conditional expression itself
+//
+// `body`-like values are lists of rendered lines; the `indent`, `lines` and `join` helpers
+// lay them out.
+use coalesce_core::{Generator, GeneratorConfig, Language, LiteralValue, Operator, UIRNode, NodeType, ControlFlowType, LoopType, ExpressionType, StatementType, Result, CoalesceError};
+use crate::{accessor_functions, assignment_operation, binary_operator, call_parts, expression_operator, grouped, declared_default, declared_type, destructured_default, enum_values, is_constructor, is_documentation, is_field, handler_binding, ImportSet, has_tag, is_punctuation, is_async, literal_code, operand, original_text, paragraph_jump, raised_message, verbatim, is_cleanup, is_handler, closure_value, breaks, counted_loop, iterates_keys, loop_parts, lowered_body, ternary_operand, ternary_parts};
+use crate::layout::generate_marked;
+use handlebars::{handlebars_helper, no_escape, Handlebars};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+/// Declarative description of a target language
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplateSet {
+    /// Target language name, e.g. `lua`
+    pub language: String,
+    /// Replacements for C-family operators, e.g. `"!=": "~="`
+    #[serde(default)]
+    pub operators: HashMap<String, String>,
+    /// Replacements for literal spellings, e.g. `"null": "nil"`
+    #[serde(default)]
+    pub literals: HashMap<String, String>,
+    /// Templates keyed by node key
+    pub templates: HashMap<String, String>,
+}
+
+pub struct TemplateGenerator {
+    language: Language,
+    registry: Handlebars<'static>,
+    operators: HashMap<String, String>,
+    literals: HashMap<String, String>,
+}
+
+/// Names declared so far in the function being rendered, and the class around it
+#[derive(Default, Clone)]
+struct RenderScope {
+    declared: HashSet<String>,
+    owner: Option<String>,
+}
+
+handlebars_helper!(indent: |block: Json| {
+    block_lines(block).iter()
+        .map(|line| if line.is_empty() { String::new() } else { format!("    {}", line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+});
+
+handlebars_helper!(lines: |block: Json| block_lines(block).join("\n"));
+
+handlebars_helper!(join: |items: Json, separator: str| {
+    match items {
+        Value::Array(items) => items.iter()
+            .map(|item| item.as_str().map(str::to_string).unwrap_or_else(|| item.to_string()))
+            .collect::<Vec<_>>()
+            .join(separator),
+        Value::String(item) => item.clone(),
+        _ => String::new(),
+    }
+});
+
+/// Lines of a rendered block, which may be a string or a list of strings
+fn block_lines(block: &Value) -> Vec<String> {
+    match block {
+        Value::Array(items) => items.iter().flat_map(block_lines).collect(),
+        Value::String(text) => text.lines().map(str::to_string).collect(),
+        Value::Null => Vec::new(),
+        other => vec![other.to_string()],
+    }
+}
+
+impl TemplateGenerator {
+    /// Compile every template in `set`
+    pub fn new(set: TemplateSet) -> Result<Self> {
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(no_escape);
+        registry.register_helper("indent", Box::new(indent));
+        registry.register_helper("lines", Box::new(lines));
+        registry.register_helper("join", Box::new(join));
+        
+        let mut generator = Self {
+            language: Language::Custom(set.language),
+            registry,
+            operators: set.operators,
+            literals: set.literals,
+        };
+        for (key, template) in &set.templates {
+            generator.register_template(key, template)?;
+        }
+        Ok(generator)
+    }
+    
+    /// Load a JSON template set
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let set: TemplateSet = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        Self::new(set)
+    }
+    
+    /// The bundled Lua template set
+    pub fn lua() -> Result<Self> {
+        let set: TemplateSet = serde_json::from_str(include_str!("../templates/lua.json"))?;
+        Self::new(set)
+    }
+    
+    /// Add or replace the template for a node key
+    pub fn register_template(&mut self, key: &str, template: &str) -> Result<()> {
+        self.registry.register_template_string(key, template)
+            .map_err(|e| CoalesceError::GenerationError(format!("Invalid template for {}: {}", key, e)))
+    }
+    
+    fn render(&self, uir: &UIRNode, scope: &mut RenderScope) -> Result<String> {
+        if is_documentation(uir) {
+            return Ok(String::new());
+        }
+        if let Some(jump) = paragraph_jump(uir) {
+            return self.render(&jump, scope);
+        }
+        if self.is_untranslated_operation(uir) {
+            return Ok(verbatim(uir, original_text(uir), self.language.clone()));
+        }
+        let context = self.context(uir, scope)?;
+        // `a ? b : c` and Python's `b if a else c` share one key whatever node the parser made
+        let ternary = ternary_parts(uir).map(|_| "Expression.Ternary".to_string());
+        let keys = ternary.into_iter().chain(uir.metadata.semantic_tags.iter().cloned()).chain(node_keys(&uir.node_type));
+        match keys.into_iter().find(|key| self.registry.has_template(key)) {
+            Some(key) => self.registry.render(&key, &context)
+                .map_err(|e| CoalesceError::GenerationError(format!("Failed to render {}: {}", key, e))),
+            None => Ok(block_lines(&context["children"]).join("\n")),
+        }
+    }
+    
+    fn render_all<'a>(&self, nodes: impl IntoIterator<Item = &'a UIRNode>, scope: &mut RenderScope) -> Result<Vec<String>> {
+        let mut rendered = Vec::new();
+        for node in nodes {
+            let code = self.render(node, scope)?;
+            if !code.trim().is_empty() {
+                rendered.push(code);
+            }
+        }
+        Ok(rendered)
+    }
+    
+    fn context(&self, uir: &UIRNode, scope: &mut RenderScope) -> Result<Value> {
+        let text = original_text(uir);
+        let mut context = json!({
+            "kind": node_keys(&uir.node_type)[0],
+            "name": uir.name,
+            "text": text,
+            "tags": uir.metadata.semantic_tags,
+            "annotations": uir.metadata.annotations,
+            "owner": scope.owner,
+            "documentation": uir.documentation.as_deref().map(|d| d.lines().collect::<Vec<_>>()),
+        });
+        let content: Vec<&UIRNode> = uir.children.iter().filter(|c| !is_punctuation(c)).collect();
+        
+        match &uir.node_type {
+            NodeType::Module => {
+                context["imports"] = json!(ImportSet::collect(uir).lines(&self.language));
+            }
+            NodeType::Comment { style, text, .. } => {
+                context["style"] = json!(style);
+                context["lines"] = json!(text.lines().collect::<Vec<_>>());
+            }
+            NodeType::Import { path, alias, symbols } => {
+                context["path"] = json!(path);
+                context["alias"] = json!(alias);
+                context["symbols"] = json!(symbols);
+            }
+            NodeType::Function | NodeType::Closure { .. } => {
+                let parameters: Vec<&UIRNode> = content.iter().copied()
+                    .filter(|c| c.node_type == NodeType::Variable)
+                    .filter(|c| !matches!(c.name.as_deref(), Some("self" | "this" | "cls")))
+                    .collect();
+                let mut function_scope = RenderScope {
+                    declared: parameters.iter().filter_map(|p| p.name.clone()).collect(),
+                    owner: scope.owner.clone(),
+                };
+                let body = self.render_all(content.iter().copied().filter(|c| c.node_type != NodeType::Variable), &mut function_scope)?;
+                let mut typed_parameters = Vec::new();
+                for parameter in &parameters {
+                    let mut typed_parameter = typed(parameter);
+                    if let Some(default) = declared_default(parameter) {
+                        typed_parameter["default"] = json!(self.literals.get(default).map_or(default, String::as_str));
+                    } else if let Some(default) = destructured_default(parameter) {
+                        typed_parameter["default"] = json!(self.render(default, &mut function_scope)?);
+                    }
+                    typed_parameters.push(typed_parameter);
+                }
+                context["parameters"] = json!(typed_parameters);
+                context["parameter_names"] = json!(parameters.iter().map(|p| p.name.clone()).collect::<Vec<_>>());
+                context["return_type"] = json!(uir.metadata.annotations.get("return_type"));
+                context["is_async"] = json!(is_async(uir));
+                context["constructor"] = json!(scope.owner.is_some() && is_constructor(uir));
+                context["body"] = json!(body);
+                if let NodeType::Closure { captures, .. } = &uir.node_type {
+                    let statements: Vec<&UIRNode> = content.iter().copied().filter(|c| c.node_type != NodeType::Variable).collect();
+                    context["captures"] = json!(captures);
+                    // Expression-bodied closures, which templates may render as a single return
+                    if let Some(value) = closure_value(&statements) {
+                        context["value"] = json!(self.render(value, &mut function_scope)?);
+                    }
+                }
+            }
+            NodeType::Class | NodeType::Interface => {
+                let mut class_scope = RenderScope { declared: HashSet::new(), owner: uir.name.clone() };
+                let mut fields = Vec::new();
+                for child in &content {
+                    match &child.node_type {
+                        _ if is_field(child) => {
+                            let mut field = typed(child);
+                            if let Some(default) = declared_default(child) {
+                                field["default"] = json!(self.literals.get(default).map_or(default, String::as_str));
+                            }
+                            fields.push(field);
+                        }
+                        // Class-level `count: int = 0`
+                        NodeType::Statement(StatementType::Expression) => {
+                            let Some(assignment) = child.children.iter()
+                                .find(|c| c.node_type == NodeType::Expression(ExpressionType::Assignment)) else { continue };
+                            let [target, value] = assignment.children.as_slice() else { continue };
+                            fields.push(json!({
+                                "name": target.name,
+                                "type": assignment.metadata.annotations.get("type"),
+                                "default": self.render(value, &mut class_scope)?,
+                            }));
+                        }
+                        _ => {}
+                    }
+                }
+                context["fields"] = json!(fields);
+                let mut members: Vec<Cow<UIRNode>> = Vec::new();
+                for child in &content {
+                    match child.node_type {
+                        NodeType::Function => members.push(Cow::Borrowed(*child)),
+                        NodeType::Property { .. } => {
+                            members.extend(accessor_functions(child).into_iter().map(Cow::Owned));
+                        }
+                        _ => {}
+                    }
+                }
+                context["body"] = json!(self.render_all(members.iter().map(|m| m.as_ref()), &mut class_scope)?);
+            }
+            NodeType::Enum { variants } => {
+                let variants: Vec<Value> = variants.iter().zip(enum_values(variants))
+                    .map(|(variant, value)| json!({
+                        "name": variant.name,
+                        "value": value,
+                        "fields": variant.fields.iter()
+                            .map(|field| json!({ "name": field.name, "type": field.field_type }))
+                            .collect::<Vec<_>>(),
+                    }))
+                    .collect();
+                context["variants"] = json!(variants);
+            }
+            NodeType::Expression(ExpressionType::Arithmetic | ExpressionType::Comparison | ExpressionType::Logical) => {
+                match content.as_slice() {
+                    [operand] => {
+                        let operator = match expression_operator(uir) {
+                            Some(Operator::Not) => "!",
+                            Some(Operator::Negate) => "-",
+                            _ => "",
+                        };
+                        context["operator"] = json!(self.map_operator(operator));
+                        context["operand"] = json!(grouped(self.render(operand, scope)?, uir, operand, true));
+                    }
+                    // Unary operators some parsers keep as a child, e.g. JavaScript `-y`
+                    [operator, operand] if operator.node_type == NodeType::Expression(ExpressionType::Literal)
+                        && matches!(original_text(operator), "-" | "+" | "!" | "~" | "not") => {
+                        context["operator"] = json!(self.map_operator(original_text(operator)));
+                        context["operand"] = json!(grouped(self.render(operand, scope)?, uir, operand, true));
+                    }
+                    [left, right] => {
+                        // Floor division keeps its own symbol for template sets that have it
+                        let operator = match uir.operator {
+                            Some(Operator::IntegerDivide) => "//",
+                            _ => binary_operator(uir, left).unwrap_or_default(),
+                        };
+                        context["operator"] = json!(self.map_operator(operator));
+                        context["left"] = json!(grouped(self.render(left, scope)?, uir, left, false));
+                        context["right"] = json!(grouped(self.render(right, scope)?, uir, right, true));
+                    }
+                    _ => {}
+                }
+            }
+            // Declarators such as JavaScript `let y = a * 2`
+            NodeType::Variable if content.len() == 1 => {
+                let name = uir.name.clone().unwrap_or_default();
+                context["value"] = json!(self.render(content[0], scope)?);
+                context["declares"] = json!(scope.declared.insert(name));
+            }
+            // The receiver keyword is spelled per language, e.g. `this` as `self`
+            NodeType::Expression(ExpressionType::Variable) if uir.children.is_empty() => {
+                if let Some(name) = uir.name.as_ref().and_then(|n| self.literals.get(n)) {
+                    context["name"] = json!(name);
+                }
+            }
+            NodeType::Expression(ExpressionType::Literal) => {
+                let value = match (self.literals.get(text), &uir.literal, text.strip_prefix("this.")) {
+                    (Some(literal), _, _) => literal.clone(),
+                    // Other languages' spellings of `true`, `false` and `null`
+                    (None, Some(LiteralValue::Bool(value)), _) => {
+                        self.literals.get(&value.to_string()).cloned().unwrap_or_else(|| value.to_string())
+                    }
+                    (None, Some(LiteralValue::Null), _) => {
+                        self.literals.get("null").cloned().unwrap_or_else(|| text.to_string())
+                    }
+                    (None, Some(value), _) => literal_code(value, &self.language),
+                    // Member accesses the parser keeps as text, e.g. `this.x`
+                    (None, None, Some(member)) => match self.literals.get("this") {
+                        Some(receiver) => format!("{}.{}", receiver, member),
+                        None => text.to_string(),
+                    },
+                    (None, None, None) => verbatim(uir, text, self.language.clone()),
+                };
+                context["value"] = json!(value);
+            }
+            NodeType::Expression(ExpressionType::FunctionCall) => {
+                let (function, arguments) = call_parts(uir);
+                let callee = match function {
+                    Some(function) => Some(self.render(function, scope)?),
+                    None => uir.name.clone(),
+                };
+                context["callee"] = json!(callee);
+                context["arguments"] = json!(self.render_all(arguments, scope)?);
+            }
+            NodeType::Expression(ExpressionType::Await | ExpressionType::Yield) => {
+                if let Some(value) = operand(uir) {
+                    context["value"] = json!(self.render(value, scope)?);
+                }
+            }
+            NodeType::Expression(ExpressionType::Assignment) if content.len() >= 2 => {
+                let target = self.render(content[0], scope)?;
+                // Template sets have no compound forms, so `n -= 1` assigns `n - 1`
+                let value = match assignment_operation(uir) {
+                    Some(operation) => self.render(&operation, scope)?,
+                    None => self.render(content[content.len() - 1], scope)?,
+                };
+                context["declares"] = json!(uir.operator.is_none() && content[0].children.is_empty() && scope.declared.insert(target.clone()));
+                context["target"] = json!(target);
+                context["value"] = json!(value);
+            }
+            NodeType::ControlFlow(ControlFlowType::Conditional) if !content.is_empty() => {
+                let (alternatives, then_branch): (Vec<&UIRNode>, Vec<&UIRNode>) = content[1..].iter()
+                    .partition(|c| has_tag(c, &["else_clause", "elif_clause", "else"]));
+                context["condition"] = json!(self.render(content[0], scope)?);
+                context["body"] = json!(self.render_all(then_branch, scope)?);
+                
+                let mut rendered_alternatives = Vec::new();
+                for alternative in alternatives {
+                    if has_tag(alternative, &["elif_clause"]) && !alternative.children.is_empty() {
+                        rendered_alternatives.push(json!({
+                            "condition": self.render(&alternative.children[0], scope)?,
+                            "body": self.render_all(&alternative.children[1..], scope)?,
+                        }));
+                    } else {
+                        rendered_alternatives.push(json!({ "body": self.render_all(&alternative.children, scope)? }));
+                    }
+                }
+                context["alternatives"] = json!(rendered_alternatives);
+            }
+            NodeType::ControlFlow(ControlFlowType::Loop(loop_type)) => {
+                let parts = loop_parts(uir);
+                let lowered;
+                let body: Vec<&UIRNode> = match (loop_type, counted_loop(&parts)) {
+                    (LoopType::ForEach, _) => {
+                        if let (Some(target), Some(iterable)) = (parts.target, parts.iterable) {
+                            context["target"] = json!(self.render(target, scope)?);
+                            context["iterable"] = json!(self.render(iterable, scope)?);
+                        }
+                        context["keys"] = json!(iterates_keys(uir));
+                        parts.body.clone()
+                    }
+                    (LoopType::For, Some(counted)) => {
+                        context["range"] = json!({
+                            "variable": counted.variable,
+                            "start": self.render(counted.start, scope)?,
+                            "stop": counted.inclusive_end(self.render(counted.end, scope)?),
+                            "step": counted.step,
+                        });
+                        parts.body.clone()
+                    }
+                    (LoopType::While, _) => {
+                        if let Some(condition) = parts.condition {
+                            context["condition"] = json!(self.render(condition, scope)?);
+                        }
+                        parts.body.clone()
+                    }
+                    // Any other `for` runs as a `while` and `do ... while` as an endless loop
+                    _ => {
+                        if let Some(initializer) = parts.initializer {
+                            context["initializer"] = json!(self.render(initializer, scope)?);
+                        }
+                        if let Some(condition) = parts.condition.filter(|_| *loop_type == LoopType::For) {
+                            context["condition"] = json!(self.render(condition, scope)?);
+                        }
+                        lowered = lowered_body(uir, &parts);
+                        lowered.iter().collect()
+                    }
+                };
+                context["continues"] = json!(body.iter().any(|c| continues(c)));
+                context["body"] = json!(self.render_all(body, scope)?);
+                // Python's loop `else` runs unless the loop broke out
+                if !parts.body.iter().any(|c| breaks(c)) {
+                    context["otherwise"] = json!(self.render_all(parts.otherwise.iter().copied(), scope)?);
+                }
+            }
+            NodeType::Statement(StatementType::Expression) if has_tag(uir, &["with_statement"]) => {
+                let mut resources = Vec::new();
+                let mut body = Vec::new();
+                for child in content.iter().copied() {
+                    if !has_tag(child, &["with_clause"]) {
+                        body.push(child);
+                        continue;
+                    }
+                    for item in child.children.iter().filter(|c| !is_punctuation(c)) {
+                        let resource = item.children.first().unwrap_or(item);
+                        let (value, target) = match resource.children.as_slice() {
+                            [value, target] if has_tag(resource, &["as_pattern"]) => (value, handler_binding(resource).or_else(|| target.name.clone())),
+                            _ => (resource, None),
+                        };
+                        if let Some(name) = &target {
+                            scope.declared.insert(name.clone());
+                        }
+                        resources.push(json!({ "target": target, "value": self.render(value, scope)? }));
+                    }
+                }
+                context["resources"] = json!(resources);
+                context["body"] = json!(self.render_all(body, scope)?);
+            }
+            NodeType::ControlFlow(ControlFlowType::Try) => {
+                let mut body = Vec::new();
+                let mut handlers = Vec::new();
+                let mut cleanup = Vec::new();
+                for child in content {
+                    if is_cleanup(child) {
+                        cleanup.extend(child.children.iter().filter(|c| is_statement(c)));
+                    } else if is_handler(child) {
+                        handlers.extend(child.children.iter().filter(|c| is_statement(c)));
+                        if context["binding"].is_null() {
+                            context["binding"] = json!(handler_binding(child));
+                        }
+                    } else {
+                        body.push(child);
+                    }
+                }
+                context["body"] = json!(self.render_all(body, scope)?);
+                context["handlers"] = json!(self.render_all(handlers, scope)?);
+                context["cleanup"] = json!(self.render_all(cleanup, scope)?);
+                return Ok(context_with_children(context));
+            }
+            NodeType::Statement(StatementType::Return) => {
+                let value = match content.first() {
+                    Some(expr) => Some(self.render(expr, scope)?),
+                    None => None,
+                };
+                context["value"] = json!(value);
+            }
+            NodeType::Statement(StatementType::Throw) => {
+                context["message"] = json!(raised_message(uir));
+            }
+            // Accessors outside a class render as functions unless the set maps `Property`
+            NodeType::Property { .. } => {
+                context["body"] = json!(self.render_all(accessor_functions(uir).iter(), scope)?);
+            }
+            _ => {}
+        }
+        if let Some((condition, value, otherwise)) = ternary_parts(uir) {
+            context["condition"] = json!(ternary_operand(self.render(condition, scope)?, condition));
+            context["then"] = json!(ternary_operand(self.render(value, scope)?, value));
+            context["otherwise"] = json!(self.render(otherwise, scope)?);
+        }
+        
+        if context.get("body").is_none() {
+            context["children"] = json!(self.render_all(content, scope)?);
+        }
+        Ok(context_with_children(context))
+    }
+    
+    fn map_operator(&self, operator: &str) -> String {
+        self.operators.get(operator).cloned().unwrap_or_else(|| operator.to_string())
+    }
+    
+    /// An operation with no operator in the set: one it can't spell, or one of JavaScript's
+    /// `instanceof`, `in`, `>>>`, `typeof`, `delete` and `void` it doesn't map
+    fn is_untranslated_operation(&self, uir: &UIRNode) -> bool {
+        if !matches!(uir.node_type, NodeType::Expression(ExpressionType::Arithmetic | ExpressionType::Comparison | ExpressionType::Logical)) {
+            return false;
+        }
+        let content: Vec<&UIRNode> = uir.children.iter().filter(|c| !is_punctuation(c)).collect();
+        match (expression_operator(uir), content.as_slice()) {
+            // Unary operators some parsers keep as a child are spelled by `context`
+            (None, [operator, _]) if operator.node_type == NodeType::Expression(ExpressionType::Literal)
+                && matches!(original_text(operator), "-" | "+" | "!" | "~" | "not") => false,
+            (None, [left, _]) => binary_operator(uir, left).is_none(),
+            (Some(operator @ (Operator::InstanceOf | Operator::In | Operator::UnsignedShiftRight | Operator::TypeOf | Operator::Delete | Operator::Void)), _) => {
+                uir.metadata.source_language != self.language && !self.operators.contains_key(operator.symbol())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Generator for TemplateGenerator {
+    fn target_language(&self) -> Language {
+        self.language.clone()
+    }
+    
+    fn generate(&self, uir: &UIRNode) -> Result<String> {
+        let code = self.render(uir, &mut RenderScope::default())?;
+        Ok(format!("{}\n", code.trim_end()))
+    }
+    
+    fn generate_with_fallbacks(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<(String, Vec<usize>)> {
+        generate_marked(|| self.generate_with_config(uir, config))
+    }
+}
+
+/// Template keys for a node type, most specific first
+fn node_keys(node_type: &NodeType) -> Vec<String> {
+    let path: Vec<&str> = match node_type {
+        NodeType::Module => vec!["Module"],
+        NodeType::Function => vec!["Function"],
+        NodeType::Class => vec!["Class"],
+        NodeType::Interface => vec!["Interface"],
+        NodeType::Variable => vec!["Variable"],
+        NodeType::Constant => vec!["Constant"],
+        NodeType::Field { .. } => vec!["Field"],
+        NodeType::Property { .. } => vec!["Property"],
+        NodeType::Enum { .. } => vec!["Enum"],
+        NodeType::Closure { .. } => vec!["Closure"],
+        NodeType::Comment { .. } => vec!["Comment"],
+        NodeType::Import { .. } => vec!["Import"],
+        NodeType::ControlFlow(flow) => match flow {
+            ControlFlowType::Conditional => vec!["ControlFlow", "Conditional"],
+            ControlFlowType::Loop(LoopType::For) => vec!["ControlFlow", "Loop", "For"],
+            ControlFlowType::Loop(LoopType::While) => vec!["ControlFlow", "Loop", "While"],
+            ControlFlowType::Loop(LoopType::DoWhile) => vec!["ControlFlow", "Loop", "DoWhile"],
+            ControlFlowType::Loop(LoopType::ForEach) => vec!["ControlFlow", "Loop", "ForEach"],
+            ControlFlowType::Switch => vec!["ControlFlow", "Switch"],
+            ControlFlowType::Try => vec!["ControlFlow", "Try"],
+            ControlFlowType::Catch { .. } => vec!["ControlFlow", "Catch"],
+            ControlFlowType::Finally => vec!["ControlFlow", "Finally"],
+            ControlFlowType::Goto => vec!["ControlFlow", "Goto"],
+            ControlFlowType::Concurrent => vec!["ControlFlow", "Concurrent"],
+        },
+        NodeType::Expression(expression) => vec!["Expression", match expression {
+            ExpressionType::Literal => "Literal",
+            ExpressionType::Variable => "Variable",
+            ExpressionType::FunctionCall => "FunctionCall",
+            ExpressionType::Arithmetic => "Arithmetic",
+            ExpressionType::Comparison => "Comparison",
+            ExpressionType::Logical => "Logical",
+            ExpressionType::Assignment => "Assignment",
+            ExpressionType::Await => "Await",
+            ExpressionType::Yield => "Yield",
+        }],
+        NodeType::Statement(statement) => vec!["Statement", match statement {
+            StatementType::Expression => "Expression",
+            StatementType::Return => "Return",
+            StatementType::Break => "Break",
+            StatementType::Continue => "Continue",
+            StatementType::Throw => "Throw",
+            StatementType::Defer => "Defer",
+        }],
+    };
+    (1..=path.len()).rev().map(|depth| path[..depth].join(".")).collect()
+}
+
+/// Whether `uir` continues the loop around it, ignoring loops nested inside
+fn continues(uir: &UIRNode) -> bool {
+    match uir.node_type {
+        NodeType::Statement(StatementType::Continue) => true,
+        NodeType::ControlFlow(ControlFlowType::Loop(_)) | NodeType::Function | NodeType::Closure { .. } => false,
+        _ => uir.children.iter().any(continues),
+    }
+}
+
+fn is_statement(uir: &UIRNode) -> bool {
+    matches!(uir.node_type, NodeType::Statement(_) | NodeType::ControlFlow(_))
+}
+
+/// Nodes with a body expose it as their children too
+fn context_with_children(mut context: Value) -> Value {
+    if let Some(body) = context.get("body").cloned() {
+        context["children"] = body;
+    }
+    context
+}
+
+/// `{name, type}` for a parameter or field
+fn typed(uir: &UIRNode) -> Value {
+    json!({ "name": uir.name, "type": declared_type(uir, "type") })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{generate, CONSTRUCTS};
+    
+    /// A language of a few templates, the rest of its nodes rendering their children
+    fn sketch() -> TemplateGenerator {
+        let set: TemplateSet = serde_json::from_value(json!({
+            "language": "sketch",
+            "operators": { "!=": "<>" },
+            "literals": { "null": "nothing" },
+            "templates": {
+                "Function": "fn {{name}}({{join parameter_names \", \"}})\n{{indent body}}\nend",
+                "ControlFlow.Loop": "loop\n{{indent body}}\nend",
+                "Expression.Comparison": "{{left}} {{operator}} {{right}}",
+                "Expression.Literal": "{{value}}",
+                "Expression.Variable": "{{name}}",
+                "Statement.Return": "return {{value}}",
+            },
+        })).unwrap();
+        TemplateGenerator::new(set).unwrap()
+    }
+    
+    #[test]
+    fn test_templates_fall_back_to_general_keys_and_map_spellings() {
+        let source = "function f(a) {\n  while (a != null) {\n    return a;\n  }\n}\n";
+        // `ControlFlow.Loop.While` takes the template of `ControlFlow.Loop`
+        let code = generate(&sketch(), source);
+        assert!(code.contains("fn f(a)\n    loop\n        return a\n    end\nend"), "{}", code);
+        
+        // A more specific key takes over from the general one
+        let mut generator = sketch();
+        generator.register_template("ControlFlow.Loop.While", "while {{condition}}\n{{indent body}}\nend").unwrap();
+        let code = generate(&generator, source);
+        assert!(code.contains("    while a <> nothing\n        return a\n    end\n"), "{}", code);
+        
+        assert!(generator.register_template("Function", "{{#if}}").is_err());
+    }
+    
+    #[test]
+    fn test_lua_generates_classes_loops_lambdas_and_try_finally() {
+        let lua = generate(&TemplateGenerator::lua().unwrap(), CONSTRUCTS);
+        assert!(lua.contains("local Counter = {}\nCounter.__index = Counter\n\n"), "{}", lua);
+        assert!(lua.contains("function Counter.new(start)\n    local self = setmetatable({}, Counter)\n    self.count = start\n    return self\nend\n"), "{}", lua);
+        assert!(lua.contains("function Counter:add(step)\n    if step == nil then step = 1 end\n    self.count = self.count + step\n    return self.count\nend\n"), "{}", lua);
+        assert!(lua.contains("local function run(items, limit)\n    local double = function(x) return x * 2 end\n    local total = 0\n"), "{}", lua);
+        assert!(lua.contains("    for i = 0, limit - 1 do\n        total = total + double(i)\n    end\n    for _, item in ipairs(items) do\n"), "{}", lua);
+        assert!(lua.contains("    while total > 100 do\n        total = total - 10\n    end\n"), "{}", lua);
+        assert!(lua.contains("    local ok, err = pcall(function()\n        total = check(total)\n    end)\n    log(total)\n    if not ok then\n        error(err)\n    end\n"), "{}", lua);
+    }
+}
@@ -0,0 +1,1024 @@
+// Generators for the JavaScript ecosystem
+
+use coalesce_core::{EnumVariant, Generator, GeneratorConfig, Language, Modifier, Operator, UIRNode, Visibility, NodeType, ControlFlowType, LoopType, ExpressionType, StatementType, Result, Walk, walk};
+use crate::layout::{argument_list, block, continued, generate_marked, generate_styled, indent_unit};
+use crate::{accessor_functions, accessor_method, accessors, assignment_form, comprehension_parts, subscript_parts, AssignmentForm, FALLBACK_TODO, attached_comments, breaks, call_parts, class_bases, comment_code, declaration_kind, declared_default, declared_type, declared_variables, destructured_default, destructured_value, documentation_block, enum_values, expression_operator, grouped, binary_operator, original_text, handler_binding, has_payload, has_tag, identifier, initializer, is_async, is_cleanup, is_comment, is_documentation, is_field, is_handler, is_import, is_punctuation, is_setter, is_static, iterates_keys, literal_code, loop_parts, module_items, operand, paragraph_jump, raised_message, split_type_arguments, ternary_operand, ternary_parts, verbatim, ImportSet};
+
+pub struct TypeScriptGenerator;
+
+/// Where a function is declared, which decides the keywords it is written with
+#[derive(Clone, Copy, PartialEq)]
+enum Placement {
+    /// `export function` at module level
+    Module,
+    /// A class method, written without `function`
+    Member,
+    /// `function` in another function's body
+    Local,
+}
+
+impl Generator for TypeScriptGenerator {
+    fn target_language(&self) -> Language {
+        Language::TypeScript
+    }
+    
+    fn generate_with_config(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<String> {
+        generate_styled(self, uir, config)
+    }
+    
+    fn generate_with_fallbacks(&self, uir: &UIRNode, config: &GeneratorConfig) -> Result<(String, Vec<usize>)> {
+        generate_marked(|| self.generate_with_config(uir, config))
+    }
+    
+    fn generate(&self, uir: &UIRNode) -> Result<String> {
+        if let Some((collection, index)) = subscript_parts(uir) {
+            return Ok(format!("{}[{}]", self.generate(collection)?, self.generate(index)?));
+        }
+        if let Some(comprehension) = comprehension_parts(uir) {
+            let target = self.generate(comprehension.target)?;
+            let mut code = self.generate(comprehension.iterable)?;
+            for condition in &comprehension.conditions {
+                code.push_str(&format!(".filter(({}: any) => {})", target, self.generate(condition)?));
+            }
+            let element = self.generate(comprehension.element)?;
+            if element != target {
+                code.push_str(&format!(".map(({}: any) => {})", target, element));
+            }
+            return Ok(code);
+        }
+        if let Some((condition, value, otherwise)) = ternary_parts(uir) {
+            return Ok(format!(
+                "{} ? {} : {}",
+                ternary_operand(self.generate(condition)?, condition),
+                ternary_operand(self.generate(value)?, value),
+                self.generate(otherwise)?,
+            ));
+        }
+        match &uir.node_type {
+            NodeType::Module => {
+                let mut code = String::from("// Generated by Coalesce\n\n");
+                code.push_str(&ImportSet::collect(uir).render(&Language::TypeScript));
+                
+                for child in module_items(uir).into_iter().filter(|c| !is_import(c) && !is_documentation(c)) {
+                    // Functions and classes are exported anyway; declarations keep their `export`
+                    let (child, export) = match exported_declaration(child) {
+                        Some(declaration) => (declaration, declared_variables(declaration).is_some()),
+                        None => (child, false),
+                    };
+                    // A property's accessor functions are documented one by one
+                    if !matches!(child.node_type, NodeType::Property { .. }) {
+                        code.push_str(&documentation_block(child));
+                    }
+                    if matches!(child.node_type, NodeType::Statement(_) | NodeType::Expression(_) | NodeType::ControlFlow(_)) {
+                        let item = self.generate_statement(child)?;
+                        if !item.trim().is_empty() {
+                            let export = if export { "export " } else { "" };
+                            code.push_str(&format!("{}{}\n", export, item.trim()));
+                        }
+                    } else {
+                        code.push_str(&self.generate(child)?);
+                        code.push('\n');
+                    }
+                }
+                
+                Ok(code)
+            }
+            NodeType::Function => {
+                self.generate_function(uir, Placement::Module)
+            }
+            NodeType::Property { .. } => {
+                let functions = accessor_functions(uir).iter()
+                    .map(|function| Ok(format!("{}{}", documentation_block(function), self.generate_function(function, Placement::Module)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(functions.join("\n\n"))
+            }
+            NodeType::Interface | NodeType::Class => {
+                self.generate_type(uir, true)
+            }
+            NodeType::Enum { variants } => {
+                Ok(self.generate_enum(uir, variants))
+            }
+            // Member accesses Python keeps as their parts, as `self.count`
+            NodeType::Expression(ExpressionType::Variable) if original_text(uir).contains('.') && uir.children.len() >= 2 => {
+                let mut parts = Vec::new();
+                for child in uir.children.iter().filter(|c| original_text(c) != ".") {
+                    parts.push(match child.name.as_deref() {
+                        Some("self") if uir.metadata.source_language == Language::Python => "this".to_string(),
+                        _ => self.generate(child)?,
+                    });
+                }
+                Ok(parts.join("."))
+            }
+            NodeType::Variable | NodeType::Expression(ExpressionType::Variable) => {
+                Ok(identifier(uir.name.as_deref().unwrap_or("unknown")))
+            }
+            NodeType::Comment { .. } => Ok(comment_code(uir, "//", "///", Some(("/*", "*/")))),
+            NodeType::Statement(StatementType::Return) => {
+                self.generate_return_statement(uir)
+            }
+            NodeType::Statement(StatementType::Break) => Ok("break".to_string()),
+            NodeType::Statement(StatementType::Continue) => Ok("continue".to_string()),
+            NodeType::Statement(StatementType::Throw) => {
+                // JavaScript throws any value; other sources raise an error with a message
+                let thrown = uir.children.iter().find(|c| !is_punctuation(c) && original_text(c) != "throw");
+                match thrown {
+                    Some(thrown) if matches!(uir.metadata.source_language, Language::JavaScript | Language::TypeScript) => {
+                        Ok(format!("throw {}", self.generate(thrown)?))
+                    }
+                    _ => Ok(format!("throw new Error({})", raised_message(uir))),
+                }
+            }
+            NodeType::ControlFlow(_) => {
+                self.generate_control_flow(uir)
+            }
+            NodeType::Expression(ExpressionType::Arithmetic | ExpressionType::Comparison | ExpressionType::Logical) => {
+                self.generate_operator_expression(uir)
+            }
+            NodeType::Expression(ExpressionType::Literal) => {
+                if let Some(value) = &uir.literal {
+                    return Ok(literal_code(value, &Language::TypeScript));
+                }
+                if let Some(original) = uir.metadata.annotations.get("original_text") {
+                    if let Some(text) = original.as_str() {
+                        Ok(verbatim(uir, text, Language::TypeScript))
+                    } else {
+                        Ok("0".to_string())
+                    }
+                } else {
+                    Ok("0".to_string())
+                }
+            }
+            NodeType::Statement(_) if declared_variables(uir).is_some() => {
+                // `var` is function-scoped; `let` is what the code means wherever it is read
+                let keyword = match declaration_kind(uir) {
+                    Some("const") => "const",
+                    _ => "let",
+                };
+                let mut lines = Vec::new();
+                for variable in declared_variables(uir).unwrap_or_default() {
+                    let mut declaration = format!("{} {}", keyword, identifier(variable.name.as_deref().unwrap_or("value")));
+                    if declared_type(variable, "type").is_some() {
+                        declaration.push_str(&format!(": {}", self.annotated_type(variable, "type")));
+                    }
+                    let value = match (destructured_value(variable, |key| format!(".{}", key)), initializer(variable)) {
+                        (Some(value), _) => match destructured_default(variable) {
+                            Some(default) => Some(format!("{} ?? {}", value, self.generate(default)?)),
+                            None => Some(value),
+                        },
+                        (None, Some(value)) => Some(self.generate(value)?),
+                        (None, None) => None,
+                    };
+                    if let Some(value) = value {
+                        declaration.push_str(&format!(" = {}", value));
+                    }
+                    lines.push(declaration);
+                }
+                // Whoever places the statement terminates the last one
+                Ok(lines.join(";\n"))
+            }
+            NodeType::Closure { .. } => {
+                let parameters = self.parameters(uir)?;
+                let body: Vec<&UIRNode> = uir.children.iter()
+                    .filter(|c| c.node_type != NodeType::Variable && !is_punctuation(c) && !is_comment(c))
+                    .collect();
+                // An expression body stays one; statements go in a block kept on one line
+                let body = match body.as_slice() {
+                    [expression] if matches!(expression.node_type, NodeType::Expression(_)) => {
+                        let value = self.generate(expression)?;
+                        // An object literal body would read as a block
+                        if value.starts_with('{') { format!("({})", value) } else { value }
+                    }
+                    statements => {
+                        let mut lines = Vec::new();
+                        for statement in statements {
+                            let mut line = String::new();
+                            for part in self.generate_statement(statement)?.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                                // Argument lists split over lines join back up
+                                if !line.is_empty() && !line.ends_with('(') {
+                                    line.push(' ');
+                                }
+                                // A line comment would end the line the block is kept on
+                                match part.strip_prefix("//") {
+                                    Some(comment) => line.push_str(&format!("/*{} */", comment)),
+                                    None => line.push_str(part),
+                                }
+                            }
+                            if !line.is_empty() {
+                                lines.push(line);
+                            }
+                        }
+                        format!("{{ {} }}", lines.join(" "))
+                    }
+                };
+                let prefix = if is_async(uir) { "async " } else { "" };
+                Ok(format!("{}({}) => {}", prefix, parameters.join(", "), body))
+            }
+            NodeType::Expression(ExpressionType::Assignment) if uir.children.len() >= 2 => {
+                let target = self.generate(&uir.children[0])?;
+                let (symbol, value) = match assignment_form(uir, &Language::TypeScript) {
+                    AssignmentForm::Plain => ("=", self.generate(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Compound(symbol) => (symbol, self.generate(&uir.children[uir.children.len() - 1])?),
+                    AssignmentForm::Expanded(operation) => ("=", self.generate(&operation)?),
+                };
+                Ok(format!("{} {} {}", target, symbol, value))
+            }
+            // Expression statements wrap the expression itself
+            NodeType::Statement(StatementType::Expression) => match uir.children.iter().filter(|c| !is_punctuation(c)).collect::<Vec<_>>().as_slice() {
+                [expression] => self.generate(expression),
+                _ => Ok(format!("// {}\n", FALLBACK_TODO)),
+            },
+            NodeType::Expression(ExpressionType::FunctionCall) => {
+                let (function, arguments) = call_parts(uir);
+                let callee = match function {
+                    Some(function) => self.generate(function)?,
+                    None => identifier(uir.name.as_deref().unwrap_or("unknown")),
+                };
+                let arguments = arguments.into_iter().map(|a| self.generate(a)).collect::<Result<Vec<_>>>()?;
+                Ok(argument_list(&callee, &arguments))
+            }
+            NodeType::Expression(ExpressionType::Await) => match operand(uir) {
+                Some(promise) => Ok(format!("await {}", self.generate(promise)?)),
+                None => Ok(format!("// {}\n", FALLBACK_TODO)),
+            },
+            NodeType::Expression(ExpressionType::Yield) => {
+                let keyword = if has_tag(uir, &["yield_from"]) { "yield*" } else { "yield" };
+                match operand(uir) {
+                    Some(value) => Ok(format!("{} {}", keyword, self.generate(value)?)),
+                    None => Ok(keyword.to_string()),
+                }
+            }
+            _ => {
+                Ok(format!("// {}\n", FALLBACK_TODO))
+            }
+        }
+    }
+}
+
+impl TypeScriptGenerator {
+    /// Emit a function where `placement` puts it: exported, as a class method or nested
+    fn generate_function(&self, uir: &UIRNode, placement: Placement) -> Result<String> {
+        let func_name = match uir.name.as_deref() {
+            Some("__init__") if placement == Placement::Member => "constructor".to_string(),
+            name => identifier(name.unwrap_or("generatedFunction")),
+        };
+        let is_async = is_async(uir);
+        
+        let mut statements = Vec::new();
+        for child in &uir.children {
+            if child.node_type != NodeType::Variable && !is_import(child) {
+                statements.push(child);
+            }
+        }
+        
+        let body = if statements.is_empty() {
+            indent("// Empty function")
+        } else {
+            self.generate_block(&statements)?
+        };
+        
+        let keyword = match placement {
+            Placement::Module => "export function",
+            Placement::Member => "",
+            Placement::Local => "function",
+        };
+        let prefix = match (keyword, is_async, self.is_generator(uir)) {
+            ("", true, false) => "async ".to_string(),
+            ("", true, true) => "async *".to_string(),
+            ("", false, false) => String::new(),
+            ("", false, true) => "*".to_string(),
+            (keyword, true, generator) => format!("{}{} ", keyword.replace("function", "async function"), if generator { "*" } else { "" }),
+            (keyword, false, generator) => format!("{}{} ", keyword, if generator { "*" } else { "" }),
+        };
+        
+        let signature = self.signature(uir, &statements, placement == Placement::Member && func_name == "constructor")?;
+        Ok(block(&format!("{}{}{}", prefix, func_name, signature), &body))
+    }
+    
+    /// Statements of a function body or block, indented one level
+    fn generate_block(&self, statements: &[&UIRNode]) -> Result<String> {
+        let mut lines = Vec::new();
+        for statement in statements.iter().filter(|s| !is_punctuation(s)) {
+            // Python's block of several statements under an `if` is spliced in
+            if statement.node_type == NodeType::Statement(StatementType::Expression) && has_tag(statement, &["block"]) && statement.children.len() > 1 {
+                let nested: Vec<&UIRNode> = statement.children.iter().collect();
+                lines.push(self.generate_block(&nested)?);
+                continue;
+            }
+            let code = self.generate_statement(statement)?;
+            if !code.trim().is_empty() {
+                lines.push(indent(code.trim_end()));
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+    
+    /// A statement in a body or at module level; only simple statements end in `;`
+    fn generate_statement(&self, uir: &UIRNode) -> Result<String> {
+        match &uir.node_type {
+            NodeType::Comment { .. } => self.generate(uir),
+            NodeType::Function => self.generate_function(uir, Placement::Local),
+            NodeType::Class | NodeType::Interface => self.generate_type(uir, false),
+            NodeType::ControlFlow(_) => self.generate_control_flow(uir),
+            // Source the parser kept as written, such as a `switch`, ends as it did
+            NodeType::Expression(ExpressionType::Literal) if uir.literal.is_none() && !original_text(uir).is_empty() => {
+                let text = self.generate(uir)?;
+                Ok(if text.ends_with([';', '}']) { text } else { format!("{};", text) })
+            }
+            _ => {
+                let code = self.generate(uir)?;
+                let code = code.trim();
+                // Fallback comments are not statements
+                Ok(if code.is_empty() || code.starts_with("//") { code.to_string() } else { format!("{};", code) })
+            }
+        }
+    }
+    
+    fn generate_control_flow(&self, uir: &UIRNode) -> Result<String> {
+        match &uir.node_type {
+            NodeType::ControlFlow(ControlFlowType::Conditional) => self.generate_conditional(uir),
+            NodeType::ControlFlow(ControlFlowType::Loop(loop_type)) => self.generate_loop(uir, loop_type),
+            NodeType::ControlFlow(ControlFlowType::Try) => self.generate_try(uir),
+            NodeType::ControlFlow(ControlFlowType::Goto) => match paragraph_jump(uir) {
+                Some(jump) => self.generate_statement(&jump),
+                None => Ok(format!("// {}", FALLBACK_TODO)),
+            },
+            _ => Ok(format!("// {}", FALLBACK_TODO)),
+        }
+    }
+    
+    fn generate_conditional(&self, uir: &UIRNode) -> Result<String> {
+        let Some(condition) = uir.children.first() else {
+            return Ok(format!("// {}", FALLBACK_TODO));
+        };
+        let (alternatives, then_branch): (Vec<&UIRNode>, Vec<&UIRNode>) = uir.children[1..].iter()
+            .partition(|c| has_tag(c, &["else_clause", "elif_clause", "else"]));
+        
+        let mut code = block(&format!("if ({})", self.generate(condition)?), &self.generate_block(&then_branch)?);
+        for alternative in alternatives {
+            if has_tag(alternative, &["elif_clause"]) {
+                code.push_str(&continued("else "));
+                code.push_str(&self.generate_conditional(alternative)?);
+                continue;
+            }
+            let body: Vec<&UIRNode> = alternative.children.iter()
+                .filter(|c| !is_punctuation(c) && original_text(c) != "else")
+                .collect();
+            match body.as_slice() {
+                // JavaScript's `else if` is an `if` alone in the `else`
+                [nested] if nested.node_type == NodeType::ControlFlow(ControlFlowType::Conditional) => {
+                    code.push_str(&continued("else "));
+                    code.push_str(&self.generate_conditional(nested)?);
+                }
+                _ => code.push_str(&block(&continued("else"), &self.generate_block(&body)?)),
+            }
+        }
+        Ok(code)
+    }
+    
+    fn generate_loop(&self, uir: &UIRNode, loop_type: &LoopType) -> Result<String> {
+        let parts = loop_parts(uir);
+        let body = self.generate_block(&parts.body)?;
+        let condition = parts.condition.map(|c| self.generate(c)).transpose()?.unwrap_or_else(|| "true".to_string());
+        let mut code = match (loop_type, parts.target, parts.iterable) {
+            (LoopType::ForEach, Some(target), Some(iterable)) => {
+                let keyword = if iterates_keys(uir) { "in" } else { "of" };
+                let declaration = match declaration_kind(target) {
+                    Some("let" | "var") => "let",
+                    _ => "const",
+                };
+                block(&format!("for ({} {} {} {})", declaration, self.generate(target)?, keyword, self.generate(iterable)?), &body)
+            }
+            (LoopType::For, ..) => {
+                let part = |node: Option<&UIRNode>| node.map(|n| self.generate(n)).transpose().map(Option::unwrap_or_default);
+                let header = format!("for ({}; {}; {})", part(parts.initializer)?, part(parts.condition)?, part(parts.update)?);
+                block(&header.replace("(; ", "(;").replace("; ; ", ";; ").replace("; )", ";)"), &body)
+            }
+            (LoopType::DoWhile, ..) => format!("{} while ({});", block("do", &body), condition),
+            _ => block(&format!("while ({})", condition), &body),
+        };
+        // Python's loop `else` runs unless the loop broke out
+        if parts.body.iter().any(|statement| breaks(statement)) && !parts.otherwise.is_empty() {
+            code.push_str(&format!("\n// {}", FALLBACK_TODO));
+        } else {
+            for statement in &parts.otherwise {
+                code.push('\n');
+                code.push_str(&self.generate_statement(statement)?);
+            }
+        }
+        Ok(code)
+    }
+    
+    /// try/catch/finally; several handlers share the one `catch`, chosen by `instanceof`
+    fn generate_try(&self, uir: &UIRNode) -> Result<String> {
+        let body: Vec<&UIRNode> = uir.children.iter().filter(|c| !is_handler(c) && !is_cleanup(c)).collect();
+        let handlers: Vec<&UIRNode> = uir.children.iter().filter(|c| is_handler(c)).collect();
+        let cleanup: Vec<&UIRNode> = uir.children.iter().filter(|c| is_cleanup(c)).flat_map(|c| c.children.iter()).collect();
+        
+        let mut code = block("try", &self.generate_block(&body)?);
+        match handlers.as_slice() {
+            [] => {}
+            // The first handler catches everything, so no other runs
+            [handler, ..] if exception_types(handler).is_empty() => {
+                let header = match handler_binding(handler) {
+                    Some(binding) => format!("catch ({})", binding),
+                    None => "catch".to_string(),
+                };
+                code.push_str(&block(&continued(&header), &self.generate_block(&handler_statements(handler))?));
+            }
+            handlers => {
+                let binding = handlers.iter().find_map(|h| handler_binding(h)).unwrap_or_else(|| "error".to_string());
+                let mut dispatch = String::new();
+                let mut catches_all = false;
+                for handler in handlers {
+                    let handled = self.generate_block(&handler_statements(handler))?;
+                    let types = exception_types(handler);
+                    if types.is_empty() {
+                        dispatch.push_str(&block(&continued("else"), &handled));
+                        catches_all = true;
+                        break;
+                    }
+                    let test = types.iter().map(|t| format!("{} instanceof {}", binding, t)).collect::<Vec<_>>().join(" || ");
+                    let keyword = if dispatch.is_empty() { String::new() } else { continued("else ") };
+                    dispatch.push_str(&block(&format!("{}if ({})", keyword, test), &handled));
+                }
+                // Errors no handler takes propagate
+                if !catches_all {
+                    dispatch.push_str(&block(&continued("else"), &indent(&format!("throw {};", binding))));
+                }
+                code.push_str(&block(&continued(&format!("catch ({})", binding)), &indent(&dispatch)));
+            }
+        }
+        if !cleanup.is_empty() {
+            code.push_str(&block(&continued("finally"), &self.generate_block(&cleanup)?));
+        }
+        Ok(code)
+    }
+    
+    /// Parameters as declared: each destructuring pattern written back whole, rest parameters
+    /// spread and defaults kept
+    fn parameters(&self, uir: &UIRNode) -> Result<Vec<String>> {
+        let mut parameters = Vec::new();
+        let mut pattern = None;
+        for param in uir.children.iter().filter(|c| c.node_type == NodeType::Variable) {
+            // The receiver is implicit in TypeScript methods
+            let Some(name) = param.name.as_deref().filter(|n| !matches!(*n, "self" | "&self" | "&mut self" | "cls")) else {
+                continue;
+            };
+            let annotation = |key: &str| param.metadata.annotations.get(key);
+            // The bindings of one destructured parameter follow each other
+            if let Some(text) = annotation("pattern").and_then(|v| v.as_str()) {
+                if pattern != Some(text) {
+                    parameters.push(match annotation("pattern_default").and_then(|v| v.as_str()) {
+                        Some(default) => format!("{}: any = {}", text, default),
+                        None => format!("{}: any", text),
+                    });
+                }
+                pattern = Some(text);
+                continue;
+            }
+            pattern = None;
+            
+            let name = identifier(name);
+            let rest = annotation("rest").and_then(|v| v.as_bool()).unwrap_or(false);
+            let parameter = match annotation("splat").and_then(|v| v.as_str()) {
+                Some("dictionary") => format!("{}: Record<string, any>", name),
+                Some(_) => format!("...{}: any[]", name),
+                None if rest && declared_type(param, "type").is_none() => format!("...{}: any[]", name),
+                None if rest => format!("...{}: {}", name, self.annotated_type(param, "type")),
+                None => format!("{}: {}", name, self.annotated_type(param, "type")),
+            };
+            let has_default = annotation("has_default").and_then(|v| v.as_bool()).unwrap_or(false);
+            let default = match (declared_default(param), initializer(param)) {
+                (Some(value), _) => Some(match value {
+                    "True" => "true".to_string(),
+                    "False" => "false".to_string(),
+                    "None" | "nil" => "null".to_string(),
+                    value => value.to_string(),
+                }),
+                (None, Some(value)) if has_default => Some(self.generate(value)?),
+                _ => None,
+            };
+            parameters.push(match default {
+                Some(default) => format!("{} = {}", parameter, default),
+                None => parameter,
+            });
+        }
+        Ok(parameters)
+    }
+    
+    /// Parameter list and return type, e.g. `(a: number, b: number): number`; constructors
+    /// declare no return type
+    fn signature(&self, uir: &UIRNode, statements: &[&UIRNode], constructor: bool) -> Result<String> {
+        let parameters = self.parameters(uir)?;
+        if constructor {
+            return Ok(argument_list("", &parameters));
+        }
+        
+        let mut return_type = match uir.metadata.annotations.get("return_type").and_then(|v| v.as_str()) {
+            Some(source_type) => self.map_type(source_type),
+            None if statements.iter().any(|s| self.has_value_return(s)) => "any".to_string(),
+            None => "void".to_string(),
+        };
+        let is_async = is_async(uir);
+        if self.is_generator(uir) && !return_type.contains("Generator<") && !return_type.contains("Iter") {
+            return_type = if is_async { "AsyncGenerator<any>" } else { "Generator<any>" }.to_string();
+        } else if is_async && !return_type.starts_with("Promise<") {
+            return_type = format!("Promise<{}>", return_type);
+        }
+        
+        Ok(format!("{}: {}", argument_list("", &parameters), return_type))
+    }
+    
+    /// A class, or an interface for data-only types such as records and structs, which are
+    /// plain object shapes in TypeScript; only module-level types are exported
+    fn generate_type(&self, uir: &UIRNode, exported: bool) -> Result<String> {
+        let data_only = !matches!(uir.metadata.source_language, Language::JavaScript | Language::TypeScript)
+            && !uir.children.iter().any(|c| c.node_type == NodeType::Function || !accessors(c).is_empty())
+            && class_bases(uir).is_empty()
+            && !uir.children.iter().any(|c| declared_default(c).is_some());
+        if uir.node_type == NodeType::Interface || data_only {
+            self.generate_interface(uir, exported)
+        } else {
+            self.generate_class(uir, exported)
+        }
+    }
+    
+    fn generate_interface(&self, uir: &UIRNode, exported: bool) -> Result<String> {
+        let interface_name = uir.name.as_deref().unwrap_or("GeneratedInterface");
+        
+        let mut members = Vec::new();
+        for child in &uir.children {
+            match &child.node_type {
+                _ if is_field(child) => {
+                    if let Some(field_name) = &child.name {
+                        let readonly = matches!(child.node_type, NodeType::Constant | NodeType::Property { has_setter: false, .. });
+                        let readonly = if readonly { "readonly " } else { "" };
+                        let member = format!("{}{}{}: {};", documentation_block(child), readonly, field_name, self.annotated_type(child, "type"));
+                        members.push(indent(&member));
+                    }
+                }
+                NodeType::Function => {
+                    let method_name = child.name.as_deref().unwrap_or("method");
+                    members.push(indent(&format!("{}{}{};", documentation_block(child), method_name, self.signature(child, &[], false)?)));
+                }
+                NodeType::Statement(StatementType::Expression) => {
+                    for (field_name, assignment, _) in class_assignments(child) {
+                        members.push(indent(&format!("{}: {};", field_name, self.annotated_type(assignment, "type"))));
+                    }
+                }
+                _ => {}
+            }
+        }
+        
+        let export = if exported { "export " } else { "" };
+        Ok(block(&format!("{}interface {}", export, interface_name), &members.join("\n")))
+    }
+    
+    /// A TypeScript enum, or for variants carrying data a union discriminated by `kind`
+    fn generate_enum(&self, uir: &UIRNode, variants: &[EnumVariant]) -> String {
+        let enum_name = uir.name.as_deref().unwrap_or("GeneratedEnum");
+        if has_payload(variants) {
+            let members: Vec<String> = variants.iter()
+                .map(|variant| {
+                    let mut fields = vec![format!("kind: \"{}\"", variant.name)];
+                    fields.extend(variant.fields.iter().enumerate().map(|(index, field)| {
+                        let name = field.name.clone().unwrap_or_else(|| format!("_{}", index));
+                        format!("{}: {}", name, self.map_type(&field.field_type))
+                    }));
+                    indent(&format!("| {{ {} }}", fields.join("; ")))
+                })
+                .collect();
+            return format!("export type {} =\n{};", enum_name, members.join("\n"));
+        }
+        let members: Vec<String> = variants.iter().zip(enum_values(variants))
+            .map(|(variant, value)| match variant.value {
+                Some(_) => format!("{} = {},", variant.name, value),
+                None => format!("{},", variant.name),
+            })
+            .collect();
+        block(&format!("export enum {}", enum_name), &indent(&members.join("\n")))
+    }
+    
+    fn generate_class(&self, uir: &UIRNode, exported: bool) -> Result<String> {
+        let class_name = uir.name.as_deref().unwrap_or("GeneratedClass");
+        
+        let mut fields = Vec::new();
+        let mut methods = Vec::new();
+        for child in &uir.children {
+            match &child.node_type {
+                _ if is_field(child) => {
+                    if let Some(field_name) = &child.name {
+                        let readonly = matches!(child.node_type, NodeType::Constant | NodeType::Property { has_setter: false, .. })
+                            || child.modifiers.iter().any(|m| matches!(m, Modifier::Const | Modifier::ReadOnly));
+                        let readonly = if readonly { "readonly " } else { "" };
+                        let field_name = if has_tag(child, &["private_name"]) { format!("#{}", field_name) } else { field_name.clone() };
+                        // An initialized field without a declared type takes its value's
+                        let declaration = match (declared_type(child, "type"), declared_default(child)) {
+                            (None, Some(value)) => format!("{} = {}", field_name, value),
+                            (_, Some(value)) => format!("{}: {} = {}", field_name, self.annotated_type(child, "type"), value),
+                            (_, None) => format!("{}: {}", field_name, self.annotated_type(child, "type")),
+                        };
+                        fields.push(indent(&format!(
+                            "{}{}{}{};",
+                            documentation_block(child), self.member_modifiers(child), readonly, declaration
+                        )));
+                    }
+                }
+                NodeType::Property { .. } => {
+                    for accessor in accessors(child) {
+                        let method = accessor_method(child, accessor);
+                        let code = self.generate_function(&method, Placement::Member)?;
+                        // Setters take the new value and may not declare a return type
+                        let code = if is_setter(accessor) {
+                            format!("set {}", code.replacen("): void {", ") {", 1))
+                        } else {
+                            format!("get {}", code)
+                        };
+                        let code = format!("{}{}{}", documentation_block(&method), self.member_modifiers(&method), code);
+                        methods.push(indent(&code));
+                    }
+                }
+                NodeType::Function => {
+                    let mut method_code: String = attached_comments(&uir.children, child).into_iter()
+                        .map(|comment| format!("{}\n", comment_code(comment, "//", "///", Some(("/*", "*/")))))
+                        .collect();
+                    method_code.push_str(&documentation_block(child));
+                    method_code.push_str(&format!("{}{}", self.member_modifiers(child), self.generate_function(child, Placement::Member)?));
+                    methods.push(indent(&method_code));
+                }
+                NodeType::Statement(StatementType::Expression) => {
+                    for (field_name, assignment, value) in class_assignments(child) {
+                        let declaration = match (declared_type(assignment, "type"), value) {
+                            (None, Some(value)) => format!("{} = {}", field_name, self.generate(value)?),
+                            (_, Some(value)) => format!("{}: {} = {}", field_name, self.annotated_type(assignment, "type"), self.generate(value)?),
+                            (_, None) => format!("{}: {}", field_name, self.annotated_type(assignment, "type")),
+                        };
+                        fields.push(indent(&format!("{};", declaration)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // JavaScript declares fields by assigning them; TypeScript needs them declared
+        for name in assigned_fields(uir) {
+            fields.push(indent(&format!("{}: any;", name)));
+        }
+        
+        let mut class_body = fields.join("\n");
+        if !fields.is_empty() && !methods.is_empty() {
+            class_body.push_str("\n\n");
+        }
+        class_body.push_str(&methods.join("\n\n"));
+        
+        let export = if exported { "export " } else { "" };
+        let extends = match class_bases(uir).first() {
+            Some(base) => format!(" extends {}", base),
+            None => String::new(),
+        };
+        Ok(block(&format!("{}class {}{}", export, class_name, extends), &class_body))
+    }
+    
+    /// Access level and `static` for a class member; `internal` has no TypeScript equivalent,
+    /// and `#name` members are private by their name
+    fn member_modifiers(&self, uir: &UIRNode) -> String {
+        let mut modifiers = String::new();
+        match uir.visibility {
+            _ if has_tag(uir, &["private_name"]) => {}
+            Some(Visibility::Private | Visibility::PrivateProtected) => modifiers.push_str("private "),
+            Some(Visibility::Protected | Visibility::ProtectedInternal) => modifiers.push_str("protected "),
+            _ => {}
+        }
+        if is_static(uir) {
+            modifiers.push_str("static ");
+        }
+        modifiers
+    }
+    
+    fn generate_return_statement(&self, uir: &UIRNode) -> Result<String> {
+        if uir.children.is_empty() {
+            Ok("return".to_string())
+        } else {
+            let expr_code = self.generate(&uir.children[0])?;
+            Ok(format!("return {}", expr_code.trim()))
+        }
+    }
+    
+    fn generate_operator_expression(&self, uir: &UIRNode) -> Result<String> {
+        match uir.children.as_slice() {
+            [operand] => {
+                let operand_code = grouped(self.generate(operand)?.trim().to_string(), uir, operand, true);
+                match expression_operator(uir) {
+                    Some(Operator::Not) => Ok(format!("!{}", operand_code)),
+                    Some(Operator::Negate) => Ok(format!("-{}", operand_code)),
+                    Some(operator @ (Operator::TypeOf | Operator::Delete | Operator::Void)) => Ok(format!("{} {}", operator.symbol(), operand_code)),
+                    _ => Ok(operand_code),
+                }
+            }
+            [left, right] => {
+                let left_code = grouped(self.generate(left)?.trim().to_string(), uir, left, false);
+                let right_code = grouped(self.generate(right)?.trim().to_string(), uir, right, true);
+                let operator = match uir.operator {
+                    Some(Operator::StrictEqual) => "===",
+                    Some(Operator::StrictNotEqual) => "!==",
+                    // Python's `//` rounds down where `/` does not
+                    Some(Operator::IntegerDivide) => return Ok(format!("Math.floor({} / {})", left_code, right_code)),
+                    _ => match binary_operator(uir, left) {
+                        Some(operator) => operator,
+                        None => return Ok(verbatim(uir, original_text(uir), Language::TypeScript)),
+                    },
+                };
+                Ok(format!("{} {} {}", left_code, operator, right_code))
+            }
+            _ => Ok("unknown_expression".to_string()),
+        }
+    }
+    
+    /// Functions whose own body yields, not counting nested functions
+    fn is_generator(&self, uir: &UIRNode) -> bool {
+        uir.children.iter().any(|child| match child.node_type {
+            NodeType::Expression(ExpressionType::Yield) => true,
+            NodeType::Function | NodeType::Closure { .. } => false,
+            _ => self.is_generator(child),
+        })
+    }
+    
+    fn has_value_return(&self, uir: &UIRNode) -> bool {
+        match uir.node_type {
+            NodeType::Statement(StatementType::Return) => !uir.children.is_empty(),
+            NodeType::Function | NodeType::Closure { .. } => false,
+            _ => uir.children.iter().any(|c| self.has_value_return(c)),
+        }
+    }
+    
+    /// TypeScript type for a source type annotation, `any` when the source left it untyped
+    fn annotated_type(&self, uir: &UIRNode, key: &str) -> String {
+        declared_type(uir, key)
+            .map(|source_type| self.map_type(source_type))
+            .unwrap_or_else(|| "any".to_string())
+    }
+    
+    /// Map a source-language type to TypeScript, recursing into generic arguments
+    fn map_type(&self, source_type: &str) -> String {
+        let source_type = source_type.trim()
+            .trim_start_matches('&')
+            .trim_start_matches("mut ")
+            .trim_start_matches("const ")
+            .trim();
+        
+        if matches!(source_type, "char*" | "char *" | "const char*" | "wchar_t*") {
+            return "string".to_string();
+        }
+        // ML-style postfix generics: `int list`, `string option`
+        if let Some((inner, wrapper)) = source_type.rsplit_once(' ') {
+            match wrapper {
+                "list" | "array" | "seq" | "[]" => return self.array_of(inner),
+                "option" | "voption" => return format!("{} | null", self.map_type(inner)),
+                "set" => return format!("Set<{}>", self.map_type(inner)),
+                "Task" | "Async" => return format!("Promise<{}>", self.map_type(inner)),
+                _ => {}
+            }
+        }
+        if let Some(inner) = source_type.strip_suffix('?') {
+            return format!("{} | null", self.map_type(inner));
+        }
+        if let Some(inner) = source_type.strip_suffix("[]") {
+            return self.array_of(inner);
+        }
+        if let Some(inner) = source_type.strip_prefix("[]") {
+            // Go slice
+            return self.array_of(inner);
+        }
+        if let Some(rest) = source_type.strip_prefix("map[") {
+            // Go map[K]V
+            if let Some((key, value)) = rest.split_once(']') {
+                return format!("Record<{}, {}>", self.map_type(key), self.map_type(value));
+            }
+        }
+        if source_type.starts_with('(') && source_type.ends_with(')') && source_type.len() > 2 {
+            let elements: Vec<String> = split_type_arguments(&source_type[1..source_type.len() - 1])
+                .iter().map(|e| self.map_type(e)).collect();
+            return format!("[{}]", elements.join(", "));
+        }
+        if source_type.starts_with('[') && source_type.ends_with(']') {
+            // Rust slice or array: [T] / [T; N]
+            let inner = source_type[1..source_type.len() - 1].split(';').next().unwrap_or("");
+            return self.array_of(inner);
+        }
+        
+        let (base, arguments) = match source_type.find(['<', '[']) {
+            Some(open) if source_type.ends_with(['>', ']']) => {
+                (&source_type[..open], split_type_arguments(&source_type[open + 1..source_type.len() - 1]))
+            }
+            _ => (source_type, Vec::new()),
+        };
+        let base = base.rsplit(['.', ':']).next().unwrap_or(base);
+        let mapped: Vec<String> = arguments.iter().map(|a| self.map_type(a)).collect();
+        let argument = |i: usize| mapped.get(i).cloned().unwrap_or_else(|| "any".to_string());
+        
+        match base {
+            "int" | "long" | "short" | "float" | "double" | "decimal" | "byte" | "sbyte" | "uint" | "ulong" | "ushort"
+            | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+            | "f32" | "f64" | "int8" | "int16" | "int32" | "int64" | "uint8" | "uint16" | "uint32" | "uint64"
+            | "float32" | "float64" | "size_t" | "Int" | "Long" | "Short" | "Float" | "Double" | "Integer"
+            | "Decimal" | "Byte" | "number" | "Number" | "unsigned" | "signed" => "number".to_string(),
+            "bigint" | "BigInteger" | "BigInt" => "bigint".to_string(),
+            "string" | "String" | "str" | "char" | "Char" | "rune" | "StringBuilder" => "string".to_string(),
+            "bool" | "boolean" | "Boolean" => "boolean".to_string(),
+            "void" | "None" | "()" | "Unit" | "Void" => "void".to_string(),
+            "object" | "Object" | "Any" | "any" | "dynamic" | "interface{}" => "any".to_string(),
+            "Vec" | "List" | "IList" | "IEnumerable" | "ICollection" | "ArrayList" | "LinkedList" | "VecDeque"
+            | "list" | "Sequence" | "Iterable" | "Array" | "MutableList" | "Seq" => self.array_of_mapped(argument(0)),
+            "Set" | "HashSet" | "BTreeSet" | "ISet" | "set" | "frozenset" | "MutableSet" => format!("Set<{}>", argument(0)),
+            "Map" | "HashMap" | "BTreeMap" | "Dictionary" | "IDictionary" | "dict" | "Dict" | "MutableMap" | "Mapping" => {
+                format!("Record<{}, {}>", argument(0), argument(1))
+            }
+            "Option" | "Optional" | "Nullable" => format!("{} | null", argument(0)),
+            "Task" | "ValueTask" | "Future" | "Promise" | "Awaitable" | "Coroutine" | "Deferred" => {
+                // Python's Coroutine[Y, S, R] resolves to its last argument
+                let result = if base == "Coroutine" { mapped.last().cloned() } else { mapped.first().cloned() };
+                format!("Promise<{}>", result.unwrap_or_else(|| "void".to_string()))
+            }
+            "Box" | "Rc" | "Arc" | "RefCell" | "Cell" | "Mutex" | "Cow" => argument(0),
+            "Result" => argument(0),
+            "tuple" | "Tuple" => format!("[{}]", mapped.join(", ")),
+            _ if mapped.is_empty() => base.to_string(),
+            _ => format!("{}<{}>", base, mapped.join(", ")),
+        }
+    }
+    
+    fn array_of(&self, element: &str) -> String {
+        self.array_of_mapped(self.map_type(element))
+    }
+    
+    fn array_of_mapped(&self, element: String) -> String {
+        if element.contains(' ') {
+            format!("({})[]", element)
+        } else {
+            format!("{}[]", element)
+        }
+    }
+}
+
+/// The declaration an `export` statement wraps, such as the function of `export function f`;
+/// `export default` stays as written
+fn exported_declaration(uir: &UIRNode) -> Option<&UIRNode> {
+    if !has_tag(uir, &["export_statement"]) || uir.children.iter().any(|c| has_tag(c, &["default"])) {
+        return None;
+    }
+    uir.children.iter().find(|c| {
+        matches!(c.node_type, NodeType::Function | NodeType::Class | NodeType::Interface | NodeType::Enum { .. })
+            || declared_variables(c).is_some()
+    })
+}
+
+/// The statements of a handler's block, without its binding or exception types
+fn handler_statements(handler: &UIRNode) -> Vec<&UIRNode> {
+    handler.children.iter()
+        .filter(|c| matches!(c.node_type, NodeType::Statement(_) | NodeType::ControlFlow(_) | NodeType::Comment { .. }))
+        .collect()
+}
+
+/// The exception types a handler catches; none when it catches everything
+fn exception_types(handler: &UIRNode) -> &[String] {
+    match &handler.node_type {
+        NodeType::ControlFlow(ControlFlowType::Catch { exception_types, .. }) => exception_types,
+        _ => &[],
+    }
+}
+
+/// Python's class-level field declarations in a statement, as `count: int = 0`: each name with
+/// the assignment, which holds its type, and the value
+fn class_assignments(statement: &UIRNode) -> Vec<(String, &UIRNode, Option<&UIRNode>)> {
+    statement.children.iter()
+        .filter(|c| c.node_type == NodeType::Expression(ExpressionType::Assignment))
+        .filter_map(|assignment| {
+            let name = assignment.children.first()?.name.clone()?;
+            Some((identifier(&name), assignment, assignment.children.get(1)))
+        })
+        .collect()
+}
+
+/// Fields a class's methods assign through `this`, or Python's `self`, without declaring them,
+/// in order
+fn assigned_fields(class: &UIRNode) -> Vec<String> {
+    let class_level: Vec<String> = class.children.iter()
+        .filter(|c| c.node_type == NodeType::Statement(StatementType::Expression))
+        .flat_map(|c| class_assignments(c).into_iter().map(|(name, ..)| name))
+        .collect();
+    let declared: Vec<&str> = class.children.iter().filter_map(|c| c.name.as_deref()).chain(class_level.iter().map(String::as_str)).collect();
+    let mut fields: Vec<String> = Vec::new();
+    for method in class.children.iter().filter(|c| c.node_type == NodeType::Function) {
+        walk(method, &mut |node: &UIRNode| {
+            if node.node_type != NodeType::Expression(ExpressionType::Assignment) {
+                return Walk::Continue;
+            }
+            let target = node.children.first().map(original_text).unwrap_or_default();
+            if let Some(name) = target.strip_prefix("this.").or_else(|| target.strip_prefix("self.")) {
+                let plain = name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+                if plain && !declared.contains(&name) && !fields.iter().any(|f| f == name) {
+                    fields.push(name.to_string());
+                }
+            }
+            Walk::Continue
+        });
+    }
+    fields
+}
+
+/// `code` indented one level, two spaces unless configured, leaving blank lines empty
+fn indent(code: &str) -> String {
+    let unit = indent_unit(2);
+    code.lines()
+        .map(|line| if line.trim().is_empty() { line.to_string() } else { format!("{}{}", unit, line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{generate, CONSTRUCTS};
+    use coalesce_core::Parser;
+    use coalesce_parser::PythonParser;
+    
+    #[test]
+    fn test_typescript_types_python_classes_and_signatures() {
+        let source = "class Point:\n    x: int\n    label: str\n\n\
+            class Counter:\n    count: int = 0\n\n    def __init__(self, start):\n        self.count = start\n        self.step = 1\n\n\
+            def scale(p: Point, k: float) -> float:\n    return p.x * k\n";
+        let uir = PythonParser::new().unwrap().parse(source).unwrap();
+        let typescript = TypeScriptGenerator.generate(&uir).unwrap();
+        // A class of fields only is the shape of an object
+        assert!(typescript.contains("export interface Point {\n  x: number;\n  label: string;\n}"), "{}", typescript);
+        assert!(typescript.contains("export class Counter {\n  count: number = 0;\n  step: any;\n\n  constructor(start: any) {\n    this.count = start;\n    this.step = 1;\n  }\n}"), "{}", typescript);
+        assert!(typescript.contains("export function scale(p: Point, k: number): number {\n  return p.x * k;\n}"), "{}", typescript);
+    }
+    
+    #[test]
+    fn test_typescript_declarations_and_assignments() {
+        let source = "const LIMIT = 10;\nfunction f(a) { let t; t = LIMIT; t += a; const g = (x) => x * 2; return g(t); }";
+        let typescript = generate(&TypeScriptGenerator, source);
+        assert!(typescript.contains("const LIMIT = 10;\n"), "{}", typescript);
+        assert!(typescript.contains("  let t;\n  t = LIMIT;\n  t += a;\n  const g = (x: any) => x * 2;\n"), "{}", typescript);
+        assert!(!typescript.contains(FALLBACK_TODO));
+    }
+    
+    #[test]
+    fn test_typescript_keeps_classes_parameters_and_control_flow() {
+        let source = "class Base { constructor(n) { this.n = n; } }\n\
+            class Point extends Base {\n\
+            \x20 #secret = 1;\n\
+            \x20 static count = 0;\n\
+            \x20 constructor(x, y) { super(x); this.x = x; this.y = y; }\n\
+            \x20 get v() { return this.x; }\n\
+            \x20 set v(value) { this.x = value; }\n\
+            }\n\
+            function opts({limit = 3, name}, [first, second], ...rest) {\n\
+            \x20 class Local { }\n\
+            \x20 const add = (x) => ({...x, y: 1});\n\
+            \x20 const z = name?.a?.[first] ?? (add(second), limit);\n\
+            \x20 for (let i = 0; i < limit; i++) { if (i > 1) { break; } else { continue; } }\n\
+            \x20 do { limit--; } while (limit > 5);\n\
+            \x20 try { add(z); } catch (err) { console.log(err); } finally { limit = 0; }\n\
+            \x20 return rest.length + first;\n\
+            }\n\
+            function* gen() { yield 1; }\n\
+            export default gen;\n";
+        let typescript = generate(&TypeScriptGenerator, source);
+        
+        assert!(typescript.contains("export class Point extends Base {\n  #secret = 1;\n  static count = 0;\n  x: any;\n  y: any;\n"), "{}", typescript);
+        assert!(typescript.contains("  constructor(x: any, y: any) {\n"), "{}", typescript);
+        assert!(typescript.contains("  get v(): any {\n") && typescript.contains("  set v(value: any) {\n"), "{}", typescript);
+        assert!(typescript.contains("export function opts({limit = 3, name}: any, [first, second]: any, ...rest: any[]): any {"), "{}", typescript);
+        assert!(typescript.contains("  class Local {\n") && !typescript.contains("  export class"), "{}", typescript);
+        assert!(typescript.contains("const add = (x: any) => ({...x, y: 1});"), "{}", typescript);
+        assert!(typescript.contains("const z = name?.a?.[first] ?? (add(second), limit);"), "{}", typescript);
+        assert!(typescript.contains("  for (let i = 0; i < limit; i += 1) {\n    if (i > 1) {\n      break;\n    } else {\n      continue;\n    }\n  }\n"), "{}", typescript);
+        assert!(typescript.contains("  do {\n    limit -= 1;\n  } while (limit > 5);\n"), "{}", typescript);
+        assert!(typescript.contains("  try {\n    add(z);\n  } catch (err) {\n    console.log(err);\n  } finally {\n    limit = 0;\n  }\n"), "{}", typescript);
+        assert!(typescript.ends_with("export default gen;\n"), "{}", typescript);
+        assert!(!typescript.contains(";;") && !typescript.contains("TODO") && !typescript.contains("): void {\n    super"), "{}", typescript);
+        
+        // The compiler is optional; without it the structure above is what is checked
+        let Ok(version) = std::process::Command::new("tsc").arg("--version").output() else {
+            return;
+        };
+        assert!(version.status.success());
+        let path = std::env::temp_dir().join(format!("coalesce_typescript_{}.ts", std::process::id()));
+        std::fs::write(&path, &typescript).unwrap();
+        let checked = std::process::Command::new("tsc").args(["--noEmit", "--target", "es2022"]).arg(&path).output().unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(checked.status.success(), "{}\n{}", typescript, String::from_utf8_lossy(&checked.stdout));
+    }
+    
+    #[test]
+    fn test_typescript_generates_classes_loops_lambdas_and_try_finally() {
+        let typescript = generate(&TypeScriptGenerator, CONSTRUCTS);
+        assert!(typescript.contains("export class Counter {\n  count: any;\n\n  constructor(start: any) {\n    this.count = start;\n  }\n"), "{}", typescript);
+        assert!(typescript.contains("  add(step: any = 1): any {\n    this.count += step;\n    return this.count;\n  }\n"), "{}", typescript);
+        assert!(typescript.contains("export function run(items: any, limit: any): any {\n  const double = (x: any) => x * 2;\n"), "{}", typescript);
+        assert!(typescript.contains("  for (let i = 0; i < limit; i += 1) {\n    total += double(i);\n  }\n"), "{}", typescript);
+        assert!(typescript.contains("  for (const item of items) {\n    total += item;\n  }\n  while (total > 100) {\n    total -= 10;\n  }\n"), "{}", typescript);
+        assert!(typescript.contains("  try {\n    total = check(total);\n  } finally {\n    log(total);\n  }\n  return total;\n"), "{}", typescript);
+    }
+}
@@ -0,0 +1,95 @@
+// Translation coverage: how much of a project's library usage translates without manual work
+
+use crate::LibraryDependency;
+use crate::registry::SuggestionType;
+use coalesce_core::SourceLocation;
+use serde::Serialize;
+
+/// How one library usage translates to a target
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageCoverage {
+    pub library: String,
+    pub pattern: String,
+    pub method_name: String,
+    pub source_location: SourceLocation,
+    pub snippet: String,
+    pub translation: SuggestionType,
+    pub confidence: f32,
+    /// The target library and pattern as `library:pattern`, unless it needs writing by hand
+    pub target: Option<String>,
+}
+
+/// The translations of a module's or project's library usages, with how many need manual work
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CoverageReport {
+    pub automatic: usize,
+    pub manual: usize,
+    pub usages: Vec<UsageCoverage>,
+}
+
+impl CoverageReport {
+    pub fn push(&mut self, usage: UsageCoverage) {
+        if usage.translation == SuggestionType::ManualImplementation {
+            self.manual += 1;
+        } else {
+            self.automatic += 1;
+        }
+        self.usages.push(usage);
+    }
+    
+    /// Add another module's usages, for a project's report
+    pub fn merge(&mut self, other: CoverageReport) {
+        for usage in other.usages {
+            self.push(usage);
+        }
+    }
+    
+    /// The report with the usages `applied` rejects as left for manual work: their rules didn't
+    /// replace them in the output, whatever they would translate to
+    pub fn only_applied(self, applied: impl Fn(&UsageCoverage) -> bool) -> CoverageReport {
+        let mut report = CoverageReport::default();
+        for mut usage in self.usages {
+            if !applied(&usage) {
+                usage.translation = SuggestionType::ManualImplementation;
+                usage.confidence = usage.translation.confidence();
+                usage.target = None;
+            }
+            report.push(usage);
+        }
+        report
+    }
+    
+    /// The dependencies with only their usages the report has translated, those the output
+    /// calls the target's libraries for, and without the dependencies left with none
+    pub fn translated_dependencies(&self, dependencies: &[LibraryDependency]) -> Vec<LibraryDependency> {
+        let translated = |library: &str, usage: &crate::LibraryUsage| self.usages.iter().any(|covered| {
+            covered.translation != SuggestionType::ManualImplementation
+                && covered.library == library
+                && covered.pattern == usage.pattern_name
+                && covered.source_location == usage.source_location
+        });
+        dependencies.iter()
+            .map(|dependency| LibraryDependency {
+                usage_patterns: dependency.usage_patterns.iter().filter(|usage| translated(&dependency.name, usage)).cloned().collect(),
+                ..dependency.clone()
+            })
+            .filter(|dependency| !dependency.usage_patterns.is_empty())
+            .collect()
+    }
+    
+    /// The share of usages translated automatically, in percent; none without usages
+    pub fn percent(&self) -> Option<f32> {
+        match self.usages.len() {
+            0 => None,
+            total => Some(self.automatic as f32 * 100.0 / total as f32),
+        }
+    }
+    
+    /// The mean confidence of the translations, from 0 to 1; none without usages
+    pub fn confidence(&self) -> Option<f32> {
+        match self.usages.len() {
+            0 => None,
+            total => Some(self.usages.iter().map(|usage| usage.confidence).sum::<f32>() / total as f32),
+        }
+    }
+}
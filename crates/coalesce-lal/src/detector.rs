@@ -1,9 +1,14 @@
-use crate::{LibraryDependency, LibraryUsage};
-use coalesce_core::{Language, Result, CoalesceError};
-use regex::Regex;
-use std::collections::HashMap;
+use crate::{ConflictReason, Detection, LibraryDependency, LibraryUsage, PatternConflict};
+use crate::patterns::LibraryPattern;
+use coalesce_core::{CoalesceError, ExpressionType, Language, NodeType, Result, SourceLocation, UIRNode};
+use regex::{Captures, Regex};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
-/// Detects library dependencies and usage patterns in source code
+/// Detects library dependencies and usage patterns by walking the UIR of a module: imports bind
+/// names to library modules, and the calls, base classes and statements that resolve through
+/// those bindings are usages, however they are aliased or laid out across lines
 pub struct DependencyDetector {
     patterns: HashMap<Language, Vec<DetectionPattern>>,
 }
@@ -11,17 +16,240 @@ pub struct DependencyDetector {
 #[derive(Debug, Clone)]
 struct DetectionPattern {
     library_name: String,
-    import_regex: Regex,
-    usage_patterns: Vec<UsagePattern>,
+    /// Import paths that bring the library in; none for a library built into the language
+    modules: Vec<String>,
+    /// Names a script tag, test runner or the language defines globally, with the path each is
+    /// bound to wherever the module doesn't import it: jQuery's `$`, jest's `expect`, Python's `open`
+    globals: Vec<(String, String)>,
     ecosystem: String,
+    usage_patterns: Vec<UsagePattern>,
 }
 
 #[derive(Debug, Clone)]
 struct UsagePattern {
     name: String,
-    regex: Regex,
+    site: Site,
+    /// A regex the site's text must match, whose named groups can be parameters
+    text: Option<Regex>,
     semantic_intent: String,
-    extract_params: Vec<String>,
+    parameters: Vec<(String, Parameter)>,
+}
+
+/// Where a usage appears
+#[derive(Debug, Clone, PartialEq)]
+enum Site {
+    /// A call to what one of the library's modules exports under this name
+    Call(String),
+    /// A class deriving from what the library exports under this name
+    Base(String),
+    /// A statement the parser tags, for libraries built into the language
+    Tagged(String),
+    /// A declaration decorated with a method of an instance of what the library exports under
+    /// the first name: `@app.route(...)` with `app = Flask(__name__)`
+    Decorator(String, String),
+    /// A call of a method under the second name on a chain that starts with a call of what
+    /// the library exports under the first, directly or through a variable: jQuery's
+    /// `$("#save").on("click", save)` and `$items.click(select)`
+    Chained(String, String),
+    /// A declaration carrying an annotation or attribute the library exports under this name:
+    /// Spring's `@RestController`
+    Annotated(String),
+    /// A property, field or parameter declared with a type the library exports under this
+    /// name, generic or not: Entity Framework's `DbSet<Product> Products`
+    Typed(String),
+    /// A call of a method under this name on any receiver, for the extension methods a
+    /// library's namespace adds: `services.AddScoped<IService, Service>()`
+    Method(String),
+    /// A parameter defaulting to a call of what the library exports under this name: FastAPI's
+    /// `db: Session = Depends(get_db)`
+    Default(String),
+    /// A function whose name starts with this prefix, as test runners find tests: pytest's
+    /// `test_` and Go's `Test`
+    Prefixed(String),
+    /// A read of a property of what the library exports under this name: C#'s `DateTime.Now`
+    Member(String),
+}
+
+/// Where a usage parameter comes from
+#[derive(Debug, Clone)]
+enum Parameter {
+    /// The argument at a position, as written
+    Argument(usize),
+    /// The name of the first named argument: `total` of R's `mutate(total = price * n())`
+    FirstName,
+    /// The value of the first named argument, as written
+    FirstNamed,
+    /// A keyword argument, or a property of an object literal argument, as written: `url` of
+    /// jQuery's `$.ajax({ url: "/api/items" })`
+    Keyword(String),
+    /// The last argument, as written: the callback most JavaScript APIs take last
+    Last,
+    /// The argument at a position among those passed without a name, as written: the message
+    /// of tracing's `info!(user_id = id, "created")`
+    Positional(usize),
+    /// Every argument but the first few passed without a name, as written: the fields and
+    /// format arguments of a log statement
+    Rest(usize),
+    /// The level a logging call names, in a style: `warn` of `console.warn`, `logger.warning`
+    /// and log4net's `WarnFormat`
+    LogLevel(LevelStyle),
+    /// The first of two parameters with a value: requests' body, passed as `json=` or `data=`
+    Either(Box<Parameter>, Box<Parameter>),
+    /// A value of the pattern's own, for what a call leaves to its defaults: the `0` numpy's
+    /// `arange(10)` starts from
+    Literal(String),
+    /// The receiver of a method call, as written
+    Receiver,
+    /// An argument of the call a method chain starts with, as written: `"#save"` of
+    /// `$("#save").hide().on(...)`
+    RootArgument(usize),
+    /// The variable a method chain starts with, as written: `program` of
+    /// `program.option(...).option(...)`
+    Root,
+    /// Every argument, as written
+    Arguments,
+    /// The variable the result is assigned to
+    Target,
+    /// The variable destructuring binds the element at a position of the result to
+    Destructured(usize),
+    /// The class, function or parameter declared
+    Declared,
+    /// An annotation the parser records on the site
+    Annotation(String),
+    /// A named group of the pattern's text regex
+    Matched(String),
+    /// Another parameter without the quotes of a string literal
+    Unquoted(Box<Parameter>),
+    /// Another parameter without what qualifies it: `Products` of `_db.Products`
+    Unqualified(Box<Parameter>),
+    /// A route path of another parameter, unquoted, with its placeholders in a style:
+    /// `/users/{id}` of Flask's `/users/<int:id>`
+    RoutePath(Box<Parameter>, PathStyle),
+    /// A type argument of the generic the site calls or declares: `Product` of `DbSet<Product>`
+    TypeArgument(usize),
+    /// The method called, without its receiver and type arguments: `AddScoped`
+    Called,
+    /// A date format of another parameter, unquoted, translated from the first style into the
+    /// second: `%d/%m/%Y` of moment's `DD/MM/YYYY`
+    DateFormat(Box<Parameter>, DateStyle, DateStyle),
+    /// The unit of time another parameter names, unquoted, as a plural: `days` of moment's
+    /// `'d'`, Java's `plusDays` and C#'s `AddDays`
+    TimeUnit(Box<Parameter>),
+    /// Another parameter, unquoted, with one separator swapped for another: `Db:Host` of .NET's
+    /// `Db.Host`
+    Replaced(Box<Parameter>, char, char),
+    /// The words of another parameter, unquoted and without a leading `test`, in a case:
+    /// `adds numbers` of `test_adds_numbers` and `TestAddsNumbers`
+    Words(Box<Parameter>, NameCase),
+    /// The HTTP methods a route or request uses, upper-cased: the verb `@app.get(...)`,
+    /// `@GetMapping`, `[HttpGet]`, `MapGet` or `$.post` names, else every one of its `methods`,
+    /// `method` or jQuery's `type`, comma-separated, else `GET`
+    HttpMethod,
+}
+
+/// How a route path writes its placeholders
+#[derive(Debug, Clone, Copy)]
+enum PathStyle {
+    /// `{id}`, as ASP.NET, Axum and FastAPI do
+    Braces,
+    /// `:id`, as Gin and Express do
+    Colons,
+    /// `<id>`, as Django and Flask do
+    Angles,
+}
+
+/// How a log level is written
+#[derive(Debug, Clone, Copy)]
+enum LevelStyle {
+    /// `warn`, as console, winston, log4j and tracing write it
+    Short,
+    /// `Warn`, as log4net and zap write it
+    Title,
+    /// `warning`, as Python's logging writes it
+    Long,
+}
+
+/// How a date format writes its fields
+#[derive(Debug, Clone, Copy)]
+enum DateStyle {
+    /// `YYYY-MM-DD`, as moment and dayjs write it
+    Moment,
+    /// `%Y-%m-%d`, as Python's strftime writes it
+    Python,
+    /// `%Y-%m-%d`, as chrono writes it, with `%3f` for milliseconds
+    Chrono,
+    /// `yyyy-MM-dd`, as java.time's DateTimeFormatter writes it
+    Java,
+    /// `yyyy-MM-dd`, as .NET's custom format strings write it
+    DotNet,
+}
+
+/// Date fields as each style writes them, in the order of `DateStyle`; empty where the
+/// style can't write the field, as Python's portable strftime can't drop padding
+const DATE_FIELDS: &[[&str; 5]] = &[
+    ["YYYY", "%Y", "%Y", "yyyy", "yyyy"],
+    ["YY", "%y", "%y", "yy", "yy"],
+    ["MMMM", "%B", "%B", "MMMM", "MMMM"],
+    ["MMM", "%b", "%b", "MMM", "MMM"],
+    ["MM", "%m", "%m", "MM", "MM"],
+    ["M", "", "%-m", "M", "M"],
+    ["dddd", "%A", "%A", "EEEE", "dddd"],
+    ["ddd", "%a", "%a", "EEE", "ddd"],
+    ["DD", "%d", "%d", "dd", "dd"],
+    ["D", "", "%-d", "d", "d"],
+    ["HH", "%H", "%H", "HH", "HH"],
+    ["H", "", "%-H", "H", "H"],
+    ["hh", "%I", "%I", "hh", "hh"],
+    ["h", "", "%-I", "h", "h"],
+    ["mm", "%M", "%M", "mm", "mm"],
+    ["m", "", "%-M", "m", "m"],
+    ["ss", "%S", "%S", "ss", "ss"],
+    ["s", "", "%-S", "s", "s"],
+    ["SSS", "%f", "%3f", "SSS", "fff"],
+    ["A", "%p", "%p", "a", "tt"],
+    ["Z", "%z", "%:z", "XXX", "zzz"],
+];
+
+/// How a name made of words is written
+#[derive(Debug, Clone, Copy)]
+enum NameCase {
+    /// `adds numbers`, as jest names tests
+    Sentence,
+    /// `adds_numbers`, as pytest and Rust name test functions
+    Snake,
+    /// `AddsNumbers`, as Go and xUnit name test functions
+    Pascal,
+}
+
+/// What a module's imports bring into scope
+#[derive(Debug, Default, Clone)]
+struct Imports {
+    /// Paths imported or re-exported, in order
+    paths: Vec<String>,
+    /// Local names bound to a module or to an item of one, as a dotted path: `useS` to `react.useState`
+    bindings: HashMap<String, String>,
+    /// Modules whose every export is in scope: C headers, R packages and Python's `import *`
+    wildcards: Vec<String>,
+    /// Local names assigned the result of a call, by the callee as written: `app` to `Flask`,
+    /// `$items` to `$`
+    instances: HashMap<String, String>,
+}
+
+/// A call, base class or statement a usage pattern matched
+struct Found<'a> {
+    node: &'a UIRNode,
+    /// The callee or base class as written
+    written: String,
+    /// Arguments, with the name of keyword and named arguments
+    arguments: Vec<(Option<String>, String)>,
+    target: Option<String>,
+    destructured: Vec<(usize, String)>,
+}
+
+impl Default for DependencyDetector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl DependencyDetector {
@@ -33,130 +261,1479 @@ impl DependencyDetector {
         detector
     }
     
-    pub fn detect_dependencies(&self, code: &str, language: Language) -> Result<Vec<LibraryDependency>> {
-        let patterns = self.patterns.get(&language)
-            .ok_or_else(|| CoalesceError::UnsupportedLanguage(language))?;
+    /// Detect a pattern by its `detection`, as a usage of its library next to those already
+    /// known; it replaces the detection of a pattern of the same library and name
+    pub fn register_pattern(&mut self, pattern: &LibraryPattern) -> Result<()> {
+        let Some(detection) = &pattern.detection else {
+            return Ok(());
+        };
+        let languages = ecosystem_languages(&pattern.ecosystem);
+        if languages.is_empty() {
+            return Err(CoalesceError::TransformationError(format!(
+                "pattern `{}` of `{}`: no detection for `{}` code",
+                pattern.name, pattern.library, pattern.ecosystem,
+            )));
+        }
         
-        let mut dependencies = Vec::new();
+        let site = match &detection.method {
+            Some(method) => Site::Chained(detection.call.clone(), method.clone()),
+            None => Site::Call(detection.call.clone()),
+        };
+        let text = match &detection.text {
+            Some(text) => Some(Regex::new(text).map_err(|e| CoalesceError::TransformationError(format!(
+                "pattern `{}` of `{}`: `detection.text` isn't a regex: {}",
+                pattern.name, pattern.library, e,
+            )))?),
+            None => None,
+        };
+        let groups: Vec<&str> = text.iter().flat_map(|text| text.capture_names().flatten()).collect();
+        let mut position = 0;
+        let parameters = pattern.parameters.iter().map(|parameter| {
+            let source = if groups.contains(&parameter.name.as_str()) {
+                Parameter::Matched(parameter.name.clone())
+            } else if parameter.name == "target" {
+                Parameter::Target
+            } else {
+                position += 1;
+                Parameter::Either(Box::new(Parameter::Keyword(parameter.name.clone())), Box::new(Parameter::Positional(position - 1)))
+            };
+            (parameter.name.as_str(), source)
+        }).collect();
+        let usage_pattern = UsagePattern { text: text.clone(), ..usage(&pattern.name, site, &pattern.semantics.intent, parameters) };
         
+        for language in languages {
+            let libraries = self.patterns.entry(language).or_default();
+            let index = match libraries.iter().position(|known| known.library_name == pattern.library) {
+                Some(index) => index,
+                None => {
+                    libraries.push(DetectionPattern {
+                        library_name: pattern.library.clone(),
+                        modules: Vec::new(),
+                        globals: Vec::new(),
+                        ecosystem: pattern.ecosystem.clone(),
+                        usage_patterns: Vec::new(),
+                    });
+                    libraries.len() - 1
+                }
+            };
+            let library = &mut libraries[index];
+            for module in &detection.modules {
+                if !library.modules.contains(module) {
+                    library.modules.push(module.clone());
+                }
+            }
+            library.usage_patterns.retain(|known| known.name != pattern.name);
+            library.usage_patterns.push(usage_pattern.clone());
+        }
+        Ok(())
+    }
+    
+    /// The libraries `module` uses, with byte ranges into `source`, the text it was parsed from
+    pub fn detect_dependencies(&self, module: &UIRNode, source: &str) -> Result<Vec<LibraryDependency>> {
+        Ok(self.detect(module, source, |_, _| 0)?.dependencies)
+    }
+    
+    /// The libraries `module` uses, keeping one of the usages found on the same code for the
+    /// same intent: the one whose pattern `priority` ranks highest, then the most specific, then
+    /// the first found
+    pub fn detect(&self, module: &UIRNode, source: &str, priority: impl Fn(&str, &str) -> i32) -> Result<Detection> {
+        let language = &module.metadata.source_language;
+        // No library of a language without patterns is known, e.g. COBOL's
+        let Some(patterns) = self.patterns.get(language) else {
+            return Ok(Detection::default());
+        };
+        
+        let mut imports = Imports::default();
+        imports.collect(module, language);
+        let line_starts: Vec<usize> = std::iter::once(0).chain(source.match_indices('\n').map(|(index, _)| index + 1)).collect();
+        
+        let mut detected = Vec::new();
         for pattern in patterns {
-            if let Some(dep) = self.detect_library_usage(code, pattern)? {
-                dependencies.push(dep);
+            if let Some(found) = self.detect_library_usage(module, source, &imports, &line_starts, pattern) {
+                detected.push(found);
             }
         }
+        let conflicts = settle_conflicts(&mut detected, priority);
         
-        Ok(dependencies)
+        Ok(Detection {
+            dependencies: detected.into_iter()
+                .map(|(dependency, _)| dependency)
+                .filter(|dependency| !dependency.usage_patterns.is_empty())
+                .collect(),
+            conflicts,
+        })
     }
     
-    fn detect_library_usage(&self, code: &str, pattern: &DetectionPattern) -> Result<Option<LibraryDependency>> {
+    /// A library's usages in `module`, with the specificity of the pattern each matched
+    fn detect_library_usage(&self, module: &UIRNode, source: &str, imports: &Imports, line_starts: &[usize], pattern: &DetectionPattern) -> Option<(LibraryDependency, Vec<Specificity>)> {
         // Check if the library is imported
-        if !pattern.import_regex.is_match(code) {
-            return Ok(None);
+        let import_path = imports.paths.iter().find(|path| pattern.provides_path(path)).cloned();
+        if import_path.is_none() && !pattern.modules.is_empty() && pattern.globals.is_empty() {
+            return None;
+        }
+        let mut imports = Cow::Borrowed(imports);
+        for (global, path) in &pattern.globals {
+            imports.to_mut().bindings.entry(global.clone()).or_insert_with(|| path.clone());
         }
         
         let mut usage_patterns = Vec::new();
-        
-        // Look for usage patterns
-        for usage in &pattern.usage_patterns {
-            for capture in usage.regex.captures_iter(code) {
-                let mut parameters = HashMap::new();
+        let mut specificities = Vec::new();
+        visit(module, None, false, &mut |node, parent, awaited| {
+            // Patterns on the same site are alternatives: the first whose text matches wins
+            let mut matched: Vec<&Site> = Vec::new();
+            for usage in &pattern.usage_patterns {
+                if matched.contains(&&usage.site) {
+                    continue;
+                }
+                let Some(found) = usage.site.find(node, parent, &imports, pattern) else {
+                    continue;
+                };
+                let text = original_text(node);
+                let captures = match &usage.text {
+                    Some(regex) => match regex.captures(text) {
+                        Some(captures) => Some(captures),
+                        None => continue,
+                    },
+                    None => None,
+                };
                 
-                // Extract parameters based on named groups
-                for param_name in &usage.extract_params {
-                    if let Some(value) = capture.name(param_name) {
-                        parameters.insert(param_name.clone(), value.as_str().to_string());
+                let mut parameters = HashMap::new();
+                for (name, parameter) in &usage.parameters {
+                    if let Some(value) = found.parameter(parameter, captures.as_ref()).filter(|value| !value.is_empty()) {
+                        parameters.insert(name.clone(), value);
                     }
                 }
+                if awaited {
+                    parameters.insert("awaited".to_string(), "true".to_string());
+                }
                 
                 usage_patterns.push(LibraryUsage {
                     pattern_name: usage.name.clone(),
-                    method_name: capture.get(0).unwrap().as_str().to_string(),
+                    method_name: found.written.clone(),
                     parameters,
                     semantic_intent: usage.semantic_intent.clone(),
-                    source_location: (
-                        capture.get(0).unwrap().start(),
-                        capture.get(0).unwrap().end(),
-                    ),
+                    source_location: node.source_location.clone().unwrap_or_default(),
+                    snippet: snippet(source, byte_range(node, line_starts)),
                 });
+                specificities.push(usage.specificity(pattern));
+                matched.push(&usage.site);
             }
-        }
+        });
         
         if usage_patterns.is_empty() {
-            return Ok(None);
+            return None;
         }
         
-        Ok(Some(LibraryDependency {
-            name: pattern.library_name.clone(),
-            version: None, // TODO: Extract version from imports
-            ecosystem: pattern.ecosystem.clone(),
-            import_path: None, // TODO: Extract import path
-            usage_patterns,
-        }))
+        Some((
+            LibraryDependency {
+                name: pattern.library_name.clone(),
+                version: None, // TODO: Extract version from imports
+                ecosystem: pattern.ecosystem.clone(),
+                import_path,
+                usage_patterns,
+            },
+            specificities,
+        ))
     }
     
     fn register_default_patterns(&mut self) {
         self.register_react_patterns();
+        self.register_jquery_patterns();
+        self.register_lodash_patterns();
+        self.register_http_client_patterns();
+        self.register_logging_patterns();
+        self.register_test_framework_patterns();
+        self.register_datetime_patterns();
+        self.register_file_io_patterns();
+        self.register_concurrency_patterns();
+        self.register_crypto_patterns();
+        self.register_cli_patterns();
+        self.register_config_patterns();
         self.register_django_patterns();
+        self.register_python_web_patterns();
+        self.register_spring_patterns();
+        self.register_dotnet_patterns();
         self.register_networking_patterns();
+        self.register_scientific_patterns();
+        self.register_tidyverse_patterns();
+        self.register_open_sql_patterns();
     }
     
     fn register_react_patterns(&mut self) {
         let patterns = vec![
             DetectionPattern {
                 library_name: "react".to_string(),
-                import_regex: Regex::new(r#"import.*\{[^}]*useState[^}]*\}.*from.*['"]react['"]"#).unwrap(),
+                modules: vec!["react".to_string()],
+                globals: Vec::new(),
                 ecosystem: "javascript".to_string(),
                 usage_patterns: vec![
-                    UsagePattern {
-                        name: "useState".to_string(),
-                        regex: Regex::new(r"const\s*\[\s*(?P<state>\w+)\s*,\s*(?P<setter>\w+)\s*\]\s*=\s*useState\s*\(\s*(?P<initial>[^)]*)\s*\)").unwrap(),
-                        semantic_intent: "reactive_state_management".to_string(),
-                        extract_params: vec!["state".to_string(), "setter".to_string(), "initial".to_string()],
-                    },
+                    usage("useState", Site::Call("useState".to_string()), "reactive_state_management", vec![
+                        ("state", Parameter::Destructured(0)),
+                        ("setter", Parameter::Destructured(1)),
+                        ("initial", Parameter::Argument(0)),
+                    ]),
+                    usage("useEffect", Site::Call("useEffect".to_string()), "side_effect_lifecycle", vec![
+                        ("callback", Parameter::Argument(0)),
+                        ("deps", Parameter::Argument(1)),
+                    ]),
                 ],
             },
+        ];
+        
+        self.patterns.insert(Language::JavaScript, patterns);
+    }
+    
+    fn register_jquery_patterns(&mut self) {
+        let chained = |name: &str, method: &str, intent: &str, parameters: Vec<(&str, Parameter)>| usage(name, Site::Chained("default".to_string(), method.to_string()), intent, parameters);
+        let selector = || Parameter::Unquoted(Box::new(Parameter::RootArgument(0)));
+        let request = |called: &str, url: Parameter, callback: Parameter| usage("ajax", Site::Call(called.to_string()), "http_request", vec![
+            ("url", Parameter::Unquoted(Box::new(url))),
+            ("method", Parameter::HttpMethod),
+            ("data", Parameter::Keyword("data".to_string())),
+            ("callback", callback),
+        ]);
+        
+        // `$("#id")` selects elements, `$(document)` and `$(this)` wrap ones already in hand
+        let mut usage_patterns = vec![
+            UsagePattern {
+                text: Some(Regex::new(r#"^[\w$.]+\(\s*["'`]"#).unwrap()),
+                ..usage("selector", Site::Call("default".to_string()), "dom_query", vec![
+                    ("selector", Parameter::Unquoted(Box::new(Parameter::Argument(0)))),
+                    ("target", Parameter::Target),
+                ])
+            },
+            chained("ready", "ready", "dom_ready", vec![("handler", Parameter::Argument(0))]),
+            chained("event_handler", "on", "event_listener", vec![
+                ("event", Parameter::Unquoted(Box::new(Parameter::Argument(0)))),
+                ("handler", Parameter::Last),
+                ("selector", selector()),
+                ("receiver", Parameter::Receiver),
+            ]),
+            request("ajax", Parameter::Keyword("url".to_string()), Parameter::Keyword("success".to_string())),
+        ];
+        for event in ["click", "dblclick", "submit", "change", "input", "focus", "blur", "keydown", "keyup", "mouseenter", "mouseleave"] {
+            usage_patterns.push(chained("event_handler", event, "event_listener", vec![
+                ("event", Parameter::Called),
+                ("handler", Parameter::Argument(0)),
+                ("selector", selector()),
+                ("receiver", Parameter::Receiver),
+            ]));
+        }
+        for called in ["get", "post", "getJSON"] {
+            usage_patterns.push(request(called, Parameter::Argument(0), Parameter::Last));
+        }
+        
+        let jquery = DetectionPattern {
+            library_name: "jquery".to_string(),
+            modules: vec!["jquery".to_string()],
+            globals: vec![("$".to_string(), "jquery".to_string()), ("jQuery".to_string(), "jquery".to_string())],
+            ecosystem: "javascript".to_string(),
+            usage_patterns,
+        };
+        
+        self.patterns.entry(Language::JavaScript).or_default().push(jquery);
+    }
+    
+    fn register_lodash_patterns(&mut self) {
+        let call = |name: &str, intent: &str, parameters: Vec<(&str, Parameter)>| usage(name, Site::Call(name.to_string()), intent, parameters);
+        let usage_patterns = |deep_clone: bool| {
+            let mut usage_patterns = vec![
+                call("map", "collection_map", vec![("collection", Parameter::Argument(0)), ("iteratee", Parameter::Argument(1)), ("target", Parameter::Target)]),
+                call("filter", "collection_filter", vec![("collection", Parameter::Argument(0)), ("predicate", Parameter::Argument(1)), ("target", Parameter::Target)]),
+                call("reduce", "collection_reduce", vec![
+                    ("collection", Parameter::Argument(0)),
+                    ("reducer", Parameter::Argument(1)),
+                    ("initial", Parameter::Argument(2)),
+                    ("target", Parameter::Target),
+                ]),
+                call("flatten", "collection_flatten", vec![("collection", Parameter::Argument(0)), ("target", Parameter::Target)]),
+                call("debounce", "debounce", vec![("function", Parameter::Argument(0)), ("wait", Parameter::Argument(1)), ("target", Parameter::Target)]),
+            ];
+            // Underscore's `clone` is shallow
+            if deep_clone {
+                usage_patterns.push(call("cloneDeep", "deep_copy", vec![("value", Parameter::Argument(0)), ("target", Parameter::Target)]));
+            }
+            usage_patterns
+        };
+        
+        let patterns = [
+            DetectionPattern {
+                library_name: "lodash".to_string(),
+                modules: vec!["lodash".to_string(), "lodash-es".to_string()],
+                globals: vec![("_".to_string(), "lodash".to_string())],
+                ecosystem: "javascript".to_string(),
+                usage_patterns: usage_patterns(true),
+            },
             DetectionPattern {
-                library_name: "react".to_string(),
-                import_regex: Regex::new(r#"import.*\{[^}]*useEffect[^}]*\}.*from.*['"]react['"]"#).unwrap(),
+                library_name: "underscore".to_string(),
+                modules: vec!["underscore".to_string()],
+                globals: Vec::new(),
                 ecosystem: "javascript".to_string(),
-                usage_patterns: vec![
-                    UsagePattern {
-                        name: "useEffect".to_string(),
-                        regex: Regex::new(r"useEffect\s*\(\s*(?P<callback>[^,]+)\s*,\s*(?P<deps>\[[^\]]*\])\s*\)").unwrap(),
-                        semantic_intent: "side_effect_lifecycle".to_string(),
-                        extract_params: vec!["callback".to_string(), "deps".to_string()],
-                    },
-                ],
+                usage_patterns: usage_patterns(false),
             },
         ];
         
-        self.patterns.insert(Language::JavaScript, patterns);
+        self.patterns.entry(Language::JavaScript).or_default().extend(patterns);
+    }
+    
+    fn register_http_client_patterns(&mut self) {
+        let verbs = ["get", "post", "put", "patch", "delete", "head"];
+        let request = |site: Site, url: Parameter, method: Parameter, body: Option<Parameter>| {
+            let mut parameters = vec![("url", url), ("method", method), ("target", Parameter::Target)];
+            parameters.extend(body.map(|body| ("body", body)));
+            usage("request", site, "http_request", parameters)
+        };
+        let keyword = |keyword: &str| Parameter::Keyword(keyword.to_string());
+        let method_argument = || Parameter::Unquoted(Box::new(Parameter::Argument(0)));
+        
+        // `axios.post(url, data)`, `axios({ method, url, data })` and the same on `axios.create()`'s instances
+        let mut axios_usages = vec![
+            request(Site::Call("default".to_string()), keyword("url"), Parameter::HttpMethod, Some(keyword("data"))),
+            request(Site::Call("request".to_string()), keyword("url"), Parameter::HttpMethod, Some(keyword("data"))),
+        ];
+        for verb in verbs {
+            axios_usages.push(request(Site::Call(verb.to_string()), Parameter::Argument(0), Parameter::HttpMethod, Some(Parameter::Argument(1))));
+            axios_usages.push(request(Site::Chained("create".to_string(), verb.to_string()), Parameter::Argument(0), Parameter::HttpMethod, Some(Parameter::Argument(1))));
+        }
+        let axios = DetectionPattern {
+            library_name: "axios".to_string(),
+            modules: vec!["axios".to_string()],
+            globals: Vec::new(),
+            ecosystem: "javascript".to_string(),
+            usage_patterns: axios_usages,
+        };
+        
+        // `fetch` is global in browsers and Node 18; older code imports a polyfill
+        let fetch = DetectionPattern {
+            library_name: "fetch".to_string(),
+            modules: vec!["node-fetch".to_string(), "cross-fetch".to_string()],
+            globals: vec![("fetch".to_string(), "node-fetch".to_string())],
+            ecosystem: "javascript".to_string(),
+            usage_patterns: vec![request(Site::Call("default".to_string()), Parameter::Argument(0), Parameter::HttpMethod, Some(keyword("body")))],
+        };
+        
+        let body = || Parameter::Either(Box::new(keyword("json")), Box::new(keyword("data")));
+        let mut requests_usages = vec![request(Site::Call("request".to_string()), Parameter::Argument(1), method_argument(), Some(body()))];
+        for verb in verbs {
+            requests_usages.push(request(Site::Call(verb.to_string()), Parameter::Argument(0), Parameter::HttpMethod, Some(body())));
+            requests_usages.push(request(Site::Chained("Session".to_string(), verb.to_string()), Parameter::Argument(0), Parameter::HttpMethod, Some(body())));
+        }
+        let requests = DetectionPattern {
+            library_name: "requests".to_string(),
+            modules: vec!["requests".to_string()],
+            globals: Vec::new(),
+            ecosystem: "python".to_string(),
+            usage_patterns: requests_usages,
+        };
+        
+        // httpx takes requests' calls, and the same on its sync and async clients
+        let mut httpx_usages = vec![request(Site::Call("request".to_string()), Parameter::Argument(1), method_argument(), Some(body()))];
+        for verb in verbs {
+            httpx_usages.push(request(Site::Call(verb.to_string()), Parameter::Argument(0), Parameter::HttpMethod, Some(body())));
+            for client in ["Client", "AsyncClient"] {
+                httpx_usages.push(request(Site::Chained(client.to_string(), verb.to_string()), Parameter::Argument(0), Parameter::HttpMethod, Some(body())));
+            }
+        }
+        let httpx = DetectionPattern {
+            library_name: "httpx".to_string(),
+            modules: vec!["httpx".to_string()],
+            globals: Vec::new(),
+            ecosystem: "python".to_string(),
+            usage_patterns: httpx_usages,
+        };
+        
+        let net_http = DetectionPattern {
+            library_name: "net_http".to_string(),
+            modules: vec!["net/http".to_string()],
+            globals: Vec::new(),
+            ecosystem: "go".to_string(),
+            usage_patterns: vec![
+                request(Site::Call("Get".to_string()), Parameter::Argument(0), Parameter::HttpMethod, None),
+                request(Site::Call("Head".to_string()), Parameter::Argument(0), Parameter::HttpMethod, None),
+                request(Site::Call("Post".to_string()), Parameter::Argument(0), Parameter::HttpMethod, Some(Parameter::Argument(2))),
+                request(Site::Call("PostForm".to_string()), Parameter::Argument(0), Parameter::HttpMethod, Some(Parameter::Argument(1))),
+                request(Site::Call("NewRequest".to_string()), Parameter::Argument(1), method_argument(), Some(Parameter::Argument(2))),
+            ],
+        };
+        
+        // reqwest sets a body with a later `.json()` or `.body()` in the chain, out of reach here
+        let mut reqwest_usages = Vec::new();
+        for verb in verbs {
+            reqwest_usages.push(request(Site::Call(verb.to_string()), Parameter::Argument(0), Parameter::HttpMethod, None));
+            reqwest_usages.push(request(Site::Chained("new".to_string(), verb.to_string()), Parameter::Argument(0), Parameter::HttpMethod, None));
+        }
+        let reqwest = DetectionPattern {
+            library_name: "reqwest".to_string(),
+            modules: ["reqwest", "reqwest.Client", "reqwest.blocking", "reqwest.blocking.Client"].into_iter().map(String::from).collect(),
+            globals: Vec::new(),
+            ecosystem: "rust".to_string(),
+            usage_patterns: reqwest_usages,
+        };
+        
+        self.patterns.entry(Language::JavaScript).or_default().extend([axios, fetch]);
+        self.patterns.entry(Language::Python).or_default().extend([requests, httpx]);
+        self.patterns.entry(Language::Go).or_default().push(net_http);
+        self.patterns.entry(Language::Rust).or_default().push(reqwest);
+    }
+    
+    fn register_logging_patterns(&mut self) {
+        let log = |site: Site| usage("log", site, "structured_logging", vec![
+            ("level", Parameter::LogLevel(LevelStyle::Short)),
+            ("level_title", Parameter::LogLevel(LevelStyle::Title)),
+            ("level_long", Parameter::LogLevel(LevelStyle::Long)),
+            ("message", Parameter::Positional(0)),
+            ("fields", Parameter::Rest(1)),
+        ]);
+        // Calls of each level on the library itself, and on loggers its factories make
+        let logging = |library: &str, modules: &[&str], ecosystem: &str, levels: &[&str], factories: &[&str]| DetectionPattern {
+            library_name: library.to_string(),
+            modules: modules.iter().map(|module| module.to_string()).collect(),
+            globals: Vec::new(),
+            ecosystem: ecosystem.to_string(),
+            usage_patterns: levels.iter()
+                .flat_map(|level| {
+                    let on_loggers = factories.iter().map(|factory| log(Site::Chained(factory.to_string(), level.to_string())));
+                    std::iter::once(log(Site::Call(level.to_string()))).chain(on_loggers)
+                })
+                .collect(),
+        };
+        
+        let console = DetectionPattern {
+            globals: vec![("console".to_string(), "console".to_string())],
+            ..logging("console", &["console"], "javascript", &["log", "trace", "debug", "info", "warn", "error"], &[])
+        };
+        let winston = logging("winston", &["winston"], "javascript", &["debug", "verbose", "info", "warn", "error"], &["createLogger"]);
+        let log4j = logging(
+            "log4j",
+            &["org.apache.logging.log4j.LogManager", "org.apache.log4j.Logger"],
+            "java",
+            &["trace", "debug", "info", "warn", "error", "fatal"],
+            &["getLogger"],
+        );
+        let log4net = logging(
+            "log4net",
+            &["log4net", "log4net.LogManager"],
+            "csharp",
+            &["Debug", "DebugFormat", "Info", "InfoFormat", "Warn", "WarnFormat", "Error", "ErrorFormat", "Fatal", "FatalFormat"],
+            &["GetLogger"],
+        );
+        let python = logging("logging", &["logging"], "python", &["debug", "info", "warning", "warn", "error", "exception", "critical"], &["getLogger"]);
+        let go = logging("log", &["log"], "go", &["Print", "Printf", "Println", "Fatal", "Fatalf", "Fatalln", "Panic", "Panicf", "Panicln"], &[]);
+        let zap = logging(
+            "zap",
+            &["go.uber.org/zap"],
+            "go",
+            &["Debug", "Info", "Warn", "Error", "DPanic", "Panic", "Fatal"],
+            &["NewProduction", "NewDevelopment", "NewExample", "L"],
+        );
+        let tracing = logging("tracing", &["tracing"], "rust", &["trace", "debug", "info", "warn", "error"], &[]);
+        
+        self.patterns.entry(Language::JavaScript).or_default().extend([console, winston]);
+        for language in [Language::Java, Language::Kotlin] {
+            self.patterns.entry(language).or_default().push(log4j.clone());
+        }
+        self.patterns.entry(Language::CSharp).or_default().push(log4net);
+        self.patterns.entry(Language::Python).or_default().push(python);
+        self.patterns.entry(Language::Go).or_default().extend([go, zap]);
+        self.patterns.entry(Language::Rust).or_default().push(tracing);
+    }
+    
+    fn register_test_framework_patterns(&mut self) {
+        let named = |name: Parameter| {
+            let words = |case: NameCase| Parameter::Words(Box::new(name.clone()), case);
+            vec![("name", words(NameCase::Sentence)), ("snake_name", words(NameCase::Snake)), ("pascal_name", words(NameCase::Pascal))]
+        };
+        let compared = |actual: Parameter, expected: Parameter| vec![("actual", actual), ("expected", expected)];
+        let value = |value: Parameter| vec![("value", value)];
+        let matched = |group: &str| Parameter::Matched(group.to_string());
+        let with_text = |regex: &str, usage: UsagePattern| UsagePattern { text: Some(Regex::new(regex).unwrap()), ..usage };
+        let framework = |library: &str, modules: &[&str], ecosystem: &str, usage_patterns: Vec<UsagePattern>| DetectionPattern {
+            library_name: library.to_string(),
+            modules: modules.iter().map(|module| module.to_string()).collect(),
+            globals: Vec::new(),
+            ecosystem: ecosystem.to_string(),
+            usage_patterns,
+        };
+        
+        // jest defines its API globally; `@jest/globals` exports the same names
+        let matcher = |name: &str, matcher: &str, parameters| usage(name, Site::Chained("expect".to_string(), matcher.to_string()), name, parameters);
+        let mut jest_usages = vec![
+            usage("suite", Site::Call("describe".to_string()), "test_suite", named(Parameter::Argument(0))),
+            usage("test", Site::Call("test".to_string()), "test_case", named(Parameter::Argument(0))),
+            usage("test", Site::Call("it".to_string()), "test_case", named(Parameter::Argument(0))),
+        ];
+        for equal in ["toBe", "toEqual", "toStrictEqual"] {
+            jest_usages.push(with_text(r"\.not\.\w+\(", matcher("assert_not_equal", equal, compared(Parameter::RootArgument(0), Parameter::Argument(0)))));
+            jest_usages.push(matcher("assert_equal", equal, compared(Parameter::RootArgument(0), Parameter::Argument(0))));
+        }
+        jest_usages.extend([
+            matcher("assert_true", "toBeTruthy", value(Parameter::RootArgument(0))),
+            matcher("assert_false", "toBeFalsy", value(Parameter::RootArgument(0))),
+            matcher("assert_throws", "toThrow", vec![("error", Parameter::Argument(0)), ("action", Parameter::RootArgument(0))]),
+        ]);
+        let jest = DetectionPattern {
+            globals: ["describe", "test", "it", "expect"].into_iter().map(|name| (name.to_string(), format!("@jest/globals.{}", name))).collect(),
+            ..framework("jest", &["@jest/globals"], "javascript", jest_usages)
+        };
+        
+        let assertion = |name: &str, called: &str, parameters| usage(name, Site::Call(called.to_string()), name, parameters);
+        let xunit = framework("xunit", &["Xunit", "Xunit.Assert"], "csharp", vec![
+            usage("test", Site::Annotated("Fact".to_string()), "test_case", named(Parameter::Declared)),
+            usage("test", Site::Annotated("Theory".to_string()), "test_case", named(Parameter::Declared)),
+            assertion("assert_equal", "Equal", compared(Parameter::Argument(1), Parameter::Argument(0))),
+            assertion("assert_not_equal", "NotEqual", compared(Parameter::Argument(1), Parameter::Argument(0))),
+            assertion("assert_true", "True", value(Parameter::Argument(0))),
+            assertion("assert_false", "False", value(Parameter::Argument(0))),
+            assertion("assert_throws", "Throws", vec![("error", Parameter::TypeArgument(0)), ("action", Parameter::Argument(0))]),
+        ]);
+        
+        // pytest needs no import: it collects `test_` functions and rewrites plain asserts, so
+        // the assert's text tells the comparison apart
+        let assert = |name: &str, regex: &str, parameters| with_text(regex, usage(name, Site::Tagged("assert_statement".to_string()), name, parameters));
+        let pytest = framework("pytest", &[], "python", vec![
+            usage("test", Site::Prefixed("test_".to_string()), "test_case", named(Parameter::Declared)),
+            assert("assert_equal", r"(?s)^assert\s+(?P<actual>.+?)\s*==\s*(?P<expected>.+?)\s*$", compared(matched("actual"), matched("expected"))),
+            assert("assert_not_equal", r"(?s)^assert\s+(?P<actual>.+?)\s*!=\s*(?P<expected>.+?)\s*$", compared(matched("actual"), matched("expected"))),
+            assert("assert_false", r"(?s)^assert\s+not\s+(?P<value>.+?)\s*$", value(matched("value"))),
+            assert("assert_true", r"(?s)^assert\s+(?P<value>.+?)\s*$", value(matched("value"))),
+            with_text(
+                r"pytest\.raises\((?P<error>[^)]*)\)",
+                usage("assert_throws", Site::Tagged("with_statement".to_string()), "assert_throws", vec![("error", matched("error"))]),
+            ),
+        ]);
+        
+        // Go's tests report failures through the `t *testing.T` they're handed
+        let mut go_usages = vec![usage("test", Site::Prefixed("Test".to_string()), "test_case", named(Parameter::Declared))];
+        for report in ["Error", "Errorf", "Fatal", "Fatalf"] {
+            go_usages.push(with_text(r"^t\.", usage("fail", Site::Method(report.to_string()), "fail", vec![("message", Parameter::Argument(0))])));
+        }
+        let go = framework("testing", &["testing"], "go", go_usages);
+        
+        let cargo_test = framework("cargo_test", &[], "rust", vec![
+            usage("test", Site::Annotated("test".to_string()), "test_case", named(Parameter::Declared)),
+            // `#[should_panic]` shares its function with `#[test]`, so the attribute itself is the usage
+            with_text(
+                r#"^#\[should_panic(?:\(\s*expected\s*=\s*(?P<error>"[^"]*")\s*\))?\]$"#,
+                usage("assert_throws", Site::Tagged("attribute_item".to_string()), "assert_throws", vec![("error", matched("error"))]),
+            ),
+            assertion("assert_equal", "assert_eq", compared(Parameter::Argument(0), Parameter::Argument(1))),
+            assertion("assert_not_equal", "assert_ne", compared(Parameter::Argument(0), Parameter::Argument(1))),
+            assertion("assert_true", "assert", value(Parameter::Argument(0))),
+        ]);
+        
+        self.patterns.entry(Language::JavaScript).or_default().push(jest);
+        self.patterns.entry(Language::CSharp).or_default().push(xunit);
+        self.patterns.entry(Language::Python).or_default().push(pytest);
+        self.patterns.entry(Language::Go).or_default().push(go);
+        self.patterns.entry(Language::Rust).or_default().push(cargo_test);
+    }
+    
+    fn register_datetime_patterns(&mut self) {
+        let target = || ("target", Parameter::Target);
+        let with_text = |regex: &str, usage: UsagePattern| UsagePattern { text: Some(Regex::new(regex).unwrap()), ..usage };
+        let matched = |group: &str| Parameter::Matched(group.to_string());
+        // A date format in every style, from the one the library writes
+        let formats = |format: Parameter, from: DateStyle| {
+            let mut parameters = vec![target()];
+            for (name, to) in [
+                ("format_moment", DateStyle::Moment),
+                ("format_python", DateStyle::Python),
+                ("format_chrono", DateStyle::Chrono),
+                ("format_java", DateStyle::Java),
+                ("format_dotnet", DateStyle::DotNet),
+            ] {
+                parameters.push((name, Parameter::DateFormat(Box::new(format.clone()), from, to)));
+            }
+            parameters
+        };
+        let formatted = |value: Parameter, format: Parameter, from: DateStyle| {
+            let mut parameters = vec![("value", value)];
+            parameters.extend(formats(format, from));
+            parameters
+        };
+        let timed = |amount: Parameter, unit: Parameter, value: Option<Parameter>| {
+            let unit = Parameter::TimeUnit(Box::new(unit));
+            let mut parameters = vec![("amount", amount), ("unit_title", Parameter::Words(Box::new(unit.clone()), NameCase::Pascal)), ("unit", unit), target()];
+            parameters.extend(value.map(|value| ("value", value)));
+            parameters
+        };
+        let library = |name: &str, modules: &[&str], ecosystem: &str, usage_patterns: Vec<UsagePattern>| DetectionPattern {
+            library_name: name.to_string(),
+            modules: modules.iter().map(|module| module.to_string()).collect(),
+            globals: Vec::new(),
+            ecosystem: ecosystem.to_string(),
+            usage_patterns,
+        };
+        
+        // moment and dayjs share an API: calling the library makes a date, now without arguments,
+        // parsed by a format with one; a date wrapped to format it, as in `moment(d).format(...)`,
+        // is the date itself, and one made to format it, as in `moment().format(...)`, is now
+        let moment_like = |name: &str| {
+            let chained = |method: &str| Site::Chained("default".to_string(), method.to_string());
+            let wrapped = Parameter::Either(Box::new(matched("wrapped")), Box::new(Parameter::Receiver));
+            library(name, &[name], "javascript", vec![
+                with_text(r"^[\w$.]+\(\s*\)$", usage("now", Site::Call("default".to_string()), "datetime_now", vec![target()])),
+                with_text(
+                    r#"(?s)^[\w$.]+\(.+,\s*(?:"[^"]*"|'[^']*')\s*\)$"#,
+                    usage("parse", Site::Call("default".to_string()), "datetime_parse", formatted(Parameter::Argument(0), Parameter::Argument(1), DateStyle::Moment)),
+                ),
+                with_text(
+                    r"^[\w$.]+\(\s*\)\.format\(",
+                    usage("format_now", chained("format"), "datetime_format_now", formats(Parameter::Argument(0), DateStyle::Moment)),
+                ),
+                with_text(
+                    r"(?s)^(?:[\w$.]+\((?P<wrapped>[^,()]+)\)\.format\()?",
+                    usage("format", chained("format"), "datetime_format", formatted(wrapped, Parameter::Argument(0), DateStyle::Moment)),
+                ),
+                usage("add", chained("add"), "datetime_add", timed(Parameter::Argument(0), Parameter::Argument(1), Some(Parameter::Receiver))),
+                usage("duration", Site::Call("duration".to_string()), "datetime_duration", timed(Parameter::Argument(0), Parameter::Argument(1), None)),
+            ])
+        };
+        
+        let datetime = library("datetime", &["datetime", "datetime.datetime"], "python", vec![
+            usage("now", Site::Call("now".to_string()), "datetime_now", vec![target()]),
+            usage("parse", Site::Call("strptime".to_string()), "datetime_parse", formatted(Parameter::Argument(0), Parameter::Argument(1), DateStyle::Python)),
+            usage("format", Site::Method("strftime".to_string()), "datetime_format", formatted(Parameter::Receiver, Parameter::Argument(0), DateStyle::Python)),
+            usage("duration", Site::Call("timedelta".to_string()), "datetime_duration", timed(Parameter::FirstNamed, Parameter::FirstName, None)),
+        ]);
+        
+        // java.time takes formats through `DateTimeFormatter.ofPattern`, which the text holds
+        let pattern = r#"ofPattern\(\s*(?P<format>"[^"]*")"#;
+        let mut java_usages = vec![
+            usage("now", Site::Call("now".to_string()), "datetime_now", vec![target()]),
+            with_text(pattern, usage("parse", Site::Call("parse".to_string()), "datetime_parse", formatted(Parameter::Argument(0), matched("format"), DateStyle::Java))),
+            with_text(pattern, usage("format", Site::Method("format".to_string()), "datetime_format", formatted(Parameter::Receiver, matched("format"), DateStyle::Java))),
+        ];
+        for unit in ["Years", "Months", "Weeks", "Days", "Hours", "Minutes", "Seconds"] {
+            java_usages.push(usage("add", Site::Method(format!("plus{}", unit)), "datetime_add", timed(Parameter::Argument(0), Parameter::Called, Some(Parameter::Receiver))));
+        }
+        for unit in ["Days", "Hours", "Minutes", "Seconds", "Millis"] {
+            java_usages.push(usage("duration", Site::Call(format!("of{}", unit)), "datetime_duration", timed(Parameter::Argument(0), Parameter::Called, None)));
+        }
+        let java_time = library(
+            "java_time",
+            &["java.time.LocalDateTime", "java.time.LocalDate", "java.time.ZonedDateTime", "java.time.Duration", "java.time.format.DateTimeFormatter"],
+            "java",
+            java_usages,
+        );
+        
+        // `ToString` formats anything; a format with date fields formats a date
+        let mut dotnet_usages = vec![
+            usage("now", Site::Member("Now".to_string()), "datetime_now", vec![target()]),
+            usage("now", Site::Member("UtcNow".to_string()), "datetime_now", vec![target()]),
+            usage("parse", Site::Call("ParseExact".to_string()), "datetime_parse", formatted(Parameter::Argument(0), Parameter::Argument(1), DateStyle::DotNet)),
+            with_text(
+                r#"\.ToString\(\s*(?P<format>"[^"]*(?:yy|MM|dd|HH|mm)[^"]*")"#,
+                usage("format", Site::Method("ToString".to_string()), "datetime_format", formatted(Parameter::Receiver, matched("format"), DateStyle::DotNet)),
+            ),
+        ];
+        for unit in ["Years", "Months", "Days", "Hours", "Minutes", "Seconds", "Milliseconds"] {
+            dotnet_usages.push(usage("add", Site::Method(format!("Add{}", unit)), "datetime_add", timed(Parameter::Argument(0), Parameter::Called, Some(Parameter::Receiver))));
+        }
+        for unit in ["Days", "Hours", "Minutes", "Seconds", "Milliseconds"] {
+            dotnet_usages.push(usage("duration", Site::Call(format!("From{}", unit)), "datetime_duration", timed(Parameter::Argument(0), Parameter::Called, None)));
+        }
+        let system_datetime = library("system_datetime", &["System.DateTime", "System.TimeSpan"], "csharp", dotnet_usages);
+        
+        let mut chrono_usages = vec![
+            usage("now", Site::Call("now".to_string()), "datetime_now", vec![target()]),
+            usage("parse", Site::Call("parse_from_str".to_string()), "datetime_parse", formatted(Parameter::Argument(0), Parameter::Argument(1), DateStyle::Chrono)),
+            with_text(r"\.format\(", usage("format", Site::Method("format".to_string()), "datetime_format", formatted(Parameter::Receiver, Parameter::Argument(0), DateStyle::Chrono))),
+        ];
+        for unit in ["weeks", "days", "hours", "minutes", "seconds", "milliseconds"] {
+            chrono_usages.push(usage("duration", Site::Call(unit.to_string()), "datetime_duration", timed(Parameter::Argument(0), Parameter::Called, None)));
+        }
+        let chrono = library(
+            "chrono",
+            &["chrono", "chrono.Utc", "chrono.Local", "chrono.DateTime", "chrono.NaiveDateTime", "chrono.NaiveDate", "chrono.Duration", "chrono.TimeDelta"],
+            "rust",
+            chrono_usages,
+        );
+        
+        self.patterns.entry(Language::JavaScript).or_default().extend([moment_like("moment"), moment_like("dayjs")]);
+        self.patterns.entry(Language::Python).or_default().push(datetime);
+        for language in [Language::Java, Language::Kotlin] {
+            self.patterns.entry(language).or_default().push(java_time.clone());
+        }
+        self.patterns.entry(Language::CSharp).or_default().push(system_datetime);
+        self.patterns.entry(Language::Rust).or_default().push(chrono);
+    }
+    
+    fn register_file_io_patterns(&mut self) {
+        let read = |path: Parameter, target: Parameter| vec![("path", path), ("target", target)];
+        let write = |path: Parameter, data: Parameter| vec![("path", path), ("data", data)];
+        let join = |base: Parameter, rest: Parameter| vec![("base", base), ("rest", rest)];
+        let matched = |group: &str| Parameter::Matched(group.to_string());
+        let with_text = |regex: &str, usage: UsagePattern| UsagePattern { text: Some(Regex::new(regex).unwrap()), ..usage };
+        let library = |name: &str, modules: &[&str], ecosystem: &str, usage_patterns: Vec<UsagePattern>| DetectionPattern {
+            library_name: name.to_string(),
+            modules: modules.iter().map(|module| module.to_string()).collect(),
+            globals: Vec::new(),
+            ecosystem: ecosystem.to_string(),
+            usage_patterns,
+        };
+        
+        // Node's fs, sync or promised, with path for joining
+        let mut fs_usages = Vec::new();
+        for suffix in ["Sync", ""] {
+            fs_usages.extend([
+                usage("read", Site::Call(format!("readFile{}", suffix)), "file_read", read(Parameter::Argument(0), Parameter::Target)),
+                usage("write", Site::Call(format!("writeFile{}", suffix)), "file_write", write(Parameter::Argument(0), Parameter::Argument(1))),
+                usage("append", Site::Call(format!("appendFile{}", suffix)), "file_append", write(Parameter::Argument(0), Parameter::Argument(1))),
+            ]);
+        }
+        fs_usages.push(usage("path_join", Site::Call("join".to_string()), "path_join", join(Parameter::Argument(0), Parameter::Rest(1))));
+        let fs = library("fs", &["fs", "fs/promises", "node:fs", "node:fs/promises", "path", "node:path"], "javascript", fs_usages);
+        
+        // Python opens files with the builtin `open`, mostly in a `with` block whose mode tells
+        // reading from writing
+        let with_open = |name: &str, regex: &str, intent: &str, parameters| with_text(regex, usage(name, Site::Tagged("with_statement".to_string()), intent, parameters));
+        let io = DetectionPattern {
+            globals: vec![("open".to_string(), "io.open".to_string())],
+            ..library("io", &["io", "os.path", "pathlib"], "python", vec![
+                with_open(
+                    "write",
+                    r#"(?s)^with\s+open\(\s*(?P<path>[^,]+?)\s*,\s*(?:mode\s*=\s*)?["']w[bt]?["']\s*\)\s*as\s+\w+\s*:.*?\.write\((?P<data>[^\n]*)\)"#,
+                    "file_write",
+                    write(matched("path"), matched("data")),
+                ),
+                with_open(
+                    "append",
+                    r#"(?s)^with\s+open\(\s*(?P<path>[^,]+?)\s*,\s*(?:mode\s*=\s*)?["']a[bt]?["']\s*\)\s*as\s+\w+\s*:.*?\.write\((?P<data>[^\n]*)\)"#,
+                    "file_append",
+                    write(matched("path"), matched("data")),
+                ),
+                with_open(
+                    "read",
+                    r#"(?s)^with\s+open\(\s*(?P<path>[^,)]+?)\s*(?:,\s*(?:mode\s*=\s*)?["']r[bt]?["']\s*)?\)\s*as\s+\w+\s*:.*?(?P<target>\w+)\s*=\s*\w+\.read\(\)"#,
+                    "file_read",
+                    read(matched("path"), matched("target")),
+                ),
+                usage("read", Site::Chained("open".to_string(), "read".to_string()), "file_read", read(Parameter::RootArgument(0), Parameter::Target)),
+                usage("read", Site::Chained("Path".to_string(), "read_text".to_string()), "file_read", read(Parameter::RootArgument(0), Parameter::Target)),
+                usage("write", Site::Chained("Path".to_string(), "write_text".to_string()), "file_write", write(Parameter::RootArgument(0), Parameter::Argument(0))),
+                usage("path_join", Site::Call("join".to_string()), "path_join", join(Parameter::Argument(0), Parameter::Rest(1))),
+            ])
+        };
+        
+        // Go appends through `os.OpenFile` with `O_APPEND` among its flags
+        let os = library("os", &["os", "path/filepath"], "go", vec![
+            usage("read", Site::Call("ReadFile".to_string()), "file_read", read(Parameter::Argument(0), Parameter::Target)),
+            usage("write", Site::Call("WriteFile".to_string()), "file_write", write(Parameter::Argument(0), Parameter::Argument(1))),
+            with_text(r"O_APPEND", usage("append", Site::Call("OpenFile".to_string()), "file_append", vec![("path", Parameter::Argument(0))])),
+            usage("path_join", Site::Call("Join".to_string()), "path_join", join(Parameter::Argument(0), Parameter::Rest(1))),
+        ]);
+        
+        let system_io = library("system_io", &["System.IO.File", "System.IO.Path"], "csharp", vec![
+            usage("read", Site::Call("ReadAllText".to_string()), "file_read", read(Parameter::Argument(0), Parameter::Target)),
+            usage("write", Site::Call("WriteAllText".to_string()), "file_write", write(Parameter::Argument(0), Parameter::Argument(1))),
+            usage("append", Site::Call("AppendAllText".to_string()), "file_append", write(Parameter::Argument(0), Parameter::Argument(1))),
+            usage("path_join", Site::Call("Combine".to_string()), "path_join", join(Parameter::Argument(0), Parameter::Rest(1))),
+        ]);
+        
+        // std appends through `OpenOptions`, whose builder chain the text holds
+        let std_fs = library("std_fs", &["std.fs", "std.fs.OpenOptions", "std.path.Path", "std.path.PathBuf"], "rust", vec![
+            usage("read", Site::Call("read_to_string".to_string()), "file_read", read(Parameter::Argument(0), Parameter::Target)),
+            usage("write", Site::Call("write".to_string()), "file_write", write(Parameter::Argument(0), Parameter::Argument(1))),
+            with_text(
+                r"\.append\(\s*true\s*\)",
+                usage("append", Site::Chained("new".to_string(), "open".to_string()), "file_append", vec![("path", Parameter::Argument(0))]),
+            ),
+            usage("path_join", Site::Chained("new".to_string(), "join".to_string()), "path_join", join(Parameter::RootArgument(0), Parameter::Argument(0))),
+        ]);
+        
+        self.patterns.entry(Language::JavaScript).or_default().push(fs);
+        self.patterns.entry(Language::Python).or_default().push(io);
+        self.patterns.entry(Language::Go).or_default().push(os);
+        self.patterns.entry(Language::CSharp).or_default().push(system_io);
+        self.patterns.entry(Language::Rust).or_default().push(std_fs);
+    }
+    
+    fn register_concurrency_patterns(&mut self) {
+        let target = || ("target", Parameter::Target);
+        let spawned = |task: Parameter| vec![("task", task), target()];
+        let pooled = |workers: Parameter| vec![("workers", workers), target()];
+        let joined = |tasks: Parameter| vec![("tasks", tasks)];
+        let call = |name: &str, called: &str, intent: &str, parameters| usage(name, Site::Call(called.to_string()), intent, parameters);
+        let library = |name: &str, modules: &[&str], ecosystem: &str, usage_patterns: Vec<UsagePattern>| DetectionPattern {
+            library_name: name.to_string(),
+            modules: modules.iter().map(|module| module.to_string()).collect(),
+            globals: Vec::new(),
+            ecosystem: ecosystem.to_string(),
+            usage_patterns,
+        };
+        
+        // Executors run what's submitted to them, directly or through a variable
+        let java_concurrent = library(
+            "java_concurrent",
+            &["java.util.concurrent.Executors", "java.util.concurrent.CompletableFuture", "java.util.concurrent.locks", "java.util.concurrent.locks.ReentrantLock"],
+            "java",
+            vec![
+                call("thread_pool", "newFixedThreadPool", "thread_pool", pooled(Parameter::Argument(0))),
+                usage("spawn", Site::Chained("newFixedThreadPool".to_string(), "submit".to_string()), "concurrent_execution", spawned(Parameter::Argument(0))),
+                usage("spawn", Site::Chained("newFixedThreadPool".to_string(), "execute".to_string()), "concurrent_execution", spawned(Parameter::Argument(0))),
+                call("spawn", "runAsync", "concurrent_execution", spawned(Parameter::Argument(0))),
+                call("spawn", "supplyAsync", "concurrent_execution", spawned(Parameter::Argument(0))),
+                call("mutex", "ReentrantLock", "mutual_exclusion", vec![target()]),
+                call("join", "allOf", "concurrent_join", joined(Parameter::Arguments)),
+            ],
+        );
+        
+        let dotnet_tasks = library("dotnet_tasks", &["System.Threading.Tasks.Task", "System.Threading"], "csharp", vec![
+            call("spawn", "Run", "concurrent_execution", spawned(Parameter::Argument(0))),
+            call("join", "WhenAll", "concurrent_join", joined(Parameter::Arguments)),
+            call("join", "WaitAll", "concurrent_join", joined(Parameter::Arguments)),
+            usage("mutex", Site::Typed("Mutex".to_string()), "mutual_exclusion", vec![("target", Parameter::Declared)]),
+        ]);
+        
+        // Threads are joined one by one, through the variable holding each
+        let threading = library("threading", &["threading", "concurrent.futures"], "python", vec![
+            call("spawn", "Thread", "concurrent_execution", spawned(Parameter::Keyword("target".to_string()))),
+            usage("join", Site::Chained("Thread".to_string(), "join".to_string()), "concurrent_join", joined(Parameter::Receiver)),
+            call("mutex", "Lock", "mutual_exclusion", vec![target()]),
+            call("mutex", "RLock", "mutual_exclusion", vec![target()]),
+            call("thread_pool", "ThreadPoolExecutor", "thread_pool", pooled(Parameter::Either(Box::new(Parameter::Keyword("max_workers".to_string())), Box::new(Parameter::Positional(0))))),
+        ]);
+        
+        let asyncio = library("asyncio", &["asyncio"], "python", vec![
+            call("spawn", "create_task", "concurrent_execution", spawned(Parameter::Argument(0))),
+            call("spawn", "ensure_future", "concurrent_execution", spawned(Parameter::Argument(0))),
+            call("join", "gather", "concurrent_join", joined(Parameter::Arguments)),
+            call("mutex", "Lock", "mutual_exclusion", vec![target()]),
+            call("thread_pool", "Semaphore", "thread_pool", pooled(Parameter::Argument(0))),
+        ]);
+        
+        // goroutines are built into Go, and the parser tags the statements starting them;
+        // `sync`'s types are declared, not called, so their declarations' text tells them
+        let matched = |group: &str| Parameter::Matched(group.to_string());
+        let with_text = |regex: &str, usage: UsagePattern| UsagePattern { text: Some(Regex::new(regex).unwrap()), ..usage };
+        let goroutines = library("goroutines", &[], "go", vec![
+            with_text(r"(?s)^go\s+(?P<task>.+)$", usage("spawn", Site::Tagged("go_statement".to_string()), "concurrent_execution", vec![("task", matched("task"))])),
+            with_text(r"^(?P<target>\w+)\s+sync\.(?:RW)?Mutex$", usage("mutex", Site::Tagged("var_spec".to_string()), "mutual_exclusion", vec![("target", matched("target"))])),
+            with_text(r"^(?P<target>\w+)\s+sync\.(?:RW)?Mutex$", usage("mutex", Site::Tagged("field_declaration".to_string()), "mutual_exclusion", vec![("target", matched("target"))])),
+            with_text(r"^\w+\.Wait\(\)$", usage("join", Site::Method("Wait".to_string()), "concurrent_join", Vec::new())),
+        ]);
+        
+        let std_thread = library("std_thread", &["std.thread", "std.sync.Mutex", "std.sync.RwLock"], "rust", vec![
+            call("spawn", "spawn", "concurrent_execution", spawned(Parameter::Argument(0))),
+            usage("join", Site::Chained("spawn".to_string(), "join".to_string()), "concurrent_join", joined(Parameter::Receiver)),
+            call("mutex", "new", "mutual_exclusion", vec![("value", Parameter::Argument(0)), target()]),
+        ]);
+        
+        let tokio = library("tokio", &["tokio", "tokio.task", "tokio.sync.Mutex", "tokio.sync.Semaphore"], "rust", vec![
+            call("spawn", "spawn", "concurrent_execution", spawned(Parameter::Argument(0))),
+            call("join", "join", "concurrent_join", joined(Parameter::Arguments)),
+            with_text(r"Mutex::new", call("mutex", "new", "mutual_exclusion", vec![("value", Parameter::Argument(0)), target()])),
+            with_text(r"Semaphore::new", call("thread_pool", "new", "thread_pool", pooled(Parameter::Argument(0)))),
+        ]);
+        
+        for language in [Language::Java, Language::Kotlin] {
+            self.patterns.entry(language).or_default().push(java_concurrent.clone());
+        }
+        self.patterns.entry(Language::CSharp).or_default().push(dotnet_tasks);
+        self.patterns.entry(Language::Python).or_default().extend([threading, asyncio]);
+        self.patterns.entry(Language::Go).or_default().push(goroutines);
+        self.patterns.entry(Language::Rust).or_default().extend([std_thread, tokio]);
+    }
+    
+    fn register_cli_patterns(&mut self) {
+        let target = || ("target", Parameter::Target);
+        let matched = |group: &str| Parameter::Matched(group.to_string());
+        let unquoted = |parameter: Parameter| Parameter::Unquoted(Box::new(parameter));
+        let with_text = |regex: &str, usage: UsagePattern| UsagePattern { text: Some(Regex::new(regex).unwrap()), ..usage };
+        let library = |name: &str, modules: &[&str], ecosystem: &str, usage_patterns: Vec<UsagePattern>| DetectionPattern {
+            library_name: name.to_string(),
+            modules: modules.iter().map(|module| module.to_string()).collect(),
+            globals: Vec::new(),
+            ecosystem: ecosystem.to_string(),
+            usage_patterns,
+        };
+        // Builders chained off a call have no variable of their own to name
+        let parser = |fallback: &str| ("parser", Parameter::Either(Box::new(Parameter::Root), Box::new(Parameter::Literal(fallback.to_string()))));
+        
+        let argparse = library("argparse", &["argparse"], "python", vec![
+            usage("program", Site::Call("ArgumentParser".to_string()), "cli_program", vec![
+                ("parser", Parameter::Target),
+                ("name", unquoted(Parameter::Keyword("prog".to_string()))),
+                ("description", unquoted(Parameter::Keyword("description".to_string()))),
+            ]),
+            with_text(
+                r#"(?s)^[\w.]+\.add_argument\((?:\s*["']-\w["']\s*,)?\s*["']--(?P<name>[\w-]+)["'].*\baction\s*=\s*["']store_true["']"#,
+                usage("flag", Site::Chained("ArgumentParser".to_string(), "add_argument".to_string()), "cli_flag", vec![parser("parser"), ("name", matched("name")), ("help", unquoted(Parameter::Keyword("help".to_string())))]),
+            ),
+            with_text(
+                r#"^[\w.]+\.add_argument\((?:\s*["']-\w["']\s*,)?\s*["']--(?P<name>[\w-]+)["']"#,
+                usage("option", Site::Chained("ArgumentParser".to_string(), "add_argument".to_string()), "cli_option", vec![parser("parser"), ("name", matched("name")), ("help", unquoted(Parameter::Keyword("help".to_string())))]),
+            ),
+            with_text(
+                r#"^[\w.]+\.add_argument\(\s*["'](?P<name>\w[\w-]*)["']"#,
+                usage("positional", Site::Chained("ArgumentParser".to_string(), "add_argument".to_string()), "cli_positional", vec![parser("parser"), ("name", matched("name")), ("help", unquoted(Parameter::Keyword("help".to_string())))]),
+            ),
+            usage("parse", Site::Chained("ArgumentParser".to_string(), "parse_args".to_string()), "cli_parse", vec![parser("parser"), target()]),
+        ]);
+        
+        // commander spells an option's value into its flags: `--output <dir>`; the chain's text
+        // runs from its start, so each regex reads the last call
+        let commander = library("commander", &["commander"], "javascript", vec![
+            usage("program", Site::Call("Command".to_string()), "cli_program", vec![("parser", Parameter::Target), ("name", unquoted(Parameter::Argument(0)))]),
+            with_text(
+                r#"\.option\(\s*['"](?:-\w,\s*)?--(?P<name>[\w-]+)\s+[<\[][\w.-]+[>\]]['"]\s*(?:,[^()]*)?\)\s*$"#,
+                usage("option", Site::Chained("Command".to_string(), "option".to_string()), "cli_option", vec![parser("program"), ("name", matched("name")), ("help", unquoted(Parameter::Argument(1)))]),
+            ),
+            with_text(
+                r#"\.option\(\s*['"](?:-\w,\s*)?--(?P<name>[\w-]+)['"]\s*(?:,[^()]*)?\)\s*$"#,
+                usage("flag", Site::Chained("Command".to_string(), "option".to_string()), "cli_flag", vec![parser("program"), ("name", matched("name")), ("help", unquoted(Parameter::Argument(1)))]),
+            ),
+            with_text(
+                r#"\.argument\(\s*['"][<\[](?P<name>[\w-]+)[>\]]['"]\s*(?:,[^()]*)?\)\s*$"#,
+                usage("positional", Site::Chained("Command".to_string(), "argument".to_string()), "cli_positional", vec![parser("program"), ("name", matched("name")), ("help", unquoted(Parameter::Argument(1)))]),
+            ),
+            usage("parse", Site::Chained("Command".to_string(), "opts".to_string()), "cli_parse", vec![parser("program"), target()]),
+        ]);
+        
+        // yargs takes the type among an option's settings
+        let yargs = library("yargs", &["yargs", "yargs/yargs"], "javascript", vec![
+            with_text(
+                r#"\.option\(\s*['"](?P<name>[\w-]+)['"]\s*,\s*\{[^{}]*\btype\s*:\s*['"]boolean['"][^{}]*\}\s*\)\s*$"#,
+                usage("flag", Site::Chained("default".to_string(), "option".to_string()), "cli_flag", vec![parser("parser"), ("name", matched("name")), ("help", unquoted(Parameter::Keyword("describe".to_string())))]),
+            ),
+            with_text(
+                r#"\.option\(\s*['"](?P<name>[\w-]+)['"][^()]*\)\s*$"#,
+                usage("option", Site::Chained("default".to_string(), "option".to_string()), "cli_option", vec![parser("parser"), ("name", matched("name")), ("help", unquoted(Parameter::Keyword("describe".to_string())))]),
+            ),
+            usage("parse", Site::Chained("default".to_string(), "parse".to_string()), "cli_parse", vec![parser("parser"), target()]),
+            usage("parse", Site::Chained("default".to_string(), "parseSync".to_string()), "cli_parse", vec![parser("parser"), target()]),
+        ]);
+        
+        // cobra declares a command as a struct literal and its flags by type
+        let command = r#"(?s)^(?P<parser>\w+)\s*:?=\s*&?cobra\.Command\{.*?\bUse:\s*"(?P<name>[^"]*)"(?:.*?\bShort:\s*"(?P<description>[^"]*)")?"#;
+        let program = |tag: &str| with_text(command, usage("program", Site::Tagged(tag.to_string()), "cli_program", vec![
+            ("parser", matched("parser")),
+            ("name", matched("name")),
+            ("description", matched("description")),
+        ]));
+        let flag = r#"^(?P<parser>\w+)\.(?:Persistent)?Flags\(\)\.\w+\(\s*"(?P<name>[^"]+)""#;
+        let mut cobra_usages = vec![program("var_spec"), program("short_var_declaration")];
+        for (method, name, intent) in [
+            ("String", "option", "cli_option"),
+            ("StringP", "option", "cli_option"),
+            ("Int", "option", "cli_option"),
+            ("IntP", "option", "cli_option"),
+            ("Bool", "flag", "cli_flag"),
+            ("BoolP", "flag", "cli_flag"),
+        ] {
+            cobra_usages.push(with_text(flag, usage(name, Site::Method(method.to_string()), intent, vec![("parser", matched("parser")), ("name", matched("name")), ("help", unquoted(Parameter::Last))])));
+        }
+        cobra_usages.push(with_text(r"^(?P<parser>\w+)\.Execute\(", usage("parse", Site::Method("Execute".to_string()), "cli_parse", vec![("parser", matched("parser")), target()])));
+        let cobra = library("cobra", &["github.com/spf13/cobra"], "go", cobra_usages);
+        
+        // clap's builder chain reads from its start, so each regex takes the last `arg` and its
+        // settings, which hold at most one level of parentheses
+        let setting = r#"\s*\.\w+\((?:[^()]|\([^()]*\))*\)"#;
+        let arg = |required: &str| format!(
+            r#"\.arg\(\s*Arg::new\(\s*"(?P<name>[^"]+)"\s*\)(?:\s*\.help\(\s*"(?P<help>[^"]*)"\s*\)|{setting})*{required}(?:\s*\.help\(\s*"(?P<help_after>[^"]*)"\s*\)|{setting})*\s*\)\s*$"#,
+        );
+        let help = || ("help", Parameter::Either(Box::new(matched("help")), Box::new(matched("help_after"))));
+        let clap = library("clap", &["clap", "clap.Command", "clap.Arg", "clap.ArgAction"], "rust", vec![
+            with_text(
+                r#"Command::new\(\s*"(?P<name>[^"]*)"\s*\)\s*\.about\(\s*"(?P<description>[^"]*)"\s*\)\s*$"#,
+                usage("program", Site::Chained("new".to_string(), "about".to_string()), "cli_program", vec![parser("cli"), ("name", matched("name")), ("description", matched("description"))]),
+            ),
+            with_text(&arg(r"\s*\.action\(\s*ArgAction::SetTrue\s*\)"), usage("flag", Site::Chained("new".to_string(), "arg".to_string()), "cli_flag", vec![parser("cli"), ("name", matched("name")), help()])),
+            with_text(&arg(r#"\s*\.long\(\s*"[^"]*"\s*\)"#), usage("option", Site::Chained("new".to_string(), "arg".to_string()), "cli_option", vec![parser("cli"), ("name", matched("name")), help()])),
+            with_text(&arg(""), usage("positional", Site::Chained("new".to_string(), "arg".to_string()), "cli_positional", vec![parser("cli"), ("name", matched("name")), help()])),
+            usage("parse", Site::Chained("new".to_string(), "get_matches".to_string()), "cli_parse", vec![parser("cli"), target()]),
+        ]);
+        
+        // System.CommandLine declares options apart from the command they're added to
+        let system_commandline = library("system_commandline", &["System.CommandLine"], "csharp", vec![
+            usage("program", Site::Call("RootCommand".to_string()), "cli_program", vec![
+                ("parser", Parameter::Either(Box::new(Parameter::Target), Box::new(Parameter::Literal("rootCommand".to_string())))),
+                ("description", unquoted(Parameter::Argument(0))),
+            ]),
+            with_text(
+                r#"^new\s+Option<bool>\(\s*"--(?P<name>[\w-]+)""#,
+                usage("flag", Site::Call("Option".to_string()), "cli_flag", vec![("parser", Parameter::Literal("rootCommand".to_string())), ("name", matched("name")), ("help", unquoted(Parameter::Argument(1)))]),
+            ),
+            with_text(
+                r#"^new\s+Option<[\w?]+>\(\s*"--(?P<name>[\w-]+)""#,
+                usage("option", Site::Call("Option".to_string()), "cli_option", vec![("parser", Parameter::Literal("rootCommand".to_string())), ("name", matched("name")), ("help", unquoted(Parameter::Argument(1)))]),
+            ),
+            with_text(
+                r#"^new\s+Argument<[\w?]+>\(\s*"(?P<name>[\w-]+)""#,
+                usage("positional", Site::Call("Argument".to_string()), "cli_positional", vec![("parser", Parameter::Literal("rootCommand".to_string())), ("name", matched("name")), ("help", unquoted(Parameter::Argument(1)))]),
+            ),
+            with_text(r"^(?P<parser>\w+)\.(?:Invoke|Parse)\(", usage("parse", Site::Method("Invoke".to_string()), "cli_parse", vec![("parser", matched("parser")), target()])),
+            with_text(r"^(?P<parser>\w+)\.(?:Invoke|Parse)\(", usage("parse", Site::Method("Parse".to_string()), "cli_parse", vec![("parser", matched("parser")), target()])),
+        ]);
+        
+        self.patterns.entry(Language::Python).or_default().push(argparse);
+        self.patterns.entry(Language::JavaScript).or_default().extend([commander, yargs]);
+        self.patterns.entry(Language::Go).or_default().push(cobra);
+        self.patterns.entry(Language::Rust).or_default().push(clap);
+        self.patterns.entry(Language::CSharp).or_default().push(system_commandline);
+    }
+    
+    fn register_config_patterns(&mut self) {
+        let target = || ("target", Parameter::Target);
+        let matched = |group: &str| Parameter::Matched(group.to_string());
+        let unquoted = |parameter: Parameter| Parameter::Unquoted(Box::new(parameter));
+        let either = |first: Parameter, second: Parameter| Parameter::Either(Box::new(first), Box::new(second));
+        let literal = |value: &str| Parameter::Literal(value.to_string());
+        let with_text = |regex: &str, usage: UsagePattern| UsagePattern { text: Some(Regex::new(regex).unwrap()), ..usage };
+        let library = |name: &str, modules: &[&str], ecosystem: &str, usage_patterns: Vec<UsagePattern>| DetectionPattern {
+            library_name: name.to_string(),
+            modules: modules.iter().map(|module| module.to_string()).collect(),
+            globals: Vec::new(),
+            ecosystem: ecosystem.to_string(),
+            usage_patterns,
+        };
+        let named = || ("name", unquoted(Parameter::Argument(0)));
+        let env_file = |path: Parameter| ("path", either(unquoted(path), literal(".env")));
+        // Nested keys are `db.host` but for .NET's `Db:Host`
+        let keys = |separator: char| {
+            let key = unquoted(Parameter::Argument(0));
+            let replaced = Parameter::Replaced(Box::new(key.clone()), separator, if separator == ':' { '.' } else { ':' });
+            if separator == ':' {
+                [("key", replaced), ("section_key", key)]
+            } else {
+                [("key", key), ("section_key", replaced)]
+            }
+        };
+        
+        // `process` is Node's global, read as a variable is declared
+        let declared = r#"(?s)^(?P<target>[\w$]+)\s*=\s*process\.env(?:\.(?P<name>\w+)|\[\s*["'](?P<key>\w+)["']\s*\])\s*"#;
+        let process_env = library("process_env", &[], "javascript", vec![
+            with_text(&format!(r"{}(?:\|\||\?\?)\s*(?P<default>.+)$", declared), usage("env_default", Site::Tagged("variable_declarator".to_string()), "environment_read", vec![
+                ("name", either(matched("name"), matched("key"))),
+                ("default", unquoted(matched("default"))),
+                ("target", matched("target")),
+            ])),
+            with_text(&format!("{}$", declared), usage("env_get", Site::Tagged("variable_declarator".to_string()), "environment_read", vec![
+                ("name", either(matched("name"), matched("key"))),
+                ("target", matched("target")),
+            ])),
+        ]);
+        let dotenv = library("dotenv", &["dotenv", "dotenv/config"], "javascript", vec![
+            usage("load_dotenv", Site::Call("config".to_string()), "environment_file", vec![env_file(Parameter::Keyword("path".to_string()))]),
+            with_text(r#"^import\s+["']dotenv/config["']"#, usage("load_dotenv", Site::Tagged("import_statement".to_string()), "environment_file", vec![env_file(literal(".env"))])),
+        ]);
+        let node_config = library("node_config", &["config"], "javascript", {
+            let mut parameters = vec![("config", Parameter::Receiver), target()];
+            parameters.extend(keys('.'));
+            vec![usage("config_get", Site::Call("get".to_string()), "config_read", parameters)]
+        });
+        
+        // A second argument is the default
+        let os_environ = library("os_environ", &["os", "os.environ"], "python", vec![
+            with_text(r"^[\w.]*getenv\([^,]+,", usage("env_default", Site::Call("getenv".to_string()), "environment_read", vec![named(), ("default", unquoted(Parameter::Argument(1))), target()])),
+            usage("env_get", Site::Call("getenv".to_string()), "environment_read", vec![named(), target()]),
+            with_text(r"^os\.environ\.get\([^,]+,", usage("env_default", Site::Call("get".to_string()), "environment_read", vec![named(), ("default", unquoted(Parameter::Argument(1))), target()])),
+            with_text(r"^os\.environ\.get\(", usage("env_get", Site::Call("get".to_string()), "environment_read", vec![named(), target()])),
+            with_text(
+                r#"^os\.environ\[\s*["'](?P<name>\w+)["']\s*\]$"#,
+                usage("env_get", Site::Tagged("subscript".to_string()), "environment_read", vec![("name", matched("name")), target()]),
+            ),
+        ]);
+        let python_dotenv = library("python_dotenv", &["dotenv"], "python", vec![
+            usage("load_dotenv", Site::Call("load_dotenv".to_string()), "environment_file", vec![env_file(Parameter::Argument(0))]),
+        ]);
+        
+        let go_env = library("go_env", &["os"], "go", vec![
+            usage("env_get", Site::Call("Getenv".to_string()), "environment_read", vec![named(), target()]),
+        ]);
+        let godotenv = library("godotenv", &["github.com/joho/godotenv"], "go", vec![
+            usage("load_dotenv", Site::Call("Load".to_string()), "environment_file", vec![env_file(Parameter::Argument(0))]),
+        ]);
+        // viper keeps its settings in a package-level instance
+        let mut viper_usages = vec![usage("config_load", Site::Call("SetConfigFile".to_string()), "config_file", vec![("path", unquoted(Parameter::Argument(0))), ("target", literal("config"))])];
+        for getter in ["Get", "GetString", "GetInt", "GetBool", "GetFloat64"] {
+            let mut parameters = vec![("config", literal("config")), target()];
+            parameters.extend(keys('.'));
+            viper_usages.push(usage("config_get", Site::Call(getter.to_string()), "config_read", parameters));
+        }
+        let viper = library("viper", &["github.com/spf13/viper"], "go", viper_usages);
+        
+        let dotnet_config = library("dotnet_config", &["System.Environment", "Microsoft.Extensions.Configuration"], "csharp", vec![
+            usage("env_get", Site::Call("GetEnvironmentVariable".to_string()), "environment_read", vec![named(), target()]),
+            with_text(
+                r#"(?s)ConfigurationBuilder\(\).*?\.AddJsonFile\(\s*"(?P<path>[^"]+)""#,
+                usage("config_load", Site::Method("Build".to_string()), "config_file", vec![("path", matched("path")), ("target", either(Parameter::Target, literal("configuration")))]),
+            ),
+            usage("config_get", Site::Method("GetValue".to_string()), "config_read", {
+                let mut parameters = vec![("config", Parameter::Receiver), target()];
+                parameters.extend(keys(':'));
+                parameters
+            }),
+        ]);
+        let dotnetenv = library("dotnetenv", &["DotNetEnv", "DotNetEnv.Env"], "csharp", vec![
+            usage("load_dotenv", Site::Call("Load".to_string()), "environment_file", vec![env_file(Parameter::Argument(0))]),
+        ]);
+        
+        let std_env = library("std_env", &["std.env"], "rust", vec![
+            with_text(r"^(?:std::)?env::var\(", usage("env_get", Site::Call("var".to_string()), "environment_read", vec![named(), target()])),
+        ]);
+        let dotenvy = library("dotenvy", &["dotenvy"], "rust", vec![
+            usage("load_dotenv", Site::Call("dotenv".to_string()), "environment_file", vec![env_file(literal(".env"))]),
+            usage("load_dotenv", Site::Call("from_filename".to_string()), "environment_file", vec![env_file(Parameter::Argument(0))]),
+        ]);
+        let mut config_rs_usages = vec![with_text(
+            r#"File::with_name\(\s*"(?P<path>[^"]+)""#,
+            usage("config_load", Site::Chained("builder".to_string(), "build".to_string()), "config_file", vec![("path", matched("path")), ("target", either(Parameter::Target, literal("settings")))]),
+        )];
+        for getter in ["get_string", "get_int", "get_bool", "get_float"] {
+            let mut parameters = vec![("config", Parameter::Receiver), target()];
+            parameters.extend(keys('.'));
+            config_rs_usages.push(usage("config_get", Site::Method(getter.to_string()), "config_read", parameters));
+        }
+        let config_rs = library("config_rs", &["config", "config.Config", "config.Environment", "config.File"], "rust", config_rs_usages);
+        
+        self.patterns.entry(Language::JavaScript).or_default().extend([process_env, dotenv, node_config]);
+        self.patterns.entry(Language::Python).or_default().extend([os_environ, python_dotenv]);
+        self.patterns.entry(Language::Go).or_default().extend([go_env, godotenv, viper]);
+        self.patterns.entry(Language::CSharp).or_default().extend([dotnet_config, dotnetenv]);
+        self.patterns.entry(Language::Rust).or_default().extend([std_env, dotenvy, config_rs]);
+    }
+    
+    fn register_crypto_patterns(&mut self) {
+        let target = || ("target", Parameter::Target);
+        let matched = |group: &str| Parameter::Matched(group.to_string());
+        let with_text = |regex: &str, usage: UsagePattern| UsagePattern { text: Some(Regex::new(regex).unwrap()), ..usage };
+        let library = |name: &str, modules: Vec<String>, ecosystem: &str, usage_patterns: Vec<UsagePattern>| DetectionPattern {
+            library_name: name.to_string(),
+            modules,
+            globals: Vec::new(),
+            ecosystem: ecosystem.to_string(),
+            usage_patterns,
+        };
+        let owned = |modules: &[&str]| modules.iter().map(|module| module.to_string()).collect::<Vec<_>>();
+        // Algorithms as Go's packages name them, with the function hashing in one call
+        let algorithms = [("md5", "Sum"), ("sha1", "Sum"), ("sha256", "Sum256"), ("sha512", "Sum512")];
+        
+        // Node names the algorithm in a string; the data goes to `update` along the chain
+        let mut node_usages = Vec::new();
+        for (algorithm, _) in algorithms {
+            node_usages.extend([
+                with_text(
+                    &format!(r#"createHash\(\s*["']{}["']"#, algorithm),
+                    usage(&format!("hash_{}", algorithm), Site::Chained("createHash".to_string(), "update".to_string()), "cryptographic_hash", vec![("data", Parameter::Argument(0))]),
+                ),
+                with_text(
+                    &format!(r#"createHmac\(\s*["']{}["']"#, algorithm),
+                    usage(&format!("hmac_{}", algorithm), Site::Chained("createHmac".to_string(), "update".to_string()), "message_authentication", vec![("key", Parameter::RootArgument(1)), ("data", Parameter::Argument(0))]),
+                ),
+            ]);
+        }
+        node_usages.extend([
+            usage("random_bytes", Site::Call("randomBytes".to_string()), "secure_random", vec![("length", Parameter::Argument(0)), target()]),
+            with_text(
+                r#"^[\w$.]*createCipheriv\(\s*["']aes-\d+-gcm["']"#,
+                usage("encrypt", Site::Call("createCipheriv".to_string()), "authenticated_encryption", vec![("key", Parameter::Argument(1)), ("nonce", Parameter::Argument(2))]),
+            ),
+        ]);
+        let crypto = library("crypto", owned(&["crypto", "node:crypto"]), "javascript", node_usages);
+        
+        // hmac takes the hash as a hashlib constructor or its name
+        let mut hashlib_usages = Vec::new();
+        for (algorithm, _) in algorithms {
+            hashlib_usages.extend([
+                usage(&format!("hash_{}", algorithm), Site::Call(algorithm.to_string()), "cryptographic_hash", vec![("data", Parameter::Argument(0)), target()]),
+                with_text(
+                    &format!(r#"(?:hashlib\.|digestmod\s*=\s*["']){}\b"#, algorithm),
+                    usage(&format!("hmac_{}", algorithm), Site::Call("new".to_string()), "message_authentication", vec![("key", Parameter::Argument(0)), ("data", Parameter::Argument(1)), target()]),
+                ),
+            ]);
+        }
+        hashlib_usages.extend([
+            usage("random_bytes", Site::Call("token_bytes".to_string()), "secure_random", vec![("length", Parameter::Argument(0)), target()]),
+            usage("random_bytes", Site::Call("urandom".to_string()), "secure_random", vec![("length", Parameter::Argument(0)), target()]),
+        ]);
+        let hashlib = library("hashlib", owned(&["hashlib", "hmac", "secrets", "os"]), "python", hashlib_usages);
+        
+        let cryptography = library("cryptography", owned(&["cryptography.hazmat.primitives.ciphers.aead"]), "python", vec![
+            usage("encrypt", Site::Chained("AESGCM".to_string(), "encrypt".to_string()), "authenticated_encryption", vec![
+                ("key", Parameter::RootArgument(0)),
+                ("nonce", Parameter::Argument(0)),
+                ("data", Parameter::Argument(1)),
+                target(),
+            ]),
+        ]);
+        
+        // Go's hash packages share function names, so the package written tells them apart
+        let mut go_usages = Vec::new();
+        for (algorithm, sum) in algorithms {
+            go_usages.extend([
+                with_text(&format!(r"^{}\.", algorithm), usage(&format!("hash_{}", algorithm), Site::Call(sum.to_string()), "cryptographic_hash", vec![("data", Parameter::Argument(0)), target()])),
+                with_text(&format!(r"^hmac\.New\(\s*{}\.New\b", algorithm), usage(&format!("hmac_{}", algorithm), Site::Call("New".to_string()), "message_authentication", vec![("key", Parameter::Argument(1))])),
+            ]);
+        }
+        go_usages.extend([
+            with_text(r"^rand\.Read\(", usage("random_bytes", Site::Call("Read".to_string()), "secure_random", vec![("target", Parameter::Argument(0))])),
+            with_text(r"^\w+\.Seal\(", usage("encrypt", Site::Method("Seal".to_string()), "authenticated_encryption", vec![("nonce", Parameter::Argument(1)), ("data", Parameter::Argument(2)), target()])),
+        ]);
+        let mut go_modules = owned(&["crypto/hmac", "crypto/rand", "crypto/aes", "crypto/cipher"]);
+        go_modules.extend(algorithms.iter().map(|(algorithm, _)| format!("crypto/{}", algorithm)));
+        let go_crypto = library("go_crypto", go_modules, "go", go_usages);
+        
+        // .NET names algorithms in upper case, one class each
+        let namespace = "System.Security.Cryptography";
+        let mut dotnet_modules = vec![format!("{}.RandomNumberGenerator", namespace), format!("{}.AesGcm", namespace)];
+        let mut dotnet_usages = Vec::new();
+        for (algorithm, _) in algorithms {
+            let class = algorithm.to_uppercase();
+            dotnet_modules.extend([format!("{}.{}", namespace, class), format!("{}.HMAC{}", namespace, class)]);
+            dotnet_usages.extend([
+                with_text(&format!(r"^{}\.HashData\(", class), usage(&format!("hash_{}", algorithm), Site::Call("HashData".to_string()), "cryptographic_hash", vec![("data", Parameter::Argument(0)), target()])),
+                with_text(&format!(r"^{}\.Create\(", class), usage(&format!("hash_{}", algorithm), Site::Chained("Create".to_string(), "ComputeHash".to_string()), "cryptographic_hash", vec![("data", Parameter::Argument(0)), target()])),
+                with_text(&format!(r"^HMAC{}\.HashData\(", class), usage(&format!("hmac_{}", algorithm), Site::Call("HashData".to_string()), "message_authentication", vec![("key", Parameter::Argument(0)), ("data", Parameter::Argument(1)), target()])),
+            ]);
+        }
+        dotnet_usages.extend([
+            usage("random_bytes", Site::Call("GetBytes".to_string()), "secure_random", vec![("length", Parameter::Argument(0)), target()]),
+            with_text(r"^\w+\.Encrypt\(", usage("encrypt", Site::Method("Encrypt".to_string()), "authenticated_encryption", vec![("nonce", Parameter::Argument(0)), ("data", Parameter::Argument(1)), ("target", Parameter::Argument(2))])),
+        ]);
+        let dotnet_crypto = library("dotnet_crypto", dotnet_modules, "csharp", dotnet_usages);
+        
+        // RustCrypto's hashes are types of their own crates; randomness comes from rand
+        let mut rust_usages = Vec::new();
+        let mut rust_modules = owned(&["rand", "rand.rngs", "rand.RngCore", "aes_gcm", "aes_gcm.Aes256Gcm", "aes_gcm.Aes128Gcm"]);
+        for (algorithm, krate) in [("md5", "md5"), ("sha1", "sha1"), ("sha256", "sha2"), ("sha512", "sha2")] {
+            let hasher = format!("{}{}", algorithm[..1].to_uppercase(), &algorithm[1..]);
+            rust_modules.push(format!("{}.{}", krate, hasher));
+            rust_usages.push(with_text(&format!(r"^{}::digest\(", hasher), usage(&format!("hash_{}", algorithm), Site::Call("digest".to_string()), "cryptographic_hash", vec![("data", Parameter::Argument(0)), target()])));
+        }
+        rust_usages.extend([
+            with_text(r"fill_bytes\(\s*&mut\s+(?P<target>[\w.]+)", usage("random_bytes", Site::Method("fill_bytes".to_string()), "secure_random", vec![("target", matched("target"))])),
+            usage("encrypt", Site::Chained("new".to_string(), "encrypt".to_string()), "authenticated_encryption", vec![("nonce", Parameter::Argument(0)), ("data", Parameter::Argument(1)), target()]),
+            usage("encrypt", Site::Chained("new_from_slice".to_string(), "encrypt".to_string()), "authenticated_encryption", vec![("nonce", Parameter::Argument(0)), ("data", Parameter::Argument(1)), target()]),
+        ]);
+        let rustcrypto = library("rustcrypto", rust_modules, "rust", rust_usages);
+        
+        self.patterns.entry(Language::JavaScript).or_default().push(crypto);
+        self.patterns.entry(Language::Python).or_default().extend([hashlib, cryptography]);
+        self.patterns.entry(Language::Go).or_default().push(go_crypto);
+        self.patterns.entry(Language::CSharp).or_default().push(dotnet_crypto);
+        self.patterns.entry(Language::Rust).or_default().push(rustcrypto);
     }
     
     fn register_django_patterns(&mut self) {
         let patterns = vec![
             DetectionPattern {
                 library_name: "django".to_string(),
-                import_regex: Regex::new(r"from\s+django\.db\s+import\s+models").unwrap(),
+                modules: vec!["django.db.models".to_string()],
+                globals: Vec::new(),
                 ecosystem: "python".to_string(),
                 usage_patterns: vec![
-                    UsagePattern {
-                        name: "Model".to_string(),
-                        regex: Regex::new(r"class\s+(?P<name>\w+)\s*\(\s*models\.Model\s*\)").unwrap(),
-                        semantic_intent: "orm_model".to_string(),
-                        extract_params: vec!["name".to_string()],
-                    },
-                    UsagePattern {
-                        name: "CharField".to_string(),
-                        regex: Regex::new(r"(?P<field>\w+)\s*=\s*models\.CharField\s*\(\s*max_length\s*=\s*(?P<length>\d+)").unwrap(),
-                        semantic_intent: "text_field".to_string(),
-                        extract_params: vec!["field".to_string(), "length".to_string()],
-                    },
+                    usage("Model", Site::Base("Model".to_string()), "orm_model", vec![("name", Parameter::Declared)]),
+                    usage("CharField", Site::Call("CharField".to_string()), "text_field", vec![
+                        ("field", Parameter::Target),
+                        ("length", Parameter::Keyword("max_length".to_string())),
+                    ]),
                 ],
             },
         ];
         
-        self.patterns.insert(Language::Python, patterns);
+        self.patterns.entry(Language::Python).or_default().extend(patterns);
+    }
+    
+    fn register_python_web_patterns(&mut self) {
+        // Routes are methods of the application object, or of a blueprint or router grouping them
+        let routes = |classes: &[&str], methods: &[&str]| -> Vec<UsagePattern> {
+            classes.iter()
+                .flat_map(|class| methods.iter().map(move |method| (class, method)))
+                .map(|(class, method)| usage("route", Site::Decorator(class.to_string(), method.to_string()), "http_route", vec![
+                    ("path", Parameter::RoutePath(Box::new(Parameter::Argument(0)), PathStyle::Braces)),
+                    ("colon_path", Parameter::RoutePath(Box::new(Parameter::Argument(0)), PathStyle::Colons)),
+                    ("method", Parameter::HttpMethod),
+                    ("handler", Parameter::Declared),
+                ]))
+                .collect()
+        };
+        let call = |name: &str, intent: &str, parameters: Vec<(&str, Parameter)>| usage(name, Site::Call(name.to_string()), intent, parameters);
+        let unquoted = |parameter: Parameter| Parameter::Unquoted(Box::new(parameter));
+        let verbs = ["get", "post", "put", "delete", "patch"];
+        
+        let flask = DetectionPattern {
+            library_name: "flask".to_string(),
+            modules: vec!["flask".to_string(), "flask.request".to_string(), "flask.request.args".to_string()],
+            globals: Vec::new(),
+            ecosystem: "python".to_string(),
+            usage_patterns: [
+                vec![
+                    usage("app", Site::Call("Flask".to_string()), "web_application", vec![("var", Parameter::Target)]),
+                    usage("blueprint", Site::Call("Blueprint".to_string()), "route_group", vec![
+                        ("var", Parameter::Target),
+                        ("name", unquoted(Parameter::Argument(0))),
+                        ("prefix", Parameter::RoutePath(Box::new(Parameter::Keyword("url_prefix".to_string())), PathStyle::Braces)),
+                    ]),
+                ],
+                routes(&["Flask", "Blueprint"], &["route"]),
+                routes(&["Flask", "Blueprint"], &verbs),
+                vec![
+                    usage("request_json", Site::Call("get_json".to_string()), "http_request_body", vec![("var", Parameter::Target)]),
+                    usage("query_param", Site::Call("get".to_string()), "http_query_parameter", vec![
+                        ("var", Parameter::Target),
+                        ("name", unquoted(Parameter::Argument(0))),
+                    ]),
+                    call("jsonify", "json_response", vec![("body", Parameter::Arguments)]),
+                    call("abort", "http_error", vec![("status", Parameter::Argument(0))]),
+                ],
+            ].concat(),
+        };
+        
+        let fastapi = DetectionPattern {
+            library_name: "fastapi".to_string(),
+            modules: vec!["fastapi".to_string()],
+            globals: Vec::new(),
+            ecosystem: "python".to_string(),
+            usage_patterns: [
+                vec![
+                    usage("app", Site::Call("FastAPI".to_string()), "web_application", vec![("var", Parameter::Target)]),
+                    usage("router", Site::Call("APIRouter".to_string()), "route_group", vec![
+                        ("var", Parameter::Target),
+                        ("prefix", Parameter::RoutePath(Box::new(Parameter::Keyword("prefix".to_string())), PathStyle::Braces)),
+                    ]),
+                ],
+                routes(&["FastAPI", "APIRouter"], &verbs),
+                vec![
+                    usage("depends", Site::Default("Depends".to_string()), "dependency_injection", vec![
+                        ("parameter", Parameter::Declared),
+                        ("type", Parameter::Annotation("type".to_string())),
+                        ("provider", Parameter::Argument(0)),
+                    ]),
+                    usage("http_exception", Site::Call("HTTPException".to_string()), "http_error", vec![
+                        ("status", Parameter::Keyword("status_code".to_string())),
+                        ("detail", Parameter::Keyword("detail".to_string())),
+                    ]),
+                ],
+            ].concat(),
+        };
+        
+        self.patterns.entry(Language::Python).or_default().extend([flask, fastapi]);
+    }
+    
+    fn register_spring_patterns(&mut self) {
+        let annotated = |name: &str, annotation: &str, intent: &str, parameters: Vec<(&str, Parameter)>| usage(name, Site::Annotated(annotation.to_string()), intent, parameters);
+        let path = |style: PathStyle| Parameter::RoutePath(Box::new(Parameter::Argument(0)), style);
+        let injected = || vec![("parameter", Parameter::Declared), ("type", Parameter::Annotation("type".to_string()))];
+        
+        // Mapping annotations route the handler methods they carry; `@RequestMapping` on a
+        // controller class sets the prefix of its routes
+        let mut usage_patterns = vec![
+            annotated("rest_controller", "RestController", "http_controller", vec![("name", Parameter::Declared)]),
+            annotated("request_mapping", "RequestMapping", "route_group", vec![("name", Parameter::Declared), ("path", path(PathStyle::Braces))]),
+        ];
+        for mapping in ["GetMapping", "PostMapping", "PutMapping", "DeleteMapping", "PatchMapping"] {
+            usage_patterns.push(annotated("route", mapping, "http_route", vec![
+                ("path", path(PathStyle::Braces)),
+                ("colon_path", path(PathStyle::Colons)),
+                ("method", Parameter::HttpMethod),
+                ("handler", Parameter::Declared),
+            ]));
+        }
+        usage_patterns.extend([
+            annotated("autowired", "Autowired", "dependency_injection", injected()),
+            annotated("autowired", "Inject", "dependency_injection", injected()),
+            annotated("entity", "Entity", "orm_model", vec![("name", Parameter::Declared)]),
+            annotated("component", "Service", "injectable_service", vec![("name", Parameter::Declared)]),
+            annotated("component", "Repository", "injectable_service", vec![("name", Parameter::Declared)]),
+            annotated("component", "Component", "injectable_service", vec![("name", Parameter::Declared)]),
+        ]);
+        
+        let spring = DetectionPattern {
+            library_name: "spring".to_string(),
+            modules: [
+                "org.springframework.web.bind.annotation",
+                "org.springframework.beans.factory.annotation",
+                "org.springframework.stereotype",
+                "javax.inject",
+                "javax.persistence",
+                "jakarta.inject",
+                "jakarta.persistence",
+            ].into_iter().map(String::from).collect(),
+            globals: Vec::new(),
+            ecosystem: "java".to_string(),
+            usage_patterns,
+        };
+        
+        // Spring Boot is written in Kotlin as much as in Java, with the same annotations
+        for language in [Language::Java, Language::Kotlin] {
+            self.patterns.entry(language).or_default().push(spring.clone());
+        }
+    }
+    
+    fn register_dotnet_patterns(&mut self) {
+        let annotated = |name: &str, attribute: &str, intent: &str, parameters: Vec<(&str, Parameter)>| usage(name, Site::Annotated(attribute.to_string()), intent, parameters);
+        let method = |name: &str, called: &str, intent: &str, parameters: Vec<(&str, Parameter)>| usage(name, Site::Method(called.to_string()), intent, parameters);
+        let path = |style: PathStyle| Parameter::RoutePath(Box::new(Parameter::Argument(0)), style);
+        let route = |handler: Parameter| vec![
+            ("path", path(PathStyle::Braces)),
+            ("colon_path", path(PathStyle::Colons)),
+            ("angle_path", path(PathStyle::Angles)),
+            ("method", Parameter::HttpMethod),
+            ("handler", handler),
+        ];
+        let verbs = ["Get", "Post", "Put", "Delete", "Patch"];
+        
+        // Controllers route with attributes; minimal APIs with `Map*` calls on the application
+        let mut aspnet_usages = vec![
+            usage("controller", Site::Base("ControllerBase".to_string()), "http_controller", vec![("name", Parameter::Declared)]),
+            usage("controller", Site::Base("Controller".to_string()), "http_controller", vec![("name", Parameter::Declared)]),
+            annotated("route_prefix", "Route", "route_group", vec![("name", Parameter::Declared), ("path", path(PathStyle::Braces))]),
+        ];
+        for verb in verbs {
+            aspnet_usages.push(annotated("route", &format!("Http{}", verb), "http_route", route(Parameter::Declared)));
+            aspnet_usages.push(method("route", &format!("Map{}", verb), "http_route", route(Parameter::Argument(1))));
+        }
+        for lifetime in ["AddScoped", "AddTransient", "AddSingleton"] {
+            aspnet_usages.push(method("service_registration", lifetime, "dependency_injection", vec![
+                ("lifetime", Parameter::Called),
+                ("service", Parameter::TypeArgument(0)),
+                ("implementation", Parameter::TypeArgument(1)),
+            ]));
+        }
+        
+        let aspnet = DetectionPattern {
+            library_name: "aspnet".to_string(),
+            modules: [
+                "Microsoft.AspNetCore.Mvc",
+                "Microsoft.AspNetCore.Builder",
+                "Microsoft.AspNetCore.Http",
+                "Microsoft.Extensions.DependencyInjection",
+            ].into_iter().map(String::from).collect(),
+            globals: Vec::new(),
+            ecosystem: "csharp".to_string(),
+            usage_patterns: aspnet_usages,
+        };
+        
+        // LINQ in a module using Entity Framework queries the database. The parser tags method
+        // chains and query expressions; the text holds the filter condition
+        let query = |tag: &str, regex: &str, listed: &str| UsagePattern {
+            text: Some(Regex::new(regex).unwrap()),
+            ..usage("query", Site::Tagged(tag.to_string()), "database_query", vec![
+                ("model", Parameter::Unqualified(Box::new(Parameter::Annotation("query_source".to_string())))),
+                ("source", Parameter::Annotation("query_source".to_string())),
+                ("operators", Parameter::Annotation(listed.to_string())),
+                ("condition", Parameter::Matched("condition".to_string())),
+            ])
+        };
+        let entity_framework = DetectionPattern {
+            library_name: "entity_framework".to_string(),
+            modules: vec!["Microsoft.EntityFrameworkCore".to_string()],
+            globals: Vec::new(),
+            ecosystem: "csharp".to_string(),
+            usage_patterns: vec![
+                usage("db_context", Site::Base("DbContext".to_string()), "database_session", vec![("name", Parameter::Declared)]),
+                usage("db_set", Site::Typed("DbSet".to_string()), "orm_model", vec![("name", Parameter::Declared), ("entity", Parameter::TypeArgument(0))]),
+                method("add_db_context", "AddDbContext", "database_connection", vec![("context", Parameter::TypeArgument(0))]),
+                query("linq_chain", r"^(?s)(?:.*?\.Where\(\s*\w+\s*=>\s*(?P<condition>[^()]*(?:\([^()]*\)[^()]*)*)\))?", "linq_operators"),
+                query("linq_query", r"^(?s)(?:.*?\bwhere\s+(?P<condition>.*?)\s+(?:orderby|select|group|join|let)\b)?", "query_clauses"),
+                method("save_changes", "SaveChanges", "database_commit", vec![]),
+                method("save_changes", "SaveChangesAsync", "database_commit", vec![]),
+            ],
+        };
+        
+        self.patterns.entry(Language::CSharp).or_default().extend([aspnet, entity_framework]);
     }
     
     fn register_networking_patterns(&mut self) {
@@ -164,19 +1741,1208 @@ impl DependencyDetector {
         let c_patterns = vec![
             DetectionPattern {
                 library_name: "socket".to_string(),
-                import_regex: Regex::new(r"#include\s+<sys/socket\.h>").unwrap(),
+                modules: vec!["sys/socket.h".to_string()],
+                globals: Vec::new(),
                 ecosystem: "c".to_string(),
                 usage_patterns: vec![
-                    UsagePattern {
-                        name: "socket".to_string(),
-                        regex: Regex::new(r"(?P<var>\w+)\s*=\s*socket\s*\(\s*(?P<family>AF_\w+)\s*,\s*(?P<type>SOCK_\w+)\s*,\s*(?P<protocol>\d+)\s*\)").unwrap(),
-                        semantic_intent: "tcp_socket_creation".to_string(),
-                        extract_params: vec!["var".to_string(), "family".to_string(), "type".to_string()],
-                    },
+                    usage("socket", Site::Call("socket".to_string()), "tcp_socket_creation", vec![
+                        ("var", Parameter::Target),
+                        ("family", Parameter::Argument(0)),
+                        ("type", Parameter::Argument(1)),
+                    ]),
                 ],
             },
         ];
         
         self.patterns.insert(Language::C, c_patterns);
     }
+    
+    fn register_scientific_patterns(&mut self) {
+        let target = || ("target", Parameter::Target);
+        let literal = |value: &str| Parameter::Literal(value.to_string());
+        let either = |first: Parameter, second: Parameter| Parameter::Either(Box::new(first), Box::new(second));
+        let matched = |group: &str| Parameter::Matched(group.to_string());
+        let keyword = |keyword: &str| Parameter::Keyword(keyword.to_string());
+        let with_text = |regex: &str, usage: UsagePattern| UsagePattern { text: Some(Regex::new(regex).unwrap()), ..usage };
+        let call = |name: &str, intent: &str, parameters: Vec<(&str, Parameter)>| usage(name, Site::Call(name.to_string()), intent, parameters);
+        
+        // A shape is a `(rows, cols)` tuple or a length; a length makes a single column
+        let mut numpy_usages = vec![
+            with_text(r"^[\w.]+\(\s*(?:\[(?P<elements>[^\[\]]*)\])?", call("array", "array_creation", vec![("values", Parameter::Argument(0)), ("elements", matched("elements")), target()])),
+        ];
+        for fill in ["zeros", "ones"] {
+            let shaped = |cols: Parameter| vec![("shape", Parameter::Argument(0)), ("rows", matched("rows")), ("cols", cols), target()];
+            numpy_usages.push(with_text(r"^[\w.]+\(\s*\(\s*(?P<rows>[^,()]+?)\s*,\s*(?P<cols>[^,()]+?)\s*\)", call(fill, "array_fill", shaped(matched("cols")))));
+            numpy_usages.push(with_text(r"^[\w.]+\(\s*(?P<rows>[^,()]+?)\s*[,)]", call(fill, "array_fill", shaped(literal("1")))));
+        }
+        numpy_usages.extend([
+            // `arange(10)` counts from zero
+            with_text(
+                r"^[\w.]+\(\s*(?P<stop>(?:[^,()]|\([^()]*\))+?)\s*\)$",
+                call("arange", "array_range", vec![("start", literal("0")), ("stop", matched("stop")), ("step", literal("1")), target()]),
+            ),
+            call("arange", "array_range", vec![
+                ("start", Parameter::Positional(0)),
+                ("stop", Parameter::Positional(1)),
+                ("step", either(either(Parameter::Positional(2), keyword("step")), literal("1"))),
+                target(),
+            ]),
+            call("linspace", "array_range", vec![
+                ("start", Parameter::Positional(0)),
+                ("stop", Parameter::Positional(1)),
+                ("num", either(either(Parameter::Positional(2), keyword("num")), literal("50"))),
+                target(),
+            ]),
+        ]);
+        // Products and reductions are functions of the module and methods of its arrays
+        let creators = ["array", "zeros", "ones", "arange", "linspace"];
+        for product in ["dot", "matmul"] {
+            numpy_usages.push(call(product, "matrix_multiply", vec![("left", Parameter::Argument(0)), ("right", Parameter::Argument(1)), target()]));
+        }
+        for creator in creators {
+            numpy_usages.push(usage("dot", Site::Chained(creator.to_string(), "dot".to_string()), "matrix_multiply", vec![
+                ("left", Parameter::Receiver),
+                ("right", Parameter::Argument(0)),
+                target(),
+            ]));
+        }
+        for reduction in ["sum", "mean", "max", "min"] {
+            numpy_usages.push(call(reduction, "array_reduce", vec![("array", Parameter::Argument(0)), ("axis", keyword("axis")), target()]));
+            for creator in creators {
+                numpy_usages.push(usage(reduction, Site::Chained(creator.to_string(), reduction.to_string()), "array_reduce", vec![
+                    ("array", Parameter::Receiver),
+                    ("axis", keyword("axis")),
+                    target(),
+                ]));
+            }
+        }
+        for function in ["sqrt", "exp", "abs", "sin", "cos", "tan", "log2", "log10"] {
+            numpy_usages.push(usage("ufunc", Site::Call(function.to_string()), "vectorized_map", vec![
+                ("function", Parameter::Called),
+                ("function_title", Parameter::Words(Box::new(Parameter::Called), NameCase::Pascal)),
+                ("array", Parameter::Argument(0)),
+                target(),
+            ]));
+        }
+        
+        // DataFrames come from the constructor or a reader, directly or through a variable
+        let mut pandas_usages = vec![
+            call("DataFrame", "dataframe_creation", vec![("data", Parameter::Argument(0)), target()]),
+            call("read_csv", "dataframe_read", vec![("path", Parameter::Argument(0)), target()]),
+            call("merge", "dataframe_join", vec![
+                ("frame", Parameter::Argument(0)),
+                ("other", Parameter::Argument(1)),
+                ("on", keyword("on")),
+                ("how", Parameter::Words(Box::new(Parameter::Unquoted(Box::new(either(keyword("how"), literal("inner"))))), NameCase::Pascal)),
+                target(),
+            ]),
+        ];
+        for maker in ["DataFrame", "read_csv"] {
+            let method = |name: &str, intent: &str, mut parameters: Vec<(&'static str, Parameter)>| {
+                parameters.extend([("frame", Parameter::Receiver), ("target", Parameter::Target)]);
+                usage(name, Site::Chained(maker.to_string(), name.to_string()), intent, parameters)
+            };
+            pandas_usages.extend([
+                method("query", "dataframe_filter", vec![("condition", Parameter::Unquoted(Box::new(Parameter::Argument(0))))]),
+                method("groupby", "dataframe_group", vec![("columns", either(keyword("by"), Parameter::Argument(0)))]),
+                method("agg", "dataframe_aggregate", vec![("aggregations", Parameter::Arguments)]),
+                method("sort_values", "dataframe_sort", vec![("columns", either(keyword("by"), Parameter::Argument(0)))]),
+                method("merge", "dataframe_join", vec![
+                    ("other", Parameter::Argument(0)),
+                    ("on", keyword("on")),
+                    ("how", Parameter::Words(Box::new(Parameter::Unquoted(Box::new(either(keyword("how"), literal("inner"))))), NameCase::Pascal)),
+                ]),
+                method("assign", "dataframe_mutate", vec![("column", Parameter::FirstName), ("expression", Parameter::FirstNamed)]),
+                method("to_csv", "dataframe_write", vec![("path", Parameter::Argument(0))]),
+            ]);
+        }
+        
+        let patterns = [
+            DetectionPattern {
+                library_name: "numpy".to_string(),
+                modules: vec!["numpy".to_string()],
+                globals: Vec::new(),
+                ecosystem: "python".to_string(),
+                usage_patterns: numpy_usages,
+            },
+            DetectionPattern {
+                library_name: "pandas".to_string(),
+                modules: vec!["pandas".to_string()],
+                globals: Vec::new(),
+                ecosystem: "python".to_string(),
+                usage_patterns: pandas_usages,
+            },
+        ];
+        
+        self.patterns.entry(Language::Python).or_default().extend(patterns);
+    }
+    
+    fn register_tidyverse_patterns(&mut self) {
+        let verb = |name: &str, intent: &str, parameters: Vec<(&str, Parameter)>| usage(name, Site::Call(name.to_string()), intent, parameters);
+        // `mutate(total = price * n())` names the column it computes
+        let named = || vec![("column", Parameter::FirstName), ("expression", Parameter::FirstNamed)];
+        
+        let patterns = vec![
+            DetectionPattern {
+                library_name: "dplyr".to_string(),
+                modules: vec!["dplyr".to_string(), "tidyverse".to_string()],
+                globals: Vec::new(),
+                ecosystem: "r".to_string(),
+                usage_patterns: vec![
+                    verb("filter", "dataframe_filter", vec![("condition", Parameter::Arguments)]),
+                    verb("select", "dataframe_select", vec![("columns", Parameter::Arguments)]),
+                    verb("mutate", "dataframe_mutate", named()),
+                    verb("group_by", "dataframe_group", vec![("columns", Parameter::Arguments)]),
+                    verb("summarise", "dataframe_aggregate", named()),
+                    usage("summarise", Site::Call("summarize".to_string()), "dataframe_aggregate", named()),
+                    verb("arrange", "dataframe_sort", vec![("columns", Parameter::Arguments)]),
+                ],
+            },
+        ];
+        
+        self.patterns.insert(Language::R, patterns);
+    }
+    
+    fn register_open_sql_patterns(&mut self) {
+        // The parser tags each database statement; the text tells their forms apart and holds
+        // what its annotations leave out
+        let statement = |name: &str, tag: &str, regex: &str, intent: &str, parameters: Vec<(&str, Parameter)>| UsagePattern {
+            text: Some(Regex::new(regex).unwrap()),
+            ..usage(name, Site::Tagged(tag.to_string()), intent, parameters)
+        };
+        let annotation = |key: &str| Parameter::Annotation(key.to_string());
+        let matched = |group: &str| Parameter::Matched(group.to_string());
+        
+        // Open SQL is part of the language, so any database statement counts as usage
+        let patterns = vec![
+            DetectionPattern {
+                library_name: "open_sql".to_string(),
+                modules: Vec::new(),
+                globals: Vec::new(),
+                ecosystem: "abap".to_string(),
+                usage_patterns: vec![
+                    statement("select_single", "sql_select", r"(?i)^\s*SELECT\s+SINGLE\b", "database_query", vec![("fields", annotation("fields")), ("table", annotation("table")), ("condition", annotation("where"))]),
+                    statement("select_into_table", "sql_select", r"(?i)\b(?:INTO|APPENDING)\s+(?:CORRESPONDING\s+FIELDS\s+OF\s+)?TABLE\s+@?(?P<target>\w+)", "database_query", vec![("fields", annotation("fields")), ("table", annotation("table")), ("target", matched("target"))]),
+                    statement("insert", "sql_insert", r"(?i)\bINSERT\s+(?:INTO\s+)?(?P<table>\w+)\s+(?:FROM|VALUES)\s+@?(?P<source>\w+)", "database_insert", vec![("table", matched("table")), ("source", matched("source"))]),
+                    statement("update", "sql_update", r"(?i)\bUPDATE\s+(?P<table>\w+)\s+SET\s+(?P<assignments>[^.]*?)(?:\s+WHERE\s+(?P<condition>[^.]*))?\.?\s*$", "database_update", vec![("table", matched("table")), ("assignments", matched("assignments")), ("condition", matched("condition"))]),
+                    statement("delete", "sql_delete", r"(?i)\bDELETE\s+FROM\b", "database_delete", vec![("table", annotation("table")), ("condition", annotation("where"))]),
+                ],
+            },
+        ];
+        
+        self.patterns.insert(Language::Abap, patterns);
+    }
+}
+
+/// How narrowly a usage pattern picks out its code: whether it resolves through the library's
+/// imports, how telling its site is, and whether a text regex narrows it
+type Specificity = (bool, u8, bool);
+
+impl UsagePattern {
+    fn specificity(&self, pattern: &DetectionPattern) -> Specificity {
+        let site = match self.site {
+            // Bound to an instance of what the library exports
+            Site::Decorator(..) | Site::Chained(..) => 2,
+            Site::Call(_) | Site::Base(_) | Site::Annotated(_) | Site::Typed(_) | Site::Default(_) | Site::Member(_) => 1,
+            // Found by a name or a tag alone
+            Site::Method(_) | Site::Tagged(_) | Site::Prefixed(_) => 0,
+        };
+        (!pattern.modules.is_empty(), site, self.text.is_some())
+    }
+}
+
+/// Drop all but one of the usages found on the same code for the same intent, reporting each
+/// set; the usages of each library keep their order
+fn settle_conflicts(detected: &mut [(LibraryDependency, Vec<Specificity>)], priority: impl Fn(&str, &str) -> i32) -> Vec<PatternConflict> {
+    let mut sites = HashMap::new();
+    for (d, (dependency, _)) in detected.iter().enumerate() {
+        for (u, usage) in dependency.usage_patterns.iter().enumerate() {
+            sites.entry((&usage.source_location, usage.semantic_intent.as_str())).or_insert_with(Vec::new).push((d, u));
+        }
+    }
+    
+    let rank = |(d, u): (usize, usize)| {
+        let (dependency, specificities) = &detected[d];
+        (priority(&dependency.name, &dependency.usage_patterns[u].pattern_name), specificities[u])
+    };
+    let label = |(d, u): (usize, usize)| format!("{}:{}", detected[d].0.name, detected[d].0.usage_patterns[u].pattern_name);
+    let mut conflicts = Vec::new();
+    let mut dropped = HashSet::new();
+    for ((source_location, intent), mut usages) in sites {
+        if usages.len() < 2 {
+            continue;
+        }
+        // Found in library then document order; the sort keeps it among equals
+        usages.sort();
+        usages.sort_by_key(|&usage| std::cmp::Reverse(rank(usage)));
+        let (best, runner_up) = (rank(usages[0]), rank(usages[1]));
+        conflicts.push(PatternConflict {
+            source_location: source_location.clone(),
+            semantic_intent: intent.to_string(),
+            kept: label(usages[0]),
+            dropped: usages[1..].iter().map(|&usage| label(usage)).collect(),
+            reason: if best.0 != runner_up.0 {
+                ConflictReason::Priority
+            } else if best.1 != runner_up.1 {
+                ConflictReason::Specificity
+            } else {
+                ConflictReason::Order
+            },
+        });
+        dropped.extend(usages[1..].iter().copied());
+    }
+    conflicts.sort_by(|a, b| (position(&a.source_location), &a.kept).cmp(&(position(&b.source_location), &b.kept)));
+    
+    for (d, (dependency, _)) in detected.iter_mut().enumerate() {
+        let mut u = 0;
+        dependency.usage_patterns.retain(|_| {
+            u += 1;
+            !dropped.contains(&(d, u - 1))
+        });
+    }
+    conflicts
+}
+
+/// The languages whose code uses the libraries of a registry ecosystem
+pub(crate) fn ecosystem_languages(ecosystem: &str) -> Vec<Language> {
+    match ecosystem {
+        "javascript" => vec![Language::JavaScript],
+        "python" => vec![Language::Python],
+        "go" => vec![Language::Go],
+        "rust" => vec![Language::Rust],
+        "csharp" => vec![Language::CSharp],
+        "fsharp" => vec![Language::FSharp],
+        "java" => vec![Language::Java, Language::Kotlin],
+        "kotlin" => vec![Language::Kotlin],
+        "c" => vec![Language::C],
+        "cpp" => vec![Language::Cpp],
+        "r" => vec![Language::R],
+        "abap" => vec![Language::Abap],
+        _ => Vec::new(),
+    }
+}
+
+fn usage(name: &str, site: Site, intent: &str, parameters: Vec<(&str, Parameter)>) -> UsagePattern {
+    UsagePattern {
+        name: name.to_string(),
+        site,
+        text: None,
+        semantic_intent: intent.to_string(),
+        parameters: parameters.into_iter().map(|(name, parameter)| (name.to_string(), parameter)).collect(),
+    }
+}
+
+impl DetectionPattern {
+    /// Whether the module a name resolves to belongs to the library
+    fn provides(&self, module: &str) -> bool {
+        self.modules.iter().any(|provided| provided == module)
+    }
+    
+    /// Whether importing `path` brings in the library: one of its modules, or a package of one,
+    /// as `import django` brings in `django.db.models`
+    fn provides_path(&self, path: &str) -> bool {
+        self.modules.iter().any(|module| module == path || module.strip_prefix(path).is_some_and(|rest| rest.starts_with('.')))
+    }
+}
+
+impl Site {
+    fn find<'a>(&self, node: &'a UIRNode, parent: Option<&'a UIRNode>, imports: &Imports, pattern: &DetectionPattern) -> Option<Found<'a>> {
+        let exported = |written: &str, name: &str| {
+            let resolved = imports.resolve(written);
+            resolved.iter().any(|(module, member)| member == name && pattern.provides(module))
+                // What's built into the language is in scope wherever nothing shadows it
+                || (pattern.modules.is_empty() && written == name && !imports.bindings.contains_key(written))
+        };
+        match self {
+            Site::Call(name) if node.node_type == NodeType::Expression(ExpressionType::FunctionCall) => {
+                let written = callee(node)?;
+                exported(&written, name).then(|| Found {
+                    node,
+                    arguments: arguments(node),
+                    target: parent.and_then(target),
+                    destructured: Vec::new(),
+                    written,
+                })
+            }
+            // `new Command("sync")` calls the constructor of what it names
+            Site::Call(name) if has_tag(node, &["new_expression", "object_creation_expression"]) => {
+                let written = constructed(node)?;
+                let list = node.children.iter().find(|child| has_tag(child, &["arguments", "argument_list"]));
+                exported(&written, name).then(|| Found {
+                    node,
+                    arguments: list.map(arguments).unwrap_or_default(),
+                    target: parent.and_then(target),
+                    destructured: Vec::new(),
+                    written,
+                })
+            }
+            // `const [count, setCount] = useState(0)` keeps the call only as text
+            Site::Call(name) if has_tag(node, &["lexical_declaration", "variable_declaration"]) => {
+                let destructured: Vec<&UIRNode> = node.children.iter().filter(|child| has_tag(child, &["destructured"])).collect();
+                let call = destructured.first()?.metadata.annotations.get("destructured_from")?.as_str()?;
+                let (written, arguments) = text_call(call)?;
+                if !exported(&written, name) {
+                    return None;
+                }
+                Some(Found {
+                    node,
+                    arguments: arguments.into_iter().map(|argument| (None, argument)).collect(),
+                    target: None,
+                    destructured: destructured.iter()
+                        .filter_map(|child| {
+                            let position = child.metadata.annotations.get("destructuring_path")?.as_array()?.first()?.as_u64()?;
+                            Some((position as usize, child.name.clone()?))
+                        })
+                        .collect(),
+                    written,
+                })
+            }
+            Site::Base(name) if node.node_type == NodeType::Class => {
+                let base = bases(node).into_iter().find(|base| exported(base, name))?;
+                Some(Found { node, written: base, arguments: Vec::new(), target: None, destructured: Vec::new() })
+            }
+            Site::Typed(name) => {
+                let declared = match &node.node_type {
+                    NodeType::Property { property_type, .. } => property_type.clone(),
+                    NodeType::Field { field_type, .. } => field_type.clone(),
+                    NodeType::Variable => node.metadata.annotations.get("type").and_then(Value::as_str).map(String::from),
+                    _ => None,
+                }?;
+                let generic = declared.split('<').next().unwrap_or_default().trim();
+                exported(generic, name).then(|| Found { node, written: declared.clone(), arguments: Vec::new(), target: None, destructured: Vec::new() })
+            }
+            Site::Method(name) if node.node_type == NodeType::Expression(ExpressionType::FunctionCall) => {
+                let written = callee(node)?;
+                let called = written.split('<').next().unwrap_or_default().rsplit('.').next().unwrap_or_default();
+                (called == name).then(|| Found {
+                    node,
+                    arguments: arguments(node),
+                    target: parent.and_then(target),
+                    destructured: Vec::new(),
+                    written,
+                })
+            }
+            Site::Tagged(tag) if has_tag(node, &[tag.as_str()]) => {
+                let written = node.name.clone().unwrap_or_else(|| tag.clone());
+                Some(Found { node, written, arguments: Vec::new(), target: parent.and_then(target), destructured: Vec::new() })
+            }
+            Site::Decorator(class, method) if !node.attributes.is_empty() => {
+                let attribute = node.attributes.iter().find(|attribute| {
+                    let Some((instance, called)) = attribute.name.rsplit_once('.') else {
+                        return false;
+                    };
+                    called == method && imports.instances.get(instance).is_some_and(|made_by| exported(made_by, class))
+                })?;
+                Some(Found {
+                    node,
+                    written: attribute.name.clone(),
+                    arguments: attribute.arguments.iter().map(|argument| keyword_split(argument)).collect(),
+                    target: None,
+                    destructured: Vec::new(),
+                })
+            }
+            Site::Chained(class, method) if node.node_type == NodeType::Expression(ExpressionType::FunctionCall) => {
+                let written = callee(node)?;
+                let (instance, called) = written.rsplit_once('.')?;
+                if called != method {
+                    return None;
+                }
+                let made_by = match receiver(node) {
+                    Some(_) => {
+                        let root = chain_root(node);
+                        match &root.node_type {
+                            NodeType::Expression(ExpressionType::FunctionCall) => callee(root),
+                            // A chain can start with a module's function: `crypto.createHash("sha256").update(body)`
+                            _ => root.name.as_ref().and_then(|name| imports.instances.get(name)).cloned().or_else(|| callee(first_call(node))),
+                        }
+                    }
+                    // Calls the parser names, as Kotlin's `log.info`, keep their receiver as text
+                    None => imports.instances.get(instance).cloned(),
+                }?;
+                if !exported(&made_by, class) {
+                    return None;
+                }
+                Some(Found {
+                    node,
+                    arguments: arguments(node),
+                    target: parent.and_then(target),
+                    destructured: Vec::new(),
+                    written,
+                })
+            }
+            Site::Annotated(name) if !node.attributes.is_empty() => {
+                let attribute = node.attributes.iter().find(|attribute| exported(&attribute.name, name))?;
+                Some(Found {
+                    node,
+                    written: attribute.name.clone(),
+                    arguments: attribute.arguments.iter().map(|argument| keyword_split(argument)).collect(),
+                    target: None,
+                    destructured: Vec::new(),
+                })
+            }
+            Site::Default(name) if has_tag(node, &["parameter"]) => {
+                let default = node.metadata.annotations.get("default_value")?.as_str()?;
+                let (written, arguments) = text_call(default)?;
+                exported(&written, name).then(|| Found {
+                    node,
+                    arguments: arguments.iter().map(|argument| keyword_split(argument)).collect(),
+                    target: None,
+                    destructured: Vec::new(),
+                    written,
+                })
+            }
+            Site::Member(name) if has_tag(node, &["member_access_expression", "member_expression", "attribute", "field_expression", "navigation_expression"]) => {
+                // A callee is a call's, not a read
+                if parent.is_some_and(|parent| parent.node_type == NodeType::Expression(ExpressionType::FunctionCall) && parent.children.first().is_some_and(|first| std::ptr::eq(first, node))) {
+                    return None;
+                }
+                let written: String = original_text(node).chars().filter(|c| !c.is_whitespace()).collect();
+                exported(&written, name).then(|| Found { node, written, arguments: Vec::new(), target: parent.and_then(target), destructured: Vec::new() })
+            }
+            Site::Prefixed(prefix) if node.node_type == NodeType::Function => {
+                let written = node.name.clone()?;
+                (written.len() > prefix.len() && written.starts_with(prefix.as_str())).then(|| Found {
+                    node,
+                    written,
+                    arguments: Vec::new(),
+                    target: None,
+                    destructured: Vec::new(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Found<'_> {
+    /// The last segment of the callee, decorator or annotation, without type arguments
+    fn called(&self) -> &str {
+        let name = self.written.split('<').next().unwrap_or_default();
+        name.rsplit(['.', ':']).next().unwrap_or_default()
+    }
+    
+    /// A parameter's value, with the named groups the pattern's text regex captured
+    fn parameter(&self, parameter: &Parameter, captures: Option<&Captures>) -> Option<String> {
+        match parameter {
+            Parameter::Argument(index) => self.arguments.get(*index).map(|(_, value)| value.clone()),
+            Parameter::FirstName => self.arguments.iter().find_map(|(name, _)| name.clone()),
+            Parameter::FirstNamed => self.arguments.iter().find(|(name, _)| name.is_some()).map(|(_, value)| value.clone()),
+            Parameter::Keyword(keyword) => self.arguments.iter()
+                .find(|(name, _)| name.as_deref() == Some(keyword.as_str()))
+                .map(|(_, value)| value.clone())
+                .or_else(|| self.arguments.iter().find_map(|(_, value)| property(value, keyword))),
+            Parameter::Last => self.arguments.last().map(|(_, value)| value.clone()),
+            Parameter::Positional(index) => self.arguments.iter().filter(|(name, _)| name.is_none()).nth(*index).map(|(_, value)| value.clone()),
+            Parameter::Rest(skipped) => {
+                let mut positional = 0;
+                let rest: Vec<String> = self.arguments.iter()
+                    .filter_map(|(name, value)| match name {
+                        Some(name) => Some(format!("{}={}", name, value)),
+                        None => {
+                            positional += 1;
+                            (positional > *skipped).then(|| value.clone())
+                        }
+                    })
+                    .collect();
+                (!rest.is_empty()).then(|| rest.join(", "))
+            }
+            Parameter::LogLevel(style) => {
+                let called = self.called().to_lowercase();
+                let called = ["format", "ln", "f", "w"].into_iter()
+                    .find_map(|suffix| called.strip_suffix(suffix))
+                    .unwrap_or(&called);
+                let level = match called {
+                    "trace" => "trace",
+                    "debug" | "verbose" | "silly" => "debug",
+                    "info" | "log" | "print" | "notice" => "info",
+                    "warn" | "warning" => "warn",
+                    "error" | "exception" | "critical" | "fatal" | "dpanic" | "panic" => "error",
+                    _ => return None,
+                };
+                Some(match style {
+                    LevelStyle::Short => level.to_string(),
+                    LevelStyle::Title => level[..1].to_uppercase() + &level[1..],
+                    LevelStyle::Long => match level {
+                        "trace" => "debug".to_string(),
+                        "warn" => "warning".to_string(),
+                        level => level.to_string(),
+                    },
+                })
+            }
+            Parameter::Either(first, second) => self.parameter(first, captures).or_else(|| self.parameter(second, captures)),
+            Parameter::Literal(value) => Some(value.clone()),
+            Parameter::Receiver => match receiver(self.node) {
+                Some(receiver) => Some(original_text(receiver).to_string()),
+                // Calls the parser names, as Kotlin's `due.format(...)`, keep their receiver as text
+                None => self.written.rsplit_once('.').map(|(receiver, _)| receiver.to_string()).filter(|_| self.node.name.is_some()),
+            },
+            Parameter::RootArgument(index) => {
+                let root = match chain_root(self.node) {
+                    root if root.node_type == NodeType::Expression(ExpressionType::FunctionCall) => root,
+                    // Called on a module: `crypto.createHmac("sha256", secret)`
+                    _ => Some(first_call(self.node)).filter(|first| !std::ptr::eq(*first, self.node))?,
+                };
+                arguments(root).get(*index).map(|(_, value)| value.clone())
+            }
+            Parameter::Root => receiver(self.node)
+                .map(|_| chain_root(self.node))
+                .filter(|root| root.node_type != NodeType::Expression(ExpressionType::FunctionCall))
+                .map(|root| original_text(root).to_string()),
+            Parameter::Arguments => Some(self.arguments.iter().map(|(_, value)| value.as_str()).collect::<Vec<_>>().join(", ")),
+            Parameter::Target => self.target.clone(),
+            Parameter::Destructured(index) => self.destructured.iter().find(|(position, _)| position == index).map(|(_, name)| name.clone()),
+            Parameter::Declared => self.node.name.clone(),
+            Parameter::Annotation(key) => match self.node.metadata.annotations.get(key)? {
+                Value::String(value) => Some(value.clone()),
+                Value::Array(values) => Some(values.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(", ")),
+                value => Some(value.to_string()),
+            },
+            Parameter::Matched(group) => captures?.name(group).map(|value| value.as_str().trim().to_string()),
+            Parameter::Unquoted(parameter) => self.parameter(parameter, captures).map(|value| unquote(&value).to_string()),
+            Parameter::Unqualified(parameter) => self.parameter(parameter, captures).map(|value| value.rsplit(['.', ':']).next().unwrap_or_default().to_string()),
+            Parameter::RoutePath(parameter, style) => self.parameter(parameter, captures).map(|value| route_path(unquote(&value), *style)),
+            Parameter::TypeArgument(index) => {
+                let (_, arguments) = self.written.split_once('<')?;
+                split_top_level(arguments.strip_suffix('>')?).into_iter().nth(*index)
+            }
+            Parameter::Called => Some(self.called().to_string()),
+            Parameter::DateFormat(parameter, from, to) => self.parameter(parameter, captures).and_then(|value| date_format(unquote(&value), *from, *to)),
+            Parameter::TimeUnit(parameter) => self.parameter(parameter, captures).and_then(|value| time_unit(unquote(&value))).map(String::from),
+            Parameter::Replaced(parameter, from, to) => self.parameter(parameter, captures).map(|value| unquote(&value).replace(*from, &to.to_string())),
+            Parameter::Words(parameter, case) => {
+                let value = self.parameter(parameter, captures)?;
+                let mut words = words(unquote(&value));
+                if words.first().is_some_and(|first| first == "test") {
+                    words.remove(0);
+                }
+                let name = match case {
+                    NameCase::Sentence => words.join(" "),
+                    NameCase::Snake => words.join("_"),
+                    NameCase::Pascal => words.iter()
+                        .flat_map(|word| {
+                            let mut chars = word.chars();
+                            chars.next().into_iter().flat_map(char::to_uppercase).chain(chars)
+                        })
+                        .collect(),
+                };
+                Some(name)
+            }
+            Parameter::HttpMethod => {
+                let called = self.called().to_lowercase();
+                let verb = called.strip_suffix("mapping")
+                    .or_else(|| called.strip_prefix("http"))
+                    .or_else(|| called.strip_prefix("map"))
+                    .unwrap_or(&called);
+                let methods = match verb {
+                    "get" | "post" | "put" | "delete" | "patch" | "head" | "options" => verb.to_string(),
+                    _ => ["methods", "method", "type"].into_iter()
+                        .find_map(|keyword| self.parameter(&Parameter::Keyword(keyword.to_string()), captures))
+                        .map(|methods| {
+                            split_top_level(methods.trim_start_matches(['[', '{']).trim_end_matches([']', '}'])).iter()
+                                .map(|method| unquote(method.rsplit('.').next().unwrap_or_default()))
+                                .filter(|method| !method.is_empty())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        })
+                        .filter(|methods| !methods.is_empty())
+                        .unwrap_or_else(|| "GET".to_string()),
+                };
+                Some(methods.to_uppercase())
+            }
+        }
+    }
+}
+
+impl Imports {
+    fn collect(&mut self, node: &UIRNode, language: &Language) {
+        match &node.node_type {
+            NodeType::Import { path, alias, symbols } => {
+                // Rust's `std::fs` is dotted like every other path
+                let path = &if *language == Language::Rust { path.replace("::", ".") } else { path.clone() };
+                self.paths.push(path.clone());
+                // Headers, R packages and .NET namespaces bring in everything they declare
+                if matches!(language, Language::C | Language::Cpp | Language::R | Language::CSharp | Language::FSharp | Language::VisualBasic) {
+                    self.wildcards.push(path.clone());
+                }
+                match (alias, symbols.is_empty()) {
+                    (Some(alias), true) => {
+                        self.bindings.insert(alias.clone(), path.clone());
+                    }
+                    // `import org.springframework.stereotype.Service` binds `Service`
+                    (None, true) if matches!(language, Language::Java | Language::Kotlin) => {
+                        let last = path.rsplit('.').next().unwrap_or(path);
+                        self.bindings.insert(last.to_string(), path.clone());
+                    }
+                    // `import "net/http"` binds `http`
+                    (None, true) if *language == Language::Go => {
+                        let last = path.rsplit('/').next().unwrap_or(path);
+                        self.bindings.insert(last.to_string(), path.clone());
+                    }
+                    // `import django.db.models` binds `django`
+                    (None, true) => {
+                        let root = path.split('.').next().unwrap_or(path);
+                        self.bindings.entry(root.to_string()).or_insert_with(|| root.to_string());
+                    }
+                    _ => {}
+                }
+                for symbol in symbols {
+                    let local = symbol.alias.clone().unwrap_or_else(|| symbol.name.clone());
+                    match symbol.name.as_str() {
+                        "*" => self.wildcards.push(path.clone()),
+                        // `use std::fs::{self, OpenOptions}` binds `fs`
+                        "self" => {
+                            let last = path.rsplit('.').next().unwrap_or(path);
+                            self.bindings.insert(symbol.alias.clone().unwrap_or_else(|| last.to_string()), path.clone());
+                        }
+                        "default" => {
+                            self.bindings.insert(local, path.clone());
+                        }
+                        name => {
+                            self.bindings.insert(local, format!("{}.{}", path, name));
+                        }
+                    }
+                }
+            }
+            // `export { useState } from 'react'` depends on what it re-exports
+            _ if has_tag(node, &["export_statement"]) => {
+                if let Some(path) = node.children.iter().find_map(|child| match &child.literal {
+                    Some(coalesce_core::LiteralValue::String(path)) => Some(path.clone()),
+                    _ => None,
+                }) {
+                    self.paths.push(path);
+                }
+            }
+            // `const { useState } = require('react')`
+            NodeType::Variable if has_tag(node, &["destructured"]) => {
+                let from = node.metadata.annotations.get("destructured_from").and_then(Value::as_str).and_then(required);
+                let key = node.metadata.annotations.get("destructuring_path").and_then(Value::as_array).and_then(|path| path.first()?.as_str());
+                if let (Some(path), Some(key), Some(name)) = (from, key, &node.name) {
+                    self.paths.push(path.clone());
+                    self.bindings.insert(name.clone(), format!("{}.{}", path, key));
+                }
+            }
+            // `app = Flask(__name__)`
+            NodeType::Expression(ExpressionType::Assignment) => {
+                if let [variable, call] = node.children.as_slice() {
+                    if let (Some(name), Some(made_by)) = (&variable.name, callee(call).filter(|_| call.node_type == NodeType::Expression(ExpressionType::FunctionCall))) {
+                        self.instances.insert(name.clone(), made_by);
+                    }
+                }
+            }
+            // `let client = Client::new();`
+            _ if has_tag(node, &["let_declaration"]) => {
+                let name = node.children.iter().find(|child| child.node_type == NodeType::Expression(ExpressionType::Variable)).and_then(|name| name.name.as_ref());
+                let call = node.children.iter().find(|child| child.node_type == NodeType::Expression(ExpressionType::FunctionCall));
+                if let (Some(name), Some(made_by)) = (name, call.and_then(callee)) {
+                    self.instances.insert(name.clone(), made_by);
+                }
+            }
+            // `private static readonly ILog log = LogManager.GetLogger(typeof(A));`
+            NodeType::Field { default_value: Some(value), .. } => {
+                if let (Some((made_by, _)), Some(name)) = (text_call(value), &node.name) {
+                    self.instances.insert(name.clone(), made_by);
+                }
+            }
+            // `const React = require('react')`
+            NodeType::Variable => {
+                // Go's `logger, err := zap.NewProduction()` holds the call in a list, and binds the first name
+                let call = node.children.iter().find_map(|child| match child.node_type {
+                    NodeType::Expression(ExpressionType::FunctionCall) => Some(child),
+                    _ if has_tag(child, &["expression_list"]) => child.children.iter().find(|call| call.node_type == NodeType::Expression(ExpressionType::FunctionCall)),
+                    _ => None,
+                });
+                let name = node.name.as_ref().and_then(|name| name.split(',').next()).map(|name| name.trim().to_string());
+                if let (Some(path), Some(name)) = (call.and_then(|call| required(original_text(call))), &name) {
+                    self.paths.push(path.clone());
+                    self.bindings.insert(name.clone(), path);
+                } else if let (Some(made_by), Some(name)) = (call.and_then(callee).or_else(|| node.children.iter().find_map(constructed)), name) {
+                    // `const $items = $(".item")`, `const program = new Command()`
+                    self.instances.insert(name, made_by);
+                }
+            }
+            _ => {}
+        }
+        for child in &node.children {
+            self.collect(child, language);
+        }
+    }
+    
+    /// The modules and names a callee or base class as written may stand for: `m.CharField`
+    /// with `m` bound to `django.db.models` is `CharField` of `django.db.models`
+    fn resolve(&self, written: &str) -> Vec<(String, String)> {
+        // `Assert.Throws<FormatException>` resolves as `Assert.Throws`
+        let written = written.split('<').next().unwrap_or_default();
+        let written: String = written.chars().filter(|c| !c.is_whitespace()).collect::<String>().replace("::", ".");
+        let (root, rest) = match written.split_once('.') {
+            Some((root, rest)) => (root, Some(rest)),
+            None => (written.as_str(), None),
+        };
+        let full = match (self.bindings.get(root), rest) {
+            (Some(bound), Some(rest)) => format!("{}.{}", bound, rest),
+            // A module bound whole and called is its default export: `$` of `import $ from 'jquery'`
+            (Some(bound), None) if !bound.contains('.') => return vec![(bound.clone(), "default".to_string())],
+            (Some(bound), None) => bound.clone(),
+            // Qualified by the package itself, as R's `dplyr::filter`, or by a type of a module
+            // imported whole, as `LogManager.GetLogger` under C#'s `using log4net;`
+            (None, Some(_)) => {
+                return std::iter::once(written.clone())
+                    .chain(self.wildcards.iter().map(|module| format!("{}.{}", module, written)))
+                    .filter_map(|full| full.rsplit_once('.').map(|(module, name)| (module.to_string(), name.to_string())))
+                    .collect();
+            }
+            (None, None) => return self.wildcards.iter().map(|module| (module.clone(), written.clone())).collect(),
+        };
+        full.rsplit_once('.').map(|(module, name)| (module.to_string(), name.to_string())).into_iter().collect()
+    }
+}
+
+/// Visit every node with its parent and whether it is awaited; an awaited node's parent is
+/// that of its `await`, which takes what the node resolves to
+fn visit<'a>(node: &'a UIRNode, parent: Option<&'a UIRNode>, awaited: bool, f: &mut impl FnMut(&'a UIRNode, Option<&'a UIRNode>, bool)) {
+    f(node, parent, awaited);
+    let is_await = node.node_type == NodeType::Expression(ExpressionType::Await);
+    for child in &node.children {
+        match is_await {
+            true => visit(child, parent, true, f),
+            false => visit(child, Some(node), false, f),
+        }
+    }
+}
+
+/// What a constructor expression constructs, as written: `Command` of `new Command("sync")`
+fn constructed(node: &UIRNode) -> Option<String> {
+    if !has_tag(node, &["new_expression", "object_creation_expression"]) {
+        return None;
+    }
+    let constructed = node.children.iter().find(|child| !has_tag(child, &["new"]))?;
+    let written: String = original_text(constructed).chars().filter(|c| !c.is_whitespace()).collect();
+    (!written.is_empty()).then_some(written)
+}
+
+/// The base classes of a class as written: the parser records Python's, and C#'s stay in its
+/// base list
+fn bases(class: &UIRNode) -> Vec<String> {
+    let mut bases = class.metadata.dependencies.clone();
+    for list in class.children.iter().filter(|child| has_tag(child, &["base_list"])) {
+        bases.extend(list.children.iter().filter(|base| !has_tag(base, &[":", ","])).map(|base| original_text(base).to_string()));
+    }
+    bases
+}
+
+pub(crate) fn has_tag(node: &UIRNode, tags: &[&str]) -> bool {
+    node.metadata.semantic_tags.iter().any(|tag| tags.contains(&tag.as_str()))
+}
+
+fn original_text(node: &UIRNode) -> &str {
+    node.metadata.annotations.get("original_text").and_then(Value::as_str).unwrap_or_default()
+}
+
+/// The module of `require('react')`
+fn required(call: &str) -> Option<String> {
+    let argument = call.trim().strip_prefix("require")?.trim().strip_prefix('(')?.strip_suffix(')')?.trim();
+    let path = argument.strip_prefix(['\'', '"', '`'])?.strip_suffix(['\'', '"', '`'])?;
+    Some(path.to_string())
+}
+
+/// A call's callee as written, without the whitespace a multi-line call puts in it
+fn callee(call: &UIRNode) -> Option<String> {
+    let written = match &call.name {
+        Some(name) => name.as_str(),
+        None => match call.children.first()? {
+            callee if callee.children.is_empty() && callee.name.is_some() => callee.name.as_deref()?,
+            callee => original_text(callee),
+        },
+    };
+    let written: String = written.chars().filter(|c| !c.is_whitespace()).collect();
+    (!written.is_empty()).then_some(written)
+}
+
+/// A call's arguments as written, with the names of keyword and named arguments
+fn arguments(call: &UIRNode) -> Vec<(Option<String>, String)> {
+    // A macro's arguments are tokens; split them as text
+    if let Some(tokens) = call.children.iter().find(|child| has_tag(child, &["token_tree"])) {
+        let inner = original_text(tokens).get(1..original_text(tokens).len().saturating_sub(1)).unwrap_or_default();
+        return split_top_level(inner).iter().map(|argument| keyword_split(argument)).collect();
+    }
+    // Calls named by the parser hold only their arguments, but for Kotlin's callee
+    let named_callee = call.children.first().is_some_and(|first| Some(original_text(first)) == call.name.as_deref());
+    let mut arguments: Vec<&UIRNode> = call.children.iter().skip(usize::from(call.name.is_none() || named_callee)).collect();
+    if let [list] = arguments.as_slice() {
+        if has_tag(list, &["argument_list", "arguments"]) {
+            arguments = list.children.iter().collect();
+        }
+    }
+    arguments.into_iter()
+        .filter(|argument| !has_tag(argument, &["(", ")", ","]))
+        .map(|argument| {
+            if has_tag(argument, &["keyword_argument"]) {
+                let name = argument.children.first().and_then(|name| name.name.clone());
+                let value = argument.children.last().map(original_text).unwrap_or_default();
+                return (name, value.to_string());
+            }
+            let name = argument.metadata.annotations.get("argument_name").and_then(Value::as_str).map(String::from);
+            (name, original_text(argument).to_string())
+        })
+        .collect()
+}
+
+/// The variable a call's result is assigned to, from the node holding the call
+fn target(parent: &UIRNode) -> Option<String> {
+    let assigned = match parent.node_type {
+        NodeType::Variable => return parent.name.clone(),
+        NodeType::Expression(ExpressionType::Assignment) => true,
+        _ => has_tag(parent, &["init_declarator", "variable_declarator"]),
+    };
+    let target = parent.children.first().filter(|_| assigned)?;
+    target.name.clone().or_else(|| Some(original_text(target).to_string()))
+}
+
+/// The receiver of a method call: `$("#save")` of `$("#save").on(...)`
+fn receiver(call: &UIRNode) -> Option<&UIRNode> {
+    let member = call.children.first().filter(|_| call.name.is_none())?;
+    // Rust's `Path::new` is a path, not a method of `Path`
+    member.children.first().filter(|_| member.children.len() > 1 && !has_tag(member, &["scoped_identifier"]))
+}
+
+/// The call or variable a method chain starts with: `$("#save")` of `$("#save").hide().on(...)`
+fn chain_root(call: &UIRNode) -> &UIRNode {
+    let Some(mut inner) = receiver(call) else {
+        return call;
+    };
+    // Properties read along the chain, as jest's `expect(a).not.toBe(b)`, pass through
+    while has_tag(inner, &["member_expression"]) && inner.children.len() > 1 {
+        inner = &inner.children[0];
+    }
+    match inner {
+        inner if inner.node_type == NodeType::Expression(ExpressionType::FunctionCall) && receiver(inner).is_some() => chain_root(inner),
+        inner => inner,
+    }
+}
+
+/// The call a method chain starts with, whatever it's called on: `crypto.createHash("sha256")`
+/// of `crypto.createHash("sha256").update(body)`
+fn first_call(call: &UIRNode) -> &UIRNode {
+    match receiver(call) {
+        Some(inner) if inner.node_type == NodeType::Expression(ExpressionType::FunctionCall) => first_call(inner),
+        _ => call,
+    }
+}
+
+/// A property of an object literal written as text: `"/api"` of `{ url: "/api" }` for `url`
+fn property(object: &str, key: &str) -> Option<String> {
+    let inner = object.trim().strip_prefix('{')?.strip_suffix('}')?;
+    split_top_level(inner).into_iter().find_map(|pair| {
+        let (name, value) = pair.split_once(':')?;
+        (unquote(name) == key).then(|| value.trim().to_string())
+    })
+}
+
+/// The callee and arguments of a call written as text
+fn text_call(call: &str) -> Option<(String, Vec<String>)> {
+    let (written, rest) = call.split_once('(')?;
+    let written: String = written.chars().filter(|c| !c.is_whitespace()).collect();
+    let inner = rest.trim_end().strip_suffix(')')?;
+    Some((written, split_top_level(inner)))
+}
+
+/// An argument written as text, split into the name and value of a keyword argument
+fn keyword_split(argument: &str) -> (Option<String>, String) {
+    match argument.split_once('=') {
+        Some((name, value)) if !value.starts_with('=') && name.trim().chars().all(|c| c.is_alphanumeric() || c == '_') => {
+            (Some(name.trim().to_string()), value.trim().to_string())
+        }
+        _ => (None, argument.trim().to_string()),
+    }
+}
+
+/// A string literal's text without its quotes and prefix, or the text itself
+pub(crate) fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    let unprefixed = text.trim_start_matches(['r', 'b', 'f', 'u', 'R', 'B', 'F', 'U', '@']);
+    for quote in ["\"\"\"", "'''", "\"", "'", "`"] {
+        if let Some(inner) = unprefixed.strip_prefix(quote).and_then(|rest| rest.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    text
+}
+
+/// The lower-case words of a name or sentence: `adds`, `numbers` of `AddsNumbers`
+fn words(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        if !c.is_alphanumeric() {
+            previous = None;
+            continue;
+        }
+        let starts_word = match previous {
+            None => true,
+            Some(previous) => c.is_uppercase() && !previous.is_uppercase(),
+        };
+        match words.last_mut() {
+            Some(word) if !starts_word => word.extend(c.to_lowercase()),
+            _ => words.push(c.to_lowercase().collect()),
+        }
+        previous = Some(c);
+    }
+    words
+}
+
+/// A route path with its placeholders written in a style, from Flask's `<int:id>`, Spring's
+/// `{id}` and ASP.NET's `{id:int}`
+fn route_path(path: &str, style: PathStyle) -> String {
+    let mut written = String::new();
+    let mut rest = path;
+    while let Some(start) = rest.find(['<', '{']) {
+        let close = if rest[start..].starts_with('<') { '>' } else { '}' };
+        let Some(length) = rest[start..].find(close) else {
+            break;
+        };
+        let inner = &rest[start + 1..start + length];
+        let name = match close {
+            '>' => inner.rsplit(':').next().unwrap_or(inner),
+            _ => inner.split(':').next().unwrap_or(inner),
+        };
+        written.push_str(&rest[..start]);
+        match style {
+            PathStyle::Braces => written.push_str(&format!("{{{}}}", name)),
+            PathStyle::Colons => written.push_str(&format!(":{}", name)),
+            PathStyle::Angles => written.push_str(&format!("<{}>", name)),
+        }
+        rest = &rest[start + length + 1..];
+    }
+    written.push_str(rest);
+    written
+}
+
+/// A date format written in one style, in another: `dd/MM/yyyy 'at' HH:mm` of
+/// `%d/%m/%Y at %H:%M` from Python for Java; none when the format has a field the
+/// target can't write, or a letter or directive that isn't a field we know
+fn date_format(format: &str, from: DateStyle, to: DateStyle) -> Option<String> {
+    let strftime = |style: DateStyle| matches!(style, DateStyle::Python | DateStyle::Chrono);
+    let (from_index, to_index) = (from as usize, to as usize);
+    
+    // Read the format into fields, written in the target style, and literal characters
+    enum Piece {
+        Field(&'static str),
+        Literal(char),
+    }
+    let mut pieces = Vec::new();
+    let mut rest = format;
+    while let Some(c) = rest.chars().next() {
+        let close = match (from, c) {
+            (DateStyle::Moment, '[') => Some(']'),
+            (DateStyle::Java | DateStyle::DotNet, '\'') => Some('\''),
+            (DateStyle::DotNet, '"') => Some('"'),
+            _ => None,
+        };
+        if let Some(close) = close {
+            let inner = &rest[1..];
+            let length = inner.find(close).unwrap_or(inner.len());
+            pieces.extend(inner[..length].chars().map(Piece::Literal));
+            rest = inner.get(length + 1..).unwrap_or_default();
+            continue;
+        }
+        if strftime(from) && rest.starts_with("%%") {
+            pieces.push(Piece::Literal('%'));
+            rest = &rest[2..];
+            continue;
+        }
+        if let (DateStyle::DotNet, Some(escaped)) = (from, rest.strip_prefix('\\').and_then(|escaped| escaped.chars().next())) {
+            pieces.push(Piece::Literal(escaped));
+            rest = &rest[1 + escaped.len_utf8()..];
+            continue;
+        }
+        let read = DATE_FIELDS.iter()
+            .filter(|field| !field[from_index].is_empty() && rest.starts_with(field[from_index]))
+            .max_by_key(|field| field[from_index].len());
+        match read {
+            Some(field) if field[to_index].is_empty() => return None,
+            Some(field) => {
+                pieces.push(Piece::Field(field[to_index]));
+                rest = &rest[field[from_index].len()..];
+            }
+            // Unknown tokens such as moment's `Do` would come out as other fields or text
+            None if field_letter(from, c) => return None,
+            None if c == '%' && strftime(from) => return None,
+            None => {
+                pieces.push(Piece::Literal(c));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    
+    // Write them, quoting literal letters the style would read as fields
+    let mut written = String::new();
+    let mut letters = String::new();
+    let quote = |written: &mut String, letters: &mut String| {
+        if !letters.is_empty() {
+            match to {
+                DateStyle::Moment => written.push_str(&format!("[{}]", letters)),
+                _ => written.push_str(&format!("'{}'", letters)),
+            }
+            letters.clear();
+        }
+    };
+    for piece in pieces {
+        match piece {
+            Piece::Literal(c) if c.is_ascii_alphabetic() && !strftime(to) => letters.push(c),
+            piece => {
+                quote(&mut written, &mut letters);
+                match piece {
+                    Piece::Field(field) => written.push_str(field),
+                    Piece::Literal('%') if strftime(to) => written.push_str("%%"),
+                    Piece::Literal(c) => written.push(c),
+                }
+            }
+        }
+    }
+    quote(&mut written, &mut letters);
+    Some(written)
+}
+
+/// Whether the style reads a letter, written bare, as part of a field
+fn field_letter(style: DateStyle, c: char) -> bool {
+    match style {
+        DateStyle::Moment => "MQDdEeWwYyGgAaHhkmsSZXxNo".contains(c),
+        DateStyle::Java => c.is_ascii_alphabetic(),
+        DateStyle::DotNet => "dfFghHKmMstyz".contains(c),
+        DateStyle::Python | DateStyle::Chrono => false,
+    }
+}
+
+/// A unit of time as a plural: `days` of moment's `d`, Java's `plusDays` and C#'s `AddDays`
+fn time_unit(written: &str) -> Option<&'static str> {
+    // moment's shorthand tells months from minutes by case
+    match written {
+        "M" => return Some("months"),
+        "m" => return Some("minutes"),
+        _ => {}
+    }
+    let lower = written.to_lowercase();
+    let unit = ["plus", "add", "from", "of"].into_iter()
+        .find_map(|prefix| lower.strip_prefix(prefix).filter(|unit| !unit.is_empty()))
+        .unwrap_or(&lower);
+    Some(match unit {
+        "y" | "year" | "years" => "years",
+        "month" | "months" => "months",
+        "w" | "week" | "weeks" => "weeks",
+        "d" | "day" | "days" => "days",
+        "h" | "hour" | "hours" => "hours",
+        "minute" | "minutes" => "minutes",
+        "s" | "second" | "seconds" => "seconds",
+        "ms" | "millisecond" | "milliseconds" => "milliseconds",
+        _ => return None,
+    })
+}
+
+/// Split arguments written as text at the commas outside brackets and quotes
+pub(crate) fn split_top_level(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let (mut depth, mut quote, mut start) = (0i32, None, 0);
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(open), _) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(text[start..index].trim().to_string());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = text[start..].trim();
+    if !last.is_empty() {
+        parts.push(last.to_string());
+    }
+    parts
+}
+
+/// A node's byte range in the source, from its lines and columns
+pub(crate) fn byte_range(node: &UIRNode, line_starts: &[usize]) -> (usize, usize) {
+    node.source_location.as_ref().map_or((0, 0), |location| location_range(location, line_starts))
+}
+
+/// The byte range of a location in the text whose lines start at `line_starts`
+pub(crate) fn location_range(location: &SourceLocation, line_starts: &[usize]) -> (usize, usize) {
+    let offset = |line: u32, column: u32| line_starts.get(line.saturating_sub(1) as usize).map_or(0, |start| start + column as usize);
+    (offset(location.start_line, location.start_column), offset(location.end_line, location.end_column))
+}
+
+/// Where a location starts then ends, in document order
+fn position(location: &SourceLocation) -> (u32, u32, u32, u32) {
+    (location.start_line, location.start_column, location.end_line, location.end_column)
+}
+
+/// The text of a usage at `range` of the source, its first line where it spans several
+fn snippet(source: &str, (start, end): (usize, usize)) -> String {
+    let text = source.get(start..end).unwrap_or_default().trim();
+    match text.split_once('\n') {
+        Some((first, _)) => format!("{} ...", first.trim_end()),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConflictReason;
+    use coalesce_core::SourceLocation;
+    
+    /// A library's usages, each a pattern found on a line for an intent with its specificity
+    fn found(library: &str, usages: &[(&str, u32, &str, Specificity)]) -> (LibraryDependency, Vec<Specificity>) {
+        let dependency = LibraryDependency {
+            name: library.to_string(),
+            version: None,
+            ecosystem: "javascript".to_string(),
+            import_path: None,
+            usage_patterns: usages.iter().map(|&(pattern, line, intent, _)| LibraryUsage {
+                pattern_name: pattern.to_string(),
+                method_name: pattern.to_string(),
+                parameters: HashMap::new(),
+                semantic_intent: intent.to_string(),
+                source_location: SourceLocation { start_line: line, end_line: line, ..Default::default() },
+                snippet: String::new(),
+            }).collect(),
+        };
+        (dependency, usages.iter().map(|usage| usage.3).collect())
+    }
+    
+    fn settled(priority: impl Fn(&str, &str) -> i32) -> (Vec<PatternConflict>, Vec<Vec<String>>) {
+        let mut detected = vec![
+            found("native", &[("map", 1, "array_map", (false, 0, false)), ("each", 2, "array_each", (false, 0, false))]),
+            found("lodash", &[("map", 1, "array_map", (true, 1, false)), ("each", 2, "array_each", (true, 0, false))]),
+            found("underscore", &[("each", 2, "array_each", (true, 0, false)), ("size", 2, "collection_size", (true, 1, true))]),
+        ];
+        let conflicts = settle_conflicts(&mut detected, priority);
+        let kept = detected.iter()
+            .map(|(dependency, _)| dependency.usage_patterns.iter().map(|usage| usage.pattern_name.clone()).collect())
+            .collect();
+        (conflicts, kept)
+    }
+    
+    #[test]
+    fn test_more_specific_patterns_then_the_first_found_win() {
+        let (conflicts, kept) = settled(|_, _| 0);
+        let summary: Vec<(&str, &[String], ConflictReason)> = conflicts.iter()
+            .map(|conflict| (conflict.kept.as_str(), &conflict.dropped[..], conflict.reason))
+            .collect();
+        assert_eq!(summary, [
+            ("lodash:map", &["native:map".to_string()][..], ConflictReason::Specificity),
+            ("lodash:each", &["underscore:each".to_string(), "native:each".to_string()][..], ConflictReason::Order),
+        ]);
+        assert_eq!(kept, [vec![], vec!["map", "each"], vec!["size"]]);
+    }
+    
+    #[test]
+    fn test_priority_outranks_specificity() {
+        let (conflicts, kept) = settled(|library, _| if library == "native" { 1 } else { 0 });
+        assert!(conflicts.iter().all(|conflict| conflict.kept.starts_with("native:")), "{:?}", conflicts);
+        assert!(conflicts.iter().all(|conflict| conflict.reason == ConflictReason::Priority), "{:?}", conflicts);
+        assert_eq!(kept, [vec!["map", "each"], vec![], vec!["size"]]);
+    }
 }
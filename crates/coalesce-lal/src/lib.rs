@@ -2,18 +2,33 @@ pub mod registry;
 pub mod patterns;
 pub mod transformer;
 pub mod detector;
+pub mod packs;
+pub mod coverage;
+pub mod lint;
+pub mod manifest;
+pub mod template;
 
+use crate::coverage::CoverageReport;
+use crate::lint::{LintReport, PatternLinter};
+use crate::manifest::{DependencyManifest, TargetManifest};
 use crate::registry::LibraryRegistry;
 use crate::detector::DependencyDetector;
+use crate::packs::PatternPacks;
 use crate::transformer::LibraryTransformer;
-use coalesce_core::{UIRNode, Language, Result};
+use coalesce_core::{UIRNode, Language, Parser, Result, SourceLocation};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
 /// Main entry point for the Library Abstraction Layer
 pub struct LibraryAbstractionLayer {
     registry: LibraryRegistry,
     detector: DependencyDetector,
+    /// Patterns loaded over the built-in ones
+    project_patterns: usize,
+    /// Libraries the target project depends on
+    target_dependencies: Vec<String>,
 }
 
 /// Represents a detected library dependency
@@ -31,9 +46,63 @@ pub struct LibraryDependency {
 pub struct LibraryUsage {
     pub pattern_name: String,
     pub method_name: String,
+    #[serde(serialize_with = "coalesce_core::serialize_sorted")]
     pub parameters: HashMap<String, String>,
     pub semantic_intent: String,
-    pub source_location: (usize, usize), // (start, end)
+    /// Where the usage is: its lines, 1-based, and byte columns, 0-based
+    pub source_location: SourceLocation,
+    /// The source text of the usage, its first line where it spans several
+    pub snippet: String,
+}
+
+/// Usages of several patterns found on the same code for the same intent, of which detection
+/// keeps one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternConflict {
+    pub source_location: SourceLocation,
+    pub semantic_intent: String,
+    /// The usage kept, as `library:pattern`
+    pub kept: String,
+    /// The usages dropped, as `library:pattern`
+    pub dropped: Vec<String>,
+    /// What set the kept usage above the best of those dropped
+    pub reason: ConflictReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictReason {
+    Priority,
+    Specificity,
+    Order,
+}
+
+impl fmt::Display for ConflictReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConflictReason::Priority => "higher priority",
+            ConflictReason::Specificity => "more specific",
+            ConflictReason::Order => "found first",
+        })
+    }
+}
+
+/// The dependencies detection found in a module, and the conflicts it settled on the way
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Detection {
+    pub dependencies: Vec<LibraryDependency>,
+    pub conflicts: Vec<PatternConflict>,
+}
+
+impl Detection {
+    /// Name the file the module was read from in the locations of its usages and conflicts
+    pub fn in_file(mut self, file: &str) -> Self {
+        let usages = self.dependencies.iter_mut().flat_map(|dependency| &mut dependency.usage_patterns);
+        for location in usages.map(|usage| &mut usage.source_location).chain(self.conflicts.iter_mut().map(|conflict| &mut conflict.source_location)) {
+            location.file = file.to_string();
+        }
+        self
+    }
 }
 
 impl LibraryAbstractionLayer {
@@ -46,18 +115,127 @@ impl LibraryAbstractionLayer {
         Ok(Self {
             registry,
             detector,
+            project_patterns: 0,
+            target_dependencies: Vec::new(),
         })
     }
     
-    /// Analyze source code to detect library dependencies
-    pub fn analyze_dependencies(&self, code: &str, language: Language) -> Result<Vec<LibraryDependency>> {
-        self.detector.detect_dependencies(code, language)
+    /// The layer for the project at `root`: the built-in patterns, overridden in turn by the
+    /// pattern packs its `.coalesce/config.json` declares, the pattern files in
+    /// `.coalesce/patterns` and the `patterns` the config lists
+    pub fn new_for_project(root: impl AsRef<Path>) -> Result<Self> {
+        let dir = root.as_ref().join(".coalesce");
+        let config = dir.join("config.json");
+        let mut layer = Self::new()?;
+        if config.is_file() {
+            for pack in PatternPacks::from_config_file(&config)?.installed_dirs()? {
+                layer.load_patterns(pack)?;
+            }
+        }
+        if dir.join("patterns").is_dir() {
+            layer.load_patterns(dir.join("patterns"))?;
+        }
+        if config.is_file() {
+            layer.project_patterns += layer.registry.load_config(&config)?;
+            layer.detect_loaded()?;
+        }
+        Ok(layer)
+    }
+    
+    /// Merge the pattern files in `dir` (e.g. `.coalesce/patterns`) over the built-in patterns
+    pub fn load_patterns(&mut self, dir: impl AsRef<Path>) -> Result<usize> {
+        let loaded = self.registry.load_dir(dir)?;
+        self.project_patterns += loaded;
+        self.detect_loaded()?;
+        Ok(loaded)
+    }
+    
+    /// How many patterns were loaded over the built-in ones
+    pub fn project_patterns(&self) -> usize {
+        self.project_patterns
+    }
+    
+    /// Translate, where no ecosystem is given, to the libraries the target project at `root`
+    /// already depends on, as its manifest for `target_lang` lists them, e.g. to tokio when its
+    /// Cargo.toml has it. Returns the manifest read, if the project has one.
+    pub fn use_target_project(&mut self, root: impl AsRef<Path>, target_lang: &Language) -> Result<Option<PathBuf>> {
+        self.target_dependencies = manifest::dependency_names(&root, target_lang)?;
+        manifest::manifest_path(root, target_lang)
+    }
+    
+    /// Libraries the target project depends on, as `use_target_project` read them
+    pub fn target_dependencies(&self) -> &[String] {
+        &self.target_dependencies
+    }
+    
+    /// The project's dependency inventory: every library its manifests at `root` declare, with
+    /// the version declared and the usages of it among `detected`, used or not, then the
+    /// detected libraries no manifest declares, such as standard ones
+    pub fn inventory(&self, root: impl AsRef<Path>, detected: &[LibraryDependency]) -> Result<Vec<LibraryDependency>> {
+        let mut inventory: Vec<LibraryDependency> = Vec::new();
+        for manifest in DependencyManifest::find_all(root)? {
+            for declared in manifest.dependencies {
+                if inventory.iter().any(|known| known.name == declared.name && known.ecosystem == manifest.ecosystem) {
+                    continue;
+                }
+                inventory.push(LibraryDependency {
+                    name: declared.name,
+                    version: declared.version,
+                    ecosystem: manifest.ecosystem.clone(),
+                    import_path: None,
+                    usage_patterns: Vec::new(),
+                });
+            }
+        }
+        for dependency in detected {
+            let declared = inventory.iter_mut()
+                .find(|known| known.ecosystem == dependency.ecosystem && declares(&known.name, dependency));
+            match declared {
+                Some(declared) => {
+                    declared.import_path = declared.import_path.take().or_else(|| dependency.import_path.clone());
+                    declared.usage_patterns.extend(dependency.usage_patterns.iter().cloned());
+                }
+                None => inventory.push(dependency.clone()),
+            }
+        }
+        Ok(inventory)
+    }
+    
+    /// Lint pattern files, e.g. a pack before it's committed, against the registered patterns
+    pub fn lint_patterns(&self, paths: &[impl AsRef<Path>]) -> Result<LintReport> {
+        PatternLinter::new(&self.registry).lint_paths(paths)
+    }
+    
+    /// Teach the detector the loaded patterns that say how to find them
+    fn detect_loaded(&mut self) -> Result<()> {
+        for pattern in self.registry.detected_patterns() {
+            self.detector.register_pattern(pattern)?;
+        }
+        Ok(())
+    }
+    
+    /// Detect the library dependencies of a parsed module; `code` is the source it was parsed from
+    pub fn analyze_dependencies(&self, module: &UIRNode, code: &str) -> Result<Vec<LibraryDependency>> {
+        Ok(self.analyze(module, code)?.dependencies)
+    }
+    
+    /// Detect the library dependencies of a parsed module, settling patterns found on the same
+    /// code by the priorities of the registered patterns
+    pub fn analyze(&self, module: &UIRNode, code: &str) -> Result<Detection> {
+        self.detector.detect(module, code, |library, pattern| {
+            self.registry.get_pattern(library, pattern).map_or(0, |pattern| pattern.priority)
+        })
     }
     
     /// Enhance UIR nodes with library-specific metadata
     pub fn enhance_uir(&self, node: &mut UIRNode, deps: &[LibraryDependency]) -> Result<()> {
+        // Every dependency, so that the last doesn't hide the others
+        node.metadata.annotations.insert(
+            "library_dependencies".to_string(),
+            serde_json::Value::String(serde_json::to_string(deps)?),
+        );
         for dep in deps {
-            self.add_library_metadata(node, dep)?;
+            self.add_library_metadata(node, dep);
         }
         Ok(())
     }
@@ -69,22 +247,69 @@ impl LibraryAbstractionLayer {
         target_lang: Language,
         target_ecosystem: Option<&str>,
     ) -> Result<UIRNode> {
-        let transformer = LibraryTransformer::new(&self.registry);
+        let transformer = LibraryTransformer::new(&self.registry).with_installed(&self.target_dependencies);
         transformer.transform(node, target_lang, target_ecosystem)
     }
     
+    /// Replace library calls with the UIR of their translations, parsed by the target
+    /// language's `parser`; `code` is the source `node` was parsed from
+    pub fn rewrite_library_calls(
+        &self,
+        node: &UIRNode,
+        code: &str,
+        dependencies: &[LibraryDependency],
+        parser: &dyn Parser,
+        target_ecosystem: Option<&str>,
+    ) -> Result<UIRNode> {
+        LibraryTransformer::new(&self.registry).with_installed(&self.target_dependencies).rewrite(node, code, dependencies, parser, target_ecosystem)
+    }
+    
+    /// `rewrite_library_calls`, with how much of the dependencies' usage it translated: only
+    /// usages whose calls it replaced count
+    pub fn rewrite_library_calls_with_coverage(
+        &self,
+        node: &UIRNode,
+        code: &str,
+        dependencies: &[LibraryDependency],
+        parser: &dyn Parser,
+        target_ecosystem: Option<&str>,
+    ) -> Result<(UIRNode, CoverageReport)> {
+        LibraryTransformer::new(&self.registry).with_installed(&self.target_dependencies).rewrite_with_coverage(node, code, dependencies, parser, target_ecosystem)
+    }
+    
+    /// How much of the dependencies' usage has a translation to a target without manual work;
+    /// only `rewrite_library_calls` applies them
+    pub fn coverage(
+        &self,
+        dependencies: &[LibraryDependency],
+        target_lang: Language,
+        target_ecosystem: Option<&str>,
+    ) -> CoverageReport {
+        LibraryTransformer::new(&self.registry).with_installed(&self.target_dependencies).coverage(dependencies, &target_lang, target_ecosystem)
+    }
+    
+    /// The packages a target project needs for the translations of the dependencies, for its
+    /// manifest
+    pub fn target_manifest(
+        &self,
+        dependencies: &[LibraryDependency],
+        target_lang: Language,
+        target_ecosystem: Option<&str>,
+    ) -> TargetManifest {
+        LibraryTransformer::new(&self.registry).with_installed(&self.target_dependencies).target_manifest(dependencies, &target_lang, target_ecosystem)
+    }
+    
+    /// Side effects of the registered library patterns by name, for purity analysis
+    pub fn side_effects(&self) -> HashMap<String, Vec<String>> {
+        self.registry.side_effects()
+    }
+    
     /// Get available target ecosystems for a source library
     pub fn get_target_ecosystems(&self, source_library: &str) -> Vec<String> {
         self.registry.get_target_ecosystems(source_library)
     }
     
-    fn add_library_metadata(&self, node: &mut UIRNode, dep: &LibraryDependency) -> Result<()> {
-        // Add library information to node metadata
-        node.metadata.annotations.insert(
-            "library_dependency".to_string(),
-            serde_json::Value::String(serde_json::to_string(dep)?),
-        );
-        
+    fn add_library_metadata(&self, node: &mut UIRNode, dep: &LibraryDependency) {
         // Mark nodes that use library patterns
         for usage in &dep.usage_patterns {
             if let Some(ref node_name) = node.name {
@@ -101,12 +326,411 @@ impl LibraryAbstractionLayer {
             }
         }
         
-        Ok(())
     }
 }
 
+/// Whether a manifest's dependency of a name is a detected library: by the library's name, its
+/// package spelled `sea-orm` or `SQLAlchemy`, or its Go module, by path or last segment
+fn declares(name: &str, library: &LibraryDependency) -> bool {
+    let normalize = |name: &str| name.to_lowercase().replace('-', "_");
+    let module = library.import_path.as_deref()
+        .is_some_and(|path| path == name || path.strip_prefix(name).is_some_and(|rest| rest.starts_with('/')));
+    // `github.com/labstack/echo/v4` is echo
+    let last = name.rsplit('/').find(|segment| !(segment.starts_with('v') && segment[1..].parse::<u32>().is_ok()));
+    module || normalize(name) == normalize(&library.name) || last.is_some_and(|last| normalize(last) == normalize(&library.name))
+}
+
 impl Default for LibraryAbstractionLayer {
     fn default() -> Self {
         Self::new().expect("Failed to initialize LibraryAbstractionLayer")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::SuggestionType;
+    use coalesce_parser::JavaScriptParser;
+    
+    fn detect(lal: &LibraryAbstractionLayer, source: &str) -> Vec<LibraryDependency> {
+        let module = JavaScriptParser::new().unwrap().parse(source).unwrap();
+        lal.analyze_dependencies(&module, source).unwrap()
+    }
+    
+    #[test]
+    fn test_lodash_function_literal_is_manual_in_another_language() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let source = "const _ = require('lodash');\nconst a = _.map(items, x => x * 2);\nconst b = _.map(items, double);\n";
+        let dependencies = detect(&lal, source);
+        
+        let rust = lal.coverage(&dependencies, Language::Rust, None);
+        let translations: Vec<_> = rust.usages.iter().map(|usage| (usage.snippet.as_str(), usage.translation)).collect();
+        assert_eq!(translations, vec![
+            ("_.map(items, x => x * 2)", SuggestionType::ManualImplementation),
+            ("_.map(items, double)", SuggestionType::DirectTransform),
+        ]);
+        // JavaScript takes the arrow as it is
+        assert_eq!(lal.coverage(&dependencies, Language::JavaScript, None).manual, 0);
+    }
+    
+    #[test]
+    fn test_coverage_counts_only_calls_the_rewrite_replaced() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let source = "const _ = require('lodash');\nconst c = _.cloneDeep(a);\nconst d = _.map(_.filter(items, keep), double);\n";
+        let module = JavaScriptParser::new().unwrap().parse(source).unwrap();
+        let dependencies = lal.analyze_dependencies(&module, source).unwrap();
+        // Every call has a rule for Rust
+        assert_eq!(lal.coverage(&dependencies, Language::Rust, None).manual, 0);
+        
+        let parser = coalesce_parser::create_parser(Language::Rust).unwrap();
+        let (rewritten, coverage) = lal.rewrite_library_calls_with_coverage(&module, source, &dependencies, parser.as_ref(), None).unwrap();
+        let mut translated = Vec::new();
+        coalesce_core::walk(&rewritten, &mut |node: &UIRNode| {
+            if let Some(to) = node.metadata.annotations.get("transformed_to") {
+                translated.push(to.as_str().unwrap_or_default().to_string());
+            }
+            coalesce_core::Walk::Continue
+        });
+        assert_eq!(translated, vec!["rust:Clone::clone", "rust:Iterator::map"]);
+        // The filter went into the map's arguments as it was written
+        let manual: Vec<_> = coverage.usages.iter().filter(|usage| usage.target.is_none()).map(|usage| usage.snippet.as_str()).collect();
+        assert_eq!(manual, vec!["_.filter(items, keep)"]);
+        assert_eq!((coverage.automatic, coverage.manual), (2, 1));
+    }
+    
+    /// The `required_imports` generators import for, over the nodes of `node`
+    fn required_imports(node: &UIRNode) -> Vec<String> {
+        let mut imports = Vec::new();
+        coalesce_core::walk(node, &mut |node: &UIRNode| {
+            if let Some(required) = node.metadata.annotations.get("required_imports").and_then(|required| required.as_str()) {
+                imports.extend(serde_json::from_str::<Vec<String>>(required).unwrap());
+            }
+            coalesce_core::Walk::Continue
+        });
+        imports
+    }
+    
+    #[test]
+    fn test_imports_are_required_only_where_a_call_was_rewritten() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let source = "function f(x) {\n  console.log('hi', x);\n}\n";
+        let mut module = JavaScriptParser::new().unwrap().parse(source).unwrap();
+        let dependencies = detect(&lal, source);
+        lal.enhance_uir(&mut module, &dependencies).unwrap();
+        
+        // Annotated calls stay as written
+        let annotated = lal.transform_library_calls(&module, Language::Python, None).unwrap();
+        assert!(required_imports(&annotated).is_empty());
+        
+        let parser = coalesce_parser::create_parser(Language::Python).unwrap();
+        let rewritten = lal.rewrite_library_calls(&module, source, &dependencies, parser.as_ref(), None).unwrap();
+        assert_eq!(required_imports(&rewritten), vec!["import logging"]);
+    }
+    
+    /// The code annotating the calls of `node` translate to
+    fn generated_code(node: &UIRNode) -> Vec<String> {
+        let mut code = Vec::new();
+        coalesce_core::walk(node, &mut |node: &UIRNode| {
+            if let Some(generated) = node.metadata.annotations.get("generated_code").and_then(|generated| generated.as_str()) {
+                code.push(generated.to_string());
+            }
+            coalesce_core::Walk::Continue
+        });
+        code
+    }
+    
+    #[test]
+    fn test_route_keeps_every_http_method() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let source = "from flask import Flask\napp = Flask(__name__)\n\n@app.route(\"/users/<int:id>\", methods=[\"GET\", \"POST\"])\ndef user(id):\n    return id\n";
+        let mut module = coalesce_parser::PythonParser::new().unwrap().parse(source).unwrap();
+        let dependencies = lal.analyze_dependencies(&module, source).unwrap();
+        let route = dependencies[0].usage_patterns.iter().find(|usage| usage.pattern_name == "route").unwrap();
+        assert_eq!(route.parameters["method"], "GET, POST");
+        lal.enhance_uir(&mut module, &dependencies).unwrap();
+        
+        let axum = generated_code(&lal.transform_library_calls(&module, Language::Rust, Some("axum")).unwrap());
+        assert!(axum.contains(&".route(\"/users/{id}\", on(MethodFilter::GET.or(MethodFilter::POST), user))".to_string()), "{:?}", axum);
+        let gin = generated_code(&lal.transform_library_calls(&module, Language::Go, Some("gin")).unwrap());
+        assert!(gin.contains(&"router.Match([]string{ \"GET\", \"POST\" }, \"/users/:id\", user)".to_string()), "{:?}", gin);
+    }
+    
+    #[test]
+    fn test_lodash_python_rules_are_not_go() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let dependencies = detect(&lal, "const _ = require('lodash');\nconst c = _.cloneDeep(a);\n");
+        
+        let python = lal.coverage(&dependencies, Language::Python, None);
+        assert_eq!(python.usages[0].target.as_deref(), Some("python:copy.deepcopy"));
+        let go = lal.coverage(&dependencies, Language::Go, None);
+        assert_eq!(go.usages[0].translation, SuggestionType::ManualImplementation);
+    }
+    
+    #[test]
+    fn test_coverage_without_library_calls_has_no_share_or_confidence() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let none = lal.coverage(&[], Language::Python, None);
+        assert_eq!((none.percent(), none.confidence()), (None, None));
+        
+        let dependencies = detect(&lal, "const _ = require('lodash');\nconst c = _.cloneDeep(a);\n");
+        let go = lal.coverage(&dependencies, Language::Go, None);
+        assert_eq!(go.percent(), Some(0.0));
+    }
+    
+    #[test]
+    fn test_date_format_without_a_field_in_the_target_is_manual() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let source = "const moment = require('moment');\nconst a = moment(d).format('YYYY-MM-DD HH:mm');\nconst b = moment(d).format('D/M/YYYY');\nconst c = moment(d).format('Do MMMM');\n";
+        let dependencies = detect(&lal, source);
+        let format = |snippet: &str, key: &str| {
+            let usage = dependencies[0].usage_patterns.iter().find(|usage| usage.snippet.contains(snippet)).unwrap();
+            usage.parameters.get(key).cloned()
+        };
+        assert_eq!(format("'YYYY-MM-DD HH:mm'", "format_python").as_deref(), Some("%Y-%m-%d %H:%M"));
+        // Python's strftime has no portable unpadded day, chrono has
+        assert_eq!(format("'D/M/YYYY'", "format_python"), None);
+        assert_eq!(format("'D/M/YYYY'", "format_chrono").as_deref(), Some("%-d/%-m/%Y"));
+        // An ordinal day isn't a field any other style writes
+        assert_eq!(format("'Do MMMM'", "format_java"), None);
+        
+        let python: Vec<_> = lal.coverage(&dependencies, Language::Python, None).usages.iter().map(|usage| usage.translation).collect();
+        assert_eq!(python, vec![SuggestionType::DirectTransform, SuggestionType::ManualImplementation, SuggestionType::ManualImplementation]);
+    }
+    
+    /// The code the calls of a JavaScript module translate to for a target
+    fn translate(lal: &LibraryAbstractionLayer, source: &str, target: Language) -> Vec<String> {
+        let mut module = JavaScriptParser::new().unwrap().parse(source).unwrap();
+        let dependencies = detect(lal, source);
+        lal.enhance_uir(&mut module, &dependencies).unwrap();
+        generated_code(&lal.transform_library_calls(&module, target, None).unwrap())
+    }
+    
+    #[test]
+    fn test_log_messages_take_the_target_string_quotes() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let source = "const winston = require('winston');\nconsole.log('hi', x);\nwinston.error(message);\n";
+        
+        assert_eq!(translate(&lal, source, Language::Python), vec!["logger.info('hi')", "logger.error(message)"]);
+        assert_eq!(translate(&lal, source, Language::Rust), vec!["tracing::info!(\"hi\");", "tracing::error!(message);"]);
+        assert_eq!(translate(&lal, source, Language::Go), vec!["log.Println(\"info:\", \"hi\")", "log.Println(\"error:\", message)"]);
+    }
+    
+    #[test]
+    fn test_lodash_calls_render_in_the_target() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let source = "const _ = require('lodash');\nconst a = _.cloneDeep(x);\nconst b = _.map(items, double);\n";
+        
+        assert_eq!(translate(&lal, source, Language::Python), vec!["copy.deepcopy(x)", "list(map(double, items))"]);
+        assert_eq!(translate(&lal, source, Language::Rust), vec!["x.clone()", "items.iter().map(double).collect::<Vec<_>>()"]);
+    }
+    
+    #[test]
+    fn test_flask_app_and_responses_render_for_each_framework() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let source = "from flask import Flask, jsonify\napp = Flask(__name__)\n\n@app.route('/users/<int:id>')\ndef user(id):\n    return jsonify({'id': id})\n";
+        let mut module = coalesce_parser::PythonParser::new().unwrap().parse(source).unwrap();
+        let dependencies = lal.analyze_dependencies(&module, source).unwrap();
+        lal.enhance_uir(&mut module, &dependencies).unwrap();
+        
+        let axum = generated_code(&lal.transform_library_calls(&module, Language::Rust, Some("axum")).unwrap());
+        assert_eq!(axum[..2], ["let app = Router::new();", ".route(\"/users/{id}\", on(MethodFilter::GET, user))"]);
+        // Arguments stay as written until the call is rewritten
+        assert_eq!(axum[2], "Json({'id': id})");
+        let gin = generated_code(&lal.transform_library_calls(&module, Language::Go, Some("gin")).unwrap());
+        assert_eq!(gin[..2], ["app := gin.Default()", "router.Match([]string{ \"GET\" }, \"/users/:id\", user)"]);
+        assert_eq!(gin[2], "c.JSON(http.StatusOK, {'id': id})");
+    }
+    
+    #[test]
+    fn test_dates_render_with_the_target_format() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let source = "const moment = require('moment');\nconst s = moment(d).format('YYYY-MM-DD');\nconst now = moment();\n";
+        
+        assert_eq!(translate(&lal, source, Language::Python), vec!["d.strftime(\"%Y-%m-%d\")", "datetime.now()"]);
+        assert_eq!(translate(&lal, source, Language::Rust), vec!["d.format(\"%Y-%m-%d\").to_string()", "Local::now()"]);
+        
+        let source = "from datetime import datetime\ns = now.strftime('%Y-%m-%d')\n";
+        let mut module = coalesce_parser::PythonParser::new().unwrap().parse(source).unwrap();
+        let dependencies = lal.analyze_dependencies(&module, source).unwrap();
+        lal.enhance_uir(&mut module, &dependencies).unwrap();
+        let javascript = generated_code(&lal.transform_library_calls(&module, Language::JavaScript, None).unwrap());
+        assert_eq!(javascript, vec!["dayjs(now).format(\"YYYY-MM-DD\")"]);
+    }
+    
+    /// The code a JavaScript module migrates to with its library calls rewritten
+    fn migrate(lal: &LibraryAbstractionLayer, source: &str, target: Language) -> String {
+        use coalesce_core::Generator;
+        let module = JavaScriptParser::new().unwrap().parse(source).unwrap();
+        let dependencies = lal.analyze_dependencies(&module, source).unwrap();
+        let parser = coalesce_parser::create_parser(target.clone()).unwrap();
+        let rewritten = lal.rewrite_library_calls(&module, source, &dependencies, parser.as_ref(), None).unwrap();
+        let generator: Box<dyn Generator> = match target {
+            Language::Python => Box::new(coalesce_gen::PythonGenerator),
+            Language::Rust => Box::new(coalesce_gen::RustGenerator),
+            Language::Go => Box::new(coalesce_gen::GoGenerator),
+            _ => unreachable!(),
+        };
+        generator.generate(&rewritten).unwrap()
+    }
+    
+    #[test]
+    fn test_formatting_a_date_made_now_formats_the_target_now() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let source = "const moment = require('moment');\nconst stamp = moment().format('YYYY-MM-DD');\n";
+        
+        let python = migrate(&lal, source, Language::Python);
+        assert!(python.contains("stamp = datetime.now().strftime(\"%Y-%m-%d\")"), "{}", python);
+        assert!(!python.contains("moment"), "{}", python);
+        let rust = migrate(&lal, source, Language::Rust);
+        assert!(rust.contains("Local::now().format(\"%Y-%m-%d\").to_string()"), "{}", rust);
+        assert!(!rust.contains("moment"), "{}", rust);
+    }
+    
+    /// A project whose `.coalesce` holds `patterns/lodash.yaml` and `config.json`
+    fn project(name: &str, patterns_dir: &str, config: serde_json::Value) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("coalesce-project-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join(".coalesce/patterns")).unwrap();
+        std::fs::write(dir.join(".coalesce/patterns/lodash.yaml"), patterns_dir).unwrap();
+        std::fs::write(dir.join(".coalesce/config.json"), config.to_string()).unwrap();
+        dir
+    }
+    
+    /// lodash's `cloneDeep` translating to Python with `template`
+    fn clone_deep(template: &str) -> serde_json::Value {
+        serde_json::json!({
+            "name": "cloneDeep",
+            "library": "lodash",
+            "ecosystem": "javascript",
+            "semantics": { "intent": "deep_copy", "category": "data" },
+            "parameters": [{ "name": "value", "param_type": "any", "required": true, "default_value": null }],
+            "transformations": {
+                "stdlib": { "target_library": "copy", "target_pattern": "copy", "template": template, "setup_code": null, "cleanup_code": null },
+            },
+        })
+    }
+    
+    #[test]
+    fn test_project_patterns_override_built_ins_and_map_in_house_calls() {
+        let telemetry = serde_json::json!({
+            "name": "track",
+            "library": "acme-telemetry",
+            "ecosystem": "javascript",
+            "semantics": { "intent": "track_event", "category": "telemetry" },
+            "parameters": [{ "name": "event", "param_type": "string", "required": true, "default_value": null }],
+            "detection": { "modules": ["@acme/telemetry"], "call": "track" },
+            "transformations": {
+                "stdlib": { "target_library": "acme_telemetry", "target_pattern": "track", "template": "acme_telemetry.track({{event}})", "setup_code": null, "cleanup_code": null },
+            },
+        });
+        let overridden = serde_yaml::to_string(&clone_deep("copy.copy({{value}})")).unwrap();
+        let root = project("overrides", &overridden, serde_json::json!({ "patterns": [telemetry] }));
+        let lal = LibraryAbstractionLayer::new_for_project(&root).unwrap();
+        assert_eq!(lal.project_patterns(), 2);
+        
+        let source = "import { track } from '@acme/telemetry';\nconst _ = require('lodash');\ntrack('signup');\nconst b = _.cloneDeep(a);\n";
+        assert_eq!(translate(&lal, source, Language::Python), vec!["acme_telemetry.track('signup')", "copy.copy(a)"]);
+        // Built-in rules the override leaves out are gone with the pattern they were part of
+        assert_eq!(translate(&lal, source, Language::Rust), Vec::<String>::new());
+        assert_eq!(LibraryAbstractionLayer::new_for_project(root.join("missing")).unwrap().project_patterns(), 0);
+    }
+    
+    #[test]
+    fn test_project_config_patterns_override_pattern_files() {
+        let from_file = serde_yaml::to_string(&clone_deep("copy.copy({{value}})")).unwrap();
+        let root = project("precedence", &from_file, serde_json::json!({ "patterns": [clone_deep("deepcopy({{value}})")] }));
+        let lal = LibraryAbstractionLayer::new_for_project(&root).unwrap();
+        let source = "const _ = require('lodash');\nconst b = _.cloneDeep(a);\n";
+        assert_eq!(translate(&lal, source, Language::Python), vec!["deepcopy(a)"]);
+    }
+    
+    /// A target project holding only the manifest `name` with `content`
+    fn target_project(name: &str, content: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("coalesce-target-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(name), content).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn test_translations_pick_the_libraries_the_target_project_depends_on() {
+        let source = "const axios = require('axios');\nconsole.log('hi');\nconst res = axios.get(url);\n";
+        let mut lal = LibraryAbstractionLayer::new().unwrap();
+        assert_eq!(translate(&lal, source, Language::Python), vec!["logger.info('hi')", "httpx.request(\"GET\", url)"]);
+        
+        let python = target_project("requirements.txt", "requests==2.31.0\n");
+        assert_eq!(lal.use_target_project(&python, &Language::Python).unwrap(), Some(python.join("requirements.txt")));
+        assert_eq!(lal.target_dependencies(), ["requests"]);
+        assert_eq!(translate(&lal, source, Language::Python), vec!["logger.info('hi')", "requests.request(\"GET\", url)"]);
+        
+        // Go modules are named by their path
+        let go = target_project("go.mod", "module example.com/app\n\nrequire go.uber.org/zap v1.27.0\n");
+        lal.use_target_project(&go, &Language::Go).unwrap();
+        assert_eq!(translate(&lal, source, Language::Go)[0], "logger.Info(\"hi\")");
+        
+        // An ecosystem given outranks the project's
+        let mut module = JavaScriptParser::new().unwrap().parse(source).unwrap();
+        lal.enhance_uir(&mut module, &detect(&lal, source)).unwrap();
+        let log = generated_code(&lal.transform_library_calls(&module, Language::Go, Some("log")).unwrap());
+        assert_eq!(log[0], "log.Println(\"info:\", \"hi\")");
+        
+        assert_eq!(lal.use_target_project(python.join("missing"), &Language::Go).unwrap(), None);
+        assert!(lal.target_dependencies().is_empty());
+    }
+    
+    const AXIOS_CLIENT: &str = "import axios from 'axios';\n\nasync function load(url) {\n  const res = await axios.get(url);\n  return res.data;\n}\n\nasync function save(url, item) {\n  await axios.post(url, item);\n}\n";
+    
+    #[test]
+    fn test_target_manifest_lists_the_packages_of_translated_calls_only() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let module = JavaScriptParser::new().unwrap().parse(AXIOS_CLIENT).unwrap();
+        let dependencies = lal.analyze_dependencies(&module, AXIOS_CLIENT).unwrap();
+        let parser = coalesce_parser::create_parser(Language::Python).unwrap();
+        let (_, coverage) = lal.rewrite_library_calls_with_coverage(&module, AXIOS_CLIENT, &dependencies, parser.as_ref(), None).unwrap();
+        let translated = coverage.translated_dependencies(&dependencies);
+        let packages: Vec<String> = lal.target_manifest(&translated, Language::Python, None).packages.into_iter().map(|package| package.name).collect();
+        assert_eq!(packages, vec!["httpx"]);
+        
+        // Calls only annotated are left as written: nothing to install for them
+        let annotated = lal.coverage(&dependencies, Language::Python, None).only_applied(|_| false);
+        assert!(annotated.translated_dependencies(&dependencies).is_empty());
+    }
+    
+    #[test]
+    fn test_awaited_requests_migrate_to_python_with_an_async_client() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let python = migrate(&lal, AXIOS_CLIENT, Language::Python);
+        assert!(python.contains("import httpx"));
+        assert!(python.contains("    res = await httpx.AsyncClient().request(\"GET\", url)\n    return res.data"));
+        assert!(python.contains("    await httpx.AsyncClient().request(\"POST\", url, json=item)"));
+        assert!(!python.contains("requests"));
+    }
+    
+    #[test]
+    fn test_awaited_requests_migrate_to_rust_awaiting_once() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let rust = migrate(&lal, AXIOS_CLIENT, Language::Rust);
+        assert!(rust.contains("    let res = reqwest::Client::new().request(reqwest::Method::GET, url).send().await?;\n    res.data"));
+        assert!(rust.contains("    reqwest::Client::new().request(reqwest::Method::POST, url).json(&item).send().await?;"));
+        assert!(!rust.contains(".await.await") && !rust.contains(".?"));
+    }
+    
+    #[test]
+    fn test_awaited_requests_migrate_to_go_binding_the_target() {
+        let lal = LibraryAbstractionLayer::new().unwrap();
+        let go = migrate(&lal, AXIOS_CLIENT, Language::Go);
+        assert!(go.contains(concat!(
+            "func load(url int) int {\n",
+            "    var res *http.Response\n",
+            "    req, err := http.NewRequest(\"GET\", url, bytes.NewReader(nil))\n",
+            "    if err == nil {\n",
+            "        res, err = http.DefaultClient.Do(req)\n",
+            "    }\n",
+            "    return res.data\n",
+        )));
+        // Without a target the response goes unused
+        assert!(go.contains("func save(url int, item int) {\n    req, err := http.NewRequest(\"POST\", url, bytes.NewReader(item))"));
+        assert!(go.contains("        _, err = http.DefaultClient.Do(req)"));
+    }
+}
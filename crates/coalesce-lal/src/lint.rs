@@ -0,0 +1,291 @@
+// Pattern linting: mistakes in pattern files that loading lets through but translation trips over
+
+use crate::detector::ecosystem_languages;
+use crate::patterns::{LibraryPattern, TransformRule};
+use crate::registry::{is_pattern_file, parse_patterns, validate, LibraryRegistry};
+use crate::template::placeholders;
+use coalesce_core::Result;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The file or pattern won't load
+    Error,
+    /// It loads, but translates wrongly or not at all
+    Warning,
+}
+
+/// A problem with a pattern file, or with one pattern in it
+#[derive(Debug, Clone, Serialize)]
+pub struct LintIssue {
+    pub file: PathBuf,
+    /// The pattern, as `library:name`; none for a file that doesn't parse
+    pub pattern: Option<String>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// The issues found in the linted pattern files
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LintReport {
+    pub files: usize,
+    pub patterns: usize,
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    pub fn errors(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Error).count()
+    }
+    
+    pub fn warnings(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Warning).count()
+    }
+}
+
+/// Lints pattern files against what a registry already knows: its libraries, their ecosystems
+/// and the targets of their rules
+pub struct PatternLinter<'a> {
+    registry: &'a LibraryRegistry,
+}
+
+impl<'a> PatternLinter<'a> {
+    pub fn new(registry: &'a LibraryRegistry) -> Self {
+        Self { registry }
+    }
+    
+    /// Lint the pattern files at each path, a `*.yaml`/`*.yml`/`*.toml` file or a directory of
+    /// them
+    pub fn lint_paths(&self, paths: &[impl AsRef<Path>]) -> Result<LintReport> {
+        let mut report = LintReport::default();
+        let mut files = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            if !path.exists() {
+                report.issues.push(LintIssue {
+                    file: path.to_path_buf(),
+                    pattern: None,
+                    severity: Severity::Error,
+                    message: "no such file or directory".to_string(),
+                });
+            } else if !path.is_dir() {
+                files.push(path.to_path_buf());
+            } else {
+                let mut found = Vec::new();
+                for entry in fs::read_dir(path)? {
+                    let file = entry?.path();
+                    if is_pattern_file(&file) && file.is_file() {
+                        found.push(file);
+                    }
+                }
+                found.sort();
+                files.extend(found);
+            }
+        }
+        
+        report.files = files.len();
+        let mut loaded = Vec::new();
+        for file in files {
+            match parse_patterns(&file, &fs::read_to_string(&file)?) {
+                Ok(patterns) => loaded.extend(patterns.into_iter().map(|pattern| (file.clone(), pattern))),
+                Err(e) => report.issues.push(LintIssue {
+                    file,
+                    pattern: None,
+                    severity: Severity::Error,
+                    message: e,
+                }),
+            }
+        }
+        report.patterns = loaded.len();
+        
+        // The linted patterns may target each other
+        let mut known: HashSet<&str> = self.registry.known_ecosystems();
+        known.extend(loaded.iter().map(|(_, pattern)| pattern.library.as_str()));
+        let mut seen: HashMap<(&str, &str), &Path> = HashMap::new();
+        for (file, pattern) in &loaded {
+            let mut issues = Vec::new();
+            if let Some(first) = seen.insert((&pattern.library, &pattern.name), file) {
+                issues.push((Severity::Error, format!("already defined in {}", first.display())));
+            }
+            issues.extend(self.lint_pattern(pattern, &known));
+            report.issues.extend(issues.into_iter().map(|(severity, message)| LintIssue {
+                file: file.clone(),
+                pattern: Some(format!("{}:{}", pattern.library, pattern.name)),
+                severity,
+                message,
+            }));
+        }
+        Ok(report)
+    }
+    
+    /// The issues of one pattern: what keeps it from loading, then what keeps its rules from
+    /// translating, given the ecosystems its transformations may target
+    pub fn lint_pattern(&self, pattern: &LibraryPattern, known: &HashSet<&str>) -> Vec<(Severity, String)> {
+        let mut issues = Vec::new();
+        if let Err(message) = validate(pattern) {
+            issues.push((Severity::Error, message));
+        }
+        
+        let mut declared: HashSet<&str> = pattern.parameters.iter().map(|parameter| parameter.name.as_str()).collect();
+        if let Some(detection) = &pattern.detection {
+            if ecosystem_languages(&pattern.ecosystem).is_empty() {
+                issues.push((Severity::Error, format!("`detection` can't find patterns in `{}` code", pattern.ecosystem)));
+            }
+            if let Some(text) = &detection.text {
+                match Regex::new(text) {
+                    Ok(regex) => {
+                        for group in regex.capture_names().flatten().filter(|group| !declared.contains(group)) {
+                            issues.push((Severity::Warning, format!("`detection.text` captures `{}`, which isn't a parameter", group)));
+                        }
+                    }
+                    // The last line of a syntax error says what's wrong; those above point at it
+                    Err(e) => {
+                        let e = e.to_string();
+                        let reason = e.lines().last().unwrap_or_default().trim_start_matches("error: ");
+                        issues.push((Severity::Error, format!("`detection.text` isn't a regex: {}", reason)));
+                    }
+                }
+            }
+        }
+        
+        // The built-in detector fills `{{target}}` with what a usage is assigned to; a pattern's
+        // own detection fills only what it declares
+        if pattern.detection.is_none() {
+            declared.insert("target");
+        }
+        
+        let mut targets: Vec<(&String, &TransformRule)> = pattern.transformations.iter().collect();
+        targets.sort_by(|a, b| a.0.cmp(b.0));
+        for (target, rule) in targets {
+            if !known.contains(target.as_str()) {
+                issues.push((Severity::Warning, format!("transformation `{}` targets an ecosystem no library or rule knows", target)));
+            }
+            
+            let written = [Some(&rule.template), rule.setup_code.as_ref(), rule.cleanup_code.as_ref()];
+            let mut undeclared: Vec<String> = written.into_iter().flatten()
+                .chain(rule.parameter_mappings.values())
+                .flat_map(|code| placeholders(code))
+                .filter(|name| !declared.contains(name.as_str()))
+                .collect();
+            undeclared.sort();
+            undeclared.dedup();
+            for name in undeclared {
+                issues.push((Severity::Warning, format!("transformation `{}` uses `{{{{{}}}}}`, which isn't a parameter", target, name)));
+            }
+            
+            let language = self.rule_language(target, rule);
+            for import in &rule.imports {
+                if let Some(problem) = import_problem(import, language) {
+                    issues.push((Severity::Warning, format!("transformation `{}` imports `{}`, which {}", target, import, problem)));
+                }
+            }
+        }
+        issues
+    }
+    
+    /// The ecosystem a rule's code is written for: that of its target library's patterns, or
+    /// the language a transformation to a language names
+    fn rule_language<'b>(&'b self, target: &'b str, rule: &TransformRule) -> Option<&'b str> {
+        let library = self.registry.get_library_patterns(&rule.target_library)
+            .and_then(|patterns| patterns.values().next())
+            .map(|pattern| pattern.ecosystem.as_str());
+        library.or_else(|| (!ecosystem_languages(target).is_empty()).then_some(target))
+    }
+}
+
+/// What's wrong with an import line, if anything, for code of an ecosystem
+fn import_problem(import: &str, language: Option<&str>) -> Option<String> {
+    if import.trim().is_empty() {
+        return Some("is empty".to_string());
+    }
+    if import.contains('\n') {
+        return Some("spans lines: list each import on its own".to_string());
+    }
+    if import.trim() != import {
+        return Some("has surrounding whitespace".to_string());
+    }
+    let (form, description) = match language? {
+        "python" => (r"^(?:import [\w.]+(?: as \w+)?(?:, [\w.]+(?: as \w+)?)*|from [\w.]+ import .+)$", "a Python import"),
+        "javascript" => (r"^(?:import .+|(?:const|let|var) .+ = require\(.+\));?$", "a JavaScript import"),
+        "rust" => (r"^use [\w:{}, *]+;?$", "a Rust `use`"),
+        "go" => (r#"^(?:import )?"?[\w./-]+"?$"#, "a Go import path"),
+        "csharp" => (r"^using (?:static )?[\w.]+;$", "a C# `using`"),
+        "java" | "kotlin" => (r"^import (?:static )?[\w.*]+;?$", "a Java import"),
+        _ => return None,
+    };
+    (!Regex::new(form).unwrap().is_match(import)).then(|| format!("isn't {}", description))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// A greeter pattern with what `extra` adds, as YAML
+    fn pattern(name: &str, extra: &str) -> String {
+        format!("name: {}\nlibrary: greeter\necosystem: javascript\nsemantics:\n  intent: greet\n  category: io\nparameters:\n  - {{ name: person, param_type: string, required: true, default_value: null }}\n{}", name, extra)
+    }
+    
+    fn lint(files: &[(&str, String)]) -> (LintReport, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("coalesce-lint-{}-{}", files[0].0, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            fs::write(dir.join(file), content).unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "not a pattern").unwrap();
+        let registry = LibraryRegistry::default();
+        let report = PatternLinter::new(&registry).lint_paths(&[dir.clone(), dir.join("missing.yaml")]).unwrap();
+        (report, dir)
+    }
+    
+    fn messages(report: &LintReport) -> Vec<(Option<&str>, Severity, &str)> {
+        report.issues.iter().map(|issue| (issue.pattern.as_deref(), issue.severity, issue.message.as_str())).collect()
+    }
+    
+    #[test]
+    fn test_clean_patterns_have_no_issues_but_missing_paths_do() {
+        let rule = "transformations:\n  stdlib:\n    target_library: logging\n    target_pattern: info\n    template: 'logging.info({{person}})'\n    imports: [import logging]\n    setup_code: null\n    cleanup_code: null\n";
+        let (report, dir) = lint(&[("clean.yaml", pattern("greet", rule))]);
+        assert_eq!((report.files, report.patterns), (1, 1));
+        assert_eq!(report.issues.len(), 1, "{:?}", report.issues);
+        assert_eq!(report.issues[0].file, dir.join("missing.yaml"));
+        assert_eq!(report.errors(), 1);
+    }
+    
+    #[test]
+    fn test_problems_that_stop_loading_or_translating() {
+        let detection = "detection:\n  call: greet\n  text: 'greet\\((?P<who>\\w+)\\)'\n";
+        let rule = "transformations:\n  klingon:\n    target_library: tlhingan\n    target_pattern: greet\n    template: 'nuqneH({{person}}, {{mood}})'\n    setup_code: null\n    cleanup_code: null\n  python:\n    target_library: logging\n    target_pattern: info\n    template: 'logging.info({{person}})'\n    imports: [require logging]\n    setup_code: null\n    cleanup_code: null\n";
+        let (report, _) = lint(&[
+            ("broken.yaml", "name: [".to_string()),
+            ("greet.yaml", pattern("greet", &format!("{}{}", detection, rule))),
+            ("twice.yaml", pattern("greet", "")),
+        ]);
+        assert_eq!((report.files, report.patterns), (3, 2));
+        let found = messages(&report);
+        assert!(found.contains(&(Some("greeter:greet"), Severity::Warning, "`detection.text` captures `who`, which isn't a parameter")), "{:?}", found);
+        assert!(found.contains(&(Some("greeter:greet"), Severity::Warning, "transformation `klingon` targets an ecosystem no library or rule knows")), "{:?}", found);
+        assert!(found.contains(&(Some("greeter:greet"), Severity::Warning, "transformation `klingon` uses `{{mood}}`, which isn't a parameter")), "{:?}", found);
+        assert!(found.contains(&(Some("greeter:greet"), Severity::Warning, "transformation `python` imports `require logging`, which isn't a Python import")), "{:?}", found);
+        assert!(found.iter().any(|&(pattern, severity, message)| pattern == Some("greeter:greet") && severity == Severity::Error && message.starts_with("already defined in ")), "{:?}", found);
+        assert!(matches!(found[1], (None, Severity::Error, message) if message.starts_with("YAML parse error")), "{:?}", found);
+        assert_eq!(report.errors(), 3);
+    }
+    
+    #[test]
+    fn test_invalid_regexes_and_detection_in_unknown_languages_are_errors() {
+        let registry = LibraryRegistry::default();
+        let mut pattern: LibraryPattern = serde_yaml::from_str(&pattern("greet", "detection:\n  call: greet\n  text: 'greet('\n")).unwrap();
+        pattern.ecosystem = "cobol".to_string();
+        let issues = PatternLinter::new(&registry).lint_pattern(&pattern, &HashSet::new());
+        assert_eq!(issues, [
+            (Severity::Error, "`detection` can't find patterns in `cobol` code".to_string()),
+            (Severity::Error, "`detection.text` isn't a regex: unclosed group".to_string()),
+        ]);
+    }
+}
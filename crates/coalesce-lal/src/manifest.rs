@@ -0,0 +1,703 @@
+// Dependency manifests: the libraries a project declares, with their versions, whether or not
+// its code exercises a pattern of them. They make up its dependency inventory and decide the
+// ecosystem its translations target, and a translation gets one declaring the packages its
+// rules need.
+
+use crate::patterns::{PackageRequirement, TransformRule};
+use coalesce_core::{CoalesceError, Language, Result};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How a manifest declares a dependency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyKind {
+    Runtime,
+    /// For tests, tooling and builds of the project itself
+    Development,
+    /// For build scripts
+    Build,
+    /// Expected of whatever installs the project
+    Peer,
+    Optional,
+    /// Required by another dependency, not the project's code
+    Indirect,
+}
+
+/// A library a manifest declares
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestDependency {
+    pub name: String,
+    /// The version or requirement as written, e.g. `^1.2`, `==2.31.0` or `v0.9.1`; none for
+    /// dependencies on a path, repository or workspace
+    pub version: Option<String>,
+    pub kind: DependencyKind,
+}
+
+/// A dependency file of a project and what it declares
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyManifest {
+    pub path: PathBuf,
+    /// The ecosystem of the declared libraries, as detection names it, e.g. `python`
+    pub ecosystem: String,
+    pub dependencies: Vec<ManifestDependency>,
+}
+
+impl DependencyManifest {
+    /// Read a manifest, of the kind its file name says
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let Some(ecosystem) = manifest_ecosystem(name) else {
+            return Err(manifest_error(path, "isn't a dependency manifest".to_string()));
+        };
+        let text = fs::read_to_string(path)?;
+        let dependencies = match name {
+            "package.json" => package_json_dependencies(&text),
+            "requirements.txt" => requirements_dependencies(&text, path, &mut vec![path.to_path_buf()]),
+            "go.mod" => Ok(go_mod_dependencies(&text)),
+            "Cargo.toml" => cargo_dependencies(&text),
+            _ => Ok(csproj_dependencies(&text)),
+        }.map_err(|e| manifest_error(path, e))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            ecosystem: ecosystem.to_string(),
+            dependencies,
+        })
+    }
+    
+    /// Every manifest at the root of the project at `root`, in file name order
+    pub fn find_all(root: impl AsRef<Path>) -> Result<Vec<Self>> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(root)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            if manifest_ecosystem(name).is_some() && path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths.iter().map(Self::read).collect()
+    }
+}
+
+/// The ecosystem of a manifest, by its file name; `None` for files that aren't manifests
+fn manifest_ecosystem(file_name: &str) -> Option<&'static str> {
+    match file_name {
+        "package.json" => Some("javascript"),
+        "requirements.txt" => Some("python"),
+        "go.mod" => Some("go"),
+        "Cargo.toml" => Some("rust"),
+        _ if file_name.ends_with(".csproj") => Some("csharp"),
+        _ => None,
+    }
+}
+
+/// The manifest listing the dependencies of a project in `language`, e.g. its `Cargo.toml`, if
+/// the project at `root` has one
+pub fn manifest_path(root: impl AsRef<Path>, language: &Language) -> Result<Option<PathBuf>> {
+    let Some(ecosystem) = language_ecosystem(language) else {
+        return Ok(None);
+    };
+    if !root.as_ref().is_dir() {
+        return Ok(None);
+    }
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if manifest_ecosystem(name) == Some(ecosystem) && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths.into_iter().next())
+}
+
+/// Names of the libraries the project at `root` depends on, from its manifest for `language`;
+/// none when it has no such manifest
+pub fn dependency_names(root: impl AsRef<Path>, language: &Language) -> Result<Vec<String>> {
+    let Some(path) = manifest_path(root, language)? else {
+        return Ok(Vec::new());
+    };
+    let mut names: Vec<String> = DependencyManifest::read(path)?.dependencies.into_iter()
+        .map(|dependency| dependency.name)
+        .collect();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+fn manifest_error(path: &Path, message: String) -> CoalesceError {
+    CoalesceError::TransformationError(format!("Failed to read {}: {}", path.display(), message))
+}
+
+/// Packages of a `package.json`, of every kind
+fn package_json_dependencies(text: &str) -> std::result::Result<Vec<ManifestDependency>, String> {
+    let manifest: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let kinds = [
+        ("dependencies", DependencyKind::Runtime),
+        ("devDependencies", DependencyKind::Development),
+        ("peerDependencies", DependencyKind::Peer),
+        ("optionalDependencies", DependencyKind::Optional),
+    ];
+    let mut dependencies = Vec::new();
+    for (key, kind) in kinds {
+        let Some(declared) = manifest.get(key).and_then(Value::as_object) else {
+            continue;
+        };
+        dependencies.extend(declared.iter().map(|(name, version)| ManifestDependency {
+            name: name.clone(),
+            version: version.as_str().map(str::to_string),
+            kind,
+        }));
+    }
+    Ok(dependencies)
+}
+
+/// Requirements of a `requirements.txt` and of the files it includes with `-r`, each read once
+fn requirements_dependencies(text: &str, path: &Path, read: &mut Vec<PathBuf>) -> std::result::Result<Vec<ManifestDependency>, String> {
+    let requirement = Regex::new(r"^([A-Za-z0-9][A-Za-z0-9._-]*)\s*(?:\[[^\]]*\])?\s*([^;]*)").unwrap();
+    let egg = Regex::new(r"#egg=([A-Za-z0-9][A-Za-z0-9._-]*)").unwrap();
+    let mut dependencies = Vec::new();
+    for line in joined_lines(text) {
+        let line = line.split(" #").next().unwrap_or_default().trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(included) = line.strip_prefix("-r ").or_else(|| line.strip_prefix("--requirement ")) {
+            let included = path.parent().unwrap_or(Path::new(".")).join(included.trim());
+            if read.contains(&included) {
+                continue;
+            }
+            read.push(included.clone());
+            let text = fs::read_to_string(&included).map_err(|e| format!("{}: {}", included.display(), e))?;
+            dependencies.extend(requirements_dependencies(&text, &included, read)?);
+        } else if let Some(captures) = egg.captures(line) {
+            // A repository or archive, named by its egg
+            dependencies.push(ManifestDependency { name: captures[1].to_string(), version: None, kind: DependencyKind::Runtime });
+        } else if let Some(captures) = requirement.captures(line).filter(|_| !line.starts_with('-')) {
+            // Options such as `--hash` follow the version
+            let version = captures[2].split(" --").next().unwrap_or_default().trim();
+            dependencies.push(ManifestDependency {
+                name: captures[1].to_string(),
+                version: (!version.is_empty()).then(|| version.to_string()),
+                kind: DependencyKind::Runtime,
+            });
+        }
+    }
+    Ok(dependencies)
+}
+
+/// The lines of a `requirements.txt` as pip reads them, each ending in `\` joined to the next;
+/// a comment ends its line whatever it ends in
+fn joined_lines(text: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut continued = String::new();
+    for line in text.lines() {
+        let comment = line.trim_start().starts_with('#') || line.contains(" #");
+        match line.strip_suffix('\\') {
+            Some(line) if !comment => continued.push_str(line),
+            _ if comment => lines.push(std::mem::take(&mut continued) + " " + line),
+            _ => lines.push(std::mem::take(&mut continued) + line),
+        }
+    }
+    if !continued.is_empty() {
+        lines.push(continued);
+    }
+    lines
+}
+
+/// Modules a `go.mod` requires, on their own `require` lines or in `require ( ... )` blocks
+fn go_mod_dependencies(text: &str) -> Vec<ManifestDependency> {
+    let mut dependencies = Vec::new();
+    let mut in_block = false;
+    for line in text.lines() {
+        let line = line.trim();
+        let (line, comment) = line.split_once("//").map_or((line, ""), |(line, comment)| (line.trim(), comment.trim()));
+        let required = if in_block {
+            in_block = line != ")";
+            line
+        } else if line == "require (" {
+            in_block = true;
+            continue;
+        } else if let Some(required) = line.strip_prefix("require ") {
+            required
+        } else {
+            continue;
+        };
+        let mut parts = required.split_whitespace();
+        if let (Some(module), Some(version)) = (parts.next(), parts.next()) {
+            dependencies.push(ManifestDependency {
+                name: module.to_string(),
+                version: Some(version.to_string()),
+                kind: if comment == "indirect" { DependencyKind::Indirect } else { DependencyKind::Runtime },
+            });
+        }
+    }
+    dependencies
+}
+
+/// Crates of a `Cargo.toml`: its dependencies of every kind, for every target, and those of
+/// its workspace, by package name where a dependency renames one
+fn cargo_dependencies(text: &str) -> std::result::Result<Vec<ManifestDependency>, String> {
+    let manifest: toml::Table = text.parse().map_err(|e: toml::de::Error| e.message().to_string())?;
+    let mut sections = vec![&manifest];
+    if let Some(workspace) = manifest.get("workspace").and_then(toml::Value::as_table) {
+        sections.push(workspace);
+    }
+    if let Some(targets) = manifest.get("target").and_then(toml::Value::as_table) {
+        sections.extend(targets.values().filter_map(toml::Value::as_table));
+    }
+    let kinds = [
+        ("dependencies", DependencyKind::Runtime),
+        ("dev-dependencies", DependencyKind::Development),
+        ("build-dependencies", DependencyKind::Build),
+    ];
+    
+    let mut dependencies = Vec::new();
+    for section in sections {
+        for (key, kind) in kinds {
+            let Some(declared) = section.get(key).and_then(toml::Value::as_table) else {
+                continue;
+            };
+            for (name, dependency) in declared {
+                let package = dependency.get("package").and_then(toml::Value::as_str);
+                let version = dependency.as_str().or_else(|| dependency.get("version").and_then(toml::Value::as_str));
+                dependencies.push(ManifestDependency {
+                    name: package.unwrap_or(name).to_string(),
+                    version: version.map(str::to_string),
+                    kind,
+                });
+            }
+        }
+    }
+    Ok(dependencies)
+}
+
+/// Packages a `*.csproj` references, with their versions as attributes or elements
+fn csproj_dependencies(text: &str) -> Vec<ManifestDependency> {
+    let reference = Regex::new(r#"(?s)<PackageReference\s([^>]*?)(?:/>|>(.*?)</PackageReference>)"#).unwrap();
+    let include = Regex::new(r#"\bInclude\s*=\s*"([^"]+)""#).unwrap();
+    let version = Regex::new(r#"\bVersion\s*=\s*"([^"]+)"|<Version>\s*([^<]+?)\s*</Version>"#).unwrap();
+    let assets = Regex::new(r#"PrivateAssets\s*=\s*"all"|<PrivateAssets>\s*all\s*</PrivateAssets>"#).unwrap();
+    reference.captures_iter(text).filter_map(|captures| {
+        let attributes = &captures[1];
+        let name = include.captures(attributes)?[1].to_string();
+        let body = captures.get(2).map_or("", |body| body.as_str());
+        let version = version.captures(attributes).or_else(|| version.captures(body))
+            .and_then(|version| version.get(1).or_else(|| version.get(2)))
+            .map(|version| version.as_str().to_string());
+        // Analyzers and build tools don't flow to the project's consumers
+        let private = assets.is_match(attributes) || assets.is_match(body);
+        Some(ManifestDependency {
+            name,
+            version,
+            kind: if private { DependencyKind::Development } else { DependencyKind::Runtime },
+        })
+    }).collect()
+}
+
+/// The packages a translation needs, for the manifest of the target project
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TargetManifest {
+    /// The ecosystem of the target language, as detection names it
+    pub ecosystem: String,
+    pub packages: Vec<PackageRequirement>,
+    /// The target libraries the translation uses
+    pub libraries: BTreeSet<String>,
+    /// Target libraries of no known package, that rules would have to list under `packages`
+    /// if they aren't standard
+    pub unresolved: BTreeSet<String>,
+    /// Target libraries a rule listed the packages of
+    #[serde(skip)]
+    listed: BTreeSet<String>,
+}
+
+impl TargetManifest {
+    pub fn new(ecosystem: &str) -> Self {
+        Self {
+            ecosystem: ecosystem.to_string(),
+            ..Self::default()
+        }
+    }
+    
+    /// Add the packages a rule's code needs: those it lists, else those known for its target
+    /// library and the crates, modules and packages its imports name
+    pub fn add_rule(&mut self, rule: &TransformRule) {
+        self.libraries.insert(rule.target_library.clone());
+        if !rule.packages.is_empty() {
+            self.unresolved.remove(&rule.target_library);
+            self.listed.insert(rule.target_library.clone());
+            rule.packages.iter().for_each(|package| self.add_package(package.clone()));
+            return;
+        }
+        let known = known_packages(&self.ecosystem, &rule.target_library);
+        let imported: Vec<PackageRequirement> = rule.imports.iter()
+            .filter_map(|import| imported_package(&self.ecosystem, import))
+            .collect();
+        if known.is_none() && imported.is_empty() && !self.listed.contains(&rule.target_library) {
+            self.unresolved.insert(rule.target_library.clone());
+        }
+        known.into_iter().flatten().chain(imported).for_each(|package| self.add_package(package));
+    }
+    
+    /// Add the packages known for a target library, as a translation to an equivalent pattern
+    /// of it needs
+    pub fn add_library(&mut self, library: &str) {
+        self.libraries.insert(library.to_string());
+        match known_packages(&self.ecosystem, library) {
+            Some(packages) => packages.into_iter().for_each(|package| self.add_package(package)),
+            None => {
+                self.unresolved.insert(library.to_string());
+            }
+        }
+    }
+    
+    /// Add a package, enabling its features on one already added
+    pub fn add_package(&mut self, package: PackageRequirement) {
+        match self.packages.iter_mut().find(|known| known.name == package.name) {
+            Some(known) => {
+                for feature in package.features {
+                    if !known.features.contains(&feature) {
+                        known.features.push(feature);
+                    }
+                }
+            }
+            None => self.packages.push(package),
+        }
+    }
+    
+    /// The manifest's file name, for a project named `name`; `None` for ecosystems without
+    /// one
+    pub fn file_name(&self, name: &str) -> Option<String> {
+        match self.ecosystem.as_str() {
+            "rust" => Some("Cargo.toml".to_string()),
+            "python" => Some("requirements.txt".to_string()),
+            "go" => Some("go.mod".to_string()),
+            "javascript" => Some("package.json".to_string()),
+            "csharp" => Some(format!("{}.csproj", name)),
+            _ => None,
+        }
+    }
+    
+    /// The manifest of a project named `name` that declares the packages; `None` for
+    /// ecosystems without one
+    pub fn render(&self, name: &str) -> Option<String> {
+        let mut packages = self.packages.clone();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        let text = match self.ecosystem.as_str() {
+            "rust" => {
+                let mut text = format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n", name);
+                for package in &packages {
+                    if package.features.is_empty() {
+                        text.push_str(&format!("{} = \"{}\"\n", package.name, package.version));
+                    } else {
+                        let features: Vec<String> = package.features.iter().map(|feature| format!("\"{}\"", feature)).collect();
+                        text.push_str(&format!("{} = {{ version = \"{}\", features = [{}] }}\n", package.name, package.version, features.join(", ")));
+                    }
+                }
+                text
+            }
+            "python" => packages.iter().map(|package| {
+                // A bare version pins it
+                let operator = if package.version.starts_with(['<', '>', '=', '~', '!']) { "" } else { "==" };
+                format!("{}{}{}\n", package.name, operator, package.version)
+            }).collect(),
+            "go" => {
+                let mut text = format!("module {}\n\ngo 1.21\n", name);
+                if !packages.is_empty() {
+                    text.push_str("\nrequire (\n");
+                    packages.iter().for_each(|package| text.push_str(&format!("\t{} {}\n", package.name, package.version)));
+                    text.push_str(")\n");
+                }
+                text
+            }
+            "javascript" => {
+                // Written by hand to keep npm's order of fields
+                let quote = |text: &str| Value::String(text.to_string()).to_string();
+                let dependencies: Vec<String> = packages.iter()
+                    .map(|package| format!("    {}: {}", quote(&package.name), quote(&package.version)))
+                    .collect();
+                let dependencies = if dependencies.is_empty() { "{}".to_string() } else { format!("{{\n{}\n  }}", dependencies.join(",\n")) };
+                format!("{{\n  \"name\": {},\n  \"version\": \"0.1.0\",\n  \"private\": true,\n  \"dependencies\": {}\n}}\n", quote(name), dependencies)
+            }
+            "csharp" => {
+                // ASP.NET Core ships with the web SDK rather than as a package
+                let sdk = if self.libraries.contains("aspnet") { "Microsoft.NET.Sdk.Web" } else { "Microsoft.NET.Sdk" };
+                let mut text = format!("<Project Sdk=\"{}\">\n\n  <PropertyGroup>\n    <TargetFramework>net8.0</TargetFramework>\n    <ImplicitUsings>enable</ImplicitUsings>\n    <Nullable>enable</Nullable>\n  </PropertyGroup>\n", sdk);
+                if !packages.is_empty() {
+                    text.push_str("\n  <ItemGroup>\n");
+                    for package in &packages {
+                        text.push_str(&format!("    <PackageReference Include=\"{}\" Version=\"{}\" />\n", package.name, package.version));
+                    }
+                    text.push_str("  </ItemGroup>\n");
+                }
+                text.push_str("\n</Project>\n");
+                text
+            }
+            _ => return None,
+        };
+        Some(text)
+    }
+}
+
+/// The ecosystem a manifest for code in `language` declares packages of, as detection names it
+pub fn language_ecosystem(language: &Language) -> Option<&'static str> {
+    match language {
+        Language::JavaScript | Language::TypeScript => Some("javascript"),
+        Language::Python => Some("python"),
+        Language::Go => Some("go"),
+        Language::Rust => Some("rust"),
+        Language::CSharp => Some("csharp"),
+        _ => None,
+    }
+}
+
+fn package(name: &str, version: &str, features: &[&str]) -> PackageRequirement {
+    PackageRequirement {
+        name: name.to_string(),
+        version: version.to_string(),
+        features: features.iter().map(|feature| feature.to_string()).collect(),
+    }
+}
+
+/// Whether `library` comes with the language of `ecosystem`, needing no package
+pub fn is_standard_library(ecosystem: &str, library: &str) -> bool {
+    known_packages(ecosystem, library).is_some_and(|packages| packages.is_empty())
+}
+
+/// Packages of the libraries rules translate to, by the name rules know them by: a target
+/// library, or a crate or package their imports name. `Some` of nothing for libraries that
+/// come with the language, `None` for those it doesn't know.
+fn known_packages(ecosystem: &str, library: &str) -> Option<Vec<PackageRequirement>> {
+    let serde = || package("serde", "1", &["derive"]);
+    let tokio = || package("tokio", "1", &["full"]);
+    Some(match (ecosystem, library) {
+        ("rust", "axum") => vec![package("axum", "0.7", &[]), tokio(), serde(), package("serde_json", "1", &[])],
+        ("rust", "reqwest") => vec![package("reqwest", "0.12", &["json"]), tokio(), package("serde_json", "1", &[])],
+        ("rust", "sea_orm") => vec![package("sea-orm", "1", &["sqlx-postgres", "runtime-tokio-rustls", "macros"]), tokio()],
+        ("rust", "tokio") => vec![tokio()],
+        ("rust", "serde") => vec![serde()],
+        ("rust", "serde_json") => vec![package("serde_json", "1", &[])],
+        ("rust", "chrono") => vec![package("chrono", "0.4", &[])],
+        ("rust", "clap") => vec![package("clap", "4", &["derive"])],
+        ("rust", "config_rs" | "config") => vec![package("config", "0.14", &[])],
+        ("rust", "diesel") => vec![package("diesel", "2", &["postgres"])],
+        ("rust", "dotenvy") => vec![package("dotenvy", "0.15", &[])],
+        ("rust", "ndarray") => vec![package("ndarray", "0.15", &[])],
+        ("rust", "polars") => vec![package("polars", "0.41", &["lazy", "sql"])],
+        ("rust", "tracing") => vec![package("tracing", "0.1", &[]), package("tracing-subscriber", "0.3", &[])],
+        ("rust", "log") => vec![package("log", "0.4", &[])],
+        ("rust", "rand") => vec![package("rand", "0.8", &[])],
+        ("rust", "sha2") => vec![package("sha2", "0.10", &[])],
+        ("rust", "sha1") => vec![package("sha1", "0.10", &[])],
+        ("rust", "md5") => vec![package("md-5", "0.10", &[])],
+        ("rust", "hmac") => vec![package("hmac", "0.12", &[])],
+        ("rust", "aes_gcm") => vec![package("aes-gcm", "0.10", &[])],
+        ("rust", "std" | "std_fs" | "std_env" | "std_thread" | "rust" | "cargo_test") => Vec::new(),
+        ("python", "flask") => vec![package("Flask", ">=3.0", &[])],
+        ("python", "django") => vec![package("Django", ">=4.2", &[])],
+        ("python", "fastapi") => vec![package("fastapi", ">=0.110", &[]), package("uvicorn", ">=0.29", &[])],
+        ("python", "requests") => vec![package("requests", ">=2.31", &[])],
+        ("python", "httpx") => vec![package("httpx", ">=0.27", &[])],
+        ("python", "sqlalchemy") => vec![package("SQLAlchemy", ">=2.0", &[])],
+        ("python", "pytest") => vec![package("pytest", ">=8.0", &[])],
+        ("python", "python_dotenv") => vec![package("python-dotenv", ">=1.0", &[])],
+        ("python", "cryptography") => vec![package("cryptography", ">=42.0", &[])],
+        ("python", "pandas") => vec![package("pandas", ">=2.0", &[])],
+        ("python", "argparse" | "asyncio" | "datetime" | "hashlib" | "io" | "logging" | "os" | "os_environ" | "python" | "socket" | "stdlib" | "threading") => Vec::new(),
+        ("go", "gin") => vec![package("github.com/gin-gonic/gin", "v1.9.1", &[])],
+        ("go", "cobra") => vec![package("github.com/spf13/cobra", "v1.8.0", &[])],
+        ("go", "viper") => vec![package("github.com/spf13/viper", "v1.18.2", &[])],
+        ("go", "godotenv") => vec![package("github.com/joho/godotenv", "v1.5.1", &[])],
+        ("go", "gonum") => vec![package("gonum.org/v1/gonum", "v0.14.0", &[])],
+        ("go", "zap") => vec![package("go.uber.org/zap", "v1.27.0", &[])],
+        ("go", "go_crypto" | "go_env" | "goroutines" | "log" | "net" | "net_http" | "stdlib" | "testing") => Vec::new(),
+        ("javascript", "axios") => vec![package("axios", "^1.6.0", &[])],
+        ("javascript", "commander") => vec![package("commander", "^12.0.0", &[])],
+        ("javascript", "dayjs") => vec![package("dayjs", "^1.11.10", &[])],
+        ("javascript", "dotenv") => vec![package("dotenv", "^16.4.0", &[])],
+        ("javascript", "express") => vec![package("express", "^4.18.2", &[])],
+        ("javascript", "lodash") => vec![package("lodash", "^4.17.21", &[])],
+        ("javascript", "jest") => vec![package("jest", "^29.7.0", &[])],
+        ("javascript", "moment") => vec![package("moment", "^2.30.1", &[])],
+        ("javascript", "node_config" | "config") => vec![package("config", "^3.3.9", &[])],
+        ("javascript", "react") => vec![package("react", "^18.2.0", &[]), package("react-dom", "^18.2.0", &[])],
+        ("javascript", "svelte") => vec![package("svelte", "^4.2.0", &[])],
+        ("javascript", "vue") => vec![package("vue", "^3.4.0", &[])],
+        ("javascript", "winston") => vec![package("winston", "^3.11.0", &[])],
+        ("javascript", "yargs") => vec![package("yargs", "^17.7.2", &[])],
+        ("javascript", "console" | "crypto" | "fetch" | "fs" | "javascript" | "process_env" | "vanilla") => Vec::new(),
+        ("csharp", "entity_framework") => vec![package("Microsoft.EntityFrameworkCore", "8.0.0", &[])],
+        ("csharp", "dotnetenv") => vec![package("DotNetEnv", "3.0.0", &[])],
+        ("csharp", "system_commandline") => vec![package("System.CommandLine", "2.0.0-beta4.22272.1", &[])],
+        ("csharp", "xunit") => vec![package("xunit", "2.6.6", &[]), package("xunit.runner.visualstudio", "2.5.6", &[]), package("Microsoft.NET.Test.Sdk", "17.9.0", &[])],
+        ("csharp", "aspnet" | "dotnet" | "dotnet_config" | "dotnet_crypto" | "dotnet_tasks" | "system_datetime" | "system_io") => Vec::new(),
+        _ => return None,
+    })
+}
+
+/// The package an import line names, for ecosystems whose imports name packages: a Rust
+/// crate, a Go module or an npm package. Unknown crates and npm packages take any version; Go
+/// modules need one, so only known modules count.
+fn imported_package(ecosystem: &str, import: &str) -> Option<PackageRequirement> {
+    match ecosystem {
+        "rust" => {
+            let path = import.trim().strip_prefix("use ")?;
+            let root = path.split([':', ';', ' ']).next()?;
+            if matches!(root, "std" | "core" | "alloc" | "crate" | "self" | "super") || root.is_empty() {
+                return None;
+            }
+            Some(known_packages("rust", root).and_then(|packages| packages.into_iter().next()).unwrap_or_else(|| package(root, "*", &[])))
+        }
+        "go" => {
+            let path = import.trim().trim_start_matches("import ").trim_matches('"');
+            GO_LIBRARIES.iter()
+                .filter_map(|library| known_packages("go", library)?.into_iter().next())
+                .find(|module| path == module.name || path.starts_with(&format!("{}/", module.name)))
+        }
+        "javascript" => {
+            let specifier = Regex::new(r#"(?:from\s+|require\(\s*|^import\s+)['"]([^'"]+)['"]"#).unwrap();
+            let specifier = specifier.captures(import.trim())?.get(1)?.as_str().to_string();
+            if specifier.starts_with('.') || specifier.starts_with('/') || specifier.starts_with("node:") || NODE_MODULES.contains(&specifier.as_str()) {
+                return None;
+            }
+            // `@scope/name/sub` is `@scope/name`; `name/sub` is `name`
+            let segments = if specifier.starts_with('@') { 2 } else { 1 };
+            let name = specifier.split('/').take(segments).collect::<Vec<_>>().join("/");
+            let known = known_packages("javascript", &name).and_then(|packages| packages.into_iter().find(|package| package.name == name));
+            Some(known.unwrap_or_else(|| package(&name, "*", &[])))
+        }
+        _ => None,
+    }
+}
+
+/// Go target libraries of known modules, for matching import paths
+const GO_LIBRARIES: &[&str] = &["gin", "cobra", "viper", "godotenv", "gonum", "zap"];
+
+/// Node's own modules, imported without a `node:` prefix
+const NODE_MODULES: &[&str] = &[
+    "assert", "buffer", "child_process", "cluster", "crypto", "dns", "events", "fs", "fs/promises", "http", "https",
+    "net", "os", "path", "process", "readline", "stream", "timers", "url", "util", "worker_threads", "zlib",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// The dependencies read from the manifest `name` with `content`, beside `others`
+    fn read(name: &str, content: &str, others: &[(&str, &str)]) -> Vec<(String, Option<String>, DependencyKind)> {
+        let dir = std::env::temp_dir().join(format!("coalesce-manifest-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, text) in others.iter().chain([&(name, content)]) {
+            fs::write(dir.join(file), text).unwrap();
+        }
+        let manifest = DependencyManifest::read(dir.join(name)).unwrap();
+        manifest.dependencies.into_iter().map(|dependency| (dependency.name, dependency.version, dependency.kind)).collect()
+    }
+    
+    fn runtime(name: &str, version: Option<&str>) -> (String, Option<String>, DependencyKind) {
+        (name.to_string(), version.map(str::to_string), DependencyKind::Runtime)
+    }
+    
+    #[test]
+    fn test_requirements_join_continued_lines_and_follow_includes() {
+        let requirements = concat!(
+            "# pinned with hashes\n",
+            "requests==2.31.0 \\\n",
+            "    --hash=sha256:942c5a758f98d790eaed1a29cb6eefc7ffb0d1cf7af05c3d2791656dbd6ad1e1 \\\n",
+            "    --hash=sha256:58cd2187c01e70e6e26505bca751777aa9f2ee0b7f4300988b709f44e013003f\n",
+            "httpx[http2] >=0.27, <1.0 ; python_version >= \"3.8\"  # async client\n",
+            "pydantic\\\n",
+            ">=2 \\\n",
+            "# a comment isn't continued \\\n",
+            "-e git+https://github.com/acme/sdk.git#egg=acme-sdk\n",
+            "-r dev.txt\n",
+            "--index-url https://pypi.org/simple\n",
+        );
+        assert_eq!(read("requirements.txt", requirements, &[("dev.txt", "pytest~=8.0\n-r requirements.txt\n")]), [
+            runtime("requests", Some("==2.31.0")),
+            runtime("httpx", Some(">=0.27, <1.0")),
+            runtime("pydantic", Some(">=2")),
+            runtime("acme-sdk", None),
+            runtime("pytest", Some("~=8.0")),
+        ]);
+    }
+    
+    #[test]
+    fn test_package_json_and_go_mod_kinds() {
+        let package = r#"{ "dependencies": { "axios": "^1.6.0" }, "devDependencies": { "jest": "^29.0.0" }, "peerDependencies": { "react": ">=18" }, "optionalDependencies": { "fsevents": "*" } }"#;
+        assert_eq!(read("package.json", package, &[]), [
+            runtime("axios", Some("^1.6.0")),
+            ("jest".to_string(), Some("^29.0.0".to_string()), DependencyKind::Development),
+            ("react".to_string(), Some(">=18".to_string()), DependencyKind::Peer),
+            ("fsevents".to_string(), Some("*".to_string()), DependencyKind::Optional),
+        ]);
+        
+        let go_mod = "module example.com/app\n\ngo 1.21\n\nrequire github.com/gin-gonic/gin v1.9.1\n\nrequire (\n\tgo.uber.org/zap v1.27.0 // logging\n\tgolang.org/x/sys v0.15.0 // indirect\n)\n";
+        assert_eq!(read("go.mod", go_mod, &[]), [
+            runtime("github.com/gin-gonic/gin", Some("v1.9.1")),
+            runtime("go.uber.org/zap", Some("v1.27.0")),
+            ("golang.org/x/sys".to_string(), Some("v0.15.0".to_string()), DependencyKind::Indirect),
+        ]);
+    }
+    
+    #[test]
+    fn test_cargo_and_csproj_dependencies() {
+        let cargo = concat!(
+            "[package]\nname = \"app\"\n\n",
+            "[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\nhttp = { package = \"reqwest\", version = \"0.12\" }\nlocal = { path = \"../local\" }\n\n",
+            "[build-dependencies]\ncc = \"1.0\"\n\n",
+            "[target.'cfg(unix)'.dev-dependencies]\nnix = \"0.27\"\n\n",
+            "[workspace.dependencies]\ntokio = \"1\"\n",
+        );
+        let mut crates = read("Cargo.toml", cargo, &[]);
+        crates.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(crates, [
+            ("cc".to_string(), Some("1.0".to_string()), DependencyKind::Build),
+            runtime("local", None),
+            ("nix".to_string(), Some("0.27".to_string()), DependencyKind::Development),
+            runtime("reqwest", Some("0.12")),
+            runtime("serde", Some("1")),
+            runtime("tokio", Some("1")),
+        ]);
+        
+        let csproj = r#"<Project Sdk="Microsoft.NET.Sdk">
+  <ItemGroup>
+    <PackageReference Include="Newtonsoft.Json" Version="13.0.3" />
+    <PackageReference Include="Serilog">
+      <Version>3.1.1</Version>
+    </PackageReference>
+    <PackageReference Include="StyleCop.Analyzers" Version="1.1.118" PrivateAssets="all" />
+  </ItemGroup>
+</Project>"#;
+        assert_eq!(read("App.csproj", csproj, &[]), [
+            runtime("Newtonsoft.Json", Some("13.0.3")),
+            runtime("Serilog", Some("3.1.1")),
+            ("StyleCop.Analyzers".to_string(), Some("1.1.118".to_string()), DependencyKind::Development),
+        ]);
+    }
+    
+    #[test]
+    fn test_manifests_are_found_by_name_and_language() {
+        let dir = std::env::temp_dir().join(format!("coalesce-manifest-find-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("requirements.txt"), "requests\nhttpx==0.27\nrequests\n").unwrap();
+        fs::write(dir.join("package.json"), "{}").unwrap();
+        fs::write(dir.join("README.md"), "").unwrap();
+        
+        let found: Vec<String> = DependencyManifest::find_all(&dir).unwrap().into_iter().map(|manifest| manifest.ecosystem).collect();
+        assert_eq!(found, ["javascript", "python"]);
+        assert_eq!(manifest_path(&dir, &Language::Python).unwrap(), Some(dir.join("requirements.txt")));
+        assert_eq!(manifest_path(&dir, &Language::Rust).unwrap(), None);
+        assert_eq!(dependency_names(&dir, &Language::Python).unwrap(), ["httpx", "requests"]);
+        assert!(DependencyManifest::read(dir.join("README.md")).is_err());
+    }
+}
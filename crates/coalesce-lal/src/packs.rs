@@ -0,0 +1,464 @@
+// Pattern packs: library patterns fetched from a URL or git repository and pinned in a lockfile
+
+use crate::registry::{is_pattern_file, LibraryRegistry};
+use coalesce_core::{CoalesceError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// Lockfile pinning the synced packs, next to the project config
+pub const LOCK_FILE: &str = "patterns.lock";
+
+/// Directory the synced packs are installed in, next to the project config
+pub const PACKS_DIR: &str = "packs";
+
+/// A pattern pack declared under `pattern_packs` in the project config; exactly one of `url`
+/// (a single pattern file) or `git` (a repository of pattern files) is set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackSource {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// Branch, tag or commit of `git`, its default branch when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// Directory of the pattern files within `git`, its root when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Expected checksum of the pack, see [`PackLock`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl PackSource {
+    /// Where the pack is fetched from, as recorded in the lockfile
+    pub fn location(&self) -> String {
+        match (&self.url, &self.git) {
+            (Some(url), _) => url.clone(),
+            (None, Some(git)) => match &self.path {
+                Some(path) => format!("{}#{}", git, path),
+                None => git.clone(),
+            },
+            (None, None) => String::new(),
+        }
+    }
+    
+    fn check(&self) -> Result<()> {
+        let safe = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+        if self.name.is_empty() || self.name.starts_with('.') || !self.name.chars().all(safe) {
+            return Err(pack_error(&self.name, "names must be letters, digits, `-`, `_` or `.`".to_string()));
+        }
+        if !matches!((&self.url, &self.git), (Some(_), None) | (None, Some(_))) {
+            return Err(pack_error(&self.name, "set exactly one of `url` or `git`".to_string()));
+        }
+        // git would read a value starting with `-` as an option
+        for (field, value) in [("git", &self.git), ("rev", &self.rev)] {
+            if value.as_deref().is_some_and(|value| value.is_empty() || value.starts_with('-')) {
+                return Err(pack_error(&self.name, format!("`{}` must not be empty or start with `-`", field)));
+            }
+        }
+        let escapes = |path: &str| Path::new(path).components().any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+        if self.path.as_deref().is_some_and(escapes) {
+            return Err(pack_error(&self.name, "`path` must be relative to the repository, without `..`".to_string()));
+        }
+        Ok(())
+    }
+    
+    /// Whether a pack was locked from this source, as declared now
+    fn locked(&self, pack: &LockedPack) -> bool {
+        pack.source == self.location() && pack.rev == self.rev
+    }
+}
+
+/// A synced pack as pinned in the lockfile. `sha256` is the SHA-256 of the file for a `url`
+/// pack, and of each file name and contents in name order for a `git` pack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedPack {
+    pub name: String,
+    pub source: String,
+    /// Branch, tag or commit a `git` pack was declared at; the pack is re-resolved when it changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// Commit a `git` pack was synced at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    pub sha256: String,
+    pub files: Vec<String>,
+}
+
+/// The lockfile of a project's pattern packs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PackLock {
+    pub packs: Vec<LockedPack>,
+}
+
+impl PackLock {
+    /// Read a lockfile, an empty lock when there is none
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+    
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+    
+    pub fn get(&self, name: &str) -> Option<&LockedPack> {
+        self.packs.iter().find(|pack| pack.name == name)
+    }
+}
+
+/// Outcome of syncing one pack
+#[derive(Debug, Clone)]
+pub struct SyncedPack {
+    pub pack: LockedPack,
+    /// Number of patterns the pack defines
+    pub patterns: usize,
+    /// Whether the pin differs from the previous lockfile
+    pub changed: bool,
+}
+
+/// The pattern packs of a project, read from its config file
+#[derive(Debug, Clone)]
+pub struct PatternPacks {
+    /// Directory of the config file, holding the lockfile and installed packs
+    dir: PathBuf,
+    sources: Vec<PackSource>,
+}
+
+impl PatternPacks {
+    /// Read the `pattern_packs` section of a project config file, no packs when it has none
+    pub fn from_config_file(config_path: impl AsRef<Path>) -> Result<Self> {
+        let config_path = config_path.as_ref();
+        let config: Value = serde_json::from_str(&fs::read_to_string(config_path)?)?;
+        let sources: Vec<PackSource> = match config.get("pattern_packs") {
+            Some(section) => serde_json::from_value(section.clone())?,
+            None => Vec::new(),
+        };
+        for (i, source) in sources.iter().enumerate() {
+            source.check()?;
+            if sources[..i].iter().any(|other| other.name == source.name) {
+                return Err(pack_error(&source.name, "declared twice".to_string()));
+            }
+        }
+        Ok(Self { dir: config_path.parent().unwrap_or(Path::new(".")).to_path_buf(), sources })
+    }
+    
+    pub fn sources(&self) -> &[PackSource] {
+        &self.sources
+    }
+    
+    pub fn lock_path(&self) -> PathBuf {
+        self.dir.join(LOCK_FILE)
+    }
+    
+    /// Directories of the locked packs in declaration order, to load after the built-in patterns
+    pub fn installed_dirs(&self) -> Result<Vec<PathBuf>> {
+        let lock = PackLock::read(self.lock_path())?;
+        Ok(self.sources.iter()
+            .filter(|source| lock.get(&source.name).is_some_and(|pack| source.locked(pack)))
+            .map(|source| self.dir.join(PACKS_DIR).join(&source.name))
+            .filter(|dir| dir.is_dir())
+            .collect())
+    }
+    
+    /// Fetch every declared pack, verify and validate it, install it and rewrite the lockfile.
+    /// Packs are fetched at their locked revision and must match their locked checksum unless
+    /// `update` re-resolves them. Nothing is installed unless every pack syncs
+    pub fn sync(&self, update: bool) -> Result<Vec<SyncedPack>> {
+        let lock = PackLock::read(self.lock_path())?;
+        let packs_dir = self.dir.join(PACKS_DIR);
+        fs::create_dir_all(&packs_dir)?;
+        
+        let mut synced = Vec::new();
+        let mut staged = Vec::new();
+        for source in &self.sources {
+            let staging = packs_dir.join(format!(".{}.staging", source.name));
+            let locked = lock.get(&source.name)
+                .filter(|pack| !update && source.locked(pack));
+            let result = self.stage(source, locked, &staging);
+            staged.push(staging);
+            match result {
+                Ok(pack) => synced.push(pack),
+                Err(e) => {
+                    for staging in &staged {
+                        let _ = fs::remove_dir_all(staging);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        
+        // Every pack verified; swap them in and drop the ones no longer declared
+        for (source, staging) in self.sources.iter().zip(&staged) {
+            let installed = packs_dir.join(&source.name);
+            if installed.exists() {
+                fs::remove_dir_all(&installed)?;
+            }
+            fs::rename(staging, &installed)?;
+        }
+        for pack in &lock.packs {
+            let installed = packs_dir.join(&pack.name);
+            if self.sources.iter().all(|source| source.name != pack.name) && installed.is_dir() {
+                fs::remove_dir_all(&installed)?;
+            }
+        }
+        for pack in &mut synced {
+            pack.changed = lock.get(&pack.pack.name) != Some(&pack.pack);
+        }
+        PackLock { packs: synced.iter().map(|pack| pack.pack.clone()).collect() }.write(self.lock_path())?;
+        Ok(synced)
+    }
+    
+    /// Fetch a pack into `staging` and check it against its pins
+    fn stage(&self, source: &PackSource, locked: Option<&LockedPack>, staging: &Path) -> Result<SyncedPack> {
+        if staging.exists() {
+            fs::remove_dir_all(staging)?;
+        }
+        fs::create_dir_all(staging)?;
+        
+        let (revision, sha256) = match (&source.url, &source.git) {
+            (Some(url), _) => (None, fetch_url(source, url, staging)?),
+            (None, Some(git)) => {
+                let rev = locked.and_then(|pack| pack.revision.as_deref()).or(source.rev.as_deref());
+                let (revision, sha256) = fetch_git(source, git, rev, staging)?;
+                (Some(revision), sha256)
+            }
+            (None, None) => unreachable!("pack sources are checked when read"),
+        };
+        
+        let pins = [("the project config", source.sha256.as_deref()), (LOCK_FILE, locked.map(|pack| pack.sha256.as_str()))];
+        for (pinned_by, expected) in pins {
+            if let Some(expected) = expected {
+                if !expected.eq_ignore_ascii_case(&sha256) {
+                    return Err(pack_error(&source.name, format!(
+                        "checksum mismatch: {} expects {}, fetched {}",
+                        pinned_by, expected, sha256,
+                    )));
+                }
+            }
+        }
+        
+        let patterns = LibraryRegistry::new().load_dir(staging)?;
+        let mut files: Vec<String> = fs::read_dir(staging)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        files.sort();
+        
+        Ok(SyncedPack {
+            pack: LockedPack { name: source.name.clone(), source: source.location(), rev: source.rev.clone(), revision, sha256, files },
+            patterns,
+            changed: false,
+        })
+    }
+}
+
+/// Download a single-file pack, returning its checksum
+fn fetch_url(source: &PackSource, url: &str, staging: &Path) -> Result<String> {
+    let body = ureq::get(url)
+        .call()
+        .and_then(|mut response| response.body_mut().read_to_string())
+        .map_err(|e| pack_error(&source.name, format!("fetching {} failed: {}", url, e)))?;
+    
+    let file_name = url.rsplit('/').next()
+        .map(|segment| segment.split(['?', '#']).next().unwrap_or_default())
+        .filter(|segment| is_pattern_file(Path::new(segment)))
+        .map(String::from)
+        .unwrap_or_else(|| format!("{}.yaml", source.name));
+    fs::write(staging.join(file_name), &body)?;
+    Ok(hex(&Sha256::digest(body.as_bytes())))
+}
+
+/// Check out a repository pack at `rev` and copy its pattern files, returning the commit and
+/// the checksum of the copied files
+fn fetch_git(source: &PackSource, repo: &str, rev: Option<&str>, staging: &Path) -> Result<(String, String)> {
+    let checkout = staging.with_extension("git");
+    if checkout.exists() {
+        fs::remove_dir_all(&checkout)?;
+    }
+    let result = (|| {
+        git(source, None, &["clone", "--quiet", "--", repo, &checkout.to_string_lossy()])?;
+        if let Some(rev) = rev {
+            git(source, Some(&checkout), &["checkout", "--quiet", "--detach", rev, "--"])?;
+        }
+        let revision = git(source, Some(&checkout), &["rev-parse", "HEAD"])?;
+        
+        // `path` has no `..`, but may still be a symlink out of the checkout
+        let dir = match &source.path {
+            Some(path) => checkout.join(path),
+            None => checkout.clone(),
+        };
+        let dir = dir.canonicalize().map_err(|e| pack_error(&source.name, format!("{}: {}", dir.display(), e)))?;
+        if !dir.starts_with(checkout.canonicalize()?) {
+            return Err(pack_error(&source.name, format!("{} is outside the repository", source.location())));
+        }
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&dir).map_err(|e| pack_error(&source.name, format!("{}: {}", dir.display(), e)))? {
+            let entry = entry?;
+            let path = entry.path();
+            // Symlinked files aren't copied: they may point anywhere
+            if is_pattern_file(&path) && entry.file_type()?.is_file() {
+                files.push(path);
+            }
+        }
+        files.sort();
+        if files.is_empty() {
+            return Err(pack_error(&source.name, format!("no pattern files in {}", source.location())));
+        }
+        
+        let mut hasher = Sha256::new();
+        for file in &files {
+            let name = file.file_name().unwrap_or_default();
+            let content = fs::read(file)?;
+            hasher.update(name.as_encoded_bytes());
+            hasher.update([0]);
+            hasher.update(&content);
+            hasher.update([0]);
+            fs::write(staging.join(name), content)?;
+        }
+        Ok((revision, hex(&hasher.finalize())))
+    })();
+    let _ = fs::remove_dir_all(&checkout);
+    result
+}
+
+/// Run git, returning its trimmed output
+fn git(source: &PackSource, dir: Option<&Path>, args: &[&str]) -> Result<String> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    let output = command.args(args).output()
+        .map_err(|e| pack_error(&source.name, format!("running git failed: {}", e)))?;
+    if !output.status.success() {
+        return Err(pack_error(&source.name, format!(
+            "git {} failed: {}",
+            args[0], String::from_utf8_lossy(&output.stderr).trim(),
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn pack_error(name: &str, message: String) -> CoalesceError {
+    CoalesceError::PatternPackError(format!("`{}`: {}", name, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const PATTERN: &str = "name: greet\nlibrary: greeter\necosystem: javascript\nsemantics:\n  intent: greet\n  category: io\n";
+    
+    /// A project directory holding a git repository `repo` with `patterns.yaml` committed
+    fn project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("coalesce-packs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("repo")).unwrap();
+        fs::write(dir.join("repo/patterns.yaml"), PATTERN).unwrap();
+        git(&dir.join("repo"), &["init", "--quiet"]);
+        commit(&dir.join("repo"));
+        dir
+    }
+    
+    fn commit(repo: &Path) {
+        git(repo, &["add", "-A"]);
+        git(repo, &["-c", "user.name=test", "-c", "user.email=test@example.com", "commit", "--quiet", "-m", "patterns"]);
+    }
+    
+    fn git(repo: &Path, args: &[&str]) {
+        let status = Command::new("git").current_dir(repo).args(args).status().unwrap();
+        assert!(status.success());
+    }
+    
+    fn packs(dir: &Path, pack: Value) -> Result<PatternPacks> {
+        let config = dir.join("coalesce.json");
+        fs::write(&config, serde_json::json!({ "pattern_packs": [pack] }).to_string()).unwrap();
+        PatternPacks::from_config_file(&config)
+    }
+    
+    #[test]
+    fn test_packs_are_held_to_their_pinned_checksum() {
+        let dir = project("checksum");
+        let repo = dir.join("repo").to_string_lossy().to_string();
+        let synced = packs(&dir, serde_json::json!({ "name": "greeter", "git": repo })).unwrap().sync(false).unwrap();
+        assert_eq!(synced[0].patterns, 1);
+        assert_eq!(synced[0].pack.files, ["patterns.yaml"]);
+        let sha256 = synced[0].pack.sha256.clone();
+        
+        // A new commit upstream isn't picked up until the pack is updated
+        fs::write(dir.join("repo/patterns.yaml"), PATTERN.replace("intent: greet", "intent: welcome")).unwrap();
+        commit(&dir.join("repo"));
+        let pinned = packs(&dir, serde_json::json!({ "name": "greeter", "git": repo })).unwrap();
+        assert_eq!(pinned.sync(false).unwrap()[0].pack.sha256, sha256);
+        let updated = pinned.sync(true).unwrap();
+        assert_ne!(updated[0].pack.sha256, sha256);
+        assert!(updated[0].changed);
+        
+        // A lockfile pin that no longer matches fails the sync and keeps the installed pack
+        let mut lock = PackLock::read(pinned.lock_path()).unwrap();
+        lock.packs[0].sha256 = "0".repeat(64);
+        lock.write(pinned.lock_path()).unwrap();
+        let error = pinned.sync(false).unwrap_err().to_string();
+        assert!(error.contains(&format!("checksum mismatch: {} expects {}", LOCK_FILE, "0".repeat(64))), "{}", error);
+        assert!(dir.join(PACKS_DIR).join("greeter/patterns.yaml").is_file());
+        assert!(!dir.join(PACKS_DIR).join(".greeter.staging").exists());
+        
+        // So does a pin in the project config, even when updating
+        let error = packs(&dir, serde_json::json!({ "name": "greeter", "git": repo, "sha256": sha256 })).unwrap()
+            .sync(true).unwrap_err().to_string();
+        assert!(error.contains(&format!("checksum mismatch: the project config expects {}", sha256)), "{}", error);
+        fs::remove_dir_all(&dir).ok();
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_pack_files_are_not_read_through_symlinks_out_of_the_repository() {
+        let dir = project("symlink");
+        fs::create_dir_all(dir.join("outside")).unwrap();
+        fs::write(dir.join("outside/stolen.yaml"), PATTERN.replace("name: greet", "name: stolen")).unwrap();
+        std::os::unix::fs::symlink(dir.join("outside"), dir.join("repo/escape")).unwrap();
+        std::os::unix::fs::symlink(dir.join("outside/stolen.yaml"), dir.join("repo/stolen.yaml")).unwrap();
+        commit(&dir.join("repo"));
+        let repo = dir.join("repo").to_string_lossy().to_string();
+        
+        let error = packs(&dir, serde_json::json!({ "name": "greeter", "git": repo, "path": "escape" })).unwrap()
+            .sync(false).unwrap_err().to_string();
+        assert!(error.contains("is outside the repository"), "{}", error);
+        
+        let synced = packs(&dir, serde_json::json!({ "name": "greeter", "git": repo })).unwrap().sync(false).unwrap();
+        assert_eq!(synced[0].pack.files, ["patterns.yaml"]);
+        assert!(!dir.join(PACKS_DIR).join("greeter/stolen.yaml").exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+    
+    #[test]
+    fn test_git_arguments_are_never_read_as_options() {
+        let dir = project("options");
+        for (field, value) in [("git", "--upload-pack=touch pwned"), ("rev", "-b"), ("rev", "")] {
+            let mut pack = serde_json::json!({ "name": "greeter", "git": dir.join("repo").to_string_lossy() });
+            pack[field] = value.into();
+            let error = packs(&dir, pack).unwrap_err().to_string();
+            assert!(error.contains(&format!("`{}` must not be empty or start with `-`", field)), "{}", error);
+        }
+        
+        // A rev naming a file is checked out as a revision, not used to restore the file
+        let error = packs(&dir, serde_json::json!({ "name": "greeter", "git": dir.join("repo").to_string_lossy(), "rev": "patterns.yaml" })).unwrap()
+            .sync(false).unwrap_err().to_string();
+        assert!(error.contains("git checkout failed") && error.contains("invalid reference"), "{}", error);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
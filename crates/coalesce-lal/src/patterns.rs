@@ -1,16 +1,51 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// A route's HTTP methods quoted and comma-separated, `"GET", "POST"`, for a rule's list of them
+const QUOTED_METHODS: &str = "{{#each (args method)}}{{#unless @first}}, {{/unless}}\"{{this}}\"{{/each}}";
+
+/// axum's filter for a route's HTTP methods, `MethodFilter::GET.or(MethodFilter::POST)`
+const AXUM_METHOD_FILTER: &str = "{{#each (args method)}}{{#if @first}}MethodFilter::{{this}}{{else}}.or(MethodFilter::{{this}}){{/if}}{{/each}}";
+
 /// Represents a library pattern that can be detected and transformed
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LibraryPattern {
     pub name: String,
     pub library: String,
     pub ecosystem: String,
+    #[serde(default)]
     pub signature: String,
     pub semantics: PatternSemantics,
+    #[serde(default)]
     pub parameters: Vec<PatternParameter>,
+    #[serde(default)]
     pub transformations: HashMap<String, TransformRule>,
+    /// Which of several patterns found on the same code for the same intent detection keeps:
+    /// the highest, before the most specific
+    #[serde(default)]
+    pub priority: i32,
+    /// How to find the pattern in code, for libraries the built-in detection doesn't know
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detection: Option<PatternDetection>,
+}
+
+/// Where a project's own pattern appears: a call of what one of `modules` exports under
+/// `call`, or of `method` on what that call returns. The pattern's parameters take the named
+/// group of `text` of their name, else the call's arguments by keyword or else in order, and
+/// one named `target` the variable assigned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternDetection {
+    /// Import paths that bring the library in; none for what the language defines
+    #[serde(default)]
+    pub modules: Vec<String>,
+    pub call: String,
+    /// A method of the instance `call` makes, called directly or through a variable: `track`
+    /// of `client = TelemetryClient(key)` then `client.track(event)`
+    #[serde(default)]
+    pub method: Option<String>,
+    /// A regex the call's text must match
+    #[serde(default)]
+    pub text: Option<String>,
 }
 
 /// Semantic meaning of a library pattern
@@ -18,10 +53,15 @@ pub struct LibraryPattern {
 pub struct PatternSemantics {
     pub intent: String,
     pub category: String,
+    #[serde(default)]
     pub behavior: String,
+    #[serde(default)]
     pub side_effects: Vec<String>,
+    #[serde(default)]
     pub requirements: Vec<String>,
+    #[serde(default)]
     pub mutability: bool,
+    #[serde(default)]
     pub reactivity: bool,
 }
 
@@ -32,6 +72,7 @@ pub struct PatternParameter {
     pub param_type: String,
     pub required: bool,
     pub default_value: Option<String>,
+    #[serde(default)]
     pub description: String,
 }
 
@@ -40,11 +81,37 @@ pub struct PatternParameter {
 pub struct TransformRule {
     pub target_library: String,
     pub target_pattern: String,
+    /// Handlebars over the usage's parameters, as are the setup and cleanup code; see
+    /// `template` for the helpers
     pub template: String,
+    #[serde(default)]
     pub imports: Vec<String>,
     pub setup_code: Option<String>,
     pub cleanup_code: Option<String>,
+    #[serde(default)]
     pub parameter_mappings: HashMap<String, String>,
+    /// Where the target behaves differently from the source, for whoever reviews the output
+    #[serde(default)]
+    pub caveats: Vec<String>,
+    /// Whether the registry derives the rule back, from the target pattern to this one
+    #[serde(default)]
+    pub bidirectional: bool,
+    /// Packages the target project needs for the rule's code; those known for its target
+    /// library and imports when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<PackageRequirement>,
+}
+
+/// A package a target project's manifest declares
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageRequirement {
+    pub name: String,
+    /// The version as the target's manifest writes it: `0.12` for Cargo, `>=2.31` for pip,
+    /// `v1.9.1` for Go, `^1.6.0` for npm, `8.0.0` for NuGet
+    pub version: String,
+    /// Cargo features to enable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
 }
 
 /// Built-in library patterns
@@ -88,6 +155,9 @@ impl PatternLibrary {
                         parameter_mappings: HashMap::from([
                             ("setState".to_string(), "{{state}}.value = ".to_string()),
                         ]),
+                        caveats: Vec::new(),
+                        bidirectional: true,
+                        packages: Vec::new(),
                     }),
                     ("svelte".to_string(), TransformRule {
                         target_library: "svelte".to_string(),
@@ -97,8 +167,13 @@ impl PatternLibrary {
                         setup_code: None,
                         cleanup_code: None,
                         parameter_mappings: HashMap::new(),
+                        caveats: Vec::new(),
+                        bidirectional: true,
+                        packages: Vec::new(),
                     }),
                 ]),
+                priority: 0,
+                detection: None,
             },
             LibraryPattern {
                 name: "useEffect".to_string(),
@@ -139,8 +214,13 @@ impl PatternLibrary {
                         setup_code: None,
                         cleanup_code: None,
                         parameter_mappings: HashMap::new(),
+                        caveats: Vec::new(),
+                        bidirectional: true,
+                        packages: Vec::new(),
                     }),
                 ]),
+                priority: 0,
+                detection: None,
             },
         ]
     }
@@ -175,8 +255,13 @@ impl PatternLibrary {
                         setup_code: None,
                         cleanup_code: None,
                         parameter_mappings: HashMap::new(),
+                        caveats: Vec::new(),
+                        bidirectional: false,
+                        packages: Vec::new(),
                     }),
                 ]),
+                priority: 0,
+                detection: None,
             },
             LibraryPattern {
                 name: "CharField".to_string(),
@@ -210,12 +295,730 @@ impl PatternLibrary {
                         setup_code: None,
                         cleanup_code: None,
                         parameter_mappings: HashMap::new(),
+                        caveats: Vec::new(),
+                        bidirectional: false,
+                        packages: Vec::new(),
                     }),
                 ]),
+                priority: 0,
+                detection: None,
             },
         ]
     }
     
+    /// Get jQuery patterns
+    pub fn jquery_patterns() -> Vec<LibraryPattern> {
+        vec![
+            Self::web_pattern(
+                "jquery",
+                "javascript",
+                "selector",
+                "$(selector)",
+                Self::dom_semantics("dom_query", "Selects the elements matching a CSS selector", &[]),
+                &[("selector", "css_selector")],
+                vec![
+                    ("vanilla", Self::reversible_rule("vanilla", "querySelectorAll", "document.querySelectorAll(\"{{selector}}\")", &[])),
+                    ("react", Self::web_rule("react", "useRef", "const {{target}}Ref = useRef(null);", &["import { useRef } from 'react';"])),
+                ],
+            ),
+            Self::web_pattern(
+                "jquery",
+                "javascript",
+                "ready",
+                "$(document).ready(handler)",
+                Self::dom_semantics("dom_ready", "Runs a handler once the document has loaded", &[]),
+                &[("handler", "function")],
+                vec![
+                    ("vanilla", Self::reversible_rule("vanilla", "DOMContentLoaded", "document.addEventListener(\"DOMContentLoaded\", {{handler}});", &[])),
+                    ("react", Self::web_rule("react", "useEffect", "useEffect({{handler}}, []);", &["import { useEffect } from 'react';"])),
+                ],
+            ),
+            Self::web_pattern(
+                "jquery",
+                "javascript",
+                "event_handler",
+                "$(selector).on(event, handler)",
+                Self::dom_semantics("event_listener", "Calls a handler whenever the selected elements receive an event", &["event_subscription"]),
+                &[("event", "event_name"), ("handler", "function"), ("selector", "css_selector")],
+                vec![
+                    ("vanilla", Self::reversible_rule("vanilla", "addEventListener", "document.querySelectorAll(\"{{selector}}\").forEach((element) => element.addEventListener(\"{{event}}\", {{handler}}));", &[])),
+                    ("react", Self::web_rule("react", "useEffect", "useEffect(() => {\n  const element = ref.current;\n  element.addEventListener(\"{{event}}\", {{handler}});\n  return () => element.removeEventListener(\"{{event}}\", {{handler}});\n}, []);", &["import { useEffect } from 'react';"])),
+                ],
+            ),
+            Self::web_pattern(
+                "jquery",
+                "javascript",
+                "ajax",
+                "$.ajax({ url, method, data, success })",
+                PatternSemantics {
+                    category: "networking".to_string(),
+                    ..Self::dom_semantics("http_request", "Sends an HTTP request and passes the response to a callback", &["network_io"])
+                },
+                &[("url", "url"), ("method", "http_method"), ("callback", "function")],
+                vec![
+                    ("vanilla", Self::web_rule("vanilla", "fetch", "fetch(\"{{url}}\", { method: \"{{method}}\" }).then((response) => response.json()).then({{callback}});", &[])),
+                    ("react", Self::web_rule("react", "useEffect", "useEffect(() => {\n  fetch(\"{{url}}\", { method: \"{{method}}\" }).then((response) => response.json()).then({{callback}});\n}, []);", &["import { useEffect } from 'react';"])),
+                ],
+            ),
+        ]
+    }
+    
+    /// Get lodash patterns, or Underscore's, which lack `cloneDeep`; rules to each
+    /// language's own functions are keyed by its default ecosystem
+    pub fn lodash_patterns(library: &str) -> Vec<LibraryPattern> {
+        let mut patterns = vec![
+            Self::web_pattern(
+                library,
+                "javascript",
+                "map",
+                "_.map(collection, iteratee)",
+                Self::collection_semantics("collection_map", "Builds a list of an iteratee's results for each element"),
+                &[("collection", "collection"), ("iteratee", "function")],
+                vec![
+                    ("vanilla", Self::web_rule("javascript", "Array.prototype.map", "{{collection}}.map({{iteratee}})", &[])),
+                    ("stdlib", Self::web_rule("python", "map", "list(map({{iteratee}}, {{collection}}))", &[])),
+                    ("std", Self::web_rule("rust", "Iterator::map", "{{collection}}.iter().map({{iteratee}}).collect::<Vec<_>>()", &[])),
+                ],
+            ),
+            Self::web_pattern(
+                library,
+                "javascript",
+                "filter",
+                "_.filter(collection, predicate)",
+                Self::collection_semantics("collection_filter", "Builds a list of the elements a predicate holds for"),
+                &[("collection", "collection"), ("predicate", "function")],
+                vec![
+                    ("vanilla", Self::web_rule("javascript", "Array.prototype.filter", "{{collection}}.filter({{predicate}})", &[])),
+                    ("stdlib", Self::web_rule("python", "filter", "list(filter({{predicate}}, {{collection}}))", &[])),
+                    ("std", Self::web_rule("rust", "Iterator::filter", "{{collection}}.iter().filter({{predicate}}).cloned().collect::<Vec<_>>()", &[])),
+                ],
+            ),
+            Self::web_pattern(
+                library,
+                "javascript",
+                "reduce",
+                "_.reduce(collection, reducer, initial)",
+                Self::collection_semantics("collection_reduce", "Folds the elements into an accumulator, starting from an initial value"),
+                &[("collection", "collection"), ("reducer", "function"), ("initial", "any")],
+                vec![
+                    ("vanilla", Self::web_rule("javascript", "Array.prototype.reduce", "{{collection}}.reduce({{reducer}}, {{initial}})", &[])),
+                    ("stdlib", Self::web_rule("python", "functools.reduce", "functools.reduce({{reducer}}, {{collection}}, {{initial}})", &["import functools"])),
+                    ("std", Self::web_rule("rust", "Iterator::fold", "{{collection}}.iter().fold({{initial}}, {{reducer}})", &[])),
+                ],
+            ),
+            Self::web_pattern(
+                library,
+                "javascript",
+                "flatten",
+                "_.flatten(collection)",
+                Self::collection_semantics("collection_flatten", "Flattens a list of lists one level deep"),
+                &[("collection", "collection")],
+                vec![
+                    ("vanilla", Self::web_rule("javascript", "Array.prototype.flat", "{{collection}}.flat()", &[])),
+                    ("stdlib", Self::web_rule("python", "itertools.chain.from_iterable", "list(itertools.chain.from_iterable({{collection}}))", &["import itertools"])),
+                    ("std", Self::web_rule("rust", "Iterator::flatten", "{{collection}}.into_iter().flatten().collect::<Vec<_>>()", &[])),
+                ],
+            ),
+            Self::web_pattern(
+                library,
+                "javascript",
+                "debounce",
+                "_.debounce(function, wait)",
+                PatternSemantics {
+                    category: "timing".to_string(),
+                    side_effects: vec!["timer".to_string()],
+                    ..Self::collection_semantics("debounce", "Delays calling a function until a wait has passed since it was last called")
+                },
+                &[("function", "function"), ("wait", "milliseconds")],
+                vec![
+                    ("vanilla", TransformRule {
+                        setup_code: Some("let {{target}}Timer;".to_string()),
+                        ..Self::web_rule("javascript", "setTimeout", "(...args) => {\n  clearTimeout({{target}}Timer);\n  {{target}}Timer = setTimeout(() => {{function}}(...args), {{wait}});\n}", &[])
+                    }),
+                    ("stdlib", TransformRule {
+                        setup_code: Some("{{target}}_timer = None".to_string()),
+                        ..Self::web_rule("python", "threading.Timer", "def {{target}}(*args):\n    global {{target}}_timer\n    if {{target}}_timer:\n        {{target}}_timer.cancel()\n    {{target}}_timer = threading.Timer({{wait}} / 1000, {{function}}, args)\n    {{target}}_timer.start()", &["import threading"])
+                    }),
+                ],
+            ),
+        ];
+        if library == "lodash" {
+            patterns.push(Self::web_pattern(
+                library,
+                "javascript",
+                "cloneDeep",
+                "_.cloneDeep(value)",
+                Self::collection_semantics("deep_copy", "Copies a value and everything it references"),
+                &[("value", "any")],
+                vec![
+                    ("vanilla", Self::web_rule("javascript", "structuredClone", "structuredClone({{value}})", &[])),
+                    ("stdlib", Self::web_rule("python", "copy.deepcopy", "copy.deepcopy({{value}})", &["import copy"])),
+                    ("std", Self::web_rule("rust", "Clone::clone", "{{value}}.clone()", &[])),
+                ],
+            ));
+        }
+        patterns
+    }
+    
+    /// Get HTTP client patterns: axios, fetch, requests, httpx, Go's net/http and reqwest each
+    /// translate into the others
+    pub fn http_client_patterns() -> Vec<LibraryPattern> {
+        [
+            ("axios", "javascript", "axios({ method, url, data })"),
+            ("fetch", "javascript", "fetch(url, { method, body })"),
+            ("requests", "python", "requests.request(method, url, json=body)"),
+            ("httpx", "python", "httpx.request(method, url, json=body)"),
+            ("net_http", "go", "http.NewRequest(method, url, body)"),
+            ("reqwest", "rust", "client.request(method, url).json(&body).send()"),
+        ].into_iter().map(|(library, ecosystem, signature)| Self::web_pattern(
+            library,
+            ecosystem,
+            "request",
+            signature,
+            PatternSemantics {
+                category: "networking".to_string(),
+                requirements: Vec::new(),
+                ..Self::web_semantics("http_request", "Sends an HTTP request with a method, URL and optional body", &["network_io"])
+            },
+            &[("method", "http_method"), ("url", "url"), ("body", "any")],
+            Self::http_request_rules().into_iter().filter(|(target, _)| *target != library).collect(),
+        )).collect()
+    }
+    
+    /// Get logging patterns: each logger's statements translate into the others' at the same level
+    pub fn logging_patterns() -> Vec<LibraryPattern> {
+        [
+            ("console", "javascript", "console.warn(message)"),
+            ("winston", "javascript", "logger.warn(message, meta)"),
+            ("log4j", "java", "logger.warn(message, args)"),
+            ("log4net", "csharp", "log.Warn(message)"),
+            ("logging", "python", "logger.warning(message, *args)"),
+            ("log", "go", "log.Printf(format, args)"),
+            ("zap", "go", "logger.Warn(message, fields)"),
+            ("tracing", "rust", "tracing::warn!(fields, message)"),
+        ].into_iter().map(|(library, ecosystem, signature)| Self::web_pattern(
+            library,
+            ecosystem,
+            "log",
+            signature,
+            PatternSemantics {
+                category: "logging".to_string(),
+                requirements: Vec::new(),
+                ..Self::web_semantics("structured_logging", "Writes a message with fields to the log at a level", &["log_output"])
+            },
+            &[("level", "log_level"), ("level_title", "log_level"), ("level_long", "log_level"), ("message", "string"), ("fields", "any")],
+            Self::logging_rules().into_iter().filter(|(target, _)| *target != library).collect(),
+        )).collect()
+    }
+    
+    /// Get test framework patterns: jest, xUnit, pytest, Go's testing and Rust's built-in tests
+    /// each translate tests and assertions into the others
+    pub fn test_framework_patterns() -> Vec<LibraryPattern> {
+        let frameworks = [("jest", "javascript"), ("xunit", "csharp"), ("pytest", "python"), ("testing", "go"), ("cargo_test", "rust")];
+        let named: &[(&str, &str)] = &[("name", "string"), ("snake_name", "identifier"), ("pascal_name", "identifier")];
+        let compared: &[(&str, &str)] = &[("actual", "any"), ("expected", "any")];
+        let kinds = [
+            ("test", "test_case", "Declares a test case", named),
+            ("suite", "test_suite", "Groups test cases under a name", named),
+            ("assert_equal", "assert_equal", "Fails the test unless a value equals the expected one", compared),
+            ("assert_not_equal", "assert_not_equal", "Fails the test if a value equals another", compared),
+            ("assert_true", "assert_true", "Fails the test unless a condition holds", &[("value", "boolean")]),
+            ("assert_false", "assert_false", "Fails the test if a condition holds", &[("value", "boolean")]),
+            ("assert_throws", "assert_throws", "Fails the test unless an action throws or panics", &[("error", "type"), ("action", "function")]),
+            ("fail", "fail", "Fails the test with a message", &[("message", "string")]),
+        ];
+        
+        let mut patterns = Vec::new();
+        for (library, ecosystem) in frameworks {
+            for (name, intent, behavior, parameters) in kinds {
+                let rules = Self::test_framework_rules(name);
+                let signature = rules.iter().find(|(target, _)| *target == library).map(|(_, rule)| rule.template.clone()).unwrap_or_default();
+                patterns.push(Self::web_pattern(
+                    library,
+                    ecosystem,
+                    name,
+                    &signature,
+                    PatternSemantics {
+                        category: "testing".to_string(),
+                        requirements: vec!["test_runner".to_string()],
+                        ..Self::web_semantics(intent, behavior, &[])
+                    },
+                    parameters,
+                    rules.into_iter().filter(|(target, _)| *target != library).collect(),
+                ));
+            }
+        }
+        patterns
+    }
+    
+    /// Get datetime patterns: moment, dayjs, Python's datetime, java.time, .NET's DateTime and
+    /// chrono each translate into the others, formats included
+    pub fn datetime_patterns() -> Vec<LibraryPattern> {
+        let libraries = [
+            ("moment", "javascript"),
+            ("dayjs", "javascript"),
+            ("datetime", "python"),
+            ("java_time", "java"),
+            ("system_datetime", "csharp"),
+            ("chrono", "rust"),
+        ];
+        let formatted: &[(&str, &str)] = &[
+            ("value", "datetime"),
+            ("format_moment", "date_format"),
+            ("format_python", "date_format"),
+            ("format_chrono", "date_format"),
+            ("format_java", "date_format"),
+            ("format_dotnet", "date_format"),
+        ];
+        let kinds = [
+            ("now", "datetime_now", "Reads the current local date and time", &[][..]),
+            ("parse", "datetime_parse", "Reads a date and time from text in a format", formatted),
+            ("format", "datetime_format", "Writes a date and time as text in a format", formatted),
+            ("format_now", "datetime_format_now", "Writes the current local date and time as text in a format", &formatted[1..]),
+            ("add", "datetime_add", "Moves a date and time by an amount of a unit of time", &[("value", "datetime"), ("amount", "number"), ("unit", "time_unit"), ("unit_title", "time_unit")]),
+            ("duration", "datetime_duration", "Makes a span of an amount of a unit of time", &[("amount", "number"), ("unit", "time_unit"), ("unit_title", "time_unit")]),
+        ];
+        
+        let mut patterns = Vec::new();
+        for (library, ecosystem) in libraries {
+            for (name, intent, behavior, parameters) in kinds {
+                let rules = Self::datetime_rules(name);
+                let signature = rules.iter().find(|(target, _)| *target == library).map(|(_, rule)| rule.template.clone()).unwrap_or_default();
+                patterns.push(Self::web_pattern(
+                    library,
+                    ecosystem,
+                    name,
+                    &signature,
+                    PatternSemantics {
+                        category: "datetime".to_string(),
+                        requirements: Vec::new(),
+                        ..Self::web_semantics(intent, behavior, &[])
+                    },
+                    parameters,
+                    rules.into_iter().filter(|(target, _)| *target != library).collect(),
+                ));
+            }
+        }
+        patterns
+    }
+    
+    /// Get file I/O patterns: reading, writing and appending whole files, and joining paths
+    pub fn file_io_patterns() -> Vec<LibraryPattern> {
+        let libraries = [
+            ("fs", "javascript"),
+            ("io", "python"),
+            ("os", "go"),
+            ("system_io", "csharp"),
+            ("std_fs", "rust"),
+        ];
+        let kinds = [
+            ("read", "file_read", "Reads a whole file as text", &[("path", "file_path")][..], &["filesystem"][..]),
+            ("write", "file_write", "Writes text to a file, replacing what it held", &[("path", "file_path"), ("data", "text")], &["filesystem"]),
+            ("append", "file_append", "Adds text to the end of a file", &[("path", "file_path"), ("data", "text")], &["filesystem"]),
+            ("path_join", "path_join", "Joins path segments with the platform's separator", &[("base", "file_path"), ("rest", "path_segments")], &[]),
+        ];
+        
+        let mut patterns = Vec::new();
+        for (library, ecosystem) in libraries {
+            for (name, intent, behavior, parameters, side_effects) in kinds {
+                let rules = Self::file_io_rules(name);
+                let signature = rules.iter().find(|(target, _)| *target == library).map(|(_, rule)| rule.template.clone()).unwrap_or_default();
+                patterns.push(Self::web_pattern(
+                    library,
+                    ecosystem,
+                    name,
+                    &signature,
+                    PatternSemantics {
+                        category: "file_io".to_string(),
+                        requirements: Vec::new(),
+                        ..Self::web_semantics(intent, behavior, side_effects)
+                    },
+                    parameters,
+                    rules.into_iter().filter(|(target, _)| *target != library).collect(),
+                ));
+            }
+        }
+        patterns
+    }
+    
+    /// Get concurrency patterns: threads, async tasks, locks and pools, whose targets seldom
+    /// behave quite like their sources, so rules carry caveats
+    pub fn concurrency_patterns() -> Vec<LibraryPattern> {
+        let libraries = [
+            ("java_concurrent", "java"),
+            ("dotnet_tasks", "csharp"),
+            ("threading", "python"),
+            ("asyncio", "python"),
+            ("goroutines", "go"),
+            ("std_thread", "rust"),
+            ("tokio", "rust"),
+        ];
+        let kinds = [
+            ("spawn", "concurrent_execution", "Runs work concurrently with the code that starts it", &[("task", "callable")][..], &["concurrency"][..]),
+            ("join", "concurrent_join", "Waits for concurrent work to finish", &[("tasks", "task_handles")], &[]),
+            ("mutex", "mutual_exclusion", "Makes a lock that lets one holder at a time into what it guards", &[("value", "any")], &[]),
+            ("thread_pool", "thread_pool", "Bounds how many pieces of work run at once", &[("workers", "number")], &[]),
+        ];
+        
+        let mut patterns = Vec::new();
+        for (library, ecosystem) in libraries {
+            for (name, intent, behavior, parameters, side_effects) in kinds {
+                let rules = Self::concurrency_rules(name);
+                // std has no pool of its own
+                let Some(signature) = rules.iter().find(|(target, _)| *target == library).map(|(_, rule)| rule.template.clone()) else {
+                    continue;
+                };
+                patterns.push(Self::web_pattern(
+                    library,
+                    ecosystem,
+                    name,
+                    &signature,
+                    PatternSemantics {
+                        category: "concurrency".to_string(),
+                        requirements: Vec::new(),
+                        ..Self::web_semantics(intent, behavior, side_effects)
+                    },
+                    parameters,
+                    rules.into_iter().filter(|(target, _)| *target != library).collect(),
+                ));
+            }
+        }
+        patterns
+    }
+    
+    /// Get cryptography patterns: a hash and an HMAC per algorithm, secure random bytes and
+    /// AES-GCM encryption, each translating to the target's vetted API
+    pub fn crypto_patterns() -> Vec<LibraryPattern> {
+        let libraries = [
+            ("crypto", "javascript"),
+            ("hashlib", "python"),
+            ("cryptography", "python"),
+            ("go_crypto", "go"),
+            ("dotnet_crypto", "csharp"),
+            ("rustcrypto", "rust"),
+        ];
+        let mut kinds = vec![
+            ("random_bytes".to_string(), "secure_random", "Draws bytes from the operating system's secure random source".to_string(), &[("length", "number")][..]),
+            ("encrypt".to_string(), "authenticated_encryption", "Encrypts and authenticates data with AES-GCM under a key and nonce".to_string(), &[("key", "bytes"), ("nonce", "bytes"), ("data", "bytes")]),
+        ];
+        for algorithm in Self::HASH_ALGORITHMS {
+            let name = algorithm.to_uppercase();
+            kinds.push((format!("hash_{}", algorithm), "cryptographic_hash", format!("Hashes data with {}, as lower-case hex", name), &[("data", "bytes")]));
+            kinds.push((format!("hmac_{}", algorithm), "message_authentication", format!("Signs data with HMAC-{} under a key, as lower-case hex", name), &[("key", "bytes"), ("data", "bytes")]));
+        }
+        
+        let mut patterns = Vec::new();
+        for (library, ecosystem) in libraries {
+            for (name, intent, behavior, parameters) in &kinds {
+                let rules = Self::crypto_rules(name);
+                let Some(signature) = rules.iter().find(|(target, _)| *target == library).map(|(_, rule)| rule.template.clone()) else {
+                    continue;
+                };
+                patterns.push(Self::web_pattern(
+                    library,
+                    ecosystem,
+                    name,
+                    &signature,
+                    PatternSemantics {
+                        category: "cryptography".to_string(),
+                        requirements: Vec::new(),
+                        ..Self::web_semantics(intent, behavior, &[])
+                    },
+                    parameters,
+                    rules.into_iter().filter(|(target, _)| *target != library).collect(),
+                ));
+            }
+        }
+        patterns
+    }
+    
+    /// Get command-line parsing patterns: a program, its options, flags and positional
+    /// arguments, and the parse that reads them
+    pub fn cli_patterns() -> Vec<LibraryPattern> {
+        let libraries = [
+            ("argparse", "python"),
+            ("commander", "javascript"),
+            ("yargs", "javascript"),
+            ("cobra", "go"),
+            ("clap", "rust"),
+            ("system_commandline", "csharp"),
+        ];
+        let argument = &[("parser", "parser"), ("name", "identifier"), ("help", "string")][..];
+        let kinds = [
+            ("program", "cli_program", "Declares a command-line program with a name and a description", &[("parser", "parser"), ("name", "identifier"), ("description", "string")][..], &[][..]),
+            ("option", "cli_option", "Declares a named option taking a value", argument, &[]),
+            ("flag", "cli_flag", "Declares a named option that is on when given", argument, &[]),
+            ("positional", "cli_positional", "Declares an argument taken by its position", argument, &[]),
+            ("parse", "cli_parse", "Parses the process's arguments, exiting with usage on --help or a bad argument", &[("parser", "parser")], &["process_exit"]),
+        ];
+        
+        let mut patterns = Vec::new();
+        for (library, ecosystem) in libraries {
+            for (name, intent, behavior, parameters, side_effects) in kinds {
+                let rules = Self::cli_rules(name);
+                // yargs declares positionals only inside a command's usage
+                let Some(signature) = rules.iter().find(|(target, _)| *target == library).map(|(_, rule)| rule.template.clone()) else {
+                    continue;
+                };
+                patterns.push(Self::web_pattern(
+                    library,
+                    ecosystem,
+                    name,
+                    &signature,
+                    PatternSemantics {
+                        category: "cli".to_string(),
+                        requirements: Vec::new(),
+                        ..Self::web_semantics(intent, behavior, side_effects)
+                    },
+                    parameters,
+                    rules.into_iter().filter(|(target, _)| *target != library).collect(),
+                ));
+            }
+        }
+        patterns
+    }
+    
+    /// Get environment and configuration patterns: variables, `.env` files and config files,
+    /// with rules reading settings bringing the target's config-loading preamble
+    pub fn config_patterns() -> Vec<LibraryPattern> {
+        let libraries = [
+            ("process_env", "javascript"),
+            ("dotenv", "javascript"),
+            ("node_config", "javascript"),
+            ("os_environ", "python"),
+            ("python_dotenv", "python"),
+            ("go_env", "go"),
+            ("godotenv", "go"),
+            ("viper", "go"),
+            ("dotnet_config", "csharp"),
+            ("dotnetenv", "csharp"),
+            ("std_env", "rust"),
+            ("dotenvy", "rust"),
+            ("config_rs", "rust"),
+        ];
+        let kinds = [
+            ("env_get", "environment_read", "Reads an environment variable, which may be unset", &[("name", "identifier")][..]),
+            ("env_default", "environment_read", "Reads an environment variable, falling back to a default when it's unset", &[("name", "identifier"), ("default", "string")]),
+            ("load_dotenv", "environment_file", "Loads variables from a .env file into the environment, keeping those already set", &[("path", "path")]),
+            ("config_load", "config_file", "Loads settings from a config file, overridden by environment variables", &[("path", "path")]),
+            ("config_get", "config_read", "Reads a setting by its nested key", &[("config", "config"), ("key", "string"), ("section_key", "string")]),
+        ];
+        
+        let mut patterns = Vec::new();
+        for (library, ecosystem) in libraries {
+            for (name, intent, behavior, parameters) in kinds {
+                let rules = Self::config_rules(name);
+                let Some(signature) = rules.iter().find(|(target, _)| *target == library).map(|(_, rule)| rule.template.clone()) else {
+                    continue;
+                };
+                patterns.push(Self::web_pattern(
+                    library,
+                    ecosystem,
+                    name,
+                    &signature,
+                    PatternSemantics {
+                        category: "configuration".to_string(),
+                        requirements: Vec::new(),
+                        ..Self::web_semantics(intent, behavior, &[])
+                    },
+                    parameters,
+                    rules.into_iter().filter(|(target, _)| *target != library).collect(),
+                ));
+            }
+        }
+        patterns
+    }
+    
+    /// Get numpy patterns: arrays map onto ndarray in Rust and gonum's matrices in Go
+    pub fn numpy_patterns() -> Vec<LibraryPattern> {
+        let mat = "gonum.org/v1/gonum/mat";
+        let array_pattern = |name: &str, signature: &str, intent: &str, behavior: &str, parameters: &[(&str, &str)], ndarray: TransformRule, gonum: TransformRule| Self::web_pattern(
+            "numpy",
+            "python",
+            name,
+            signature,
+            Self::array_semantics(intent, behavior),
+            parameters,
+            vec![("ndarray", ndarray), ("gonum", gonum)],
+        );
+        let shape: &[(&str, &str)] = &[("shape", "shape"), ("rows", "integer"), ("cols", "integer")];
+        
+        let mut patterns = vec![
+            array_pattern(
+                "array",
+                "np.array(values)",
+                "array_creation",
+                "Makes an array of the values in a list",
+                &[("values", "list"), ("elements", "expression_list")],
+                Self::web_rule("ndarray", "array!", "let {{target}} = array!{{values}};", &["use ndarray::array;"]),
+                Self::web_rule("gonum", "NewVecDense", "{{target}}Data := []float64{ {{elements}} }\n{{target}} := mat.NewVecDense(len({{target}}Data), {{target}}Data)", &[mat]),
+            ),
+            array_pattern(
+                "zeros",
+                "np.zeros(shape)",
+                "array_fill",
+                "Makes an array of a shape filled with zeros",
+                shape,
+                Self::web_rule("ndarray", "Array2::zeros", "let {{target}} = Array2::<f64>::zeros(({{rows}}, {{cols}}));", &["use ndarray::Array2;"]),
+                Self::web_rule("gonum", "NewDense", "{{target}} := mat.NewDense({{rows}}, {{cols}}, nil)", &[mat]),
+            ),
+            array_pattern(
+                "ones",
+                "np.ones(shape)",
+                "array_fill",
+                "Makes an array of a shape filled with ones",
+                shape,
+                Self::web_rule("ndarray", "Array2::ones", "let {{target}} = Array2::<f64>::ones(({{rows}}, {{cols}}));", &["use ndarray::Array2;"]),
+                Self::web_rule("gonum", "Dense.Apply", "{{target}} := mat.NewDense({{rows}}, {{cols}}, nil)\n{{target}}.Apply(func(_, _ int, _ float64) float64 { return 1 }, {{target}})", &[mat]),
+            ),
+            array_pattern(
+                "arange",
+                "np.arange(start, stop, step)",
+                "array_range",
+                "Makes an array of evenly stepped values from a start up to a stop",
+                &[("start", "number"), ("stop", "number"), ("step", "number")],
+                Self::web_rule("ndarray", "Array1::range", "let {{target}} = Array1::range({{start}} as f64, {{stop}} as f64, {{step}} as f64);", &["use ndarray::Array1;"]),
+                Self::web_rule("gonum", "append", "var {{target}} []float64\nfor v := float64({{start}}); v < {{stop}}; v += {{step}} {\n\t{{target}} = append({{target}}, v)\n}", &[]),
+            ),
+            array_pattern(
+                "linspace",
+                "np.linspace(start, stop, num)",
+                "array_range",
+                "Makes an array of a number of evenly spaced values from a start to a stop",
+                &[("start", "number"), ("stop", "number"), ("num", "integer")],
+                Self::web_rule("ndarray", "Array1::linspace", "let {{target}} = Array1::linspace({{start}} as f64, {{stop}} as f64, {{num}});", &["use ndarray::Array1;"]),
+                Self::web_rule("gonum", "floats.Span", "{{target}} := floats.Span(make([]float64, {{num}}), {{start}}, {{stop}})", &["gonum.org/v1/gonum/floats"]),
+            ),
+            array_pattern(
+                "dot",
+                "np.dot(left, right)",
+                "matrix_multiply",
+                "Multiplies two arrays as matrices",
+                &[("left", "array"), ("right", "array")],
+                Self::web_rule("ndarray", "dot", "let {{target}} = {{left}}.dot(&{{right}});", &[]),
+                Self::web_rule("gonum", "Dense.Mul", "var {{target}} mat.Dense\n{{target}}.Mul({{left}}, {{right}})", &[mat]),
+            ),
+            array_pattern(
+                "ufunc",
+                "np.sqrt(array)",
+                "vectorized_map",
+                "Applies a math function to each element of an array",
+                &[("function", "identifier"), ("function_title", "identifier"), ("array", "array")],
+                Self::web_rule("ndarray", "mapv", "let {{target}} = {{array}}.mapv(f64::{{function}});", &[]),
+                Self::web_rule("gonum", "Dense.Apply", "var {{target}} mat.Dense\n{{target}}.Apply(func(_, _ int, v float64) float64 { return math.{{function_title}}(v) }, {{array}})", &[mat, "math"]),
+            ),
+        ];
+        // Reductions are over the whole array
+        for (reduction, behavior, ndarray, gonum) in [
+            ("sum", "Adds up the elements of an array", "{{array}}.sum()", "{{target}} := mat.Sum({{array}})"),
+            ("mean", "Averages the elements of an array", "{{array}}.mean().unwrap()", "rows, cols := {{array}}.Dims()\n{{target}} := mat.Sum({{array}}) / float64(rows*cols)"),
+            ("max", "Finds the largest element of an array", "{{array}}.fold(f64::NEG_INFINITY, |max, &v| max.max(v))", "{{target}} := mat.Max({{array}})"),
+            ("min", "Finds the smallest element of an array", "{{array}}.fold(f64::INFINITY, |min, &v| min.min(v))", "{{target}} := mat.Min({{array}})"),
+        ] {
+            patterns.push(array_pattern(
+                reduction,
+                &format!("np.{}(array, axis)", reduction),
+                "array_reduce",
+                behavior,
+                &[("array", "array"), ("axis", "integer")],
+                Self::web_rule("ndarray", reduction, &format!("let {{{{target}}}} = {};", ndarray), &[]),
+                Self::web_rule("gonum", reduction, gonum, &[mat]),
+            ));
+        }
+        patterns
+    }
+    
+    /// Get pandas patterns: DataFrame operations map onto polars' lazy frames
+    pub fn pandas_patterns() -> Vec<LibraryPattern> {
+        let polars = "use polars::prelude::*;";
+        let frame_pattern = |name: &str, signature: &str, intent: &str, behavior: &str, parameters: &[(&str, &str)], template: Option<&str>, imports: &[&str]| Self::web_pattern(
+            "pandas",
+            "python",
+            name,
+            signature,
+            Self::data_frame_semantics(intent, behavior),
+            parameters,
+            template.map(|template| ("polars", Self::web_rule("polars", name, template, imports))).into_iter().collect(),
+        );
+        
+        vec![
+            // polars builds frames from columns of Series, which a dict literal doesn't spell out
+            frame_pattern("DataFrame", "pd.DataFrame(data)", "dataframe_creation", "Makes a DataFrame from columns of values", &[("data", "mapping")], None, &[]),
+            frame_pattern(
+                "read_csv",
+                "pd.read_csv(path)",
+                "dataframe_read",
+                "Reads a CSV file into a DataFrame",
+                &[("path", "path")],
+                Some("let {{target}} = CsvReadOptions::default().try_into_reader_with_file_path(Some({{path}}.into()))?.finish()?;"),
+                &[polars],
+            ),
+            frame_pattern(
+                "query",
+                "df.query(condition)",
+                "dataframe_filter",
+                "Keeps rows matching a condition",
+                &[("frame", "dataframe"), ("condition", "expression")],
+                Some("let {{target}} = {{frame}}.clone().lazy().filter(sql_expr(\"{{condition}}\")?).collect()?;"),
+                &[polars, "use polars::sql::sql_expr;"],
+            ),
+            frame_pattern(
+                "groupby",
+                "df.groupby(columns)",
+                "dataframe_group",
+                "Groups rows by column values",
+                &[("frame", "dataframe"), ("columns", "column_list")],
+                Some("{{frame}}.clone().lazy().group_by([col({{columns}})])"),
+                &[polars],
+            ),
+            frame_pattern(
+                "agg",
+                "df.groupby(columns).agg(aggregations)",
+                "dataframe_aggregate",
+                "Reduces each group to a single row",
+                &[("frame", "dataframe"), ("aggregations", "mapping")],
+                Some("let {{target}} = {{frame}}.agg([{{aggregations}}]).collect()?;"),
+                &[polars],
+            ),
+            frame_pattern(
+                "sort_values",
+                "df.sort_values(by)",
+                "dataframe_sort",
+                "Orders rows by column values",
+                &[("frame", "dataframe"), ("columns", "column_list")],
+                Some("let {{target}} = {{frame}}.sort([{{columns}}], SortMultipleOptions::default())?;"),
+                &[polars],
+            ),
+            frame_pattern(
+                "merge",
+                "df.merge(other, on, how)",
+                "dataframe_join",
+                "Joins the rows of two DataFrames on matching column values",
+                &[("frame", "dataframe"), ("other", "dataframe"), ("on", "column"), ("how", "join_type")],
+                Some("let {{target}} = {{frame}}.join(&{{other}}, [{{on}}], [{{on}}], JoinArgs::new(JoinType::{{how}}), None)?;"),
+                &[polars],
+            ),
+            frame_pattern(
+                "assign",
+                "df.assign(column=expression)",
+                "dataframe_mutate",
+                "Adds or replaces a computed column",
+                &[("frame", "dataframe"), ("column", "identifier"), ("expression", "expression")],
+                Some("let {{target}} = {{frame}}.clone().lazy().with_column(({{expression}}).alias(\"{{column}}\")).collect()?;"),
+                &[polars],
+            ),
+            frame_pattern(
+                "to_csv",
+                "df.to_csv(path)",
+                "dataframe_write",
+                "Writes a DataFrame to a CSV file",
+                &[("frame", "dataframe"), ("path", "path")],
+                Some("CsvWriter::new(&mut File::create({{path}})?).finish(&mut {{frame}})?;"),
+                &[polars, "use std::fs::File;"],
+            ),
+        ]
+    }
+    
     /// Get networking patterns (cross-platform)
     pub fn networking_patterns() -> Vec<LibraryPattern> {
         vec![
@@ -243,6 +1046,9 @@ impl PatternLibrary {
                         setup_code: None,
                         cleanup_code: None,
                         parameter_mappings: HashMap::new(),
+                        caveats: Vec::new(),
+                        bidirectional: false,
+                        packages: Vec::new(),
                     }),
                     ("go".to_string(), TransformRule {
                         target_library: "net".to_string(),
@@ -252,6 +1058,9 @@ impl PatternLibrary {
                         setup_code: None,
                         cleanup_code: None,
                         parameter_mappings: HashMap::new(),
+                        caveats: Vec::new(),
+                        bidirectional: false,
+                        packages: Vec::new(),
                     }),
                     ("python".to_string(), TransformRule {
                         target_library: "socket".to_string(),
@@ -261,9 +1070,1357 @@ impl PatternLibrary {
                         setup_code: None,
                         cleanup_code: None,
                         parameter_mappings: HashMap::new(),
+                        caveats: Vec::new(),
+                        bidirectional: false,
+                        packages: Vec::new(),
                     }),
                 ]),
+                priority: 0,
+                detection: None,
             },
         ]
     }
+    
+    /// Get tidyverse (dplyr) data-frame patterns
+    pub fn tidyverse_patterns() -> Vec<LibraryPattern> {
+        vec![
+            Self::dplyr_verb(
+                "filter",
+                "df %>% filter(condition)",
+                "dataframe_filter",
+                "Keeps rows matching a condition",
+                vec![("condition", "expression")],
+                ".query(\"{{condition}}\")",
+            ),
+            Self::dplyr_verb(
+                "select",
+                "df %>% select(columns)",
+                "dataframe_select",
+                "Keeps a subset of columns",
+                vec![("columns", "column_list")],
+                "[[{{columns}}]]",
+            ),
+            Self::dplyr_verb(
+                "mutate",
+                "df %>% mutate(column = expression)",
+                "dataframe_mutate",
+                "Adds or replaces a computed column",
+                vec![("column", "identifier"), ("expression", "expression")],
+                ".assign({{column}}=lambda d: {{expression}})",
+            ),
+            Self::dplyr_verb(
+                "group_by",
+                "df %>% group_by(columns)",
+                "dataframe_group",
+                "Groups rows by column values",
+                vec![("columns", "column_list")],
+                ".groupby([{{columns}}])",
+            ),
+            Self::dplyr_verb(
+                "summarise",
+                "df %>% summarise(column = aggregate)",
+                "dataframe_aggregate",
+                "Reduces each group to a single row",
+                vec![("column", "identifier"), ("expression", "expression")],
+                ".apply(lambda d: pd.Series({\"{{column}}\": {{expression}}}))",
+            ),
+            Self::dplyr_verb(
+                "arrange",
+                "df %>% arrange(columns)",
+                "dataframe_sort",
+                "Orders rows by column values",
+                vec![("columns", "column_list")],
+                ".sort_values([{{columns}}])",
+            ),
+        ]
+    }
+    
+    /// Get ABAP Open SQL database access patterns
+    pub fn open_sql_patterns() -> Vec<LibraryPattern> {
+        vec![
+            Self::open_sql_statement(
+                "select_single",
+                "SELECT SINGLE fields FROM table INTO target WHERE condition",
+                "database_query",
+                "Reads at most one row matching the condition",
+                vec![("table", "table_name"), ("condition", "sql_condition")],
+                "session.query({{table}}).filter(text(\"{{condition}}\")).first()",
+                false,
+            ),
+            Self::open_sql_statement(
+                "select_into_table",
+                "SELECT fields FROM table INTO TABLE target WHERE condition",
+                "database_query",
+                "Reads all matching rows into an internal table",
+                vec![("table", "table_name"), ("condition", "sql_condition")],
+                "session.query({{table}}).filter(text(\"{{condition}}\")).all()",
+                false,
+            ),
+            Self::open_sql_statement(
+                "insert",
+                "INSERT table FROM work_area",
+                "database_insert",
+                "Inserts a row from a work area",
+                vec![("table", "table_name"), ("source", "work_area")],
+                "session.add({{table}}(**{{source}}))",
+                true,
+            ),
+            Self::open_sql_statement(
+                "update",
+                "UPDATE table SET assignments WHERE condition",
+                "database_update",
+                "Updates columns of the rows matching the condition",
+                vec![("table", "table_name"), ("assignments", "sql_assignments"), ("condition", "sql_condition")],
+                "session.query({{table}}).filter(text(\"{{condition}}\")).update({{assignments}})",
+                true,
+            ),
+            Self::open_sql_statement(
+                "delete",
+                "DELETE FROM table WHERE condition",
+                "database_delete",
+                "Deletes the rows matching the condition",
+                vec![("table", "table_name"), ("condition", "sql_condition")],
+                "session.query({{table}}).filter(text(\"{{condition}}\")).delete()",
+                true,
+            ),
+        ]
+    }
+    
+    /// Get Flask web framework patterns
+    pub fn flask_patterns() -> Vec<LibraryPattern> {
+        vec![
+            Self::web_pattern(
+                "flask",
+                "python",
+                "app",
+                "app = Flask(__name__)",
+                Self::web_semantics("web_application", "Creates the application routes are registered on", &[]),
+                &[("var", "identifier")],
+                Self::web_application_rules(),
+            ),
+            Self::web_pattern(
+                "flask",
+                "python",
+                "blueprint",
+                "bp = Blueprint(name, __name__)",
+                Self::web_semantics("route_group", "Groups routes to register on an application together", &[]),
+                &[("var", "identifier"), ("name", "string"), ("prefix", "route_path")],
+                Self::web_group_rules(),
+            ),
+            Self::web_pattern(
+                "flask",
+                "python",
+                "route",
+                "@app.route(path, methods=[...])",
+                Self::web_semantics("http_route", "Routes requests for a path and method to a handler", &["http_listener"]),
+                &[("path", "route_path"), ("colon_path", "route_path"), ("method", "http_method"), ("handler", "function")],
+                Self::web_route_rules(),
+            ),
+            Self::web_pattern(
+                "flask",
+                "python",
+                "request_json",
+                "data = request.get_json()",
+                Self::web_semantics("http_request_body", "Reads the JSON body of the current request", &[]),
+                &[("var", "identifier")],
+                vec![
+                    ("axum", Self::web_rule("axum", "Json", "Json({{var}}): Json<serde_json::Value>", &["use axum::Json;"])),
+                    ("gin", Self::web_rule("gin", "ShouldBindJSON", "var {{var}} map[string]any\nc.ShouldBindJSON(&{{var}})", &["github.com/gin-gonic/gin"])),
+                    ("aspnet", Self::web_rule("aspnet", "ReadFromJsonAsync", "var {{var}} = await request.ReadFromJsonAsync<JsonElement>();", &["using System.Text.Json;"])),
+                ],
+            ),
+            Self::web_pattern(
+                "flask",
+                "python",
+                "query_param",
+                "value = request.args.get(name)",
+                Self::web_semantics("http_query_parameter", "Reads a query string parameter of the current request", &[]),
+                &[("var", "identifier"), ("name", "string")],
+                Self::web_query_rules(),
+            ),
+            Self::web_pattern(
+                "flask",
+                "python",
+                "jsonify",
+                "return jsonify(body)",
+                Self::web_semantics("json_response", "Serializes a value into a JSON response", &[]),
+                &[("body", "expression")],
+                vec![
+                    ("axum", Self::web_rule("axum", "Json", "Json({{body}})", &["use axum::Json;"])),
+                    ("gin", Self::web_rule("gin", "JSON", "c.JSON(http.StatusOK, {{body}})", &["net/http", "github.com/gin-gonic/gin"])),
+                    ("aspnet", Self::web_rule("aspnet", "Results.Json", "Results.Json({{body}})", &[])),
+                ],
+            ),
+            Self::web_pattern(
+                "flask",
+                "python",
+                "abort",
+                "abort(status)",
+                Self::web_semantics("http_error", "Ends the request with an error status", &["control_flow_exit"]),
+                &[("status", "integer")],
+                vec![
+                    ("axum", Self::web_rule("axum", "StatusCode", "return Err(StatusCode::from_u16({{status}}).unwrap())", &["use axum::http::StatusCode;"])),
+                    ("gin", Self::web_rule("gin", "AbortWithStatus", "c.AbortWithStatus({{status}})", &["github.com/gin-gonic/gin"])),
+                    ("aspnet", Self::web_rule("aspnet", "Results.StatusCode", "return Results.StatusCode({{status}});", &[])),
+                ],
+            ),
+        ]
+    }
+    
+    /// Get FastAPI web framework patterns
+    pub fn fastapi_patterns() -> Vec<LibraryPattern> {
+        vec![
+            Self::web_pattern(
+                "fastapi",
+                "python",
+                "app",
+                "app = FastAPI()",
+                Self::web_semantics("web_application", "Creates the application routes are registered on", &[]),
+                &[("var", "identifier")],
+                Self::web_application_rules(),
+            ),
+            Self::web_pattern(
+                "fastapi",
+                "python",
+                "router",
+                "router = APIRouter(prefix=path)",
+                Self::web_semantics("route_group", "Groups routes under a common prefix", &[]),
+                &[("var", "identifier"), ("prefix", "route_path")],
+                Self::web_group_rules(),
+            ),
+            Self::web_pattern(
+                "fastapi",
+                "python",
+                "route",
+                "@app.get(path)",
+                Self::web_semantics("http_route", "Routes requests for a path and method to a handler", &["http_listener"]),
+                &[("path", "route_path"), ("colon_path", "route_path"), ("method", "http_method"), ("handler", "function")],
+                Self::web_route_rules(),
+            ),
+            Self::web_pattern(
+                "fastapi",
+                "python",
+                "depends",
+                "param: Type = Depends(provider)",
+                Self::web_semantics("dependency_injection", "Injects the value a provider returns into a handler parameter", &[]),
+                &[("parameter", "identifier"), ("type", "type"), ("provider", "function")],
+                vec![
+                    ("axum", Self::web_rule("axum", "State", "State({{parameter}}): State<{{type}}>", &["use axum::extract::State;"])),
+                    ("gin", Self::web_rule("gin", "Context", "{{parameter}} := {{provider}}(c)", &["github.com/gin-gonic/gin"])),
+                    ("aspnet", Self::web_rule("aspnet", "FromServices", "[FromServices] {{type}} {{parameter}}", &["using Microsoft.AspNetCore.Mvc;"])),
+                ],
+            ),
+            Self::web_pattern(
+                "fastapi",
+                "python",
+                "http_exception",
+                "raise HTTPException(status_code=status, detail=message)",
+                Self::web_semantics("http_error", "Ends the request with an error status and detail", &["control_flow_exit"]),
+                &[("status", "integer"), ("detail", "expression")],
+                vec![
+                    ("axum", Self::web_rule("axum", "StatusCode", "return Err((StatusCode::from_u16({{status}}).unwrap(), {{detail}}))", &["use axum::http::StatusCode;"])),
+                    ("gin", Self::web_rule("gin", "AbortWithStatusJSON", "c.AbortWithStatusJSON({{status}}, gin.H{\"detail\": {{detail}}})", &["github.com/gin-gonic/gin"])),
+                    ("aspnet", Self::web_rule("aspnet", "Results.Problem", "return Results.Problem({{detail}}, statusCode: {{status}});", &[])),
+                ],
+            ),
+        ]
+    }
+    
+    /// Get Spring Boot patterns
+    pub fn spring_patterns() -> Vec<LibraryPattern> {
+        vec![
+            Self::web_pattern(
+                "spring",
+                "java",
+                "rest_controller",
+                "@RestController class Name",
+                Self::web_semantics("http_controller", "Declares a class whose methods handle HTTP requests", &[]),
+                &[("name", "class")],
+                vec![
+                    ("aspnet", Self::web_rule("aspnet", "ControllerBase", "[ApiController]\npublic class {{name}} : ControllerBase", &["using Microsoft.AspNetCore.Mvc;"])),
+                    ("axum", Self::web_rule("axum", "Router", "pub fn {{name}}() -> Router {\n    Router::new()\n}", &["use axum::Router;"])),
+                    ("fastapi", Self::web_rule("fastapi", "APIRouter", "{{name}} = APIRouter()", &["from fastapi import APIRouter"])),
+                ],
+            ),
+            Self::web_pattern(
+                "spring",
+                "java",
+                "request_mapping",
+                "@RequestMapping(path) class Name",
+                Self::web_semantics("route_group", "Prefixes the routes of a controller with a path", &[]),
+                &[("name", "class"), ("path", "route_path")],
+                vec![
+                    ("aspnet", Self::web_rule("aspnet", "Route", "[Route(\"{{path}}\")]", &["using Microsoft.AspNetCore.Mvc;"])),
+                    ("axum", Self::web_rule("axum", "Router::nest", ".nest(\"{{path}}\", {{name}}())", &["use axum::Router;"])),
+                    ("fastapi", Self::web_rule("fastapi", "APIRouter", "{{name}} = APIRouter(prefix=\"{{path}}\")", &["from fastapi import APIRouter"])),
+                ],
+            ),
+            Self::web_pattern(
+                "spring",
+                "java",
+                "route",
+                "@GetMapping(path) fun handler()",
+                Self::web_semantics("http_route", "Routes requests for a path and method to a handler", &["http_listener"]),
+                &[("path", "route_path"), ("colon_path", "route_path"), ("method", "http_method"), ("handler", "function")],
+                vec![
+                    ("aspnet", Self::web_rule("aspnet", "AcceptVerbs", &format!("[AcceptVerbs({}), Route(\"{{{{path}}}}\")]", QUOTED_METHODS), &["using Microsoft.AspNetCore.Mvc;"])),
+                    ("axum", Self::web_rule("axum", "Router::route", &format!(".route(\"{{{{path}}}}\", on({}, {{{{handler}}}}))", AXUM_METHOD_FILTER), &["use axum::routing::{on, MethodFilter};"])),
+                    ("fastapi", Self::web_rule("fastapi", "api_route", &format!("@router.api_route(\"{{{{path}}}}\", methods=[{}])", QUOTED_METHODS), &["from fastapi import APIRouter"])),
+                ],
+            ),
+            Self::web_pattern(
+                "spring",
+                "java",
+                "autowired",
+                "@Autowired lateinit var name: Type",
+                Self::web_semantics("dependency_injection", "Injects a managed component into a field or constructor parameter", &[]),
+                &[("parameter", "identifier"), ("type", "type")],
+                vec![
+                    ("aspnet", Self::web_rule("aspnet", "constructor injection", "{{type}} {{parameter}}", &[])),
+                    ("axum", Self::web_rule("axum", "State", "State({{parameter}}): State<{{type}}>", &["use axum::extract::State;"])),
+                    ("fastapi", Self::web_rule("fastapi", "Depends", "{{parameter}}: {{type}} = Depends({{type}})", &["from fastapi import Depends"])),
+                ],
+            ),
+            Self::web_pattern(
+                "spring",
+                "java",
+                "entity",
+                "@Entity class Name",
+                PatternSemantics {
+                    requirements: vec!["database_connection".to_string()],
+                    ..Self::web_semantics("orm_model", "Maps a class onto a database table", &["database_table_creation"])
+                },
+                &[("name", "class")],
+                vec![
+                    ("aspnet", Self::web_rule("entity_framework", "DbSet", "public DbSet<{{name}}> {{name}}s { get; set; }", &["using Microsoft.EntityFrameworkCore;"])),
+                    ("axum", Self::web_rule("sea_orm", "DeriveEntityModel", "#[derive(Clone, Debug, DeriveEntityModel)]\n#[sea_orm(table_name = \"{{name}}\")]\npub struct Model", &["use sea_orm::entity::prelude::*;"])),
+                    ("fastapi", Self::web_rule("sqlalchemy", "declarative_base", "class {{name}}(Base):\n    __tablename__ = '{{name}}'", &["from sqlalchemy.orm import declarative_base"])),
+                ],
+            ),
+            Self::web_pattern(
+                "spring",
+                "java",
+                "component",
+                "@Service class Name",
+                Self::web_semantics("injectable_service", "Registers a class as a component other components are injected with", &[]),
+                &[("name", "class")],
+                vec![
+                    ("aspnet", Self::web_rule("aspnet", "AddScoped", "builder.Services.AddScoped<{{name}}>();", &[])),
+                    ("axum", Self::web_rule("axum", "with_state", ".with_state(Arc::new({{name}}::new()))", &["use std::sync::Arc;"])),
+                    ("fastapi", Self::web_rule("fastapi", "Depends", "def provide_{{name}}() -> {{name}}:\n    return {{name}}()", &["from fastapi import Depends"])),
+                ],
+            ),
+        ]
+    }
+    
+    /// Get ASP.NET Core patterns
+    pub fn aspnet_patterns() -> Vec<LibraryPattern> {
+        vec![
+            Self::web_pattern(
+                "aspnet",
+                "csharp",
+                "controller",
+                "public class NameController : ControllerBase",
+                Self::web_semantics("http_controller", "Declares a class whose actions handle HTTP requests", &[]),
+                &[("name", "class")],
+                vec![
+                    ("django", Self::web_rule("django", "View", "class {{name}}(View):\n    pass", &["from django.views import View"])),
+                    ("axum", Self::web_rule("axum", "Router", "pub fn {{name}}() -> Router {\n    Router::new()\n}", &["use axum::Router;"])),
+                ],
+            ),
+            Self::web_pattern(
+                "aspnet",
+                "csharp",
+                "route_prefix",
+                "[Route(path)] class NameController",
+                Self::web_semantics("route_group", "Prefixes the routes of a controller with a path", &[]),
+                &[("name", "class"), ("path", "route_path")],
+                vec![
+                    ("django", Self::web_rule("django", "include", "path(\"{{path}}/\", include({{name}}.urls))", &["from django.urls import include, path"])),
+                    ("axum", Self::web_rule("axum", "Router::nest", ".nest(\"/{{path}}\", {{name}}())", &["use axum::Router;"])),
+                ],
+            ),
+            Self::web_pattern(
+                "aspnet",
+                "csharp",
+                "route",
+                "[HttpGet(path)] public IActionResult Handler()",
+                Self::web_semantics("http_route", "Routes requests for a path and method to an action", &["http_listener"]),
+                &[("path", "route_path"), ("colon_path", "route_path"), ("angle_path", "route_path"), ("method", "http_method"), ("handler", "function")],
+                vec![
+                    ("django", Self::web_rule("django", "require_http_methods", &format!("path(\"{{{{angle_path}}}}\", require_http_methods([{}])({{{{handler}}}}))", QUOTED_METHODS), &["from django.urls import path", "from django.views.decorators.http import require_http_methods"])),
+                    ("axum", Self::web_rule("axum", "Router::route", &format!(".route(\"{{{{path}}}}\", on({}, {{{{handler}}}}))", AXUM_METHOD_FILTER), &["use axum::routing::{on, MethodFilter};"])),
+                ],
+            ),
+            Self::web_pattern(
+                "aspnet",
+                "csharp",
+                "service_registration",
+                "services.AddScoped<IService, Service>()",
+                Self::web_semantics("dependency_injection", "Registers a service implementation with the dependency injection container", &[]),
+                &[("lifetime", "identifier"), ("service", "type"), ("implementation", "type")],
+                vec![
+                    ("django", Self::web_rule("django", "module instance", "{{service}} = {{implementation}}()", &[])),
+                    ("axum", Self::web_rule("axum", "with_state", ".with_state(Arc::new({{implementation}}::new()) as Arc<dyn {{service}}>)", &["use std::sync::Arc;"])),
+                ],
+            ),
+        ]
+    }
+    
+    /// Get Entity Framework Core patterns
+    pub fn entity_framework_patterns() -> Vec<LibraryPattern> {
+        vec![
+            Self::web_pattern(
+                "entity_framework",
+                "csharp",
+                "db_context",
+                "public class AppContext : DbContext",
+                Self::database_semantics("database_session", "Tracks entities and saves their changes as a unit of work", &[]),
+                &[("name", "class")],
+                vec![
+                    ("django", Self::web_rule("django", "transaction.atomic", "with transaction.atomic():", &["from django.db import transaction"])),
+                    ("sqlalchemy", Self::web_rule("sqlalchemy", "sessionmaker", "{{name}} = sessionmaker(bind=engine)", &["from sqlalchemy.orm import sessionmaker"])),
+                    ("diesel", Self::web_rule("diesel", "PgConnection", "pub type {{name}} = PgConnection;", &["use diesel::pg::PgConnection;"])),
+                    ("sea_orm", Self::web_rule("sea_orm", "DatabaseConnection", "pub type {{name}} = DatabaseConnection;", &["use sea_orm::DatabaseConnection;"])),
+                ],
+            ),
+            Self::web_pattern(
+                "entity_framework",
+                "csharp",
+                "db_set",
+                "public DbSet<Entity> Entities { get; set; }",
+                Self::database_semantics("orm_model", "Maps an entity class onto a database table", &["database_table_creation"]),
+                &[("name", "identifier"), ("entity", "class")],
+                vec![
+                    ("django", Self::web_rule("django", "Model", "class {{entity}}(models.Model):\n    class Meta:\n        db_table = \"{{name}}\"", &["from django.db import models"])),
+                    ("sqlalchemy", Self::web_rule("sqlalchemy", "declarative_base", "class {{entity}}(Base):\n    __tablename__ = '{{name}}'", &["from sqlalchemy.orm import declarative_base"])),
+                    ("diesel", Self::web_rule("diesel", "Queryable", "#[derive(Queryable, Selectable)]\n#[diesel(table_name = {{name}})]\npub struct {{entity}}", &["use diesel::prelude::*;"])),
+                    ("sea_orm", Self::web_rule("sea_orm", "DeriveEntityModel", "#[derive(Clone, Debug, DeriveEntityModel)]\n#[sea_orm(table_name = \"{{name}}\")]\npub struct Model", &["use sea_orm::entity::prelude::*;"])),
+                ],
+            ),
+            Self::web_pattern(
+                "entity_framework",
+                "csharp",
+                "add_db_context",
+                "services.AddDbContext<AppContext>(options => ...)",
+                Self::database_semantics("database_connection", "Registers a context with the dependency injection container", &["database_connection"]),
+                &[("context", "class")],
+                vec![
+                    ("django", Self::web_rule("django", "DATABASES", "DATABASES = {\"default\": env.db()}", &[])),
+                    ("sqlalchemy", Self::web_rule("sqlalchemy", "create_engine", "engine = create_engine(DATABASE_URL)", &["from sqlalchemy import create_engine"])),
+                    ("diesel", Self::web_rule("diesel", "Pool", "let pool = Pool::builder().build(ConnectionManager::<PgConnection>::new(database_url))?;", &["use diesel::r2d2::{ConnectionManager, Pool};"])),
+                    ("sea_orm", Self::web_rule("sea_orm", "Database::connect", "let db = Database::connect(database_url).await?;", &["use sea_orm::Database;"])),
+                ],
+            ),
+            Self::web_pattern(
+                "entity_framework",
+                "csharp",
+                "query",
+                "context.Entities.Where(e => condition).ToList()",
+                Self::database_semantics("database_query", "Queries the rows of a table that match a condition", &["database_read"]),
+                &[("model", "identifier"), ("condition", "expression")],
+                vec![
+                    ("django", Self::web_rule("django", "QuerySet.filter", "{{model}}.objects.filter({{condition}})", &[])),
+                    ("sqlalchemy", Self::web_rule("sqlalchemy", "select", "session.scalars(select({{model}}).where({{condition}})).all()", &["from sqlalchemy import select"])),
+                    ("diesel", Self::web_rule("diesel", "filter", "{{model}}::table.filter({{condition}}).load(conn)?", &["use diesel::prelude::*;"])),
+                    ("sea_orm", Self::web_rule("sea_orm", "find", "{{model}}::Entity::find().filter({{condition}}).all(db).await?", &["use sea_orm::entity::prelude::*;"])),
+                ],
+            ),
+            Self::web_pattern(
+                "entity_framework",
+                "csharp",
+                "save_changes",
+                "context.SaveChanges()",
+                Self::database_semantics("database_commit", "Writes the tracked changes to the database", &["database_write"]),
+                &[],
+                vec![
+                    ("django", Self::web_rule("django", "Model.save", "instance.save()", &[])),
+                    ("sqlalchemy", Self::web_rule("sqlalchemy", "Session.commit", "session.commit()", &[])),
+                    ("diesel", Self::web_rule("diesel", "Connection::transaction", "conn.transaction(|conn| { /* writes */ Ok(()) })?;", &["use diesel::prelude::*;"])),
+                    ("sea_orm", Self::web_rule("sea_orm", "TransactionTrait::commit", "txn.commit().await?;", &["use sea_orm::TransactionTrait;"])),
+                ],
+            ),
+        ]
+    }
+    
+    /// Route registration; routes carry their path with `{name}` placeholders (`:name` for Gin)
+    /// and their upper-case HTTP methods
+    fn web_route_rules() -> Vec<(&'static str, TransformRule)> {
+        vec![
+            ("axum", Self::web_rule("axum", "Router::route", &format!(".route(\"{{{{path}}}}\", on({}, {{{{handler}}}}))", AXUM_METHOD_FILTER), &["use axum::routing::{on, MethodFilter};"])),
+            ("gin", Self::web_rule("gin", "Engine.Match", &format!("router.Match([]string{{ {} }}, \"{{{{colon_path}}}}\", {{{{handler}}}})", QUOTED_METHODS), &["github.com/gin-gonic/gin"])),
+            ("aspnet", Self::web_rule("aspnet", "MapMethods", &format!("app.MapMethods(\"{{{{path}}}}\", new[] {{ {} }}, {{{{handler}}}});", QUOTED_METHODS), &[])),
+        ]
+    }
+    
+    /// Requests carry their URL as written and an upper-case HTTP method, a body only when one
+    /// was sent, and `awaited` when the source awaits them. Each rule's code is the awaited
+    /// response, except Go's, which assigns it to the usage's target
+    fn http_request_rules() -> Vec<(&'static str, TransformRule)> {
+        let with_caveat = |rule: TransformRule, caveat: &str| TransformRule { caveats: vec![caveat.to_string()], ..rule };
+        vec![
+            ("axios", Self::web_rule("axios", "axios", "await axios({ method: \"{{method}}\", url: {{url}}{{#if body}}, data: {{body}}{{/if}} })", &["import axios from 'axios';"])),
+            ("fetch", Self::web_rule("fetch", "fetch", "await fetch({{url}}, { method: \"{{method}}\"{{#if body}}, body: JSON.stringify({{body}}){{/if}} })", &[])),
+            ("requests", with_caveat(
+                Self::web_rule("requests", "request", "requests.request(\"{{method}}\", {{url}}{{#if body}}, json={{body}}{{/if}})", &["import requests"]),
+                "requests blocks: an awaited request holds up the event loop until it completes",
+            )),
+            ("httpx", with_caveat(
+                Self::web_rule("httpx", "request", "{{#if awaited}}await httpx.AsyncClient().request({{else}}httpx.request({{/if}}\"{{method}}\", {{url}}{{#if body}}, json={{body}}{{/if}})", &["import httpx"]),
+                "An awaited request opens a client of its own; share one `httpx.AsyncClient` across requests",
+            )),
+            ("net_http", Self::web_rule("net_http", "NewRequest", "{{#if target}}var {{target}} *http.Response\n{{/if}}req, err := http.NewRequest(\"{{method}}\", {{url}}, bytes.NewReader({{#if body}}{{body}}{{else}}nil{{/if}}))\nif err == nil {\n\t{{#if target}}{{target}}{{else}}_{{/if}}, err = http.DefaultClient.Do(req)\n}", &["net/http", "bytes"])),
+            ("reqwest", Self::web_rule("reqwest", "Client::request", "reqwest::Client::new().request(reqwest::Method::{{method}}, {{url}}){{#if body}}.json(&{{body}}){{/if}}.send().await?", &[])),
+        ]
+    }
+    
+    /// Log statements carry their level as `warn`, `Warn` and `warning`; loggers other than the
+    /// built-in ones are set up once per module
+    fn logging_rules() -> Vec<(&'static str, TransformRule)> {
+        let with_setup = |rule: TransformRule, setup: &str| TransformRule { setup_code: Some(setup.to_string()), ..rule };
+        vec![
+            ("console", Self::web_rule("console", "console", "console.{{level}}({{message}});", &[])),
+            ("winston", with_setup(
+                Self::web_rule("winston", "Logger", "logger.{{level}}({{message}});", &["import winston from 'winston';"]),
+                "const logger = winston.createLogger({ transports: [new winston.transports.Console()] });",
+            )),
+            ("log4j", with_setup(
+                Self::web_rule("log4j", "Logger", "logger.{{level}}({{string message}});", &["import org.apache.logging.log4j.LogManager;", "import org.apache.logging.log4j.Logger;"]),
+                "private static final Logger logger = LogManager.getLogger();",
+            )),
+            ("log4net", with_setup(
+                Self::web_rule("log4net", "ILog", "log.{{level_title}}({{string message}});", &["using log4net;"]),
+                "private static readonly ILog log = LogManager.GetLogger(System.Reflection.MethodBase.GetCurrentMethod().DeclaringType);",
+            )),
+            ("logging", with_setup(
+                Self::web_rule("logging", "Logger", "logger.{{level_long}}({{message}})", &["import logging"]),
+                "logger = logging.getLogger(__name__)",
+            )),
+            ("log", Self::web_rule("log", "Printf", "log.Println(\"{{level}}:\", {{string message}})", &["log"])),
+            ("zap", with_setup(
+                Self::web_rule("zap", "Logger", "logger.{{level_title}}({{string message}})", &["go.uber.org/zap"]),
+                "logger, _ := zap.NewProduction()",
+            )),
+            ("tracing", Self::web_rule("tracing", "event", "tracing::{{level}}!({{string message}});", &[])),
+        ]
+    }
+    
+    /// Tests carry their name as words, `snake_name` and `pascal_name`; assertions carry their
+    /// operands as written. Rules give a test's declaration, not its body
+    fn test_framework_rules(kind: &str) -> Vec<(&'static str, TransformRule)> {
+        // Templates for jest, xUnit, pytest, Go's testing and Rust, in that order
+        let templates: [&str; 5] = match kind {
+            "test" => [
+                "test(\"{{name}}\", () => {\n});",
+                "[Fact]\npublic void {{pascal_name}}()\n{\n}",
+                "def test_{{snake_name}}():\n    ...",
+                "func Test{{pascal_name}}(t *testing.T) {\n}",
+                "#[test]\nfn {{snake_name}}() {\n}",
+            ],
+            "suite" => [
+                "describe(\"{{name}}\", () => {\n});",
+                "public class {{pascal_name}}Tests\n{\n}",
+                "class Test{{pascal_name}}:\n    ...",
+                "t.Run(\"{{name}}\", func(t *testing.T) {\n})",
+                "#[cfg(test)]\nmod {{snake_name}} {\n    use super::*;\n}",
+            ],
+            "assert_equal" => [
+                "expect({{actual}}).toEqual({{expected}});",
+                "Assert.Equal({{expected}}, {{actual}});",
+                "assert {{actual}} == {{expected}}",
+                "if got := {{actual}}; !reflect.DeepEqual(got, {{expected}}) {\n\tt.Errorf(\"got %v, want %v\", got, {{expected}})\n}",
+                "assert_eq!({{actual}}, {{expected}});",
+            ],
+            "assert_not_equal" => [
+                "expect({{actual}}).not.toEqual({{expected}});",
+                "Assert.NotEqual({{expected}}, {{actual}});",
+                "assert {{actual}} != {{expected}}",
+                "if got := {{actual}}; reflect.DeepEqual(got, {{expected}}) {\n\tt.Errorf(\"got %v, want anything else\", got)\n}",
+                "assert_ne!({{actual}}, {{expected}});",
+            ],
+            "assert_true" => [
+                "expect({{value}}).toBeTruthy();",
+                "Assert.True({{value}});",
+                "assert {{value}}",
+                "if !({{value}}) {\n\tt.Error(\"expected true\")\n}",
+                "assert!({{value}});",
+            ],
+            "assert_false" => [
+                "expect({{value}}).toBeFalsy();",
+                "Assert.False({{value}});",
+                "assert not {{value}}",
+                "if {{value}} {\n\tt.Error(\"expected false\")\n}",
+                "assert!(!({{value}}));",
+            ],
+            "assert_throws" => [
+                "expect({{action}}).toThrow({{error}});",
+                "Assert.Throws<{{error}}>({{action}});",
+                "pytest.raises({{error}}, {{action}})",
+                "defer func() {\n\tif recover() == nil {\n\t\tt.Error(\"expected a panic\")\n\t}\n}()\n{{action}}()",
+                "assert!(std::panic::catch_unwind({{action}}).is_err());",
+            ],
+            "fail" => [
+                "throw new Error({{message}});",
+                "Assert.Fail({{message}});",
+                "pytest.fail({{message}})",
+                "t.Errorf({{message}})",
+                "panic!({{message}});",
+            ],
+            _ => return Vec::new(),
+        };
+        let pytest_imports: &[&str] = if matches!(kind, "assert_throws" | "fail") { &["import pytest"] } else { &[] };
+        let go_imports: &[&str] = if matches!(kind, "assert_equal" | "assert_not_equal") { &["testing", "reflect"] } else { &["testing"] };
+        vec![
+            ("jest", Self::web_rule("jest", kind, templates[0], &[])),
+            ("xunit", Self::web_rule("xunit", kind, templates[1], &["using Xunit;"])),
+            ("pytest", Self::web_rule("pytest", kind, templates[2], pytest_imports)),
+            ("testing", Self::web_rule("testing", kind, templates[3], go_imports)),
+            ("cargo_test", Self::web_rule("cargo_test", kind, templates[4], &[])),
+        ]
+    }
+    
+    /// Dates carry their format in each library's style, as `format_moment`, `format_python`,
+    /// `format_chrono`, `format_java` and `format_dotnet`, and units of time as plurals
+    fn datetime_rules(kind: &str) -> Vec<(&'static str, TransformRule)> {
+        match kind {
+            "now" => vec![
+                ("moment", Self::web_rule("moment", "moment", "moment()", &["import moment from 'moment';"])),
+                ("dayjs", Self::web_rule("dayjs", "dayjs", "dayjs()", &["import dayjs from 'dayjs';"])),
+                ("datetime", Self::web_rule("datetime", "datetime.now", "datetime.now()", &["from datetime import datetime"])),
+                ("java_time", Self::web_rule("java_time", "LocalDateTime.now", "LocalDateTime.now()", &["import java.time.LocalDateTime;"])),
+                ("system_datetime", Self::web_rule("system_datetime", "DateTime.Now", "DateTime.Now", &["using System;"])),
+                ("chrono", Self::web_rule("chrono", "Local::now", "Local::now()", &["use chrono::Local;"])),
+            ],
+            "parse" => vec![
+                ("moment", Self::web_rule("moment", "moment", "moment({{value}}, \"{{format_moment}}\")", &["import moment from 'moment';"])),
+                ("dayjs", TransformRule {
+                    setup_code: Some("dayjs.extend(customParseFormat);".to_string()),
+                    ..Self::web_rule("dayjs", "dayjs", "dayjs({{value}}, \"{{format_moment}}\")", &["import dayjs from 'dayjs';", "import customParseFormat from 'dayjs/plugin/customParseFormat';"])
+                }),
+                ("datetime", Self::web_rule("datetime", "datetime.strptime", "datetime.strptime({{value}}, \"{{format_python}}\")", &["from datetime import datetime"])),
+                ("java_time", Self::web_rule("java_time", "LocalDateTime.parse", "LocalDateTime.parse({{value}}, DateTimeFormatter.ofPattern(\"{{format_java}}\"))", &["import java.time.LocalDateTime;", "import java.time.format.DateTimeFormatter;"])),
+                ("system_datetime", Self::web_rule("system_datetime", "DateTime.ParseExact", "DateTime.ParseExact({{value}}, \"{{format_dotnet}}\", CultureInfo.InvariantCulture)", &["using System;", "using System.Globalization;"])),
+                ("chrono", Self::web_rule("chrono", "NaiveDateTime::parse_from_str", "NaiveDateTime::parse_from_str({{value}}, \"{{format_chrono}}\")?", &["use chrono::NaiveDateTime;"])),
+            ],
+            "format" => vec![
+                // Formatting takes a date, which moment and dayjs wrap first
+                ("moment", Self::web_rule("moment", "format", "moment({{value}}).format(\"{{format_moment}}\")", &["import moment from 'moment';"])),
+                ("dayjs", Self::web_rule("dayjs", "format", "dayjs({{value}}).format(\"{{format_moment}}\")", &["import dayjs from 'dayjs';"])),
+                ("datetime", Self::web_rule("datetime", "strftime", "{{value}}.strftime(\"{{format_python}}\")", &[])),
+                ("java_time", Self::web_rule("java_time", "format", "{{value}}.format(DateTimeFormatter.ofPattern(\"{{format_java}}\"))", &["import java.time.format.DateTimeFormatter;"])),
+                ("system_datetime", Self::web_rule("system_datetime", "ToString", "{{value}}.ToString(\"{{format_dotnet}}\")", &[])),
+                ("chrono", Self::web_rule("chrono", "format", "{{value}}.format(\"{{format_chrono}}\").to_string()", &[])),
+            ],
+            // The date formatted is made where it's formatted, as moment's `moment().format(...)`
+            "format_now" => vec![
+                ("moment", Self::web_rule("moment", "format", "moment().format(\"{{format_moment}}\")", &["import moment from 'moment';"])),
+                ("dayjs", Self::web_rule("dayjs", "format", "dayjs().format(\"{{format_moment}}\")", &["import dayjs from 'dayjs';"])),
+                ("datetime", Self::web_rule("datetime", "strftime", "datetime.now().strftime(\"{{format_python}}\")", &["from datetime import datetime"])),
+                ("java_time", Self::web_rule("java_time", "format", "LocalDateTime.now().format(DateTimeFormatter.ofPattern(\"{{format_java}}\"))", &["import java.time.LocalDateTime;", "import java.time.format.DateTimeFormatter;"])),
+                ("system_datetime", Self::web_rule("system_datetime", "ToString", "DateTime.Now.ToString(\"{{format_dotnet}}\")", &["using System;"])),
+                ("chrono", Self::web_rule("chrono", "format", "Local::now().format(\"{{format_chrono}}\").to_string()", &["use chrono::Local;"])),
+            ],
+            // moment's dates are mutable, so adding to a copy keeps the original
+            "add" => vec![
+                ("moment", Self::web_rule("moment", "add", "{{value}}.clone().add({{amount}}, \"{{unit}}\")", &[])),
+                ("dayjs", Self::web_rule("dayjs", "add", "{{value}}.add({{amount}}, \"{{unit}}\")", &[])),
+                ("datetime", Self::web_rule("datetime", "timedelta", "{{value}} + timedelta({{unit}}={{amount}})", &["from datetime import timedelta"])),
+                ("java_time", Self::web_rule("java_time", "plus", "{{value}}.plus{{unit_title}}({{amount}})", &[])),
+                ("system_datetime", Self::web_rule("system_datetime", "Add", "{{value}}.Add{{unit_title}}({{amount}})", &[])),
+                ("chrono", Self::web_rule("chrono", "Duration", "{{value}} + Duration::{{unit}}({{amount}})", &["use chrono::Duration;"])),
+            ],
+            "duration" => vec![
+                ("moment", Self::web_rule("moment", "duration", "moment.duration({{amount}}, \"{{unit}}\")", &["import moment from 'moment';"])),
+                ("dayjs", TransformRule {
+                    setup_code: Some("dayjs.extend(duration);".to_string()),
+                    ..Self::web_rule("dayjs", "duration", "dayjs.duration({{amount}}, \"{{unit}}\")", &["import dayjs from 'dayjs';", "import duration from 'dayjs/plugin/duration';"])
+                }),
+                ("datetime", Self::web_rule("datetime", "timedelta", "timedelta({{unit}}={{amount}})", &["from datetime import timedelta"])),
+                ("java_time", Self::web_rule("java_time", "Duration", "Duration.of{{unit_title}}({{amount}})", &["import java.time.Duration;"])),
+                ("system_datetime", Self::web_rule("system_datetime", "TimeSpan", "TimeSpan.From{{unit_title}}({{amount}})", &["using System;"])),
+                ("chrono", Self::web_rule("chrono", "Duration", "Duration::{{unit}}({{amount}})", &["use chrono::Duration;"])),
+            ],
+            _ => Vec::new(),
+        }
+    }
+    
+    fn file_io_rules(kind: &str) -> Vec<(&'static str, TransformRule)> {
+        match kind {
+            // Go reads bytes and reports failure alongside them
+            "read" => vec![
+                ("fs", Self::web_rule("fs", "readFileSync", "fs.readFileSync({{path}}, \"utf8\")", &["import fs from 'fs';"])),
+                ("io", Self::web_rule("io", "read_text", "Path({{path}}).read_text()", &["from pathlib import Path"])),
+                ("os", Self::web_rule("os", "ReadFile", "{{target}}, err := os.ReadFile({{path}})", &["os"])),
+                ("system_io", Self::web_rule("system_io", "File.ReadAllText", "File.ReadAllText({{path}})", &["using System.IO;"])),
+                ("std_fs", Self::web_rule("std_fs", "read_to_string", "fs::read_to_string({{path}})?", &["use std::fs;"])),
+            ],
+            "write" => vec![
+                ("fs", Self::web_rule("fs", "writeFileSync", "fs.writeFileSync({{path}}, {{data}});", &["import fs from 'fs';"])),
+                ("io", Self::web_rule("io", "write_text", "Path({{path}}).write_text({{data}})", &["from pathlib import Path"])),
+                ("os", Self::web_rule("os", "WriteFile", "err := os.WriteFile({{path}}, []byte({{data}}), 0644)", &["os"])),
+                ("system_io", Self::web_rule("system_io", "File.WriteAllText", "File.WriteAllText({{path}}, {{data}});", &["using System.IO;"])),
+                ("std_fs", Self::web_rule("std_fs", "write", "fs::write({{path}}, {{data}})?;", &["use std::fs;"])),
+            ],
+            // Python, Go and Rust append through a file opened for it
+            "append" => vec![
+                ("fs", Self::web_rule("fs", "appendFileSync", "fs.appendFileSync({{path}}, {{data}});", &["import fs from 'fs';"])),
+                ("io", Self::web_rule("io", "open", "with open({{path}}, \"a\") as file:\n    file.write({{data}})", &[])),
+                (
+                    "os",
+                    Self::web_rule(
+                        "os",
+                        "OpenFile",
+                        "file, err := os.OpenFile({{path}}, os.O_APPEND|os.O_CREATE|os.O_WRONLY, 0644)\nif err == nil {\n\tdefer file.Close()\n\t_, err = file.WriteString({{data}})\n}",
+                        &["os"],
+                    ),
+                ),
+                ("system_io", Self::web_rule("system_io", "File.AppendAllText", "File.AppendAllText({{path}}, {{data}});", &["using System.IO;"])),
+                (
+                    "std_fs",
+                    Self::web_rule(
+                        "std_fs",
+                        "OpenOptions",
+                        "OpenOptions::new().append(true).create(true).open({{path}})?.write_all({{data}}.as_bytes())?;",
+                        &["use std::fs::OpenOptions;", "use std::io::Write;"],
+                    ),
+                ),
+            ],
+            "path_join" => vec![
+                ("fs", Self::web_rule("fs", "path.join", "path.join({{base}}, {{rest}})", &["import path from 'path';"])),
+                ("io", Self::web_rule("io", "os.path.join", "os.path.join({{base}}, {{rest}})", &["import os"])),
+                ("os", Self::web_rule("os", "filepath.Join", "filepath.Join({{base}}, {{rest}})", &["path/filepath"])),
+                ("system_io", Self::web_rule("system_io", "Path.Combine", "Path.Combine({{base}}, {{rest}})", &["using System.IO;"])),
+                ("std_fs", Self::web_rule("std_fs", "PathBuf", "[{{base}}, {{rest}}].iter().collect::<PathBuf>()", &["use std::path::PathBuf;"])),
+            ],
+            _ => Vec::new(),
+        }
+    }
+    
+    fn concurrency_rules(kind: &str) -> Vec<(&'static str, TransformRule)> {
+        let rule = Self::caveated_rule;
+        match kind {
+            // What each API takes differs: a callable for threads, a coroutine or future for
+            // async runtimes, a call for `go`
+            "spawn" => vec![
+                ("java_concurrent", rule("java_concurrent", "CompletableFuture.runAsync", "CompletableFuture<Void> {{target}} = CompletableFuture.runAsync({{task}});", &["import java.util.concurrent.CompletableFuture;"], &[
+                    "runAsync takes a Runnable: pass a lambda or method reference",
+                    "Runs on the common ForkJoinPool unless given an executor",
+                ])),
+                ("dotnet_tasks", rule("dotnet_tasks", "Task.Run", "var {{target}} = Task.Run({{task}});", &["using System.Threading.Tasks;"], &[
+                    "Task.Run takes a delegate: pass a lambda or method group",
+                    "Exceptions surface only where the task is awaited",
+                ])),
+                ("threading", rule("threading", "Thread", "{{target}} = threading.Thread(target={{task}})\n{{target}}.start()", &["import threading"], &[
+                    "Thread takes a callable, not a call: pass arguments through `args`",
+                    "Threads share the GIL: CPU-bound work doesn't run in parallel",
+                ])),
+                ("asyncio", rule("asyncio", "create_task", "{{target}} = asyncio.create_task({{task}})", &["import asyncio"], &[
+                    "create_task takes a coroutine, such as `work()`, and needs a running event loop",
+                    "Blocking calls in the task stall every other task on the loop",
+                ])),
+                ("goroutines", rule("goroutines", "go", "go {{task}}", &[], &[
+                    "`go` takes a call, such as `work()` or `func() { ... }()`",
+                    "A goroutine has no handle: wait on a sync.WaitGroup or a channel to join it",
+                ])),
+                ("std_thread", rule("std_thread", "thread::spawn", "let {{target}} = thread::spawn({{task}});", &["use std::thread;"], &[
+                    "spawn takes a `Send + 'static` closure: move what it captures into it",
+                    "A panic in the thread surfaces as an `Err` from `join`",
+                ])),
+                ("tokio", rule("tokio", "tokio::spawn", "let {{target}} = tokio::spawn({{task}});", &[], &[
+                    "tokio::spawn takes a `Send + 'static` future, such as `work()` of an async fn",
+                    "Needs a running Tokio runtime",
+                ])),
+            ],
+            "join" => vec![
+                ("java_concurrent", rule("java_concurrent", "CompletableFuture.allOf", "CompletableFuture.allOf({{tasks}}).join();", &["import java.util.concurrent.CompletableFuture;"], &[
+                    "allOf takes CompletableFutures",
+                ])),
+                ("dotnet_tasks", rule("dotnet_tasks", "Task.WhenAll", "await Task.WhenAll({{tasks}});", &["using System.Threading.Tasks;"], &[
+                    "The enclosing method must be async",
+                ])),
+                ("threading", rule("threading", "join", "for thread in [{{tasks}}]:\n    thread.join()", &[], &[])),
+                ("asyncio", rule("asyncio", "gather", "await asyncio.gather({{tasks}})", &["import asyncio"], &[
+                    "The enclosing function must be async",
+                ])),
+                ("goroutines", rule("goroutines", "WaitGroup.Wait", "wg.Wait()", &["sync"], &[
+                    "Declare `var wg sync.WaitGroup`; each goroutine needs `wg.Add(1)` before it starts and `wg.Done()` when it finishes",
+                ])),
+                ("std_thread", rule("std_thread", "JoinHandle::join", "for handle in [{{tasks}}] {\n    handle.join().unwrap();\n}", &[], &[])),
+                ("tokio", rule("tokio", "tokio::join", "tokio::join!({{tasks}});", &[], &[
+                    "join! takes futures and must be used inside an async function",
+                ])),
+            ],
+            // Rust's locks own the data they guard, which other languages leave beside them
+            "mutex" => vec![
+                ("java_concurrent", rule("java_concurrent", "ReentrantLock", "ReentrantLock {{target}} = new ReentrantLock();", &["import java.util.concurrent.locks.ReentrantLock;"], &[
+                    "Release it in a finally block: an exception doesn't unlock it",
+                ])),
+                ("dotnet_tasks", rule("dotnet_tasks", "lock", "private readonly object {{target}} = new();", &[], &[
+                    "Guard sections with a `lock` statement on it; it's reentrant on the same thread",
+                ])),
+                ("threading", rule("threading", "Lock", "{{target}} = threading.Lock()", &["import threading"], &[
+                    "Hold it in a `with` block so exceptions release it; Lock isn't reentrant, RLock is",
+                ])),
+                ("asyncio", rule("asyncio", "Lock", "{{target}} = asyncio.Lock()", &["import asyncio"], &[
+                    "Guards tasks on one event loop, not threads; hold it with `async with`",
+                ])),
+                ("goroutines", rule("goroutines", "sync.Mutex", "var {{target}} sync.Mutex", &["sync"], &[
+                    "Share it by pointer: copying a Mutex copies its state",
+                    "Unlock explicitly, usually with `defer`; it isn't reentrant",
+                ])),
+                ("std_thread", rule("std_thread", "Mutex", "let {{target}} = Arc::new(Mutex::new(()));", &["use std::sync::{Arc, Mutex};"], &[
+                    "A Mutex owns the data it guards: move the guarded state into it in place of `()`",
+                    "It isn't reentrant, and a panic while it's held poisons it",
+                ])),
+                ("tokio", rule("tokio", "Mutex", "let {{target}} = Arc::new(tokio::sync::Mutex::new(()));", &["use std::sync::Arc;"], &[
+                    "A Mutex owns the data it guards: move the guarded state into it in place of `()`",
+                    "Lock with `.lock().await`; std's Mutex is cheaper where the guard isn't held across an await",
+                ])),
+            ],
+            // Go and the async runtimes schedule work on pools of their own, so a semaphore
+            // bounds it; std has no pool at all
+            "thread_pool" => vec![
+                ("java_concurrent", rule("java_concurrent", "Executors.newFixedThreadPool", "ExecutorService {{target}} = Executors.newFixedThreadPool({{workers}});", &["import java.util.concurrent.ExecutorService;", "import java.util.concurrent.Executors;"], &[
+                    "Call shutdown() when done, or its threads keep the JVM alive",
+                ])),
+                ("dotnet_tasks", rule("dotnet_tasks", "SemaphoreSlim", "var {{target}} = new SemaphoreSlim({{workers}});", &["using System.Threading;"], &[
+                    "Tasks share the runtime's pool; the semaphore bounds how many run at once: WaitAsync before each and Release after",
+                ])),
+                ("threading", rule("threading", "ThreadPoolExecutor", "{{target}} = ThreadPoolExecutor(max_workers={{workers}})", &["from concurrent.futures import ThreadPoolExecutor"], &[
+                    "Call shutdown(), or use it in a `with` block, to wait for submitted work",
+                ])),
+                ("asyncio", rule("asyncio", "Semaphore", "{{target}} = asyncio.Semaphore({{workers}})", &["import asyncio"], &[
+                    "Tasks share one thread; the semaphore bounds how many run at once: hold it with `async with`",
+                ])),
+                ("goroutines", rule("goroutines", "chan", "{{target}} := make(chan struct{}, {{workers}})", &[], &[
+                    "Goroutines need no pool; the buffered channel bounds how many run at once: send before each and receive after",
+                ])),
+                ("tokio", rule("tokio", "Semaphore", "let {{target}} = Arc::new(Semaphore::new({{workers}}));", &["use std::sync::Arc;", "use tokio::sync::Semaphore;"], &[
+                    "Tasks share the runtime's pool; the semaphore bounds how many run at once: acquire a permit in each",
+                ])),
+            ],
+            _ => Vec::new(),
+        }
+    }
+    
+    /// Hash algorithms with a pattern each, as Node and Python name them
+    const HASH_ALGORITHMS: [&'static str; 4] = ["md5", "sha1", "sha256", "sha512"];
+    
+    fn crypto_rules(kind: &str) -> Vec<(&'static str, TransformRule)> {
+        let rule = Self::caveated_rule;
+        let nonce = "Never reuse a nonce under the same key: draw 12 fresh random bytes for each message";
+        let tag = "The 16-byte authentication tag follows the ciphertext, as Go's Seal, Python's AESGCM and aes-gcm lay it out";
+        match kind {
+            "random_bytes" => vec![
+                ("crypto", rule("crypto", "randomBytes", "crypto.randomBytes({{length}})", &["import crypto from 'crypto';"], &[])),
+                ("hashlib", rule("hashlib", "secrets.token_bytes", "secrets.token_bytes({{length}})", &["import secrets"], &[])),
+                ("go_crypto", rule("go_crypto", "rand.Read", "{{target}} := make([]byte, {{length}})\n_, err := rand.Read({{target}})", &["crypto/rand"], &[
+                    "Read from crypto/rand, never math/rand",
+                ])),
+                ("dotnet_crypto", rule("dotnet_crypto", "RandomNumberGenerator.GetBytes", "RandomNumberGenerator.GetBytes({{length}})", &["using System.Security.Cryptography;"], &[])),
+                ("rustcrypto", rule("rustcrypto", "OsRng", "let mut {{target}} = vec![0u8; {{length}}];\nOsRng.fill_bytes(&mut {{target}});", &["use rand::rngs::OsRng;", "use rand::RngCore;"], &[])),
+            ],
+            "encrypt" => vec![
+                (
+                    "crypto",
+                    rule(
+                        "crypto",
+                        "createCipheriv",
+                        "const cipher = crypto.createCipheriv(\"aes-256-gcm\", {{key}}, {{nonce}});\nconst {{target}} = Buffer.concat([cipher.update({{data}}), cipher.final(), cipher.getAuthTag()]);",
+                        &["import crypto from 'crypto';"],
+                        &[nonce, tag],
+                    ),
+                ),
+                ("cryptography", rule("cryptography", "AESGCM", "{{target}} = AESGCM({{key}}).encrypt({{nonce}}, {{data}}, None)", &["from cryptography.hazmat.primitives.ciphers.aead import AESGCM"], &[nonce])),
+                (
+                    "go_crypto",
+                    rule(
+                        "go_crypto",
+                        "cipher.NewGCM",
+                        "block, err := aes.NewCipher({{key}})\ngcm, err := cipher.NewGCM(block)\n{{target}} := gcm.Seal(nil, {{nonce}}, {{data}}, nil)",
+                        &["crypto/aes", "crypto/cipher"],
+                        &[nonce, "Check each err before going on"],
+                    ),
+                ),
+                (
+                    "dotnet_crypto",
+                    rule(
+                        "dotnet_crypto",
+                        "AesGcm",
+                        "using var aes = new AesGcm({{key}}, 16);\nvar {{target}} = new byte[{{data}}.Length + 16];\naes.Encrypt({{nonce}}, {{data}}, {{target}}.AsSpan(0, {{data}}.Length), {{target}}.AsSpan({{data}}.Length));",
+                        &["using System;", "using System.Security.Cryptography;"],
+                        &[nonce, tag],
+                    ),
+                ),
+                (
+                    "rustcrypto",
+                    rule(
+                        "rustcrypto",
+                        "Aes256Gcm",
+                        "let {{target}} = Aes256Gcm::new_from_slice({{key}})?.encrypt(Nonce::from_slice({{nonce}}), {{data}}.as_ref())?;",
+                        &["use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit, Nonce};"],
+                        &[nonce],
+                    ),
+                ),
+            ],
+            _ => {
+                let Some((kind, algorithm)) = kind.split_once('_').filter(|(_, algorithm)| Self::HASH_ALGORITHMS.contains(algorithm)) else {
+                    return Vec::new();
+                };
+                let class = algorithm.to_uppercase();
+                let (sum, krate) = match algorithm {
+                    "sha256" => ("Sum256", "sha2"),
+                    "sha512" => ("Sum512", "sha2"),
+                    other => ("Sum", other),
+                };
+                let hasher = format!("{}{}", algorithm[..1].to_uppercase(), &algorithm[1..]);
+                let rust_import = format!("use {}::{{Digest, {}}};", krate, hasher);
+                let go_import = format!("crypto/{}", algorithm);
+                let broken = format!("{} is broken for signatures and passwords: keep it to checksums and legacy formats", class);
+                let weak: &[&str] = if matches!(algorithm, "md5" | "sha1") { &[&broken] } else { &[] };
+                match kind {
+                    "hash" => vec![
+                        ("crypto", rule("crypto", "createHash", &format!("crypto.createHash(\"{}\").update({{{{data}}}}).digest(\"hex\")", algorithm), &["import crypto from 'crypto';"], weak)),
+                        ("hashlib", rule("hashlib", algorithm, &format!("hashlib.{}({{{{data}}}}).hexdigest()", algorithm), &["import hashlib"], &[&["Takes bytes: encode strings first"][..], weak].concat())),
+                        ("go_crypto", rule("go_crypto", sum, &format!("fmt.Sprintf(\"%x\", {}.{}([]byte({{{{data}}}})))", algorithm, sum), &["fmt", &go_import], weak)),
+                        (
+                            "dotnet_crypto",
+                            rule(
+                                "dotnet_crypto",
+                                &format!("{}.HashData", class),
+                                &format!("Convert.ToHexString({}.HashData(Encoding.UTF8.GetBytes({{{{data}}}}))).ToLowerInvariant()", class),
+                                &["using System;", "using System.Security.Cryptography;", "using System.Text;"],
+                                weak,
+                            ),
+                        ),
+                        ("rustcrypto", rule("rustcrypto", &hasher, &format!("format!(\"{{:x}}\", {}::digest({{{{data}}}}))", hasher), &[&rust_import], weak)),
+                    ],
+                    "hmac" => vec![
+                        (
+                            "crypto",
+                            rule(
+                                "crypto",
+                                "createHmac",
+                                &format!("crypto.createHmac(\"{}\", {{{{key}}}}).update({{{{data}}}}).digest(\"hex\")", algorithm),
+                                &["import crypto from 'crypto';"],
+                                &[&["Compare signatures with crypto.timingSafeEqual"][..], weak].concat(),
+                            ),
+                        ),
+                        (
+                            "hashlib",
+                            rule(
+                                "hashlib",
+                                "hmac.new",
+                                &format!("hmac.new({{{{key}}}}, {{{{data}}}}, hashlib.{}).hexdigest()", algorithm),
+                                &["import hashlib", "import hmac"],
+                                &[&["Compare signatures with hmac.compare_digest"][..], weak].concat(),
+                            ),
+                        ),
+                        (
+                            "go_crypto",
+                            rule(
+                                "go_crypto",
+                                "hmac.New",
+                                &format!("mac := hmac.New({}.New, []byte({{{{key}}}}))\nmac.Write([]byte({{{{data}}}}))\n{{{{target}}}} := hex.EncodeToString(mac.Sum(nil))", algorithm),
+                                &["crypto/hmac", &go_import, "encoding/hex"],
+                                &[&["Compare signatures with hmac.Equal"][..], weak].concat(),
+                            ),
+                        ),
+                        (
+                            "dotnet_crypto",
+                            rule(
+                                "dotnet_crypto",
+                                &format!("HMAC{}.HashData", class),
+                                &format!("Convert.ToHexString(HMAC{}.HashData(Encoding.UTF8.GetBytes({{{{key}}}}), Encoding.UTF8.GetBytes({{{{data}}}}))).ToLowerInvariant()", class),
+                                &["using System;", "using System.Security.Cryptography;", "using System.Text;"],
+                                &[&["Compare signatures with CryptographicOperations.FixedTimeEquals"][..], weak].concat(),
+                            ),
+                        ),
+                        (
+                            "rustcrypto",
+                            rule(
+                                "rustcrypto",
+                                "Hmac",
+                                &format!("let mut mac = Hmac::<{}>::new_from_slice({{{{key}}}}.as_ref())?;\nmac.update({{{{data}}}}.as_ref());\nlet {{{{target}}}} = format!(\"{{:x}}\", mac.finalize().into_bytes());", hasher),
+                                &["use hmac::{Hmac, Mac};", &format!("use {}::{};", krate, hasher)],
+                                &[&["Verify signatures with Mac::verify_slice, which compares in constant time"][..], weak].concat(),
+                            ),
+                        ),
+                    ],
+                    _ => Vec::new(),
+                }
+            }
+        }
+    }
+    
+    fn cli_rules(kind: &str) -> Vec<(&'static str, TransformRule)> {
+        let rule = Self::caveated_rule;
+        let commander = "import { Command } from 'commander';";
+        let yargs = ["import yargs from 'yargs';", "import { hideBin } from 'yargs/helpers';"];
+        let cobra = "github.com/spf13/cobra";
+        let system_commandline = "using System.CommandLine;";
+        match kind {
+            "program" => vec![
+                ("argparse", rule("argparse", "ArgumentParser", "{{parser}} = argparse.ArgumentParser(prog=\"{{name}}\", description=\"{{description}}\")", &["import argparse"], &[])),
+                ("commander", rule("commander", "Command", "const {{parser}} = new Command(\"{{name}}\").description(\"{{description}}\");", &[commander], &[])),
+                ("yargs", rule("yargs", "yargs", "const {{parser}} = yargs(hideBin(process.argv)).scriptName(\"{{name}}\").usage(\"{{description}}\");", &yargs, &[])),
+                ("cobra", rule("cobra", "cobra.Command", "var {{parser}} = &cobra.Command{\n\tUse:   \"{{name}}\",\n\tShort: \"{{description}}\",\n}", &[cobra], &[])),
+                ("clap", rule("clap", "Command", "let {{parser}} = Command::new(\"{{name}}\").about(\"{{description}}\");", &["use clap::Command;"], &[])),
+                ("system_commandline", rule("system_commandline", "RootCommand", "var {{parser}} = new RootCommand(\"{{description}}\");", &[system_commandline], &[
+                    "RootCommand takes its name from the executable",
+                ])),
+            ],
+            "option" => vec![
+                ("argparse", rule("argparse", "add_argument", "{{parser}}.add_argument(\"--{{name}}\", help=\"{{help}}\")", &[], &[])),
+                ("commander", rule("commander", "option", "{{parser}}.option(\"--{{name}} <value>\", \"{{help}}\");", &[], &[])),
+                ("yargs", rule("yargs", "option", "{{parser}}.option(\"{{name}}\", { type: \"string\", describe: \"{{help}}\" });", &[], &[])),
+                ("cobra", rule("cobra", "Flags().String", "{{parser}}.Flags().String(\"{{name}}\", \"\", \"{{help}}\")", &[cobra], &[])),
+                ("clap", rule("clap", "Arg", "let {{parser}} = {{parser}}.arg(Arg::new(\"{{name}}\").long(\"{{name}}\").help(\"{{help}}\"));", &["use clap::Arg;"], &[])),
+                ("system_commandline", rule("system_commandline", "Option", "{{parser}}.AddOption(new Option<string>(\"--{{name}}\", \"{{help}}\"));", &[system_commandline], &[])),
+            ],
+            "flag" => vec![
+                ("argparse", rule("argparse", "add_argument", "{{parser}}.add_argument(\"--{{name}}\", action=\"store_true\", help=\"{{help}}\")", &[], &[])),
+                ("commander", rule("commander", "option", "{{parser}}.option(\"--{{name}}\", \"{{help}}\");", &[], &[])),
+                ("yargs", rule("yargs", "option", "{{parser}}.option(\"{{name}}\", { type: \"boolean\", describe: \"{{help}}\" });", &[], &[])),
+                ("cobra", rule("cobra", "Flags().Bool", "{{parser}}.Flags().Bool(\"{{name}}\", false, \"{{help}}\")", &[cobra], &[])),
+                (
+                    "clap",
+                    rule(
+                        "clap",
+                        "ArgAction::SetTrue",
+                        "let {{parser}} = {{parser}}.arg(Arg::new(\"{{name}}\").long(\"{{name}}\").help(\"{{help}}\").action(ArgAction::SetTrue));",
+                        &["use clap::{Arg, ArgAction};"],
+                        &[],
+                    ),
+                ),
+                ("system_commandline", rule("system_commandline", "Option<bool>", "{{parser}}.AddOption(new Option<bool>(\"--{{name}}\", \"{{help}}\"));", &[system_commandline], &[])),
+            ],
+            "positional" => vec![
+                ("argparse", rule("argparse", "add_argument", "{{parser}}.add_argument(\"{{name}}\", help=\"{{help}}\")", &[], &[])),
+                ("commander", rule("commander", "argument", "{{parser}}.argument(\"<{{name}}>\", \"{{help}}\");", &[], &[])),
+                ("cobra", rule("cobra", "Args", "{{parser}}.Args = cobra.MinimumNArgs(1)", &[cobra], &[
+                    "cobra passes positional arguments to Run unnamed: read each by its position in args",
+                ])),
+                ("clap", rule("clap", "Arg", "let {{parser}} = {{parser}}.arg(Arg::new(\"{{name}}\").help(\"{{help}}\").required(true));", &["use clap::Arg;"], &[])),
+                ("system_commandline", rule("system_commandline", "Argument", "{{parser}}.AddArgument(new Argument<string>(\"{{name}}\", \"{{help}}\"));", &[system_commandline], &[])),
+            ],
+            "parse" => vec![
+                ("argparse", rule("argparse", "parse_args", "{{target}} = {{parser}}.parse_args()", &[], &[
+                    "argparse turns dashes in names into underscores: `--dry-run` reads as `dry_run`",
+                ])),
+                ("commander", rule("commander", "opts", "{{parser}}.parse(process.argv);\nconst {{target}} = {{parser}}.opts();", &[], &[
+                    "opts() holds only the options, camel-cased: `--dry-run` reads as `dryRun`; positional arguments are in the command's `args`",
+                ])),
+                ("yargs", rule("yargs", "parseSync", "const {{target}} = {{parser}}.parseSync();", &[], &[])),
+                ("cobra", rule("cobra", "Execute", "err := {{parser}}.Execute()", &[cobra], &[
+                    "Execute hands the parsed flags to the command's Run rather than returning them: move the code that reads them into Run",
+                ])),
+                ("clap", rule("clap", "get_matches", "let {{target}} = {{parser}}.get_matches();", &[], &[])),
+                ("system_commandline", rule("system_commandline", "Parse", "var {{target}} = {{parser}}.Parse(args);", &[system_commandline], &[])),
+            ],
+            _ => Vec::new(),
+        }
+    }
+    
+    fn config_rules(kind: &str) -> Vec<(&'static str, TransformRule)> {
+        let rule = Self::caveated_rule;
+        let unset = "Getenv reads an unset variable as \"\": use os.LookupEnv where empty and unset differ";
+        // How each library loads a config file, overridden by the environment
+        let dotnet_load = "var {{config}} = new ConfigurationBuilder()\n    .AddJsonFile(\"{{path}}\")\n    .AddEnvironmentVariables()\n    .Build();";
+        let config_rs_load = "let {{config}} = Config::builder()\n    .add_source(File::with_name(\"{{path}}\"))\n    .add_source(Environment::default())\n    .build()?;";
+        let viper_load = "viper.SetConfigFile(\"{{path}}\")\nviper.AutomaticEnv()\nerr := viper.ReadInConfig()";
+        let dotnet_imports = ["using Microsoft.Extensions.Configuration;"];
+        let config_rs_imports = ["use config::{Config, Environment, File};"];
+        let viper = "github.com/spf13/viper";
+        let viper_global = "viper keeps its settings in a package-level instance: read them with viper.Get* rather than through a variable";
+        match kind {
+            "env_get" => vec![
+                ("process_env", rule("process_env", "process.env", "const {{target}} = process.env.{{name}};", &[], &[])),
+                ("os_environ", rule("os_environ", "os.getenv", "{{target}} = os.getenv(\"{{name}}\")", &["import os"], &[])),
+                ("go_env", rule("go_env", "os.Getenv", "{{target}} := os.Getenv(\"{{name}}\")", &["os"], &[unset])),
+                ("dotnet_config", rule("dotnet_config", "Environment.GetEnvironmentVariable", "var {{target}} = Environment.GetEnvironmentVariable(\"{{name}}\");", &["using System;"], &[])),
+                ("std_env", rule("std_env", "env::var", "let {{target}} = env::var(\"{{name}}\").ok();", &["use std::env;"], &[])),
+            ],
+            "env_default" => vec![
+                ("process_env", rule("process_env", "process.env", "const {{target}} = process.env.{{name}} ?? \"{{default}}\";", &[], &[])),
+                ("os_environ", rule("os_environ", "os.getenv", "{{target}} = os.getenv(\"{{name}}\", \"{{default}}\")", &["import os"], &[])),
+                ("go_env", rule("go_env", "os.Getenv", "{{target}} := os.Getenv(\"{{name}}\")\nif {{target}} == \"\" {\n\t{{target}} = \"{{default}}\"\n}", &["os"], &[unset])),
+                (
+                    "dotnet_config",
+                    rule("dotnet_config", "Environment.GetEnvironmentVariable", "var {{target}} = Environment.GetEnvironmentVariable(\"{{name}}\") ?? \"{{default}}\";", &["using System;"], &[]),
+                ),
+                ("std_env", rule("std_env", "env::var", "let {{target}} = env::var(\"{{name}}\").unwrap_or_else(|_| \"{{default}}\".to_string());", &["use std::env;"], &[])),
+            ],
+            "load_dotenv" => vec![
+                ("dotenv", rule("dotenv", "config", "dotenv.config({ path: \"{{path}}\" });", &["import dotenv from 'dotenv';"], &[])),
+                ("python_dotenv", rule("python_dotenv", "load_dotenv", "load_dotenv(\"{{path}}\")", &["from dotenv import load_dotenv"], &[])),
+                ("godotenv", rule("godotenv", "Load", "err := godotenv.Load(\"{{path}}\")", &["github.com/joho/godotenv"], &[])),
+                ("dotnetenv", rule("dotnetenv", "Env.Load", "Env.Load(\"{{path}}\");", &["using DotNetEnv;"], &[])),
+                ("dotenvy", rule("dotenvy", "from_filename", "dotenvy::from_filename(\"{{path}}\").ok();", &[], &[])),
+            ],
+            "config_load" => vec![
+                ("node_config", rule("node_config", "config", "import {{target}} from 'config';", &[], &[
+                    "node-config reads config/default.json and config/<NODE_ENV>.json rather than a file it's given",
+                ])),
+                ("viper", rule("viper", "ReadInConfig", viper_load, &[viper], &[viper_global])),
+                ("dotnet_config", rule("dotnet_config", "ConfigurationBuilder", &dotnet_load.replace("{{config}}", "{{target}}"), &dotnet_imports, &[])),
+                ("config_rs", rule("config_rs", "Config::builder", &config_rs_load.replace("{{config}}", "{{target}}"), &config_rs_imports, &[])),
+            ],
+            // Reading a setting brings the config it's read from, loaded from the target's usual file
+            "config_get" => vec![
+                ("node_config", rule("node_config", "config.get", "const {{target}} = config.get(\"{{key}}\");", &["import config from 'config';"], &[])),
+                (
+                    "viper",
+                    TransformRule {
+                        setup_code: Some("viper.SetConfigName(\"config\")\nviper.AddConfigPath(\".\")\nviper.AutomaticEnv()\nerr := viper.ReadInConfig()".to_string()),
+                        ..rule("viper", "GetString", "{{target}} := viper.GetString(\"{{key}}\")", &[viper], &[viper_global])
+                    },
+                ),
+                (
+                    "dotnet_config",
+                    TransformRule {
+                        setup_code: Some(dotnet_load.replace("{{path}}", "appsettings.json")),
+                        ..rule("dotnet_config", "IConfiguration", "var {{target}} = {{config}}[\"{{section_key}}\"];", &dotnet_imports, &[])
+                    },
+                ),
+                (
+                    "config_rs",
+                    TransformRule {
+                        setup_code: Some(config_rs_load.replace("{{path}}", "config")),
+                        ..rule("config_rs", "Config::get", "let {{target}}: String = {{config}}.get(\"{{key}}\")?;", &config_rs_imports, &[])
+                    },
+                ),
+            ],
+            _ => Vec::new(),
+        }
+    }
+    
+    fn web_application_rules() -> Vec<(&'static str, TransformRule)> {
+        vec![
+            ("axum", Self::web_rule("axum", "Router", "let {{var}} = Router::new();", &["use axum::Router;"])),
+            ("gin", Self::web_rule("gin", "Default", "{{var}} := gin.Default()", &["github.com/gin-gonic/gin"])),
+            ("aspnet", Self::web_rule("aspnet", "WebApplication", "var {{var}} = WebApplication.CreateBuilder(args).Build();", &[])),
+        ]
+    }
+    
+    fn web_group_rules() -> Vec<(&'static str, TransformRule)> {
+        vec![
+            ("axum", Self::web_rule("axum", "Router", "let {{var}} = Router::new();", &["use axum::Router;"])),
+            ("gin", Self::web_rule("gin", "RouterGroup", "{{var}} := router.Group(\"{{prefix}}\")", &["github.com/gin-gonic/gin"])),
+            ("aspnet", Self::web_rule("aspnet", "MapGroup", "var {{var}} = app.MapGroup(\"{{prefix}}\");", &[])),
+        ]
+    }
+    
+    fn web_query_rules() -> Vec<(&'static str, TransformRule)> {
+        vec![
+            ("axum", Self::web_rule("axum", "Query", "let {{var}} = params.get(\"{{name}}\");", &["use axum::extract::Query;"])),
+            ("gin", Self::web_rule("gin", "Query", "{{var}} := c.Query(\"{{name}}\")", &["github.com/gin-gonic/gin"])),
+            ("aspnet", Self::web_rule("aspnet", "Query", "var {{var}} = request.Query[\"{{name}}\"];", &[])),
+        ]
+    }
+    
+    fn web_semantics(intent: &str, behavior: &str, side_effects: &[&str]) -> PatternSemantics {
+        PatternSemantics {
+            intent: intent.to_string(),
+            category: "web".to_string(),
+            behavior: behavior.to_string(),
+            side_effects: side_effects.iter().map(|effect| effect.to_string()).collect(),
+            requirements: vec!["web_application".to_string()],
+            mutability: false,
+            reactivity: false,
+        }
+    }
+    
+    fn array_semantics(intent: &str, behavior: &str) -> PatternSemantics {
+        PatternSemantics {
+            category: "numeric_array".to_string(),
+            requirements: Vec::new(),
+            ..Self::web_semantics(intent, behavior, &[])
+        }
+    }
+    
+    fn data_frame_semantics(intent: &str, behavior: &str) -> PatternSemantics {
+        PatternSemantics {
+            category: "data_frame".to_string(),
+            requirements: vec!["data_frame".to_string()],
+            ..Self::web_semantics(intent, behavior, &[])
+        }
+    }
+    
+    fn collection_semantics(intent: &str, behavior: &str) -> PatternSemantics {
+        PatternSemantics {
+            category: "collection".to_string(),
+            requirements: Vec::new(),
+            ..Self::web_semantics(intent, behavior, &[])
+        }
+    }
+    
+    fn dom_semantics(intent: &str, behavior: &str, side_effects: &[&str]) -> PatternSemantics {
+        PatternSemantics {
+            category: "dom".to_string(),
+            requirements: vec!["browser".to_string()],
+            ..Self::web_semantics(intent, behavior, side_effects)
+        }
+    }
+    
+    fn database_semantics(intent: &str, behavior: &str, side_effects: &[&str]) -> PatternSemantics {
+        PatternSemantics {
+            category: "database".to_string(),
+            requirements: vec!["database_connection".to_string()],
+            mutability: true,
+            ..Self::web_semantics(intent, behavior, side_effects)
+        }
+    }
+    
+    fn web_rule(target_library: &str, target_pattern: &str, template: &str, imports: &[&str]) -> TransformRule {
+        TransformRule {
+            target_library: target_library.to_string(),
+            target_pattern: target_pattern.to_string(),
+            template: template.to_string(),
+            imports: imports.iter().map(|import| import.to_string()).collect(),
+            setup_code: None,
+            cleanup_code: None,
+            parameter_mappings: HashMap::new(),
+            caveats: Vec::new(),
+            bidirectional: false,
+            packages: Vec::new(),
+        }
+    }
+    
+    /// A rule the registry also derives backwards, for a target as expressive as the source
+    fn reversible_rule(target_library: &str, target_pattern: &str, template: &str, imports: &[&str]) -> TransformRule {
+        TransformRule {
+            bidirectional: true,
+            ..Self::web_rule(target_library, target_pattern, template, imports)
+        }
+    }
+    
+    fn caveated_rule(target_library: &str, target_pattern: &str, template: &str, imports: &[&str], caveats: &[&str]) -> TransformRule {
+        TransformRule {
+            caveats: caveats.iter().map(|caveat| caveat.to_string()).collect(),
+            ..Self::web_rule(target_library, target_pattern, template, imports)
+        }
+    }
+    
+    /// Web framework patterns translate between frameworks, so each carries a rule per target
+    fn web_pattern(
+        library: &str,
+        ecosystem: &str,
+        name: &str,
+        signature: &str,
+        semantics: PatternSemantics,
+        parameters: &[(&str, &str)],
+        transformations: Vec<(&str, TransformRule)>,
+    ) -> LibraryPattern {
+        LibraryPattern {
+            name: name.to_string(),
+            library: library.to_string(),
+            ecosystem: ecosystem.to_string(),
+            signature: signature.to_string(),
+            semantics,
+            parameters: parameters.iter().map(|(parameter, param_type)| PatternParameter {
+                name: parameter.to_string(),
+                param_type: param_type.to_string(),
+                required: true,
+                default_value: None,
+                description: format!("{} of the {} {}", parameter, library, name),
+            }).collect(),
+            transformations: transformations.into_iter()
+                .map(|(target, rule)| (target.to_string(), rule))
+                .collect(),
+            priority: 0,
+            detection: None,
+        }
+    }
+    
+    /// Open SQL statements map onto SQLAlchemy session queries
+    fn open_sql_statement(
+        name: &str,
+        signature: &str,
+        intent: &str,
+        behavior: &str,
+        parameters: Vec<(&str, &str)>,
+        sqlalchemy_template: &str,
+        writes: bool,
+    ) -> LibraryPattern {
+        LibraryPattern {
+            name: name.to_string(),
+            library: "open_sql".to_string(),
+            ecosystem: "abap".to_string(),
+            signature: signature.to_string(),
+            semantics: PatternSemantics {
+                intent: intent.to_string(),
+                category: "database".to_string(),
+                behavior: behavior.to_string(),
+                side_effects: if writes { vec!["database_write".to_string()] } else { vec![] },
+                requirements: vec!["database_connection".to_string()],
+                mutability: writes,
+                reactivity: false,
+            },
+            parameters: parameters.into_iter().map(|(name, param_type)| PatternParameter {
+                name: name.to_string(),
+                param_type: param_type.to_string(),
+                required: true,
+                default_value: None,
+                description: format!("{} of the statement", name),
+            }).collect(),
+            transformations: HashMap::from([
+                ("sqlalchemy".to_string(), TransformRule {
+                    target_library: "sqlalchemy".to_string(),
+                    target_pattern: "Session".to_string(),
+                    template: sqlalchemy_template.to_string(),
+                    imports: vec!["from sqlalchemy import text".to_string()],
+                    setup_code: None,
+                    cleanup_code: if writes { Some("session.commit()".to_string()) } else { None },
+                    parameter_mappings: HashMap::new(),
+                    caveats: Vec::new(),
+                    bidirectional: false,
+                    packages: Vec::new(),
+                }),
+            ]),
+            priority: 0,
+            detection: None,
+        }
+    }
+    
+    /// dplyr verbs chain like pandas methods, so each maps to a method-call suffix
+    fn dplyr_verb(
+        name: &str,
+        signature: &str,
+        intent: &str,
+        behavior: &str,
+        parameters: Vec<(&str, &str)>,
+        pandas_template: &str,
+    ) -> LibraryPattern {
+        LibraryPattern {
+            name: name.to_string(),
+            library: "dplyr".to_string(),
+            ecosystem: "r".to_string(),
+            signature: signature.to_string(),
+            semantics: Self::data_frame_semantics(intent, behavior),
+            parameters: parameters.into_iter().map(|(name, param_type)| PatternParameter {
+                name: name.to_string(),
+                param_type: param_type.to_string(),
+                required: true,
+                default_value: None,
+                description: format!("{} argument", name),
+            }).collect(),
+            transformations: HashMap::from([
+                ("pandas".to_string(), TransformRule {
+                    target_library: "pandas".to_string(),
+                    target_pattern: "DataFrame".to_string(),
+                    template: pandas_template.to_string(),
+                    imports: vec!["import pandas as pd".to_string()],
+                    setup_code: None,
+                    cleanup_code: None,
+                    parameter_mappings: HashMap::new(),
+                    caveats: Vec::new(),
+                    bidirectional: false,
+                    packages: Vec::new(),
+                }),
+            ]),
+            priority: 0,
+            detection: None,
+        }
+    }
 }
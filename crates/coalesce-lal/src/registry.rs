@@ -1,6 +1,11 @@
-use crate::patterns::{LibraryPattern, PatternLibrary};
+use crate::patterns::{LibraryPattern, PatternLibrary, PatternParameter, TransformRule};
+use crate::template::{self, placeholders};
 use coalesce_core::{Result, CoalesceError};
-use std::collections::HashMap;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use serde::Serialize;
 use serde_yaml;
 
 /// Registry for managing library patterns and transformations
@@ -24,6 +29,61 @@ impl LibraryRegistry {
             self.register_pattern(pattern)?;
         }
         
+        // Register jQuery patterns
+        for pattern in PatternLibrary::jquery_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register lodash and Underscore patterns
+        for pattern in PatternLibrary::lodash_patterns("lodash").into_iter().chain(PatternLibrary::lodash_patterns("underscore")) {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register HTTP client patterns
+        for pattern in PatternLibrary::http_client_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register logging patterns
+        for pattern in PatternLibrary::logging_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register test framework patterns
+        for pattern in PatternLibrary::test_framework_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register datetime patterns
+        for pattern in PatternLibrary::datetime_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register file I/O patterns
+        for pattern in PatternLibrary::file_io_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register concurrency patterns
+        for pattern in PatternLibrary::concurrency_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register cryptography patterns
+        for pattern in PatternLibrary::crypto_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register command-line parsing patterns
+        for pattern in PatternLibrary::cli_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register environment and configuration patterns
+        for pattern in PatternLibrary::config_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
         // Register Django patterns
         for pattern in PatternLibrary::django_patterns() {
             self.register_pattern(pattern)?;
@@ -34,9 +94,42 @@ impl LibraryRegistry {
             self.register_pattern(pattern)?;
         }
         
+        // Register numpy and pandas patterns
+        for pattern in PatternLibrary::numpy_patterns().into_iter().chain(PatternLibrary::pandas_patterns()) {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register tidyverse patterns
+        for pattern in PatternLibrary::tidyverse_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register ABAP Open SQL patterns
+        for pattern in PatternLibrary::open_sql_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register Python web framework patterns
+        for pattern in PatternLibrary::flask_patterns().into_iter().chain(PatternLibrary::fastapi_patterns()) {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register Spring Boot patterns
+        for pattern in PatternLibrary::spring_patterns() {
+            self.register_pattern(pattern)?;
+        }
+        
+        // Register .NET patterns
+        for pattern in PatternLibrary::aspnet_patterns().into_iter().chain(PatternLibrary::entity_framework_patterns()) {
+            self.register_pattern(pattern)?;
+        }
+        
         // Register ecosystem mappings
         self.register_ecosystem_mappings();
         
+        // Translate back along bidirectional rules
+        self.derive_reverse_rules();
+        
         Ok(())
     }
     
@@ -44,7 +137,7 @@ impl LibraryRegistry {
     pub fn register_pattern(&mut self, pattern: LibraryPattern) -> Result<()> {
         let library_patterns = self.patterns
             .entry(pattern.library.clone())
-            .or_insert_with(HashMap::new);
+            .or_default();
         
         library_patterns.insert(pattern.name.clone(), pattern);
         Ok(())
@@ -62,6 +155,23 @@ impl LibraryRegistry {
         self.patterns.get(library)
     }
     
+    /// Side effects of every pattern by name, merged across the libraries sharing a name
+    pub fn side_effects(&self) -> HashMap<String, Vec<String>> {
+        let mut effects: HashMap<String, Vec<String>> = HashMap::new();
+        for pattern in self.patterns.values().flat_map(HashMap::values) {
+            let known = effects.entry(pattern.name.clone()).or_default();
+            for effect in &pattern.semantics.side_effects {
+                if !known.contains(effect) {
+                    known.push(effect.clone());
+                }
+            }
+        }
+        for known in effects.values_mut() {
+            known.sort();
+        }
+        effects
+    }
+    
     /// Get available target ecosystems for a source library
     pub fn get_target_ecosystems(&self, source_library: &str) -> Vec<String> {
         self.ecosystems
@@ -78,6 +188,106 @@ impl LibraryRegistry {
         Ok(())
     }
     
+    /// Load every `*.yaml`/`*.yml`/`*.toml` pattern file in `dir`, merging over the registered
+    /// patterns. A file holds one pattern or a list; a pattern with the library and name of a
+    /// built-in replaces it. Returns the number of patterns loaded.
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> Result<usize> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            if is_pattern_file(&path) && path.is_file() {
+                files.push(path);
+            }
+        }
+        files.sort();
+        
+        // Parse and validate everything before registering anything
+        let mut loaded = Vec::new();
+        let mut seen: HashMap<(String, String), &Path> = HashMap::new();
+        for path in &files {
+            let error = |message: String| CoalesceError::TransformationError(format!("{}: {}", path.display(), message));
+            let content = fs::read_to_string(path)?;
+            let patterns = parse_patterns(path, &content).map_err(error)?;
+            for pattern in patterns {
+                validate(&pattern).map_err(error)?;
+                let key = (pattern.library.clone(), pattern.name.clone());
+                if let Some(first) = seen.insert(key, path) {
+                    return Err(error(format!(
+                        "pattern `{}` of `{}` is already defined in {}",
+                        pattern.name, pattern.library, first.display(),
+                    )));
+                }
+                loaded.push(pattern);
+            }
+        }
+        
+        self.register_loaded(loaded)
+    }
+    
+    /// Load the patterns listed under `patterns` in a project config file, merging them over
+    /// the registered patterns as [`load_dir`](Self::load_dir) does. Returns the number loaded.
+    pub fn load_config(&mut self, config_path: impl AsRef<Path>) -> Result<usize> {
+        let config_path = config_path.as_ref();
+        let error = |message: String| CoalesceError::TransformationError(format!("{}: {}", config_path.display(), message));
+        let config: serde_json::Value = serde_json::from_str(&fs::read_to_string(config_path)?)?;
+        let patterns: Vec<LibraryPattern> = match config.get("patterns") {
+            Some(section) => serde_json::from_value(section.clone()).map_err(|e| error(format!("`patterns`: {}", e)))?,
+            None => Vec::new(),
+        };
+        
+        let mut seen = HashSet::new();
+        for pattern in &patterns {
+            validate(pattern).map_err(error)?;
+            if !seen.insert((&pattern.library, &pattern.name)) {
+                return Err(error(format!("pattern `{}` of `{}` is listed twice", pattern.name, pattern.library)));
+            }
+        }
+        self.register_loaded(patterns)
+    }
+    
+    /// Every name a transformation could target: the libraries, their ecosystems and the
+    /// targets of their rules
+    pub fn known_ecosystems(&self) -> HashSet<&str> {
+        let mut known: HashSet<&str> = HashSet::new();
+        for pattern in self.patterns.values().flat_map(HashMap::values) {
+            known.extend([pattern.library.as_str(), pattern.ecosystem.as_str()]);
+            for (target, rule) in &pattern.transformations {
+                known.extend([target.as_str(), rule.target_library.as_str()]);
+            }
+        }
+        known.extend(self.ecosystems.values().flatten().map(String::as_str));
+        known
+    }
+    
+    /// Patterns that say how to find them in code, by library and name
+    pub fn detected_patterns(&self) -> Vec<&LibraryPattern> {
+        let mut patterns: Vec<&LibraryPattern> = self.patterns.values()
+            .flat_map(HashMap::values)
+            .filter(|pattern| pattern.detection.is_some())
+            .collect();
+        patterns.sort_by(|a, b| (&a.library, &a.name).cmp(&(&b.library, &b.name)));
+        patterns
+    }
+    
+    /// Register validated patterns over those registered, returning how many there were
+    fn register_loaded(&mut self, loaded: Vec<LibraryPattern>) -> Result<usize> {
+        let count = loaded.len();
+        for pattern in loaded {
+            // Transformation targets become target ecosystems of the library
+            let targets = self.ecosystems.entry(pattern.library.clone()).or_default();
+            let mut keys: Vec<&String> = pattern.transformations.keys().collect();
+            keys.sort();
+            for key in keys {
+                if !targets.contains(key) {
+                    targets.push(key.clone());
+                }
+            }
+            self.register_pattern(pattern)?;
+        }
+        self.derive_reverse_rules();
+        Ok(count)
+    }
+    
     /// Find equivalent patterns across ecosystems
     pub fn find_equivalent_patterns(&self, semantic_intent: &str) -> Vec<&LibraryPattern> {
         let mut equivalents = Vec::new();
@@ -89,7 +299,8 @@ impl LibraryRegistry {
                 }
             }
         }
-        
+        // Registry maps iterate in hash order
+        equivalents.sort_by(|a, b| (&a.library, &a.name).cmp(&(&b.library, &b.name)));
         equivalents
     }
     
@@ -106,7 +317,7 @@ impl LibraryRegistry {
             // Direct transformation
             if pattern.transformations.contains_key(target_ecosystem) {
                 suggestions.push(TransformationSuggestion {
-                    confidence: 1.0,
+                    confidence: SuggestionType::DirectTransform.confidence(),
                     suggestion_type: SuggestionType::DirectTransform,
                     target_library: pattern.transformations[target_ecosystem].target_library.clone(),
                     target_pattern: pattern.transformations[target_ecosystem].target_pattern.clone(),
@@ -118,7 +329,7 @@ impl LibraryRegistry {
             for equiv_pattern in self.find_equivalent_patterns(&pattern.semantics.intent) {
                 if equiv_pattern.ecosystem == target_ecosystem && equiv_pattern.library != source_library {
                     suggestions.push(TransformationSuggestion {
-                        confidence: 0.8,
+                        confidence: SuggestionType::SemanticEquivalent.confidence(),
                         suggestion_type: SuggestionType::SemanticEquivalent,
                         target_library: equiv_pattern.library.clone(),
                         target_pattern: equiv_pattern.name.clone(),
@@ -132,6 +343,90 @@ impl LibraryRegistry {
         suggestions
     }
     
+    /// Give the target pattern of every bidirectional rule a rule back to the rule's pattern,
+    /// registering the target pattern if it's missing. Rules a pattern already has win, and a
+    /// target several patterns of one library translate into gets none, as it can't pick one.
+    fn derive_reverse_rules(&mut self) {
+        let mut sources = HashMap::new();
+        for pattern in self.patterns.values().flat_map(HashMap::values) {
+            for rule in pattern.transformations.values().filter(|rule| rule.bidirectional) {
+                sources
+                    .entry((&rule.target_library, &rule.target_pattern, &pattern.library))
+                    .or_insert_with(Vec::new)
+                    .push((pattern, rule));
+            }
+        }
+        
+        let mut derived = Vec::new();
+        for ((library, name, source_library), rules) in sources {
+            let [(pattern, rule)] = rules[..] else {
+                continue;
+            };
+            // A pattern made up for the target takes its library's language
+            let ecosystem = self.patterns.get(library)
+                .and_then(|patterns| patterns.values().next())
+                .map_or(&pattern.ecosystem, |known| &known.ecosystem);
+            let target = LibraryPattern {
+                name: name.to_string(),
+                library: library.to_string(),
+                ecosystem: ecosystem.clone(),
+                signature: rule.template.clone(),
+                semantics: pattern.semantics.clone(),
+                parameters: placeholders(&rule.template).into_iter().map(|parameter| PatternParameter {
+                    name: parameter,
+                    param_type: "any".to_string(),
+                    required: true,
+                    default_value: None,
+                    description: String::new(),
+                }).collect(),
+                transformations: HashMap::new(),
+                priority: 0,
+                detection: None,
+            };
+            let reverse = TransformRule {
+                target_library: pattern.library.clone(),
+                target_pattern: pattern.name.clone(),
+                template: reverse_template(pattern, rule),
+                imports: self.imports_of(&pattern.library, &pattern.name),
+                setup_code: None,
+                cleanup_code: None,
+                parameter_mappings: HashMap::new(),
+                caveats: rule.caveats.clone(),
+                bidirectional: false,
+                packages: Vec::new(),
+            };
+            derived.push((source_library.to_string(), target, reverse));
+        }
+        // Keep the first pattern made up for a target the same from run to run
+        derived.sort_by(|a, b| (&a.1.library, &a.1.name, &a.0).cmp(&(&b.1.library, &b.1.name, &b.0)));
+        
+        for (source_library, target, reverse) in derived {
+            let targets = self.ecosystems.entry(target.library.clone()).or_default();
+            if !targets.contains(&source_library) {
+                targets.push(source_library.clone());
+            }
+            self.patterns
+                .entry(target.library.clone())
+                .or_default()
+                .entry(target.name.clone())
+                .or_insert(target)
+                .transformations
+                .entry(source_library)
+                .or_insert(reverse);
+        }
+    }
+    
+    /// Imports some rule into a library's pattern brings, as a rule back to it needs them too
+    fn imports_of(&self, library: &str, pattern_name: &str) -> Vec<String> {
+        let mut rules: Vec<&TransformRule> = self.patterns.values()
+            .flat_map(HashMap::values)
+            .flat_map(|pattern| pattern.transformations.values())
+            .filter(|rule| rule.target_library == library && rule.target_pattern == pattern_name && !rule.imports.is_empty())
+            .collect();
+        rules.sort_by(|a, b| a.imports.cmp(&b.imports));
+        rules.first().map(|rule| rule.imports.clone()).unwrap_or_default()
+    }
+    
     fn register_ecosystem_mappings(&mut self) {
         // JavaScript ecosystem mappings
         self.ecosystems.insert("react".to_string(), vec![
@@ -141,6 +436,19 @@ impl LibraryRegistry {
             "vanilla".to_string(),
         ]);
         
+        self.ecosystems.insert("jquery".to_string(), vec![
+            "vanilla".to_string(),
+            "react".to_string(),
+        ]);
+        
+        for library in ["lodash", "underscore"] {
+            self.ecosystems.insert(library.to_string(), vec![
+                "javascript".to_string(),
+                "python".to_string(),
+                "rust".to_string(),
+            ]);
+        }
+        
         // Python ecosystem mappings
         self.ecosystems.insert("django".to_string(), vec![
             "sqlalchemy".to_string(),
@@ -155,7 +463,262 @@ impl LibraryRegistry {
             "python".to_string(),
             "javascript".to_string(),
         ]);
+        
+        // Python web framework mappings
+        for framework in ["flask", "fastapi"] {
+            self.ecosystems.insert(framework.to_string(), vec![
+                "axum".to_string(),
+                "gin".to_string(),
+                "aspnet".to_string(),
+            ]);
+        }
+        
+        // JVM web framework mappings
+        self.ecosystems.insert("spring".to_string(), vec![
+            "aspnet".to_string(),
+            "axum".to_string(),
+            "fastapi".to_string(),
+        ]);
+        
+        // .NET mappings
+        self.ecosystems.insert("aspnet".to_string(), vec![
+            "django".to_string(),
+            "axum".to_string(),
+        ]);
+        self.ecosystems.insert("entity_framework".to_string(), vec![
+            "django".to_string(),
+            "sqlalchemy".to_string(),
+            "diesel".to_string(),
+            "sea_orm".to_string(),
+        ]);
+        
+        // HTTP clients translate into each other
+        let http_clients = ["axios", "fetch", "requests", "httpx", "net_http", "reqwest"];
+        for client in http_clients {
+            self.ecosystems.insert(client.to_string(), http_clients.iter()
+                .filter(|other| **other != client)
+                .map(|other| other.to_string())
+                .collect());
+        }
+        
+        // Loggers translate into each other
+        let loggers = ["console", "winston", "log4j", "log4net", "logging", "log", "zap", "tracing"];
+        for logger in loggers {
+            self.ecosystems.insert(logger.to_string(), loggers.iter()
+                .filter(|other| **other != logger)
+                .map(|other| other.to_string())
+                .collect());
+        }
+        
+        // Test frameworks translate into each other
+        let test_frameworks = ["jest", "xunit", "pytest", "testing", "cargo_test"];
+        for framework in test_frameworks {
+            self.ecosystems.insert(framework.to_string(), test_frameworks.iter()
+                .filter(|other| **other != framework)
+                .map(|other| other.to_string())
+                .collect());
+        }
+        
+        // Date and time libraries translate into each other
+        let datetime_libraries = ["moment", "dayjs", "datetime", "java_time", "system_datetime", "chrono"];
+        for library in datetime_libraries {
+            self.ecosystems.insert(library.to_string(), datetime_libraries.iter()
+                .filter(|other| **other != library)
+                .map(|other| other.to_string())
+                .collect());
+        }
+        
+        // File I/O translates between each language's standard library
+        let file_io_libraries = ["fs", "io", "os", "system_io", "std_fs"];
+        for library in file_io_libraries {
+            self.ecosystems.insert(library.to_string(), file_io_libraries.iter()
+                .filter(|other| **other != library)
+                .map(|other| other.to_string())
+                .collect());
+        }
+        
+        // Threads, tasks and goroutines translate into each other, with caveats
+        let concurrency_libraries = ["java_concurrent", "dotnet_tasks", "threading", "asyncio", "goroutines", "std_thread", "tokio"];
+        for library in concurrency_libraries {
+            self.ecosystems.insert(library.to_string(), concurrency_libraries.iter()
+                .filter(|other| **other != library)
+                .map(|other| other.to_string())
+                .collect());
+        }
+        
+        // Hashing, signing and encryption translate between each language's vetted APIs
+        let crypto_libraries = ["crypto", "hashlib", "cryptography", "go_crypto", "dotnet_crypto", "rustcrypto"];
+        for library in crypto_libraries {
+            self.ecosystems.insert(library.to_string(), crypto_libraries.iter()
+                .filter(|other| **other != library)
+                .map(|other| other.to_string())
+                .collect());
+        }
+        
+        // Argument parsers translate into each other
+        let cli_libraries = ["argparse", "commander", "yargs", "cobra", "clap", "system_commandline"];
+        for library in cli_libraries {
+            self.ecosystems.insert(library.to_string(), cli_libraries.iter()
+                .filter(|other| **other != library)
+                .map(|other| other.to_string())
+                .collect());
+        }
+        
+        // Environment variables, .env files and config files translate between each language's loaders
+        let config_libraries = [
+            "process_env", "dotenv", "node_config", "os_environ", "python_dotenv", "go_env", "godotenv", "viper",
+            "dotnet_config", "dotnetenv", "std_env", "dotenvy", "config_rs",
+        ];
+        for library in config_libraries {
+            self.ecosystems.insert(library.to_string(), config_libraries.iter()
+                .filter(|other| **other != library)
+                .map(|other| other.to_string())
+                .collect());
+        }
+        
+        // Scientific Python mappings
+        self.ecosystems.insert("numpy".to_string(), vec![
+            "ndarray".to_string(),
+            "gonum".to_string(),
+        ]);
+        self.ecosystems.insert("pandas".to_string(), vec![
+            "polars".to_string(),
+        ]);
+        
+        // R data-frame mappings
+        self.ecosystems.insert("dplyr".to_string(), vec![
+            "pandas".to_string(),
+            "polars".to_string(),
+        ]);
+        
+        // SAP database access mappings
+        self.ecosystems.insert("open_sql".to_string(), vec![
+            "sqlalchemy".to_string(),
+            "jdbc".to_string(),
+        ]);
+    }
+}
+
+/// A pattern's signature as a template for a rule back to it. Signatures written as code
+/// name their parameters bare: each one the forward rule fills becomes a placeholder, quoted
+/// where the rule quotes it, and each one it drops takes its default.
+fn reverse_template(pattern: &LibraryPattern, rule: &TransformRule) -> String {
+    if pattern.signature.contains("{{") {
+        return pattern.signature.clone();
+    }
+    let filled = placeholders(&rule.template);
+    let mut replacements: HashMap<String, String> = HashMap::new();
+    for name in &filled {
+        let quoted = ["\"", "'"].iter().any(|quote| rule.template.contains(&format!("{}{{{{{}}}}}{}", quote, name, quote)));
+        let placeholder = format!("{{{{{}}}}}", name);
+        replacements.insert(name.clone(), if quoted { format!("\"{}\"", placeholder) } else { placeholder });
+    }
+    for parameter in &pattern.parameters {
+        if let Some(default) = &parameter.default_value {
+            replacements.entry(parameter.name.clone()).or_insert_with(|| default.clone());
+        }
+    }
+    if replacements.is_empty() {
+        return pattern.signature.clone();
+    }
+    let mut names: Vec<&String> = replacements.keys().collect();
+    names.sort();
+    let alternatives: Vec<String> = names.iter().map(|name| regex::escape(name)).collect();
+    let bare = Regex::new(&format!(r"\b({})\b", alternatives.join("|"))).unwrap();
+    bare.replace_all(&pattern.signature, |captures: &regex::Captures| replacements[&captures[1]].clone()).into_owned()
+}
+
+/// Whether `path` names a pattern file: YAML (`*.yaml`, `*.yml`) or TOML (`*.toml`)
+pub(crate) fn is_pattern_file(path: &Path) -> bool {
+    matches!(path.extension().and_then(|e| e.to_str()), Some("yaml" | "yml" | "toml"))
+}
+
+/// The patterns of a pattern file, read as TOML or YAML by its extension. A YAML file holds a
+/// single pattern or a list of them; a TOML file, which can't be a list, holds a single pattern
+/// or lists them as `[[patterns]]` tables.
+pub(crate) fn parse_patterns(path: &Path, content: &str) -> std::result::Result<Vec<LibraryPattern>, String> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum PatternFile {
+        Many(Vec<LibraryPattern>),
+        Listed { patterns: Vec<LibraryPattern> },
+        One(Box<LibraryPattern>),
     }
+    
+    let file = if path.extension().is_some_and(|e| e == "toml") {
+        toml::from_str(content).map_err(|e: toml::de::Error| format!("TOML parse error: {}", e.message()))?
+    } else {
+        serde_yaml::from_str(content).map_err(|e| format!("YAML parse error: {}", e))?
+    };
+    Ok(match file {
+        PatternFile::Many(patterns) | PatternFile::Listed { patterns } => patterns,
+        PatternFile::One(pattern) => vec![*pattern],
+    })
+}
+
+/// Check a loaded pattern for the fields detection and transformation rely on
+pub(crate) fn validate(pattern: &LibraryPattern) -> std::result::Result<(), String> {
+    let label = if pattern.name.trim().is_empty() { "<unnamed>" } else { pattern.name.as_str() };
+    let required = [
+        ("name", &pattern.name),
+        ("library", &pattern.library),
+        ("ecosystem", &pattern.ecosystem),
+        ("semantics.intent", &pattern.semantics.intent),
+        ("semantics.category", &pattern.semantics.category),
+    ];
+    for (field, value) in required {
+        if value.trim().is_empty() {
+            return Err(format!("pattern `{}` has an empty `{}`", label, field));
+        }
+    }
+    
+    if pattern.detection.as_ref().is_some_and(|detection| detection.call.trim().is_empty()) {
+        return Err(format!("pattern `{}` has a `detection` without a `call`", label));
+    }
+    
+    let mut parameters = HashSet::new();
+    for parameter in &pattern.parameters {
+        if parameter.name.trim().is_empty() {
+            return Err(format!("pattern `{}` has a parameter without a name", label));
+        }
+        if !parameters.insert(parameter.name.as_str()) {
+            return Err(format!("pattern `{}` declares parameter `{}` twice", label, parameter.name));
+        }
+    }
+    
+    let mut targets: Vec<_> = pattern.transformations.iter().collect();
+    targets.sort_by(|a, b| a.0.cmp(b.0));
+    for (target, rule) in targets {
+        if target.trim().is_empty() {
+            return Err(format!("pattern `{}` has a transformation without a target ecosystem", label));
+        }
+        if rule.target_library.trim().is_empty() || rule.template.trim().is_empty() {
+            return Err(format!(
+                "transformation `{}` of pattern `{}` needs a `target_library` and a `template`",
+                target, label,
+            ));
+        }
+        let written = [Some(&rule.template), rule.setup_code.as_ref(), rule.cleanup_code.as_ref()];
+        for code in written.into_iter().flatten() {
+            if let Err(e) = template::check(code) {
+                return Err(format!("transformation `{}` of pattern `{}` has a template that won't render: {}", target, label, e));
+            }
+        }
+        // The reverse rule reads the forward rule's placeholders back
+        if rule.bidirectional && !template::is_plain(&rule.template) {
+            return Err(format!(
+                "transformation `{}` of pattern `{}` is bidirectional, so its template can't use blocks or helpers",
+                target, label,
+            ));
+        }
+        if rule.bidirectional && rule.target_pattern.trim().is_empty() {
+            return Err(format!(
+                "transformation `{}` of pattern `{}` is bidirectional, so it needs a `target_pattern`",
+                target, label,
+            ));
+        }
+    }
+    Ok(())
 }
 
 /// Suggestion for transforming a library pattern
@@ -168,7 +731,8 @@ pub struct TransformationSuggestion {
     pub description: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SuggestionType {
     DirectTransform,
     SemanticEquivalent,
@@ -176,6 +740,18 @@ pub enum SuggestionType {
     PartialTransform,
 }
 
+impl SuggestionType {
+    /// How far a translation of this kind can be trusted without review, from 0 to 1
+    pub fn confidence(self) -> f32 {
+        match self {
+            SuggestionType::DirectTransform => 1.0,
+            SuggestionType::SemanticEquivalent => 0.8,
+            SuggestionType::PartialTransform => 0.5,
+            SuggestionType::ManualImplementation => 0.0,
+        }
+    }
+}
+
 impl Default for LibraryRegistry {
     fn default() -> Self {
         let mut registry = Self::new();
@@ -183,3 +759,86 @@ impl Default for LibraryRegistry {
         registry
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// A `greeter` pattern whose rule into `pygreeter` is declared `bidirectional`
+    fn greeting(name: &str, target_pattern: &str, bidirectional: bool) -> String {
+        format!(r#"
+name: {name}
+library: greeter
+ecosystem: javascript
+signature: {name}(person, punctuation)
+semantics:
+  intent: greet
+  category: io
+parameters:
+  - {{ name: person, param_type: string, required: true, default_value: null }}
+  - {{ name: punctuation, param_type: string, required: false, default_value: "'!'" }}
+transformations:
+  pygreeter:
+    target_library: pygreeter
+    target_pattern: {target_pattern}
+    template: '{target_pattern}("{{{{person}}}}")'
+    imports: ["from pygreeter import {target_pattern}"]
+    setup_code: null
+    cleanup_code: null
+    bidirectional: {bidirectional}
+"#)
+    }
+    
+    fn registry(patterns: &[String]) -> LibraryRegistry {
+        let mut registry = LibraryRegistry::new();
+        for pattern in patterns {
+            registry.register_from_yaml(pattern).unwrap();
+        }
+        registry.derive_reverse_rules();
+        registry
+    }
+    
+    #[test]
+    fn test_bidirectional_rules_are_derived_back() {
+        let registry = registry(&[greeting("greet", "say_hello", true), greeting("wave", "wave_at", false)]);
+        let target = registry.get_pattern("pygreeter", "say_hello").unwrap();
+        let reverse = &target.transformations["greeter"];
+        
+        assert_eq!(reverse.target_pattern, "greet");
+        assert_eq!(reverse.template, "greet(\"{{person}}\", '!')");
+        assert!(!reverse.bidirectional);
+        assert_eq!(target.signature, "say_hello(\"{{person}}\")");
+        assert_eq!(target.parameters.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), ["person"]);
+        assert_eq!(target.semantics.intent, "greet");
+        assert_eq!(registry.get_target_ecosystems("pygreeter"), ["greeter"]);
+        assert!(registry.get_pattern("pygreeter", "wave_at").is_none());
+    }
+    
+    #[test]
+    fn test_rules_a_target_has_and_ambiguous_targets_are_kept() {
+        let mut own = serde_yaml::from_str::<LibraryPattern>(&greeting("say_hello", "greet", false)).unwrap();
+        own.library = "pygreeter".to_string();
+        own.ecosystem = "python".to_string();
+        own.transformations = HashMap::from([("greeter".to_string(), TransformRule {
+            target_library: "greeter".to_string(),
+            template: "hello({{person}})".to_string(),
+            ..own.transformations.remove("pygreeter").unwrap()
+        })]);
+        let mut registry = registry(&[greeting("greet", "say_hello", true), greeting("hi", "say_hi", true), greeting("hey", "say_hi", true)]);
+        registry.register_pattern(own).unwrap();
+        registry.derive_reverse_rules();
+        
+        assert_eq!(registry.get_pattern("pygreeter", "say_hello").unwrap().transformations["greeter"].template, "hello({{person}})");
+        assert!(registry.get_pattern("pygreeter", "say_hi").is_none());
+    }
+    
+    #[test]
+    fn test_built_in_bidirectional_rules_translate_back() {
+        let registry = LibraryRegistry::default();
+        assert!(registry.get_target_ecosystems("vue").iter().any(|target| target == "react"));
+        let back: Vec<&TransformRule> = registry.get_library_patterns("vue").unwrap().values()
+            .filter_map(|pattern| pattern.transformations.get("react"))
+            .collect();
+        assert!(back.iter().any(|rule| rule.target_pattern == "useState"), "{:?}", back);
+    }
+}
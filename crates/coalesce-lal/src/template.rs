@@ -0,0 +1,289 @@
+// Rule templates
+//
+// A rule's template, setup and cleanup code are Handlebars templates rendered over the
+// parameters detection found for a usage, each the source text of a value. A parameter the
+// usage doesn't have stays in the code as its `{{placeholder}}`, except where a block tests it:
+// `{{#if timeout}}, timeout={{timeout}}{{/if}}` writes an optional argument only when it was
+// passed. Usages are only translated by rules `render_complete` takes, which leave none.
+//
+// Helpers, each of one value:
+//
+// - `snake_case`, `camel_case`, `pascal_case`: recase a name
+// - `quote`: the value as a double-quoted string literal, requoting one written in other quotes
+// - `string`: a string literal requoted in double quotes, any other value as written
+// - `unquote`: a string literal's text without its quotes
+// - `args`: an argument list split at its top-level commas, for `{{#each (args fields)}}`
+
+use crate::detector::{split_top_level, unquote};
+use coalesce_core::{CoalesceError, NamingConvention, Result};
+use handlebars::{
+    no_escape, Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+    RenderErrorReason, ScopedJson, Template, TemplateErrorReason,
+};
+use handlebars::template::{HelperTemplate, Parameter, TemplateElement};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Handlebars' own helpers, then ours
+const HELPERS: &[&str] = &[
+    "if", "unless", "each", "with", "lookup", "raw", "log", "eq", "ne", "gt", "gte", "lt", "lte", "and", "or", "not", "len",
+    "snake_case", "camel_case", "pascal_case", "quote", "string", "unquote", "args",
+];
+
+/// Render a rule's code over a usage's parameters
+pub(crate) fn render(template: &str, parameters: &HashMap<String, String>) -> Result<String> {
+    engine().render_template(template, parameters)
+        .map_err(|e| CoalesceError::TransformationError(format!("Failed to render `{}`: {}", template, e)))
+}
+
+/// Render a rule's code over a usage's parameters, failing where a placeholder would stay in
+/// the code for want of its parameter
+pub(crate) fn render_complete(template: &str, parameters: &HashMap<String, String>) -> Result<String> {
+    let code = render(template, parameters)?;
+    let missing = placeholders(template).into_iter()
+        .find(|name| !parameters.contains_key(name) && code.contains(&format!("{{{{{}}}}}", name)));
+    match missing {
+        Some(name) => Err(CoalesceError::TransformationError(format!("`{}` has no value for `{}`", template, name))),
+        None => Ok(code),
+    }
+}
+
+/// Why a template won't render, if it won't: bad syntax or a helper that doesn't exist
+pub(crate) fn check(template: &str) -> std::result::Result<(), String> {
+    let compiled = Template::compile(template).map_err(|e| {
+        // The parser lists every token it expected
+        let reason = match e.reason() {
+            TemplateErrorReason::InvalidSyntax(_) => "invalid syntax".to_string(),
+            reason => reason.to_string(),
+        };
+        match e.pos() {
+            Some((line, column)) => format!("{} at line {}, column {}", reason, line, column),
+            None => reason,
+        }
+    })?;
+    match unknown_helper(&compiled) {
+        Some(name) => Err(format!("there's no `{}` helper", name)),
+        None => Ok(()),
+    }
+}
+
+fn unknown_helper(template: &Template) -> Option<String> {
+    template.elements.iter().find_map(|element| {
+        let expression = match element {
+            TemplateElement::Expression(expression) | TemplateElement::HtmlExpression(expression) | TemplateElement::HelperBlock(expression) => expression,
+            _ => return None,
+        };
+        let name = expression.name.as_name().unwrap_or_default();
+        if !is_name_only(expression) && !HELPERS.contains(&name) {
+            return Some(name.to_string());
+        }
+        let subexpressions = expression.params.iter().filter_map(|parameter| match parameter {
+            Parameter::Subexpression(subexpression) => Some(subexpression.as_element()),
+            _ => None,
+        });
+        for element in subexpressions {
+            if let TemplateElement::Expression(subexpression) = element {
+                let name = subexpression.name.as_name().unwrap_or_default();
+                if !HELPERS.contains(&name) {
+                    return Some(name.to_string());
+                }
+            }
+        }
+        expression.template.iter().chain(&expression.inverse).find_map(unknown_helper)
+    })
+}
+
+/// Whether a template is only code and `{{placeholder}}`s, with no blocks or helpers
+pub(crate) fn is_plain(template: &str) -> bool {
+    let Ok(compiled) = Template::compile(template) else {
+        return false;
+    };
+    compiled.elements.iter().all(|element| match element {
+        TemplateElement::RawString(_) | TemplateElement::Comment(_) => true,
+        TemplateElement::Expression(expression) => is_name_only(expression),
+        _ => false,
+    })
+}
+
+/// Names of the parameters a template uses, in order of first use: its placeholders, the
+/// values its helpers take and the values its blocks test or loop over
+pub(crate) fn placeholders(template: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Ok(compiled) = Template::compile(template) {
+        collect_names(&compiled, &mut names);
+    }
+    names
+}
+
+/// A `{{name}}`: no block, arguments or helper
+fn is_name_only(expression: &HelperTemplate) -> bool {
+    !expression.block && expression.params.is_empty() && expression.hash.is_empty()
+}
+
+fn collect_names(template: &Template, names: &mut Vec<String>) {
+    for element in &template.elements {
+        let (expression, blocks) = match element {
+            TemplateElement::Expression(expression) | TemplateElement::HtmlExpression(expression) => (expression, None),
+            TemplateElement::HelperBlock(block) => (block, Some((&block.template, &block.inverse))),
+            _ => continue,
+        };
+        if is_name_only(expression) {
+            push_name(expression.name.as_name(), names);
+        }
+        for parameter in &expression.params {
+            collect_parameter(parameter, names);
+        }
+        // Inside a loop, names are fields of the item
+        if let Some((template, inverse)) = blocks.filter(|_| expression.name.as_name() != Some("each")) {
+            template.iter().chain(inverse).for_each(|template| collect_names(template, names));
+        }
+    }
+}
+
+fn collect_parameter(parameter: &Parameter, names: &mut Vec<String>) {
+    match parameter {
+        Parameter::Path(_) => push_name(parameter.as_name(), names),
+        Parameter::Subexpression(expression) => {
+            if let TemplateElement::Expression(expression) = expression.as_element() {
+                expression.params.iter().for_each(|parameter| collect_parameter(parameter, names));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push_name(name: Option<&str>, names: &mut Vec<String>) {
+    let Some(name) = name else {
+        return;
+    };
+    let parameter = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') && name != "this" && name != "else";
+    if parameter && !names.iter().any(|known| known == name) {
+        names.push(name.to_string());
+    }
+}
+
+fn engine() -> &'static Handlebars<'static> {
+    static ENGINE: OnceLock<Handlebars<'static>> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut engine = Handlebars::new();
+        engine.register_escape_fn(no_escape);
+        engine.register_helper("helperMissing", Box::new(KeepPlaceholder));
+        engine.register_helper("snake_case", Box::new(ValueHelper(|value| Value::String(NamingConvention::SnakeCase.apply(value)))));
+        engine.register_helper("camel_case", Box::new(ValueHelper(|value| Value::String(NamingConvention::CamelCase.apply(value)))));
+        engine.register_helper("pascal_case", Box::new(ValueHelper(|value| Value::String(NamingConvention::PascalCase.apply(value)))));
+        engine.register_helper("quote", Box::new(ValueHelper(|value| Value::String(quote(value)))));
+        engine.register_helper("string", Box::new(ValueHelper(|value| Value::String(string(value)))));
+        engine.register_helper("unquote", Box::new(ValueHelper(|value| Value::String(unquote(value).to_string()))));
+        engine.register_helper("args", Box::new(ValueHelper(|value| Value::Array(split_top_level(value).into_iter().map(Value::String).collect()))));
+        engine
+    })
+}
+
+/// A string literal, double-quoted, of a value written as a literal in any quotes or as text
+fn quote(value: &str) -> String {
+    let trimmed = value.trim();
+    if trimmed.len() >= 2 && trimmed.starts_with('"') && trimmed.ends_with('"') && !trimmed.starts_with("\"\"\"") {
+        return trimmed.to_string();
+    }
+    let text = unquote(trimmed);
+    let text = if text.len() == trimmed.len() { text.replace('\\', "\\\\") } else { text.replace("\\'", "'") };
+    format!("\"{}\"", text.replace('"', "\\\""))
+}
+
+/// A string literal double-quoted, for targets whose strings take no other quotes; a value
+/// that isn't a literal stays as written
+fn string(value: &str) -> String {
+    if unquote(value).len() == value.trim().len() {
+        return value.to_string();
+    }
+    quote(value)
+}
+
+/// Writes a parameter the usage doesn't have as its placeholder
+struct KeepPlaceholder;
+
+impl HelperDef for KeepPlaceholder {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        // A call with arguments names a helper that doesn't exist
+        if !h.params().is_empty() || !h.hash().is_empty() {
+            return Err(RenderErrorReason::HelperNotFound(h.name().to_string()).into());
+        }
+        out.write(&format!("{{{{{}}}}}", h.name()))?;
+        Ok(())
+    }
+}
+
+/// A helper of one value; a parameter the usage doesn't have stays its placeholder
+struct ValueHelper(fn(&str) -> Value);
+
+impl HelperDef for ValueHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> std::result::Result<ScopedJson<'rc>, RenderError> {
+        let parameter = h.param(0).ok_or(RenderErrorReason::ParamNotFoundForIndex("value", 0))?;
+        if parameter.is_value_missing() {
+            let name = parameter.relative_path().cloned().unwrap_or_default();
+            return Ok(ScopedJson::Derived(Value::String(format!("{{{{{}}}}}", name))));
+        }
+        let value = match parameter.value() {
+            Value::String(value) => value.clone(),
+            other => other.to_string(),
+        };
+        Ok(ScopedJson::Derived((self.0)(&value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn parameters(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|&(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+    
+    #[test]
+    fn test_helpers_recase_quote_and_split() {
+        let values = parameters(&[("name", "userName"), ("text", "'it\\'s'"), ("plain", "say \"hi\""), ("fields", "a, f(b, c), 'd,e'")]);
+        let render = |template: &str| render(template, &values).unwrap();
+        assert_eq!(render("{{snake_case name}} {{pascal_case name}} {{camel_case (snake_case name)}}"), "user_name UserName userName");
+        assert_eq!(render("{{quote text}} {{quote plain}} {{unquote text}}"), r#""it's" "say \"hi\"" it\'s"#);
+        assert_eq!(render("{{string text}} {{string name}}"), r#""it's" userName"#);
+        assert_eq!(render("{{#each (args fields)}}[{{this}}]{{/each}}"), "[a][f(b, c)]['d,e']");
+    }
+    
+    #[test]
+    fn test_optional_arguments_and_missing_parameters() {
+        let template = "get({{url}}{{#if timeout}}, timeout={{timeout}}{{/if}})";
+        assert_eq!(render(template, &parameters(&[("url", "u"), ("timeout", "5")])).unwrap(), "get(u, timeout=5)");
+        assert_eq!(render_complete(template, &parameters(&[("url", "u")])).unwrap(), "get(u)");
+        
+        // A parameter the usage doesn't have stays its placeholder, which `render_complete` refuses
+        assert_eq!(render("{{a}} {{snake_case b}}", &parameters(&[])).unwrap(), "{{a}} {{b}}");
+        assert!(render_complete("{{a}} {{snake_case b}}", &parameters(&[("a", "x")])).is_err());
+    }
+    
+    #[test]
+    fn test_templates_are_checked_and_read_for_their_parameters() {
+        assert_eq!(check("{{#if a}}{{b}}{{/if}}"), Ok(()));
+        assert_eq!(check("{{shout a}}"), Err("there's no `shout` helper".to_string()));
+        assert_eq!(check("{{#each (split a)}}{{this}}{{/each}}"), Err("there's no `split` helper".to_string()));
+        assert!(check("{{#if a}}").unwrap_err().contains("line 1"), "{:?}", check("{{#if a}}"));
+        
+        assert!(is_plain("f({{a}}, {{b}})"));
+        assert!(!is_plain("f({{quote a}})"));
+        assert!(!is_plain("{{#if a}}{{a}}{{/if}}"));
+        assert_eq!(placeholders("{{b}}{{#if c}}{{quote a}}{{b}}{{/if}}{{#each (args d)}}{{this.e}}{{/each}}"), ["b", "c", "a", "d"]);
+    }
+}
@@ -1,16 +1,159 @@
-use crate::{LibraryDependency, patterns::{LibraryPattern, TransformRule}};
-use crate::registry::LibraryRegistry;
-use coalesce_core::{UIRNode, Language, Result, CoalesceError};
-use std::collections::HashMap;
+use crate::{LibraryDependency, LibraryUsage, patterns::{LibraryPattern, TransformRule}};
+use crate::coverage::{CoverageReport, UsageCoverage};
+use crate::manifest::{is_standard_library, language_ecosystem, TargetManifest};
+use crate::registry::{LibraryRegistry, SuggestionType};
+use crate::detector::{byte_range, has_tag, location_range};
+use crate::template;
+use coalesce_core::{UIRNode, NodeType, ExpressionType, SourceLocation, StatementType, Language, Parser, Result, Walk, walk_mut};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 
 /// Transforms library-specific patterns between ecosystems
 pub struct LibraryTransformer<'a> {
     registry: &'a LibraryRegistry,
+    /// Libraries the target project depends on, whose rules win where no ecosystem is given
+    installed: &'a [String],
+}
+
+/// What a library usage translates to
+enum Resolution<'a> {
+    /// A rule of the usage's own pattern for the target ecosystem
+    Direct(&'a LibraryPattern, &'a TransformRule),
+    /// A pattern of the target language with the same intent, whose signature is a template
+    /// the usage's parameters render
+    Equivalent(&'a LibraryPattern, &'a LibraryPattern),
+    /// Nothing: someone has to write it
+    Manual(&'a LibraryPattern),
+}
+
+impl Resolution<'_> {
+    fn kind(&self) -> SuggestionType {
+        match self {
+            Resolution::Direct(..) => SuggestionType::DirectTransform,
+            Resolution::Equivalent(..) => SuggestionType::SemanticEquivalent,
+            Resolution::Manual(_) => SuggestionType::ManualImplementation,
+        }
+    }
 }
 
 impl<'a> LibraryTransformer<'a> {
     pub fn new(registry: &'a LibraryRegistry) -> Self {
-        Self { registry }
+        Self { registry, installed: &[] }
+    }
+    
+    /// Translate, where no ecosystem is given, with the rules whose ecosystem or target library
+    /// is one of `installed`, the libraries the target project depends on
+    pub fn with_installed(mut self, installed: &'a [String]) -> Self {
+        self.installed = installed;
+        self
+    }
+    
+    /// How each usage of the dependencies would translate, without transforming anything
+    pub fn coverage(&self, dependencies: &[LibraryDependency], target_lang: &Language, target_ecosystem: Option<&str>) -> CoverageReport {
+        let mut report = CoverageReport::default();
+        for dependency in dependencies {
+            for usage in &dependency.usage_patterns {
+                let resolution = self.resolve(&dependency.name, usage, target_lang, target_ecosystem);
+                let translation = resolution.as_ref().map_or(SuggestionType::ManualImplementation, Resolution::kind);
+                report.push(UsageCoverage {
+                    library: dependency.name.clone(),
+                    pattern: usage.pattern_name.clone(),
+                    method_name: usage.method_name.clone(),
+                    source_location: usage.source_location.clone(),
+                    snippet: usage.snippet.clone(),
+                    translation,
+                    confidence: translation.confidence(),
+                    target: match resolution {
+                        Some(Resolution::Direct(_, rule)) => Some(format!("{}:{}", rule.target_library, rule.target_pattern)),
+                        Some(Resolution::Equivalent(_, equivalent)) => Some(format!("{}:{}", equivalent.library, equivalent.name)),
+                        _ => None,
+                    },
+                });
+            }
+        }
+        report
+    }
+    
+    /// The packages a target project needs for the translations of the dependencies' usages
+    pub fn target_manifest(&self, dependencies: &[LibraryDependency], target_lang: &Language, target_ecosystem: Option<&str>) -> TargetManifest {
+        let mut manifest = TargetManifest::new(language_ecosystem(target_lang).unwrap_or_default());
+        for dependency in dependencies {
+            for usage in &dependency.usage_patterns {
+                match self.resolve(&dependency.name, usage, target_lang, target_ecosystem) {
+                    Some(Resolution::Direct(_, rule)) => manifest.add_rule(rule),
+                    Some(Resolution::Equivalent(_, equivalent)) => manifest.add_library(&equivalent.library),
+                    _ => {}
+                }
+            }
+        }
+        manifest
+    }
+    
+    /// A rule for the target ecosystem that the usage has every parameter of, else a pattern of
+    /// the target language meant for the same, preferring one of the same name, when its
+    /// signature renders for the usage; `None` for patterns the registry doesn't know. Usages
+    /// passing a function written in the source to another language are left to be done by hand
+    fn resolve(&self, library: &str, usage: &LibraryUsage, target_lang: &Language, target_ecosystem: Option<&str>) -> Option<Resolution<'a>> {
+        let pattern = self.registry.get_pattern(library, &usage.pattern_name)?;
+        if passes_foreign_code(pattern, usage, target_lang) {
+            return Some(Resolution::Manual(pattern));
+        }
+        // A rule the usage lacks parameters for would write `{{placeholder}}`s: it has to be
+        // done by hand
+        if let Some(rule) = self.rule_for(pattern, target_lang, target_ecosystem) {
+            let complete = [Some(&rule.template), rule.setup_code.as_ref(), rule.cleanup_code.as_ref()].into_iter().flatten()
+                .all(|code| template::render_complete(code, &usage.parameters).is_ok());
+            return Some(if complete { Resolution::Direct(pattern, rule) } else { Resolution::Manual(pattern) });
+        }
+        let language = format!("{:?}", target_lang).to_lowercase();
+        let equivalent = self.registry.find_equivalent_patterns(&pattern.semantics.intent)
+            .into_iter()
+            .filter(|equivalent| equivalent.ecosystem == language && equivalent.library != library)
+            .filter(|equivalent| renders(&equivalent.signature, &usage.parameters))
+            .min_by_key(|equivalent| equivalent.name != pattern.name);
+        Some(match equivalent {
+            Some(equivalent) => Resolution::Equivalent(pattern, equivalent),
+            None => Resolution::Manual(pattern),
+        })
+    }
+    
+    /// The rule of the given ecosystem, else that of a library the target project depends on,
+    /// else that of the target language's default ecosystem, else that of a library of the
+    /// target language, preferring one that comes with it: rules keyed by library, like those
+    /// of loggers, have none for a default ecosystem
+    fn rule_for(&self, pattern: &'a LibraryPattern, target_lang: &Language, target_ecosystem: Option<&str>) -> Option<&'a TransformRule> {
+        if let Some(ecosystem) = target_ecosystem {
+            return pattern.transformations.get(ecosystem);
+        }
+        let installed = pattern.transformations.iter()
+            .filter(|(ecosystem, rule)| self.is_installed(ecosystem) || self.is_installed(&rule.target_library))
+            .min_by_key(|(ecosystem, _)| *ecosystem);
+        match installed {
+            Some((_, rule)) => Some(rule),
+            None => pattern.transformations.get(&self.get_default_ecosystem(target_lang))
+                // Go's and Python's standard libraries are both `stdlib`
+                .filter(|rule| !is_other_language(&rule.target_library, target_lang))
+                .or_else(|| self.language_rule(pattern, target_lang)),
+        }
+    }
+    
+    fn language_rule(&self, pattern: &'a LibraryPattern, target_lang: &Language) -> Option<&'a TransformRule> {
+        let ecosystem = language_ecosystem(target_lang)?;
+        // Names are ambiguous across languages: `log` is Go's package and a Rust crate
+        let of_language = |library: &str| self.registry.get_library_patterns(library)
+            .is_some_and(|patterns| patterns.values().any(|pattern| pattern.ecosystem == ecosystem));
+        pattern.transformations.iter()
+            .filter(|(key, rule)| of_language(key) || of_language(&rule.target_library))
+            .min_by_key(|(key, rule)| (!is_standard_library(ecosystem, &rule.target_library), *key))
+            .map(|(_, rule)| rule)
+    }
+    
+    /// Whether the target project depends on a library; manifests may spell `sea_orm` as
+    /// `sea-orm`, `SQLAlchemy` as `sqlalchemy` and Go's `zap` as `go.uber.org/zap`
+    fn is_installed(&self, library: &str) -> bool {
+        let normalize = |name: &str| name.to_lowercase().replace('-', "_");
+        let library = normalize(library);
+        self.installed.iter().any(|name| normalize(name) == library || normalize(module_name(name)) == library)
     }
     
     /// Transform a UIR node with library patterns to target language
@@ -20,22 +163,36 @@ impl<'a> LibraryTransformer<'a> {
         target_lang: Language,
         target_ecosystem: Option<&str>,
     ) -> Result<UIRNode> {
-        let mut transformed_node = node.clone();
-        
-        // Check if this node has library annotations
-        if let Some(library_dep_value) = node.metadata.annotations.get("library_dependency") {
-            if let serde_json::Value::String(library_dep_str) = library_dep_value {
-                let library_dep: LibraryDependency = serde_json::from_str(library_dep_str)?;
-                self.transform_library_node(&mut transformed_node, &library_dep, &target_lang, target_ecosystem)?;
+        let mut transformed = node.clone();
+        let mut result = Ok(());
+        walk_mut(&mut transformed, &mut |node: &mut UIRNode| {
+            match self.transform_annotated_node(node, &target_lang, target_ecosystem) {
+                Ok(()) => Walk::Continue,
+                Err(error) => {
+                    result = Err(error);
+                    Walk::Stop
+                }
+            }
+        });
+        result?;
+        transformed.debug_validate();
+        Ok(transformed)
+    }
+    
+    /// Transform the node if it has library annotations
+    fn transform_annotated_node(
+        &self,
+        node: &mut UIRNode,
+        target_lang: &Language,
+        target_ecosystem: Option<&str>,
+    ) -> Result<()> {
+        if let Some(serde_json::Value::String(library_deps_str)) = node.metadata.annotations.get("library_dependencies") {
+            let library_deps: Vec<LibraryDependency> = serde_json::from_str(library_deps_str)?;
+            for library_dep in &library_deps {
+                self.transform_library_node(node, library_dep, target_lang, target_ecosystem)?;
             }
         }
-        
-        // Recursively transform children
-        for child in &mut transformed_node.children {
-            *child = self.transform(child, target_lang.clone(), target_ecosystem)?;
-        }
-        
-        Ok(transformed_node)
+        Ok(())
     }
     
     fn transform_library_node(
@@ -45,22 +202,189 @@ impl<'a> LibraryTransformer<'a> {
         target_lang: &Language,
         target_ecosystem: Option<&str>,
     ) -> Result<()> {
-        let default_ecosystem = self.get_default_ecosystem(target_lang);
-        let target_eco = target_ecosystem.unwrap_or(&default_ecosystem);
-        
         // Find the appropriate pattern for this library usage
         for usage in &library_dep.usage_patterns {
-            if let Some(pattern) = self.registry.get_pattern(&library_dep.name, &usage.pattern_name) {
-                if let Some(transform_rule) = pattern.transformations.get(target_eco) {
-                    self.apply_transform_rule(node, &pattern, transform_rule, usage)?;
-                } else {
-                    // No direct transformation available, create fallback
-                    self.create_fallback_implementation(node, &pattern, target_lang)?;
+            let Some(resolution) = self.resolve(&library_dep.name, usage, target_lang, target_ecosystem) else {
+                continue;
+            };
+            // Each usage on its own node, so that nested usages don't overwrite each other
+            let path = find_location(node, &usage.source_location).unwrap_or_default();
+            self.annotate(node_at(node, &path), &resolution, usage, target_lang)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Record how a usage translates in the annotations of `node`
+    fn annotate(&self, node: &mut UIRNode, resolution: &Resolution, usage: &LibraryUsage, target_lang: &Language) -> Result<()> {
+        match *resolution {
+            Resolution::Direct(pattern, transform_rule) => self.apply_transform_rule(node, pattern, transform_rule, usage)?,
+            Resolution::Equivalent(pattern, equivalent) => self.apply_equivalent(node, pattern, equivalent, usage)?,
+            // No transformation available, create fallback
+            Resolution::Manual(pattern) => self.create_fallback_implementation(node, pattern, target_lang)?,
+        }
+        mark_translation(node, resolution.kind())
+    }
+    
+    /// Replace each usage a rule of the target ecosystem covers with the UIR of the rule's code,
+    /// parsed by `parser`, so generators lay it out, import for it and nest it like the code
+    /// around it; `source` is the text `node` was parsed from. Usages without a rule, and those
+    /// whose code doesn't parse, get the annotations `transform` gives them instead.
+    pub fn rewrite(
+        &self,
+        node: &UIRNode,
+        source: &str,
+        dependencies: &[LibraryDependency],
+        parser: &dyn Parser,
+        target_ecosystem: Option<&str>,
+    ) -> Result<UIRNode> {
+        Ok(self.rewrite_with_coverage(node, source, dependencies, parser, target_ecosystem)?.0)
+    }
+    
+    /// `rewrite`, with the coverage of the dependencies' usages, of which only those whose calls
+    /// it replaced count as translated
+    pub fn rewrite_with_coverage(
+        &self,
+        node: &UIRNode,
+        source: &str,
+        dependencies: &[LibraryDependency],
+        parser: &dyn Parser,
+        target_ecosystem: Option<&str>,
+    ) -> Result<(UIRNode, CoverageReport)> {
+        let target_lang = parser.language();
+        let line_starts: Vec<usize> = std::iter::once(0).chain(source.match_indices('\n').map(|(index, _)| index + 1)).collect();
+        
+        // Outermost usages first: a usage in the arguments of another goes with them, as written
+        let mut usages: Vec<(&str, &LibraryUsage)> = dependencies.iter()
+            .flat_map(|dependency| dependency.usage_patterns.iter().map(move |usage| (dependency.name.as_str(), usage)))
+            .collect();
+        usages.sort_by_key(|(_, usage)| {
+            let (start, end) = location_range(&usage.source_location, &line_starts);
+            (start, Reverse(end))
+        });
+        
+        let mut rewritten = node.clone();
+        let mut replaced: Vec<(usize, usize)> = Vec::new();
+        let mut setups: HashSet<(String, String)> = HashSet::new();
+        let mut applied: Vec<(&str, &LibraryUsage)> = Vec::new();
+        for (library, usage) in usages {
+            let (start, end) = location_range(&usage.source_location, &line_starts);
+            if replaced.iter().any(|&(from, to)| from <= start && end <= to) {
+                continue;
+            }
+            let Some(mut path) = find_path(&rewritten, &line_starts, (start, end)).filter(|path| !path.is_empty()) else {
+                continue;
+            };
+            // An awaited call goes with its `await`: the rule's code awaits where the target does
+            if usage.parameters.contains_key("awaited") && path.len() > 1 && is_await(node_ref(&rewritten, &path[..path.len() - 1])) {
+                path.pop();
+            }
+            let Some(resolution) = self.resolve(library, usage, &target_lang, target_ecosystem) else {
+                continue;
+            };
+            let Resolution::Direct(pattern, rule) = resolution else {
+                self.annotate(node_at(&mut rewritten, &path), &resolution, usage, &target_lang)?;
+                continue;
+            };
+            let Some(mut statements) = parse_statements(parser, &template::render(&rule.template, &usage.parameters)?) else {
+                self.annotate(node_at(&mut rewritten, &path), &resolution, usage, &target_lang)?;
+                continue;
+            };
+            
+            // Code that assigns the usage's target, or that takes several statements, replaces
+            // the statement the usage is in; a single expression replaces the usage itself
+            let assigns = usage.parameters.contains_key("target") && template::placeholders(&rule.template).iter().any(|name| name == "target");
+            let statement = statement_depth(&rewritten, &path).map(|depth| path[..depth].to_vec());
+            let expression = if assigns || statements.len() > 1 { None } else { as_expression(&statements[0]) };
+            let block_site = statement.unwrap_or_else(|| path.clone());
+            let site = if expression.is_some() { &path } else { &block_site };
+            let replaced_node = node_at(&mut rewritten, site);
+            replaced.push(byte_range(replaced_node, &line_starts));
+            let id = replaced_node.id.clone();
+            if let Some(expression) = expression {
+                statements = vec![expression];
+            }
+            for (index, statement) in statements.iter_mut().enumerate() {
+                detach(statement, &format!("{}~{}", id, index));
+            }
+            self.annotate_rewrite(&mut statements[0], pattern, rule)?;
+            applied.push((library, usage));
+            let (parent, index) = site.split_at(site.len() - 1);
+            let count = statements.len();
+            node_at(&mut rewritten, parent).children.splice(index[0]..=index[0], statements);
+            
+            // Setup runs once per block, ahead of the first usage in it; cleanup after each usage
+            let (block, index) = block_site.split_at(block_site.len() - 1);
+            let index = index[0];
+            let count = if site == &block_site { count } else { 1 };
+            let mut before = Vec::new();
+            let mut after = Vec::new();
+            let mut unparsed = Vec::new();
+            let block_id = node_at(&mut rewritten, block).id.clone();
+            if let Some(setup) = &rule.setup_code {
+                let code = template::render(setup, &usage.parameters)?;
+                if setups.insert((block_id, code.clone())) {
+                    match parse_statements(parser, &code) {
+                        Some(setup) => before = setup,
+                        None => unparsed.push(("setup_code", code)),
+                    }
                 }
             }
+            if let Some(cleanup) = &rule.cleanup_code {
+                let code = template::render(cleanup, &usage.parameters)?;
+                match parse_statements(parser, &code) {
+                    Some(cleanup) => after = cleanup,
+                    None => unparsed.push(("cleanup_code", code)),
+                }
+            }
+            // What doesn't parse stays for review on the rule's code, as `transform` leaves it
+            let translated = node_at(&mut rewritten, site);
+            for (key, code) in unparsed {
+                translated.metadata.annotations.insert(key.to_string(), serde_json::Value::String(code));
+            }
+            for (offset, statement) in before.iter_mut().enumerate() {
+                detach(statement, &format!("{}~setup{}", id, offset));
+            }
+            for (offset, statement) in after.iter_mut().enumerate() {
+                detach(statement, &format!("{}~cleanup{}", id, offset));
+            }
+            let block = node_at(&mut rewritten, block);
+            block.children.splice(index + count..index + count, after);
+            block.children.splice(index..index, before);
         }
         
-        Ok(())
+        rewritten.debug_validate();
+        let coverage = self.coverage(dependencies, &target_lang, target_ecosystem).only_applied(|covered| {
+            applied.iter().any(|(library, usage)| {
+                covered.library == *library && covered.pattern == usage.pattern_name && covered.source_location == usage.source_location
+            })
+        });
+        Ok((rewritten, coverage))
+    }
+    
+    /// Record on the first node of a rule's code where it came from, and what it needs
+    fn annotate_rewrite(&self, node: &mut UIRNode, pattern: &LibraryPattern, rule: &TransformRule) -> Result<()> {
+        node.metadata.annotations.insert(
+            "transformed_from".to_string(),
+            serde_json::Value::String(format!("{}:{}", pattern.library, pattern.name)),
+        );
+        node.metadata.annotations.insert(
+            "transformed_to".to_string(),
+            serde_json::Value::String(format!("{}:{}", rule.target_library, rule.target_pattern)),
+        );
+        if !rule.imports.is_empty() {
+            node.metadata.annotations.insert(
+                "required_imports".to_string(),
+                serde_json::Value::String(serde_json::to_string(&rule.imports)?),
+            );
+        }
+        if !rule.caveats.is_empty() {
+            node.metadata.annotations.insert(
+                "caveats".to_string(),
+                serde_json::Value::String(serde_json::to_string(&rule.caveats)?),
+            );
+        }
+        mark_translation(node, SuggestionType::DirectTransform)
     }
     
     fn apply_transform_rule(
@@ -68,16 +392,11 @@ impl<'a> LibraryTransformer<'a> {
         node: &mut UIRNode,
         pattern: &LibraryPattern,
         rule: &TransformRule,
-        usage: &crate::LibraryUsage,
+        usage: &LibraryUsage,
     ) -> Result<()> {
-        // Apply template transformation
-        let mut transformed_code = rule.template.clone();
-        
-        // Replace parameter placeholders
-        for (param_name, param_value) in &usage.parameters {
-            let placeholder = format!("{{{{{}}}}}", param_name);
-            transformed_code = transformed_code.replace(&placeholder, param_value);
-        }
+        // Render the rule's code over the usage's parameters
+        let fill = |code: &str| template::render(code, &usage.parameters);
+        let transformed_code = fill(&rule.template)?;
         
         // Update node metadata with transformation info
         node.metadata.annotations.insert(
@@ -93,10 +412,11 @@ impl<'a> LibraryTransformer<'a> {
             serde_json::Value::String(transformed_code),
         );
         
-        // Add import requirements
+        // The call stays as written, so generators import nothing for it: the imports are
+        // only for whoever writes the generated code in
         if !rule.imports.is_empty() {
             node.metadata.annotations.insert(
-                "required_imports".to_string(),
+                "generated_code_imports".to_string(),
                 serde_json::Value::String(serde_json::to_string(&rule.imports)?),
             );
         }
@@ -105,20 +425,46 @@ impl<'a> LibraryTransformer<'a> {
         if let Some(setup) = &rule.setup_code {
             node.metadata.annotations.insert(
                 "setup_code".to_string(),
-                serde_json::Value::String(setup.clone()),
+                serde_json::Value::String(fill(setup)?),
             );
         }
         
         if let Some(cleanup) = &rule.cleanup_code {
             node.metadata.annotations.insert(
                 "cleanup_code".to_string(),
-                serde_json::Value::String(cleanup.clone()),
+                serde_json::Value::String(fill(cleanup)?),
+            );
+        }
+        
+        if !rule.caveats.is_empty() {
+            node.metadata.annotations.insert(
+                "caveats".to_string(),
+                serde_json::Value::String(serde_json::to_string(&rule.caveats)?),
             );
         }
         
         Ok(())
     }
     
+    /// Write a pattern of the target language meant for the same as the source, from its
+    /// signature, for review
+    fn apply_equivalent(&self, node: &mut UIRNode, pattern: &LibraryPattern, equivalent: &LibraryPattern, usage: &LibraryUsage) -> Result<()> {
+        let generated_code = template::render(&equivalent.signature, &usage.parameters)?;
+        node.metadata.annotations.insert(
+            "transformed_from".to_string(),
+            serde_json::Value::String(format!("{}:{}", pattern.library, pattern.name)),
+        );
+        node.metadata.annotations.insert(
+            "transformed_to".to_string(),
+            serde_json::Value::String(format!("{}:{}", equivalent.library, equivalent.name)),
+        );
+        node.metadata.annotations.insert(
+            "generated_code".to_string(),
+            serde_json::Value::String(generated_code),
+        );
+        Ok(())
+    }
+    
     fn create_fallback_implementation(
         &self,
         node: &mut UIRNode,
@@ -160,3 +506,171 @@ impl<'a> LibraryTransformer<'a> {
         }
     }
 }
+
+/// Whether a signature is code to write: a template whose placeholders all are parameters of
+/// the usage, where built-in signatures only name them
+fn renders(signature: &str, parameters: &HashMap<String, String>) -> bool {
+    let placeholders = template::placeholders(signature);
+    !placeholders.is_empty() && placeholders.iter().all(|name| parameters.contains_key(name))
+}
+
+/// Whether `library` names a language other than `target_lang`, as rules to a language's own
+/// functions do
+/// The last element of a Go module path, past any major version: `gin` of
+/// `github.com/gin-gonic/gin/v2`
+fn module_name(path: &str) -> &str {
+    let mut elements = path.rsplit('/');
+    let last = elements.next().unwrap_or(path);
+    let is_version = last.len() > 1 && last.starts_with('v') && last[1..].bytes().all(|b| b.is_ascii_digit());
+    match elements.next() {
+        Some(name) if is_version => name,
+        _ => last,
+    }
+}
+
+fn is_other_language(library: &str, target_lang: &Language) -> bool {
+    ["javascript", "python", "go", "rust", "csharp"].contains(&library) && language_ecosystem(target_lang) != Some(library)
+}
+
+/// Whether the usage passes a function written out in the source, like `x => x * 2`, which
+/// code in another language can't take as it is; one passed by name, like `double`, it can
+fn passes_foreign_code(pattern: &LibraryPattern, usage: &LibraryUsage, target_lang: &Language) -> bool {
+    if language_ecosystem(target_lang) == Some(pattern.ecosystem.as_str()) {
+        return false;
+    }
+    let is_name = |code: &str| {
+        let mut characters = code.trim().chars();
+        characters.next().is_some_and(|c| c.is_alphabetic() || c == '_') && characters.all(|c| c.is_alphanumeric() || c == '_')
+    };
+    pattern.parameters.iter()
+        .filter(|parameter| parameter.param_type == "function")
+        .filter_map(|parameter| usage.parameters.get(&parameter.name))
+        .any(|code| !is_name(code))
+}
+
+fn mark_translation(node: &mut UIRNode, kind: SuggestionType) -> Result<()> {
+    node.metadata.annotations.insert(
+        "translation".to_string(),
+        serde_json::to_value(kind)?,
+    );
+    node.metadata.annotations.insert(
+        "translation_confidence".to_string(),
+        serde_json::Value::String(kind.confidence().to_string()),
+    );
+    Ok(())
+}
+
+/// The child indexes that lead from `node` to the first node spanning `range` of the source
+fn find_path(node: &UIRNode, line_starts: &[usize], range: (usize, usize)) -> Option<Vec<usize>> {
+    if node.source_location.is_some() && byte_range(node, line_starts) == range {
+        return Some(Vec::new());
+    }
+    node.children.iter().enumerate().find_map(|(index, child)| {
+        find_path(child, line_starts, range).map(|mut path| {
+            path.insert(0, index);
+            path
+        })
+    })
+}
+
+/// The path to the outermost node at a location, by its lines and columns
+fn find_location(node: &UIRNode, location: &SourceLocation) -> Option<Vec<usize>> {
+    let span = |location: &SourceLocation| (location.start_line, location.start_column, location.end_line, location.end_column);
+    if node.source_location.as_ref().is_some_and(|own| span(own) == span(location)) {
+        return Some(Vec::new());
+    }
+    node.children.iter().enumerate().find_map(|(index, child)| {
+        find_location(child, location).map(|mut path| {
+            path.insert(0, index);
+            path
+        })
+    })
+}
+
+fn node_at<'n>(node: &'n mut UIRNode, path: &[usize]) -> &'n mut UIRNode {
+    path.iter().fold(node, |node, &index| &mut node.children[index])
+}
+
+fn node_ref<'n>(node: &'n UIRNode, path: &[usize]) -> &'n UIRNode {
+    path.iter().fold(node, |node, &index| &node.children[index])
+}
+
+fn is_await(node: &UIRNode) -> bool {
+    node.node_type == NodeType::Expression(ExpressionType::Await)
+}
+
+/// How far down `path` the innermost statement or declaration around its node is
+fn statement_depth(node: &UIRNode, path: &[usize]) -> Option<usize> {
+    let mut depth = None;
+    let mut current = node;
+    for (index, &child) in path.iter().enumerate() {
+        current = &current.children[child];
+        if matches!(current.node_type, NodeType::Statement(_) | NodeType::Variable | NodeType::Constant) {
+            depth = Some(index + 1);
+        }
+    }
+    depth
+}
+
+/// The statements of a rule's code, parsed; `None` when it doesn't parse cleanly
+fn parse_statements(parser: &dyn Parser, code: &str) -> Option<Vec<UIRNode>> {
+    // Rust and Go take only declarations at the top level: statements go in a function
+    let wrapped = match parser.language() {
+        Language::Rust => Some(format!("fn main() {{\n{}\n}}\n", code)),
+        Language::Go => Some(format!("package main\n\nfunc main() {{\n{}\n}}\n", code)),
+        _ => None,
+    };
+    let module = parser.parse(wrapped.as_deref().unwrap_or(code)).ok()?;
+    if has_tag(&module, &["partial_parse"]) {
+        return None;
+    }
+    let statements = match wrapped {
+        Some(_) => {
+            let function = module.children.into_iter().find(|child| child.node_type == NodeType::Function)?;
+            function.children.into_iter().find(|child| has_tag(child, &["block"]))?.children
+        }
+        None => module.children,
+    };
+    // A declaration stands in a statement, as source parsers put it, or a function takes it for a parameter
+    let statements: Vec<UIRNode> = statements.into_iter()
+        .filter(|child| !is_punctuation(child))
+        .map(|child| match child.node_type {
+            NodeType::Variable => UIRNode::new(child.id.clone(), NodeType::Statement(StatementType::Expression))
+                .with_metadata(child.metadata.clone())
+                .add_child(child),
+            _ => child,
+        })
+        .collect();
+    (!statements.is_empty()).then_some(statements)
+}
+
+/// The expression a statement of a single expression evaluates
+fn as_expression(statement: &UIRNode) -> Option<UIRNode> {
+    match statement.node_type {
+        NodeType::Expression(_) => Some(statement.clone()),
+        NodeType::Statement(StatementType::Expression) => {
+            let mut parts = statement.children.iter().filter(|child| !is_punctuation(child));
+            match (parts.next(), parts.next()) {
+                (Some(expression), None) if matches!(expression.node_type, NodeType::Expression(_)) => Some(expression.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Separators and newlines the parser keeps as nodes of their own
+fn is_punctuation(node: &UIRNode) -> bool {
+    let text = node.metadata.annotations.get("original_text").and_then(|text| text.as_str()).unwrap_or_default();
+    node.children.is_empty() && text.chars().all(|c| c.is_whitespace() || c.is_ascii_punctuation())
+}
+
+/// Ready parsed code to stand in the tree: ids under `seed`, and no positions, which would
+/// point into the code rather than the source
+fn detach(node: &mut UIRNode, seed: &str) {
+    node.assign_stable_ids(seed);
+    walk_mut(node, &mut |node: &mut UIRNode| {
+        node.source_location = None;
+        Walk::Continue
+    });
+}
@@ -0,0 +1,1501 @@
+use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, StatementType, LegacyPattern, Result,
+                   Parser as CoalesceParser, Operator, LiteralValue};
+use serde_json::Value;
+use crate::operators::mark_grouped;
+
+/// Event keywords that open an implicit processing block in reports
+const EVENT_BLOCKS: &[&str] = &[
+    "INITIALIZATION", "START-OF-SELECTION", "END-OF-SELECTION", "LOAD-OF-PROGRAM", "TOP-OF-PAGE",
+    "END-OF-PAGE", "AT SELECTION-SCREEN", "AT LINE-SELECTION", "AT USER-COMMAND",
+];
+
+/// Statements that start a new processing block and therefore end an event block
+const PROCESSING_BLOCKS: &[&str] = &["FORM", "FUNCTION", "CLASS", "MODULE", "INTERFACE"];
+
+/// Declarative statements that introduce data objects or types
+const DECLARATIONS: &[&str] = &[
+    "DATA", "CLASS-DATA", "STATICS", "CONSTANTS", "PARAMETERS", "PARAMETER", "SELECT-OPTIONS",
+    "TYPES", "FIELD-SYMBOLS", "TABLES", "RANGES",
+];
+
+/// Statements operating on internal tables rather than the database
+const TABLE_STATEMENTS: &[&str] = &["APPEND", "READ", "SORT", "COLLECT", "DESCRIBE", "LOOP"];
+
+/// Keywords separating parameter groups in FORM and METHODS signatures
+const PARAMETER_SECTIONS: &[&str] = &[
+    "USING", "CHANGING", "TABLES", "IMPORTING", "EXPORTING", "RETURNING", "RAISING", "EXCEPTIONS",
+];
+
+const COMPARISON_OPERATORS: &[&str] = &[
+    "=", "<>", "<", ">", "<=", ">=", "EQ", "NE", "LT", "GT", "LE", "GE",
+    "CO", "CN", "CA", "NA", "CS", "NS", "CP", "NP", "IN", "BETWEEN",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word,
+    Literal,
+    Colon,
+    Comma,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    line: u32,
+}
+
+impl Token {
+    fn is_word(&self, word: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(word)
+    }
+    
+    fn upper(&self) -> String {
+        self.text.to_ascii_uppercase()
+    }
+}
+
+/// A period-terminated ABAP statement, with chains (`DATA: a, b.`) already expanded
+#[derive(Debug, Clone)]
+struct Statement {
+    tokens: Vec<Token>,
+    line: u32,
+}
+
+/// ABAP parser for SAP modernization.
+///
+/// ABAP is statement-oriented: every statement ends with a period and colon
+/// chains share a common prefix. Reports, FORM routines, function modules and
+/// classes become modules, functions and classes; Open SQL statements are
+/// tagged as database access so the LAL can map them to a target data layer.
+pub struct AbapParser {
+}
+
+impl CoalesceParser for AbapParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::Abap
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        let tokens = self.tokenize(source);
+        let statements = self.split_statements(tokens);
+        let mut uir = self.parse_program(source, &statements);
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
+    }
+}
+
+impl AbapParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    fn tokenize(&self, source: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        
+        for (index, raw) in source.lines().enumerate() {
+            let line = index as u32 + 1;
+            // A '*' in the first column comments out the whole line
+            if raw.starts_with('*') {
+                continue;
+            }
+            
+            let chars: Vec<char> = raw.chars().collect();
+            let mut i = 0;
+            while i < chars.len() {
+                let c = chars[i];
+                let at_separator = |j: usize| chars.get(j).is_none_or(|n| n.is_whitespace());
+                
+                if c == '"' {
+                    // Rest of the line is a comment
+                    break;
+                } else if c.is_whitespace() {
+                    i += 1;
+                } else if c == '.' && at_separator(i + 1) {
+                    tokens.push(Token { kind: TokenKind::Word, text: ".".to_string(), line });
+                    i += 1;
+                } else if c == ':' && at_separator(i + 1) {
+                    tokens.push(Token { kind: TokenKind::Colon, text: ":".to_string(), line });
+                    i += 1;
+                } else if c == ',' {
+                    tokens.push(Token { kind: TokenKind::Comma, text: ",".to_string(), line });
+                    i += 1;
+                } else if matches!(c, '\'' | '`' | '|') {
+                    let mut text = String::from(c);
+                    i += 1;
+                    while i < chars.len() {
+                        text.push(chars[i]);
+                        if chars[i] == c {
+                            // Doubled quotes are an escaped quote
+                            if c != '|' && chars.get(i + 1) == Some(&c) {
+                                text.push(c);
+                                i += 2;
+                                continue;
+                            }
+                            i += 1;
+                            break;
+                        }
+                        i += 1;
+                    }
+                    tokens.push(Token { kind: TokenKind::Literal, text, line });
+                } else {
+                    let start = i;
+                    while i < chars.len() {
+                        let ch = chars[i];
+                        if ch.is_whitespace() || ch == '"' || ch == ','
+                            || (matches!(ch, '.' | ':') && at_separator(i + 1)) {
+                            break;
+                        }
+                        i += 1;
+                    }
+                    let text: String = chars[start..i].iter().collect();
+                    tokens.push(Token { kind: TokenKind::Word, text, line });
+                }
+            }
+        }
+        
+        tokens
+    }
+    
+    /// Split the token stream at periods and expand colon chains
+    fn split_statements(&self, tokens: Vec<Token>) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        let mut current: Vec<Token> = Vec::new();
+        
+        for token in tokens {
+            if token.kind == TokenKind::Word && token.text == "." {
+                self.expand_chain(std::mem::take(&mut current), &mut statements);
+            } else {
+                current.push(token);
+            }
+        }
+        self.expand_chain(current, &mut statements);
+        
+        statements
+    }
+    
+    fn expand_chain(&self, tokens: Vec<Token>, statements: &mut Vec<Statement>) {
+        if tokens.is_empty() {
+            return;
+        }
+        
+        let Some(colon) = tokens.iter().position(|t| t.kind == TokenKind::Colon) else {
+            let tokens: Vec<Token> = tokens.into_iter().filter(|t| t.kind != TokenKind::Comma).collect();
+            let line = tokens.first().map(|t| t.line).unwrap_or(0);
+            if !tokens.is_empty() {
+                statements.push(Statement { tokens, line });
+            }
+            return;
+        };
+        
+        let prefix = &tokens[..colon];
+        for part in tokens[colon + 1..].split(|t| t.kind == TokenKind::Comma) {
+            if part.is_empty() {
+                continue;
+            }
+            let mut chained: Vec<Token> = prefix.to_vec();
+            chained.extend(part.iter().filter(|t| t.kind != TokenKind::Colon).cloned());
+            let line = part[0].line;
+            statements.push(Statement { tokens: chained, line });
+        }
+    }
+    
+    fn parse_program(&self, source: &str, statements: &[Statement]) -> UIRNode {
+        let mut root = self.create_node(NodeType::Module, Some("abap_program".to_string()), 1, "source_file");
+        root.source_location = Some(SourceLocation {
+            file: String::new(),
+            start_line: 1,
+            end_line: source.lines().count() as u32,
+            start_column: 0,
+            end_column: source.len() as u32,
+        });
+        
+        let mut pos = 0;
+        while pos < statements.len() {
+            let statement = &statements[pos];
+            let keyword = self.keyword(statement);
+            
+            match keyword.as_str() {
+                "REPORT" | "PROGRAM" | "FUNCTION-POOL" | "CLASS-POOL" => {
+                    if let Some(name) = statement.tokens.get(1) {
+                        root.name = Some(name.text.clone());
+                    }
+                    root.metadata.annotations.insert("program_type".to_string(), Value::String(keyword.to_ascii_lowercase()));
+                    pos += 1;
+                }
+                _ if EVENT_BLOCKS.contains(&keyword.as_str()) => {
+                    pos += 1;
+                    let mut block = self.create_node(NodeType::Function, Some(keyword.clone()), statement.line, "event_block");
+                    let terminators: Vec<&str> = EVENT_BLOCKS.iter().chain(PROCESSING_BLOCKS).copied().collect();
+                    block.children = self.parse_block(statements, &mut pos, &terminators);
+                    root.children.push(block);
+                }
+                _ => {
+                    let node = self.parse_statement(statements, &mut pos);
+                    self.push_or_merge(&mut root.children, node);
+                }
+            }
+        }
+        
+        root
+    }
+    
+    /// Statements up to (not including) one whose keyword is in `terminators`
+    fn parse_block(&self, statements: &[Statement], pos: &mut usize, terminators: &[&str]) -> Vec<UIRNode> {
+        let mut nodes = Vec::new();
+        
+        while let Some(statement) = statements.get(*pos) {
+            if terminators.contains(&self.keyword(statement).as_str()) {
+                break;
+            }
+            let node = self.parse_statement(statements, pos);
+            self.push_or_merge(&mut nodes, node);
+        }
+        
+        nodes
+    }
+    
+    /// Consume the closing statement of a block if it is present
+    fn expect_end(&self, statements: &[Statement], pos: &mut usize, keyword: &str) {
+        if statements.get(*pos).is_some_and(|s| self.keyword(s) == keyword) {
+            *pos += 1;
+        }
+    }
+    
+    /// Class implementations are folded into the matching definition
+    fn push_or_merge(&self, nodes: &mut Vec<UIRNode>, node: UIRNode) {
+        let is_implementation = node.metadata.semantic_tags.iter().any(|t| t == "class_implementation");
+        let definition = nodes.iter_mut().find(|n| {
+            n.node_type == NodeType::Class && n.name == node.name
+                && n.metadata.semantic_tags.iter().any(|t| t == "class_definition")
+        });
+        
+        match definition {
+            Some(definition) if is_implementation => {
+                definition.metadata.semantic_tags.push("class_implementation".to_string());
+                for method in node.children {
+                    let declaration = definition.children.iter_mut()
+                        .find(|d| d.node_type == NodeType::Function && d.name == method.name);
+                    match declaration {
+                        Some(declaration) => {
+                            declaration.children.extend(method.children);
+                            declaration.source_location = method.source_location;
+                        }
+                        None => definition.children.push(method),
+                    }
+                }
+            }
+            _ => nodes.push(node),
+        }
+    }
+    
+    fn parse_statement(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let tokens = &statement.tokens;
+        let line = statement.line;
+        let keyword = self.keyword(statement);
+        
+        let mut node = match keyword.as_str() {
+            "FORM" => self.parse_form(statements, pos),
+            "FUNCTION" => {
+                *pos += 1;
+                let name = tokens.get(1).map(|t| self.unquote(&t.text)).unwrap_or_default();
+                let mut function = self.create_node(NodeType::Function, Some(name), line, "function_module");
+                function.children = self.parse_block(statements, pos, &["ENDFUNCTION"]);
+                self.expect_end(statements, pos, "ENDFUNCTION");
+                function
+            }
+            "CLASS" => self.parse_class(statements, pos),
+            "INTERFACE" => {
+                *pos += 1;
+                let name = tokens.get(1).map(|t| t.text.clone());
+                let mut interface = self.create_node(NodeType::Interface, name, line, "interface");
+                if !tokens.iter().any(|t| t.is_word("DEFERRED") || t.is_word("LOAD")) {
+                    interface.children = self.parse_block(statements, pos, &["ENDINTERFACE"]);
+                    self.expect_end(statements, pos, "ENDINTERFACE");
+                }
+                interface
+            }
+            "METHOD" => {
+                *pos += 1;
+                let name = tokens.get(1).map(|t| t.text.clone());
+                let mut method = self.create_node(NodeType::Function, name, line, "method");
+                method.children = self.parse_block(statements, pos, &["ENDMETHOD"]);
+                self.expect_end(statements, pos, "ENDMETHOD");
+                method
+            }
+            "METHODS" | "CLASS-METHODS" => {
+                *pos += 1;
+                let name = tokens.get(1).map(|t| t.text.clone());
+                let mut method = self.create_node(NodeType::Function, name, line, "method_declaration");
+                method.children = self.parse_parameters(&tokens[2.min(tokens.len())..], line);
+                if keyword == "CLASS-METHODS" {
+                    method.metadata.semantic_tags.push("static".to_string());
+                }
+                method
+            }
+            "MODULE" => {
+                *pos += 1;
+                let name = tokens.get(1).map(|t| t.text.clone());
+                let mut module = self.create_node(NodeType::Function, name, line, "dialog_module");
+                if let Some(kind) = tokens.get(2) {
+                    module.metadata.annotations.insert("module_type".to_string(), Value::String(kind.text.to_ascii_lowercase()));
+                }
+                module.children = self.parse_block(statements, pos, &["ENDMODULE"]);
+                self.expect_end(statements, pos, "ENDMODULE");
+                module
+            }
+            "IF" => self.parse_if(statements, pos),
+            "CASE" => self.parse_case(statements, pos),
+            "LOOP" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)), Some("loop".to_string()), line, "loop_at");
+                self.annotate_after(&mut node, tokens, "AT", "table");
+                self.annotate_after(&mut node, tokens, "INTO", "target");
+                self.annotate_after(&mut node, tokens, "ASSIGNING", "target");
+                if let Some(where_pos) = tokens.iter().position(|t| t.is_word("WHERE")) {
+                    node.children.push(self.parse_expression(&tokens[where_pos + 1..], line));
+                }
+                node.metadata.semantic_tags.push("internal_table".to_string());
+                node.children.extend(self.parse_block(statements, pos, &["ENDLOOP"]));
+                self.expect_end(statements, pos, "ENDLOOP");
+                node
+            }
+            "DO" => {
+                *pos += 1;
+                let times = tokens.iter().position(|t| t.is_word("TIMES")).and_then(|i| tokens.get(i - 1));
+                let mut node = match times {
+                    Some(count) => {
+                        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For)), Some("do".to_string()), line, "do_times");
+                        node.metadata.annotations.insert("times".to_string(), Value::String(count.text.clone()));
+                        node
+                    }
+                    None => self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)), Some("do".to_string()), line, "infinite_loop"),
+                };
+                node.children = self.parse_block(statements, pos, &["ENDDO"]);
+                self.expect_end(statements, pos, "ENDDO");
+                node
+            }
+            "WHILE" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)), Some("while".to_string()), line, "while");
+                node.children.push(self.parse_expression(&tokens[1..], line));
+                node.children.extend(self.parse_block(statements, pos, &["ENDWHILE"]));
+                self.expect_end(statements, pos, "ENDWHILE");
+                node
+            }
+            "TRY" => self.parse_try(statements, pos),
+            "SELECT" => self.parse_select(statements, pos),
+            "INSERT" | "UPDATE" | "MODIFY" | "DELETE" => {
+                *pos += 1;
+                if self.is_database_write(&keyword, tokens) {
+                    self.create_sql_node(&keyword, tokens, line)
+                } else {
+                    self.create_table_operation(&keyword, tokens, line)
+                }
+            }
+            "COMMIT" | "ROLLBACK" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(keyword.clone()), line, "database_access");
+                node.metadata.semantic_tags.push("transaction".to_string());
+                node
+            }
+            "EXEC" => {
+                // Native SQL runs verbatim until ENDEXEC
+                let mut sql = Vec::new();
+                *pos += 1;
+                while let Some(s) = statements.get(*pos) {
+                    if self.keyword(s) == "ENDEXEC" {
+                        *pos += 1;
+                        break;
+                    }
+                    sql.push(self.join_tokens(&s.tokens));
+                    *pos += 1;
+                }
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some("native_sql".to_string()), line, "database_access");
+                let sql = sql.join("; ");
+                node.metadata.annotations.insert("sql".to_string(), Value::String(sql.clone()));
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "native_sql",
+                    &sql,
+                    "Database-specific SQL; port to the target's query layer by hand",
+                    true,
+                ));
+                node
+            }
+            "PERFORM" => {
+                *pos += 1;
+                let name = tokens.get(1).map(|t| t.text.clone()).unwrap_or_default();
+                let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(name.clone()), line, "perform");
+                call.children = self.parse_arguments(&tokens[2.min(tokens.len())..], line);
+                call.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "perform",
+                    &format!("PERFORM {}", name),
+                    "Replace with a direct method or function call",
+                    false,
+                ));
+                call
+            }
+            "CALL" => {
+                *pos += 1;
+                self.parse_call(tokens, line)
+            }
+            _ if DECLARATIONS.contains(&keyword.as_str()) => self.parse_declaration(statements, pos),
+            "MOVE" | "MOVE-CORRESPONDING" => {
+                *pos += 1;
+                let to = tokens.iter().position(|t| t.is_word("TO")).unwrap_or(tokens.len());
+                let source_expr = self.parse_expression(&tokens[1..to], line);
+                let target = tokens.get(to + 1).map(|t| self.create_operand(t))
+                    .unwrap_or_else(|| self.create_node(NodeType::Expression(ExpressionType::Variable), None, line, "identifier"));
+                let mut node = self.build_assignment(target, source_expr, line, &keyword.to_ascii_lowercase());
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "move",
+                    &self.join_tokens(tokens),
+                    "Use a plain assignment",
+                    false,
+                ));
+                node
+            }
+            "COMPUTE" => {
+                *pos += 1;
+                self.parse_assignment(&tokens[1..], line)
+                    .unwrap_or_else(|| self.generic_statement(&keyword, tokens, line))
+            }
+            "CLEAR" | "REFRESH" | "FREE" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::Assignment), Some(keyword.to_ascii_lowercase()), line, "reset");
+                node.children = tokens[1..].iter()
+                    .filter(|t| t.kind == TokenKind::Word)
+                    .map(|t| self.create_operand(t))
+                    .collect();
+                node
+            }
+            "WRITE" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some("WRITE".to_string()), line, "io_statement");
+                node.children = tokens[1..].iter()
+                    .filter(|t| !t.text.starts_with('/'))
+                    .map(|t| self.create_operand(t))
+                    .collect();
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "list_output",
+                    &self.join_tokens(tokens),
+                    "Classic list output; emit through a report or UI API instead",
+                    false,
+                ));
+                node
+            }
+            "MESSAGE" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some("MESSAGE".to_string()), line, "message");
+                node.children = tokens.get(1).map(|t| self.create_operand(t)).into_iter().collect();
+                node
+            }
+            "RETURN" => {
+                *pos += 1;
+                self.create_node(NodeType::Statement(StatementType::Return), None, line, "return")
+            }
+            "EXIT" => {
+                *pos += 1;
+                self.create_node(NodeType::Statement(StatementType::Break), None, line, "exit")
+            }
+            "CONTINUE" => {
+                *pos += 1;
+                self.create_node(NodeType::Statement(StatementType::Continue), None, line, "continue")
+            }
+            "CHECK" => {
+                // CHECK leaves the loop pass or processing block when the condition is false
+                *pos += 1;
+                let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("check".to_string()), line, "check");
+                node.children.push(self.parse_expression(&tokens[1..], line));
+                node.children.push(self.create_node(NodeType::Statement(StatementType::Continue), None, line, "check_exit"));
+                node
+            }
+            "RAISE" => {
+                *pos += 1;
+                let name = tokens.iter().position(|t| t.is_word("TYPE"))
+                    .and_then(|i| tokens.get(i + 1))
+                    .or_else(|| tokens.get(1))
+                    .map(|t| t.text.clone());
+                self.create_node(NodeType::Statement(StatementType::Throw), name, line, "raise")
+            }
+            _ if TABLE_STATEMENTS.contains(&keyword.as_str()) => {
+                *pos += 1;
+                self.create_table_operation(&keyword, tokens, line)
+            }
+            _ => {
+                *pos += 1;
+                self.parse_assignment(tokens, line)
+                    .or_else(|| self.parse_method_call_statement(tokens, line))
+                    .unwrap_or_else(|| self.generic_statement(&keyword, tokens, line))
+            }
+        };
+        
+        node.metadata.annotations.entry("original_text".to_string())
+            .or_insert_with(|| Value::String(self.join_tokens(tokens)));
+        node
+    }
+    
+    fn parse_form(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let tokens = &statement.tokens;
+        *pos += 1;
+        
+        let name = tokens.get(1).map(|t| t.text.clone()).unwrap_or_default();
+        let mut form = self.create_node(NodeType::Function, Some(name.clone()), statement.line, "form");
+        form.children = self.parse_parameters(&tokens[2.min(tokens.len())..], statement.line);
+        form.metadata.legacy_patterns.push(self.legacy_pattern(
+            "form_routine",
+            &format!("FORM {}", name),
+            "Subroutines are obsolete; convert to a method or function",
+            false,
+        ));
+        
+        form.children.extend(self.parse_block(statements, pos, &["ENDFORM"]));
+        self.expect_end(statements, pos, "ENDFORM");
+        form
+    }
+    
+    fn parse_class(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let tokens = &statement.tokens;
+        *pos += 1;
+        
+        let name = tokens.get(1).map(|t| t.text.clone());
+        let is_implementation = tokens.iter().any(|t| t.is_word("IMPLEMENTATION"));
+        let tag = if is_implementation { "class_implementation" } else { "class_definition" };
+        let mut class = self.create_node(NodeType::Class, name, statement.line, tag);
+        
+        if let Some(superclass) = tokens.iter().position(|t| t.is_word("FROM")).and_then(|i| tokens.get(i + 1)) {
+            class.metadata.dependencies.push(superclass.text.clone());
+            class.metadata.annotations.insert("inherits".to_string(), Value::String(superclass.text.clone()));
+        }
+        if tokens.iter().any(|t| t.is_word("ABSTRACT")) {
+            class.metadata.semantic_tags.push("abstract".to_string());
+        }
+        
+        // Forward declarations have no body
+        if tokens.iter().any(|t| t.is_word("DEFERRED") || t.is_word("LOAD")) {
+            return class;
+        }
+        
+        // Visibility sections apply to the components that follow them
+        let mut visibility: Option<String> = None;
+        while let Some(member) = statements.get(*pos) {
+            let keyword = self.keyword(member);
+            if keyword == "ENDCLASS" {
+                *pos += 1;
+                break;
+            }
+            if member.tokens.get(1).is_some_and(|t| t.is_word("SECTION")) {
+                visibility = Some(keyword.to_ascii_lowercase());
+                *pos += 1;
+                continue;
+            }
+            
+            let mut node = self.parse_statement(statements, pos);
+            if let Some(visibility) = &visibility {
+                node.metadata.annotations.insert("visibility".to_string(), Value::String(visibility.clone()));
+            }
+            class.children.push(node);
+        }
+        
+        class
+    }
+    
+    fn parse_if(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        *pos += 1;
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("if_statement".to_string()), line, "if");
+        node.children.push(self.parse_expression(&statement.tokens[1..], line));
+        
+        let mut then_block = self.create_node(NodeType::Statement(StatementType::Expression), Some("then".to_string()), line, "block");
+        then_block.children = self.parse_block(statements, pos, &["ELSEIF", "ELSE", "ENDIF"]);
+        node.children.push(then_block);
+        
+        while let Some(branch) = statements.get(*pos).filter(|s| self.keyword(s) == "ELSEIF") {
+            *pos += 1;
+            let mut elseif = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("elseif".to_string()), branch.line, "elseif");
+            elseif.children.push(self.parse_expression(&branch.tokens[1..], branch.line));
+            elseif.children.extend(self.parse_block(statements, pos, &["ELSEIF", "ELSE", "ENDIF"]));
+            node.children.push(elseif);
+        }
+        
+        if let Some(branch) = statements.get(*pos).filter(|s| self.keyword(s) == "ELSE") {
+            *pos += 1;
+            let mut else_block = self.create_node(NodeType::Statement(StatementType::Expression), Some("else".to_string()), branch.line, "block");
+            else_block.children = self.parse_block(statements, pos, &["ENDIF"]);
+            node.children.push(else_block);
+        }
+        
+        self.expect_end(statements, pos, "ENDIF");
+        node
+    }
+    
+    fn parse_case(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        *pos += 1;
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Switch), Some("case".to_string()), line, "case");
+        node.children.push(self.parse_expression(&statement.tokens[1..], line));
+        
+        // Statements before the first WHEN are unreachable, skip them
+        self.parse_block(statements, pos, &["WHEN", "ENDCASE"]);
+        
+        while let Some(branch) = statements.get(*pos).filter(|s| self.keyword(s) == "WHEN") {
+            *pos += 1;
+            let values = &branch.tokens[1..];
+            let is_others = values.len() == 1 && values[0].is_word("OTHERS");
+            let mut case = self.create_node(
+                NodeType::ControlFlow(ControlFlowType::Conditional),
+                Some(if is_others { "when_others".to_string() } else { "when".to_string() }),
+                branch.line,
+                "when",
+            );
+            if !is_others {
+                case.children.extend(
+                    values.iter()
+                        .filter(|t| !t.is_word("OR"))
+                        .map(|t| self.create_operand(t)),
+                );
+            }
+            case.children.extend(self.parse_block(statements, pos, &["WHEN", "ENDCASE"]));
+            node.children.push(case);
+        }
+        
+        self.expect_end(statements, pos, "ENDCASE");
+        node
+    }
+    
+    fn parse_try(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let line = statements[*pos].line;
+        *pos += 1;
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Try), Some("try".to_string()), line, "try");
+        let mut body = self.create_node(NodeType::Statement(StatementType::Expression), Some("try_block".to_string()), line, "block");
+        body.children = self.parse_block(statements, pos, &["CATCH", "CLEANUP", "ENDTRY"]);
+        node.children.push(body);
+        
+        while let Some(branch) = statements.get(*pos).filter(|s| matches!(self.keyword(s).as_str(), "CATCH" | "CLEANUP")) {
+            *pos += 1;
+            let keyword = self.keyword(branch);
+            let mut handler = self.create_node(
+                NodeType::ControlFlow(ControlFlowType::Conditional),
+                Some(keyword.to_ascii_lowercase()),
+                branch.line,
+                &keyword.to_ascii_lowercase(),
+            );
+            
+            if keyword == "CATCH" {
+                let into = branch.tokens.iter().position(|t| t.is_word("INTO")).unwrap_or(branch.tokens.len());
+                let exceptions: Vec<Value> = branch.tokens[1..into].iter()
+                    .filter(|t| !t.is_word("BEFORE") && !t.is_word("UNWIND"))
+                    .map(|t| Value::String(t.text.clone()))
+                    .collect();
+                handler.metadata.annotations.insert("exceptions".to_string(), Value::Array(exceptions));
+                if let Some(target) = branch.tokens.get(into + 1) {
+                    handler.metadata.annotations.insert("target".to_string(), Value::String(target.text.clone()));
+                }
+            }
+            
+            handler.children = self.parse_block(statements, pos, &["CATCH", "CLEANUP", "ENDTRY"]);
+            node.children.push(handler);
+        }
+        
+        self.expect_end(statements, pos, "ENDTRY");
+        node
+    }
+    
+    fn parse_select(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let tokens = &statement.tokens;
+        let line = statement.line;
+        *pos += 1;
+        
+        let select = self.create_sql_node("SELECT", tokens, line);
+        
+        // SELECT ... ENDSELECT fetches row by row in a loop
+        let single = tokens.get(1).is_some_and(|t| t.is_word("SINGLE"));
+        let into_table = tokens.windows(2).any(|w| (w[0].is_word("INTO") || w[0].is_word("APPENDING"))
+            && (w[1].is_word("TABLE") || w[1].is_word("CORRESPONDING")));
+        let has_end = statements[*pos..].iter().any(|s| self.keyword(s) == "ENDSELECT");
+        if single || into_table || !has_end {
+            return select;
+        }
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)), Some("select_loop".to_string()), line, "database_access");
+        node.metadata.legacy_patterns.push(self.legacy_pattern(
+            "select_endselect",
+            &self.join_tokens(tokens),
+            "Fetch into an internal table with one query and loop over the result",
+            false,
+        ));
+        node.children.push(select);
+        node.children.extend(self.parse_block(statements, pos, &["ENDSELECT"]));
+        self.expect_end(statements, pos, "ENDSELECT");
+        node
+    }
+    
+    /// Open SQL statement with its table, field list, filter and target
+    fn create_sql_node(&self, operation: &str, tokens: &[Token], line: u32) -> UIRNode {
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(operation.to_string()), line, "database_access");
+        node.metadata.semantic_tags.push(format!("sql_{}", operation.to_ascii_lowercase()));
+        node.metadata.annotations.insert("sql_operation".to_string(), Value::String(operation.to_ascii_lowercase()));
+        
+        let table = match operation {
+            "SELECT" | "DELETE" => self.word_after(tokens, "FROM"),
+            "INSERT" => self.word_after(tokens, "INTO").or_else(|| tokens.get(1).map(|t| t.text.clone())),
+            _ => tokens.get(1).map(|t| t.text.clone()),
+        };
+        if let Some(table) = table {
+            node.metadata.dependencies.push(table.clone());
+            node.metadata.annotations.insert("table".to_string(), Value::String(table));
+        }
+        
+        if operation == "SELECT" {
+            let start = if tokens.get(1).is_some_and(|t| t.is_word("SINGLE")) { 2 } else { 1 };
+            let from = tokens.iter().position(|t| t.is_word("FROM")).unwrap_or(tokens.len());
+            let fields: Vec<Value> = tokens[start.min(from)..from].iter()
+                .map(|t| Value::String(t.text.clone()))
+                .collect();
+            node.metadata.annotations.insert("fields".to_string(), Value::Array(fields));
+            if start == 2 {
+                node.metadata.annotations.insert("single".to_string(), Value::Bool(true));
+            }
+            let target = tokens.iter().position(|t| t.is_word("INTO") || t.is_word("APPENDING"))
+                .and_then(|i| tokens[i + 1..].iter().find(|t| !t.is_word("TABLE") && !t.is_word("CORRESPONDING")
+                    && !t.is_word("FIELDS") && !t.is_word("OF")));
+            if let Some(target) = target {
+                node.metadata.annotations.insert("into".to_string(), Value::String(target.text.trim_start_matches('@').to_string()));
+            }
+        }
+        
+        if let Some(where_pos) = tokens.iter().position(|t| t.is_word("WHERE")) {
+            let end = tokens[where_pos..].iter()
+                .position(|t| t.is_word("INTO") || t.is_word("ORDER") || t.is_word("GROUP"))
+                .map(|i| where_pos + i)
+                .unwrap_or(tokens.len());
+            let condition = &tokens[where_pos + 1..end];
+            node.metadata.annotations.insert("where".to_string(), Value::String(self.join_tokens(condition)));
+            node.children.push(self.parse_expression(condition, line));
+        }
+        
+        node
+    }
+    
+    /// INSERT/UPDATE/MODIFY/DELETE address either a database table or an internal table
+    fn is_database_write(&self, keyword: &str, tokens: &[Token]) -> bool {
+        let has = |word: &str| tokens.iter().any(|t| t.is_word(word));
+        match keyword {
+            "UPDATE" => true,
+            "DELETE" => tokens.get(1).is_some_and(|t| t.is_word("FROM")) && !has("TABLE"),
+            "INSERT" => !has("TABLE") || tokens.get(1).is_some_and(|t| t.is_word("INTO")) && !has("INDEX"),
+            "MODIFY" => !has("TABLE") && !has("INDEX") && !has("TRANSPORTING"),
+            _ => false,
+        }
+    }
+    
+    fn create_table_operation(&self, keyword: &str, tokens: &[Token], line: u32) -> UIRNode {
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(keyword.to_string()), line, "internal_table");
+        node.children = tokens[1..].iter()
+            .filter(|t| t.kind == TokenKind::Literal || !self.is_keyword_operand(&t.upper()))
+            .map(|t| self.create_operand(t))
+            .collect();
+        node
+    }
+    
+    fn parse_call(&self, tokens: &[Token], line: u32) -> UIRNode {
+        let kind = tokens.get(1).map(|t| t.upper()).unwrap_or_default();
+        let name = tokens.get(2).map(|t| self.unquote(&t.text)).unwrap_or_default();
+        
+        let tag = match kind.as_str() {
+            "FUNCTION" => "function_module_call",
+            "METHOD" => "method_call",
+            "TRANSACTION" => "transaction_call",
+            "SCREEN" => "screen_call",
+            _ => "call",
+        };
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(name.clone()), line, tag);
+        node.children = self.parse_arguments(&tokens[3.min(tokens.len())..], line);
+        
+        if kind == "FUNCTION" {
+            node.metadata.dependencies.push(name.clone());
+            if let Some(destination) = self.word_after(tokens, "DESTINATION") {
+                node.metadata.semantic_tags.push("remote_function_call".to_string());
+                node.metadata.annotations.insert("destination".to_string(), Value::String(self.unquote(&destination)));
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "rfc",
+                    &format!("CALL FUNCTION {} DESTINATION", name),
+                    "Remote function call; expose it as a service endpoint",
+                    false,
+                ));
+            }
+        }
+        if kind == "METHOD" {
+            node.metadata.legacy_patterns.push(self.legacy_pattern(
+                "call_method",
+                &format!("CALL METHOD {}", name),
+                "Use functional method call syntax",
+                false,
+            ));
+        }
+        
+        node
+    }
+    
+    /// `EXPORTING a = x b = y` style argument lists; bare operands pass positionally
+    fn parse_arguments(&self, tokens: &[Token], line: u32) -> Vec<UIRNode> {
+        let mut arguments = Vec::new();
+        let mut direction: Option<String> = None;
+        let mut i = 0;
+        
+        while i < tokens.len() {
+            let token = &tokens[i];
+            let upper = token.upper();
+            if token.kind == TokenKind::Word && (PARAMETER_SECTIONS.contains(&upper.as_str()) || upper == "DESTINATION") {
+                direction = Some(upper.to_ascii_lowercase());
+                if upper == "DESTINATION" {
+                    i += 1;
+                }
+                i += 1;
+                continue;
+            }
+            if token.kind == TokenKind::Word && tokens.get(i + 1).is_some_and(|t| t.text == "=") {
+                if let Some(value) = tokens.get(i + 2) {
+                    let mut argument = self.create_operand(value);
+                    argument.metadata.annotations.insert("argument_name".to_string(), Value::String(token.text.clone()));
+                    if let Some(direction) = &direction {
+                        argument.metadata.annotations.insert("direction".to_string(), Value::String(direction.clone()));
+                    }
+                    arguments.push(argument);
+                }
+                i += 3;
+                continue;
+            }
+            if token.text != "(" && token.text != ")" {
+                let mut argument = self.create_operand(token);
+                if let Some(direction) = &direction {
+                    argument.metadata.annotations.insert("direction".to_string(), Value::String(direction.clone()));
+                }
+                argument.metadata.annotations.insert("original_text".to_string(), Value::String(token.text.clone()));
+                arguments.push(argument);
+            }
+            let _ = line;
+            i += 1;
+        }
+        
+        arguments
+    }
+    
+    /// FORM/METHODS signature: `USING p1 TYPE i VALUE(p2) TYPE string CHANGING ct_tab TYPE tt`
+    fn parse_parameters(&self, tokens: &[Token], line: u32) -> Vec<UIRNode> {
+        let mut parameters: Vec<UIRNode> = Vec::new();
+        let mut section: Option<String> = None;
+        let mut i = 0;
+        
+        while i < tokens.len() {
+            let upper = tokens[i].upper();
+            if PARAMETER_SECTIONS.contains(&upper.as_str()) {
+                section = Some(upper.to_ascii_lowercase());
+                i += 1;
+                continue;
+            }
+            if section.as_deref() == Some("raising") || section.as_deref() == Some("exceptions") {
+                i += 1;
+                continue;
+            }
+            
+            match upper.as_str() {
+                "TYPE" | "LIKE" => {
+                    let (type_name, consumed, is_table) = self.parse_type_spec(&tokens[i + 1..]);
+                    if let Some(param) = parameters.last_mut() {
+                        if let Some(type_name) = type_name {
+                            param.metadata.annotations.insert("type".to_string(), Value::String(type_name));
+                        }
+                        if is_table {
+                            param.metadata.semantic_tags.push("internal_table".to_string());
+                        }
+                    }
+                    i += 1 + consumed;
+                }
+                "OPTIONAL" => {
+                    if let Some(param) = parameters.last_mut() {
+                        param.metadata.annotations.insert("optional".to_string(), Value::Bool(true));
+                    }
+                    i += 1;
+                }
+                "DEFAULT" => {
+                    if let (Some(param), Some(value)) = (parameters.last_mut(), tokens.get(i + 1)) {
+                        param.metadata.annotations.insert("default_value".to_string(), Value::String(value.text.clone()));
+                    }
+                    i += 2;
+                }
+                _ => {
+                    // VALUE(x) passes by value, REFERENCE(x) by reference
+                    let text = &tokens[i].text;
+                    let (name, by_value) = match text.split_once('(') {
+                        Some((mode, rest)) => (rest.trim_end_matches(')').to_string(), mode.eq_ignore_ascii_case("VALUE")),
+                        None => (text.clone(), false),
+                    };
+                    let mut param = self.create_node(NodeType::Variable, Some(name), line, "parameter");
+                    if let Some(section) = &section {
+                        param.metadata.annotations.insert("passing".to_string(), Value::String(section.clone()));
+                    }
+                    if by_value {
+                        param.metadata.annotations.insert("by_value".to_string(), Value::Bool(true));
+                    }
+                    parameters.push(param);
+                    i += 1;
+                }
+            }
+        }
+        
+        parameters
+    }
+    
+    /// Type after TYPE/LIKE, returning (type name, tokens consumed, is internal table)
+    fn parse_type_spec(&self, tokens: &[Token]) -> (Option<String>, usize, bool) {
+        let mut i = 0;
+        let mut is_table = false;
+        while let Some(token) = tokens.get(i) {
+            match token.upper().as_str() {
+                "REF" | "TO" | "LINE" | "OF" | "RANGE" => i += 1,
+                "STANDARD" | "SORTED" | "HASHED" | "TABLE" => {
+                    is_table = true;
+                    i += 1;
+                }
+                _ => return (Some(token.text.clone()), i + 1, is_table),
+            }
+        }
+        (None, i, is_table)
+    }
+    
+    fn parse_declaration(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let tokens = &statement.tokens;
+        let line = statement.line;
+        let keyword = self.keyword(statement);
+        *pos += 1;
+        
+        // DATA: BEGIN OF rec, ..., END OF rec. declares a structure
+        if tokens.get(1).is_some_and(|t| t.is_word("BEGIN")) && tokens.get(2).is_some_and(|t| t.is_word("OF")) {
+            let name = tokens.get(3).map(|t| t.text.clone());
+            let mut structure = self.create_node(NodeType::Class, name, line, "structure");
+            structure.metadata.annotations.insert("declaration".to_string(), Value::String(keyword.to_ascii_lowercase()));
+            while let Some(member) = statements.get(*pos) {
+                if self.keyword(member) != keyword {
+                    break;
+                }
+                if member.tokens.get(1).is_some_and(|t| t.is_word("END")) && member.tokens.get(2).is_some_and(|t| t.is_word("OF")) {
+                    *pos += 1;
+                    break;
+                }
+                structure.children.push(self.parse_declaration(statements, pos));
+            }
+            return structure;
+        }
+        
+        let name = tokens.get(1).map(|t| t.text.trim_start_matches('<').trim_end_matches('>').to_string());
+        let (node_type, tag) = match keyword.as_str() {
+            "CONSTANTS" => (NodeType::Constant, "constant"),
+            "TYPES" => (NodeType::Class, "type_definition"),
+            "PARAMETERS" | "PARAMETER" | "SELECT-OPTIONS" => (NodeType::Variable, "selection_screen"),
+            "FIELD-SYMBOLS" => (NodeType::Variable, "field_symbol"),
+            _ => (NodeType::Variable, "variable"),
+        };
+        let mut node = self.create_node(node_type, name.clone(), line, tag);
+        node.metadata.annotations.insert("declaration".to_string(), Value::String(keyword.to_ascii_lowercase()));
+        
+        if let Some(type_pos) = tokens.iter().position(|t| t.is_word("TYPE") || t.is_word("LIKE")) {
+            let (type_name, _, is_table) = self.parse_type_spec(&tokens[type_pos + 1..]);
+            if let Some(type_name) = type_name {
+                node.metadata.annotations.insert("type".to_string(), Value::String(type_name));
+            }
+            if is_table {
+                node.metadata.semantic_tags.push("internal_table".to_string());
+            }
+        }
+        if let Some(length) = self.word_after(tokens, "LENGTH") {
+            node.metadata.annotations.insert("length".to_string(), Value::String(length));
+        }
+        if let Some(value) = self.word_after(tokens, "VALUE") {
+            node.metadata.annotations.insert("value".to_string(), Value::String(value));
+        }
+        if let Some(table) = self.word_after(tokens, "FOR") {
+            node.metadata.annotations.insert("for".to_string(), Value::String(table));
+        }
+        if keyword == "CLASS-DATA" || keyword == "STATICS" {
+            node.metadata.semantic_tags.push("static".to_string());
+        }
+        
+        let original = self.join_tokens(tokens);
+        if tokens.windows(2).any(|w| w[0].is_word("HEADER") && w[1].is_word("LINE")) || keyword == "TABLES" {
+            node.metadata.legacy_patterns.push(self.legacy_pattern(
+                "header_line",
+                &original,
+                "Implicit work areas are obsolete; declare an explicit work area",
+                false,
+            ));
+        }
+        if keyword == "RANGES" || tokens.iter().any(|t| t.is_word("OCCURS")) {
+            node.metadata.semantic_tags.push("internal_table".to_string());
+            node.metadata.legacy_patterns.push(self.legacy_pattern(
+                "obsolete_declaration",
+                &original,
+                "Declare the table with TYPE STANDARD TABLE OF",
+                false,
+            ));
+        }
+        
+        node
+    }
+    
+    /// `target = expression` (and the `+=`-style operators)
+    fn parse_assignment(&self, tokens: &[Token], line: u32) -> Option<UIRNode> {
+        let operator = tokens.get(1).filter(|t| matches!(t.text.as_str(), "=" | "?=" | "+=" | "-=" | "*=" | "/="))?;
+        let target_token = &tokens[0];
+        
+        // DATA(x) = ... declares x inline
+        let inline = target_token.text.to_ascii_uppercase().starts_with("DATA(")
+            || target_token.text.to_ascii_uppercase().starts_with("FINAL(");
+        let mut target = if inline {
+            let name = target_token.text.split_once('(').map(|(_, n)| n.trim_end_matches(')').to_string());
+            let mut variable = self.create_node(NodeType::Variable, name, line, "inline_declaration");
+            variable.metadata.annotations.insert("original_text".to_string(), Value::String(target_token.text.clone()));
+            variable
+        } else {
+            self.create_operand(target_token)
+        };
+        target.metadata.semantic_tags.push("assignment_target".to_string());
+        
+        let value = self.parse_expression(&tokens[2..], line);
+        let mut node = self.build_assignment(target, value, line, "assignment");
+        if operator.text != "=" {
+            // `?=` is a down-cast, not an operation
+            node.operator = Operator::binary(operator.text.trim_end_matches('='), &CoalesceLanguage::Abap);
+            node.metadata.annotations.insert("operator".to_string(), Value::String(operator.text.clone()));
+        }
+        Some(node)
+    }
+    
+    /// Functional method call used as a statement: `lo_obj->run( iv_x = 1 ).`
+    fn parse_method_call_statement(&self, tokens: &[Token], line: u32) -> Option<UIRNode> {
+        if !tokens[0].text.ends_with('(') {
+            return None;
+        }
+        let mut pos = 0;
+        self.parse_primary(tokens, &mut pos, line)
+    }
+    
+    fn build_assignment(&self, target: UIRNode, value: UIRNode, line: u32, tag: &str) -> UIRNode {
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::Assignment), target.name.clone(), line, tag);
+        node.children = vec![target, value];
+        node
+    }
+    
+    fn generic_statement(&self, keyword: &str, tokens: &[Token], line: u32) -> UIRNode {
+        let mut node = self.create_node(
+            NodeType::Statement(StatementType::Expression),
+            Some(keyword.to_ascii_lowercase()),
+            line,
+            "statement",
+        );
+        node.children = tokens[1..].iter()
+            .filter(|t| t.kind == TokenKind::Literal || !self.is_keyword_operand(&t.upper()))
+            .map(|t| self.create_operand(t))
+            .collect();
+        node
+    }
+    
+    /// Logical/comparison/arithmetic expression with standard precedence
+    fn parse_expression(&self, tokens: &[Token], line: u32) -> UIRNode {
+        let mut pos = 0;
+        let expression = self.parse_logical(tokens, &mut pos, line, "OR");
+        let mut node = expression.unwrap_or_else(|| self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "empty"));
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(self.join_tokens(tokens)));
+        node
+    }
+    
+    fn parse_logical(&self, tokens: &[Token], pos: &mut usize, line: u32, operator: &str) -> Option<UIRNode> {
+        let mut left = if operator == "OR" {
+            self.parse_logical(tokens, pos, line, "AND")?
+        } else {
+            self.parse_not(tokens, pos, line)?
+        };
+        
+        while tokens.get(*pos).is_some_and(|t| t.is_word(operator)) {
+            *pos += 1;
+            let right = if operator == "OR" {
+                self.parse_logical(tokens, pos, line, "AND")?
+            } else {
+                self.parse_not(tokens, pos, line)?
+            };
+            left = self.binary(NodeType::Expression(ExpressionType::Logical), left, operator, right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_not(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        if tokens.get(*pos).is_some_and(|t| t.is_word("NOT")) {
+            *pos += 1;
+            let operand = self.parse_not(tokens, pos, line)?;
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Logical), None, line, "not");
+            node.operator = Some(Operator::Not);
+            node.metadata.annotations.insert("operator".to_string(), Value::String("NOT".to_string()));
+            node.children.push(operand);
+            return Some(node);
+        }
+        self.parse_comparison(tokens, pos, line)
+    }
+    
+    fn parse_comparison(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let left = self.parse_additive(tokens, pos, line)?;
+        
+        // Predicates: IS [NOT] INITIAL / BOUND / ASSIGNED / SUPPLIED
+        if tokens.get(*pos).is_some_and(|t| t.is_word("IS")) {
+            *pos += 1;
+            let mut operator = String::from("IS");
+            if tokens.get(*pos).is_some_and(|t| t.is_word("NOT")) {
+                operator.push_str(" NOT");
+                *pos += 1;
+            }
+            if let Some(predicate) = tokens.get(*pos) {
+                operator = format!("{} {}", operator, predicate.upper());
+                *pos += 1;
+            }
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Comparison), None, line, "predicate");
+            node.metadata.annotations.insert("operator".to_string(), Value::String(operator));
+            node.children.push(left);
+            return Some(node);
+        }
+        
+        let Some(operator) = tokens.get(*pos).filter(|t| t.kind == TokenKind::Word && COMPARISON_OPERATORS.contains(&t.upper().as_str())) else {
+            return Some(left);
+        };
+        let operator = operator.upper();
+        *pos += 1;
+        let right = self.parse_additive(tokens, pos, line)?;
+        let mut node = self.binary(NodeType::Expression(ExpressionType::Comparison), left, &operator, right, line);
+        if operator == "BETWEEN" && tokens.get(*pos).is_some_and(|t| t.is_word("AND")) {
+            *pos += 1;
+            if let Some(upper) = self.parse_additive(tokens, pos, line) {
+                node.children.push(upper);
+            }
+        }
+        Some(node)
+    }
+    
+    fn parse_additive(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let mut left = self.parse_multiplicative(tokens, pos, line)?;
+        while let Some(op) = tokens.get(*pos).filter(|t| t.kind == TokenKind::Word && matches!(t.text.as_str(), "+" | "-" | "&&" | "&")) {
+            let op = op.text.clone();
+            *pos += 1;
+            let right = self.parse_multiplicative(tokens, pos, line)?;
+            left = self.binary(NodeType::Expression(ExpressionType::Arithmetic), left, &op, right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_multiplicative(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let mut left = self.parse_primary(tokens, pos, line)?;
+        while let Some(op) = tokens.get(*pos).filter(|t| matches!(t.upper().as_str(), "*" | "/" | "**" | "MOD" | "DIV")) {
+            let op = op.upper();
+            *pos += 1;
+            let right = self.parse_primary(tokens, pos, line)?;
+            left = self.binary(NodeType::Expression(ExpressionType::Arithmetic), left, &op, right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_primary(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let token = tokens.get(*pos)?;
+        *pos += 1;
+        
+        if token.kind == TokenKind::Word && token.text == "(" {
+            let mut inner = self.parse_logical(tokens, pos, line, "OR")?;
+            if tokens.get(*pos).is_some_and(|t| t.text == ")") {
+                *pos += 1;
+            }
+            mark_grouped(&mut inner);
+            return Some(inner);
+        }
+        
+        // Constructor operators: NEW zcl_x( ), VALUE ty( ), CONV i( ), ...
+        let upper = token.upper();
+        if token.kind == TokenKind::Word
+            && matches!(upper.as_str(), "NEW" | "VALUE" | "CONV" | "COND" | "SWITCH" | "REF" | "CORRESPONDING" | "CAST" | "EXACT" | "REDUCE" | "FILTER")
+            && tokens.get(*pos).is_some_and(|t| t.text.ends_with('('))
+        {
+            let mut call = self.parse_primary(tokens, pos, line)?;
+            call.name = call.name.map(|n| format!("{} {}", upper, n));
+            call.metadata.semantic_tags.push("constructor_expression".to_string());
+            return Some(call);
+        }
+        
+        if token.kind == TokenKind::Word && token.text.ends_with('(') && token.text.len() > 1 {
+            // Functional call: arguments run to the matching ")"
+            let name = token.text.trim_end_matches('(').to_string();
+            let start = *pos;
+            let mut depth = 1;
+            while let Some(t) = tokens.get(*pos) {
+                if t.kind == TokenKind::Word && t.text.ends_with('(') {
+                    depth += 1;
+                } else if t.kind == TokenKind::Word && t.text.starts_with(')') {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                *pos += 1;
+            }
+            let arguments = &tokens[start..*pos];
+            *pos = (*pos + 1).min(tokens.len());
+            
+            let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(name.clone()), line, "call");
+            if name.contains("->") || name.contains("=>") {
+                call.metadata.semantic_tags.push("method_call".to_string());
+            }
+            call.children = self.parse_arguments(arguments, line);
+            call.metadata.annotations.insert(
+                "original_text".to_string(),
+                Value::String(format!("{} {} )", token.text, self.join_tokens(arguments)).replace("  ", " ")),
+            );
+            return Some(call);
+        }
+        
+        Some(self.create_operand(token))
+    }
+    
+    fn binary(&self, node_type: NodeType, left: UIRNode, op: &str, right: UIRNode, line: u32) -> UIRNode {
+        let tag = match node_type {
+            NodeType::Expression(ExpressionType::Logical) => "logical",
+            NodeType::Expression(ExpressionType::Comparison) => "comparison",
+            _ => "arithmetic",
+        };
+        let mut node = self.create_node(node_type, None, line, tag);
+        node.operator = Operator::binary(op, &CoalesceLanguage::Abap);
+        node.metadata.annotations.insert("operator".to_string(), Value::String(op.to_string()));
+        node.children = vec![left, right];
+        node
+    }
+    
+    fn create_operand(&self, token: &Token) -> UIRNode {
+        let is_literal = token.kind == TokenKind::Literal
+            || token.text.parse::<f64>().is_ok()
+            || matches!(token.upper().as_str(), "ABAP_TRUE" | "ABAP_FALSE" | "SPACE");
+        
+        let mut node = if is_literal {
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Literal), None, token.line, "literal");
+            node.literal = LiteralValue::parse(&token.text, &CoalesceLanguage::Abap);
+            node
+        } else {
+            // Host variables in new Open SQL are escaped with @
+            let name = token.text.trim_start_matches('@').to_string();
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Variable), Some(name), token.line, "identifier");
+            if token.text.contains('-') && !token.text.starts_with('-') {
+                node.metadata.semantic_tags.push("structure_component".to_string());
+            }
+            node
+        };
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(token.text.clone()));
+        node
+    }
+    
+    /// Statement keyword, combining `AT SELECTION-SCREEN`-style event names
+    fn keyword(&self, statement: &Statement) -> String {
+        let first = statement.tokens.first().map(|t| t.upper()).unwrap_or_default();
+        if first == "AT" {
+            if let Some(second) = statement.tokens.get(1) {
+                let event = format!("AT {}", second.upper());
+                if EVENT_BLOCKS.contains(&event.as_str()) {
+                    return event;
+                }
+            }
+        }
+        first
+    }
+    
+    fn annotate_after(&self, node: &mut UIRNode, tokens: &[Token], word: &str, key: &str) {
+        if let Some(value) = self.word_after(tokens, word) {
+            node.metadata.annotations.insert(key.to_string(), Value::String(value));
+        }
+    }
+    
+    fn word_after(&self, tokens: &[Token], word: &str) -> Option<String> {
+        tokens.iter().position(|t| t.is_word(word))
+            .and_then(|i| tokens.get(i + 1))
+            .map(|t| t.text.trim_start_matches('@').to_string())
+    }
+    
+    fn is_keyword_operand(&self, word: &str) -> bool {
+        matches!(word, "TO" | "FROM" | "INTO" | "TABLE" | "INDEX" | "WITH" | "KEY" | "BY" | "ASCENDING"
+            | "DESCENDING" | "WHERE" | "TRANSPORTING" | "ASSIGNING" | "REFERENCE" | "INITIAL" | "LINE"
+            | "LINES" | "OF" | "BINARY" | "SEARCH" | "=")
+    }
+    
+    fn unquote(&self, text: &str) -> String {
+        text.trim_matches(|c| c == '\'' || c == '`').to_string()
+    }
+    
+    fn join_tokens(&self, tokens: &[Token]) -> String {
+        tokens.iter().map(|t| t.text.as_str()).collect::<Vec<_>>().join(" ")
+    }
+    
+    fn legacy_pattern(&self, pattern_type: &str, original: &str, hint: &str, preserve_exactly: bool) -> LegacyPattern {
+        LegacyPattern {
+            pattern_type: pattern_type.to_string(),
+            original_construct: original.to_string(),
+            modernization_hint: Some(hint.to_string()),
+            preserve_exactly,
+        }
+    }
+    
+    fn create_node(&self, node_type: NodeType, name: Option<String>, line: u32, tag: &str) -> UIRNode {
+        let id = format!(
+            "{}_{}_{}",
+            tag,
+            line,
+            name.as_deref().unwrap_or("").replace(|c: char| !c.is_alphanumeric(), "_"),
+        );
+        
+        UIRNode {
+            id,
+            node_type,
+            name,
+            children: Vec::new(),
+            metadata: Metadata {
+                source_language: CoalesceLanguage::Abap,
+                semantic_tags: vec![tag.to_string()],
+                ..Default::default()
+            },
+            source_location: Some(SourceLocation {
+                file: String::new(),
+                start_line: line,
+                end_line: line,
+                start_column: 0,
+                end_column: 0,
+            }),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const FLIGHTS: &str = r#"
+REPORT zflights.
+* Flight report
+DATA: lt_flights TYPE STANDARD TABLE OF sflight,
+      ls_flight  TYPE sflight,
+      lv_count   TYPE i VALUE 0.
+PARAMETERS p_carr TYPE s_carr_id.
+
+START-OF-SELECTION.
+  SELECT * FROM sflight INTO TABLE @lt_flights WHERE carrid = @p_carr.
+  LOOP AT lt_flights INTO ls_flight.
+    IF ls_flight-seatsocc > 100.
+      lv_count = lv_count + 1.
+    ELSE.
+      PERFORM log_flight USING ls_flight.
+    ENDIF.
+  ENDLOOP.
+  WRITE: / 'Full flights:', lv_count.
+
+FORM log_flight USING is_flight TYPE sflight.
+  UPDATE sflight SET seatsmax = 200 WHERE carrid = is_flight-carrid.
+ENDFORM.
+"#;
+
+    #[test]
+    fn test_abap_report_and_declarations() {
+        let parser = AbapParser::new().unwrap();
+        let uir = parser.parse(FLIGHTS).unwrap();
+        
+        assert_eq!(uir.name.as_deref(), Some("zflights"));
+        let flights = &uir.children[0];
+        assert_eq!(flights.name.as_deref(), Some("lt_flights"));
+        assert!(flights.metadata.semantic_tags.contains(&"internal_table".to_string()));
+        assert_eq!(uir.children[2].metadata.annotations["value"], "0");
+        assert!(uir.children[3].metadata.semantic_tags.contains(&"selection_screen".to_string()));
+    }
+    
+    #[test]
+    fn test_abap_event_block_and_open_sql() {
+        let parser = AbapParser::new().unwrap();
+        let uir = parser.parse(FLIGHTS).unwrap();
+        
+        let event = uir.children.iter().find(|c| c.name.as_deref() == Some("START-OF-SELECTION")).unwrap();
+        let select = &event.children[0];
+        assert!(select.metadata.semantic_tags.contains(&"database_access".to_string()));
+        assert_eq!(select.metadata.annotations["table"], "sflight");
+        assert_eq!(select.metadata.annotations["into"], "lt_flights");
+        
+        let loop_node = &event.children[1];
+        assert_eq!(loop_node.node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)));
+        assert_eq!(loop_node.children[0].node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        
+        // The chained WRITE expands to one statement per chain link
+        assert_eq!(event.children.len(), 4);
+        assert_eq!(event.children[3].metadata.annotations["original_text"], "WRITE lv_count");
+    }
+    
+    #[test]
+    fn test_abap_form_and_class() {
+        let parser = AbapParser::new().unwrap();
+        let source = r#"
+CLASS lcl_calc DEFINITION.
+  PUBLIC SECTION.
+    METHODS add IMPORTING iv_a TYPE i iv_b TYPE i RETURNING VALUE(rv_sum) TYPE i.
+ENDCLASS.
+
+CLASS lcl_calc IMPLEMENTATION.
+  METHOD add.
+    rv_sum = iv_a + iv_b.
+  ENDMETHOD.
+ENDCLASS.
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.children.len(), 1);
+        let class = &uir.children[0];
+        assert_eq!(class.node_type, NodeType::Class);
+        
+        let method = &class.children[0];
+        assert_eq!(method.metadata.annotations["visibility"], "public");
+        assert_eq!(method.children.len(), 4);
+        assert_eq!(method.children[2].metadata.annotations["by_value"], true);
+        assert_eq!(method.children[3].node_type, NodeType::Expression(ExpressionType::Assignment));
+        
+        let form = AbapParser::new().unwrap().parse(FLIGHTS).unwrap();
+        let form = form.children.iter().find(|c| c.name.as_deref() == Some("log_flight")).unwrap();
+        assert!(form.metadata.legacy_patterns.iter().any(|p| p.pattern_type == "form_routine"));
+        assert_eq!(form.children[1].metadata.annotations["sql_operation"], "update");
+    }
+    
+    #[test]
+    fn test_abap_when_others_has_no_condition() {
+        let parser = AbapParser::new().unwrap();
+        let uir = parser.parse("CASE x. WHEN 1. WRITE 1. WHEN OTHERS. ENDCASE.").unwrap();
+        
+        let case = &uir.children[0];
+        assert_eq!(case.node_type, NodeType::ControlFlow(ControlFlowType::Switch));
+        let others = &case.children[2];
+        assert_eq!(others.name.as_deref(), Some("when_others"));
+        assert!(others.children.is_empty());
+    }
+}
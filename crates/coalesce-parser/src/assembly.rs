@@ -0,0 +1,765 @@
+use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, StatementType, LegacyPattern, Result,
+                   Parser as CoalesceParser, LiteralValue};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// x86 condition-code suffixes for `jcc`
+const X86_CONDITIONS: &[&str] = &[
+    "e", "ne", "z", "nz", "g", "ge", "l", "le", "a", "ae", "b", "be", "s", "ns", "o", "no",
+    "c", "nc", "p", "np", "pe", "po", "na", "nae", "nb", "nbe", "ng", "nge", "nl", "nle",
+    "cxz", "ecxz", "rcxz",
+];
+
+/// ARM condition-code suffixes for `bcc` / `b.cc`
+const ARM_CONDITIONS: &[&str] = &[
+    "eq", "ne", "cs", "hs", "cc", "lo", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le",
+];
+
+/// Data-definition directives (GAS and NASM/MASM spellings)
+const DATA_DIRECTIVES: &[&str] = &[
+    ".byte", ".short", ".hword", ".word", ".long", ".int", ".quad", ".octa", ".ascii", ".asciz", ".string",
+    ".zero", ".space", ".skip", ".fill", ".float", ".single", ".double", ".comm", ".lcomm",
+    "db", "dw", "dd", "dq", "dt", "resb", "resw", "resd", "resq", "byte", "word", "dword", "qword", "equ",
+];
+
+#[derive(Debug, Clone)]
+enum AsmItem {
+    Label(String),
+    Directive(String, Vec<String>),
+    Instruction(String, Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+struct AsmLine {
+    item: AsmItem,
+    line: u32,
+    text: String,
+}
+
+/// Experimental assembly parser for x86 (AT&T and Intel syntax) and ARM.
+///
+/// Assembly has no structured control flow, so this lifts only what the call
+/// graph needs: symbols become functions, local labels become basic blocks,
+/// branches become gotos or conditionals and calls become function calls.
+/// Blocks targeted by a backward branch are marked as loops. Everything else
+/// is kept as opaque instruction statements.
+pub struct AssemblyParser {
+}
+
+impl CoalesceParser for AssemblyParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::Assembly
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        let lines = self.split_lines(source);
+        let mut uir = self.lift(source, &lines);
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
+    }
+}
+
+impl AssemblyParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    fn split_lines(&self, source: &str) -> Vec<AsmLine> {
+        let mut lines = Vec::new();
+        let mut in_block_comment = false;
+        
+        for (index, raw) in source.lines().enumerate() {
+            let line = index as u32 + 1;
+            let mut text = self.strip_comments(raw, &mut in_block_comment);
+            
+            // Any number of `label:` prefixes may precede an instruction
+            loop {
+                let trimmed = text.trim_start();
+                let Some(colon) = trimmed.find(':') else {
+                    break;
+                };
+                let label = &trimmed[..colon];
+                if label.is_empty() || !label.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '$' | '@')) {
+                    break;
+                }
+                lines.push(AsmLine { item: AsmItem::Label(label.to_string()), line, text: format!("{}:", label) });
+                text = trimmed[colon + 1..].to_string();
+            }
+            
+            let trimmed = text.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            
+            let (head, rest) = match trimmed.find(char::is_whitespace) {
+                Some(split) => (&trimmed[..split], trimmed[split..].trim()),
+                None => (trimmed, ""),
+            };
+            let operands = self.split_operands(rest);
+            let head_lower = head.to_ascii_lowercase();
+            
+            // MASM `name PROC` / NASM `msg db ...` put the symbol first
+            let second = operands.first()
+                .and_then(|o| o.split_whitespace().next())
+                .map(|w| w.to_ascii_lowercase())
+                .unwrap_or_default();
+            let item = if matches!(second.as_str(), "proc" | "endp") {
+                AsmItem::Directive(second.clone(), vec![head.to_string()])
+            } else if !head.starts_with('.') && DATA_DIRECTIVES.contains(&second.as_str()) && !DATA_DIRECTIVES.contains(&head_lower.as_str()) {
+                lines.push(AsmLine { item: AsmItem::Label(head.to_string()), line, text: trimmed.to_string() });
+                let rest = rest[second.len()..].trim();
+                AsmItem::Directive(second.clone(), self.split_operands(rest))
+            } else if head.starts_with('.') || self.is_directive(&head_lower) {
+                AsmItem::Directive(head_lower, operands)
+            } else {
+                AsmItem::Instruction(head_lower, operands)
+            };
+            lines.push(AsmLine { item, line, text: trimmed.to_string() });
+        }
+        
+        lines
+    }
+    
+    /// Remove `;`, `//`, `/* */`, leading `#` and ARM `@` comments
+    fn strip_comments(&self, raw: &str, in_block_comment: &mut bool) -> String {
+        let chars: Vec<char> = raw.chars().collect();
+        let mut out = String::new();
+        let mut in_quote: Option<char> = None;
+        let mut i = 0;
+        
+        if raw.trim_start().starts_with('#') && !raw.trim_start().starts_with("#define") {
+            return String::new();
+        }
+        
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            if *in_block_comment {
+                if c == '*' && next == Some('/') {
+                    *in_block_comment = false;
+                    i += 1;
+                }
+                i += 1;
+                continue;
+            }
+            if let Some(quote) = in_quote {
+                if c == quote {
+                    in_quote = None;
+                }
+                out.push(c);
+                i += 1;
+                continue;
+            }
+            match c {
+                '"' | '\'' => in_quote = Some(c),
+                ';' => break,
+                '/' if next == Some('/') => break,
+                '/' if next == Some('*') => {
+                    *in_block_comment = true;
+                    i += 2;
+                    continue;
+                }
+                '@' if (i == 0 || chars[i - 1].is_whitespace()) && next.is_none_or(|n| n.is_whitespace()) => break,
+                _ => {}
+            }
+            out.push(c);
+            i += 1;
+        }
+        
+        out
+    }
+    
+    fn is_directive(&self, word: &str) -> bool {
+        matches!(word, "global" | "extern" | "section" | "segment" | "bits" | "default" | "public" | "externdef"
+            | "include" | "includelib" | "end" | "align" | "%include" | "%define" | "#define")
+    }
+    
+    /// Comma-separated operands, ignoring commas inside brackets, parentheses and braces
+    fn split_operands(&self, text: &str) -> Vec<String> {
+        let mut operands = Vec::new();
+        let mut current = String::new();
+        let mut depth = 0;
+        let mut in_quote: Option<char> = None;
+        
+        for c in text.chars() {
+            match c {
+                '"' | '\'' if in_quote.is_none() => in_quote = Some(c),
+                _ if Some(c) == in_quote => in_quote = None,
+                '(' | '[' | '{' if in_quote.is_none() => depth += 1,
+                ')' | ']' | '}' if in_quote.is_none() => depth -= 1,
+                ',' if depth == 0 && in_quote.is_none() => {
+                    operands.push(current.trim().to_string());
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(c);
+        }
+        if !current.trim().is_empty() {
+            operands.push(current.trim().to_string());
+        }
+        operands
+    }
+    
+    fn lift(&self, source: &str, lines: &[AsmLine]) -> UIRNode {
+        let mut root = self.create_node(NodeType::Module, Some("assembly_unit".to_string()), 1, "source_file");
+        root.source_location = Some(SourceLocation {
+            file: String::new(),
+            start_line: 1,
+            end_line: source.lines().count() as u32,
+            start_column: 0,
+            end_column: source.len() as u32,
+        });
+        root.metadata.annotations.insert("experimental".to_string(), Value::Bool(true));
+        root.metadata.annotations.insert("architecture".to_string(), Value::String(self.detect_architecture(lines).to_string()));
+        
+        let (functions, exports, externs) = self.collect_symbols(lines);
+        let labels: HashSet<String> = lines.iter()
+            .filter_map(|l| match &l.item {
+                AsmItem::Label(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        
+        root.metadata.annotations.insert("exports".to_string(), Value::Array(exports.iter().map(|e| Value::String(e.clone())).collect()));
+        root.metadata.dependencies.extend(externs.iter().cloned());
+        
+        let mut section = String::from("text");
+        let mut function: Option<UIRNode> = None;
+        let mut block: Option<UIRNode> = None;
+        let mut data: Option<UIRNode> = None;
+        
+        for asm in lines {
+            match &asm.item {
+                AsmItem::Directive(name, args) => match name.as_str() {
+                    ".text" | ".code" => section = "text".to_string(),
+                    ".data" | ".bss" | ".rodata" | ".const" | ".data?" => section = name.trim_start_matches('.').trim_end_matches('?').to_string(),
+                    ".section" | "section" | "segment" => {
+                        let target = args.first().map(|a| a.trim_start_matches('.').to_ascii_lowercase()).unwrap_or_default();
+                        section = if target.starts_with("text") { "text".to_string() } else { target };
+                    }
+                    "proc" => {
+                        self.close_function(&mut root, &mut function, &mut block);
+                        let name = args.first().cloned().unwrap_or_default();
+                        function = Some(self.create_function(&name, asm.line, exports.contains(&name)));
+                    }
+                    "endp" => self.close_function(&mut root, &mut function, &mut block),
+                    _ if DATA_DIRECTIVES.contains(&name.as_str()) => {
+                        if let Some(variable) = data.as_mut() {
+                            self.annotate_data(variable, name, args);
+                            if name == "equ" {
+                                variable.node_type = NodeType::Constant;
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                AsmItem::Label(name) => {
+                    if section != "text" {
+                        if let Some(variable) = data.take() {
+                            root.children.push(variable);
+                        }
+                        let node_type = if section == "rodata" || section == "const" { NodeType::Constant } else { NodeType::Variable };
+                        let mut variable = self.create_node(node_type, Some(name.clone()), asm.line, "data");
+                        variable.metadata.annotations.insert("section".to_string(), Value::String(section.clone()));
+                        data = Some(variable);
+                        continue;
+                    }
+                    if let Some(variable) = data.take() {
+                        root.children.push(variable);
+                    }
+                    
+                    if functions.contains(name) {
+                        self.close_function(&mut root, &mut function, &mut block);
+                        function = Some(self.create_function(name, asm.line, exports.contains(name)));
+                    } else {
+                        self.close_block(&mut function, &mut root, &mut block);
+                        block = Some(self.create_node(NodeType::Statement(StatementType::Expression), Some(name.clone()), asm.line, "basic_block"));
+                    }
+                }
+                AsmItem::Instruction(mnemonic, operands) => {
+                    if section != "text" {
+                        // NASM `equ`/`times` style lines without a label land here
+                        continue;
+                    }
+                    let node = self.lift_instruction(mnemonic, operands, asm, &labels);
+                    if let Some(node) = node {
+                        if let Some(function) = function.as_mut() {
+                            for dependency in &node.metadata.dependencies {
+                                if !function.metadata.dependencies.contains(dependency) {
+                                    function.metadata.dependencies.push(dependency.clone());
+                                }
+                            }
+                        }
+                        match (block.as_mut(), function.as_mut()) {
+                            (Some(block), _) => block.children.push(node),
+                            (None, Some(function)) => function.children.push(node),
+                            (None, None) => root.children.push(node),
+                        }
+                    }
+                }
+            }
+        }
+        
+        if let Some(variable) = data.take() {
+            root.children.push(variable);
+        }
+        self.close_function(&mut root, &mut function, &mut block);
+        
+        // Calls to symbols defined elsewhere are the unit's external dependencies
+        let mut called = Vec::new();
+        self.collect_external_calls(&root, &labels, &mut called);
+        for callee in called {
+            if !root.metadata.dependencies.contains(&callee) {
+                root.metadata.dependencies.push(callee);
+            }
+        }
+        
+        root
+    }
+    
+    /// Functions are exported symbols, `.type sym, @function` symbols, PROCs and call targets
+    fn collect_symbols(&self, lines: &[AsmLine]) -> (HashSet<String>, Vec<String>, Vec<String>) {
+        let mut functions = HashSet::new();
+        let mut exports = Vec::new();
+        let mut externs = Vec::new();
+        
+        for asm in lines {
+            match &asm.item {
+                AsmItem::Directive(name, args) => match name.as_str() {
+                    ".globl" | ".global" | "global" | "public" => {
+                        for symbol in args.iter().flat_map(|a| a.split_whitespace()) {
+                            let symbol = symbol.split(':').next().unwrap_or(symbol).to_string();
+                            functions.insert(symbol.clone());
+                            exports.push(symbol);
+                        }
+                    }
+                    ".extern" | "extern" | "externdef" => {
+                        for symbol in args.iter().flat_map(|a| a.split_whitespace()) {
+                            let symbol = symbol.split(':').next().unwrap_or(symbol).to_string();
+                            if !externs.contains(&symbol) {
+                                externs.push(symbol);
+                            }
+                        }
+                    }
+                    ".type" if args.get(1).is_some_and(|t| t.ends_with("function")) => {
+                        functions.insert(args[0].clone());
+                    }
+                    "proc" => {
+                        functions.extend(args.first().cloned());
+                    }
+                    _ => {}
+                },
+                AsmItem::Instruction(mnemonic, operands) if self.is_call(mnemonic) => {
+                    if let Some(target) = operands.first().and_then(|t| self.direct_target(t)) {
+                        functions.insert(target);
+                    }
+                }
+                _ => {}
+            }
+        }
+        
+        (functions, exports, externs)
+    }
+    
+    fn detect_architecture(&self, lines: &[AsmLine]) -> &'static str {
+        let arm = lines.iter().any(|l| match &l.item {
+            AsmItem::Instruction(mnemonic, operands) => {
+                matches!(mnemonic.as_str(), "ldr" | "str" | "ldp" | "stp" | "adrp" | "bl" | "blr" | "bx" | "cbz" | "cbnz" | "svc")
+                    || operands.iter().any(|o| o.starts_with('{'))
+            }
+            _ => false,
+        });
+        if arm {
+            "arm"
+        } else {
+            "x86"
+        }
+    }
+    
+    fn create_function(&self, name: &str, line: u32, exported: bool) -> UIRNode {
+        let mut function = self.create_node(NodeType::Function, Some(name.to_string()), line, "assembly_function");
+        if exported {
+            function.metadata.semantic_tags.push("exported".to_string());
+        }
+        function.metadata.legacy_patterns.push(LegacyPattern {
+            pattern_type: "hand_written_assembly".to_string(),
+            original_construct: name.to_string(),
+            modernization_hint: Some("Reimplement in the target language or keep behind an FFI boundary".to_string()),
+            preserve_exactly: true,
+        });
+        function
+    }
+    
+    fn close_block(&self, function: &mut Option<UIRNode>, root: &mut UIRNode, block: &mut Option<UIRNode>) {
+        if let Some(block) = block.take() {
+            match function.as_mut() {
+                Some(function) => function.children.push(block),
+                None => root.children.push(block),
+            }
+        }
+    }
+    
+    fn close_function(&self, root: &mut UIRNode, function: &mut Option<UIRNode>, block: &mut Option<UIRNode>) {
+        self.close_block(function, root, block);
+        if let Some(mut function) = function.take() {
+            self.mark_loops(&mut function);
+            if let Some(last) = self.last_line(&function) {
+                if let Some(location) = function.source_location.as_mut() {
+                    location.end_line = last;
+                }
+            }
+            root.children.push(function);
+        }
+    }
+    
+    /// A branch back to an earlier block closes a loop; that block becomes the loop node
+    fn mark_loops(&self, function: &mut UIRNode) {
+        let mut seen: Vec<String> = Vec::new();
+        let mut headers: HashSet<String> = HashSet::new();
+        
+        for child in function.children.iter_mut() {
+            if child.metadata.semantic_tags.iter().any(|t| t == "basic_block") {
+                seen.extend(child.name.clone());
+            }
+            let mut targets = Vec::new();
+            self.branch_targets(child, &mut targets);
+            for target in targets {
+                if seen.contains(&target) {
+                    headers.insert(target);
+                }
+            }
+        }
+        
+        for child in function.children.iter_mut() {
+            if child.name.as_ref().is_some_and(|n| headers.contains(n)) && child.metadata.semantic_tags.iter().any(|t| t == "basic_block") {
+                child.node_type = NodeType::ControlFlow(ControlFlowType::Loop(LoopType::DoWhile));
+                child.metadata.semantic_tags.push("loop_header".to_string());
+            }
+        }
+        self.tag_back_edges(function, &headers);
+    }
+    
+    fn tag_back_edges(&self, node: &mut UIRNode, headers: &HashSet<String>) {
+        for child in node.children.iter_mut() {
+            if child.node_type == NodeType::ControlFlow(ControlFlowType::Goto) && child.name.as_ref().is_some_and(|n| headers.contains(n)) {
+                child.metadata.semantic_tags.push("back_edge".to_string());
+            }
+            self.tag_back_edges(child, headers);
+        }
+    }
+    
+    fn branch_targets(&self, node: &UIRNode, targets: &mut Vec<String>) {
+        if node.node_type == NodeType::ControlFlow(ControlFlowType::Goto) {
+            targets.extend(node.name.clone());
+        }
+        for child in &node.children {
+            self.branch_targets(child, targets);
+        }
+    }
+    
+    fn collect_external_calls(&self, node: &UIRNode, labels: &HashSet<String>, called: &mut Vec<String>) {
+        if node.node_type == NodeType::Expression(ExpressionType::FunctionCall) && node.metadata.semantic_tags.iter().any(|t| t == "call") {
+            if let Some(name) = node.name.as_ref().filter(|n| !labels.contains(*n) && !called.contains(n)) {
+                called.push(name.clone());
+            }
+        }
+        for child in &node.children {
+            self.collect_external_calls(child, labels, called);
+        }
+    }
+    
+    fn last_line(&self, node: &UIRNode) -> Option<u32> {
+        node.children.iter()
+            .filter_map(|c| self.last_line(c).or_else(|| c.source_location.as_ref().map(|l| l.end_line)))
+            .max()
+    }
+    
+    fn lift_instruction(&self, mnemonic: &str, operands: &[String], asm: &AsmLine, labels: &HashSet<String>) -> Option<UIRNode> {
+        let line = asm.line;
+        // Size suffixes and prefixes do not change control flow
+        let mnemonic = mnemonic.trim_start_matches("rep ").trim();
+        
+        let mut node = if self.is_call(mnemonic) {
+            match operands.first().and_then(|t| self.direct_target(t)) {
+                Some(target) => {
+                    let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(target.clone()), line, "call");
+                    if !labels.contains(&target) {
+                        call.metadata.dependencies.push(target);
+                        call.metadata.semantic_tags.push("external_call".to_string());
+                    }
+                    call
+                }
+                None => {
+                    let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), None, line, "indirect_call");
+                    call.metadata.annotations.insert("target".to_string(), Value::String(operands.join(", ")));
+                    call
+                }
+            }
+        } else if self.is_return(mnemonic, operands) {
+            self.create_node(NodeType::Statement(StatementType::Return), None, line, "return")
+        } else if matches!(mnemonic, "syscall" | "sysenter" | "svc" | "swi") || (mnemonic == "int" && operands.first().is_some_and(|o| o.contains("80"))) {
+            self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some("syscall".to_string()), line, "syscall")
+        } else if let Some((condition, target)) = self.conditional_branch(mnemonic, operands) {
+            let mut branch = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some(condition.clone()), line, "conditional_branch");
+            let mut test = self.create_node(NodeType::Expression(ExpressionType::Comparison), None, line, "condition_code");
+            test.metadata.annotations.insert("condition".to_string(), Value::String(condition));
+            // cbz/tbz-style branches test a register directly
+            for operand in &operands[..operands.len().saturating_sub(1)] {
+                test.children.push(self.create_operand(operand, line));
+            }
+            branch.children.push(test);
+            branch.children.push(self.create_jump(&target, line));
+            branch
+        } else if matches!(mnemonic, "jmp" | "jmpq" | "b" | "br" | "bx") {
+            match operands.first().and_then(|t| self.direct_target(t)) {
+                Some(target) => self.create_jump(&target, line),
+                None => {
+                    let mut jump = self.create_node(NodeType::ControlFlow(ControlFlowType::Goto), None, line, "indirect_jump");
+                    jump.metadata.annotations.insert("target".to_string(), Value::String(operands.join(", ")));
+                    jump
+                }
+            }
+        } else {
+            let mut instruction = self.create_node(NodeType::Statement(StatementType::Expression), Some(mnemonic.to_string()), line, "instruction");
+            instruction.metadata.annotations.insert("operands".to_string(), Value::Array(
+                operands.iter().map(|o| Value::String(o.clone())).collect(),
+            ));
+            instruction
+        };
+        
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(asm.text.clone()));
+        Some(node)
+    }
+    
+    fn is_call(&self, mnemonic: &str) -> bool {
+        matches!(mnemonic, "call" | "callq" | "calll" | "bl" | "blx" | "blr" | "jal" | "jalr")
+    }
+    
+    fn is_return(&self, mnemonic: &str, operands: &[String]) -> bool {
+        match mnemonic {
+            "ret" | "retq" | "retl" | "retn" | "retf" | "eret" => true,
+            "bx" => operands.first().is_some_and(|o| o.eq_ignore_ascii_case("lr")),
+            "pop" | "ldmfd" | "ldmia" => operands.iter().any(|o| o.to_ascii_lowercase().contains("pc")),
+            _ => false,
+        }
+    }
+    
+    /// (condition code, target label) for conditional branches
+    fn conditional_branch(&self, mnemonic: &str, operands: &[String]) -> Option<(String, String)> {
+        let target = operands.last().and_then(|t| self.direct_target(t))?;
+        
+        if let Some(condition) = mnemonic.strip_prefix('j') {
+            if X86_CONDITIONS.contains(&condition) {
+                return Some((condition.to_string(), target));
+            }
+        }
+        if matches!(mnemonic, "loop" | "loope" | "loopne" | "loopz" | "loopnz") {
+            return Some((mnemonic.to_string(), target));
+        }
+        if matches!(mnemonic, "cbz" | "cbnz" | "tbz" | "tbnz") {
+            let condition = if mnemonic.contains('n') { "nonzero" } else { "zero" };
+            return Some((condition.to_string(), target));
+        }
+        if let Some(condition) = mnemonic.strip_prefix("b.").or_else(|| mnemonic.strip_prefix('b')) {
+            if ARM_CONDITIONS.contains(&condition) {
+                return Some((condition.to_string(), target));
+            }
+        }
+        None
+    }
+    
+    /// Symbol name of a direct branch/call target, or None for register/memory operands
+    fn direct_target(&self, operand: &str) -> Option<String> {
+        let target = operand.trim();
+        let target = target.strip_prefix("near ").or_else(|| target.strip_prefix("short ")).unwrap_or(target).trim();
+        if target.is_empty() || target.starts_with(['*', '%', '[', '#', '$']) || self.is_register(target) {
+            return None;
+        }
+        let target = target.split('@').next().unwrap_or(target);
+        if !target.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '$')) {
+            return None;
+        }
+        // GAS numeric local labels are referenced as `1f` (forward) / `1b` (backward)
+        let numeric = target.strip_suffix(['f', 'b']).filter(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+        Some(numeric.unwrap_or(target).to_string())
+    }
+    
+    fn is_register(&self, operand: &str) -> bool {
+        let lower = operand.to_ascii_lowercase();
+        let numbered = |prefix: char| lower.strip_prefix(prefix).is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+        numbered('x') || numbered('w') || numbered('r') || matches!(lower.as_str(),
+            "lr" | "sp" | "pc" | "fp" | "ip" | "xzr" | "wzr"
+            | "rax" | "rbx" | "rcx" | "rdx" | "rsi" | "rdi" | "rbp" | "rsp"
+            | "eax" | "ebx" | "ecx" | "edx" | "esi" | "edi" | "ebp" | "esp")
+    }
+    
+    fn create_jump(&self, target: &str, line: u32) -> UIRNode {
+        let mut jump = self.create_node(NodeType::ControlFlow(ControlFlowType::Goto), Some(target.to_string()), line, "jump");
+        jump.metadata.annotations.insert("target".to_string(), Value::String(target.to_string()));
+        jump
+    }
+    
+    fn create_operand(&self, operand: &str, line: u32) -> UIRNode {
+        let is_immediate = operand.starts_with(['#', '$']) || operand.parse::<i64>().is_ok();
+        let mut node = if is_immediate {
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "immediate");
+            node.literal = LiteralValue::parse(operand.trim_start_matches(['#', '$']), &CoalesceLanguage::Assembly);
+            node
+        } else {
+            self.create_node(NodeType::Expression(ExpressionType::Variable), Some(operand.trim_start_matches('%').to_string()), line, "register")
+        };
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(operand.to_string()));
+        node
+    }
+    
+    fn annotate_data(&self, variable: &mut UIRNode, directive: &str, args: &[String]) {
+        variable.metadata.annotations.insert("directive".to_string(), Value::String(directive.to_string()));
+        let values = variable.metadata.annotations.entry("values".to_string()).or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(values) = values {
+            values.extend(args.iter().map(|a| Value::String(a.clone())));
+        }
+    }
+    
+    fn create_node(&self, node_type: NodeType, name: Option<String>, line: u32, tag: &str) -> UIRNode {
+        let id = format!(
+            "{}_{}_{}",
+            tag,
+            line,
+            name.as_deref().unwrap_or("").replace(|c: char| !c.is_alphanumeric(), "_"),
+        );
+        
+        UIRNode {
+            id,
+            node_type,
+            name,
+            children: Vec::new(),
+            metadata: Metadata {
+                source_language: CoalesceLanguage::Assembly,
+                semantic_tags: vec![tag.to_string()],
+                ..Default::default()
+            },
+            source_location: Some(SourceLocation {
+                file: String::new(),
+                start_line: line,
+                end_line: line,
+                start_column: 0,
+                end_column: 0,
+            }),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_att_function_with_loop_and_call() {
+        let parser = AssemblyParser::new().unwrap();
+        let source = r#"
+    .text
+    .globl  sum_array
+    .type   sum_array, @function
+sum_array:
+    xorl    %eax, %eax          # accumulator
+.Lloop:
+    addl    (%rdi), %eax
+    addq    $4, %rdi
+    decq    %rsi
+    jnz     .Lloop
+    call    log_total@PLT
+    ret
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.metadata.annotations["architecture"], "x86");
+        assert!(uir.metadata.dependencies.contains(&"log_total".to_string()));
+        
+        let function = &uir.children[0];
+        assert_eq!(function.name.as_deref(), Some("sum_array"));
+        assert!(function.metadata.semantic_tags.contains(&"exported".to_string()));
+        
+        let block = &function.children[1];
+        assert_eq!(block.node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::DoWhile)));
+        let branch = &block.children[3];
+        assert_eq!(branch.node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        assert!(branch.children[1].metadata.semantic_tags.contains(&"back_edge".to_string()));
+        assert_eq!(block.children[4].name.as_deref(), Some("log_total"));
+        assert_eq!(block.children[5].node_type, NodeType::Statement(StatementType::Return));
+    }
+    
+    #[test]
+    fn test_arm64_branches_and_calls() {
+        let parser = AssemblyParser::new().unwrap();
+        let source = r#"
+        .global checked_div
+checked_div:
+        stp     x29, x30, [sp, #-16]!
+        cbz     x1, 1f
+        sdiv    x0, x0, x1
+        b       2f
+1:
+        bl      abort
+2:
+        ldp     x29, x30, [sp], #16
+        ret
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.metadata.annotations["architecture"], "arm");
+        
+        let function = &uir.children[0];
+        let branch = &function.children[1];
+        assert_eq!(branch.node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        assert_eq!(branch.children[0].metadata.annotations["condition"], "zero");
+        assert_eq!(branch.children[1].name.as_deref(), Some("1"));
+        assert_eq!(function.children[3].node_type, NodeType::ControlFlow(ControlFlowType::Goto));
+        
+        let failure = &function.children[4];
+        assert_eq!(failure.name.as_deref(), Some("1"));
+        assert_eq!(failure.children[0].name.as_deref(), Some("abort"));
+        assert_eq!(function.metadata.dependencies, vec!["abort".to_string()]);
+    }
+    
+    #[test]
+    fn test_nasm_data_and_syscall() {
+        let parser = AssemblyParser::new().unwrap();
+        let source = r#"
+section .data
+msg     db  "hello", 10
+len     equ $ - msg
+
+section .text
+global _start
+extern exit_hook
+_start:
+    mov rax, 1
+    mov rsi, msg
+    syscall
+    call exit_hook
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let message = &uir.children[0];
+        assert_eq!(message.node_type, NodeType::Variable);
+        assert_eq!(message.metadata.annotations["directive"], "db");
+        assert_eq!(uir.children[1].node_type, NodeType::Constant);
+        
+        let start = &uir.children[2];
+        assert_eq!(start.name.as_deref(), Some("_start"));
+        assert!(start.children[2].metadata.semantic_tags.contains(&"syscall".to_string()));
+        assert_eq!(uir.metadata.dependencies, vec!["exit_hook".to_string()]);
+    }
+}
@@ -0,0 +1,610 @@
+use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, StatementType, Result, CoalesceError,
+                   Parser as CoalesceParser};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::operators::annotate_operator;
+use crate::literals::annotate_literal;
+use serde_json::Value;
+
+/// Shell builtins, tagged so generators can map them to library calls instead of subprocesses
+const BUILTINS: &[&str] = &[
+    "echo", "printf", "read", "cd", "pwd", "set", "unset", "shift", "source", ".", "eval", "exec",
+    "test", "[", "true", "false", "trap", "wait", "kill", "getopts", "type", "command", "mapfile",
+];
+
+/// Shell script parser using tree-sitter-bash
+pub struct BashParser {
+}
+
+impl CoalesceParser for BashParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::Bash
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        // Create a new parser for this parse operation
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_bash::language())
+            .map_err(|e| CoalesceError::ParseError {
+                message: format!("Failed to set Bash language: {}", e),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let tree = parser.parse(source, None)
+            .ok_or_else(|| CoalesceError::ParseError {
+                message: "Failed to parse shell script".to_string(),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let mut uir = self.ast_to_uir(tree.root_node(), source)?;
+        recovery::summarize(source, tree.root_node(), &mut uir);
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
+    }
+}
+
+impl BashParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    fn ast_to_uir(&self, node: Node, source: &str) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::Bash, |child| self.ast_to_uir(child, source));
+        }
+        
+        match node.kind() {
+            "program" => self.convert_program(node, source),
+            "function_definition" => self.convert_function_definition(node, source),
+            "command" => self.convert_command(node, source),
+            "pipeline" => self.convert_pipeline(node, source),
+            "list" => self.convert_operator_expression(node, source, NodeType::Expression(ExpressionType::Logical)),
+            "redirected_statement" => self.convert_redirected_statement(node, source),
+            "variable_assignment" => self.convert_variable_assignment(node, source),
+            "declaration_command" => self.convert_declaration(node, source),
+            "binary_expression" => self.convert_binary_expression(node, source),
+            "if_statement" | "elif_clause" | "case_item" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Conditional)),
+            "case_statement" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Switch)),
+            "for_statement" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach))),
+            "c_style_for_statement" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For))),
+            "while_statement" => {
+                let mut uir = self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)))?;
+                if self.has_keyword(node, "until") {
+                    uir.metadata.semantic_tags.push("until".to_string());
+                }
+                Ok(uir)
+            }
+            "test_command" | "unary_expression" => self.convert_simple(node, source, NodeType::Expression(ExpressionType::Comparison)),
+            "simple_expansion" | "expansion" => self.convert_expansion(node, source),
+            "command_substitution" => {
+                let mut uir = self.convert_simple(node, source, NodeType::Expression(ExpressionType::FunctionCall))?;
+                uir.name = Some("command_substitution".to_string());
+                Ok(uir)
+            }
+            "variable_name" => self.convert_named_leaf(node, source, NodeType::Expression(ExpressionType::Variable)),
+            "word" | "number" | "raw_string" | "ansi_c_string" | "string_content" | "extglob_pattern" => {
+                self.convert_named_leaf(node, source, NodeType::Expression(ExpressionType::Literal))
+            }
+            "string" | "concatenation" => self.convert_string(node, source),
+            _ => self.convert_generic(node, source),
+        }
+    }
+    
+    fn convert_program(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut metadata = self.create_metadata(node, source);
+        if let Some(interpreter) = source.lines().next().and_then(|l| l.strip_prefix("#!")) {
+            metadata.annotations.insert("interpreter".to_string(), Value::String(interpreter.trim().to_string()));
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Module,
+            name: Some("shell_program".to_string()),
+            children: self.convert_children(node, source)?,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_function_definition(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let name = node.child_by_field_name("name")
+            .map(|n| self.node_text(n, source).to_string());
+        
+        let children = match node.child_by_field_name("body") {
+            Some(body) => self.convert_children(body, source)?,
+            None => vec![],
+        };
+        
+        // Shell functions take positional arguments ($1, $2, ...) rather than named parameters
+        let mut metadata = self.create_metadata(node, source);
+        let arity = self.max_positional_parameter(node, source);
+        if arity > 0 {
+            metadata.annotations.insert("positional_parameters".to_string(), Value::from(arity));
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Function,
+            name,
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_command(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let name = node.child_by_field_name("name")
+            .map(|n| self.node_text(n, source).to_string())
+            .unwrap_or_default();
+        
+        let node_type = match name.as_str() {
+            "return" | "exit" => NodeType::Statement(StatementType::Return),
+            "break" => NodeType::Statement(StatementType::Break),
+            "continue" => NodeType::Statement(StatementType::Continue),
+            _ => NodeType::Expression(ExpressionType::FunctionCall),
+        };
+        
+        let mut metadata = self.create_metadata(node, source);
+        if BUILTINS.contains(&name.as_str()) {
+            metadata.semantic_tags.push("builtin".to_string());
+        } else if node_type == NodeType::Expression(ExpressionType::FunctionCall) {
+            metadata.semantic_tags.push("external_command".to_string());
+        }
+        if name == "exit" {
+            metadata.semantic_tags.push("exit".to_string());
+        }
+        
+        let mut children = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "command_name" => {}
+                "variable_assignment" => {
+                    // `FOO=bar cmd` sets FOO only for this command
+                    let mut assignment = self.convert_variable_assignment(child, source)?;
+                    assignment.metadata.semantic_tags.push("command_environment".to_string());
+                    children.push(assignment);
+                }
+                _ if child.is_extra() => {}
+                _ => children.push(self.ast_to_uir(child, source)?),
+            }
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: Some(name),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_pipeline(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let children = self.convert_children(node, source)?;
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.annotations.insert("stages".to_string(), Value::from(children.len()));
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::FunctionCall),
+            name: Some("pipeline".to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_redirected_statement(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let Some(body) = node.child_by_field_name("body") else {
+            return self.convert_generic(node, source);
+        };
+        
+        // Redirections are recorded on the statement they apply to
+        let mut uir = self.ast_to_uir(body, source)?;
+        let mut redirects = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind().ends_with("redirect") {
+                redirects.push(Value::String(self.node_text(child, source).to_string()));
+            }
+        }
+        uir.metadata.annotations.insert("redirects".to_string(), Value::Array(redirects));
+        uir.source_location = self.create_source_location(node);
+        
+        Ok(uir)
+    }
+    
+    fn convert_variable_assignment(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        if let Some(name) = node.child_by_field_name("name") {
+            let mut target = self.convert_named_leaf(name, source, NodeType::Variable)?;
+            target.metadata.semantic_tags.push("shell_variable".to_string());
+            children.push(target);
+        }
+        if let Some(value) = node.child_by_field_name("value") {
+            children.push(self.ast_to_uir(value, source)?);
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Assignment),
+            name: None,
+            children,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_declaration(&self, node: Node, source: &str) -> Result<UIRNode> {
+        // export/local/readonly/declare
+        let keyword = node.child(0).map(|k| self.node_text(k, source).to_string()).unwrap_or_default();
+        
+        let mut children = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            let mut uir = self.ast_to_uir(child, source)?;
+            uir.metadata.semantic_tags.push(keyword.clone());
+            children.push(uir);
+        }
+        
+        if children.len() == 1 {
+            return Ok(children.remove(0));
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.semantic_tags.push(keyword.clone());
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Statement(StatementType::Expression),
+            name: Some(keyword),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_binary_expression(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let operator = node.child_by_field_name("operator")
+            .map(|op| self.node_text(op, source).to_string())
+            .unwrap_or_default();
+        
+        let node_type = match operator.as_str() {
+            "&&" | "||" | "-a" | "-o" => NodeType::Expression(ExpressionType::Logical),
+            "=" | "+=" | "-=" | "*=" | "/=" => NodeType::Expression(ExpressionType::Assignment),
+            "+" | "-" | "*" | "/" | "%" | "**" => NodeType::Expression(ExpressionType::Arithmetic),
+            _ => NodeType::Expression(ExpressionType::Comparison),
+        };
+        
+        self.convert_operator_expression(node, source, node_type)
+    }
+    
+    fn convert_operator_expression(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = self.convert_simple(node, source, node_type)?;
+        
+        let mut cursor = node.walk();
+        let operator = node.children(&mut cursor)
+            .find(|c| !c.is_named() || c.kind() == "test_operator")
+            .map(|op| self.node_text(op, source).to_string());
+        if let Some(operator) = operator {
+            uir.metadata.annotations.insert("operator".to_string(), Value::String(operator));
+        }
+        
+        Ok(uir)
+    }
+    
+    fn convert_expansion(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let name = self.find_child_by_kind(node, "variable_name")
+            .or_else(|| self.find_child_by_kind(node, "special_variable_name"))
+            .map(|n| self.node_text(n, source).to_string());
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.semantic_tags.push("expansion".to_string());
+        if name.as_deref().is_some_and(|n| n.chars().all(|c| c.is_ascii_digit())) {
+            metadata.semantic_tags.push("positional_parameter".to_string());
+        }
+        
+        // ${VAR:-default}, ${VAR#prefix}, ...
+        let mut cursor = node.walk();
+        let operator = node.children(&mut cursor)
+            .filter(|c| !c.is_named() && !matches!(c.kind(), "$" | "${" | "}"))
+            .map(|c| self.node_text(c, source).to_string())
+            .next();
+        let mut children = Vec::new();
+        if let Some(operator) = operator {
+            metadata.annotations.insert("expansion_operator".to_string(), Value::String(operator));
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor).skip(1) {
+                children.push(self.ast_to_uir(child, source)?);
+            }
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Variable),
+            name,
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_string(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut uir = self.convert_simple(node, source, NodeType::Expression(ExpressionType::Literal))?;
+        if uir.children.iter().any(|c| c.node_type != NodeType::Expression(ExpressionType::Literal)) {
+            uir.metadata.semantic_tags.push("interpolated".to_string());
+        } else {
+            uir.children.clear();
+        }
+        Ok(uir)
+    }
+    
+    fn convert_named_leaf(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: Some(self.node_text(node, source).to_string()),
+            children: vec![],
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_literal(node, source, &CoalesceLanguage::Bash, &mut uir);
+        Ok(uir)
+    }
+    
+    fn convert_simple(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: None,
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::Bash, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::Bash, &mut uir);
+        Ok(uir)
+    }
+    
+    fn convert_generic(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: self.map_node_type(node.kind()),
+            name: Some(node.kind().to_string()),
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::Bash, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::Bash, &mut uir);
+        Ok(uir)
+    }
+    
+    // Helper methods
+    fn convert_children(&self, node: Node, source: &str) -> Result<Vec<UIRNode>> {
+        let mut children = Vec::new();
+        
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.is_extra() || child.kind() == "comment" {
+                continue;
+            }
+            if matches!(child.kind(), "compound_statement" | "do_group") {
+                children.extend(self.convert_children(child, source)?);
+            } else {
+                children.push(self.ast_to_uir(child, source)?);
+            }
+        }
+        
+        Ok(children)
+    }
+    
+    fn max_positional_parameter(&self, node: Node, source: &str) -> usize {
+        let mut max = 0;
+        if node.kind() == "variable_name" && matches!(node.parent().map(|p| p.kind()), Some("simple_expansion" | "expansion")) {
+            max = self.node_text(node, source).parse().unwrap_or(0);
+        }
+        
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            max = max.max(self.max_positional_parameter(child, source));
+        }
+        max
+    }
+    
+    fn has_keyword(&self, node: Node, keyword: &str) -> bool {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).any(|child| !child.is_named() && child.kind() == keyword);
+        found
+    }
+    
+    fn node_text<'a>(&self, node: Node, source: &'a str) -> &'a str {
+        &source[node.byte_range()]
+    }
+    
+    fn find_child_by_kind<'a>(&self, node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).find(|c| c.kind() == kind);
+        found
+    }
+    
+    fn create_metadata(&self, node: Node, source: &str) -> Metadata {
+        let mut metadata = Metadata {
+            source_language: CoalesceLanguage::Bash,
+            ..Default::default()
+        };
+        metadata.semantic_tags.push(node.kind().to_string());
+        
+        let text = self.node_text(node, source);
+        if text.len() < 100 {
+            metadata.annotations.insert(
+                "original_text".to_string(),
+                Value::String(text.to_string()),
+            );
+        }
+        
+        metadata
+    }
+    
+    fn create_source_location(&self, node: Node) -> Option<SourceLocation> {
+        Some(SourceLocation {
+            file: String::new(),
+            start_line: node.start_position().row as u32 + 1,
+            end_line: node.end_position().row as u32 + 1,
+            start_column: node.start_position().column as u32,
+            end_column: node.end_position().column as u32,
+        })
+    }
+    
+    fn generate_node_id(&self, node: Node, source: &str) -> String {
+        let text = self.node_text(node, source);
+        
+        format!("{}_{}_{}_{}", node.kind(), node.start_position().row, node.start_position().column,
+                text.chars().take(20).collect::<String>()
+                    .replace(|c: char| !c.is_alphanumeric(), "_"))
+    }
+    
+    fn map_node_type(&self, kind: &str) -> NodeType {
+        match kind {
+            "else_clause" | "subshell" | "compound_statement" => NodeType::Statement(StatementType::Expression),
+            "arithmetic_expansion" | "parenthesized_expression" => NodeType::Expression(ExpressionType::Arithmetic),
+            "negated_command" => NodeType::Expression(ExpressionType::Logical),
+            _ => NodeType::Expression(ExpressionType::Literal), // Generic fallback
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_bash_function_and_shebang() {
+        let parser = BashParser::new().unwrap();
+        let source = "#!/bin/bash\ngreet() {\n    echo \"Hello, $1\"\n}\n";
+        
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.metadata.annotations["interpreter"], "/bin/bash");
+        
+        let func = &uir.children[0];
+        assert_eq!(func.node_type, NodeType::Function);
+        assert_eq!(func.name.as_deref(), Some("greet"));
+        assert_eq!(func.metadata.annotations["positional_parameters"], 1);
+        assert_eq!(func.children[0].name.as_deref(), Some("echo"));
+    }
+    
+    #[test]
+    fn test_bash_pipeline() {
+        let parser = BashParser::new().unwrap();
+        let source = "cat input.txt | grep error | wc -l > count.txt\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let pipeline = &uir.children[0];
+        assert_eq!(pipeline.name.as_deref(), Some("pipeline"));
+        assert_eq!(pipeline.metadata.annotations["stages"], 3);
+        assert_eq!(pipeline.metadata.annotations["redirects"][0], "> count.txt");
+        assert_eq!(pipeline.children[1].name.as_deref(), Some("grep"));
+    }
+    
+    #[test]
+    fn test_bash_conditional_and_expansion() {
+        let parser = BashParser::new().unwrap();
+        let source = "NAME=\"${1:-world}\"\nif [ -z \"$NAME\" ]; then\n    exit 1\nfi\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let assignment = &uir.children[0];
+        assert_eq!(assignment.node_type, NodeType::Expression(ExpressionType::Assignment));
+        let expansion = &assignment.children[1].children[0];
+        assert_eq!(expansion.metadata.annotations["expansion_operator"], ":-");
+        
+        let conditional = &uir.children[1];
+        assert_eq!(conditional.node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        assert_eq!(conditional.children[1].node_type, NodeType::Statement(StatementType::Return));
+    }
+}
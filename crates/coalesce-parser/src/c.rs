@@ -1,11 +1,48 @@
-use tree_sitter::{Language, Node, Parser};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::comments;
+use crate::declarators;
+use crate::operators::{annotate_operator, unwrap_groups};
+use crate::literals::annotate_literal;
+use crate::incremental::SyntaxTree;
 use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage, 
-                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser};
+                   ExpressionType, StatementType, LegacyPattern, Result, CoalesceError, Parser as CoalesceParser,
+                   IncrementalParser, IncrementalUpdate, TextEdit};
 use serde_json::Value;
 use std::collections::HashMap;
 
+/// A macro definition as seen by the preprocessor
+#[derive(Debug, Clone)]
+struct Macro {
+    /// `None` for object-like macros, parameter names for function-like ones
+    params: Option<Vec<String>>,
+    body: String,
+}
+
+/// Macro definitions in source order, on top of externally configured defines.
+///
+/// Lookups are positional so `#ifdef` regions see exactly the macros defined
+/// (and not `#undef`-ed) above them.
+struct MacroTable {
+    configured: HashMap<String, Macro>,
+    /// (byte offset, name, definition); `None` records an `#undef`
+    events: Vec<(usize, String, Option<Macro>)>,
+}
+
+impl MacroTable {
+    fn lookup(&self, name: &str, offset: usize) -> Option<&Macro> {
+        let latest = self.events.iter()
+            .rev()
+            .find(|(at, event_name, _)| *at < offset && event_name == name);
+        match latest {
+            Some((_, _, definition)) => definition.as_ref(),
+            None => self.configured.get(name),
+        }
+    }
+}
+
 pub struct CParser {
-    parser: Parser,
+    defines: HashMap<String, String>,
 }
 
 impl CoalesceParser for CParser {
@@ -22,7 +59,7 @@ impl CoalesceParser for CParser {
                 line: 0,
                 column: 0,
             })?;
-            
+        
         let tree = parser.parse(source, None)
             .ok_or_else(|| CoalesceError::ParseError {
                 message: "Failed to parse C source".to_string(),
@@ -30,15 +67,63 @@ impl CoalesceParser for CParser {
                 column: 0,
             })?;
         
-        let root_node = tree.root_node();
-        self.convert_to_uir(source, root_node, 0)
+        self.convert_root(source, tree.root_node())
+    }
+}
+
+impl IncrementalParser for CParser {
+    type Tree = SyntaxTree;
+    
+    fn parse_tree(&self, source: &str) -> Result<(SyntaxTree, UIRNode)> {
+        let tree = SyntaxTree::parse(tree_sitter_c::language(), source)?;
+        let uir = self.convert_root(source, tree.root_node())?;
+        Ok((tree, uir))
+    }
+    
+    fn reparse(&self, previous: &SyntaxTree, edits: &[TextEdit]) -> Result<IncrementalUpdate<SyntaxTree>> {
+        previous.reparse(edits, |source, node| {
+            // Macros defined anywhere in the file still apply to the changed subtree
+            let mut root = node;
+            while let Some(parent) = root.parent() {
+                root = parent;
+            }
+            self.convert_to_uir(source, node, &self.collect_macros(source, root))
+        })
+    }
+    
+    fn to_uir(&self, tree: &SyntaxTree) -> Result<UIRNode> {
+        self.convert_root(tree.source(), tree.root_node())
     }
 }
 
 impl CParser {
     pub fn new() -> Result<Self> {
         // We don't need to store the parser, we'll create it per-parse
-        Ok(Self { parser: tree_sitter::Parser::new() })
+        Ok(Self { defines: HashMap::new() })
+    }
+    
+    fn convert_root(&self, source: &str, root_node: Node) -> Result<UIRNode> {
+        let macros = self.collect_macros(source, root_node);
+        let mut uir = self.convert_to_uir(source, root_node, &macros)?;
+        recovery::summarize(source, root_node, &mut uir);
+        unwrap_groups(&mut uir);
+        uir.make_ids_unique();
+        comments::attach_comments(&mut uir);
+        
+        let mut includes = Vec::new();
+        self.collect_includes(&uir, &mut includes);
+        uir.metadata.dependencies = includes;
+        uir.debug_validate();
+        Ok(uir)
+    }
+    
+    /// Predefine a macro, as `-DNAME=value` would for the compiler.
+    ///
+    /// Configured defines decide which `#ifdef`/`#if` regions are marked
+    /// active; definitions in the source take precedence after their line.
+    pub fn with_define(mut self, name: &str, value: &str) -> Self {
+        self.defines.insert(name.to_string(), value.to_string());
+        self
     }
     
     pub fn new_parser(&mut self) -> Result<UIRNode> {
@@ -46,7 +131,14 @@ impl CParser {
         Ok(UIRNode::new("temp".to_string(), NodeType::Module))
     }
     
-    fn convert_to_uir(&self, source: &str, node: Node, depth: usize) -> Result<UIRNode> {
+    fn convert_to_uir(&self, source: &str, node: Node, macros: &MacroTable) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::C, |child| self.convert_to_uir(source, child, macros));
+        }
+        if comments::is_comment(node) {
+            return Ok(comments::comment_node(node, source, &CoalesceLanguage::C));
+        }
+        
         let node_type = node.kind();
         let start_position = node.start_position();
         let end_position = node.end_position();
@@ -92,10 +184,8 @@ impl CParser {
                 let func_name = self.extract_function_name(source, node);
                 (NodeType::Function, func_name)
             }
-            "parameter_declaration" => {
-                let param_name = self.extract_parameter_name(source, node);
-                (NodeType::Variable, param_name)
-            }
+            // Named, typed and given an ownership by `declarators::annotate_parameter`
+            "parameter_declaration" => (NodeType::Variable, None),
             "identifier" => {
                 let var_name = Some(original_text.clone());
                 (NodeType::Expression(ExpressionType::Variable), var_name)
@@ -109,7 +199,7 @@ impl CParser {
             "return_statement" => {
                 (NodeType::Statement(StatementType::Return), None)
             }
-            "binary_expression" => {
+            "binary_expression" | "unary_expression" => {
                 (NodeType::Expression(ExpressionType::Arithmetic), None)
             }
             "call_expression" => {
@@ -127,6 +217,23 @@ impl CParser {
             "while_statement" => {
                 (NodeType::ControlFlow(coalesce_core::ControlFlowType::Loop(coalesce_core::LoopType::While)), None)
             }
+            "do_statement" => {
+                (NodeType::ControlFlow(coalesce_core::ControlFlowType::Loop(coalesce_core::LoopType::DoWhile)), None)
+            }
+            "preproc_def" => {
+                (NodeType::Constant, self.field_text(source, node, "name"))
+            }
+            "preproc_function_def" => {
+                (NodeType::Function, self.field_text(source, node, "name"))
+            }
+            "preproc_include" => {
+                let path = self.field_text(source, node, "path")
+                    .map(|p| p.trim_matches(|c| c == '"' || c == '<' || c == '>').to_string());
+                (NodeType::Import { path: path.clone().unwrap_or_default(), alias: None, symbols: Vec::new() }, path)
+            }
+            "preproc_if" | "preproc_ifdef" | "preproc_elif" | "preproc_elifdef" | "preproc_else" => {
+                (NodeType::ControlFlow(coalesce_core::ControlFlowType::Conditional), Some(node_type.to_string()))
+            }
             _ => {
                 // For other node types, try to categorize them generically
                 if node_type.contains("statement") {
@@ -146,59 +253,364 @@ impl CParser {
             children: Vec::new(),
             metadata,
             source_location: Some(source_location),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         };
+        annotate_operator(node, source, &CoalesceLanguage::C, &mut uir_node);
+        annotate_literal(node, source, &CoalesceLanguage::C, &mut uir_node);
+        declarators::annotate_parameter(node, source, &mut uir_node);
+        
+        self.annotate_preprocessor(source, node, macros, &mut uir_node);
+        if matches!(uir_node.node_type, NodeType::Import { .. }) {
+            // The header path is the whole of an include; its tokens are not kept
+            return Ok(uir_node);
+        }
         
         // Process children
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            if !child.is_error() {
-                let child_uir = self.convert_to_uir(source, child, depth + 1)?;
-                uir_node.children.push(child_uir);
-            }
+            let child_uir = self.convert_to_uir(source, child, macros)?;
+            uir_node.children.push(child_uir);
         }
         
         Ok(uir_node)
     }
     
-    fn extract_function_name(&self, source: &str, node: Node) -> Option<String> {
+    /// First preprocessing pass: record every `#define` and `#undef` in order
+    fn collect_macros(&self, source: &str, root: Node) -> MacroTable {
+        let configured = self.defines.iter()
+            .map(|(name, value)| (name.clone(), Macro { params: None, body: value.clone() }))
+            .collect();
+        let mut table = MacroTable { configured, events: Vec::new() };
+        self.collect_macro_events(source, root, &mut table.events);
+        table
+    }
+    
+    fn collect_macro_events(&self, source: &str, node: Node, events: &mut Vec<(usize, String, Option<Macro>)>) {
+        match node.kind() {
+            "preproc_def" | "preproc_function_def" => {
+                if let Some(name) = self.field_text(source, node, "name") {
+                    let params = node.child_by_field_name("parameters").map(|params| {
+                        let mut cursor = params.walk();
+                        params.named_children(&mut cursor)
+                            .filter_map(|p| p.utf8_text(source.as_bytes()).ok())
+                            .map(str::to_string)
+                            .collect()
+                    });
+                    let body = self.field_text(source, node, "value").unwrap_or_default().trim().to_string();
+                    events.push((node.start_byte(), name, Some(Macro { params, body })));
+                }
+            }
+            "preproc_call" => {
+                let directive = self.field_text(source, node, "directive").unwrap_or_default();
+                if directive == "#undef" {
+                    if let Some(name) = self.field_text(source, node, "argument") {
+                        events.push((node.start_byte(), name.trim().to_string(), None));
+                    }
+                }
+            }
+            _ => {}
+        }
+        
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            if child.kind() == "identifier" {
-                if let Ok(name) = child.utf8_text(source.as_bytes()) {
-                    return Some(name.to_string());
+            self.collect_macro_events(source, child, events);
+        }
+    }
+    
+    /// Attach preprocessor semantics: macro definitions, includes,
+    /// conditional-compilation regions and macro invocations
+    fn annotate_preprocessor(&self, source: &str, node: Node, macros: &MacroTable, uir_node: &mut UIRNode) {
+        let metadata = &mut uir_node.metadata;
+        match node.kind() {
+            "preproc_def" | "preproc_function_def" => {
+                let value = self.field_text(source, node, "value").unwrap_or_default().trim().to_string();
+                metadata.semantic_tags.push("macro_definition".to_string());
+                metadata.annotations.insert("macro_value".to_string(), Value::String(value));
+                
+                if let Some(params) = node.child_by_field_name("parameters") {
+                    let mut cursor = params.walk();
+                    let names = params.named_children(&mut cursor)
+                        .filter_map(|p| p.utf8_text(source.as_bytes()).ok())
+                        .map(|p| Value::String(p.to_string()))
+                        .collect();
+                    metadata.annotations.insert("macro_kind".to_string(), Value::String("function".to_string()));
+                    metadata.annotations.insert("macro_parameters".to_string(), Value::Array(names));
+                    metadata.legacy_patterns.push(LegacyPattern {
+                        pattern_type: "function_like_macro".to_string(),
+                        original_construct: node.utf8_text(source.as_bytes()).unwrap_or("").to_string(),
+                        modernization_hint: Some("Replace with an inline function or generic".to_string()),
+                        preserve_exactly: false,
+                    });
+                } else {
+                    metadata.annotations.insert("macro_kind".to_string(), Value::String("object".to_string()));
                 }
-            } else if child.kind() == "function_declarator" {
-                // Recursive search in function_declarator
-                return self.extract_function_name(source, child);
             }
+            "preproc_include" => {
+                let system = node.child_by_field_name("path").is_some_and(|p| p.kind() == "system_lib_string");
+                metadata.semantic_tags.push("include".to_string());
+                metadata.annotations.insert("system_header".to_string(), Value::Bool(system));
+                if let Some(name) = &uir_node.name {
+                    metadata.dependencies.push(name.clone());
+                }
+            }
+            "preproc_if" | "preproc_ifdef" | "preproc_elif" | "preproc_elifdef" | "preproc_else" => {
+                metadata.semantic_tags.push("conditional_compilation".to_string());
+                if let Some(condition) = self.directive_condition(source, node) {
+                    metadata.annotations.insert("condition".to_string(), Value::String(condition));
+                }
+                
+                // A branch is active when its condition holds and no earlier branch was taken;
+                // conditions on macros we can't evaluate leave the region undetermined
+                let active = match self.earlier_branch_taken(source, node, macros) {
+                    Some(true) => Some(false),
+                    Some(false) => self.branch_condition(source, node, macros),
+                    None => None,
+                };
+                if let Some(active) = active {
+                    metadata.annotations.insert("active".to_string(), Value::Bool(active));
+                }
+            }
+            "call_expression" => {
+                let function = node.child_by_field_name("function")
+                    .filter(|f| f.kind() == "identifier")
+                    .and_then(|f| f.utf8_text(source.as_bytes()).ok());
+                let Some(definition) = function.and_then(|name| macros.lookup(name, node.start_byte())) else {
+                    return;
+                };
+                let Some(params) = &definition.params else {
+                    return;
+                };
+                
+                let arguments: Vec<String> = node.child_by_field_name("arguments")
+                    .map(|args| {
+                        let mut cursor = args.walk();
+                        args.named_children(&mut cursor)
+                            .filter_map(|a| a.utf8_text(source.as_bytes()).ok())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                metadata.semantic_tags.push("macro_invocation".to_string());
+                metadata.annotations.insert(
+                    "macro_expansion".to_string(),
+                    Value::String(self.substitute(&definition.body, params, &arguments)),
+                );
+            }
+            "identifier" => {
+                // Object-like macro uses, but not the names in #define/#ifdef themselves
+                let in_directive = node.parent().is_some_and(|p| p.kind().starts_with("preproc_"));
+                let is_callee = node.parent().is_some_and(|p| p.kind() == "call_expression");
+                if in_directive || is_callee {
+                    return;
+                }
+                let name = node.utf8_text(source.as_bytes()).unwrap_or("");
+                if let Some(definition) = macros.lookup(name, node.start_byte()).filter(|m| m.params.is_none()) {
+                    metadata.semantic_tags.push("macro_reference".to_string());
+                    metadata.annotations.insert("macro_expansion".to_string(), Value::String(definition.body.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// One level of function-like macro expansion (no rescanning)
+    fn substitute(&self, body: &str, params: &[String], arguments: &[String]) -> String {
+        let mut expanded = String::new();
+        let mut word = String::new();
+        for c in body.chars().chain(std::iter::once('\0')) {
+            if c.is_alphanumeric() || c == '_' {
+                word.push(c);
+                continue;
+            }
+            match params.iter().position(|p| *p == word) {
+                Some(index) => expanded.push_str(arguments.get(index).map(String::as_str).unwrap_or("")),
+                None => expanded.push_str(&word),
+            }
+            word.clear();
+            if c != '\0' {
+                expanded.push(c);
+            }
+        }
+        expanded
+    }
+    
+    fn directive_condition(&self, source: &str, node: Node) -> Option<String> {
+        match node.kind() {
+            "preproc_ifdef" | "preproc_elifdef" => {
+                let directive = node.child(0).and_then(|d| d.utf8_text(source.as_bytes()).ok()).unwrap_or("");
+                let name = self.field_text(source, node, "name")?;
+                if directive.ends_with("ndef") {
+                    Some(format!("!defined({})", name))
+                } else {
+                    Some(format!("defined({})", name))
+                }
+            }
+            "preproc_if" | "preproc_elif" => self.field_text(source, node, "condition"),
+            _ => None,
+        }
+    }
+    
+    /// Whether this branch's own condition holds (`#else` always does)
+    fn branch_condition(&self, source: &str, node: Node, macros: &MacroTable) -> Option<bool> {
+        match node.kind() {
+            "preproc_ifdef" | "preproc_elifdef" => {
+                let directive = node.child(0).and_then(|d| d.utf8_text(source.as_bytes()).ok()).unwrap_or("");
+                let name = self.field_text(source, node, "name")?;
+                let defined = macros.lookup(&name, node.start_byte()).is_some();
+                Some(defined != directive.ends_with("ndef"))
+            }
+            "preproc_if" | "preproc_elif" => {
+                let condition = node.child_by_field_name("condition")?;
+                self.evaluate_condition(source, condition, macros, node.start_byte()).map(|v| v != 0)
+            }
+            "preproc_else" => Some(true),
+            _ => None,
         }
-        None
     }
     
-    fn extract_parameter_name(&self, source: &str, node: Node) -> Option<String> {
+    /// Whether any branch before this one in the same `#if` chain was taken
+    fn earlier_branch_taken(&self, source: &str, node: Node, macros: &MacroTable) -> Option<bool> {
+        let Some(parent) = node.parent().filter(|p| {
+            p.child_by_field_name("alternative").is_some_and(|alt| alt.id() == node.id())
+        }) else {
+            return Some(false);
+        };
+        
+        match (self.branch_condition(source, parent, macros), self.earlier_branch_taken(source, parent, macros)) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        }
+    }
+    
+    /// Evaluate an `#if` expression; `None` when it depends on something unknown
+    fn evaluate_condition(&self, source: &str, node: Node, macros: &MacroTable, offset: usize) -> Option<i64> {
+        let text = node.utf8_text(source.as_bytes()).ok()?;
+        match node.kind() {
+            "number_literal" => self.parse_integer(text),
+            "char_literal" => text.chars().nth(1).map(|c| c as i64),
+            "identifier" => match macros.lookup(text, offset) {
+                // Undefined identifiers are 0 in #if
+                None => Some(0),
+                Some(definition) => self.parse_integer(&definition.body),
+            },
+            "preproc_defined" => {
+                let name = node.named_child(0)?.utf8_text(source.as_bytes()).ok()?;
+                Some(macros.lookup(name, offset).is_some() as i64)
+            }
+            "parenthesized_expression" => self.evaluate_condition(source, node.named_child(0)?, macros, offset),
+            "unary_expression" => {
+                let operator = node.child_by_field_name("operator")?.kind();
+                let operand = self.evaluate_condition(source, node.child_by_field_name("argument")?, macros, offset)?;
+                match operator {
+                    "!" => Some((operand == 0) as i64),
+                    "-" => Some(-operand),
+                    "~" => Some(!operand),
+                    _ => Some(operand),
+                }
+            }
+            "binary_expression" => {
+                let operator = node.child_by_field_name("operator")?.kind();
+                let left = self.evaluate_condition(source, node.child_by_field_name("left")?, macros, offset);
+                let right = self.evaluate_condition(source, node.child_by_field_name("right")?, macros, offset);
+                // Short-circuit so `defined(X) && X > 2` works when only one side is known
+                match (operator, left, right) {
+                    ("&&", Some(0), _) | ("&&", _, Some(0)) => return Some(0),
+                    ("||", Some(l), _) | ("||", _, Some(l)) if l != 0 => return Some(1),
+                    _ => {}
+                }
+                let (left, right) = (left?, right?);
+                match operator {
+                    "&&" => Some((left != 0 && right != 0) as i64),
+                    "||" => Some((left != 0 || right != 0) as i64),
+                    "==" => Some((left == right) as i64),
+                    "!=" => Some((left != right) as i64),
+                    "<" => Some((left < right) as i64),
+                    ">" => Some((left > right) as i64),
+                    "<=" => Some((left <= right) as i64),
+                    ">=" => Some((left >= right) as i64),
+                    "+" => left.checked_add(right),
+                    "-" => left.checked_sub(right),
+                    "*" => left.checked_mul(right),
+                    "/" => left.checked_div(right),
+                    "%" => left.checked_rem(right),
+                    "&" => Some(left & right),
+                    "|" => Some(left | right),
+                    "^" => Some(left ^ right),
+                    "<<" => left.checked_shl(right as u32),
+                    ">>" => left.checked_shr(right as u32),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+    
+    /// C integer literal, ignoring `U`/`L` suffixes
+    fn parse_integer(&self, text: &str) -> Option<i64> {
+        let digits = text.trim().trim_end_matches(['u', 'U', 'l', 'L']);
+        if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+            i64::from_str_radix(hex, 16).ok()
+        } else if digits.len() > 1 && digits.starts_with('0') {
+            i64::from_str_radix(&digits[1..], 8).ok()
+        } else {
+            digits.parse().ok()
+        }
+    }
+    
+    fn collect_includes(&self, node: &UIRNode, includes: &mut Vec<String>) {
+        for child in &node.children {
+            if let NodeType::Import { path, .. } = &child.node_type {
+                includes.push(path.clone());
+            }
+            self.collect_includes(child, includes);
+        }
+    }
+    
+    fn field_text(&self, source: &str, node: Node, field: &str) -> Option<String> {
+        node.child_by_field_name(field)
+            .and_then(|child| child.utf8_text(source.as_bytes()).ok())
+            .map(str::to_string)
+    }
+    
+    fn extract_function_name(&self, source: &str, node: Node) -> Option<String> {
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
             if child.kind() == "identifier" {
                 if let Ok(name) = child.utf8_text(source.as_bytes()) {
                     return Some(name.to_string());
                 }
+            } else if child.kind() == "function_declarator" {
+                // Recursive search in function_declarator
+                return self.extract_function_name(source, child);
             }
         }
         None
     }
 }
 
-extern "C" {
-    fn tree_sitter_c() -> Language;
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use coalesce_core::{Operator, Ownership, Walk, walk};
+    
+    fn find_tag<'a>(node: &'a UIRNode, tag: &str) -> Option<&'a UIRNode> {
+        if node.metadata.semantic_tags.iter().any(|t| t == tag) {
+            return Some(node);
+        }
+        node.children.iter().find_map(|c| find_tag(c, tag))
+    }
     
     #[test]
     fn test_simple_c_function() {
-        let mut parser = CParser::new().unwrap();
+        let parser = CParser::new().unwrap();
         let source = "int add(int a, int b) { return a + b; }";
         
         let result = parser.parse(source);
@@ -209,9 +621,24 @@ mod tests {
         assert!(!uir.children.is_empty());
     }
     
+    #[test]
+    fn test_c_compound_assignment_keeps_operator() {
+        let parser = CParser::new().unwrap();
+        let uir = parser.parse("void f(int n) { n -= 1; n <<= 2; n = 0; }").unwrap();
+        
+        let mut operators = Vec::new();
+        walk(&uir, &mut |node: &UIRNode| {
+            if node.node_type == NodeType::Expression(ExpressionType::Assignment) {
+                operators.push(node.operator);
+            }
+            Walk::Continue
+        });
+        assert_eq!(operators, vec![Some(Operator::Subtract), Some(Operator::ShiftLeft), None]);
+    }
+    
     #[test]
     fn test_c_program_with_main() {
-        let mut parser = CParser::new().unwrap();
+        let parser = CParser::new().unwrap();
         let source = r#"
 #include <stdio.h>
 
@@ -220,8 +647,94 @@ int main() {
     return 0;
 }
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
+    
+    #[test]
+    fn test_c_preprocessor_regions_and_macros() {
+        let parser = CParser::new().unwrap().with_define("USE_SSL", "1");
+        let source = r#"
+#include <stdio.h>
+#include "mayaqua.h"
+#define MAX_CONN 64
+#define SQUARE(x) ((x) * (x))
+
+#ifdef USE_SSL
+int secure = 1;
+#else
+int secure = 0;
+#endif
+
+#if MAX_CONN > 128
+int big = 1;
+#endif
+
+int area(int side) { return SQUARE(side) + MAX_CONN; }
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.metadata.dependencies, vec!["stdio.h".to_string(), "mayaqua.h".to_string()]);
+        
+        let square = uir.children.iter().find(|c| c.name.as_deref() == Some("SQUARE")).unwrap();
+        assert_eq!(square.metadata.annotations["macro_kind"], "function");
+        assert_eq!(square.metadata.legacy_patterns[0].pattern_type, "function_like_macro");
+        
+        let regions: Vec<_> = uir.children.iter()
+            .filter(|c| c.metadata.semantic_tags.contains(&"conditional_compilation".to_string()))
+            .collect();
+        assert_eq!(regions[0].metadata.annotations["condition"], "defined(USE_SSL)");
+        assert_eq!(regions[0].metadata.annotations["active"], true);
+        let else_branch = regions[0].children.iter().find(|c| c.name.as_deref() == Some("preproc_else")).unwrap();
+        assert_eq!(else_branch.metadata.annotations["active"], false);
+        assert_eq!(regions[1].metadata.annotations["active"], false);
+        
+        let invocation = find_tag(&uir, "macro_invocation").unwrap();
+        assert_eq!(invocation.metadata.annotations["macro_expansion"], "((side) * (side))");
+        let reference = find_tag(&uir, "macro_reference").unwrap();
+        assert_eq!(reference.metadata.annotations["macro_expansion"], "64");
+    }
+    
+    #[test]
+    fn test_c_unterminated_function_is_recovered() {
+        let parser = CParser::new().unwrap();
+        let source = "int f() {\n  if (x) { y(); \n  z();\n";
+        
+        let uir = parser.parse(source).unwrap();
+        assert!(uir.metadata.semantic_tags.contains(&"partial_parse".to_string()));
+        assert_eq!(uir.metadata.annotations["parse_error_count"], 1);
+        
+        let error = find_tag(&uir, "parse_error").unwrap();
+        assert_eq!(error.metadata.annotations["diagnostic"]["line"], 1);
+        let recovered: Vec<_> = error.children.iter()
+            .filter(|c| c.metadata.semantic_tags.contains(&"recovered".to_string()))
+            .collect();
+        assert_eq!(recovered.len(), 4);
+        assert_eq!(recovered[0].name.as_deref(), Some("f"));
+    }
+    
+    #[test]
+    fn test_c_parameter_types_and_ownership() {
+        let parser = CParser::new().unwrap();
+        let source = "size_t copy(char *dst, const char *src, char * const *argv, size_t n, int items[4]);";
+        
+        fn parameters(node: &UIRNode, found: &mut Vec<(Option<String>, String, Option<Ownership>)>) {
+            if node.metadata.semantic_tags.iter().any(|t| t == "parameter_declaration") {
+                found.push((node.name.clone(), node.metadata.annotations["type"].as_str().unwrap().to_string(), node.ownership));
+            }
+            node.children.iter().for_each(|c| parameters(c, found));
+        }
+        let uir = parser.parse(source).unwrap();
+        let mut found = Vec::new();
+        parameters(&uir, &mut found);
+        let expected = [
+            ("dst", "char*", Ownership::Pointer { mutable: true }),
+            ("src", "const char*", Ownership::Pointer { mutable: false }),
+            ("argv", "char* const*", Ownership::Pointer { mutable: false }),
+            ("n", "size_t", Ownership::ByValue),
+            ("items", "int[4]", Ownership::Pointer { mutable: true }),
+        ];
+        assert_eq!(found, expected.map(|(name, declared, ownership)| (Some(name.to_string()), declared.to_string(), Some(ownership))));
+    }
 }
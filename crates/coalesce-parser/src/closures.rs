@@ -0,0 +1,109 @@
+// Captured-variable analysis shared by the parsers
+//
+// Parsers build closures with an empty capture list and the capture mode their language
+// implies; once the whole tree exists, `annotate_captures` fills in the names each closure
+// uses that are bound by an enclosing function or closure. Module-level names are globals
+// rather than captures, and names bound inside the closure (its parameters and locals) shadow
+// the enclosing ones.
+use coalesce_core::{CaptureMode, ExpressionType, NodeType, UIRNode};
+
+/// A closure whose captures are filled in by `annotate_captures`
+pub(crate) fn closure(capture_mode: CaptureMode) -> NodeType {
+    NodeType::Closure { captures: Vec::new(), capture_mode }
+}
+
+/// Record the captured variables of every closure in `root`
+pub(crate) fn annotate_captures(root: &mut UIRNode) {
+    annotate(root, &[]);
+}
+
+fn annotate(node: &mut UIRNode, enclosing: &[String]) {
+    let opens_scope = matches!(node.node_type, NodeType::Function | NodeType::Closure { .. });
+    let mut scope = enclosing.to_vec();
+    if opens_scope {
+        let mut bound = Vec::new();
+        for child in &node.children {
+            bound_names(child, &mut bound);
+        }
+        if let NodeType::Closure { captures, .. } = &mut node.node_type {
+            let mut used = Vec::new();
+            for child in &node.children {
+                used_names(child, &mut used);
+            }
+            *captures = used.into_iter()
+                .filter(|name| enclosing.contains(name) && !bound.contains(name))
+                .collect();
+        }
+        scope.extend(bound);
+    }
+    for child in &mut node.children {
+        annotate(child, &scope);
+    }
+}
+
+/// Parser kinds of local declarations that the generic tree-sitter conversion keeps as plain
+/// expressions, naming the declared variable in their first child
+const DECLARATOR_TAGS: &[&str] = &["variable_declarator", "init_declarator", "let_declaration", "short_var_declaration", "var_spec"];
+
+/// Parameters and locals declared in `node`, not counting those of nested functions
+fn bound_names(node: &UIRNode, bound: &mut Vec<String>) {
+    match &node.node_type {
+        NodeType::Variable | NodeType::Constant | NodeType::Function => {
+            if let Some(name) = &node.name {
+                push_unique(bound, name);
+            }
+        }
+        NodeType::Closure { .. } | NodeType::Class | NodeType::Interface => return,
+        _ if node.metadata.semantic_tags.iter().any(|t| DECLARATOR_TAGS.contains(&t.as_str())) => {
+            declared_names(node).into_iter().for_each(|name| push_unique(bound, name));
+        }
+        _ => {}
+    }
+    // C and C++ keep a function's parameters under its declarator, itself a Function node
+    let declarator = node.metadata.semantic_tags.iter().any(|t| t == "function_declarator");
+    if node.node_type != NodeType::Function || declarator {
+        for child in &node.children {
+            bound_names(child, bound);
+        }
+    }
+}
+
+/// The names a declarator introduces: its first identifier, or each identifier of a leading
+/// list such as Go's `a, b := ...` (keywords like `let` are skipped as childless literals)
+fn declared_names(node: &UIRNode) -> Vec<&str> {
+    let target = node.children.iter().find(|c| {
+        c.node_type == NodeType::Expression(ExpressionType::Variable) && c.name.is_some() || !c.children.is_empty()
+    });
+    match target {
+        Some(target) if target.name.is_some() => target.name.as_deref().into_iter().collect(),
+        Some(list) => list.children.iter()
+            .filter(|c| c.node_type == NodeType::Expression(ExpressionType::Variable))
+            .filter_map(|c| c.name.as_deref())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Variables referenced in `node`, including from nested closures, in order of first use
+fn used_names(node: &UIRNode, used: &mut Vec<String>) {
+    if node.node_type == NodeType::Expression(ExpressionType::Variable) {
+        // Member accesses such as `self.total` name their parts as children
+        if let Some(name) = node.name.as_deref().filter(|name| is_identifier(name)) {
+            push_unique(used, name);
+        }
+    }
+    for child in &node.children {
+        used_names(child, used);
+    }
+}
+
+fn push_unique(names: &mut Vec<String>, name: &str) {
+    if !names.iter().any(|existing| existing == name) {
+        names.push(name.to_string());
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$') && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
@@ -0,0 +1,1672 @@
+use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, StatementType, LegacyPattern, Result,
+                   Parser as CoalesceParser, Operator, LiteralValue};
+use serde_json::Value;
+use crate::operators::mark_grouped;
+
+/// COBOL verbs that start a statement in the PROCEDURE DIVISION
+const VERBS: &[&str] = &[
+    "ACCEPT", "ADD", "ALTER", "CALL", "CANCEL", "CLOSE", "COMPUTE", "CONTINUE", "DELETE", "DISPLAY",
+    "DIVIDE", "ELSE", "EVALUATE", "EXIT", "GO", "GOBACK", "IF", "INITIALIZE", "INSPECT", "MERGE",
+    "MOVE", "MULTIPLY", "NEXT", "OPEN", "PERFORM", "READ", "RELEASE", "RETURN", "REWRITE", "SEARCH",
+    "SET", "SORT", "START", "STOP", "STRING", "SUBTRACT", "UNSTRING", "WHEN", "WRITE",
+];
+
+/// Figurative constants that behave like literals
+const FIGURATIVE_CONSTANTS: &[&str] = &[
+    "ZERO", "ZEROS", "ZEROES", "SPACE", "SPACES", "HIGH-VALUE", "HIGH-VALUES",
+    "LOW-VALUE", "LOW-VALUES", "QUOTE", "QUOTES", "NULL", "NULLS",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word,
+    Literal,
+    Period,
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    line: u32,
+}
+
+impl Token {
+    fn is_word(&self, word: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(word)
+    }
+    
+    fn upper(&self) -> String {
+        self.text.to_ascii_uppercase()
+    }
+}
+
+/// Move the condition of a loop that tests it last, PERFORM WITH TEST AFTER, behind its body
+fn test_last(loop_node: &mut UIRNode) {
+    if loop_node.node_type == NodeType::ControlFlow(ControlFlowType::Loop(LoopType::DoWhile)) && !loop_node.children.is_empty() {
+        let condition = loop_node.children.remove(0);
+        loop_node.children.push(condition);
+    }
+}
+
+/// COBOL parser for legacy migration.
+///
+/// Handles both fixed-format (sequence area + indicator column) and free-format
+/// sources. Divisions become modules, paragraphs become functions and data items
+/// become (possibly nested) variables annotated with their PIC clause.
+pub struct CobolParser {
+}
+
+impl CoalesceParser for CobolParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::Cobol
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        let lines = self.normalize_lines(source);
+        let tokens = self.tokenize(&lines);
+        let mut uir = self.parse_program(source, &tokens);
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
+    }
+}
+
+impl CobolParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    /// Strip sequence/indicator areas and comments, returning (line number, code) pairs
+    fn normalize_lines(&self, source: &str) -> Vec<(u32, String)> {
+        let fixed_format = source.lines()
+            .filter(|l| !l.trim().is_empty())
+            .all(|l| l.len() > 6 && l.chars().take(6).all(|c| c.is_ascii_digit() || c == ' '));
+        
+        let mut lines: Vec<(u32, String)> = Vec::new();
+        for (index, raw) in source.lines().enumerate() {
+            let line_num = index as u32 + 1;
+            let mut code = if fixed_format {
+                let chars: Vec<char> = raw.chars().collect();
+                let indicator = chars.get(6).copied().unwrap_or(' ');
+                if matches!(indicator, '*' | '/') {
+                    continue;
+                }
+                let content: String = chars.iter().skip(7).take(65).collect();
+                if indicator == '-' {
+                    // Continuation line: glue onto the previous logical line
+                    if let Some(last) = lines.last_mut() {
+                        last.1.push_str(content.trim_start().trim_start_matches(['"', '\'']));
+                    }
+                    continue;
+                }
+                content
+            } else {
+                if raw.trim_start().starts_with('*') {
+                    continue;
+                }
+                raw.to_string()
+            };
+            
+            if let Some(comment_start) = code.find("*>") {
+                code.truncate(comment_start);
+            }
+            lines.push((line_num, code));
+        }
+        
+        lines
+    }
+    
+    fn tokenize(&self, lines: &[(u32, String)]) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        
+        for (line, code) in lines {
+            let chars: Vec<char> = code.chars().collect();
+            let mut i = 0;
+            while i < chars.len() {
+                let c = chars[i];
+                let next_is_space = chars.get(i + 1).is_none_or(|n| n.is_whitespace());
+                
+                if c.is_whitespace() || ((c == ',' || c == ';') && next_is_space) {
+                    i += 1;
+                } else if c == '.' && next_is_space {
+                    tokens.push(Token { kind: TokenKind::Period, text: ".".to_string(), line: *line });
+                    i += 1;
+                } else if c == '(' || c == ')' {
+                    let kind = if c == '(' { TokenKind::LParen } else { TokenKind::RParen };
+                    tokens.push(Token { kind, text: c.to_string(), line: *line });
+                    i += 1;
+                } else if c == '"' || c == '\'' {
+                    let mut text = String::from(c);
+                    i += 1;
+                    while i < chars.len() {
+                        text.push(chars[i]);
+                        if chars[i] == c {
+                            // Doubled quotes are an escaped quote
+                            if chars.get(i + 1) == Some(&c) {
+                                text.push(c);
+                                i += 2;
+                                continue;
+                            }
+                            i += 1;
+                            break;
+                        }
+                        i += 1;
+                    }
+                    tokens.push(Token { kind: TokenKind::Literal, text, line: *line });
+                } else {
+                    let start = i;
+                    while i < chars.len() {
+                        let ch = chars[i];
+                        let at_separator = chars.get(i + 1).is_none_or(|n| n.is_whitespace());
+                        if ch.is_whitespace() || ch == '(' || ch == ')' || ch == '"' || ch == '\''
+                            || (matches!(ch, '.' | ',' | ';') && at_separator) {
+                            break;
+                        }
+                        i += 1;
+                    }
+                    let text: String = chars[start..i].iter().collect();
+                    tokens.push(Token { kind: TokenKind::Word, text, line: *line });
+                }
+            }
+        }
+        
+        tokens
+    }
+    
+    fn parse_program(&self, source: &str, tokens: &[Token]) -> UIRNode {
+        let mut root = self.create_node(NodeType::Module, Some("cobol_program".to_string()), 1, "source_file");
+        root.source_location = Some(SourceLocation {
+            file: String::new(),
+            start_line: 1,
+            end_line: source.lines().count() as u32,
+            start_column: 0,
+            end_column: source.len() as u32,
+        });
+        
+        // Split the token stream at each "<NAME> DIVISION" header
+        let headers: Vec<usize> = (0..tokens.len().saturating_sub(1))
+            .filter(|&i| tokens[i].kind == TokenKind::Word && tokens[i + 1].is_word("DIVISION"))
+            .collect();
+        
+        for (index, &start) in headers.iter().enumerate() {
+            let end = headers.get(index + 1).copied().unwrap_or(tokens.len());
+            let division_name = tokens[start].upper();
+            
+            // Skip past the header sentence ("PROCEDURE DIVISION USING X.")
+            let mut body_start = start + 2;
+            let mut using = Vec::new();
+            while body_start < end && tokens[body_start].kind != TokenKind::Period {
+                if !tokens[body_start].is_word("USING") {
+                    using.push(tokens[body_start].text.clone());
+                }
+                body_start += 1;
+            }
+            let body = &tokens[(body_start + 1).min(end)..end];
+            
+            let mut division = self.create_node(
+                NodeType::Module,
+                Some(format!("{}_DIVISION", division_name)),
+                tokens[start].line,
+                "division",
+            );
+            
+            match division_name.as_str() {
+                "IDENTIFICATION" | "ID" => self.parse_identification(body, &mut root, &mut division),
+                "ENVIRONMENT" => self.parse_environment(body, &mut division),
+                "DATA" => self.parse_data(body, &mut division),
+                "PROCEDURE" => {
+                    if !using.is_empty() {
+                        division.metadata.annotations.insert(
+                            "using".to_string(),
+                            Value::Array(using.into_iter().map(Value::String).collect()),
+                        );
+                    }
+                    self.parse_procedure(body, &mut division);
+                }
+                _ => {}
+            }
+            
+            root.children.push(division);
+        }
+        
+        root
+    }
+    
+    fn parse_identification(&self, tokens: &[Token], root: &mut UIRNode, division: &mut UIRNode) {
+        let sentences = self.split_sentences(tokens);
+        
+        let mut i = 0;
+        while i < sentences.len() {
+            let sentence = &sentences[i];
+            let key = sentence[0].upper();
+            // "PROGRAM-ID. NAME." puts the value in its own sentence
+            let value = if sentence.len() > 1 {
+                self.join_tokens(&sentence[1..])
+            } else if let Some(next) = sentences.get(i + 1) {
+                i += 1;
+                self.join_tokens(next)
+            } else {
+                String::new()
+            };
+            
+            if key == "PROGRAM-ID" {
+                root.name = Some(value.clone());
+            }
+            division.metadata.annotations.insert(key.to_ascii_lowercase().replace('-', "_"), Value::String(value));
+            i += 1;
+        }
+    }
+    
+    fn parse_environment(&self, tokens: &[Token], division: &mut UIRNode) {
+        for sentence in self.split_sentences(tokens) {
+            // SELECT file-name ASSIGN TO external-name
+            let Some(select_pos) = sentence.iter().position(|t| t.is_word("SELECT")) else {
+                continue;
+            };
+            let Some(file_name) = sentence.get(select_pos + 1) else {
+                continue;
+            };
+            
+            let mut file_node = self.create_node(NodeType::Variable, Some(file_name.text.clone()), file_name.line, "file_control");
+            if let Some(assign_pos) = sentence.iter().position(|t| t.is_word("ASSIGN")) {
+                let target = sentence[assign_pos + 1..].iter()
+                    .find(|t| !t.is_word("TO"))
+                    .map(|t| t.text.trim_matches(['"', '\'']).to_string());
+                if let Some(target) = target {
+                    file_node.metadata.annotations.insert("assign_to".to_string(), Value::String(target));
+                }
+            }
+            file_node.metadata.annotations.insert("original_text".to_string(), Value::String(self.join_tokens(&sentence)));
+            division.children.push(file_node);
+        }
+    }
+    
+    fn parse_data(&self, tokens: &[Token], division: &mut UIRNode) {
+        let mut section: Option<UIRNode> = None;
+        let mut stack: Vec<(u32, UIRNode)> = Vec::new();
+        
+        for sentence in self.split_sentences(tokens) {
+            if sentence.len() >= 2 && sentence[1].is_word("SECTION") {
+                let target = section.as_mut().unwrap_or(division);
+                self.unwind_data_stack(&mut stack, target, 0);
+                if let Some(done) = section.take() {
+                    division.children.push(done);
+                }
+                section = Some(self.create_node(
+                    NodeType::Module,
+                    Some(format!("{}_SECTION", sentence[0].upper())),
+                    sentence[0].line,
+                    "section",
+                ));
+                continue;
+            }
+            
+            let target = section.as_mut().unwrap_or(division);
+            
+            if sentence[0].is_word("FD") || sentence[0].is_word("SD") {
+                self.unwind_data_stack(&mut stack, target, 0);
+                let name = sentence.get(1).map(|t| t.text.clone());
+                let mut file_node = self.create_node(NodeType::Class, name, sentence[0].line, "file_description");
+                file_node.metadata.annotations.insert("original_text".to_string(), Value::String(self.join_tokens(&sentence)));
+                // Record layouts that follow (level 01) are nested under the FD
+                stack.push((0, file_node));
+                continue;
+            }
+            
+            let Ok(level) = sentence[0].text.parse::<u32>() else {
+                continue;
+            };
+            let item = self.parse_data_entry(level, &sentence);
+            
+            if level == 88 {
+                // Condition names belong to the item they follow
+                match stack.last_mut() {
+                    Some((_, parent)) => parent.children.push(item),
+                    None => target.children.push(item),
+                }
+                continue;
+            }
+            
+            let min_level = if level == 77 || level == 1 {
+                // Top-level records still nest under an open FD
+                if matches!(stack.first(), Some((0, _))) { 1 } else { 0 }
+            } else {
+                level
+            };
+            self.unwind_data_stack(&mut stack, target, min_level.max(1).min(level));
+            stack.push((level, item));
+        }
+        
+        let target = section.as_mut().unwrap_or(division);
+        self.unwind_data_stack(&mut stack, target, 0);
+        if let Some(done) = section.take() {
+            division.children.push(done);
+        }
+    }
+    
+    /// Pop every item whose level is >= `level`, attaching each one to its parent
+    fn unwind_data_stack(&self, stack: &mut Vec<(u32, UIRNode)>, target: &mut UIRNode, level: u32) {
+        while let Some((top_level, _)) = stack.last() {
+            if *top_level < level || (level > 0 && *top_level == 0) {
+                break;
+            }
+            let (_, mut item) = stack.pop().unwrap();
+            if item.node_type == NodeType::Variable
+                && item.children.iter().any(|c| matches!(c.node_type, NodeType::Variable | NodeType::Class)) {
+                // Group items are record structures
+                item.node_type = NodeType::Class;
+                item.metadata.semantic_tags.push("group_item".to_string());
+            }
+            match stack.last_mut() {
+                Some((_, parent)) => parent.children.push(item),
+                None => target.children.push(item),
+            }
+        }
+    }
+    
+    fn parse_data_entry(&self, level: u32, sentence: &[&Token]) -> UIRNode {
+        let has_name = sentence.get(1).is_some_and(|t| !self.is_data_clause(&t.upper()));
+        let name = if has_name { sentence[1].text.clone() } else { "FILLER".to_string() };
+        let node_type = if level == 88 { NodeType::Constant } else { NodeType::Variable };
+        let mut item = self.create_node(node_type, Some(name), sentence[0].line, "data_item");
+        
+        let annotations = &mut item.metadata.annotations;
+        annotations.insert("level".to_string(), Value::from(level));
+        annotations.insert("original_text".to_string(), Value::String(self.join_tokens(sentence)));
+        
+        let mut i = if has_name { 2 } else { 1 };
+        while i < sentence.len() {
+            let word = sentence[i].upper();
+            match word.as_str() {
+                "PIC" | "PICTURE" => {
+                    i += 1;
+                    if sentence.get(i).is_some_and(|t| t.is_word("IS")) {
+                        i += 1;
+                    }
+                    // "9(7)V99" is tokenized as 9 ( 7 ) V99
+                    let mut picture = String::new();
+                    while let Some(t) = sentence.get(i) {
+                        let continues = picture.is_empty()
+                            || matches!(t.kind, TokenKind::LParen | TokenKind::RParen)
+                            || picture.ends_with('(')
+                            || picture.ends_with(')') && !self.is_data_clause(&t.upper());
+                        if !continues {
+                            break;
+                        }
+                        picture.push_str(&t.text);
+                        i += 1;
+                    }
+                    self.annotate_picture(&mut item, &picture);
+                    continue;
+                }
+                "VALUE" | "VALUES" => {
+                    i += 1;
+                    while sentence.get(i).is_some_and(|t| t.is_word("IS") || t.is_word("ARE")) {
+                        i += 1;
+                    }
+                    let start = i;
+                    while i < sentence.len() && !self.is_data_clause(&sentence[i].upper()) {
+                        i += 1;
+                    }
+                    item.metadata.annotations.insert(
+                        "value".to_string(),
+                        Value::String(self.join_tokens(&sentence[start..i])),
+                    );
+                    // A single literal initializes the item; condition names' values are not initializers
+                    if let [token] = &sentence[start..i] {
+                        let operand = self.create_operand(token);
+                        if level != 88 && operand.node_type == NodeType::Expression(ExpressionType::Literal) {
+                            item.children.push(operand);
+                        }
+                    }
+                    continue;
+                }
+                "OCCURS" => {
+                    if let Some(count) = sentence.get(i + 1) {
+                        item.metadata.annotations.insert("occurs".to_string(), Value::String(count.text.clone()));
+                        item.metadata.semantic_tags.push("array".to_string());
+                    }
+                }
+                "REDEFINES" => {
+                    if let Some(other) = sentence.get(i + 1) {
+                        item.metadata.annotations.insert("redefines".to_string(), Value::String(other.text.clone()));
+                        item.metadata.legacy_patterns.push(self.legacy_pattern(
+                            "redefines",
+                            &self.join_tokens(sentence),
+                            "Model overlapping storage as a tagged union or explicit conversion",
+                            true,
+                        ));
+                    }
+                }
+                "COMP" | "COMP-1" | "COMP-2" | "COMP-3" | "COMP-4" | "COMP-5" | "COMPUTATIONAL"
+                | "COMPUTATIONAL-3" | "BINARY" | "PACKED-DECIMAL" | "DISPLAY" | "INDEX" => {
+                    item.metadata.annotations.insert("usage".to_string(), Value::String(word.clone()));
+                    if word == "COMP-3" || word == "COMPUTATIONAL-3" || word == "PACKED-DECIMAL" {
+                        item.metadata.legacy_patterns.push(self.legacy_pattern(
+                            "packed_decimal",
+                            &self.join_tokens(sentence),
+                            "Use a fixed-point decimal type to preserve precision",
+                            true,
+                        ));
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        
+        // A table of OCCURS n items is a list of the item's type
+        if item.metadata.annotations.contains_key("occurs") {
+            if let Some(Value::String(element)) = item.metadata.annotations.get("type").cloned() {
+                item.metadata.annotations.insert("type".to_string(), Value::String(format!("{}[]", element)));
+            }
+        }
+        
+        item
+    }
+    
+    fn annotate_picture(&self, item: &mut UIRNode, picture: &str) {
+        let upper = picture.to_ascii_uppercase();
+        let annotations = &mut item.metadata.annotations;
+        annotations.insert("pic".to_string(), Value::String(upper.clone()));
+        
+        // Expand repeat counts: X(3)9(2) -> XXX99
+        let mut expanded = String::new();
+        let chars: Vec<char> = upper.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] == '(' {
+                let close = chars[i..].iter().position(|&c| c == ')').map(|p| p + i).unwrap_or(chars.len());
+                let count: usize = chars[i + 1..close].iter().collect::<String>().parse().unwrap_or(1);
+                if let Some(last) = expanded.pop() {
+                    expanded.extend(std::iter::repeat_n(last, count));
+                }
+                i = close + 1;
+            } else {
+                expanded.push(chars[i]);
+                i += 1;
+            }
+        }
+        
+        let is_edited = expanded.chars().any(|c| matches!(c, 'Z' | '*' | '$' | ',' | '.' | '+' | '-' | 'B' | '/'));
+        let (pic_type, length, scale) = if expanded.contains('X') || expanded.contains('A') {
+            ("string", expanded.len(), 0)
+        } else if is_edited {
+            ("edited", expanded.len(), 0)
+        } else {
+            let digits = expanded.chars().filter(|&c| c == '9').count();
+            let scale = expanded.split('V').nth(1).map(|f| f.chars().filter(|&c| c == '9').count()).unwrap_or(0);
+            (if scale > 0 { "decimal" } else { "integer" }, digits, scale)
+        };
+        
+        annotations.insert("pic_type".to_string(), Value::String(pic_type.to_string()));
+        // The type generators declare the item with; more than nine digits overflow 32 bits
+        let declared_type = match pic_type {
+            "integer" if length > 9 => "long",
+            "integer" => "int",
+            "decimal" => "decimal",
+            _ => "string",
+        };
+        annotations.insert("type".to_string(), Value::String(declared_type.to_string()));
+        annotations.insert("pic_length".to_string(), Value::from(length));
+        if scale > 0 {
+            annotations.insert("pic_scale".to_string(), Value::from(scale));
+        }
+        if expanded.starts_with('S') {
+            annotations.insert("signed".to_string(), Value::Bool(true));
+        }
+    }
+    
+    fn parse_procedure(&self, tokens: &[Token], division: &mut UIRNode) {
+        let mut section: Option<UIRNode> = None;
+        let mut paragraph: Option<UIRNode> = None;
+        let mut pos = 0;
+        
+        while pos < tokens.len() {
+            let token = &tokens[pos];
+            
+            if token.kind == TokenKind::Period {
+                pos += 1;
+                continue;
+            }
+            
+            let is_header = token.kind == TokenKind::Word && !self.is_verb(&token.upper());
+            if is_header && tokens.get(pos + 1).is_some_and(|t| t.is_word("SECTION")) {
+                self.close_paragraph(&mut paragraph, &mut section, division);
+                if let Some(done) = section.take() {
+                    division.children.push(done);
+                }
+                section = Some(self.create_node(NodeType::Module, Some(token.text.clone()), token.line, "section"));
+                pos += 2;
+                continue;
+            }
+            if is_header && tokens.get(pos + 1).is_some_and(|t| t.kind == TokenKind::Period) {
+                self.close_paragraph(&mut paragraph, &mut section, division);
+                paragraph = Some(self.create_node(NodeType::Function, Some(token.text.clone()), token.line, "paragraph"));
+                pos += 2;
+                continue;
+            }
+            
+            let statement = self.parse_statement(tokens, &mut pos);
+            match (paragraph.as_mut(), section.as_mut()) {
+                (Some(p), _) => p.children.push(statement),
+                (None, Some(s)) => s.children.push(statement),
+                (None, None) => division.children.push(statement),
+            }
+        }
+        
+        self.close_paragraph(&mut paragraph, &mut section, division);
+        if let Some(done) = section.take() {
+            division.children.push(done);
+        }
+    }
+    
+    fn close_paragraph(&self, paragraph: &mut Option<UIRNode>, section: &mut Option<UIRNode>, division: &mut UIRNode) {
+        if let Some(done) = paragraph.take() {
+            match section.as_mut() {
+                Some(s) => s.children.push(done),
+                None => division.children.push(done),
+            }
+        }
+    }
+    
+    /// Parse statements until a period or one of `stop_words` (which is not consumed)
+    fn parse_block(&self, tokens: &[Token], pos: &mut usize, stop_words: &[&str]) -> Vec<UIRNode> {
+        let mut statements = Vec::new();
+        
+        while let Some(token) = tokens.get(*pos) {
+            if token.kind == TokenKind::Period || stop_words.iter().any(|w| token.is_word(w)) {
+                break;
+            }
+            statements.push(self.parse_statement(tokens, pos));
+        }
+        
+        statements
+    }
+    
+    fn parse_statement(&self, tokens: &[Token], pos: &mut usize) -> UIRNode {
+        let token = &tokens[*pos];
+        let verb = token.upper();
+        let line = token.line;
+        let start = *pos;
+        
+        let mut node = match verb.as_str() {
+            "IF" => self.parse_if(tokens, pos),
+            "EVALUATE" => self.parse_evaluate(tokens, pos),
+            "PERFORM" => self.parse_perform(tokens, pos),
+            "GO" => self.parse_go_to(tokens, pos),
+            "COMPUTE" => self.parse_compute(tokens, pos),
+            "MOVE" => self.parse_move(tokens, pos),
+            "ADD" | "SUBTRACT" | "MULTIPLY" | "DIVIDE" => self.parse_arithmetic_verb(tokens, pos),
+            "CALL" => self.parse_call(tokens, pos),
+            "DISPLAY" | "ACCEPT" => {
+                *pos += 1;
+                let operands = self.collect_operands(tokens, pos);
+                let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(verb.clone()), line, "io_statement");
+                call.children = operands;
+                call
+            }
+            "STOP" | "GOBACK" => {
+                *pos += 1;
+                if tokens.get(*pos).is_some_and(|t| t.is_word("RUN")) {
+                    *pos += 1;
+                }
+                self.create_node(NodeType::Statement(StatementType::Return), None, line, "stop_run")
+            }
+            "EXIT" if tokens.get(*pos + 1).is_some_and(|t| t.is_word("PROGRAM")) => {
+                *pos += 2;
+                self.create_node(NodeType::Statement(StatementType::Return), None, line, "exit_program")
+            }
+            "NEXT" if tokens.get(*pos + 1).is_some_and(|t| t.is_word("SENTENCE")) => {
+                *pos += 2;
+                let mut node = self.create_node(NodeType::Statement(StatementType::Break), Some("NEXT SENTENCE".to_string()), line, "next_sentence");
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "next_sentence",
+                    "NEXT SENTENCE",
+                    "Jumps past the next period; restructure as an explicit branch",
+                    true,
+                ));
+                node
+            }
+            "ALTER" => {
+                *pos += 1;
+                let operands = self.collect_operands(tokens, pos);
+                let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Goto), Some("ALTER".to_string()), line, "alter");
+                node.children = operands;
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "alter",
+                    &self.join_tokens_owned(&tokens[start..*pos]),
+                    "Self-modifying GO TO; replace with a state variable and dispatch",
+                    true,
+                ));
+                node
+            }
+            _ => {
+                // Generic statement: verb followed by its operands
+                *pos += 1;
+                let operands = self.collect_operands(tokens, pos);
+                if tokens.get(*pos).is_some_and(|t| t.is_word(&format!("END-{}", verb))) {
+                    *pos += 1;
+                }
+                let mut node = self.create_node(
+                    NodeType::Statement(StatementType::Expression),
+                    Some(verb.to_ascii_lowercase()),
+                    line,
+                    "statement",
+                );
+                node.children = operands;
+                node
+            }
+        };
+        
+        node.metadata.annotations.entry("original_text".to_string())
+            .or_insert_with(|| Value::String(self.join_tokens_owned(&tokens[start..*pos])));
+        node
+    }
+    
+    fn parse_if(&self, tokens: &[Token], pos: &mut usize) -> UIRNode {
+        let line = tokens[*pos].line;
+        *pos += 1;
+        
+        let condition = self.parse_condition(tokens, pos);
+        if tokens.get(*pos).is_some_and(|t| t.is_word("THEN")) {
+            *pos += 1;
+        }
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("if_statement".to_string()), line, "if");
+        node.children.push(condition);
+        
+        let mut then_block = self.create_node(NodeType::Statement(StatementType::Expression), Some("then".to_string()), line, "block");
+        then_block.children = self.parse_block(tokens, pos, &["ELSE", "END-IF"]);
+        node.children.push(then_block);
+        
+        if tokens.get(*pos).is_some_and(|t| t.is_word("ELSE")) {
+            let else_line = tokens[*pos].line;
+            *pos += 1;
+            let mut else_block = self.create_node(NodeType::Statement(StatementType::Expression), Some("else".to_string()), else_line, "block");
+            else_block.children = self.parse_block(tokens, pos, &["END-IF"]);
+            node.children.push(else_block);
+        }
+        
+        if tokens.get(*pos).is_some_and(|t| t.is_word("END-IF")) {
+            *pos += 1;
+        }
+        
+        node
+    }
+    
+    fn parse_evaluate(&self, tokens: &[Token], pos: &mut usize) -> UIRNode {
+        let line = tokens[*pos].line;
+        *pos += 1;
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Switch), Some("evaluate".to_string()), line, "evaluate");
+        let subject_start = *pos;
+        while tokens.get(*pos).is_some_and(|t| t.kind != TokenKind::Period && !t.is_word("WHEN")) {
+            *pos += 1;
+        }
+        node.children.push(self.parse_expression(&tokens[subject_start..*pos], line));
+        
+        while tokens.get(*pos).is_some_and(|t| t.is_word("WHEN")) {
+            let when_line = tokens[*pos].line;
+            *pos += 1;
+            let value_start = *pos;
+            while tokens.get(*pos).is_some_and(|t| t.kind != TokenKind::Period && !self.is_verb(&t.upper())) {
+                *pos += 1;
+            }
+            
+            let values = &tokens[value_start..*pos];
+            let is_other = values.len() == 1 && values[0].is_word("OTHER");
+            let mut case = self.create_node(
+                NodeType::ControlFlow(ControlFlowType::Conditional),
+                Some(if is_other { "when_other".to_string() } else { "when".to_string() }),
+                when_line,
+                "when",
+            );
+            if !is_other {
+                case.children.push(self.parse_expression(values, when_line));
+            }
+            case.children.extend(self.parse_block(tokens, pos, &["WHEN", "END-EVALUATE"]));
+            node.children.push(case);
+        }
+        
+        if tokens.get(*pos).is_some_and(|t| t.is_word("END-EVALUATE")) {
+            *pos += 1;
+        }
+        
+        node
+    }
+    
+    fn parse_perform(&self, tokens: &[Token], pos: &mut usize) -> UIRNode {
+        let line = tokens[*pos].line;
+        let start = *pos;
+        *pos += 1;
+        
+        // Out-of-line PERFORM names a paragraph; inline PERFORM starts with a loop clause
+        let target = tokens.get(*pos)
+            .filter(|t| t.kind == TokenKind::Word && !t.text.chars().all(|c| c.is_ascii_digit()))
+            .filter(|t| !["VARYING", "UNTIL", "WITH", "TEST"].iter().any(|w| t.is_word(w)))
+            .filter(|t| !self.is_verb(&t.upper()))
+            .map(|t| t.text.clone());
+        
+        let mut call = None;
+        if let Some(name) = target {
+            *pos += 1;
+            let mut call_node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(name.clone()), line, "perform");
+            call_node.metadata.legacy_patterns.push(self.legacy_pattern(
+                "perform",
+                &format!("PERFORM {}", name),
+                "Replace with a direct function call",
+                false,
+            ));
+            
+            if tokens.get(*pos).is_some_and(|t| t.is_word("THRU") || t.is_word("THROUGH")) {
+                *pos += 1;
+                if let Some(end) = tokens.get(*pos) {
+                    call_node.metadata.annotations.insert("thru".to_string(), Value::String(end.text.clone()));
+                    call_node.metadata.legacy_patterns.push(self.legacy_pattern(
+                        "perform_thru",
+                        &format!("PERFORM {} THRU {}", name, end.text),
+                        "Executes a range of paragraphs; merge them or call each in order",
+                        true,
+                    ));
+                    *pos += 1;
+                }
+            }
+            call = Some(call_node);
+        }
+        
+        let loop_node = self.parse_loop_clause(tokens, pos, line);
+        
+        match (loop_node, call) {
+            (Some(mut loop_node), Some(call)) => {
+                loop_node.children.push(call);
+                test_last(&mut loop_node);
+                loop_node.metadata.annotations.insert(
+                    "original_text".to_string(),
+                    Value::String(self.join_tokens_owned(&tokens[start..*pos])),
+                );
+                loop_node
+            }
+            (None, Some(call)) => call,
+            (loop_node, None) => {
+                // Inline PERFORM ... END-PERFORM
+                let mut loop_node = loop_node.unwrap_or_else(|| {
+                    self.create_node(NodeType::Statement(StatementType::Expression), Some("perform_block".to_string()), line, "perform")
+                });
+                loop_node.metadata.annotations.insert(
+                    "original_text".to_string(),
+                    Value::String(self.join_tokens_owned(&tokens[start..*pos])),
+                );
+                loop_node.children.extend(self.parse_block(tokens, pos, &["END-PERFORM"]));
+                if tokens.get(*pos).is_some_and(|t| t.is_word("END-PERFORM")) {
+                    *pos += 1;
+                }
+                test_last(&mut loop_node);
+                loop_node
+            }
+        }
+    }
+    
+    fn parse_loop_clause(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let mut test_after = false;
+        if tokens.get(*pos).is_some_and(|t| t.is_word("WITH") || t.is_word("TEST")) {
+            while tokens.get(*pos).is_some_and(|t| t.is_word("WITH") || t.is_word("TEST")) {
+                *pos += 1;
+            }
+            test_after = tokens.get(*pos).is_some_and(|t| t.is_word("AFTER"));
+            *pos = (*pos + 1).min(tokens.len());
+        }
+        
+        let token = tokens.get(*pos)?;
+        if token.is_word("UNTIL") {
+            *pos += 1;
+            let loop_type = if test_after { LoopType::DoWhile } else { LoopType::While };
+            let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(loop_type)), Some("perform_until".to_string()), line, "perform");
+            let condition = self.parse_condition(tokens, pos);
+            node.children.push(self.negated(condition));
+            return Some(node);
+        }
+        
+        // VARYING i FROM a BY b UNTIL c runs as `for (i = a; !c; i += b)`
+        if token.is_word("VARYING") {
+            *pos += 1;
+            let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For)), Some("perform_varying".to_string()), line, "perform");
+            let keys = ["FROM", "BY", "UNTIL"];
+            let Some(variable) = tokens.get(*pos).map(|t| self.create_operand(t)) else {
+                return Some(node);
+            };
+            node.metadata.annotations.insert("variable".to_string(), Value::String(self.operand_text(&variable)));
+            *pos += 1;
+            let (mut start, mut step, mut condition) = (None, None, None);
+            while let Some(key) = tokens.get(*pos).filter(|t| keys.iter().any(|k| t.is_word(k))) {
+                let key = key.upper();
+                *pos += 1;
+                if key == "UNTIL" {
+                    condition = Some(self.parse_condition(tokens, pos));
+                    break;
+                }
+                if let Some(value) = tokens.get(*pos) {
+                    node.metadata.annotations.insert(key.to_ascii_lowercase(), Value::String(value.text.clone()));
+                    let value = self.create_operand(value);
+                    if key == "FROM" { start = Some(value) } else { step = Some(value) }
+                    *pos += 1;
+                }
+            }
+            
+            let one = || {
+                let mut one = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "literal");
+                one.literal = Some(LiteralValue::Int(1));
+                one.metadata.annotations.insert("original_text".to_string(), Value::String("1".to_string()));
+                one
+            };
+            let mut initializer = self.build_assignment(vec![variable.clone()], start.unwrap_or_else(one), line, "loop_initializer");
+            initializer.metadata.annotations.insert("original_text".to_string(), Value::String(format!("{} = {}", self.operand_text(&variable), self.operand_text(&initializer.children[1]))));
+            node.children.push(initializer);
+            if let Some(condition) = condition {
+                let mut condition = self.negated(condition);
+                condition.metadata.semantic_tags.push("loop_condition".to_string());
+                node.children.push(condition);
+            }
+            let step = step.unwrap_or_else(one);
+            let mut update = self.create_node(NodeType::Expression(ExpressionType::Assignment), None, line, "loop_update");
+            update.operator = Some(Operator::Add);
+            update.metadata.annotations.insert("original_text".to_string(), Value::String(format!("{} += {}", self.operand_text(&variable), self.operand_text(&step))));
+            update.children = vec![variable, step];
+            node.children.push(update);
+            return Some(node);
+        }
+        
+        // n TIMES counts with a counter of its own
+        if tokens.get(*pos + 1).is_some_and(|t| t.is_word("TIMES")) {
+            let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For)), Some("perform_times".to_string()), line, "perform");
+            node.metadata.annotations.insert("times".to_string(), Value::String(token.text.clone()));
+            let counter = || {
+                let mut counter = self.create_node(NodeType::Expression(ExpressionType::Variable), Some("PERFORM-COUNT".to_string()), line, "identifier");
+                counter.metadata.annotations.insert("original_text".to_string(), Value::String("PERFORM-COUNT".to_string()));
+                counter
+            };
+            let number = |value: i64| {
+                let mut number = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "literal");
+                number.literal = Some(LiteralValue::Int(value));
+                number.metadata.annotations.insert("original_text".to_string(), Value::String(value.to_string()));
+                number
+            };
+            let mut initializer = self.build_assignment(vec![counter()], number(0), line, "loop_initializer");
+            initializer.metadata.annotations.insert("original_text".to_string(), Value::String("PERFORM-COUNT = 0".to_string()));
+            let mut condition = self.comparison(counter(), Operator::Less, self.create_operand(token), line);
+            condition.metadata.semantic_tags.push("loop_condition".to_string());
+            let mut update = self.create_node(NodeType::Expression(ExpressionType::Assignment), None, line, "loop_update");
+            update.operator = Some(Operator::Add);
+            update.metadata.annotations.insert("original_text".to_string(), Value::String("PERFORM-COUNT += 1".to_string()));
+            update.children = vec![counter(), number(1)];
+            node.children.extend([initializer, condition, update]);
+            *pos += 2;
+            return Some(node);
+        }
+        
+        None
+    }
+    
+    fn parse_go_to(&self, tokens: &[Token], pos: &mut usize) -> UIRNode {
+        let line = tokens[*pos].line;
+        let start = *pos;
+        *pos += 1;
+        if tokens.get(*pos).is_some_and(|t| t.is_word("TO")) {
+            *pos += 1;
+        }
+        
+        let mut targets = Vec::new();
+        while let Some(t) = tokens.get(*pos) {
+            if t.kind != TokenKind::Word || t.is_word("DEPENDING") || self.is_verb(&t.upper()) || self.is_scope_terminator(&t.upper()) {
+                break;
+            }
+            targets.push(t.text.clone());
+            *pos += 1;
+        }
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Goto), targets.first().cloned(), line, "go_to");
+        if tokens.get(*pos).is_some_and(|t| t.is_word("DEPENDING")) {
+            *pos += 1;
+            if tokens.get(*pos).is_some_and(|t| t.is_word("ON")) {
+                *pos += 1;
+            }
+            if let Some(selector) = tokens.get(*pos) {
+                node.metadata.annotations.insert("depending_on".to_string(), Value::String(selector.text.clone()));
+                *pos += 1;
+            }
+        }
+        node.metadata.annotations.insert(
+            "targets".to_string(),
+            Value::Array(targets.into_iter().map(Value::String).collect()),
+        );
+        node.metadata.legacy_patterns.push(self.legacy_pattern(
+            "goto",
+            &self.join_tokens_owned(&tokens[start..*pos]),
+            "Restructure into loops, early returns or paragraph calls",
+            true,
+        ));
+        
+        node
+    }
+    
+    fn parse_compute(&self, tokens: &[Token], pos: &mut usize) -> UIRNode {
+        let line = tokens[*pos].line;
+        let start = *pos;
+        *pos += 1;
+        
+        let mut targets = Vec::new();
+        while let Some(t) = tokens.get(*pos) {
+            if t.text == "=" || t.is_word("EQUAL") || t.kind == TokenKind::Period {
+                break;
+            }
+            if !t.is_word("ROUNDED") {
+                targets.push(self.create_operand(t));
+            }
+            *pos += 1;
+        }
+        *pos = (*pos + 1).min(tokens.len());
+        
+        let expr_start = *pos;
+        while tokens.get(*pos).is_some_and(|t| {
+            t.kind != TokenKind::Period && !self.is_verb(&t.upper()) && !self.is_scope_terminator(&t.upper())
+                && !t.is_word("ELSE") && !t.is_word("ON")
+        }) {
+            *pos += 1;
+        }
+        let expression = self.parse_expression(&tokens[expr_start..*pos], line);
+        if tokens.get(*pos).is_some_and(|t| t.is_word("END-COMPUTE")) {
+            *pos += 1;
+        }
+        
+        let mut node = self.build_assignment(targets, expression, line, "compute");
+        node.metadata.legacy_patterns.push(self.legacy_pattern(
+            "compute",
+            &self.join_tokens_owned(&tokens[start..*pos]),
+            "Translate to an assignment with fixed-point decimal arithmetic",
+            false,
+        ));
+        node
+    }
+    
+    fn parse_move(&self, tokens: &[Token], pos: &mut usize) -> UIRNode {
+        let line = tokens[*pos].line;
+        *pos += 1;
+        if tokens.get(*pos).is_some_and(|t| t.is_word("CORRESPONDING") || t.is_word("CORR")) {
+            *pos += 1;
+        }
+        
+        let source = tokens.get(*pos).map(|t| self.create_operand(t));
+        *pos = (*pos + 1).min(tokens.len());
+        self.skip_subscript(tokens, pos);
+        if tokens.get(*pos).is_some_and(|t| t.is_word("TO")) {
+            *pos += 1;
+        }
+        
+        let targets = self.collect_operands(tokens, pos);
+        let value = source.unwrap_or_else(|| self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "literal"));
+        self.build_assignment(targets, value, line, "move")
+    }
+    
+    fn parse_arithmetic_verb(&self, tokens: &[Token], pos: &mut usize) -> UIRNode {
+        let verb = tokens[*pos].upper();
+        let line = tokens[*pos].line;
+        *pos += 1;
+        
+        // ADD a b TO c / SUBTRACT a FROM b / MULTIPLY a BY b / DIVIDE a INTO b [GIVING c]
+        let mut operands = Vec::new();
+        let mut targets = Vec::new();
+        let mut giving = Vec::new();
+        let mut section = 0;
+        while let Some(t) = tokens.get(*pos) {
+            if t.kind == TokenKind::Period || self.is_verb(&t.upper()) || self.is_scope_terminator(&t.upper()) || t.is_word("ON") {
+                break;
+            }
+            match t.upper().as_str() {
+                "TO" | "FROM" | "BY" | "INTO" => section = 1,
+                "GIVING" => section = 2,
+                "ROUNDED" | "REMAINDER" => {}
+                _ if t.kind == TokenKind::LParen || t.kind == TokenKind::RParen => {}
+                _ => match section {
+                    0 => operands.push(self.create_operand(t)),
+                    1 => targets.push(self.create_operand(t)),
+                    _ => giving.push(self.create_operand(t)),
+                },
+            }
+            *pos += 1;
+        }
+        if tokens.get(*pos).is_some_and(|t| t.is_word(&format!("END-{}", verb))) {
+            *pos += 1;
+        }
+        
+        let operator = match verb.as_str() {
+            "ADD" => "+",
+            "SUBTRACT" => "-",
+            "MULTIPLY" => "*",
+            _ => "/",
+        };
+        
+        let (assign_to, mut expr_operands) = if giving.is_empty() {
+            let first_target = targets.first().cloned();
+            (targets, first_target.into_iter().collect::<Vec<_>>())
+        } else {
+            (giving, targets)
+        };
+        // SUBTRACT a FROM b means b - a; DIVIDE a INTO b means b / a
+        if verb == "SUBTRACT" || verb == "DIVIDE" && !expr_operands.is_empty() {
+            expr_operands.extend(operands);
+        } else {
+            operands.extend(expr_operands);
+            expr_operands = operands;
+        }
+        
+        let mut expression = self.create_node(NodeType::Expression(ExpressionType::Arithmetic), None, line, "arithmetic");
+        expression.operator = Operator::binary(operator, &CoalesceLanguage::Cobol);
+        expression.metadata.annotations.insert("operator".to_string(), Value::String(operator.to_string()));
+        let text = expr_operands.iter()
+            .map(|o| o.name.clone().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(&format!(" {} ", operator));
+        expression.metadata.annotations.insert("original_text".to_string(), Value::String(text));
+        expression.children = expr_operands;
+        
+        self.build_assignment(assign_to, expression, line, &verb.to_ascii_lowercase())
+    }
+    
+    fn parse_call(&self, tokens: &[Token], pos: &mut usize) -> UIRNode {
+        let line = tokens[*pos].line;
+        *pos += 1;
+        
+        let program = tokens.get(*pos).map(|t| t.text.trim_matches(['"', '\'']).to_string());
+        *pos = (*pos + 1).min(tokens.len());
+        
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), program.clone(), line, "call");
+        if let Some(program) = program {
+            node.metadata.dependencies.push(program);
+        }
+        if tokens.get(*pos).is_some_and(|t| t.is_word("USING")) {
+            *pos += 1;
+            node.children = self.collect_operands(tokens, pos)
+                .into_iter()
+                .filter(|o| !matches!(o.name.as_deref(), Some("BY" | "REFERENCE" | "CONTENT" | "VALUE")))
+                .collect();
+        }
+        if tokens.get(*pos).is_some_and(|t| t.is_word("END-CALL")) {
+            *pos += 1;
+        }
+        
+        node
+    }
+    
+    fn build_assignment(&self, targets: Vec<UIRNode>, value: UIRNode, line: u32, tag: &str) -> UIRNode {
+        let mut assignments: Vec<UIRNode> = targets.into_iter().map(|target| {
+            let mut assignment = self.create_node(NodeType::Expression(ExpressionType::Assignment), None, line, tag);
+            assignment.children = vec![target, value.clone()];
+            assignment
+        }).collect();
+        
+        if assignments.len() == 1 {
+            return assignments.remove(0);
+        }
+        
+        let mut statement = self.create_node(NodeType::Statement(StatementType::Expression), Some(tag.to_string()), line, tag);
+        statement.children = assignments;
+        statement
+    }
+    
+    /// Collect condition tokens up to the next verb or period and parse them: relations joined
+    /// by AND, OR and NOT, where an abbreviated relation such as `< 10` of `A > 5 AND < 10`
+    /// compares the last subject again
+    fn parse_condition(&self, tokens: &[Token], pos: &mut usize) -> UIRNode {
+        let line = tokens.get(*pos).map(|t| t.line).unwrap_or(0);
+        let start = *pos;
+        while tokens.get(*pos).is_some_and(|t| {
+            t.kind != TokenKind::Period && !self.is_verb(&t.upper()) && !t.is_word("THEN") && !self.is_scope_terminator(&t.upper())
+        }) {
+            *pos += 1;
+        }
+        
+        let condition_tokens = &tokens[start..*pos];
+        let mut cursor = 0;
+        let mut node = self.parse_or(condition_tokens, &mut cursor, line, &mut None)
+            .unwrap_or_else(|| self.create_node(NodeType::Expression(ExpressionType::Comparison), None, line, "condition"));
+        node.metadata.annotations.insert("condition".to_string(), Value::String(self.join_tokens_owned(condition_tokens)));
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(self.join_tokens_owned(condition_tokens)));
+        node
+    }
+    
+    fn parse_or(&self, tokens: &[Token], pos: &mut usize, line: u32, subject: &mut Option<UIRNode>) -> Option<UIRNode> {
+        let mut left = self.parse_and(tokens, pos, line, subject)?;
+        while tokens.get(*pos).is_some_and(|t| t.is_word("OR")) {
+            *pos += 1;
+            let right = self.parse_and(tokens, pos, line, subject)?;
+            left = self.logical(left, Operator::Or, right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_and(&self, tokens: &[Token], pos: &mut usize, line: u32, subject: &mut Option<UIRNode>) -> Option<UIRNode> {
+        let mut left = self.parse_not(tokens, pos, line, subject)?;
+        while tokens.get(*pos).is_some_and(|t| t.is_word("AND")) {
+            *pos += 1;
+            let right = self.parse_not(tokens, pos, line, subject)?;
+            left = self.logical(left, Operator::And, right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_not(&self, tokens: &[Token], pos: &mut usize, line: u32, subject: &mut Option<UIRNode>) -> Option<UIRNode> {
+        // `NOT < 10` negates an abbreviated relation, which parse_relation reads
+        let negates = tokens.get(*pos).is_some_and(|t| t.is_word("NOT"))
+            && self.relational_operator(tokens, &mut (*pos + 1)).is_none();
+        if negates {
+            *pos += 1;
+            let operand = self.parse_not(tokens, pos, line, subject)?;
+            return Some(self.negated(operand));
+        }
+        self.parse_relation(tokens, pos, line, subject)
+    }
+    
+    /// `subject [IS] [NOT] operator value`, a parenthesized condition, or a condition name alone
+    fn parse_relation(&self, tokens: &[Token], pos: &mut usize, line: u32, subject: &mut Option<UIRNode>) -> Option<UIRNode> {
+        if tokens.get(*pos).is_some_and(|t| t.kind == TokenKind::LParen) {
+            let close = self.matching_paren(tokens, *pos);
+            // `(A + B) > C` opens an arithmetic subject rather than a nested condition
+            let arithmetic = self.relational_operator(tokens, &mut (close + 1)).is_some()
+                || tokens.get(close + 1).is_some_and(|t| ["+", "-", "*", "/", "**"].contains(&t.text.as_str()))
+                || tokens.get(close + 1).is_some_and(|t| t.is_word("IS") || t.is_word("NOT"));
+            if !arithmetic {
+                *pos += 1;
+                let mut inner = self.parse_or(&tokens[..close], pos, line, subject)?;
+                *pos = (close + 1).min(tokens.len());
+                mark_grouped(&mut inner);
+                return Some(inner);
+            }
+        }
+        
+        let abbreviated = self.relational_operator(tokens, &mut pos.clone()).is_some()
+            || tokens.get(*pos).is_some_and(|t| t.is_word("NOT"));
+        let left = match (abbreviated, subject.clone()) {
+            (true, Some(left)) => left,
+            _ => self.parse_additive(tokens, pos, line)?,
+        };
+        if tokens.get(*pos).is_some_and(|t| t.is_word("IS")) {
+            *pos += 1;
+        }
+        let negate = tokens.get(*pos).is_some_and(|t| t.is_word("NOT"));
+        if negate {
+            *pos += 1;
+        }
+        let Some(operator) = self.relational_operator(tokens, pos) else {
+            // An 88-level condition name, such as `IF END-OF-FILE`
+            return Some(if negate { self.negated(left) } else { left });
+        };
+        let right = self.parse_additive(tokens, pos, line)?;
+        *subject = Some(left.clone());
+        let relation = self.comparison(left, operator, right, line);
+        Some(if negate { self.negated(relation) } else { relation })
+    }
+    
+    /// `>`, `GREATER THAN`, `LESS THAN OR EQUAL TO`, `EQUALS` and the like, read from `pos`
+    fn relational_operator(&self, tokens: &[Token], pos: &mut usize) -> Option<Operator> {
+        let word = tokens.get(*pos)?.upper();
+        let operator = match word.as_str() {
+            ">" => Operator::Greater,
+            "<" => Operator::Less,
+            "=" => Operator::Equal,
+            ">=" => Operator::GreaterEqual,
+            "<=" => Operator::LessEqual,
+            "<>" => Operator::NotEqual,
+            "GREATER" | "LESS" => {
+                let mut end = *pos + 1;
+                if tokens.get(end).is_some_and(|t| t.is_word("THAN")) {
+                    end += 1;
+                }
+                let or_equal = tokens.get(end).is_some_and(|t| t.is_word("OR")) && tokens.get(end + 1).is_some_and(|t| t.is_word("EQUAL"));
+                if or_equal {
+                    end += 2;
+                    if tokens.get(end).is_some_and(|t| t.is_word("TO")) {
+                        end += 1;
+                    }
+                }
+                *pos = end;
+                return Some(match (word.as_str(), or_equal) {
+                    ("GREATER", false) => Operator::Greater,
+                    ("GREATER", true) => Operator::GreaterEqual,
+                    (_, false) => Operator::Less,
+                    (_, true) => Operator::LessEqual,
+                });
+            }
+            "EQUAL" | "EQUALS" => {
+                *pos += 1;
+                if tokens.get(*pos).is_some_and(|t| t.is_word("TO")) {
+                    *pos += 1;
+                }
+                return Some(Operator::Equal);
+            }
+            _ => return None,
+        };
+        *pos += 1;
+        Some(operator)
+    }
+    
+    /// Index of the `)` closing the `(` at `open`, or the end of `tokens`
+    fn matching_paren(&self, tokens: &[Token], open: usize) -> usize {
+        let mut depth = 0;
+        for (index, token) in tokens.iter().enumerate().skip(open) {
+            match token.kind {
+                TokenKind::LParen => depth += 1,
+                TokenKind::RParen if depth == 1 => return index,
+                TokenKind::RParen => depth -= 1,
+                _ => {}
+            }
+        }
+        tokens.len()
+    }
+    
+    fn comparison(&self, left: UIRNode, operator: Operator, right: UIRNode, line: u32) -> UIRNode {
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::Comparison), None, line, "condition");
+        let text = format!("{} {} {}", self.operand_text(&left), operator.symbol(), self.operand_text(&right));
+        node.operator = Some(operator);
+        node.metadata.annotations.insert("operator".to_string(), Value::String(operator.symbol().to_string()));
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(text));
+        node.children = vec![left, right];
+        node
+    }
+    
+    fn logical(&self, left: UIRNode, operator: Operator, right: UIRNode, line: u32) -> UIRNode {
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::Logical), None, line, "condition");
+        let keyword = if operator == Operator::And { "AND" } else { "OR" };
+        let text = format!("{} {} {}", self.operand_text(&left), keyword, self.operand_text(&right));
+        node.operator = Some(operator);
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(text));
+        node.children = vec![left, right];
+        node
+    }
+    
+    /// `condition` negated: a relation takes the opposite operator, anything else a NOT
+    fn negated(&self, mut condition: UIRNode) -> UIRNode {
+        let opposite = match condition.operator {
+            Some(Operator::Greater) => Some(Operator::LessEqual),
+            Some(Operator::LessEqual) => Some(Operator::Greater),
+            Some(Operator::Less) => Some(Operator::GreaterEqual),
+            Some(Operator::GreaterEqual) => Some(Operator::Less),
+            Some(Operator::Equal) => Some(Operator::NotEqual),
+            Some(Operator::NotEqual) => Some(Operator::Equal),
+            _ => None,
+        };
+        let line = condition.source_location.as_ref().map_or(0, |l| l.start_line);
+        match (opposite, condition.children.len()) {
+            (Some(operator), 2) => {
+                let mut operands = std::mem::take(&mut condition.children).into_iter();
+                match (operands.next(), operands.next()) {
+                    (Some(left), Some(right)) => self.comparison(left, operator, right, line),
+                    _ => condition,
+                }
+            }
+            _ => {
+                let text = format!("NOT {}", self.operand_text(&condition));
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::Logical), None, line, "condition");
+                node.operator = Some(Operator::Not);
+                node.metadata.annotations.insert("original_text".to_string(), Value::String(text));
+                node.children = vec![condition];
+                node
+            }
+        }
+    }
+    
+    /// Parse an arithmetic expression with standard precedence
+    fn parse_expression(&self, tokens: &[Token], line: u32) -> UIRNode {
+        let mut pos = 0;
+        let expression = self.parse_additive(tokens, &mut pos, line);
+        expression.unwrap_or_else(|| self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "empty"))
+    }
+    
+    fn parse_additive(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let mut left = self.parse_multiplicative(tokens, pos, line)?;
+        while let Some(op) = tokens.get(*pos).filter(|t| t.text == "+" || t.text == "-") {
+            let op = op.text.clone();
+            *pos += 1;
+            let right = self.parse_multiplicative(tokens, pos, line)?;
+            left = self.binary(left, &op, right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_multiplicative(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let mut left = self.parse_primary(tokens, pos, line)?;
+        while let Some(op) = tokens.get(*pos).filter(|t| t.text == "*" || t.text == "/" || t.text == "**") {
+            let op = op.text.clone();
+            *pos += 1;
+            let right = self.parse_primary(tokens, pos, line)?;
+            left = self.binary(left, &op, right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_primary(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let token = tokens.get(*pos)?;
+        *pos += 1;
+        if token.kind == TokenKind::LParen {
+            let mut inner = self.parse_additive(tokens, pos, line)?;
+            if tokens.get(*pos).is_some_and(|t| t.kind == TokenKind::RParen) {
+                *pos += 1;
+            }
+            let text = format!("({})", self.operand_text(&inner));
+            inner.metadata.annotations.insert("original_text".to_string(), Value::String(text));
+            mark_grouped(&mut inner);
+            return Some(inner);
+        }
+        Some(self.create_operand(token))
+    }
+    
+    fn binary(&self, left: UIRNode, op: &str, right: UIRNode, line: u32) -> UIRNode {
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::Arithmetic), None, line, "arithmetic");
+        let text = format!(
+            "{} {} {}",
+            self.operand_text(&left),
+            op,
+            self.operand_text(&right),
+        );
+        node.operator = Operator::binary(op, &CoalesceLanguage::Cobol);
+        node.metadata.annotations.insert("operator".to_string(), Value::String(op.to_string()));
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(text));
+        node.children = vec![left, right];
+        node
+    }
+    
+    fn operand_text(&self, node: &UIRNode) -> String {
+        node.metadata.annotations.get("original_text")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .or_else(|| node.name.clone())
+            .unwrap_or_default()
+    }
+    
+    /// Operands of a statement: words and literals up to the next verb, period or scope terminator
+    fn collect_operands(&self, tokens: &[Token], pos: &mut usize) -> Vec<UIRNode> {
+        let mut operands = Vec::new();
+        while let Some(t) = tokens.get(*pos) {
+            if t.kind == TokenKind::Period || self.is_verb(&t.upper()) || self.is_scope_terminator(&t.upper()) {
+                break;
+            }
+            if matches!(t.kind, TokenKind::Word | TokenKind::Literal) && !self.is_noise_word(&t.upper())
+                && !t.text.chars().all(|c| "+-*/".contains(c)) {
+                operands.push(self.create_operand(t));
+            }
+            *pos += 1;
+        }
+        operands
+    }
+    
+    fn skip_subscript(&self, tokens: &[Token], pos: &mut usize) {
+        if tokens.get(*pos).is_some_and(|t| t.kind == TokenKind::LParen) {
+            while tokens.get(*pos).is_some_and(|t| t.kind != TokenKind::RParen) {
+                *pos += 1;
+            }
+            *pos = (*pos + 1).min(tokens.len());
+        }
+    }
+    
+    fn create_operand(&self, token: &Token) -> UIRNode {
+        let upper = token.upper();
+        let is_literal = token.kind == TokenKind::Literal
+            || token.text.parse::<f64>().is_ok()
+            || FIGURATIVE_CONSTANTS.contains(&upper.as_str());
+        
+        let mut node = if is_literal {
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Literal), None, token.line, "literal");
+            node.literal = LiteralValue::parse(&token.text, &CoalesceLanguage::Cobol);
+            node
+        } else {
+            self.create_node(NodeType::Expression(ExpressionType::Variable), Some(token.text.clone()), token.line, "identifier")
+        };
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(token.text.clone()));
+        node
+    }
+    
+    fn split_sentences<'a>(&self, tokens: &'a [Token]) -> Vec<Vec<&'a Token>> {
+        let mut sentences = Vec::new();
+        let mut current = Vec::new();
+        for token in tokens {
+            if token.kind == TokenKind::Period {
+                if !current.is_empty() {
+                    sentences.push(std::mem::take(&mut current));
+                }
+            } else {
+                current.push(token);
+            }
+        }
+        if !current.is_empty() {
+            sentences.push(current);
+        }
+        sentences
+    }
+    
+    fn join_tokens(&self, tokens: &[&Token]) -> String {
+        let mut text = String::new();
+        for token in tokens {
+            let glue = token.kind == TokenKind::RParen || text.ends_with('(') || text.is_empty()
+                || token.kind == TokenKind::LParen && text.ends_with(|c: char| c.is_alphanumeric());
+            if !glue {
+                text.push(' ');
+            }
+            text.push_str(&token.text);
+        }
+        text
+    }
+    
+    fn join_tokens_owned(&self, tokens: &[Token]) -> String {
+        self.join_tokens(&tokens.iter().collect::<Vec<_>>())
+    }
+    
+    fn is_verb(&self, word: &str) -> bool {
+        VERBS.contains(&word)
+    }
+    
+    /// `END-IF`, `END-PERFORM` and the like, which close a verb's scope; a paragraph may be
+    /// named `END-PARA`
+    fn is_scope_terminator(&self, word: &str) -> bool {
+        word.strip_prefix("END-").is_some_and(|verb| self.is_verb(verb))
+    }
+    
+    fn is_data_clause(&self, word: &str) -> bool {
+        matches!(word, "PIC" | "PICTURE" | "VALUE" | "VALUES" | "OCCURS" | "REDEFINES" | "USAGE"
+            | "COMP" | "COMP-1" | "COMP-2" | "COMP-3" | "COMP-4" | "COMP-5" | "COMPUTATIONAL"
+            | "COMPUTATIONAL-3" | "BINARY" | "PACKED-DECIMAL" | "DISPLAY" | "INDEX" | "INDEXED"
+            | "JUSTIFIED" | "JUST" | "SIGN" | "SYNC" | "SYNCHRONIZED" | "BLANK" | "EXTERNAL" | "GLOBAL")
+    }
+    
+    fn is_noise_word(&self, word: &str) -> bool {
+        matches!(word, "TO" | "FROM" | "UPON" | "WITH" | "NO" | "ADVANCING" | "USING" | "INTO" | "GIVING")
+    }
+    
+    fn legacy_pattern(&self, pattern_type: &str, original: &str, hint: &str, preserve_exactly: bool) -> LegacyPattern {
+        LegacyPattern {
+            pattern_type: pattern_type.to_string(),
+            original_construct: original.to_string(),
+            modernization_hint: Some(hint.to_string()),
+            preserve_exactly,
+        }
+    }
+    
+    fn create_node(&self, node_type: NodeType, name: Option<String>, line: u32, tag: &str) -> UIRNode {
+        let id = format!(
+            "{}_{}_{}",
+            tag,
+            line,
+            name.as_deref().unwrap_or("").replace(|c: char| !c.is_alphanumeric(), "_"),
+        );
+        
+        UIRNode {
+            id,
+            node_type,
+            name,
+            children: Vec::new(),
+            metadata: Metadata {
+                source_language: CoalesceLanguage::Cobol,
+                semantic_tags: vec![tag.to_string()],
+                ..Default::default()
+            },
+            source_location: Some(SourceLocation {
+                file: String::new(),
+                start_line: line,
+                end_line: line,
+                start_column: 0,
+                end_column: 0,
+            }),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const INTEREST: &str = r#"
+       IDENTIFICATION DIVISION.
+       PROGRAM-ID. CALCULATE-INTEREST.
+
+       DATA DIVISION.
+       WORKING-STORAGE SECTION.
+       01 WS-PRINCIPAL       PIC 9(7)V99.
+       01 WS-RECORD.
+           05 WS-NAME        PIC X(30).
+           05 WS-COUNT       PIC 99 VALUE 0.
+
+       PROCEDURE DIVISION.
+       MAIN-PARA.
+           PERFORM CALC-PARA.
+           IF WS-COUNT > 10
+               GO TO DONE-PARA
+           END-IF.
+           STOP RUN.
+
+       CALC-PARA.
+           COMPUTE WS-PRINCIPAL = WS-PRINCIPAL * (1 + WS-COUNT / 100).
+
+       DONE-PARA.
+           DISPLAY "DONE".
+"#;
+
+    #[test]
+    fn test_cobol_divisions_and_program_id() {
+        let parser = CobolParser::new().unwrap();
+        let uir = parser.parse(INTEREST).unwrap();
+        
+        assert_eq!(uir.node_type, NodeType::Module);
+        assert_eq!(uir.name.as_deref(), Some("CALCULATE-INTEREST"));
+        assert_eq!(uir.children.len(), 3);
+    }
+    
+    #[test]
+    fn test_cobol_data_items() {
+        let parser = CobolParser::new().unwrap();
+        let uir = parser.parse(INTEREST).unwrap();
+        
+        let storage = &uir.children[1].children[0];
+        assert_eq!(storage.name.as_deref(), Some("WORKING-STORAGE_SECTION"));
+        
+        let principal = &storage.children[0];
+        assert_eq!(principal.metadata.annotations["pic"], "9(7)V99");
+        assert_eq!(principal.metadata.annotations["pic_type"], "decimal");
+        assert_eq!(principal.metadata.annotations["pic_scale"], 2);
+        
+        let record = &storage.children[1];
+        assert_eq!(record.node_type, NodeType::Class);
+        assert_eq!(record.children.len(), 2);
+        assert_eq!(record.children[1].metadata.annotations["value"], "0");
+    }
+    
+    #[test]
+    fn test_cobol_paragraphs_and_legacy_patterns() {
+        let parser = CobolParser::new().unwrap();
+        let uir = parser.parse(INTEREST).unwrap();
+        
+        let procedure = &uir.children[2];
+        let names: Vec<_> = procedure.children.iter().filter_map(|p| p.name.as_deref()).collect();
+        assert_eq!(names, vec!["MAIN-PARA", "CALC-PARA", "DONE-PARA"]);
+        
+        let main = &procedure.children[0];
+        assert_eq!(main.children[0].node_type, NodeType::Expression(ExpressionType::FunctionCall));
+        let goto = &main.children[1].children[1].children[0];
+        assert_eq!(goto.node_type, NodeType::ControlFlow(ControlFlowType::Goto));
+        assert_eq!(goto.metadata.legacy_patterns[0].pattern_type, "goto");
+        
+        let compute = &procedure.children[1].children[0];
+        assert_eq!(compute.node_type, NodeType::Expression(ExpressionType::Assignment));
+        assert_eq!(compute.metadata.legacy_patterns[0].pattern_type, "compute");
+    }
+    
+    #[test]
+    fn test_cobol_conditions_loops_and_tables() {
+        let parser = CobolParser::new().unwrap();
+        let source = "       DATA DIVISION.\n\
+            \x20      WORKING-STORAGE SECTION.\n\
+            \x20      01 WS-TABLE.\n\
+            \x20         05 WS-ITEM PIC 9(3) OCCURS 10 TIMES.\n\
+            \x20      PROCEDURE DIVISION.\n\
+            \x20      MAIN-PARA.\n\
+            \x20          PERFORM VARYING WS-I FROM 1 BY 2 UNTIL WS-I > 10\n\
+            \x20              ADD WS-I TO WS-COUNT\n\
+            \x20          END-PERFORM\n\
+            \x20          IF WS-A IS GREATER THAN 50 AND WS-B < 100 OR NOT (WS-C = 1)\n\
+            \x20              GO TO END-PARA\n\
+            \x20          END-IF.\n\
+            \x20      END-PARA.\n\
+            \x20          STOP RUN.\n";
+        let uir = parser.parse(source).unwrap();
+        
+        let item = &uir.children[0].children[0].children[0].children[0];
+        assert_eq!(item.metadata.annotations["type"], "int[]");
+        assert_eq!(item.metadata.annotations["occurs"], "10");
+        
+        let main = &uir.children[1].children[0];
+        let varying = &main.children[0];
+        let roles: Vec<&str> = varying.children.iter().map(|c| c.metadata.semantic_tags[0].as_str()).collect();
+        assert_eq!(roles, vec!["loop_initializer", "condition", "loop_update", "add"]);
+        // The loop runs until the condition holds
+        assert_eq!(varying.children[1].operator, Some(Operator::LessEqual));
+        assert!(varying.children[1].metadata.semantic_tags.iter().any(|t| t == "loop_condition"));
+        assert_eq!(varying.children[2].operator, Some(Operator::Add));
+        assert_eq!(varying.children[2].children[1].literal, Some(LiteralValue::Int(2)));
+        
+        let condition = &main.children[1].children[0];
+        assert_eq!(condition.operator, Some(Operator::Or));
+        let both = &condition.children[0];
+        assert_eq!(both.operator, Some(Operator::And));
+        assert_eq!(both.children[0].operator, Some(Operator::Greater));
+        assert_eq!(both.children[0].children[0].name.as_deref(), Some("WS-A"));
+        assert_eq!(both.children[1].operator, Some(Operator::Less));
+        assert_eq!(condition.children[1].operator, Some(Operator::NotEqual));
+        
+        let goto = &main.children[1].children[1].children[0];
+        assert_eq!(goto.node_type, NodeType::ControlFlow(ControlFlowType::Goto));
+        assert_eq!(goto.name.as_deref(), Some("END-PARA"));
+        assert_eq!(main.children[1].children[1].children.len(), 1);
+        let names: Vec<_> = uir.children[1].children.iter().filter_map(|p| p.name.as_deref()).collect();
+        assert_eq!(names, vec!["MAIN-PARA", "END-PARA"]);
+    }
+    
+    #[test]
+    fn test_cobol_literal_values() {
+        let parser = CobolParser::new().unwrap();
+        let source = "       PROCEDURE DIVISION.\n           MOVE 'IT''S' TO WS-NAME.\n           MOVE ZERO TO WS-COUNT.\n";
+        let uir = parser.parse(source).unwrap();
+        
+        let moves = &uir.children[0].children;
+        assert_eq!(moves[0].children[1].literal, Some(LiteralValue::String("IT'S".to_string())));
+        assert_eq!(moves[1].children[1].literal, Some(LiteralValue::Int(0)));
+    }
+}
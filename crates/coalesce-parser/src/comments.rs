@@ -0,0 +1,220 @@
+// Comment nodes shared by the tree-sitter parsers
+//
+// Grammars mark comments as extras, so they can appear between any two nodes. Parsers keep
+// those between statements and declarations as `Comment` nodes; once the tree is built,
+// `attach_comments` links each one to the declaration it documents: the one it trails on the
+// same line, or else the next one after it. The text of attached doc comments also becomes
+// the declaration's `documentation`, with C# XML doc markup reduced to plain text.
+use coalesce_core::{CommentStyle, Language, Metadata, NodeType, SourceLocation, UIRNode};
+use serde_json::Value;
+use std::collections::HashMap;
+use tree_sitter::Node;
+
+/// Comment kinds across grammars: `comment`, `line_comment`, `block_comment`, `multiline_comment`
+pub(crate) fn is_comment(node: Node) -> bool {
+    node.kind().ends_with("comment")
+}
+
+pub(crate) fn comment_node(node: Node, source: &str, language: &Language) -> UIRNode {
+    let original = node.utf8_text(source.as_bytes()).unwrap_or("");
+    let (style, text) = parse_comment(original);
+    let start = node.start_position();
+    let end = node.end_position();
+    
+    let mut annotations = HashMap::new();
+    annotations.insert("original_text".to_string(), Value::String(original.to_string()));
+    UIRNode {
+        id: format!("{}_{}_{}", node.kind(), start.row, start.column),
+        node_type: NodeType::Comment { style, text, attached_to: None },
+        name: None,
+        children: Vec::new(),
+        metadata: Metadata {
+            source_language: language.clone(),
+            semantic_tags: vec![node.kind().to_string()],
+            annotations,
+            ..Metadata::default()
+        },
+        source_location: Some(SourceLocation {
+            file: String::new(),
+            start_line: start.row as u32 + 1,
+            end_line: end.row as u32 + 1,
+            start_column: start.column as u32,
+            end_column: end.column as u32,
+        }),
+        operator: None,
+        literal: None,
+        visibility: None,
+        modifiers: Vec::new(),
+        is_async: false,
+        attributes: Vec::new(),
+        ownership: None,
+        documentation: None,
+    }
+}
+
+/// The style of a comment and its text without delimiters or the `*` that leads block lines
+fn parse_comment(original: &str) -> (CommentStyle, String) {
+    let original = original.trim();
+    if let Some(body) = original.strip_prefix("/*").and_then(|b| b.strip_suffix("*/")) {
+        let (style, body) = match body.strip_prefix('*') {
+            Some(doc) if !doc.is_empty() => (CommentStyle::Doc, doc),
+            _ => (CommentStyle::Block, body.strip_prefix('!').unwrap_or(body)),
+        };
+        let lines: Vec<&str> = body.lines()
+            .map(|line| {
+                let line = line.trim();
+                line.strip_prefix("* ").or_else(|| line.strip_prefix('*')).unwrap_or(line)
+            })
+            .collect();
+        let start = lines.iter().position(|l| !l.is_empty()).unwrap_or(lines.len());
+        let end = lines.iter().rposition(|l| !l.is_empty()).map_or(start, |i| i + 1);
+        return (style, lines[start..end].join("\n"));
+    }
+    
+    let (style, body) = if let Some(doc) = original.strip_prefix("///").or_else(|| original.strip_prefix("//!")) {
+        (CommentStyle::Doc, doc)
+    } else if let Some(line) = original.strip_prefix("//") {
+        (CommentStyle::Line, line)
+    } else {
+        (CommentStyle::Line, original.trim_start_matches('#'))
+    };
+    (style, body.trim().to_string())
+}
+
+/// Attach every comment in `root` to the declaration beside it
+pub(crate) fn attach_comments(root: &mut UIRNode) {
+    let targets: Vec<Option<String>> = (0..root.children.len())
+        .map(|index| attachment(&root.children, index))
+        .collect();
+    let mut documentation: Vec<(String, Vec<String>)> = Vec::new();
+    for (child, target) in root.children.iter_mut().zip(targets) {
+        if let NodeType::Comment { style, text, attached_to } = &mut child.node_type {
+            if let (CommentStyle::Doc, Some(id)) = (&*style, &target) {
+                match documentation.iter_mut().find(|(target, _)| target == id) {
+                    Some((_, lines)) => lines.push(text.clone()),
+                    None => documentation.push((id.clone(), vec![text.clone()])),
+                }
+            }
+            *attached_to = target;
+        }
+        attach_comments(child);
+    }
+    for (id, lines) in documentation {
+        if let Some(declaration) = declaration_mut(&mut root.children, &id) {
+            declaration.documentation = Some(plain_documentation(&lines.join("\n")));
+        }
+    }
+}
+
+/// The declaration with `id` among `siblings` or the declarators of a statement among them
+fn declaration_mut<'a>(siblings: &'a mut [UIRNode], id: &str) -> Option<&'a mut UIRNode> {
+    siblings.iter_mut().find_map(|sibling| {
+        if sibling.id == id {
+            Some(sibling)
+        } else {
+            sibling.children.iter_mut().find(|c| c.id == id)
+        }
+    })
+}
+
+/// Doc comment text without the XML markup of C# doc comments: the summary first, then any
+/// remarks, one `name: text` line per parameter and a `Returns:` line
+fn plain_documentation(text: &str) -> String {
+    if !text.trim_start().starts_with('<') {
+        return text.to_string();
+    }
+    let mut sections = Vec::new();
+    for tag in ["summary", "remarks"] {
+        sections.extend(xml_elements(text, tag).into_iter().map(|(_, body)| body));
+    }
+    for (name, body) in xml_elements(text, "param") {
+        sections.push(format!("{}: {}", name.unwrap_or_default(), body));
+    }
+    for (_, body) in xml_elements(text, "returns") {
+        sections.push(format!("Returns: {}", body));
+    }
+    if sections.is_empty() {
+        return strip_tags(text);
+    }
+    sections.join("\n")
+}
+
+/// The `name` attribute and text of each `<tag>` element
+fn xml_elements(text: &str, tag: &str) -> Vec<(Option<String>, String)> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut elements = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let (Some(head_end), Some(end)) = (after.find('>'), after.find(&close)) else { break };
+        if !after.starts_with([' ', '>']) || head_end > end {
+            rest = after;
+            continue;
+        }
+        let name = after[..head_end].split_once("name=\"")
+            .and_then(|(_, value)| value.split('"').next())
+            .map(str::to_string);
+        let body = strip_tags(&after[head_end + 1..end]);
+        elements.push((name, body.split_whitespace().collect::<Vec<_>>().join(" ")));
+        rest = &after[end + close.len()..];
+    }
+    elements
+}
+
+/// `text` without its tags, keeping the target of self-closing references such as
+/// `<see cref="Parse"/>`
+fn strip_tags(text: &str) -> String {
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        plain.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        if tag.ends_with('/') {
+            if let Some(reference) = tag.split('"').nth(1) {
+                plain.push_str(reference);
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    plain.push_str(rest);
+    plain.trim().to_string()
+}
+
+/// The declaration the comment at `index` trails on its line, or the next one after it
+fn attachment(siblings: &[UIRNode], index: usize) -> Option<String> {
+    let comment_line = siblings[index].source_location.as_ref()?.start_line;
+    if let Some(previous) = siblings[..index].iter().rev().find(|s| !is_comment_node(s)) {
+        if previous.source_location.as_ref().is_some_and(|l| l.end_line == comment_line) {
+            return declaration(previous).map(|d| d.id.clone());
+        }
+    }
+    siblings[index + 1..].iter()
+        .find(|s| !is_comment_node(s))
+        .and_then(declaration)
+        .map(|d| d.id.clone())
+}
+
+fn is_comment_node(uir: &UIRNode) -> bool {
+    matches!(uir.node_type, NodeType::Comment { .. })
+}
+
+/// `uir` itself if it declares something, or the first declaration of a statement such as
+/// JavaScript's `const a = 1, b = 2;` that wraps its declarators
+fn declaration(uir: &UIRNode) -> Option<&UIRNode> {
+    let is_declaration = |n: &UIRNode| {
+        matches!(n.node_type, NodeType::Function | NodeType::Class | NodeType::Interface | NodeType::Variable | NodeType::Constant
+            | NodeType::Field { .. } | NodeType::Property { .. } | NodeType::Enum { .. })
+    };
+    if is_declaration(uir) {
+        return Some(uir);
+    }
+    match uir.node_type {
+        NodeType::Statement(_) => uir.children.iter().find(|c| is_declaration(c)),
+        _ => None,
+    }
+}
@@ -1,11 +1,18 @@
-use tree_sitter::{Language, Node, Parser};
-use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage, 
-                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::comments;
+use crate::declarators;
+use crate::closures;
+use crate::operators::{annotate_operator, unwrap_groups};
+use crate::literals::annotate_literal;
+use crate::incremental::SyntaxTree;
+use coalesce_core::{UIRNode, NodeType, CaptureMode, EnumVariant, ImportedSymbol, Metadata, SourceLocation, Language as CoalesceLanguage, 
+                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser,
+                   IncrementalParser, IncrementalUpdate, TextEdit, Visibility, Modifier};
 use serde_json::Value;
 use std::collections::HashMap;
 
 pub struct CppParser {
-    parser: Parser,
 }
 
 impl CoalesceParser for CppParser {
@@ -22,7 +29,7 @@ impl CoalesceParser for CppParser {
                 line: 0,
                 column: 0,
             })?;
-            
+        
         let tree = parser.parse(source, None)
             .ok_or_else(|| CoalesceError::ParseError {
                 message: "Failed to parse C++ source".to_string(),
@@ -30,15 +37,43 @@ impl CoalesceParser for CppParser {
                 column: 0,
             })?;
         
-        let root_node = tree.root_node();
-        self.convert_to_uir(source, root_node, 0)
+        self.convert_root(source, tree.root_node())
+    }
+}
+
+impl IncrementalParser for CppParser {
+    type Tree = SyntaxTree;
+    
+    fn parse_tree(&self, source: &str) -> Result<(SyntaxTree, UIRNode)> {
+        let tree = SyntaxTree::parse(tree_sitter_cpp::language(), source)?;
+        let uir = self.convert_root(source, tree.root_node())?;
+        Ok((tree, uir))
+    }
+    
+    fn reparse(&self, previous: &SyntaxTree, edits: &[TextEdit]) -> Result<IncrementalUpdate<SyntaxTree>> {
+        previous.reparse(edits, |source, node| self.convert_to_uir(source, node))
+    }
+    
+    fn to_uir(&self, tree: &SyntaxTree) -> Result<UIRNode> {
+        self.convert_root(tree.source(), tree.root_node())
     }
 }
 
 impl CppParser {
     pub fn new() -> Result<Self> {
         // We don't need to store the parser, we'll create it per-parse
-        Ok(Self { parser: tree_sitter::Parser::new() })
+        Ok(Self {})
+    }
+    
+    fn convert_root(&self, source: &str, root_node: Node) -> Result<UIRNode> {
+        let mut uir = self.convert_to_uir(source, root_node)?;
+        recovery::summarize(source, root_node, &mut uir);
+        unwrap_groups(&mut uir);
+        uir.make_ids_unique();
+        comments::attach_comments(&mut uir);
+        closures::annotate_captures(&mut uir);
+        uir.debug_validate();
+        Ok(uir)
     }
     
     pub fn new_parser(&mut self) -> Result<UIRNode> {
@@ -46,7 +81,14 @@ impl CppParser {
         Ok(UIRNode::new("temp".to_string(), NodeType::Module))
     }
     
-    fn convert_to_uir(&self, source: &str, node: Node, depth: usize) -> Result<UIRNode> {
+    fn convert_to_uir(&self, source: &str, node: Node) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::Cpp, |child| self.convert_to_uir(source, child));
+        }
+        if comments::is_comment(node) {
+            return Ok(comments::comment_node(node, source, &CoalesceLanguage::Cpp));
+        }
+        
         let node_type = node.kind();
         let start_position = node.start_position();
         let end_position = node.end_position();
@@ -92,18 +134,45 @@ impl CppParser {
                 let func_name = self.extract_function_name(source, node);
                 (NodeType::Function, func_name)
             }
-            "parameter_declaration" => {
-                let param_name = self.extract_parameter_name(source, node);
-                (NodeType::Variable, param_name)
-            }
+            // Named, typed and given an ownership by `declarators::annotate_parameter`
+            "parameter_declaration" | "optional_parameter_declaration" => (NodeType::Variable, None),
             "class_specifier" => {
                 let class_name = self.extract_class_name(source, node);
                 (NodeType::Class, class_name)
             }
+            "struct_specifier" | "union_specifier" if node.child_by_field_name("body").is_some() => {
+                let struct_name = self.extract_class_name(source, node);
+                (NodeType::Class, struct_name)
+            }
+            "enum_specifier" if node.child_by_field_name("body").is_some() => {
+                let enum_name = node.child_by_field_name("name")
+                    .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                    .map(str::to_string);
+                (NodeType::Enum { variants: self.enumerators(source, node) }, enum_name)
+            }
+            // Data members, named and typed per declarator by `convert_member`
+            "field_declaration" if self.declares_data(node) => {
+                (NodeType::Field { field_type: None, default_value: None }, None)
+            }
+            "operator_cast" => {
+                let target = node.child_by_field_name("type")
+                    .and_then(|t| t.utf8_text(source.as_bytes()).ok())
+                    .map(|t| format!("operator {}", t));
+                (NodeType::Function, target)
+            }
+            "template_declaration" => {
+                let entity_name = self.templated_entity(node)
+                    .and_then(|entity| match entity.kind() {
+                        "class_specifier" | "struct_specifier" | "union_specifier" => self.extract_class_name(source, entity),
+                        _ => self.extract_function_name(source, entity),
+                    });
+                (NodeType::Statement(StatementType::Expression), entity_name)
+            }
             "method_definition" => {
                 let method_name = self.extract_function_name(source, node);
                 (NodeType::Function, method_name)
             }
+            "lambda_expression" => (closures::closure(self.lambda_capture_mode(source, node)), None),
             "identifier" => {
                 let var_name = Some(original_text.clone());
                 (NodeType::Expression(ExpressionType::Variable), var_name)
@@ -123,7 +192,7 @@ impl CppParser {
             "return_statement" => {
                 (NodeType::Statement(StatementType::Return), None)
             }
-            "binary_expression" => {
+            "binary_expression" | "unary_expression" => {
                 (NodeType::Expression(ExpressionType::Arithmetic), None)
             }
             "call_expression" => {
@@ -141,13 +210,29 @@ impl CppParser {
             "while_statement" => {
                 (NodeType::ControlFlow(coalesce_core::ControlFlowType::Loop(coalesce_core::LoopType::While)), None)
             }
+            "do_statement" => {
+                (NodeType::ControlFlow(coalesce_core::ControlFlowType::Loop(coalesce_core::LoopType::DoWhile)), None)
+            }
             "try_statement" => {
                 (NodeType::ControlFlow(coalesce_core::ControlFlowType::Try), None)
             }
+            "catch_clause" => {
+                (NodeType::ControlFlow(self.catch_clause(source, node)), None)
+            }
+            "throw_statement" | "throw_expression" => {
+                (NodeType::Statement(StatementType::Throw), None)
+            }
             "namespace_definition" => {
                 let namespace_name = self.extract_namespace_name(source, node);
                 (NodeType::Module, namespace_name)
             }
+            "preproc_include" => {
+                let path = node.child_by_field_name("path")
+                    .and_then(|p| p.utf8_text(source.as_bytes()).ok())
+                    .map(|p| p.trim_matches(|c| c == '"' || c == '<' || c == '>').to_string());
+                (NodeType::Import { path: path.clone().unwrap_or_default(), alias: None, symbols: Vec::new() }, path)
+            }
+            "using_declaration" => self.using_import(source, node),
             _ => {
                 // For other node types, try to categorize them generically
                 if node_type.contains("statement") {
@@ -167,50 +252,475 @@ impl CppParser {
             children: Vec::new(),
             metadata,
             source_location: Some(source_location),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         };
+        annotate_operator(node, source, &CoalesceLanguage::Cpp, &mut uir_node);
+        annotate_literal(node, source, &CoalesceLanguage::Cpp, &mut uir_node);
+        declarators::annotate_parameter(node, source, &mut uir_node);
+        if let NodeType::Import { path, .. } = &uir_node.node_type {
+            // The imported path is the whole of an include or using; its tokens are not kept
+            uir_node.metadata.dependencies.push(path.clone());
+            return Ok(uir_node);
+        }
+        
+        self.annotate_template(source, node, &mut uir_node);
+        self.annotate_operator(source, node, &mut uir_node);
+        self.annotate_modifiers(source, node, &mut uir_node);
+        if matches!(uir_node.node_type, NodeType::Field { .. } | NodeType::Enum { .. }) {
+            // Data members and enumerators are fully described by their node type
+            return Ok(uir_node);
+        }
         
         // Process children
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            if !child.is_error() {
-                let child_uir = self.convert_to_uir(source, child, depth + 1)?;
-                uir_node.children.push(child_uir);
-            }
+            uir_node.children.extend(self.convert_member(source, child)?);
         }
         
         Ok(uir_node)
     }
     
+    /// A node converted on its own, or one field for each declarator of `int x = 0, *next;`
+    fn convert_member(&self, source: &str, node: Node) -> Result<Vec<UIRNode>> {
+        let member = self.convert_to_uir(source, node)?;
+        if !matches!(member.node_type, NodeType::Field { .. }) {
+            return Ok(vec![member]);
+        }
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        
+        // Each initializer follows the declarator it belongs to
+        let mut declarators: Vec<(Node, Option<String>)> = Vec::new();
+        let mut cursor = node.walk();
+        if cursor.goto_first_child() {
+            loop {
+                match cursor.field_name() {
+                    Some("declarator") => declarators.push((cursor.node(), None)),
+                    Some("default_value") => {
+                        if let Some((_, value)) = declarators.last_mut() {
+                            *value = Some(text(cursor.node()));
+                        }
+                    }
+                    _ => {}
+                }
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+        
+        Ok(declarators.into_iter()
+            .map(|(declarator, value)| {
+                // `*next` and `items[8]` keep their pointer and array parts with the type
+                let declared = declarators::declared(node, Some(declarator), source);
+                let name = declared.name.unwrap_or_else(|| text(declarators::name_of(declarator)));
+                let mut field = member.clone();
+                field.node_type = NodeType::Field { field_type: Some(declared.declared_type), default_value: value };
+                field.ownership = Some(declared.ownership);
+                field.id = format!("{}_{}", member.id, name);
+                field.name = Some(name);
+                field
+            })
+            .collect())
+    }
+    
+    /// Whether a member declaration declares data rather than member functions or nested types
+    fn declares_data(&self, node: Node) -> bool {
+        let mut cursor = node.walk();
+        let mut declarators = node.children_by_field_name("declarator", &mut cursor).peekable();
+        declarators.peek().is_some() && declarators.all(|declarator| {
+            let mut current = Some(declarator);
+            while let Some(inner) = current {
+                if inner.kind() == "function_declarator" {
+                    return false;
+                }
+                current = declarators::inner_declarator(inner);
+            }
+            true
+        })
+    }
+    
+    /// The enumerators of an `enum` or `enum class`, with the values given to them
+    fn enumerators(&self, source: &str, node: Node) -> Vec<EnumVariant> {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let Some(body) = node.child_by_field_name("body") else {
+            return Vec::new();
+        };
+        let mut cursor = body.walk();
+        let enumerators = body.named_children(&mut cursor)
+            .filter(|c| c.kind() == "enumerator")
+            .map(|enumerator| EnumVariant {
+                name: enumerator.child_by_field_name("name").map(text).unwrap_or_default(),
+                value: enumerator.child_by_field_name("value").map(text),
+                fields: Vec::new(),
+            })
+            .collect();
+        enumerators
+    }
+    
+    /// `using namespace std;`, which brings in the whole namespace, or `using std::string;`
+    fn using_import(&self, source: &str, node: Node) -> (NodeType, Option<String>) {
+        let mut cursor = node.walk();
+        let is_namespace = node.children(&mut cursor).any(|c| c.kind() == "namespace");
+        let mut cursor = node.walk();
+        let name = node.named_children(&mut cursor)
+            .find(|c| matches!(c.kind(), "identifier" | "qualified_identifier"))
+            .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+            .unwrap_or("");
+        let (path, symbol) = match name.rsplit_once("::") {
+            _ if is_namespace => (name, "*"),
+            Some((path, symbol)) => (path, symbol),
+            None => ("", name),
+        };
+        let import = NodeType::Import { path: path.to_string(), alias: None, symbols: vec![ImportedSymbol::new(symbol)] };
+        (import, Some(path.to_string()))
+    }
+    
     fn extract_function_name(&self, source: &str, node: Node) -> Option<String> {
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            if child.kind() == "identifier" {
-                if let Ok(name) = child.utf8_text(source.as_bytes()) {
-                    return Some(name.to_string());
+            match child.kind() {
+                "identifier" | "field_identifier" | "destructor_name" | "operator_name" => {
+                    if let Ok(name) = child.utf8_text(source.as_bytes()) {
+                        return Some(name.split_whitespace().collect::<Vec<_>>().join(" "));
+                    }
                 }
-            } else if child.kind() == "function_declarator" {
-                // Recursive search in function_declarator
-                return self.extract_function_name(source, child);
+                // Recursive search through declarators: Foo::bar, max<int>, T& operator[]
+                "function_declarator" | "reference_declarator" | "pointer_declarator" => {
+                    return self.extract_function_name(source, child);
+                }
+                "qualified_identifier" | "template_function" => {
+                    let name = child.child_by_field_name("name")?;
+                    if name.kind() == "qualified_identifier" || name.kind() == "template_function" {
+                        return self.extract_function_name(source, child);
+                    }
+                    return name.utf8_text(source.as_bytes()).ok()
+                        .map(|n| n.split_whitespace().collect::<Vec<_>>().join(" "));
+                }
+                _ => {}
             }
         }
         None
     }
     
-    fn extract_parameter_name(&self, source: &str, node: Node) -> Option<String> {
+    /// The declaration a `template <...>` applies to
+    fn templated_entity<'a>(&self, node: Node<'a>) -> Option<Node<'a>> {
         let mut cursor = node.walk();
-        for child in node.children(&mut cursor) {
-            if child.kind() == "identifier" {
-                if let Ok(name) = child.utf8_text(source.as_bytes()) {
-                    return Some(name.to_string());
+        let entity = node.named_children(&mut cursor)
+            .find(|child| child.kind() != "template_parameter_list" && child.kind() != "comment");
+        entity
+    }
+    
+    /// Record template parameters and specializations on the templated declaration
+    fn annotate_template(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        if node.kind() == "template_type" || node.kind() == "template_function" {
+            if let Some(arguments) = node.child_by_field_name("arguments") {
+                uir_node.name = self.extract_class_name(source, node).or_else(|| self.extract_function_name(source, node));
+                uir_node.metadata.annotations.insert("template_arguments".to_string(), self.template_arguments(source, arguments));
+            }
+            return;
+        }
+        
+        let Some(template) = node.parent().filter(|p| p.kind() == "template_declaration") else {
+            return;
+        };
+        if node.kind() == "template_parameter_list" || !node.is_named() || self.templated_entity(template).map(|e| e.id()) != Some(node.id()) {
+            return;
+        }
+        
+        let parameters = template.child_by_field_name("parameters")
+            .map(|list| self.template_parameters(source, list))
+            .unwrap_or_default();
+        let metadata = &mut uir_node.metadata;
+        metadata.semantic_tags.push("generic".to_string());
+        
+        // `template <>` introduces a full specialization; a parameterized template
+        // whose name carries arguments (`struct Vec<T*>`) is a partial one
+        let specialized_name = node.child_by_field_name("name")
+            .filter(|name| name.kind() == "template_type")
+            .or_else(|| self.find_declarator_name(node).filter(|name| name.kind() == "template_function"));
+        let specialization = match (parameters.is_empty(), specialized_name) {
+            (true, _) => Some("full"),
+            (false, Some(_)) => Some("partial"),
+            (false, None) => None,
+        };
+        if let Some(specialization) = specialization {
+            metadata.semantic_tags.push("template_specialization".to_string());
+            metadata.annotations.insert("specialization".to_string(), Value::String(specialization.to_string()));
+        }
+        if let Some(arguments) = specialized_name.and_then(|name| name.child_by_field_name("arguments")) {
+            metadata.annotations.insert("specialization_arguments".to_string(), self.template_arguments(source, arguments));
+        }
+        metadata.annotations.insert("template_parameters".to_string(), Value::Array(parameters));
+    }
+    
+    /// Template parameters as `{name, kind, type?, default?, variadic}` objects
+    fn template_parameters(&self, source: &str, list: Node) -> Vec<Value> {
+        let text = |node: Option<Node>| node.and_then(|n| n.utf8_text(source.as_bytes()).ok()).map(str::to_string);
+        let mut parameters = Vec::new();
+        
+        let mut cursor = list.walk();
+        for param in list.named_children(&mut cursor) {
+            let mut entry = serde_json::Map::new();
+            let (kind, name, param_type, default) = match param.kind() {
+                "type_parameter_declaration" | "variadic_type_parameter_declaration" => {
+                    ("type", text(param.named_child(0)), None, None)
+                }
+                "optional_type_parameter_declaration" => {
+                    ("type", text(param.child_by_field_name("name")), None, text(param.child_by_field_name("default_type")))
+                }
+                "parameter_declaration" | "variadic_parameter_declaration" | "optional_parameter_declaration" => {
+                    let name = param.child_by_field_name("declarator")
+                        .and_then(|d| text(Some(declarators::name_of(d))))
+                        .map(|n| n.trim_start_matches("...").trim().to_string());
+                    ("value", name, text(param.child_by_field_name("type")), text(param.child_by_field_name("default_value")))
+                }
+                "template_template_parameter_declaration" => {
+                    let inner = {
+                        let mut inner_cursor = param.walk();
+                        let inner = param.named_children(&mut inner_cursor).find(|c| c.kind() != "template_parameter_list");
+                        inner
+                    };
+                    ("template", inner.and_then(|i| text(i.named_child(0))), None, None)
+                }
+                _ => continue,
+            };
+            
+            entry.insert("kind".to_string(), Value::String(kind.to_string()));
+            if let Some(name) = name {
+                entry.insert("name".to_string(), Value::String(name));
+            }
+            if let Some(param_type) = param_type {
+                entry.insert("type".to_string(), Value::String(param_type));
+            }
+            if let Some(default) = default {
+                entry.insert("default".to_string(), Value::String(default));
+            }
+            let variadic = param.kind().starts_with("variadic") || text(Some(param)).is_some_and(|t| t.contains("..."));
+            entry.insert("variadic".to_string(), Value::Bool(variadic));
+            parameters.push(Value::Object(entry));
+        }
+        
+        parameters
+    }
+    
+    fn template_arguments(&self, source: &str, arguments: Node) -> Value {
+        let mut cursor = arguments.walk();
+        let values = arguments.named_children(&mut cursor)
+            .filter_map(|arg| arg.utf8_text(source.as_bytes()).ok())
+            .map(|arg| Value::String(arg.to_string()))
+            .collect();
+        Value::Array(values)
+    }
+    
+    /// The innermost name of a declaration's declarator chain
+    fn find_declarator_name<'a>(&self, node: Node<'a>) -> Option<Node<'a>> {
+        let mut current = node.child_by_field_name("declarator")?;
+        loop {
+            match current.kind() {
+                "function_declarator" | "pointer_declarator" | "reference_declarator" => {
+                    current = current.child_by_field_name("declarator")
+                        .or_else(|| current.named_children(&mut current.walk()).find(|c| c.kind() != "type_qualifier"))?;
                 }
+                "qualified_identifier" => current = current.child_by_field_name("name")?,
+                _ => return Some(current),
             }
         }
-        None
+    }
+    
+    /// Record operator overloads (`operator+`, `operator[]`, conversions)
+    fn annotate_operator(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        let (operator, declarator) = match node.kind() {
+            "operator_cast" => {
+                let target = node.child_by_field_name("type").and_then(|t| t.utf8_text(source.as_bytes()).ok()).unwrap_or("");
+                uir_node.metadata.annotations.insert("conversion_type".to_string(), Value::String(target.to_string()));
+                ("conversion".to_string(), node.child_by_field_name("declarator"))
+            }
+            "function_definition" | "function_declarator" => {
+                // Declarations are annotated on their declarator, definitions on the definition
+                if node.kind() == "function_declarator" {
+                    let mut owner = node.parent();
+                    while let Some(parent) = owner.filter(|p| p.kind().ends_with("_declarator")) {
+                        owner = parent.parent();
+                    }
+                    if owner.is_some_and(|o| o.kind() == "function_definition") {
+                        return;
+                    }
+                }
+                let Some(name) = self.find_declarator_name(node)
+                    .or_else(|| Some(node).filter(|n| n.kind() == "function_declarator").and_then(|n| n.child_by_field_name("declarator")))
+                    .filter(|name| name.kind() == "operator_name") else {
+                    return;
+                };
+                let text = name.utf8_text(source.as_bytes()).unwrap_or("");
+                let operator: String = text.trim_start_matches("operator").split_whitespace().collect::<Vec<_>>().join(" ");
+                let mut function = node;
+                if function.kind() != "function_declarator" {
+                    let mut current = node.child_by_field_name("declarator");
+                    while let Some(declarator) = current.filter(|d| d.kind() != "function_declarator") {
+                        current = declarator.child_by_field_name("declarator")
+                            .or_else(|| declarator.named_children(&mut declarator.walk()).find(|c| c.kind().ends_with("declarator")));
+                    }
+                    function = current.unwrap_or(node);
+                }
+                (operator, Some(function))
+            }
+            _ => return,
+        };
+        
+        // Members take the object as an implicit first operand
+        let declared = declarator.and_then(|d| d.child_by_field_name("parameters"))
+            .map(|params| {
+                let mut cursor = params.walk();
+                let count = params.named_children(&mut cursor).filter(|p| p.kind().ends_with("parameter_declaration")).count();
+                count
+            })
+            .unwrap_or(0);
+        let arity = declared + self.is_member(node) as usize;
+        
+        let operator_kind = match operator.as_str() {
+            "conversion" => "conversion",
+            "()" => "call",
+            "[]" => "subscript",
+            "=" => "assignment",
+            "==" | "!=" | "<" | ">" | "<=" | ">=" | "<=>" => "comparison",
+            "++" | "--" => "increment",
+            op if op.starts_with("new") || op.starts_with("delete") => "allocation",
+            op if op.starts_with("\"\"") => "literal",
+            op if op.ends_with('=') => "compound_assignment",
+            _ if arity == 1 => "unary",
+            _ => "binary",
+        };
+        
+        let metadata = &mut uir_node.metadata;
+        metadata.semantic_tags.push("operator_overload".to_string());
+        metadata.annotations.insert("operator".to_string(), Value::String(operator));
+        metadata.annotations.insert("operator_kind".to_string(), Value::String(operator_kind.to_string()));
+        metadata.annotations.insert("arity".to_string(), Value::from(arity));
+    }
+    
+    /// `[=]` and `[x]` copy what the lambda captures; `[&]`, or `&x` anywhere in the list, refer
+    /// to the enclosing variables
+    fn lambda_capture_mode(&self, source: &str, node: Node) -> CaptureMode {
+        let Some(specifier) = node.child_by_field_name("captures") else {
+            return CaptureMode::ByValue;
+        };
+        let mut cursor = specifier.walk();
+        let by_reference = specifier.named_children(&mut cursor)
+            .any(|capture| capture.utf8_text(source.as_bytes()).is_ok_and(|text| text.starts_with('&')));
+        if by_reference { CaptureMode::ByReference } else { CaptureMode::ByValue }
+    }
+    
+    /// `catch (const std::exception& e)`, `catch (int)` or `catch (...)`, which catches anything
+    fn catch_clause(&self, source: &str, node: Node) -> coalesce_core::ControlFlowType {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let parameter = node.child_by_field_name("parameters").and_then(|list| {
+            let mut cursor = list.walk();
+            let parameter = list.named_children(&mut cursor).find(|p| p.kind() == "parameter_declaration");
+            parameter
+        });
+        coalesce_core::ControlFlowType::Catch {
+            exception_types: parameter.and_then(|p| p.child_by_field_name("type")).map(text).into_iter().collect(),
+            binding: parameter.and_then(|p| self.find_declarator_name(p)).map(text),
+        }
+    }
+    
+    /// Record the access level of class members and their `static`, `const`, `virtual`,
+    /// `override` and `final` specifiers. Member function declarations are annotated on
+    /// their declarator as well, since that is the function node.
+    fn annotate_modifiers(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        let declaration = match node.kind() {
+            "function_definition" | "field_declaration" | "declaration" | "class_specifier" | "struct_specifier" => node,
+            "function_declarator" => match node.parent() {
+                Some(parent) if matches!(parent.kind(), "field_declaration" | "declaration") => parent,
+                _ => return,
+            },
+            _ => return,
+        };
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("");
+        
+        let mut cursor = declaration.walk();
+        let mut modifiers: Vec<Modifier> = declaration.children(&mut cursor)
+            .filter(|c| matches!(c.kind(), "storage_class_specifier" | "type_qualifier" | "virtual" | "virtual_specifier"))
+            .filter_map(|c| Modifier::from_keyword(text(c), &CoalesceLanguage::Cpp))
+            .collect();
+        // `override` and `final` follow the parameter list; a trailing `const` there
+        // qualifies `this`, not the declaration
+        let mut current = declaration.child_by_field_name("declarator");
+        while let Some(declarator) = current.filter(|d| d.kind() != "function_declarator") {
+            current = declarator.child_by_field_name("declarator");
+        }
+        if let Some(function) = current {
+            let mut cursor = function.walk();
+            modifiers.extend(function.children(&mut cursor)
+                .filter(|c| c.kind() == "virtual_specifier")
+                .filter_map(|c| Modifier::from_keyword(text(c), &CoalesceLanguage::Cpp)));
+            // Pure virtual: `virtual void f() = 0;`
+            if declaration.child_by_field_name("default_value").is_some_and(|value| text(value) == "0") {
+                modifiers.push(Modifier::Abstract);
+            }
+        }
+        uir_node.modifiers = modifiers;
+        
+        // Members take the last access specifier before them, or the default of the class key
+        let member = declaration.parent().filter(|p| p.kind() == "template_declaration").unwrap_or(declaration);
+        let Some(body) = member.parent().filter(|p| p.kind() == "field_declaration_list") else {
+            return;
+        };
+        let mut previous = member.prev_sibling();
+        while let Some(sibling) = previous {
+            if sibling.kind() == "access_specifier" {
+                uir_node.visibility = Visibility::from_keyword(text(sibling), &CoalesceLanguage::Cpp);
+                return;
+            }
+            previous = sibling.prev_sibling();
+        }
+        let is_class = body.parent().is_some_and(|owner| owner.kind() == "class_specifier");
+        uir_node.visibility = Some(if is_class { Visibility::Private } else { Visibility::Public });
+    }
+    
+    /// Whether a function is a class member (in-class or `Class::method` definitions)
+    fn is_member(&self, node: Node) -> bool {
+        if node.child_by_field_name("declarator").is_some_and(|d| self.has_qualified_name(d)) {
+            return true;
+        }
+        
+        let mut current = node.parent();
+        while let Some(parent) = current {
+            match parent.kind() {
+                "friend_declaration" | "translation_unit" | "namespace_definition" | "compound_statement" => return false,
+                "field_declaration_list" => return true,
+                _ => current = parent.parent(),
+            }
+        }
+        false
+    }
+    
+    fn has_qualified_name(&self, declarator: Node) -> bool {
+        match declarator.kind() {
+            "qualified_identifier" => true,
+            "function_declarator" | "pointer_declarator" | "reference_declarator" => {
+                let mut cursor = declarator.walk();
+                let found = declarator.named_children(&mut cursor).any(|child| self.has_qualified_name(child));
+                found
+            }
+            _ => false,
+        }
     }
     
     fn extract_class_name(&self, source: &str, node: Node) -> Option<String> {
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
+            // Specializations name the class through a template_type: Vec<T*>
+            if child.kind() == "template_type" {
+                return self.extract_class_name(source, child);
+            }
             if child.kind() == "type_identifier" || child.kind() == "identifier" {
                 if let Ok(name) = child.utf8_text(source.as_bytes()) {
                     return Some(name.to_string());
@@ -233,17 +743,14 @@ impl CppParser {
     }
 }
 
-extern "C" {
-    fn tree_sitter_cpp() -> Language;
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use coalesce_core::Ownership;
     
     #[test]
     fn test_simple_cpp_function() {
-        let mut parser = CppParser::new().unwrap();
+        let parser = CppParser::new().unwrap();
         let source = "int add(int a, int b) { return a + b; }";
         
         let result = parser.parse(source);
@@ -256,7 +763,7 @@ mod tests {
     
     #[test]
     fn test_cpp_class() {
-        let mut parser = CppParser::new().unwrap();
+        let parser = CppParser::new().unwrap();
         let source = r#"
 class Calculator {
 public:
@@ -265,14 +772,14 @@ public:
     }
 };
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
     
     #[test]
     fn test_cpp_namespace() {
-        let mut parser = CppParser::new().unwrap();
+        let parser = CppParser::new().unwrap();
         let source = r#"
 namespace math {
     int add(int a, int b) {
@@ -280,8 +787,173 @@ namespace math {
     }
 }
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
+    
+    #[test]
+    fn test_cpp_templates_and_operator_overloads() {
+        let parser = CppParser::new().unwrap();
+        let source = r#"
+template <typename T, int N = 4, typename... Rest>
+class Vec {
+public:
+    Vec operator+(const Vec& other) const;
+    T& operator[](int i);
+    explicit operator bool() const;
+};
+
+template <typename T> struct Vec<T*, 2> {};
+
+Point operator-(const Point& p) { return p; }
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        fn find<'a>(node: &'a UIRNode, predicate: &dyn Fn(&UIRNode) -> bool) -> Vec<&'a UIRNode> {
+            let mut found: Vec<&UIRNode> = node.children.iter().flat_map(|c| find(c, predicate)).collect();
+            if predicate(node) {
+                found.insert(0, node);
+            }
+            found
+        }
+        
+        let templates = find(&uir, &|n| n.metadata.semantic_tags.contains(&"generic".to_string()));
+        assert_eq!(templates[0].name.as_deref(), Some("Vec"));
+        assert_eq!(templates[0].metadata.annotations["template_parameters"], serde_json::json!([
+            {"kind": "type", "name": "T", "variadic": false},
+            {"kind": "value", "name": "N", "type": "int", "default": "4", "variadic": false},
+            {"kind": "type", "name": "Rest", "variadic": true},
+        ]));
+        assert_eq!(templates[1].metadata.annotations["specialization"], "partial");
+        assert_eq!(templates[1].metadata.annotations["specialization_arguments"], serde_json::json!(["T*", "2"]));
+        
+        let operators = find(&uir, &|n| n.metadata.semantic_tags.contains(&"operator_overload".to_string()));
+        let summary: Vec<_> = operators.iter()
+            .map(|n| (n.metadata.annotations["operator"].as_str().unwrap(), n.metadata.annotations["operator_kind"].as_str().unwrap()))
+            .collect();
+        assert_eq!(summary, vec![("+", "binary"), ("[]", "subscript"), ("conversion", "conversion"), ("-", "unary")]);
+        assert_eq!(operators[3].name.as_deref(), Some("operator-"));
+        assert_eq!(operators[2].metadata.annotations["conversion_type"], "bool");
+    }
+    
+    #[test]
+    fn test_cpp_member_access_and_modifiers() {
+        let parser = CppParser::new().unwrap();
+        let source = r#"
+class Shape {
+    int id;
+public:
+    static const int sides = 0;
+    virtual double area() const = 0;
+protected:
+    void draw() final {}
+};
+
+struct Point { int x; };
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let shape = &uir.children[0];
+        let members: Vec<_> = shape.children.iter()
+            .find(|c| c.metadata.semantic_tags.contains(&"field_declaration_list".to_string()))
+            .unwrap()
+            .children.iter()
+            .filter(|c| matches!(c.metadata.semantic_tags[0].as_str(), "field_declaration" | "function_definition"))
+            .map(|c| (c.visibility, c.modifiers.clone()))
+            .collect();
+        assert_eq!(members, vec![
+            (Some(Visibility::Private), vec![]),
+            (Some(Visibility::Public), vec![Modifier::Static, Modifier::Const]),
+            (Some(Visibility::Public), vec![Modifier::Virtual, Modifier::Abstract]),
+            (Some(Visibility::Protected), vec![Modifier::Sealed]),
+        ]);
+        
+        let point = uir.children.iter().find(|c| c.name.as_deref() == Some("Point")).unwrap();
+        let field = &point.children[2].children[1];
+        assert_eq!(field.visibility, Some(Visibility::Public));
+    }
+    
+    #[test]
+    fn test_cpp_lambda_captures() {
+        let parser = CppParser::new().unwrap();
+        let source = r#"
+int total(int base) {
+    int step = 2;
+    auto by_ref = [&](int x) { return x + base + step; };
+    auto by_value = [=](int x) { return x * base; };
+    auto pure = [](int x) { return x; };
+    return by_ref(1) + by_value(2) + pure(3);
+}
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        fn closures(node: &UIRNode, found: &mut Vec<NodeType>) {
+            if matches!(node.node_type, NodeType::Closure { .. }) {
+                found.push(node.node_type.clone());
+            }
+            node.children.iter().for_each(|c| closures(c, found));
+        }
+        let mut found = Vec::new();
+        closures(&uir, &mut found);
+        assert_eq!(found, vec![
+            NodeType::Closure { captures: vec!["base".to_string(), "step".to_string()], capture_mode: CaptureMode::ByReference },
+            NodeType::Closure { captures: vec!["base".to_string()], capture_mode: CaptureMode::ByValue },
+            NodeType::Closure { captures: vec![], capture_mode: CaptureMode::ByValue },
+        ]);
+    }
+    
+    #[test]
+    fn test_cpp_fields_and_enums() {
+        let parser = CppParser::new().unwrap();
+        let source = r#"
+struct Node {
+    int id = 0, count;
+    Node *next;
+    char name[16];
+    void visit(const Node &other, Node &&moved);
+};
+
+enum class Mode : int { Fast, Slow = 4 };
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let body = uir.children[0].children.iter()
+            .find(|c| c.metadata.semantic_tags.contains(&"field_declaration_list".to_string()))
+            .unwrap();
+        let fields: Vec<_> = body.children.iter()
+            .filter_map(|c| match &c.node_type {
+                NodeType::Field { field_type, default_value } => Some((c.name.as_deref(), field_type.as_deref(), default_value.as_deref())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fields, vec![
+            (Some("id"), Some("int"), Some("0")),
+            (Some("count"), Some("int"), None),
+            (Some("next"), Some("Node*"), None),
+            (Some("name"), Some("char[16]"), None),
+        ]);
+        let next = body.children.iter().find(|c| c.name.as_deref() == Some("next")).unwrap();
+        assert_eq!(next.ownership, Some(Ownership::Pointer { mutable: true }));
+        
+        fn parameters(node: &UIRNode, found: &mut Vec<(Option<String>, Option<Ownership>)>) {
+            if node.metadata.semantic_tags[0] == "parameter_declaration" {
+                found.push((node.name.clone(), node.ownership));
+            }
+            node.children.iter().for_each(|c| parameters(c, found));
+        }
+        let mut found = Vec::new();
+        parameters(body, &mut found);
+        assert_eq!(found, vec![
+            (Some("other".to_string()), Some(Ownership::Reference { mutable: false })),
+            (Some("moved".to_string()), Some(Ownership::Owned)),
+        ]);
+        
+        let mode = uir.children.iter().find(|c| c.name.as_deref() == Some("Mode")).unwrap();
+        let NodeType::Enum { variants } = &mode.node_type else {
+            panic!("expected an enum, got {:?}", mode.node_type);
+        };
+        let enumerators: Vec<_> = variants.iter().map(|v| (v.name.as_str(), v.value.as_deref())).collect();
+        assert_eq!(enumerators, vec![("Fast", None), ("Slow", Some("4"))]);
+    }
 }
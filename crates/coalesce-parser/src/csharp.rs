@@ -1,11 +1,27 @@
-use tree_sitter::{Language, Node, Parser};
-use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage, 
-                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::comments;
+use crate::closures;
+use crate::operators::{annotate_operator, unwrap_groups};
+use crate::literals::annotate_literal;
+use crate::incremental::SyntaxTree;
+use coalesce_core::{UIRNode, NodeType, Attribute, CaptureMode, EnumVariant, ImportedSymbol, Metadata, Ownership, SourceLocation, Language as CoalesceLanguage, 
+                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser,
+                   IncrementalParser, IncrementalUpdate, TextEdit, Visibility, Modifier};
 use serde_json::Value;
 use std::collections::HashMap;
 
+/// Enumerable/Queryable operators recognized in LINQ method chains
+const LINQ_OPERATORS: &[&str] = &[
+    "Where", "Select", "SelectMany", "OrderBy", "OrderByDescending", "ThenBy", "ThenByDescending",
+    "GroupBy", "Join", "GroupJoin", "Any", "All", "First", "FirstOrDefault", "Last", "LastOrDefault",
+    "Single", "SingleOrDefault", "Count", "LongCount", "Sum", "Min", "Max", "Average", "Aggregate",
+    "Distinct", "Take", "Skip", "TakeWhile", "SkipWhile", "Reverse", "Zip", "Concat", "Union",
+    "Intersect", "Except", "ToList", "ToArray", "ToDictionary", "ToLookup", "ToHashSet", "AsEnumerable",
+    "Cast", "OfType", "Contains",
+];
+
 pub struct CSharpParser {
-    parser: Parser,
 }
 
 impl CoalesceParser for CSharpParser {
@@ -22,7 +38,7 @@ impl CoalesceParser for CSharpParser {
                 line: 0,
                 column: 0,
             })?;
-            
+        
         let tree = parser.parse(source, None)
             .ok_or_else(|| CoalesceError::ParseError {
                 message: "Failed to parse C# source".to_string(),
@@ -30,18 +46,53 @@ impl CoalesceParser for CSharpParser {
                 column: 0,
             })?;
         
-        let root_node = tree.root_node();
-        self.convert_to_uir(source, root_node, 0)
+        self.convert_root(source, tree.root_node())
+    }
+}
+
+impl IncrementalParser for CSharpParser {
+    type Tree = SyntaxTree;
+    
+    fn parse_tree(&self, source: &str) -> Result<(SyntaxTree, UIRNode)> {
+        let tree = SyntaxTree::parse(tree_sitter_c_sharp::language(), source)?;
+        let uir = self.convert_root(source, tree.root_node())?;
+        Ok((tree, uir))
+    }
+    
+    fn reparse(&self, previous: &SyntaxTree, edits: &[TextEdit]) -> Result<IncrementalUpdate<SyntaxTree>> {
+        previous.reparse(edits, |source, node| self.convert_to_uir(source, node))
+    }
+    
+    fn to_uir(&self, tree: &SyntaxTree) -> Result<UIRNode> {
+        self.convert_root(tree.source(), tree.root_node())
     }
 }
 
 impl CSharpParser {
     pub fn new() -> Result<Self> {
         // We don't need to store the parser, we'll create it per-parse
-        Ok(Self { parser: tree_sitter::Parser::new() })
+        Ok(Self {})
+    }
+    
+    fn convert_root(&self, source: &str, root_node: Node) -> Result<UIRNode> {
+        let mut uir = self.convert_to_uir(source, root_node)?;
+        recovery::summarize(source, root_node, &mut uir);
+        unwrap_groups(&mut uir);
+        uir.make_ids_unique();
+        comments::attach_comments(&mut uir);
+        closures::annotate_captures(&mut uir);
+        uir.debug_validate();
+        Ok(uir)
     }
     
-    fn convert_to_uir(&self, source: &str, node: Node, depth: usize) -> Result<UIRNode> {
+    fn convert_to_uir(&self, source: &str, node: Node) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::CSharp, |child| self.convert_to_uir(source, child));
+        }
+        if comments::is_comment(node) {
+            return Ok(comments::comment_node(node, source, &CoalesceLanguage::CSharp));
+        }
+        
         let node_type = node.kind();
         let start_position = node.start_position();
         let end_position = node.end_position();
@@ -101,12 +152,37 @@ impl CSharpParser {
             }
             "enum_declaration" => {
                 let enum_name = self.extract_enum_name(source, node);
-                (NodeType::Class, enum_name)
+                (NodeType::Enum { variants: self.enum_members(source, node) }, enum_name)
+            }
+            // Named per declarator by `convert_member`
+            "field_declaration" => {
+                let (field_type, _) = self.field_declarators(source, node);
+                (NodeType::Field { field_type, default_value: None }, None)
+            }
+            "property_declaration" => {
+                let name = node.child_by_field_name("name")
+                    .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                    .map(str::to_string);
+                (self.property(source, node), name)
+            }
+            // Accessors with bodies, children of their property; `init` sets it once
+            "accessor_declaration" => {
+                let mut cursor = node.walk();
+                let keyword = node.children(&mut cursor)
+                    .find(|c| matches!(c.kind(), "get" | "set" | "init" | "add" | "remove"))
+                    .map(|c| c.kind().to_string());
+                (NodeType::Function, keyword)
             }
             "parameter" => {
                 let param_name = self.extract_parameter_name(source, node);
                 (NodeType::Variable, param_name)
             }
+            // C# lambdas share the enclosing variables
+            "lambda_expression" | "anonymous_method_expression" => (closures::closure(CaptureMode::ByReference), None),
+            // The lone parameter of `x => ...`
+            "identifier" if node.parent().is_some_and(|p| p.kind() == "lambda_expression" && p.child_by_field_name("parameters") == Some(node)) => {
+                (NodeType::Variable, Some(original_text.clone()))
+            }
             "identifier" => {
                 let var_name = Some(original_text.clone());
                 (NodeType::Expression(ExpressionType::Variable), var_name)
@@ -126,11 +202,24 @@ impl CSharpParser {
             "return_statement" => {
                 (NodeType::Statement(StatementType::Return), None)
             }
-            "binary_expression" => {
+            "binary_expression" | "prefix_unary_expression" => {
                 (NodeType::Expression(ExpressionType::Arithmetic), None)
             }
             "invocation_expression" => {
-                (NodeType::Expression(ExpressionType::FunctionCall), None)
+                (NodeType::Expression(ExpressionType::FunctionCall), self.linq_operator(source, node))
+            }
+            "await_expression" => {
+                (NodeType::Expression(ExpressionType::Await), Some("await".to_string()))
+            }
+            // `yield break` ends the iterator like a return
+            "yield_statement" if node.named_child_count() > 0 => {
+                (NodeType::Expression(ExpressionType::Yield), Some("yield".to_string()))
+            }
+            "yield_statement" => {
+                (NodeType::Statement(StatementType::Return), None)
+            }
+            "query_expression" => {
+                (NodeType::Expression(ExpressionType::FunctionCall), Some("linq_query".to_string()))
             }
             "assignment_expression" => {
                 (NodeType::Expression(ExpressionType::Assignment), None)
@@ -153,14 +242,20 @@ impl CSharpParser {
             "try_statement" => {
                 (NodeType::ControlFlow(coalesce_core::ControlFlowType::Try), None)
             }
+            "catch_clause" => {
+                (NodeType::ControlFlow(self.catch_clause(source, node)), None)
+            }
+            "finally_clause" => {
+                (NodeType::ControlFlow(coalesce_core::ControlFlowType::Finally), None)
+            }
+            "throw_statement" | "throw_expression" => {
+                (NodeType::Statement(StatementType::Throw), None)
+            }
             "namespace_declaration" => {
                 let namespace_name = self.extract_namespace_name(source, node);
                 (NodeType::Module, namespace_name)
             }
-            "using_directive" => {
-                let using_name = self.extract_using_name(source, node);
-                (NodeType::Module, using_name)
-            }
+            "using_directive" => self.using_import(source, node),
             _ => {
                 // For other node types, try to categorize them generically
                 if node_type.contains("statement") {
@@ -180,21 +275,367 @@ impl CSharpParser {
             children: Vec::new(),
             metadata,
             source_location: Some(source_location),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         };
+        annotate_operator(node, source, &CoalesceLanguage::CSharp, &mut uir_node);
+        annotate_literal(node, source, &CoalesceLanguage::CSharp, &mut uir_node);
+        
+        if let NodeType::Import { path, .. } = &uir_node.node_type {
+            // The module path is the whole of an import; its tokens are not kept
+            uir_node.metadata.dependencies.push(path.clone());
+            return Ok(uir_node);
+        }
+        self.annotate_modifiers(source, node, &mut uir_node);
+        self.annotate_attributes(source, node, &mut uir_node);
+        self.annotate_parameter(source, node, &mut uir_node);
+        if matches!(uir_node.node_type, NodeType::Field { .. } | NodeType::Enum { .. }) {
+            // Fields and enum members are fully described by their node type
+            return Ok(uir_node);
+        }
+        if matches!(uir_node.node_type, NodeType::Property { .. }) {
+            uir_node.children = self.property_accessors(source, node)?;
+            return Ok(uir_node);
+        }
+        self.annotate_async(source, node, &mut uir_node);
+        self.annotate_linq(source, node, &mut uir_node);
         
-        // Process children
+        // Process children; awaits and yields keep only their operand
+        let operand_only = matches!(uir_node.node_type, NodeType::Expression(ExpressionType::Await | ExpressionType::Yield));
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            if !child.is_error() {
-                let child_uir = self.convert_to_uir(source, child, depth + 1)?;
-                uir_node.children.push(child_uir);
+            if operand_only && !child.is_named() {
+                continue;
+            }
+            // Fields and methods are members of the type itself rather than of its body
+            if matches!(node_type, "class_declaration" | "struct_declaration") && child.kind() == "declaration_list" {
+                let mut members = child.walk();
+                for member in child.named_children(&mut members) {
+                    uir_node.children.extend(self.convert_member(source, member)?);
+                }
+                continue;
             }
+            uir_node.children.extend(self.convert_member(source, child)?);
         }
         
         Ok(uir_node)
     }
     
+    /// A node converted on its own, or one field for each declarator of `int a, b = 1;`
+    fn convert_member(&self, source: &str, node: Node) -> Result<Vec<UIRNode>> {
+        let member = self.convert_to_uir(source, node)?;
+        if !matches!(member.node_type, NodeType::Field { .. }) {
+            return Ok(vec![member]);
+        }
+        let (_, declarators) = self.field_declarators(source, node);
+        Ok(declarators.into_iter()
+            .map(|(name, value)| {
+                let mut field = member.clone();
+                if let NodeType::Field { default_value, .. } = &mut field.node_type {
+                    *default_value = value;
+                }
+                field.id = format!("{}_{}", member.id, name);
+                field.name = Some(name);
+                field
+            })
+            .collect())
+    }
+    
+    /// A property's type and initializer, and which accessors it offers; `=> expr` is a getter
+    fn property(&self, source: &str, node: Node) -> NodeType {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let value = node.child_by_field_name("value");
+        let mut keywords = Vec::new();
+        if let Some(list) = node.child_by_field_name("accessors") {
+            let mut cursor = list.walk();
+            for accessor in list.named_children(&mut cursor) {
+                let mut accessor_cursor = accessor.walk();
+                keywords.extend(accessor.children(&mut accessor_cursor).map(|c| c.kind()));
+            }
+        }
+        NodeType::Property {
+            property_type: node.child_by_field_name("type").map(text),
+            default_value: value.filter(|v| v.kind() != "arrow_expression_clause").map(text),
+            has_getter: keywords.contains(&"get") || value.is_some_and(|v| v.kind() == "arrow_expression_clause"),
+            has_setter: keywords.iter().any(|k| matches!(*k, "set" | "init")),
+        }
+    }
+    
+    /// The accessors of a property that have bodies, as functions named `get`, `set` or `init`
+    /// tagged `getter` or `setter` whose children are their statements
+    fn property_accessors(&self, source: &str, node: Node) -> Result<Vec<UIRNode>> {
+        let mut accessors = Vec::new();
+        if let Some(arrow) = node.child_by_field_name("value").filter(|v| v.kind() == "arrow_expression_clause") {
+            let returned = self.returning(source, arrow)?;
+            let mut getter = returned.clone();
+            getter.id = format!("{}_get", returned.id);
+            getter.node_type = NodeType::Function;
+            getter.name = Some("get".to_string());
+            getter.metadata.semantic_tags.push("getter".to_string());
+            getter.children = vec![returned];
+            accessors.push(getter);
+        }
+        let Some(list) = node.child_by_field_name("accessors") else {
+            return Ok(accessors);
+        };
+        let mut cursor = list.walk();
+        for declaration in list.named_children(&mut cursor).filter(|c| c.kind() == "accessor_declaration") {
+            let Some(body) = declaration.child_by_field_name("body") else {
+                continue;
+            };
+            let mut accessor = self.convert_to_uir(source, declaration)?;
+            let tag = if accessor.name.as_deref() == Some("get") { "getter" } else { "setter" };
+            accessor.metadata.semantic_tags.push(tag.to_string());
+            accessor.children = if body.kind() == "arrow_expression_clause" {
+                vec![self.returning(source, body)?]
+            } else {
+                let mut statements = body.walk();
+                let statements: Vec<Node> = body.named_children(&mut statements).collect();
+                statements.into_iter().map(|s| self.convert_to_uir(source, s)).collect::<Result<_>>()?
+            };
+            accessors.push(accessor);
+        }
+        Ok(accessors)
+    }
+    
+    /// The expression of `=> expr` as the statement returning it
+    fn returning(&self, source: &str, arrow: Node) -> Result<UIRNode> {
+        let mut returned = self.convert_to_uir(source, arrow)?;
+        returned.node_type = NodeType::Statement(StatementType::Return);
+        returned.children.retain(|c| c.metadata.semantic_tags.first().map(String::as_str) != Some("=>"));
+        Ok(returned)
+    }
+    
+    /// The type of a field declaration and the name and initializer of each of its declarators
+    fn field_declarators(&self, source: &str, node: Node) -> (Option<String>, Vec<(String, Option<String>)>) {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let mut cursor = node.walk();
+        let Some(declaration) = node.named_children(&mut cursor).find(|c| c.kind() == "variable_declaration") else {
+            return (None, Vec::new());
+        };
+        let mut cursor = declaration.walk();
+        let declarators = declaration.named_children(&mut cursor)
+            .filter(|c| c.kind() == "variable_declarator")
+            .filter_map(|declarator| {
+                let mut cursor = declarator.walk();
+                let children: Vec<Node> = declarator.named_children(&mut cursor).collect();
+                let name = children.iter().find(|c| c.kind() == "identifier").copied().map(text)?;
+                let value = children.iter()
+                    .find(|c| c.kind() == "equals_value_clause")
+                    .and_then(|clause| clause.named_child(0))
+                    .map(text);
+                Some((name, value))
+            })
+            .collect();
+        (declaration.child_by_field_name("type").map(text), declarators)
+    }
+    
+    /// The members of an `enum`, with the values given to them
+    fn enum_members(&self, source: &str, node: Node) -> Vec<EnumVariant> {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let Some(body) = node.child_by_field_name("body") else {
+            return Vec::new();
+        };
+        let mut cursor = body.walk();
+        let members = body.named_children(&mut cursor)
+            .filter(|c| c.kind() == "enum_member_declaration")
+            .map(|member| EnumVariant {
+                name: member.child_by_field_name("name").map(text).unwrap_or_default(),
+                value: member.child_by_field_name("value").map(text),
+                fields: Vec::new(),
+            })
+            .collect();
+        members
+    }
+    
+    /// `catch (IOException e)`, `catch (Exception)` or a bare `catch`; a `when` filter stays a
+    /// child of the clause
+    fn catch_clause(&self, source: &str, node: Node) -> coalesce_core::ControlFlowType {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let mut cursor = node.walk();
+        let declaration = node.named_children(&mut cursor).find(|c| c.kind() == "catch_declaration");
+        coalesce_core::ControlFlowType::Catch {
+            exception_types: declaration.and_then(|d| d.child_by_field_name("type")).map(text).into_iter().collect(),
+            binding: declaration.and_then(|d| d.child_by_field_name("name")).map(text),
+        }
+    }
+    
+    /// Access level and modifiers of a declaration, from its `modifier` children
+    fn annotate_modifiers(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        let mut cursor = node.walk();
+        let keywords: Vec<&str> = node.children(&mut cursor)
+            .filter(|c| c.kind() == "modifier")
+            .filter_map(|c| c.utf8_text(source.as_bytes()).ok())
+            .collect();
+        uir_node.visibility = Visibility::from_keywords(keywords.iter().copied(), &CoalesceLanguage::CSharp);
+        uir_node.modifiers = keywords.iter().filter_map(|k| Modifier::from_keyword(k, &CoalesceLanguage::CSharp)).collect();
+    }
+    
+    /// The type of a parameter, and the references `ref`, `out` and `in` pass instead of a copy
+    fn annotate_parameter(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        if node.kind() != "parameter" {
+            return;
+        }
+        if let Some(declared) = node.child_by_field_name("type").and_then(|t| t.utf8_text(source.as_bytes()).ok()) {
+            uir_node.metadata.annotations.insert("type".to_string(), Value::String(declared.to_string()));
+        }
+        let mut cursor = node.walk();
+        let modifier = node.children(&mut cursor)
+            .find(|c| c.kind() == "parameter_modifier")
+            .and_then(|c| c.utf8_text(source.as_bytes()).ok());
+        uir_node.ownership = match modifier {
+            Some("ref" | "out") => Some(Ownership::Reference { mutable: true }),
+            Some("in") => Some(Ownership::Reference { mutable: false }),
+            _ => None,
+        };
+    }
+    
+    /// Attributes of a declaration from its `[...]` lists, without any `return:`-style target
+    fn annotate_attributes(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        let mut cursor = node.walk();
+        for list in node.children(&mut cursor).filter(|c| c.kind() == "attribute_list") {
+            let mut list_cursor = list.walk();
+            uir_node.attributes.extend(list.named_children(&mut list_cursor)
+                .filter(|c| c.kind() == "attribute")
+                .filter_map(|c| c.utf8_text(source.as_bytes()).ok())
+                .map(Attribute::parse));
+        }
+    }
+    
+    /// Mark async methods, lambdas and local functions, awaits and `await foreach`/`await using`
+    fn annotate_async(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        let metadata = &mut uir_node.metadata;
+        match node.kind() {
+            "method_declaration" | "local_function_statement" | "lambda_expression" | "anonymous_method_expression" => {
+                let mut cursor = node.walk();
+                let is_async = node.children(&mut cursor)
+                    .any(|c| c.kind() == "async" || (c.kind() == "modifier" && c.utf8_text(source.as_bytes()) == Ok("async")));
+                if !is_async {
+                    return;
+                }
+                uir_node.is_async = true;
+                let metadata = &mut uir_node.metadata;
+                metadata.semantic_tags.push("async".to_string());
+                metadata.annotations.insert("async".to_string(), Value::Bool(true));
+                
+                // Task<T> resolves to T; Task and ValueTask to nothing
+                if let Some(return_type) = node.child_by_field_name("type").and_then(|t| t.utf8_text(source.as_bytes()).ok()) {
+                    let result = match return_type.split_once('<') {
+                        Some((wrapper, inner)) if wrapper.ends_with("Task") => inner.strip_suffix('>').unwrap_or(inner).trim().to_string(),
+                        Some(_) => return_type.to_string(),
+                        None if return_type.ends_with("Task") || return_type == "void" => "void".to_string(),
+                        None => return_type.to_string(),
+                    };
+                    metadata.annotations.insert("awaited_result".to_string(), Value::String(result));
+                }
+            }
+            "await_expression" => {
+                metadata.semantic_tags.push("await".to_string());
+                let text = node.utf8_text(source.as_bytes()).unwrap_or("");
+                if text.replace(' ', "").contains(".ConfigureAwait(false)") {
+                    metadata.annotations.insert("configure_await".to_string(), Value::Bool(false));
+                }
+            }
+            "foreach_statement" | "using_statement" | "local_declaration_statement" => {
+                let mut cursor = node.walk();
+                let awaited = node.children(&mut cursor).any(|c| c.kind() == "await");
+                if awaited {
+                    let tag = if node.kind() == "foreach_statement" { "async_stream" } else { "async_dispose" };
+                    metadata.semantic_tags.push(tag.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// The LINQ operator an invocation calls, if any: `xs.Where(...)` gives `Where`
+    fn linq_operator(&self, source: &str, node: Node) -> Option<String> {
+        let function = node.child_by_field_name("function").filter(|f| f.kind() == "member_access_expression")?;
+        let name = function.child_by_field_name("name")?;
+        // Generic calls like OfType<T>() name the method through generic_name
+        let name = if name.kind() == "generic_name" { name.named_child(0)? } else { name };
+        let operator = name.utf8_text(source.as_bytes()).ok()?;
+        LINQ_OPERATORS.contains(&operator).then(|| operator.to_string())
+    }
+    
+    /// Tag query syntax with its clauses and method chains with their operators
+    fn annotate_linq(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        let metadata = &mut uir_node.metadata;
+        match node.kind() {
+            "query_expression" => {
+                let mut clauses = Vec::new();
+                let mut cursor = node.walk();
+                for clause in node.named_children(&mut cursor) {
+                    let clause_name = clause.kind().trim_end_matches("_clause").replace('_', "");
+                    if clause.kind() == "from_clause" && clauses.is_empty() {
+                        // from x in source: the last expression is the source
+                        let mut clause_cursor = clause.walk();
+                        let parts: Vec<Node> = clause.named_children(&mut clause_cursor).collect();
+                        if let Some(range_variable) = parts.iter().find(|p| p.kind() == "identifier") {
+                            metadata.annotations.insert("range_variable".to_string(),
+                                Value::String(range_variable.utf8_text(source.as_bytes()).unwrap_or("").to_string()));
+                        }
+                        if let Some(query_source) = parts.last() {
+                            metadata.annotations.insert("query_source".to_string(),
+                                Value::String(query_source.utf8_text(source.as_bytes()).unwrap_or("").to_string()));
+                        }
+                    }
+                    clauses.push(Value::String(clause_name));
+                }
+                metadata.semantic_tags.push("linq".to_string());
+                metadata.semantic_tags.push("linq_query".to_string());
+                metadata.annotations.insert("query_clauses".to_string(), Value::Array(clauses));
+            }
+            "invocation_expression" => {
+                let Some(operator) = uir_node.name.clone().filter(|_| self.linq_operator(source, node).is_some()) else {
+                    return;
+                };
+                metadata.semantic_tags.push("linq".to_string());
+                metadata.annotations.insert("linq_operator".to_string(), Value::String(operator));
+                
+                // Only the outermost call of a chain describes the whole pipeline
+                let outer_call = node.parent()
+                    .filter(|p| p.kind() == "member_access_expression")
+                    .and_then(|p| p.parent())
+                    .filter(|p| p.kind() == "invocation_expression" && self.linq_operator(source, *p).is_some());
+                if outer_call.is_some() {
+                    return;
+                }
+                
+                let mut operators = Vec::new();
+                let mut current = Some(node);
+                while let Some(call) = current {
+                    let Some(operator) = self.linq_operator(source, call) else {
+                        break;
+                    };
+                    operators.push(Value::String(operator));
+                    let receiver = call.child_by_field_name("function").and_then(|f| f.child_by_field_name("expression"));
+                    if let Some(receiver) = receiver.filter(|r| r.kind() != "invocation_expression") {
+                        metadata.annotations.insert("query_source".to_string(),
+                            Value::String(receiver.utf8_text(source.as_bytes()).unwrap_or("").to_string()));
+                    }
+                    current = receiver.filter(|r| r.kind() == "invocation_expression");
+                }
+                operators.reverse();
+                metadata.semantic_tags.push("linq_chain".to_string());
+                metadata.annotations.insert("linq_operators".to_string(), Value::Array(operators));
+            }
+            _ => {}
+        }
+    }
+    
+    /// The declared name, which follows a return type that may itself be an identifier such as
+    /// `IActionResult Get()`
     fn extract_method_name(&self, source: &str, node: Node) -> Option<String> {
+        if let Some(name) = node.child_by_field_name("name").and_then(|n| n.utf8_text(source.as_bytes()).ok()) {
+            return Some(name.to_string());
+        }
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
             if child.kind() == "identifier" {
@@ -218,16 +659,11 @@ impl CSharpParser {
         Some("constructor".to_string())
     }
     
+    /// The declared name, which follows a type that may itself be an identifier such as `Big b`
     fn extract_parameter_name(&self, source: &str, node: Node) -> Option<String> {
-        let mut cursor = node.walk();
-        for child in node.children(&mut cursor) {
-            if child.kind() == "identifier" {
-                if let Ok(name) = child.utf8_text(source.as_bytes()) {
-                    return Some(name.to_string());
-                }
-            }
-        }
-        None
+        node.child_by_field_name("name")
+            .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+            .map(str::to_string)
     }
     
     fn extract_class_name(&self, source: &str, node: Node) -> Option<String> {
@@ -290,26 +726,31 @@ impl CSharpParser {
         Some("global_namespace".to_string())
     }
     
-    fn extract_using_name(&self, source: &str, node: Node) -> Option<String> {
+    /// `using A.B;`, `using Alias = A.B;` or `using static A.B.C;`, which brings in the static
+    /// members of `C`
+    fn using_import(&self, source: &str, node: Node) -> (NodeType, Option<String>) {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
         let mut cursor = node.walk();
-        for child in node.children(&mut cursor) {
-            if child.kind() == "qualified_name" || child.kind() == "identifier" {
-                if let Ok(name) = child.utf8_text(source.as_bytes()) {
-                    return Some(format!("using_{}", name.replace(".", "_")));
-                }
-            }
-        }
-        Some("unknown_using".to_string())
+        let parts: Vec<Node> = node.named_children(&mut cursor).collect();
+        let alias = parts.iter()
+            .find(|c| c.kind() == "name_equals")
+            .and_then(|n| n.named_child(0))
+            .map(text);
+        let path = parts.iter().rev()
+            .find(|c| c.kind() != "name_equals" && !comments::is_comment(**c))
+            .map(|n| text(*n))
+            .unwrap_or_default();
+        let mut cursor = node.walk();
+        let is_static = node.children(&mut cursor).any(|c| c.kind() == "static");
+        let symbols = if is_static { vec![ImportedSymbol::new("*")] } else { Vec::new() };
+        (NodeType::Import { path: path.clone(), alias, symbols }, Some(path))
     }
 }
 
-extern "C" {
-    fn tree_sitter_c_sharp() -> Language;
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use coalesce_core::ControlFlowType;
     
     #[test]
     fn test_simple_csharp_method() {
@@ -324,6 +765,18 @@ mod tests {
         assert!(!uir.children.is_empty());
     }
     
+    #[test]
+    fn test_csharp_method_returning_named_type() {
+        let parser = CSharpParser::new().unwrap();
+        let source = "public class C { public IActionResult Get(int id) { return null; } }";
+        
+        let uir = parser.parse(source).unwrap();
+        let method = uir.children[0].children.iter()
+            .find(|child| matches!(child.node_type, NodeType::Function))
+            .unwrap();
+        assert_eq!(method.name.as_deref(), Some("Get"));
+    }
+    
     #[test]
     fn test_csharp_class() {
         let parser = CSharpParser::new().unwrap();
@@ -334,7 +787,7 @@ public class Calculator {
     }
 }
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
@@ -351,8 +804,213 @@ namespace MathLibrary {
     }
 }
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
+    
+    #[test]
+    fn test_csharp_async_and_linq() {
+        let parser = CSharpParser::new().unwrap();
+        let source = r#"
+public class Orders {
+    public async Task<List<string>> LoadAsync(Db db) {
+        var rows = await db.FetchAsync().ConfigureAwait(false);
+        var names = rows.Where(r => r.Active).OrderBy(r => r.Name).Select(r => r.Name).ToList();
+        var big = from o in rows where o.Total > 100 orderby o.Total select o.Id;
+        return names;
+    }
+}
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        fn find<'a>(node: &'a UIRNode, tag: &str) -> Vec<&'a UIRNode> {
+            let mut found: Vec<&UIRNode> = node.children.iter().flat_map(|c| find(c, tag)).collect();
+            if node.metadata.semantic_tags.iter().any(|t| t == tag) {
+                found.insert(0, node);
+            }
+            found
+        }
+        
+        let method = find(&uir, "method_declaration")[0];
+        assert!(method.is_async);
+        assert_eq!(method.metadata.annotations["awaited_result"], "List<string>");
+        
+        let await_node = find(&uir, "await")[0];
+        assert_eq!(await_node.node_type, NodeType::Expression(ExpressionType::Await));
+        assert_eq!(await_node.metadata.annotations["configure_await"], false);
+        
+        let chain = find(&uir, "linq_chain");
+        assert_eq!(chain.len(), 1);
+        assert_eq!(chain[0].metadata.annotations["linq_operators"], serde_json::json!(["Where", "OrderBy", "Select", "ToList"]));
+        assert_eq!(chain[0].metadata.annotations["query_source"], "rows");
+        
+        let query = find(&uir, "linq_query")[0];
+        assert_eq!(query.metadata.annotations["query_clauses"], serde_json::json!(["from", "where", "orderby", "select"]));
+        assert_eq!(query.metadata.annotations["range_variable"], "o");
+    }
+    
+    #[test]
+    fn test_csharp_exception_handling() {
+        let parser = CSharpParser::new().unwrap();
+        let source = r#"
+public class Loader {
+    public void Load() {
+        try {
+            Read();
+        } catch (IOException ex) {
+            throw;
+        } finally {
+            Close();
+        }
+    }
+}
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        fn find<'a>(node: &'a UIRNode, tag: &str) -> Option<&'a UIRNode> {
+            if node.metadata.semantic_tags.iter().any(|t| t == tag) {
+                return Some(node);
+            }
+            node.children.iter().find_map(|c| find(c, tag))
+        }
+        
+        let handler = find(&uir, "catch_clause").unwrap();
+        assert_eq!(handler.node_type, NodeType::ControlFlow(ControlFlowType::Catch {
+            exception_types: vec!["IOException".to_string()],
+            binding: Some("ex".to_string()),
+        }));
+        assert_eq!(find(&uir, "finally_clause").unwrap().node_type, NodeType::ControlFlow(ControlFlowType::Finally));
+        assert_eq!(find(handler, "throw_statement").unwrap().node_type, NodeType::Statement(StatementType::Throw));
+    }
+    
+    #[test]
+    fn test_csharp_attributes() {
+        let parser = CSharpParser::new().unwrap();
+        let source = r#"
+[Serializable, Obsolete("Use V2")]
+public class UsersController {
+    [HttpGet("{id}")]
+    [return: NotNull]
+    public string Get([FromRoute] int id) { return null; }
+}
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let class = &uir.children[0];
+        assert_eq!(class.attributes, vec![
+            Attribute { name: "Serializable".to_string(), arguments: Vec::new() },
+            Attribute { name: "Obsolete".to_string(), arguments: vec!["\"Use V2\"".to_string()] },
+        ]);
+        
+        fn find<'a>(node: &'a UIRNode, name: &str) -> Option<&'a UIRNode> {
+            if node.name.as_deref() == Some(name) {
+                return Some(node);
+            }
+            node.children.iter().find_map(|child| find(child, name))
+        }
+        let method = find(class, "Get").unwrap();
+        let names: Vec<&str> = method.attributes.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["HttpGet", "NotNull"]);
+        assert_eq!(find(method, "id").unwrap().attributes[0].name, "FromRoute");
+    }
+    
+    #[test]
+    fn test_csharp_fields_and_enums() {
+        let parser = CSharpParser::new().unwrap();
+        let source = r#"
+public struct Point {
+    public double X, Y = 1.5;
+    private const int Dimensions = 2;
+}
+
+enum Color : byte { Red = 1, Green }
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let fields: Vec<_> = uir.children[0].children.iter()
+            .filter_map(|c| match &c.node_type {
+                NodeType::Field { field_type, default_value } => Some((c.name.as_deref(), field_type.as_deref(), default_value.as_deref(), c.visibility)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fields, vec![
+            (Some("X"), Some("double"), None, Some(Visibility::Public)),
+            (Some("Y"), Some("double"), Some("1.5"), Some(Visibility::Public)),
+            (Some("Dimensions"), Some("int"), Some("2"), Some(Visibility::Private)),
+        ]);
+        
+        let NodeType::Enum { variants } = &uir.children[1].node_type else {
+            panic!("expected an enum, got {:?}", uir.children[1].node_type);
+        };
+        let members: Vec<_> = variants.iter().map(|v| (v.name.as_str(), v.value.as_deref())).collect();
+        assert_eq!(members, vec![("Red", Some("1")), ("Green", None)]);
+    }
+    
+    #[test]
+    fn test_csharp_properties() {
+        let parser = CSharpParser::new().unwrap();
+        let source = r#"
+class Account {
+    public int Count { get; private set; } = 5;
+    public string Name { get { return name; } set { name = value; } }
+    public decimal Total => balance * 2;
+}
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let properties: Vec<_> = uir.children[0].children.iter()
+            .filter(|c| matches!(c.node_type, NodeType::Property { .. }))
+            .collect();
+        assert_eq!(properties.len(), 3);
+        assert_eq!(properties[0].name.as_deref(), Some("Count"));
+        assert_eq!(properties[0].node_type, NodeType::Property {
+            property_type: Some("int".to_string()),
+            default_value: Some("5".to_string()),
+            has_getter: true,
+            has_setter: true,
+        });
+        assert!(properties[0].children.is_empty());
+        
+        let accessors: Vec<_> = properties[1].children.iter()
+            .map(|a| (a.name.as_deref(), a.metadata.semantic_tags.last().map(String::as_str), a.children.len()))
+            .collect();
+        assert_eq!(accessors, vec![(Some("get"), Some("getter"), 1), (Some("set"), Some("setter"), 1)]);
+        
+        let NodeType::Property { has_getter, has_setter, .. } = properties[2].node_type else { unreachable!() };
+        assert!(has_getter && !has_setter);
+        let getter = &properties[2].children[0];
+        assert_eq!(getter.children[0].node_type, NodeType::Statement(StatementType::Return));
+    }
+    
+    #[test]
+    fn test_csharp_xml_documentation() {
+        let parser = CSharpParser::new().unwrap();
+        let source = r#"
+class Geometry {
+    /// <summary>
+    /// Distance to <see cref="Point"/> <c>other</c>.
+    /// </summary>
+    /// <param name="other">The other point.</param>
+    /// <returns>The distance.</returns>
+    public double Distance(Point other) { return 0; }
+    
+    /// <summary>The origin.</summary>
+    public Point Origin { get; }
+    
+    // Not documentation
+    public int count;
+}
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let class = &uir.children[0];
+        let member = |name: &str| class.children.iter().find(|c| c.name.as_deref() == Some(name)).unwrap();
+        assert_eq!(
+            member("Distance").documentation.as_deref(),
+            Some("Distance to Point other.\nother: The other point.\nReturns: The distance.")
+        );
+        assert_eq!(member("Origin").documentation.as_deref(), Some("The origin."));
+        assert_eq!(member("count").documentation, None);
+    }
 }
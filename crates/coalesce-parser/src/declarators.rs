@@ -0,0 +1,122 @@
+// C and C++ declarators shared by their parsers
+//
+// A C-family declaration splits its type between the specifiers (`const char`) and each
+// declarator (`*name`, `&name`, `&&name`, `name[8]`), so the type of a parameter or field and
+// how it holds its value come from both: `const char *s` points at data it may not change,
+// while `char * const s` may change the data but not the pointer.
+use coalesce_core::{Ownership, UIRNode};
+use serde_json::Value;
+use tree_sitter::Node;
+
+/// A name declared by a parameter or field declaration
+pub(crate) struct Declared {
+    pub name: Option<String>,
+    /// The specifiers with the declarator's pointer, reference and array parts: `const char*`
+    pub declared_type: String,
+    pub ownership: Ownership,
+}
+
+/// The name `declarator` declares in `declaration`, with its full type and ownership
+pub(crate) fn declared(declaration: Node, declarator: Option<Node>, source: &str) -> Declared {
+    let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+    let type_node = declaration.child_by_field_name("type");
+    let mut cursor = declaration.walk();
+    let specifiers: Vec<String> = declaration.children(&mut cursor)
+        .filter(|c| c.kind() == "type_qualifier" || Some(*c) == type_node)
+        .map(text)
+        .collect();
+    let specifier = specifiers.join(" ");
+    let is_const = specifiers.iter().any(|s| s == "const");
+    
+    let Some(declarator) = declarator else {
+        return Declared { name: None, ownership: specifier_ownership(&specifier), declared_type: specifier };
+    };
+    let name = Some(name_of(declarator)).filter(|n| matches!(n.kind(), "identifier" | "field_identifier"));
+    let written = text(declarator);
+    let suffix = match name {
+        Some(name) => written.replacen(&text(name), "", 1),
+        None => written,
+    };
+    let suffix = suffix.split_whitespace().collect::<Vec<_>>().join(" ").replace(" *", "*");
+    
+    let ownership = match declarator.kind() {
+        "pointer_declarator" | "abstract_pointer_declarator" => {
+            // The `*` nearest the name is the pointer itself, and the qualifiers of the one before it
+            // (or the specifiers) say what it points at: `char * const *p` points at constant
+            // pointers, while `const char **p` points at mutable ones
+            let mut pointee_const = is_const;
+            let mut current = declarator;
+            while let Some(inner) = inner_declarator(current).filter(|d| d.kind().ends_with("pointer_declarator")) {
+                pointee_const = has_const(current);
+                current = inner;
+            }
+            Ownership::Pointer { mutable: !pointee_const }
+        }
+        // Array parameters decay to pointers to their first element
+        "array_declarator" | "abstract_array_declarator" => Ownership::Pointer { mutable: !is_const },
+        "reference_declarator" | "abstract_reference_declarator" => {
+            if declarator.child(0).is_some_and(|c| c.kind() == "&&") {
+                Ownership::Owned
+            } else {
+                Ownership::Reference { mutable: !is_const }
+            }
+        }
+        _ => specifier_ownership(&specifier),
+    };
+    Declared {
+        name: name.map(text),
+        declared_type: format!("{}{}", specifier, suffix),
+        ownership,
+    }
+}
+
+/// Name a function parameter after its declarator and record its type and ownership
+pub(crate) fn annotate_parameter(node: Node, source: &str, uir_node: &mut UIRNode) {
+    if !matches!(node.kind(), "parameter_declaration" | "optional_parameter_declaration") {
+        return;
+    }
+    let parameter = declared(node, node.child_by_field_name("declarator"), source);
+    uir_node.name = parameter.name;
+    uir_node.metadata.annotations.insert("type".to_string(), Value::String(parameter.declared_type));
+    uir_node.ownership = Some(parameter.ownership);
+    if let Some(default) = node.child_by_field_name("default_value").and_then(|d| d.utf8_text(source.as_bytes()).ok()) {
+        uir_node.metadata.annotations.insert("default_value".to_string(), Value::String(default.to_string()));
+    }
+}
+
+/// Values held directly: smart pointers that own what they point at are moved, others copied
+fn specifier_ownership(specifier: &str) -> Ownership {
+    if specifier.contains("unique_ptr<") {
+        Ownership::Owned
+    } else {
+        Ownership::ByValue
+    }
+}
+
+fn has_const(declarator: Node) -> bool {
+    let mut cursor = declarator.walk();
+    let found = declarator.children(&mut cursor).any(|c| c.kind() == "type_qualifier" && c.child(0).is_some_and(|k| k.kind() == "const"));
+    found
+}
+
+/// The innermost node of a declarator such as `*next`, `&ref` or `items[8]`: the declared
+/// identifier, or the last declarator of an unnamed parameter like `char *`
+pub(crate) fn name_of(declarator: Node) -> Node {
+    let mut current = declarator;
+    while let Some(inner) = inner_declarator(current) {
+        current = inner;
+    }
+    current
+}
+
+/// The declarator a pointer, reference, array or function declarator wraps
+pub(crate) fn inner_declarator(declarator: Node) -> Option<Node> {
+    match declarator.kind() {
+        "reference_declarator" | "abstract_reference_declarator" => {
+            let mut cursor = declarator.walk();
+            let inner = declarator.named_children(&mut cursor).find(|c| c.kind() != "type_qualifier");
+            inner
+        }
+        _ => declarator.child_by_field_name("declarator"),
+    }
+}
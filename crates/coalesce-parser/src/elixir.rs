@@ -0,0 +1,750 @@
+use coalesce_core::{UIRNode, NodeType, CaptureMode, ImportedSymbol, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, Result, CoalesceError,
+                   Parser as CoalesceParser};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::closures;
+use crate::operators::annotate_operator;
+use crate::literals::annotate_literal;
+use serde_json::Value;
+
+/// Calls that start a new process or task
+const SPAWN_FUNCTIONS: &[&str] = &[
+    "spawn", "spawn_link", "spawn_monitor", "Task.async", "Task.start", "Task.start_link",
+    "Process.spawn", "GenServer.start", "GenServer.start_link",
+];
+
+/// Calls that send a message to a process
+const SEND_FUNCTIONS: &[&str] = &["send", "Process.send", "GenServer.cast", "GenServer.call"];
+
+const DEF_KEYWORDS: &[&str] = &["def", "defp", "defmacro", "defmacrop"];
+
+/// Elixir parser using tree-sitter
+pub struct ElixirParser {
+}
+
+impl CoalesceParser for ElixirParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::Elixir
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        // Create a new parser for this parse operation
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_elixir::language())
+            .map_err(|e| CoalesceError::ParseError {
+                message: format!("Failed to set Elixir language: {}", e),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let tree = parser.parse(source, None)
+            .ok_or_else(|| CoalesceError::ParseError {
+                message: "Failed to parse Elixir source".to_string(),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let root = tree.root_node();
+        let mut uir = UIRNode {
+            id: self.generate_node_id(root, source),
+            node_type: NodeType::Module,
+            name: Some("elixir_program".to_string()),
+            children: self.convert_body(root, source)?,
+            metadata: self.create_metadata(root, source),
+            source_location: self.create_source_location(root),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        recovery::summarize(source, root, &mut uir);
+        closures::annotate_captures(&mut uir);
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
+    }
+}
+
+impl ElixirParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    fn ast_to_uir(&self, node: Node, source: &str) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::Elixir, |child| self.ast_to_uir(child, source));
+        }
+        
+        match node.kind() {
+            "call" => self.convert_call(node, source),
+            "anonymous_function" => self.convert_anonymous_function(node, source),
+            "stab_clause" => self.convert_clause(node, source),
+            "binary_operator" => self.convert_binary_operator(node, source),
+            "unary_operator" => self.convert_simple(node, source, NodeType::Expression(ExpressionType::Arithmetic)),
+            "identifier" | "dot" => self.convert_named_leaf(node, source, NodeType::Expression(ExpressionType::Variable)),
+            "atom" | "quoted_atom" | "integer" | "float" | "string" | "charlist" | "char" | "boolean" | "nil" => {
+                self.convert_named_leaf(node, source, NodeType::Expression(ExpressionType::Literal))
+            }
+            _ => self.convert_generic(node, source),
+        }
+    }
+    
+    /// Convert a sequence of expressions, merging `def` clauses of the same name and arity
+    fn convert_body(&self, node: Node, source: &str) -> Result<Vec<UIRNode>> {
+        enum Item<'a> {
+            Ready(Box<UIRNode>),
+            Function(usize, Vec<Node<'a>>),
+        }
+        
+        let mut items: Vec<Item> = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.is_extra() {
+                continue;
+            }
+            if !self.is_def(child, source) {
+                items.push(Item::Ready(Box::new(self.ast_to_uir(child, source)?)));
+                continue;
+            }
+            
+            let (name, args, _) = self.def_head(child, source);
+            let existing = items.iter_mut().find_map(|item| match item {
+                Item::Function(arity, clauses) if *arity == args.len() && self.def_head(clauses[0], source).0 == name => Some(clauses),
+                _ => None,
+            });
+            match existing {
+                Some(clauses) => clauses.push(child),
+                None => items.push(Item::Function(args.len(), vec![child])),
+            }
+        }
+        
+        let mut children = Vec::new();
+        for item in items {
+            match item {
+                Item::Ready(uir) => children.push(*uir),
+                Item::Function(arity, clauses) => children.push(self.convert_function(arity, &clauses, source)?),
+            }
+        }
+        
+        Ok(children)
+    }
+    
+    fn convert_call(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let target = node.child_by_field_name("target")
+            .map(|t| self.node_text(t, source).to_string())
+            .unwrap_or_default();
+        
+        match target.as_str() {
+            "defmodule" => return self.convert_module(node, source),
+            "import" | "alias" | "require" | "use" => return self.convert_import(node, source, &target),
+            t if DEF_KEYWORDS.contains(&t) => return self.convert_function(0, &[node], source),
+            _ => {}
+        }
+        
+        let node_type = match target.as_str() {
+            "if" | "unless" => NodeType::ControlFlow(ControlFlowType::Conditional),
+            "case" | "cond" | "with" | "receive" => NodeType::ControlFlow(ControlFlowType::Switch),
+            "try" => NodeType::ControlFlow(ControlFlowType::Try),
+            "for" => NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)),
+            _ => NodeType::Expression(ExpressionType::FunctionCall),
+        };
+        
+        let mut metadata = self.create_metadata(node, source);
+        if target == "receive" {
+            self.tag_concurrency(&mut metadata, "receive");
+        } else if SPAWN_FUNCTIONS.contains(&target.as_str()) {
+            self.tag_concurrency(&mut metadata, "spawn");
+        } else if SEND_FUNCTIONS.contains(&target.as_str()) {
+            self.tag_concurrency(&mut metadata, "message_send");
+        } else if target == "for" {
+            metadata.semantic_tags.push("comprehension".to_string());
+        }
+        if let Some((module, _)) = target.rsplit_once('.') {
+            metadata.dependencies.push(module.to_string());
+        }
+        
+        let mut children = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "arguments" => children.extend(self.convert_children(child, source)?),
+                "do_block" => children.extend(self.convert_body(child, source)?),
+                _ => {}
+            }
+        }
+        
+        let name = match node_type {
+            NodeType::Expression(ExpressionType::FunctionCall) => Some(target),
+            _ => None,
+        };
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name,
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_module(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let name = self.find_child_by_kind(node, "arguments")
+            .and_then(|args| args.named_child(0))
+            .map(|alias| self.node_text(alias, source).to_string());
+        
+        let children = match self.find_child_by_kind(node, "do_block") {
+            Some(block) => self.convert_body(block, source)?,
+            None => vec![],
+        };
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Module,
+            name,
+            children,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// `alias A.B, as: C`, `import A, only: [f: 1]`, `require A` or `use A`; an `only:` list
+    /// becomes the imported symbols as `name/arity`
+    fn convert_import(&self, node: Node, source: &str, directive: &str) -> Result<UIRNode> {
+        let mut metadata = self.create_metadata(node, source);
+        metadata.semantic_tags.push(directive.to_string());
+        
+        let arguments = self.find_child_by_kind(node, "arguments");
+        let module_name = arguments
+            .and_then(|args| args.named_child(0))
+            .map(|alias| self.node_text(alias, source).to_string())
+            .unwrap_or_default();
+        metadata.dependencies.push(module_name.clone());
+        
+        let mut alias = None;
+        let mut symbols = Vec::new();
+        for (key, value) in arguments.and_then(|args| self.find_child_by_kind(args, "keywords")).map(|k| self.pairs(k)).unwrap_or_default() {
+            match self.node_text(key, source).trim().trim_end_matches(':') {
+                "as" => alias = Some(self.node_text(value, source).to_string()),
+                "only" => {
+                    let functions = self.find_child_by_kind(value, "keywords").map(|k| self.pairs(k)).unwrap_or_default();
+                    symbols.extend(functions.into_iter().map(|(name, arity)| {
+                        let name = self.node_text(name, source).trim().trim_end_matches(':');
+                        ImportedSymbol::new(format!("{}/{}", name, self.node_text(arity, source)))
+                    }));
+                }
+                _ => {}
+            }
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Import { path: module_name.clone(), alias, symbols },
+            name: Some(module_name),
+            children: vec![],
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// The key and value of each pair in a keyword list
+    fn pairs<'a>(&self, keywords: Node<'a>) -> Vec<(Node<'a>, Node<'a>)> {
+        let mut cursor = keywords.walk();
+        let pairs = keywords.named_children(&mut cursor)
+            .filter_map(|pair| Some((pair.child_by_field_name("key")?, pair.child_by_field_name("value")?)))
+            .collect();
+        pairs
+    }
+    
+    /// Build one function from its `def` clauses; multiple clauses become pattern-matched branches
+    fn convert_function(&self, arity: usize, clauses: &[Node], source: &str) -> Result<UIRNode> {
+        let first = clauses[0];
+        let last = clauses[clauses.len() - 1];
+        let (name, args, _) = self.def_head(first, source);
+        let keyword = first.child_by_field_name("target").map(|t| self.node_text(t, source)).unwrap_or("def");
+        
+        let mut metadata = self.create_metadata(first, source);
+        metadata.annotations.insert("arity".to_string(), Value::from(arity.max(args.len())));
+        metadata.annotations.insert("clauses".to_string(), Value::from(clauses.len()));
+        if keyword.ends_with('p') {
+            metadata.semantic_tags.push("private".to_string());
+        }
+        if keyword.starts_with("defmacro") {
+            metadata.semantic_tags.push("macro".to_string());
+        }
+        
+        let mut converted = Vec::new();
+        for clause in clauses {
+            converted.push(self.convert_def_clause(*clause, source)?);
+        }
+        if converted.iter().any(|c| c.metadata.semantic_tags.contains(&"pattern_matched".to_string())) {
+            metadata.semantic_tags.push("pattern_matched".to_string());
+        }
+        
+        let children = if converted.len() == 1 {
+            converted.remove(0).children
+        } else {
+            metadata.semantic_tags.push("multi_clause".to_string());
+            converted
+        };
+        
+        let mut location = self.create_source_location(first);
+        if let Some(location) = location.as_mut() {
+            location.end_line = last.end_position().row as u32 + 1;
+            location.end_column = last.end_position().column as u32;
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(first, source),
+            node_type: NodeType::Function,
+            name: Some(name),
+            children,
+            metadata,
+            source_location: location,
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// A `def` clause holds its parameter patterns, an optional guard and the body
+    fn convert_def_clause(&self, def: Node, source: &str) -> Result<UIRNode> {
+        let (_, args, guard) = self.def_head(def, source);
+        
+        let mut metadata = self.create_metadata(def, source);
+        metadata.semantic_tags.push("function_clause".to_string());
+        let mut children = self.convert_patterns(&args, &mut metadata, source)?;
+        
+        if let Some(guard) = guard {
+            let mut guard_node = self.ast_to_uir(guard, source)?;
+            guard_node.metadata.semantic_tags.push("guard".to_string());
+            metadata.annotations.insert("guard".to_string(), Value::String(self.node_text(guard, source).to_string()));
+            children.push(guard_node);
+        }
+        
+        let mut cursor = def.walk();
+        for child in def.named_children(&mut cursor) {
+            match child.kind() {
+                "do_block" => children.extend(self.convert_body(child, source)?),
+                "arguments" => {
+                    // Keyword form: `def f(x), do: expr`
+                    if let Some(value) = self.find_child_by_kind(child, "keywords")
+                        .and_then(|k| self.find_child_by_kind(k, "pair"))
+                        .and_then(|p| p.child_by_field_name("value")) {
+                        children.push(self.ast_to_uir(value, source)?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(def, source),
+            node_type: NodeType::ControlFlow(ControlFlowType::Conditional),
+            name: None,
+            children,
+            metadata,
+            source_location: self.create_source_location(def),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_anonymous_function(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut clauses = Vec::new();
+        let mut cursor = node.walk();
+        for clause in node.named_children(&mut cursor) {
+            if clause.kind() == "stab_clause" {
+                clauses.push(self.convert_clause(clause, source)?);
+            }
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.semantic_tags.push("lambda".to_string());
+        let children = if clauses.len() == 1 {
+            clauses.remove(0).children
+        } else {
+            metadata.semantic_tags.push("multi_clause".to_string());
+            clauses
+        };
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            // Elixir data is immutable, so closures hold copies
+            node_type: closures::closure(CaptureMode::ByValue),
+            name: Some("fn".to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// `pattern -> body` clauses of case/receive/fn
+    fn convert_clause(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut metadata = self.create_metadata(node, source);
+        let mut children = Vec::new();
+        
+        if let Some(left) = node.child_by_field_name("left") {
+            let mut cursor = left.walk();
+            let patterns: Vec<Node> = left.named_children(&mut cursor).collect();
+            children.extend(self.convert_patterns(&patterns, &mut metadata, source)?);
+        }
+        if let Some(right) = node.child_by_field_name("right") {
+            children.extend(self.convert_body(right, source)?);
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::ControlFlow(ControlFlowType::Conditional),
+            name: None,
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_patterns(&self, patterns: &[Node], metadata: &mut Metadata, source: &str) -> Result<Vec<UIRNode>> {
+        let mut children = Vec::new();
+        
+        for pattern in patterns {
+            let mut param = self.ast_to_uir(*pattern, source)?;
+            param.metadata.semantic_tags.push("parameter".to_string());
+            if pattern.kind() == "identifier" {
+                param.node_type = NodeType::Variable;
+            } else {
+                param.metadata.semantic_tags.push("pattern".to_string());
+                if !metadata.semantic_tags.contains(&"pattern_matched".to_string()) {
+                    metadata.semantic_tags.push("pattern_matched".to_string());
+                }
+            }
+            children.push(param);
+        }
+        
+        Ok(children)
+    }
+    
+    fn convert_binary_operator(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let operator = node.child_by_field_name("operator")
+            .map(|op| self.node_text(op, source).to_string())
+            .unwrap_or_default();
+        
+        let node_type = match operator.as_str() {
+            "=" => NodeType::Expression(ExpressionType::Assignment),
+            "|>" => NodeType::Expression(ExpressionType::FunctionCall),
+            "==" | "!=" | "===" | "!==" | "<" | ">" | "<=" | ">=" | "in" => NodeType::Expression(ExpressionType::Comparison),
+            "and" | "or" | "&&" | "||" | "when" => NodeType::Expression(ExpressionType::Logical),
+            _ => NodeType::Expression(ExpressionType::Arithmetic),
+        };
+        
+        let mut uir = self.convert_simple(node, source, node_type)?;
+        uir.metadata.annotations.insert("operator".to_string(), Value::String(operator.clone()));
+        if operator == "|>" {
+            uir.name = Some("pipe".to_string());
+            uir.metadata.semantic_tags.push("pipe".to_string());
+        }
+        
+        Ok(uir)
+    }
+    
+    fn convert_named_leaf(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: Some(self.node_text(node, source).to_string()),
+            children: vec![],
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_literal(node, source, &CoalesceLanguage::Elixir, &mut uir);
+        Ok(uir)
+    }
+    
+    fn convert_simple(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: None,
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::Elixir, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::Elixir, &mut uir);
+        Ok(uir)
+    }
+    
+    fn convert_generic(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: self.map_node_type(node.kind()),
+            name: Some(node.kind().to_string()),
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::Elixir, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::Elixir, &mut uir);
+        Ok(uir)
+    }
+    
+    // Helper methods
+    fn convert_children(&self, node: Node, source: &str) -> Result<Vec<UIRNode>> {
+        let mut children = Vec::new();
+        
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.is_extra() {
+                continue;
+            }
+            if matches!(child.kind(), "arguments" | "body" | "block" | "do_block") {
+                children.extend(self.convert_body(child, source)?);
+            } else {
+                children.push(self.ast_to_uir(child, source)?);
+            }
+        }
+        
+        Ok(children)
+    }
+    
+    fn is_def(&self, node: Node, source: &str) -> bool {
+        node.kind() == "call"
+            && node.child_by_field_name("target").is_some_and(|t| DEF_KEYWORDS.contains(&self.node_text(t, source)))
+    }
+    
+    /// Split a `def` head into name, argument patterns and optional guard
+    fn def_head<'a>(&self, def: Node<'a>, source: &str) -> (String, Vec<Node<'a>>, Option<Node<'a>>) {
+        let Some(mut head) = self.find_child_by_kind(def, "arguments").and_then(|args| args.named_child(0)) else {
+            return (String::new(), vec![], None);
+        };
+        
+        let mut guard = None;
+        if head.kind() == "binary_operator"
+            && head.child_by_field_name("operator").is_some_and(|op| self.node_text(op, source) == "when") {
+            guard = head.child_by_field_name("right");
+            head = head.child_by_field_name("left").unwrap_or(head);
+        }
+        
+        match head.kind() {
+            "call" => {
+                let name = head.child_by_field_name("target")
+                    .map(|t| self.node_text(t, source).to_string())
+                    .unwrap_or_default();
+                let args = self.find_child_by_kind(head, "arguments")
+                    .map(|a| {
+                        let mut cursor = a.walk();
+                        let named: Vec<Node> = a.named_children(&mut cursor).collect();
+                        named
+                    })
+                    .unwrap_or_default();
+                (name, args, guard)
+            }
+            _ => (self.node_text(head, source).to_string(), vec![], guard),
+        }
+    }
+    
+    /// Mark a node as a concurrency construct so generators can map it to goroutines or async tasks
+    fn tag_concurrency(&self, metadata: &mut Metadata, construct: &str) {
+        metadata.semantic_tags.push("concurrency".to_string());
+        metadata.semantic_tags.push(construct.to_string());
+        metadata.annotations.insert("concurrency".to_string(), Value::String(construct.to_string()));
+    }
+    
+    fn node_text<'a>(&self, node: Node, source: &'a str) -> &'a str {
+        &source[node.byte_range()]
+    }
+    
+    fn find_child_by_kind<'a>(&self, node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).find(|c| c.kind() == kind);
+        found
+    }
+    
+    fn create_metadata(&self, node: Node, source: &str) -> Metadata {
+        let mut metadata = Metadata {
+            source_language: CoalesceLanguage::Elixir,
+            ..Default::default()
+        };
+        metadata.semantic_tags.push(node.kind().to_string());
+        
+        let text = self.node_text(node, source);
+        if text.len() < 100 {
+            metadata.annotations.insert(
+                "original_text".to_string(),
+                Value::String(text.to_string()),
+            );
+        }
+        
+        metadata
+    }
+    
+    fn create_source_location(&self, node: Node) -> Option<SourceLocation> {
+        Some(SourceLocation {
+            file: String::new(),
+            start_line: node.start_position().row as u32 + 1,
+            end_line: node.end_position().row as u32 + 1,
+            start_column: node.start_position().column as u32,
+            end_column: node.end_position().column as u32,
+        })
+    }
+    
+    fn generate_node_id(&self, node: Node, source: &str) -> String {
+        let text = self.node_text(node, source);
+        
+        format!("{}_{}_{}_{}", node.kind(), node.start_position().row, node.start_position().column,
+                text.chars().take(20).collect::<String>()
+                    .replace(|c: char| !c.is_alphanumeric(), "_"))
+    }
+    
+    fn map_node_type(&self, kind: &str) -> NodeType {
+        match kind {
+            "after_block" => NodeType::ControlFlow(ControlFlowType::Conditional),
+            "rescue_block" | "catch_block" | "else_block" => NodeType::ControlFlow(ControlFlowType::Try),
+            "access_call" => NodeType::Expression(ExpressionType::Variable),
+            _ => NodeType::Expression(ExpressionType::Literal), // Generic fallback
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_elixir_module_and_function() {
+        let parser = ElixirParser::new().unwrap();
+        let source = "defmodule Math do\n  def add(a, b) do\n    a + b\n  end\nend\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let module = &uir.children[0];
+        assert_eq!(module.node_type, NodeType::Module);
+        assert_eq!(module.name.as_deref(), Some("Math"));
+        
+        let func = &module.children[0];
+        assert_eq!(func.node_type, NodeType::Function);
+        assert_eq!(func.name.as_deref(), Some("add"));
+        assert_eq!(func.children.len(), 3);
+    }
+    
+    #[test]
+    fn test_elixir_multi_clause_function() {
+        let parser = ElixirParser::new().unwrap();
+        let source = "defmodule M do\n  def fact(0), do: 1\n  def fact(n) when n > 0, do: n * fact(n - 1)\nend\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let module = &uir.children[0];
+        assert_eq!(module.children.len(), 1);
+        
+        let func = &module.children[0];
+        assert_eq!(func.metadata.annotations["clauses"], 2);
+        assert!(func.metadata.semantic_tags.contains(&"pattern_matched".to_string()));
+        assert!(func.children[1].metadata.annotations.contains_key("guard"));
+    }
+    
+    #[test]
+    fn test_elixir_concurrency_constructs() {
+        let parser = ElixirParser::new().unwrap();
+        let source = "pid = spawn(fn -> loop() end)\nsend(pid, :ping)\nreceive do\n  :pong -> :ok\nend\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let spawn = &uir.children[0].children[1];
+        assert_eq!(spawn.metadata.annotations["concurrency"], "spawn");
+        assert_eq!(uir.children[1].metadata.annotations["concurrency"], "message_send");
+        
+        let receive = &uir.children[2];
+        assert_eq!(receive.node_type, NodeType::ControlFlow(ControlFlowType::Switch));
+        assert_eq!(receive.metadata.annotations["concurrency"], "receive");
+    }
+    
+    #[test]
+    fn test_elixir_receive_after_timeout() {
+        let parser = ElixirParser::new().unwrap();
+        let source = "receive do\n  {:ok, v} -> v\nafter 1000 -> :timeout\nend\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let receive = &uir.children[0];
+        assert_eq!(receive.node_type, NodeType::ControlFlow(ControlFlowType::Switch));
+        let after = receive.children.last().unwrap();
+        assert_eq!(after.name.as_deref(), Some("after_block"));
+        assert_eq!(after.children[0].children[0].metadata.annotations["original_text"], "1000");
+    }
+}
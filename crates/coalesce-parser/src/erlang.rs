@@ -0,0 +1,1096 @@
+use coalesce_core::{UIRNode, NodeType, CaptureMode, ImportedSymbol, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, StatementType, LiteralValue, Operator, Result, CoalesceError,
+                   Parser as CoalesceParser, walk_mut, Walk};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::closures;
+use crate::operators::{annotate_operator, mark_grouped};
+use crate::literals::annotate_literal;
+use serde_json::Value;
+
+/// Built-in functions that start a new process
+const SPAWN_FUNCTIONS: &[&str] = &["spawn", "spawn_link", "spawn_monitor", "spawn_opt", "erlang:spawn", "erlang:spawn_link"];
+
+/// Erlang parser using tree-sitter
+pub struct ErlangParser {
+}
+
+impl CoalesceParser for ErlangParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::Erlang
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        // Create a new parser for this parse operation
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_erlang::language())
+            .map_err(|e| CoalesceError::ParseError {
+                message: format!("Failed to set Erlang language: {}", e),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let tree = parser.parse(source, None)
+            .ok_or_else(|| CoalesceError::ParseError {
+                message: "Failed to parse Erlang source".to_string(),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let mut uir = self.convert_source_file(tree.root_node(), source)?;
+        recovery::summarize(source, tree.root_node(), &mut uir);
+        closures::annotate_captures(&mut uir);
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
+    }
+}
+
+impl ErlangParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    fn ast_to_uir(&self, node: Node, source: &str) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::Erlang, |child| self.ast_to_uir(child, source));
+        }
+        
+        match node.kind() {
+            "anonymous_fun" => self.convert_anonymous_fun(node, source),
+            "call" => self.convert_call(node, source),
+            "binary_op_expr" => self.convert_binary_op(node, source),
+            "match_expr" => self.convert_simple(node, source, NodeType::Expression(ExpressionType::Assignment)),
+            "receive_expr" => {
+                let mut uir = self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Switch))?;
+                self.tag_concurrency(&mut uir.metadata, "receive");
+                Ok(uir)
+            }
+            // An `if` has no condition of its own, only guarded clauses, like a `case`
+            "case_expr" | "if_expr" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Switch)),
+            "cr_clause" | "if_clause" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Conditional)),
+            "try_expr" | "catch_clause" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Try)),
+            "list_comprehension" => self.convert_list_comprehension(node, source),
+            "binary_comprehension" => {
+                let mut uir = self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)))?;
+                uir.metadata.semantic_tags.push("comprehension".to_string());
+                Ok(uir)
+            }
+            "guard" => self.convert_guard(node, source),
+            "paren_expr" => {
+                let mut cursor = node.walk();
+                let inner = node.named_children(&mut cursor).find(|c| !c.is_extra());
+                match inner {
+                    Some(inner) => {
+                        let mut uir = self.ast_to_uir(inner, source)?;
+                        mark_grouped(&mut uir);
+                        Ok(uir)
+                    }
+                    None => self.convert_generic(node, source),
+                }
+            }
+            "var" => self.convert_named_leaf(node, source, NodeType::Expression(ExpressionType::Variable)),
+            "atom" => {
+                let mut uir = self.convert_named_leaf(node, source, NodeType::Expression(ExpressionType::Literal))?;
+                // Atoms other than `true` and `false` are names standing for themselves, as strings do
+                if uir.literal.is_none() {
+                    let atom = self.node_text(node, source);
+                    uir.literal = Some(LiteralValue::String(atom.trim_matches('\'').to_string()));
+                }
+                Ok(uir)
+            }
+            "integer" | "float" | "string" | "char" => {
+                self.convert_named_leaf(node, source, NodeType::Expression(ExpressionType::Literal))
+            }
+            _ => self.convert_generic(node, source),
+        }
+    }
+    
+    fn convert_source_file(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut metadata = self.create_metadata(node, source);
+        let mut name = "erlang_program".to_string();
+        let mut exports = Vec::new();
+        let mut children = Vec::new();
+        
+        // Consecutive clauses of the same function arrive as separate declarations
+        let mut functions: Vec<(String, usize, Vec<Node>)> = Vec::new();
+        
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() != "fun_decl" {
+                self.flush_functions(&mut functions, &mut children, source)?;
+            }
+            match child.kind() {
+                "module_attribute" => {
+                    if let Some(atom) = child.child_by_field_name("name") {
+                        name = self.node_text(atom, source).to_string();
+                    }
+                }
+                "export_attribute" => {
+                    exports.extend(self.children_by_kind(child, "fa").into_iter()
+                        .map(|fa| self.node_text(fa, source).to_string()));
+                }
+                "import_attribute" => children.push(self.convert_import(child, source)),
+                "fun_decl" => {
+                    let Some(clause) = self.find_child_by_kind(child, "function_clause") else {
+                        continue;
+                    };
+                    let fun_name = clause.child_by_field_name("name")
+                        .map(|n| self.node_text(n, source).to_string())
+                        .unwrap_or_default();
+                    let arity = self.clause_args(clause).len();
+                    
+                    match functions.last_mut() {
+                        Some((last_name, last_arity, clauses)) if *last_name == fun_name && *last_arity == arity => {
+                            clauses.push(clause);
+                        }
+                        _ => {
+                            self.flush_functions(&mut functions, &mut children, source)?;
+                            functions.push((fun_name, arity, vec![clause]));
+                        }
+                    }
+                }
+                _ if child.is_extra() => {}
+                _ => children.push(self.ast_to_uir(child, source)?),
+            }
+        }
+        self.flush_functions(&mut functions, &mut children, source)?;
+        
+        for function in children.iter_mut().filter(|c| c.node_type == NodeType::Function) {
+            let key = format!("{}/{}", function.name.as_deref().unwrap_or(""), function.metadata.annotations["arity"]);
+            if exports.contains(&key) {
+                function.metadata.semantic_tags.push("exported".to_string());
+            }
+        }
+        metadata.annotations.insert(
+            "exports".to_string(),
+            Value::Array(exports.into_iter().map(Value::String).collect()),
+        );
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Module,
+            name: Some(name),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn flush_functions(&self, functions: &mut Vec<(String, usize, Vec<Node>)>, children: &mut Vec<UIRNode>, source: &str) -> Result<()> {
+        for (name, arity, clauses) in functions.drain(..) {
+            children.push(self.convert_function(&name, arity, &clauses, source)?);
+        }
+        Ok(())
+    }
+    
+    /// Build one function from its clauses. Several clauses, or patterns and guards in the head,
+    /// become a match over the arguments: conditionals testing each clause in turn
+    fn convert_function(&self, name: &str, arity: usize, clauses: &[Node], source: &str) -> Result<UIRNode> {
+        let first = clauses[0];
+        let last = clauses[clauses.len() - 1];
+        
+        let mut metadata = self.create_metadata(first, source);
+        metadata.annotations.insert("arity".to_string(), Value::from(arity));
+        metadata.annotations.insert("clauses".to_string(), Value::from(clauses.len()));
+        
+        let mut converted = Vec::new();
+        for clause in clauses {
+            converted.push(self.convert_clause(*clause, source)?);
+        }
+        let pattern_matched = converted.iter().any(|c| c.metadata.semantic_tags.contains(&"pattern_matched".to_string()));
+        if pattern_matched {
+            metadata.semantic_tags.push("pattern_matched".to_string());
+        }
+        if converted.len() > 1 {
+            metadata.semantic_tags.push("multi_clause".to_string());
+        }
+        
+        let guarded = converted.iter().any(|c| c.children.iter().any(is_guard));
+        let matched = if converted.len() > 1 || pattern_matched || guarded {
+            self.match_arguments(&converted, arity)
+        } else {
+            None
+        };
+        let mut children = match matched {
+            Some(children) => children,
+            None if converted.len() == 1 => {
+                let clause = converted.remove(0).children;
+                let (parameters, body): (Vec<UIRNode>, Vec<UIRNode>) = clause.into_iter().partition(is_parameter);
+                parameters.into_iter().chain(self.lower_body(body)).collect()
+            }
+            // Heads with list or other patterns are kept as the clauses they are
+            None => converted,
+        };
+        self.return_last(&mut children);
+        
+        let mut location = self.create_source_location(first);
+        if let Some(location) = location.as_mut() {
+            location.end_line = last.end_position().row as u32 + 1;
+            location.end_column = last.end_position().column as u32;
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(first, source),
+            node_type: NodeType::Function,
+            name: Some(name.to_string()),
+            children,
+            metadata,
+            source_location: location,
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// A clause holds its parameter patterns, an optional guard and the body expressions
+    fn convert_clause(&self, clause: Node, source: &str) -> Result<UIRNode> {
+        let mut metadata = self.create_metadata(clause, source);
+        if clause.kind() != "function_clause" {
+            metadata.semantic_tags.push("function_clause".to_string());
+        }
+        
+        let mut children = Vec::new();
+        for arg in self.clause_args(clause) {
+            let mut param = self.ast_to_uir(arg, source)?;
+            param.metadata.semantic_tags.push("parameter".to_string());
+            if arg.kind() == "var" {
+                param.node_type = NodeType::Variable;
+            } else {
+                param.metadata.semantic_tags.push("pattern".to_string());
+                if !metadata.semantic_tags.contains(&"pattern_matched".to_string()) {
+                    metadata.semantic_tags.push("pattern_matched".to_string());
+                }
+            }
+            children.push(param);
+        }
+        
+        let mut cursor = clause.walk();
+        for child in clause.named_children(&mut cursor) {
+            match child.kind() {
+                "guard" => {
+                    let guard = self.convert_guard(child, source)?;
+                    metadata.annotations.insert("guard".to_string(), Value::String(self.node_text(child, source).to_string()));
+                    children.push(guard);
+                }
+                "clause_body" => children.extend(self.convert_children(child, source)?),
+                _ => {}
+            }
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(clause, source),
+            node_type: NodeType::ControlFlow(ControlFlowType::Conditional),
+            name: None,
+            children,
+            metadata,
+            source_location: self.create_source_location(clause),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// A guard as one condition: its `,`-separated tests all hold, in one of its `;`-separated
+    /// alternatives
+    fn convert_guard(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut alternatives = Vec::new();
+        let mut cursor = node.walk();
+        for alternative in node.named_children(&mut cursor).filter(|c| !c.is_extra()) {
+            let tests = if alternative.kind() == "guard_clause" {
+                self.convert_children(alternative, source)?
+            } else {
+                vec![self.ast_to_uir(alternative, source)?]
+            };
+            alternatives.extend(self.combined(tests, Operator::And));
+        }
+        let mut guard = match self.combined(alternatives, Operator::Or) {
+            Some(guard) => guard,
+            None => return self.convert_simple(node, source, NodeType::Expression(ExpressionType::Logical)),
+        };
+        guard.metadata.semantic_tags.push("guard".to_string());
+        Ok(guard)
+    }
+    
+    /// `[X * 2 || X <- L, X > 1]` in the shape of Python's comprehensions: the element, a
+    /// `for_in_clause` loop per generator and an `if_clause` per filter
+    fn convert_list_comprehension(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut uir = self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)))?;
+        uir.metadata.semantic_tags.push("comprehension".to_string());
+        let Some(position) = uir.children.iter().position(|c| c.metadata.semantic_tags.first().is_some_and(|t| t == "lc_exprs")) else {
+            return Ok(uir);
+        };
+        for mut qualifier in uir.children.remove(position).children {
+            if qualifier.metadata.semantic_tags.first().is_some_and(|t| t == "generator") {
+                qualifier.node_type = NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach));
+                qualifier.name = Some("for_in_clause".to_string());
+                qualifier.metadata.semantic_tags.insert(0, "for_in_clause".to_string());
+                uir.children.push(qualifier);
+            } else {
+                let id = format!("{}_filter", qualifier.id);
+                let mut filter = self.lowered(id, NodeType::ControlFlow(ControlFlowType::Conditional), "if_clause", vec![qualifier], &uir);
+                filter.name = Some("if_clause".to_string());
+                uir.children.push(filter);
+            }
+        }
+        Ok(uir)
+    }
+    
+    /// A function's clauses as a match over its arguments, or `None` when a head holds a pattern
+    /// other than a variable, a literal or a tuple of them. Each parameter is named by the first clause binding
+    /// a variable in its place, and the other clauses' variables there are renamed to it
+    fn match_arguments(&self, clauses: &[UIRNode], arity: usize) -> Option<Vec<UIRNode>> {
+        let heads: Vec<Vec<&UIRNode>> = clauses.iter().map(|c| c.children.iter().filter(|p| is_parameter(p)).collect()).collect();
+        let mut parameters = Vec::new();
+        for position in 0..arity {
+            let bound = heads.iter().filter_map(|head| head.get(position)).find(|p| binding(p).is_some());
+            let parameter = match bound {
+                Some(&variable) => variable.clone(),
+                None => {
+                    let mut parameter = self.lowered(
+                        format!("{}_arg{}", clauses[0].id, position + 1), NodeType::Variable, "parameter", Vec::new(), &clauses[0],
+                    );
+                    parameter.name = Some(format!("Arg{}", position + 1));
+                    parameter
+                }
+            };
+            parameters.push(parameter);
+        }
+        let subjects: Vec<UIRNode> = parameters.iter()
+            .map(|p| self.variable(p.name.as_deref().unwrap_or_default(), &clauses[0]))
+            .collect();
+        
+        let mut branches = Vec::new();
+        for (clause, head) in clauses.iter().zip(&heads) {
+            let mut bindings = Vec::new();
+            let mut tests = Vec::new();
+            for (pattern, subject) in head.iter().zip(&subjects) {
+                tests.extend(self.pattern_tests(pattern, subject, &mut bindings, clause)?);
+            }
+            let body = clause.children.iter().filter(|c| !is_parameter(c)).cloned().collect();
+            branches.push(self.branch(clause, tests, body, &bindings));
+        }
+        parameters.extend(self.clause_chain(branches, Some("function_clause"), &clauses[0]));
+        Some(parameters)
+    }
+    
+    /// Lower the `if`, `case` and `receive` expressions among `body`'s statements to
+    /// conditionals; those matching list or other patterns stay as they are
+    fn lower_body(&self, body: Vec<UIRNode>) -> Vec<UIRNode> {
+        let mut lowered = Vec::new();
+        for statement in body {
+            let replacement = match statement.metadata.semantic_tags.first().map(String::as_str) {
+                Some("if_expr") if statement.node_type == NodeType::ControlFlow(ControlFlowType::Switch) => self.lower_if(&statement),
+                Some("case_expr") if statement.node_type == NodeType::ControlFlow(ControlFlowType::Switch) => self.lower_case(&statement),
+                Some("receive_expr") if statement.node_type == NodeType::ControlFlow(ControlFlowType::Switch) => self.lower_receive(&statement),
+                _ => None,
+            };
+            match replacement {
+                Some(statements) => lowered.extend(statements),
+                None => lowered.push(statement),
+            }
+        }
+        lowered
+    }
+    
+    /// `if`'s clauses hold only a guard, and one of `true` always runs
+    fn lower_if(&self, switch: &UIRNode) -> Option<Vec<UIRNode>> {
+        let branches = switch.children.iter()
+            .map(|clause| self.branch(clause, Vec::new(), clause.children.clone(), &[]))
+            .map(|(condition, body, clause)| {
+                let always = condition.as_ref().is_some_and(|c| c.literal == Some(LiteralValue::Bool(true)));
+                (condition.filter(|_| !always), body, clause)
+            })
+            .collect();
+        Some(self.clause_chain(branches, Some("if_clause"), switch))
+    }
+    
+    /// `case Expr of` matches each clause's pattern against the expression, bound first to
+    /// `Subject` unless it is a variable already
+    fn lower_case(&self, switch: &UIRNode) -> Option<Vec<UIRNode>> {
+        let (subject, clauses) = switch.children.split_first()?;
+        let mut statements = Vec::new();
+        let name = match (&subject.node_type, subject.name.as_deref()) {
+            (NodeType::Expression(ExpressionType::Variable), Some(name)) if !name.starts_with('_') => name.to_string(),
+            _ => {
+                statements.push(self.binding_of("Subject", subject.clone(), switch));
+                "Subject".to_string()
+            }
+        };
+        let branches = self.pattern_branches(clauses, &self.variable(&name, switch))?;
+        statements.extend(self.clause_chain(branches, Some("case_clause"), switch));
+        Some(statements)
+    }
+    
+    /// `receive` takes the next message, bound to `Message`, and matches it like a `case`;
+    /// a timeout (`after`) has no such lowering
+    fn lower_receive(&self, switch: &UIRNode) -> Option<Vec<UIRNode>> {
+        if switch.children.iter().any(|c| c.metadata.semantic_tags.first().is_some_and(|t| t == "receive_after")) {
+            return None;
+        }
+        let branches = self.pattern_branches(&switch.children, &self.variable("Message", switch))?;
+        let mut receive = self.lowered(format!("{}_message", switch.id), NodeType::Expression(ExpressionType::FunctionCall), "call", Vec::new(), switch);
+        receive.name = Some("receive".to_string());
+        self.tag_concurrency(&mut receive.metadata, "receive");
+        let mut statements = vec![self.binding_of("Message", receive, switch)];
+        // No clause matching leaves the message in the mailbox rather than failing
+        statements.extend(self.clause_chain(branches, None, switch));
+        Some(statements)
+    }
+    
+    /// The branches of `case` or `receive` clauses, each a pattern, maybe a guard and a body
+    fn pattern_branches<'a>(&self, clauses: &'a [UIRNode], subject: &UIRNode) -> Option<Vec<Branch<'a>>> {
+        clauses.iter()
+            .map(|clause| {
+                let (pattern, body) = clause.children.split_first()?;
+                let mut bindings = Vec::new();
+                let tests = self.pattern_tests(pattern, subject, &mut bindings, clause)?;
+                Some(self.branch(clause, tests, body.to_vec(), &bindings))
+            })
+            .collect()
+    }
+    
+    /// The tests matching `pattern` against `subject` makes: an equality for a literal, one per
+    /// element of a tuple, indexing it, and none for a variable, bound to `subject` in
+    /// `bindings` unless an earlier pattern bound it. `None` for a pattern of any other shape
+    fn pattern_tests(&self, pattern: &UIRNode, subject: &UIRNode, bindings: &mut Vec<(String, UIRNode)>, clause: &UIRNode) -> Option<Vec<UIRNode>> {
+        let equal = |value: UIRNode| {
+            let mut test = self.lowered(
+                format!("{}_test", pattern.id), NodeType::Expression(ExpressionType::Comparison), "pattern_test", vec![subject.clone(), value], clause,
+            );
+            test.operator = Some(Operator::Equal);
+            test
+        };
+        if matches!(pattern.node_type, NodeType::Variable | NodeType::Expression(ExpressionType::Variable)) {
+            let name = pattern.name.as_deref()?;
+            if name.starts_with('_') {
+                return Some(Vec::new());
+            }
+            // `same(X, X)` matches equal arguments only
+            if let Some((_, bound)) = bindings.iter().find(|(bound, _)| bound == name) {
+                return Some(vec![equal(bound.clone())]);
+            }
+            bindings.push((name.to_string(), subject.clone()));
+            return Some(Vec::new());
+        }
+        let negated_literal = pattern.operator == Some(Operator::Negate) && pattern.children.iter().all(|c| c.literal.is_some());
+        if pattern.literal.is_some() || negated_literal {
+            let mut value = pattern.clone();
+            value.metadata.semantic_tags.retain(|t| t != "parameter" && t != "pattern");
+            return Some(vec![equal(value)]);
+        }
+        if pattern.metadata.semantic_tags.first().is_some_and(|t| t == "tuple") {
+            let mut tests = Vec::new();
+            for (index, element) in pattern.children.iter().enumerate() {
+                let mut position = self.lowered(format!("{}_index", element.id), NodeType::Expression(ExpressionType::Literal), "integer", Vec::new(), clause);
+                position.literal = Some(LiteralValue::Int(index as i64));
+                let mut item = self.lowered(format!("{}_element", element.id), NodeType::Expression(ExpressionType::Literal), "subscript", vec![subject.clone(), position], clause);
+                item.name = Some("subscript".to_string());
+                let subject_text = subject.metadata.annotations.get("original_text").and_then(|t| t.as_str()).or(subject.name.as_deref()).unwrap_or_default();
+                item.metadata.annotations.insert("original_text".to_string(), Value::String(format!("{}[{}]", subject_text, index)));
+                tests.extend(self.pattern_tests(element, &item, bindings, clause)?);
+            }
+            return Some(tests);
+        }
+        None
+    }
+    
+    /// A clause as a branch: the condition its pattern `tests` and guard make, `None` when it
+    /// always matches, and its lowered body. Variables the pattern bound to another variable are
+    /// renamed to it, and to anything else are assigned at the top of the body; the guard reads
+    /// what they are bound to
+    fn branch<'a>(&self, clause: &'a UIRNode, mut tests: Vec<UIRNode>, body: Vec<UIRNode>, bindings: &[(String, UIRNode)]) -> Branch<'a> {
+        let (guards, mut body): (Vec<UIRNode>, Vec<UIRNode>) = body.into_iter().partition(is_guard);
+        let bound = |name: &str| bindings.iter().find(|(bound, _)| bound == name).map(|(_, value)| value);
+        for mut guard in guards {
+            walk_mut(&mut guard, &mut |reference: &mut UIRNode| {
+                match reference.name.as_deref().filter(|_| is_variable(reference)).and_then(bound) {
+                    Some(value) => {
+                        *reference = value.clone();
+                        Walk::SkipChildren
+                    }
+                    None => Walk::Continue,
+                }
+            });
+            tests.push(guard);
+        }
+        for node in body.iter_mut() {
+            walk_mut(node, &mut |reference: &mut UIRNode| {
+                if let Some(value) = reference.name.as_deref().filter(|_| is_variable(reference)).and_then(bound).filter(|v| is_variable(v)) {
+                    reference.name = value.name.clone();
+                }
+                Walk::Continue
+            });
+        }
+        let assigned = bindings.iter()
+            .filter(|(_, value)| !is_variable(value))
+            .map(|(name, value)| self.binding_of(name, value.clone(), clause));
+        let body = assigned.chain(body).collect();
+        (self.combined(tests, Operator::And), self.lower_body(body), clause)
+    }
+    
+    /// Branches as the conditional Python's parser builds for `if`/`elif`/`else`: a condition
+    /// and a block, then `elif_clause` conditionals and an `else_clause`. Clauses after one that
+    /// always matches are unreachable; without one, the `else` raises `failure`, as Erlang does
+    fn clause_chain(&self, branches: Vec<Branch<'_>>, failure: Option<&str>, origin: &UIRNode) -> Vec<UIRNode> {
+        let mut conditioned = Vec::new();
+        let mut otherwise = None;
+        for (condition, body, clause) in branches {
+            match condition {
+                Some(condition) => conditioned.push((condition, body, clause)),
+                None => {
+                    otherwise = Some((body, clause));
+                    break;
+                }
+            }
+        }
+        let mut conditioned = conditioned.into_iter();
+        let Some((condition, body, clause)) = conditioned.next() else {
+            return otherwise.map(|(body, _)| body).unwrap_or_default();
+        };
+        let block = |body: Vec<UIRNode>, clause: &UIRNode| {
+            let mut block = self.lowered(format!("{}_block", clause.id), NodeType::Statement(StatementType::Expression), "block", body, clause);
+            block.name = Some("block".to_string());
+            block
+        };
+        let tag = origin.metadata.semantic_tags.first().map(String::as_str).unwrap_or("clause");
+        let mut chain = self.lowered(format!("{}_match", origin.id), NodeType::ControlFlow(ControlFlowType::Conditional), tag, vec![condition, block(body, clause)], clause);
+        chain.metadata.semantic_tags.push("clause_match".to_string());
+        chain.metadata.annotations.extend(clause.metadata.annotations.iter().filter(|(k, _)| *k == "guard").map(|(k, v)| (k.clone(), v.clone())));
+        for (condition, body, clause) in conditioned {
+            let mut elif = self.lowered(format!("{}_elif", clause.id), NodeType::ControlFlow(ControlFlowType::Conditional), "elif_clause", vec![condition, block(body, clause)], clause);
+            elif.metadata.semantic_tags.push("clause_match".to_string());
+            elif.metadata.annotations.extend(clause.metadata.annotations.iter().filter(|(k, _)| *k == "guard").map(|(k, v)| (k.clone(), v.clone())));
+            chain.children.push(elif);
+        }
+        let otherwise = match (otherwise, failure) {
+            (Some((body, clause)), _) => Some(block(body, clause)),
+            (None, Some(failure)) => {
+                let mut error = self.lowered(format!("{}_error", origin.id), NodeType::Expression(ExpressionType::Literal), "atom", Vec::new(), origin);
+                error.literal = Some(LiteralValue::String(failure.to_string()));
+                let raise = self.lowered(format!("{}_raise", origin.id), NodeType::Statement(StatementType::Throw), "error", vec![error], origin);
+                Some(block(vec![raise], origin))
+            }
+            (None, None) => None,
+        };
+        if let Some(otherwise) = otherwise {
+            let mut clause = self.lowered(format!("{}_else", otherwise.id), NodeType::Statement(StatementType::Expression), "else_clause", vec![otherwise], origin);
+            clause.name = Some("else_clause".to_string());
+            chain.children.push(clause);
+        }
+        vec![chain]
+    }
+    
+    /// The last expression of a body is its value: returned, or in a lowered match, the last
+    /// of each branch is
+    fn return_last(&self, body: &mut [UIRNode]) {
+        let Some(last) = body.last_mut() else { return };
+        let tags = &last.metadata.semantic_tags;
+        if tags.iter().any(|t| t == "clause_match") {
+            for branch in last.children.iter_mut().skip(1) {
+                self.return_last(std::slice::from_mut(branch));
+            }
+        } else if last.node_type == NodeType::Statement(StatementType::Expression) && tags.iter().any(|t| t == "block" || t == "else_clause") {
+            self.return_last(&mut last.children);
+        } else if matches!(last.node_type, NodeType::Expression(_) | NodeType::Closure { .. }) && last.node_type != NodeType::Expression(ExpressionType::Assignment)
+            || tags.iter().any(|t| t == "comprehension")
+        {
+            let id = format!("{}_return", last.id);
+            let location = last.clone();
+            let value = std::mem::replace(last, UIRNode::new(String::new(), NodeType::Statement(StatementType::Return)));
+            *last = self.lowered(id, NodeType::Statement(StatementType::Return), "return", vec![value], &location);
+        }
+    }
+    
+    /// A reference to the variable `name`
+    fn variable(&self, name: &str, origin: &UIRNode) -> UIRNode {
+        let mut variable = self.lowered(format!("{}_{}_reference", origin.id, name), NodeType::Expression(ExpressionType::Variable), "var", Vec::new(), origin);
+        variable.name = Some(name.to_string());
+        variable
+    }
+    
+    /// `Name = value`, as a match binding a fresh variable
+    fn binding_of(&self, name: &str, value: UIRNode, origin: &UIRNode) -> UIRNode {
+        let mut variable = self.lowered(format!("{}_{}", origin.id, name), NodeType::Variable, "var", Vec::new(), origin);
+        variable.name = Some(name.to_string());
+        self.lowered(format!("{}_{}_binding", origin.id, name), NodeType::Expression(ExpressionType::Assignment), "match_expr", vec![variable, value], origin)
+    }
+    
+    /// `operands` joined by `operator`, left to right; `None` when there are none
+    fn combined(&self, operands: Vec<UIRNode>, operator: Operator) -> Option<UIRNode> {
+        operands.into_iter().reduce(|left, right| {
+            let id = format!("{}_{}", left.id, right.id);
+            let origin = left.clone();
+            let mut node = self.lowered(id, NodeType::Expression(ExpressionType::Logical), "binary_op_expr", vec![left, right], &origin);
+            node.operator = Some(operator);
+            node
+        })
+    }
+    
+    /// A node the lowering makes, placed where `origin` is
+    fn lowered(&self, id: String, node_type: NodeType, tag: &str, children: Vec<UIRNode>, origin: &UIRNode) -> UIRNode {
+        UIRNode {
+            id,
+            node_type,
+            name: None,
+            children,
+            metadata: Metadata {
+                source_language: CoalesceLanguage::Erlang,
+                semantic_tags: vec![tag.to_string()],
+                ..Default::default()
+            },
+            source_location: origin.source_location.clone(),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+    
+    fn convert_anonymous_fun(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let clauses = self.children_by_kind(node, "fun_clause");
+        let Some(first) = clauses.first() else {
+            return self.convert_generic(node, source);
+        };
+        let mut uir = self.convert_function("fun", self.clause_args(*first).len(), &clauses, source)?;
+        uir.id = self.generate_node_id(node, source);
+        // Erlang terms are immutable, so funs hold copies
+        uir.node_type = closures::closure(CaptureMode::ByValue);
+        uir.metadata.semantic_tags.push("lambda".to_string());
+        Ok(uir)
+    }
+    
+    fn convert_import(&self, node: Node, source: &str) -> UIRNode {
+        let mut metadata = self.create_metadata(node, source);
+        
+        let module_name = node.child_by_field_name("module")
+            .map(|n| self.node_text(n, source).to_string())
+            .unwrap_or_default();
+        metadata.dependencies.push(module_name.clone());
+        // `-import(lists, [map/2, filter/2]).`
+        let mut cursor = node.walk();
+        let symbols = node.children_by_field_name("funs", &mut cursor)
+            .map(|fun| ImportedSymbol::new(self.node_text(fun, source)))
+            .collect();
+        
+        UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Import { path: module_name.clone(), alias: None, symbols },
+            name: Some(module_name),
+            children: vec![],
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+    
+    fn convert_call(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let callee = node.child_by_field_name("expr");
+        let name = callee.map(|c| self.node_text(c, source).to_string()).unwrap_or_default();
+        
+        let mut metadata = self.create_metadata(node, source);
+        if let Some((module, _)) = name.split_once(':') {
+            metadata.dependencies.push(module.to_string());
+        }
+        if SPAWN_FUNCTIONS.contains(&name.as_str()) {
+            self.tag_concurrency(&mut metadata, "spawn");
+        }
+        
+        let children = match node.child_by_field_name("args") {
+            Some(args) => self.convert_children(args, source)?,
+            None => vec![],
+        };
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::FunctionCall),
+            name: Some(name),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_binary_op(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let operator = node.child_by_field_name("lhs")
+            .and_then(|lhs| lhs.next_sibling())
+            .map(|op| self.node_text(op, source).to_string())
+            .unwrap_or_default();
+        
+        let node_type = match operator.as_str() {
+            "!" => NodeType::Expression(ExpressionType::FunctionCall),
+            "==" | "/=" | "=:=" | "=/=" | "<" | ">" | "=<" | ">=" => NodeType::Expression(ExpressionType::Comparison),
+            "and" | "or" | "andalso" | "orelse" | "xor" => NodeType::Expression(ExpressionType::Logical),
+            _ => NodeType::Expression(ExpressionType::Arithmetic),
+        };
+        
+        let mut uir = self.convert_simple(node, source, node_type)?;
+        uir.metadata.annotations.insert("operator".to_string(), Value::String(operator.clone()));
+        if operator == "!" {
+            uir.name = Some("send".to_string());
+            self.tag_concurrency(&mut uir.metadata, "message_send");
+            return Ok(uir);
+        }
+        
+        Ok(rebalanced(uir))
+    }
+    
+    fn convert_named_leaf(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: Some(self.node_text(node, source).to_string()),
+            children: vec![],
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_literal(node, source, &CoalesceLanguage::Erlang, &mut uir);
+        Ok(uir)
+    }
+    
+    fn convert_simple(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: None,
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::Erlang, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::Erlang, &mut uir);
+        Ok(uir)
+    }
+    
+    fn convert_generic(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: self.map_node_type(node.kind()),
+            name: Some(node.kind().to_string()),
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::Erlang, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::Erlang, &mut uir);
+        Ok(uir)
+    }
+    
+    // Helper methods
+    fn convert_children(&self, node: Node, source: &str) -> Result<Vec<UIRNode>> {
+        let mut children = Vec::new();
+        
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.is_extra() {
+                continue;
+            }
+            if matches!(child.kind(), "clause_body" | "expr_args" | "guard_clause") {
+                children.extend(self.convert_children(child, source)?);
+            } else {
+                children.push(self.ast_to_uir(child, source)?);
+            }
+        }
+        
+        Ok(children)
+    }
+    
+    fn clause_args<'a>(&self, clause: Node<'a>) -> Vec<Node<'a>> {
+        let Some(args) = self.find_child_by_kind(clause, "expr_args") else {
+            return vec![];
+        };
+        let mut cursor = args.walk();
+        let named = args.named_children(&mut cursor).filter(|c| !c.is_extra()).collect();
+        named
+    }
+    
+    /// Mark a node as a concurrency construct so generators can map it to goroutines or async tasks
+    fn tag_concurrency(&self, metadata: &mut Metadata, construct: &str) {
+        metadata.semantic_tags.push("concurrency".to_string());
+        metadata.semantic_tags.push(construct.to_string());
+        metadata.annotations.insert("concurrency".to_string(), Value::String(construct.to_string()));
+    }
+    
+    fn node_text<'a>(&self, node: Node, source: &'a str) -> &'a str {
+        &source[node.byte_range()]
+    }
+    
+    fn children_by_kind<'a>(&self, node: Node<'a>, kind: &str) -> Vec<Node<'a>> {
+        let mut cursor = node.walk();
+        let children = node.children(&mut cursor).filter(|c| c.kind() == kind).collect();
+        children
+    }
+    
+    fn find_child_by_kind<'a>(&self, node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).find(|c| c.kind() == kind);
+        found
+    }
+    
+    fn create_metadata(&self, node: Node, source: &str) -> Metadata {
+        let mut metadata = Metadata {
+            source_language: CoalesceLanguage::Erlang,
+            ..Default::default()
+        };
+        metadata.semantic_tags.push(node.kind().to_string());
+        
+        let text = self.node_text(node, source);
+        if text.len() < 100 {
+            metadata.annotations.insert(
+                "original_text".to_string(),
+                Value::String(text.to_string()),
+            );
+        }
+        
+        metadata
+    }
+    
+    fn create_source_location(&self, node: Node) -> Option<SourceLocation> {
+        Some(SourceLocation {
+            file: String::new(),
+            start_line: node.start_position().row as u32 + 1,
+            end_line: node.end_position().row as u32 + 1,
+            start_column: node.start_position().column as u32,
+            end_column: node.end_position().column as u32,
+        })
+    }
+    
+    fn generate_node_id(&self, node: Node, source: &str) -> String {
+        let text = self.node_text(node, source);
+        
+        format!("{}_{}_{}_{}", node.kind(), node.start_position().row, node.start_position().column,
+                text.chars().take(20).collect::<String>()
+                    .replace(|c: char| !c.is_alphanumeric(), "_"))
+    }
+    
+    fn map_node_type(&self, kind: &str) -> NodeType {
+        match kind {
+            "receive_after" => NodeType::ControlFlow(ControlFlowType::Conditional),
+            "remote" | "record_field_expr" => NodeType::Expression(ExpressionType::Variable),
+            "unary_op_expr" => NodeType::Expression(ExpressionType::Arithmetic),
+            _ => NodeType::Expression(ExpressionType::Literal), // Generic fallback
+        }
+    }
+}
+
+/// `uir` regrouped where the grammar binds a comparison tighter than arithmetic, reading
+/// `X + 1 > 2` as `X + (1 > 2)`: an operand binding looser than its operator without
+/// parentheses takes the operator in instead
+fn rebalanced(mut uir: UIRNode) -> UIRNode {
+    let loose = |operand: &UIRNode, operator: Operator| {
+        operand.metadata.semantic_tags.first().is_some_and(|t| t == "binary_op_expr")
+            && !operand.metadata.semantic_tags.iter().any(|t| t == "parenthesized")
+            && operand.children.len() == 2
+            && operand.operator.is_some_and(|inner| inner.precedence() < operator.precedence())
+    };
+    let Some(operator) = uir.operator else { return uir };
+    if uir.children.len() != 2 {
+        return uir;
+    }
+    // `a op (b inner c)` is `(a op b) inner c`
+    if loose(&uir.children[1], operator) {
+        let mut outer = uir.children.remove(1);
+        uir.children.push(outer.children.remove(0));
+        retext(&mut uir);
+        outer.children.insert(0, rebalanced(uir));
+        retext(&mut outer);
+        return rebalanced(outer);
+    }
+    // `(a inner b) op c` is `a inner (b op c)`
+    if loose(&uir.children[0], operator) {
+        let mut outer = uir.children.remove(0);
+        uir.children.insert(0, outer.children.remove(1));
+        retext(&mut uir);
+        outer.children.push(rebalanced(uir));
+        retext(&mut outer);
+        return rebalanced(outer);
+    }
+    uir
+}
+
+/// Rewrite a regrouped operation's original text from its operands'
+fn retext(uir: &mut UIRNode) {
+    let text = |node: &UIRNode| node.metadata.annotations.get("original_text").and_then(|t| t.as_str()).map(str::to_string);
+    let symbol = uir.metadata.annotations.get("operator").and_then(|t| t.as_str()).map(str::to_string);
+    if let (Some(left), Some(symbol), Some(right)) = (text(&uir.children[0]), symbol, text(&uir.children[1])) {
+        uir.metadata.annotations.insert("original_text".to_string(), Value::String(format!("{} {} {}", left, symbol, right)));
+    }
+}
+
+/// A parameter of a function clause, a variable or a pattern
+fn is_parameter(node: &UIRNode) -> bool {
+    node.metadata.semantic_tags.iter().any(|t| t == "parameter")
+}
+
+/// A clause lowered to the condition that selects it, `None` when it always matches, its body
+/// and the clause itself
+type Branch<'a> = (Option<UIRNode>, Vec<UIRNode>, &'a UIRNode);
+
+fn is_variable(node: &UIRNode) -> bool {
+    matches!(node.node_type, NodeType::Variable | NodeType::Expression(ExpressionType::Variable))
+}
+
+fn is_guard(node: &UIRNode) -> bool {
+    node.metadata.semantic_tags.iter().any(|t| t == "guard")
+}
+
+/// The variable a pattern binds when it is a plain one, not `_`
+fn binding(pattern: &UIRNode) -> Option<&str> {
+    let name = pattern.name.as_deref()?;
+    (pattern.node_type == NodeType::Variable && !name.starts_with('_')).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_erlang_module_and_exports() {
+        let parser = ErlangParser::new().unwrap();
+        let source = "-module(math).\n-export([add/2]).\n\nadd(A, B) -> A + B.\n";
+        
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.name.as_deref(), Some("math"));
+        
+        let func = &uir.children[0];
+        assert_eq!(func.node_type, NodeType::Function);
+        assert_eq!(func.name.as_deref(), Some("add"));
+        assert!(func.metadata.semantic_tags.contains(&"exported".to_string()));
+        assert_eq!(func.children[0].node_type, NodeType::Variable);
+    }
+    
+    #[test]
+    fn test_erlang_multi_clause_function() {
+        let parser = ErlangParser::new().unwrap();
+        let source = "-module(m).\n\nfact(0) -> 1;\nfact(N) when N > 0 -> N * fact(N - 1).\n";
+        
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.children.len(), 1);
+        
+        let func = &uir.children[0];
+        assert_eq!(func.metadata.annotations["clauses"], 2);
+        assert!(func.metadata.semantic_tags.contains(&"multi_clause".to_string()));
+        assert!(func.metadata.semantic_tags.contains(&"pattern_matched".to_string()));
+        let chain = &func.children[1];
+        assert_eq!(chain.node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        assert_eq!(chain.children[0].operator, Some(Operator::Equal));
+        let guarded = chain.children.iter().find(|c| c.metadata.semantic_tags.contains(&"elif_clause".to_string())).unwrap();
+        assert!(guarded.metadata.annotations.contains_key("guard"));
+        // No clause matches a negative argument
+        let otherwise = chain.children.last().unwrap();
+        assert!(otherwise.metadata.semantic_tags.contains(&"else_clause".to_string()));
+        assert_eq!(otherwise.children[0].children[0].node_type, NodeType::Statement(StatementType::Throw));
+    }
+    
+    #[test]
+    fn test_erlang_concurrency_constructs() {
+        let parser = ErlangParser::new().unwrap();
+        let source = "-module(m).\n\nstart() -> spawn(fun() -> loop() end).\n\nloop() ->\n    receive\n        {ping, From} -> From ! pong, loop()\n    end.\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let spawn = &uir.children[0].children[0].children[0];
+        assert_eq!(spawn.metadata.annotations["concurrency"], "spawn");
+        
+        // The message is received, then matched element by element
+        let received = &uir.children[1].children[0];
+        assert_eq!(received.node_type, NodeType::Expression(ExpressionType::Assignment));
+        assert_eq!(received.children[1].metadata.annotations["concurrency"], "receive");
+        
+        let matched = &uir.children[1].children[1];
+        assert_eq!(matched.node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        assert_eq!(matched.children[0].children[0].metadata.annotations["original_text"], "Message[0]");
+        let body = &matched.children[1].children;
+        assert_eq!(body[0].children[0].name.as_deref(), Some("From"));
+        assert_eq!(body[1].metadata.annotations["concurrency"], "message_send");
+    }
+    
+    #[test]
+    fn test_erlang_if_is_a_chain_of_guarded_conditionals() {
+        let parser = ErlangParser::new().unwrap();
+        let source = "-module(m).\n\nsign(X) ->\n    if X > 0 -> 1; true -> 0 end.\n";
+        
+        let uir = parser.parse(source).unwrap();
+        assert!(uir.validate().is_ok());
+        let chain = &uir.children[0].children[1];
+        assert_eq!(chain.node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        assert_eq!(chain.children[0].operator, Some(Operator::Greater));
+        // `true` is the clause that always matches
+        let otherwise = &chain.children[2];
+        assert!(otherwise.metadata.semantic_tags.contains(&"else_clause".to_string()));
+        assert_eq!(otherwise.children[0].children[0].node_type, NodeType::Statement(StatementType::Return));
+    }
+    
+    #[test]
+    fn test_erlang_list_comprehension_is_a_python_comprehension() {
+        let parser = ErlangParser::new().unwrap();
+        let source = "-module(m).\n\nevens(L) -> [X || X <- L, X rem 2 =:= 0].\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let comprehension = &uir.children[0].children[1].children[0];
+        assert_eq!(comprehension.node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)));
+        assert!(comprehension.metadata.semantic_tags.contains(&"list_comprehension".to_string()));
+        assert_eq!(comprehension.children[1].name.as_deref(), Some("for_in_clause"));
+        assert_eq!(comprehension.children[2].name.as_deref(), Some("if_clause"));
+    }
+}
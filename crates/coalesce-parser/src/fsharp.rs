@@ -1,9 +1,70 @@
-use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage, 
-                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser};
+use coalesce_core::{UIRNode, NodeType, Attribute, CaptureMode, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, StatementType, Result, CoalesceError,
+                   Parser as CoalesceParser, Operator, LiteralValue};
 use serde_json::Value;
-use std::collections::HashMap;
-use regex::Regex;
+use crate::operators::{mark_grouped, takes_operator};
+use crate::closures;
 
+const KEYWORDS: &[&str] = &[
+    "abstract", "and", "and!", "as", "assert", "begin", "class", "default", "delegate", "do", "do!", "done",
+    "downcast", "downto", "elif", "else", "end", "exception", "extern", "false", "finally", "for", "fun",
+    "function", "if", "in", "inherit", "inline", "interface", "internal", "lazy", "let", "let!", "match",
+    "match!", "member", "module", "mutable", "namespace", "new", "null", "of", "open", "or", "override",
+    "private", "public", "rec", "return", "return!", "static", "struct", "then", "to", "true", "try", "type",
+    "upcast", "use", "use!", "val", "when", "while", "with", "yield", "yield!",
+];
+
+/// Keywords that may carry a `!` suffix inside computation expressions
+const BANG_KEYWORDS: &[&str] = &["let", "use", "do", "return", "yield", "match", "and"];
+
+/// Keywords that start an expression extending to the end of the enclosing block
+const LEADING_KEYWORDS: &[&str] = &[
+    "if", "match", "match!", "fun", "function", "try", "for", "while", "let", "use",
+];
+
+/// Keywords that continue the construct on the previous line rather than starting a new one
+const CONTINUATION_KEYWORDS: &[&str] = &[
+    "then", "else", "elif", "with", "finally", "and", "done", "end", "in", "of", "when", "to", "downto",
+];
+
+/// Functions that raise exceptions
+const THROW_FUNCTIONS: &[&str] = &["raise", "failwith", "failwithf", "invalidArg", "invalidOp", "nullArg", "reraise"];
+
+/// Class member keywords
+const MEMBER_KEYWORDS: &[&str] = &["member", "override", "abstract", "default", "static", "new", "val"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident,
+    Keyword,
+    Operator,
+    Literal,
+    Punct,
+    Directive,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    line: u32,
+    col: u32,
+    first_on_line: bool,
+    space_before: bool,
+}
+
+impl Token {
+    fn is(&self, text: &str) -> bool {
+        self.kind != TokenKind::Literal && self.text == text
+    }
+}
+
+/// F# parser built on a hand-written recursive descent over the offside rule.
+///
+/// Indentation decides where constructs end: a slice of tokens is split into
+/// items at lines that start at or left of the first token's column. Modules
+/// nest, `let` bindings carry their bodies, and match expressions, pipelines
+/// and computation expressions (`async { }`, `seq { }`, ...) get dedicated nodes.
 pub struct FSharpParser {
 }
 
@@ -13,7 +74,10 @@ impl CoalesceParser for FSharpParser {
     }
     
     fn parse(&self, source: &str) -> Result<UIRNode> {
-        self.parse_fsharp_source(source)
+        let mut uir = self.parse_fsharp_source(source)?;
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
     }
 }
 
@@ -23,232 +87,1732 @@ impl FSharpParser {
     }
     
     fn parse_fsharp_source(&self, source: &str) -> Result<UIRNode> {
-        let mut root = UIRNode {
-            id: "fsharp_program".to_string(),
-            node_type: NodeType::Module,
-            name: Some("fsharp_program".to_string()),
-            children: Vec::new(),
-            metadata: Metadata {
-                source_language: CoalesceLanguage::FSharp,
-                semantic_tags: vec!["source_file".to_string()],
-                complexity_score: None,
-                dependencies: Vec::new(),
-                annotations: HashMap::new(),
-                legacy_patterns: Vec::new(),
-            },
-            source_location: Some(SourceLocation {
-                file: String::new(),
-                start_line: 1,
-                end_line: source.lines().count() as u32,
-                start_column: 0,
-                end_column: source.len() as u32,
-            }),
-        };
+        let tokens = self.tokenize(source);
+        self.check_brackets(&tokens)?;
+        
+        let mut root = self.create_node(NodeType::Module, Some("fsharp_program".to_string()), 1, "source_file");
+        root.id = "fsharp_program".to_string();
+        root.source_location = Some(SourceLocation {
+            file: String::new(),
+            start_line: 1,
+            end_line: source.lines().count() as u32,
+            start_column: 0,
+            end_column: source.len() as u32,
+        });
+        root.children = self.parse_items(&tokens);
         
-        // Parse different F# constructs
-        self.parse_modules(source, &mut root)?;
-        self.parse_types(source, &mut root)?;
-        self.parse_functions(source, &mut root)?;
-        self.parse_let_bindings(source, &mut root)?;
+        let mut dependencies = Vec::new();
+        self.collect_imports(&root, &mut dependencies);
+        root.metadata.dependencies = dependencies;
+        closures::annotate_captures(&mut root);
         
         Ok(root)
     }
     
-    fn parse_modules(&self, source: &str, root: &mut UIRNode) -> Result<()> {
-        let module_regex = Regex::new(r"(?m)^module\s+(\w+(?:\.\w+)*)\s*=?\s*$").unwrap();
+    fn tokenize(&self, source: &str) -> Vec<Token> {
+        let chars: Vec<char> = source.chars().collect();
+        let mut tokens: Vec<Token> = Vec::new();
+        let (mut i, mut line, mut col) = (0usize, 1u32, 0u32);
+        let mut first_on_line = true;
+        let mut space_before = true;
+        let mut comment_depth = 0;
         
-        for caps in module_regex.captures_iter(source) {
-            let module_name = caps.get(1).unwrap().as_str();
-            let line_num = source[..caps.get(0).unwrap().start()].lines().count() + 1;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            
+            if c == '\n' {
+                line += 1;
+                col = 0;
+                first_on_line = true;
+                space_before = true;
+                i += 1;
+                continue;
+            }
+            if comment_depth > 0 {
+                // Block comments nest
+                if c == '(' && next == Some('*') {
+                    comment_depth += 1;
+                    i += 1;
+                    col += 1;
+                } else if c == '*' && next == Some(')') {
+                    comment_depth -= 1;
+                    i += 1;
+                    col += 1;
+                }
+                i += 1;
+                col += 1;
+                continue;
+            }
+            if c.is_whitespace() {
+                i += 1;
+                col += 1;
+                space_before = true;
+                continue;
+            }
+            if c == '/' && next == Some('/') {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            // `(*)` is the multiplication operator, not a comment
+            if c == '(' && next == Some('*') && chars.get(i + 2) != Some(&')') {
+                comment_depth = 1;
+                i += 2;
+                col += 2;
+                continue;
+            }
             
-            let module_node = UIRNode {
-                id: format!("module_{}", module_name),
-                node_type: NodeType::Module,
-                name: Some(module_name.to_string()),
-                children: Vec::new(),
-                metadata: Metadata {
-                    source_language: CoalesceLanguage::FSharp,
-                    semantic_tags: vec!["module".to_string()],
-                    complexity_score: None,
-                    dependencies: Vec::new(),
-                    annotations: {
-                        let mut map = HashMap::new();
-                        map.insert("original_text".to_string(), Value::String(caps.get(0).unwrap().as_str().to_string()));
-                        map
-                    },
-                    legacy_patterns: Vec::new(),
-                },
-                source_location: Some(SourceLocation {
-                    file: String::new(),
-                    start_line: line_num as u32,
-                    end_line: line_num as u32,
-                    start_column: 0,
-                    end_column: caps.get(0).unwrap().len() as u32,
-                }),
+            let (kind, len) = if c == '#' && first_on_line {
+                let end = chars[i..].iter().position(|c| *c == '\n').map(|p| i + p).unwrap_or(chars.len());
+                (TokenKind::Directive, end - i)
+            } else {
+                self.lex_token(&chars, i, &tokens)
             };
             
-            root.children.push(module_node);
+            let text: String = chars[i..i + len].iter().collect();
+            tokens.push(Token { kind, text: text.trim_end().to_string(), line, col, first_on_line, space_before });
+            for ch in &chars[i..i + len] {
+                if *ch == '\n' {
+                    line += 1;
+                    col = 0;
+                } else {
+                    col += 1;
+                }
+            }
+            i += len;
+            first_on_line = false;
+            space_before = false;
         }
         
-        Ok(())
+        tokens
     }
     
-    fn parse_types(&self, source: &str, root: &mut UIRNode) -> Result<()> {
-        // Parse type definitions
-        let type_regex = Regex::new(r"(?m)^type\s+(\w+)(?:\s*=)?").unwrap();
+    /// Kind and length of the token starting at `i`
+    fn lex_token(&self, chars: &[char], i: usize, tokens: &[Token]) -> (TokenKind, usize) {
+        let c = chars[i];
+        let at = |j: usize| chars.get(j).copied().unwrap_or('\0');
+        let is_ident_char = |ch: char| ch.is_alphanumeric() || ch == '_' || ch == '\'';
+        
+        // Strings: "..", @"..", $"..", """..""" and combinations of the prefixes
+        let mut prefix = 0;
+        while prefix < 2 && matches!(at(i + prefix), '@' | '$') {
+            prefix += 1;
+        }
+        if at(i + prefix) == '"' {
+            let verbatim = chars[i..i + prefix].contains(&'@');
+            let start = i + prefix;
+            let mut j = start + 1;
+            if at(start + 1) == '"' && at(start + 2) == '"' {
+                j = start + 3;
+                while j < chars.len() && !(at(j) == '"' && at(j + 1) == '"' && at(j + 2) == '"') {
+                    j += 1;
+                }
+                j = (j + 3).min(chars.len());
+            } else {
+                while j < chars.len() {
+                    match chars[j] {
+                        '\\' if !verbatim => j += 2,
+                        '"' if verbatim && at(j + 1) == '"' => j += 2,
+                        '"' => {
+                            j += 1;
+                            break;
+                        }
+                        _ => j += 1,
+                    }
+                }
+            }
+            if at(j) == 'B' {
+                j += 1;
+            }
+            return (TokenKind::Literal, j.min(chars.len()) - i);
+        }
+        
+        // Character literals, as opposed to type variables like 'T
+        if c == '\'' {
+            if at(i + 2) == '\'' && at(i + 1) != '\\' {
+                return (TokenKind::Literal, 3);
+            }
+            if at(i + 1) == '\\' {
+                if let Some(end) = (i + 2..(i + 10).min(chars.len())).find(|j| chars[*j] == '\'') {
+                    return (TokenKind::Literal, end + 1 - i);
+                }
+            }
+        }
+        
+        if c.is_ascii_digit() {
+            let mut j = i + 1;
+            while j < chars.len() {
+                let ch = chars[j];
+                let exponent_sign = matches!(ch, '-' | '+') && matches!(chars[j - 1], 'e' | 'E') && !chars[i..j].contains(&'x');
+                if ch.is_alphanumeric() || ch == '_' || exponent_sign || (ch == '.' && at(j + 1).is_ascii_digit()) {
+                    j += 1;
+                } else {
+                    break;
+                }
+            }
+            return (TokenKind::Literal, j - i);
+        }
+        
+        if c == '`' && at(i + 1) == '`' {
+            let mut j = i + 2;
+            while j + 1 < chars.len() && !(chars[j] == '`' && chars[j + 1] == '`') {
+                j += 1;
+            }
+            return (TokenKind::Ident, (j + 2).min(chars.len()) - i);
+        }
         
-        for caps in type_regex.captures_iter(source) {
-            let type_name = caps.get(1).unwrap().as_str();
-            let line_num = source[..caps.get(0).unwrap().start()].lines().count() + 1;
+        if c.is_alphabetic() || c == '_' || c == '\'' {
+            let mut j = i + 1;
+            loop {
+                while j < chars.len() && is_ident_char(chars[j]) {
+                    j += 1;
+                }
+                // Qualified names (List.map, System.IO.File) form one token
+                if at(j) == '.' && (at(j + 1).is_alphabetic() || at(j + 1) == '_') {
+                    j += 1;
+                    continue;
+                }
+                break;
+            }
             
-            let type_node = UIRNode {
-                id: format!("type_{}", type_name),
-                node_type: NodeType::Class,
-                name: Some(type_name.to_string()),
-                children: Vec::new(),
-                metadata: Metadata {
-                    source_language: CoalesceLanguage::FSharp,
-                    semantic_tags: vec!["type".to_string()],
-                    complexity_score: None,
-                    dependencies: Vec::new(),
-                    annotations: {
-                        let mut map = HashMap::new();
-                        map.insert("original_text".to_string(), Value::String(caps.get(0).unwrap().as_str().to_string()));
-                        map
-                    },
-                    legacy_patterns: Vec::new(),
-                },
-                source_location: Some(SourceLocation {
-                    file: String::new(),
-                    start_line: line_num as u32,
-                    end_line: line_num as u32,
-                    start_column: 0,
-                    end_column: caps.get(0).unwrap().len() as u32,
-                }),
-            };
+            let word: String = chars[i..j].iter().collect();
+            if BANG_KEYWORDS.contains(&word.as_str()) && at(j) == '!' && at(j + 1) != '=' {
+                return (TokenKind::Keyword, j + 1 - i);
+            }
+            if KEYWORDS.contains(&word.as_str()) {
+                return (TokenKind::Keyword, j - i);
+            }
+            
+            // Generic arguments glued to a name: List<int>, Dictionary<string, int>
+            if at(j) == '<' {
+                let mut depth = 0;
+                let mut k = j;
+                while k < chars.len() && k < j + 60 {
+                    match chars[k] {
+                        '<' => depth += 1,
+                        '>' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return (TokenKind::Ident, k + 1 - i);
+                            }
+                        }
+                        ' ' if chars[k - 1] == ',' => {}
+                        ch if is_ident_char(ch) || matches!(ch, ',' | '.' | '*' | '[' | ']') => {}
+                        _ => break,
+                    }
+                    k += 1;
+                }
+            }
+            return (TokenKind::Ident, j - i);
+        }
+        
+        let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+        let attribute_open = tokens.iter().filter(|t| t.is("[<")).count() > tokens.iter().filter(|t| t.is(">]")).count();
+        if two == "[|" || two == "|]" || two == "[<" || two == ";;" || (two == ">]" && attribute_open) {
+            return (TokenKind::Punct, 2);
+        }
+        if matches!(c, '(' | ')' | '[' | ']' | '{' | '}' | ',' | ';') {
+            return (TokenKind::Punct, 1);
+        }
+        
+        const OPERATOR_CHARS: &str = "!%&*+-./<=>?@^|~:$";
+        if OPERATOR_CHARS.contains(c) {
+            let mut j = i + 1;
+            while j < chars.len() && OPERATOR_CHARS.contains(chars[j]) && !(chars[j] == '|' && at(j + 1) == ']')
+                && !(chars[j] == '>' && at(j + 1) == ']' && attribute_open) {
+                j += 1;
+            }
+            return (TokenKind::Operator, j - i);
+        }
+        
+        (TokenKind::Operator, 1)
+    }
+    
+    fn delta(&self, token: &Token) -> i32 {
+        if token.kind != TokenKind::Punct {
+            return 0;
+        }
+        match token.text.as_str() {
+            "(" | "[" | "[|" | "[<" | "{" => 1,
+            ")" | "]" | "|]" | ">]" | "}" => -1,
+            _ => 0,
+        }
+    }
+    
+    /// First index at bracket depth zero (relative to `from`) whose token satisfies `pred`
+    fn find_depth0(&self, tokens: &[Token], from: usize, pred: impl Fn(&Token) -> bool) -> Option<usize> {
+        let mut depth = 0;
+        for (i, token) in tokens.iter().enumerate().skip(from) {
+            if depth == 0 && pred(token) {
+                return Some(i);
+            }
+            depth += self.delta(token);
+        }
+        None
+    }
+    
+    /// Reject a bracket left open: everything after it would be read as its contents, and the
+    /// slicing between brackets relies on each having a match
+    fn check_brackets(&self, tokens: &[Token]) -> Result<()> {
+        let mut open = Vec::new();
+        for token in tokens {
+            match self.delta(token) {
+                1 => open.push(token),
+                -1 => {
+                    open.pop();
+                }
+                _ => {}
+            }
+        }
+        match open.first() {
+            Some(token) => Err(CoalesceError::ParseError {
+                message: format!("`{}` is never closed", token.text),
+                line: token.line,
+                column: token.col,
+            }),
+            None => Ok(()),
+        }
+    }
+    
+    fn matching_close(&self, tokens: &[Token], open: usize) -> usize {
+        let mut depth = 0;
+        for (i, token) in tokens.iter().enumerate().skip(open) {
+            depth += self.delta(token);
+            if depth == 0 {
+                return i;
+            }
+        }
+        tokens.len().saturating_sub(1)
+    }
+    
+    fn split_on<'a>(&self, tokens: &'a [Token], separator: &str) -> Vec<&'a [Token]> {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut depth = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            if depth == 0 && token.is(separator) {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            depth += self.delta(token);
+        }
+        parts.push(&tokens[start..]);
+        parts
+    }
+    
+    /// Offside rule: a new item starts on a line at or left of the first token's column
+    fn split_items<'a>(&self, tokens: &'a [Token]) -> Vec<&'a [Token]> {
+        let mut items = Vec::new();
+        let Some(first) = tokens.first() else {
+            return items;
+        };
+        let base = first.col;
+        let mut start = 0;
+        let mut depth = 0;
+        
+        for (i, token) in tokens.iter().enumerate() {
+            if depth <= 0 && (token.is(";") || token.is(";;")) {
+                if i > start {
+                    items.push(&tokens[start..i]);
+                }
+                start = i + 1;
+                continue;
+            }
+            if i > start && depth <= 0 && token.first_on_line && token.col <= base && !self.continues(token) {
+                items.push(&tokens[start..i]);
+                start = i;
+            }
+            depth += self.delta(token);
+        }
+        if start < tokens.len() {
+            items.push(&tokens[start..]);
+        }
+        items
+    }
+    
+    fn continues(&self, token: &Token) -> bool {
+        match token.kind {
+            TokenKind::Operator => true,
+            TokenKind::Keyword => CONTINUATION_KEYWORDS.contains(&token.text.as_str()),
+            TokenKind::Punct => self.delta(token) < 0,
+            _ => false,
+        }
+    }
+    
+    fn parse_items(&self, tokens: &[Token]) -> Vec<UIRNode> {
+        let mut nodes: Vec<UIRNode> = Vec::new();
+        let mut attributes: Vec<String> = Vec::new();
+        let mut container: Option<UIRNode> = None;
+        
+        for item in self.split_items(tokens) {
+            let mut item = item;
+            while item.first().is_some_and(|t| t.is("[<")) {
+                let close = self.matching_close(item, 0);
+                attributes.push(self.join(&item[1..close]));
+                item = &item[close + 1..];
+            }
+            if item.is_empty() {
+                continue;
+            }
             
-            root.children.push(type_node);
+            // `namespace X` / `module X` without `=` own every declaration that follows
+            if item[0].is("namespace") || (item[0].is("module") && self.find_depth0(item, 1, |t| t.is("=")).is_none()) {
+                if let Some(previous) = container.take() {
+                    nodes.push(previous);
+                }
+                let tag = if item[0].is("namespace") { "namespace" } else { "module" };
+                let name = self.join(&item[1..].iter().filter(|t| t.kind == TokenKind::Ident).cloned().collect::<Vec<_>>());
+                container = Some(self.create_node(NodeType::Module, Some(name), item[0].line, tag));
+                attributes.clear();
+                continue;
+            }
+            
+            let mut parsed = self.parse_item(item);
+            if let Some(first) = parsed.first_mut() {
+                self.apply_attributes(first, &attributes);
+            }
+            attributes.clear();
+            
+            match container.as_mut() {
+                Some(container) => container.children.extend(parsed),
+                None => nodes.extend(parsed),
+            }
+        }
+        
+        nodes.extend(container);
+        nodes
+    }
+    
+    fn apply_attributes(&self, node: &mut UIRNode, attributes: &[String]) {
+        if attributes.is_empty() {
+            return;
+        }
+        // `[<A; B(1)>]` holds several attributes
+        for attribute in attributes.iter().flat_map(|a| a.split(';')).map(Attribute::parse) {
+            match attribute.name.as_str() {
+                "EntryPoint" => node.metadata.semantic_tags.push("entry_point".to_string()),
+                "Literal" => node.node_type = NodeType::Constant,
+                _ => {}
+            }
+            node.attributes.push(attribute);
+        }
+    }
+    
+    fn parse_item(&self, tokens: &[Token]) -> Vec<UIRNode> {
+        let first = &tokens[0];
+        let line = first.line;
+        
+        if first.kind == TokenKind::Directive {
+            return self.parse_directive(first).into_iter().collect();
         }
         
-        Ok(())
+        match first.text.as_str() {
+            "open" => {
+                let name = self.join(&tokens[1..].iter().filter(|t| !t.is("type")).cloned().collect::<Vec<_>>());
+                let mut import = self.create_node(self.import(&name), Some(name.clone()), line, "import");
+                import.metadata.dependencies.push(name);
+                vec![import]
+            }
+            "module" => {
+                let eq = self.find_depth0(tokens, 1, |t| t.is("=")).unwrap_or(tokens.len());
+                let name = tokens[1..eq].iter()
+                    .filter(|t| t.kind == TokenKind::Ident)
+                    .map(|t| t.text.clone())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                let mut module = self.create_node(NodeType::Module, Some(name), line, "module");
+                if eq < tokens.len() {
+                    // `module Alias = Some.Other.Module` abbreviates rather than defines
+                    let body = &tokens[eq + 1..];
+                    if body.len() == 1 && body[0].kind == TokenKind::Ident {
+                        module.metadata.semantic_tags.push("module_alias".to_string());
+                        module.metadata.dependencies.push(body[0].text.clone());
+                    } else {
+                        module.children = self.parse_items(body);
+                    }
+                }
+                vec![module]
+            }
+            "type" => self.parse_type(tokens),
+            "exception" => {
+                let name = tokens.get(1).map(|t| t.text.clone());
+                let mut exception = self.create_node(NodeType::Class, name, line, "exception");
+                if let Some(of) = self.find_depth0(tokens, 1, |t| t.is("of")) {
+                    exception.metadata.annotations.insert("fields".to_string(), Value::String(self.join(&tokens[of + 1..])));
+                }
+                vec![exception]
+            }
+            "let" | "use" | "let!" | "use!" | "and!" => {
+                self.split_and(tokens).into_iter().map(|part| self.parse_let(part)).collect()
+            }
+            "do" => vec![self.parse_expr(&tokens[1..])],
+            "do!" => {
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::Await), Some("await".to_string()), line, "await");
+                node.children.push(self.parse_expr(&tokens[1..]));
+                vec![node]
+            }
+            "return" | "return!" => {
+                let mut node = self.create_node(NodeType::Statement(StatementType::Return), None, line, "return");
+                if tokens.len() > 1 {
+                    node.children.push(self.parse_expr(&tokens[1..]));
+                }
+                if first.text.ends_with('!') {
+                    node.metadata.semantic_tags.push("await".to_string());
+                }
+                vec![node]
+            }
+            "yield" | "yield!" => {
+                let tag = if first.text.ends_with('!') { "yield_from" } else { "yield" };
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::Yield), Some("yield".to_string()), line, tag);
+                node.children.push(self.parse_expr(&tokens[1..]));
+                vec![node]
+            }
+            _ if first.kind == TokenKind::Keyword && MEMBER_KEYWORDS.contains(&first.text.as_str()) => {
+                vec![self.parse_member(tokens)]
+            }
+            _ => vec![self.parse_expr(tokens)],
+        }
+    }
+    
+    /// `#r "nuget: Package"` and `#load "file.fsx"` reference other code
+    fn parse_directive(&self, token: &Token) -> Option<UIRNode> {
+        let (directive, argument) = token.text.split_once(char::is_whitespace)?;
+        if !matches!(directive, "#r" | "#load" | "#reference") {
+            return None;
+        }
+        let argument = argument.trim().trim_matches('"');
+        let name = argument.strip_prefix("nuget:")
+            .map(|package| package.split(',').next().unwrap_or(package).trim())
+            .unwrap_or(argument)
+            .to_string();
+        let tag = if argument.starts_with("nuget:") { "nuget_reference" } else { "import" };
+        let mut import = self.create_node(self.import(&name), Some(name.clone()), token.line, tag);
+        import.metadata.semantic_tags.push("import".to_string());
+        import.metadata.dependencies.push(name);
+        Some(import)
+    }
+    
+    fn import(&self, path: &str) -> NodeType {
+        NodeType::Import { path: path.to_string(), alias: None, symbols: Vec::new() }
+    }
+    
+    /// `let rec f ... and g ...` defines several bindings in one group
+    fn split_and<'a>(&self, tokens: &'a [Token]) -> Vec<&'a [Token]> {
+        let col = tokens[0].col;
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut depth = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            if i > start && depth == 0 && token.is("and") && token.first_on_line && token.col <= col {
+                parts.push(&tokens[start..i]);
+                start = i;
+            }
+            depth += self.delta(token);
+        }
+        parts.push(&tokens[start..]);
+        parts
     }
     
-    fn parse_functions(&self, source: &str, root: &mut UIRNode) -> Result<()> {
-        // Parse function definitions with explicit parameters
-        let func_regex = Regex::new(r"(?m)^let\s+(\w+)\s+([^=]+?)\s*=").unwrap();
+    fn parse_let(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens[0].line;
+        let keyword = tokens[0].text.clone();
         
-        for caps in func_regex.captures_iter(source) {
-            let func_name = caps.get(1).unwrap().as_str();
-            let params_str = caps.get(2).unwrap().as_str().trim();
-            let line_num = source[..caps.get(0).unwrap().start()].lines().count() + 1;
+        let mut index = 1;
+        let mut modifiers = Vec::new();
+        while let Some(token) = tokens.get(index).filter(|t| {
+            t.kind == TokenKind::Keyword && matches!(t.text.as_str(), "rec" | "inline" | "mutable" | "private" | "internal" | "public")
+        }) {
+            modifiers.push(token.text.clone());
+            index += 1;
+        }
+        
+        let eq = self.find_depth0(tokens, index, |t| t.is("="));
+        let (head, body) = match eq {
+            Some(eq) => (&tokens[index..eq], &tokens[eq + 1..]),
+            None => (&tokens[index.min(tokens.len())..], &tokens[tokens.len()..]),
+        };
+        let colon = self.find_depth0(head, 0, |t| t.is(":"));
+        let (pattern, type_annotation) = match colon {
+            Some(colon) => (&head[..colon], Some(self.join(&head[colon + 1..]))),
+            None => (head, None),
+        };
+        
+        let is_function = pattern.len() > 1 && pattern[0].kind == TokenKind::Ident
+            && self.find_depth0(pattern, 0, |t| t.is(",")).is_none();
+        
+        let mut node = if is_function {
+            let mut function = self.create_node(NodeType::Function, Some(pattern[0].text.clone()), line, "function");
+            function.children = self.parse_params(&pattern[1..]);
+            if let Some(return_type) = type_annotation {
+                function.metadata.annotations.insert("return_type".to_string(), Value::String(return_type));
+            }
+            function.children.extend(self.parse_function_body(body));
+            function
+        } else {
+            let name = self.join(pattern);
+            let tag = if pattern.len() > 1 { "destructuring" } else { "binding" };
+            let mut variable = self.create_node(NodeType::Variable, Some(name), line, tag);
+            if let Some(type_name) = type_annotation {
+                variable.metadata.annotations.insert("type".to_string(), Value::String(type_name));
+            }
             
-            // Only treat as function if it has parameters
-            if !params_str.is_empty() && params_str.chars().any(|c| c.is_alphabetic()) {
-                let mut func_node = UIRNode {
-                    id: format!("func_{}", func_name),
-                    node_type: NodeType::Function,
-                    name: Some(func_name.to_string()),
-                    children: Vec::new(),
-                    metadata: Metadata {
-                        source_language: CoalesceLanguage::FSharp,
-                        semantic_tags: vec!["function".to_string()],
-                        complexity_score: None,
-                        dependencies: Vec::new(),
-                        annotations: {
-                            let mut map = HashMap::new();
-                            map.insert("original_text".to_string(), Value::String(caps.get(0).unwrap().as_str().to_string()));
-                            map
-                        },
-                        legacy_patterns: Vec::new(),
-                    },
-                    source_location: Some(SourceLocation {
-                        file: String::new(),
-                        start_line: line_num as u32,
-                        end_line: line_num as u32,
-                        start_column: 0,
-                        end_column: caps.get(0).unwrap().len() as u32,
-                    }),
+            let value = (!body.is_empty()).then(|| self.parse_body(body));
+            match value {
+                // `let f = fun x -> ...` is a function definition
+                Some(mut lambda) if matches!(lambda.node_type, NodeType::Closure { .. }) => {
+                    lambda.node_type = NodeType::Function;
+                    lambda.name = variable.name.clone();
+                    lambda.id = self.node_id("function", line, lambda.name.as_deref());
+                    lambda.metadata.semantic_tags.push("function".to_string());
+                    lambda
+                }
+                Some(value) => {
+                    variable.children.push(value);
+                    variable
+                }
+                None => variable,
+            }
+        };
+        
+        for modifier in modifiers {
+            match modifier.as_str() {
+                "rec" => node.metadata.semantic_tags.push("recursive".to_string()),
+                "private" | "internal" | "public" => {
+                    node.metadata.annotations.insert("visibility".to_string(), Value::String(modifier));
+                }
+                _ => node.metadata.semantic_tags.push(modifier),
+            }
+        }
+        if keyword.ends_with('!') {
+            node.metadata.semantic_tags.push("await".to_string());
+        }
+        if keyword.starts_with("use") {
+            node.metadata.semantic_tags.push("disposable".to_string());
+        }
+        node
+    }
+    
+    /// Curried and tupled parameters: `x (y: int) (a, b: string)`
+    fn parse_params(&self, tokens: &[Token]) -> Vec<UIRNode> {
+        let mut params = Vec::new();
+        let mut i = 0;
+        
+        while i < tokens.len() {
+            let token = &tokens[i];
+            if token.is("(") {
+                let close = self.matching_close(tokens, i);
+                let inner = &tokens[i + 1..close];
+                if !inner.is_empty() {
+                    let parts = self.split_on(inner, ",");
+                    let tupled = parts.len() > 1;
+                    for part in parts.into_iter().filter(|p| !p.is_empty()) {
+                        let mut param = self.parse_param(part);
+                        if tupled {
+                            param.metadata.annotations.insert("tupled".to_string(), Value::Bool(true));
+                        }
+                        params.push(param);
+                    }
+                }
+                i = close + 1;
+            } else if token.is("[<") {
+                i = self.matching_close(tokens, i) + 1;
+            } else if token.kind == TokenKind::Ident {
+                params.push(self.parse_param(std::slice::from_ref(token)));
+                i += 1;
+            } else {
+                i += 1;
+            }
+        }
+        
+        params
+    }
+    
+    fn parse_param(&self, tokens: &[Token]) -> UIRNode {
+        let colon = self.find_depth0(tokens, 0, |t| t.is(":")).unwrap_or(tokens.len());
+        let tokens_before: Vec<Token> = tokens[..colon].iter().filter(|t| !t.is("[<") && !t.is(">]")).cloned().collect();
+        let name = self.join(&tokens_before);
+        let optional = name.starts_with('?');
+        let line = tokens.first().map(|t| t.line).unwrap_or(0);
+        
+        let mut param = self.create_node(NodeType::Variable, Some(name.trim_start_matches('?').to_string()), line, "parameter");
+        if colon < tokens.len() {
+            param.metadata.annotations.insert("type".to_string(), Value::String(self.join(&tokens[colon + 1..])));
+        }
+        if optional {
+            param.metadata.semantic_tags.push("optional".to_string());
+        }
+        param
+    }
+    
+    /// Function bodies return the value of their last expression
+    fn parse_function_body(&self, tokens: &[Token]) -> Vec<UIRNode> {
+        let mut statements = self.parse_items(tokens);
+        if let Some(last) = statements.pop() {
+            statements.push(self.implicit_return(last));
+        }
+        statements
+    }
+    
+    fn implicit_return(&self, node: UIRNode) -> UIRNode {
+        if !matches!(node.node_type, NodeType::Expression(_)) {
+            return node;
+        }
+        
+        let mut metadata = node.metadata.clone();
+        metadata.semantic_tags = vec!["implicit_return".to_string()];
+        
+        UIRNode {
+            id: format!("return_{}", node.id),
+            node_type: NodeType::Statement(StatementType::Return),
+            name: None,
+            source_location: node.source_location.clone(),
+            metadata,
+            children: vec![node],
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+    
+    /// A single expression, or a block node when the body has several items
+    fn parse_body(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens.first().map(|t| t.line).unwrap_or(0);
+        let mut nodes = self.parse_items(tokens);
+        if nodes.len() == 1 {
+            return nodes.remove(0);
+        }
+        let mut block = self.create_node(NodeType::Statement(StatementType::Expression), Some("block".to_string()), line, "block");
+        block.children = nodes;
+        block
+    }
+    
+    fn block(&self, name: &str, tokens: &[Token], line: u32) -> UIRNode {
+        let mut block = self.create_node(NodeType::Statement(StatementType::Expression), Some(name.to_string()), line, "block");
+        block.children = self.parse_items(tokens);
+        block
+    }
+    
+    fn parse_type(&self, tokens: &[Token]) -> Vec<UIRNode> {
+        self.split_and(tokens).into_iter()
+            .filter(|part| part.len() > 1)
+            .map(|part| self.parse_type_definition(&part[1..], part[0].line))
+            .collect()
+    }
+    
+    fn parse_type_definition(&self, tokens: &[Token], line: u32) -> UIRNode {
+        let mut start = 0;
+        while tokens.get(start).is_some_and(|t| t.is("[<") || t.kind == TokenKind::Keyword) {
+            start = if tokens[start].is("[<") { self.matching_close(tokens, start) + 1 } else { start + 1 };
+        }
+        let tokens = &tokens[start.min(tokens.len())..];
+        
+        let raw_name = tokens.first().map(|t| t.text.clone()).unwrap_or_default();
+        let (name, type_parameters) = match raw_name.split_once('<') {
+            Some((name, params)) => (name.to_string(), Some(params.trim_end_matches('>').to_string())),
+            None => (raw_name, None),
+        };
+        
+        let eq = self.find_depth0(tokens, 1, |t| t.is("="));
+        let head = &tokens[1.min(tokens.len())..eq.unwrap_or(tokens.len())];
+        let body = eq.map(|eq| &tokens[eq + 1..]).unwrap_or(&[]);
+        let constructor = head.iter().position(|t| t.is("(")).map(|open| {
+            let close = self.matching_close(head, open);
+            &head[open..=close]
+        });
+        
+        let (node_type, tag) = self.classify_type(body, constructor.is_some());
+        let mut node = self.create_node(node_type, Some(name), line, tag);
+        if let Some(type_parameters) = type_parameters {
+            node.metadata.annotations.insert("type_parameters".to_string(), Value::String(type_parameters));
+        }
+        if let Some(constructor) = constructor {
+            for mut param in self.parse_params(constructor) {
+                param.metadata.semantic_tags.push("constructor_parameter".to_string());
+                node.children.push(param);
+            }
+        }
+        
+        match tag {
+            "record" => {
+                let close = self.matching_close(body, 0);
+                for field in self.split_items(&body[1..close]) {
+                    node.children.push(self.parse_field(field));
+                }
+                self.parse_class_body(self.member_section(&body[close + 1..]), &mut node);
+            }
+            "union" | "enum" => {
+                let members = (0..body.len())
+                    .find(|i| body[*i].first_on_line && (body[*i].is("with") || MEMBER_KEYWORDS.contains(&body[*i].text.as_str()) || body[*i].is("interface")))
+                    .unwrap_or(body.len());
+                for case in self.split_on(&body[..members], "|").into_iter().filter(|c| !c.is_empty()) {
+                    node.children.push(self.parse_union_case(case));
+                }
+                self.parse_class_body(self.member_section(&body[members..]), &mut node);
+            }
+            "type_alias" => {
+                node.metadata.annotations.insert("aliased_type".to_string(), Value::String(self.join(body)));
+            }
+            _ => {
+                // class/struct/interface ... end wrappers
+                let inner = if body.first().is_some_and(|t| t.is("class") || t.is("struct") || t.is("interface"))
+                    && body.last().is_some_and(|t| t.is("end")) {
+                    &body[1..body.len() - 1]
+                } else {
+                    body
                 };
-                
-                // Parse parameters
-                for param in params_str.split_whitespace() {
-                    if param.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                        let param_node = UIRNode {
-                            id: format!("param_{}", param),
-                            node_type: NodeType::Variable,
-                            name: Some(param.to_string()),
-                            children: Vec::new(),
-                            metadata: Metadata {
-                                source_language: CoalesceLanguage::FSharp,
-                                semantic_tags: vec!["parameter".to_string()],
-                                complexity_score: None,
-                                dependencies: Vec::new(),
-                                annotations: HashMap::new(),
-                                legacy_patterns: Vec::new(),
-                            },
-                            source_location: Some(SourceLocation {
-                                file: String::new(),
-                                start_line: line_num as u32,
-                                end_line: line_num as u32,
-                                start_column: 0,
-                                end_column: param.len() as u32,
-                            }),
-                        };
-                        func_node.children.push(param_node);
+                self.parse_class_body(inner, &mut node);
+            }
+        }
+        
+        node
+    }
+    
+    fn classify_type(&self, body: &[Token], has_constructor: bool) -> (NodeType, &'static str) {
+        let Some(first) = body.first() else {
+            return (NodeType::Class, "class");
+        };
+        
+        if first.is("{") {
+            return (NodeType::Class, "record");
+        }
+        let has_cases = first.kind == TokenKind::Ident && self.find_depth0(body, 0, |t| t.is("|")).is_some();
+        if first.is("|") || has_cases || body.get(1).is_some_and(|t| t.is("of")) {
+            let is_enum = self.split_on(body, "|").iter()
+                .filter(|c| !c.is_empty())
+                .all(|c| c.get(1).is_some_and(|t| t.is("=")));
+            return (NodeType::Class, if is_enum { "enum" } else { "union" });
+        }
+        if first.is("interface") {
+            return (NodeType::Interface, "interface");
+        }
+        if first.is("struct") {
+            return (NodeType::Class, "struct");
+        }
+        if first.is("delegate") {
+            return (NodeType::Class, "delegate");
+        }
+        
+        let items = self.split_items(body);
+        let is_member = |item: &&[Token]| item[0].kind == TokenKind::Keyword
+            && (MEMBER_KEYWORDS.contains(&item[0].text.as_str()) || matches!(item[0].text.as_str(), "let" | "inherit" | "interface" | "do"))
+            || item[0].is("[<");
+        if !has_constructor && !items.is_empty() && items.iter().all(|i| i[0].is("abstract") || i[0].is("[<")) {
+            (NodeType::Interface, "interface")
+        } else if has_constructor || first.is("class") || items.iter().any(is_member) {
+            (NodeType::Class, "class")
+        } else {
+            (NodeType::Class, "type_alias")
+        }
+    }
+    
+    /// Members of records and unions follow an optional `with` ... `end`
+    fn member_section<'a>(&self, tokens: &'a [Token]) -> &'a [Token] {
+        let mut tokens = tokens;
+        if tokens.first().is_some_and(|t| t.is("with")) {
+            tokens = &tokens[1..];
+        }
+        if tokens.last().is_some_and(|t| t.is("end")) {
+            tokens = &tokens[..tokens.len() - 1];
+        }
+        tokens
+    }
+    
+    fn parse_field(&self, tokens: &[Token]) -> UIRNode {
+        let mutable = tokens.first().is_some_and(|t| t.is("mutable"));
+        let tokens = if mutable { &tokens[1..] } else { tokens };
+        let line = tokens.first().map(|t| t.line).unwrap_or(0);
+        let name = tokens.first().map(|t| t.text.clone());
+        
+        let mut field = self.create_node(NodeType::Variable, name, line, "field");
+        if let Some(colon) = self.find_depth0(tokens, 0, |t| t.is(":")) {
+            field.metadata.annotations.insert("type".to_string(), Value::String(self.join(&tokens[colon + 1..])));
+        }
+        if mutable {
+            field.metadata.semantic_tags.push("mutable".to_string());
+        }
+        field
+    }
+    
+    fn parse_union_case(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens[0].line;
+        let mut case = self.create_node(NodeType::Constant, Some(tokens[0].text.clone()), line, "union_case");
+        if let Some(of) = self.find_depth0(tokens, 1, |t| t.is("of")) {
+            case.metadata.annotations.insert("fields".to_string(), Value::String(self.join(&tokens[of + 1..])));
+        }
+        if let Some(eq) = self.find_depth0(tokens, 1, |t| t.is("=")) {
+            case.metadata.semantic_tags = vec!["enum_case".to_string()];
+            case.metadata.annotations.insert("value".to_string(), Value::String(self.join(&tokens[eq + 1..])));
+        }
+        case
+    }
+    
+    fn parse_class_body(&self, tokens: &[Token], class: &mut UIRNode) {
+        for item in self.split_items(tokens) {
+            match item[0].text.as_str() {
+                "inherit" => {
+                    let base = item.get(1).map(|t| t.text.clone()).unwrap_or_default();
+                    class.metadata.dependencies.push(base.clone());
+                    class.metadata.annotations.insert("inherits".to_string(), Value::String(base));
+                }
+                "interface" if item[0].kind == TokenKind::Keyword => {
+                    let interface = item.get(1).map(|t| t.text.clone()).unwrap_or_default();
+                    let implements = class.metadata.annotations.entry("implements".to_string())
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let Value::Array(implements) = implements {
+                        implements.push(Value::String(interface.clone()));
+                    }
+                    if let Some(with) = self.find_depth0(item, 1, |t| t.is("with")) {
+                        for mut member in self.parse_items(self.member_section(&item[with..])) {
+                            member.metadata.annotations.insert("interface".to_string(), Value::String(interface.clone()));
+                            class.children.push(member);
+                        }
+                    }
+                }
+                _ => class.children.extend(self.parse_items(item)),
+            }
+        }
+    }
+    
+    fn parse_member(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens[0].line;
+        let mut index = 0;
+        let mut modifiers: Vec<String> = Vec::new();
+        while let Some(token) = tokens.get(index).filter(|t| {
+            t.kind == TokenKind::Keyword && matches!(t.text.as_str(),
+                "static" | "override" | "default" | "abstract" | "member" | "private" | "internal" | "public" | "inline" | "val")
+        }) {
+            modifiers.push(token.text.clone());
+            index += 1;
+        }
+        let rest = &tokens[index.min(tokens.len())..];
+        let has = |modifier: &str| modifiers.iter().any(|m| m == modifier);
+        
+        let mut node = if rest.first().is_some_and(|t| t.is("new")) {
+            let mut constructor = self.create_node(NodeType::Function, Some("new".to_string()), line, "constructor");
+            let eq = self.find_depth0(rest, 1, |t| t.is("=")).unwrap_or(rest.len());
+            constructor.children = self.parse_params(&rest[1..eq]);
+            if eq < rest.len() {
+                constructor.children.extend(self.parse_items(&rest[eq + 1..]));
+            }
+            constructor
+        } else if has("abstract") {
+            let name = rest.first().map(|t| t.text.clone());
+            let mut member = self.create_node(NodeType::Function, name, line, "abstract");
+            if let Some(colon) = self.find_depth0(rest, 0, |t| t.is(":")) {
+                member.metadata.annotations.insert("signature".to_string(), Value::String(self.join(&rest[colon + 1..])));
+            }
+            member
+        } else if has("val") {
+            let name = rest.first().map(|t| t.text.clone());
+            let tag = if has("member") { "auto_property" } else { "field" };
+            let mut field = self.create_node(NodeType::Variable, name, line, tag);
+            if let Some(colon) = self.find_depth0(rest, 0, |t| t.is(":")) {
+                let end = self.find_depth0(rest, colon, |t| t.is("=") || t.is("with")).unwrap_or(rest.len());
+                field.metadata.annotations.insert("type".to_string(), Value::String(self.join(&rest[colon + 1..end])));
+            }
+            if let Some(eq) = self.find_depth0(rest, 0, |t| t.is("=")) {
+                let end = self.find_depth0(rest, eq, |t| t.is("with")).unwrap_or(rest.len());
+                field.children.push(self.parse_expr(&rest[eq + 1..end]));
+            }
+            field
+        } else {
+            // Instance members name a self identifier: this.Name / _.Name
+            let head = rest.first().map(|t| t.text.clone()).unwrap_or_default();
+            let name = match head.split_once('.') {
+                Some((_, name)) if !has("static") => name.to_string(),
+                _ => head,
+            };
+            let eq = self.find_depth0(rest, 1, |t| t.is("="));
+            let header = &rest[1.min(rest.len())..eq.unwrap_or(rest.len())];
+            let body = eq.map(|eq| &rest[eq + 1..]).unwrap_or(&[]);
+            
+            if header.first().is_some_and(|t| t.is("with")) {
+                let mut property = self.create_node(NodeType::Function, Some(name), line, "property");
+                let accessors: Vec<Value> = ["get", "set"].iter()
+                    .filter(|a| rest.iter().any(|t| t.text == **a))
+                    .map(|a| Value::String(a.to_string()))
+                    .collect();
+                property.metadata.annotations.insert("accessors".to_string(), Value::Array(accessors));
+                property
+            } else {
+                let colon = self.find_depth0(header, 0, |t| t.is(":"));
+                let params = &header[..colon.unwrap_or(header.len())];
+                let tag = if params.is_empty() { "property" } else { "method" };
+                let mut member = self.create_node(NodeType::Function, Some(name), line, tag);
+                member.children = self.parse_params(params);
+                if let Some(colon) = colon {
+                    member.metadata.annotations.insert("return_type".to_string(), Value::String(self.join(&header[colon + 1..])));
+                }
+                member.children.extend(self.parse_function_body(body));
+                member
+            }
+        };
+        
+        for modifier in modifiers {
+            match modifier.as_str() {
+                "static" | "override" | "default" | "inline" => node.metadata.semantic_tags.push(modifier),
+                "private" | "internal" | "public" => {
+                    node.metadata.annotations.insert("visibility".to_string(), Value::String(modifier));
+                }
+                _ => {}
+            }
+        }
+        node.metadata.semantic_tags.push("member".to_string());
+        node
+    }
+    
+    fn parse_expr(&self, tokens: &[Token]) -> UIRNode {
+        let Some(first) = tokens.first() else {
+            return self.create_node(NodeType::Expression(ExpressionType::Literal), None, 0, "empty");
+        };
+        
+        let mut node = match first.text.as_str() {
+            _ if first.kind != TokenKind::Keyword => self.parse_operators(tokens),
+            "if" | "elif" => self.parse_if(tokens),
+            "match" | "match!" => self.parse_match(tokens),
+            "function" => self.parse_function_keyword(tokens),
+            "fun" => self.parse_lambda(tokens),
+            "for" => self.parse_for(tokens),
+            "while" => self.parse_while(tokens),
+            "try" => self.parse_try(tokens),
+            "let" | "use" | "let!" | "use!" => self.parse_let(tokens),
+            "return" | "return!" | "yield" | "yield!" | "do!" => self.parse_item(tokens).remove(0),
+            _ => self.parse_operators(tokens),
+        };
+        
+        let text = self.join(tokens);
+        if text.len() < 100 {
+            node.metadata.annotations.entry("original_text".to_string()).or_insert(Value::String(text));
+        }
+        node
+    }
+    
+    fn parse_operators(&self, tokens: &[Token]) -> UIRNode {
+        let mut pos = 0;
+        self.parse_binary(tokens, &mut pos, 0)
+            .unwrap_or_else(|| self.create_node(NodeType::Expression(ExpressionType::Literal), None, tokens[0].line, "empty"))
+    }
+    
+    fn parse_if(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens[0].line;
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("if_expression".to_string()), line, "if");
+        let Some(then) = self.find_depth0(tokens, 1, |t| t.is("then")) else {
+            node.children.push(self.parse_expr(&tokens[1..]));
+            return node;
+        };
+        node.children.push(self.parse_expr(&tokens[1..then]));
+        
+        let otherwise = self.find_else(tokens, then);
+        node.children.push(self.block("then", &tokens[then + 1..otherwise.unwrap_or(tokens.len())], line));
+        
+        if let Some(otherwise) = otherwise {
+            if tokens[otherwise].is("elif") {
+                node.children.push(self.parse_if(&tokens[otherwise..]));
+            } else {
+                node.children.push(self.block("else", &tokens[otherwise + 1..], tokens[otherwise].line));
+            }
+        }
+        node
+    }
+    
+    /// The `else`/`elif` belonging to this `if`, skipping those of nested ifs
+    fn find_else(&self, tokens: &[Token], then: usize) -> Option<usize> {
+        let column = tokens[0].col;
+        let mut inline_ifs = 0;
+        let mut depth = 0;
+        
+        for (i, token) in tokens.iter().enumerate().skip(then + 1) {
+            if depth == 0 {
+                if token.is("if") && !token.first_on_line {
+                    inline_ifs += 1;
+                } else if token.is("else") || token.is("elif") {
+                    if token.first_on_line {
+                        if token.col <= column {
+                            return Some(i);
+                        }
+                    } else if inline_ifs == 0 {
+                        return Some(i);
+                    } else if token.is("else") {
+                        inline_ifs -= 1;
                     }
                 }
+            }
+            depth += self.delta(token);
+        }
+        None
+    }
+    
+    fn parse_match(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens[0].line;
+        let with = self.find_depth0(tokens, 1, |t| t.is("with")).unwrap_or(tokens.len());
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Switch), Some("match".to_string()), line, "match");
+        node.children.push(self.parse_expr(&tokens[1..with]));
+        if with < tokens.len() {
+            node.children.extend(self.parse_cases(&tokens[with + 1..]));
+        }
+        if tokens[0].text.ends_with('!') {
+            node.metadata.semantic_tags.push("await".to_string());
+        }
+        node
+    }
+    
+    /// `| pattern [when guard] -> body` cases, split by layout and arrows
+    fn parse_cases(&self, tokens: &[Token]) -> Vec<UIRNode> {
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+        
+        let mut bar_col = tokens[0].is("|").then_some(tokens[0].col);
+        let mut start = if tokens[0].is("|") { 1 } else { 0 };
+        let mut segments = Vec::new();
+        let mut seen_arrow = false;
+        let mut depth = 0;
+        
+        for (i, token) in tokens.iter().enumerate().skip(start) {
+            if depth == 0 {
+                if token.is("->") {
+                    seen_arrow = true;
+                } else if token.is("|") && i > start {
+                    let separator = if token.first_on_line {
+                        bar_col.is_none_or(|c| token.col <= c)
+                    } else {
+                        // A bar before the arrow is an or-pattern
+                        seen_arrow
+                    };
+                    if separator {
+                        bar_col = bar_col.or(Some(token.col));
+                        segments.push(&tokens[start..i]);
+                        start = i + 1;
+                        seen_arrow = false;
+                        continue;
+                    }
+                }
+            }
+            depth += self.delta(token);
+        }
+        segments.push(&tokens[start..]);
+        
+        segments.into_iter()
+            .filter(|s| !s.is_empty())
+            .map(|segment| {
+                let line = segment[0].line;
+                let arrow = self.find_depth0(segment, 0, |t| t.is("->")).unwrap_or(segment.len());
+                let pattern_tokens = &segment[..arrow];
+                let guard = self.find_depth0(pattern_tokens, 0, |t| t.is("when"));
+                
+                let mut case = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("case".to_string()), line, "match_case");
+                let pattern_text = self.join(&pattern_tokens[..guard.unwrap_or(pattern_tokens.len())]);
+                case.metadata.annotations.insert("pattern".to_string(), Value::String(pattern_text.clone()));
                 
-                root.children.push(func_node);
+                let mut pattern = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "pattern");
+                pattern.metadata.annotations.insert("original_text".to_string(), Value::String(pattern_text));
+                case.children.push(pattern);
+                if let Some(guard) = guard {
+                    let condition = &pattern_tokens[guard + 1..];
+                    case.metadata.annotations.insert("guard".to_string(), Value::String(self.join(condition)));
+                    case.children.push(self.parse_expr(condition));
+                }
+                if arrow < segment.len() {
+                    case.children.extend(self.parse_items(&segment[arrow + 1..]));
+                }
+                case
+            })
+            .collect()
+    }
+    
+    fn parse_function_keyword(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens[0].line;
+        let mut lambda = self.create_node(closures::closure(CaptureMode::ByValue), Some("lambda".to_string()), line, "lambda");
+        lambda.metadata.semantic_tags.push("pattern_function".to_string());
+        let mut matcher = self.create_node(NodeType::ControlFlow(ControlFlowType::Switch), Some("match".to_string()), line, "match");
+        matcher.children = self.parse_cases(&tokens[1..]);
+        lambda.children.push(matcher);
+        lambda
+    }
+    
+    fn parse_lambda(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens[0].line;
+        let arrow = self.find_depth0(tokens, 1, |t| t.is("->")).unwrap_or(tokens.len());
+        // F# values are immutable, so closures hold copies
+        let mut lambda = self.create_node(closures::closure(CaptureMode::ByValue), Some("lambda".to_string()), line, "lambda");
+        lambda.children = self.parse_params(&tokens[1..arrow]);
+        if arrow < tokens.len() {
+            lambda.children.extend(self.parse_function_body(&tokens[arrow + 1..]));
+        }
+        lambda
+    }
+    
+    fn parse_for(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens[0].line;
+        let body_start = self.find_depth0(tokens, 1, |t| t.is("do") || t.is("->")).unwrap_or(tokens.len());
+        let head = &tokens[1..body_start];
+        let mut body = if body_start < tokens.len() { &tokens[body_start + 1..] } else { &[][..] };
+        if body.last().is_some_and(|t| t.is("done")) {
+            body = &body[..body.len() - 1];
+        }
+        
+        let mut node = if let Some(within) = self.find_depth0(head, 0, |t| t.is("in")) {
+            let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)), Some("for".to_string()), line, "for_in");
+            node.metadata.annotations.insert("variable".to_string(), Value::String(self.join(&head[..within])));
+            node.children.push(self.parse_expr(&head[within + 1..]));
+            node
+        } else {
+            let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For)), Some("for".to_string()), line, "for");
+            let eq = self.find_depth0(head, 0, |t| t.is("=")).unwrap_or(0);
+            let to = self.find_depth0(head, 0, |t| t.is("to") || t.is("downto")).unwrap_or(head.len());
+            node.metadata.annotations.insert("variable".to_string(), Value::String(self.join(&head[..eq])));
+            if head.get(to).is_some_and(|t| t.is("downto")) {
+                node.metadata.annotations.insert("descending".to_string(), Value::Bool(true));
+            }
+            node.children.push(self.parse_expr(&head[(eq + 1).min(to)..to]));
+            if to < head.len() {
+                node.children.push(self.parse_expr(&head[to + 1..]));
+            }
+            node
+        };
+        if tokens.get(body_start).is_some_and(|t| t.is("->")) {
+            node.metadata.semantic_tags.push("comprehension".to_string());
+        }
+        node.children.extend(self.parse_items(body));
+        node
+    }
+    
+    fn parse_while(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens[0].line;
+        let body_start = self.find_depth0(tokens, 1, |t| t.is("do")).unwrap_or(tokens.len());
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)), Some("while".to_string()), line, "while");
+        node.children.push(self.parse_expr(&tokens[1..body_start]));
+        if body_start < tokens.len() {
+            let body = &tokens[body_start + 1..];
+            let body = if body.last().is_some_and(|t| t.is("done")) { &body[..body.len() - 1] } else { body };
+            node.children.extend(self.parse_items(body));
+        }
+        node
+    }
+    
+    fn parse_try(&self, tokens: &[Token]) -> UIRNode {
+        let line = tokens[0].line;
+        let column = tokens[0].col;
+        let mut inline_tries = 0;
+        let mut depth = 0;
+        let mut handler = None;
+        for (i, token) in tokens.iter().enumerate().skip(1) {
+            if depth == 0 {
+                if token.is("try") && !token.first_on_line {
+                    inline_tries += 1;
+                } else if token.is("with") || token.is("finally") {
+                    if token.first_on_line && token.col <= column {
+                        handler = Some(i);
+                        break;
+                    }
+                    if !token.first_on_line {
+                        if inline_tries == 0 {
+                            handler = Some(i);
+                            break;
+                        }
+                        inline_tries -= 1;
+                    }
+                }
             }
+            depth += self.delta(token);
         }
         
-        Ok(())
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Try), Some("try".to_string()), line, "try");
+        node.children.push(self.block("try_block", &tokens[1..handler.unwrap_or(tokens.len())], line));
+        
+        if let Some(handler) = handler {
+            let rest = &tokens[handler + 1..];
+            if tokens[handler].is("finally") {
+                let mut finally = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("finally".to_string()), tokens[handler].line, "finally");
+                finally.children = self.parse_items(rest);
+                node.children.push(finally);
+            } else {
+                for mut case in self.parse_cases(rest) {
+                    case.metadata.semantic_tags.push("catch".to_string());
+                    node.children.push(case);
+                }
+            }
+        }
+        node
+    }
+    
+    /// Binding power of binary operators, loosest first
+    fn operator_level(&self, token: &Token) -> Option<usize> {
+        if token.is(",") {
+            return Some(1);
+        }
+        if token.kind == TokenKind::Keyword && token.text == "or" {
+            return Some(2);
+        }
+        if token.kind != TokenKind::Operator {
+            return None;
+        }
+        let op = token.text.as_str();
+        match op {
+            "<-" | ":=" => Some(0),
+            "||" => Some(2),
+            "&&" | "&" => Some(3),
+            "|" | "->" | ":" | ":>" | ":?>" | ":?" | "." | "?" => None,
+            ".." => Some(4),
+            "::" => Some(6),
+            _ if op.starts_with("**") => Some(9),
+            _ if op.starts_with(['|', '<', '>', '=', '$']) || op == "!=" => Some(4),
+            _ if op.starts_with(['^', '@']) => Some(5),
+            _ if op.starts_with(['+', '-']) => Some(7),
+            _ if op.starts_with(['*', '/', '%']) => Some(8),
+            _ => None,
+        }
     }
     
-    fn parse_let_bindings(&self, source: &str, root: &mut UIRNode) -> Result<()> {
-        // Parse simple let bindings (variables)
-        let let_regex = Regex::new(r"(?m)^let\s+(\w+)\s*=\s*([^=\r\n]+)").unwrap();
+    fn parse_binary(&self, tokens: &[Token], pos: &mut usize, level: usize) -> Option<UIRNode> {
+        if level > 9 {
+            return self.parse_unary(tokens, pos);
+        }
         
-        for caps in let_regex.captures_iter(source) {
-            let var_name = caps.get(1).unwrap().as_str();
-            let value = caps.get(2).unwrap().as_str().trim();
-            let line_num = source[..caps.get(0).unwrap().start()].lines().count() + 1;
+        let mut left = self.parse_binary(tokens, pos, level + 1)?;
+        let mut elements: Vec<UIRNode> = Vec::new();
+        while let Some(op) = tokens.get(*pos).filter(|t| self.operator_level(t) == Some(level)) {
+            let op = op.clone();
+            *pos += 1;
+            let right = if tokens.get(*pos).is_some_and(|t| t.kind == TokenKind::Keyword && LEADING_KEYWORDS.contains(&t.text.as_str())) {
+                let rest = self.parse_expr(&tokens[*pos..]);
+                *pos = tokens.len();
+                rest
+            } else {
+                match self.parse_binary(tokens, pos, level + 1) {
+                    Some(right) => right,
+                    None => break,
+                }
+            };
             
-            // Skip if this looks like a function (has parameters before =)
-            let full_match = caps.get(0).unwrap().as_str();
-            if full_match.matches(char::is_whitespace).count() <= 3 {
-                let var_node = UIRNode {
-                    id: format!("var_{}", var_name),
-                    node_type: NodeType::Variable,
-                    name: Some(var_name.to_string()),
-                    children: Vec::new(),
-                    metadata: Metadata {
-                        source_language: CoalesceLanguage::FSharp,
-                        semantic_tags: vec!["variable".to_string()],
-                        complexity_score: None,
-                        dependencies: Vec::new(),
-                        annotations: {
-                            let mut map = HashMap::new();
-                            map.insert("original_text".to_string(), Value::String(caps.get(0).unwrap().as_str().to_string()));
-                            map.insert("value".to_string(), Value::String(value.to_string()));
-                            map
-                        },
-                        legacy_patterns: Vec::new(),
-                    },
-                    source_location: Some(SourceLocation {
-                        file: String::new(),
-                        start_line: line_num as u32,
-                        end_line: line_num as u32,
-                        start_column: 0,
-                        end_column: caps.get(0).unwrap().len() as u32,
-                    }),
+            if level == 1 {
+                elements.push(right);
+                continue;
+            }
+            left = self.combine(left, &op.text, right, op.line);
+        }
+        
+        if !elements.is_empty() {
+            let line = left.source_location.as_ref().map(|l| l.start_line).unwrap_or(0);
+            let mut tuple = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "tuple");
+            tuple.children.push(left);
+            tuple.children.extend(elements);
+            left = tuple;
+        }
+        Some(left)
+    }
+    
+    fn combine(&self, left: UIRNode, op: &str, right: UIRNode, line: u32) -> UIRNode {
+        let (node_type, name, tag) = match op {
+            "<-" => (NodeType::Expression(ExpressionType::Assignment), left.name.clone(), "mutation"),
+            ":=" => (NodeType::Expression(ExpressionType::Assignment), left.name.clone(), "ref_assignment"),
+            "||" | "or" | "&&" | "&" => (NodeType::Expression(ExpressionType::Logical), None, "logical"),
+            "|>" | "||>" | "|||>" | "<|" | "<||" | "<|||" => (NodeType::Expression(ExpressionType::FunctionCall), Some("pipe".to_string()), "pipe"),
+            ">>" | "<<" => (NodeType::Expression(ExpressionType::FunctionCall), Some("compose".to_string()), "composition"),
+            ".." => (NodeType::Expression(ExpressionType::FunctionCall), Some("range".to_string()), "range"),
+            "::" => (NodeType::Expression(ExpressionType::FunctionCall), Some("cons".to_string()), "cons"),
+            "=" | "<>" | "<" | ">" | "<=" | ">=" | "==" | "!=" => (NodeType::Expression(ExpressionType::Comparison), None, "comparison"),
+            _ if self.operator_level(&Token {
+                kind: TokenKind::Operator, text: op.to_string(), line, col: 0, first_on_line: false, space_before: true,
+            }) == Some(4) => (NodeType::Expression(ExpressionType::FunctionCall), Some(op.to_string()), "custom_operator"),
+            _ => (NodeType::Expression(ExpressionType::Arithmetic), None, "arithmetic"),
+        };
+        
+        let mut node = self.create_node(node_type, name, line, tag);
+        if takes_operator(&node.node_type) {
+            node.operator = Operator::binary(op, &CoalesceLanguage::FSharp);
+        }
+        node.metadata.annotations.insert("operator".to_string(), Value::String(op.to_string()));
+        node.children = vec![left, right];
+        node
+    }
+    
+    fn parse_unary(&self, tokens: &[Token], pos: &mut usize) -> Option<UIRNode> {
+        let token = tokens.get(*pos)?;
+        if token.kind == TokenKind::Operator && matches!(token.text.as_str(), "-" | "+" | "-." | "!" | "~~~") && *pos + 1 < tokens.len() {
+            let op = token.text.clone();
+            let line = token.line;
+            *pos += 1;
+            let operand = self.parse_unary(tokens, pos)?;
+            let mut node = if op == "!" {
+                self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some("!".to_string()), line, "dereference")
+            } else {
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::Arithmetic), None, line, "negation");
+                node.operator = Operator::unary(&op, &CoalesceLanguage::FSharp);
+                node
+            };
+            node.metadata.annotations.insert("operator".to_string(), Value::String(op));
+            node.children.push(operand);
+            return Some(node);
+        }
+        self.parse_application(tokens, pos)
+    }
+    
+    /// Juxtaposition applies a function to its arguments: `List.map f xs`
+    fn parse_application(&self, tokens: &[Token], pos: &mut usize) -> Option<UIRNode> {
+        let line = tokens[*pos].line;
+        let head = self.parse_postfix(tokens, pos)?;
+        let mut args = Vec::new();
+        
+        while let Some(token) = tokens.get(*pos) {
+            if !self.starts_atom(token) {
+                break;
+            }
+            if token.kind == TokenKind::Keyword && LEADING_KEYWORDS.contains(&token.text.as_str()) {
+                args.push(self.parse_expr(&tokens[*pos..]));
+                *pos = tokens.len();
+                break;
+            }
+            match self.parse_postfix(tokens, pos) {
+                Some(arg) => args.push(arg),
+                None => break,
+            }
+        }
+        
+        if args.is_empty() {
+            return Some(head);
+        }
+        Some(self.make_call(head, args, line))
+    }
+    
+    fn starts_atom(&self, token: &Token) -> bool {
+        match token.kind {
+            TokenKind::Ident | TokenKind::Literal => true,
+            TokenKind::Punct => matches!(token.text.as_str(), "(" | "[" | "[|" | "{"),
+            TokenKind::Keyword => LEADING_KEYWORDS.contains(&token.text.as_str())
+                || matches!(token.text.as_str(), "new" | "null" | "true" | "false" | "lazy" | "upcast" | "downcast" | "assert"),
+            _ => false,
+        }
+    }
+    
+    fn make_call(&self, callee: UIRNode, args: Vec<UIRNode>, line: u32) -> UIRNode {
+        let is_named = callee.node_type == NodeType::Expression(ExpressionType::Variable);
+        let name = if is_named { callee.name.clone() } else { Some("apply".to_string()) };
+        
+        let mut node = match name.as_deref() {
+            Some(throw) if THROW_FUNCTIONS.contains(&throw) => {
+                self.create_node(NodeType::Statement(StatementType::Throw), name.clone(), line, "throw")
+            }
+            Some("not") => {
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::Logical), None, line, "not");
+                node.operator = Some(Operator::Not);
+                node.metadata.annotations.insert("operator".to_string(), Value::String("not".to_string()));
+                node
+            }
+            _ => self.create_node(NodeType::Expression(ExpressionType::FunctionCall), name, line, "call"),
+        };
+        if !is_named {
+            node.children.push(callee);
+        }
+        node.children.extend(args);
+        node
+    }
+    
+    fn parse_postfix(&self, tokens: &[Token], pos: &mut usize) -> Option<UIRNode> {
+        let mut node = self.parse_primary(tokens, pos)?;
+        
+        while let Some(token) = tokens.get(*pos) {
+            let line = token.line;
+            if token.is("(") && !token.space_before {
+                // Method-call syntax: obj.Method(a, b)
+                let close = self.matching_close(tokens, *pos);
+                let inner = &tokens[*pos + 1..close];
+                let args = if inner.is_empty() {
+                    Vec::new()
+                } else {
+                    self.split_on(inner, ",").into_iter().map(|arg| self.parse_expr(arg)).collect()
                 };
-                
-                root.children.push(var_node);
+                *pos = close + 1;
+                node = self.make_call(node, args, line);
+                node.metadata.semantic_tags.push("method_call".to_string());
+            } else if (token.is("[") && !token.space_before) || (token.is(".") && tokens.get(*pos + 1).is_some_and(|t| t.is("["))) {
+                let open = if token.is(".") { *pos + 1 } else { *pos };
+                let close = self.matching_close(tokens, open);
+                let index = self.parse_expr(&tokens[open + 1..close]);
+                *pos = close + 1;
+                let mut indexing = self.create_node(NodeType::Expression(ExpressionType::Variable), node.name.clone(), line, "indexing");
+                indexing.children = vec![node, index];
+                node = indexing;
+            } else if token.is(".") && tokens.get(*pos + 1).is_some_and(|t| t.kind == TokenKind::Ident && !t.space_before) {
+                let member = tokens[*pos + 1].text.clone();
+                *pos += 2;
+                let mut access = self.create_node(NodeType::Expression(ExpressionType::Variable), Some(member), line, "member_access");
+                access.children.push(node);
+                node = access;
+            } else if token.is(":>") || token.is(":?>") {
+                let target = tokens.get(*pos + 1).map(|t| t.text.clone()).unwrap_or_default();
+                *pos += 2;
+                node.metadata.annotations.insert("cast".to_string(), Value::String(target));
+            } else if token.is(":?") {
+                let target = tokens.get(*pos + 1).map(|t| t.text.clone()).unwrap_or_default();
+                *pos += 2;
+                let mut test = self.create_node(NodeType::Expression(ExpressionType::Comparison), None, line, "type_test");
+                test.metadata.annotations.insert("type".to_string(), Value::String(target));
+                test.children.push(node);
+                node = test;
+            } else {
+                break;
+            }
+        }
+        
+        Some(node)
+    }
+    
+    fn parse_primary(&self, tokens: &[Token], pos: &mut usize) -> Option<UIRNode> {
+        let token = tokens.get(*pos)?.clone();
+        let line = token.line;
+        
+        match token.kind {
+            TokenKind::Literal => {
+                *pos += 1;
+                Some(self.create_literal(&token))
+            }
+            TokenKind::Ident => {
+                // builder { ... } is a computation expression
+                let next = tokens.get(*pos + 1);
+                if next.is_some_and(|n| n.is("{") && n.line == line) {
+                    let open = *pos + 1;
+                    let close = self.matching_close(tokens, open);
+                    let inner = &tokens[open + 1..close];
+                    if !self.is_record(inner) {
+                        *pos = close + 1;
+                        return Some(self.computation_expression(&token, inner));
+                    }
+                }
+                *pos += 1;
+                let tag = if token.text == "_" { "wildcard" } else { "identifier" };
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::Variable), Some(token.text.clone()), line, tag);
+                node.metadata.annotations.insert("original_text".to_string(), Value::String(token.text.clone()));
+                Some(node)
+            }
+            TokenKind::Keyword => match token.text.as_str() {
+                "true" | "false" | "null" => {
+                    *pos += 1;
+                    Some(self.create_literal(&token))
+                }
+                "new" => {
+                    *pos += 1;
+                    let type_name = tokens.get(*pos).map(|t| t.text.clone());
+                    *pos += 1;
+                    let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), type_name, line, "constructor_call");
+                    if tokens.get(*pos).is_some_and(|t| t.is("(")) {
+                        let close = self.matching_close(tokens, *pos);
+                        let inner = &tokens[*pos + 1..close];
+                        if !inner.is_empty() {
+                            call.children = self.split_on(inner, ",").into_iter().map(|arg| self.parse_expr(arg)).collect();
+                        }
+                        *pos = close + 1;
+                    }
+                    Some(call)
+                }
+                "lazy" | "assert" | "upcast" | "downcast" => {
+                    *pos += 1;
+                    let operand = self.parse_postfix(tokens, pos)?;
+                    if matches!(token.text.as_str(), "upcast" | "downcast") {
+                        return Some(operand);
+                    }
+                    let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(token.text.clone()), line, "call");
+                    call.children.push(operand);
+                    Some(call)
+                }
+                _ if LEADING_KEYWORDS.contains(&token.text.as_str()) => {
+                    let node = self.parse_expr(&tokens[*pos..]);
+                    *pos = tokens.len();
+                    Some(node)
+                }
+                _ => None,
+            },
+            TokenKind::Punct => {
+                let close = self.matching_close(tokens, *pos);
+                let inner = &tokens[(*pos + 1).min(close)..close];
+                let node = match token.text.as_str() {
+                    "(" if inner.is_empty() => self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "unit"),
+                    "(" if inner.len() == 1 && inner[0].kind == TokenKind::Operator => {
+                        self.create_node(NodeType::Expression(ExpressionType::Variable), Some(inner[0].text.clone()), line, "operator_function")
+                    }
+                    "(" => {
+                        let mut grouped = self.parse_expr(inner);
+                        mark_grouped(&mut grouped);
+                        grouped
+                    }
+                    "[" | "[|" => self.parse_collection(inner, if token.is("[") { "list" } else { "array" }, line),
+                    "{" => self.parse_record(inner, line),
+                    "[<" => {
+                        *pos = close + 1;
+                        return self.parse_primary(tokens, pos);
+                    }
+                    _ => return None,
+                };
+                *pos = close + 1;
+                Some(node)
+            }
+            _ => None,
+        }
+    }
+    
+    fn parse_collection(&self, inner: &[Token], tag: &str, line: u32) -> UIRNode {
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, tag);
+        if inner.is_empty() {
+            return node;
+        }
+        
+        let comprehension = inner[0].kind == TokenKind::Keyword
+            && matches!(inner[0].text.as_str(), "for" | "yield" | "yield!" | "if" | "let" | "match" | "while");
+        if comprehension {
+            node.metadata.semantic_tags.push("comprehension".to_string());
+        }
+        node.children = self.parse_items(inner);
+        node
+    }
+    
+    /// `{ X = 1; Y = 2 }`, `{ r with X = 1 }` or `{ new IFoo with ... }`
+    fn parse_record(&self, inner: &[Token], line: u32) -> UIRNode {
+        if inner.first().is_some_and(|t| t.is("new")) {
+            let interface = inner.get(1).map(|t| t.text.clone());
+            let mut object = self.create_node(NodeType::Class, interface.clone(), line, "object_expression");
+            if let Some(interface) = interface {
+                object.metadata.annotations.insert("implements".to_string(), Value::Array(vec![Value::String(interface)]));
+            }
+            if let Some(with) = self.find_depth0(inner, 1, |t| t.is("with")) {
+                object.children = self.parse_items(&inner[with + 1..]);
             }
+            return object;
         }
         
-        Ok(())
+        let mut record = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "record");
+        let mut fields = inner;
+        if let Some(with) = self.find_depth0(inner, 0, |t| t.is("with")) {
+            record.metadata.semantic_tags.push("copy_and_update".to_string());
+            record.children.push(self.parse_expr(&inner[..with]));
+            fields = &inner[with + 1..];
+        }
+        
+        for field in self.split_items(fields) {
+            let Some(eq) = self.find_depth0(field, 0, |t| t.is("=")) else {
+                record.children.push(self.parse_expr(field));
+                continue;
+            };
+            let target = self.create_node(NodeType::Expression(ExpressionType::Variable), Some(self.join(&field[..eq])), field[0].line, "field");
+            let mut assignment = self.create_node(NodeType::Expression(ExpressionType::Assignment), target.name.clone(), field[0].line, "field_assignment");
+            assignment.children = vec![target, self.parse_expr(&field[eq + 1..])];
+            record.children.push(assignment);
+        }
+        record
+    }
+    
+    fn is_record(&self, inner: &[Token]) -> bool {
+        inner.first().is_some_and(|t| t.kind == TokenKind::Ident)
+            && inner.get(1).is_some_and(|t| t.is("=") || t.is("with"))
+    }
+    
+    fn computation_expression(&self, builder: &Token, inner: &[Token]) -> UIRNode {
+        let mut node = self.create_node(
+            NodeType::Expression(ExpressionType::FunctionCall),
+            Some(builder.text.clone()),
+            builder.line,
+            "computation_expression",
+        );
+        node.metadata.annotations.insert("builder".to_string(), Value::String(builder.text.clone()));
+        match builder.text.as_str() {
+            "async" | "task" | "backgroundTask" => {
+                node.metadata.semantic_tags.push("async".to_string());
+                node.is_async = true;
+            }
+            "seq" => node.metadata.semantic_tags.push("sequence".to_string()),
+            "query" => node.metadata.semantic_tags.push("query".to_string()),
+            _ => {}
+        }
+        node.children = self.parse_items(inner);
+        node
+    }
+    
+    fn create_literal(&self, token: &Token) -> UIRNode {
+        let text = &token.text;
+        let tag = if text.starts_with('$') {
+            "interpolated"
+        } else if text.contains('"') {
+            "string"
+        } else if text.starts_with('\'') {
+            "char"
+        } else if matches!(text.as_str(), "true" | "false") {
+            "boolean"
+        } else if text == "null" {
+            "null"
+        } else {
+            "number"
+        };
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::Literal), None, token.line, tag);
+        node.literal = LiteralValue::parse(text, &CoalesceLanguage::FSharp);
+        node.metadata.annotations.insert("value".to_string(), Value::String(text.clone()));
+        node
+    }
+    
+    fn collect_imports(&self, node: &UIRNode, dependencies: &mut Vec<String>) {
+        for child in &node.children {
+            match child.node_type {
+                NodeType::Import { .. } if child.metadata.semantic_tags.iter().any(|t| t == "import") => {
+                    dependencies.extend(child.metadata.dependencies.iter().cloned());
+                }
+                NodeType::Module => self.collect_imports(child, dependencies),
+                _ => {}
+            }
+        }
+    }
+    
+    fn join(&self, tokens: &[Token]) -> String {
+        let mut text = String::new();
+        for (i, token) in tokens.iter().enumerate() {
+            if i > 0 && token.space_before {
+                text.push(' ');
+            }
+            text.push_str(&token.text);
+        }
+        text
+    }
+    
+    fn node_id(&self, tag: &str, line: u32, name: Option<&str>) -> String {
+        format!(
+            "{}_{}_{}",
+            tag,
+            line,
+            name.unwrap_or("").replace(|c: char| !c.is_alphanumeric(), "_"),
+        )
+    }
+    
+    fn create_node(&self, node_type: NodeType, name: Option<String>, line: u32, tag: &str) -> UIRNode {
+        UIRNode {
+            id: self.node_id(tag, line, name.as_deref()),
+            node_type,
+            name,
+            children: Vec::new(),
+            metadata: Metadata {
+                source_language: CoalesceLanguage::FSharp,
+                semantic_tags: vec![tag.to_string()],
+                ..Default::default()
+            },
+            source_location: Some(SourceLocation {
+                file: String::new(),
+                start_line: line,
+                end_line: line,
+                start_column: 0,
+                end_column: 0,
+            }),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
     }
 }
 
@@ -278,7 +1842,7 @@ type Person = {
     Age: int
 }
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
@@ -291,8 +1855,119 @@ module Math =
     let add x y = x + y
     let multiply x y = x * y
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
+    
+    #[test]
+    fn test_fsharp_nested_modules_match_and_pipeline() {
+        let parser = FSharpParser::new().unwrap();
+        let source = r#"
+open System
+
+module Geometry =
+    type Shape =
+        | Circle of radius: float
+        | Rect of float * float
+
+    module Area =
+        let compute shape =
+            match shape with
+            | Circle r -> Math.PI * r * r
+            | Rect (w, h) when w > 0.0 -> w * h
+            | _ -> 0.0
+
+let total shapes =
+    shapes
+    |> List.map Geometry.Area.compute
+    |> List.sum
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.metadata.dependencies, vec!["System".to_string()]);
+        
+        let geometry = &uir.children[1];
+        assert_eq!(geometry.node_type, NodeType::Module);
+        let shape = &geometry.children[0];
+        assert!(shape.metadata.semantic_tags.contains(&"union".to_string()));
+        assert_eq!(shape.children.len(), 2);
+        
+        let area = &geometry.children[1];
+        let compute = &area.children[0];
+        assert_eq!(compute.node_type, NodeType::Function);
+        let matcher = &compute.children[1];
+        assert_eq!(matcher.node_type, NodeType::ControlFlow(ControlFlowType::Switch));
+        assert_eq!(matcher.children.len(), 4);
+        assert_eq!(matcher.children[2].metadata.annotations["guard"], "w > 0.0");
+        
+        let total = &uir.children[2];
+        let body = &total.children[1];
+        assert_eq!(body.node_type, NodeType::Statement(StatementType::Return));
+        let pipeline = &body.children[0];
+        assert!(pipeline.metadata.semantic_tags.contains(&"pipe".to_string()));
+        assert_eq!(pipeline.children[1].name.as_deref(), Some("List.sum"));
+    }
+    
+    #[test]
+    fn test_fsharp_computation_expression_and_class() {
+        let parser = FSharpParser::new().unwrap();
+        let source = r#"
+type Client(baseUrl: string) =
+    inherit HttpBase()
+    member this.Fetch(path: string) =
+        async {
+            let! response = this.Get(baseUrl + path)
+            if response.IsSuccess then
+                return Some response.Body
+            else
+                return None
+        }
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let client = &uir.children[0];
+        assert_eq!(client.node_type, NodeType::Class);
+        assert_eq!(client.metadata.annotations["inherits"], "HttpBase");
+        assert!(client.children[0].metadata.semantic_tags.contains(&"constructor_parameter".to_string()));
+        
+        let fetch = &client.children[1];
+        assert!(fetch.metadata.semantic_tags.contains(&"method".to_string()));
+        let workflow = &fetch.children[1].children[0];
+        assert!(workflow.metadata.semantic_tags.contains(&"async".to_string()));
+        assert!(workflow.children[0].metadata.semantic_tags.contains(&"await".to_string()));
+        assert_eq!(workflow.children[1].node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+    }
+    
+    #[test]
+    fn test_fsharp_unclosed_bracket_is_a_parse_error() {
+        let parser = FSharpParser::new().unwrap();
+        for (source, bracket, column) in [("type R = {", "{", 9), ("let f x = async {\n    return x", "{", 16), ("let g = f (1, 2", "(", 10)] {
+            match parser.parse(source) {
+                Err(CoalesceError::ParseError { message, line, column: at }) => {
+                    assert_eq!(message, format!("`{}` is never closed", bracket));
+                    assert_eq!((line, at), (1, column));
+                }
+                other => panic!("expected a parse error for {:?}, got {:?}", source, other.map(|_| ())),
+            }
+        }
+    }
+    
+    #[test]
+    fn test_fsharp_non_ascii_string_literals() {
+        fn collect(node: &UIRNode, values: &mut Vec<LiteralValue>) {
+            values.extend(node.literal.clone());
+            node.children.iter().for_each(|child| collect(child, values));
+        }
+        
+        let parser = FSharpParser::new().unwrap();
+        let uir = parser.parse("let label = \"€100\"\nlet name = \"héllo\"\nlet note = \"\"\"é\"\"\"\n").unwrap();
+        let mut values = Vec::new();
+        collect(&uir, &mut values);
+        assert_eq!(values, vec![
+            LiteralValue::String("€100".to_string()),
+            LiteralValue::String("héllo".to_string()),
+            LiteralValue::String("é".to_string()),
+        ]);
+    }
 }
@@ -1,11 +1,17 @@
-use tree_sitter::{Language, Node, Parser};
-use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage, 
-                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::comments;
+use crate::closures;
+use crate::operators::{annotate_operator, unwrap_groups};
+use crate::literals::annotate_literal;
+use crate::incremental::SyntaxTree;
+use coalesce_core::{UIRNode, NodeType, CaptureMode, EnumVariant, ImportedSymbol, Ownership, Visibility, Metadata, SourceLocation, Language as CoalesceLanguage, 
+                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser,
+                   IncrementalParser, IncrementalUpdate, TextEdit};
 use serde_json::Value;
 use std::collections::HashMap;
 
 pub struct GoParser {
-    parser: Parser,
 }
 
 impl CoalesceParser for GoParser {
@@ -22,7 +28,7 @@ impl CoalesceParser for GoParser {
                 line: 0,
                 column: 0,
             })?;
-            
+        
         let tree = parser.parse(source, None)
             .ok_or_else(|| CoalesceError::ParseError {
                 message: "Failed to parse Go source".to_string(),
@@ -30,18 +36,53 @@ impl CoalesceParser for GoParser {
                 column: 0,
             })?;
         
-        let root_node = tree.root_node();
-        self.convert_to_uir(source, root_node, 0)
+        self.convert_root(source, tree.root_node())
+    }
+}
+
+impl IncrementalParser for GoParser {
+    type Tree = SyntaxTree;
+    
+    fn parse_tree(&self, source: &str) -> Result<(SyntaxTree, UIRNode)> {
+        let tree = SyntaxTree::parse(tree_sitter_go::language(), source)?;
+        let uir = self.convert_root(source, tree.root_node())?;
+        Ok((tree, uir))
+    }
+    
+    fn reparse(&self, previous: &SyntaxTree, edits: &[TextEdit]) -> Result<IncrementalUpdate<SyntaxTree>> {
+        previous.reparse(edits, |source, node| self.convert_to_uir(source, node))
+    }
+    
+    fn to_uir(&self, tree: &SyntaxTree) -> Result<UIRNode> {
+        self.convert_root(tree.source(), tree.root_node())
     }
 }
 
 impl GoParser {
     pub fn new() -> Result<Self> {
         // We don't need to store the parser, we'll create it per-parse
-        Ok(Self { parser: tree_sitter::Parser::new() })
+        Ok(Self {})
     }
     
-    fn convert_to_uir(&self, source: &str, node: Node, depth: usize) -> Result<UIRNode> {
+    fn convert_root(&self, source: &str, root_node: Node) -> Result<UIRNode> {
+        let mut uir = self.convert_to_uir(source, root_node)?;
+        recovery::summarize(source, root_node, &mut uir);
+        unwrap_groups(&mut uir);
+        uir.make_ids_unique();
+        comments::attach_comments(&mut uir);
+        closures::annotate_captures(&mut uir);
+        uir.debug_validate();
+        Ok(uir)
+    }
+    
+    fn convert_to_uir(&self, source: &str, node: Node) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::Go, |child| self.convert_to_uir(source, child));
+        }
+        if comments::is_comment(node) {
+            return Ok(comments::comment_node(node, source, &CoalesceLanguage::Go));
+        }
+        
         let node_type = node.kind();
         let start_position = node.start_position();
         let end_position = node.end_position();
@@ -91,6 +132,13 @@ impl GoParser {
                 let struct_name = self.extract_struct_name(source, node);
                 (NodeType::Class, struct_name)
             }
+            // Named per field by `convert_member`
+            "field_declaration" => {
+                (NodeType::Field { field_type: self.field_type(source, node), default_value: None }, None)
+            }
+            "const_declaration" if self.iota_type(source, node).is_some() => {
+                (NodeType::Enum { variants: self.iota_variants(source, node) }, self.iota_type(source, node))
+            }
             "interface_type" => {
                 let interface_name = self.extract_interface_name(source, node);
                 (NodeType::Interface, interface_name)
@@ -99,6 +147,8 @@ impl GoParser {
                 let param_name = self.extract_parameter_name(source, node);
                 (NodeType::Variable, param_name)
             }
+            // Function literals share the enclosing variables
+            "func_literal" => (closures::closure(CaptureMode::ByReference), None),
             "identifier" => {
                 let var_name = Some(original_text.clone());
                 (NodeType::Expression(ExpressionType::Variable), var_name)
@@ -136,14 +186,47 @@ impl GoParser {
             "switch_statement" | "type_switch_statement" => {
                 (NodeType::ControlFlow(coalesce_core::ControlFlowType::Switch), None)
             }
+            "go_statement" => {
+                let callee = self.extract_callee_name(source, node);
+                (NodeType::ControlFlow(coalesce_core::ControlFlowType::Concurrent), callee)
+            }
+            "defer_statement" => {
+                let callee = self.extract_callee_name(source, node);
+                (NodeType::Statement(StatementType::Defer), callee)
+            }
+            "select_statement" => {
+                (NodeType::ControlFlow(coalesce_core::ControlFlowType::Switch), Some("select".to_string()))
+            }
+            "communication_case" => {
+                (NodeType::ControlFlow(coalesce_core::ControlFlowType::Conditional), None)
+            }
+            "send_statement" => {
+                (NodeType::Expression(ExpressionType::FunctionCall), Some("channel_send".to_string()))
+            }
+            "receive_statement" if node.child_by_field_name("left").is_some() => {
+                (NodeType::Expression(ExpressionType::Assignment), None)
+            }
+            "receive_statement" => {
+                (NodeType::Expression(ExpressionType::FunctionCall), Some("channel_receive".to_string()))
+            }
+            "unary_expression" if self.operator_of(node) == Some("<-") => {
+                (NodeType::Expression(ExpressionType::FunctionCall), Some("channel_receive".to_string()))
+            }
+            "unary_expression" => {
+                (NodeType::Expression(ExpressionType::Arithmetic), None)
+            }
+            "var_spec" | "short_var_declaration" => {
+                let var_name = node.child_by_field_name("name")
+                    .or_else(|| node.child_by_field_name("left"))
+                    .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                    .map(str::to_string);
+                (NodeType::Variable, var_name)
+            }
             "package_clause" => {
                 let package_name = self.extract_package_name(source, node);
                 (NodeType::Module, package_name)
             }
-            "import_declaration" => {
-                let import_name = self.extract_import_name(source, node);
-                (NodeType::Module, import_name)
-            }
+            "import_spec" => self.import_spec(source, node),
             _ => {
                 // For other node types, try to categorize them generically
                 if node_type.contains("statement") {
@@ -163,20 +246,261 @@ impl GoParser {
             children: Vec::new(),
             metadata,
             source_location: Some(source_location),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         };
+        annotate_operator(node, source, &CoalesceLanguage::Go, &mut uir_node);
+        annotate_literal(node, source, &CoalesceLanguage::Go, &mut uir_node);
+        if let NodeType::Import { path, .. } = &uir_node.node_type {
+            // The package path is the whole of an import; its tokens are not kept
+            uir_node.metadata.dependencies.push(path.clone());
+            return Ok(uir_node);
+        }
+        self.annotate_ownership(source, node, &mut uir_node);
+        if matches!(uir_node.node_type, NodeType::Field { .. } | NodeType::Enum { .. }) {
+            // Fields and constants are fully described by their node type
+            if let Some(tag) = node.child_by_field_name("tag").and_then(|t| t.utf8_text(source.as_bytes()).ok()) {
+                uir_node.metadata.annotations.insert("tag".to_string(), Value::String(tag.trim_matches('`').to_string()));
+            }
+            return Ok(uir_node);
+        }
         
-        // Process children
+        self.annotate_concurrency(source, node, &mut uir_node);
+        
+        // Process children; an import declaration gives one import per package, and a struct's
+        // fields are its own members rather than those of its body
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            if !child.is_error() {
-                let child_uir = self.convert_to_uir(source, child, depth + 1)?;
-                uir_node.children.push(child_uir);
+            if child.kind() == "import_declaration" {
+                for spec in self.import_specs(child) {
+                    uir_node.children.push(self.convert_to_uir(source, spec)?);
+                }
+                continue;
             }
+            if child.kind() == "field_declaration_list" {
+                let mut fields = child.walk();
+                for field in child.named_children(&mut fields) {
+                    uir_node.children.extend(self.convert_member(source, field)?);
+                }
+                continue;
+            }
+            let child_uir = self.convert_to_uir(source, child)?;
+            uir_node.children.push(child_uir);
         }
         
         Ok(uir_node)
     }
     
+    /// A node converted on its own, or one field for each name of `X, Y int`; an embedded
+    /// field such as `*Base` is named after its type
+    fn convert_member(&self, source: &str, node: Node) -> Result<Vec<UIRNode>> {
+        let member = self.convert_to_uir(source, node)?;
+        if !matches!(member.node_type, NodeType::Field { .. }) {
+            return Ok(vec![member]);
+        }
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let mut cursor = node.walk();
+        let mut names: Vec<String> = node.children_by_field_name("name", &mut cursor).map(text).collect();
+        let embedded = names.is_empty();
+        if embedded {
+            let type_name = node.child_by_field_name("type").map(text).unwrap_or_default();
+            names.push(type_name.rsplit('.').next().unwrap_or_default().to_string());
+        }
+        Ok(names.into_iter()
+            .map(|name| {
+                let mut field = member.clone();
+                if embedded {
+                    field.metadata.semantic_tags.push("embedded_field".to_string());
+                }
+                // Exported names start with a capital letter; others stay within the package
+                let exported = name.starts_with(|c: char| c.is_uppercase());
+                field.visibility = Some(if exported { Visibility::Public } else { Visibility::Internal });
+                field.id = format!("{}_{}", member.id, name);
+                field.name = Some(name);
+                field
+            })
+            .collect())
+    }
+    
+    /// Record the type of a parameter and how it and fields hold their value: through a
+    /// pointer, which the callee may write through, or as a copy
+    fn annotate_ownership(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        if !matches!(node.kind(), "parameter_declaration" | "variadic_parameter_declaration" | "field_declaration") {
+            return;
+        }
+        let Some(declared) = node.child_by_field_name("type") else {
+            return;
+        };
+        if node.kind() != "field_declaration" {
+            let written = declared.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+            uir_node.metadata.annotations.insert("type".to_string(), Value::String(written));
+        }
+        // An embedded `*Base` keeps its `*` outside the type
+        let mut cursor = node.walk();
+        let embedded_pointer = node.children(&mut cursor).any(|c| c.kind() == "*");
+        uir_node.ownership = Some(if declared.kind() == "pointer_type" || embedded_pointer {
+            Ownership::Pointer { mutable: true }
+        } else {
+            Ownership::ByValue
+        });
+    }
+    
+    /// The declared type of a field, with the `*` of an embedded pointer such as `*Base`
+    fn field_type(&self, source: &str, node: Node) -> Option<String> {
+        let field_type = node.child_by_field_name("type")?;
+        let mut cursor = node.walk();
+        let start = node.children(&mut cursor)
+            .find(|c| !matches!(c.kind(), "field_identifier" | ","))
+            .map_or(field_type.start_byte(), |c| c.start_byte());
+        source.get(start..field_type.end_byte()).map(str::to_string)
+    }
+    
+    /// The type of a `const (...)` block enumerating its values with `iota`, as in
+    /// `const ( Red Color = iota; Green )`
+    fn iota_type(&self, source: &str, node: Node) -> Option<String> {
+        let mut cursor = node.walk();
+        let first = node.named_children(&mut cursor).find(|c| c.kind() == "const_spec")?;
+        let value = first.child_by_field_name("value")?.utf8_text(source.as_bytes()).ok()?;
+        if !value.split(|c: char| !c.is_alphanumeric() && c != '_').any(|word| word == "iota") {
+            return None;
+        }
+        first.child_by_field_name("type")?.utf8_text(source.as_bytes()).ok().map(str::to_string)
+    }
+    
+    /// The constants of an `iota` block; those without a value repeat the previous expression
+    fn iota_variants(&self, source: &str, node: Node) -> Vec<EnumVariant> {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let mut cursor = node.walk();
+        let specs: Vec<Node> = node.named_children(&mut cursor).filter(|c| c.kind() == "const_spec").collect();
+        let mut variants = Vec::new();
+        for spec in specs {
+            let value = spec.child_by_field_name("value").map(text);
+            let mut names = spec.walk();
+            for name in spec.children_by_field_name("name", &mut names).filter(|n| n.kind() == "identifier") {
+                variants.push(EnumVariant { name: text(name), value: value.clone(), fields: Vec::new() });
+            }
+        }
+        variants
+    }
+    
+    /// Tag goroutines, deferred calls, select cases and channel operations
+    fn annotate_concurrency(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        let metadata = &mut uir_node.metadata;
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        
+        match node.kind() {
+            "go_statement" => {
+                metadata.semantic_tags.push("concurrent".to_string());
+                metadata.annotations.insert("spawn".to_string(), Value::String("goroutine".to_string()));
+            }
+            "defer_statement" => {
+                metadata.semantic_tags.push("deferred".to_string());
+            }
+            "select_statement" => {
+                metadata.semantic_tags.push("channel_select".to_string());
+                let mut cursor = node.walk();
+                let has_default = node.named_children(&mut cursor).any(|c| c.kind() == "default_case");
+                // A default case makes the select non-blocking
+                metadata.annotations.insert("blocking".to_string(), Value::Bool(!has_default));
+            }
+            "communication_case" => {
+                let operation = node.child_by_field_name("communication")
+                    .map(|c| if c.kind() == "send_statement" { "send" } else { "receive" })
+                    .unwrap_or("receive");
+                metadata.semantic_tags.push("select_case".to_string());
+                metadata.annotations.insert("channel_operation".to_string(), Value::String(operation.to_string()));
+            }
+            "send_statement" => {
+                metadata.semantic_tags.push("channel_send".to_string());
+                if let Some(channel) = node.child_by_field_name("channel") {
+                    metadata.annotations.insert("channel".to_string(), Value::String(text(channel)));
+                }
+            }
+            "receive_statement" | "unary_expression" => {
+                let receive = if node.kind() == "receive_statement" {
+                    node.child_by_field_name("right")
+                        .and_then(|r| if r.kind() == "unary_expression" { r.child_by_field_name("operand") } else { Some(r) })
+                } else if self.operator_of(node) == Some("<-") {
+                    node.child_by_field_name("operand")
+                } else {
+                    None
+                };
+                if let Some(channel) = receive {
+                    metadata.semantic_tags.push("channel_receive".to_string());
+                    metadata.annotations.insert("channel".to_string(), Value::String(text(channel)));
+                }
+            }
+            "channel_type" => {
+                metadata.annotations.insert("channel".to_string(), self.channel_info(source, node, None));
+            }
+            "var_spec" | "short_var_declaration" | "parameter_declaration" => {
+                // Declared channel type, or the type of a `make(chan T, n)` initializer
+                let declared = node.child_by_field_name("type").filter(|t| t.kind() == "channel_type");
+                let channel = declared.map(|t| self.channel_info(source, t, None)).or_else(|| {
+                    let values = node.child_by_field_name("value").or_else(|| node.child_by_field_name("right"))?;
+                    let call = values.named_child(0).filter(|c| c.kind() == "call_expression")?;
+                    let function = call.child_by_field_name("function")?;
+                    if text(function) != "make" {
+                        return None;
+                    }
+                    let arguments = call.child_by_field_name("arguments")?;
+                    let channel_type = arguments.named_child(0).filter(|a| a.kind() == "channel_type")?;
+                    Some(self.channel_info(source, channel_type, arguments.named_child(1)))
+                });
+                if let Some(channel) = channel {
+                    metadata.semantic_tags.push("channel".to_string());
+                    metadata.annotations.insert("channel".to_string(), channel);
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// Element type, direction and (for `make`) buffer size of a channel
+    fn channel_info(&self, source: &str, channel_type: Node, capacity: Option<Node>) -> Value {
+        let element = channel_type.child_by_field_name("value")
+            .and_then(|v| v.utf8_text(source.as_bytes()).ok())
+            .unwrap_or("");
+        let tokens: Vec<&str> = {
+            let mut cursor = channel_type.walk();
+            let tokens = channel_type.children(&mut cursor).filter(|c| !c.is_named()).map(|c| c.kind()).collect();
+            tokens
+        };
+        let direction = match tokens.as_slice() {
+            ["<-", "chan", ..] => "receive",
+            ["chan", "<-", ..] => "send",
+            _ => "bidirectional",
+        };
+        
+        let mut info = serde_json::Map::new();
+        info.insert("element_type".to_string(), Value::String(element.to_string()));
+        info.insert("direction".to_string(), Value::String(direction.to_string()));
+        if let Some(capacity) = capacity {
+            info.insert("buffer".to_string(), Value::String(capacity.utf8_text(source.as_bytes()).unwrap_or("").to_string()));
+        }
+        Value::Object(info)
+    }
+    
+    fn operator_of<'a>(&self, node: Node<'a>) -> Option<&'a str> {
+        node.child_by_field_name("operator").map(|op| op.kind())
+    }
+    
+    /// Name of the function launched by `go f(x)` / `defer f(x)`
+    fn extract_callee_name(&self, source: &str, node: Node) -> Option<String> {
+        let call = node.named_child(0).filter(|c| c.kind() == "call_expression")?;
+        let function = call.child_by_field_name("function")?;
+        if function.kind() == "func_literal" {
+            return Some("anonymous_function".to_string());
+        }
+        function.utf8_text(source.as_bytes()).ok().map(str::to_string)
+    }
+    
     fn extract_function_name(&self, source: &str, node: Node) -> Option<String> {
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
@@ -223,12 +547,12 @@ impl GoParser {
         None
     }
     
-    fn extract_struct_name(&self, source: &str, node: Node) -> Option<String> {
+    fn extract_struct_name(&self, _source: &str, _node: Node) -> Option<String> {
         // For anonymous structs
         Some("anonymous_struct".to_string())
     }
     
-    fn extract_interface_name(&self, source: &str, node: Node) -> Option<String> {
+    fn extract_interface_name(&self, _source: &str, _node: Node) -> Option<String> {
         // For anonymous interfaces
         Some("anonymous_interface".to_string())
     }
@@ -245,21 +569,34 @@ impl GoParser {
         Some("main_package".to_string())
     }
     
-    fn extract_import_name(&self, source: &str, node: Node) -> Option<String> {
-        let mut cursor = node.walk();
-        for child in node.children(&mut cursor) {
-            if child.kind() == "import_spec" {
-                if let Ok(name) = child.utf8_text(source.as_bytes()) {
-                    return Some(format!("import_{}", name.replace("\"", "").replace("/", "_")));
+    /// The specs of `import "fmt"` or of a parenthesized `import (...)` list
+    fn import_specs<'a>(&self, declaration: Node<'a>) -> Vec<Node<'a>> {
+        let mut cursor = declaration.walk();
+        let mut specs = Vec::new();
+        for child in declaration.named_children(&mut cursor) {
+            match child.kind() {
+                "import_spec" => specs.push(child),
+                "import_spec_list" => {
+                    let mut list_cursor = child.walk();
+                    specs.extend(child.named_children(&mut list_cursor).filter(|c| c.kind() == "import_spec"));
                 }
+                _ => {}
             }
         }
-        Some("unknown_import".to_string())
+        specs
+    }
+    
+    /// `"fmt"`, `f "fmt"`, `_ "embed"` (imported for its side effects) or `. "fmt"`, which
+    /// brings the package's exported names into scope
+    fn import_spec(&self, source: &str, node: Node) -> (NodeType, Option<String>) {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let path = node.child_by_field_name("path").map(text).unwrap_or_default().trim_matches(['"', '`']).to_string();
+        let import = match node.child_by_field_name("name").map(text) {
+            Some(name) if name == "." => NodeType::Import { path: path.clone(), alias: None, symbols: vec![ImportedSymbol::new("*")] },
+            alias => NodeType::Import { path: path.clone(), alias, symbols: Vec::new() },
+        };
+        (import, Some(path))
     }
-}
-
-extern "C" {
-    fn tree_sitter_go() -> Language;
 }
 
 #[cfg(test)]
@@ -268,7 +605,7 @@ mod tests {
     
     #[test]
     fn test_simple_go_function() {
-        let mut parser = GoParser::new().unwrap();
+        let parser = GoParser::new().unwrap();
         let source = "func add(a, b int) int { return a + b }";
         
         let result = parser.parse(source);
@@ -281,7 +618,7 @@ mod tests {
     
     #[test]
     fn test_go_struct() {
-        let mut parser = GoParser::new().unwrap();
+        let parser = GoParser::new().unwrap();
         let source = r#"
 type Point struct {
     X, Y float64
@@ -291,27 +628,27 @@ func (p Point) Distance() float64 {
     return math.Sqrt(p.X*p.X + p.Y*p.Y)
 }
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
     
     #[test]
     fn test_go_interface() {
-        let mut parser = GoParser::new().unwrap();
+        let parser = GoParser::new().unwrap();
         let source = r#"
 type Writer interface {
     Write([]byte) (int, error)
 }
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
     
     #[test]
     fn test_go_package() {
-        let mut parser = GoParser::new().unwrap();
+        let parser = GoParser::new().unwrap();
         let source = r#"
 package main
 
@@ -321,8 +658,124 @@ func main() {
     fmt.Println("Hello, World!")
 }
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
+    
+    #[test]
+    fn test_go_concurrency() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"
+package main
+
+func worker(jobs <-chan int, results chan<- int) {
+    defer close(results)
+    for j := range jobs {
+        results <- j * 2
+    }
+}
+
+func main() {
+    jobs := make(chan int, 10)
+    go worker(jobs, nil)
+    select {
+    case v := <-jobs:
+        println(v)
+    default:
+    }
+}
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        fn find<'a>(node: &'a UIRNode, tag: &str) -> Vec<&'a UIRNode> {
+            let mut found: Vec<&UIRNode> = node.children.iter().flat_map(|c| find(c, tag)).collect();
+            if node.metadata.semantic_tags.iter().any(|t| t == tag) {
+                found.insert(0, node);
+            }
+            found
+        }
+        
+        let goroutine = find(&uir, "concurrent")[0];
+        assert_eq!(goroutine.node_type, NodeType::ControlFlow(coalesce_core::ControlFlowType::Concurrent));
+        assert_eq!(goroutine.name.as_deref(), Some("worker"));
+        
+        let deferred = find(&uir, "deferred")[0];
+        assert_eq!(deferred.node_type, NodeType::Statement(StatementType::Defer));
+        assert_eq!(deferred.name.as_deref(), Some("close"));
+        
+        let channels = find(&uir, "channel");
+        assert_eq!(channels[0].metadata.annotations["channel"]["direction"], "receive");
+        assert_eq!(channels[1].metadata.annotations["channel"]["direction"], "send");
+        assert_eq!(channels[2].name.as_deref(), Some("jobs"));
+        assert_eq!(channels[2].metadata.annotations["channel"]["buffer"], "10");
+        
+        let select = find(&uir, "channel_select")[0];
+        assert_eq!(select.metadata.annotations["blocking"], false);
+        assert_eq!(find(&uir, "channel_send")[0].metadata.annotations["channel"], "results");
+        assert_eq!(find(&uir, "channel_receive")[0].metadata.annotations["channel"], "jobs");
+    }
+    
+    #[test]
+    fn test_go_import_specs() {
+        let parser = GoParser::new().unwrap();
+        let source = "package main\n\nimport (\n    \"fmt\"\n    str \"strings\"\n    _ \"embed\"\n)\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let imports: Vec<&NodeType> = uir.children.iter()
+            .map(|c| &c.node_type)
+            .filter(|t| matches!(t, NodeType::Import { .. }))
+            .collect();
+        assert_eq!(imports, vec![
+            &NodeType::Import { path: "fmt".to_string(), alias: None, symbols: Vec::new() },
+            &NodeType::Import { path: "strings".to_string(), alias: Some("str".to_string()), symbols: Vec::new() },
+            &NodeType::Import { path: "embed".to_string(), alias: Some("_".to_string()), symbols: Vec::new() },
+        ]);
+    }
+    
+    #[test]
+    fn test_go_struct_fields_and_iota() {
+        let parser = GoParser::new().unwrap();
+        let source = r#"
+type Point struct {
+    X, y float64 `json:"x"`
+    *Base
+}
+
+const (
+    Red Color = iota
+    Green
+)
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        fn find<'a>(node: &'a UIRNode, tag: &str) -> Option<&'a UIRNode> {
+            if node.metadata.semantic_tags.iter().any(|t| t == tag) {
+                return Some(node);
+            }
+            node.children.iter().find_map(|child| find(child, tag))
+        }
+        let point = find(&uir, "struct_type").unwrap();
+        let fields: Vec<_> = point.children.iter()
+            .filter_map(|c| match &c.node_type {
+                NodeType::Field { field_type, .. } => Some((c.name.as_deref(), field_type.as_deref(), c.visibility)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fields, vec![
+            (Some("X"), Some("float64"), Some(Visibility::Public)),
+            (Some("y"), Some("float64"), Some(Visibility::Internal)),
+            (Some("Base"), Some("*Base"), Some(Visibility::Public)),
+        ]);
+        let x = point.children.iter().find(|c| c.name.as_deref() == Some("X")).unwrap();
+        assert_eq!(x.metadata.annotations["tag"], "json:\"x\"");
+        
+        let colors = find(&uir, "const_declaration").unwrap();
+        assert_eq!(colors.name.as_deref(), Some("Color"));
+        let NodeType::Enum { variants } = &colors.node_type else {
+            panic!("expected an enum, got {:?}", colors.node_type);
+        };
+        let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["Red", "Green"]);
+    }
 }
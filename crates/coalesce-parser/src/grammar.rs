@@ -0,0 +1,343 @@
+// Tree-sitter grammars loaded at runtime
+//
+// Languages without a built-in parser can be registered under `grammars` in the project
+// config and loaded from a compiled grammar library, without rebuilding coalesce-parser.
+// Nodes are mapped to UIR from their kind names, so results are coarser than a dedicated
+// parser but keep structure, names and source locations.
+use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, StatementType, Result, CoalesceError,
+                   Parser as CoalesceParser, IncrementalParser, IncrementalUpdate, TextEdit, ProjectConfig};
+use crate::recovery;
+use crate::operators::{annotate_operator, unwrap_groups};
+use crate::literals::annotate_literal;
+use crate::incremental::SyntaxTree;
+use libloading::Library;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tree_sitter::{Language, Node};
+
+pub use coalesce_core::GrammarConfig;
+
+#[derive(Clone)]
+struct ExternalGrammar {
+    language: Language,
+    extensions: Vec<String>,
+    // Keeps the grammar's code mapped for as long as its Language is in use
+    library: Option<Arc<Library>>,
+}
+
+/// Grammars registered at runtime, by name
+#[derive(Default)]
+pub struct GrammarRegistry {
+    grammars: HashMap<String, ExternalGrammar>,
+}
+
+impl GrammarRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Load every grammar listed in a project config file
+    ///
+    /// Relative grammar paths are resolved against the directory holding the config.
+    pub fn from_config_file(config_path: impl AsRef<Path>) -> Result<Self> {
+        let config_path = config_path.as_ref();
+        let config = ProjectConfig::from_file(config_path)?;
+        Self::from_config(&config, config_path.parent().unwrap_or(Path::new(".")))
+    }
+    
+    /// Load every grammar listed in a project config, resolving relative paths against `base`
+    pub fn from_config(config: &ProjectConfig, base: &Path) -> Result<Self> {
+        let mut registry = Self::new();
+        for grammar in &config.grammars {
+            let mut grammar = grammar.clone();
+            if grammar.path.is_relative() {
+                grammar.path = base.join(&grammar.path);
+            }
+            registry.load(&grammar)?;
+        }
+        Ok(registry)
+    }
+    
+    /// Load a compiled grammar and register it under its configured name
+    pub fn load(&mut self, config: &GrammarConfig) -> Result<()> {
+        let load_error = |reason: String| CoalesceError::ParseError {
+            message: format!("Failed to load grammar '{}' from {}: {}", config.name, config.path.display(), reason),
+            line: 0,
+            column: 0,
+        };
+        
+        if config.path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wasm")) {
+            return Err(load_error("only shared-library grammars are supported; build one with `tree-sitter build`".to_string()));
+        }
+        
+        let symbol = config.symbol.clone()
+            .unwrap_or_else(|| format!("tree_sitter_{}", config.name.replace('-', "_")));
+        
+        // SAFETY: loading runs the library's initializers; grammar libraries generated by the
+        // tree-sitter CLI have none beyond the C runtime's.
+        let library = unsafe { Library::new(&config.path) }.map_err(|e| load_error(e.to_string()))?;
+        // SAFETY: tree-sitter grammars export `const TSLanguage *tree_sitter_<name>(void)`, which
+        // is what tree_sitter::Language wraps.
+        let language = unsafe {
+            let constructor = library.get::<unsafe extern "C" fn() -> Language>(symbol.as_bytes())
+                .map_err(|e| load_error(e.to_string()))?;
+            constructor()
+        };
+        
+        // Reject grammars generated for an incompatible tree-sitter ABI up front
+        tree_sitter::Parser::new().set_language(language).map_err(|e| load_error(e.to_string()))?;
+        
+        self.grammars.insert(config.name.clone(), ExternalGrammar {
+            language,
+            extensions: config.extensions.clone(),
+            library: Some(Arc::new(library)),
+        });
+        Ok(())
+    }
+    
+    /// Register a grammar that is already linked into the process
+    pub fn register(&mut self, name: &str, language: Language, extensions: &[&str]) {
+        self.grammars.insert(name.to_string(), ExternalGrammar {
+            language,
+            extensions: extensions.iter().map(|ext| ext.to_string()).collect(),
+            library: None,
+        });
+    }
+    
+    pub fn contains(&self, name: &str) -> bool {
+        self.grammars.contains_key(name)
+    }
+    
+    /// The registered language whose extensions match `filename`, if any
+    pub fn language_for_file(&self, filename: &str) -> Option<CoalesceLanguage> {
+        let extension = Path::new(filename).extension()?.to_str()?;
+        self.grammars.iter()
+            .find(|(_, grammar)| grammar.extensions.iter().any(|ext| ext.eq_ignore_ascii_case(extension)))
+            .map(|(name, _)| CoalesceLanguage::Custom(name.clone()))
+    }
+    
+    pub fn create_parser(&self, name: &str) -> Result<ExternalParser> {
+        let grammar = self.grammars.get(name)
+            .ok_or_else(|| CoalesceError::UnsupportedLanguage(CoalesceLanguage::Custom(name.to_string())))?;
+        
+        Ok(ExternalParser {
+            name: name.to_string(),
+            language: grammar.language,
+            _library: grammar.library.clone(),
+        })
+    }
+}
+
+/// Parser for a runtime-loaded grammar
+pub struct ExternalParser {
+    name: String,
+    language: Language,
+    _library: Option<Arc<Library>>,
+}
+
+impl CoalesceParser for ExternalParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::Custom(self.name.clone())
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        let tree = SyntaxTree::parse(self.language, source)?;
+        self.convert_root(source, tree.root_node())
+    }
+}
+
+impl IncrementalParser for ExternalParser {
+    type Tree = SyntaxTree;
+    
+    fn parse_tree(&self, source: &str) -> Result<(SyntaxTree, UIRNode)> {
+        let tree = SyntaxTree::parse(self.language, source)?;
+        let uir = self.convert_root(source, tree.root_node())?;
+        Ok((tree, uir))
+    }
+    
+    fn reparse(&self, previous: &SyntaxTree, edits: &[TextEdit]) -> Result<IncrementalUpdate<SyntaxTree>> {
+        previous.reparse(edits, |source, node| self.convert_to_uir(source, node))
+    }
+    
+    fn to_uir(&self, tree: &SyntaxTree) -> Result<UIRNode> {
+        self.convert_root(tree.source(), tree.root_node())
+    }
+}
+
+impl ExternalParser {
+    fn convert_root(&self, source: &str, root_node: Node) -> Result<UIRNode> {
+        let mut uir = self.convert_to_uir(source, root_node)?;
+        recovery::summarize(source, root_node, &mut uir);
+        unwrap_groups(&mut uir);
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
+    }
+    
+    fn convert_to_uir(&self, source: &str, node: Node) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, self.language(), |child| self.convert_to_uir(source, child));
+        }
+        
+        let start_position = node.start_position();
+        let end_position = node.end_position();
+        let original_text = node.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        
+        let name = node.child_by_field_name("name")
+            .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+            .map(|n| n.to_string())
+            .or_else(|| (node.kind() == "identifier").then(|| original_text.clone()));
+        
+        let mut annotations = HashMap::new();
+        annotations.insert("original_text".to_string(), Value::String(original_text));
+        
+        let mut uir_node = UIRNode {
+            id: format!("{}_{}_{}", node.kind(), start_position.row, start_position.column),
+            node_type: self.map_node_type(node),
+            name,
+            children: Vec::new(),
+            metadata: Metadata {
+                source_language: self.language(),
+                semantic_tags: vec![node.kind().to_string()],
+                annotations,
+                ..Metadata::default()
+            },
+            source_location: Some(SourceLocation {
+                file: String::new(),
+                start_line: start_position.row as u32 + 1,
+                end_line: end_position.row as u32 + 1,
+                start_column: start_position.column as u32,
+                end_column: end_position.column as u32,
+            }),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &self.language(), &mut uir_node);
+        annotate_literal(node, source, &self.language(), &mut uir_node);
+        
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if !child.is_extra() {
+                uir_node.children.push(self.convert_to_uir(source, child)?);
+            }
+        }
+        
+        Ok(uir_node)
+    }
+    
+    /// Guess a UIR node type from the words in a grammar's node kind, e.g. `if_statement`
+    fn map_node_type(&self, node: Node) -> NodeType {
+        if node.parent().is_none() {
+            return NodeType::Module;
+        }
+        
+        let kind = node.kind();
+        let words: Vec<&str> = kind.split('_').collect();
+        let has = |candidates: &[&str]| words.iter().any(|w| candidates.contains(w));
+        
+        if has(&["call", "invocation", "application"]) {
+            NodeType::Expression(ExpressionType::FunctionCall)
+        } else if has(&["function", "method", "procedure", "subroutine", "lambda", "closure"]) {
+            NodeType::Function
+        } else if has(&["class", "struct", "record", "object"]) {
+            NodeType::Class
+        } else if has(&["interface", "trait", "protocol"]) {
+            NodeType::Interface
+        } else if has(&["if", "elif", "else", "unless", "conditional", "ternary"]) {
+            NodeType::ControlFlow(ControlFlowType::Conditional)
+        } else if has(&["foreach"]) || (has(&["for"]) && has(&["in", "of", "each"])) {
+            NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach))
+        } else if has(&["for"]) {
+            NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For))
+        } else if has(&["while", "loop", "until", "repeat"]) {
+            NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While))
+        } else if has(&["switch", "match", "case", "select"]) && !has(&["clause", "arm", "pattern"]) {
+            NodeType::ControlFlow(ControlFlowType::Switch)
+        } else if has(&["try", "rescue"]) {
+            NodeType::ControlFlow(ControlFlowType::Try)
+        } else if has(&["goto"]) {
+            NodeType::ControlFlow(ControlFlowType::Goto)
+        } else if has(&["return"]) {
+            NodeType::Statement(StatementType::Return)
+        } else if has(&["break"]) {
+            NodeType::Statement(StatementType::Break)
+        } else if has(&["continue", "next"]) {
+            NodeType::Statement(StatementType::Continue)
+        } else if has(&["throw", "raise"]) {
+            NodeType::Statement(StatementType::Throw)
+        } else if has(&["const", "constant"]) {
+            NodeType::Constant
+        } else if has(&["variable", "var", "let"]) && has(&["declaration", "definition", "statement"]) {
+            NodeType::Variable
+        } else if has(&["assignment"]) {
+            NodeType::Expression(ExpressionType::Assignment)
+        } else if has(&["comparison"]) {
+            NodeType::Expression(ExpressionType::Comparison)
+        } else if has(&["boolean", "logical", "not"]) && has(&["operator", "expression"]) {
+            NodeType::Expression(ExpressionType::Logical)
+        } else if has(&["binary", "unary", "arithmetic"]) {
+            NodeType::Expression(ExpressionType::Arithmetic)
+        } else if has(&["identifier", "name", "variable"]) {
+            NodeType::Expression(ExpressionType::Variable)
+        } else if has(&["statement"]) {
+            NodeType::Statement(StatementType::Expression)
+        } else {
+            NodeType::Expression(ExpressionType::Literal)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::Operator;
+    
+    #[test]
+    fn test_registered_grammar_maps_node_kinds() {
+        let mut registry = GrammarRegistry::new();
+        registry.register("snake", tree_sitter_python::language(), &["snk"]);
+        assert_eq!(registry.language_for_file("lib/util.snk"), Some(CoalesceLanguage::Custom("snake".to_string())));
+        
+        let parser = registry.create_parser("snake").unwrap();
+        let uir = parser.parse("def add(a, b):\n    return a + b\n").unwrap();
+        assert_eq!(uir.node_type, NodeType::Module);
+        assert_eq!(uir.metadata.source_language, CoalesceLanguage::Custom("snake".to_string()));
+        
+        let function = &uir.children[0];
+        assert_eq!(function.node_type, NodeType::Function);
+        assert_eq!(function.name.as_deref(), Some("add"));
+        let body = function.children.last().unwrap();
+        assert_eq!(body.children[0].node_type, NodeType::Statement(StatementType::Return));
+        assert_eq!(body.children[0].children[0].node_type, NodeType::Expression(ExpressionType::Arithmetic));
+        assert_eq!(body.children[0].children[0].operator, Some(Operator::Add));
+    }
+    
+    #[test]
+    fn test_config_loading_errors() {
+        let dir = std::env::temp_dir().join(format!("coalesce-grammar-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        
+        let config = dir.join("config.json");
+        std::fs::write(&config, r#"{"version": "0.1.0", "grammars": [{"name": "zig", "path": "zig.wasm"}]}"#).unwrap();
+        let error = GrammarRegistry::from_config_file(&config).err().unwrap();
+        assert!(error.to_string().contains("shared-library"));
+        
+        std::fs::write(&config, r#"{"grammars": [{"name": "zig", "path": "missing/libtree-sitter-zig.so"}]}"#).unwrap();
+        assert!(GrammarRegistry::from_config_file(&config).is_err());
+        
+        std::fs::write(&config, r#"{"version": "0.1.0"}"#).unwrap();
+        let registry = GrammarRegistry::from_config_file(&config).unwrap();
+        assert!(registry.create_parser("zig").is_err());
+        
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
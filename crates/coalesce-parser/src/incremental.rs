@@ -0,0 +1,210 @@
+// Incremental reparsing on top of tree-sitter
+//
+// Edits are applied to the previous tree so tree-sitter can reuse unchanged subtrees, and
+// only the declarations or statements enclosing changed ranges are converted to UIR again.
+use coalesce_core::{UIRNode, TextEdit, IncrementalUpdate, Result, CoalesceError};
+use tree_sitter::{InputEdit, Language, Node, Parser, Point, Tree};
+use std::ops::Range;
+
+/// Node kinds that bound a changed subtree: converting anything smaller could leave a stale
+/// name or signature on the enclosing UIR node
+const BOUNDARY_SUFFIXES: &[&str] = &["_statement", "_declaration", "_definition", "_item"];
+
+/// A tree-sitter tree together with the source it was parsed from
+#[derive(Clone)]
+pub struct SyntaxTree {
+    tree: Tree,
+    source: String,
+    language: Language,
+}
+
+impl SyntaxTree {
+    pub(crate) fn parse(language: Language, source: &str) -> Result<Self> {
+        let tree = parse_with(language, source, None)?;
+        Ok(Self { tree, source: source.to_string(), language })
+    }
+    
+    /// Source text the tree currently reflects
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    
+    pub(crate) fn root_node(&self) -> Node<'_> {
+        self.tree.root_node()
+    }
+    
+    /// Apply `edits` and convert each changed subtree with `convert`
+    pub(crate) fn reparse(
+        &self,
+        edits: &[TextEdit],
+        convert: impl Fn(&str, Node) -> Result<UIRNode>,
+    ) -> Result<IncrementalUpdate<SyntaxTree>> {
+        let mut old_tree = self.tree.clone();
+        let mut source = self.source.clone();
+        let mut edited: Vec<Range<usize>> = Vec::new();
+        
+        for edit in edits {
+            let valid = edit.start_byte <= edit.old_end_byte
+                && edit.old_end_byte <= source.len()
+                && source.is_char_boundary(edit.start_byte)
+                && source.is_char_boundary(edit.old_end_byte);
+            if !valid {
+                return Err(CoalesceError::ParseError {
+                    message: format!("Edit {}..{} is outside the source", edit.start_byte, edit.old_end_byte),
+                    line: 0,
+                    column: 0,
+                });
+            }
+            
+            let start_position = point_at(&source, edit.start_byte);
+            let old_end_position = point_at(&source, edit.old_end_byte);
+            source.replace_range(edit.start_byte..edit.old_end_byte, &edit.new_text);
+            let new_end_byte = edit.start_byte + edit.new_text.len();
+            
+            old_tree.edit(&InputEdit {
+                start_byte: edit.start_byte,
+                old_end_byte: edit.old_end_byte,
+                new_end_byte,
+                start_position,
+                old_end_position,
+                new_end_position: point_at(&source, new_end_byte),
+            });
+            
+            // Earlier edits are in pre-edit offsets; move them past this one
+            let shift = |offset: usize| match offset {
+                o if o >= edit.old_end_byte => o - edit.old_end_byte + new_end_byte,
+                o if o > edit.start_byte => new_end_byte,
+                o => o,
+            };
+            for range in &mut edited {
+                *range = shift(range.start)..shift(range.end);
+            }
+            edited.push(edit.start_byte..new_end_byte);
+        }
+        
+        let tree = parse_with(self.language, &source, Some(&old_tree))?;
+        
+        // Token-level edits such as renames don't change structure, so changed_ranges alone
+        // would miss them
+        let mut ranges: Vec<Range<usize>> = old_tree.changed_ranges(&tree)
+            .map(|range| range.start_byte..range.end_byte)
+            .collect();
+        ranges.extend(edited);
+        
+        let root = tree.root_node();
+        let mut nodes: Vec<Node> = ranges.iter()
+            .map(|range| enclosing_subtree(root, range))
+            .collect();
+        nodes.sort_by_key(|n| (n.start_byte(), std::cmp::Reverse(n.end_byte())));
+        
+        let mut changed_nodes: Vec<Node> = Vec::new();
+        for node in nodes {
+            let covered = changed_nodes.last()
+                .is_some_and(|last| last.start_byte() <= node.start_byte() && node.end_byte() <= last.end_byte());
+            if !covered {
+                changed_nodes.push(node);
+            }
+        }
+        
+        let changed = changed_nodes.into_iter()
+            .map(|node| convert(&source, node))
+            .collect::<Result<Vec<_>>>()?;
+        
+        Ok(IncrementalUpdate {
+            tree: SyntaxTree { tree, source, language: self.language },
+            changed,
+        })
+    }
+}
+
+fn parse_with(language: Language, source: &str, old_tree: Option<&Tree>) -> Result<Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(language)
+        .map_err(|e| CoalesceError::ParseError {
+            message: format!("Failed to set language: {}", e),
+            line: 0,
+            column: 0,
+        })?;
+    
+    parser.parse(source, old_tree)
+        .ok_or_else(|| CoalesceError::ParseError {
+            message: "Failed to parse source".to_string(),
+            line: 0,
+            column: 0,
+        })
+}
+
+/// The smallest statement or declaration containing `range`, or a direct child of the root
+fn enclosing_subtree<'a>(root: Node<'a>, range: &Range<usize>) -> Node<'a> {
+    let mut node = root.named_descendant_for_byte_range(range.start, range.end).unwrap_or(root);
+    while let Some(parent) = node.parent() {
+        let boundary = BOUNDARY_SUFFIXES.iter().any(|suffix| node.kind().ends_with(suffix));
+        if boundary || parent.parent().is_none() {
+            break;
+        }
+        node = parent;
+    }
+    node
+}
+
+fn point_at(source: &str, byte: usize) -> Point {
+    let before = &source[..byte];
+    let row = before.matches('\n').count();
+    let column = before.rfind('\n').map_or(byte, |newline| byte - newline - 1);
+    Point { row, column }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CParser, JavaScriptParser, PythonParser};
+    use coalesce_core::{IncrementalParser, NodeType, Parser, TextEdit};
+    
+    #[test]
+    fn test_reparse_returns_only_changed_function() {
+        let parser = CParser::new().unwrap();
+        let source = "int add(int a, int b) { return a + b; }\nint sub(int a, int b) { return a - b; }\n";
+        let (tree, _) = parser.parse_tree(source).unwrap();
+        
+        let start = source.find("sub").unwrap();
+        let edit = TextEdit { start_byte: start, old_end_byte: start + 3, new_text: "minus".to_string() };
+        let update = parser.reparse(&tree, &[edit]).unwrap();
+        
+        assert_eq!(update.tree.source(), source.replace("sub", "minus"));
+        assert_eq!(update.changed.len(), 1);
+        assert_eq!(update.changed[0].node_type, NodeType::Function);
+        assert_eq!(update.changed[0].name.as_deref(), Some("minus"));
+    }
+    
+    #[test]
+    fn test_reparse_applies_edits_in_sequence() {
+        let parser = PythonParser::new().unwrap();
+        let source = "def f():\n    return 1\n\ndef g():\n    return 2\n";
+        let (tree, _) = parser.parse_tree(source).unwrap();
+        
+        // The second edit's offsets refer to the text after the first
+        let first = TextEdit { start_byte: 20, old_end_byte: 21, new_text: "10".to_string() };
+        let second = TextEdit { start_byte: 44, old_end_byte: 45, new_text: "20".to_string() };
+        let update = parser.reparse(&tree, &[first, second]).unwrap();
+        
+        assert_eq!(update.tree.source(), "def f():\n    return 10\n\ndef g():\n    return 20\n");
+        assert_eq!(update.changed.len(), 2);
+        assert!(update.changed.iter().all(|c| c.node_type == NodeType::Statement(coalesce_core::StatementType::Return)));
+        
+        let bad = TextEdit { start_byte: 500, old_end_byte: 501, new_text: String::new() };
+        assert!(parser.reparse(&update.tree, &[bad]).is_err());
+    }
+    
+    #[test]
+    fn test_reparsed_tree_converts_like_a_fresh_parse() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "function add(a, b) { return a + b; }\nfunction sub(a, b) { return a - b; }\n";
+        let (tree, _) = parser.parse_tree(source).unwrap();
+        
+        let start = source.find("a - b").unwrap();
+        let edit = TextEdit { start_byte: start, old_end_byte: start + 5, new_text: "a - b - 1".to_string() };
+        let update = parser.reparse(&tree, &[edit]).unwrap();
+        
+        let fresh = parser.parse(update.tree.source()).unwrap();
+        assert!(parser.to_uir(&update.tree).unwrap().diff(&fresh).is_empty());
+    }
+}
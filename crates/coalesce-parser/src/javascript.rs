@@ -1,5 +1,11 @@
-use coalesce_core::{types::*, errors::*, traits::Parser};
+use coalesce_core::{types::*, errors::*, traits::{Parser, IncrementalParser, IncrementalUpdate}};
 use tree_sitter::{Parser as TSParser, Node};
+use crate::recovery;
+use crate::closures;
+use crate::comments;
+use crate::operators::{annotate_operator, unwrap_groups};
+use crate::literals::annotate_literal;
+use crate::incremental::SyntaxTree;
 
 /// JavaScript parser using tree-sitter
 pub struct JavaScriptParser {
@@ -17,6 +23,24 @@ impl Parser for JavaScriptParser {
     }
 }
 
+impl IncrementalParser for JavaScriptParser {
+    type Tree = SyntaxTree;
+    
+    fn parse_tree(&self, source: &str) -> Result<(SyntaxTree, UIRNode)> {
+        let tree = SyntaxTree::parse(tree_sitter_javascript::language(), source)?;
+        let uir = self.convert_root(source, tree.root_node())?;
+        Ok((tree, uir))
+    }
+    
+    fn reparse(&self, previous: &SyntaxTree, edits: &[TextEdit]) -> Result<IncrementalUpdate<SyntaxTree>> {
+        previous.reparse(edits, |source, node| self.ast_to_uir(node, source))
+    }
+    
+    fn to_uir(&self, tree: &SyntaxTree) -> Result<UIRNode> {
+        self.convert_root(tree.source(), tree.root_node())
+    }
+}
+
 impl Clone for JavaScriptParser {
     fn clone(&self) -> Self {
         JavaScriptParser::new().unwrap()
@@ -41,11 +65,8 @@ impl JavaScriptParser {
         
         match tree {
             Some(tree) => {
-                if tree.root_node().has_error() {
-                    self.handle_parse_error(source, tree.root_node())
-                } else {
-                    self.ast_to_uir(tree.root_node(), source)
-                }
+                // Syntax errors become diagnostic nodes; the rest of the file still converts
+                self.convert_root(source, tree.root_node())
             }
             None => Err(CoalesceError::ParseError {
                 message: "Failed to parse source code".to_string(),
@@ -55,20 +76,49 @@ impl JavaScriptParser {
         }
     }
     
+    fn convert_root(&self, source: &str, root: Node) -> Result<UIRNode> {
+        let mut uir = self.ast_to_uir(root, source)?;
+        recovery::summarize(source, root, &mut uir);
+        unwrap_groups(&mut uir);
+        closures::annotate_captures(&mut uir);
+        uir.make_ids_unique();
+        comments::attach_comments(&mut uir);
+        uir.debug_validate();
+        Ok(uir)
+    }
+    
     fn ast_to_uir(&self, node: Node, source: &str) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, Language::JavaScript, |child| self.ast_to_uir(child, source));
+        }
+        
         match node.kind() {
             "program" => self.convert_program(node, source),
-            "function_declaration" => self.convert_function_declaration(node, source),
+            "function_declaration" | "generator_function_declaration" | "function" | "generator_function" => {
+                self.convert_function_declaration(node, source)
+            }
             "arrow_function" => self.convert_arrow_function(node, source),
             "class_declaration" => self.convert_class_declaration(node, source),
             "method_definition" => self.convert_method(node, source),
-            "variable_declaration" => self.convert_variable_declaration(node, source),
+            "field_definition" => self.convert_field(node, source),
+            "variable_declaration" | "lexical_declaration" => self.convert_variable_declaration(node, source),
+            "import_statement" => self.convert_import(node, source),
             "return_statement" => self.convert_return_statement(node, source),
             "if_statement" => self.convert_if_statement(node, source),
+            "while_statement" => self.convert_while_statement(node, source),
+            "do_statement" => self.convert_do_statement(node, source),
+            "for_statement" => self.convert_for_statement(node, source),
+            "for_in_statement" => self.convert_for_in_statement(node, source),
+            "update_expression" => self.convert_update_expression(node, source),
             "call_expression" => self.convert_call_expression(node, source),
-            "binary_expression" => self.convert_binary_expression(node, source),
+            "binary_expression" | "unary_expression" => self.convert_operator_expression(node, source),
+            "await_expression" => self.convert_await_expression(node, source),
+            "yield_expression" => self.convert_yield_expression(node, source),
+            "template_string" => self.convert_template_string(node, source),
+            "try_statement" => self.convert_try_statement(node, source),
+            "object_pattern" | "array_pattern" => self.convert_destructuring_pattern(node, source),
             "identifier" => self.convert_identifier(node, source),
-            "number" | "string" | "true" | "false" => self.convert_literal(node, source),
+            "number" | "string" | "true" | "false" | "null" => self.convert_literal(node, source),
             _ => self.convert_generic(node, source),
         }
     }
@@ -80,7 +130,9 @@ impl JavaScriptParser {
         if cursor.goto_first_child() {
             loop {
                 let child = cursor.node();
-                if !child.is_extra() {
+                if comments::is_comment(child) {
+                    children.push(comments::comment_node(child, source, &Language::JavaScript));
+                } else if !child.is_extra() {
                     if let Ok(child_uir) = self.ast_to_uir(child, source) {
                         children.push(child_uir);
                     }
@@ -99,18 +151,30 @@ impl JavaScriptParser {
             children,
             metadata: self.create_metadata(node, source),
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         })
     }
     
     fn convert_function_declaration(&self, node: Node, source: &str) -> Result<UIRNode> {
-        let name_node = self.find_child_by_kind(node, "identifier")
-            .ok_or_else(|| CoalesceError::ParseError {
-                message: "Function missing name".to_string(),
-                line: node.start_position().row as u32 + 1,
-                column: node.start_position().column as u32,
-            })?;
-        
-        let function_name = self.node_text(name_node, source);
+        // Function expressions may be anonymous; declarations must be named
+        let function_name = match self.find_child_by_kind(node, "identifier") {
+            Some(name_node) => self.node_text(name_node, source),
+            None if node.kind().ends_with("_declaration") => {
+                return Err(CoalesceError::ParseError {
+                    message: "Function missing name".to_string(),
+                    line: node.start_position().row as u32 + 1,
+                    column: node.start_position().column as u32,
+                });
+            }
+            None => "anonymous_function",
+        };
         
         // Get parameters
         let mut parameters = Vec::new();
@@ -133,8 +197,16 @@ impl JavaScriptParser {
             node_type: NodeType::Function,
             name: Some(function_name.to_string()),
             children,
-            metadata: self.create_metadata(node, source),
+            metadata: self.create_function_metadata(node, source),
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: self.has_token(node, "async"),
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         })
     }
     
@@ -143,7 +215,7 @@ impl JavaScriptParser {
         
         if let Some(params_node) = self.find_child_by_kind(node, "formal_parameters") {
             parameters = self.extract_parameters(params_node, source)?;
-        } else if let Some(param_node) = self.find_child_by_kind(node, "identifier") {
+        } else if let Some(param_node) = node.child_by_field_name("parameter") {
             parameters.push(UIRNode {
                 id: self.generate_node_id(param_node, source),
                 node_type: NodeType::Variable,
@@ -151,6 +223,14 @@ impl JavaScriptParser {
                 children: vec![],
                 metadata: self.create_metadata(param_node, source),
                 source_location: self.create_source_location(param_node, ""),
+                operator: None,
+                literal: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                is_async: false,
+                attributes: Vec::new(),
+                ownership: None,
+                documentation: None,
             });
         }
         
@@ -177,11 +257,19 @@ impl JavaScriptParser {
         
         Ok(UIRNode {
             id: self.generate_node_id(node, source),
-            node_type: NodeType::Function,
+            node_type: closures::closure(CaptureMode::ByReference),
             name: Some("arrow_function".to_string()),
             children,
-            metadata: self.create_metadata(node, source),
+            metadata: self.create_function_metadata(node, source),
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: self.has_token(node, "async"),
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         })
     }
     
@@ -201,7 +289,9 @@ impl JavaScriptParser {
             if cursor.goto_first_child() {
                 loop {
                     let child = cursor.node();
-                    if !child.is_extra() {
+                    if comments::is_comment(child) {
+                        children.push(comments::comment_node(child, source, &Language::JavaScript));
+                    } else if !child.is_extra() {
                         if let Ok(child_uir) = self.ast_to_uir(child, source) {
                             children.push(child_uir);
                         }
@@ -213,14 +303,30 @@ impl JavaScriptParser {
                 }
             }
         }
+        let children = self.group_accessors(children);
+        
+        let mut metadata = self.create_metadata(node, source);
+        // `class Point extends Base`; the superclass may be any expression
+        let superclass = self.find_child_by_kind(node, "class_heritage").and_then(|heritage| heritage.named_child(0));
+        if let Some(superclass) = superclass {
+            metadata.annotations.insert("inherits".to_string(), serde_json::Value::String(self.node_text(superclass, source).to_string()));
+        }
         
         Ok(UIRNode {
             id: self.generate_node_id(node, source),
             node_type: NodeType::Class,
             name: Some(class_name.to_string()),
             children,
-            metadata: self.create_metadata(node, source),
+            metadata,
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: self.decorators(node, source),
+            ownership: None,
+            documentation: None,
         })
     }
     
@@ -248,42 +354,147 @@ impl JavaScriptParser {
         let mut children = parameters;
         children.extend(body_children);
         
+        let mut metadata = self.create_function_metadata(node, source);
+        if self.has_token(node, "get") {
+            metadata.semantic_tags.push("getter".to_string());
+        } else if self.has_token(node, "set") {
+            metadata.semantic_tags.push("setter".to_string());
+        }
+        let modifiers = if self.has_token(node, "static") { vec![Modifier::Static] } else { Vec::new() };
+        
         Ok(UIRNode {
             id: self.generate_node_id(node, source),
             node_type: NodeType::Function,
             name: Some(method_name.to_string()),
             children,
-            metadata: self.create_metadata(node, source),
+            metadata,
+            source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers,
+            is_async: self.has_token(node, "async"),
+            attributes: self.decorators(node, source),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// A class field such as `static count = 0`; `#name` fields are private, named without
+    /// the `#` and tagged `private_name`
+    fn convert_field(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let name_node = node.child_by_field_name("property")
+            .ok_or_else(|| CoalesceError::ParseError {
+                message: "Field missing name".to_string(),
+                line: node.start_position().row as u32 + 1,
+                column: node.start_position().column as u32,
+            })?;
+        let default_value = node.child_by_field_name("value").map(|value| self.node_text(value, source).to_string());
+        let private = name_node.kind() == "private_property_identifier";
+        let field_name = self.node_text(name_node, source).trim_start_matches('#');
+        let visibility = private.then_some(Visibility::Private);
+        let modifiers = if self.has_token(node, "static") { vec![Modifier::Static] } else { Vec::new() };
+        let mut metadata = self.create_metadata(node, source);
+        if private {
+            metadata.semantic_tags.push("private_name".to_string());
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Field { field_type: None, default_value },
+            name: Some(field_name.to_string()),
+            children: Vec::new(),
+            metadata,
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility,
+            modifiers,
+            is_async: false,
+            attributes: self.decorators(node, source),
+            ownership: None,
+            documentation: None,
         })
     }
     
+    /// Merge `get`/`set` methods into one property per name, holding them as accessors named
+    /// `get` and `set` where the first of them was declared
+    fn group_accessors(&self, members: Vec<UIRNode>) -> Vec<UIRNode> {
+        let mut grouped: Vec<UIRNode> = Vec::new();
+        for mut member in members {
+            let getter = member.metadata.semantic_tags.iter().any(|t| t == "getter");
+            let setter = member.metadata.semantic_tags.iter().any(|t| t == "setter");
+            if member.node_type != NodeType::Function || !(getter || setter) {
+                grouped.push(member);
+                continue;
+            }
+            let name = member.name.replace(if getter { "get" } else { "set" }.to_string());
+            let existing = grouped.iter_mut()
+                .find(|p| matches!(p.node_type, NodeType::Property { .. }) && p.name == name);
+            let property = match existing {
+                Some(property) => property,
+                None => {
+                    grouped.push(UIRNode {
+                        id: format!("{}_property", member.id),
+                        node_type: NodeType::Property { property_type: None, default_value: None, has_getter: false, has_setter: false },
+                        name,
+                        children: Vec::new(),
+                        metadata: member.metadata.clone(),
+                        source_location: member.source_location.clone(),
+                        operator: None,
+                        literal: None,
+                        visibility: None,
+                        modifiers: member.modifiers.clone(),
+                        is_async: false,
+                        attributes: Vec::new(),
+                        ownership: None,
+                        documentation: member.documentation.clone(),
+                    });
+                    grouped.last_mut().expect("just pushed")
+                }
+            };
+            if let NodeType::Property { has_getter, has_setter, .. } = &mut property.node_type {
+                *has_getter |= getter;
+                *has_setter |= setter;
+            }
+            property.metadata.semantic_tags.retain(|t| t != "getter" && t != "setter");
+            property.children.push(member);
+        }
+        grouped
+    }
+    
     fn convert_variable_declaration(&self, node: Node, source: &str) -> Result<UIRNode> {
         let mut children = Vec::new();
         
         let declarators = self.children_by_kind(node, "variable_declarator");
         for declarator in declarators {
-            if let Some(name_node) = self.find_child_by_kind(declarator, "identifier") {
-                let var_name = self.node_text(name_node, source);
-                
-                let mut value_child = None;
-                let mut cursor = declarator.walk();
-                if cursor.goto_first_child() {
-                    while cursor.goto_next_sibling() {
-                        let child = cursor.node();
-                        if child.kind() != "identifier" && child.kind() != "=" {
-                            if let Ok(init_uir) = self.ast_to_uir(child, source) {
-                                value_child = Some(Box::new(init_uir));
-                            }
-                            break;
-                        }
+            let pattern = declarator.child_by_field_name("name")
+                .filter(|name| name.kind() == "object_pattern" || name.kind() == "array_pattern");
+            if let Some(pattern) = pattern {
+                // `const { a, b: [c] } = obj` declares one variable per binding
+                let initializer = declarator.child_by_field_name("value")
+                    .map(|value| self.node_text(value, source).to_string());
+                let mut bindings = Vec::new();
+                self.expand_pattern(pattern, source, &[], &mut bindings)?;
+                for binding in bindings.iter_mut() {
+                    if let Some(initializer) = &initializer {
+                        binding.metadata.annotations.insert(
+                            "destructured_from".to_string(),
+                            serde_json::Value::String(initializer.clone())
+                        );
                     }
                 }
+                children.extend(bindings);
+                continue;
+            }
+            
+            if let Some(name_node) = self.find_child_by_kind(declarator, "identifier") {
+                let var_name = self.node_text(name_node, source);
                 
-                let var_children = if let Some(val) = value_child {
-                    vec![*val]
-                } else {
-                    vec![]
+                // The initializer may itself be an identifier, as in `let p = response`
+                let var_children = match declarator.child_by_field_name("value") {
+                    Some(value) => vec![self.ast_to_uir(value, source)?],
+                    None => Vec::new(),
                 };
                 
                 children.push(UIRNode {
@@ -293,17 +504,90 @@ impl JavaScriptParser {
                     children: var_children,
                     metadata: self.create_metadata(declarator, source),
                     source_location: self.create_source_location(declarator, ""),
+                    operator: None,
+                    literal: None,
+                    visibility: None,
+                    modifiers: Vec::new(),
+                    is_async: false,
+                    attributes: Vec::new(),
+                    ownership: None,
+                    documentation: None,
                 });
             }
         }
         
+        // `var`, `let` or `const`
+        let mut metadata = self.create_metadata(node, source);
+        if let Some(kind) = node.child(0).filter(|k| !k.is_named()) {
+            metadata.annotations.insert(
+                "declaration_kind".to_string(),
+                serde_json::Value::String(kind.kind().to_string())
+            );
+        }
+        
         Ok(UIRNode {
             id: self.generate_node_id(node, source),
             node_type: NodeType::Statement(StatementType::Expression),
             name: Some("variable_declaration".to_string()),
             children,
-            metadata: self.create_metadata(node, source),
+            metadata,
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// `import d, { a as b } from 'm'`, `import * as ns from 'm'` or a bare `import 'm'`; a
+    /// default import binds the module's `default` symbol
+    fn convert_import(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let path = node.child_by_field_name("source")
+            .map(|s| self.node_text(s, source).trim_matches(|c| c == '\'' || c == '"').to_string())
+            .unwrap_or_default();
+        
+        let mut alias = None;
+        let mut symbols = Vec::new();
+        if let Some(clause) = self.find_child_by_kind(node, "import_clause") {
+            let mut cursor = clause.walk();
+            for part in clause.named_children(&mut cursor) {
+                match part.kind() {
+                    "identifier" => symbols.push(ImportedSymbol {
+                        name: "default".to_string(),
+                        alias: Some(self.node_text(part, source).to_string()),
+                    }),
+                    "namespace_import" => {
+                        alias = self.find_child_by_kind(part, "identifier").map(|n| self.node_text(n, source).to_string());
+                    }
+                    "named_imports" => symbols.extend(self.children_by_kind(part, "import_specifier")
+                        .into_iter()
+                        .map(|specifier| ImportedSymbol::parse(self.node_text(specifier, source)))),
+                    _ => {}
+                }
+            }
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.dependencies.push(path.clone());
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Import { path: path.clone(), alias, symbols },
+            name: Some(path),
+            children: Vec::new(),
+            metadata,
+            source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         })
     }
     
@@ -329,38 +613,56 @@ impl JavaScriptParser {
             children,
             metadata: self.create_metadata(node, source),
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         })
     }
     
+    /// The condition, then the consequence's statements, then an `else_clause` holding the
+    /// alternative's; either branch may be a block or a single statement
     fn convert_if_statement(&self, node: Node, source: &str) -> Result<UIRNode> {
         let mut children = Vec::new();
-        
-        let mut cursor = node.walk();
-        if cursor.goto_first_child() {
-            loop {
-                let child = cursor.node();
-                match child.kind() {
-                    "parenthesized_expression" => {
-                        if let Some(condition) = self.find_child_by_kind(child, "binary_expression")
-                            .or_else(|| self.find_child_by_kind(child, "identifier"))
-                            .or_else(|| self.find_child_by_kind(child, "call_expression")) {
-                            if let Ok(cond_uir) = self.ast_to_uir(condition, source) {
-                                children.push(cond_uir);
-                            }
-                        }
-                    }
-                    "statement_block" | "expression_statement" => {
-                        if let Ok(body_uir) = self.ast_to_uir(child, source) {
-                            children.push(body_uir);
-                        }
-                    }
-                    _ => {}
-                }
-                
-                if !cursor.goto_next_sibling() {
-                    break;
-                }
-            }
+        if let Some(condition) = node.child_by_field_name("condition") {
+            // The parentheses of `if (...)` belong to the statement, not the expression
+            let condition = match condition.kind() {
+                "parenthesized_expression" => condition.named_child(0).unwrap_or(condition),
+                _ => condition,
+            };
+            children.push(self.ast_to_uir(condition, source)?);
+        }
+        if let Some(consequence) = node.child_by_field_name("consequence") {
+            children.extend(self.branch_statements(consequence, source)?);
+        }
+        if let Some(alternative) = node.child_by_field_name("alternative") {
+            let body = alternative.named_children(&mut alternative.walk())
+                .filter(|child| !comments::is_comment(*child))
+                .last();
+            let statements = match body {
+                Some(body) => self.branch_statements(body, source)?,
+                None => Vec::new(),
+            };
+            children.push(UIRNode {
+                id: self.generate_node_id(alternative, source),
+                node_type: NodeType::Statement(StatementType::Expression),
+                name: Some("else_clause".to_string()),
+                children: statements,
+                metadata: self.create_metadata(alternative, source),
+                source_location: self.create_source_location(alternative, ""),
+                operator: None,
+                literal: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                is_async: false,
+                attributes: Vec::new(),
+                ownership: None,
+                documentation: None,
+            });
         }
         
         Ok(UIRNode {
@@ -370,18 +672,282 @@ impl JavaScriptParser {
             children,
             metadata: self.create_metadata(node, source),
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         })
     }
     
-    fn convert_call_expression(&self, node: Node, source: &str) -> Result<UIRNode> {
+    /// The condition, then the body's statements
+    fn convert_while_statement(&self, node: Node, source: &str) -> Result<UIRNode> {
         let mut children = Vec::new();
+        if let Some(condition) = node.child_by_field_name("condition") {
+            children.push(self.ast_to_uir(self.unparenthesized(condition), source)?);
+        }
+        if let Some(body) = node.child_by_field_name("body") {
+            children.extend(self.branch_statements(body, source)?);
+        }
+        Ok(self.loop_node(node, LoopType::While, children, self.create_metadata(node, source), source))
+    }
+    
+    /// The body's statements, then the condition
+    fn convert_do_statement(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        if let Some(body) = node.child_by_field_name("body") {
+            children.extend(self.branch_statements(body, source)?);
+        }
+        if let Some(condition) = node.child_by_field_name("condition") {
+            children.push(self.ast_to_uir(self.unparenthesized(condition), source)?);
+        }
+        Ok(self.loop_node(node, LoopType::DoWhile, children, self.create_metadata(node, source), source))
+    }
+    
+    /// The header parts the source gives, tagged `loop_initializer`, `loop_condition` and
+    /// `loop_update`, then the body's statements
+    fn convert_for_statement(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        for (field, role) in [("initializer", "loop_initializer"), ("condition", "loop_condition"), ("increment", "loop_update")] {
+            let Some(part) = node.child_by_field_name(field) else { continue };
+            // A part left empty is a lone `;`; the others end with theirs
+            let part = match part.kind() {
+                "empty_statement" => continue,
+                "expression_statement" => part.named_child(0).unwrap_or(part),
+                _ => part,
+            };
+            let mut uir = self.ast_to_uir(part, source)?;
+            uir.metadata.semantic_tags.push(role.to_string());
+            children.push(uir);
+        }
+        if let Some(body) = node.child_by_field_name("body") {
+            children.extend(self.branch_statements(body, source)?);
+        }
+        Ok(self.loop_node(node, LoopType::For, children, self.create_metadata(node, source), source))
+    }
+    
+    /// `for (const x of xs)` and `for (k in o)`: the variable, the iterable, then the body's
+    /// statements. The loop's `iteration` says whether it visits values (`of`) or keys (`in`)
+    fn convert_for_in_statement(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        if let Some(left) = node.child_by_field_name("left") {
+            let mut target = match left.kind() {
+                "identifier" => UIRNode {
+                    id: self.generate_node_id(left, source),
+                    node_type: NodeType::Variable,
+                    name: Some(self.node_text(left, source).to_string()),
+                    children: Vec::new(),
+                    metadata: self.create_metadata(left, source),
+                    source_location: self.create_source_location(left, ""),
+                    operator: None,
+                    literal: None,
+                    visibility: None,
+                    modifiers: Vec::new(),
+                    is_async: false,
+                    attributes: Vec::new(),
+                    ownership: None,
+                    documentation: None,
+                },
+                _ => self.ast_to_uir(left, source)?,
+            };
+            if let Some(kind) = node.child_by_field_name("kind") {
+                target.metadata.annotations.insert(
+                    "declaration_kind".to_string(),
+                    serde_json::Value::String(kind.kind().to_string())
+                );
+            }
+            children.push(target);
+        }
+        if let Some(right) = node.child_by_field_name("right") {
+            children.push(self.ast_to_uir(right, source)?);
+        }
+        if let Some(body) = node.child_by_field_name("body") {
+            children.extend(self.branch_statements(body, source)?);
+        }
         
-        if let Some(func_node) = self.find_child_by_kind(node, "identifier")
-            .or_else(|| self.find_child_by_kind(node, "member_expression")) {
-            if let Ok(func_uir) = self.ast_to_uir(func_node, source) {
-                children.push(func_uir);
+        let mut metadata = self.create_metadata(node, source);
+        let iteration = node.child_by_field_name("operator").map(|o| o.kind()).unwrap_or("of");
+        metadata.annotations.insert("iteration".to_string(), serde_json::Value::String(iteration.to_string()));
+        // `for await (const x of stream)`
+        if self.has_token(node, "await") {
+            metadata.semantic_tags.push("async".to_string());
+            metadata.annotations.insert("await".to_string(), serde_json::Value::Bool(true));
+        }
+        Ok(self.loop_node(node, LoopType::ForEach, children, metadata, source))
+    }
+    
+    fn loop_node(&self, node: Node, loop_type: LoopType, children: Vec<UIRNode>, metadata: Metadata, source: &str) -> UIRNode {
+        UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::ControlFlow(ControlFlowType::Loop(loop_type)),
+            name: Some(node.kind().to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+    
+    /// `i++` and `--i` as the compound assignments `i += 1` and `i -= 1`, marked `prefix` when
+    /// the operator comes first
+    fn convert_update_expression(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        if let Some(argument) = node.child_by_field_name("argument").or_else(|| node.named_child(0)) {
+            children.push(self.ast_to_uir(argument, source)?);
+        }
+        let operator_token = node.child_by_field_name("operator")
+            .map(|o| o.kind())
+            .unwrap_or_else(|| if self.node_text(node, source).contains("--") { "--" } else { "++" });
+        let mut step = Metadata { source_language: Language::JavaScript, ..Default::default() };
+        step.semantic_tags.push("number".to_string());
+        step.annotations.insert("original_text".to_string(), serde_json::Value::String("1".to_string()));
+        children.push(UIRNode {
+            id: format!("{}_step", self.generate_node_id(node, source)),
+            node_type: NodeType::Expression(ExpressionType::Literal),
+            name: None,
+            children: Vec::new(),
+            metadata: step,
+            source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: Some(LiteralValue::Int(1)),
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        });
+        
+        let mut metadata = self.create_metadata(node, source);
+        let prefix = node.child(0).is_some_and(|first| !first.is_named());
+        metadata.annotations.insert("prefix".to_string(), serde_json::Value::Bool(prefix));
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Assignment),
+            name: Some("update_expression".to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node, ""),
+            operator: Some(if operator_token == "--" { Operator::Subtract } else { Operator::Add }),
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// The expression inside the parentheses of `while (...)` and `do ... while (...)`, which
+    /// belong to the statement
+    fn unparenthesized<'a>(&self, node: Node<'a>) -> Node<'a> {
+        match node.kind() {
+            "parenthesized_expression" => node.named_child(0).unwrap_or(node),
+            _ => node,
+        }
+    }
+    
+    /// The protected statements, then Catch and Finally nodes holding their own statements.
+    /// JavaScript catches every exception; the clause may bind it, possibly by destructuring.
+    fn convert_try_statement(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = self.block_statements(node.child_by_field_name("body"), source)?;
+        if let Some(handler) = node.child_by_field_name("handler") {
+            let catch = ControlFlowType::Catch {
+                exception_types: Vec::new(),
+                binding: handler.child_by_field_name("parameter").map(|p| self.node_text(p, source).to_string()),
+            };
+            children.push(self.convert_clause(handler, catch, source)?);
+        }
+        if let Some(finalizer) = node.child_by_field_name("finalizer") {
+            children.push(self.convert_clause(finalizer, ControlFlowType::Finally, source)?);
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::ControlFlow(ControlFlowType::Try),
+            name: Some("try_statement".to_string()),
+            children,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_clause(&self, node: Node, flow: ControlFlowType, source: &str) -> Result<UIRNode> {
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::ControlFlow(flow),
+            name: Some(node.kind().to_string()),
+            children: self.block_statements(node.child_by_field_name("body"), source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// The statements of a `{ ... }` block
+    fn block_statements(&self, block: Option<Node>, source: &str) -> Result<Vec<UIRNode>> {
+        let Some(block) = block else {
+            return Ok(Vec::new());
+        };
+        let mut statements = Vec::new();
+        let mut cursor = block.walk();
+        for statement in block.named_children(&mut cursor) {
+            if comments::is_comment(statement) {
+                statements.push(comments::comment_node(statement, source, &Language::JavaScript));
+            } else if !statement.is_extra() {
+                statements.push(self.ast_to_uir(statement, source)?);
             }
         }
+        Ok(statements)
+    }
+    
+    /// The statements of a branch: a block's, or the single statement of a braceless one
+    fn branch_statements(&self, branch: Node, source: &str) -> Result<Vec<UIRNode>> {
+        match branch.kind() {
+            "statement_block" => self.block_statements(Some(branch), source),
+            _ => Ok(vec![self.ast_to_uir(branch, source)?]),
+        }
+    }
+    
+    fn convert_call_expression(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        
+        // The called function comes first, whatever it is: `f`, `console.log`, `f()` or `(g || h)`
+        if let Some(func_node) = node.child_by_field_name("function") {
+            children.push(self.ast_to_uir(func_node, source)?);
+        }
+        
+        // Tagged templates: html`<p>${text}</p>`
+        let tagged_template = self.find_child_by_kind(node, "template_string");
+        if let Some(template_node) = tagged_template {
+            children.push(self.convert_template_string(template_node, source)?);
+        }
         
         if let Some(args_node) = self.find_child_by_kind(node, "arguments") {
             let mut cursor = args_node.walk();
@@ -406,42 +972,175 @@ impl JavaScriptParser {
             node_type: NodeType::Expression(ExpressionType::FunctionCall),
             name: None,
             children,
-            metadata: self.create_metadata(node, source),
+            metadata: {
+                let mut metadata = self.create_metadata(node, source);
+                if tagged_template.is_some() {
+                    metadata.semantic_tags.push("tagged_template".to_string());
+                }
+                metadata
+            },
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         })
     }
     
-    fn convert_binary_expression(&self, node: Node, source: &str) -> Result<UIRNode> {
+    /// Binary and unary expressions; the operator token becomes the node's `operator`
+    fn convert_operator_expression(&self, node: Node, source: &str) -> Result<UIRNode> {
         let mut children = Vec::new();
         
         let mut cursor = node.walk();
-        if cursor.goto_first_child() {
-            loop {
-                let child = cursor.node();
-                match child.kind() {
-                    "+" | "-" | "*" | "/" | "%" | "==" | "!=" | "<" | ">" | "<=" | ">=" | "&&" | "||" => {
-                        // Skip operators - they're implicit in the binary expression type
-                    }
-                    _ => {
-                        if let Ok(operand_uir) = self.ast_to_uir(child, source) {
-                            children.push(operand_uir);
-                        }
-                    }
-                }
-                
-                if !cursor.goto_next_sibling() {
-                    break;
-                }
+        for child in node.named_children(&mut cursor) {
+            if let Ok(operand_uir) = self.ast_to_uir(child, source) {
+                children.push(operand_uir);
             }
         }
         
-        Ok(UIRNode {
+        let mut uir = UIRNode {
             id: self.generate_node_id(node, source),
             node_type: NodeType::Expression(ExpressionType::Arithmetic),
             name: None,
             children,
             metadata: self.create_metadata(node, source),
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &Language::JavaScript, &mut uir);
+        Ok(uir)
+    }
+    
+    fn convert_await_expression(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        if let Some(operand) = node.named_child(0) {
+            children.push(self.ast_to_uir(operand, source)?);
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.semantic_tags.push("async".to_string());
+        metadata.annotations.insert("await".to_string(), serde_json::Value::Bool(true));
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Await),
+            name: Some("await".to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_yield_expression(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        if let Some(operand) = node.named_child(0) {
+            children.push(self.ast_to_uir(operand, source)?);
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.semantic_tags.push("generator".to_string());
+        // `yield*` delegates to another iterable
+        if self.has_token(node, "*") {
+            metadata.semantic_tags.push("yield_from".to_string());
+            metadata.annotations.insert("delegate".to_string(), serde_json::Value::Bool(true));
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Yield),
+            name: Some("yield".to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_template_string(&self, node: Node, source: &str) -> Result<UIRNode> {
+        // Literal segments ("quasis") alternate with substituted expressions
+        let mut quasis = Vec::new();
+        let mut children = Vec::new();
+        let mut segment_start = node.start_byte() + 1;
+        
+        for substitution in self.children_by_kind(node, "template_substitution") {
+            quasis.push(serde_json::Value::String(source[segment_start..substitution.start_byte()].to_string()));
+            segment_start = substitution.end_byte();
+            
+            if let Some(expression) = substitution.named_child(0) {
+                children.push(self.ast_to_uir(expression, source)?);
+            }
+        }
+        let segment_end = node.end_byte().saturating_sub(1).max(segment_start);
+        quasis.push(serde_json::Value::String(source[segment_start..segment_end].to_string()));
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.semantic_tags.push("template_literal".to_string());
+        metadata.annotations.insert("quasis".to_string(), serde_json::Value::Array(quasis));
+        metadata.annotations.insert("interpolated".to_string(), serde_json::Value::Bool(!children.is_empty()));
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Literal),
+            name: Some("template_literal".to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_destructuring_pattern(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        self.expand_pattern(node, source, &[], &mut children)?;
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Statement(StatementType::Expression),
+            name: Some("destructuring_pattern".to_string()),
+            children,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         })
     }
     
@@ -455,18 +1154,36 @@ impl JavaScriptParser {
             children: vec![],
             metadata: self.create_metadata(node, source),
             source_location: self.create_source_location(node, ""),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         })
     }
     
     fn convert_literal(&self, node: Node, source: &str) -> Result<UIRNode> {
-        Ok(UIRNode {
+        let mut uir = UIRNode {
             id: self.generate_node_id(node, source),
             node_type: NodeType::Expression(ExpressionType::Literal),
             name: None,
             children: vec![],
             metadata: self.create_metadata(node, source),
             source_location: self.create_source_location(node, ""),
-        })
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_literal(node, source, &Language::JavaScript, &mut uir);
+        Ok(uir)
     }
     
     fn convert_generic(&self, node: Node, source: &str) -> Result<UIRNode> {
@@ -488,14 +1205,24 @@ impl JavaScriptParser {
             }
         }
         
-        Ok(UIRNode {
+        let mut uir = UIRNode {
             id: self.generate_node_id(node, source),
             node_type: self.map_node_type(node.kind()),
             name: Some(node.kind().to_string()),
             children,
             metadata: self.create_metadata(node, source),
             source_location: self.create_source_location(node, ""),
-        })
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &Language::JavaScript, &mut uir);
+        Ok(uir)
     }
     
     // Helper methods
@@ -506,16 +1233,50 @@ impl JavaScriptParser {
         if cursor.goto_first_child() {
             loop {
                 let child = cursor.node();
-                if child.kind() == "identifier" {
-                    let param_name = self.node_text(child, source);
-                    parameters.push(UIRNode {
-                        id: self.generate_node_id(child, source),
-                        node_type: NodeType::Variable,
-                        name: Some(param_name.to_string()),
-                        children: vec![],
-                        metadata: self.create_metadata(child, source),
-                        source_location: self.create_source_location(child, ""),
-                    });
+                match child.kind() {
+                    "identifier" => {
+                        let param_name = self.node_text(child, source);
+                        parameters.push(UIRNode {
+                            id: self.generate_node_id(child, source),
+                            node_type: NodeType::Variable,
+                            name: Some(param_name.to_string()),
+                            children: vec![],
+                            metadata: self.create_metadata(child, source),
+                            source_location: self.create_source_location(child, ""),
+                            operator: None,
+                            literal: None,
+                            visibility: None,
+                            modifiers: Vec::new(),
+                            is_async: false,
+                            attributes: Vec::new(),
+                            ownership: None,
+                            documentation: None,
+                        });
+                    }
+                    // Defaults, rest parameters and destructured parameters
+                    "assignment_pattern" | "rest_pattern" | "object_pattern" | "array_pattern" => {
+                        let (pattern, default) = match child.kind() {
+                            "assignment_pattern" => (child.child_by_field_name("left"), child.child_by_field_name("right")),
+                            _ => (Some(child), None),
+                        };
+                        match pattern.filter(|p| matches!(p.kind(), "object_pattern" | "array_pattern")) {
+                            // Each binding keeps the pattern it is read from, and the default of
+                            // the whole parameter
+                            Some(pattern) => {
+                                let first = parameters.len();
+                                self.expand_pattern(pattern, source, &[], &mut parameters)?;
+                                for binding in &mut parameters[first..] {
+                                    let annotations = &mut binding.metadata.annotations;
+                                    annotations.insert("pattern".to_string(), serde_json::Value::String(self.node_text(pattern, source).to_string()));
+                                    if let Some(default) = default {
+                                        annotations.insert("pattern_default".to_string(), serde_json::Value::String(self.node_text(default, source).to_string()));
+                                    }
+                                }
+                            }
+                            None => self.expand_pattern(child, source, &[], &mut parameters)?,
+                        }
+                    }
+                    _ => {}
                 }
                 
                 if !cursor.goto_next_sibling() {
@@ -534,7 +1295,9 @@ impl JavaScriptParser {
         if cursor.goto_first_child() {
             loop {
                 let child = cursor.node();
-                if !child.is_extra() && child.kind() != "{" && child.kind() != "}" {
+                if comments::is_comment(child) {
+                    statements.push(comments::comment_node(child, source, &Language::JavaScript));
+                } else if !child.is_extra() && child.kind() != "{" && child.kind() != "}" {
                     if let Ok(stmt_uir) = self.ast_to_uir(child, source) {
                         statements.push(stmt_uir);
                     }
@@ -549,6 +1312,130 @@ impl JavaScriptParser {
         Ok(statements)
     }
     
+    /// Expand a binding pattern into one Variable per bound name.
+    ///
+    /// Each variable records the property/index path it is read from, so
+    /// `const { a: [, b] } = obj` yields `b` with path `["a", 1]`.
+    fn expand_pattern(&self, node: Node, source: &str, path: &[serde_json::Value], bindings: &mut Vec<UIRNode>) -> Result<()> {
+        match node.kind() {
+            "identifier" | "shorthand_property_identifier_pattern" => {
+                let name = self.node_text(node, source);
+                let mut path = path.to_vec();
+                if node.kind() == "shorthand_property_identifier_pattern" {
+                    path.push(serde_json::Value::String(name.to_string()));
+                }
+                
+                let mut metadata = self.create_metadata(node, source);
+                metadata.semantic_tags.push("destructured".to_string());
+                if !path.is_empty() {
+                    metadata.annotations.insert("destructuring_path".to_string(), serde_json::Value::Array(path));
+                }
+                
+                bindings.push(UIRNode {
+                    id: self.generate_node_id(node, source),
+                    node_type: NodeType::Variable,
+                    name: Some(name.to_string()),
+                    children: vec![],
+                    metadata,
+                    source_location: self.create_source_location(node, ""),
+                    operator: None,
+                    literal: None,
+                    visibility: None,
+                    modifiers: Vec::new(),
+                    is_async: false,
+                    attributes: Vec::new(),
+                    ownership: None,
+                    documentation: None,
+                });
+            }
+            "object_pattern" => {
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    self.expand_pattern(child, source, path, bindings)?;
+                }
+            }
+            "array_pattern" => {
+                // Holes (`[, b]`) still advance the index
+                let mut index = 0;
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor) {
+                    match child.kind() {
+                        "," => index += 1,
+                        "[" | "]" => {}
+                        _ if child.is_named() => {
+                            let mut element_path = path.to_vec();
+                            element_path.push(serde_json::Value::from(index));
+                            self.expand_pattern(child, source, &element_path, bindings)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            "pair_pattern" => {
+                if let (Some(key), Some(value)) = (node.child_by_field_name("key"), node.child_by_field_name("value")) {
+                    let key = self.node_text(key, source).trim_matches(|c| c == '"' || c == '\'');
+                    let mut pair_path = path.to_vec();
+                    pair_path.push(serde_json::Value::String(key.to_string()));
+                    self.expand_pattern(value, source, &pair_path, bindings)?;
+                }
+            }
+            "assignment_pattern" | "object_assignment_pattern" => {
+                // `{ a = 1 }` / `[b = 2]`: the default becomes the variable's child
+                if let Some(left) = node.child_by_field_name("left") {
+                    let first = bindings.len();
+                    self.expand_pattern(left, source, path, bindings)?;
+                    if let (Some(right), Some(binding)) = (node.child_by_field_name("right"), bindings.get_mut(first)) {
+                        binding.children.push(self.ast_to_uir(right, source)?);
+                        binding.metadata.annotations.insert("has_default".to_string(), serde_json::Value::Bool(true));
+                    }
+                }
+            }
+            "rest_pattern" => {
+                if let Some(target) = node.named_child(0) {
+                    let first = bindings.len();
+                    self.expand_pattern(target, source, path, bindings)?;
+                    if let Some(binding) = bindings.get_mut(first) {
+                        binding.metadata.annotations.insert("rest".to_string(), serde_json::Value::Bool(true));
+                    }
+                }
+            }
+            _ => {}
+        }
+        
+        Ok(())
+    }
+    
+    /// Metadata for functions, marking `async` and generator (`*`) functions
+    fn create_function_metadata(&self, node: Node, source: &str) -> Metadata {
+        let mut metadata = self.create_metadata(node, source);
+        
+        if self.has_token(node, "async") {
+            metadata.semantic_tags.push("async".to_string());
+            metadata.annotations.insert("async".to_string(), serde_json::Value::Bool(true));
+        }
+        if node.kind().starts_with("generator_function") || self.has_token(node, "*") {
+            metadata.semantic_tags.push("generator".to_string());
+            metadata.annotations.insert("generator".to_string(), serde_json::Value::Bool(true));
+        }
+        
+        metadata
+    }
+    
+    /// Whether `node` has a direct anonymous child token such as `async` or `*`
+    fn has_token(&self, node: Node, token: &str) -> bool {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).any(|child| !child.is_named() && child.kind() == token);
+        found
+    }
+    
+    /// `@name` and `@name(...)` decorators on a class or method
+    fn decorators(&self, node: Node, source: &str) -> Vec<Attribute> {
+        self.children_by_kind(node, "decorator")
+            .into_iter()
+            .map(|d| Attribute::parse(self.node_text(d, source).trim_start_matches('@')))
+            .collect()
+    }
+    
     fn node_text<'a>(&self, node: Node, source: &'a str) -> &'a str {
         &source[node.byte_range()]
     }
@@ -593,18 +1480,18 @@ impl JavaScriptParser {
     }
     
     fn create_metadata(&self, node: Node, source: &str) -> Metadata {
-        let mut metadata = Metadata::default();
-        metadata.source_language = coalesce_core::types::Language::JavaScript;
+        let mut metadata = Metadata {
+            source_language: coalesce_core::types::Language::JavaScript,
+            ..Default::default()
+        };
         metadata.semantic_tags.push(node.kind().to_string());
         
-        // Add text content as annotation for debugging
+        // Add text content as annotation, which library detection reads
         let text = self.node_text(node, source);
-        if text.len() < 100 {
-            metadata.annotations.insert(
-                "original_text".to_string(), 
-                serde_json::Value::String(text.to_string())
-            );
-        }
+        metadata.annotations.insert(
+            "original_text".to_string(), 
+            serde_json::Value::String(text.to_string())
+        );
         
         metadata
     }
@@ -633,12 +1520,15 @@ impl JavaScriptParser {
     fn map_node_type(&self, kind: &str) -> NodeType {
         match kind {
             "program" | "source_file" => NodeType::Module,
-            "function_declaration" | "function_definition" => NodeType::Function,
-            "variable_declaration" | "variable_declarator" => NodeType::Variable,
-            "if_statement" | "while_statement" | "for_statement" => NodeType::ControlFlow(ControlFlowType::Conditional),
+            "function_declaration" | "function_definition" | "generator_function_declaration" => NodeType::Function,
+            "variable_declaration" | "lexical_declaration" | "variable_declarator" => NodeType::Variable,
+            "if_statement" => NodeType::ControlFlow(ControlFlowType::Conditional),
+            "break_statement" => NodeType::Statement(StatementType::Break),
+            "continue_statement" => NodeType::Statement(StatementType::Continue),
+            "throw_statement" => NodeType::Statement(StatementType::Throw),
             "return_statement" => NodeType::Statement(StatementType::Return),
             "expression_statement" => NodeType::Statement(StatementType::Expression),
-            "assignment_expression" => NodeType::Expression(ExpressionType::Assignment),
+            "assignment_expression" | "augmented_assignment_expression" => NodeType::Expression(ExpressionType::Assignment),
             "binary_expression" | "unary_expression" => NodeType::Expression(ExpressionType::Arithmetic),
             "call_expression" => NodeType::Expression(ExpressionType::FunctionCall),
             "identifier" => NodeType::Expression(ExpressionType::Variable),
@@ -647,49 +1537,221 @@ impl JavaScriptParser {
             _ => NodeType::Expression(ExpressionType::Literal), // Generic fallback
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
     
-    fn handle_parse_error(&self, source: &str, root: Node) -> Result<UIRNode> {
-        let errors = self.collect_error_nodes(root);
-        let error_msg = format!(
-            "Parse errors found: {} error nodes. First error at line {}",
-            errors.len(),
-            errors.first().map(|n| n.start_position().row + 1).unwrap_or(0)
-        );
+    fn find<'a>(node: &'a UIRNode, predicate: &dyn Fn(&UIRNode) -> bool) -> Option<&'a UIRNode> {
+        if predicate(node) {
+            return Some(node);
+        }
+        node.children.iter().find_map(|child| find(child, predicate))
+    }
+    
+    #[test]
+    fn test_async_function_and_await() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "async function load(url) { const res = await fetch(url); return res; }";
         
-        Ok(UIRNode {
-            id: "error_recovery".to_string(),
-            node_type: NodeType::Module,
-            name: Some("partial_parse".to_string()),
-            children: vec![],
-            metadata: {
-                let mut metadata = Metadata::default();
-                metadata.annotations.insert(
-                    "parse_error".to_string(), 
-                    serde_json::Value::String(error_msg)
-                );
-                metadata
-            },
-            source_location: None,
-        })
+        let uir = parser.parse(source).unwrap();
+        let function = find(&uir, &|n| n.name.as_deref() == Some("load")).unwrap();
+        assert_eq!(function.metadata.annotations["async"], true);
+        assert!(function.is_async);
+        
+        let await_node = find(function, &|n| n.name.as_deref() == Some("await")).unwrap();
+        assert_eq!(await_node.node_type, NodeType::Expression(ExpressionType::Await));
+        assert_eq!(await_node.children[0].node_type, NodeType::Expression(ExpressionType::FunctionCall));
     }
     
-    fn collect_error_nodes<'a>(&self, node: Node<'a>) -> Vec<Node<'a>> {
-        let mut errors = Vec::new();
+    #[test]
+    fn test_generator_and_template_literal() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "function* greet(names) { for (const n of names) { yield `Hello, ${n}!`; } }";
         
-        if node.is_error() {
-            errors.push(node);
-        }
+        let uir = parser.parse(source).unwrap();
+        let function = find(&uir, &|n| n.name.as_deref() == Some("greet")).unwrap();
+        assert_eq!(function.metadata.annotations["generator"], true);
         
-        let mut cursor = node.walk();
-        if cursor.goto_first_child() {
-            loop {
-                errors.extend(self.collect_error_nodes(cursor.node()));
-                if !cursor.goto_next_sibling() {
-                    break;
-                }
-            }
-        }
+        let template = find(function, &|n| n.name.as_deref() == Some("template_literal")).unwrap();
+        assert_eq!(template.metadata.annotations["quasis"], serde_json::json!(["Hello, ", "!"]));
+        assert_eq!(template.children[0].name.as_deref(), Some("n"));
+    }
+    
+    #[test]
+    fn test_destructuring_declaration() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "const { id, profile: [, email], retries = 3, ...rest } = user;";
+        
+        let uir = parser.parse(source).unwrap();
+        let declaration = &uir.children[0];
+        assert_eq!(declaration.metadata.annotations["declaration_kind"], "const");
+        
+        let names: Vec<_> = declaration.children.iter().filter_map(|c| c.name.as_deref()).collect();
+        assert_eq!(names, vec!["id", "email", "retries", "rest"]);
+        assert_eq!(declaration.children[1].metadata.annotations["destructuring_path"], serde_json::json!(["profile", 1]));
+        assert_eq!(declaration.children[2].children.len(), 1);
+        assert_eq!(declaration.children[3].metadata.annotations["rest"], true);
+        assert_eq!(declaration.children[0].metadata.annotations["destructured_from"], "user");
+    }
+    
+    #[test]
+    fn test_syntax_error_keeps_valid_code() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "function good() { return 1; }\nconst y = 1 +* 2;\nfunction alsoGood(a) { return a + 1; }";
+        
+        let uir = parser.parse(source).unwrap();
+        assert!(uir.metadata.semantic_tags.contains(&"partial_parse".to_string()));
+        assert!(find(&uir, &|n| n.name.as_deref() == Some("good")).is_some());
+        assert!(find(&uir, &|n| n.name.as_deref() == Some("alsoGood")).is_some());
+        
+        let error = find(&uir, &|n| n.metadata.semantic_tags.contains(&"parse_error".to_string())).unwrap();
+        assert_eq!(error.metadata.annotations["diagnostic"]["line"], 2);
+    }
+    
+    #[test]
+    fn test_doc_comment_and_trailing_comment() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "/**\n * Doubles a value\n */\nfunction double(a) { return a * 2; }\nconst limit = 3; // upper bound\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let function = find(&uir, &|n| n.name.as_deref() == Some("double")).unwrap();
+        let doc = find(&uir, &|n| matches!(n.node_type, NodeType::Comment { style: CommentStyle::Doc, .. })).unwrap();
+        assert_eq!(doc.node_type, NodeType::Comment {
+            style: CommentStyle::Doc,
+            text: "Doubles a value".to_string(),
+            attached_to: Some(function.id.clone()),
+        });
+        
+        let trailing = find(&uir, &|n| matches!(n.node_type, NodeType::Comment { style: CommentStyle::Line, .. })).unwrap();
+        assert!(matches!(&trailing.node_type, NodeType::Comment { text, attached_to: Some(_), .. } if text == "upper bound"));
+    }
+    
+    #[test]
+    fn test_es_module_imports() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "import React, { useState as useLocal } from 'react';\nimport * as path from \"path\";\nimport './polyfills';\n";
+        
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.children[0].node_type, NodeType::Import {
+            path: "react".to_string(),
+            alias: None,
+            symbols: vec![
+                ImportedSymbol { name: "default".to_string(), alias: Some("React".to_string()) },
+                ImportedSymbol { name: "useState".to_string(), alias: Some("useLocal".to_string()) },
+            ],
+        });
+        assert_eq!(uir.children[1].node_type, NodeType::Import { path: "path".to_string(), alias: Some("path".to_string()), symbols: Vec::new() });
+        assert_eq!(uir.children[2].node_type, NodeType::Import { path: "./polyfills".to_string(), alias: None, symbols: Vec::new() });
+    }
+    
+    #[test]
+    fn test_parentheses_become_grouping_tags() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "const y = a * ((b + c));";
+        
+        let uir = parser.parse(source).unwrap();
+        let product = find(&uir, &|n| n.operator == Some(Operator::Multiply)).unwrap();
+        assert!(!product.metadata.semantic_tags.contains(&"parenthesized".to_string()));
+        
+        let sum = &product.children[1];
+        assert_eq!(sum.operator, Some(Operator::Add));
+        assert!(sum.metadata.semantic_tags.contains(&"parenthesized".to_string()));
+        assert!(find(&uir, &|n| n.metadata.semantic_tags.contains(&"parenthesized_expression".to_string())).is_none());
+    }
+    
+    #[test]
+    fn test_if_keeps_any_condition_and_braceless_branches() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "function f(user) { if (!user) return 1; if (user.active) { log(user); } else return 2; }";
+        
+        let uir = parser.parse(source).unwrap();
+        assert!(uir.validate().is_ok());
+        let function = find(&uir, &|n| n.name.as_deref() == Some("f")).unwrap();
+        let conditionals: Vec<_> = function.children.iter()
+            .filter(|c| c.node_type == NodeType::ControlFlow(ControlFlowType::Conditional))
+            .collect();
+        assert_eq!(conditionals.len(), 2);
+        
+        assert_eq!(conditionals[0].children[0].operator, Some(Operator::Not));
+        assert_eq!(conditionals[0].children[1].node_type, NodeType::Statement(StatementType::Return));
+        
+        let else_clause = conditionals[1].children.last().unwrap();
+        assert_eq!(else_clause.name.as_deref(), Some("else_clause"));
+        assert_eq!(else_clause.children[0].node_type, NodeType::Statement(StatementType::Return));
+    }
+    
+    #[test]
+    fn test_loops_keep_their_header_parts_and_body() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "function f(items, n) {\n\
+            for (let i = 0; i < n; i++) { if (i > 5) break; log(i); }\n\
+            for (const k in items) log(k);\n\
+            while (n > 0) { n--; continue; }\n\
+            do { n += 2; } while (n < 9);\n\
+            for (;;) {}\n}";
+        
+        let uir = parser.parse(source).unwrap();
+        assert!(uir.validate().is_ok());
+        let function = find(&uir, &|n| n.name.as_deref() == Some("f")).unwrap();
+        let loops: Vec<_> = function.children.iter().filter(|c| matches!(c.node_type, NodeType::ControlFlow(ControlFlowType::Loop(_)))).collect();
+        
+        assert_eq!(loops[0].node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For)));
+        let roles: Vec<_> = loops[0].children.iter().map(|c| c.metadata.semantic_tags.last().unwrap().as_str()).collect();
+        assert_eq!(roles, ["loop_initializer", "loop_condition", "loop_update", "if_statement", "expression_statement"]);
+        assert_eq!(loops[0].children[2].node_type, NodeType::Expression(ExpressionType::Assignment));
+        assert_eq!(loops[0].children[2].operator, Some(Operator::Add));
+        assert_eq!(loops[0].children[3].children[1].node_type, NodeType::Statement(StatementType::Break));
+        
+        assert_eq!(loops[1].node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)));
+        assert_eq!(loops[1].metadata.annotations["iteration"], "in");
+        assert_eq!(loops[1].children[0].node_type, NodeType::Variable);
+        assert_eq!(loops[1].children[1].name.as_deref(), Some("items"));
+        
+        assert_eq!(loops[2].node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)));
+        assert_eq!(loops[2].children[0].operator, Some(Operator::Greater));
+        assert_eq!(loops[2].children[2].node_type, NodeType::Statement(StatementType::Continue));
+        
+        assert_eq!(loops[3].node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::DoWhile)));
+        assert_eq!(loops[3].children.last().unwrap().operator, Some(Operator::Less));
+        
+        assert!(loops[4].children.is_empty());
+    }
+    
+    #[test]
+    fn test_class_members_and_parameter_patterns() {
+        let parser = JavaScriptParser::new().unwrap();
+        let source = "class Point extends Base { #secret = 1; static count = 0; get v() { return 1; } set v(value) {} static make() {} }\n\
+            function opts({limit = 3}, [first] = [], ...rest) {}";
+        
+        let uir = parser.parse(source).unwrap();
+        let class = find(&uir, &|n| n.node_type == NodeType::Class).unwrap();
+        assert_eq!(class.metadata.annotations["inherits"], "Base");
+        
+        let secret = find(class, &|n| n.name.as_deref() == Some("secret")).unwrap();
+        assert_eq!(secret.node_type, NodeType::Field { field_type: None, default_value: Some("1".to_string()) });
+        assert_eq!(secret.visibility, Some(Visibility::Private));
+        assert!(secret.metadata.semantic_tags.contains(&"private_name".to_string()));
+        let count = find(class, &|n| n.name.as_deref() == Some("count")).unwrap();
+        assert_eq!(count.modifiers, vec![Modifier::Static]);
+        
+        let property = find(class, &|n| matches!(n.node_type, NodeType::Property { .. })).unwrap();
+        assert!(matches!(property.node_type, NodeType::Property { has_getter: true, has_setter: true, .. }));
+        let accessors: Vec<(Option<&str>, bool)> = property.children.iter()
+            .map(|a| (a.name.as_deref(), a.metadata.semantic_tags.contains(&"setter".to_string())))
+            .collect();
+        assert_eq!(accessors, vec![(Some("get"), false), (Some("set"), true)]);
+        let make = find(class, &|n| n.name.as_deref() == Some("make")).unwrap();
+        assert_eq!(make.modifiers, vec![Modifier::Static]);
         
-        errors
+        let function = find(&uir, &|n| n.name.as_deref() == Some("opts")).unwrap();
+        let pattern = |name: &str| {
+            let parameter = find(function, &|n| n.name.as_deref() == Some(name)).unwrap();
+            (parameter.metadata.annotations.get("pattern").cloned(), parameter.metadata.annotations.get("pattern_default").cloned())
+        };
+        assert_eq!(pattern("limit"), (Some(serde_json::json!("{limit = 3}")), None));
+        assert_eq!(pattern("first"), (Some(serde_json::json!("[first]")), Some(serde_json::json!("[]"))));
+        assert_eq!(pattern("rest"), (None, None));
     }
 }
@@ -0,0 +1,783 @@
+use coalesce_core::{UIRNode, NodeType, Attribute, CaptureMode, ImportedSymbol, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, StatementType, Result, CoalesceError,
+                   Parser as CoalesceParser};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::closures;
+use crate::comments;
+use crate::operators::{annotate_operator, mark_grouped};
+use crate::literals::annotate_literal;
+use serde_json::Value;
+
+/// Node kinds that only group other nodes and are flattened into their parent
+const TRANSPARENT_KINDS: &[&str] = &[
+    "statements", "control_structure_body", "class_body", "function_body", "import_list",
+    "call_suffix", "value_arguments", "value_argument",
+];
+
+const TYPE_KINDS: &[&str] = &["user_type", "nullable_type", "function_type", "parenthesized_type"];
+
+/// Kotlin parser using tree-sitter
+pub struct KotlinParser {
+}
+
+impl CoalesceParser for KotlinParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::Kotlin
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        // Create a new parser for this parse operation
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_kotlin::language())
+            .map_err(|e| CoalesceError::ParseError {
+                message: format!("Failed to set Kotlin language: {}", e),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let tree = parser.parse(source, None)
+            .ok_or_else(|| CoalesceError::ParseError {
+                message: "Failed to parse Kotlin source".to_string(),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let mut uir = self.ast_to_uir(tree.root_node(), source)?;
+        recovery::summarize(source, tree.root_node(), &mut uir);
+        closures::annotate_captures(&mut uir);
+        uir.make_ids_unique();
+        comments::attach_comments(&mut uir);
+        uir.debug_validate();
+        Ok(uir)
+    }
+}
+
+impl KotlinParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    fn ast_to_uir(&self, node: Node, source: &str) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::Kotlin, |child| self.ast_to_uir(child, source));
+        }
+        
+        match node.kind() {
+            "source_file" => self.convert_source_file(node, source),
+            "import_header" => self.convert_import(node, source),
+            "class_declaration" | "object_declaration" | "companion_object" => self.convert_class_declaration(node, source),
+            "function_declaration" | "secondary_constructor" => self.convert_function_declaration(node, source),
+            "property_declaration" => self.convert_property_declaration(node, source),
+            "lambda_literal" | "anonymous_function" => self.convert_lambda(node, source),
+            "jump_expression" => self.convert_jump(node, source),
+            "call_expression" => self.convert_call(node, source),
+            "navigation_expression" => self.convert_null_safe(node, source, "?.", "safe_call", NodeType::Expression(ExpressionType::Variable)),
+            "elvis_expression" => self.convert_null_safe(node, source, "?:", "elvis", NodeType::Expression(ExpressionType::Logical)),
+            "as_expression" => self.convert_null_safe(node, source, "as?", "safe_cast", NodeType::Expression(ExpressionType::Variable)),
+            "postfix_expression" => self.convert_null_safe(node, source, "!!", "not_null_assertion", NodeType::Expression(ExpressionType::Arithmetic)),
+            "if_expression" | "when_entry" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Conditional)),
+            "when_expression" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Switch)),
+            "for_statement" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach))),
+            "while_statement" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While))),
+            "do_while_statement" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::DoWhile))),
+            "try_expression" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Try)),
+            "assignment" => self.convert_simple(node, source, NodeType::Expression(ExpressionType::Assignment)),
+            "additive_expression" | "multiplicative_expression" | "range_expression" => {
+                self.convert_simple(node, source, NodeType::Expression(ExpressionType::Arithmetic))
+            }
+            "comparison_expression" | "equality_expression" | "check_expression" => {
+                self.convert_simple(node, source, NodeType::Expression(ExpressionType::Comparison))
+            }
+            "conjunction_expression" | "disjunction_expression" => {
+                self.convert_simple(node, source, NodeType::Expression(ExpressionType::Logical))
+            }
+            "simple_identifier" | "this_expression" => self.convert_identifier(node, source),
+            "integer_literal" | "real_literal" | "string_literal" | "boolean_literal" | "character_literal"
+            | "long_literal" | "hex_literal" | "null_literal" => {
+                self.convert_simple(node, source, NodeType::Expression(ExpressionType::Literal))
+            }
+            _ => self.convert_generic(node, source),
+        }
+    }
+    
+    fn convert_source_file(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut metadata = self.create_metadata(node, source);
+        if let Some(package) = self.find_child_by_kind(node, "package_header")
+            .and_then(|p| self.find_child_by_kind(p, "identifier")) {
+            metadata.annotations.insert(
+                "package".to_string(),
+                Value::String(self.node_text(package, source).to_string()),
+            );
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Module,
+            name: Some("kotlin_program".to_string()),
+            children: self.convert_children(node, source)?,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// `import a.b.C`, `import a.b.C as D` or `import a.b.*`
+    fn convert_import(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut metadata = self.create_metadata(node, source);
+        
+        let module_name = self.find_child_by_kind(node, "identifier")
+            .map(|n| self.node_text(n, source).to_string())
+            .unwrap_or_default();
+        metadata.dependencies.push(module_name.clone());
+        let alias = self.find_child_by_kind(node, "import_alias")
+            .and_then(|a| self.find_child_by_kind(a, "type_identifier"))
+            .map(|n| self.node_text(n, source).to_string());
+        let is_wildcard = self.node_text(node, source).trim_end().ends_with('*');
+        let symbols = if is_wildcard { vec![ImportedSymbol::new("*")] } else { Vec::new() };
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Import { path: module_name.clone(), alias, symbols },
+            name: Some(module_name),
+            children: vec![],
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_class_declaration(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let is_interface = self.has_keyword(node, "interface");
+        let name = self.find_child_by_kind(node, "type_identifier")
+            .map(|n| self.node_text(n, source).to_string())
+            .unwrap_or_else(|| "Companion".to_string());
+        
+        let mut metadata = self.create_metadata(node, source);
+        let attributes = self.attach_modifiers(&mut metadata, node, source);
+        if node.kind() != "class_declaration" {
+            metadata.semantic_tags.push("object".to_string());
+        }
+        if metadata.semantic_tags.contains(&"enum".to_string()) {
+            metadata.semantic_tags.push("enum_class".to_string());
+        }
+        if metadata.semantic_tags.contains(&"data".to_string()) {
+            metadata.semantic_tags.push("data_class".to_string());
+        }
+        
+        let mut children = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "primary_constructor" => {
+                    let mut params_cursor = child.walk();
+                    for param in child.named_children(&mut params_cursor) {
+                        if param.kind() == "class_parameter" {
+                            children.push(self.convert_parameter(param, source));
+                        }
+                    }
+                }
+                "delegation_specifier" => {
+                    let base = self.find_child_by_kind(child, "constructor_invocation")
+                        .and_then(|c| self.find_child_by_kind(c, "user_type"))
+                        .unwrap_or(child);
+                    metadata.dependencies.push(self.node_text(base, source).to_string());
+                }
+                "class_body" | "enum_class_body" => children.extend(self.convert_children(child, source)?),
+                _ => {}
+            }
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: if is_interface { NodeType::Interface } else { NodeType::Class },
+            name: Some(name),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes,
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_function_declaration(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut metadata = self.create_metadata(node, source);
+        let attributes = self.attach_modifiers(&mut metadata, node, source);
+        let is_async = metadata.semantic_tags.iter().any(|t| t == "suspend");
+        
+        let mut name = None;
+        let mut children = Vec::new();
+        let mut after_colon = false;
+        
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                // A type before the name is the receiver of an extension function
+                kind if TYPE_KINDS.contains(&kind) && name.is_none() => {
+                    metadata.semantic_tags.push("extension_function".to_string());
+                    metadata.annotations.insert(
+                        "receiver_type".to_string(),
+                        Value::String(self.node_text(child, source).to_string()),
+                    );
+                }
+                kind if TYPE_KINDS.contains(&kind) && after_colon => {
+                    metadata.annotations.insert(
+                        "return_type".to_string(),
+                        Value::String(self.node_text(child, source).to_string()),
+                    );
+                    after_colon = false;
+                }
+                ":" => after_colon = true,
+                "simple_identifier" if name.is_none() => name = Some(self.node_text(child, source).to_string()),
+                "function_value_parameters" => {
+                    let mut params_cursor = child.walk();
+                    for param in child.named_children(&mut params_cursor) {
+                        if param.kind() == "parameter" {
+                            children.push(self.convert_parameter(param, source));
+                        }
+                    }
+                }
+                "function_body" => {
+                    if self.has_keyword(child, "=") {
+                        // Expression body: `fun f() = expr` returns the expression
+                        metadata.semantic_tags.push("expression_body".to_string());
+                        let mut body_cursor = child.walk();
+                        for expr in child.named_children(&mut body_cursor) {
+                            children.push(UIRNode {
+                                id: self.generate_node_id(child, source),
+                                node_type: NodeType::Statement(StatementType::Return),
+                                name: None,
+                                children: vec![self.ast_to_uir(expr, source)?],
+                                metadata: self.create_metadata(child, source),
+                                source_location: self.create_source_location(child),
+                                operator: None,
+                                literal: None,
+                                visibility: None,
+                                modifiers: Vec::new(),
+                                is_async: false,
+                                attributes: Vec::new(),
+                                ownership: None,
+                                documentation: None,
+                            });
+                        }
+                    } else {
+                        children.extend(self.convert_children(child, source)?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        
+        let name = name.unwrap_or_else(|| "constructor".to_string());
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Function,
+            name: Some(name),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async,
+            attributes,
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_property_declaration(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut metadata = self.create_metadata(node, source);
+        let attributes = self.attach_modifiers(&mut metadata, node, source);
+        metadata.annotations.insert("mutable".to_string(), Value::Bool(self.has_keyword(node, "var")));
+        
+        let mut name = None;
+        let mut children = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "variable_declaration" | "multi_variable_declaration" => {
+                    let name_node = self.find_child_by_kind(child, "simple_identifier").unwrap_or(child);
+                    name = Some(self.node_text(name_node, source).to_string());
+                    if let Some(type_node) = self.find_type(child) {
+                        self.annotate_type(&mut metadata, type_node, source);
+                    }
+                }
+                "modifiers" | "type_constraints" => {}
+                "property_delegate" => {
+                    metadata.semantic_tags.push("delegated_property".to_string());
+                    children.extend(self.convert_children(child, source)?);
+                }
+                _ => children.push(self.ast_to_uir(child, source)?),
+            }
+        }
+        
+        let is_const = metadata.semantic_tags.contains(&"const".to_string());
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: if is_const { NodeType::Constant } else { NodeType::Variable },
+            name,
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes,
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_lambda(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "lambda_parameters" | "function_value_parameters" => {
+                    let mut params_cursor = child.walk();
+                    for param in child.named_children(&mut params_cursor) {
+                        children.push(self.convert_parameter(param, source));
+                    }
+                }
+                "statements" | "function_body" => children.extend(self.convert_children(child, source)?),
+                _ => {}
+            }
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: closures::closure(CaptureMode::ByReference),
+            name: Some("lambda".to_string()),
+            children,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_jump(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let keyword = node.child(0).map(|c| c.kind()).unwrap_or("return");
+        let node_type = match keyword {
+            "throw" => NodeType::Statement(StatementType::Throw),
+            k if k.starts_with("break") => NodeType::Statement(StatementType::Break),
+            k if k.starts_with("continue") => NodeType::Statement(StatementType::Continue),
+            _ => NodeType::Statement(StatementType::Return),
+        };
+        
+        self.convert_simple(node, source, node_type)
+    }
+    
+    fn convert_call(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = self.convert_children(node, source)?;
+        
+        // The first child is the callee; keep it only when it carries structure (e.g. a?.b())
+        let name = node.named_child(0).map(|callee| self.node_text(callee, source).to_string());
+        if node.named_child(0).is_some_and(|callee| callee.kind() == "simple_identifier") && !children.is_empty() {
+            children.remove(0);
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        if node.named_child(0).is_some_and(|callee| self.has_safe_call(callee)) {
+            metadata.semantic_tags.push("safe_call".to_string());
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::FunctionCall),
+            name,
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// Convert an expression that may carry a null-safety operator (`?.`, `?:`, `as?`, `!!`)
+    fn convert_null_safe(&self, node: Node, source: &str, operator: &str, tag: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = self.convert_simple(node, source, node_type)?;
+        
+        let present = match node.kind() {
+            "navigation_expression" => self.has_safe_call(node),
+            _ => self.has_keyword(node, operator),
+        };
+        if present {
+            uir.metadata.semantic_tags.push(tag.to_string());
+            uir.metadata.semantic_tags.push("null_safety".to_string());
+            uir.metadata.annotations.insert("operator".to_string(), Value::String(operator.to_string()));
+        }
+        
+        Ok(uir)
+    }
+    
+    fn convert_parameter(&self, param: Node, source: &str) -> UIRNode {
+        let mut metadata = self.create_metadata(param, source);
+        metadata.semantic_tags.push("parameter".to_string());
+        let attributes = self.attach_modifiers(&mut metadata, param, source);
+        
+        if param.kind() == "class_parameter" && (self.has_keyword(param, "val") || self.has_keyword(param, "var")) {
+            // Constructor parameters declared with val/var are also properties
+            metadata.semantic_tags.push("property".to_string());
+            metadata.annotations.insert("mutable".to_string(), Value::Bool(self.has_keyword(param, "var")));
+        }
+        if let Some(type_node) = self.find_type(param) {
+            self.annotate_type(&mut metadata, type_node, source);
+        }
+        if let Some(default_node) = self.child_after_keyword(param, "=") {
+            metadata.annotations.insert(
+                "default_value".to_string(),
+                Value::String(self.node_text(default_node, source).to_string()),
+            );
+        }
+        
+        let name_node = self.find_child_by_kind(param, "simple_identifier").unwrap_or(param);
+        
+        UIRNode {
+            id: self.generate_node_id(param, source),
+            node_type: NodeType::Variable,
+            name: Some(self.node_text(name_node, source).to_string()),
+            children: vec![],
+            metadata,
+            source_location: self.create_source_location(param),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes,
+            ownership: None,
+            documentation: None,
+        }
+    }
+    
+    fn convert_identifier(&self, node: Node, source: &str) -> Result<UIRNode> {
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Variable),
+            name: Some(self.node_text(node, source).to_string()),
+            children: vec![],
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_simple(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: None,
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::Kotlin, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::Kotlin, &mut uir);
+        Ok(uir)
+    }
+    
+    fn convert_generic(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: self.map_node_type(node.kind()),
+            name: Some(node.kind().to_string()),
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::Kotlin, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::Kotlin, &mut uir);
+        Ok(uir)
+    }
+    
+    // Helper methods
+    fn convert_children(&self, node: Node, source: &str) -> Result<Vec<UIRNode>> {
+        let mut children = Vec::new();
+        
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            // Comments between statements and declarations are kept; those inside expressions are not
+            if comments::is_comment(child) && matches!(node.kind(), "source_file" | "class_body" | "statements") {
+                children.push(comments::comment_node(child, source, &CoalesceLanguage::Kotlin));
+                continue;
+            }
+            if child.is_extra() || child.kind() == "package_header" || child.kind() == "modifiers" {
+                continue;
+            }
+            if child.kind() == "parenthesized_expression" {
+                let mut inner = self.convert_children(child, source)?;
+                if let [expression] = inner.as_mut_slice() {
+                    mark_grouped(expression);
+                }
+                children.extend(inner);
+            } else if TRANSPARENT_KINDS.contains(&child.kind()) {
+                children.extend(self.convert_children(child, source)?);
+            } else {
+                children.push(self.ast_to_uir(child, source)?);
+            }
+        }
+        
+        Ok(children)
+    }
+    
+    /// Record modifiers (`data`, `suspend`, `private`, ...) as semantic tags and return the
+    /// annotations among them
+    fn attach_modifiers(&self, metadata: &mut Metadata, node: Node, source: &str) -> Vec<Attribute> {
+        let Some(modifiers) = self.find_child_by_kind(node, "modifiers") else {
+            return Vec::new();
+        };
+        
+        let mut annotations = Vec::new();
+        let mut cursor = modifiers.walk();
+        for modifier in modifiers.named_children(&mut cursor) {
+            let text = self.node_text(modifier, source).trim().to_string();
+            if modifier.kind() == "annotation" {
+                annotations.push(Attribute::parse(text.trim_start_matches('@')));
+                continue;
+            }
+            if text == "suspend" {
+                metadata.semantic_tags.push("async".to_string());
+            }
+            metadata.semantic_tags.push(text);
+        }
+        annotations
+    }
+    
+    fn annotate_type(&self, metadata: &mut Metadata, type_node: Node, source: &str) {
+        metadata.annotations.insert(
+            "type".to_string(),
+            Value::String(self.node_text(type_node, source).to_string()),
+        );
+        if type_node.kind() == "nullable_type" {
+            metadata.annotations.insert("nullable".to_string(), Value::Bool(true));
+        }
+    }
+    
+    fn has_safe_call(&self, node: Node) -> bool {
+        node.kind() == "navigation_expression"
+            && self.find_child_by_kind(node, "navigation_suffix").is_some_and(|s| self.has_keyword(s, "?."))
+    }
+    
+    fn find_type<'a>(&self, node: Node<'a>) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let found = node.named_children(&mut cursor).find(|c| TYPE_KINDS.contains(&c.kind()));
+        found
+    }
+    
+    fn child_after_keyword<'a>(&self, node: Node<'a>, keyword: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor)
+            .skip_while(|c| c.kind() != keyword)
+            .nth(1);
+        found
+    }
+    
+    fn has_keyword(&self, node: Node, keyword: &str) -> bool {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).any(|child| !child.is_named() && child.kind() == keyword);
+        found
+    }
+    
+    fn node_text<'a>(&self, node: Node, source: &'a str) -> &'a str {
+        &source[node.byte_range()]
+    }
+    
+    fn find_child_by_kind<'a>(&self, node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).find(|c| c.kind() == kind);
+        found
+    }
+    
+    fn create_metadata(&self, node: Node, source: &str) -> Metadata {
+        let mut metadata = Metadata {
+            source_language: CoalesceLanguage::Kotlin,
+            ..Default::default()
+        };
+        metadata.semantic_tags.push(node.kind().to_string());
+        
+        let text = self.node_text(node, source);
+        if text.len() < 100 {
+            metadata.annotations.insert(
+                "original_text".to_string(),
+                Value::String(text.to_string()),
+            );
+        }
+        
+        metadata
+    }
+    
+    fn create_source_location(&self, node: Node) -> Option<SourceLocation> {
+        Some(SourceLocation {
+            file: String::new(),
+            start_line: node.start_position().row as u32 + 1,
+            end_line: node.end_position().row as u32 + 1,
+            start_column: node.start_position().column as u32,
+            end_column: node.end_position().column as u32,
+        })
+    }
+    
+    fn generate_node_id(&self, node: Node, source: &str) -> String {
+        let text = self.node_text(node, source);
+        
+        format!("{}_{}_{}_{}", node.kind(), node.start_position().row, node.start_position().column,
+                text.chars().take(20).collect::<String>()
+                    .replace(|c: char| !c.is_alphanumeric(), "_"))
+    }
+    
+    fn map_node_type(&self, kind: &str) -> NodeType {
+        match kind {
+            "catch_block" | "finally_block" => NodeType::ControlFlow(ControlFlowType::Try),
+            "when_subject" | "when_condition" | "navigation_suffix" => NodeType::Expression(ExpressionType::Variable),
+            "prefix_expression" => NodeType::Expression(ExpressionType::Arithmetic),
+            "infix_expression" => NodeType::Expression(ExpressionType::FunctionCall),
+            "variable_declaration" => NodeType::Variable,
+            _ => NodeType::Expression(ExpressionType::Literal), // Generic fallback
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_kotlin_data_class() {
+        let parser = KotlinParser::new().unwrap();
+        let source = "data class User(val name: String, var age: Int? = null) : Base()\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let class = &uir.children[0];
+        assert_eq!(class.node_type, NodeType::Class);
+        assert_eq!(class.name.as_deref(), Some("User"));
+        assert!(class.metadata.semantic_tags.contains(&"data_class".to_string()));
+        assert_eq!(class.metadata.dependencies, vec!["Base".to_string()]);
+        
+        let age = &class.children[1];
+        assert_eq!(age.name.as_deref(), Some("age"));
+        assert_eq!(age.metadata.annotations["nullable"], Value::Bool(true));
+        assert_eq!(age.metadata.annotations["default_value"], "null");
+    }
+    
+    #[test]
+    fn test_kotlin_extension_function() {
+        let parser = KotlinParser::new().unwrap();
+        let source = "fun String.shout(times: Int): String = this.repeat(times)\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let func = &uir.children[0];
+        assert_eq!(func.node_type, NodeType::Function);
+        assert_eq!(func.name.as_deref(), Some("shout"));
+        assert!(func.metadata.semantic_tags.contains(&"extension_function".to_string()));
+        assert_eq!(func.metadata.annotations["receiver_type"], "String");
+        assert_eq!(func.metadata.annotations["return_type"], "String");
+        assert_eq!(func.children[0].name.as_deref(), Some("times"));
+        assert_eq!(func.children[1].node_type, NodeType::Statement(StatementType::Return));
+    }
+    
+    #[test]
+    fn test_kotlin_null_safety_operators() {
+        let parser = KotlinParser::new().unwrap();
+        let source = "fun len(s: String?): Int {\n    return s?.length ?: 0\n}\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let ret = &uir.children[0].children[1];
+        assert_eq!(ret.node_type, NodeType::Statement(StatementType::Return));
+        
+        let elvis = &ret.children[0];
+        assert!(elvis.metadata.semantic_tags.contains(&"elvis".to_string()));
+        assert!(elvis.children[0].metadata.semantic_tags.contains(&"safe_call".to_string()));
+    }
+    
+    #[test]
+    fn test_kotlin_when_else_branch() {
+        let parser = KotlinParser::new().unwrap();
+        let source = "fun f(x: Int) {\n    when (x) {\n        1 -> println(1)\n        else -> {}\n    }\n}\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let when = &uir.children[0].children[1];
+        assert_eq!(when.node_type, NodeType::ControlFlow(ControlFlowType::Switch));
+        let otherwise = when.children.last().unwrap();
+        assert_eq!(otherwise.node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        assert_eq!(otherwise.metadata.annotations["original_text"], "else -> {}");
+    }
+    
+    #[test]
+    fn test_kotlin_non_ascii_string_literals() {
+        fn collect(node: &UIRNode, values: &mut Vec<coalesce_core::LiteralValue>) {
+            values.extend(node.literal.clone());
+            node.children.iter().for_each(|child| collect(child, values));
+        }
+        
+        let parser = KotlinParser::new().unwrap();
+        let uir = parser.parse("val label = \"€100\"\nval name = \"héllo\"\nval note = \"\"\"é\"\"\"\n").unwrap();
+        let mut values = Vec::new();
+        collect(&uir, &mut values);
+        assert_eq!(values, vec![
+            coalesce_core::LiteralValue::String("€100".to_string()),
+            coalesce_core::LiteralValue::String("héllo".to_string()),
+            coalesce_core::LiteralValue::String("é".to_string()),
+        ]);
+    }
+}
@@ -1,4 +1,4 @@
-use coalesce_core::{types::*, errors::*, traits::Parser};
+use coalesce_core::{types::*, errors::*, traits::{Parser, IncrementalParser}};
 
 mod javascript;
 mod c;
@@ -8,6 +8,24 @@ mod fsharp;
 mod vb;
 mod rust_parser;
 mod go;
+mod python;
+mod cobol;
+mod kotlin;
+mod erlang;
+mod elixir;
+mod bash;
+mod r;
+mod abap;
+mod rpg;
+mod assembly;
+mod operators;
+mod literals;
+mod closures;
+mod comments;
+mod declarators;
+mod recovery;
+mod incremental;
+mod grammar;
 
 pub use javascript::JavaScriptParser;
 pub use c::CParser;
@@ -17,6 +35,18 @@ pub use fsharp::FSharpParser;
 pub use vb::VisualBasicParser;
 pub use rust_parser::RustParser;
 pub use go::GoParser;
+pub use python::PythonParser;
+pub use cobol::CobolParser;
+pub use kotlin::KotlinParser;
+pub use erlang::ErlangParser;
+pub use elixir::ElixirParser;
+pub use bash::BashParser;
+pub use r::RParser;
+pub use abap::AbapParser;
+pub use rpg::RpgParser;
+pub use assembly::AssemblyParser;
+pub use incremental::SyntaxTree;
+pub use grammar::{GrammarConfig, GrammarRegistry, ExternalParser};
 
 // Language detection
 pub fn detect_language(source: &str, filename: Option<&str>) -> Language {
@@ -42,16 +72,65 @@ pub fn detect_language(source: &str, filename: Option<&str>) -> Language {
         if name.ends_with(".fs") || name.ends_with(".fsx") {
             return Language::FSharp;
         }
-        if name.ends_with(".vb") || name.ends_with(".bas") {
+        if name.ends_with(".vb") || name.ends_with(".bas") || name.ends_with(".frm") || name.ends_with(".cls") {
             return Language::VisualBasic;
         }
         if name.ends_with(".py") {
             return Language::Python;
         }
+        if name.ends_with(".kt") || name.ends_with(".kts") {
+            return Language::Kotlin;
+        }
+        if name.ends_with(".erl") || name.ends_with(".hrl") {
+            return Language::Erlang;
+        }
+        if name.ends_with(".ex") || name.ends_with(".exs") {
+            return Language::Elixir;
+        }
+        if name.ends_with(".sh") || name.ends_with(".bash") {
+            return Language::Bash;
+        }
+        if name.ends_with(".R") || name.ends_with(".r") || name.ends_with(".Rscript") {
+            return Language::R;
+        }
+        if name.ends_with(".abap") {
+            return Language::Abap;
+        }
+        if name.ends_with(".rpgle") || name.ends_with(".sqlrpgle") || name.ends_with(".rpg")
+            || name.ends_with(".RPGLE") || name.ends_with(".SQLRPGLE") {
+            return Language::Rpg;
+        }
+        if name.ends_with(".s") || name.ends_with(".S") || name.ends_with(".asm") {
+            return Language::Assembly;
+        }
+        if name.ends_with(".cob") || name.ends_with(".cbl") || name.ends_with(".cpy")
+            || name.ends_with(".COB") || name.ends_with(".CBL") || name.ends_with(".CPY") {
+            return Language::Cobol;
+        }
+    }
+    
+    // Extensionless scripts declare their interpreter in the shebang
+    if let Some(shebang) = source.lines().next().and_then(|l| l.strip_prefix("#!")) {
+        let interpreter = shebang.split_whitespace()
+            .map(|part| part.rsplit('/').next().unwrap_or(part))
+            .find(|part| *part != "env")
+            .unwrap_or("");
+        if matches!(interpreter, "sh" | "bash" | "zsh" | "ksh" | "dash") {
+            return Language::Bash;
+        }
+        if interpreter == "Rscript" {
+            return Language::R;
+        }
     }
     
     // Fallback to content-based detection (prioritize system languages)
-    if source.contains("using System") || source.contains("namespace ") && source.contains("class ") && source.contains("public ") {
+    if source.contains("IDENTIFICATION DIVISION") || source.contains("PROCEDURE DIVISION") {
+        Language::Cobol
+    } else if source.contains("START-OF-SELECTION") || source.contains("ENDFORM.") {
+        Language::Abap
+    } else if source.trim_start().to_ascii_uppercase().starts_with("**FREE") || source.contains("dcl-proc") || source.contains("DCL-PROC") {
+        Language::Rpg
+    } else if source.contains("using System") || source.contains("namespace ") && source.contains("class ") && source.contains("public ") {
         Language::CSharp
     } else if source.contains("let ") && (source.contains("=") || source.contains("->")) && (source.contains("module ") || source.contains("type ")) {
         Language::FSharp
@@ -85,16 +164,16 @@ pub fn create_parser(language: Language) -> Result<Box<dyn Parser>> {
         Language::VisualBasic => Ok(Box::new(VisualBasicParser::new()?)),
         Language::Rust => Ok(Box::new(RustParser::new()?)),
         Language::Go => Ok(Box::new(GoParser::new()?)),
-        Language::Python => Err(CoalesceError::ParseError {
-            message: "Python parser not yet implemented".to_string(),
-            line: 0,
-            column: 0,
-        }),
-        Language::Cobol => Err(CoalesceError::ParseError {
-            message: "COBOL parser not yet implemented".to_string(),
-            line: 0,
-            column: 0,
-        }),
+        Language::Python => Ok(Box::new(PythonParser::new()?)),
+        Language::Cobol => Ok(Box::new(CobolParser::new()?)),
+        Language::Kotlin => Ok(Box::new(KotlinParser::new()?)),
+        Language::Erlang => Ok(Box::new(ErlangParser::new()?)),
+        Language::Elixir => Ok(Box::new(ElixirParser::new()?)),
+        Language::Bash => Ok(Box::new(BashParser::new()?)),
+        Language::R => Ok(Box::new(RParser::new()?)),
+        Language::Abap => Ok(Box::new(AbapParser::new()?)),
+        Language::Rpg => Ok(Box::new(RpgParser::new()?)),
+        Language::Assembly => Ok(Box::new(AssemblyParser::new()?)),
         _ => Err(CoalesceError::ParseError {
             message: "Unsupported language".to_string(),
             line: 0,
@@ -103,6 +182,20 @@ pub fn create_parser(language: Language) -> Result<Box<dyn Parser>> {
     }
 }
 
+/// A parser that can reparse after edits, for languages whose parsers keep a syntax tree
+pub fn create_incremental_parser(language: Language) -> Result<Box<dyn IncrementalParser<Tree = SyntaxTree>>> {
+    match language {
+        Language::JavaScript => Ok(Box::new(JavaScriptParser::new()?)),
+        Language::C => Ok(Box::new(CParser::new()?)),
+        Language::Cpp => Ok(Box::new(CppParser::new()?)),
+        Language::CSharp => Ok(Box::new(CSharpParser::new()?)),
+        Language::Rust => Ok(Box::new(RustParser::new()?)),
+        Language::Go => Ok(Box::new(GoParser::new()?)),
+        Language::Python => Ok(Box::new(PythonParser::new()?)),
+        language => Err(CoalesceError::UnsupportedLanguage(language)),
+    }
+}
+
 // Legacy stub functions for backward compatibility
 pub fn parse_javascript(source: &str) -> Result<UIRNode> {
     let parser = JavaScriptParser::new()?;
@@ -145,21 +238,51 @@ pub fn parse_vb(source: &str) -> Result<UIRNode> {
 }
 
 pub fn parse_python(source: &str) -> Result<UIRNode> {
-    // Legacy stub - will be replaced with real parser
-    if source.contains("def ") {
-        Ok(UIRNode {
-            id: "python_func".to_string(),
-            node_type: NodeType::Function,
-            name: Some("extracted_function".to_string()),
-            children: vec![],
-            metadata: Metadata::default(),
-            source_location: None,
-        })
-    } else {
-        Err(CoalesceError::ParseError {
-            message: "No Python functions found".to_string(),
-            line: 0,
-            column: 0,
-        })
-    }
+    let parser = PythonParser::new()?;
+    parser.parse(source)
+}
+
+pub fn parse_cobol(source: &str) -> Result<UIRNode> {
+    let parser = CobolParser::new()?;
+    parser.parse(source)
+}
+
+pub fn parse_kotlin(source: &str) -> Result<UIRNode> {
+    let parser = KotlinParser::new()?;
+    parser.parse(source)
+}
+
+pub fn parse_erlang(source: &str) -> Result<UIRNode> {
+    let parser = ErlangParser::new()?;
+    parser.parse(source)
+}
+
+pub fn parse_elixir(source: &str) -> Result<UIRNode> {
+    let parser = ElixirParser::new()?;
+    parser.parse(source)
+}
+
+pub fn parse_bash(source: &str) -> Result<UIRNode> {
+    let parser = BashParser::new()?;
+    parser.parse(source)
+}
+
+pub fn parse_r(source: &str) -> Result<UIRNode> {
+    let parser = RParser::new()?;
+    parser.parse(source)
+}
+
+pub fn parse_abap(source: &str) -> Result<UIRNode> {
+    let parser = AbapParser::new()?;
+    parser.parse(source)
+}
+
+pub fn parse_rpg(source: &str) -> Result<UIRNode> {
+    let parser = RpgParser::new()?;
+    parser.parse(source)
+}
+
+pub fn parse_assembly(source: &str) -> Result<UIRNode> {
+    let parser = AssemblyParser::new()?;
+    parser.parse(source)
 }
@@ -0,0 +1,12 @@
+// Literal values shared by the tree-sitter parsers
+use coalesce_core::{Language, LiteralValue, UIRNode, NodeType, ExpressionType};
+use tree_sitter::Node;
+
+/// Record the value of a literal node on `uir`; grammars fall back to literal nodes for
+/// kinds they don't map, so the text must parse as a literal of `language`
+pub(crate) fn annotate_literal(node: Node, source: &str, language: &Language, uir: &mut UIRNode) {
+    if uir.node_type != NodeType::Expression(ExpressionType::Literal) || node.kind().contains("comment") {
+        return;
+    }
+    uir.literal = node.utf8_text(source.as_bytes()).ok().and_then(|text| LiteralValue::parse(text, language));
+}
@@ -0,0 +1,129 @@
+// Operator extraction shared by the tree-sitter parsers
+//
+// Grammars expose an expression's operator differently: an `operator` field, bare anonymous
+// tokens between the operands, or a small named node such as Kotlin's `unary_prefix`.
+//
+// Grouping parentheses become a `parenthesized` tag on the expression they wrap, so the
+// operands of an expression are the expressions themselves and generators know which
+// groupings the source spelled out.
+use coalesce_core::{Language, Operator, UIRNode, NodeType, ExpressionType, Walk, walk_mut};
+use tree_sitter::Node;
+
+/// Whether a node type carries an operator
+pub(crate) fn takes_operator(node_type: &NodeType) -> bool {
+    matches!(node_type, NodeType::Expression(ExpressionType::Arithmetic | ExpressionType::Comparison | ExpressionType::Logical))
+}
+
+/// The operator of an expression node; unary when it has a single operand
+pub(crate) fn expression_operator(node: Node, source: &str, language: &Language) -> Option<Operator> {
+    let text = |n: &Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.children(&mut cursor).collect();
+    // Leaf tokens such as Kotlin's `unary_prefix` or `in_operator`, not expressions like
+    // Python's `binary_operator`
+    let is_operator_node = |n: &Node| {
+        n.is_named() && n.named_child_count() == 0 && (n.kind().contains("operator") || n.kind().ends_with("_prefix"))
+    };
+    
+    let symbol = match node.child_by_field_name("operator") {
+        Some(operator) => text(&operator),
+        None => {
+            let tokens: Vec<String> = children.iter()
+                .filter(|c| !c.is_named() && !matches!(c.kind(), "(" | ")" | ","))
+                .map(text)
+                .collect();
+            if tokens.is_empty() {
+                children.iter().filter(|c| is_operator_node(c)).map(text).collect::<Vec<_>>().join(" ")
+            } else {
+                tokens.join(" ")
+            }
+        }
+    };
+    let operands = children.iter()
+        .filter(|c| c.is_named() && !is_operator_node(c) && c.kind() != "comment")
+        .count();
+    
+    if operands <= 1 {
+        Operator::unary(&symbol, language)
+    } else {
+        Operator::binary(&symbol, language)
+    }
+}
+
+/// The operator a compound assignment applies to its target: `-` of `n -= 1`, `**` of `n **= 2`;
+/// `None` for a plain `=` or `:=`
+pub(crate) fn assignment_operator(node: Node, source: &str, language: &Language) -> Option<Operator> {
+    let symbol = match node.child_by_field_name("operator") {
+        Some(operator) => operator.utf8_text(source.as_bytes()).ok()?,
+        None => {
+            let mut cursor = node.walk();
+            // A bare token, or one wrapped in a node like C#'s `assignment_operator`
+            let token = node.children(&mut cursor)
+                .find(|c| !c.is_named() && c.kind().ends_with('=') || c.kind() == "assignment_operator")?;
+            token.utf8_text(source.as_bytes()).ok()?
+        }
+    };
+    match symbol.trim().strip_suffix('=')? {
+        "" | ":" => None,
+        operator => Operator::binary(operator, language),
+    }
+}
+
+/// Record the operator of `node` on `uir`, refining a catch-all arithmetic node into a
+/// comparison or logical one; on an assignment, the operator of a compound one
+pub(crate) fn annotate_operator(node: Node, source: &str, language: &Language, uir: &mut UIRNode) {
+    if uir.node_type == NodeType::Expression(ExpressionType::Assignment) {
+        uir.operator = assignment_operator(node, source, language);
+        return;
+    }
+    if !takes_operator(&uir.node_type) {
+        return;
+    }
+    uir.operator = expression_operator(node, source, language);
+    if let (Some(operator), NodeType::Expression(ExpressionType::Arithmetic)) = (uir.operator, &uir.node_type) {
+        uir.node_type = NodeType::Expression(operator.expression_type());
+    }
+}
+
+/// Mark `uir` as written in parentheses
+pub(crate) fn mark_grouped(uir: &mut UIRNode) {
+    if !uir.metadata.semantic_tags.iter().any(|t| t == "parenthesized") {
+        uir.metadata.semantic_tags.push("parenthesized".to_string());
+    }
+}
+
+/// Replace each `parenthesized_expression` holding a single expression with that expression,
+/// marked grouped and keeping the tags recovery added to the parentheses
+pub(crate) fn unwrap_groups(root: &mut UIRNode) {
+    walk_mut(root, &mut |node: &mut UIRNode| {
+        for child in &mut node.children {
+            while let Some(index) = grouped_expression(child) {
+                let mut inner = child.children.remove(index);
+                for tag in child.metadata.semantic_tags.drain(1..) {
+                    if !inner.metadata.semantic_tags.contains(&tag) {
+                        inner.metadata.semantic_tags.push(tag);
+                    }
+                }
+                mark_grouped(&mut inner);
+                *child = inner;
+            }
+        }
+        Walk::Continue
+    });
+}
+
+/// The index of the expression inside a parenthesized expression, which besides it holds
+/// only the parentheses
+fn grouped_expression(node: &UIRNode) -> Option<usize> {
+    if node.metadata.semantic_tags.first().map(String::as_str) != Some("parenthesized_expression") {
+        return None;
+    }
+    let mut content = node.children.iter().enumerate()
+        .filter(|(_, child)| !matches!(child.metadata.semantic_tags.first().map(String::as_str), Some("(" | ")")));
+    match (content.next(), content.next()) {
+        (Some((index, child)), None) if matches!(child.node_type, NodeType::Expression(_)) => Some(index),
+        // Python's `b if a else c` is a conditional the parser keeps as control flow
+        (Some((index, child)), None) if child.metadata.semantic_tags.first().map(String::as_str) == Some("conditional_expression") => Some(index),
+        _ => None,
+    }
+}
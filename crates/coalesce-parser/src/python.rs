@@ -0,0 +1,948 @@
+use coalesce_core::{UIRNode, NodeType, Attribute, CaptureMode, ImportedSymbol, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LiteralValue, LoopType, ExpressionType, StatementType, Result, CoalesceError,
+                   Parser as CoalesceParser, IncrementalParser, IncrementalUpdate, TextEdit};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::closures;
+use crate::comments;
+use crate::operators::{annotate_operator, assignment_operator, unwrap_groups};
+use crate::literals::annotate_literal;
+use crate::incremental::SyntaxTree;
+use serde_json::Value;
+
+/// Python parser using tree-sitter
+pub struct PythonParser {
+}
+
+impl CoalesceParser for PythonParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::Python
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        // Create a new parser for this parse operation
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_python::language())
+            .map_err(|e| CoalesceError::ParseError {
+                message: format!("Failed to set Python language: {}", e),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let tree = parser.parse(source, None)
+            .ok_or_else(|| CoalesceError::ParseError {
+                message: "Failed to parse Python source".to_string(),
+                line: 0,
+                column: 0,
+            })?;
+        
+        self.convert_root(source, tree.root_node())
+    }
+}
+
+impl IncrementalParser for PythonParser {
+    type Tree = SyntaxTree;
+    
+    fn parse_tree(&self, source: &str) -> Result<(SyntaxTree, UIRNode)> {
+        let tree = SyntaxTree::parse(tree_sitter_python::language(), source)?;
+        let uir = self.convert_root(source, tree.root_node())?;
+        Ok((tree, uir))
+    }
+    
+    fn reparse(&self, previous: &SyntaxTree, edits: &[TextEdit]) -> Result<IncrementalUpdate<SyntaxTree>> {
+        previous.reparse(edits, |source, node| self.ast_to_uir(node, source))
+    }
+    
+    fn to_uir(&self, tree: &SyntaxTree) -> Result<UIRNode> {
+        self.convert_root(tree.source(), tree.root_node())
+    }
+}
+
+impl PythonParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    fn convert_root(&self, source: &str, root: Node) -> Result<UIRNode> {
+        let mut uir = self.ast_to_uir(root, source)?;
+        recovery::summarize(source, root, &mut uir);
+        unwrap_groups(&mut uir);
+        closures::annotate_captures(&mut uir);
+        uir.make_ids_unique();
+        comments::attach_comments(&mut uir);
+        uir.debug_validate();
+        Ok(uir)
+    }
+    
+    fn ast_to_uir(&self, node: Node, source: &str) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::Python, |child| self.ast_to_uir(child, source));
+        }
+        
+        match node.kind() {
+            "module" => self.convert_module(node, source),
+            "function_definition" => self.convert_function_definition(node, source, &[]),
+            "class_definition" => self.convert_class_definition(node, source, &[]),
+            "decorated_definition" => self.convert_decorated_definition(node, source),
+            "lambda" => self.convert_lambda(node, source),
+            "list_comprehension" | "set_comprehension" | "dictionary_comprehension" | "generator_expression" => {
+                self.convert_comprehension(node, source)
+            }
+            "assignment" | "augmented_assignment" => self.convert_assignment(node, source),
+            "return_statement" => self.convert_simple(node, source, NodeType::Statement(StatementType::Return)),
+            "raise_statement" => self.convert_simple(node, source, NodeType::Statement(StatementType::Throw)),
+            "break_statement" => self.convert_simple(node, source, NodeType::Statement(StatementType::Break)),
+            "continue_statement" => self.convert_simple(node, source, NodeType::Statement(StatementType::Continue)),
+            "expression_statement" => self.convert_simple(node, source, NodeType::Statement(StatementType::Expression)),
+            "if_statement" | "elif_clause" | "conditional_expression" => {
+                self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Conditional))
+            }
+            "for_statement" => {
+                let uir = self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)))?;
+                Ok(self.mark_async(node, uir))
+            }
+            "with_statement" => {
+                let uir = self.convert_generic(node, source)?;
+                Ok(self.mark_async(node, uir))
+            }
+            "while_statement" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While))),
+            "try_statement" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Try)),
+            "match_statement" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Switch)),
+            "call" => self.convert_simple(node, source, NodeType::Expression(ExpressionType::FunctionCall)),
+            "await" => self.convert_simple(node, source, NodeType::Expression(ExpressionType::Await)),
+            "yield" => self.convert_yield(node, source),
+            "binary_operator" | "unary_operator" => self.convert_simple(node, source, NodeType::Expression(ExpressionType::Arithmetic)),
+            "comparison_operator" => self.convert_simple(node, source, NodeType::Expression(ExpressionType::Comparison)),
+            "boolean_operator" | "not_operator" => self.convert_simple(node, source, NodeType::Expression(ExpressionType::Logical)),
+            "identifier" => self.convert_identifier(node, source),
+            "integer" | "float" | "string" | "true" | "false" | "none" => {
+                self.convert_simple(node, source, NodeType::Expression(ExpressionType::Literal))
+            }
+            _ => self.convert_generic(node, source),
+        }
+    }
+    
+    fn convert_module(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = self.convert_children(node, source)?;
+        let documentation = take_docstring(&mut children);
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Module,
+            name: Some("python_program".to_string()),
+            children,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation,
+        })
+    }
+    
+    fn convert_function_definition(&self, node: Node, source: &str, decorators: &[String]) -> Result<UIRNode> {
+        let name_node = node.child_by_field_name("name")
+            .ok_or_else(|| CoalesceError::ParseError {
+                message: "Function missing name".to_string(),
+                line: node.start_position().row as u32 + 1,
+                column: node.start_position().column as u32,
+            })?;
+        
+        let mut children = Vec::new();
+        if let Some(params_node) = node.child_by_field_name("parameters") {
+            children.extend(self.extract_parameters(params_node, source));
+        }
+        children.extend(self.header_comments(node, source));
+        let mut documentation = None;
+        if let Some(body_node) = node.child_by_field_name("body") {
+            let mut body = self.convert_children(body_node, source)?;
+            documentation = take_docstring(&mut body);
+            children.extend(body);
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        if let Some(return_type) = node.child_by_field_name("return_type") {
+            metadata.annotations.insert(
+                "return_type".to_string(),
+                Value::String(self.node_text(return_type, source).to_string()),
+            );
+        }
+        let is_async = self.has_keyword(node, "async");
+        if is_async {
+            metadata.semantic_tags.push("async".to_string());
+        }
+        self.attach_decorators(&mut metadata, decorators);
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Function,
+            name: Some(self.node_text(name_node, source).to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async,
+            attributes: decorators.iter().map(|d| Attribute::parse(d)).collect(),
+            ownership: None,
+            documentation,
+        })
+    }
+    
+    fn convert_class_definition(&self, node: Node, source: &str, decorators: &[String]) -> Result<UIRNode> {
+        let name_node = node.child_by_field_name("name")
+            .ok_or_else(|| CoalesceError::ParseError {
+                message: "Class missing name".to_string(),
+                line: node.start_position().row as u32 + 1,
+                column: node.start_position().column as u32,
+            })?;
+        
+        let mut children = self.header_comments(node, source);
+        let mut documentation = None;
+        if let Some(body_node) = node.child_by_field_name("body") {
+            let mut body = self.convert_children(body_node, source)?;
+            documentation = take_docstring(&mut body);
+            children.extend(body);
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        if let Some(superclasses) = node.child_by_field_name("superclasses") {
+            let mut cursor = superclasses.walk();
+            for base in superclasses.named_children(&mut cursor) {
+                metadata.dependencies.push(self.node_text(base, source).to_string());
+            }
+        }
+        self.attach_decorators(&mut metadata, decorators);
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Class,
+            name: Some(self.node_text(name_node, source).to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: decorators.iter().map(|d| Attribute::parse(d)).collect(),
+            ownership: None,
+            documentation,
+        })
+    }
+    
+    fn convert_decorated_definition(&self, node: Node, source: &str) -> Result<UIRNode> {
+        // Decorators carry no UIR of their own, they are recorded on the decorated definition
+        let decorators: Vec<String> = self.children_by_kind(node, "decorator")
+            .into_iter()
+            .map(|d| self.node_text(d, source).trim_start_matches('@').trim().to_string())
+            .collect();
+        
+        match node.child_by_field_name("definition") {
+            Some(def) if def.kind() == "function_definition" => self.convert_function_definition(def, source, &decorators),
+            Some(def) if def.kind() == "class_definition" => self.convert_class_definition(def, source, &decorators),
+            _ => self.convert_generic(node, source),
+        }
+    }
+    
+    fn convert_lambda(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        if let Some(params_node) = node.child_by_field_name("parameters") {
+            children.extend(self.extract_parameters(params_node, source));
+        }
+        if let Some(body_node) = node.child_by_field_name("body") {
+            children.push(self.ast_to_uir(body_node, source)?);
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: closures::closure(CaptureMode::ByReference),
+            name: Some("lambda".to_string()),
+            children,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_comprehension(&self, node: Node, source: &str) -> Result<UIRNode> {
+        // A comprehension is an inline for-each loop: body expression, then for/if clauses
+        let mut children = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "for_in_clause" => {
+                    let mut clause = self.convert_generic(child, source)?;
+                    clause.node_type = NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach));
+                    children.push(clause);
+                }
+                "if_clause" => {
+                    let mut clause = self.convert_generic(child, source)?;
+                    clause.node_type = NodeType::ControlFlow(ControlFlowType::Conditional);
+                    children.push(clause);
+                }
+                "comment" => {}
+                _ => children.push(self.ast_to_uir(child, source)?),
+            }
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.semantic_tags.push("comprehension".to_string());
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)),
+            name: Some(node.kind().to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    /// `import a.b, c as d` gives an import per module, `from m import x, y as z` a single
+    /// import of `m` with its symbols
+    fn convert_import(&self, node: Node, source: &str) -> Vec<UIRNode> {
+        let mut cursor = node.walk();
+        let names: Vec<Node> = node.children_by_field_name("name", &mut cursor).collect();
+        if node.kind() == "import_statement" {
+            return names.into_iter()
+                .map(|name| {
+                    let module = ImportedSymbol::parse(self.node_text(name, source));
+                    self.import_node(node, name, source, module.name, module.alias, Vec::new())
+                })
+                .collect();
+        }
+        
+        let path = node.child_by_field_name("module_name")
+            .map(|module| self.node_text(module, source).to_string())
+            .unwrap_or_else(|| "__future__".to_string());
+        let mut symbols: Vec<ImportedSymbol> = names.into_iter()
+            .map(|name| ImportedSymbol::parse(self.node_text(name, source)))
+            .collect();
+        if self.find_child_by_kind(node, "wildcard_import").is_some() {
+            symbols.push(ImportedSymbol::new("*"));
+        }
+        vec![self.import_node(node, node, source, path, None, symbols)]
+    }
+    
+    fn import_node(&self, statement: Node, node: Node, source: &str, path: String, alias: Option<String>, symbols: Vec<ImportedSymbol>) -> UIRNode {
+        let mut metadata = self.create_metadata(statement, source);
+        metadata.dependencies.push(path.clone());
+        
+        UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Import { path: path.clone(), alias, symbols },
+            name: Some(path),
+            children: vec![],
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+    
+    fn convert_assignment(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        if let Some(left) = node.child_by_field_name("left") {
+            let mut target = self.ast_to_uir(left, source)?;
+            if left.kind() == "identifier" {
+                target.node_type = NodeType::Variable;
+            }
+            children.push(target);
+        }
+        if let Some(right) = node.child_by_field_name("right") {
+            children.push(self.ast_to_uir(right, source)?);
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        if let Some(type_node) = node.child_by_field_name("type") {
+            metadata.annotations.insert(
+                "type".to_string(),
+                Value::String(self.node_text(type_node, source).to_string()),
+            );
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Assignment),
+            name: None,
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            // `n -= 1` assigns `n - 1`
+            operator: assignment_operator(node, source, &CoalesceLanguage::Python),
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_identifier(&self, node: Node, source: &str) -> Result<UIRNode> {
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Variable),
+            name: Some(self.node_text(node, source).to_string()),
+            children: vec![],
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_simple(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: None,
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::Python, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::Python, &mut uir);
+        Ok(uir)
+    }
+    
+    /// `yield`, `yield value` and `yield from iterable`
+    fn convert_yield(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut uir = self.convert_simple(node, source, NodeType::Expression(ExpressionType::Yield))?;
+        if self.has_keyword(node, "from") {
+            uir.metadata.semantic_tags.push("yield_from".to_string());
+        }
+        Ok(uir)
+    }
+    
+    /// `async for` and `async with` are tagged like `async def`
+    fn mark_async(&self, node: Node, mut uir: UIRNode) -> UIRNode {
+        if self.has_keyword(node, "async") {
+            uir.is_async = true;
+            uir.metadata.semantic_tags.push("async".to_string());
+        }
+        uir
+    }
+    
+    fn convert_generic(&self, node: Node, source: &str) -> Result<UIRNode> {
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: self.map_node_type(node.kind()),
+            name: Some(node.kind().to_string()),
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    // Helper methods
+    fn convert_children(&self, node: Node, source: &str) -> Result<Vec<UIRNode>> {
+        let mut children = Vec::new();
+        
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if matches!(child.kind(), "import_statement" | "import_from_statement" | "future_import_statement") {
+                children.extend(self.convert_import(child, source));
+            } else if !child.is_extra() {
+                children.push(self.ast_to_uir(child, source)?);
+            } else if comments::is_comment(child) && matches!(node.kind(), "module" | "block") {
+                // Comments between statements are kept; those inside expressions are not
+                children.push(comments::comment_node(child, source, &CoalesceLanguage::Python));
+            }
+        }
+        
+        Ok(children)
+    }
+    
+    /// Comments between a definition's colon and its first statement, which the grammar places
+    /// on the definition rather than in its body
+    fn header_comments(&self, node: Node, source: &str) -> Vec<UIRNode> {
+        let mut cursor = node.walk();
+        let comments = node.children(&mut cursor)
+            .filter(|child| comments::is_comment(*child))
+            .map(|child| comments::comment_node(child, source, &CoalesceLanguage::Python))
+            .collect();
+        comments
+    }
+    
+    fn extract_parameters(&self, params_node: Node, source: &str) -> Vec<UIRNode> {
+        let mut parameters = Vec::new();
+        
+        let mut cursor = params_node.walk();
+        for param in params_node.named_children(&mut cursor) {
+            // `*args: int` nests the splat inside the typed parameter
+            let pattern = match param.kind() {
+                "typed_parameter" => param.named_child(0).filter(|child| child.kind().ends_with("_splat_pattern")),
+                "list_splat_pattern" | "dictionary_splat_pattern" => Some(param),
+                _ => None,
+            };
+            let (name_node, type_node, default_node) = match param.kind() {
+                "identifier" => (Some(param), None, None),
+                "typed_parameter" => (
+                    self.find_child_by_kind(pattern.unwrap_or(param), "identifier"),
+                    param.child_by_field_name("type"),
+                    None,
+                ),
+                "default_parameter" => (param.child_by_field_name("name"), None, param.child_by_field_name("value")),
+                "typed_default_parameter" => (
+                    param.child_by_field_name("name"),
+                    param.child_by_field_name("type"),
+                    param.child_by_field_name("value"),
+                ),
+                "list_splat_pattern" | "dictionary_splat_pattern" => (self.find_child_by_kind(param, "identifier"), None, None),
+                _ => (None, None, None),
+            };
+            
+            if let Some(name_node) = name_node {
+                let mut metadata = self.create_metadata(param, source);
+                metadata.semantic_tags.push("parameter".to_string());
+                if let Some(type_node) = type_node {
+                    metadata.annotations.insert(
+                        "type".to_string(),
+                        Value::String(self.node_text(type_node, source).to_string()),
+                    );
+                }
+                if let Some(default_node) = default_node {
+                    metadata.annotations.insert(
+                        "default_value".to_string(),
+                        Value::String(self.node_text(default_node, source).to_string()),
+                    );
+                }
+                // `*args` collects the extra positional arguments, `**kwargs` the keyword ones
+                if let Some(pattern) = pattern {
+                    let splat = if pattern.kind() == "list_splat_pattern" { "list" } else { "dictionary" };
+                    metadata.annotations.insert("splat".to_string(), Value::String(splat.to_string()));
+                }
+                
+                parameters.push(UIRNode {
+                    id: self.generate_node_id(param, source),
+                    node_type: NodeType::Variable,
+                    name: Some(self.node_text(name_node, source).to_string()),
+                    children: vec![],
+                    metadata,
+                    source_location: self.create_source_location(param),
+                    operator: None,
+                    literal: None,
+                    visibility: None,
+                    modifiers: Vec::new(),
+                    is_async: false,
+                    attributes: Vec::new(),
+                    ownership: None,
+                    documentation: None,
+                });
+            }
+        }
+        
+        parameters
+    }
+    
+    fn attach_decorators(&self, metadata: &mut Metadata, decorators: &[String]) {
+        if decorators.is_empty() {
+            return;
+        }
+        
+        for decorator in decorators {
+            metadata.semantic_tags.push(format!("decorator:{}", decorator));
+        }
+    }
+    
+    fn has_keyword(&self, node: Node, keyword: &str) -> bool {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).any(|child| !child.is_named() && child.kind() == keyword);
+        found
+    }
+    
+    fn node_text<'a>(&self, node: Node, source: &'a str) -> &'a str {
+        &source[node.byte_range()]
+    }
+    
+    fn children_by_kind<'a>(&self, node: Node<'a>, kind: &str) -> Vec<Node<'a>> {
+        let mut cursor = node.walk();
+        let children = node.children(&mut cursor).filter(|c| c.kind() == kind).collect();
+        children
+    }
+    
+    fn find_child_by_kind<'a>(&self, node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).find(|c| c.kind() == kind);
+        found
+    }
+    
+    fn create_metadata(&self, node: Node, source: &str) -> Metadata {
+        let mut metadata = Metadata {
+            source_language: CoalesceLanguage::Python,
+            ..Default::default()
+        };
+        metadata.semantic_tags.push(node.kind().to_string());
+        
+        let text = self.node_text(node, source);
+        if text.len() < 100 {
+            metadata.annotations.insert(
+                "original_text".to_string(),
+                Value::String(text.to_string()),
+            );
+        }
+        
+        metadata
+    }
+    
+    fn create_source_location(&self, node: Node) -> Option<SourceLocation> {
+        Some(SourceLocation {
+            file: String::new(),
+            start_line: node.start_position().row as u32 + 1,
+            end_line: node.end_position().row as u32 + 1,
+            start_column: node.start_position().column as u32,
+            end_column: node.end_position().column as u32,
+        })
+    }
+    
+    fn generate_node_id(&self, node: Node, source: &str) -> String {
+        let text = self.node_text(node, source);
+        
+        format!("{}_{}_{}_{}", node.kind(), node.start_position().row, node.start_position().column,
+                text.chars().take(20).collect::<String>()
+                    .replace(|c: char| !c.is_alphanumeric(), "_"))
+    }
+    
+    fn map_node_type(&self, kind: &str) -> NodeType {
+        match kind {
+            "block" | "else_clause" | "finally_clause" | "with_statement" => NodeType::Statement(StatementType::Expression),
+            "except_clause" => NodeType::ControlFlow(ControlFlowType::Try),
+            "attribute" => NodeType::Expression(ExpressionType::Variable),
+            _ => NodeType::Expression(ExpressionType::Literal), // Generic fallback
+        }
+    }
+}
+
+/// Remove the docstring, a string literal opening a module, class or function body, from
+/// `body` and return its text with the indentation of its continuation lines removed
+fn take_docstring(body: &mut Vec<UIRNode>) -> Option<String> {
+    let index = body.iter().position(|c| !matches!(c.node_type, NodeType::Comment { .. }))?;
+    let statement = &body[index];
+    if statement.node_type != NodeType::Statement(StatementType::Expression) {
+        return None;
+    }
+    let [string] = statement.children.as_slice() else { return None };
+    let Some(LiteralValue::String(text)) = &string.literal else { return None };
+    
+    let mut lines = text.lines();
+    let first = lines.next().unwrap_or("").trim();
+    let rest: Vec<&str> = lines.collect();
+    let margin = rest.iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut cleaned = vec![first.to_string()];
+    cleaned.extend(rest.iter().map(|l| l.get(margin..).unwrap_or("").trim_end().to_string()));
+    let documentation = cleaned.join("\n").trim_matches('\n').to_string();
+    body.remove(index);
+    Some(documentation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coalesce_core::{CommentStyle, Operator, ValidationRule};
+    
+    #[test]
+    fn test_simple_python_function() {
+        let parser = PythonParser::new().unwrap();
+        let source = "def add(a, b):\n    return a + b\n";
+        
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.node_type, NodeType::Module);
+        
+        let func = &uir.children[0];
+        assert_eq!(func.node_type, NodeType::Function);
+        assert_eq!(func.name.as_deref(), Some("add"));
+        assert_eq!(func.children.len(), 3);
+    }
+    
+    #[test]
+    fn test_python_splat_parameters() {
+        let parser = PythonParser::new().unwrap();
+        let source = "def f(a, b=2, *args: int, **kwargs):\n    pass\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let params: Vec<_> = uir.children[0].children.iter().filter(|c| c.node_type == NodeType::Variable).collect();
+        let names: Vec<_> = params.iter().map(|p| p.name.as_deref().unwrap()).collect();
+        assert_eq!(names, vec!["a", "b", "args", "kwargs"]);
+        assert_eq!(params[1].metadata.annotations["default_value"], "2");
+        assert!(!params[1].metadata.annotations.contains_key("splat"));
+        assert_eq!(params[2].metadata.annotations["splat"], "list");
+        assert_eq!(params[2].metadata.annotations["type"], "int");
+        assert_eq!(params[3].metadata.annotations["splat"], "dictionary");
+    }
+    
+    #[test]
+    fn test_python_decorated_class() {
+        let parser = PythonParser::new().unwrap();
+        let source = r#"
+@dataclass
+class Point(Base):
+    @staticmethod
+    def origin() -> "Point":
+        return Point(0, 0)
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let class = &uir.children[0];
+        assert_eq!(class.node_type, NodeType::Class);
+        assert!(class.metadata.semantic_tags.contains(&"decorator:dataclass".to_string()));
+        assert_eq!(class.metadata.dependencies, vec!["Base".to_string()]);
+        
+        let method = &class.children[0];
+        assert_eq!(method.name.as_deref(), Some("origin"));
+        assert!(method.metadata.semantic_tags.contains(&"decorator:staticmethod".to_string()));
+    }
+    
+    #[test]
+    fn test_python_comprehension() {
+        let parser = PythonParser::new().unwrap();
+        let source = "squares = [x * x for x in items if x > 0]\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let assignment = &uir.children[0].children[0];
+        assert_eq!(assignment.node_type, NodeType::Expression(ExpressionType::Assignment));
+        
+        let comprehension = &assignment.children[1];
+        assert_eq!(comprehension.node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)));
+        assert!(comprehension.metadata.semantic_tags.contains(&"comprehension".to_string()));
+    }
+    
+    #[test]
+    fn test_python_operator_kinds() {
+        let parser = PythonParser::new().unwrap();
+        let uir = parser.parse("ok = x is not None and -n // 2 not in seen\n").unwrap();
+        
+        let assignment = &uir.children[0].children[0];
+        let condition = &assignment.children[1];
+        assert_eq!(condition.operator, Some(Operator::And));
+        assert_eq!(condition.children[0].operator, Some(Operator::IsNot));
+        
+        let membership = &condition.children[1];
+        assert_eq!(membership.node_type, NodeType::Expression(ExpressionType::Comparison));
+        assert_eq!(membership.operator, Some(Operator::NotIn));
+        assert_eq!(membership.children[0].operator, Some(Operator::IntegerDivide));
+        assert_eq!(membership.children[0].children[0].operator, Some(Operator::Negate));
+    }
+    
+    #[test]
+    fn test_python_augmented_assignment_keeps_operator() {
+        let parser = PythonParser::new().unwrap();
+        let uir = parser.parse("n -= 1\nn **= 2\nm = n\n").unwrap();
+        
+        let operators: Vec<_> = uir.children.iter().map(|statement| statement.children[0].operator).collect();
+        assert_eq!(operators, vec![Some(Operator::Subtract), Some(Operator::Power), None]);
+        assert_eq!(uir.children[0].children[0].node_type, NodeType::Expression(ExpressionType::Assignment));
+    }
+    
+    #[test]
+    fn test_python_literal_values() {
+        fn collect(node: &UIRNode, values: &mut Vec<LiteralValue>) {
+            values.extend(node.literal.clone());
+            node.children.iter().for_each(|child| collect(child, values));
+        }
+        
+        let parser = PythonParser::new().unwrap();
+        let uir = parser.parse("values = [0x1F, 1_000, 2.5e3, 'it\\'s\\n', r'\\d', f'{x}', None, True]\n").unwrap();
+        let mut values = Vec::new();
+        collect(&uir, &mut values);
+        assert_eq!(values, vec![
+            LiteralValue::Int(31),
+            LiteralValue::Int(1000),
+            LiteralValue::Float(2500.0),
+            LiteralValue::String("it's\n".to_string()),
+            LiteralValue::String("\\d".to_string()),
+            LiteralValue::Null,
+            LiteralValue::Bool(true),
+        ]);
+    }
+    
+    #[test]
+    fn test_python_non_ascii_string_literals() {
+        fn collect(node: &UIRNode, values: &mut Vec<LiteralValue>) {
+            values.extend(node.literal.clone());
+            node.children.iter().for_each(|child| collect(child, values));
+        }
+        
+        let parser = PythonParser::new().unwrap();
+        let uir = parser.parse("price = \"€100\"\nname = 'héllo'\nnote = '''é'''\n").unwrap();
+        let mut values = Vec::new();
+        collect(&uir, &mut values);
+        assert_eq!(values, vec![
+            LiteralValue::String("€100".to_string()),
+            LiteralValue::String("héllo".to_string()),
+            LiteralValue::String("é".to_string()),
+        ]);
+    }
+    
+    #[test]
+    fn test_python_lambda_captures() {
+        let parser = PythonParser::new().unwrap();
+        let source = "LIMIT = 10\n\ndef scale(items, factor):\n    offset = 1\n    return map(lambda x: min(x * factor + offset, LIMIT), items)\n";
+        
+        let uir = parser.parse(source).unwrap();
+        fn find(node: &UIRNode) -> Option<&UIRNode> {
+            if matches!(node.node_type, NodeType::Closure { .. }) {
+                return Some(node);
+            }
+            node.children.iter().find_map(find)
+        }
+        
+        // The module-level LIMIT is a global, not a capture
+        let lambda = find(&uir).unwrap();
+        assert_eq!(lambda.node_type, NodeType::Closure {
+            captures: vec!["factor".to_string(), "offset".to_string()],
+            capture_mode: CaptureMode::ByReference,
+        });
+        assert_eq!(lambda.children[0].name.as_deref(), Some("x"));
+    }
+    
+    #[test]
+    fn test_python_comments_attach_to_declarations() {
+        let parser = PythonParser::new().unwrap();
+        let source = "# Adds one\ndef inc(x):\n    return x + 1\n\nclass A:\n    # about m\n    def m(self):\n        pass\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let function = uir.children.iter().find(|c| c.name.as_deref() == Some("inc")).unwrap();
+        assert_eq!(uir.children[0].node_type, NodeType::Comment {
+            style: CommentStyle::Line,
+            text: "Adds one".to_string(),
+            attached_to: Some(function.id.clone()),
+        });
+        
+        // The grammar puts a comment before a class's first statement on the class itself
+        let class = uir.children.iter().find(|c| c.node_type == NodeType::Class).unwrap();
+        let method = class.children.iter().find(|c| c.name.as_deref() == Some("m")).unwrap();
+        assert!(matches!(&class.children[0].node_type,
+            NodeType::Comment { text, attached_to: Some(target), .. } if text == "about m" && *target == method.id));
+    }
+    
+    #[test]
+    fn test_python_tree_validation() {
+        let parser = PythonParser::new().unwrap();
+        let mut uir = parser.parse("def f(a):\n    while a:\n        a = a - 1\n    return a\n").unwrap();
+        assert!(uir.validate().is_ok());
+        
+        // Move the parameter after the body, drop the loop's condition and reuse its id
+        let function = &mut uir.children[0];
+        let parameter = function.children.remove(0);
+        function.children.push(parameter);
+        function.children[0].children.retain(|c| !matches!(c.node_type, NodeType::Expression(_)));
+        function.children[1].id = function.children[0].id.clone();
+        
+        let rules: Vec<ValidationRule> = uir.validate().unwrap_err().into_iter().map(|v| v.rule).collect();
+        assert_eq!(rules, vec![
+            ValidationRule::ParameterAfterBody,
+            ValidationRule::MissingCondition,
+            ValidationRule::DuplicateId,
+        ]);
+    }
+    
+    #[test]
+    fn test_python_docstrings() {
+        let parser = PythonParser::new().unwrap();
+        let source = "\"\"\"Shapes.\"\"\"\n\nclass Square:\n    \"\"\"A square.\"\"\"\n\n    def area(self):\n        \"\"\"Area of the square.\n\n        Sides are equal.\n        \"\"\"\n        return self.side * self.side\n";
+        
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.documentation.as_deref(), Some("Shapes."));
+        let class = uir.children.iter().find(|c| c.node_type == NodeType::Class).unwrap();
+        assert_eq!(class.documentation.as_deref(), Some("A square."));
+        
+        // The docstring leaves the body, and its continuation lines lose their indentation
+        let method = class.children.iter().find(|c| c.node_type == NodeType::Function).unwrap();
+        assert_eq!(method.documentation.as_deref(), Some("Area of the square.\n\nSides are equal."));
+        let body: Vec<_> = method.children.iter().filter(|c| c.node_type != NodeType::Variable).collect();
+        assert_eq!(body.len(), 1);
+        assert_eq!(body[0].node_type, NodeType::Statement(StatementType::Return));
+    }
+    
+    #[test]
+    fn test_python_decorator_arguments() {
+        let parser = PythonParser::new().unwrap();
+        let source = "@app.route(\"/users/<id>\", methods=[\"GET\", \"POST\"])\n@login_required\ndef show(id):\n    return id\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let function = &uir.children[0];
+        assert_eq!(function.attributes, vec![
+            Attribute {
+                name: "app.route".to_string(),
+                arguments: vec!["\"/users/<id>\"".to_string(), "methods=[\"GET\", \"POST\"]".to_string()],
+            },
+            Attribute { name: "login_required".to_string(), arguments: Vec::new() },
+        ]);
+    }
+    
+    #[test]
+    fn test_python_imports() {
+        let parser = PythonParser::new().unwrap();
+        let source = "import os.path as osp, sys\nfrom django.db import models, transaction as tx\nfrom .utils import *\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let imports: Vec<&NodeType> = uir.children.iter().map(|c| &c.node_type).collect();
+        assert_eq!(imports, vec![
+            &NodeType::Import { path: "os.path".to_string(), alias: Some("osp".to_string()), symbols: Vec::new() },
+            &NodeType::Import { path: "sys".to_string(), alias: None, symbols: Vec::new() },
+            &NodeType::Import {
+                path: "django.db".to_string(),
+                alias: None,
+                symbols: vec![ImportedSymbol::new("models"), ImportedSymbol { name: "transaction".to_string(), alias: Some("tx".to_string()) }],
+            },
+            &NodeType::Import { path: ".utils".to_string(), alias: None, symbols: vec![ImportedSymbol::new("*")] },
+        ]);
+    }
+}
@@ -0,0 +1,745 @@
+use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, StatementType, Result, CoalesceError,
+                   Parser as CoalesceParser, Operator};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::operators::{annotate_operator, unwrap_groups};
+use crate::literals::annotate_literal;
+use serde_json::Value;
+
+/// Calls that load another package or script
+const IMPORT_FUNCTIONS: &[&str] = &["library", "require", "requireNamespace", "source"];
+
+/// dplyr/tidyr/readr verbs and base data-frame constructors, tagged for library mapping
+const DATA_FRAME_VERBS: &[&str] = &[
+    "filter", "select", "mutate", "transmute", "arrange", "group_by", "ungroup", "summarise", "summarize",
+    "rename", "distinct", "count", "left_join", "right_join", "inner_join", "full_join", "bind_rows",
+    "pivot_longer", "pivot_wider", "read_csv", "write_csv", "read.csv", "write.csv", "data.frame",
+    "tibble", "merge", "aggregate", "subset",
+];
+
+/// R parser using tree-sitter-r
+pub struct RParser {
+}
+
+impl CoalesceParser for RParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::R
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        // Create a new parser for this parse operation
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_r::language())
+            .map_err(|e| CoalesceError::ParseError {
+                message: format!("Failed to set R language: {}", e),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let tree = parser.parse(source, None)
+            .ok_or_else(|| CoalesceError::ParseError {
+                message: "Failed to parse R source".to_string(),
+                line: 0,
+                column: 0,
+            })?;
+        
+        let mut uir = self.ast_to_uir(tree.root_node(), source)?;
+        recovery::summarize(source, tree.root_node(), &mut uir);
+        unwrap_groups(&mut uir);
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
+    }
+}
+
+impl RParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    fn ast_to_uir(&self, node: Node, source: &str) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::R, |child| self.ast_to_uir(child, source));
+        }
+        
+        match node.kind() {
+            "program" => self.convert_program(node, source),
+            "left_assignment" | "left_assignment2" | "equals_assignment" | "super_assignment"
+            | "right_assignment" | "super_right_assignment" => self.convert_assignment(node, source),
+            "function_definition" | "lambda_function" => self.convert_function(node, source, None),
+            "call" => self.convert_call(node, source),
+            "binary" => self.convert_binary(node, source),
+            "pipe" => {
+                let mut uir = self.convert_simple(node, source, NodeType::Expression(ExpressionType::FunctionCall))?;
+                uir.name = Some("pipe".to_string());
+                uir.metadata.semantic_tags.push("pipe".to_string());
+                uir.metadata.annotations.insert("operator".to_string(), Value::String("|>".to_string()));
+                Ok(uir)
+            }
+            "unary" => self.convert_unary(node, source),
+            "if" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Conditional)),
+            "switch" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Switch)),
+            "for" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach))),
+            "while" => self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While))),
+            "repeat" => {
+                let mut uir = self.convert_simple(node, source, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)))?;
+                uir.metadata.semantic_tags.push("infinite_loop".to_string());
+                Ok(uir)
+            }
+            "break" => self.convert_simple(node, source, NodeType::Statement(StatementType::Break)),
+            "next" => self.convert_simple(node, source, NodeType::Statement(StatementType::Continue)),
+            "dollar" | "slot" | "subset" | "subset2" => self.convert_member_access(node, source),
+            "identifier" => self.convert_named_leaf(node, source, NodeType::Expression(ExpressionType::Variable)),
+            "string" | "float" | "integer" | "complex" | "true" | "false" | "null" | "na" | "nan" | "inf" => {
+                self.convert_named_leaf(node, source, NodeType::Expression(ExpressionType::Literal))
+            }
+            _ => self.convert_generic(node, source),
+        }
+    }
+    
+    fn convert_program(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let children = self.convert_children(node, source)?;
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.dependencies = self.collect_dependencies(&children);
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Module,
+            name: Some("r_program".to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_assignment(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let target = node.child_by_field_name("name");
+        let value = node.child_by_field_name("value");
+        
+        // `f <- function(x) ...` is R's only way to name a function
+        if let (Some(target), Some(value)) = (target, value) {
+            if target.kind() == "identifier" && matches!(value.kind(), "function_definition" | "lambda_function") {
+                return self.convert_function(value, source, Some(self.node_text(target, source).to_string()));
+            }
+        }
+        
+        let mut children = Vec::new();
+        if let Some(target) = target {
+            children.push(self.ast_to_uir(target, source)?);
+        }
+        if let Some(value) = value {
+            children.push(self.ast_to_uir(value, source)?);
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        if matches!(node.kind(), "super_assignment" | "super_right_assignment") {
+            metadata.semantic_tags.push("global_assignment".to_string());
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Assignment),
+            name: target.filter(|t| t.kind() == "identifier").map(|t| self.node_text(t, source).to_string()),
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_function(&self, node: Node, source: &str, name: Option<String>) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        if let Some(params) = self.find_child_by_kind(node, "formal_parameters") {
+            children.extend(self.extract_parameters(params, source)?);
+        }
+        
+        // The body is the expression following the parameter list
+        let mut cursor = node.walk();
+        let body = node.named_children(&mut cursor)
+            .filter(|c| !c.is_extra())
+            .find(|c| c.kind() != "formal_parameters");
+        if let Some(body) = body {
+            let mut statements = if body.kind() == "brace_list" {
+                self.convert_children(body, source)?
+            } else {
+                vec![self.ast_to_uir(body, source)?]
+            };
+            
+            // The value of the last expression is returned implicitly
+            if let Some(last) = statements.pop() {
+                statements.push(self.implicit_return(last));
+            }
+            children.extend(statements);
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        if name.is_none() || node.kind() == "lambda_function" {
+            metadata.semantic_tags.push("lambda".to_string());
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Function,
+            name,
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn implicit_return(&self, node: UIRNode) -> UIRNode {
+        if !matches!(node.node_type, NodeType::Expression(_)) {
+            return node;
+        }
+        
+        let mut metadata = node.metadata.clone();
+        metadata.semantic_tags = vec!["implicit_return".to_string()];
+        
+        UIRNode {
+            id: format!("return_{}", node.id),
+            node_type: NodeType::Statement(StatementType::Return),
+            name: None,
+            source_location: node.source_location.clone(),
+            children: vec![node],
+            metadata,
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+    
+    fn extract_parameters(&self, params: Node, source: &str) -> Result<Vec<UIRNode>> {
+        let mut parameters = Vec::new();
+        
+        for (name_node, default_node) in self.split_arguments(params) {
+            let Some(name_node) = name_node.or(default_node) else {
+                continue;
+            };
+            let default_node = default_node.filter(|d| d.id() != name_node.id());
+            
+            let mut metadata = self.create_metadata(name_node, source);
+            metadata.semantic_tags.push("parameter".to_string());
+            if name_node.kind() == "dots" {
+                metadata.semantic_tags.push("variadic".to_string());
+            }
+            if let Some(default_node) = default_node {
+                metadata.annotations.insert(
+                    "default_value".to_string(),
+                    Value::String(self.node_text(default_node, source).to_string()),
+                );
+            }
+            
+            parameters.push(UIRNode {
+                id: self.generate_node_id(name_node, source),
+                node_type: NodeType::Variable,
+                name: Some(self.node_text(name_node, source).to_string()),
+                children: vec![],
+                metadata,
+                source_location: self.create_source_location(name_node),
+                operator: None,
+                literal: None,
+                visibility: None,
+                modifiers: Vec::new(),
+                is_async: false,
+                attributes: Vec::new(),
+                ownership: None,
+                documentation: None,
+            });
+        }
+        
+        Ok(parameters)
+    }
+    
+    fn convert_call(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let function = node.child_by_field_name("function");
+        let (namespace, name) = match function {
+            Some(f) if matches!(f.kind(), "namespace_get" | "namespace_get_internal") => (
+                f.child_by_field_name("namespace").map(|n| self.node_text(n, source).to_string()),
+                f.child_by_field_name("function").map(|n| self.node_text(n, source).to_string()),
+            ),
+            Some(f) => (None, Some(self.node_text(f, source).to_string())),
+            None => (None, None),
+        };
+        let name = name.unwrap_or_default();
+        
+        let arguments = match self.find_child_by_kind(node, "arguments") {
+            Some(args) => self.convert_arguments(args, source)?,
+            None => vec![],
+        };
+        
+        if IMPORT_FUNCTIONS.contains(&name.as_str()) && namespace.is_none() {
+            if let Some(target) = arguments.first().and_then(|a| a.name.clone()) {
+                return Ok(self.create_import(node, source, &name, &target));
+            }
+        }
+        
+        let node_type = if name == "return" {
+            NodeType::Statement(StatementType::Return)
+        } else {
+            NodeType::Expression(ExpressionType::FunctionCall)
+        };
+        
+        let mut metadata = self.create_metadata(node, source);
+        if let Some(namespace) = namespace {
+            metadata.dependencies.push(namespace.clone());
+            metadata.annotations.insert("namespace".to_string(), Value::String(namespace));
+        }
+        if DATA_FRAME_VERBS.contains(&name.as_str()) {
+            metadata.semantic_tags.push("data_frame_operation".to_string());
+            metadata.annotations.insert("dataframe_verb".to_string(), Value::String(name.clone()));
+        }
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: Some(name),
+            children: arguments,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_arguments(&self, args: Node, source: &str) -> Result<Vec<UIRNode>> {
+        let mut arguments = Vec::new();
+        
+        for (name_node, value_node) in self.split_arguments(args) {
+            match (name_node, value_node) {
+                (Some(name_node), value_node) => {
+                    // Named argument: `col = expr`
+                    let mut uir = match value_node {
+                        Some(value_node) => self.ast_to_uir(value_node, source)?,
+                        None => self.convert_named_leaf(name_node, source, NodeType::Expression(ExpressionType::Literal))?,
+                    };
+                    uir.metadata.semantic_tags.push("named_argument".to_string());
+                    uir.metadata.annotations.insert(
+                        "argument_name".to_string(),
+                        Value::String(self.node_text(name_node, source).trim_matches(|c| c == '"' || c == '\'').to_string()),
+                    );
+                    arguments.push(uir);
+                }
+                (None, Some(value_node)) => arguments.push(self.ast_to_uir(value_node, source)?),
+                (None, None) => {}
+            }
+        }
+        
+        Ok(arguments)
+    }
+    
+    /// Split a flat argument/parameter list into (name, value) pairs on commas
+    fn split_arguments<'a>(&self, node: Node<'a>) -> Vec<(Option<Node<'a>>, Option<Node<'a>>)> {
+        let mut pairs = Vec::new();
+        let mut current: Vec<Node<'a>> = Vec::new();
+        let mut named = false;
+        
+        let mut flush = |current: &mut Vec<Node<'a>>, named: &mut bool| {
+            if *named {
+                pairs.push((current.first().copied(), current.get(1).copied()));
+            } else if let Some(value) = current.first() {
+                pairs.push((None, Some(*value)));
+            }
+            current.clear();
+            *named = false;
+        };
+        
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                "," => flush(&mut current, &mut named),
+                "=" => named = true,
+                _ if child.is_named() && !child.is_extra() => current.push(child),
+                _ => {}
+            }
+        }
+        flush(&mut current, &mut named);
+        
+        // Parameters without defaults are bare identifiers, which read as values
+        if node.kind() == "formal_parameters" {
+            for pair in &mut pairs {
+                if pair.0.is_none() {
+                    *pair = (pair.1, None);
+                }
+            }
+        }
+        
+        pairs
+    }
+    
+    fn create_import(&self, node: Node, source: &str, function: &str, target: &str) -> UIRNode {
+        let target = target.trim_matches(|c| c == '"' || c == '\'');
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.dependencies.push(target.to_string());
+        metadata.semantic_tags.push(if function == "source" { "source" } else { "library" }.to_string());
+        metadata.annotations.insert("import_function".to_string(), Value::String(function.to_string()));
+        
+        UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Import { path: target.to_string(), alias: None, symbols: Vec::new() },
+            name: Some(target.to_string()),
+            children: vec![],
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+    
+    fn convert_binary(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let operator = node.child_by_field_name("operator")
+            .map(|op| self.node_text(op, source).to_string())
+            .unwrap_or_default();
+        
+        let node_type = match operator.as_str() {
+            "%>%" => NodeType::Expression(ExpressionType::FunctionCall),
+            "==" | "!=" | "<" | ">" | "<=" | ">=" | "%in%" => NodeType::Expression(ExpressionType::Comparison),
+            "&" | "&&" | "|" | "||" => NodeType::Expression(ExpressionType::Logical),
+            _ => NodeType::Expression(ExpressionType::Arithmetic),
+        };
+        
+        // %op% operators are named `special` nodes, so only keep the operands
+        let mut children = Vec::new();
+        for field in ["left", "right"] {
+            if let Some(operand) = node.child_by_field_name(field) {
+                children.push(self.ast_to_uir(operand, source)?);
+            }
+        }
+        
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: None,
+            children,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: Operator::binary(&operator, &CoalesceLanguage::R),
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        uir.metadata.annotations.insert("operator".to_string(), Value::String(operator.clone()));
+        if operator == "%>%" {
+            uir.name = Some("pipe".to_string());
+            uir.metadata.semantic_tags.push("pipe".to_string());
+        } else if operator == ":" {
+            uir.metadata.semantic_tags.push("range".to_string());
+        }
+        
+        Ok(uir)
+    }
+    
+    fn convert_unary(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let operator = node.child_by_field_name("operator")
+            .map(|op| self.node_text(op, source).to_string())
+            .unwrap_or_default();
+        
+        let node_type = match operator.as_str() {
+            "!" => NodeType::Expression(ExpressionType::Logical),
+            _ => NodeType::Expression(ExpressionType::Arithmetic),
+        };
+        
+        let mut uir = self.convert_simple(node, source, node_type)?;
+        uir.metadata.annotations.insert("operator".to_string(), Value::String(operator.clone()));
+        if operator == "~" {
+            uir.metadata.semantic_tags.push("formula".to_string());
+        }
+        
+        Ok(uir)
+    }
+    
+    fn convert_member_access(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut children = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.is_extra() {
+                continue;
+            }
+            if child.kind() == "arguments" {
+                children.extend(self.convert_arguments(child, source)?);
+            } else {
+                children.push(self.ast_to_uir(child, source)?);
+            }
+        }
+        
+        let mut metadata = self.create_metadata(node, source);
+        metadata.semantic_tags.push(if matches!(node.kind(), "subset" | "subset2") { "indexing" } else { "member_access" }.to_string());
+        
+        Ok(UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: NodeType::Expression(ExpressionType::Variable),
+            name: None,
+            children,
+            metadata,
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn convert_named_leaf(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: Some(self.node_text(node, source).to_string()),
+            children: vec![],
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_literal(node, source, &CoalesceLanguage::R, &mut uir);
+        Ok(uir)
+    }
+    
+    fn convert_simple(&self, node: Node, source: &str, node_type: NodeType) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type,
+            name: None,
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::R, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::R, &mut uir);
+        Ok(uir)
+    }
+    
+    fn convert_generic(&self, node: Node, source: &str) -> Result<UIRNode> {
+        let mut uir = UIRNode {
+            id: self.generate_node_id(node, source),
+            node_type: self.map_node_type(node.kind()),
+            name: Some(node.kind().to_string()),
+            children: self.convert_children(node, source)?,
+            metadata: self.create_metadata(node, source),
+            source_location: self.create_source_location(node),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        annotate_operator(node, source, &CoalesceLanguage::R, &mut uir);
+        annotate_literal(node, source, &CoalesceLanguage::R, &mut uir);
+        Ok(uir)
+    }
+    
+    // Helper methods
+    fn convert_children(&self, node: Node, source: &str) -> Result<Vec<UIRNode>> {
+        let mut children = Vec::new();
+        
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.is_extra() {
+                continue;
+            }
+            match child.kind() {
+                "brace_list" | "paren_list" => children.extend(self.convert_children(child, source)?),
+                "arguments" => children.extend(self.convert_arguments(child, source)?),
+                _ => children.push(self.ast_to_uir(child, source)?),
+            }
+        }
+        
+        Ok(children)
+    }
+    
+    fn collect_dependencies(&self, children: &[UIRNode]) -> Vec<String> {
+        let mut dependencies: Vec<String> = Vec::new();
+        for child in children {
+            for dependency in &child.metadata.dependencies {
+                if !dependencies.contains(dependency) {
+                    dependencies.push(dependency.clone());
+                }
+            }
+        }
+        dependencies
+    }
+    
+    fn node_text<'a>(&self, node: Node, source: &'a str) -> &'a str {
+        &source[node.byte_range()]
+    }
+    
+    fn find_child_by_kind<'a>(&self, node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).find(|c| c.kind() == kind);
+        found
+    }
+    
+    fn create_metadata(&self, node: Node, source: &str) -> Metadata {
+        let mut metadata = Metadata {
+            source_language: CoalesceLanguage::R,
+            ..Default::default()
+        };
+        metadata.semantic_tags.push(node.kind().to_string());
+        
+        let text = self.node_text(node, source);
+        if text.len() < 100 {
+            metadata.annotations.insert(
+                "original_text".to_string(),
+                Value::String(text.to_string()),
+            );
+        }
+        
+        metadata
+    }
+    
+    fn create_source_location(&self, node: Node) -> Option<SourceLocation> {
+        Some(SourceLocation {
+            file: String::new(),
+            start_line: node.start_position().row as u32 + 1,
+            end_line: node.end_position().row as u32 + 1,
+            start_column: node.start_position().column as u32,
+            end_column: node.end_position().column as u32,
+        })
+    }
+    
+    fn generate_node_id(&self, node: Node, source: &str) -> String {
+        let text = self.node_text(node, source);
+        
+        format!("{}_{}_{}_{}", node.kind(), node.start_position().row, node.start_position().column,
+                text.chars().take(20).collect::<String>()
+                    .replace(|c: char| !c.is_alphanumeric(), "_"))
+    }
+    
+    fn map_node_type(&self, kind: &str) -> NodeType {
+        match kind {
+            "namespace_get" | "namespace_get_internal" | "dots" => NodeType::Expression(ExpressionType::Variable),
+            _ => NodeType::Expression(ExpressionType::Literal), // Generic fallback
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_r_function_with_defaults() {
+        let parser = RParser::new().unwrap();
+        let source = "add <- function(x, y = 2, ...) {\n  x + y\n}\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let func = &uir.children[0];
+        assert_eq!(func.node_type, NodeType::Function);
+        assert_eq!(func.name.as_deref(), Some("add"));
+        
+        let params: Vec<_> = func.children.iter().filter(|c| c.node_type == NodeType::Variable).collect();
+        assert_eq!(params.len(), 3);
+        assert_eq!(params[1].metadata.annotations["default_value"], "2");
+        assert!(params[2].metadata.semantic_tags.contains(&"variadic".to_string()));
+        
+        let body = func.children.last().unwrap();
+        assert_eq!(body.node_type, NodeType::Statement(StatementType::Return));
+        assert!(body.metadata.semantic_tags.contains(&"implicit_return".to_string()));
+    }
+    
+    #[test]
+    fn test_r_library_calls() {
+        let parser = RParser::new().unwrap();
+        let source = "library(dplyr)\nrequire(\"ggplot2\")\nstats::median(x)\n";
+        
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.children[0].node_type, NodeType::Import { path: "dplyr".to_string(), alias: None, symbols: Vec::new() });
+        assert!(matches!(&uir.children[1].node_type, NodeType::Import { path, .. } if path == "ggplot2"));
+        assert_eq!(uir.children[2].metadata.annotations["namespace"], "stats");
+        assert_eq!(uir.metadata.dependencies, vec!["dplyr", "ggplot2", "stats"]);
+    }
+    
+    #[test]
+    fn test_r_data_frame_pipeline() {
+        let parser = RParser::new().unwrap();
+        let source = "result <- df %>% filter(age > 30) %>% mutate(ratio = a / b)\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let assignment = &uir.children[0];
+        assert_eq!(assignment.node_type, NodeType::Expression(ExpressionType::Assignment));
+        
+        let pipe = &assignment.children[1];
+        assert_eq!(pipe.name.as_deref(), Some("pipe"));
+        let mutate = &pipe.children[1];
+        assert_eq!(mutate.name.as_deref(), Some("mutate"));
+        assert!(mutate.metadata.semantic_tags.contains(&"data_frame_operation".to_string()));
+        assert_eq!(mutate.children[0].metadata.annotations["argument_name"], "ratio");
+    }
+    
+    #[test]
+    fn test_r_repeat_is_an_infinite_loop() {
+        let parser = RParser::new().unwrap();
+        let uir = parser.parse("repeat { break }\n").unwrap();
+        
+        let repeat = &uir.children[0];
+        assert_eq!(repeat.node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)));
+        assert!(repeat.metadata.semantic_tags.contains(&"infinite_loop".to_string()));
+        assert_eq!(repeat.children[0].node_type, NodeType::Statement(StatementType::Break));
+    }
+}
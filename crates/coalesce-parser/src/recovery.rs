@@ -0,0 +1,117 @@
+// Recovery from tree-sitter syntax errors
+//
+// Tree-sitter always produces a tree, wrapping whatever it could not fit into ERROR nodes
+// and inserting zero-width MISSING nodes for tokens it expected. Rather than discarding the
+// whole file, parsers convert such nodes into diagnostic UIR nodes that keep any complete
+// constructs found inside them.
+use coalesce_core::{UIRNode, NodeType, StatementType, Metadata, SourceLocation, Language, Result};
+use tree_sitter::Node;
+use serde_json::{json, Value};
+
+/// Whether tree-sitter flagged this node as a syntax error or an inserted token
+pub(crate) fn is_damaged(node: Node) -> bool {
+    node.is_error() || node.is_missing()
+}
+
+/// Describe an ERROR or MISSING node as a serializable diagnostic
+pub(crate) fn diagnostic(source: &str, node: Node) -> Value {
+    let position = node.start_position();
+    let (kind, message) = if node.is_missing() {
+        ("missing", format!("missing {}", node.kind()))
+    } else {
+        let text = node.utf8_text(source.as_bytes()).unwrap_or("");
+        let snippet: String = text.lines().next().unwrap_or("").chars().take(40).collect();
+        ("syntax_error", format!("unexpected `{}`", snippet.trim()))
+    };
+    
+    json!({
+        "kind": kind,
+        "message": message,
+        "line": position.row + 1,
+        "column": position.column + 1,
+    })
+}
+
+/// Convert an ERROR or MISSING node into a diagnostic statement
+///
+/// Named children that are complete constructs (not bare tokens) are converted with
+/// `convert` and kept as children, so valid code caught up in the error survives. Parsers
+/// must route damaged nodes passed to `convert` back into this function.
+pub(crate) fn error_node<'a>(
+    source: &str,
+    node: Node<'a>,
+    language: Language,
+    mut convert: impl FnMut(Node<'a>) -> Result<UIRNode>,
+) -> Result<UIRNode> {
+    let mut children = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        // Nested errors come back through the parser, which routes them here again. Bare
+        // unexpected tokens are already covered by this node's diagnostic.
+        if child.is_missing() || (child.is_error() && child.child_count() > 0) {
+            children.push(convert(child)?);
+        } else if child.child_count() > 0 && !child.is_extra() && !child.is_error() {
+            let mut recovered = convert(child)?;
+            recovered.metadata.semantic_tags.push("recovered".to_string());
+            children.push(recovered);
+        }
+    }
+    
+    let mut metadata = Metadata {
+        source_language: language,
+        semantic_tags: vec!["parse_error".to_string()],
+        ..Metadata::default()
+    };
+    metadata.annotations.insert("diagnostic".to_string(), diagnostic(source, node));
+    metadata.annotations.insert("original_text".to_string(),
+        Value::String(node.utf8_text(source.as_bytes()).unwrap_or("").to_string()));
+    
+    let start = node.start_position();
+    let end = node.end_position();
+    Ok(UIRNode {
+        id: format!("parse_error_{}_{}", start.row, start.column),
+        node_type: NodeType::Statement(StatementType::Expression),
+        name: None,
+        children,
+        metadata,
+        source_location: Some(SourceLocation {
+            file: String::new(),
+            start_line: start.row as u32 + 1,
+            end_line: end.row as u32 + 1,
+            start_column: start.column as u32,
+            end_column: end.column as u32,
+        }),
+        operator: None,
+        literal: None,
+        visibility: None,
+        modifiers: Vec::new(),
+        is_async: false,
+        attributes: Vec::new(),
+        ownership: None,
+        documentation: None,
+    })
+}
+
+/// Record every syntax error in the tree on the root UIR node
+pub(crate) fn summarize(source: &str, root: Node, uir: &mut UIRNode) {
+    if !root.has_error() {
+        return;
+    }
+    
+    let mut diagnostics = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        // An error region is reported once, not again for each token inside it
+        if is_damaged(node) {
+            diagnostics.push(diagnostic(source, node));
+        } else if node.has_error() {
+            let mut cursor = node.walk();
+            let children: Vec<Node> = node.children(&mut cursor).collect();
+            stack.extend(children.into_iter().rev());
+        }
+    }
+    
+    uir.metadata.semantic_tags.push("partial_parse".to_string());
+    uir.metadata.annotations.insert("parse_error_count".to_string(), json!(diagnostics.len()));
+    uir.metadata.annotations.insert("diagnostics".to_string(), Value::Array(diagnostics));
+}
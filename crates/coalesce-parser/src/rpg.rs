@@ -0,0 +1,1780 @@
+use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage,
+                   ControlFlowType, LoopType, ExpressionType, StatementType, LegacyPattern, Result,
+                   Parser as CoalesceParser, Operator, LiteralValue};
+use serde_json::Value;
+use crate::operators::mark_grouped;
+use std::collections::HashMap;
+
+/// Operation codes recognised at the start of a free-format statement
+const OPCODES: &[&str] = &[
+    "IF", "ELSEIF", "ELSE", "ENDIF", "DOW", "DOU", "ENDDO", "FOR", "ENDFOR", "SELECT", "WHEN", "OTHER",
+    "ENDSL", "MONITOR", "ON-ERROR", "ENDMON", "LEAVE", "ITER", "RETURN", "LEAVESR", "EVAL", "EVALR",
+    "EVAL-CORR", "CALLP", "CHAIN", "READ", "READE", "READP", "READPE", "SETLL", "SETGT", "WRITE",
+    "UPDATE", "DELETE", "EXFMT", "OPEN", "CLOSE", "UNLOCK", "EXSR", "BEGSR", "ENDSR", "CLEAR", "RESET",
+    "DSPLY", "SORTA", "COMMIT", "ROLBK", "DEALLOC", "CTL-OPT", "DCL-F", "DCL-S", "DCL-C", "DCL-DS",
+    "DCL-SUBF", "END-DS", "DCL-PR", "END-PR", "DCL-PI", "END-PI", "DCL-PARM", "DCL-PROC", "END-PROC",
+];
+
+/// Fixed-format opcodes whose operands occupy the extended factor 2 (columns 36-80)
+const EXTENDED_OPCODES: &[&str] = &[
+    "EVAL", "EVALR", "EVAL-CORR", "IF", "ELSEIF", "DOW", "DOU", "WHEN", "FOR", "CALLP", "RETURN",
+    "ON-ERROR", "SORTA", "DSPLY",
+];
+
+/// Record-level file operations
+const FILE_OPCODES: &[&str] = &[
+    "CHAIN", "READ", "READE", "READP", "READPE", "SETLL", "SETGT", "WRITE", "UPDATE", "DELETE",
+    "EXFMT", "OPEN", "CLOSE", "UNLOCK",
+];
+
+/// File operations that take a search argument before the file name
+const KEYED_OPCODES: &[&str] = &["CHAIN", "READE", "READPE", "SETLL", "SETGT", "DELETE"];
+
+/// Classic fixed-format arithmetic and move operations
+const CLASSIC_OPCODES: &[&str] = &["ADD", "SUB", "MULT", "DIV", "Z-ADD", "Z-SUB", "MOVE", "MOVEL", "MOVEA"];
+
+/// Figurative constants that behave like literals
+const FIGURATIVE_CONSTANTS: &[&str] = &[
+    "*ON", "*OFF", "*BLANK", "*BLANKS", "*ZERO", "*ZEROS", "*HIVAL", "*LOVAL", "*NULL", "*OMIT",
+];
+
+/// A normalised RPG statement; fixed-format specs are mapped onto their free-format opcodes
+#[derive(Debug, Clone, Default)]
+struct RpgStatement {
+    opcode: String,
+    extender: Option<String>,
+    factor1: String,
+    factor2: String,
+    result: String,
+    conditioning: String,
+    resulting: String,
+    name: String,
+    type_spec: String,
+    keywords: String,
+    line: u32,
+    original: String,
+    fixed: bool,
+}
+
+/// RPG IV parser for IBM i modernization.
+///
+/// Fixed-format specifications (H, F, D, P and C) are read by column and
+/// normalised onto the free-format `CTL-OPT`/`DCL-*` statements, so both
+/// styles (and sources mixing them) produce the same UIR. Subprocedures and
+/// subroutines become functions and file specs become file variables;
+/// indicators, subroutines and fixed-format specs are recorded as legacy patterns.
+pub struct RpgParser {
+}
+
+impl CoalesceParser for RpgParser {
+    fn language(&self) -> CoalesceLanguage {
+        CoalesceLanguage::Rpg
+    }
+    
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        let statements = self.normalize(source);
+        let mut uir = self.parse_program(source, &statements);
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
+    }
+}
+
+impl RpgParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    /// Split the source into statements, reading fixed-format specs by column
+    fn normalize(&self, source: &str) -> Vec<RpgStatement> {
+        let fully_free = source.lines().next()
+            .is_some_and(|l| l.trim_start().to_ascii_uppercase().starts_with("**FREE"));
+        
+        let mut statements = Vec::new();
+        let mut group: Option<&'static str> = None;
+        let mut pending = String::new();
+        let mut pending_line = 0;
+        
+        for (index, raw) in source.lines().enumerate() {
+            let line = index as u32 + 1;
+            if fully_free && index == 0 {
+                continue;
+            }
+            
+            let chars: Vec<char> = raw.chars().collect();
+            let free_text = if fully_free {
+                Some(raw.to_string())
+            } else {
+                let spec = chars.get(5).map(|c| c.to_ascii_uppercase()).unwrap_or(' ');
+                let indicator = chars.get(6).copied().unwrap_or(' ');
+                if indicator == '*' {
+                    continue;
+                }
+                if "HFDICOP".contains(spec) {
+                    self.close_group(&mut group, line, &mut statements);
+                    self.parse_fixed_line(&chars, spec, line, &mut group, &mut statements);
+                    None
+                } else if spec == ' ' && chars.len() > 6 {
+                    Some(chars[6..].iter().collect())
+                } else {
+                    None
+                }
+            };
+            
+            let Some(text) = free_text else {
+                continue;
+            };
+            let trimmed = text.trim();
+            
+            // Compiler directives occupy their own line
+            if trimmed.starts_with('/') && !trimmed.starts_with("//") {
+                let mut words = trimmed.split_whitespace();
+                let directive = words.next().unwrap_or("").to_ascii_uppercase();
+                if directive == "/COPY" || directive == "/INCLUDE" {
+                    statements.push(RpgStatement {
+                        opcode: "COPY".to_string(),
+                        factor2: words.next().unwrap_or("").to_string(),
+                        line,
+                        original: trimmed.to_string(),
+                        ..Default::default()
+                    });
+                }
+                continue;
+            }
+            
+            for c in self.strip_comment(&text).chars() {
+                if c == ';' && !self.in_literal(&pending) {
+                    let text = std::mem::take(&mut pending);
+                    if let Some(statement) = self.free_statement(text.trim(), pending_line, &mut group) {
+                        statements.push(statement);
+                    }
+                    continue;
+                }
+                if pending.trim().is_empty() && !c.is_whitespace() {
+                    pending_line = line;
+                }
+                pending.push(c);
+            }
+            pending.push(' ');
+        }
+        
+        let last_line = source.lines().count() as u32;
+        self.close_group(&mut group, last_line, &mut statements);
+        statements
+    }
+    
+    fn strip_comment(&self, text: &str) -> String {
+        let mut in_quote = false;
+        let chars: Vec<char> = text.chars().collect();
+        for (i, c) in chars.iter().enumerate() {
+            if *c == '\'' {
+                in_quote = !in_quote;
+            } else if !in_quote && *c == '/' && chars.get(i + 1) == Some(&'/') {
+                return chars[..i].iter().collect();
+            }
+        }
+        text.to_string()
+    }
+    
+    fn in_literal(&self, text: &str) -> bool {
+        text.chars().filter(|c| *c == '\'').count() % 2 == 1
+    }
+    
+    /// Fixed-format data structures and prototypes end implicitly at the next non-subfield spec
+    fn close_group(&self, group: &mut Option<&'static str>, line: u32, statements: &mut Vec<RpgStatement>) {
+        if let Some(end) = group.take() {
+            statements.push(RpgStatement { opcode: end.to_string(), line, ..Default::default() });
+        }
+    }
+    
+    /// Columns are 1-based and inclusive, as in the IBM specification layouts
+    fn column(&self, chars: &[char], from: usize, to: usize) -> String {
+        if chars.len() < from {
+            return String::new();
+        }
+        chars[from - 1..to.min(chars.len())].iter().collect::<String>().trim().to_string()
+    }
+    
+    fn parse_fixed_line(&self, chars: &[char], spec: char, line: u32, group: &mut Option<&'static str>, statements: &mut Vec<RpgStatement>) {
+        let original: String = chars[5.min(chars.len())..].iter().collect::<String>().trim_end().to_string();
+        let mut statement = RpgStatement { line, original, fixed: true, ..Default::default() };
+        
+        match spec {
+            'H' => {
+                statement.opcode = "CTL-OPT".to_string();
+                statement.keywords = self.column(chars, 7, 80);
+            }
+            'F' => {
+                statement.opcode = "DCL-F".to_string();
+                statement.name = self.column(chars, 7, 16);
+                let usage = match self.column(chars, 17, 17).to_ascii_uppercase().as_str() {
+                    "O" => "*OUTPUT",
+                    "U" => "*UPDATE",
+                    "C" => "*INPUT:*OUTPUT",
+                    _ => "*INPUT",
+                };
+                let device = self.column(chars, 36, 42);
+                let mut keywords = format!("{} USAGE({})", if device.is_empty() { "DISK" } else { &device }, usage);
+                if self.column(chars, 34, 34).eq_ignore_ascii_case("K") {
+                    keywords.push_str(" KEYED");
+                }
+                let extra = self.column(chars, 44, 80);
+                if !extra.is_empty() {
+                    keywords = format!("{} {}", keywords, extra);
+                }
+                statement.keywords = keywords;
+            }
+            'D' => {
+                let name = self.column(chars, 7, 21);
+                let definition = self.column(chars, 24, 25).to_ascii_uppercase();
+                let from = self.column(chars, 26, 32);
+                let to = self.column(chars, 33, 39);
+                let length = match (from.parse::<u32>(), to.parse::<u32>()) {
+                    (Ok(from), Ok(to)) if to >= from => (to - from + 1).to_string(),
+                    _ => to,
+                };
+                statement.name = name.clone();
+                statement.type_spec = self.fixed_type(&length, &self.column(chars, 40, 40), &self.column(chars, 41, 42));
+                statement.keywords = self.column(chars, 44, 80);
+                
+                statement.opcode = match definition.as_str() {
+                    "S" => "DCL-S".to_string(),
+                    "C" => "DCL-C".to_string(),
+                    "DS" => {
+                        *group = Some("END-DS");
+                        "DCL-DS".to_string()
+                    }
+                    "PR" => {
+                        *group = Some("END-PR");
+                        "DCL-PR".to_string()
+                    }
+                    "PI" => {
+                        *group = Some("END-PI");
+                        "DCL-PI".to_string()
+                    }
+                    _ => {
+                        // Keyword continuation of the previous definition
+                        if name.is_empty() && statement.type_spec.is_empty() {
+                            if let Some(previous) = statements.last_mut() {
+                                previous.keywords = format!("{} {}", previous.keywords, statement.keywords).trim().to_string();
+                            }
+                            return;
+                        }
+                        match group {
+                            Some("END-DS") => "DCL-SUBF".to_string(),
+                            Some(_) => "DCL-PARM".to_string(),
+                            None => "DCL-S".to_string(),
+                        }
+                    }
+                };
+                
+                // Subfields keep the enclosing group open
+                if statement.opcode == "DCL-SUBF" || statement.opcode == "DCL-PARM" {
+                    statements.push(statement);
+                    return;
+                }
+                if !matches!(statement.opcode.as_str(), "DCL-DS" | "DCL-PR" | "DCL-PI") {
+                    *group = None;
+                }
+                if statement.opcode == "DCL-DS" && self.keyword_value(&statement.keywords, "LIKEDS").is_some() {
+                    *group = None;
+                }
+            }
+            'P' => {
+                statement.name = self.column(chars, 7, 21);
+                statement.keywords = self.column(chars, 44, 80);
+                statement.opcode = if self.column(chars, 24, 24).eq_ignore_ascii_case("E") {
+                    "END-PROC".to_string()
+                } else {
+                    "DCL-PROC".to_string()
+                };
+            }
+            'C' => {
+                let operation = self.column(chars, 26, 35);
+                if operation.is_empty() {
+                    // Continuation of an extended factor 2
+                    let continuation = self.column(chars, 36, 80);
+                    if let Some(previous) = statements.last_mut().filter(|s| s.fixed && !continuation.is_empty()) {
+                        previous.factor2 = format!("{} {}", previous.factor2, continuation);
+                    }
+                    return;
+                }
+                let (opcode, extender) = self.split_extender(&operation);
+                statement.conditioning = self.column(chars, 9, 11);
+                statement.factor1 = self.column(chars, 12, 25);
+                if EXTENDED_OPCODES.contains(&opcode.as_str()) {
+                    statement.factor2 = self.column(chars, 36, 80);
+                } else {
+                    statement.factor2 = self.column(chars, 36, 49);
+                    statement.result = self.column(chars, 50, 63);
+                    statement.resulting = self.column(chars, 71, 76);
+                }
+                statement.opcode = opcode;
+                statement.extender = extender;
+            }
+            _ => {
+                // Input and output specs describe program-described record layouts
+                statement.opcode = if spec == 'I' { "INPUT-SPEC".to_string() } else { "OUTPUT-SPEC".to_string() };
+                statement.name = self.column(chars, 7, 16);
+            }
+        }
+        
+        statements.push(statement);
+    }
+    
+    /// Map a fixed-format length/type/decimals triple onto its free-format data type
+    fn fixed_type(&self, length: &str, data_type: &str, decimals: &str) -> String {
+        let scaled = |name: &str| format!("{}({}:{})", name, length, if decimals.is_empty() { "0" } else { decimals });
+        match data_type.to_ascii_uppercase().as_str() {
+            "A" => format!("char({})", length),
+            "P" => scaled("packed"),
+            "S" => scaled("zoned"),
+            "B" => scaled("bindec"),
+            "I" => format!("int({})", length),
+            "U" => format!("uns({})", length),
+            "F" => format!("float({})", length),
+            "G" => format!("graph({})", length),
+            "C" => format!("ucs2({})", length),
+            "D" => "date".to_string(),
+            "T" => "time".to_string(),
+            "Z" => "timestamp".to_string(),
+            "N" => "ind".to_string(),
+            "*" => "pointer".to_string(),
+            _ if length.is_empty() => String::new(),
+            _ if !decimals.is_empty() => scaled("packed"),
+            _ => format!("char({})", length),
+        }
+    }
+    
+    /// `EVAL(H)` -> ("EVAL", Some("H"))
+    fn split_extender(&self, operation: &str) -> (String, Option<String>) {
+        match operation.split_once('(') {
+            Some((opcode, extender)) => (
+                opcode.trim().to_ascii_uppercase(),
+                Some(extender.trim_end_matches(')').trim().to_ascii_uppercase()),
+            ),
+            None => (operation.trim().to_ascii_uppercase(), None),
+        }
+    }
+    
+    fn free_statement(&self, text: &str, line: u32, group: &mut Option<&'static str>) -> Option<RpgStatement> {
+        if text.is_empty() {
+            return None;
+        }
+        
+        let mut statement = RpgStatement { line, original: text.to_string(), ..Default::default() };
+        let word_end = text.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(text.len());
+        let word = text[..word_end].to_ascii_uppercase();
+        let mut rest = text[word_end..].trim_start();
+        
+        if !OPCODES.contains(&word.as_str()) {
+            // Inside a data structure or prototype, bare lines declare subfields/parameters
+            if let Some(end) = group {
+                let (name, remainder) = self.split_first_operand(text);
+                statement.opcode = if *end == "END-DS" { "DCL-SUBF".to_string() } else { "DCL-PARM".to_string() };
+                statement.name = name;
+                self.split_type(&mut statement, &remainder);
+                return Some(statement);
+            }
+            statement.opcode = if self.assignment_split(&self.tokenize_expression(text)).is_some() {
+                "EVAL".to_string()
+            } else {
+                "CALLP".to_string()
+            };
+            statement.factor2 = text.to_string();
+            return Some(statement);
+        }
+        
+        statement.opcode = word.clone();
+        if rest.starts_with('(') {
+            let close = rest.find(')').unwrap_or(rest.len() - 1);
+            let extender = &rest[1..close];
+            if !extender.is_empty() && extender.len() <= 3 && extender.chars().all(|c| c.is_ascii_alphabetic()) {
+                statement.extender = Some(extender.to_ascii_uppercase());
+                rest = rest[close + 1..].trim_start();
+            }
+        }
+        
+        match word.as_str() {
+            "DCL-F" | "DCL-S" | "DCL-C" | "DCL-DS" | "DCL-PR" | "DCL-PI" | "DCL-PROC" | "DCL-SUBF" | "DCL-PARM" => {
+                let (name, remainder) = self.split_first_operand(rest);
+                statement.name = name;
+                if word == "DCL-F" || word == "DCL-PROC" {
+                    statement.keywords = remainder;
+                } else {
+                    self.split_type(&mut statement, &remainder);
+                }
+                
+                let standalone = self.keyword_value(&statement.keywords, "LIKEDS").is_some()
+                    || self.keyword_value(&statement.keywords, "LIKEREC").is_some()
+                    || self.has_keyword(&statement.keywords, "END-DS");
+                *group = match word.as_str() {
+                    "DCL-DS" if !standalone => Some("END-DS"),
+                    "DCL-PR" => Some("END-PR"),
+                    "DCL-PI" => Some("END-PI"),
+                    _ => *group,
+                };
+            }
+            "END-DS" | "END-PR" | "END-PI" => *group = None,
+            "CTL-OPT" => statement.keywords = rest.to_string(),
+            "BEGSR" | "ENDSR" => statement.factor1 = rest.to_string(),
+            _ if KEYED_OPCODES.contains(&word.as_str()) => {
+                let operands = self.split_operands(rest);
+                if operands.len() >= 2 {
+                    statement.factor1 = operands[0].clone();
+                    statement.factor2 = operands[1].clone();
+                    statement.result = operands.get(2).cloned().unwrap_or_default();
+                } else {
+                    statement.factor2 = operands.first().cloned().unwrap_or_default();
+                }
+            }
+            _ if FILE_OPCODES.contains(&word.as_str()) => {
+                let operands = self.split_operands(rest);
+                statement.factor2 = operands.first().cloned().unwrap_or_default();
+                statement.result = operands.get(1).cloned().unwrap_or_default();
+            }
+            _ => statement.factor2 = rest.to_string(),
+        }
+        
+        Some(statement)
+    }
+    
+    /// Separate a leading data type such as `packed(7:2)` from the remaining keywords
+    fn split_type(&self, statement: &mut RpgStatement, remainder: &str) {
+        const TYPES: &[&str] = &[
+            "CHAR", "VARCHAR", "GRAPH", "VARGRAPH", "UCS2", "VARUCS2", "PACKED", "ZONED", "BINDEC", "INT",
+            "UNS", "FLOAT", "DATE", "TIME", "TIMESTAMP", "IND", "POINTER", "OBJECT", "LIKE", "LIKEDS", "LIKEREC",
+        ];
+        let (first, rest) = self.split_first_operand(remainder);
+        let base = first.split('(').next().unwrap_or("").to_ascii_uppercase();
+        if TYPES.contains(&base.as_str()) {
+            statement.type_spec = first;
+            statement.keywords = rest;
+        } else if statement.opcode == "DCL-C" && !first.is_empty() && !base.starts_with("CONST") {
+            // dcl-c NAME 'value';
+            statement.keywords = format!("CONST({}) {}", first, rest).trim().to_string();
+        } else {
+            statement.keywords = remainder.to_string();
+        }
+    }
+    
+    fn split_first_operand(&self, text: &str) -> (String, String) {
+        let operands = self.split_operands(text);
+        let first = operands.first().cloned().unwrap_or_default();
+        let rest = operands.get(1..).map(|r| r.join(" ")).unwrap_or_default();
+        (first, rest)
+    }
+    
+    /// Whitespace-separated operands, keeping parenthesised groups and literals together
+    fn split_operands(&self, text: &str) -> Vec<String> {
+        let mut operands = Vec::new();
+        let mut current = String::new();
+        let mut depth = 0;
+        let mut in_quote = false;
+        
+        for c in text.chars() {
+            match c {
+                '\'' => in_quote = !in_quote,
+                '(' if !in_quote => depth += 1,
+                ')' if !in_quote => depth -= 1,
+                _ => {}
+            }
+            if c.is_whitespace() && depth == 0 && !in_quote {
+                if !current.is_empty() {
+                    operands.push(std::mem::take(&mut current));
+                }
+            } else {
+                current.push(c);
+            }
+        }
+        if !current.is_empty() {
+            operands.push(current);
+        }
+        operands
+    }
+    
+    fn parse_program(&self, source: &str, statements: &[RpgStatement]) -> UIRNode {
+        let mut root = self.create_node(NodeType::Module, Some("rpg_program".to_string()), 1, "source_file");
+        root.source_location = Some(SourceLocation {
+            file: String::new(),
+            start_line: 1,
+            end_line: source.lines().count() as u32,
+            start_column: 0,
+            end_column: source.len() as u32,
+        });
+        
+        let fixed = statements.iter().filter(|s| s.fixed).count();
+        let format = match fixed {
+            0 => "free",
+            n if n == statements.len() => "fixed",
+            _ => "mixed",
+        };
+        root.metadata.annotations.insert("format".to_string(), Value::String(format.to_string()));
+        if fixed > 0 {
+            root.metadata.legacy_patterns.push(self.legacy_pattern(
+                "fixed_format",
+                &format!("{} fixed-format specifications", fixed),
+                "Convert to fully free-format RPG",
+                false,
+            ));
+        }
+        
+        // Devices are needed to tell database I/O from display and printer files
+        let files: HashMap<String, String> = statements.iter()
+            .filter(|s| s.opcode == "DCL-F")
+            .map(|s| (s.name.to_ascii_uppercase(), self.file_device(&s.keywords)))
+            .collect();
+        
+        let mut pos = 0;
+        while pos < statements.len() {
+            let statement = &statements[pos];
+            if statement.opcode == "CTL-OPT" {
+                let options = root.metadata.annotations.entry("control_options".to_string())
+                    .or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(options) = options {
+                    options.push(Value::String(statement.keywords.clone()));
+                }
+                pos += 1;
+                continue;
+            }
+            
+            let node = self.parse_statement(statements, &mut pos, &files);
+            // Files and /COPY members are program-level dependencies
+            if node.node_type == NodeType::Module || node.metadata.semantic_tags.iter().any(|t| t == "file") {
+                root.metadata.dependencies.extend(node.metadata.dependencies.iter().cloned());
+            }
+            root.children.push(node);
+        }
+        
+        root
+    }
+    
+    fn parse_block(&self, statements: &[RpgStatement], pos: &mut usize, stop_words: &[&str], files: &HashMap<String, String>) -> Vec<UIRNode> {
+        let mut nodes = Vec::new();
+        
+        while let Some(statement) = statements.get(*pos) {
+            if stop_words.contains(&statement.opcode.as_str()) || stop_words.iter().any(|s| self.classic_compare(&statement.opcode, s).is_some()) {
+                break;
+            }
+            nodes.push(self.parse_statement(statements, pos, files));
+        }
+        
+        nodes
+    }
+    
+    /// Consume the closing opcode of a block; fixed format closes every block with END
+    fn expect_end(&self, statements: &[RpgStatement], pos: &mut usize, end: &str) {
+        if statements.get(*pos).is_some_and(|s| s.opcode == end || s.opcode == "END") {
+            *pos += 1;
+        }
+    }
+    
+    fn parse_statement(&self, statements: &[RpgStatement], pos: &mut usize, files: &HashMap<String, String>) -> UIRNode {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        let opcode = statement.opcode.as_str();
+        
+        let mut node = match opcode {
+            "DCL-F" => {
+                *pos += 1;
+                self.parse_file(statement)
+            }
+            "DCL-S" | "DCL-C" | "DCL-SUBF" | "DCL-PARM" => {
+                *pos += 1;
+                self.parse_definition(statement)
+            }
+            "DCL-DS" => self.parse_group(statements, pos, NodeType::Class, "data_structure", "END-DS"),
+            "DCL-PR" => {
+                let mut prototype = self.parse_group(statements, pos, NodeType::Function, "prototype", "END-PR");
+                let program = self.keyword_value(&statement.keywords, "EXTPGM")
+                    .or_else(|| self.keyword_value(&statement.keywords, "EXTPROC"));
+                if let Some(program) = program {
+                    let program = self.unquote(&program);
+                    prototype.metadata.dependencies.push(program.clone());
+                    prototype.metadata.annotations.insert("external_name".to_string(), Value::String(program));
+                }
+                prototype
+            }
+            "DCL-PI" => self.parse_group(statements, pos, NodeType::Function, "procedure_interface", "END-PI"),
+            "DCL-PROC" => self.parse_procedure(statements, pos, files),
+            "BEGSR" => {
+                *pos += 1;
+                let mut subroutine = self.create_node(NodeType::Function, Some(statement.factor1.clone()), line, "subroutine");
+                subroutine.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "subroutine",
+                    &format!("BEGSR {}", statement.factor1),
+                    "Subroutines share the caller's variables; convert to a subprocedure with parameters",
+                    false,
+                ));
+                subroutine.children = self.parse_block(statements, pos, &["ENDSR"], files);
+                self.expect_end(statements, pos, "ENDSR");
+                subroutine
+            }
+            "COPY" => {
+                *pos += 1;
+                let member = statement.factor2.clone();
+                let copy = NodeType::Import { path: member.clone(), alias: None, symbols: Vec::new() };
+                let mut import = self.create_node(copy, Some(member.clone()), line, "copy_member");
+                import.metadata.dependencies.push(member);
+                import
+            }
+            "IF" | "ELSEIF" => self.parse_if(statements, pos, files),
+            _ if self.classic_compare(opcode, "IF").is_some() => self.parse_if(statements, pos, files),
+            "DOW" | "DOU" => self.parse_loop(statements, pos, files),
+            _ if self.classic_compare(opcode, "DOW").is_some() || self.classic_compare(opcode, "DOU").is_some() => {
+                self.parse_loop(statements, pos, files)
+            }
+            "DO" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For)), Some("do".to_string()), line, "do");
+                if !statement.factor2.is_empty() {
+                    node.metadata.annotations.insert("count".to_string(), Value::String(statement.factor2.clone()));
+                }
+                node.children = self.parse_block(statements, pos, &["ENDDO", "END"], files);
+                self.expect_end(statements, pos, "ENDDO");
+                node
+            }
+            "FOR" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For)), Some("for".to_string()), line, "for");
+                self.annotate_for(&mut node, &statement.factor2);
+                node.children = self.parse_block(statements, pos, &["ENDFOR", "END"], files);
+                self.expect_end(statements, pos, "ENDFOR");
+                node
+            }
+            "SELECT" => self.parse_select(statements, pos, files),
+            "MONITOR" => self.parse_monitor(statements, pos, files),
+            "LEAVE" => {
+                *pos += 1;
+                self.create_node(NodeType::Statement(StatementType::Break), None, line, "leave")
+            }
+            "ITER" => {
+                *pos += 1;
+                self.create_node(NodeType::Statement(StatementType::Continue), None, line, "iter")
+            }
+            "RETURN" | "LEAVESR" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::Statement(StatementType::Return), None, line, &opcode.to_ascii_lowercase());
+                if !statement.factor2.is_empty() {
+                    node.children.push(self.parse_expression(&statement.factor2, line));
+                }
+                node
+            }
+            "EVAL" | "EVALR" | "EVAL-CORR" => {
+                *pos += 1;
+                self.parse_eval(&statement.factor2, line)
+            }
+            "CALLP" => {
+                *pos += 1;
+                let mut call = self.parse_expression(&statement.factor2, line);
+                if call.node_type != NodeType::Expression(ExpressionType::FunctionCall) {
+                    // A procedure called without parentheses
+                    let mut bare = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), call.name.clone(), line, "call");
+                    bare.metadata.annotations = call.metadata.annotations;
+                    call = bare;
+                }
+                call.metadata.semantic_tags.push("procedure_call".to_string());
+                call
+            }
+            "CALL" | "CALLB" => {
+                *pos += 1;
+                let program = self.unquote(&statement.factor2);
+                let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(program.clone()), line, "program_call");
+                call.metadata.dependencies.push(program.clone());
+                // PARM lines following a CALL are its arguments
+                while let Some(parm) = statements.get(*pos).filter(|s| s.opcode == "PARM") {
+                    call.children.push(self.create_operand(&parm.result, parm.line));
+                    *pos += 1;
+                }
+                call.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "dynamic_program_call",
+                    &format!("CALL {}", statement.factor2),
+                    "Define a prototype with EXTPGM and use CALLP",
+                    false,
+                ));
+                call
+            }
+            "EXSR" => {
+                *pos += 1;
+                let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(statement.factor2.clone()), line, "subroutine_call");
+                call.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "exsr",
+                    &format!("EXSR {}", statement.factor2),
+                    "Call a subprocedure instead",
+                    false,
+                ));
+                call
+            }
+            "SETON" | "SETOFF" => {
+                *pos += 1;
+                let value = if opcode == "SETON" { "*ON" } else { "*OFF" };
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::Assignment), Some(opcode.to_ascii_lowercase()), line, "indicator");
+                for indicator in statement.resulting.as_bytes().chunks(2) {
+                    let indicator = String::from_utf8_lossy(indicator).trim().to_string();
+                    if !indicator.is_empty() {
+                        node.children.push(self.create_operand(&format!("*IN{}", indicator), line));
+                    }
+                }
+                node.children.push(self.create_operand(value, line));
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "indicator",
+                    &statement.original,
+                    "Replace numbered indicators with named boolean variables",
+                    false,
+                ));
+                node
+            }
+            "GOTO" | "TAG" => {
+                *pos += 1;
+                let target = if opcode == "TAG" { &statement.factor1 } else { &statement.factor2 };
+                let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Goto), Some(target.clone()), line, &opcode.to_ascii_lowercase());
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "goto",
+                    &statement.original,
+                    "Restructure with loops and LEAVE/ITER",
+                    true,
+                ));
+                node
+            }
+            "CLEAR" | "RESET" => {
+                *pos += 1;
+                let target = if statement.result.is_empty() { &statement.factor2 } else { &statement.result };
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::Assignment), Some(opcode.to_ascii_lowercase()), line, "reset");
+                node.children.push(self.create_operand(target, line));
+                node
+            }
+            "DSPLY" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some("DSPLY".to_string()), line, "io_statement");
+                let message = if statement.factor1.is_empty() { &statement.factor2 } else { &statement.factor1 };
+                node.children.push(self.parse_expression(message, line));
+                node
+            }
+            "INPUT-SPEC" | "OUTPUT-SPEC" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::Statement(StatementType::Expression), Some(statement.name.clone()), line, &opcode.to_ascii_lowercase());
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "program_described_file",
+                    &statement.original,
+                    "Use externally described files instead of I/O specs",
+                    true,
+                ));
+                node
+            }
+            _ if FILE_OPCODES.contains(&opcode) => {
+                *pos += 1;
+                self.parse_file_operation(statement, files)
+            }
+            _ if CLASSIC_OPCODES.contains(&opcode) => {
+                *pos += 1;
+                self.parse_classic_operation(statement)
+            }
+            _ => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::Statement(StatementType::Expression), Some(opcode.to_ascii_lowercase()), line, "statement");
+                for operand in [&statement.factor1, &statement.factor2, &statement.result] {
+                    if !operand.is_empty() {
+                        node.children.push(self.parse_expression(operand, line));
+                    }
+                }
+                node
+            }
+        };
+        
+        if statement.extender.is_some() {
+            node.metadata.annotations.insert("extender".to_string(), Value::String(statement.extender.clone().unwrap_or_default()));
+        }
+        if !statement.resulting.is_empty() && !matches!(opcode, "SETON" | "SETOFF") {
+            node.metadata.annotations.insert("resulting_indicators".to_string(), Value::String(statement.resulting.clone()));
+            node.metadata.legacy_patterns.push(self.legacy_pattern(
+                "resulting_indicator",
+                &statement.original,
+                "Test %FOUND, %EOF or %ERROR instead of resulting indicators",
+                false,
+            ));
+        }
+        if !statement.original.is_empty() {
+            node.metadata.annotations.entry("original_text".to_string())
+                .or_insert_with(|| Value::String(statement.original.clone()));
+        }
+        
+        // Conditioning indicators make the whole operation conditional
+        if !statement.conditioning.is_empty() {
+            node = self.wrap_conditioning(node, &statement.conditioning, &statement.original, line);
+        }
+        
+        node
+    }
+    
+    fn parse_file(&self, statement: &RpgStatement) -> UIRNode {
+        let mut file = self.create_node(NodeType::Variable, Some(statement.name.clone()), statement.line, "file");
+        let device = self.file_device(&statement.keywords);
+        let usage = self.keyword_value(&statement.keywords, "USAGE").unwrap_or_else(|| match device.as_str() {
+            "PRINTER" => "*OUTPUT".to_string(),
+            "WORKSTN" => "*INPUT:*OUTPUT".to_string(),
+            _ => "*INPUT".to_string(),
+        });
+        
+        file.metadata.dependencies.push(statement.name.clone());
+        file.metadata.annotations.insert("device".to_string(), Value::String(device.to_ascii_lowercase()));
+        file.metadata.annotations.insert("usage".to_string(), Value::Array(
+            usage.split(':').map(|u| Value::String(u.trim_start_matches('*').to_ascii_lowercase())).collect(),
+        ));
+        file.metadata.annotations.insert("keyed".to_string(), Value::Bool(self.has_keyword(&statement.keywords, "KEYED")));
+        if device == "DISK" {
+            file.metadata.semantic_tags.push("database_access".to_string());
+        }
+        if let Some(format) = self.keyword_value(&statement.keywords, "RENAME") {
+            file.metadata.annotations.insert("rename".to_string(), Value::String(format));
+        }
+        
+        file.metadata.legacy_patterns.push(self.legacy_pattern(
+            "file_spec",
+            &statement.original,
+            match device.as_str() {
+                "DISK" => "Native record-level access; map to SQL or a repository layer",
+                "WORKSTN" => "5250 display file; replace with a web or API front end",
+                "PRINTER" => "Spooled printer file; replace with a report generator",
+                _ => "Device file; map to the target platform's I/O",
+            },
+            false,
+        ));
+        if statement.fixed {
+            file.metadata.legacy_patterns.push(self.legacy_pattern(
+                "fixed_format_spec",
+                &statement.original,
+                "Convert the F-spec to DCL-F",
+                false,
+            ));
+        }
+        
+        file
+    }
+    
+    fn file_device(&self, keywords: &str) -> String {
+        ["DISK", "PRINTER", "WORKSTN", "SEQ", "SPECIAL"].iter()
+            .find(|d| self.has_keyword(keywords, d) || self.keyword_value(keywords, d).is_some())
+            .unwrap_or(&"DISK")
+            .to_string()
+    }
+    
+    fn parse_definition(&self, statement: &RpgStatement) -> UIRNode {
+        let (node_type, tag) = match statement.opcode.as_str() {
+            "DCL-C" => (NodeType::Constant, "constant"),
+            "DCL-SUBF" => (NodeType::Variable, "subfield"),
+            "DCL-PARM" => (NodeType::Variable, "parameter"),
+            _ => (NodeType::Variable, "standalone"),
+        };
+        let mut node = self.create_node(node_type, Some(statement.name.clone()), statement.line, tag);
+        
+        let type_name = if statement.type_spec.is_empty() {
+            self.keyword_value(&statement.keywords, "LIKE").map(|l| format!("like({})", l))
+                .or_else(|| self.keyword_value(&statement.keywords, "LIKEDS").map(|l| format!("likeds({})", l)))
+        } else {
+            Some(statement.type_spec.clone())
+        };
+        if let Some(type_name) = type_name {
+            node.metadata.annotations.insert("type".to_string(), Value::String(type_name));
+        }
+        
+        let value = self.keyword_value(&statement.keywords, "CONST")
+            .filter(|_| statement.opcode == "DCL-C")
+            .or_else(|| self.keyword_value(&statement.keywords, "INZ"));
+        if let Some(value) = value {
+            node.metadata.annotations.insert("value".to_string(), Value::String(value));
+        }
+        if let Some(dimension) = self.keyword_value(&statement.keywords, "DIM") {
+            node.metadata.semantic_tags.push("array".to_string());
+            node.metadata.annotations.insert("dimension".to_string(), Value::String(dimension));
+        }
+        if statement.opcode == "DCL-PARM" {
+            let passing = if self.has_keyword(&statement.keywords, "VALUE") {
+                "value"
+            } else if self.has_keyword(&statement.keywords, "CONST") {
+                "const"
+            } else {
+                "reference"
+            };
+            node.metadata.annotations.insert("passing".to_string(), Value::String(passing.to_string()));
+            if let Some(options) = self.keyword_value(&statement.keywords, "OPTIONS") {
+                node.metadata.annotations.insert("options".to_string(), Value::String(options));
+            }
+        }
+        if statement.fixed && statement.opcode != "DCL-SUBF" && statement.opcode != "DCL-PARM" {
+            node.metadata.legacy_patterns.push(self.legacy_pattern(
+                "fixed_format_spec",
+                &statement.original,
+                "Convert the D-spec to a DCL-* declaration",
+                false,
+            ));
+        }
+        
+        node
+    }
+    
+    /// Data structures, prototypes and procedure interfaces own the subfields/parameters that follow
+    fn parse_group(&self, statements: &[RpgStatement], pos: &mut usize, node_type: NodeType, tag: &str, end: &str) -> UIRNode {
+        let statement = &statements[*pos];
+        *pos += 1;
+        
+        let mut node = self.create_node(node_type, Some(statement.name.clone()), statement.line, tag);
+        if !statement.type_spec.is_empty() {
+            let key = if tag == "data_structure" { "type" } else { "return_type" };
+            node.metadata.annotations.insert(key.to_string(), Value::String(statement.type_spec.clone()));
+        }
+        for keyword in ["QUALIFIED", "TEMPLATE", "EXPORT"] {
+            if self.has_keyword(&statement.keywords, keyword) {
+                node.metadata.semantic_tags.push(keyword.to_ascii_lowercase());
+            }
+        }
+        if let Some(file) = self.keyword_value(&statement.keywords, "EXTNAME") {
+            node.metadata.dependencies.push(file.clone());
+            node.metadata.annotations.insert("external_definition".to_string(), Value::String(file));
+        }
+        if let Some(dimension) = self.keyword_value(&statement.keywords, "DIM") {
+            node.metadata.annotations.insert("dimension".to_string(), Value::String(dimension));
+        }
+        
+        while let Some(member) = statements.get(*pos).filter(|s| s.opcode == "DCL-SUBF" || s.opcode == "DCL-PARM") {
+            node.children.push(self.parse_definition(member));
+            *pos += 1;
+        }
+        if statements.get(*pos).is_some_and(|s| s.opcode == end) {
+            *pos += 1;
+        }
+        
+        if statement.fixed {
+            node.metadata.legacy_patterns.push(self.legacy_pattern(
+                "fixed_format_spec",
+                &statement.original,
+                "Convert the D-spec to a DCL-* declaration",
+                false,
+            ));
+        }
+        node
+    }
+    
+    fn parse_procedure(&self, statements: &[RpgStatement], pos: &mut usize, files: &HashMap<String, String>) -> UIRNode {
+        let statement = &statements[*pos];
+        *pos += 1;
+        
+        let mut procedure = self.create_node(NodeType::Function, Some(statement.name.clone()), statement.line, "subprocedure");
+        if self.has_keyword(&statement.keywords, "EXPORT") {
+            procedure.metadata.semantic_tags.push("exported".to_string());
+            procedure.metadata.annotations.insert("exported".to_string(), Value::Bool(true));
+        }
+        if statement.fixed {
+            procedure.metadata.legacy_patterns.push(self.legacy_pattern(
+                "fixed_format_spec",
+                &statement.original,
+                "Convert the P-specs to DCL-PROC/END-PROC",
+                false,
+            ));
+        }
+        
+        // The procedure interface supplies the parameters and return type
+        let body = self.parse_block(statements, pos, &["END-PROC"], files);
+        for node in body {
+            if node.metadata.semantic_tags.iter().any(|t| t == "procedure_interface") {
+                if let Some(return_type) = node.metadata.annotations.get("return_type") {
+                    procedure.metadata.annotations.insert("return_type".to_string(), return_type.clone());
+                }
+                procedure.children.extend(node.children);
+            } else {
+                procedure.children.push(node);
+            }
+        }
+        self.expect_end(statements, pos, "END-PROC");
+        
+        if let Some(end) = statements.get(*pos - 1) {
+            if let Some(location) = procedure.source_location.as_mut() {
+                location.end_line = end.line;
+            }
+        }
+        procedure
+    }
+    
+    fn parse_if(&self, statements: &[RpgStatement], pos: &mut usize, files: &HashMap<String, String>) -> UIRNode {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        *pos += 1;
+        
+        let tag = if statement.opcode == "ELSEIF" { "elseif" } else { "if" };
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some(format!("{}_statement", tag)), line, tag);
+        node.children.push(self.parse_condition(statements, pos, statement, "IF"));
+        
+        let stops = ["ELSEIF", "ELSE", "ENDIF", "END"];
+        let mut then_block = self.create_node(NodeType::Statement(StatementType::Expression), Some("then".to_string()), line, "block");
+        then_block.children = self.parse_block(statements, pos, &stops, files);
+        node.children.push(then_block);
+        
+        match statements.get(*pos).map(|s| s.opcode.as_str()) {
+            Some("ELSEIF") => {
+                // ELSEIF nests as the else branch and shares the closing ENDIF
+                node.children.push(self.parse_if(statements, pos, files));
+                return node;
+            }
+            Some("ELSE") => {
+                let else_line = statements[*pos].line;
+                *pos += 1;
+                let mut else_block = self.create_node(NodeType::Statement(StatementType::Expression), Some("else".to_string()), else_line, "block");
+                else_block.children = self.parse_block(statements, pos, &["ENDIF", "END"], files);
+                node.children.push(else_block);
+            }
+            _ => {}
+        }
+        
+        self.expect_end(statements, pos, "ENDIF");
+        node
+    }
+    
+    fn parse_loop(&self, statements: &[RpgStatement], pos: &mut usize, files: &HashMap<String, String>) -> UIRNode {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        *pos += 1;
+        
+        let until = statement.opcode.starts_with("DOU");
+        let (loop_type, tag, prefix) = if until {
+            (LoopType::DoWhile, "dou", "DOU")
+        } else {
+            (LoopType::While, "dow", "DOW")
+        };
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(loop_type)), Some(tag.to_string()), line, tag);
+        let condition = self.parse_condition(statements, pos, statement, prefix);
+        if until {
+            // DOU repeats until the condition holds
+            node.metadata.annotations.insert("until".to_string(), Value::Bool(true));
+        }
+        node.children.push(condition);
+        node.children.extend(self.parse_block(statements, pos, &["ENDDO", "END"], files));
+        self.expect_end(statements, pos, "ENDDO");
+        node
+    }
+    
+    fn parse_select(&self, statements: &[RpgStatement], pos: &mut usize, files: &HashMap<String, String>) -> UIRNode {
+        let line = statements[*pos].line;
+        *pos += 1;
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Switch), Some("select".to_string()), line, "select");
+        while let Some(branch) = statements.get(*pos) {
+            let is_when = branch.opcode == "WHEN" || self.classic_compare(&branch.opcode, "WHEN").is_some();
+            if !is_when && branch.opcode != "OTHER" {
+                break;
+            }
+            *pos += 1;
+            
+            let mut case = if is_when {
+                let mut case = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("when".to_string()), branch.line, "when");
+                case.children.push(self.parse_condition(statements, pos, branch, "WHEN"));
+                case
+            } else {
+                self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("other".to_string()), branch.line, "other")
+            };
+            case.children.extend(self.parse_block(statements, pos, &["WHEN", "OTHER", "ENDSL", "END"], files));
+            node.children.push(case);
+        }
+        
+        self.expect_end(statements, pos, "ENDSL");
+        node
+    }
+    
+    fn parse_monitor(&self, statements: &[RpgStatement], pos: &mut usize, files: &HashMap<String, String>) -> UIRNode {
+        let line = statements[*pos].line;
+        *pos += 1;
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Try), Some("monitor".to_string()), line, "monitor");
+        let mut body = self.create_node(NodeType::Statement(StatementType::Expression), Some("monitor_block".to_string()), line, "block");
+        body.children = self.parse_block(statements, pos, &["ON-ERROR", "ENDMON", "END"], files);
+        node.children.push(body);
+        
+        while let Some(branch) = statements.get(*pos).filter(|s| s.opcode == "ON-ERROR") {
+            *pos += 1;
+            let mut handler = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("on_error".to_string()), branch.line, "on_error");
+            let codes: Vec<Value> = branch.factor2.split(':')
+                .map(|c| c.trim())
+                .filter(|c| !c.is_empty())
+                .map(|c| Value::String(c.to_string()))
+                .collect();
+            handler.metadata.annotations.insert("status_codes".to_string(), Value::Array(codes));
+            handler.children = self.parse_block(statements, pos, &["ON-ERROR", "ENDMON", "END"], files);
+            node.children.push(handler);
+        }
+        
+        self.expect_end(statements, pos, "ENDMON");
+        node
+    }
+    
+    /// Condition of IF/DOW/DOU/WHEN, including classic IFxx/ANDxx/ORxx chains
+    fn parse_condition(&self, statements: &[RpgStatement], pos: &mut usize, statement: &RpgStatement, prefix: &str) -> UIRNode {
+        let line = statement.line;
+        let Some(operator) = self.classic_compare(&statement.opcode, prefix) else {
+            return self.parse_expression(&statement.factor2, line);
+        };
+        
+        let mut condition = self.compare_factors(&statement.factor1, operator, &statement.factor2, line);
+        condition.metadata.legacy_patterns.push(self.legacy_pattern(
+            "fixed_format_comparison",
+            &statement.original,
+            &format!("Use {} with a free-form expression", prefix),
+            false,
+        ));
+        
+        while let Some(next) = statements.get(*pos) {
+            let (logical, operator) = if let Some(operator) = self.classic_compare(&next.opcode, "AND") {
+                ("AND", operator)
+            } else if let Some(operator) = self.classic_compare(&next.opcode, "OR") {
+                ("OR", operator)
+            } else {
+                break;
+            };
+            *pos += 1;
+            let right = self.compare_factors(&next.factor1, operator, &next.factor2, next.line);
+            condition = self.binary(NodeType::Expression(ExpressionType::Logical), condition, logical, right, line);
+        }
+        
+        condition
+    }
+    
+    /// `IFEQ` with prefix `IF` -> Some("=")
+    fn classic_compare(&self, opcode: &str, prefix: &str) -> Option<&'static str> {
+        let suffix = opcode.strip_prefix(prefix)?;
+        match suffix {
+            "EQ" => Some("="),
+            "NE" => Some("<>"),
+            "GT" => Some(">"),
+            "LT" => Some("<"),
+            "GE" => Some(">="),
+            "LE" => Some("<="),
+            _ => None,
+        }
+    }
+    
+    fn compare_factors(&self, factor1: &str, operator: &str, factor2: &str, line: u32) -> UIRNode {
+        let left = self.create_operand(factor1, line);
+        let right = self.create_operand(factor2, line);
+        self.binary(NodeType::Expression(ExpressionType::Comparison), left, operator, right, line)
+    }
+    
+    /// `FOR i = 1 TO 10 BY 2` / `FOR i = 10 DOWNTO 1`
+    fn annotate_for(&self, node: &mut UIRNode, text: &str) {
+        let operands = self.split_operands(text);
+        let mut key = "variable";
+        let mut values: HashMap<&str, Vec<String>> = HashMap::new();
+        for operand in operands {
+            match operand.to_ascii_uppercase().as_str() {
+                "=" => key = "from",
+                "TO" => key = "to",
+                "DOWNTO" => {
+                    key = "to";
+                    node.metadata.annotations.insert("descending".to_string(), Value::Bool(true));
+                }
+                "BY" => key = "by",
+                _ => values.entry(key).or_default().push(operand),
+            }
+        }
+        for (key, value) in values {
+            node.metadata.annotations.insert(key.to_string(), Value::String(value.join(" ")));
+        }
+    }
+    
+    fn parse_eval(&self, text: &str, line: u32) -> UIRNode {
+        let tokens = self.tokenize_expression(text);
+        let Some(split) = self.assignment_split(&tokens) else {
+            return self.parse_expression(text, line);
+        };
+        
+        let mut pos = 0;
+        let target = self.parse_or(&tokens[..split], &mut pos, line)
+            .unwrap_or_else(|| self.create_operand(&tokens[..split].join(""), line));
+        let mut pos = 0;
+        let value = self.parse_or(&tokens[split + 1..], &mut pos, line)
+            .unwrap_or_else(|| self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "empty"));
+        
+        let mut node = self.build_assignment(target, value, line);
+        if tokens[split] != "=" {
+            node.operator = Operator::binary(tokens[split].trim_end_matches('='), &CoalesceLanguage::Rpg);
+            node.metadata.annotations.insert("operator".to_string(), Value::String(tokens[split].clone()));
+        }
+        if node.children[0].metadata.semantic_tags.iter().any(|t| t == "indicator") {
+            node.metadata.semantic_tags.push("indicator".to_string());
+            node.metadata.legacy_patterns.push(self.legacy_pattern(
+                "indicator",
+                text,
+                "Replace numbered indicators with named boolean variables",
+                false,
+            ));
+        }
+        node
+    }
+    
+    /// Index of the top-level assignment operator, if the tokens form an assignment
+    fn assignment_split(&self, tokens: &[String]) -> Option<usize> {
+        let mut depth = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            match token.as_str() {
+                "(" => depth += 1,
+                ")" => depth -= 1,
+                "=" | "+=" | "-=" | "*=" | "/=" | "**=" if depth == 0 && i > 0 => return Some(i),
+                _ => {}
+            }
+        }
+        None
+    }
+    
+    /// ADD/SUB/MULT/DIV/Z-ADD/Z-SUB/MOVE in factor 1 / factor 2 / result form
+    fn parse_classic_operation(&self, statement: &RpgStatement) -> UIRNode {
+        let line = statement.line;
+        let opcode = statement.opcode.as_str();
+        let result = self.create_operand(&statement.result, line);
+        let factor2 = self.create_operand(&statement.factor2, line);
+        
+        let value = match opcode {
+            "Z-ADD" | "MOVE" | "MOVEL" | "MOVEA" => factor2,
+            "Z-SUB" => {
+                let mut negate = self.create_node(NodeType::Expression(ExpressionType::Arithmetic), None, line, "negation");
+                negate.operator = Some(Operator::Negate);
+                negate.metadata.annotations.insert("operator".to_string(), Value::String("-".to_string()));
+                negate.children.push(factor2);
+                negate
+            }
+            _ => {
+                // Without factor 1 the result field is the first operand
+                let left = if statement.factor1.is_empty() {
+                    self.create_operand(&statement.result, line)
+                } else {
+                    self.create_operand(&statement.factor1, line)
+                };
+                let operator = match opcode {
+                    "ADD" => "+",
+                    "SUB" => "-",
+                    "MULT" => "*",
+                    _ => "/",
+                };
+                self.binary(NodeType::Expression(ExpressionType::Arithmetic), left, operator, factor2, line)
+            }
+        };
+        
+        let mut node = self.build_assignment(result, value, line);
+        let hint = if opcode.starts_with("MOVE") {
+            "MOVE truncates and pads by position; use EVAL with %SUBST or %EDITC"
+        } else {
+            "Use EVAL with a free-form expression"
+        };
+        node.metadata.legacy_patterns.push(self.legacy_pattern(
+            &opcode.to_ascii_lowercase(),
+            &statement.original,
+            hint,
+            opcode.starts_with("MOVE"),
+        ));
+        node
+    }
+    
+    fn parse_file_operation(&self, statement: &RpgStatement, files: &HashMap<String, String>) -> UIRNode {
+        let line = statement.line;
+        let file = statement.factor2.clone();
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(statement.opcode.clone()), line, "file_io");
+        node.metadata.annotations.insert("file".to_string(), Value::String(file.clone()));
+        node.metadata.dependencies.push(file.clone());
+        
+        // Record formats are not in the file map; assume database access for them
+        let device = files.get(&file.to_ascii_uppercase()).map(String::as_str).unwrap_or("DISK");
+        match device {
+            "DISK" => node.metadata.semantic_tags.push("database_access".to_string()),
+            "WORKSTN" => node.metadata.semantic_tags.push("screen_io".to_string()),
+            "PRINTER" => node.metadata.semantic_tags.push("print_output".to_string()),
+            _ => {}
+        }
+        
+        if !statement.factor1.is_empty() {
+            node.metadata.annotations.insert("key".to_string(), Value::String(statement.factor1.clone()));
+            node.children.push(self.parse_expression(&statement.factor1, line));
+        }
+        if !statement.result.is_empty() {
+            node.metadata.annotations.insert("record".to_string(), Value::String(statement.result.clone()));
+        }
+        node
+    }
+    
+    fn wrap_conditioning(&self, node: UIRNode, indicators: &str, original: &str, line: u32) -> UIRNode {
+        let (negated, indicator) = match indicators.strip_prefix(['N', 'n']) {
+            Some(rest) => (true, rest.trim()),
+            None => (false, indicators.trim()),
+        };
+        
+        let mut condition = self.create_operand(&format!("*IN{}", indicator), line);
+        if negated {
+            let mut not = self.create_node(NodeType::Expression(ExpressionType::Logical), None, line, "not");
+            not.operator = Some(Operator::Not);
+            not.metadata.annotations.insert("operator".to_string(), Value::String("NOT".to_string()));
+            not.children.push(condition);
+            condition = not;
+        }
+        
+        let mut wrapper = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("conditioned".to_string()), line, "conditioning_indicator");
+        wrapper.metadata.legacy_patterns.push(self.legacy_pattern(
+            "conditioning_indicator",
+            original,
+            "Replace the conditioning indicator with an explicit IF",
+            false,
+        ));
+        wrapper.children = vec![condition, node];
+        wrapper
+    }
+    
+    fn build_assignment(&self, target: UIRNode, value: UIRNode, line: u32) -> UIRNode {
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::Assignment), target.name.clone(), line, "assignment");
+        node.children = vec![target, value];
+        node
+    }
+    
+    /// Tokenize a free-form expression into operators, names and literals
+    fn tokenize_expression(&self, text: &str) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut tokens: Vec<String> = Vec::new();
+        let mut i = 0;
+        
+        while i < chars.len() {
+            let c = chars[i];
+            if c.is_whitespace() {
+                i += 1;
+                continue;
+            }
+            
+            if c == '\'' {
+                let mut literal = String::from('\'');
+                i += 1;
+                while i < chars.len() {
+                    literal.push(chars[i]);
+                    if chars[i] == '\'' {
+                        if chars.get(i + 1) == Some(&'\'') {
+                            literal.push('\'');
+                            i += 2;
+                            continue;
+                        }
+                        i += 1;
+                        break;
+                    }
+                    i += 1;
+                }
+                tokens.push(literal);
+                continue;
+            }
+            
+            // `*` starts a special name (*ON, *INLR) where an operand is expected
+            let expects_operand = tokens.last().is_none_or(|t| {
+                matches!(t.as_str(), "(" | ":" | "=" | "<>" | "<" | ">" | "<=" | ">=" | "+" | "-" | "*" | "/" | "**")
+                    || t.eq_ignore_ascii_case("AND") || t.eq_ignore_ascii_case("OR") || t.eq_ignore_ascii_case("NOT")
+            });
+            if c == '*' && expects_operand && chars.get(i + 1).is_some_and(|n| n.is_ascii_alphabetic()) {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(chars[start..i].iter().collect());
+                continue;
+            }
+            
+            if c.is_alphanumeric() || matches!(c, '_' | '#' | '@' | '$' | '%') {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '#' | '@' | '$' | '%' | '.')) {
+                    i += 1;
+                }
+                tokens.push(chars[start..i].iter().collect());
+                continue;
+            }
+            
+            let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            let three: String = chars[i..(i + 3).min(chars.len())].iter().collect();
+            if three == "**=" {
+                tokens.push(three);
+                i += 3;
+            } else if matches!(two.as_str(), "**" | "<=" | ">=" | "<>" | "+=" | "-=" | "*=" | "/=") {
+                tokens.push(two);
+                i += 2;
+            } else {
+                tokens.push(c.to_string());
+                i += 1;
+            }
+        }
+        
+        tokens
+    }
+    
+    fn parse_expression(&self, text: &str, line: u32) -> UIRNode {
+        let tokens = self.tokenize_expression(text);
+        let mut pos = 0;
+        let mut node = self.parse_or(&tokens, &mut pos, line)
+            .unwrap_or_else(|| self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "empty"));
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(text.trim().to_string()));
+        node
+    }
+    
+    fn parse_or(&self, tokens: &[String], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let mut left = self.parse_and(tokens, pos, line)?;
+        while tokens.get(*pos).is_some_and(|t| t.eq_ignore_ascii_case("OR")) {
+            *pos += 1;
+            let right = self.parse_and(tokens, pos, line)?;
+            left = self.binary(NodeType::Expression(ExpressionType::Logical), left, "OR", right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_and(&self, tokens: &[String], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let mut left = self.parse_not(tokens, pos, line)?;
+        while tokens.get(*pos).is_some_and(|t| t.eq_ignore_ascii_case("AND")) {
+            *pos += 1;
+            let right = self.parse_not(tokens, pos, line)?;
+            left = self.binary(NodeType::Expression(ExpressionType::Logical), left, "AND", right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_not(&self, tokens: &[String], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        if tokens.get(*pos).is_some_and(|t| t.eq_ignore_ascii_case("NOT")) {
+            *pos += 1;
+            let operand = self.parse_not(tokens, pos, line)?;
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Logical), None, line, "not");
+            node.operator = Some(Operator::Not);
+            node.metadata.annotations.insert("operator".to_string(), Value::String("NOT".to_string()));
+            node.children.push(operand);
+            return Some(node);
+        }
+        self.parse_comparison(tokens, pos, line)
+    }
+    
+    fn parse_comparison(&self, tokens: &[String], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let left = self.parse_additive(tokens, pos, line)?;
+        let Some(operator) = tokens.get(*pos).filter(|t| matches!(t.as_str(), "=" | "<>" | "<" | ">" | "<=" | ">=")) else {
+            return Some(left);
+        };
+        let operator = operator.clone();
+        *pos += 1;
+        let right = self.parse_additive(tokens, pos, line)?;
+        Some(self.binary(NodeType::Expression(ExpressionType::Comparison), left, &operator, right, line))
+    }
+    
+    fn parse_additive(&self, tokens: &[String], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let mut left = self.parse_multiplicative(tokens, pos, line)?;
+        while let Some(op) = tokens.get(*pos).filter(|t| matches!(t.as_str(), "+" | "-")) {
+            let op = op.clone();
+            *pos += 1;
+            let right = self.parse_multiplicative(tokens, pos, line)?;
+            left = self.binary(NodeType::Expression(ExpressionType::Arithmetic), left, &op, right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_multiplicative(&self, tokens: &[String], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let mut left = self.parse_unary(tokens, pos, line)?;
+        while let Some(op) = tokens.get(*pos).filter(|t| matches!(t.as_str(), "*" | "/" | "**")) {
+            let op = op.clone();
+            *pos += 1;
+            let right = self.parse_unary(tokens, pos, line)?;
+            left = self.binary(NodeType::Expression(ExpressionType::Arithmetic), left, &op, right, line);
+        }
+        Some(left)
+    }
+    
+    fn parse_unary(&self, tokens: &[String], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        if tokens.get(*pos).is_some_and(|t| t == "-" || t == "+") {
+            let operator = tokens[*pos].clone();
+            *pos += 1;
+            let operand = self.parse_unary(tokens, pos, line)?;
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Arithmetic), None, line, "negation");
+            node.operator = Operator::unary(&operator, &CoalesceLanguage::Rpg);
+            node.metadata.annotations.insert("operator".to_string(), Value::String(operator));
+            node.children.push(operand);
+            return Some(node);
+        }
+        self.parse_primary(tokens, pos, line)
+    }
+    
+    fn parse_primary(&self, tokens: &[String], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let token = tokens.get(*pos)?.clone();
+        *pos += 1;
+        
+        if token == "(" {
+            let mut inner = self.parse_or(tokens, pos, line)?;
+            if tokens.get(*pos).is_some_and(|t| t == ")") {
+                *pos += 1;
+            }
+            mark_grouped(&mut inner);
+            return Some(inner);
+        }
+        
+        // name(arg1 : arg2) is a procedure or built-in function call
+        if tokens.get(*pos).is_some_and(|t| t == "(") && !token.starts_with('\'') && !token.starts_with('*') {
+            *pos += 1;
+            let tag = if token.starts_with('%') { "builtin_function" } else { "call" };
+            let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(token.clone()), line, tag);
+            while *pos < tokens.len() && tokens[*pos] != ")" {
+                if tokens[*pos] == ":" {
+                    *pos += 1;
+                    continue;
+                }
+                match self.parse_or(tokens, pos, line) {
+                    Some(argument) => call.children.push(argument),
+                    None => break,
+                }
+            }
+            if tokens.get(*pos).is_some_and(|t| t == ")") {
+                *pos += 1;
+            }
+            return Some(call);
+        }
+        
+        Some(self.create_operand(&token, line))
+    }
+    
+    fn binary(&self, node_type: NodeType, left: UIRNode, op: &str, right: UIRNode, line: u32) -> UIRNode {
+        let tag = match node_type {
+            NodeType::Expression(ExpressionType::Logical) => "logical",
+            NodeType::Expression(ExpressionType::Comparison) => "comparison",
+            _ => "arithmetic",
+        };
+        let mut node = self.create_node(node_type, None, line, tag);
+        node.operator = Operator::binary(op, &CoalesceLanguage::Rpg);
+        node.metadata.annotations.insert("operator".to_string(), Value::String(op.to_string()));
+        node.children = vec![left, right];
+        node
+    }
+    
+    fn create_operand(&self, text: &str, line: u32) -> UIRNode {
+        let upper = text.to_ascii_uppercase();
+        let mut node = if text.starts_with('\'') || text.parse::<f64>().is_ok() {
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "literal");
+            node.literal = LiteralValue::parse(text, &CoalesceLanguage::Rpg);
+            node
+        } else if FIGURATIVE_CONSTANTS.contains(&upper.as_str()) {
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "figurative_constant");
+            node.literal = LiteralValue::parse(text, &CoalesceLanguage::Rpg);
+            node
+        } else if upper.starts_with("*IN") {
+            self.create_node(NodeType::Expression(ExpressionType::Variable), Some(upper.clone()), line, "indicator")
+        } else {
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Variable), Some(text.to_string()), line, "identifier");
+            if text.contains('.') {
+                node.metadata.semantic_tags.push("qualified".to_string());
+            }
+            node
+        };
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(text.to_string()));
+        node
+    }
+    
+    /// Value of a `KEYWORD(value)` entry, matched case-insensitively
+    fn keyword_value(&self, keywords: &str, keyword: &str) -> Option<String> {
+        let upper = keywords.to_ascii_uppercase();
+        let needle = format!("{}(", keyword);
+        let mut search = 0;
+        while let Some(found) = upper[search..].find(&needle) {
+            let start = search + found;
+            let boundary = start == 0 || !upper[..start].ends_with(|c: char| c.is_alphanumeric() || c == '-');
+            if boundary {
+                let open = start + needle.len();
+                let mut depth = 1;
+                for (offset, c) in keywords[open..].char_indices() {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return Some(keywords[open..open + offset].trim().to_string());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                return Some(keywords[open..].trim().to_string());
+            }
+            search = start + needle.len();
+        }
+        None
+    }
+    
+    fn has_keyword(&self, keywords: &str, keyword: &str) -> bool {
+        self.split_operands(keywords).iter().any(|k| k.eq_ignore_ascii_case(keyword))
+    }
+    
+    fn unquote(&self, text: &str) -> String {
+        text.trim().trim_matches('\'').to_string()
+    }
+    
+    fn legacy_pattern(&self, pattern_type: &str, original: &str, hint: &str, preserve_exactly: bool) -> LegacyPattern {
+        LegacyPattern {
+            pattern_type: pattern_type.to_string(),
+            original_construct: original.to_string(),
+            modernization_hint: Some(hint.to_string()),
+            preserve_exactly,
+        }
+    }
+    
+    fn create_node(&self, node_type: NodeType, name: Option<String>, line: u32, tag: &str) -> UIRNode {
+        let id = format!(
+            "{}_{}_{}",
+            tag,
+            line,
+            name.as_deref().unwrap_or("").replace(|c: char| !c.is_alphanumeric(), "_"),
+        );
+        
+        UIRNode {
+            id,
+            node_type,
+            name,
+            children: Vec::new(),
+            metadata: Metadata {
+                source_language: CoalesceLanguage::Rpg,
+                semantic_tags: vec![tag.to_string()],
+                ..Default::default()
+            },
+            source_location: Some(SourceLocation {
+                file: String::new(),
+                start_line: line,
+                end_line: line,
+                start_column: 0,
+                end_column: 0,
+            }),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_rpg_fixed_format_specs() {
+        let parser = RpgParser::new().unwrap();
+        let source = [
+            "     H DFTACTGRP(*NO)",
+            "     FCUSTMAST  IF   E           K DISK",
+            "     D total           S              9P 2 INZ(0)",
+            "     C     custno        CHAIN     CUSTMAST                           90",
+            "     C     *IN90         IFEQ      *OFF",
+            "     C                   ADD       balance       total",
+            "     C                   EXSR      report",
+            "     C                   ENDIF",
+            "     C                   EVAL      *INLR = *ON",
+            "     C     report        BEGSR",
+            "     C                   ENDSR",
+        ].join("\n");
+        
+        let uir = parser.parse(&source).unwrap();
+        assert_eq!(uir.metadata.annotations["format"], "fixed");
+        
+        let file = &uir.children[0];
+        assert_eq!(file.name.as_deref(), Some("CUSTMAST"));
+        assert_eq!(file.metadata.annotations["keyed"], true);
+        assert!(file.metadata.legacy_patterns.iter().any(|p| p.pattern_type == "file_spec"));
+        
+        let total = &uir.children[1];
+        assert_eq!(total.metadata.annotations["type"], "packed(9:2)");
+        assert_eq!(total.metadata.annotations["value"], "0");
+        
+        let chain = &uir.children[2];
+        assert!(chain.metadata.semantic_tags.contains(&"database_access".to_string()));
+        assert_eq!(chain.metadata.annotations["key"], "custno");
+        assert_eq!(chain.metadata.annotations["resulting_indicators"], "90");
+        
+        let condition = &uir.children[3];
+        assert_eq!(condition.node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        let then_block = &condition.children[1];
+        assert_eq!(then_block.children.len(), 2);
+        assert!(then_block.children[0].metadata.legacy_patterns.iter().any(|p| p.pattern_type == "add"));
+        
+        assert!(uir.children[4].metadata.semantic_tags.contains(&"indicator".to_string()));
+        assert!(uir.children[5].metadata.semantic_tags.contains(&"subroutine".to_string()));
+    }
+    
+    #[test]
+    fn test_rpg_free_format_subprocedure() {
+        let parser = RpgParser::new().unwrap();
+        let source = r#"**FREE
+ctl-opt nomain;
+dcl-f orders disk keyed usage(*update);
+dcl-ds order_t qualified template;
+  id int(10);
+  amount packed(11:2);
+end-ds;
+
+dcl-proc applyDiscount export;
+  dcl-pi *n packed(11:2);
+    amount packed(11:2) const;
+    rate int(5) value;
+  end-pi;
+  dcl-s result packed(11:2);
+
+  select;
+    when rate > 50;
+      result = amount;
+    other;
+      result = amount - (amount * rate / 100);
+  endsl;
+  return result;
+end-proc;
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.metadata.annotations["format"], "free");
+        
+        let file = &uir.children[0];
+        assert_eq!(file.metadata.annotations["usage"], serde_json::json!(["update"]));
+        
+        let structure = &uir.children[1];
+        assert_eq!(structure.node_type, NodeType::Class);
+        assert_eq!(structure.children.len(), 2);
+        
+        let procedure = &uir.children[2];
+        assert_eq!(procedure.node_type, NodeType::Function);
+        assert_eq!(procedure.metadata.annotations["exported"], true);
+        assert_eq!(procedure.metadata.annotations["return_type"], "packed(11:2)");
+        assert_eq!(procedure.children[0].metadata.annotations["passing"], "const");
+        assert_eq!(procedure.children[1].metadata.annotations["passing"], "value");
+        
+        let select = &procedure.children[3];
+        assert_eq!(select.node_type, NodeType::ControlFlow(ControlFlowType::Switch));
+        assert_eq!(select.children.len(), 2);
+        assert_eq!(procedure.children[4].node_type, NodeType::Statement(StatementType::Return));
+    }
+    
+    #[test]
+    fn test_rpg_mixed_format_procedure() {
+        let parser = RpgParser::new().unwrap();
+        let source = [
+            "     P getName         B                   EXPORT",
+            "     D getName         PI            30A",
+            "     D  custId                        7P 0 CONST",
+            "      /free",
+            "        chain custId custmast;",
+            "        if %found(custmast);",
+            "          return %trim(name);",
+            "        endif;",
+            "        return '';",
+            "      /end-free",
+            "     P getName         E",
+        ].join("\n");
+        
+        let uir = parser.parse(&source).unwrap();
+        assert_eq!(uir.metadata.annotations["format"], "mixed");
+        
+        let procedure = &uir.children[0];
+        assert_eq!(procedure.name.as_deref(), Some("getName"));
+        assert_eq!(procedure.metadata.annotations["return_type"], "char(30)");
+        assert_eq!(procedure.children[0].name.as_deref(), Some("custId"));
+        assert_eq!(procedure.children[0].metadata.annotations["type"], "packed(7:0)");
+        
+        let chain = &procedure.children[1];
+        assert_eq!(chain.metadata.annotations["file"], "custmast");
+        let condition = &procedure.children[2].children[0];
+        assert!(condition.metadata.semantic_tags.contains(&"builtin_function".to_string()));
+    }
+}
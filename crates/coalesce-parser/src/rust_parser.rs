@@ -1,11 +1,17 @@
-use tree_sitter::{Language, Node, Parser};
-use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage, 
-                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser};
+use tree_sitter::Node;
+use crate::recovery;
+use crate::comments;
+use crate::closures;
+use crate::operators::{annotate_operator, unwrap_groups};
+use crate::literals::annotate_literal;
+use crate::incremental::SyntaxTree;
+use coalesce_core::{UIRNode, NodeType, Attribute, CaptureMode, EnumVariant, ImportedSymbol, Ownership, VariantField, Visibility, Metadata, SourceLocation, Language as CoalesceLanguage, 
+                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser,
+                   IncrementalParser, IncrementalUpdate, TextEdit};
 use serde_json::Value;
 use std::collections::HashMap;
 
 pub struct RustParser {
-    parser: Parser,
 }
 
 impl CoalesceParser for RustParser {
@@ -22,7 +28,7 @@ impl CoalesceParser for RustParser {
                 line: 0,
                 column: 0,
             })?;
-            
+        
         let tree = parser.parse(source, None)
             .ok_or_else(|| CoalesceError::ParseError {
                 message: "Failed to parse Rust source".to_string(),
@@ -30,18 +36,53 @@ impl CoalesceParser for RustParser {
                 column: 0,
             })?;
         
-        let root_node = tree.root_node();
-        self.convert_to_uir(source, root_node, 0)
+        self.convert_root(source, tree.root_node())
+    }
+}
+
+impl IncrementalParser for RustParser {
+    type Tree = SyntaxTree;
+    
+    fn parse_tree(&self, source: &str) -> Result<(SyntaxTree, UIRNode)> {
+        let tree = SyntaxTree::parse(tree_sitter_rust::language(), source)?;
+        let uir = self.convert_root(source, tree.root_node())?;
+        Ok((tree, uir))
+    }
+    
+    fn reparse(&self, previous: &SyntaxTree, edits: &[TextEdit]) -> Result<IncrementalUpdate<SyntaxTree>> {
+        previous.reparse(edits, |source, node| self.convert_to_uir(source, node))
+    }
+    
+    fn to_uir(&self, tree: &SyntaxTree) -> Result<UIRNode> {
+        self.convert_root(tree.source(), tree.root_node())
     }
 }
 
 impl RustParser {
     pub fn new() -> Result<Self> {
         // We don't need to store the parser, we'll create it per-parse
-        Ok(Self { parser: tree_sitter::Parser::new() })
+        Ok(Self {})
     }
     
-    fn convert_to_uir(&self, source: &str, node: Node, depth: usize) -> Result<UIRNode> {
+    fn convert_root(&self, source: &str, root_node: Node) -> Result<UIRNode> {
+        let mut uir = self.convert_to_uir(source, root_node)?;
+        recovery::summarize(source, root_node, &mut uir);
+        unwrap_groups(&mut uir);
+        uir.make_ids_unique();
+        comments::attach_comments(&mut uir);
+        closures::annotate_captures(&mut uir);
+        uir.debug_validate();
+        Ok(uir)
+    }
+    
+    fn convert_to_uir(&self, source: &str, node: Node) -> Result<UIRNode> {
+        if recovery::is_damaged(node) {
+            return recovery::error_node(source, node, CoalesceLanguage::Rust, |child| self.convert_to_uir(source, child));
+        }
+        if comments::is_comment(node) {
+            return Ok(comments::comment_node(node, source, &CoalesceLanguage::Rust));
+        }
+        
         let node_type = node.kind();
         let start_position = node.start_position();
         let end_position = node.end_position();
@@ -79,6 +120,7 @@ impl RustParser {
         
         let (uir_node_type, name) = match node_type {
             "source_file" => (NodeType::Module, Some("rust_program".to_string())),
+            "await_expression" => (NodeType::Expression(ExpressionType::Await), Some("await".to_string())),
             "function_item" => {
                 let func_name = self.extract_function_name(source, node);
                 (NodeType::Function, func_name)
@@ -93,7 +135,16 @@ impl RustParser {
             }
             "enum_item" => {
                 let enum_name = self.extract_enum_name(source, node);
-                (NodeType::Class, enum_name)
+                (NodeType::Enum { variants: self.enum_variants(source, node) }, enum_name)
+            }
+            "field_declaration" => {
+                let field_name = node.child_by_field_name("name")
+                    .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                    .map(str::to_string);
+                let field_type = node.child_by_field_name("type")
+                    .and_then(|t| t.utf8_text(source.as_bytes()).ok())
+                    .map(str::to_string);
+                (NodeType::Field { field_type, default_value: None }, field_name)
             }
             "trait_item" => {
                 let trait_name = self.extract_trait_name(source, node);
@@ -103,6 +154,15 @@ impl RustParser {
                 let param_name = self.extract_parameter_name(source, node);
                 (NodeType::Variable, param_name)
             }
+            "closure_expression" => {
+                let mut cursor = node.walk();
+                let moves = node.children(&mut cursor).any(|c| c.kind() == "move");
+                (closures::closure(if moves { CaptureMode::ByValue } else { CaptureMode::ByReference }), None)
+            }
+            // Untyped closure parameters, as in `|x| x + 1`
+            "identifier" if node.parent().is_some_and(|p| p.kind() == "closure_parameters") => {
+                (NodeType::Variable, Some(original_text.clone()))
+            }
             "identifier" => {
                 let var_name = Some(original_text.clone());
                 (NodeType::Expression(ExpressionType::Variable), var_name)
@@ -119,13 +179,13 @@ impl RustParser {
             "return_expression" => {
                 (NodeType::Statement(StatementType::Return), None)
             }
-            "binary_expression" => {
+            "binary_expression" | "unary_expression" => {
                 (NodeType::Expression(ExpressionType::Arithmetic), None)
             }
             "call_expression" => {
                 (NodeType::Expression(ExpressionType::FunctionCall), None)
             }
-            "assignment_expression" => {
+            "assignment_expression" | "compound_assignment_expr" => {
                 (NodeType::Expression(ExpressionType::Assignment), None)
             }
             "if_expression" => {
@@ -147,6 +207,13 @@ impl RustParser {
                 let mod_name = self.extract_mod_name(source, node);
                 (NodeType::Module, mod_name)
             }
+            "use_declaration" => self.use_import(source, node),
+            "macro_invocation" => {
+                let macro_name = node.child_by_field_name("macro")
+                    .and_then(|m| m.utf8_text(source.as_bytes()).ok())
+                    .map(str::to_string);
+                (NodeType::Expression(ExpressionType::FunctionCall), macro_name)
+            }
             _ => {
                 // For other node types, try to categorize them generically
                 if node_type.contains("statement") || node_type.contains("expression_statement") {
@@ -166,20 +233,417 @@ impl RustParser {
             children: Vec::new(),
             metadata,
             source_location: Some(source_location),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
         };
+        annotate_operator(node, source, &CoalesceLanguage::Rust, &mut uir_node);
+        annotate_literal(node, source, &CoalesceLanguage::Rust, &mut uir_node);
+        
+        if let NodeType::Import { path, .. } = &uir_node.node_type {
+            // The imported path is the whole of a `use`; its tokens are not kept
+            uir_node.metadata.dependencies.push(path.clone());
+            uir_node.attributes = self.outer_attributes(source, node);
+            return Ok(uir_node);
+        }
+        
+        self.annotate_generics(source, node, &mut uir_node);
+        self.annotate_ownership(source, node, &mut uir_node);
+        if matches!(uir_node.node_type, NodeType::Field { .. } | NodeType::Enum { .. }) {
+            // Fields and variants are fully described by their node type
+            uir_node.visibility = self.visibility(node);
+            uir_node.attributes = self.outer_attributes(source, node);
+            return Ok(uir_node);
+        }
+        self.annotate_macro(source, node, &mut uir_node);
+        uir_node.is_async = self.is_async(node);
+        uir_node.attributes = self.outer_attributes(source, node);
         
-        // Process children
+        // Process children; an await keeps only the awaited future
+        let operand_only = uir_node.node_type == NodeType::Expression(ExpressionType::Await);
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            if !child.is_error() {
-                let child_uir = self.convert_to_uir(source, child, depth + 1)?;
-                uir_node.children.push(child_uir);
+            if operand_only && !child.is_named() {
+                continue;
+            }
+            // A struct's fields are its own members rather than those of its body
+            if node_type == "struct_item" && child.kind() == "field_declaration_list" {
+                let mut fields = child.walk();
+                for field in child.named_children(&mut fields) {
+                    uir_node.children.push(self.convert_to_uir(source, field)?);
+                }
+                continue;
             }
+            let child_uir = self.convert_to_uir(source, child)?;
+            uir_node.children.push(child_uir);
         }
         
         Ok(uir_node)
     }
     
+    /// The `#[...]` attributes before an item, field or variant, which the grammar keeps as its
+    /// preceding siblings
+    fn outer_attributes(&self, source: &str, node: Node) -> Vec<Attribute> {
+        if !node.is_named() || node.kind() == "attribute_item" || comments::is_comment(node) {
+            return Vec::new();
+        }
+        let mut attributes = Vec::new();
+        let mut sibling = node.prev_sibling();
+        while let Some(previous) = sibling.filter(|s| s.kind() == "attribute_item" || comments::is_comment(*s)) {
+            if let Some(attribute) = previous.named_children(&mut previous.walk()).find(|c| c.kind() == "attribute") {
+                attributes.push(Attribute::parse(attribute.utf8_text(source.as_bytes()).unwrap_or("")));
+            }
+            sibling = previous.prev_sibling();
+        }
+        attributes.reverse();
+        attributes
+    }
+    
+    /// `use a::b;` and `use a::b as c;` import the item `b` of `a`, `use a::{b, c::d};` several
+    /// items of `a` (nested paths kept as written), `use a::*;` all of them and `use serde;` the
+    /// crate itself
+    fn use_import(&self, source: &str, node: Node) -> (NodeType, Option<String>) {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let field = |n: Node, name: &str| n.child_by_field_name(name).map(text).unwrap_or_default();
+        let list = |n: Option<Node>| -> Vec<ImportedSymbol> {
+            let Some(list) = n else { return Vec::new() };
+            let mut cursor = list.walk();
+            let symbols = list.named_children(&mut cursor)
+                .filter(|c| !comments::is_comment(*c))
+                .map(|c| ImportedSymbol::parse(&text(c)))
+                .collect();
+            symbols
+        };
+        
+        let Some(argument) = node.child_by_field_name("argument") else {
+            return (NodeType::Import { path: String::new(), alias: None, symbols: Vec::new() }, None);
+        };
+        let (path, alias, symbols) = match argument.kind() {
+            "scoped_identifier" => (field(argument, "path"), None, vec![ImportedSymbol::new(field(argument, "name"))]),
+            "use_as_clause" => {
+                let target = argument.child_by_field_name("path");
+                let alias = field(argument, "alias");
+                match target.filter(|t| t.kind() == "scoped_identifier") {
+                    Some(scoped) => (field(scoped, "path"), None, vec![ImportedSymbol { name: field(scoped, "name"), alias: Some(alias) }]),
+                    None => (target.map(text).unwrap_or_default(), Some(alias), Vec::new()),
+                }
+            }
+            "use_wildcard" => (text(argument).trim_end_matches('*').trim_end_matches("::").to_string(), None, vec![ImportedSymbol::new("*")]),
+            "scoped_use_list" => (field(argument, "path"), None, list(argument.child_by_field_name("list"))),
+            "use_list" => (String::new(), None, list(Some(argument))),
+            _ => (text(argument), None, Vec::new()),
+        };
+        (NodeType::Import { path: path.clone(), alias, symbols }, Some(path))
+    }
+    
+    /// `pub`, or a restricted form such as `pub(crate)` that stays inside the crate
+    fn visibility(&self, node: Node) -> Option<Visibility> {
+        let mut cursor = node.walk();
+        let modifier = node.children(&mut cursor).find(|c| c.kind() == "visibility_modifier")?;
+        Some(if modifier.child_count() > 1 { Visibility::Internal } else { Visibility::Public })
+    }
+    
+    /// Record how a parameter, receiver or field holds its value: borrowed with `&` or `&mut`,
+    /// through a raw pointer, or by value, where anything but a primitive is moved in
+    fn annotate_ownership(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        let mut cursor = node.walk();
+        let has_mut = node.children(&mut cursor).any(|c| c.kind() == "mutable_specifier");
+        match node.kind() {
+            "self_parameter" => {
+                let borrowed = node.child(0).is_some_and(|c| c.kind() == "&");
+                uir_node.ownership = Some(if borrowed { Ownership::Reference { mutable: has_mut } } else { Ownership::Owned });
+            }
+            "parameter" | "field_declaration" => {
+                let Some(declared) = node.child_by_field_name("type") else {
+                    return;
+                };
+                if node.kind() == "parameter" {
+                    let written = declared.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+                    uir_node.metadata.annotations.insert("type".to_string(), Value::String(written));
+                }
+                let mut type_cursor = declared.walk();
+                let mutable = declared.children(&mut type_cursor).any(|c| c.kind() == "mutable_specifier");
+                uir_node.ownership = Some(match declared.kind() {
+                    "reference_type" => Ownership::Reference { mutable },
+                    "pointer_type" => Ownership::Pointer { mutable },
+                    "primitive_type" => Ownership::ByValue,
+                    _ => Ownership::Owned,
+                });
+            }
+            _ => {}
+        }
+    }
+    
+    /// The variants of an `enum`, with their discriminants and the fields of tuple and struct
+    /// variants
+    fn enum_variants(&self, source: &str, node: Node) -> Vec<EnumVariant> {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let Some(body) = node.child_by_field_name("body") else {
+            return Vec::new();
+        };
+        let mut cursor = body.walk();
+        let variants = body.named_children(&mut cursor)
+            .filter(|c| c.kind() == "enum_variant")
+            .map(|variant| {
+                let mut fields = Vec::new();
+                if let Some(payload) = variant.child_by_field_name("body") {
+                    let mut cursor = payload.walk();
+                    for field in payload.named_children(&mut cursor) {
+                        match field.kind() {
+                            "field_declaration" => fields.push(VariantField {
+                                name: field.child_by_field_name("name").map(text),
+                                field_type: field.child_by_field_name("type").map(text).unwrap_or_default(),
+                            }),
+                            "attribute_item" | "visibility_modifier" => {}
+                            _ if comments::is_comment(field) => {}
+                            _ => fields.push(VariantField { name: None, field_type: text(field) }),
+                        }
+                    }
+                }
+                EnumVariant {
+                    name: variant.child_by_field_name("name").map(text).unwrap_or_default(),
+                    value: variant.child_by_field_name("value").map(text),
+                    fields,
+                }
+            })
+            .collect();
+        variants
+    }
+    
+    /// `async fn`, `async` blocks and `async` closures
+    fn is_async(&self, node: Node) -> bool {
+        let marker = match node.kind() {
+            "function_item" | "function_signature_item" => {
+                let mut cursor = node.walk();
+                let modifiers = node.children(&mut cursor).find(|c| c.kind() == "function_modifiers");
+                modifiers
+            }
+            "async_block" | "closure_expression" => Some(node),
+            _ => None,
+        };
+        marker.is_some_and(|marker| {
+            let mut cursor = marker.walk();
+            let found = marker.children(&mut cursor).any(|c| c.kind() == "async");
+            found
+        })
+    }
+    
+    /// Record generic parameters, lifetimes, trait bounds and where-clauses
+    fn annotate_generics(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        let metadata = &mut uir_node.metadata;
+        
+        if node.kind() == "impl_item" {
+            if let Some(trait_node) = node.child_by_field_name("trait") {
+                metadata.annotations.insert("implements_trait".to_string(), Value::String(text(trait_node)));
+                if let Some(self_type) = node.child_by_field_name("type") {
+                    metadata.annotations.insert("self_type".to_string(), Value::String(text(self_type)));
+                }
+            }
+        }
+        
+        let Some(type_parameters) = node.child_by_field_name("type_parameters") else {
+            return;
+        };
+        
+        let mut parameters: Vec<serde_json::Map<String, Value>> = Vec::new();
+        let mut cursor = type_parameters.walk();
+        for param in type_parameters.named_children(&mut cursor) {
+            let mut entry = serde_json::Map::new();
+            let (kind, name_node) = match param.kind() {
+                "lifetime" => ("lifetime", Some(param)),
+                "type_identifier" => ("type", Some(param)),
+                "const_parameter" => {
+                    if let Some(const_type) = param.child_by_field_name("type") {
+                        entry.insert("type".to_string(), Value::String(text(const_type)));
+                    }
+                    ("const", param.child_by_field_name("name"))
+                }
+                "constrained_type_parameter" => {
+                    let left = param.child_by_field_name("left");
+                    let kind = if left.is_some_and(|l| l.kind() == "lifetime") { "lifetime" } else { "type" };
+                    if let Some(bounds) = param.child_by_field_name("bounds") {
+                        entry.insert("bounds".to_string(), Value::Array(self.trait_bounds(source, bounds)));
+                    }
+                    (kind, left)
+                }
+                "optional_type_parameter" => {
+                    if let Some(default) = param.child_by_field_name("default_type") {
+                        entry.insert("default".to_string(), Value::String(text(default)));
+                    }
+                    let name = param.child_by_field_name("name");
+                    if let Some(bounds) = name.and_then(|n| n.child_by_field_name("bounds")) {
+                        entry.insert("bounds".to_string(), Value::Array(self.trait_bounds(source, bounds)));
+                    }
+                    ("type", name.map(|n| n.child_by_field_name("left").unwrap_or(n)))
+                }
+                _ => continue,
+            };
+            entry.insert("kind".to_string(), Value::String(kind.to_string()));
+            entry.insert("name".to_string(), Value::String(name_node.map(text).unwrap_or_default()));
+            parameters.push(entry);
+        }
+        
+        // `where T: Display` adds to the parameter's bounds; other predicates are kept as written
+        let mut where_predicates = Vec::new();
+        let mut cursor = node.walk();
+        let where_clause = node.named_children(&mut cursor).find(|c| c.kind() == "where_clause");
+        if let Some(where_clause) = where_clause {
+            let mut cursor = where_clause.walk();
+            for predicate in where_clause.named_children(&mut cursor) {
+                let (Some(left), Some(bounds)) = (predicate.child_by_field_name("left"), predicate.child_by_field_name("bounds")) else {
+                    continue;
+                };
+                let target = text(left);
+                let bounds = self.trait_bounds(source, bounds);
+                
+                let mut predicate_entry = serde_json::Map::new();
+                predicate_entry.insert("target".to_string(), Value::String(target.clone()));
+                predicate_entry.insert("bounds".to_string(), Value::Array(bounds.clone()));
+                where_predicates.push(Value::Object(predicate_entry));
+                
+                if let Some(param) = parameters.iter_mut().find(|p| p.get("name") == Some(&Value::String(target.clone()))) {
+                    let existing = param.entry("bounds".to_string()).or_insert_with(|| Value::Array(Vec::new()));
+                    if let Value::Array(existing) = existing {
+                        existing.extend(bounds);
+                    }
+                }
+            }
+        }
+        
+        let lifetimes: Vec<Value> = parameters.iter()
+            .filter(|p| p.get("kind") == Some(&Value::String("lifetime".to_string())))
+            .filter_map(|p| p.get("name").cloned())
+            .collect();
+        let bounded = parameters.iter().any(|p| p.contains_key("bounds"));
+        
+        metadata.semantic_tags.push("generic".to_string());
+        metadata.annotations.insert(
+            "generic_parameters".to_string(),
+            Value::Array(parameters.into_iter().map(Value::Object).collect()),
+        );
+        if !lifetimes.is_empty() {
+            metadata.annotations.insert("lifetimes".to_string(), Value::Array(lifetimes));
+        }
+        if !where_predicates.is_empty() {
+            metadata.annotations.insert("where_clause".to_string(), Value::Array(where_predicates));
+        }
+        let note = if bounded {
+            "Generic with trait bounds; targets without generics need one copy per instantiation or an interface type"
+        } else {
+            "Generic item; targets without generics need one copy per instantiation"
+        };
+        metadata.annotations.insert("translation_note".to_string(), Value::String(note.to_string()));
+    }
+    
+    fn trait_bounds(&self, source: &str, bounds: Node) -> Vec<Value> {
+        let mut cursor = bounds.walk();
+        let values = bounds.named_children(&mut cursor)
+            .filter_map(|bound| bound.utf8_text(source.as_bytes()).ok())
+            .map(|bound| Value::String(bound.to_string()))
+            .collect();
+        values
+    }
+    
+    /// Structure the arguments of well-known macros; note the rest as opaque
+    fn annotate_macro(&self, source: &str, node: Node, uir_node: &mut UIRNode) {
+        if node.kind() != "macro_invocation" {
+            return;
+        }
+        let name = uir_node.name.clone().unwrap_or_default();
+        let short_name = name.rsplit("::").next().unwrap_or(&name);
+        let body = node.named_children(&mut node.walk())
+            .find(|c| c.kind() == "token_tree")
+            .and_then(|t| t.utf8_text(source.as_bytes()).ok())
+            .unwrap_or("");
+        let inner = body.get(1..body.len().saturating_sub(1)).unwrap_or("");
+        let arguments = self.split_macro_arguments(inner, ',');
+        
+        let metadata = &mut uir_node.metadata;
+        metadata.semantic_tags.push("macro_invocation".to_string());
+        metadata.annotations.insert("macro".to_string(), Value::String(name.clone()));
+        
+        let to_values = |items: &[String]| Value::Array(items.iter().cloned().map(Value::String).collect());
+        let kind = match short_name {
+            "println" | "print" | "eprintln" | "eprint" | "format" | "panic" | "write" | "writeln" | "format_args" => {
+                // write!/writeln! take the destination first
+                let skip = usize::from(short_name.starts_with("write"));
+                if let Some(format_string) = arguments.get(skip) {
+                    metadata.annotations.insert("format_string".to_string(), Value::String(format_string.clone()));
+                }
+                metadata.annotations.insert("format_arguments".to_string(), to_values(arguments.get(skip + 1..).unwrap_or_default()));
+                "format"
+            }
+            "vec" => {
+                // vec![value; count] repeats a value; vec![a, b] lists elements
+                let repeat = self.split_macro_arguments(inner, ';');
+                if repeat.len() == 2 {
+                    metadata.annotations.insert("repeat_value".to_string(), Value::String(repeat[0].clone()));
+                    metadata.annotations.insert("repeat_count".to_string(), Value::String(repeat[1].clone()));
+                } else {
+                    metadata.annotations.insert("elements".to_string(), to_values(&arguments));
+                }
+                "collection"
+            }
+            "assert" | "assert_eq" | "assert_ne" | "debug_assert" | "debug_assert_eq" | "debug_assert_ne" => {
+                metadata.annotations.insert("assert_arguments".to_string(), to_values(&arguments));
+                "assertion"
+            }
+            "todo" | "unimplemented" | "unreachable" => "abort",
+            _ => {
+                metadata.annotations.insert(
+                    "translation_note".to_string(),
+                    Value::String(format!("Macro `{}!` has no direct equivalent; its expansion must be translated by hand", name)),
+                );
+                "custom"
+            }
+        };
+        metadata.annotations.insert("macro_kind".to_string(), Value::String(kind.to_string()));
+    }
+    
+    /// Split macro input at top-level separators, respecting nesting and strings
+    fn split_macro_arguments(&self, input: &str, separator: char) -> Vec<String> {
+        let mut arguments = Vec::new();
+        let mut current = String::new();
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        
+        for c in input.chars() {
+            if in_string {
+                current.push(c);
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match c {
+                '"' => in_string = true,
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ if c == separator && depth == 0 => {
+                    arguments.push(current.trim().to_string());
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(c);
+        }
+        if !current.trim().is_empty() {
+            arguments.push(current.trim().to_string());
+        }
+        arguments
+    }
+    
     fn extract_function_name(&self, source: &str, node: Node) -> Option<String> {
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
@@ -265,17 +729,13 @@ impl RustParser {
     }
 }
 
-extern "C" {
-    fn tree_sitter_rust() -> Language;
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     
     #[test]
     fn test_simple_rust_function() {
-        let mut parser = RustParser::new().unwrap();
+        let parser = RustParser::new().unwrap();
         let source = "fn add(a: i32, b: i32) -> i32 { a + b }";
         
         let result = parser.parse(source);
@@ -288,11 +748,11 @@ mod tests {
     
     #[test]
     fn test_rust_struct() {
-        let mut parser = RustParser::new().unwrap();
+        let parser = RustParser::new().unwrap();
         let source = r#"
 struct Point {
-    x: f64,
-    y: f64,
+    pub x: f64,
+    y: Vec<f64>,
 }
 
 impl Point {
@@ -301,22 +761,173 @@ impl Point {
     }
 }
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
+        
+        let uir = result.unwrap();
+        let fields: Vec<_> = uir.children[0].children.iter()
+            .filter_map(|c| match &c.node_type {
+                NodeType::Field { field_type, .. } => Some((c.name.as_deref(), field_type.as_deref(), c.visibility)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fields, vec![
+            (Some("x"), Some("f64"), Some(Visibility::Public)),
+            (Some("y"), Some("Vec<f64>"), None),
+        ]);
     }
     
     #[test]
     fn test_rust_enum() {
-        let mut parser = RustParser::new().unwrap();
+        let parser = RustParser::new().unwrap();
         let source = r#"
 enum Result<T, E> {
     Ok(T),
     Err(E),
 }
+
+enum Message { Move { x: i32, y: i32 }, Quit = 3 }
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
+        
+        let uir = result.unwrap();
+        let variants = |node: &UIRNode| match &node.node_type {
+            NodeType::Enum { variants } => variants.clone(),
+            other => panic!("expected an enum, got {:?}", other),
+        };
+        let unnamed = |field_type: &str| VariantField { name: None, field_type: field_type.to_string() };
+        assert_eq!(variants(&uir.children[0]), vec![
+            EnumVariant { name: "Ok".to_string(), value: None, fields: vec![unnamed("T")] },
+            EnumVariant { name: "Err".to_string(), value: None, fields: vec![unnamed("E")] },
+        ]);
+        let named = |name: &str| VariantField { name: Some(name.to_string()), field_type: "i32".to_string() };
+        assert_eq!(variants(&uir.children[1]), vec![
+            EnumVariant { name: "Move".to_string(), value: None, fields: vec![named("x"), named("y")] },
+            EnumVariant { name: "Quit".to_string(), value: Some("3".to_string()), fields: Vec::new() },
+        ]);
+    }
+    
+    #[test]
+    fn test_rust_generics_and_macros() {
+        let parser = RustParser::new().unwrap();
+        let source = r#"
+fn longest<'a, T: Display + Clone, const N: usize>(items: &'a [T; N]) -> &'a T
+where
+    T: PartialOrd,
+{
+    let v = vec![0; N];
+    println!("{} items, first {:?}", N, items[0]);
+    my_macro!(x);
+    &items[0]
+}
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        fn find<'a>(node: &'a UIRNode, tag: &str) -> Vec<&'a UIRNode> {
+            let mut found: Vec<&UIRNode> = node.children.iter().flat_map(|c| find(c, tag)).collect();
+            if node.metadata.semantic_tags.iter().any(|t| t == tag) {
+                found.insert(0, node);
+            }
+            found
+        }
+        
+        let function = find(&uir, "generic")[0];
+        assert_eq!(function.name.as_deref(), Some("longest"));
+        assert_eq!(function.metadata.annotations["generic_parameters"], serde_json::json!([
+            {"kind": "lifetime", "name": "'a"},
+            {"kind": "type", "name": "T", "bounds": ["Display", "Clone", "PartialOrd"]},
+            {"kind": "const", "name": "N", "type": "usize"},
+        ]));
+        assert_eq!(function.metadata.annotations["lifetimes"], serde_json::json!(["'a"]));
+        
+        let macros = find(&uir, "macro_invocation");
+        assert_eq!(macros[0].metadata.annotations["macro_kind"], "collection");
+        assert_eq!(macros[0].metadata.annotations["repeat_count"], "N");
+        assert_eq!(macros[1].name.as_deref(), Some("println"));
+        assert_eq!(macros[1].metadata.annotations["format_string"], "\"{} items, first {:?}\"");
+        assert_eq!(macros[1].metadata.annotations["format_arguments"], serde_json::json!(["N", "items[0]"]));
+        assert_eq!(macros[2].metadata.annotations["macro_kind"], "custom");
+        assert!(macros[2].metadata.annotations.contains_key("translation_note"));
+    }
+    
+    #[test]
+    fn test_rust_async_and_await() {
+        let parser = RustParser::new().unwrap();
+        let source = "async fn load(url: &str) -> String { let task = async move { 1 }; fetch(url).await }";
+        
+        let uir = parser.parse(source).unwrap();
+        let function = &uir.children[0];
+        assert_eq!(function.name.as_deref(), Some("load"));
+        assert!(function.is_async);
+        
+        fn find<'a>(node: &'a UIRNode, predicate: &dyn Fn(&UIRNode) -> bool) -> Option<&'a UIRNode> {
+            if predicate(node) {
+                return Some(node);
+            }
+            node.children.iter().find_map(|child| find(child, predicate))
+        }
+        let block = find(function, &|n| n.metadata.semantic_tags[0] == "async_block").unwrap();
+        assert!(block.is_async);
+        let await_node = find(function, &|n| n.node_type == NodeType::Expression(ExpressionType::Await)).unwrap();
+        assert_eq!(await_node.children.len(), 1);
+        assert_eq!(await_node.children[0].node_type, NodeType::Expression(ExpressionType::FunctionCall));
+    }
+    
+    #[test]
+    fn test_rust_attributes() {
+        let parser = RustParser::new().unwrap();
+        let source = "#[derive(Debug, Clone)]\n/// A point\n#[serde(rename = \"pt\")]\npub struct Point { #[serde(default)] x: f64 }\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let point = uir.children.iter().find(|c| c.name.as_deref() == Some("Point")).unwrap();
+        // The doc comment between the attributes does not end the list
+        assert_eq!(point.attributes, vec![
+            Attribute { name: "derive".to_string(), arguments: vec!["Debug".to_string(), "Clone".to_string()] },
+            Attribute { name: "serde".to_string(), arguments: vec!["rename = \"pt\"".to_string()] },
+        ]);
+        
+        fn find<'a>(node: &'a UIRNode, kind: &str) -> Option<&'a UIRNode> {
+            if node.metadata.semantic_tags.iter().any(|t| t == kind) {
+                return Some(node);
+            }
+            node.children.iter().find_map(|child| find(child, kind))
+        }
+        let field = find(point, "field_declaration").unwrap();
+        assert_eq!(field.attributes, vec![Attribute::parse("serde(default)")]);
+    }
+    
+    #[test]
+    fn test_rust_parameter_ownership() {
+        let parser = RustParser::new().unwrap();
+        let source = "struct Buf { data: Vec<u8>, len: usize, raw: *const u8 }
+impl Buf {
+    fn fill(&mut self, bytes: &[u8], out: &mut String, count: u32, name: String) {}
+}
+";
+
+        fn collect(node: &UIRNode, found: &mut Vec<(String, Option<Ownership>)>) {
+            if matches!(node.metadata.semantic_tags[0].as_str(), "self_parameter" | "parameter" | "field_declaration") {
+                let name = node.name.clone().unwrap_or_else(|| "self".to_string());
+                found.push((name, node.ownership));
+            }
+            node.children.iter().for_each(|c| collect(c, found));
+        }
+        let uir = parser.parse(source).unwrap();
+        let mut found = Vec::new();
+        collect(&uir, &mut found);
+        let expected = [
+            ("data", Ownership::Owned),
+            ("len", Ownership::ByValue),
+            ("raw", Ownership::Pointer { mutable: false }),
+            ("self", Ownership::Reference { mutable: true }),
+            ("bytes", Ownership::Reference { mutable: false }),
+            ("out", Ownership::Reference { mutable: true }),
+            ("count", Ownership::ByValue),
+            ("name", Ownership::Owned),
+        ];
+        assert_eq!(found, expected.map(|(name, ownership)| (name.to_string(), Some(ownership))));
     }
 }
@@ -1,9 +1,70 @@
-use coalesce_core::{UIRNode, NodeType, Metadata, SourceLocation, Language as CoalesceLanguage, 
-                   ExpressionType, StatementType, Result, CoalesceError, Parser as CoalesceParser};
+use coalesce_core::{UIRNode, NodeType, CaptureMode, Metadata, SourceLocation, Language as CoalesceLanguage, ControlFlowType, LoopType,
+                   ExpressionType, StatementType, LegacyPattern, Result, CoalesceError, Parser as CoalesceParser, Operator, LiteralValue,
+                   Visibility, Modifier};
 use serde_json::Value;
 use std::collections::HashMap;
 use regex::Regex;
+use crate::closures;
+use crate::operators::mark_grouped;
 
+/// Modifiers that may precede a declaration
+const MODIFIERS: &[&str] = &[
+    "public", "private", "protected", "friend", "shared", "static", "global", "overrides", "overridable",
+    "mustoverride", "notoverridable", "overloads", "shadows", "readonly", "writeonly", "partial", "mustinherit",
+    "notinheritable", "async", "iterator", "default", "widening", "narrowing", "withevents", "dim",
+];
+
+/// Legacy patterns that only occur in VB6/VBA sources
+const VB6_PATTERNS: &[&str] = &[
+    "module_option", "type_suffix", "implicit_variant", "global_variable", "redim", "set_assignment",
+    "on_error_goto", "on_error_goto_0", "on_error_resume_next", "form_event_handler", "gosub", "while_wend",
+    "declare_api", "call_statement", "let_assignment",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word,
+    Literal,
+    Operator,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+}
+
+impl Token {
+    fn is(&self, text: &str) -> bool {
+        self.kind != TokenKind::Literal && self.text.eq_ignore_ascii_case(text)
+    }
+}
+
+/// A logical line: continuations joined, comments stripped, `:` separators split
+#[derive(Debug, Clone)]
+struct Statement {
+    text: String,
+    line: u32,
+}
+
+impl Statement {
+    /// Lowercased words, used to match block keywords
+    fn words(&self) -> Vec<String> {
+        self.text.split_whitespace().map(|w| w.to_ascii_lowercase()).collect()
+    }
+    
+    fn starts_with(&self, keywords: &str) -> bool {
+        let words = self.words();
+        let expected: Vec<&str> = keywords.split_whitespace().collect();
+        words.len() >= expected.len() && words.iter().zip(&expected).all(|(w, e)| w == e)
+    }
+}
+
+/// Visual Basic parser covering VB.NET and VB6/VBA.
+///
+/// Source is split into logical statements which are then matched into
+/// blocks (`Sub`/`End Sub`, `If`/`End If`, `For`/`Next`, ...), so procedures
+/// nest inside classes and namespaces and carry their bodies as children.
 pub struct VisualBasicParser {
 }
 
@@ -12,350 +73,1943 @@ impl CoalesceParser for VisualBasicParser {
         CoalesceLanguage::VisualBasic
     }
     
-    fn parse(&self, source: &str) -> Result<UIRNode> {
-        self.parse_vb_source(source)
+    fn parse(&self, source: &str) -> Result<UIRNode> {
+        let mut uir = self.parse_vb_source(source)?;
+        uir.make_ids_unique();
+        uir.debug_validate();
+        Ok(uir)
+    }
+}
+
+impl VisualBasicParser {
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+    
+    fn parse_vb_source(&self, source: &str) -> Result<UIRNode> {
+        let mut root = UIRNode {
+            id: "vb_program".to_string(),
+            node_type: NodeType::Module,
+            name: Some("vb_program".to_string()),
+            children: Vec::new(),
+            metadata: Metadata {
+                source_language: CoalesceLanguage::VisualBasic,
+                semantic_tags: vec!["source_file".to_string()],
+                complexity_score: None,
+                dependencies: Vec::new(),
+                annotations: HashMap::new(),
+                legacy_patterns: Vec::new(),
+            },
+            source_location: Some(SourceLocation {
+                file: String::new(),
+                start_line: 1,
+                end_line: source.lines().count() as u32,
+                start_column: 0,
+                end_column: source.len() as u32,
+            }),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        };
+        
+        let statements = self.split_statements(source);
+        self.check_parentheses(source, &statements)?;
+        let attribute_regex = Regex::new(r#"(?i)^Attribute\s+VB_Name\s*=\s*"([^"]+)""#).unwrap();
+        let mut vb6 = false;
+        let mut pos = 0;
+        while let Some(statement) = statements.get(pos) {
+            let words = statement.words();
+            match words[0].as_str() {
+                "option" => {
+                    vb6 |= self.parse_option(statement, &mut root);
+                    pos += 1;
+                }
+                "attribute" => {
+                    // Exported .bas/.cls/.frm files carry a module name attribute
+                    if let Some(caps) = attribute_regex.captures(&statement.text) {
+                        root.name = Some(caps[1].to_string());
+                        vb6 = true;
+                    }
+                    pos += 1;
+                }
+                "version" => {
+                    vb6 = true;
+                    pos += 1;
+                }
+                "begin" => {
+                    // Form designer blocks describe controls, not code
+                    if words.get(1).is_some_and(|w| w.starts_with("vb.")) {
+                        root.metadata.semantic_tags.push("form".to_string());
+                        vb6 = true;
+                    }
+                    self.skip_designer_block(&statements, &mut pos);
+                }
+                _ => self.parse_statement(&statements, &mut pos, &mut root.children),
+            }
+        }
+        
+        vb6 |= self.tag_event_handlers(&mut root);
+        vb6 |= self.uses_vb6_patterns(&root);
+        if vb6 {
+            root.metadata.annotations.insert("dialect".to_string(), Value::String("vb6".to_string()));
+        }
+        
+        let mut dependencies = Vec::new();
+        self.collect_dependencies(&root, &mut dependencies);
+        root.metadata.dependencies = dependencies;
+        closures::annotate_captures(&mut root);
+        
+        Ok(root)
+    }
+    
+    fn split_statements(&self, source: &str) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        let mut pending = String::new();
+        let mut pending_line = 0;
+        
+        for (index, raw) in source.lines().enumerate() {
+            let code = self.strip_comment(raw);
+            let trimmed = code.trim_end();
+            if pending.is_empty() {
+                pending_line = index as u32 + 1;
+            }
+            
+            // Line continuation: a trailing ` _`
+            if trimmed == "_" || trimmed.ends_with(" _") || trimmed.ends_with("\t_") {
+                pending.push_str(&trimmed[..trimmed.len() - 1]);
+                pending.push(' ');
+                continue;
+            }
+            pending.push_str(trimmed);
+            let logical = std::mem::take(&mut pending);
+            
+            for part in self.split_colons(logical.trim()) {
+                if !part.is_empty() {
+                    statements.push(Statement { text: part, line: pending_line });
+                }
+            }
+        }
+        
+        statements
+    }
+    
+    /// Reject a statement with a parenthesis left open, which the signature and call parsing
+    /// cannot find the end of
+    fn check_parentheses(&self, source: &str, statements: &[Statement]) -> Result<()> {
+        for statement in statements {
+            let mut open = Vec::new();
+            let mut in_string = false;
+            for (i, c) in statement.text.char_indices() {
+                match c {
+                    '"' => in_string = !in_string,
+                    '(' if !in_string => open.push(i),
+                    ')' if !in_string => {
+                        open.pop();
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(&at) = open.first() {
+                let indent = source.lines().nth(statement.line as usize - 1)
+                    .and_then(|line| line.find(statement.text.as_str()))
+                    .unwrap_or_default();
+                return Err(CoalesceError::ParseError {
+                    message: "`(` is never closed".to_string(),
+                    line: statement.line,
+                    column: (indent + at) as u32,
+                });
+            }
+        }
+        Ok(())
+    }
+    
+    fn strip_comment(&self, line: &str) -> String {
+        let trimmed = line.trim_start();
+        if trimmed.get(..3).is_some_and(|keyword| keyword.eq_ignore_ascii_case("rem"))
+            && trimmed[3..].chars().next().is_none_or(char::is_whitespace) {
+            return String::new();
+        }
+        
+        let mut in_string = false;
+        for (i, c) in line.char_indices() {
+            match c {
+                '"' => in_string = !in_string,
+                '\'' if !in_string => return line[..i].to_string(),
+                _ => {}
+            }
+        }
+        line.to_string()
+    }
+    
+    /// Split `a = 1: b = 2` into statements, keeping labels and single-line Ifs intact
+    fn split_colons(&self, text: &str) -> Vec<String> {
+        let label_regex = Regex::new(r"^(\w+):(?:\s|$)").unwrap();
+        if let Some(caps) = label_regex.captures(text) {
+            if !caps[1].eq_ignore_ascii_case("case") && !caps[1].eq_ignore_ascii_case("else") {
+                let label = caps.get(0).unwrap().as_str().trim().to_string();
+                let mut parts = vec![label];
+                parts.extend(self.split_colons(text[caps.get(0).unwrap().end()..].trim()));
+                return parts;
+            }
+        }
+        if self.is_single_line_if(text) {
+            return vec![text.to_string()];
+        }
+        
+        let mut parts = Vec::new();
+        let mut current = String::new();
+        let mut in_string = false;
+        let chars: Vec<char> = text.chars().collect();
+        for (i, c) in chars.iter().enumerate() {
+            match c {
+                '"' => in_string = !in_string,
+                ':' if !in_string && chars.get(i + 1) != Some(&'=') => {
+                    parts.push(current.trim().to_string());
+                    current.clear();
+                    // Whatever follows may itself be a single-line If
+                    let rest: String = chars[i + 1..].iter().collect();
+                    if self.is_single_line_if(rest.trim()) {
+                        parts.push(rest.trim().to_string());
+                        return parts;
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(*c);
+        }
+        parts.push(current.trim().to_string());
+        parts
+    }
+    
+    fn is_single_line_if(&self, text: &str) -> bool {
+        let lower = text.to_ascii_lowercase();
+        if !lower.starts_with("if ") {
+            return false;
+        }
+        match self.find_word(text, "Then") {
+            Some(then) => !text[then + 4..].trim().is_empty(),
+            None => false,
+        }
+    }
+    
+    fn skip_designer_block(&self, statements: &[Statement], pos: &mut usize) {
+        let mut depth = 0;
+        while let Some(statement) = statements.get(*pos) {
+            let words = statement.words();
+            *pos += 1;
+            match words[0].as_str() {
+                "begin" | "beginproperty" => depth += 1,
+                "end" | "endproperty" if words.len() == 1 || words[0] == "endproperty" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    /// Statements up to (not including) one starting with any of `terminators`
+    fn parse_block(&self, statements: &[Statement], pos: &mut usize, terminators: &[&str]) -> Vec<UIRNode> {
+        let mut nodes = Vec::new();
+        while let Some(statement) = statements.get(*pos) {
+            if terminators.iter().any(|t| statement.starts_with(t)) {
+                break;
+            }
+            self.parse_statement(statements, pos, &mut nodes);
+        }
+        nodes
+    }
+    
+    /// Consume the closing statement of a block if it is present
+    fn expect_end(&self, statements: &[Statement], pos: &mut usize, terminator: &str) {
+        if statements.get(*pos).is_some_and(|s| s.starts_with(terminator)) {
+            *pos += 1;
+        }
+    }
+    
+    /// Split leading modifiers (`Public Shared`, `Private WithEvents`, ...) from a statement
+    fn split_modifiers<'a>(&self, text: &'a str) -> (Vec<String>, &'a str) {
+        let mut modifiers = Vec::new();
+        let mut rest = text.trim_start();
+        while let Some(word) = rest.split_whitespace().next() {
+            if !MODIFIERS.contains(&word.to_ascii_lowercase().as_str()) {
+                break;
+            }
+            modifiers.push(word.to_string());
+            rest = rest[word.len()..].trim_start();
+        }
+        (modifiers, rest)
+    }
+    
+    fn parse_statement(&self, statements: &[Statement], pos: &mut usize, nodes: &mut Vec<UIRNode>) {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        let (modifiers, rest) = self.split_modifiers(&statement.text);
+        let keyword = rest.split_whitespace().next().unwrap_or_default().to_ascii_lowercase();
+        let has = |modifier: &str| modifiers.iter().any(|m| m.eq_ignore_ascii_case(modifier));
+        
+        let mut node = match keyword.as_str() {
+            "namespace" => {
+                *pos += 1;
+                let mut namespace = self.create_node(NodeType::Module, Some(self.word_at(rest, 1)), line, "namespace");
+                namespace.children = self.parse_block(statements, pos, &["end namespace"]);
+                self.expect_end(statements, pos, "end namespace");
+                namespace
+            }
+            "module" => {
+                *pos += 1;
+                let mut module = self.create_node(NodeType::Module, Some(self.word_at(rest, 1)), line, "module");
+                module.children = self.parse_block(statements, pos, &["end module"]);
+                self.expect_end(statements, pos, "end module");
+                module
+            }
+            "class" | "structure" | "interface" => self.parse_class(statements, pos, &keyword, rest),
+            "enum" => self.parse_enum(statements, pos, rest),
+            "sub" | "function" | "operator" => self.parse_procedure(statements, pos, rest, !has("MustOverride")),
+            "property" => self.parse_property(statements, pos, rest, !has("MustOverride")),
+            "declare" => {
+                *pos += 1;
+                self.parse_declare(rest, line)
+            }
+            "delegate" | "event" => {
+                *pos += 1;
+                let (node_type, tag) = if keyword == "event" { (NodeType::Variable, "event") } else { (NodeType::Function, "delegate") };
+                let signature = rest[keyword.len()..].trim();
+                let signature = signature.strip_prefix("Sub ").or_else(|| signature.strip_prefix("Function ")).unwrap_or(signature);
+                let name = signature.split(|c: char| c == '(' || c.is_whitespace()).next().unwrap_or_default().to_string();
+                let mut node = self.create_node(node_type, Some(name), line, tag);
+                node.children = self.parameters_of(signature, line);
+                node
+            }
+            "imports" => {
+                *pos += 1;
+                // `Imports System.IO` or `Imports IO = System.IO`
+                let target = rest[keyword.len()..].trim();
+                let (alias, name) = match target.split_once('=') {
+                    Some((alias, name)) => (Some(alias.trim().to_string()), name.trim().to_string()),
+                    None => (None, target.to_string()),
+                };
+                let import = NodeType::Import { path: name.clone(), alias, symbols: Vec::new() };
+                let mut import = self.create_node(import, Some(name.clone()), line, "import");
+                import.metadata.dependencies.push(name);
+                import
+            }
+            "inherits" | "implements" => {
+                *pos += 1;
+                let mut node = self.create_node(NodeType::Statement(StatementType::Expression), Some(keyword.clone()), line, &keyword);
+                node.metadata.dependencies = self.split_top_level(rest[keyword.len()..].trim(), ',');
+                node
+            }
+            "const" => {
+                *pos += 1;
+                for declarator in self.split_top_level(rest[keyword.len()..].trim(), ',') {
+                    nodes.extend(self.parse_variable(&declarator, "Const", line, &statement.text));
+                }
+                return;
+            }
+            "dim" | "redim" | "static" => {
+                *pos += 1;
+                let mut list = rest[keyword.len()..].trim();
+                let mut keyword = rest.split_whitespace().next().unwrap_or_default().to_string();
+                if list.to_ascii_lowercase().starts_with("preserve ") {
+                    keyword = format!("{} Preserve", keyword);
+                    list = list[8..].trim();
+                }
+                for declarator in self.split_top_level(list, ',') {
+                    nodes.extend(self.parse_variable(&declarator, &keyword, line, &statement.text));
+                }
+                return;
+            }
+            _ if !modifiers.is_empty() => {
+                // Field declarations: `Private x As Integer`, `Global conn As Connection`
+                *pos += 1;
+                for declarator in self.split_top_level(rest, ',') {
+                    if let Some(mut field) = self.parse_variable(&declarator, &modifiers[0], line, &statement.text) {
+                        self.apply_modifiers(&mut field, &modifiers);
+                        nodes.push(field);
+                    }
+                }
+                return;
+            }
+            "if" => self.parse_if(statements, pos),
+            "select" => self.parse_select(statements, pos),
+            "for" => self.parse_for(statements, pos),
+            "do" => self.parse_do(statements, pos),
+            "while" => self.parse_while(statements, pos),
+            "try" => self.parse_try(statements, pos),
+            "with" | "using" | "synclock" => {
+                *pos += 1;
+                let subject = rest[keyword.len()..].trim();
+                let tag = match keyword.as_str() {
+                    "with" => "with_block",
+                    "using" => "using",
+                    _ => "synclock",
+                };
+                let mut block = self.create_node(NodeType::Statement(StatementType::Expression), Some(keyword.clone()), line, tag);
+                block.metadata.annotations.insert("subject".to_string(), Value::String(subject.to_string()));
+                if keyword == "using" {
+                    block.children.extend(self.parse_variable(subject, "Using", line, &statement.text));
+                } else {
+                    block.children.push(self.parse_expression(subject, line));
+                }
+                let end = format!("end {}", keyword);
+                block.children.extend(self.parse_block(statements, pos, &[&end]));
+                self.expect_end(statements, pos, &end);
+                block
+            }
+            "end" if rest.split_whitespace().count() > 1 => {
+                // A closing statement with no matching opener
+                *pos += 1;
+                return;
+            }
+            _ => {
+                *pos += 1;
+                self.parse_simple_statement(&statement.text, line)
+            }
+        };
+        
+        self.apply_modifiers(&mut node, &modifiers);
+        nodes.push(node);
+    }
+    
+    /// Record the access level and modifiers of a declaration; keywords without a structured
+    /// equivalent (`Overloads`, `WithEvents`, ...) become semantic tags
+    fn apply_modifiers(&self, node: &mut UIRNode, modifiers: &[String]) {
+        node.visibility = Visibility::from_keywords(modifiers.iter().map(String::as_str), &CoalesceLanguage::VisualBasic);
+        for modifier in modifiers {
+            let lower = modifier.to_ascii_lowercase();
+            if let Some(modifier) = Modifier::from_keyword(&lower, &CoalesceLanguage::VisualBasic) {
+                if !node.modifiers.contains(&modifier) {
+                    node.modifiers.push(modifier);
+                }
+            } else if Visibility::from_keyword(&lower, &CoalesceLanguage::VisualBasic).is_none() && lower != "dim" {
+                node.is_async |= lower == "async";
+                node.metadata.semantic_tags.push(lower);
+            }
+        }
+    }
+    
+    fn parse_class(&self, statements: &[Statement], pos: &mut usize, keyword: &str, header: &str) -> UIRNode {
+        let line = statements[*pos].line;
+        *pos += 1;
+        
+        let name = self.word_at(header, 1);
+        let (name, type_parameters) = match name.split_once("(Of") {
+            Some((name, params)) => (name.to_string(), Some(params.trim_end_matches(')').trim().to_string())),
+            None => (name.split('(').next().unwrap_or_default().to_string(), None),
+        };
+        let node_type = if keyword == "interface" { NodeType::Interface } else { NodeType::Class };
+        let mut class = self.create_node(node_type, Some(name), line, keyword);
+        if let Some(type_parameters) = type_parameters {
+            class.metadata.annotations.insert("type_parameters".to_string(), Value::String(type_parameters));
+        }
+        
+        let end = format!("end {}", keyword);
+        while let Some(statement) = statements.get(*pos) {
+            if statement.starts_with(&end) {
+                *pos += 1;
+                break;
+            }
+            let (_, rest) = self.split_modifiers(&statement.text);
+            let member = rest.split_whitespace().next().unwrap_or_default().to_ascii_lowercase();
+            match member.as_str() {
+                "inherits" | "implements" => {
+                    let types = self.split_top_level(rest[member.len()..].trim(), ',');
+                    class.metadata.dependencies.extend(types.iter().cloned());
+                    let key = if member == "inherits" { "inherits" } else { "implements" };
+                    class.metadata.annotations.insert(key.to_string(), Value::Array(types.into_iter().map(Value::String).collect()));
+                    *pos += 1;
+                }
+                // Interface members are signatures without bodies
+                "sub" | "function" if keyword == "interface" => {
+                    class.children.push(self.parse_procedure(statements, pos, rest, false));
+                }
+                "property" if keyword == "interface" => {
+                    class.children.push(self.parse_property(statements, pos, rest, false));
+                }
+                _ => self.parse_statement(statements, pos, &mut class.children),
+            }
+        }
+        class
+    }
+    
+    fn parse_enum(&self, statements: &[Statement], pos: &mut usize, header: &str) -> UIRNode {
+        let line = statements[*pos].line;
+        *pos += 1;
+        let mut enumeration = self.create_node(NodeType::Class, Some(self.word_at(header, 1)), line, "enum");
+        if let Some(underlying) = self.find_word(header, "As") {
+            enumeration.metadata.annotations.insert("underlying_type".to_string(), Value::String(header[underlying + 2..].trim().to_string()));
+        }
+        
+        while let Some(statement) = statements.get(*pos) {
+            *pos += 1;
+            if statement.starts_with("end enum") {
+                break;
+            }
+            let (name, value) = match statement.text.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim())),
+                None => (statement.text.trim(), None),
+            };
+            let mut member = self.create_node(NodeType::Constant, Some(name.to_string()), statement.line, "enum_member");
+            if let Some(value) = value {
+                member.children.push(self.parse_expression(value, statement.line));
+            }
+            enumeration.children.push(member);
+        }
+        enumeration
+    }
+    
+    /// `Sub Name(params) [Handles ...]` / `Function Name(params) As T` and their bodies
+    fn parse_procedure(&self, statements: &[Statement], pos: &mut usize, header: &str, has_body: bool) -> UIRNode {
+        let line = statements[*pos].line;
+        *pos += 1;
+        
+        let keyword = header.split_whitespace().next().unwrap_or_default().to_ascii_lowercase();
+        let signature = header[keyword.len()..].trim();
+        let name = signature.split(|c: char| c == '(' || c.is_whitespace()).next().unwrap_or_default().to_string();
+        let tag = match keyword.as_str() {
+            "sub" if name.eq_ignore_ascii_case("New") => "constructor",
+            "sub" => "sub",
+            "operator" => "operator",
+            _ => "function",
+        };
+        
+        let mut procedure = self.create_node(NodeType::Function, Some(name), line, tag);
+        if tag == "constructor" {
+            procedure.metadata.semantic_tags.push("sub".to_string());
+        }
+        procedure.children = self.parameters_of(signature, line);
+        let tail = self.after_parameters(signature);
+        self.annotate_signature(&mut procedure, tail);
+        
+        if has_body {
+            let end = format!("end {}", keyword);
+            procedure.children.extend(self.parse_block(statements, pos, &[&end]));
+            self.expect_end(statements, pos, &end);
+        } else {
+            procedure.metadata.semantic_tags.push("abstract".to_string());
+        }
+        procedure
+    }
+    
+    /// Return type, `Handles` and `Implements` clauses after the parameter list
+    fn annotate_signature(&self, node: &mut UIRNode, tail: &str) {
+        let handles = self.find_word(tail, "Handles");
+        let implements = self.find_word(tail, "Implements");
+        if let Some(return_type) = self.signature_type(tail) {
+            node.metadata.annotations.insert("return_type".to_string(), Value::String(return_type.to_string()));
+        }
+        if let Some(handles) = handles {
+            let end = implements.filter(|i| *i > handles).unwrap_or(tail.len());
+            let events = self.split_top_level(tail[handles + 7..end].trim(), ',');
+            if let Some((control, event)) = events.first().and_then(|e| e.rsplit_once('.')) {
+                node.metadata.annotations.insert("control".to_string(), Value::String(control.to_string()));
+                node.metadata.annotations.insert("event".to_string(), Value::String(event.to_string()));
+            }
+            node.metadata.semantic_tags.push("event_handler".to_string());
+            node.metadata.annotations.insert("handles".to_string(), Value::Array(events.into_iter().map(Value::String).collect()));
+        }
+        if let Some(implements) = implements {
+            let end = handles.filter(|h| *h > implements).unwrap_or(tail.len());
+            node.metadata.annotations.insert("implements".to_string(), Value::String(tail[implements + 10..end].trim().to_string()));
+        }
+    }
+    
+    /// The `As` type after a signature's parameters, before any `Handles` or `Implements`
+    fn signature_type<'a>(&self, tail: &'a str) -> Option<&'a str> {
+        let end_of_type = [self.find_word(tail, "Handles"), self.find_word(tail, "Implements")].into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(tail.len());
+        let as_index = self.find_word(&tail[..end_of_type], "As")?;
+        Some(tail[as_index + 2..end_of_type].trim())
+    }
+    
+    /// A `Property` with its `Get` and `Set` bodies as children tagged `getter` and `setter`;
+    /// VB6 writes each accessor as its own `Property Get`, `Let` or `Set` block
+    fn parse_property(&self, statements: &[Statement], pos: &mut usize, header: &str, has_body: bool) -> UIRNode {
+        let line = statements[*pos].line;
+        let signature = header["property".len()..].trim();
+        let (modifiers, _) = self.split_modifiers(&statements[*pos].text);
+        let has = |modifier: &str| modifiers.iter().any(|m| m.eq_ignore_ascii_case(modifier));
+        let property_type = |tail: &str| self.signature_type(tail).map(|t| t.trim_start_matches("New ").to_string());
+        
+        // VB6: Property Get/Let/Set Name(...) ... End Property
+        let accessor = signature.split_whitespace().next().unwrap_or_default().to_ascii_lowercase();
+        if has_body && matches!(accessor.as_str(), "get" | "let" | "set") && signature.split_whitespace().count() > 1 {
+            *pos += 1;
+            let signature = signature[accessor.len()..].trim();
+            let name = signature.split(|c: char| c == '(' || c.is_whitespace()).next().unwrap_or_default().to_string();
+            let getter = accessor == "get";
+            let tail = self.after_parameters(signature);
+            let node_type = NodeType::Property {
+                property_type: if getter { property_type(tail) } else { None },
+                default_value: None,
+                has_getter: getter,
+                has_setter: !getter,
+            };
+            let mut property = self.create_node(node_type, Some(name), line, "property");
+            let mut body = self.create_node(NodeType::Function, Some(accessor.clone()), line, &format!("property_{}", accessor));
+            body.metadata.semantic_tags.push(if getter { "getter" } else { "setter" }.to_string());
+            body.children = self.parameters_of(signature, line);
+            self.annotate_signature(&mut body, tail);
+            body.children.extend(self.parse_block(statements, pos, &["end property"]));
+            self.expect_end(statements, pos, "end property");
+            property.children.push(body);
+            return property;
+        }
+        
+        *pos += 1;
+        let name = signature.split(|c: char| c == '(' || c.is_whitespace()).next().unwrap_or_default().to_string();
+        let tail = self.after_parameters(signature);
+        let next_is_accessor = statements.get(*pos).is_some_and(|s| {
+            let (_, rest) = self.split_modifiers(&s.text);
+            let word = rest.split_whitespace().next().unwrap_or_default().to_ascii_lowercase();
+            word == "get" || word == "set" || word.starts_with("get(") || word.starts_with("set(")
+        });
+        
+        if !has_body || !next_is_accessor {
+            // Auto-implemented property: `Property Name As String = "x"`
+            let (declaration, initializer) = self.split_initializer(tail);
+            let node_type = NodeType::Property {
+                property_type: property_type(declaration),
+                default_value: initializer.map(str::to_string),
+                has_getter: !has("WriteOnly"),
+                has_setter: !has("ReadOnly"),
+            };
+            let mut property = self.create_node(node_type, Some(name), line, "property");
+            if let Some(initializer) = initializer {
+                property.children.push(self.parse_expression(initializer, line));
+            }
+            property.metadata.annotations.insert("original_text".to_string(), Value::String(statements[*pos - 1].text.clone()));
+            return property;
+        }
+        
+        let node_type = NodeType::Property {
+            property_type: property_type(tail),
+            default_value: None,
+            has_getter: !has("WriteOnly"),
+            has_setter: !has("ReadOnly"),
+        };
+        let mut property = self.create_node(node_type, Some(name), line, "property");
+        property.children = self.parameters_of(signature, line);
+        self.annotate_signature(&mut property, tail);
+        while let Some(statement) = statements.get(*pos) {
+            if statement.starts_with("end property") {
+                *pos += 1;
+                break;
+            }
+            let (_, rest) = self.split_modifiers(&statement.text);
+            let accessor = rest.split(|c: char| c == '(' || c.is_whitespace()).next().unwrap_or_default().to_ascii_lowercase();
+            if accessor != "get" && accessor != "set" {
+                self.parse_statement(statements, pos, &mut property.children);
+                continue;
+            }
+            *pos += 1;
+            let tag = if accessor == "get" { "getter" } else { "setter" };
+            let mut body = self.create_node(NodeType::Function, Some(accessor.clone()), statement.line, tag);
+            body.children = self.parameters_of(rest, statement.line);
+            let end = format!("end {}", accessor);
+            body.children.extend(self.parse_block(statements, pos, &[&end, "end property"]));
+            self.expect_end(statements, pos, &end);
+            property.children.push(body);
+        }
+        property
+    }
+    
+    /// `Declare Function Name Lib "dll" [Alias "x"] (params) As T`
+    fn parse_declare(&self, header: &str, line: u32) -> UIRNode {
+        let declare_regex = Regex::new(r#"(?i)^Declare\s+(?:PtrSafe\s+)?(?:Ansi\s+|Unicode\s+|Auto\s+)?(Sub|Function)\s+(\w+)\s+Lib\s+"([^"]+)"(?:\s+Alias\s+"([^"]+)")?\s*(.*)$"#).unwrap();
+        let Some(caps) = declare_regex.captures(header) else {
+            return self.parse_simple_statement(header, line);
+        };
+        
+        let mut function = self.create_node(NodeType::Function, Some(caps[2].to_string()), line, "external");
+        function.metadata.annotations.insert("library".to_string(), Value::String(caps[3].to_string()));
+        if let Some(alias) = caps.get(4) {
+            function.metadata.annotations.insert("alias".to_string(), Value::String(alias.as_str().to_string()));
+        }
+        function.metadata.dependencies.push(caps[3].to_string());
+        let rest = caps.get(5).map_or("", |m| m.as_str());
+        function.children = self.parameters_of(rest, line);
+        self.annotate_signature(&mut function, self.after_parameters(rest));
+        function.metadata.legacy_patterns.push(self.legacy_pattern(
+            "declare_api",
+            header,
+            "Call the native library through the target platform's FFI",
+        ));
+        function
+    }
+    
+    /// Parameters from the first parenthesized list of a signature
+    fn parameters_of(&self, signature: &str, line: u32) -> Vec<UIRNode> {
+        let Some(open) = signature.find('(') else {
+            return Vec::new();
+        };
+        let Some(close) = self.matching_paren(signature, open) else {
+            return Vec::new();
+        };
+        let inner = &signature[open + 1..close];
+        // Generic procedures: Function Map(Of T)(items As T())
+        if inner.trim_start().to_ascii_lowercase().starts_with("of ") {
+            return self.parameters_of(&signature[close + 1..], line);
+        }
+        
+        self.split_top_level(inner, ',').into_iter()
+            .filter(|p| !p.is_empty())
+            .map(|param| self.parse_parameter(&param, line))
+            .collect()
+    }
+    
+    fn after_parameters<'a>(&self, signature: &'a str) -> &'a str {
+        let Some(open) = signature.find('(') else {
+            return signature.split_once(char::is_whitespace).map_or("", |(_, tail)| tail);
+        };
+        let Some(mut close) = self.matching_paren(signature, open) else {
+            return "";
+        };
+        // Skip the parameter list that follows an (Of T) clause
+        if signature[open + 1..].trim_start().to_ascii_lowercase().starts_with("of ") {
+            if let Some(next) = signature[close + 1..].find('(').map(|i| i + close + 1) {
+                if signature[close + 1..next].trim().is_empty() {
+                    let Some(after) = self.matching_paren(signature, next) else {
+                        return "";
+                    };
+                    close = after;
+                }
+            }
+        }
+        signature.get(close + 1..).unwrap_or("")
+    }
+    
+    fn parse_parameter(&self, param: &str, line: u32) -> UIRNode {
+        let mut rest = param.trim();
+        let mut tags = Vec::new();
+        loop {
+            let word = rest.split_whitespace().next().unwrap_or_default().to_ascii_lowercase();
+            match word.as_str() {
+                "byval" | "byref" | "optional" | "paramarray" => {
+                    if word != "byval" {
+                        tags.push(word.clone());
+                    }
+                    rest = rest[word.len()..].trim_start();
+                }
+                _ => break,
+            }
+        }
+        
+        let (declaration, default) = self.split_initializer(rest);
+        let name = declaration.split(|c: char| c == '(' || c.is_whitespace()).next().unwrap_or_default();
+        let name = name.trim_end_matches(['$', '%', '&', '!', '#', '@']).to_string();
+        let mut parameter = self.create_node(NodeType::Variable, Some(name), line, "parameter");
+        parameter.metadata.semantic_tags.extend(tags);
+        if let Some(as_index) = self.find_word(declaration, "As") {
+            parameter.metadata.annotations.insert("type".to_string(), Value::String(declaration[as_index + 2..].trim().to_string()));
+        }
+        if let Some(default) = default {
+            parameter.metadata.annotations.insert("default".to_string(), Value::String(default.to_string()));
+        }
+        parameter
+    }
+    
+    /// Variable declarators, with VB.NET initializers (`Dim x As Integer = 5`)
+    fn parse_variable(&self, declarator: &str, keyword: &str, line: u32, original: &str) -> Option<UIRNode> {
+        let (declaration, initializer) = self.split_initializer(declarator);
+        // `As New T(args)` constructs in place; the arguments don't change the type
+        let declaration = match declaration.find('(') {
+            Some(open) if self.find_word(&declaration[..open], "New").is_some() => &declaration[..open],
+            _ => declaration,
+        };
+        
+        let mut var = self.parse_declarator(declaration, keyword, line as usize, original)?;
+        if let Some(initializer) = initializer {
+            var.metadata.legacy_patterns.retain(|p| p.pattern_type != "implicit_variant");
+            if var.metadata.annotations.get("type").is_some_and(|t| t == "Variant") {
+                var.metadata.annotations.remove("type");
+            }
+            var.children.push(self.parse_expression(initializer, line));
+        }
+        if keyword.eq_ignore_ascii_case("Const") {
+            var.node_type = NodeType::Constant;
+            var.metadata.semantic_tags = vec!["constant".to_string()];
+            var.metadata.legacy_patterns.clear();
+        }
+        if keyword.eq_ignore_ascii_case("Using") {
+            var.metadata.semantic_tags.push("disposable".to_string());
+            var.metadata.legacy_patterns.clear();
+        }
+        Some(var)
+    }
+    
+    fn split_declarators(&self, list: &str) -> Vec<String> {
+        let mut declarators = Vec::new();
+        let mut current = String::new();
+        let mut depth = 0;
+        let mut in_string = false;
+        
+        for c in list.chars() {
+            match c {
+                '"' => in_string = !in_string,
+                '(' | '{' if !in_string => depth += 1,
+                ')' | '}' if !in_string => depth -= 1,
+                ',' if depth == 0 && !in_string => {
+                    declarators.push(current.trim().to_string());
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(c);
+        }
+        if !current.trim().is_empty() {
+            declarators.push(current.trim().to_string());
+        }
+        
+        declarators
+    }
+    
+    fn split_top_level(&self, list: &str, separator: char) -> Vec<String> {
+        if separator == ',' {
+            return self.split_declarators(list);
+        }
+        list.split(separator).map(|s| s.trim().to_string()).collect()
+    }
+    
+    fn parse_declarator(&self, declarator: &str, keyword: &str, line_num: usize, original: &str) -> Option<UIRNode> {
+        let declarator_regex = Regex::new(r"(?i)^(\w+)([$%&!#@]?)\s*(?:\(([^)]*)\))?(?:\s+As\s+(New\s+)?([\w.]+(?:\(Of [^)]*\))?)(?:\s*\*\s*(\d+))?)?$").unwrap();
+        let caps = declarator_regex.captures(declarator.trim())?;
+        let name = caps[1].to_string();
+        
+        let mut annotations = HashMap::new();
+        annotations.insert("original_text".to_string(), Value::String(original.to_string()));
+        annotations.insert("declaration".to_string(), Value::String(keyword.to_string()));
+        
+        let mut semantic_tags = vec!["variable".to_string()];
+        let mut legacy_patterns = vec![self.legacy_pattern(
+            "dim_declaration",
+            original,
+            "Declare with the target language's typed variable syntax",
+        )];
+        
+        // Type-declaration suffixes: x$ is a String, n% an Integer, ...
+        let suffix_type = match caps.get(2).map_or("", |m| m.as_str()) {
+            "$" => Some("String"),
+            "%" => Some("Integer"),
+            "&" => Some("Long"),
+            "!" => Some("Single"),
+            "#" => Some("Double"),
+            "@" => Some("Currency"),
+            _ => None,
+        };
+        if let Some(suffix_type) = suffix_type {
+            legacy_patterns.push(self.legacy_pattern(
+                "type_suffix",
+                declarator,
+                "Replace the type-declaration character with an explicit type",
+            ));
+            annotations.insert("type".to_string(), Value::String(suffix_type.to_string()));
+        }
+        
+        match caps.get(5) {
+            Some(var_type) => {
+                annotations.insert("type".to_string(), Value::String(var_type.as_str().to_string()));
+            }
+            None if suffix_type.is_none() => {
+                annotations.insert("type".to_string(), Value::String("Variant".to_string()));
+                legacy_patterns.push(self.legacy_pattern(
+                    "implicit_variant",
+                    declarator,
+                    "Untyped declarations are Variant; infer or declare a concrete type",
+                ));
+            }
+            None => {}
+        }
+        if caps.get(4).is_some() {
+            annotations.insert("new_instance".to_string(), Value::Bool(true));
+        }
+        if let Some(length) = caps.get(6) {
+            annotations.insert("fixed_length".to_string(), Value::from(length.as_str().parse::<u64>().unwrap_or(0)));
+        }
+        if let Some(bounds) = caps.get(3) {
+            semantic_tags.push("array".to_string());
+            annotations.insert("array_bounds".to_string(), Value::String(bounds.as_str().trim().to_string()));
+        }
+        
+        match keyword.split_whitespace().next().unwrap_or_default().to_ascii_lowercase().as_str() {
+            "global" => legacy_patterns.push(self.legacy_pattern(
+                "global_variable",
+                original,
+                "Move shared state into a module or pass it explicitly",
+            )),
+            "static" => semantic_tags.push("static".to_string()),
+            "redim" => {
+                semantic_tags.push("redim".to_string());
+                legacy_patterns.push(self.legacy_pattern(
+                    "redim",
+                    original,
+                    "Use a growable collection instead of resizing arrays in place",
+                ));
+            }
+            _ => {}
+        }
+        
+        Some(UIRNode {
+            id: format!("var_{}_{}", name, line_num),
+            node_type: NodeType::Variable,
+            name: Some(name),
+            children: Vec::new(),
+            metadata: Metadata {
+                source_language: CoalesceLanguage::VisualBasic,
+                semantic_tags,
+                complexity_score: None,
+                dependencies: Vec::new(),
+                annotations,
+                legacy_patterns,
+            },
+            source_location: Some(SourceLocation {
+                file: String::new(),
+                start_line: line_num as u32,
+                end_line: line_num as u32,
+                start_column: 0,
+                end_column: original.len() as u32,
+            }),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        })
+    }
+    
+    fn parse_if(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        *pos += 1;
+        
+        // `If` or `ElseIf` header
+        let keyword_len = statement.text.split_whitespace().next().unwrap_or_default().len();
+        let then = self.find_word(&statement.text, "Then").unwrap_or(statement.text.len());
+        let condition = statement.text[keyword_len..then].trim();
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("if".to_string()), line, "if");
+        node.children.push(self.parse_expression(condition, line));
+        
+        let inline = statement.text.get(then + 4..).map(str::trim).unwrap_or_default();
+        if !inline.is_empty() {
+            // Single-line If: If x Then a = 1 Else b = 2
+            let (then_part, else_part) = match self.find_word(inline, "Else") {
+                Some(index) => (&inline[..index], Some(&inline[index + 4..])),
+                None => (inline, None),
+            };
+            node.children.push(self.inline_block("then", then_part, line));
+            if let Some(else_part) = else_part {
+                node.children.push(self.inline_block("else", else_part, line));
+            }
+            node.metadata.semantic_tags.push("single_line".to_string());
+            return node;
+        }
+        
+        let mut then_block = self.create_node(NodeType::Statement(StatementType::Expression), Some("then".to_string()), line, "block");
+        then_block.children = self.parse_block(statements, pos, &["elseif", "else", "end if"]);
+        node.children.push(then_block);
+        
+        match statements.get(*pos) {
+            // The ElseIf chain shares the outer End If
+            Some(next) if next.starts_with("elseif") => node.children.push(self.parse_if(statements, pos)),
+            Some(next) if next.starts_with("else") => {
+                *pos += 1;
+                let mut else_block = self.create_node(NodeType::Statement(StatementType::Expression), Some("else".to_string()), next.line, "block");
+                else_block.children = self.parse_block(statements, pos, &["end if"]);
+                node.children.push(else_block);
+                self.expect_end(statements, pos, "end if");
+            }
+            _ => self.expect_end(statements, pos, "end if"),
+        }
+        node
+    }
+    
+    fn inline_block(&self, name: &str, text: &str, line: u32) -> UIRNode {
+        let statements: Vec<Statement> = self.split_colons(text.trim()).into_iter()
+            .filter(|s| !s.is_empty())
+            .map(|text| Statement { text, line })
+            .collect();
+        let mut pos = 0;
+        let mut block = self.create_node(NodeType::Statement(StatementType::Expression), Some(name.to_string()), line, "block");
+        block.children = self.parse_block(&statements, &mut pos, &[]);
+        block
+    }
+    
+    fn parse_select(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        *pos += 1;
+        
+        let subject = statement.text.split_whitespace().skip(2).collect::<Vec<_>>().join(" ");
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Switch), Some("select_case".to_string()), line, "select_case");
+        node.children.push(self.parse_expression(&subject, line));
+        
+        while let Some(statement) = statements.get(*pos) {
+            if statement.starts_with("end select") {
+                *pos += 1;
+                break;
+            }
+            if !statement.starts_with("case") {
+                // Nothing but comments may precede the first Case
+                *pos += 1;
+                continue;
+            }
+            *pos += 1;
+            let values = statement.text[4..].trim();
+            let mut case = if values.eq_ignore_ascii_case("else") {
+                self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("case_else".to_string()), statement.line, "default")
+            } else {
+                let mut case = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("case".to_string()), statement.line, "case");
+                case.metadata.annotations.insert("values".to_string(), Value::String(values.to_string()));
+                for value in self.split_top_level(values, ',') {
+                    case.children.push(self.parse_case_value(&value, statement.line));
+                }
+                case
+            };
+            case.children.extend(self.parse_block(statements, pos, &["case", "end select"]));
+            node.children.push(case);
+        }
+        node
+    }
+    
+    /// `Case 1 To 5`, `Case Is > 10` or a plain value
+    fn parse_case_value(&self, value: &str, line: u32) -> UIRNode {
+        if let Some(to) = self.find_word(value, "To") {
+            let mut range = self.create_node(NodeType::Expression(ExpressionType::Comparison), None, line, "range");
+            range.metadata.annotations.insert("operator".to_string(), Value::String("To".to_string()));
+            range.children = vec![self.parse_expression(&value[..to], line), self.parse_expression(&value[to + 2..], line)];
+            return range;
+        }
+        if value.to_ascii_lowercase().starts_with("is ") {
+            let comparison = value[3..].trim();
+            let operator_len = comparison.chars().take_while(|c| "<>=".contains(*c)).count();
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Comparison), None, line, "comparison");
+            node.operator = Operator::binary(&comparison[..operator_len], &CoalesceLanguage::VisualBasic);
+            node.metadata.annotations.insert("operator".to_string(), Value::String(comparison[..operator_len].to_string()));
+            node.children.push(self.parse_expression(&comparison[operator_len..], line));
+            return node;
+        }
+        self.parse_expression(value, line)
+    }
+    
+    fn parse_for(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        *pos += 1;
+        let header = statement.text[3..].trim();
+        
+        let mut node = if header.to_ascii_lowercase().starts_with("each ") {
+            let header = header[4..].trim();
+            let within = self.find_word(header, "In").unwrap_or(header.len());
+            let variable = self.declared_name(&header[..within]);
+            let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)), Some("for_each".to_string()), line, "for_each");
+            node.metadata.annotations.insert("variable".to_string(), Value::String(variable));
+            node.children.push(self.parse_expression(header.get(within + 2..).unwrap_or_default(), line));
+            node
+        } else {
+            let eq = header.find('=').unwrap_or(header.len());
+            let to = self.find_word(header, "To").unwrap_or(header.len());
+            let step = self.find_word(header, "Step");
+            let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::For)), Some("for".to_string()), line, "for");
+            node.metadata.annotations.insert("variable".to_string(), Value::String(self.declared_name(&header[..eq])));
+            node.children.push(self.parse_expression(header.get(eq + 1..to).unwrap_or_default(), line));
+            node.children.push(self.parse_expression(header.get(to + 2..step.unwrap_or(header.len())).unwrap_or_default(), line));
+            if let Some(step) = step {
+                node.metadata.annotations.insert("step".to_string(), Value::String(header[step + 4..].trim().to_string()));
+            }
+            node
+        };
+        
+        node.children.extend(self.parse_block(statements, pos, &["next"]));
+        self.expect_end(statements, pos, "next");
+        node
+    }
+    
+    /// Loop variable name from `i`, `i As Integer` or `item As String`
+    fn declared_name(&self, text: &str) -> String {
+        text.split_whitespace().next().unwrap_or_default().to_string()
+    }
+    
+    fn parse_do(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        *pos += 1;
+        
+        let pre_test = statement.text[2..].trim();
+        let body = self.parse_block(statements, pos, &["loop"]);
+        let post_test = statements.get(*pos).map(|s| s.text[4..].trim().to_string()).unwrap_or_default();
+        self.expect_end(statements, pos, "loop");
+        
+        let (loop_type, test, tag) = if !pre_test.is_empty() {
+            (LoopType::While, pre_test.to_string(), "do_loop")
+        } else if !post_test.is_empty() {
+            (LoopType::DoWhile, post_test, "do_loop")
+        } else {
+            (LoopType::While, String::new(), "infinite_loop")
+        };
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(loop_type)), Some("do".to_string()), line, tag);
+        if let Some((keyword, condition)) = test.split_once(char::is_whitespace) {
+            if keyword.eq_ignore_ascii_case("until") {
+                node.metadata.annotations.insert("until".to_string(), Value::Bool(true));
+            }
+            node.children.push(self.parse_expression(condition, line));
+        }
+        node.children.extend(body);
+        node
+    }
+    
+    fn parse_while(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let statement = &statements[*pos];
+        let line = statement.line;
+        *pos += 1;
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Loop(LoopType::While)), Some("while".to_string()), line, "while");
+        node.children.push(self.parse_expression(statement.text[5..].trim(), line));
+        node.children.extend(self.parse_block(statements, pos, &["wend", "end while"]));
+        if statements.get(*pos).is_some_and(|s| s.starts_with("wend")) {
+            node.metadata.legacy_patterns.push(self.legacy_pattern(
+                "while_wend",
+                &statement.text,
+                "While...Wend is superseded by Do While...Loop",
+            ));
+            *pos += 1;
+        } else {
+            self.expect_end(statements, pos, "end while");
+        }
+        node
+    }
+    
+    fn parse_try(&self, statements: &[Statement], pos: &mut usize) -> UIRNode {
+        let line = statements[*pos].line;
+        *pos += 1;
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Try), Some("try".to_string()), line, "try");
+        let mut try_block = self.create_node(NodeType::Statement(StatementType::Expression), Some("try_block".to_string()), line, "block");
+        try_block.children = self.parse_block(statements, pos, &["catch", "finally", "end try"]);
+        node.children.push(try_block);
+        
+        while let Some(statement) = statements.get(*pos) {
+            if statement.starts_with("end try") {
+                *pos += 1;
+                break;
+            }
+            *pos += 1;
+            let mut handler = if statement.starts_with("catch") {
+                let clause = statement.text[5..].trim();
+                let when = self.find_word(clause, "When");
+                let declaration = &clause[..when.unwrap_or(clause.len())];
+                let mut catch = self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("catch".to_string()), statement.line, "catch");
+                if !declaration.trim().is_empty() {
+                    catch.metadata.annotations.insert("variable".to_string(), Value::String(self.declared_name(declaration)));
+                }
+                if let Some(as_index) = self.find_word(declaration, "As") {
+                    catch.metadata.annotations.insert("exception_type".to_string(), Value::String(declaration[as_index + 2..].trim().to_string()));
+                }
+                if let Some(when) = when {
+                    catch.children.push(self.parse_expression(&clause[when + 4..], statement.line));
+                }
+                catch
+            } else {
+                self.create_node(NodeType::ControlFlow(ControlFlowType::Conditional), Some("finally".to_string()), statement.line, "finally")
+            };
+            handler.children.extend(self.parse_block(statements, pos, &["catch", "finally", "end try"]));
+            node.children.push(handler);
+        }
+        node
+    }
+    
+    fn parse_simple_statement(&self, text: &str, line: u32) -> UIRNode {
+        let words: Vec<String> = text.split_whitespace().map(|w| w.to_ascii_lowercase()).collect();
+        let first = words[0].as_str();
+        let rest = text[text.split_whitespace().next().unwrap_or_default().len()..].trim();
+        
+        let mut node = match first {
+            _ if text.ends_with(':') && !text.contains(' ') => {
+                self.create_node(NodeType::Statement(StatementType::Expression), Some(text.trim_end_matches(':').to_string()), line, "label")
+            }
+            "return" => {
+                let mut node = self.create_node(NodeType::Statement(StatementType::Return), None, line, "return");
+                if !rest.is_empty() {
+                    node.children.push(self.parse_expression(rest, line));
+                }
+                node
+            }
+            "exit" => {
+                let target = words.get(1).cloned().unwrap_or_default();
+                let mut node = match target.as_str() {
+                    "sub" | "function" | "property" => self.create_node(NodeType::Statement(StatementType::Return), None, line, "exit"),
+                    _ => self.create_node(NodeType::Statement(StatementType::Break), None, line, "exit"),
+                };
+                node.metadata.annotations.insert("target".to_string(), Value::String(target));
+                node
+            }
+            "continue" => {
+                let mut node = self.create_node(NodeType::Statement(StatementType::Continue), None, line, "continue");
+                node.metadata.annotations.insert("target".to_string(), Value::String(words.get(1).cloned().unwrap_or_default()));
+                node
+            }
+            "throw" => {
+                let mut node = self.create_node(NodeType::Statement(StatementType::Throw), None, line, "throw");
+                if !rest.is_empty() {
+                    node.children.push(self.parse_expression(rest, line));
+                }
+                node
+            }
+            "on" if words.get(1).is_some_and(|w| w == "error") => self.parse_error_handler(text, line),
+            "resume" => {
+                let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Goto), Some("resume".to_string()), line, "resume");
+                if !rest.is_empty() {
+                    node.metadata.annotations.insert("target".to_string(), Value::String(rest.to_string()));
+                }
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "resume",
+                    text,
+                    "Continue after the catch block instead of resuming into the failed statement",
+                ));
+                node
+            }
+            "goto" | "gosub" => {
+                let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Goto), Some(rest.to_string()), line, first);
+                node.metadata.annotations.insert("target".to_string(), Value::String(rest.to_string()));
+                let hint = if first == "gosub" {
+                    "Extract the labelled section into its own procedure and call it"
+                } else {
+                    "Restructure the jump into structured control flow"
+                };
+                node.metadata.legacy_patterns.push(self.legacy_pattern(first, text, hint));
+                node
+            }
+            "set" => self.parse_set_statement(text, line),
+            "let" => {
+                let mut node = self.parse_assignment(rest, line).unwrap_or_else(|| self.parse_expression(rest, line));
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "let_assignment",
+                    text,
+                    "Drop the optional Let keyword",
+                ));
+                node
+            }
+            "call" => {
+                let mut node = self.parse_call_statement(rest, line);
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "call_statement",
+                    text,
+                    "Call the procedure directly",
+                ));
+                node
+            }
+            "raiseevent" | "addhandler" | "removehandler" | "erase" => {
+                let mut node = self.parse_call_statement(rest, line);
+                if first != "raiseevent" {
+                    node.name = Some(text.split_whitespace().next().unwrap_or_default().to_string());
+                    node.children = self.split_top_level(rest, ',').iter().map(|a| self.parse_expression(a, line)).collect();
+                }
+                let tag = match first {
+                    "raiseevent" => "raise_event",
+                    "erase" => "erase",
+                    _ => "event_subscription",
+                };
+                node.metadata.semantic_tags.push(tag.to_string());
+                node
+            }
+            "end" | "stop" if words.len() == 1 => {
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(text.to_string()), line, "terminate");
+                node.metadata.legacy_patterns.push(self.legacy_pattern(
+                    "abrupt_termination",
+                    text,
+                    "Return from the entry point or raise an error instead of halting",
+                ));
+                node
+            }
+            _ => match self.parse_assignment(text, line) {
+                Some(assignment) => assignment,
+                None => self.parse_call_statement(text, line),
+            },
+        };
+        
+        node.metadata.annotations.entry("original_text".to_string()).or_insert_with(|| Value::String(text.to_string()));
+        node
+    }
+    
+    /// `target = value` or compound `target += value`
+    fn parse_assignment(&self, text: &str, line: u32) -> Option<UIRNode> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let lvalue = Regex::new(r"^\.?[\w\[\]]+[$%&!#@]?(?:\.\w+|\([^()]*\))*$").unwrap();
+        let mut depth = 0;
+        let mut in_string = false;
+        
+        for (index, &(offset, c)) in chars.iter().enumerate() {
+            match c {
+                '"' => in_string = !in_string,
+                '(' if !in_string => depth += 1,
+                ')' if !in_string => depth -= 1,
+                '=' if !in_string && depth == 0 => {
+                    let previous = index.checked_sub(1).map(|i| chars[i].1);
+                    if matches!(previous, Some('<' | '>' | ':')) {
+                        return None;
+                    }
+                    let (target_end, operator) = match previous {
+                        Some(op @ ('+' | '-' | '*' | '/' | '\\' | '&' | '^')) => (offset - 1, format!("{}=", op)),
+                        _ => (offset, "=".to_string()),
+                    };
+                    let target = text[..target_end].trim();
+                    let value = text[offset + 1..].trim();
+                    
+                    // Anything but an lvalue before `=` makes this a call or comparison
+                    if !lvalue.is_match(target) {
+                        return None;
+                    }
+                    
+                    let mut node = self.create_node(NodeType::Expression(ExpressionType::Assignment), Some(target.to_string()), line, "assignment");
+                    if operator != "=" {
+                        node.operator = Operator::binary(operator.trim_end_matches('='), &CoalesceLanguage::VisualBasic);
+                        node.metadata.annotations.insert("operator".to_string(), Value::String(operator));
+                    }
+                    node.children = vec![self.parse_expression(target, line), self.parse_expression(value, line)];
+                    return Some(node);
+                }
+                _ => {}
+            }
+        }
+        None
     }
-}
-
-impl VisualBasicParser {
-    pub fn new() -> Result<Self> {
-        Ok(Self {})
+    
+    /// `Foo(a, b)`, `obj.Method a, b` or a bare `DoWork`
+    fn parse_call_statement(&self, text: &str, line: u32) -> UIRNode {
+        let head_len = text.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(text.len());
+        let head = &text[..head_len];
+        let rest = text[head_len..].trim();
+        
+        let parenthesized = rest.starts_with('(') && self.matching_paren(rest, 0) == Some(rest.len() - 1);
+        if rest.is_empty() || parenthesized || text[head_len..].starts_with('(') {
+            let node = self.parse_expression(text, line);
+            if node.node_type != NodeType::Expression(ExpressionType::Variable) {
+                return node;
+            }
+            return self.create_node(NodeType::Expression(ExpressionType::FunctionCall), node.name, line, "call");
+        }
+        
+        // Arguments without parentheses: MsgBox "Done", vbInformation
+        let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), Some(head.to_string()), line, "call");
+        call.children = self.split_top_level(rest, ',').iter()
+            .filter(|a| !a.is_empty())
+            .map(|argument| self.parse_expression(argument, line))
+            .collect();
+        call
     }
     
-    fn parse_vb_source(&self, source: &str) -> Result<UIRNode> {
-        let mut root = UIRNode {
-            id: "vb_program".to_string(),
-            node_type: NodeType::Module,
-            name: Some("vb_program".to_string()),
-            children: Vec::new(),
-            metadata: Metadata {
-                source_language: CoalesceLanguage::VisualBasic,
-                semantic_tags: vec!["source_file".to_string()],
-                complexity_score: None,
-                dependencies: Vec::new(),
-                annotations: HashMap::new(),
-                legacy_patterns: Vec::new(),
-            },
-            source_location: Some(SourceLocation {
-                file: String::new(),
-                start_line: 1,
-                end_line: source.lines().count() as u32,
-                start_column: 0,
-                end_column: source.len() as u32,
-            }),
+    fn parse_set_statement(&self, text: &str, line: u32) -> UIRNode {
+        let rest = text[3..].trim();
+        let (target, value) = rest.split_once('=').map(|(t, v)| (t.trim(), v.trim())).unwrap_or((rest, ""));
+        
+        let mut semantic_tags = vec!["object_assignment".to_string()];
+        if value.eq_ignore_ascii_case("Nothing") {
+            semantic_tags.push("release_reference".to_string());
+        }
+        
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::Assignment), Some(target.to_string()), line, "object_assignment");
+        node.metadata.semantic_tags = semantic_tags;
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(text.to_string()));
+        node.metadata.annotations.insert("value".to_string(), Value::String(value.to_string()));
+        if value.to_ascii_lowercase().starts_with("new ") {
+            node.metadata.annotations.insert("new_instance".to_string(), Value::Bool(true));
+        }
+        node.metadata.legacy_patterns.push(self.legacy_pattern(
+            "set_assignment",
+            text,
+            "Object references assign with plain `=`; `Set x = Nothing` becomes dropping the reference",
+        ));
+        node.children = vec![self.parse_expression(target, line), self.parse_expression(value, line)];
+        node
+    }
+    
+    fn parse_error_handler(&self, text: &str, line: u32) -> UIRNode {
+        let error_regex = Regex::new(r"(?i)^On\s+Error\s+(GoTo\s+(-?\w+)|Resume\s+Next)").unwrap();
+        let Some(caps) = error_regex.captures(text) else {
+            return self.parse_call_statement(text, line);
         };
         
-        // Parse different VB constructs
-        self.parse_namespaces(source, &mut root)?;
-        self.parse_modules(source, &mut root)?;
-        self.parse_classes(source, &mut root)?;
-        self.parse_functions(source, &mut root)?;
-        self.parse_subs(source, &mut root)?;
-        self.parse_properties(source, &mut root)?;
+        let mut annotations = HashMap::new();
+        annotations.insert("original_text".to_string(), Value::String(text.to_string()));
         
-        Ok(root)
+        let (name, pattern_type, hint) = match caps.get(2).map(|l| l.as_str()) {
+            Some("0") | Some("-1") => (
+                "disable_error_handler".to_string(),
+                "on_error_goto_0",
+                "Close the enclosing try block here",
+            ),
+            Some(label) => {
+                annotations.insert("handler_label".to_string(), Value::String(label.to_string()));
+                (
+                    "on_error_goto".to_string(),
+                    "on_error_goto",
+                    "Wrap the procedure body in try/catch with the handler label's code in the catch block",
+                )
+            }
+            None => {
+                annotations.insert("resume_next".to_string(), Value::Bool(true));
+                (
+                    "on_error_resume_next".to_string(),
+                    "on_error_resume_next",
+                    "Errors are silently ignored; check each statement's failure explicitly",
+                )
+            }
+        };
+        
+        let mut node = self.create_node(NodeType::ControlFlow(ControlFlowType::Try), Some(name), line, "error_handler");
+        node.metadata.annotations = annotations;
+        node.metadata.legacy_patterns.push(LegacyPattern {
+            pattern_type: pattern_type.to_string(),
+            original_construct: text.to_string(),
+            modernization_hint: Some(hint.to_string()),
+            // Resume Next semantics can't be reproduced structurally
+            preserve_exactly: pattern_type == "on_error_resume_next",
+        });
+        node
     }
     
-    fn parse_namespaces(&self, source: &str, root: &mut UIRNode) -> Result<()> {
-        let namespace_regex = Regex::new(r"(?mi)^Namespace\s+(\w+(?:\.\w+)*)\s*$").unwrap();
+    fn parse_option(&self, statement: &Statement, root: &mut UIRNode) -> bool {
+        let option_regex = Regex::new(r"(?i)^Option\s+(Explicit|Strict|Infer|Base\s+\d|Compare\s+\w+|Private\s+Module)\b").unwrap();
+        let Some(caps) = option_regex.captures(&statement.text) else {
+            return false;
+        };
         
-        for caps in namespace_regex.captures_iter(source) {
-            let namespace_name = caps.get(1).unwrap().as_str();
-            let line_num = source[..caps.get(0).unwrap().start()].lines().count() + 1;
-            
-            let namespace_node = UIRNode {
-                id: format!("namespace_{}", namespace_name),
-                node_type: NodeType::Module,
-                name: Some(namespace_name.to_string()),
-                children: Vec::new(),
-                metadata: Metadata {
-                    source_language: CoalesceLanguage::VisualBasic,
-                    semantic_tags: vec!["namespace".to_string()],
-                    complexity_score: None,
-                    dependencies: Vec::new(),
-                    annotations: {
-                        let mut map = HashMap::new();
-                        map.insert("original_text".to_string(), Value::String(caps.get(0).unwrap().as_str().to_string()));
-                        map
-                    },
-                    legacy_patterns: Vec::new(),
-                },
-                source_location: Some(SourceLocation {
-                    file: String::new(),
-                    start_line: line_num as u32,
-                    end_line: line_num as u32,
-                    start_column: 0,
-                    end_column: caps.get(0).unwrap().len() as u32,
-                }),
-            };
-            
-            root.children.push(namespace_node);
+        let option = caps[1].split_whitespace().collect::<Vec<_>>().join(" ");
+        let original = statement.text.as_str();
+        let mut vb6 = false;
+        
+        if option.eq_ignore_ascii_case("Explicit") {
+            root.metadata.annotations.insert("option_explicit".to_string(), Value::Bool(true));
+            root.metadata.legacy_patterns.push(self.legacy_pattern(
+                "option_explicit",
+                original,
+                "Implied by statically typed targets; drop when all variables are declared",
+            ));
+        } else if option.eq_ignore_ascii_case("Strict") || option.eq_ignore_ascii_case("Infer") {
+            // VB.NET compiler options
+            root.metadata.annotations.insert(format!("option_{}", option.to_ascii_lowercase()), Value::Bool(true));
+        } else {
+            // Option Base/Compare/Private Module only exist in VB6/VBA
+            vb6 = true;
+            root.metadata.legacy_patterns.push(self.legacy_pattern(
+                "module_option",
+                original,
+                "Make array bounds and string comparison explicit at each use",
+            ));
         }
         
-        Ok(())
+        let options = root.metadata.annotations.entry("options".to_string()).or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(options) = options {
+            options.push(Value::String(option));
+        }
+        vb6
     }
     
-    fn parse_modules(&self, source: &str, root: &mut UIRNode) -> Result<()> {
-        let module_regex = Regex::new(r"(?mi)^(?:Public\s+|Private\s+)?Module\s+(\w+)\s*$").unwrap();
+    fn tag_event_handlers(&self, node: &mut UIRNode) -> bool {
+        // Control_Event naming is how VB6 forms and VBA documents bind handlers
+        let handler_regex = Regex::new(r"^(\w+)_(Click|DblClick|Load|Unload|QueryUnload|Initialize|Terminate|Activate|Deactivate|Resize|Paint|Change|GotFocus|LostFocus|KeyPress|KeyDown|KeyUp|MouseDown|MouseUp|MouseMove|Timer|Validate|Open|BeforeClose|BeforeSave|SelectionChange|Calculate)$").unwrap();
         
-        for caps in module_regex.captures_iter(source) {
-            let module_name = caps.get(1).unwrap().as_str();
-            let line_num = source[..caps.get(0).unwrap().start()].lines().count() + 1;
+        let mut found = false;
+        for child in node.children.iter_mut() {
+            found |= self.tag_event_handlers(child);
             
-            let module_node = UIRNode {
-                id: format!("module_{}", module_name),
-                node_type: NodeType::Module,
-                name: Some(module_name.to_string()),
-                children: Vec::new(),
-                metadata: Metadata {
-                    source_language: CoalesceLanguage::VisualBasic,
-                    semantic_tags: vec!["module".to_string()],
-                    complexity_score: None,
-                    dependencies: Vec::new(),
-                    annotations: {
-                        let mut map = HashMap::new();
-                        map.insert("original_text".to_string(), Value::String(caps.get(0).unwrap().as_str().to_string()));
-                        map
-                    },
-                    legacy_patterns: Vec::new(),
-                },
-                source_location: Some(SourceLocation {
-                    file: String::new(),
-                    start_line: line_num as u32,
-                    end_line: line_num as u32,
-                    start_column: 0,
-                    end_column: caps.get(0).unwrap().len() as u32,
-                }),
+            let is_sub = child.metadata.semantic_tags.iter().any(|t| t == "sub");
+            let is_bound = child.metadata.semantic_tags.iter().any(|t| t == "event_handler");
+            if !is_sub || is_bound {
+                continue;
+            }
+            let Some(caps) = child.name.as_deref().and_then(|name| handler_regex.captures(name)) else {
+                continue;
             };
+            found = true;
             
-            root.children.push(module_node);
+            let (control, event) = (caps[1].to_string(), caps[2].to_string());
+            child.metadata.semantic_tags.push("event_handler".to_string());
+            child.metadata.annotations.insert("control".to_string(), Value::String(control.clone()));
+            child.metadata.annotations.insert("event".to_string(), Value::String(event.clone()));
+            child.metadata.legacy_patterns.push(self.legacy_pattern(
+                "form_event_handler",
+                &format!("{}_{}", control, event),
+                "Register the handler with the target UI framework's event system",
+            ));
         }
         
-        Ok(())
+        found
+    }
+    
+    fn uses_vb6_patterns(&self, node: &UIRNode) -> bool {
+        node.metadata.legacy_patterns.iter().any(|p| VB6_PATTERNS.contains(&p.pattern_type.as_str()))
+            || node.children.iter().any(|child| self.uses_vb6_patterns(child))
+    }
+    
+    fn collect_dependencies(&self, node: &UIRNode, dependencies: &mut Vec<String>) {
+        for child in &node.children {
+            let is_import = child.metadata.semantic_tags.iter().any(|t| t == "import" || t == "external");
+            if is_import {
+                for dependency in &child.metadata.dependencies {
+                    if !dependencies.contains(dependency) {
+                        dependencies.push(dependency.clone());
+                    }
+                }
+            }
+            if matches!(child.node_type, NodeType::Module | NodeType::Class | NodeType::Interface) {
+                self.collect_dependencies(child, dependencies);
+            }
+        }
+    }
+    
+    fn parse_expression(&self, text: &str, line: u32) -> UIRNode {
+        let tokens = self.tokenize_expression(text);
+        let mut pos = 0;
+        let mut node = self.parse_binary(&tokens, &mut pos, 0, line)
+            .unwrap_or_else(|| self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "empty"));
+        node.metadata.annotations.insert("original_text".to_string(), Value::String(text.trim().to_string()));
+        node
     }
     
-    fn parse_classes(&self, source: &str, root: &mut UIRNode) -> Result<()> {
-        let class_regex = Regex::new(r"(?mi)^(?:Public\s+|Private\s+)?Class\s+(\w+)\s*$").unwrap();
+    fn tokenize_expression(&self, text: &str) -> Vec<Token> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut i = 0;
         
-        for caps in class_regex.captures_iter(source) {
-            let class_name = caps.get(1).unwrap().as_str();
-            let line_num = source[..caps.get(0).unwrap().start()].lines().count() + 1;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied().unwrap_or('\0');
+            let start = i;
             
-            let class_node = UIRNode {
-                id: format!("class_{}", class_name),
-                node_type: NodeType::Class,
-                name: Some(class_name.to_string()),
-                children: Vec::new(),
-                metadata: Metadata {
-                    source_language: CoalesceLanguage::VisualBasic,
-                    semantic_tags: vec!["class".to_string()],
-                    complexity_score: None,
-                    dependencies: Vec::new(),
-                    annotations: {
-                        let mut map = HashMap::new();
-                        map.insert("original_text".to_string(), Value::String(caps.get(0).unwrap().as_str().to_string()));
-                        map
-                    },
-                    legacy_patterns: Vec::new(),
-                },
-                source_location: Some(SourceLocation {
-                    file: String::new(),
-                    start_line: line_num as u32,
-                    end_line: line_num as u32,
-                    start_column: 0,
-                    end_column: caps.get(0).unwrap().len() as u32,
-                }),
+            let kind = if c.is_whitespace() {
+                i += 1;
+                continue;
+            } else if c == '"' {
+                i += 1;
+                while i < chars.len() {
+                    if chars[i] == '"' && chars.get(i + 1) == Some(&'"') {
+                        i += 2;
+                    } else if chars[i] == '"' {
+                        i += 1;
+                        break;
+                    } else {
+                        i += 1;
+                    }
+                }
+                // "x"c is a Char literal
+                if chars.get(i).is_some_and(|c| c.eq_ignore_ascii_case(&'c')) {
+                    i += 1;
+                }
+                TokenKind::Literal
+            } else if c == '#' && next.is_ascii_digit() {
+                // Date literal: #1/1/2000#
+                i += 1;
+                while i < chars.len() && chars[i] != '#' {
+                    i += 1;
+                }
+                i = (i + 1).min(chars.len());
+                TokenKind::Literal
+            } else if c == '&' && matches!(next.to_ascii_uppercase(), 'H' | 'O' | 'B') && chars.get(i + 2).is_some_and(|c| c.is_ascii_hexdigit()) {
+                i += 2;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric()) {
+                    i += 1;
+                }
+                TokenKind::Literal
+            } else if c.is_ascii_digit() || (c == '.' && next.is_ascii_digit()) {
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                    i += 1;
+                }
+                if chars.get(i).is_some_and(|c| "!#@%".contains(*c)) {
+                    i += 1;
+                }
+                TokenKind::Literal
+            } else if c.is_alphabetic() || c == '_' || c == '[' || (c == '.' && (next.is_alphabetic() || next == '_')) {
+                // Identifiers, qualified names and `.Member` inside With blocks
+                loop {
+                    if i < chars.len() && chars[i] == '[' {
+                        while i < chars.len() && chars[i] != ']' {
+                            i += 1;
+                        }
+                        i += 1;
+                    } else {
+                        if chars.get(i) == Some(&'.') {
+                            i += 1;
+                        }
+                        while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                            i += 1;
+                        }
+                    }
+                    if chars.get(i) == Some(&'.') && chars.get(i + 1).is_some_and(|c| c.is_alphabetic() || *c == '_' || *c == '[') {
+                        continue;
+                    }
+                    break;
+                }
+                // Type-declaration suffix: Left$(s, 1), count%
+                if chars.get(i).is_some_and(|c| "$%&!#@".contains(*c))
+                    && chars.get(i + 1).is_none_or(|c| !c.is_alphanumeric() && *c != '"' && *c != '&') {
+                    i += 1;
+                }
+                TokenKind::Word
+            } else {
+                let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+                i += if matches!(two.as_str(), "<=" | ">=" | "<>" | ":=" | "<<" | ">>") { 2 } else { 1 };
+                TokenKind::Operator
             };
             
-            root.children.push(class_node);
+            tokens.push(Token { kind, text: chars[start..i].iter().collect() });
         }
         
-        Ok(())
+        tokens
+    }
+    
+    /// Binding power of binary operators, loosest first
+    fn operator_level(&self, token: &Token) -> Option<usize> {
+        let text = token.text.to_ascii_lowercase();
+        match text.as_str() {
+            "or" | "orelse" | "xor" => Some(0),
+            "and" | "andalso" => Some(1),
+            "=" | "<>" | "<" | ">" | "<=" | ">=" | "is" | "isnot" | "like" => Some(2),
+            "&" => Some(3),
+            "<<" | ">>" => Some(4),
+            "+" | "-" => Some(5),
+            "mod" => Some(6),
+            "\\" => Some(7),
+            "*" | "/" => Some(8),
+            "^" => Some(9),
+            _ => None,
+        }
     }
     
-    fn parse_functions(&self, source: &str, root: &mut UIRNode) -> Result<()> {
-        let func_regex = Regex::new(r"(?mi)^(?:Public\s+|Private\s+|Protected\s+)?Function\s+(\w+)\s*\(([^)]*)\)(?:\s+As\s+\w+)?\s*$").unwrap();
+    fn parse_binary(&self, tokens: &[Token], pos: &mut usize, level: usize, line: u32) -> Option<UIRNode> {
+        if level == 2 && tokens.get(*pos).is_some_and(|t| t.is("Not")) {
+            *pos += 1;
+            let operand = self.parse_binary(tokens, pos, 2, line)?;
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Logical), None, line, "not");
+            node.operator = Some(Operator::Not);
+            node.metadata.annotations.insert("operator".to_string(), Value::String("Not".to_string()));
+            node.children.push(operand);
+            return Some(node);
+        }
+        if level > 9 {
+            return self.parse_unary(tokens, pos, line);
+        }
         
-        for caps in func_regex.captures_iter(source) {
-            let func_name = caps.get(1).unwrap().as_str();
-            let params_str = caps.get(2).map_or("", |m| m.as_str()).trim();
-            let line_num = source[..caps.get(0).unwrap().start()].lines().count() + 1;
-            
-            let mut func_node = UIRNode {
-                id: format!("func_{}", func_name),
-                node_type: NodeType::Function,
-                name: Some(func_name.to_string()),
-                children: Vec::new(),
-                metadata: Metadata {
-                    source_language: CoalesceLanguage::VisualBasic,
-                    semantic_tags: vec!["function".to_string()],
-                    complexity_score: None,
-                    dependencies: Vec::new(),
-                    annotations: {
-                        let mut map = HashMap::new();
-                        map.insert("original_text".to_string(), Value::String(caps.get(0).unwrap().as_str().to_string()));
-                        map
-                    },
-                    legacy_patterns: Vec::new(),
-                },
-                source_location: Some(SourceLocation {
-                    file: String::new(),
-                    start_line: line_num as u32,
-                    end_line: line_num as u32,
-                    start_column: 0,
-                    end_column: caps.get(0).unwrap().len() as u32,
-                }),
+        let mut left = self.parse_binary(tokens, pos, level + 1, line)?;
+        while let Some(op) = tokens.get(*pos).filter(|t| t.kind != TokenKind::Literal && self.operator_level(t) == Some(level)) {
+            let op = op.text.clone();
+            *pos += 1;
+            let Some(right) = self.parse_binary(tokens, pos, level + 1, line) else {
+                break;
             };
-            
-            // Parse parameters
-            if !params_str.is_empty() {
-                for param in params_str.split(',') {
-                    let param = param.trim();
-                    if let Some(param_name) = param.split_whitespace().next() {
-                        if param_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                            let param_node = UIRNode {
-                                id: format!("param_{}", param_name),
-                                node_type: NodeType::Variable,
-                                name: Some(param_name.to_string()),
-                                children: Vec::new(),
-                                metadata: Metadata {
-                                    source_language: CoalesceLanguage::VisualBasic,
-                                    semantic_tags: vec!["parameter".to_string()],
-                                    complexity_score: None,
-                                    dependencies: Vec::new(),
-                                    annotations: HashMap::new(),
-                                    legacy_patterns: Vec::new(),
-                                },
-                                source_location: Some(SourceLocation {
-                                    file: String::new(),
-                                    start_line: line_num as u32,
-                                    end_line: line_num as u32,
-                                    start_column: 0,
-                                    end_column: param_name.len() as u32,
-                                }),
-                            };
-                            func_node.children.push(param_node);
-                        }
+            let (node_type, tag) = match level {
+                0 | 1 => (NodeType::Expression(ExpressionType::Logical), "logical"),
+                2 => (NodeType::Expression(ExpressionType::Comparison), "comparison"),
+                3 => (NodeType::Expression(ExpressionType::Arithmetic), "concatenation"),
+                _ => (NodeType::Expression(ExpressionType::Arithmetic), "arithmetic"),
+            };
+            let mut node = self.create_node(node_type, None, line, tag);
+            node.operator = Operator::binary(&op, &CoalesceLanguage::VisualBasic);
+            node.metadata.annotations.insert("operator".to_string(), Value::String(op));
+            node.children = vec![left, right];
+            left = node;
+        }
+        Some(left)
+    }
+    
+    fn parse_unary(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let token = tokens.get(*pos)?;
+        if token.kind == TokenKind::Operator && (token.text == "-" || token.text == "+") {
+            let op = token.text.clone();
+            *pos += 1;
+            let operand = self.parse_unary(tokens, pos, line)?;
+            let mut node = self.create_node(NodeType::Expression(ExpressionType::Arithmetic), None, line, "negation");
+            node.operator = Operator::unary(&op, &CoalesceLanguage::VisualBasic);
+            node.metadata.annotations.insert("operator".to_string(), Value::String(op));
+            node.children.push(operand);
+            return Some(node);
+        }
+        self.parse_postfix(tokens, pos, line)
+    }
+    
+    fn parse_postfix(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let mut node = self.parse_primary(tokens, pos, line)?;
+        
+        loop {
+            match tokens.get(*pos) {
+                // Calls and array indexing share the same syntax
+                Some(token) if token.is("(") => {
+                    let arguments = self.parse_arguments(tokens, pos, line);
+                    let name = match node.node_type {
+                        NodeType::Expression(ExpressionType::Variable) => node.name.clone(),
+                        _ => Some("apply".to_string()),
+                    };
+                    let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), name, line, "call");
+                    if node.node_type != NodeType::Expression(ExpressionType::Variable) {
+                        call.children.push(node);
                     }
+                    call.children.extend(arguments);
+                    node = call;
+                }
+                Some(token) if token.kind == TokenKind::Word && token.text.starts_with('.') => {
+                    *pos += 1;
+                    let mut access = self.create_node(NodeType::Expression(ExpressionType::Variable), Some(token.text[1..].to_string()), line, "member_access");
+                    access.children.push(node);
+                    node = access;
                 }
+                _ => break,
             }
-            
-            root.children.push(func_node);
         }
-        
-        Ok(())
+        Some(node)
+    }
+    
+    /// Comma-separated arguments inside the parentheses at `pos`
+    fn parse_arguments(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Vec<UIRNode> {
+        *pos += 1;
+        let mut arguments = Vec::new();
+        while let Some(token) = tokens.get(*pos) {
+            if token.is(")") {
+                *pos += 1;
+                break;
+            }
+            if token.is(",") {
+                *pos += 1;
+                continue;
+            }
+            // Named arguments: Prompt:="Name"
+            if tokens.get(*pos + 1).is_some_and(|t| t.is(":=")) {
+                *pos += 2;
+            }
+            match self.parse_binary(tokens, pos, 0, line) {
+                Some(argument) => arguments.push(argument),
+                None => *pos += 1,
+            }
+        }
+        arguments
     }
     
-    fn parse_subs(&self, source: &str, root: &mut UIRNode) -> Result<()> {
-        let sub_regex = Regex::new(r"(?mi)^(?:Public\s+|Private\s+|Protected\s+)?Sub\s+(\w+)\s*\(([^)]*)\)\s*$").unwrap();
+    fn parse_primary(&self, tokens: &[Token], pos: &mut usize, line: u32) -> Option<UIRNode> {
+        let token = tokens.get(*pos)?.clone();
+        *pos += 1;
         
-        for caps in sub_regex.captures_iter(source) {
-            let sub_name = caps.get(1).unwrap().as_str();
-            let params_str = caps.get(2).map_or("", |m| m.as_str()).trim();
-            let line_num = source[..caps.get(0).unwrap().start()].lines().count() + 1;
-            
-            let mut sub_node = UIRNode {
-                id: format!("sub_{}", sub_name),
-                node_type: NodeType::Function,
-                name: Some(sub_name.to_string()),
-                children: Vec::new(),
-                metadata: Metadata {
-                    source_language: CoalesceLanguage::VisualBasic,
-                    semantic_tags: vec!["sub".to_string()],
-                    complexity_score: None,
-                    dependencies: Vec::new(),
-                    annotations: {
-                        let mut map = HashMap::new();
-                        map.insert("original_text".to_string(), Value::String(caps.get(0).unwrap().as_str().to_string()));
-                        map
-                    },
-                    legacy_patterns: Vec::new(),
-                },
-                source_location: Some(SourceLocation {
-                    file: String::new(),
-                    start_line: line_num as u32,
-                    end_line: line_num as u32,
-                    start_column: 0,
-                    end_column: caps.get(0).unwrap().len() as u32,
-                }),
-            };
-            
-            // Parse parameters
-            if !params_str.is_empty() {
-                for param in params_str.split(',') {
-                    let param = param.trim();
-                    if let Some(param_name) = param.split_whitespace().next() {
-                        if param_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                            let param_node = UIRNode {
-                                id: format!("param_{}", param_name),
-                                node_type: NodeType::Variable,
-                                name: Some(param_name.to_string()),
-                                children: Vec::new(),
-                                metadata: Metadata {
-                                    source_language: CoalesceLanguage::VisualBasic,
-                                    semantic_tags: vec!["parameter".to_string()],
-                                    complexity_score: None,
-                                    dependencies: Vec::new(),
-                                    annotations: HashMap::new(),
-                                    legacy_patterns: Vec::new(),
-                                },
-                                source_location: Some(SourceLocation {
-                                    file: String::new(),
-                                    start_line: line_num as u32,
-                                    end_line: line_num as u32,
-                                    start_column: 0,
-                                    end_column: param_name.len() as u32,
-                                }),
-                            };
-                            sub_node.children.push(param_node);
+        if token.kind == TokenKind::Literal {
+            return Some(self.create_literal(&token.text, line));
+        }
+        if token.is("(") {
+            let mut inner = self.parse_binary(tokens, pos, 0, line)?;
+            if tokens.get(*pos).is_some_and(|t| t.is(")")) {
+                *pos += 1;
+            }
+            mark_grouped(&mut inner);
+            return Some(inner);
+        }
+        if token.is("{") {
+            let mut array = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, "array");
+            while let Some(next) = tokens.get(*pos) {
+                if next.is("}") {
+                    *pos += 1;
+                    break;
+                }
+                if next.is(",") {
+                    *pos += 1;
+                    continue;
+                }
+                match self.parse_binary(tokens, pos, 0, line) {
+                    Some(element) => array.children.push(element),
+                    None => *pos += 1,
+                }
+            }
+            return Some(array);
+        }
+        if token.kind != TokenKind::Word {
+            return None;
+        }
+        
+        match token.text.to_ascii_lowercase().as_str() {
+            "true" | "false" | "nothing" => Some(self.create_literal(&token.text, line)),
+            "new" => {
+                let type_name = tokens.get(*pos).map(|t| t.text.clone());
+                *pos += 1;
+                let mut call = self.create_node(NodeType::Expression(ExpressionType::FunctionCall), type_name, line, "constructor_call");
+                if tokens.get(*pos).is_some_and(|t| t.is("(")) {
+                    call.children = self.parse_arguments(tokens, pos, line);
+                }
+                Some(call)
+            }
+            "addressof" => {
+                let target = tokens.get(*pos).map(|t| t.text.clone());
+                *pos += 1;
+                Some(self.create_node(NodeType::Expression(ExpressionType::Variable), target, line, "function_reference"))
+            }
+            "typeof" => {
+                let operand = self.parse_postfix(tokens, pos, line)?;
+                let mut test = self.create_node(NodeType::Expression(ExpressionType::Comparison), None, line, "type_test");
+                if tokens.get(*pos).is_some_and(|t| t.is("Is") || t.is("IsNot")) {
+                    *pos += 1;
+                    let type_name = tokens.get(*pos).map(|t| t.text.clone()).unwrap_or_default();
+                    *pos += 1;
+                    test.metadata.annotations.insert("type".to_string(), Value::String(type_name));
+                }
+                test.children.push(operand);
+                Some(test)
+            }
+            "function" | "sub" => {
+                // Single-line lambda: Function(x) x * 2
+                let mut lambda = self.create_node(closures::closure(CaptureMode::ByReference), Some("lambda".to_string()), line, "lambda");
+                if tokens.get(*pos).is_some_and(|t| t.is("(")) {
+                    while let Some(param) = tokens.get(*pos + 1).filter(|t| !t.is(")")) {
+                        if param.kind == TokenKind::Word && !param.is("As") && !tokens[*pos].is("As") {
+                            lambda.children.push(self.create_node(NodeType::Variable, Some(param.text.clone()), line, "parameter"));
                         }
+                        *pos += 1;
                     }
+                    *pos += 2;
                 }
+                if let Some(body) = self.parse_binary(tokens, pos, 0, line) {
+                    lambda.children.push(body);
+                }
+                Some(lambda)
+            }
+            _ => {
+                let tag = if token.text.starts_with('.') { "with_member" } else { "identifier" };
+                let name = token.text.trim_start_matches('.').to_string();
+                let mut node = self.create_node(NodeType::Expression(ExpressionType::Variable), Some(name), line, tag);
+                node.metadata.annotations.insert("original_text".to_string(), Value::String(token.text.clone()));
+                Some(node)
             }
-            
-            root.children.push(sub_node);
         }
-        
-        Ok(())
     }
     
-    fn parse_properties(&self, source: &str, root: &mut UIRNode) -> Result<()> {
-        let prop_regex = Regex::new(r"(?mi)^(?:Public\s+|Private\s+|Protected\s+)?Property\s+(\w+)\s*(?:\([^)]*\))?\s*As\s+\w+\s*$").unwrap();
-        
-        for caps in prop_regex.captures_iter(source) {
-            let prop_name = caps.get(1).unwrap().as_str();
-            let line_num = source[..caps.get(0).unwrap().start()].lines().count() + 1;
-            
-            let prop_node = UIRNode {
-                id: format!("prop_{}", prop_name),
-                node_type: NodeType::Variable,
-                name: Some(prop_name.to_string()),
-                children: Vec::new(),
-                metadata: Metadata {
-                    source_language: CoalesceLanguage::VisualBasic,
-                    semantic_tags: vec!["property".to_string()],
-                    complexity_score: None,
-                    dependencies: Vec::new(),
-                    annotations: {
-                        let mut map = HashMap::new();
-                        map.insert("original_text".to_string(), Value::String(caps.get(0).unwrap().as_str().to_string()));
-                        map
-                    },
-                    legacy_patterns: Vec::new(),
-                },
-                source_location: Some(SourceLocation {
-                    file: String::new(),
-                    start_line: line_num as u32,
-                    end_line: line_num as u32,
-                    start_column: 0,
-                    end_column: caps.get(0).unwrap().len() as u32,
-                }),
-            };
-            
-            root.children.push(prop_node);
+    fn create_literal(&self, text: &str, line: u32) -> UIRNode {
+        let tag = match text.to_ascii_lowercase().as_str() {
+            _ if text.starts_with('"') => "string",
+            _ if text.starts_with('#') => "date",
+            "true" | "false" => "boolean",
+            "nothing" => "null",
+            _ => "number",
+        };
+        let mut node = self.create_node(NodeType::Expression(ExpressionType::Literal), None, line, tag);
+        node.literal = LiteralValue::parse(text, &CoalesceLanguage::VisualBasic);
+        node.metadata.annotations.insert("value".to_string(), Value::String(text.to_string()));
+        node
+    }
+    
+    /// Declaration text and initializer around a top-level `=`
+    fn split_initializer<'a>(&self, text: &'a str) -> (&'a str, Option<&'a str>) {
+        let mut depth = 0;
+        let mut in_string = false;
+        for (i, c) in text.char_indices() {
+            match c {
+                '"' => in_string = !in_string,
+                '(' | '{' if !in_string => depth += 1,
+                ')' | '}' if !in_string => depth -= 1,
+                '=' if !in_string && depth == 0 => return (text[..i].trim(), Some(text[i + 1..].trim())),
+                _ => {}
+            }
+        }
+        (text.trim(), None)
+    }
+    
+    /// Byte offset of a whole word outside strings and parentheses, case-insensitively
+    fn find_word(&self, text: &str, word: &str) -> Option<usize> {
+        let bytes = text.as_bytes();
+        let mut depth = 0;
+        let mut in_string = false;
+        for (i, c) in text.char_indices() {
+            match c {
+                '"' => in_string = !in_string,
+                '(' if !in_string => depth += 1,
+                ')' if !in_string => depth -= 1,
+                _ if !in_string && depth == 0 => {
+                    let end = i + word.len();
+                    let boundary_before = i == 0 || !(bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_');
+                    let boundary_after = end >= bytes.len() || !(bytes[end].is_ascii_alphanumeric() || bytes[end] == b'_');
+                    if boundary_before && boundary_after && text.get(i..end).is_some_and(|w| w.eq_ignore_ascii_case(word)) {
+                        return Some(i);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+    
+    /// The `)` closing the `(` at `open`; `None` when it is never closed
+    fn matching_paren(&self, text: &str, open: usize) -> Option<usize> {
+        let mut depth = 0;
+        let mut in_string = false;
+        for (i, c) in text.char_indices().skip_while(|(i, _)| *i < open) {
+            match c {
+                '"' => in_string = !in_string,
+                '(' if !in_string => depth += 1,
+                ')' if !in_string => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(i);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+    
+    fn word_at(&self, text: &str, index: usize) -> String {
+        text.split_whitespace().nth(index).unwrap_or_default().to_string()
+    }
+    
+    fn legacy_pattern(&self, pattern_type: &str, original: &str, hint: &str) -> LegacyPattern {
+        LegacyPattern {
+            pattern_type: pattern_type.to_string(),
+            original_construct: original.to_string(),
+            modernization_hint: Some(hint.to_string()),
+            preserve_exactly: false,
         }
+    }
+    
+    fn create_node(&self, node_type: NodeType, name: Option<String>, line: u32, tag: &str) -> UIRNode {
+        let id = format!(
+            "{}_{}_{}",
+            tag,
+            line,
+            name.as_deref().unwrap_or("").replace(|c: char| !c.is_alphanumeric(), "_"),
+        );
         
-        Ok(())
+        UIRNode {
+            id,
+            node_type,
+            name,
+            children: Vec::new(),
+            metadata: Metadata {
+                source_language: CoalesceLanguage::VisualBasic,
+                semantic_tags: vec![tag.to_string()],
+                ..Default::default()
+            },
+            source_location: Some(SourceLocation {
+                file: String::new(),
+                start_line: line,
+                end_line: line,
+                start_column: 0,
+                end_column: 0,
+            }),
+            operator: None,
+            literal: None,
+            visibility: None,
+            modifiers: Vec::new(),
+            is_async: false,
+            attributes: Vec::new(),
+            ownership: None,
+            documentation: None,
+        }
     }
 }
 
@@ -371,7 +2025,7 @@ Function Add(a As Integer, b As Integer) As Integer
     Return a + b
 End Function
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
         
@@ -390,7 +2044,7 @@ Public Class Calculator
     End Function
 End Class
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
@@ -405,8 +2059,227 @@ Module MathModule
     End Sub
 End Module
 "#;
-        
+
         let result = parser.parse(source);
         assert!(result.is_ok());
     }
+    
+    #[test]
+    fn test_vb6_declarations_and_options() {
+        let parser = VisualBasicParser::new().unwrap();
+        let source = r#"Attribute VB_Name = "Inventory"
+Option Explicit
+Dim count As Integer, names(10) As String
+Dim total
+Global conn As ADODB.Connection
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.name.as_deref(), Some("Inventory"));
+        assert_eq!(uir.metadata.annotations["option_explicit"], true);
+        assert_eq!(uir.metadata.annotations["dialect"], "vb6");
+        
+        let vars: Vec<_> = uir.children.iter().filter(|c| c.node_type == NodeType::Variable).collect();
+        assert_eq!(vars.len(), 4);
+        assert_eq!(vars[0].metadata.annotations["type"], "Integer");
+        assert_eq!(vars[1].metadata.annotations["array_bounds"], "10");
+        assert!(vars[2].metadata.legacy_patterns.iter().any(|p| p.pattern_type == "implicit_variant"));
+        assert!(vars[3].metadata.legacy_patterns.iter().any(|p| p.pattern_type == "global_variable"));
+    }
+    
+    #[test]
+    fn test_vb6_set_and_error_handling() {
+        let parser = VisualBasicParser::new().unwrap();
+        let source = r#"
+Private Sub Command1_Click()
+    On Error GoTo ErrHandler
+    Set rs = New ADODB.Recordset
+    Exit Sub
+ErrHandler:
+    MsgBox Err.Description
+End Sub
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let handler = uir.children.iter().find(|c| c.name.as_deref() == Some("Command1_Click")).unwrap();
+        assert!(handler.metadata.semantic_tags.contains(&"event_handler".to_string()));
+        assert_eq!(handler.metadata.annotations["event"], "Click");
+        
+        let on_error = handler.children.iter().find(|c| c.node_type == NodeType::ControlFlow(ControlFlowType::Try)).unwrap();
+        assert_eq!(on_error.metadata.annotations["handler_label"], "ErrHandler");
+        
+        let set = handler.children.iter().find(|c| c.metadata.semantic_tags.contains(&"object_assignment".to_string())).unwrap();
+        assert_eq!(set.name.as_deref(), Some("rs"));
+        assert_eq!(set.metadata.annotations["new_instance"], true);
+    }
+    
+    #[test]
+    fn test_vb_nested_blocks() {
+        let parser = VisualBasicParser::new().unwrap();
+        let source = r#"
+Imports System.Collections.Generic
+
+Namespace Billing
+    Public Class Invoice
+        Inherits Document
+        Private total As Decimal = 0
+
+        Public Sub AddLines(lines As List(Of Decimal))
+            For Each amount As Decimal In lines
+                If amount > 0 Then
+                    total += amount
+                ElseIf amount = 0 Then
+                    Continue For
+                Else
+                    Throw New ArgumentException("negative")
+                End If
+            Next
+        End Sub
+
+        Public ReadOnly Property Total() As Decimal
+            Get
+                Return total
+            End Get
+        End Property
+    End Class
+End Namespace
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        assert_eq!(uir.metadata.dependencies, vec!["System.Collections.Generic".to_string()]);
+        
+        let namespace = &uir.children[1];
+        assert_eq!(namespace.name.as_deref(), Some("Billing"));
+        let class = &namespace.children[0];
+        assert_eq!(class.node_type, NodeType::Class);
+        assert_eq!(class.metadata.annotations["inherits"], serde_json::json!(["Document"]));
+        assert_eq!(class.children.len(), 3);
+        
+        let add_lines = &class.children[1];
+        assert_eq!(add_lines.name.as_deref(), Some("AddLines"));
+        assert_eq!(add_lines.children[0].metadata.annotations["type"], "List(Of Decimal)");
+        let for_each = &add_lines.children[1];
+        assert_eq!(for_each.node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::ForEach)));
+        
+        let conditional = &for_each.children[1];
+        assert_eq!(conditional.node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        assert_eq!(conditional.children[1].children[0].metadata.annotations["operator"], "+=");
+        let else_if = &conditional.children[2];
+        assert_eq!(else_if.node_type, NodeType::ControlFlow(ControlFlowType::Conditional));
+        assert_eq!(else_if.children[2].children[0].node_type, NodeType::Statement(StatementType::Throw));
+        
+        let property = &class.children[2];
+        assert!(matches!(property.node_type, NodeType::Property { has_getter: true, has_setter: false, .. }));
+        assert_eq!(property.visibility, Some(Visibility::Public));
+        assert!(property.modifiers.contains(&Modifier::ReadOnly));
+        assert!(property.children[0].metadata.semantic_tags.contains(&"getter".to_string()));
+    }
+    
+    #[test]
+    fn test_vb6_loops_and_select_case() {
+        let parser = VisualBasicParser::new().unwrap();
+        let source = r#"
+Sub Process()
+    Dim i As Integer
+    For i = 1 To 10 Step 2
+        Select Case i
+            Case 1, 3
+                Call Log("odd")
+            Case 5 To 9
+                If i = 7 Then Exit For
+            Case Else
+                Debug.Print i
+        End Select
+    Next i
+    Do
+        i = i - 1
+    Loop Until i <= 0
+    While i < 5
+        i = i + 1
+    Wend
+End Sub
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let process = &uir.children[0];
+        assert_eq!(process.children.len(), 4);
+        
+        let for_loop = &process.children[1];
+        assert_eq!(for_loop.metadata.annotations["step"], "2");
+        let select = &for_loop.children[2];
+        assert_eq!(select.node_type, NodeType::ControlFlow(ControlFlowType::Switch));
+        assert_eq!(select.children.len(), 4);
+        assert!(select.children[1].children[2].metadata.legacy_patterns.iter().any(|p| p.pattern_type == "call_statement"));
+        assert!(select.children[2].children[1].metadata.semantic_tags.contains(&"single_line".to_string()));
+        
+        let do_loop = &process.children[2];
+        assert_eq!(do_loop.node_type, NodeType::ControlFlow(ControlFlowType::Loop(LoopType::DoWhile)));
+        assert_eq!(do_loop.metadata.annotations["until"], true);
+        assert!(process.children[3].metadata.legacy_patterns.iter().any(|p| p.pattern_type == "while_wend"));
+        assert_eq!(uir.metadata.annotations["dialect"], "vb6");
+    }
+    
+    #[test]
+    fn test_vb_case_else_and_empty_finally() {
+        let parser = VisualBasicParser::new().unwrap();
+        let source = r#"
+Sub F(x As Integer)
+    Select Case x
+        Case 1
+            Print 1
+        Case Else
+    End Select
+    Try
+        Print 2
+    Finally
+    End Try
+    Do
+    Loop
+End Sub
+"#;
+
+        let uir = parser.parse(source).unwrap();
+        let f = &uir.children[0];
+        let select = f.children.iter().find(|c| c.node_type == NodeType::ControlFlow(ControlFlowType::Switch)).unwrap();
+        let case_else = select.children.last().unwrap();
+        assert_eq!(case_else.name.as_deref(), Some("case_else"));
+        assert!(case_else.children.is_empty());
+        
+        let try_node = f.children.iter().find(|c| c.node_type == NodeType::ControlFlow(ControlFlowType::Try)).unwrap();
+        let finally = try_node.children.last().unwrap();
+        assert_eq!(finally.name.as_deref(), Some("finally"));
+        assert!(finally.children.is_empty());
+        
+        let do_loop = f.children.last().unwrap();
+        assert!(do_loop.metadata.semantic_tags.contains(&"infinite_loop".to_string()));
+    }
+    
+    #[test]
+    fn test_vb_unclosed_parenthesis_is_a_parse_error() {
+        let parser = VisualBasicParser::new().unwrap();
+        for (source, line, column) in [("Sub F(", 1, 5), ("Sub F()\n    Call Foo(1, 2\nEnd Sub\n", 2, 12)] {
+            match parser.parse(source) {
+                Err(CoalesceError::ParseError { message, line: at_line, column: at_column }) => {
+                    assert_eq!(message, "`(` is never closed");
+                    assert_eq!((at_line, at_column), (line, column));
+                }
+                other => panic!("expected a parse error for {:?}, got {:?}", source, other.map(|_| ())),
+            }
+        }
+        // Parentheses in strings and comments don't count
+        assert!(parser.parse("Sub F()\n    MsgBox \"a (b\" ' see (1\nEnd Sub\n").is_ok());
+    }
+    
+    #[test]
+    fn test_vb_non_ascii_identifiers_are_not_comments() {
+        let parser = VisualBasicParser::new().unwrap();
+        let source = "Sub Messen()\n    Größe = 1\n    Rem Größe bleibt\n    Über = Größe\nEnd Sub\n";
+        
+        let uir = parser.parse(source).unwrap();
+        let sub = &uir.children[0];
+        let statements: Vec<_> = sub.children.iter()
+            .map(|statement| statement.metadata.annotations["original_text"].as_str().unwrap_or_default())
+            .collect();
+        assert_eq!(statements, ["Größe = 1", "Über = Größe"]);
+    }
 }